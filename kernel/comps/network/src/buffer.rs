@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
 use core::marker::PhantomData;
 
 use ostd::{
@@ -12,7 +12,10 @@ use ostd::{
 };
 use ostd_pod::Pod;
 
-use crate::dma_pool::{DmaPool, DmaSegment};
+use crate::{
+    checksum::PartialChecksum,
+    dma_pool::{DmaPool, DmaSegment},
+};
 
 pub struct TxBuffer {
     segment: DmaSegment<ToDevice>,
@@ -33,6 +36,54 @@ impl TxBuffer {
         Ok(builder.build(header))
     }
 
+    /// Creates buffers that together hold the header and the payload.
+    ///
+    /// This is used for payloads that may not fit in a single buffer (e.g., packets that are
+    /// going to be segmented by the device). The first buffer starts with the header, and the
+    /// remaining buffers hold the rest of the payload. The payload is specified in parts, which
+    /// are concatenated.
+    pub fn new_chain<H: Pod>(
+        header: &H,
+        payload: &[&[u8]],
+        pool: &Arc<DmaPool<ToDevice>>,
+    ) -> Result<Vec<Self>> {
+        assert!(size_of::<H>() <= pool.segment_size());
+
+        let mut buffers = Vec::new();
+
+        let mut segment = pool.alloc_segment()?;
+        segment
+            .writer()
+            .unwrap()
+            .write(&mut VmReader::from(header.as_bytes()));
+        let mut nbytes = size_of::<H>();
+
+        for mut part in payload.iter().copied() {
+            while !part.is_empty() {
+                if nbytes == segment.size() {
+                    let full_segment = core::mem::replace(&mut segment, pool.alloc_segment()?);
+                    buffers.push(Self::from_segment(full_segment, nbytes));
+                    nbytes = 0;
+                }
+
+                let len = part.len().min(segment.size() - nbytes);
+                let mut writer = segment.writer().unwrap();
+                writer.skip(nbytes).write(&mut VmReader::from(&part[..len]));
+                nbytes += len;
+                part = &part[len..];
+            }
+        }
+        buffers.push(Self::from_segment(segment, nbytes));
+
+        Ok(buffers)
+    }
+
+    fn from_segment(segment: DmaSegment<ToDevice>, nbytes: usize) -> Self {
+        let tx_buffer = Self { segment, nbytes };
+        tx_buffer.sync_to_device();
+        tx_buffer
+    }
+
     pub fn new_builder<H: Pod>(pool: &Arc<DmaPool<ToDevice>>) -> Result<TxBufferBuilder<H>> {
         assert!(size_of::<H>() <= pool.segment_size());
 
@@ -46,6 +97,13 @@ impl TxBuffer {
         Ok(builder)
     }
 
+    /// Returns a reader of the header and the payload in this buffer.
+    pub fn buf(&self) -> VmReader<'_, Infallible> {
+        let mut reader = self.segment.reader().unwrap();
+        reader.limit(self.nbytes);
+        reader
+    }
+
     fn sync_to_device(&self) {
         self.segment.sync_to_device(0..self.nbytes).unwrap();
     }
@@ -107,6 +165,9 @@ pub struct RxBuffer {
     segment: DmaSegment<FromDevice>,
     header_len: usize,
     payload_len: usize,
+    /// The buffers that hold the rest of the payload if the packet spans multiple buffers.
+    next: Vec<RxBuffer>,
+    partial_checksum: Option<PartialChecksum>,
 }
 
 impl RxBuffer {
@@ -118,6 +179,8 @@ impl RxBuffer {
             segment,
             header_len,
             payload_len: 0,
+            next: Vec::new(),
+            partial_checksum: None,
         })
     }
 
@@ -130,6 +193,45 @@ impl RxBuffer {
         self.payload_len = payload_len;
     }
 
+    /// Appends a buffer that holds the next `len` bytes of the payload.
+    ///
+    /// The appended buffer has no header, since only the first buffer of a packet contains the
+    /// header.
+    pub fn append(&mut self, mut next: RxBuffer, len: usize) {
+        debug_assert!(next.next.is_empty());
+
+        next.header_len = 0;
+        next.set_payload_len(len);
+        self.next.push(next);
+    }
+
+    /// Returns the length of the payload, including the payload in the appended buffers.
+    pub fn total_payload_len(&self) -> usize {
+        self.payload_len + self.next.iter().map(RxBuffer::payload_len).sum::<usize>()
+    }
+
+    /// Returns readers of the payload in this buffer and in the appended buffers.
+    pub fn payloads(&self) -> impl Iterator<Item = VmReader<'_, Infallible>> {
+        core::iter::once(self)
+            .chain(self.next.iter())
+            .map(RxBuffer::payload)
+    }
+
+    /// Marks the checksum of the packet as partially computed.
+    pub fn set_partial_checksum(&mut self, partial_checksum: PartialChecksum) {
+        self.partial_checksum = Some(partial_checksum);
+    }
+
+    /// Returns the partially computed checksum of the packet, if any.
+    ///
+    /// The checksum must be completed before the packet is processed.
+    pub const fn partial_checksum(&self) -> Option<PartialChecksum> {
+        self.partial_checksum
+    }
+
+    /// Returns a reader of the payload in this buffer.
+    ///
+    /// If buffers have been appended, the payload in them is not included.
     pub fn payload(&self) -> VmReader<'_, Infallible> {
         self.segment
             .sync_from_device(self.header_len..self.header_len + self.payload_len)
@@ -162,3 +264,41 @@ impl HasDaddr for RxBuffer {
         self.segment.daddr()
     }
 }
+
+#[cfg(ktest)]
+mod test {
+    use alloc::vec;
+
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn merge_rx_buffers() {
+        const HEADER_LEN: usize = 12;
+
+        let pool = DmaPool::<FromDevice>::new(2048, 0, 8, false);
+
+        let mut rx_buffer = RxBuffer::new(HEADER_LEN, &pool).unwrap();
+        rx_buffer.set_payload_len(2048 - HEADER_LEN);
+        assert_eq!(rx_buffer.total_payload_len(), 2048 - HEADER_LEN);
+
+        // Only the first buffer has a header.
+        rx_buffer.append(RxBuffer::new(HEADER_LEN, &pool).unwrap(), 2048);
+        rx_buffer.append(RxBuffer::new(HEADER_LEN, &pool).unwrap(), 100);
+
+        assert_eq!(rx_buffer.payload_len(), 2048 - HEADER_LEN);
+        assert_eq!(
+            rx_buffer.total_payload_len(),
+            2048 - HEADER_LEN + 2048 + 100
+        );
+        assert_eq!(
+            rx_buffer
+                .payloads()
+                .map(|payload| payload.remain())
+                .collect::<Vec<_>>(),
+            vec![2048 - HEADER_LEN, 2048, 100]
+        );
+        assert_eq!(rx_buffer.buf().remain(), 2048);
+    }
+}
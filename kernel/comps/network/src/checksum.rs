@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Internet checksums used for checksum offloading.
+//!
+//! Reference: <https://datatracker.ietf.org/doc/html/rfc1071>.
+
+/// A checksum that is partially computed.
+///
+/// The checksum field, which is located `offset` bytes after `start`, contains the checksum of
+/// the pseudo-header. The checksum of the bytes from `start` to the end of the packet has yet to
+/// be added to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialChecksum {
+    pub start: usize,
+    pub offset: usize,
+}
+
+impl PartialChecksum {
+    /// Completes the checksum in the packet.
+    ///
+    /// The packet is left untouched if the checksum field does not lie in the packet.
+    pub fn complete(&self, packet: &mut [u8]) {
+        let Some(field_start) = self.start.checked_add(self.offset) else {
+            return;
+        };
+        if field_start.saturating_add(2) > packet.len() {
+            return;
+        }
+
+        let checksum = !ones_complement_sum(&packet[self.start..]);
+        // A zero UDP checksum means that there is no checksum. So it is transmitted as all ones
+        // (i.e., the other representation of zero in ones' complement arithmetic).
+        let checksum = if checksum == 0 { 0xffff } else { checksum };
+        packet[field_start..field_start + 2].copy_from_slice(&checksum.to_be_bytes());
+    }
+}
+
+/// Computes the ones' complement sum of the data, without the final complement.
+pub fn ones_complement_sum(data: &[u8]) -> u16 {
+    let mut words = data.chunks_exact(2);
+    let mut sum = words
+        .by_ref()
+        .map(|word| u16::from_be_bytes([word[0], word[1]]) as u64)
+        .sum::<u64>();
+    if let [last] = words.remainder() {
+        sum += (*last as u64) << 8;
+    }
+
+    fold(sum)
+}
+
+/// Adds the ones' complement sums.
+pub fn ones_complement_add(sums: &[u16]) -> u16 {
+    fold(sums.iter().map(|sum| *sum as u64).sum())
+}
+
+fn fold(mut sum: u64) -> u16 {
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum as u16
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn complete_partial_checksum() {
+        const PSEUDO_HEADER_SUM: u16 = 0x1234;
+
+        // An IP header, followed by a UDP header whose checksum field holds the checksum of the
+        // pseudo-header, followed by an odd number of bytes of payload.
+        let mut packet = [0u8; 4 + 8 + 5];
+        packet[4..10].copy_from_slice(&[0x12, 0x34, 0x00, 0x35, 0x00, 0x0d]);
+        packet[10..12].copy_from_slice(&PSEUDO_HEADER_SUM.to_be_bytes());
+        packet[12..].copy_from_slice(b"hello");
+
+        let partial_checksum = PartialChecksum {
+            start: 4,
+            offset: 6,
+        };
+        partial_checksum.complete(&mut packet);
+
+        // The checksum is valid if the sum, including the pseudo-header, is all ones.
+        assert_eq!(
+            ones_complement_add(&[ones_complement_sum(&packet[4..]), PSEUDO_HEADER_SUM]),
+            0xffff
+        );
+        assert_eq!(&packet[..4], &[0; 4]);
+    }
+
+    #[ktest]
+    fn complete_out_of_range() {
+        let mut packet = [0xabu8; 8];
+        PartialChecksum {
+            start: 4,
+            offset: 3,
+        }
+        .complete(&mut packet);
+        PartialChecksum {
+            start: usize::MAX,
+            offset: 1,
+        }
+        .complete(&mut packet);
+        assert_eq!(packet, [0xab; 8]);
+    }
+
+    #[ktest]
+    fn sum_odd_length() {
+        assert_eq!(ones_complement_sum(&[]), 0);
+        assert_eq!(ones_complement_sum(&[0x01]), 0x0100);
+        assert_eq!(ones_complement_sum(&[0xff, 0xff, 0x00, 0x02]), 0x0002);
+        assert_eq!(ones_complement_add(&[0xffff, 0x0001]), 0x0001);
+    }
+}
@@ -1,44 +1,70 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::vec;
+use alloc::{sync::Arc, vec};
 
 use aster_bigtcp::{
-    device::{self, NotifyDevice},
+    device::{self, NotifyDevice, RxFilter},
     time::Instant,
 };
 use ostd::mm::VmWriter;
 
 use crate::{AnyNetworkDevice, buffer::RxBuffer};
 
-impl device::Device for dyn AnyNetworkDevice {
+/// A receive queue of a network device.
+///
+/// Packets are received from the queue, while packets are sent via the send queue of the current
+/// CPU.
+pub struct NetworkQueue {
+    device: Arc<dyn AnyNetworkDevice>,
+    index: usize,
+}
+
+impl NetworkQueue {
+    /// Creates the `index`-th receive queue of the device.
+    pub fn new(device: Arc<dyn AnyNetworkDevice>, index: usize) -> Self {
+        debug_assert!(index < device.num_queues());
+        Self { device, index }
+    }
+}
+
+impl device::Device for NetworkQueue {
     type RxToken<'a> = RxToken;
     type TxToken<'a> = TxToken<'a>;
 
     fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
-        if self.can_receive() && self.can_send() {
-            let rx_buffer = self.receive().unwrap();
-            Some((RxToken(rx_buffer), TxToken(self)))
+        if self.device.can_receive(self.index) && self.device.can_send() {
+            // Receiving may still fail if the packets are dropped by the driver.
+            let rx_buffer = self.device.receive(self.index).ok()?;
+            Some((RxToken(rx_buffer), TxToken(self.device.as_ref())))
         } else {
             None
         }
     }
 
     fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
-        if self.can_send() {
-            Some(TxToken(self))
+        if self.device.can_send() {
+            Some(TxToken(self.device.as_ref()))
         } else {
             None
         }
     }
 
     fn capabilities(&self) -> device::DeviceCapabilities {
-        self.capabilities()
+        self.device.capabilities()
     }
 }
 
-impl NotifyDevice for dyn AnyNetworkDevice {
+impl NotifyDevice for NetworkQueue {
     fn notify_poll_end(&mut self) {
-        self.notify_poll_end();
+        self.device.notify_poll_end(self.index);
+    }
+
+    fn update_rx_filter(&mut self, filter: &RxFilter) {
+        self.device.set_rx_filter(filter);
+    }
+
+    fn gso_max_size(&self) -> usize {
+        self.device.gso_max_size()
     }
 }
 
@@ -49,14 +75,19 @@ impl device::RxToken for RxToken {
     where
         F: FnOnce(&[u8]) -> R,
     {
-        let mut payload = self.0.payload();
-        let mut buffer = vec![0u8; payload.remain()];
-        payload.read(&mut VmWriter::from(&mut buffer as &mut [u8]));
+        let mut buffer = vec![0u8; self.0.total_payload_len()];
+        let mut writer = VmWriter::from(&mut buffer as &mut [u8]);
+        for mut payload in self.0.payloads() {
+            payload.read(&mut writer);
+        }
+        if let Some(partial_checksum) = self.0.partial_checksum() {
+            partial_checksum.complete(&mut buffer);
+        }
         f(&buffer)
     }
 }
 
-pub struct TxToken<'a>(&'a mut dyn AnyNetworkDevice);
+pub struct TxToken<'a>(&'a dyn AnyNetworkDevice);
 
 impl device::TxToken for TxToken<'_> {
     fn consume<R, F>(self, len: usize, f: F) -> R
@@ -65,7 +96,9 @@ impl device::TxToken for TxToken<'_> {
     {
         let mut buffer = vec![0u8; len];
         let res = f(&mut buffer);
-        self.0.send(&buffer).expect("Send packet failed");
+        // The send queue is not locked between creating the token and consuming it, so it may be
+        // filled up by another CPU. If so, the packet is dropped as if it were lost on the wire.
+        let _ = self.0.send(&buffer);
         res
     }
 }
@@ -5,6 +5,7 @@
 #![feature(trait_alias)]
 
 mod buffer;
+pub mod checksum;
 pub mod dma_pool;
 mod driver;
 
@@ -15,13 +16,14 @@ extern crate ostd_pod;
 use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
 use core::{any::Any, fmt::Debug};
 
-use aster_bigtcp::device::DeviceCapabilities;
+use aster_bigtcp::device::{DeviceCapabilities, RxFilter};
 use aster_softirq::{
     BottomHalfDisabled, SoftIrqLine,
     softirq_id::{NETWORK_RX_SOFTIRQ_ID, NETWORK_TX_SOFTIRQ_ID},
 };
 pub use buffer::{RxBuffer, TxBuffer, TxBufferBuilder};
 use component::{ComponentInitError, init_component};
+pub use driver::NetworkQueue;
 use ostd::sync::SpinLock;
 use spin::Once;
 
@@ -42,35 +44,54 @@ pub trait AnyNetworkDevice: Send + Sync + Any + Debug {
     fn mac_addr(&self) -> EthernetAddr;
     fn capabilities(&self) -> DeviceCapabilities;
 
+    /// Returns the maximum size of IP packets that the device can split into TCP segments.
+    ///
+    /// Zero means that the device does not support TCP segmentation offload. See
+    /// [`NotifyDevice::gso_max_size`] for the requirements on the packets.
+    ///
+    /// [`NotifyDevice::gso_max_size`]: aster_bigtcp::device::NotifyDevice::gso_max_size
+    fn gso_max_size(&self) -> usize {
+        0
+    }
+
     // ================Device Operation===================
 
-    fn can_receive(&self) -> bool;
+    /// Returns the number of receive queues.
+    ///
+    /// Each queue is protected by its own lock, so that different queues can be used on
+    /// different CPUs at the same time. The queues are indexed from zero.
+    fn num_queues(&self) -> usize {
+        1
+    }
+
+    fn can_receive(&self, queue: usize) -> bool;
     fn can_send(&self) -> bool;
 
-    /// Receives a packet from network. If packet is ready, returns a `RxBuffer` containing the packet.
-    /// Otherwise, return [`NetError::NotReady`].
-    fn receive(&mut self) -> Result<RxBuffer, NetError>;
+    /// Receives a packet from the `queue`-th receive queue. If packet is ready, returns a
+    /// `RxBuffer` containing the packet. Otherwise, return [`NetError::NotReady`].
+    fn receive(&self, queue: usize) -> Result<RxBuffer, NetError>;
 
     /// Sends a packet to network.
-    fn send(&mut self, packet: &[u8]) -> Result<(), NetError>;
+    ///
+    /// The packet is sent via the send queue of the current CPU.
+    fn send(&self, packet: &[u8]) -> Result<(), NetError>;
 
     /// Frees processes tx buffers.
-    fn free_processed_tx_buffers(&mut self);
+    fn free_processed_tx_buffers(&self);
+
+    /// Notifies the device driver that a polling operation on the `queue`-th receive queue has
+    /// ended.
+    fn notify_poll_end(&self, queue: usize);
 
-    /// Notifies the device driver that a polling operation has ended.
+    /// Sets the receive filter of the device.
     ///
-    /// The driver can assume that the device remains protected by acquiring a poll lock
-    /// for the entire duration of the polling process.
-    /// Thus two polling process cannot happen simultaneously.
-    fn notify_poll_end(&mut self);
+    /// Devices that do not support filtering incoming frames can ignore this.
+    fn set_rx_filter(&self, _filter: &RxFilter) {}
 }
 
 pub trait NetDeviceCallback = Fn() + Send + Sync + 'static;
 
-pub fn register_device(
-    name: String,
-    device: Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>,
-) {
+pub fn register_device(name: String, device: Arc<dyn AnyNetworkDevice>) {
     COMPONENT
         .get()
         .unwrap()
@@ -79,7 +100,7 @@ pub fn register_device(
         .insert(name, NetworkDeviceIrqCallbackSet::new(device));
 }
 
-pub fn get_device(str: &str) -> Option<Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>> {
+pub fn get_device(str: &str) -> Option<Arc<dyn AnyNetworkDevice>> {
     let table = COMPONENT.get().unwrap().network_device_table.lock();
     let callbacks = table.get(str)?;
     Some(callbacks.device.clone())
@@ -132,13 +153,10 @@ fn handle_tx_softirq() {
     // rather than processing events for all devices.
     // This issue should be addressed once new network devices are added.
     for callback_set in device_table.values() {
-        let can_send = {
-            let mut device = callback_set.device.lock();
-            device.free_processed_tx_buffers();
-            device.can_send()
-        };
+        let device = &callback_set.device;
+        device.free_processed_tx_buffers();
 
-        if !can_send {
+        if !device.can_send() {
             continue;
         }
 
@@ -181,7 +199,7 @@ fn init() -> Result<(), ComponentInitError> {
 }
 
 type NetDeviceCallbackListRef = Arc<SpinLock<Vec<Arc<dyn NetDeviceCallback>>, BottomHalfDisabled>>;
-type NetworkDeviceRef = Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>;
+type NetworkDeviceRef = Arc<dyn AnyNetworkDevice>;
 
 struct Component {
     /// Device list, the key is device name, value is (callbacks, device);
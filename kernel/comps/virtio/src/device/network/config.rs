@@ -51,7 +51,19 @@ bitflags! {
 
 impl NetworkFeatures {
     pub(super) fn supported_features() -> Self {
-        NetworkFeatures::VIRTIO_NET_F_MAC | NetworkFeatures::VIRTIO_NET_F_STATUS
+        NetworkFeatures::VIRTIO_NET_F_CSUM
+            | NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM
+            | NetworkFeatures::VIRTIO_NET_F_MAC
+            | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4
+            | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO6
+            | NetworkFeatures::VIRTIO_NET_F_HOST_TSO4
+            | NetworkFeatures::VIRTIO_NET_F_HOST_TSO6
+            | NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF
+            | NetworkFeatures::VIRTIO_NET_F_STATUS
+            | NetworkFeatures::VIRTIO_NET_F_CTRL_VQ
+            | NetworkFeatures::VIRTIO_NET_F_CTRL_RX
+            | NetworkFeatures::VIRTIO_NET_F_MQ
+            | NetworkFeatures::VIRTIO_NET_F_RSS
     }
 }
 
@@ -69,13 +81,13 @@ bitflags! {
 pub(super) struct VirtioNetConfig {
     pub mac: EthernetAddr,
     pub status: Status,
-    max_virtqueue_pairs: u16,
+    pub max_virtqueue_pairs: u16,
     pub mtu: u16,
     speed: u32,
     duplex: u8,
-    rss_max_key_size: u8,
-    rss_max_indirection_table_length: u16,
-    supported_hash_types: u32,
+    pub rss_max_key_size: u8,
+    pub rss_max_indirection_table_length: u16,
+    pub supported_hash_types: u32,
 }
 
 impl VirtioNetConfig {
@@ -100,11 +112,13 @@ impl ConfigManager<VirtioNetConfig> {
         net_config.status.bits = self
             .read_once::<u16>(offset_of!(VirtioNetConfig, status))
             .unwrap();
+        // This is also defined in the legacy interface, but only if `VIRTIO_NET_F_MQ` is
+        // negotiated. So it may not be readable.
+        net_config.max_virtqueue_pairs = self
+            .read_once::<u16>(offset_of!(VirtioNetConfig, max_virtqueue_pairs))
+            .unwrap_or(1);
 
         if self.is_modern() {
-            net_config.max_virtqueue_pairs = self
-                .read_once::<u16>(offset_of!(VirtioNetConfig, max_virtqueue_pairs))
-                .unwrap();
             net_config.mtu = self
                 .read_once::<u16>(offset_of!(VirtioNetConfig, mtu))
                 .unwrap();
@@ -131,3 +145,39 @@ impl ConfigManager<VirtioNetConfig> {
         net_config
     }
 }
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn config_layout() {
+        // The offsets are defined in `struct virtio_net_config` of the virtio specification.
+        assert_eq!(offset_of!(VirtioNetConfig, mac), 0);
+        assert_eq!(offset_of!(VirtioNetConfig, status), 6);
+        assert_eq!(offset_of!(VirtioNetConfig, max_virtqueue_pairs), 8);
+        assert_eq!(offset_of!(VirtioNetConfig, mtu), 10);
+        assert_eq!(offset_of!(VirtioNetConfig, speed), 12);
+        assert_eq!(offset_of!(VirtioNetConfig, duplex), 16);
+        assert_eq!(offset_of!(VirtioNetConfig, rss_max_key_size), 17);
+        assert_eq!(
+            offset_of!(VirtioNetConfig, rss_max_indirection_table_length),
+            18
+        );
+        assert_eq!(offset_of!(VirtioNetConfig, supported_hash_types), 20);
+        assert_eq!(size_of::<VirtioNetConfig>(), 24);
+    }
+
+    #[ktest]
+    fn supported_features() {
+        let features = NetworkFeatures::supported_features();
+
+        // Features that need large receive buffers are never supported without merging them.
+        assert!(features.contains(NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF));
+        assert!(!features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_UFO));
+        // The hash report is not supported, so the header has a fixed size.
+        assert!(!features.contains(NetworkFeatures::VIRTIO_NET_F_HASH_REPORT));
+    }
+}
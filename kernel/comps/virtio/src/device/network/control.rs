@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The control queue of a virtio-net device.
+
+use alloc::vec::Vec;
+use core::{hint::spin_loop, ops::Range};
+
+use aster_bigtcp::wire::EthernetAddress;
+use aster_util::mem_obj_slice::Slice;
+use ostd::mm::{PAGE_SIZE, VmReader, dma::DmaStream, io::util::HasVmReaderWriter};
+
+use crate::{device::VirtioDeviceError, queue::VirtQueue, transport::VirtioTransport};
+
+/// The control queue of a virtio-net device.
+///
+/// The control queue is used to send commands that configure the device (e.g., the receive
+/// filter). Commands are rare, so they are sent synchronously: the driver busy-waits until the
+/// device acknowledges the command.
+///
+/// Reference: <https://docs.oasis-open.org/virtio/virtio/v1.3/csd01/virtio-v1.3-csd01.html#x1-2480006>.
+pub(super) struct ControlQueue {
+    queue: VirtQueue,
+    buffer: DmaStream,
+}
+
+impl ControlQueue {
+    const QUEUE_SIZE: u16 = 8;
+
+    pub(super) fn new(
+        index: u16,
+        transport: &mut dyn VirtioTransport,
+    ) -> Result<Self, VirtioDeviceError> {
+        let mut queue = VirtQueue::new(index, Self::QUEUE_SIZE, transport)?;
+        queue.disable_callback();
+
+        let buffer = DmaStream::alloc(1, false).map_err(VirtioDeviceError::ResourceAlloc)?;
+
+        Ok(Self { queue, buffer })
+    }
+
+    /// Enables or disables the promiscuous mode.
+    pub(super) fn set_promiscuous(&mut self, enabled: bool) -> bool {
+        self.send_command(
+            VIRTIO_NET_CTRL_RX,
+            VIRTIO_NET_CTRL_RX_PROMISC,
+            &[&[enabled as u8]],
+        )
+    }
+
+    /// Enables or disables receiving all multicast frames.
+    pub(super) fn set_all_multicast(&mut self, enabled: bool) -> bool {
+        self.send_command(
+            VIRTIO_NET_CTRL_RX,
+            VIRTIO_NET_CTRL_RX_ALLMULTI,
+            &[&[enabled as u8]],
+        )
+    }
+
+    /// Sets the MAC address tables used to filter incoming frames.
+    ///
+    /// The tables must not contain more than [`MAX_MAC_TABLE_ENTRIES`] entries.
+    pub(super) fn set_mac_table(
+        &mut self,
+        unicast_addrs: &[EthernetAddress],
+        multicast_addrs: &[EthernetAddress],
+    ) -> bool {
+        debug_assert!(unicast_addrs.len() <= MAX_MAC_TABLE_ENTRIES);
+        debug_assert!(multicast_addrs.len() <= MAX_MAC_TABLE_ENTRIES);
+
+        let unicast_table = mac_table(unicast_addrs);
+        let multicast_table = mac_table(multicast_addrs);
+
+        self.send_command(
+            VIRTIO_NET_CTRL_MAC,
+            VIRTIO_NET_CTRL_MAC_TABLE_SET,
+            &[&unicast_table, &multicast_table],
+        )
+    }
+
+    /// Sets the number of queue pairs used by the device.
+    ///
+    /// The device steers incoming packets to the receive queues automatically.
+    pub(super) fn set_queue_pairs(&mut self, num_pairs: u16) -> bool {
+        self.send_command(
+            VIRTIO_NET_CTRL_MQ,
+            VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET,
+            &[&num_pairs.to_le_bytes()],
+        )
+    }
+
+    /// Enables receive-side scaling (RSS) over the queue pairs.
+    ///
+    /// Incoming packets are steered to the receive queues by the Toeplitz hash of the `hash_types`
+    /// fields. `indirection_table_len` must be a power of two.
+    pub(super) fn set_rss(
+        &mut self,
+        num_pairs: u16,
+        hash_types: u32,
+        indirection_table_len: u16,
+        hash_key: &[u8],
+    ) -> bool {
+        debug_assert!(indirection_table_len.is_power_of_two());
+
+        let config = rss_config(num_pairs, hash_types, indirection_table_len, hash_key);
+        self.send_command(
+            VIRTIO_NET_CTRL_MQ,
+            VIRTIO_NET_CTRL_MQ_RSS_CONFIG,
+            &[&config],
+        )
+    }
+
+    /// Sends a command and waits for the device to acknowledge it.
+    ///
+    /// The header, each part of the command-specific data, and the acknowledgment are placed in
+    /// separate descriptors, since legacy devices without `VIRTIO_F_ANY_LAYOUT` require so.
+    fn send_command(&mut self, class: u8, command: u8, data: &[&[u8]]) -> bool {
+        let mut ranges = Vec::with_capacity(data.len() + 1);
+        let mut offset = 0;
+        for part in core::iter::once(&[class, command][..]).chain(data.iter().copied()) {
+            let range = offset..offset + part.len();
+            self.write_bytes(range.clone(), part);
+            ranges.push(range);
+            offset += part.len();
+        }
+
+        let ack_range = ACK_OFFSET..ACK_OFFSET + 1;
+        debug_assert!(offset <= ACK_OFFSET);
+        self.write_bytes(ack_range.clone(), &[VIRTIO_NET_ERR]);
+
+        let inputs = ranges
+            .into_iter()
+            .map(|range| Slice::new(&self.buffer, range))
+            .collect::<Vec<_>>();
+        let input_refs = inputs.iter().collect::<Vec<_>>();
+        let ack = Slice::new(&self.buffer, ack_range.clone());
+
+        if self.queue.add_dma_bufs(&input_refs, &[&ack]).is_err() {
+            return false;
+        }
+        if self.queue.should_notify() {
+            self.queue.notify();
+        }
+        while self.queue.pop_used().is_err() {
+            spin_loop();
+        }
+
+        self.buffer.sync_from_device(ack_range).unwrap();
+        let ack = self
+            .buffer
+            .reader()
+            .unwrap()
+            .skip(ACK_OFFSET)
+            .read_val::<u8>()
+            .unwrap();
+
+        ack == VIRTIO_NET_OK
+    }
+
+    fn write_bytes(&self, range: Range<usize>, bytes: &[u8]) {
+        let mut writer = self.buffer.writer().unwrap();
+        writer.skip(range.start).write(&mut VmReader::from(bytes));
+        self.buffer.sync_to_device(range).unwrap();
+    }
+}
+
+/// Serializes the RSS configuration as `struct virtio_net_rss_config`.
+///
+/// The indirection table spreads the hash values evenly over the `num_pairs` receive queues.
+fn rss_config(
+    num_pairs: u16,
+    hash_types: u32,
+    indirection_table_len: u16,
+    hash_key: &[u8],
+) -> Vec<u8> {
+    let mut config = Vec::new();
+    config.extend_from_slice(&hash_types.to_le_bytes());
+    config.extend_from_slice(&(indirection_table_len - 1).to_le_bytes());
+    // Packets that cannot be hashed go to the first receive queue.
+    config.extend_from_slice(&0u16.to_le_bytes());
+    for i in 0..indirection_table_len {
+        config.extend_from_slice(&(i % num_pairs).to_le_bytes());
+    }
+    config.extend_from_slice(&num_pairs.to_le_bytes());
+    config.push(hash_key.len() as u8);
+    config.extend_from_slice(hash_key);
+    config
+}
+
+/// Serializes the MAC address table as `struct virtio_net_ctrl_mac`.
+fn mac_table(addrs: &[EthernetAddress]) -> Vec<u8> {
+    let mut table = Vec::with_capacity(size_of::<u32>() + addrs.len() * 6);
+    table.extend_from_slice(&(addrs.len() as u32).to_le_bytes());
+    for addr in addrs {
+        table.extend_from_slice(addr.as_bytes());
+    }
+    table
+}
+
+/// The maximum number of entries in a MAC address table.
+///
+/// The specification does not limit the number of entries, but devices are free to fall back to
+/// receiving all multicast frames if the table is too large. So we do this ourselves.
+pub(super) const MAX_MAC_TABLE_ENTRIES: usize = 64;
+
+/// The offset of the acknowledgment byte in the buffer.
+const ACK_OFFSET: usize = PAGE_SIZE - 1;
+
+const VIRTIO_NET_OK: u8 = 0;
+const VIRTIO_NET_ERR: u8 = 1;
+
+const VIRTIO_NET_CTRL_RX: u8 = 0;
+const VIRTIO_NET_CTRL_RX_PROMISC: u8 = 0;
+const VIRTIO_NET_CTRL_RX_ALLMULTI: u8 = 1;
+
+const VIRTIO_NET_CTRL_MAC: u8 = 1;
+const VIRTIO_NET_CTRL_MAC_TABLE_SET: u8 = 0;
+
+const VIRTIO_NET_CTRL_MQ: u8 = 4;
+const VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET: u8 = 0;
+const VIRTIO_NET_CTRL_MQ_RSS_CONFIG: u8 = 1;
+
+#[cfg(ktest)]
+mod test {
+    use alloc::vec;
+
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn serialize_rss_config() {
+        let config = rss_config(3, 0x12, 4, &[0xaa, 0xbb]);
+
+        #[rustfmt::skip]
+        let expected = vec![
+            // hash_types
+            0x12, 0x00, 0x00, 0x00,
+            // indirection_table_mask
+            0x03, 0x00,
+            // unclassified_queue
+            0x00, 0x00,
+            // indirection_table
+            0x00, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00,
+            // max_tx_vq
+            0x03, 0x00,
+            // hash_key_length and hash_key_data
+            0x02, 0xaa, 0xbb,
+        ];
+        assert_eq!(config, expected);
+    }
+
+    #[ktest]
+    fn serialize_mac_table() {
+        assert_eq!(mac_table(&[]), vec![0, 0, 0, 0]);
+
+        let addrs = [
+            EthernetAddress([0x02, 0, 0, 0, 0, 0x01]),
+            EthernetAddress([0x33, 0x33, 0, 0, 0, 0x01]),
+        ];
+        let table = mac_table(&addrs);
+        assert_eq!(table.len(), 4 + 2 * 6);
+        assert_eq!(&table[..4], &2u32.to_le_bytes());
+        assert_eq!(&table[4..10], addrs[0].as_bytes());
+        assert_eq!(&table[10..16], addrs[1].as_bytes());
+    }
+}
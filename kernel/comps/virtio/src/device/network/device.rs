@@ -3,12 +3,19 @@
 use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
 use core::fmt::Debug;
 
-use aster_bigtcp::device::{Checksum, DeviceCapabilities, Medium};
+use aster_bigtcp::device::{Checksum, DeviceCapabilities, Medium, RxFilter};
 use aster_network::{AnyNetworkDevice, EthernetAddr, NetError, RxBuffer, TxBuffer};
-use aster_util::slot_vec::SlotVec;
-use ostd::{arch::trap::TrapFrame, debug, sync::SpinLock, warn};
-
-use super::{config::VirtioNetConfig, header::VirtioNetHdr};
+use aster_softirq::BottomHalfDisabled;
+use ostd::{arch::trap::TrapFrame, cpu::CpuId, debug, sync::SpinLock, warn};
+
+use super::{
+    buffer::TX_BUFFER_LEN,
+    config::VirtioNetConfig,
+    control::{ControlQueue, MAX_MAC_TABLE_ENTRIES},
+    header::VirtioNetHdr,
+    offload::TxOffload,
+    queue_pair::QueuePair,
+};
 use crate::{
     device::{
         VirtioDeviceError,
@@ -17,52 +24,72 @@ use crate::{
             config::NetworkFeatures,
         },
     },
-    queue::{self, VirtQueue},
     transport::{ConfigManager, VirtioTransport},
 };
 
 pub struct NetworkDevice {
     config_manager: ConfigManager<VirtioNetConfig>,
+    features: NetworkFeatures,
     // For smoltcp use
     caps: DeviceCapabilities,
     mac_addr: EthernetAddr,
-    send_queue: VirtQueue,
-    recv_queue: VirtQueue,
-    // Since the virtio net header remains consistent for each sending packet,
-    // we store it to avoid recreating the header repeatedly.
-    header: VirtioNetHdr,
-    tx_buffers: Vec<Option<TxBuffer>>,
-    rx_buffers: SlotVec<RxBuffer>,
-    new_rx_buffer: Option<RxBuffer>,
+    /// The queue pairs, each of which is protected by its own lock.
+    ///
+    /// The receive queues are polled separately, and each CPU sends packets via its own send
+    /// queue (if there are enough queue pairs), so the queue pairs can be used on different CPUs
+    /// at the same time.
+    queue_pairs: Vec<SpinLock<QueuePair, BottomHalfDisabled>>,
+    /// The maximum number of buffers that a sending packet can take.
+    max_tx_bufs: usize,
+    control_queue: Option<SpinLock<ControlQueue, BottomHalfDisabled>>,
     transport: Box<dyn VirtioTransport>,
-    poll_stat: PollStatistics,
-}
-
-/// Structure to track the number of packets sent and received during a single polling process.
-struct PollStatistics {
-    sent_packet: usize,
-    received_packet: usize,
-}
-
-impl PollStatistics {
-    const fn new() -> Self {
-        Self {
-            sent_packet: 0,
-            received_packet: 0,
-        }
-    }
 }
 
 impl NetworkDevice {
     pub(crate) fn negotiate_features(device_features: u64) -> u64 {
         let device_features = NetworkFeatures::from_bits_truncate(device_features);
         let supported_features = NetworkFeatures::supported_features();
-        let network_features = device_features & supported_features;
+        let mut network_features = device_features & supported_features;
+
+        // Segmentation offloading requires checksum offloading.
+        if !network_features.contains(NetworkFeatures::VIRTIO_NET_F_CSUM) {
+            network_features.remove(
+                NetworkFeatures::VIRTIO_NET_F_HOST_TSO4 | NetworkFeatures::VIRTIO_NET_F_HOST_TSO6,
+            );
+        }
+        // Receiving TSO packets requires large receive buffers. We only support them by merging
+        // receive buffers.
+        if !network_features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM)
+            || !network_features.contains(NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF)
+        {
+            network_features.remove(
+                NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4 | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO6,
+            );
+        }
+
+        // The receive filter and multiple queues are configured through the control queue.
+        if !network_features.contains(NetworkFeatures::VIRTIO_NET_F_CTRL_VQ) {
+            network_features.remove(
+                NetworkFeatures::VIRTIO_NET_F_CTRL_RX
+                    | NetworkFeatures::VIRTIO_NET_F_MQ
+                    | NetworkFeatures::VIRTIO_NET_F_RSS,
+            );
+        }
+        // RSS is only used to steer packets to multiple queues.
+        if !network_features.contains(NetworkFeatures::VIRTIO_NET_F_MQ) {
+            network_features.remove(NetworkFeatures::VIRTIO_NET_F_RSS);
+        }
+        // The control queue is useless if neither is supported.
+        if !network_features
+            .intersects(NetworkFeatures::VIRTIO_NET_F_CTRL_RX | NetworkFeatures::VIRTIO_NET_F_MQ)
+        {
+            network_features.remove(NetworkFeatures::VIRTIO_NET_F_CTRL_VQ);
+        }
 
         if network_features != device_features {
             warn!(
                 "unsupported device features: {:?}",
-                device_features.difference(supported_features)
+                device_features.difference(network_features)
             );
         }
 
@@ -82,40 +109,43 @@ impl NetworkDevice {
 
         let caps = init_caps(&features, &config);
 
-        let mut send_queue = VirtQueue::new(QUEUE_SEND, QUEUE_SIZE, transport.as_mut())?;
-        send_queue.disable_callback();
-
-        let mut recv_queue = VirtQueue::new(QUEUE_RECV, QUEUE_SIZE, transport.as_mut())?;
+        // The control queue follows all the queue pairs supported by the device, but we only
+        // create a queue pair for each CPU.
+        let (max_pairs, num_pairs) = if features.contains(NetworkFeatures::VIRTIO_NET_F_MQ) {
+            let max_pairs = config.max_virtqueue_pairs.max(1);
+            let num_cpus = u16::try_from(ostd::cpu::num_cpus()).unwrap_or(u16::MAX);
+            (max_pairs, max_pairs.min(num_cpus))
+        } else {
+            (1, 1)
+        };
 
-        let tx_buffers = (0..QUEUE_SIZE).map(|_| None).collect();
+        let queue_pairs = (0..num_pairs)
+            .map(|index| QueuePair::new(index, transport.as_mut()).map(SpinLock::new))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let mut rx_buffers = SlotVec::new();
-        for i in 0..QUEUE_SIZE {
-            let rx_pool = RX_BUFFER_POOL.get().unwrap();
-            let rx_buffer = RxBuffer::new(size_of::<VirtioNetHdr>(), rx_pool)
-                .map_err(VirtioDeviceError::ResourceAlloc)?;
-            let token = recv_queue.add_output_bufs(&[&rx_buffer]).unwrap();
-            assert_eq!(i, token);
-            assert_eq!(rx_buffers.put(rx_buffer) as u16, i);
-        }
+        let control_queue = if features.contains(NetworkFeatures::VIRTIO_NET_F_CTRL_VQ) {
+            let index = QueuePair::recv_queue_index(max_pairs);
+            Some(SpinLock::new(ControlQueue::new(index, transport.as_mut())?))
+        } else {
+            None
+        };
 
-        if recv_queue.should_notify() {
-            debug!("notify receive queue");
-            recv_queue.notify();
-        }
+        let max_tx_bufs = if gso_max_size(&features) > 0 {
+            (size_of::<VirtioNetHdr>() + ETHERNET_HEADER_LEN + gso_max_size(&features))
+                .div_ceil(TX_BUFFER_LEN)
+        } else {
+            1
+        };
 
         let mut device = Self {
             config_manager,
+            features,
             caps,
             mac_addr,
-            send_queue,
-            recv_queue,
-            header: VirtioNetHdr::default(),
-            tx_buffers,
-            rx_buffers,
-            new_rx_buffer: None,
+            queue_pairs,
+            max_tx_bufs,
+            control_queue,
             transport,
-            poll_stat: PollStatistics::new(),
         };
 
         /// Interrupt handler if network device config space changes
@@ -134,136 +164,235 @@ impl NetworkDevice {
         device
             .transport
             .register_cfg_callback(Box::new(config_space_change))?;
-        device
-            .transport
-            .register_queue_callback(QUEUE_SEND, Box::new(handle_send_event), true)?;
-        device
-            .transport
-            .register_queue_callback(QUEUE_RECV, Box::new(handle_recv_event), true)?;
+        for index in 0..num_pairs {
+            device.transport.register_queue_callback(
+                QueuePair::send_queue_index(index),
+                Box::new(handle_send_event),
+                true,
+            )?;
+            device.transport.register_queue_callback(
+                QueuePair::recv_queue_index(index),
+                Box::new(handle_recv_event),
+                true,
+            )?;
+        }
 
         device.transport.finish_init();
 
-        aster_network::register_device(
-            super::DEVICE_NAME.to_string(),
-            Arc::new(SpinLock::new(device)),
-        );
+        // The device uses only the first queue pair until it is told to use more.
+        if num_pairs > 1 && !device.enable_multiqueue(&config, num_pairs) {
+            warn!("failed to enable multiple queues of the network device");
+            device.queue_pairs.truncate(1);
+        }
+
+        aster_network::register_device(super::DEVICE_NAME.to_string(), Arc::new(device));
         Ok(())
     }
 
-    /// Adds a `RxBuffer` to the receive queue.
-    fn add_rx_buffer(&mut self, rx_buffer: RxBuffer) -> Result<(), queue::AddBufsError> {
-        let token = self.recv_queue.add_output_bufs(&[&rx_buffer])?;
-        assert!(self.rx_buffers.put_at(token as usize, rx_buffer).is_none());
+    /// Tells the device to use `num_pairs` queue pairs.
+    fn enable_multiqueue(&mut self, config: &VirtioNetConfig, num_pairs: u16) -> bool {
+        let control_queue = self.control_queue.as_mut().unwrap().get_mut();
 
-        self.poll_stat.received_packet += 1;
+        if !self.features.contains(NetworkFeatures::VIRTIO_NET_F_RSS) {
+            return control_queue.set_queue_pairs(num_pairs);
+        }
 
-        if self.poll_stat.received_packet == QUEUE_SIZE as _ {
-            // If we know there are no free buffers for receiving,
-            // we will notify the receive queue as soon as possible.
-            self.notify_receive_queue();
+        match RssParams::new(config) {
+            Some(params) => control_queue.set_rss(
+                num_pairs,
+                params.hash_types,
+                params.table_len,
+                &RSS_HASH_KEY[..params.key_len],
+            ),
+            None => control_queue.set_queue_pairs(num_pairs),
         }
+    }
 
-        Ok(())
+    /// Receives a packet from the receive queue of the `pair_index`-th queue pair.
+    fn receive(&self, pair_index: usize) -> Result<RxBuffer, NetError> {
+        let mut queue_pair = self.queue_pairs[pair_index].lock();
+
+        loop {
+            if !queue_pair.can_receive() {
+                return Err(NetError::NotReady);
+            }
+
+            // If the packet is dropped, try receiving the next one.
+            if let Some(rx_buffer) = self.receive_from(&mut queue_pair)? {
+                return Ok(rx_buffer);
+            }
+        }
     }
 
-    /// Receives a packet from network.
-    fn receive(&mut self) -> Result<RxBuffer, NetError> {
-        if self.new_rx_buffer.is_none() {
+    /// Receives a packet from the receive queue of the queue pair.
+    ///
+    /// This method returns `None` if the packet is dropped.
+    fn receive_from(&self, queue_pair: &mut QueuePair) -> Result<Option<RxBuffer>, NetError> {
+        const HEADER_LEN: usize = size_of::<VirtioNetHdr>();
+
+        if queue_pair.new_rx_buffer.is_none() {
             // FIXME: Ideally, we can reuse the returned buffer without creating new buffer.
             // But this requires locking device to be compatible with smoltcp interface.
             let rx_pool = RX_BUFFER_POOL.get().unwrap();
-            let new_rx_buffer = RxBuffer::new(size_of::<VirtioNetHdr>(), rx_pool)
-                .map_err(|_| NetError::NoMemory)?;
+            let new_rx_buffer =
+                RxBuffer::new(HEADER_LEN, rx_pool).map_err(|_| NetError::NoMemory)?;
 
-            self.new_rx_buffer = Some(new_rx_buffer);
+            queue_pair.new_rx_buffer = Some(new_rx_buffer);
         }
 
-        let (token, len) = self
-            .recv_queue
-            .pop_used_with_min_bytes(size_of::<VirtioNetHdr>())
-            .map_err(|_| NetError::NotReady)?;
-        debug!("receive packet: token = {}, len = {}", token, len);
+        let (mut rx_buffer, len) = queue_pair.pop_rx_buffer(HEADER_LEN)?;
+        rx_buffer.set_payload_len(len - HEADER_LEN);
+
+        let new_rx_buffer = queue_pair.new_rx_buffer.take().unwrap();
+        queue_pair.add_rx_buffer(new_rx_buffer).unwrap();
+
+        let header = rx_buffer.buf().read_val::<VirtioNetHdr>().unwrap();
+
+        // If `VIRTIO_NET_F_MRG_RXBUF` is negotiated, a large packet can span multiple buffers.
+        // Only the first buffer contains the header.
+        let mut is_dropped = false;
+        if self
+            .features
+            .contains(NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF)
+        {
+            for _ in 1..header.num_buffers {
+                let Ok((next_rx_buffer, len)) = queue_pair.pop_rx_buffer(0) else {
+                    warn!("the network device did not provide all the buffers of a packet");
+                    return Ok(None);
+                };
+
+                let rx_pool = RX_BUFFER_POOL.get().unwrap();
+                match RxBuffer::new(HEADER_LEN, rx_pool) {
+                    Ok(new_rx_buffer) => {
+                        queue_pair.add_rx_buffer(new_rx_buffer).unwrap();
+                        rx_buffer.append(next_rx_buffer, len);
+                    }
+                    Err(_) => {
+                        // Reuse the buffer and drop the packet if we are out of memory.
+                        queue_pair.add_rx_buffer(next_rx_buffer).unwrap();
+                        is_dropped = true;
+                    }
+                }
+            }
+        }
+        if is_dropped {
+            return Ok(None);
+        }
 
-        let mut rx_buffer = self.rx_buffers.remove(token as usize).unwrap();
-        rx_buffer.set_payload_len(len as usize - size_of::<VirtioNetHdr>());
+        if let Some(partial_checksum) = header.partial_checksum() {
+            rx_buffer.set_partial_checksum(partial_checksum);
+        }
 
-        let new_rx_buffer = self.new_rx_buffer.take().unwrap();
-        self.add_rx_buffer(new_rx_buffer).unwrap();
+        Ok(Some(rx_buffer))
+    }
 
-        Ok(rx_buffer)
+    /// Returns the index of the queue pair used to send packets on the current CPU.
+    fn tx_pair_index(&self) -> usize {
+        u32::from(CpuId::current_racy()) as usize % self.queue_pairs.len()
     }
 
     /// Sends a packet to network.
-    fn send(&mut self, packet: &[u8]) -> Result<(), NetError> {
+    fn send(&self, packet: &[u8]) -> Result<(), NetError> {
         if !self.can_send() {
             return Err(NetError::Busy);
         }
 
-        let tx_pool = TX_BUFFER_POOL.get().unwrap();
-        let tx_buffer =
-            TxBuffer::new(&self.header, packet, tx_pool).map_err(|_| NetError::NoMemory)?;
-
-        let token = self.send_queue.add_input_bufs(&[&tx_buffer]).unwrap();
-
-        self.poll_stat.sent_packet += 1;
+        let offload = if self.features.contains(NetworkFeatures::VIRTIO_NET_F_CSUM) {
+            let tso_ip_mtu = (gso_max_size(&self.features) > 0)
+                .then(|| self.caps.max_transmission_unit - ETHERNET_HEADER_LEN);
+            Some(TxOffload::new(packet, tso_ip_mtu))
+        } else {
+            None
+        };
 
-        if self.send_queue.available_desc() == 0 {
-            // If the send queue is full,
-            // we will notify the send queue as soon as possible.
-            self.notify_send_queue();
+        let tx_pool = TX_BUFFER_POOL.get().unwrap();
+        let tx_buffers = match offload {
+            Some(TxOffload {
+                header,
+                csum_field: Some((offset, value)),
+            }) => TxBuffer::new_chain(
+                &header,
+                &[
+                    &packet[..offset],
+                    &value.to_be_bytes(),
+                    &packet[offset + 2..],
+                ],
+                tx_pool,
+            ),
+            Some(TxOffload {
+                header,
+                csum_field: None,
+            }) => TxBuffer::new_chain(&header, &[packet], tx_pool),
+            None => TxBuffer::new_chain(&VirtioNetHdr::default(), &[packet], tx_pool),
         }
+        .map_err(|_| NetError::NoMemory)?;
+        debug_assert!(tx_buffers.len() <= self.max_tx_bufs);
 
-        debug!("send packet, token = {}, len = {}", token, packet.len());
-
-        debug_assert!(self.tx_buffers[token as usize].is_none());
-        self.tx_buffers[token as usize] = Some(tx_buffer);
-
-        self.free_processed_tx_buffers();
-
-        // If the send queue is not full, we can free the send buffers during the next sending process.
-        // Therefore, there is no need to free the used buffers in the IRQ handlers.
-        // This allows us to temporarily disable the send queue interrupt.
-        // Conversely, if the send queue is full, the send queue interrupt should remain enabled
-        // to free the send buffers as quickly as possible.
-        if !self.can_send() {
-            self.send_queue.enable_callback();
-        } else {
-            self.send_queue.disable_callback();
+        let mut queue_pair = self.queue_pairs[self.tx_pair_index()].lock();
+        // The send queue may have been filled up by another CPU since it was last checked.
+        if !queue_pair.can_send(self.max_tx_bufs) {
+            return Err(NetError::Busy);
         }
+        queue_pair.send(tx_buffers);
+        queue_pair.update_send_callback(self.max_tx_bufs);
 
         Ok(())
     }
 
-    fn notify_send_queue(&mut self) {
-        if self.poll_stat.sent_packet == 0 {
+    /// Sets the receive filter through the control queue.
+    fn set_rx_filter(&self, filter: &RxFilter) {
+        let Some(control_queue) = self.control_queue.as_ref() else {
             return;
+        };
+        let mut control_queue = control_queue.lock();
+
+        // Fall back to receiving all multicast frames if the table is too large.
+        let (multicast_addrs, all_multicast) =
+            if filter.multicast_addrs.len() <= MAX_MAC_TABLE_ENTRIES {
+                (filter.multicast_addrs.as_slice(), filter.all_multicast)
+            } else {
+                (&[][..], true)
+            };
+
+        // The device always accepts frames destined for its own MAC address, so the unicast
+        // table can be left empty.
+        if !control_queue.set_mac_table(&[], multicast_addrs)
+            || !control_queue.set_all_multicast(all_multicast)
+            || !control_queue.set_promiscuous(filter.promiscuous)
+        {
+            warn!("failed to set the receive filter of the network device");
         }
-
-        debug!(
-            "notify send queue: sent {} packets",
-            self.poll_stat.sent_packet
-        );
-        if self.send_queue.should_notify() {
-            self.send_queue.notify();
-        }
-
-        self.poll_stat.sent_packet = 0;
     }
+}
 
-    fn notify_receive_queue(&mut self) {
-        if self.poll_stat.received_packet == 0 {
-            return;
-        }
+/// The RSS parameters that are supported by both the device and us.
+#[derive(Debug, PartialEq, Eq)]
+struct RssParams {
+    hash_types: u32,
+    /// The length of the indirection table, which is a power of two.
+    table_len: u16,
+    /// The length of the hash key, which is a prefix of [`RSS_HASH_KEY`].
+    key_len: usize,
+}
 
-        debug!(
-            "notify receive queue: received {} packets",
-            self.poll_stat.received_packet
-        );
-        if self.recv_queue.should_notify() {
-            self.recv_queue.notify();
+impl RssParams {
+    /// Decides the RSS parameters from the device configuration.
+    ///
+    /// Returns `None` if the device cannot hash the packets that we are interested in.
+    fn new(config: &VirtioNetConfig) -> Option<Self> {
+        let hash_types = config.supported_hash_types & RSS_HASH_TYPES;
+        let max_table_len = config
+            .rss_max_indirection_table_length
+            .min(MAX_RSS_INDIRECTION_TABLE_LEN);
+        if hash_types == 0 || max_table_len == 0 {
+            return None;
         }
 
-        self.poll_stat.received_packet = 0;
+        Some(Self {
+            hash_types,
+            table_len: 1 << max_table_len.ilog2(),
+            key_len: (config.rss_max_key_size as usize).min(RSS_HASH_KEY.len()),
+        })
     }
 }
 
@@ -277,35 +406,50 @@ fn init_caps(features: &NetworkFeatures, config: &VirtioNetConfig) -> DeviceCapa
         // If `VIRTIO_NET_F_MTU` is negotiated, the MTU is decided by the device.
         caps.max_transmission_unit = config.mtu as usize;
     } else {
-        // We do not support these features,
-        // so this asserts that they are _not_ negotiated.
-        //
-        // Without these features, the MTU is 1514 bytes per the virtio-net specification
-        // (see "5.1.6.3 Setting Up Receive Buffers" and "5.1.6.2 Packet Transmission").
+        // Per the virtio-net specification (see "5.1.6.3 Setting Up Receive Buffers" and
+        // "5.1.6.2 Packet Transmission"), the MTU is 1514 bytes. Larger packets can be received
+        // only if `VIRTIO_NET_F_GUEST_TSO4` or `VIRTIO_NET_F_GUEST_TSO6` is negotiated, but
+        // these features require merging receive buffers, which we always do.
         assert!(
-            !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4)
-                && !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_TSO6)
-                && !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_UFO)
+            !features.intersects(
+                NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4
+                    | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO6
+                    | NetworkFeatures::VIRTIO_NET_F_GUEST_UFO
+            ) || features.contains(NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF)
         );
         caps.max_transmission_unit = 1514;
     }
 
-    // We do not support checksum offloading.
-    // So the features must not be negotiated,
-    // and we must deliver fully checksummed packets to the device
-    // and validate all checksums for packets from the device.
-    assert!(
-        !features.contains(NetworkFeatures::VIRTIO_NET_F_CSUM)
-            && !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM)
-    );
-    caps.checksum.tcp = Checksum::Both;
-    caps.checksum.udp = Checksum::Both;
+    // If `VIRTIO_NET_F_CSUM` is negotiated, the device computes the TCP and UDP checksums of
+    // outgoing packets. Otherwise, we must deliver fully checksummed packets to the device.
+    //
+    // We always validate the checksums of incoming packets. Even if `VIRTIO_NET_F_GUEST_CSUM` is
+    // negotiated, the device may deliver packets whose checksums are not validated. Packets
+    // with partial checksums are completed before they are validated.
+    let l4_checksum = if features.contains(NetworkFeatures::VIRTIO_NET_F_CSUM) {
+        Checksum::Rx
+    } else {
+        Checksum::Both
+    };
+    caps.checksum.tcp = l4_checksum;
+    caps.checksum.udp = l4_checksum;
     caps.checksum.ipv4 = Checksum::Both;
     caps.checksum.icmpv4 = Checksum::Both;
 
     caps
 }
 
+/// Returns the maximum size of IP packets that the device can split into TCP segments.
+fn gso_max_size(features: &NetworkFeatures) -> usize {
+    if features
+        .contains(NetworkFeatures::VIRTIO_NET_F_HOST_TSO4 | NetworkFeatures::VIRTIO_NET_F_HOST_TSO6)
+    {
+        u16::MAX as usize
+    } else {
+        0
+    }
+}
+
 impl AnyNetworkDevice for NetworkDevice {
     fn mac_addr(&self) -> EthernetAddr {
         self.mac_addr
@@ -315,31 +459,49 @@ impl AnyNetworkDevice for NetworkDevice {
         self.caps.clone()
     }
 
-    fn can_receive(&self) -> bool {
-        self.recv_queue.can_pop()
+    fn gso_max_size(&self) -> usize {
+        gso_max_size(&self.features)
+    }
+
+    fn num_queues(&self) -> usize {
+        self.queue_pairs.len()
+    }
+
+    fn can_receive(&self, queue: usize) -> bool {
+        self.queue_pairs[queue].lock().can_receive()
     }
 
     fn can_send(&self) -> bool {
-        self.send_queue.available_desc() >= 1
+        self.queue_pairs[self.tx_pair_index()]
+            .lock()
+            .can_send(self.max_tx_bufs)
     }
 
-    fn receive(&mut self) -> Result<RxBuffer, NetError> {
-        self.receive()
+    fn receive(&self, queue: usize) -> Result<RxBuffer, NetError> {
+        self.receive(queue)
     }
 
-    fn send(&mut self, packet: &[u8]) -> Result<(), NetError> {
+    fn send(&self, packet: &[u8]) -> Result<(), NetError> {
         self.send(packet)
     }
 
-    fn free_processed_tx_buffers(&mut self) {
-        while let Ok((token, _)) = self.send_queue.pop_used() {
-            self.tx_buffers[token as usize] = None;
+    fn free_processed_tx_buffers(&self) {
+        for queue_pair in self.queue_pairs.iter() {
+            queue_pair.lock().free_processed_tx_buffers();
+        }
+    }
+
+    fn notify_poll_end(&self, queue: usize) {
+        // The packets sent during the polling may go to any send queue, since the polling thread
+        // may be migrated to other CPUs.
+        for queue_pair in self.queue_pairs.iter() {
+            queue_pair.lock().notify_send_queue();
         }
+        self.queue_pairs[queue].lock().notify_receive_queue();
     }
 
-    fn notify_poll_end(&mut self) {
-        self.notify_send_queue();
-        self.notify_receive_queue();
+    fn set_rx_filter(&self, filter: &RxFilter) {
+        self.set_rx_filter(filter);
     }
 }
 
@@ -348,14 +510,126 @@ impl Debug for NetworkDevice {
         f.debug_struct("NetworkDevice")
             .field("config", &self.config_manager.read_config())
             .field("mac_addr", &self.mac_addr)
-            .field("send_queue", &self.send_queue)
-            .field("recv_queue", &self.recv_queue)
+            .field("queue_pairs", &self.queue_pairs)
             .field("transport", &self.transport)
             .finish()
     }
 }
 
-const QUEUE_RECV: u16 = 0;
-const QUEUE_SEND: u16 = 1;
+const ETHERNET_HEADER_LEN: usize = 14;
+
+/// The RSS hash types that we use, i.e., the IPv4/IPv6 addresses and the TCP/UDP ports.
+const RSS_HASH_TYPES: u32 = 0b11_1111;
 
-const QUEUE_SIZE: u16 = 64;
+const MAX_RSS_INDIRECTION_TABLE_LEN: u16 = 128;
+
+/// The default Toeplitz hash key.
+///
+/// Reference: <https://learn.microsoft.com/en-us/windows-hardware/drivers/network/verifying-the-rss-hash-calculation>.
+const RSS_HASH_KEY: [u8; 40] = [
+    0x6d, 0x5a, 0x56, 0xda, 0x25, 0x5b, 0x0e, 0xc2, 0x41, 0x67, 0x25, 0x3d, 0x43, 0xa3, 0x8f, 0xb0,
+    0xd0, 0xca, 0x2b, 0xcb, 0xae, 0x7b, 0x30, 0xb4, 0x77, 0xcb, 0x2d, 0xa3, 0x80, 0x30, 0xf2, 0x0c,
+    0x6a, 0x42, 0xb7, 0x3b, 0xbe, 0xac, 0x01, 0xfa,
+];
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+    use ostd_pod::FromZeros;
+
+    use super::*;
+
+    fn negotiate(features: NetworkFeatures) -> NetworkFeatures {
+        NetworkFeatures::from_bits_truncate(NetworkDevice::negotiate_features(features.bits()))
+    }
+
+    #[ktest]
+    fn negotiate_all_features() {
+        let supported = NetworkFeatures::supported_features();
+        assert_eq!(negotiate(NetworkFeatures::all()), supported);
+    }
+
+    #[ktest]
+    fn negotiate_feature_dependencies() {
+        // TSO requires checksum offloading.
+        let features = negotiate(
+            NetworkFeatures::VIRTIO_NET_F_HOST_TSO4 | NetworkFeatures::VIRTIO_NET_F_HOST_TSO6,
+        );
+        assert!(features.is_empty());
+
+        // Receiving TSO packets requires merging receive buffers.
+        let features = negotiate(
+            NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4,
+        );
+        assert_eq!(features, NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM);
+        let features = negotiate(
+            NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM
+                | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4
+                | NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF,
+        );
+        assert!(features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4));
+
+        // Multiple queues and RSS require the control queue.
+        let features =
+            negotiate(NetworkFeatures::VIRTIO_NET_F_MQ | NetworkFeatures::VIRTIO_NET_F_RSS);
+        assert!(features.is_empty());
+
+        // RSS requires multiple queues.
+        let features = negotiate(
+            NetworkFeatures::VIRTIO_NET_F_CTRL_VQ
+                | NetworkFeatures::VIRTIO_NET_F_CTRL_RX
+                | NetworkFeatures::VIRTIO_NET_F_RSS,
+        );
+        assert_eq!(
+            features,
+            NetworkFeatures::VIRTIO_NET_F_CTRL_VQ | NetworkFeatures::VIRTIO_NET_F_CTRL_RX
+        );
+
+        // The control queue is dropped if nothing uses it.
+        let features = negotiate(NetworkFeatures::VIRTIO_NET_F_CTRL_VQ);
+        assert!(features.is_empty());
+    }
+
+    #[ktest]
+    fn decide_rss_params() {
+        let mut config = VirtioNetConfig::new_zeroed();
+        config.supported_hash_types = 0xffff_ffff;
+        config.rss_max_indirection_table_length = 100;
+        config.rss_max_key_size = 52;
+        assert_eq!(
+            RssParams::new(&config),
+            Some(RssParams {
+                hash_types: RSS_HASH_TYPES,
+                table_len: 64,
+                key_len: RSS_HASH_KEY.len(),
+            })
+        );
+
+        config.rss_max_indirection_table_length = 1024;
+        config.rss_max_key_size = 16;
+        let params = RssParams::new(&config).unwrap();
+        assert_eq!(params.table_len, MAX_RSS_INDIRECTION_TABLE_LEN);
+        assert_eq!(params.key_len, 16);
+
+        // The device cannot hash the packets that we are interested in.
+        config.supported_hash_types = 1 << 6;
+        assert_eq!(RssParams::new(&config), None);
+    }
+
+    #[ktest]
+    fn init_mtu() {
+        let mut config = VirtioNetConfig::new_zeroed();
+        config.mtu = 9000;
+
+        let caps = init_caps(&NetworkFeatures::empty(), &config);
+        assert_eq!(caps.max_transmission_unit, 1514);
+        assert_eq!(caps.checksum.tcp, Checksum::Both);
+
+        let caps = init_caps(
+            &(NetworkFeatures::VIRTIO_NET_F_MTU | NetworkFeatures::VIRTIO_NET_F_CSUM),
+            &config,
+        );
+        assert_eq!(caps.max_transmission_unit, 9000);
+        assert_eq!(caps.checksum.tcp, Checksum::Rx);
+    }
+}
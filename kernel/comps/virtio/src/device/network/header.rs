@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_network::checksum::PartialChecksum;
 use bitflags::bitflags;
 use int_to_c_enum::TryFromInt;
 
@@ -7,16 +8,28 @@ use int_to_c_enum::TryFromInt;
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, Pod)]
 pub(super) struct VirtioNetHdr {
-    flags: Flags,
-    gso_type: u8,
-    hdr_len: u16,
-    gso_size: u16,
-    csum_start: u16,
-    csum_offset: u16,
-    num_buffers: u16, // Only if PCI is modern or VIRTIO_NET_F_MRG_RXBUF negotiated
-                      // hash_value: u32,        // Only if VIRTIO_NET_F_HASH_REPORT negotiated
-                      // hash_report: u16,       // Only if VIRTIO_NET_F_HASH_REPORT negotiated
-                      // padding_reserved: u16,  // Only if VIRTIO_NET_F_HASH_REPORT negotiated
+    pub(super) flags: Flags,
+    pub(super) gso_type: u8,
+    pub(super) hdr_len: u16,
+    pub(super) gso_size: u16,
+    pub(super) csum_start: u16,
+    pub(super) csum_offset: u16,
+    pub(super) num_buffers: u16, // Only if PCI is modern or VIRTIO_NET_F_MRG_RXBUF negotiated
+                                 // hash_value: u32,        // Only if VIRTIO_NET_F_HASH_REPORT negotiated
+                                 // hash_report: u16,       // Only if VIRTIO_NET_F_HASH_REPORT negotiated
+                                 // padding_reserved: u16,  // Only if VIRTIO_NET_F_HASH_REPORT negotiated
+}
+
+impl VirtioNetHdr {
+    /// Returns the partially computed checksum of the received packet, if any.
+    pub(super) fn partial_checksum(&self) -> Option<PartialChecksum> {
+        self.flags
+            .contains(Flags::VIRTIO_NET_HDR_F_NEEDS_CSUM)
+            .then_some(PartialChecksum {
+                start: self.csum_start as usize,
+                offset: self.csum_offset as usize,
+            })
+    }
 }
 
 bitflags! {
@@ -30,7 +43,6 @@ bitflags! {
 }
 
 #[expect(non_camel_case_types)]
-#[expect(dead_code)]
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, TryFromInt)]
 pub(super) enum GsoType {
@@ -42,3 +54,47 @@ pub(super) enum GsoType {
     VIRTIO_NET_HDR_GSO_UDP_L4 = 5,
     VIRTIO_NET_HDR_GSO_ECN = 0x80,
 }
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+    use ostd_pod::Pod;
+
+    use super::*;
+
+    #[ktest]
+    fn header_layout() {
+        // The header includes `num_buffers`, but not the fields of the hash report.
+        assert_eq!(size_of::<VirtioNetHdr>(), 12);
+    }
+
+    #[ktest]
+    fn parse_header() {
+        #[rustfmt::skip]
+        let bytes = [
+            // flags, gso_type
+            0x01, 0x00,
+            // hdr_len, gso_size
+            0x00, 0x00, 0x00, 0x00,
+            // csum_start, csum_offset
+            0x22, 0x00, 0x10, 0x00,
+            // num_buffers
+            0x03, 0x00,
+        ];
+        let header = VirtioNetHdr::from_bytes(&bytes);
+        assert_eq!(header.num_buffers, 3);
+        assert_eq!(
+            header.partial_checksum(),
+            Some(PartialChecksum {
+                start: 0x22,
+                offset: 0x10,
+            })
+        );
+
+        let header = VirtioNetHdr {
+            flags: Flags::VIRTIO_NET_HDR_F_DATA_VALID,
+            ..header
+        };
+        assert_eq!(header.partial_checksum(), None);
+    }
+}
@@ -2,8 +2,11 @@
 
 mod buffer;
 mod config;
+mod control;
 pub mod device;
 mod header;
+mod offload;
+mod queue_pair;
 
 pub const DEVICE_NAME: &str = "Virtio-Net";
 
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Checksum and segmentation offloading for outgoing packets.
+
+use aster_bigtcp::wire::{IpProtocol, Ipv4Packet, Ipv6Packet, TcpPacket};
+use aster_network::checksum::{ones_complement_add, ones_complement_sum};
+
+use super::header::{Flags, GsoType, VirtioNetHdr};
+
+/// The work that the device does for an outgoing packet.
+pub(super) struct TxOffload {
+    pub(super) header: VirtioNetHdr,
+    /// The offset of the checksum field in the packet and the value it should hold.
+    ///
+    /// If the device computes the checksum, the checksum field must contain the checksum of the
+    /// pseudo-header.
+    pub(super) csum_field: Option<(usize, u16)>,
+}
+
+impl TxOffload {
+    /// Decides the work that the device can do for the outgoing Ethernet frame.
+    ///
+    /// If `tso_ip_mtu` is not `None`, TCP packets larger than the MTU will be split by the device
+    /// into segments that fit in the MTU.
+    pub(super) fn new(frame: &[u8], tso_ip_mtu: Option<usize>) -> Self {
+        Self::new_checksummed(frame, tso_ip_mtu).unwrap_or(Self {
+            header: VirtioNetHdr::default(),
+            csum_field: None,
+        })
+    }
+
+    fn new_checksummed(frame: &[u8], tso_ip_mtu: Option<usize>) -> Option<Self> {
+        let ethertype = u16::from_be_bytes(frame.get(12..ETHERNET_HEADER_LEN)?.try_into().unwrap());
+        let ip_packet = &frame[ETHERNET_HEADER_LEN..];
+
+        let (protocol, ip_header_len, l4_len, addr_sum, gso_type) = match ethertype {
+            ETHERTYPE_IPV4 => {
+                let packet = Ipv4Packet::new_checked(ip_packet).ok()?;
+                // The transport-layer header is not available in every fragment.
+                if packet.more_frags() || packet.frag_offset() != 0 {
+                    return None;
+                }
+                let header_len = packet.header_len() as usize;
+                (
+                    packet.next_header(),
+                    header_len,
+                    packet.total_len() as usize - header_len,
+                    ones_complement_add(&[
+                        ones_complement_sum(&packet.src_addr().octets()),
+                        ones_complement_sum(&packet.dst_addr().octets()),
+                    ]),
+                    GsoType::VIRTIO_NET_HDR_GSO_TCPV4,
+                )
+            }
+            ETHERTYPE_IPV6 => {
+                let packet = Ipv6Packet::new_checked(ip_packet).ok()?;
+                (
+                    packet.next_header(),
+                    IPV6_HEADER_LEN,
+                    packet.payload_len() as usize,
+                    ones_complement_add(&[
+                        ones_complement_sum(&packet.src_addr().octets()),
+                        ones_complement_sum(&packet.dst_addr().octets()),
+                    ]),
+                    GsoType::VIRTIO_NET_HDR_GSO_TCPV6,
+                )
+            }
+            _ => return None,
+        };
+
+        let csum_offset = match protocol {
+            IpProtocol::Tcp => TCP_CHECKSUM_OFFSET,
+            IpProtocol::Udp => UDP_CHECKSUM_OFFSET,
+            _ => return None,
+        };
+        let csum_start = ETHERNET_HEADER_LEN + ip_header_len;
+        if csum_start + csum_offset + 2 > frame.len() {
+            return None;
+        }
+
+        let pseudo_header_sum = ones_complement_add(&[
+            addr_sum,
+            u8::from(protocol) as u16,
+            u16::try_from(l4_len).ok()?,
+        ]);
+
+        let mut header = VirtioNetHdr {
+            flags: Flags::VIRTIO_NET_HDR_F_NEEDS_CSUM,
+            csum_start: csum_start as u16,
+            csum_offset: csum_offset as u16,
+            ..VirtioNetHdr::default()
+        };
+
+        if let Some(ip_mtu) = tso_ip_mtu
+            && protocol == IpProtocol::Tcp
+            && frame.len() > ETHERNET_HEADER_LEN + ip_mtu
+        {
+            let tcp_header_len = TcpPacket::new_checked(&frame[csum_start..])
+                .ok()?
+                .header_len() as usize;
+            let hdr_len = ip_header_len + tcp_header_len;
+            header.gso_type = gso_type as u8;
+            header.hdr_len = (ETHERNET_HEADER_LEN + hdr_len) as u16;
+            header.gso_size = ip_mtu.checked_sub(hdr_len)? as u16;
+        }
+
+        Some(Self {
+            header,
+            csum_field: Some((csum_start + csum_offset, pseudo_header_sum)),
+        })
+    }
+}
+
+const ETHERNET_HEADER_LEN: usize = 14;
+const IPV6_HEADER_LEN: usize = 40;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+
+const TCP_CHECKSUM_OFFSET: usize = 16;
+const UDP_CHECKSUM_OFFSET: usize = 6;
+
+#[cfg(ktest)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use ostd::prelude::*;
+
+    use super::*;
+
+    const SRC_V4: [u8; 4] = [10, 0, 0, 1];
+    const DST_V4: [u8; 4] = [10, 0, 0, 2];
+    const SRC_V6: [u8; 16] = [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+    const DST_V6: [u8; 16] = [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+
+    const TCP: u8 = 6;
+    const UDP: u8 = 17;
+
+    /// Builds the transport-layer header followed by `payload_len` bytes of payload.
+    fn l4_segment(protocol: u8, payload_len: usize) -> Vec<u8> {
+        let mut segment = match protocol {
+            // The data offset is five 32-bit words.
+            TCP => vec![
+                0, 80, 0, 80, 0, 0, 0, 0, 0, 0, 0, 0, 0x50, 0x10, 0xff, 0xff, 0, 0, 0, 0,
+            ],
+            UDP => {
+                let len = (8 + payload_len) as u16;
+                let [hi, lo] = len.to_be_bytes();
+                vec![0, 53, 0, 53, hi, lo, 0, 0]
+            }
+            _ => unreachable!(),
+        };
+        segment.resize(segment.len() + payload_len, 0xab);
+        segment
+    }
+
+    fn ipv4_frame(protocol: u8, frag: u16, payload_len: usize) -> Vec<u8> {
+        let segment = l4_segment(protocol, payload_len);
+        let total_len = (20 + segment.len()) as u16;
+
+        let mut frame = vec![0; 12];
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&total_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(&frag.to_be_bytes());
+        frame.extend_from_slice(&[64, protocol, 0, 0]);
+        frame.extend_from_slice(&SRC_V4);
+        frame.extend_from_slice(&DST_V4);
+        frame.extend_from_slice(&segment);
+        frame
+    }
+
+    fn ipv6_frame(protocol: u8, payload_len: usize) -> Vec<u8> {
+        let segment = l4_segment(protocol, payload_len);
+
+        let mut frame = vec![0; 12];
+        frame.extend_from_slice(&ETHERTYPE_IPV6.to_be_bytes());
+        frame.extend_from_slice(&[0x60, 0, 0, 0]);
+        frame.extend_from_slice(&(segment.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&[protocol, 64]);
+        frame.extend_from_slice(&SRC_V6);
+        frame.extend_from_slice(&DST_V6);
+        frame.extend_from_slice(&segment);
+        frame
+    }
+
+    /// Computes the checksum of the pseudo-header from scratch.
+    fn pseudo_header_sum(src: &[u8], dst: &[u8], protocol: u8, l4_len: usize) -> u16 {
+        let mut pseudo_header = Vec::new();
+        pseudo_header.extend_from_slice(src);
+        pseudo_header.extend_from_slice(dst);
+        pseudo_header.extend_from_slice(&[0, protocol]);
+        pseudo_header.extend_from_slice(&(l4_len as u16).to_be_bytes());
+        ones_complement_sum(&pseudo_header)
+    }
+
+    #[ktest]
+    fn checksum_ipv4_tcp() {
+        let frame = ipv4_frame(TCP, 0x4000, 100);
+        let offload = TxOffload::new(&frame, None);
+
+        let header = offload.header;
+        assert_eq!(header.flags, Flags::VIRTIO_NET_HDR_F_NEEDS_CSUM);
+        assert_eq!(header.csum_start, 34);
+        assert_eq!(header.csum_offset, 16);
+        assert_eq!(header.gso_type, GsoType::VIRTIO_NET_HDR_GSO_NONE as u8);
+        assert_eq!(
+            offload.csum_field,
+            Some((50, pseudo_header_sum(&SRC_V4, &DST_V4, TCP, 120)))
+        );
+    }
+
+    #[ktest]
+    fn checksum_ipv6_udp() {
+        let frame = ipv6_frame(UDP, 100);
+        // UDP packets are never segmented.
+        let offload = TxOffload::new(&frame, Some(64));
+
+        let header = offload.header;
+        assert_eq!(header.flags, Flags::VIRTIO_NET_HDR_F_NEEDS_CSUM);
+        assert_eq!(header.csum_start, 54);
+        assert_eq!(header.csum_offset, 6);
+        assert_eq!(header.gso_type, GsoType::VIRTIO_NET_HDR_GSO_NONE as u8);
+        assert_eq!(
+            offload.csum_field,
+            Some((60, pseudo_header_sum(&SRC_V6, &DST_V6, UDP, 108)))
+        );
+    }
+
+    #[ktest]
+    fn segment_tcp() {
+        let frame = ipv4_frame(TCP, 0x4000, 3000);
+        let header = TxOffload::new(&frame, Some(1500)).header;
+        assert_eq!(header.gso_type, GsoType::VIRTIO_NET_HDR_GSO_TCPV4 as u8);
+        assert_eq!(header.hdr_len, 14 + 20 + 20);
+        assert_eq!(header.gso_size, 1500 - 20 - 20);
+
+        let frame = ipv6_frame(TCP, 3000);
+        let header = TxOffload::new(&frame, Some(1500)).header;
+        assert_eq!(header.gso_type, GsoType::VIRTIO_NET_HDR_GSO_TCPV6 as u8);
+        assert_eq!(header.hdr_len, 14 + 40 + 20);
+        assert_eq!(header.gso_size, 1500 - 40 - 20);
+
+        // Packets that fit in the MTU are not segmented.
+        let frame = ipv4_frame(TCP, 0x4000, 1000);
+        let header = TxOffload::new(&frame, Some(1500)).header;
+        assert_eq!(header.gso_type, GsoType::VIRTIO_NET_HDR_GSO_NONE as u8);
+    }
+
+    #[ktest]
+    fn no_offload() {
+        // The transport-layer header is not in every fragment.
+        let frame = ipv4_frame(UDP, 0x2000, 100);
+        let offload = TxOffload::new(&frame, None);
+        assert!(offload.header.flags.is_empty());
+        assert_eq!(offload.csum_field, None);
+
+        // The frame is not an IP packet.
+        let mut frame = ipv4_frame(TCP, 0, 100);
+        frame[12..14].copy_from_slice(&0x0806u16.to_be_bytes());
+        let offload = TxOffload::new(&frame, None);
+        assert!(offload.header.flags.is_empty());
+        assert_eq!(offload.csum_field, None);
+
+        // The frame is truncated.
+        let offload = TxOffload::new(&[0; 10], None);
+        assert_eq!(offload.csum_field, None);
+    }
+}
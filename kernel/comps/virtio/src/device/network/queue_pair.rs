@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::vec::Vec;
+
+use aster_network::{NetError, RxBuffer, TxBuffer};
+use aster_util::slot_vec::SlotVec;
+use ostd::debug;
+
+use super::{buffer::RX_BUFFER_POOL, header::VirtioNetHdr};
+use crate::{
+    device::VirtioDeviceError,
+    queue::{self, VirtQueue},
+    transport::VirtioTransport,
+};
+
+/// A receive queue and a send queue.
+///
+/// If `VIRTIO_NET_F_MQ` is negotiated, the device can have multiple queue pairs. Otherwise, it
+/// has only one queue pair.
+pub(super) struct QueuePair {
+    recv_queue: VirtQueue,
+    send_queue: VirtQueue,
+    rx_buffers: SlotVec<RxBuffer>,
+    /// The buffers of the packets being sent, indexed by the tokens.
+    tx_buffers: Vec<Vec<TxBuffer>>,
+    /// The buffer that replaces the next buffer popped from the receive queue.
+    pub(super) new_rx_buffer: Option<RxBuffer>,
+    poll_stat: PollStatistics,
+}
+
+/// Structure to track the number of packets sent and received during a single polling process.
+struct PollStatistics {
+    sent_packet: usize,
+    received_packet: usize,
+}
+
+impl PollStatistics {
+    const fn new() -> Self {
+        Self {
+            sent_packet: 0,
+            received_packet: 0,
+        }
+    }
+}
+
+impl QueuePair {
+    /// Creates the `index`-th queue pair and fills its receive queue with buffers.
+    pub(super) fn new(
+        index: u16,
+        transport: &mut dyn VirtioTransport,
+    ) -> Result<Self, VirtioDeviceError> {
+        let mut recv_queue = VirtQueue::new(Self::recv_queue_index(index), QUEUE_SIZE, transport)?;
+
+        let mut send_queue = VirtQueue::new(Self::send_queue_index(index), QUEUE_SIZE, transport)?;
+        send_queue.disable_callback();
+
+        let tx_buffers = (0..QUEUE_SIZE).map(|_| Vec::new()).collect();
+
+        let mut rx_buffers = SlotVec::new();
+        for i in 0..QUEUE_SIZE {
+            let rx_pool = RX_BUFFER_POOL.get().unwrap();
+            let rx_buffer = RxBuffer::new(size_of::<VirtioNetHdr>(), rx_pool)
+                .map_err(VirtioDeviceError::ResourceAlloc)?;
+            let token = recv_queue.add_output_bufs(&[&rx_buffer]).unwrap();
+            assert_eq!(i, token);
+            assert_eq!(rx_buffers.put(rx_buffer) as u16, i);
+        }
+
+        if recv_queue.should_notify() {
+            debug!("notify receive queue {}", index);
+            recv_queue.notify();
+        }
+
+        Ok(Self {
+            recv_queue,
+            send_queue,
+            rx_buffers,
+            tx_buffers,
+            new_rx_buffer: None,
+            poll_stat: PollStatistics::new(),
+        })
+    }
+
+    pub(super) const fn recv_queue_index(index: u16) -> u16 {
+        index * 2
+    }
+
+    pub(super) const fn send_queue_index(index: u16) -> u16 {
+        index * 2 + 1
+    }
+
+    pub(super) fn can_receive(&self) -> bool {
+        self.recv_queue.can_pop()
+    }
+
+    /// Returns whether a packet that takes `num_bufs` buffers can be sent.
+    pub(super) fn can_send(&self, num_bufs: usize) -> bool {
+        self.send_queue.available_desc() >= num_bufs
+    }
+
+    /// Pops a used `RxBuffer` and returns it with the number of bytes written by the device.
+    pub(super) fn pop_rx_buffer(
+        &mut self,
+        min_bytes: usize,
+    ) -> Result<(RxBuffer, usize), NetError> {
+        let (token, len) = self
+            .recv_queue
+            .pop_used_with_min_bytes(min_bytes)
+            .map_err(|_| NetError::NotReady)?;
+        debug!("receive packet: token = {}, len = {}", token, len);
+
+        let rx_buffer = self.rx_buffers.remove(token as usize).unwrap();
+        Ok((rx_buffer, len as usize))
+    }
+
+    /// Adds a `RxBuffer` to the receive queue.
+    pub(super) fn add_rx_buffer(&mut self, rx_buffer: RxBuffer) -> Result<(), queue::AddBufsError> {
+        let token = self.recv_queue.add_output_bufs(&[&rx_buffer])?;
+        assert!(self.rx_buffers.put_at(token as usize, rx_buffer).is_none());
+
+        self.poll_stat.received_packet += 1;
+
+        if self.poll_stat.received_packet == QUEUE_SIZE as _ {
+            // If we know there are no free buffers for receiving,
+            // we will notify the receive queue as soon as possible.
+            self.notify_receive_queue();
+        }
+
+        Ok(())
+    }
+
+    /// Sends a packet held by the `TxBuffer`s.
+    pub(super) fn send(&mut self, tx_buffers: Vec<TxBuffer>) {
+        let tx_buffer_refs = tx_buffers.iter().collect::<Vec<_>>();
+        let token = self.send_queue.add_input_bufs(&tx_buffer_refs).unwrap();
+
+        self.poll_stat.sent_packet += 1;
+
+        if self.send_queue.available_desc() == 0 {
+            // If the send queue is full,
+            // we will notify the send queue as soon as possible.
+            self.notify_send_queue();
+        }
+
+        debug!(
+            "send packet, token = {}, bufs = {}",
+            token,
+            tx_buffers.len()
+        );
+
+        debug_assert!(self.tx_buffers[token as usize].is_empty());
+        self.tx_buffers[token as usize] = tx_buffers;
+
+        self.free_processed_tx_buffers();
+    }
+
+    pub(super) fn free_processed_tx_buffers(&mut self) {
+        while let Ok((token, _)) = self.send_queue.pop_used() {
+            self.tx_buffers[token as usize].clear();
+        }
+    }
+
+    /// Enables or disables the interrupt of the send queue.
+    ///
+    /// The interrupt is needed only to free the send buffers when the send queue is full.
+    pub(super) fn update_send_callback(&mut self, num_bufs: usize) {
+        // If the send queue is not full, we can free the send buffers during the next sending process.
+        // Therefore, there is no need to free the used buffers in the IRQ handlers.
+        // This allows us to temporarily disable the send queue interrupt.
+        // Conversely, if the send queue is full, the send queue interrupt should remain enabled
+        // to free the send buffers as quickly as possible.
+        if !self.can_send(num_bufs) {
+            self.send_queue.enable_callback();
+        } else {
+            self.send_queue.disable_callback();
+        }
+    }
+
+    pub(super) fn notify_send_queue(&mut self) {
+        if self.poll_stat.sent_packet == 0 {
+            return;
+        }
+
+        debug!(
+            "notify send queue: sent {} packets",
+            self.poll_stat.sent_packet
+        );
+        if self.send_queue.should_notify() {
+            self.send_queue.notify();
+        }
+
+        self.poll_stat.sent_packet = 0;
+    }
+
+    pub(super) fn notify_receive_queue(&mut self) {
+        if self.poll_stat.received_packet == 0 {
+            return;
+        }
+
+        debug!(
+            "notify receive queue: received {} packets",
+            self.poll_stat.received_packet
+        );
+        if self.recv_queue.should_notify() {
+            self.recv_queue.notify();
+        }
+
+        self.poll_stat.received_packet = 0;
+    }
+}
+
+impl core::fmt::Debug for QueuePair {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QueuePair")
+            .field("recv_queue", &self.recv_queue)
+            .field("send_queue", &self.send_queue)
+            .finish()
+    }
+}
+
+const QUEUE_SIZE: u16 = 64;
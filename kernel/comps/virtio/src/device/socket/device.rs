@@ -30,6 +30,7 @@ use crate::{
 /// Ethernet or IP protocols.
 pub struct SocketDevice {
     config_manager: ConfigManager<VirtioVsockConfig>,
+    features: VsockFeatures,
     guest_cid: AtomicU64,
     tx_queue: SpinLock<TxQueue, BottomHalfDisabled>,
     rx_queue: SpinLock<RxQueue, BottomHalfDisabled>,
@@ -47,6 +48,9 @@ impl SocketDevice {
 
     /// Initializes a virtio-vsock device from `transport` and registers it globally.
     pub(crate) fn init(mut transport: Box<dyn VirtioTransport>) -> Result<(), VirtioDeviceError> {
+        let features = VsockFeatures::from_bits_truncate(Self::negotiate_features(
+            transport.read_device_features(),
+        ));
         let config_manager = VirtioVsockConfig::new_manager(transport.as_ref());
         let guest_cid = VirtioVsockConfig::read_guest_cid(&config_manager);
 
@@ -56,6 +60,7 @@ impl SocketDevice {
 
         let device = Arc::new(Self {
             config_manager,
+            features,
             guest_cid: AtomicU64::new(guest_cid),
             tx_queue: SpinLock::new(tx_queue),
             rx_queue: SpinLock::new(rx_queue),
@@ -111,6 +116,12 @@ impl SocketDevice {
         self.guest_cid.load(Ordering::Relaxed)
     }
 
+    /// Returns whether the device supports `VirtioVsockType::SeqPacket` connections.
+    pub fn supports_seqpacket(&self) -> bool {
+        self.features
+            .contains(VsockFeatures::VIRTIO_VSOCK_F_SEQPACKET)
+    }
+
     /// Registers the callback invoked after a packet is received.
     ///
     /// The function may be called only once; subsequent calls take no effect.
@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use alloc::vec::Vec;
+
 pub use smoltcp::phy::{
     Checksum, ChecksumCapabilities, Device, DeviceCapabilities, Loopback, Medium, RxToken, TxToken,
 };
+use smoltcp::wire::EthernetAddress;
 
 /// A trait that allows to obtain a mutable reference of [`Device`].
 ///
@@ -17,10 +20,56 @@ pub trait WithDevice: Send + Sync {
     fn with<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut Self::Device) -> R;
+
+    /// Returns the number of receive queues of the device.
+    ///
+    /// Multi-queue devices can protect each receive queue with its own lock. The interface polls
+    /// the queues one by one via [`Self::with_queue`].
+    fn num_queues(&self) -> usize {
+        1
+    }
+
+    /// Calls the closure with a mutable reference of [`Device`] that receives packets from the
+    /// `queue`-th receive queue.
+    fn with_queue<F, R>(&self, _queue: usize, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Device) -> R,
+    {
+        self.with(f)
+    }
 }
 
 /// A trait for notifying device drivers about the polling process.
 pub trait NotifyDevice {
     /// Notifies the device driver that polling has ended.
     fn notify_poll_end(&mut self);
+
+    /// Notifies the device driver that the receive filter has changed.
+    ///
+    /// Devices that cannot filter incoming frames by themselves can simply ignore the
+    /// notification, since the interface will drop unwanted frames anyway.
+    fn update_rx_filter(&mut self, _filter: &RxFilter) {}
+
+    /// Returns the maximum size of IP packets that the device can split into TCP segments.
+    ///
+    /// If the device supports TCP segmentation offload (TSO), the interface may send TCP packets
+    /// larger than the MTU (i.e., GSO super-segments). The device should split their payload
+    /// into segments that are as large as possible while fitting in the MTU, and compute the TCP
+    /// checksums of the segments.
+    ///
+    /// Zero means that the device does not support TSO.
+    fn gso_max_size(&self) -> usize {
+        0
+    }
+}
+
+/// The receive filter of an Ethernet device.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RxFilter {
+    /// Whether all frames should be received.
+    pub promiscuous: bool,
+    /// Whether all multicast frames should be received.
+    pub all_multicast: bool,
+    /// The multicast addresses whose frames should be received.
+    pub multicast_addrs: Vec<EthernetAddress>,
 }
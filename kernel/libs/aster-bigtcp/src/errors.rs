@@ -140,3 +140,44 @@ pub mod udp {
         }
     }
 }
+
+pub mod raw {
+    /// An error returned by [`RawSocket::send`].
+    ///
+    /// [`RawSocket::send`]: crate::socket::RawSocket::send
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum SendError {
+        /// The packet is not addressed to the same IP version as the socket.
+        Unaddressable,
+        /// The packet is not a valid IP header or ICMP echo request.
+        Malformed,
+        BufferFull,
+        /// The packet is too large.
+        TooLarge,
+    }
+
+    /// An error returned by [`RawSocket::recv`].
+    ///
+    /// [`RawSocket::recv`]: crate::socket::RawSocket::recv
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum RecvError {
+        /// The receive queue is empty.
+        Exhausted,
+    }
+}
+
+pub mod packet {
+    /// An error returned by [`PacketSocket::send`].
+    ///
+    /// [`PacketSocket::send`]: crate::socket::PacketSocket::send
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum SendError {
+        /// The link-layer destination address is missing.
+        Unaddressable,
+        /// The frame is shorter than the link-layer header.
+        Malformed,
+        BufferFull,
+        /// The frame is too large.
+        TooLarge,
+    }
+}
@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::{iface::ScheduleNextPoll, socket::SocketEventObserver};
+use crate::{
+    iface::{PacketFilter, Router, ScheduleNextPoll},
+    socket::{FrameObserver, ReuseportSelector, SocketEventObserver},
+};
 
 /// Extension to be implemented by users of this crate.
 ///
@@ -19,4 +22,19 @@ pub trait Ext {
 
     /// The type for UDP sockets to observe events.
     type UdpEventObserver: SocketEventObserver;
+
+    /// The type for raw IP sockets and ICMP ping sockets to observe events.
+    type RawEventObserver: SocketEventObserver;
+
+    /// The type for packet sockets to observe link-layer frames.
+    type FrameObserver: FrameObserver;
+
+    /// The type for reuseport groups to select sockets to receive incoming packets.
+    type ReuseportSelector: ReuseportSelector;
+
+    /// The type for ifaces to route and forward packets.
+    type Router: Router;
+
+    /// The type for ifaces to filter and mangle packets.
+    type PacketFilter: PacketFilter;
 }
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use alloc::{
-    collections::btree_map::{BTreeMap, Entry},
+    collections::{
+        btree_map::{BTreeMap, Entry},
+        vec_deque::VecDeque,
+    },
     ffi::CString,
     sync::Arc,
     vec::Vec,
@@ -9,7 +12,7 @@ use alloc::{
 use core::{
     ffi::CStr,
     ops::Deref,
-    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
 };
 
 use aster_softirq::BottomHalfDisabled;
@@ -19,20 +22,28 @@ use ostd::sync::{SpinLock, SpinLockGuard};
 use smoltcp::{
     iface::{Context, packet::Packet},
     phy::Device,
-    wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv4Packet, Ipv6Address, Ipv6Packet},
+    wire::{
+        EthernetAddress, HardwareAddress, IpAddress, IpEndpoint, IpProtocol, IpRepr, Ipv4Address,
+        Ipv4Packet, Ipv6Address, Ipv6Cidr, Ipv6Packet,
+    },
 };
 
 use super::{
     Iface,
+    filter::PacketFilter,
+    multicast::MulticastGroups,
     poll::{FnHelper, PollContext, SocketTableAction},
     poll_iface::PollableIface,
     port::BindPortConfig,
+    route::Router,
+    stats::{IfaceCounter, IfaceCounters},
+    tap::{LinkLayer, TapDevice},
     time::get_network_timestamp,
 };
 use crate::{
     errors::BindError,
     ext::Ext,
-    socket::{TcpListenerBg, UdpSocketBg},
+    socket::{PacketSocketBg, RawSocketBg, SocketInfo, TcpListenerBg, UdpSocketBg},
     socket_table::SocketTable,
 };
 
@@ -41,19 +52,64 @@ pub struct IfaceCommon<E: Ext> {
     name: CString,
     type_: InterfaceType,
     flags: InterfaceFlags,
+    link_layer: LinkLayer,
+    promiscuity: AtomicUsize,
+    allmulti: AtomicUsize,
+    /// Whether the receive filter of the device needs to be updated.
+    rx_filter_changed: AtomicBool,
+    /// The maximum size of GSO super-segments, or zero if the device does not support TSO.
+    gso_max_size: usize,
 
     interface: SpinLock<PollableIface<E>, BottomHalfDisabled>,
-    used_ports: SpinLock<PortTable, BottomHalfDisabled>,
+    used_ports: SpinLock<PortTable<E>, BottomHalfDisabled>,
     sockets: SpinLock<SocketTable<E>, BottomHalfDisabled>,
     sched_poll: E::ScheduleNextPoll,
+    router: SpinLock<Option<Arc<E::Router>>, BottomHalfDisabled>,
+    filter: SpinLock<Option<Arc<E::PacketFilter>>, BottomHalfDisabled>,
+    /// The packets forwarded from other ifaces that have yet to be sent out.
+    forwarded: SpinLock<VecDeque<ForwardedPacket>, BottomHalfDisabled>,
+    multicast: SpinLock<MulticastGroups, BottomHalfDisabled>,
+    counters: IfaceCounters,
 }
 
+/// A packet forwarded from another iface.
+pub(super) struct ForwardedPacket {
+    pub(super) ip_repr: IpRepr,
+    pub(super) ip_payload: Vec<u8>,
+    /// The index of the iface that received the packet.
+    pub(super) in_iface: u32,
+}
+
+/// The maximum number of forwarded packets that can be queued in an iface.
+///
+/// This is similar to `net.core.netdev_max_backlog` in Linux.
+const MAX_FORWARDED_PACKETS: usize = 1000;
+
 /// An enum representing either an IPv4 or IPv6 packet.
 pub(super) enum IpPacket<'a> {
     Ipv4(Ipv4Packet<&'a [u8]>),
     Ipv6(Ipv6Packet<&'a [u8]>),
 }
 
+impl<'a> IpPacket<'a> {
+    /// Parses an IP packet whose version is determined by its first byte.
+    pub(super) fn new_checked(data: &'a [u8]) -> Option<Self> {
+        match data.first()? >> 4 {
+            4 => Some(Self::Ipv4(Ipv4Packet::new_checked(data).ok()?)),
+            6 => Some(Self::Ipv6(Ipv6Packet::new_checked(data).ok()?)),
+            _ => None,
+        }
+    }
+
+    /// Copies the IP packet, excluding any trailing link-layer padding.
+    pub(super) fn to_vec(&self) -> Vec<u8> {
+        match self {
+            Self::Ipv4(pkt) => pkt.as_ref()[..pkt.total_len() as usize].to_vec(),
+            Self::Ipv6(pkt) => pkt.as_ref()[..pkt.total_len()].to_vec(),
+        }
+    }
+}
+
 /// A normalized IP address for binding purposes.
 ///
 /// IPv4 addresses are normalized to IPv4-mapped IPv6 addresses. IPv6 addresses
@@ -90,15 +146,33 @@ impl<E: Ext> IfaceCommon<E> {
     ) -> Self {
         let index = INTERFACE_INDEX_ALLOCATOR.fetch_add(1, Ordering::Relaxed);
 
+        let link_layer = match interface.hardware_addr() {
+            HardwareAddress::Ethernet(ether_addr) => LinkLayer::Ethernet(ether_addr),
+            _ if type_ == InterfaceType::LOOPBACK => LinkLayer::Loopback,
+            _ => LinkLayer::None,
+        };
+
         Self {
             index,
             name,
             type_,
             flags,
+            link_layer,
+            promiscuity: AtomicUsize::new(0),
+            allmulti: AtomicUsize::new(0),
+            // The device may start with a receive filter that does not match the iface, so it
+            // is updated in the first poll.
+            rx_filter_changed: AtomicBool::new(true),
+            gso_max_size: 0,
             interface: SpinLock::new(PollableIface::new(interface)),
             used_ports: SpinLock::new(PortTable::new()),
             sockets: SpinLock::new(SocketTable::new()),
             sched_poll,
+            router: SpinLock::new(None),
+            filter: SpinLock::new(None),
+            forwarded: SpinLock::new(VecDeque::new()),
+            multicast: SpinLock::new(MulticastGroups::new()),
+            counters: IfaceCounters::new(),
         }
     }
 
@@ -115,7 +189,96 @@ impl<E: Ext> IfaceCommon<E> {
     }
 
     pub(super) fn flags(&self) -> InterfaceFlags {
-        self.flags
+        let mut flags = self.flags;
+        if self.is_promiscuous() {
+            flags |= InterfaceFlags::PROMISC;
+        }
+        if self.allmulti.load(Ordering::Relaxed) > 0 {
+            flags |= InterfaceFlags::ALLMULTI;
+        }
+        flags
+    }
+
+    /// Sets the maximum size of GSO super-segments.
+    ///
+    /// See [`NotifyDevice::gso_max_size`] for details.
+    ///
+    /// [`NotifyDevice::gso_max_size`]: crate::device::NotifyDevice::gso_max_size
+    pub(super) fn set_gso_max_size(&mut self, gso_max_size: usize) {
+        self.gso_max_size = gso_max_size;
+    }
+
+    /// Returns the maximum size of GSO super-segments, or zero if they cannot be sent.
+    pub(crate) fn gso_max_size(&self) -> usize {
+        self.gso_max_size
+    }
+
+    pub(crate) fn counters(&self) -> &IfaceCounters {
+        &self.counters
+    }
+
+    pub(crate) fn link_layer(&self) -> LinkLayer {
+        self.link_layer
+    }
+
+    pub(super) fn hardware_addr(&self) -> Option<EthernetAddress> {
+        match self.link_layer {
+            LinkLayer::Ethernet(ether_addr) => Some(ether_addr),
+            LinkLayer::Loopback => Some(EthernetAddress([0; 6])),
+            LinkLayer::None => None,
+        }
+    }
+
+    pub(super) fn is_promiscuous(&self) -> bool {
+        self.promiscuity.load(Ordering::Relaxed) > 0
+    }
+
+    pub(super) fn set_promiscuity(&self, inc: isize) {
+        Self::adjust_count(&self.promiscuity, inc);
+        self.rx_filter_changed.store(true, Ordering::Relaxed);
+    }
+
+    pub(super) fn set_allmulti(&self, inc: isize) {
+        Self::adjust_count(&self.allmulti, inc);
+        self.rx_filter_changed.store(true, Ordering::Relaxed);
+    }
+
+    /// Marks the receive filter of the device as outdated.
+    pub(super) fn set_rx_filter_changed(&self) {
+        self.rx_filter_changed.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the receive filter of the device needs to be updated, and resets the
+    /// state so that the next call returns `false` unless the filter changes again.
+    pub(super) fn take_rx_filter_changed(&self) -> bool {
+        self.rx_filter_changed.swap(false, Ordering::Relaxed)
+    }
+
+    pub(super) fn join_multicast_group(&self, group: IpAddress) -> bool {
+        let is_first = self.multicast.lock().join(group);
+        if is_first {
+            self.set_rx_filter_changed();
+        }
+        is_first
+    }
+
+    pub(super) fn leave_multicast_group(&self, group: IpAddress) -> bool {
+        let is_last = self.multicast.lock().leave(group);
+        if is_last {
+            self.set_rx_filter_changed();
+        }
+        is_last
+    }
+
+    pub(super) fn multicast_groups(&self) -> Vec<IpAddress> {
+        self.multicast.lock().iter().copied().collect()
+    }
+
+    fn adjust_count(count: &AtomicUsize, inc: isize) {
+        let res = count.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+            count.checked_add_signed(inc)
+        });
+        debug_assert!(res.is_ok());
     }
 
     pub(super) fn ipv4_addr(&self) -> Option<Ipv4Address> {
@@ -126,6 +289,10 @@ impl<E: Ext> IfaceCommon<E> {
         self.interface.lock().ipv6_addr()
     }
 
+    pub(super) fn ipv6_addrs(&self) -> Vec<Ipv6Cidr> {
+        self.interface.lock().ipv6_addrs()
+    }
+
     pub(super) fn prefix_len(&self) -> Option<u8> {
         self.interface.lock().prefix_len()
     }
@@ -133,6 +300,37 @@ impl<E: Ext> IfaceCommon<E> {
     pub(super) fn sched_poll(&self) -> &E::ScheduleNextPoll {
         &self.sched_poll
     }
+
+    pub(super) fn router(&self) -> Option<Arc<E::Router>> {
+        self.router.lock().clone()
+    }
+
+    pub(super) fn set_router(&self, router: Arc<E::Router>) {
+        *self.router.lock() = Some(router);
+    }
+
+    pub(super) fn set_packet_filter(&self, filter: Arc<E::PacketFilter>) {
+        *self.filter.lock() = Some(filter);
+    }
+
+    pub(super) fn enqueue_forwarded(
+        &self,
+        ip_repr: IpRepr,
+        ip_payload: Vec<u8>,
+        in_iface: u32,
+    ) -> bool {
+        let mut forwarded = self.forwarded.lock();
+        if forwarded.len() >= MAX_FORWARDED_PACKETS {
+            self.counters.inc(IfaceCounter::TxDropped);
+            return false;
+        }
+        forwarded.push_back(ForwardedPacket {
+            ip_repr,
+            ip_payload,
+            in_iface,
+        });
+        true
+    }
 }
 
 /// An allocator that allocates a unique index for each interface.
@@ -153,6 +351,25 @@ impl<E: Ext> IfaceCommon<E> {
     }
 }
 
+impl<E: Ext> IfaceCommon<E> {
+    pub(super) fn visit_sockets<F>(&self, mut f: F)
+    where
+        F: FnMut(&SocketInfo<'_, E>),
+    {
+        let sockets = self.sockets();
+
+        for listener in sockets.listener_iter() {
+            f(&SocketInfo::Tcp(listener.info()));
+        }
+        for connection in sockets.connection_iter() {
+            f(&SocketInfo::Tcp(connection.info()));
+        }
+        for udp_socket in sockets.udp_socket_iter() {
+            f(&SocketInfo::Udp(udp_socket.info()));
+        }
+    }
+}
+
 const IP_LOCAL_PORT_START: u16 = 32768;
 const IP_LOCAL_PORT_END: u16 = 60999;
 
@@ -175,6 +392,29 @@ impl<E: Ext> IfaceCommon<E> {
             .map(BoundUdpPort)
     }
 
+    pub(super) fn bind_raw(
+        &self,
+        iface: Arc<dyn Iface<E>>,
+        addr: IpAddress,
+        protocol: IpProtocol,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        // Like Linux, the protocol number is used as the port number of raw sockets. Any number
+        // of raw sockets can share the same protocol.
+        let endpoint = IpEndpoint::new(addr, u8::from(protocol).into());
+        let config = BindPortConfig::new(endpoint, true);
+        self.bind(iface, config, PortProtocol::Raw)
+            .map(BoundRawPort)
+    }
+
+    pub(super) fn bind_icmp(
+        &self,
+        iface: Arc<dyn Iface<E>>,
+        config: BindPortConfig,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        self.bind(iface, config, PortProtocol::Icmp)
+            .map(BoundRawPort)
+    }
+
     fn bind(
         &self,
         iface: Arc<dyn Iface<E>>,
@@ -182,21 +422,20 @@ impl<E: Ext> IfaceCommon<E> {
         protocol: PortProtocol,
     ) -> Result<BoundPort<E>, BindError> {
         let addr = config.addr();
-        let (port, can_reuse) = self.used_ports.lock().bind(config, protocol)?;
+        let (port, can_reuse, reuse_port) = self.used_ports.lock().bind(config, protocol)?;
         Ok(BoundPort {
             iface,
             addr,
             port,
             protocol,
             can_reuse: AtomicBool::new(can_reuse),
+            reuse_port,
         })
     }
 
     /// Releases the port so that it can be used again.
-    fn release_port(&self, addr: IpAddress, port: u16, can_reuse: bool, protocol: PortProtocol) {
-        self.used_ports
-            .lock()
-            .release(addr, port, can_reuse, protocol);
+    fn release_port(&self, key: PortKey, can_reuse: bool, reuse_port: bool) {
+        self.used_ports.lock().release(key, can_reuse, reuse_port);
     }
 }
 
@@ -206,9 +445,19 @@ impl<E: Ext> IfaceCommon<E> {
         sockets.insert_udp_socket(socket);
     }
 
+    pub(crate) fn register_raw_socket(&self, socket: Arc<RawSocketBg<E>>) {
+        let mut sockets = self.sockets.lock();
+        sockets.insert_raw_socket(socket);
+    }
+
+    pub(crate) fn register_packet_socket(&self, socket: Arc<PacketSocketBg<E>>) {
+        let mut sockets = self.sockets.lock();
+        sockets.insert_packet_socket(socket);
+    }
+
     pub(crate) fn remove_tcp_listener(&self, socket: &Arc<TcpListenerBg<E>>) {
         let mut sockets = self.sockets.lock();
-        let removed = sockets.remove_listener(socket.listener_key());
+        let removed = sockets.remove_listener(socket);
         debug_assert!(removed.is_some());
     }
 
@@ -217,9 +466,26 @@ impl<E: Ext> IfaceCommon<E> {
         let removed = sockets.remove_udp_socket(socket);
         debug_assert!(removed.is_some());
     }
+
+    pub(crate) fn remove_raw_socket(&self, socket: &Arc<RawSocketBg<E>>) {
+        let mut sockets = self.sockets.lock();
+        let removed = sockets.remove_raw_socket(socket);
+        debug_assert!(removed.is_some());
+    }
+
+    pub(crate) fn remove_packet_socket(&self, socket: &Arc<PacketSocketBg<E>>) {
+        let mut sockets = self.sockets.lock();
+        let removed = sockets.remove_packet_socket(socket);
+        debug_assert!(removed.is_some());
+    }
 }
 
 impl<E: Ext> IfaceCommon<E> {
+    /// Wraps the device so that packet sockets can see the frames going through it.
+    pub(super) fn tap<'a, D: ?Sized>(&'a self, device: &'a mut D) -> TapDevice<'a, D, E> {
+        TapDevice::new(device, self)
+    }
+
     pub(super) fn poll<D, P, Q>(
         &self,
         device: &mut D,
@@ -242,9 +508,33 @@ impl<E: Ext> IfaceCommon<E> {
         let mut sockets = self.sockets.lock();
         let mut socket_actions = Vec::new();
 
-        let mut context = PollContext::new(interface.as_mut(), &sockets, &mut socket_actions);
-        context.poll_ingress(device, &mut process_phy, &mut dispatch_phy);
+        let router = self.router();
+        let mut forward = |ip_repr: &IpRepr, ip_payload: &[u8]| {
+            router
+                .as_ref()
+                .is_some_and(|router| router.forward(self.index, ip_repr, ip_payload))
+        };
+
+        let filter = self
+            .filter
+            .lock()
+            .clone()
+            .filter(|filter| filter.is_enabled());
+
+        let report_src_addrs = (interface.ipv4_addr(), interface.ipv6_link_local_addr());
+
+        let mut context = PollContext::new(
+            interface.as_mut(),
+            &sockets,
+            &mut socket_actions,
+            filter.as_deref().map(|filter| (filter, self.index)),
+            &self.multicast,
+            &self.counters,
+        );
+        context.poll_ingress(device, &mut process_phy, &mut dispatch_phy, &mut forward);
         context.poll_egress(device, &mut dispatch_phy);
+        context.poll_forwarded(device, &self.forwarded, &mut dispatch_phy);
+        context.poll_multicast_reports(device, report_src_addrs, &mut dispatch_phy);
 
         // Insert new connections and remove dead connections.
         for action in socket_actions.into_iter() {
@@ -274,6 +564,9 @@ pub struct BoundPort<E: Ext> {
     port: u16,
     protocol: PortProtocol,
     can_reuse: AtomicBool,
+    /// The user ID of the socket owner if the socket has joined the reuseport group (i.e.,
+    /// `SO_REUSEPORT`) of the port.
+    reuse_port: Option<u32>,
 }
 
 impl<E: Ext> BoundPort<E> {
@@ -307,25 +600,58 @@ impl<E: Ext> BoundPort<E> {
             return;
         }
 
-        let key = PortKey {
+        used_ports.set_can_reuse(self.key(), can_reuse);
+
+        self.can_reuse.store(can_reuse, Ordering::Relaxed);
+    }
+
+    /// Returns the user ID of the socket owner if port reuse (i.e., `SO_REUSEPORT`) is enabled.
+    pub fn reuse_port(&self) -> Option<u32> {
+        self.reuse_port
+    }
+
+    /// Sets the program that selects a socket in the reuseport group of the port.
+    ///
+    /// The program is shared by all sockets in the group. Like Linux, it is discarded when the
+    /// last socket in the group is closed.
+    ///
+    /// This method returns the old program. If port reuse is not enabled for the port, this
+    /// method does nothing and returns `None`.
+    pub fn set_reuseport_selector(
+        &self,
+        selector: Option<Arc<E::ReuseportSelector>>,
+    ) -> Option<Arc<E::ReuseportSelector>> {
+        self.reuse_port?;
+
+        let iface_common = self.iface.common();
+        let mut used_ports = iface_common.used_ports.lock();
+        used_ports.set_reuseport_selector(&self.key(), selector)
+    }
+
+    /// Returns the program that selects a socket in the reuseport group of the port.
+    pub(crate) fn reuseport_selector(&self) -> Option<Arc<E::ReuseportSelector>> {
+        self.reuse_port?;
+
+        let iface_common = self.iface.common();
+        let used_ports = iface_common.used_ports.lock();
+        used_ports.reuseport_selector(&self.key())
+    }
+
+    fn key(&self) -> PortKey {
+        PortKey {
             addr: NormalizedAddress::from(self.addr),
             port: self.port,
             protocol: self.protocol,
-        };
-        used_ports.set_can_reuse(key, can_reuse);
-
-        self.can_reuse.store(can_reuse, Ordering::Relaxed);
+        }
     }
 }
 
 impl<E: Ext> Drop for BoundPort<E> {
     fn drop(&mut self) {
-        self.iface.common().release_port(
-            self.addr,
-            self.port,
-            *self.can_reuse.get_mut(),
-            self.protocol,
-        );
+        let key = self.key();
+        self.iface
+            .common()
+            .release_port(key, *self.can_reuse.get_mut(), self.reuse_port.is_some());
     }
 }
 
@@ -333,6 +659,8 @@ impl<E: Ext> Drop for BoundPort<E> {
 pub struct BoundTcpPort<E: Ext>(BoundPort<E>);
 /// A UDP port bound to an iface.
 pub struct BoundUdpPort<E: Ext>(BoundPort<E>);
+/// A raw IP protocol or an ICMP echo identifier bound to an iface.
+pub struct BoundRawPort<E: Ext>(BoundPort<E>);
 
 impl<E: Ext> Deref for BoundTcpPort<E> {
     type Target = BoundPort<E>;
@@ -346,6 +674,12 @@ impl<E: Ext> Deref for BoundUdpPort<E> {
         &self.0
     }
 }
+impl<E: Ext> Deref for BoundRawPort<E> {
+    type Target = BoundPort<E>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 struct PortKey {
@@ -358,31 +692,54 @@ struct PortKey {
 enum PortProtocol {
     Tcp,
     Udp,
+    Raw,
+    Icmp,
 }
 
-struct PortState {
+struct PortState<E: Ext> {
     nsocket: usize,
     /// The number of sockets that have enabled address reuse on this port.
     nreuse: usize,
+    /// The number of sockets that have enabled port reuse (i.e., `SO_REUSEPORT`) on this port.
+    nreuseport: usize,
+    /// The user ID of the owner of the sockets that have enabled port reuse.
+    reuseport_uid: Option<u32>,
+    /// The program that selects a socket in the reuseport group.
+    reuseport_selector: Option<Arc<E::ReuseportSelector>>,
 }
 
-impl PortState {
-    pub(self) fn new(can_reuse: bool) -> Self {
+impl<E: Ext> PortState<E> {
+    pub(self) fn new(can_reuse: bool, reuse_port: Option<u32>) -> Self {
         let nreuse = if can_reuse { 1 } else { 0 };
-        Self { nsocket: 1, nreuse }
+        let nreuseport = if reuse_port.is_some() { 1 } else { 0 };
+        Self {
+            nsocket: 1,
+            nreuse,
+            nreuseport,
+            reuseport_uid: reuse_port,
+            reuseport_selector: None,
+        }
     }
 
     pub(self) fn can_reuse(&self) -> bool {
         self.nsocket == self.nreuse
     }
+
+    /// Returns whether a socket owned by the user can join the reuseport group.
+    ///
+    /// Like Linux, this is allowed only if all sockets on the port have enabled port reuse and
+    /// are owned by the same user.
+    pub(self) fn can_reuse_port(&self, uid: u32) -> bool {
+        self.nsocket == self.nreuseport && self.reuseport_uid == Some(uid)
+    }
 }
 
-struct PortTable {
-    used_ports: BTreeMap<PortKey, PortState>,
+struct PortTable<E: Ext> {
+    used_ports: BTreeMap<PortKey, PortState<E>>,
     next_ephemeral_port: u16,
 }
 
-impl PortTable {
+impl<E: Ext> PortTable<E> {
     fn new() -> Self {
         Self {
             used_ports: BTreeMap::new(),
@@ -390,12 +747,17 @@ impl PortTable {
         }
     }
 
+    /// Binds a port.
+    ///
+    /// On success, this method returns the port, whether address reuse is enabled, and the user
+    /// ID of the socket owner if the socket has joined the reuseport group of the port.
     fn bind(
         &mut self,
         config: BindPortConfig,
         protocol: PortProtocol,
-    ) -> Result<(u16, bool), BindError> {
+    ) -> Result<(u16, bool, Option<u32>), BindError> {
         let config_can_reuse = config.can_reuse();
+        let config_reuse_port = config.reuse_port();
         let addr = NormalizedAddress::from(config.addr());
 
         let port = if let Some(port) = config.port() {
@@ -413,29 +775,41 @@ impl PortTable {
             protocol,
         };
         let entry = self.used_ports.entry(key);
-        match entry {
+        let reuse_port = match entry {
             Entry::Occupied(mut occupied) => {
                 let port_state = occupied.get_mut();
+                // A socket whose port reuse is not allowed (e.g., because the sockets in the
+                // reuseport group are owned by another user) may still be bound to the port via
+                // address reuse, but it does not join the reuseport group.
+                let reuse_port = config_reuse_port.filter(|uid| port_state.can_reuse_port(*uid));
                 // FIXME: If the socket is not a backlog socket,
                 // we should check whether there is a listening socket on the port.
                 // If there is, the socket cannot be bound to that port.
-                let can_reuse = config.is_backlog() || (port_state.can_reuse() & config_can_reuse);
-                if can_reuse {
-                    port_state.nsocket += 1;
-                    if config_can_reuse {
-                        port_state.nreuse += 1;
-                    }
-                } else {
+                let can_reuse = config.is_backlog()
+                    || (port_state.can_reuse() & config_can_reuse)
+                    || reuse_port.is_some();
+                if !can_reuse {
                     return Err(BindError::InUse);
                 }
+
+                port_state.nsocket += 1;
+                if config_can_reuse {
+                    port_state.nreuse += 1;
+                }
+                if reuse_port.is_some() {
+                    port_state.nreuseport += 1;
+                    port_state.reuseport_uid = reuse_port;
+                }
+                reuse_port
             }
             Entry::Vacant(vacant) => {
-                let port_state = PortState::new(config_can_reuse);
+                let port_state = PortState::new(config_can_reuse, config_reuse_port);
                 vacant.insert(port_state);
+                config_reuse_port
             }
         };
 
-        Ok((port, config_can_reuse))
+        Ok((port, config_can_reuse, reuse_port))
     }
 
     /// Allocates an ephemeral port.
@@ -488,12 +862,7 @@ impl PortTable {
         None
     }
 
-    fn release(&mut self, addr: IpAddress, port: u16, can_reuse: bool, protocol: PortProtocol) {
-        let key = PortKey {
-            addr: NormalizedAddress::from(addr),
-            port,
-            protocol,
-        };
+    fn release(&mut self, key: PortKey, can_reuse: bool, reuse_port: bool) {
         let Entry::Occupied(mut occupied) = self.used_ports.entry(key) else {
             return;
         };
@@ -503,6 +872,13 @@ impl PortTable {
         if can_reuse {
             port_state.nreuse -= 1;
         }
+        if reuse_port {
+            port_state.nreuseport -= 1;
+            if port_state.nreuseport == 0 {
+                port_state.reuseport_uid = None;
+                port_state.reuseport_selector = None;
+            }
+        }
         if port_state.nsocket == 0 {
             occupied.remove();
         }
@@ -519,6 +895,19 @@ impl PortTable {
             port_state.nreuse -= 1;
         }
     }
+
+    fn set_reuseport_selector(
+        &mut self,
+        key: &PortKey,
+        selector: Option<Arc<E::ReuseportSelector>>,
+    ) -> Option<Arc<E::ReuseportSelector>> {
+        let port_state = self.used_ports.get_mut(key)?;
+        core::mem::replace(&mut port_state.reuseport_selector, selector)
+    }
+
+    fn reuseport_selector(&self, key: &PortKey) -> Option<Arc<E::ReuseportSelector>> {
+        self.used_ports.get(key)?.reuseport_selector.clone()
+    }
 }
 
 /// Interface type.
@@ -548,6 +937,8 @@ pub enum InterfaceType {
     LOOPBACK = 772,
     /// Localtalk device
     LOCALTALK = 773,
+    /// Zero header length
+    NONE = 0xFFFE,
     // TODO: This enum is not exhaustive
 }
 
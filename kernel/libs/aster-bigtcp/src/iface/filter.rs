@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{vec, vec::Vec};
+
+use smoltcp::{
+    iface::packet::Packet,
+    phy::{ChecksumCapabilities, DeviceCapabilities},
+    wire::{IpRepr, IpVersion, Ipv4Packet, Ipv4Repr, Ipv6Packet, Ipv6Repr},
+};
+
+/// The points in the receive and transmit paths where IP packets are passed to the packet
+/// filter.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter.h>.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterHook {
+    /// Incoming packets, before the routing decision is made.
+    PreRouting = 0,
+    /// Incoming packets that are destined for a local address.
+    LocalIn = 1,
+    /// Incoming packets that are forwarded to another iface.
+    Forward = 2,
+    /// Locally generated packets.
+    LocalOut = 3,
+    /// Outgoing packets, including both locally generated and forwarded ones.
+    PostRouting = 4,
+}
+
+/// A trait to filter and mangle the IP packets that go through ifaces.
+///
+/// The packet filter, which may be shared by many ifaces, is maintained by the users of this
+/// crate.
+pub trait PacketFilter: Send + Sync {
+    /// Returns whether the packet filter may drop or modify any packets.
+    ///
+    /// If this method returns false, packets are not serialized for the packet filter, which
+    /// saves a copy for each packet.
+    fn is_enabled(&self) -> bool;
+
+    /// Filters an IP packet, including its IP header, at the hook.
+    ///
+    /// `in_iface` and `out_iface` are the indexes of the input and output ifaces, if they are
+    /// known at the hook. The packet may be modified in place (e.g., to perform NAT), in which
+    /// case the checksums must be updated accordingly.
+    ///
+    /// This method returns whether the packet should be accepted. Otherwise, the packet is
+    /// dropped.
+    ///
+    /// This method is called with the iface locked, so it must not try to lock any iface.
+    fn filter(
+        &self,
+        hook: FilterHook,
+        in_iface: Option<u32>,
+        out_iface: Option<u32>,
+        packet: &mut [u8],
+    ) -> bool;
+}
+
+/// Serializes an IP packet so that it can be passed to the packet filter.
+pub(super) fn emit_packet(pkt: &Packet) -> Vec<u8> {
+    let ip_repr = pkt.ip_repr();
+    let header_len = ip_repr.header_len();
+    let mut buffer = vec![0; header_len + ip_repr.payload_len()];
+    ip_repr.emit(&mut buffer[..header_len], &ChecksumCapabilities::default());
+    pkt.emit_payload(
+        &ip_repr,
+        &mut buffer[header_len..],
+        &DeviceCapabilities::default(),
+    );
+    buffer
+}
+
+/// Parses an IP packet that has been passed to the packet filter.
+pub(super) fn parse_packet(buffer: &[u8]) -> Option<(IpRepr, &[u8])> {
+    match IpVersion::of_packet(buffer).ok()? {
+        IpVersion::Ipv4 => {
+            let packet = Ipv4Packet::new_checked(buffer).ok()?;
+            let repr = Ipv4Repr::parse(&packet, &ChecksumCapabilities::ignored()).ok()?;
+            let header_len = packet.header_len() as usize;
+            let payload = buffer.get(header_len..header_len + repr.payload_len)?;
+            Some((IpRepr::Ipv4(repr), payload))
+        }
+        IpVersion::Ipv6 => {
+            let packet = Ipv6Packet::new_checked(buffer).ok()?;
+            let repr = Ipv6Repr::parse(&packet).ok()?;
+            let header_len = packet.header_len();
+            let payload = buffer.get(header_len..header_len + repr.payload_len)?;
+            Some((IpRepr::Ipv6(repr), payload))
+        }
+    }
+}
@@ -1,12 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
 use core::ffi::CStr;
 
-use smoltcp::wire::{Ipv4Address, Ipv4Cidr, Ipv6Address};
+use smoltcp::wire::{
+    EthernetAddress, IpAddress, IpProtocol, IpRepr, Ipv4Address, Ipv4Cidr, Ipv6Address, Ipv6Cidr,
+};
 
-use super::{BindPortConfig, BoundTcpPort, BoundUdpPort, InterfaceFlags, InterfaceType};
-use crate::{errors::BindError, ext::Ext};
+use super::{
+    BindPortConfig, BoundRawPort, BoundTcpPort, BoundUdpPort, IfaceCounter, InterfaceFlags,
+    InterfaceType,
+};
+use crate::{errors::BindError, ext::Ext, socket::SocketInfo};
 
 /// A network interface.
 ///
@@ -51,6 +56,31 @@ impl<E: Ext> dyn Iface<E> {
         common.bind_udp(self.clone(), config)
     }
 
+    /// Binds a raw IP protocol to the iface.
+    ///
+    /// Binding never conflicts with other raw sockets, since all raw sockets with the same
+    /// protocol receive a copy of each matching packet.
+    pub fn bind_raw(
+        self: &Arc<Self>,
+        addr: IpAddress,
+        protocol: IpProtocol,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        let common = self.common();
+        common.bind_raw(self.clone(), addr, protocol)
+    }
+
+    /// Binds an ICMP echo identifier to the iface.
+    ///
+    /// The port in [`BindPortConfig`] is used as the identifier. If no specific identifier is
+    /// given, the iface will pick up an ephemeral one.
+    pub fn bind_icmp(
+        self: &Arc<Self>,
+        config: BindPortConfig,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        let common = self.common();
+        common.bind_icmp(self.clone(), config)
+    }
+
     /// Returns the interface index.
     pub fn index(&self) -> u32 {
         self.common().index()
@@ -73,6 +103,46 @@ impl<E: Ext> dyn Iface<E> {
         self.common().flags()
     }
 
+    /// Gets the hardware address of the iface, if any.
+    ///
+    /// Like Linux, loopback devices have an all-zeros Ethernet address.
+    pub fn hardware_addr(&self) -> Option<EthernetAddress> {
+        self.common().hardware_addr()
+    }
+
+    /// Updates the promiscuity count of the iface.
+    ///
+    /// The iface is in promiscuous mode as long as the count is positive. In promiscuous mode,
+    /// packet sockets can see frames that are sent to other hosts.
+    pub fn set_promiscuity(&self, inc: isize) {
+        self.common().set_promiscuity(inc)
+    }
+
+    /// Updates the all-multicast count of the iface.
+    ///
+    /// The iface receives all multicast frames as long as the count is positive.
+    pub fn set_allmulti(&self, inc: isize) {
+        self.common().set_allmulti(inc)
+    }
+
+    /// Joins a multicast group on the iface.
+    ///
+    /// The iface counts how many times each group is joined, and the group is only left after
+    /// [`Self::leave_multicast_group`] is called the same number of times. This method returns
+    /// whether the group is joined for the first time, in which case a membership report will be
+    /// sent in the next poll.
+    pub fn join_multicast_group(&self, group: IpAddress) -> bool {
+        self.common().join_multicast_group(group)
+    }
+
+    /// Leaves a multicast group on the iface.
+    ///
+    /// This method returns whether the group is left for the last time, in which case a report
+    /// will be sent in the next poll.
+    pub fn leave_multicast_group(&self, group: IpAddress) -> bool {
+        self.common().leave_multicast_group(group)
+    }
+
     /// Gets the IPv4 address of the iface, if any.
     //
     // FIXME: One iface may have multiple IPv4 addresses.
@@ -81,10 +151,19 @@ impl<E: Ext> dyn Iface<E> {
     }
 
     /// Gets the IPv6 address of the iface, if any.
+    ///
+    /// If the iface has multiple IPv6 addresses, the first one will be returned.
     pub fn ipv6_addr(&self) -> Option<Ipv6Address> {
         self.common().ipv6_addr()
     }
 
+    /// Gets all IPv6 addresses of the iface, together with their prefix lengths.
+    ///
+    /// This includes link-local addresses and addresses configured by SLAAC.
+    pub fn ipv6_addrs(&self) -> Vec<Ipv6Cidr> {
+        self.common().ipv6_addrs()
+    }
+
     /// Retrieves the prefix length of the interface's IPv4 address.
     ///
     /// Both `Self::ipv4_addr` and this method will either return `Some(_)`
@@ -104,12 +183,50 @@ impl<E: Ext> dyn Iface<E> {
         cidr.broadcast()
     }
 
+    /// Returns the value of a statistics counter of the iface.
+    pub fn counter(&self, counter: IfaceCounter) -> u64 {
+        self.common().counters().get(counter)
+    }
+
+    /// Visits the TCP and UDP sockets bound to the iface.
+    ///
+    /// The socket table is locked during the visit, so `f` must not block or access the sockets
+    /// of the iface.
+    pub fn visit_sockets<F>(&self, f: F)
+    where
+        F: FnMut(&SocketInfo<'_, E>),
+    {
+        self.common().visit_sockets(f)
+    }
+
     /// Returns a reference to the associated [`ScheduleNextPoll`].
     ///
     /// [`ScheduleNextPoll`]: crate::iface::sched::ScheduleNextPoll
     pub fn sched_poll(&self) -> &E::ScheduleNextPoll {
         self.common().sched_poll()
     }
+
+    /// Sets the router that makes routing decisions for the iface.
+    ///
+    /// Without a router, the iface can only use its own routes and never forwards packets.
+    pub fn set_router(&self, router: Arc<E::Router>) {
+        self.common().set_router(router)
+    }
+
+    /// Sets the packet filter that filters and mangles the packets going through the iface.
+    pub fn set_packet_filter(&self, filter: Arc<E::PacketFilter>) {
+        self.common().set_packet_filter(filter)
+    }
+
+    /// Queues an IP packet forwarded from another iface, which will be sent out in the next poll.
+    ///
+    /// `in_iface` is the index of the iface that received the packet. If the packet is destined
+    /// for the iface itself, it will be processed locally instead. This method returns `false`
+    /// and drops the packet if the queue is full.
+    pub fn enqueue_forwarded(&self, ip_repr: IpRepr, ip_payload: Vec<u8>, in_iface: u32) -> bool {
+        self.common()
+            .enqueue_forwarded(ip_repr, ip_payload, in_iface)
+    }
 }
 
 pub(super) mod internal {
@@ -1,18 +1,29 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod common;
+mod filter;
 #[expect(clippy::module_inception)]
 mod iface;
+mod multicast;
 mod phy;
 mod poll;
 mod poll_iface;
 mod port;
+mod route;
 mod sched;
+mod stats;
+mod tap;
 mod time;
 
-pub use common::{BoundPort, BoundTcpPort, BoundUdpPort, InterfaceFlags, InterfaceType};
+pub use common::{
+    BoundPort, BoundRawPort, BoundTcpPort, BoundUdpPort, InterfaceFlags, InterfaceType,
+};
+pub use filter::{FilterHook, PacketFilter};
 pub use iface::Iface;
 pub use phy::{EtherIface, IpIface};
 pub(crate) use poll_iface::{PollKey, PollableIfaceMut};
 pub use port::BindPortConfig;
+pub use route::Router;
 pub use sched::ScheduleNextPoll;
+pub use stats::IfaceCounter;
+pub(crate) use tap::LinkLayer;
@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Multicast group membership.
+//!
+//! An iface reports the multicast groups that it has joined to the multicast routers on the link.
+//! IGMP is used for IPv4 groups and MLDv2 is used for IPv6 groups. See
+//! <https://datatracker.ietf.org/doc/html/rfc3376> and
+//! <https://datatracker.ietf.org/doc/html/rfc3810>.
+
+use alloc::{
+    collections::{btree_map::BTreeMap, vec_deque::VecDeque},
+    vec,
+    vec::Vec,
+};
+
+use smoltcp::{
+    iface::packet::{IpPayload, Packet},
+    phy::DeviceCapabilities,
+    wire::{
+        IPV4_MULTICAST_ALL_ROUTERS, IPV4_MULTICAST_ALL_SYSTEMS, IPV6_LINK_LOCAL_ALL_MLDV2_ROUTERS,
+        IPV6_LINK_LOCAL_ALL_NODES, Icmpv6Repr, IgmpPacket, IgmpRepr, IgmpVersion, IpAddress,
+        IpProtocol, IpRepr, Ipv4Address, Ipv4Repr, Ipv6Address, Ipv6ExtHeaderRepr,
+        Ipv6HopByHopRepr, Ipv6Repr, MldAddressRecordRepr, MldRecordType, MldRepr,
+    },
+};
+
+/// The multicast groups joined by an iface.
+pub(super) struct MulticastGroups {
+    /// The joined groups and the number of times that each of them has been joined.
+    groups: BTreeMap<IpAddress, usize>,
+    /// Whether an IGMPv1 or IGMPv2 querier is present on the link.
+    ///
+    /// If so, IGMPv2 reports are sent instead of IGMPv3 reports. See
+    /// <https://datatracker.ietf.org/doc/html/rfc3376#section-7.2.1>.
+    //
+    // TODO: Switch back to IGMPv3 after the Older Version Querier Present Timeout expires.
+    has_old_igmp_querier: bool,
+    /// The reports that have yet to be sent out.
+    pending_reports: VecDeque<PendingReport>,
+}
+
+/// A membership report that has yet to be sent out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PendingReport {
+    /// Reports that the group has been joined.
+    Join(IpAddress),
+    /// Reports that the group has been left.
+    Leave(IpAddress),
+    /// Reports that the group is joined, in response to a query.
+    Current(IpAddress),
+}
+
+impl PendingReport {
+    fn group(&self) -> IpAddress {
+        match self {
+            Self::Join(group) | Self::Leave(group) | Self::Current(group) => *group,
+        }
+    }
+}
+
+/// The maximum number of reports that can be queued in an iface.
+const MAX_PENDING_REPORTS: usize = 256;
+
+/// The IPv4 multicast address to which IGMPv3 reports are sent.
+const IPV4_MULTICAST_ALL_IGMPV3_ROUTERS: Ipv4Address = Ipv4Address::new(224, 0, 0, 22);
+
+impl MulticastGroups {
+    pub(super) const fn new() -> Self {
+        Self {
+            groups: BTreeMap::new(),
+            has_old_igmp_querier: false,
+            pending_reports: VecDeque::new(),
+        }
+    }
+
+    /// Joins a multicast group.
+    ///
+    /// This method returns whether the group is joined for the first time.
+    pub(super) fn join(&mut self, group: IpAddress) -> bool {
+        debug_assert!(group.is_multicast());
+
+        let count = self.groups.entry(group).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            return false;
+        }
+
+        self.push_report(PendingReport::Join(group));
+        true
+    }
+
+    /// Leaves a multicast group.
+    ///
+    /// This method returns whether the group is left for the last time.
+    pub(super) fn leave(&mut self, group: IpAddress) -> bool {
+        let Some(count) = self.groups.get_mut(&group) else {
+            return false;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return false;
+        }
+
+        self.groups.remove(&group);
+        self.push_report(PendingReport::Leave(group));
+        true
+    }
+
+    /// Returns whether the multicast group has been joined.
+    ///
+    /// The all-systems (`224.0.0.1`) and all-nodes (`ff02::1`) groups are always joined.
+    pub(super) fn has_joined(&self, group: &IpAddress) -> bool {
+        is_all_nodes(group) || self.groups.contains_key(group)
+    }
+
+    /// Returns an iterator over the joined multicast groups.
+    pub(super) fn iter(&self) -> impl Iterator<Item = &IpAddress> + '_ {
+        self.groups.keys()
+    }
+
+    /// Processes an incoming IGMP message.
+    ///
+    /// Membership queries are answered by queuing reports for the queried groups. Other
+    /// messages are ignored.
+    pub(super) fn process_igmp(&mut self, ip_payload: &[u8]) {
+        let Ok(igmp_pkt) = IgmpPacket::new_checked(ip_payload) else {
+            return;
+        };
+        if !igmp_pkt.verify_checksum() {
+            return;
+        }
+        let Ok(IgmpRepr::MembershipQuery { group_addr, .. }) = IgmpRepr::parse(&igmp_pkt) else {
+            return;
+        };
+
+        // IGMPv3 queries are at least 12 bytes long, while IGMPv1 and IGMPv2 queries are exactly
+        // 8 bytes long. See <https://datatracker.ietf.org/doc/html/rfc3376#section-7.1>.
+        if ip_payload.len() < 12 {
+            self.has_old_igmp_querier = true;
+        }
+
+        self.queue_current_reports(IpAddress::Ipv4(group_addr));
+    }
+
+    /// Processes an incoming MLD message.
+    ///
+    /// Like [`Self::process_igmp`], only queries are handled.
+    pub(super) fn process_mld(&mut self, mld_repr: &MldRepr) {
+        let MldRepr::Query { mcast_addr, .. } = mld_repr else {
+            return;
+        };
+
+        self.queue_current_reports(IpAddress::Ipv6(*mcast_addr));
+    }
+
+    /// Queues reports for the queried group, or for all joined groups of the same IP version if
+    /// the query is a general query (i.e., the queried group is unspecified).
+    fn queue_current_reports(&mut self, queried: IpAddress) {
+        if !queried.is_unspecified() {
+            if self.groups.contains_key(&queried) {
+                self.push_report(PendingReport::Current(queried));
+            }
+            return;
+        }
+
+        let groups = self
+            .groups
+            .keys()
+            .filter(|group| group.version() == queried.version())
+            .copied()
+            .collect::<Vec<_>>();
+        for group in groups {
+            self.push_report(PendingReport::Current(group));
+        }
+    }
+
+    fn push_report(&mut self, report: PendingReport) {
+        let group = report.group();
+
+        // Membership of the all-nodes groups is never reported, and neither is membership of
+        // groups whose scope is limited to the local node.
+        if is_all_nodes(&group) || is_node_local(&group) {
+            return;
+        }
+
+        // A report for the same group may already be pending. In this case, the old report is
+        // superseded, since it no longer describes the current state.
+        self.pending_reports
+            .retain(|pending| pending.group() != group);
+
+        if self.pending_reports.len() >= MAX_PENDING_REPORTS {
+            self.pending_reports.pop_front();
+        }
+        self.pending_reports.push_back(report);
+    }
+
+    /// Returns whether there are reports that have yet to be sent out.
+    pub(super) fn has_pending_reports(&self) -> bool {
+        !self.pending_reports.is_empty()
+    }
+
+    /// Dequeues a pending report and builds the packet that carries it.
+    ///
+    /// `ipv4_addr` and `ipv6_link_local_addr` are used as the source addresses of IGMP and MLD
+    /// reports, respectively. MLD reports are dropped if there is no link-local address.
+    pub(super) fn pop_report(
+        &mut self,
+        ipv4_addr: Option<Ipv4Address>,
+        ipv6_link_local_addr: Option<Ipv6Address>,
+    ) -> Option<(IpRepr, Vec<u8>)> {
+        loop {
+            let report = self.pending_reports.pop_front()?;
+
+            let packet = match report.group() {
+                IpAddress::Ipv4(group) => {
+                    let src_addr = ipv4_addr.unwrap_or(Ipv4Address::UNSPECIFIED);
+                    Some(self.build_igmp_report(report, group, src_addr))
+                }
+                IpAddress::Ipv6(group) => {
+                    ipv6_link_local_addr.map(|src_addr| build_mld_report(report, group, src_addr))
+                }
+            };
+
+            if packet.is_some() {
+                return packet;
+            }
+        }
+    }
+
+    fn build_igmp_report(
+        &self,
+        report: PendingReport,
+        group: Ipv4Address,
+        src_addr: Ipv4Address,
+    ) -> (IpRepr, Vec<u8>) {
+        let (dst_addr, igmp_payload) = if self.has_old_igmp_querier {
+            build_igmpv2_report(report, group)
+        } else {
+            build_igmpv3_report(report, group)
+        };
+
+        // All IGMP messages are sent with a TTL of 1. See
+        // <https://datatracker.ietf.org/doc/html/rfc3376#section-4>.
+        let ip_repr = IpRepr::Ipv4(Ipv4Repr {
+            src_addr,
+            dst_addr,
+            next_header: IpProtocol::Igmp,
+            payload_len: igmp_payload.len(),
+            hop_limit: 1,
+        });
+        (ip_repr, igmp_payload)
+    }
+}
+
+/// Builds an IGMPv2 report and returns its destination address and its payload.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc2236#section-2>.
+fn build_igmpv2_report(report: PendingReport, group: Ipv4Address) -> (Ipv4Address, Vec<u8>) {
+    let (dst_addr, igmp_repr) = match report {
+        PendingReport::Join(_) | PendingReport::Current(_) => (
+            group,
+            IgmpRepr::MembershipReport {
+                group_addr: group,
+                version: IgmpVersion::Version2,
+            },
+        ),
+        PendingReport::Leave(_) => (
+            IPV4_MULTICAST_ALL_ROUTERS,
+            IgmpRepr::LeaveGroup { group_addr: group },
+        ),
+    };
+
+    let mut igmp_payload = vec![0; igmp_repr.buffer_len()];
+    igmp_repr.emit(&mut IgmpPacket::new_unchecked(igmp_payload.as_mut_slice()));
+    (dst_addr, igmp_payload)
+}
+
+/// Builds an IGMPv3 report with a single group record and returns its destination address and
+/// its payload.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc3376#section-4.2>.
+fn build_igmpv3_report(report: PendingReport, group: Ipv4Address) -> (Ipv4Address, Vec<u8>) {
+    const MEMBERSHIP_REPORT_V3: u8 = 0x22;
+
+    const MODE_IS_EXCLUDE: u8 = 2;
+    const CHANGE_TO_INCLUDE_MODE: u8 = 3;
+    const CHANGE_TO_EXCLUDE_MODE: u8 = 4;
+
+    // Source filtering is not supported, so joining a group means excluding no sources and
+    // leaving a group means including no sources.
+    let record_type = match report {
+        PendingReport::Join(_) => CHANGE_TO_EXCLUDE_MODE,
+        PendingReport::Leave(_) => CHANGE_TO_INCLUDE_MODE,
+        PendingReport::Current(_) => MODE_IS_EXCLUDE,
+    };
+
+    let mut igmp_payload = Vec::with_capacity(16);
+    // Type, reserved, checksum, reserved, and the number of group records
+    igmp_payload.extend_from_slice(&[MEMBERSHIP_REPORT_V3, 0, 0, 0, 0, 0, 0, 1]);
+    // Record type, auxiliary data length, the number of sources, and the multicast address
+    igmp_payload.extend_from_slice(&[record_type, 0, 0, 0]);
+    igmp_payload.extend_from_slice(&group.octets());
+
+    // The checksum covers the whole IGMP message, just like IGMPv2 messages.
+    IgmpPacket::new_unchecked(igmp_payload.as_mut_slice()).fill_checksum();
+
+    (IPV4_MULTICAST_ALL_IGMPV3_ROUTERS, igmp_payload)
+}
+
+/// Builds an MLDv2 report with a single multicast address record.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc3810#section-5.2>.
+fn build_mld_report(
+    report: PendingReport,
+    group: Ipv6Address,
+    src_addr: Ipv6Address,
+) -> (IpRepr, Vec<u8>) {
+    let record_type = match report {
+        PendingReport::Join(_) => MldRecordType::ChangeToExclude,
+        PendingReport::Leave(_) => MldRecordType::ChangeToInclude,
+        PendingReport::Current(_) => MldRecordType::ModeIsExclude,
+    };
+    let records = [MldAddressRecordRepr::new(record_type, group)];
+    let mld_repr = MldRepr::ReportRecordReprs(&records);
+
+    // MLD messages must be sent with a Router Alert option in a Hop-by-Hop Options header. See
+    // <https://datatracker.ietf.org/doc/html/rfc3810#section-5>.
+    let mut hbh_repr = Ipv6HopByHopRepr::mldv2_router_alert();
+    hbh_repr.push_padn_option(0);
+    let ext_header_len = Ipv6ExtHeaderRepr {
+        next_header: IpProtocol::Icmpv6,
+        length: 0,
+        data: &[],
+    }
+    .header_len();
+
+    // All MLD messages are sent with a hop limit of 1.
+    let ipv6_repr = Ipv6Repr {
+        src_addr,
+        dst_addr: IPV6_LINK_LOCAL_ALL_MLDV2_ROUTERS,
+        next_header: IpProtocol::HopByHop,
+        payload_len: ext_header_len
+            + hbh_repr.buffer_len()
+            + mld_repr.buffer_len()
+            + records[0].buffer_len(),
+        hop_limit: 1,
+    };
+    let packet = Packet::new_ipv6(
+        ipv6_repr,
+        IpPayload::HopByHopIcmpv6(hbh_repr, Icmpv6Repr::Mld(mld_repr)),
+    );
+
+    let ip_repr = IpRepr::Ipv6(ipv6_repr);
+    let mut ip_payload = vec![0; ipv6_repr.payload_len];
+    packet.emit_payload(&ip_repr, &mut ip_payload, &DeviceCapabilities::default());
+    (ip_repr, ip_payload)
+}
+
+/// Returns whether the address is the all-systems (`224.0.0.1`) or all-nodes (`ff02::1`)
+/// multicast address.
+fn is_all_nodes(addr: &IpAddress) -> bool {
+    match addr {
+        IpAddress::Ipv4(addr) => *addr == IPV4_MULTICAST_ALL_SYSTEMS,
+        IpAddress::Ipv6(addr) => *addr == IPV6_LINK_LOCAL_ALL_NODES,
+    }
+}
+
+/// Returns whether the address is an IPv6 multicast address whose scope is limited to the local
+/// node (i.e., the reserved scope or the interface-local scope).
+fn is_node_local(addr: &IpAddress) -> bool {
+    match addr {
+        IpAddress::Ipv4(_) => false,
+        IpAddress::Ipv6(addr) => addr.is_multicast() && addr.octets()[1] & 0x0f <= 1,
+    }
+}
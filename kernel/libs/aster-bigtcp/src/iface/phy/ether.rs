@@ -1,23 +1,36 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{collections::btree_map::BTreeMap, ffi::CString, sync::Arc};
+use alloc::{
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+    ffi::CString,
+    sync::Arc,
+    vec::Vec,
+};
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use aster_softirq::BottomHalfDisabled;
 use ostd::sync::SpinLock;
 use smoltcp::{
-    iface::{Config, Context, packet::Packet},
-    phy::{Device, DeviceCapabilities, TxToken},
+    iface::{
+        Config, Context,
+        packet::{IpPayload, Packet},
+    },
+    phy::{Device, DeviceCapabilities, Medium, TxToken},
+    time::Duration,
     wire::{
         self, ArpOperation, ArpPacket, ArpRepr, EthernetAddress, EthernetFrame, EthernetProtocol,
-        EthernetRepr, IpAddress, Ipv4Address, Ipv4AddressExt, Ipv4Cidr, Ipv4Packet, Ipv6Packet,
+        EthernetRepr, HardwareAddress, IPV4_MULTICAST_ALL_SYSTEMS, IPV6_LINK_LOCAL_ALL_NODES,
+        IPV6_LINK_LOCAL_ALL_ROUTERS, Icmpv6Packet, Icmpv6Repr, IpAddress, IpProtocol, Ipv4Address,
+        Ipv4AddressExt, Ipv4Cidr, Ipv4Packet, Ipv6Address, Ipv6Cidr, Ipv6Packet, Ipv6Repr,
+        NdiscNeighborFlags, NdiscPrefixInfoFlags, NdiscRepr, RawHardwareAddress,
     },
 };
 
 use crate::{
-    device::{NotifyDevice, WithDevice},
+    device::{NotifyDevice, RxFilter, WithDevice},
     ext::Ext,
     iface::{
-        Iface, InterfaceFlags, ScheduleNextPoll,
+        Iface, InterfaceFlags, Router, ScheduleNextPoll,
         common::{IfaceCommon, InterfaceType, IpPacket},
         iface::internal::IfaceInternal,
         time::get_network_timestamp,
@@ -29,41 +42,69 @@ pub struct EtherIface<D, E: Ext> {
     common: IfaceCommon<E>,
     ether_addr: EthernetAddress,
     arp_table: SpinLock<BTreeMap<Ipv4Address, EthernetAddress>, BottomHalfDisabled>,
+    ndisc_table: SpinLock<BTreeMap<Ipv6Address, EthernetAddress>, BottomHalfDisabled>,
+    pending_autoconf: SpinLock<PendingAutoconf, BottomHalfDisabled>,
+    has_solicited_routers: AtomicBool,
+}
+
+/// IPv6 configurations learned from router advertisements that have yet to be applied.
+///
+/// Router advertisements are processed while the interface is locked, so the addresses and the
+/// router cannot be added to the interface immediately. Instead, they are recorded here and
+/// applied after polling the interface.
+#[derive(Default)]
+struct PendingAutoconf {
+    addrs: Vec<Ipv6Cidr>,
+    router: Option<Ipv6Address>,
+}
+
+/// A link-layer control packet generated by the interface.
+enum LinkPacket {
+    Arp(ArpRepr),
+    Ndisc(EthernetRepr, Packet<'static>),
 }
 
 impl<D: WithDevice, E: Ext> EtherIface<D, E> {
     pub fn new(
         driver: D,
         ether_addr: EthernetAddress,
-        ip_cidr: Ipv4Cidr,
-        gateway: Ipv4Address,
+        ip_cidr: Option<Ipv4Cidr>,
         name: CString,
         sched_poll: E::ScheduleNextPoll,
         flags: InterfaceFlags,
-    ) -> Arc<Self> {
-        let interface = driver.with(|device| {
+    ) -> Arc<Self>
+    where
+        D::Device: NotifyDevice,
+    {
+        let (interface, gso_max_size) = driver.with(|device| {
             let config = Config::new(wire::HardwareAddress::Ethernet(ether_addr));
             let now = get_network_timestamp();
 
             let mut interface = smoltcp::iface::Interface::new(config, device, now);
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
-                ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                if let Some(ip_cidr) = ip_cidr {
+                    ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                }
+                // Every IPv6-capable interface must have a link-local address. See
+                // <https://datatracker.ietf.org/doc/html/rfc4291#section-2.1>.
+                let link_local_cidr = Ipv6Cidr::new(link_local_addr(&ether_addr), 64);
+                ip_addrs.push(wire::IpCidr::Ipv6(link_local_cidr)).unwrap();
             });
-            interface
-                .routes_mut()
-                .add_default_ipv4_route(gateway)
-                .unwrap();
-            interface
+            (interface, device.gso_max_size())
         });
 
-        let common = IfaceCommon::new(name, InterfaceType::ETHER, flags, interface, sched_poll);
+        let mut common = IfaceCommon::new(name, InterfaceType::ETHER, flags, interface, sched_poll);
+        common.set_gso_max_size(gso_max_size);
 
         Arc::new(Self {
             driver,
             common,
             ether_addr,
             arp_table: SpinLock::new(BTreeMap::new()),
+            ndisc_table: SpinLock::new(BTreeMap::new()),
+            pending_autoconf: SpinLock::new(PendingAutoconf::default()),
+            has_solicited_routers: AtomicBool::new(false),
         })
     }
 }
@@ -79,15 +120,40 @@ where
     D::Device: NotifyDevice,
 {
     fn poll(&self) {
-        self.driver.with(|device| {
-            let next_poll = self.common.poll(
-                &mut *device,
-                |data, iface_cx, tx_token| self.process(data, iface_cx, tx_token),
-                |pkt, iface_cx, tx_token| self.dispatch(pkt, iface_cx, tx_token),
-            );
-            device.notify_poll_end();
-            self.common.sched_poll().schedule_next_poll(next_poll);
-        });
+        let mut next_poll: Option<u64> = None;
+
+        // Poll the receive queues one by one, so that only one queue is locked at a time.
+        for queue in 0..self.driver.num_queues() {
+            let queue_next_poll = self.driver.with_queue(queue, |device| {
+                let mut tap_device = self.common.tap(&mut *device);
+
+                if !self.has_solicited_routers.swap(true, Ordering::Relaxed) {
+                    self.solicit_routers(&mut tap_device);
+                }
+
+                let next_poll = self.common.poll(
+                    &mut tap_device,
+                    |data, iface_cx, tx_token| self.process(data, iface_cx, tx_token),
+                    |pkt, iface_cx, tx_token| self.dispatch(pkt, iface_cx, tx_token),
+                );
+                drop(tap_device);
+                device.notify_poll_end();
+                next_poll
+            });
+
+            next_poll = match (next_poll, queue_next_poll) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+
+        self.apply_autoconf();
+        if self.common.take_rx_filter_changed() {
+            let rx_filter = self.rx_filter();
+            self.driver
+                .with(|device| device.update_rx_filter(&rx_filter));
+        }
+        self.common.sched_poll().schedule_next_poll(next_poll);
     }
 
     fn mtu(&self) -> usize {
@@ -103,27 +169,28 @@ impl<D, E: Ext> EtherIface<D, E> {
         iface_cx: &mut Context,
         tx_token: T,
     ) -> Option<(IpPacket<'pkt>, T)> {
-        match self.parse_ip_or_process_arp(data, iface_cx) {
+        match self.parse_ip_or_process_link(data, iface_cx) {
             Ok(pkt) => Some((pkt, tx_token)),
-            Err(Some(arp)) => {
-                Self::emit_arp(&arp, tx_token);
+            Err(Some(link_pkt)) => {
+                Self::emit_link(&link_pkt, &iface_cx.caps, tx_token);
                 None
             }
             Err(None) => None,
         }
     }
 
-    fn parse_ip_or_process_arp<'pkt>(
+    fn parse_ip_or_process_link<'pkt>(
         &self,
         data: &'pkt [u8],
         iface_cx: &mut Context,
-    ) -> Result<IpPacket<'pkt>, Option<ArpRepr>> {
+    ) -> Result<IpPacket<'pkt>, Option<LinkPacket>> {
         // Parse the Ethernet header. Ignore the packet if the header is ill-formed.
         let frame = EthernetFrame::new_checked(data).map_err(|_| None)?;
         let repr = EthernetRepr::parse(&frame).map_err(|_| None)?;
 
-        // Ignore the Ethernet frame if it is not sent to us.
-        if !repr.dst_addr.is_broadcast() && repr.dst_addr != self.ether_addr {
+        // Ignore the Ethernet frame if it is not sent to us. Note that broadcast addresses are
+        // also multicast addresses, and IPv6 neighbor discovery relies on multicast addresses.
+        if !repr.dst_addr.is_multicast() && repr.dst_addr != self.ether_addr {
             return Err(None);
         }
 
@@ -135,12 +202,30 @@ impl<D, E: Ext> EtherIface<D, E> {
             }
             EthernetProtocol::Ipv6 => {
                 let pkt = Ipv6Packet::new_checked(frame.payload()).map_err(|_| None)?;
-                Ok(IpPacket::Ipv6(pkt))
+                if pkt.next_header() != IpProtocol::Icmpv6 {
+                    return Ok(IpPacket::Ipv6(pkt));
+                }
+
+                let ipv6_repr = Ipv6Repr::parse(&pkt).map_err(|_| None)?;
+                let icmp_pkt = Icmpv6Packet::new_checked(pkt.payload()).map_err(|_| None)?;
+                let icmp_repr = Icmpv6Repr::parse(
+                    &ipv6_repr.src_addr,
+                    &ipv6_repr.dst_addr,
+                    &icmp_pkt,
+                    &iface_cx.checksum_caps(),
+                )
+                .map_err(|_| None)?;
+                match icmp_repr {
+                    Icmpv6Repr::Ndisc(ndisc_repr) => {
+                        Err(self.process_ndisc(&ipv6_repr, &ndisc_repr, iface_cx))
+                    }
+                    _ => Ok(IpPacket::Ipv6(pkt)),
+                }
             }
             EthernetProtocol::Arp => {
                 let pkt = ArpPacket::new_checked(frame.payload()).map_err(|_| None)?;
                 let arp = ArpRepr::parse(&pkt).map_err(|_| None)?;
-                Err(self.process_arp(&arp, iface_cx))
+                Err(self.process_arp(&arp, iface_cx).map(LinkPacket::Arp))
             }
             _ => Err(None),
         }
@@ -202,56 +287,341 @@ impl<D, E: Ext> EtherIface<D, E> {
         }
     }
 
+    /// Processes an NDP message.
+    ///
+    /// Neighbor solicitations targeting local addresses are answered with neighbor
+    /// advertisements. Link-layer addresses carried by the messages are recorded in the neighbor
+    /// table. Prefixes and routers carried by router advertisements are used to perform stateless
+    /// address autoconfiguration (SLAAC).
+    ///
+    /// Reference: <https://datatracker.ietf.org/doc/html/rfc4861>.
+    fn process_ndisc(
+        &self,
+        ipv6_repr: &Ipv6Repr,
+        ndisc_repr: &NdiscRepr,
+        iface_cx: &mut Context,
+    ) -> Option<LinkPacket> {
+        // Ignore the NDP message if it may have been forwarded by a router. See
+        // <https://datatracker.ietf.org/doc/html/rfc4861#section-7.1.1>.
+        if ipv6_repr.hop_limit != 255 {
+            return None;
+        }
+
+        let src_addr = ipv6_repr.src_addr;
+
+        match ndisc_repr {
+            NdiscRepr::NeighborSolicit {
+                target_addr,
+                lladdr,
+            } => {
+                // Ignore the NDP message if we do not own the target address.
+                if !iface_cx.has_ip_addr(*target_addr) {
+                    return None;
+                }
+
+                // If the source address is unspecified, the solicitation comes from a node
+                // performing duplicate address detection. The advertisement must be multicast to
+                // all nodes in this case.
+                let (dst_addr, dst_ether, flags) = if src_addr.is_unspecified() {
+                    (
+                        IPV6_LINK_LOCAL_ALL_NODES,
+                        multicast_ether_addr(&IPV6_LINK_LOCAL_ALL_NODES),
+                        NdiscNeighborFlags::OVERRIDE,
+                    )
+                } else {
+                    let dst_ether = lladdr
+                        .as_ref()
+                        .and_then(|lladdr| self.learn_neighbor(src_addr, lladdr))
+                        .or_else(|| self.ndisc_table.lock().get(&src_addr).copied())?;
+                    (
+                        src_addr,
+                        dst_ether,
+                        NdiscNeighborFlags::SOLICITED | NdiscNeighborFlags::OVERRIDE,
+                    )
+                };
+
+                let icmp_repr = Icmpv6Repr::Ndisc(NdiscRepr::NeighborAdvert {
+                    flags,
+                    target_addr: *target_addr,
+                    lladdr: Some(self.ether_addr.into()),
+                });
+                Some(self.new_ndisc_packet(*target_addr, dst_addr, dst_ether, icmp_repr))
+            }
+            NdiscRepr::NeighborAdvert {
+                target_addr,
+                lladdr: Some(lladdr),
+                ..
+            } => {
+                // TODO: Remove the mapping if it expires.
+                self.learn_neighbor(*target_addr, lladdr);
+                None
+            }
+            NdiscRepr::RouterAdvert {
+                router_lifetime,
+                lladdr,
+                prefix_info,
+                ..
+            } => {
+                // Ignore the NDP message if it does not come from a link-local address. See
+                // <https://datatracker.ietf.org/doc/html/rfc4861#section-6.1.2>.
+                if !src_addr.is_unicast_link_local() {
+                    return None;
+                }
+
+                if let Some(lladdr) = lladdr {
+                    self.learn_neighbor(src_addr, lladdr);
+                }
+
+                // TODO: Remove the addresses and the router when their lifetimes expire.
+                let mut pending_autoconf = self.pending_autoconf.lock();
+                if let Some(prefix_info) = prefix_info
+                    && prefix_info.flags.contains(NdiscPrefixInfoFlags::ADDRCONF)
+                    && prefix_info.prefix_len == 64
+                    && prefix_info.valid_lifetime > Duration::ZERO
+                    && !prefix_info.prefix.is_unicast_link_local()
+                {
+                    let addr = slaac_addr(&prefix_info.prefix, &self.ether_addr);
+                    pending_autoconf.addrs.push(Ipv6Cidr::new(addr, 64));
+                }
+                if *router_lifetime > Duration::ZERO {
+                    pending_autoconf.router = Some(src_addr);
+                }
+
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Records the Ethernet address of a neighbor.
+    ///
+    /// This method returns the Ethernet address if it is valid.
+    fn learn_neighbor(
+        &self,
+        ip_addr: Ipv6Address,
+        lladdr: &RawHardwareAddress,
+    ) -> Option<EthernetAddress> {
+        let Ok(HardwareAddress::Ethernet(ether_addr)) = lladdr.parse(Medium::Ethernet) else {
+            return None;
+        };
+        if !ether_addr.is_unicast() || ip_addr.is_unspecified() || ip_addr.is_multicast() {
+            return None;
+        }
+
+        self.ndisc_table.lock().insert(ip_addr, ether_addr);
+        Some(ether_addr)
+    }
+
+    fn new_ndisc_packet(
+        &self,
+        src_addr: Ipv6Address,
+        dst_addr: Ipv6Address,
+        dst_ether: EthernetAddress,
+        icmp_repr: Icmpv6Repr<'static>,
+    ) -> LinkPacket {
+        let ether_repr = EthernetRepr {
+            src_addr: self.ether_addr,
+            dst_addr: dst_ether,
+            ethertype: EthernetProtocol::Ipv6,
+        };
+        let ipv6_repr = Ipv6Repr {
+            src_addr,
+            dst_addr,
+            next_header: IpProtocol::Icmpv6,
+            payload_len: icmp_repr.buffer_len(),
+            hop_limit: 255,
+        };
+
+        LinkPacket::Ndisc(
+            ether_repr,
+            Packet::new_ipv6(ipv6_repr, IpPayload::Icmpv6(icmp_repr)),
+        )
+    }
+
+    /// Sends a router solicitation so that routers on the link advertise themselves immediately.
+    //
+    // TODO: Retransmit router solicitations if there are no responses, and perform duplicate
+    // address detection before using the configured addresses. See
+    // <https://datatracker.ietf.org/doc/html/rfc4861#section-6.3.7> and
+    // <https://datatracker.ietf.org/doc/html/rfc4862#section-5.4>.
+    fn solicit_routers<T: Device + ?Sized>(&self, device: &mut T) {
+        let caps = device.capabilities();
+        let Some(tx_token) = device.transmit(get_network_timestamp()) else {
+            return;
+        };
+
+        let icmp_repr = Icmpv6Repr::Ndisc(NdiscRepr::RouterSolicit {
+            lladdr: Some(self.ether_addr.into()),
+        });
+        let link_pkt = self.new_ndisc_packet(
+            link_local_addr(&self.ether_addr),
+            IPV6_LINK_LOCAL_ALL_ROUTERS,
+            multicast_ether_addr(&IPV6_LINK_LOCAL_ALL_ROUTERS),
+            icmp_repr,
+        );
+        Self::emit_link(&link_pkt, &caps, tx_token);
+    }
+
+    /// Applies the IPv6 configurations learned from router advertisements.
+    fn apply_autoconf(&self) {
+        let PendingAutoconf { addrs, router } = core::mem::take(&mut *self.pending_autoconf.lock());
+        if addrs.is_empty() && router.is_none() {
+            return;
+        }
+
+        let mut interface = self.common.interface();
+        if !addrs.is_empty() {
+            // New addresses come with new solicited-node multicast addresses.
+            self.common.set_rx_filter_changed();
+        }
+        for addr in addrs {
+            interface.add_ipv6_addr(addr);
+        }
+        if let Some(router) = router {
+            interface.set_ipv6_default_router(router);
+        }
+    }
+
+    /// Computes the receive filter that the device should apply to incoming frames.
+    fn rx_filter(&self) -> RxFilter {
+        let flags = self.common.flags();
+
+        let mut multicast_addrs = BTreeSet::new();
+        multicast_addrs.insert(ipv4_multicast_ether_addr(&IPV4_MULTICAST_ALL_SYSTEMS));
+        multicast_addrs.insert(multicast_ether_addr(&IPV6_LINK_LOCAL_ALL_NODES));
+        for cidr in self.common.ipv6_addrs() {
+            multicast_addrs.insert(multicast_ether_addr(&solicited_node_addr(&cidr.address())));
+        }
+        for group in self.common.multicast_groups() {
+            multicast_addrs.insert(match group {
+                IpAddress::Ipv4(addr) => ipv4_multicast_ether_addr(&addr),
+                IpAddress::Ipv6(addr) => multicast_ether_addr(&addr),
+            });
+        }
+
+        RxFilter {
+            promiscuous: flags.contains(InterfaceFlags::PROMISC),
+            all_multicast: flags.contains(InterfaceFlags::ALLMULTI),
+            multicast_addrs: multicast_addrs.into_iter().collect(),
+        }
+    }
+
     fn dispatch<T: TxToken>(&self, pkt: &Packet, iface_cx: &mut Context, tx_token: T) {
-        match self.resolve_ether_or_generate_arp(pkt, iface_cx) {
+        match self.resolve_ether_or_generate_link(pkt, iface_cx) {
             Ok(ether) => Self::emit_ip(&ether, pkt, &iface_cx.caps, tx_token),
-            Err(Some(arp)) => Self::emit_arp(&arp, tx_token),
+            Err(Some(link_pkt)) => Self::emit_link(&link_pkt, &iface_cx.caps, tx_token),
             Err(None) => (),
         }
     }
 
-    fn resolve_ether_or_generate_arp(
+    fn resolve_ether_or_generate_link(
         &self,
         pkt: &Packet,
         iface_cx: &mut Context,
-    ) -> Result<EthernetRepr, Option<ArpRepr>> {
-        // Resolve the next-hop IP address.
-        let next_hop_ip = match iface_cx.route(&pkt.ip_repr().dst_addr(), iface_cx.now()) {
-            Some(IpAddress::Ipv4(next_hop_ip)) => next_hop_ip,
-            Some(IpAddress::Ipv6(_)) => {
-                // FIXME: Currently, we drop outbound IPv6 packets because neighbor discovery is not
-                // implemented and we have no way to resolve the next-hop link-layer address.
-                ostd::debug!("IPv6 neighbor discovery is not implemented for Ethernet interfaces");
-                return Err(None);
+    ) -> Result<EthernetRepr, Option<LinkPacket>> {
+        let ip_repr = pkt.ip_repr();
+
+        // Multicast packets are sent directly to the corresponding Ethernet addresses.
+        match ip_repr.dst_addr() {
+            IpAddress::Ipv4(dst_addr) if dst_addr.is_multicast() => {
+                return Ok(EthernetRepr {
+                    src_addr: self.ether_addr,
+                    dst_addr: ipv4_multicast_ether_addr(&dst_addr),
+                    ethertype: EthernetProtocol::Ipv4,
+                });
             }
-            None => return Err(None),
-        };
+            IpAddress::Ipv6(dst_addr) if dst_addr.is_multicast() => {
+                return Ok(EthernetRepr {
+                    src_addr: self.ether_addr,
+                    dst_addr: multicast_ether_addr(&dst_addr),
+                    ethertype: EthernetProtocol::Ipv6,
+                });
+            }
+            _ => (),
+        }
 
-        // Resolve the next-hop Ethernet address.
-        let next_hop_ether = if next_hop_ip.is_broadcast() {
-            EthernetAddress::BROADCAST
-        } else if let Some(next_hop_ether) = self.arp_table.lock().get(&next_hop_ip) {
-            *next_hop_ether
-        } else {
-            // If the next-hop Ethernet address cannot be resolved, we drop the original packet and
-            // send an ARP packet instead. The upper layer should be responsible for detecting the
-            // packet loss and retrying later to see if the Ethernet address is ready.
-            return Err(Some(ArpRepr::EthernetIpv4 {
-                operation: ArpOperation::Request,
-                source_hardware_addr: self.ether_addr,
-                source_protocol_addr: iface_cx.ipv4_addr().unwrap_or(Ipv4Address::UNSPECIFIED),
-                target_hardware_addr: EthernetAddress::BROADCAST,
-                target_protocol_addr: next_hop_ip,
-            }));
+        // Resolve the next-hop IP address and then the next-hop Ethernet address. The router is
+        // consulted first, since it knows the routes that involve other ifaces.
+        let dst_addr = ip_repr.dst_addr();
+        let next_hop_ip = self
+            .common
+            .router()
+            .and_then(|router| router.next_hop(self.common.index(), &ip_repr.src_addr(), &dst_addr))
+            .or_else(|| iface_cx.route(&dst_addr, iface_cx.now()));
+        let (next_hop_ether, ethertype) = match next_hop_ip {
+            Some(IpAddress::Ipv4(next_hop_ip)) => (
+                self.resolve_ipv4_or_generate_arp(next_hop_ip, iface_cx)?,
+                EthernetProtocol::Ipv4,
+            ),
+            Some(IpAddress::Ipv6(next_hop_ip)) => (
+                self.resolve_ipv6_or_generate_ndisc(next_hop_ip, &ip_repr)?,
+                EthernetProtocol::Ipv6,
+            ),
+            None => return Err(None),
         };
 
         Ok(EthernetRepr {
             src_addr: self.ether_addr,
             dst_addr: next_hop_ether,
-            ethertype: EthernetProtocol::Ipv4,
+            ethertype,
         })
     }
 
+    fn resolve_ipv4_or_generate_arp(
+        &self,
+        next_hop_ip: Ipv4Address,
+        iface_cx: &Context,
+    ) -> Result<EthernetAddress, Option<LinkPacket>> {
+        if next_hop_ip.is_broadcast() {
+            return Ok(EthernetAddress::BROADCAST);
+        }
+
+        if let Some(next_hop_ether) = self.arp_table.lock().get(&next_hop_ip) {
+            return Ok(*next_hop_ether);
+        }
+
+        // If the next-hop Ethernet address cannot be resolved, we drop the original packet and
+        // send an ARP packet instead. The upper layer should be responsible for detecting the
+        // packet loss and retrying later to see if the Ethernet address is ready.
+        Err(Some(LinkPacket::Arp(ArpRepr::EthernetIpv4 {
+            operation: ArpOperation::Request,
+            source_hardware_addr: self.ether_addr,
+            source_protocol_addr: iface_cx.ipv4_addr().unwrap_or(Ipv4Address::UNSPECIFIED),
+            target_hardware_addr: EthernetAddress::BROADCAST,
+            target_protocol_addr: next_hop_ip,
+        })))
+    }
+
+    fn resolve_ipv6_or_generate_ndisc(
+        &self,
+        next_hop_ip: Ipv6Address,
+        ip_repr: &wire::IpRepr,
+    ) -> Result<EthernetAddress, Option<LinkPacket>> {
+        if let Some(next_hop_ether) = self.ndisc_table.lock().get(&next_hop_ip) {
+            return Ok(*next_hop_ether);
+        }
+
+        // Similar to ARP, we drop the original packet and send a neighbor solicitation to the
+        // solicited-node multicast address of the next hop instead. See
+        // <https://datatracker.ietf.org/doc/html/rfc4861#section-7.2.2>.
+        let src_addr = match ip_repr.src_addr() {
+            IpAddress::Ipv6(src_addr) if !src_addr.is_unspecified() => src_addr,
+            _ => link_local_addr(&self.ether_addr),
+        };
+        let dst_addr = solicited_node_addr(&next_hop_ip);
+        let icmp_repr = Icmpv6Repr::Ndisc(NdiscRepr::NeighborSolicit {
+            target_addr: next_hop_ip,
+            lladdr: Some(self.ether_addr.into()),
+        });
+
+        Err(Some(self.new_ndisc_packet(
+            src_addr,
+            dst_addr,
+            multicast_ether_addr(&dst_addr),
+            icmp_repr,
+        )))
+    }
+
     /// Consumes the token and emits an IP packet.
     fn emit_ip<T: TxToken>(
         ether_repr: &EthernetRepr,
@@ -276,6 +646,16 @@ impl<D, E: Ext> EtherIface<D, E> {
         );
     }
 
+    /// Consumes the token and emits a link-layer control packet.
+    fn emit_link<T: TxToken>(link_pkt: &LinkPacket, caps: &DeviceCapabilities, tx_token: T) {
+        match link_pkt {
+            LinkPacket::Arp(arp_repr) => Self::emit_arp(arp_repr, tx_token),
+            LinkPacket::Ndisc(ether_repr, ip_pkt) => {
+                Self::emit_ip(ether_repr, ip_pkt, caps, tx_token)
+            }
+        }
+    }
+
     /// Consumes the token and emits an ARP packet.
     fn emit_arp<T: TxToken>(arp_repr: &ArpRepr, tx_token: T) {
         let ether_repr = match arp_repr {
@@ -300,3 +680,63 @@ impl<D, E: Ext> EtherIface<D, E> {
         });
     }
 }
+
+/// Returns the IPv6 address formed by the 64-bit prefix and the modified EUI-64 interface
+/// identifier derived from the Ethernet address.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4291#appendix-A>.
+fn slaac_addr(prefix: &Ipv6Address, ether_addr: &EthernetAddress) -> Ipv6Address {
+    let mac = ether_addr.as_bytes();
+
+    let mut octets = prefix.octets();
+    octets[8..].copy_from_slice(&[
+        mac[0] ^ 0x02,
+        mac[1],
+        mac[2],
+        0xff,
+        0xfe,
+        mac[3],
+        mac[4],
+        mac[5],
+    ]);
+    Ipv6Address::from(octets)
+}
+
+/// Returns the link-local IPv6 address derived from the Ethernet address.
+fn link_local_addr(ether_addr: &EthernetAddress) -> Ipv6Address {
+    const LINK_LOCAL_PREFIX: Ipv6Address = Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 0);
+    slaac_addr(&LINK_LOCAL_PREFIX, ether_addr)
+}
+
+/// Returns the solicited-node multicast address of the IPv6 address.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4291#section-2.7.1>.
+fn solicited_node_addr(addr: &Ipv6Address) -> Ipv6Address {
+    let octets = addr.octets();
+    Ipv6Address::new(
+        0xff02,
+        0,
+        0,
+        0,
+        0,
+        1,
+        0xff00 | octets[13] as u16,
+        u16::from_be_bytes([octets[14], octets[15]]),
+    )
+}
+
+/// Returns the Ethernet address that the IPv6 multicast address maps to.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc2464#section-7>.
+fn multicast_ether_addr(addr: &Ipv6Address) -> EthernetAddress {
+    let octets = addr.octets();
+    EthernetAddress([0x33, 0x33, octets[12], octets[13], octets[14], octets[15]])
+}
+
+/// Returns the Ethernet address that the IPv4 multicast address maps to.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1112#section-6.4>.
+fn ipv4_multicast_ether_addr(addr: &Ipv4Address) -> EthernetAddress {
+    let octets = addr.octets();
+    EthernetAddress([0x01, 0x00, 0x5e, octets[1] & 0x7f, octets[2], octets[3]])
+}
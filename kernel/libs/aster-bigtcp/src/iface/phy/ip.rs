@@ -25,10 +25,10 @@ pub struct IpIface<D, E: Ext> {
 }
 
 impl<D: WithDevice, E: Ext> IpIface<D, E> {
-    // TODO: Support interfaces with multiple IPv4/IPv6 addresses or without IPv4 addresses.
+    // TODO: Support interfaces with multiple IPv4/IPv6 addresses.
     pub fn new(
         driver: D,
-        ip_cidr: Ipv4Cidr,
+        ip_cidr: Option<Ipv4Cidr>,
         ipv6_cidr: Option<Ipv6Cidr>,
         name: CString,
         sched_poll: E::ScheduleNextPoll,
@@ -42,7 +42,9 @@ impl<D: WithDevice, E: Ext> IpIface<D, E> {
             let mut interface = smoltcp::iface::Interface::new(config, device, now);
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
-                ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                if let Some(ip_cidr) = ip_cidr {
+                    ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                }
                 if let Some(ipv6_cidr) = ipv6_cidr {
                     ip_addrs.push(wire::IpCidr::Ipv6(ipv6_cidr)).unwrap();
                 }
@@ -66,7 +68,7 @@ impl<D: WithDevice + 'static, E: Ext> Iface<E> for IpIface<D, E> {
     fn poll(&self) {
         self.driver.with(|device| {
             let next_poll = self.common.poll(
-                device,
+                &mut self.common.tap(device),
                 |data, _iface_cx, tx_token| {
                     if data.is_empty() {
                         return None;
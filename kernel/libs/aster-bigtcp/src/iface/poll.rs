@@ -1,21 +1,30 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{sync::Arc, vec, vec::Vec};
+use alloc::{collections::vec_deque::VecDeque, sync::Arc, vec, vec::Vec};
 
+use aster_softirq::BottomHalfDisabled;
+use ostd::sync::SpinLock;
 use smoltcp::{
     iface::{
         Context,
         packet::{IpPayload, Packet, icmp_reply_payload_len},
     },
-    phy::{ChecksumCapabilities, Device, RxToken, TxToken},
+    phy::{ChecksumCapabilities, Device, DeviceCapabilities, RxToken, TxToken},
     wire::{
-        IPV4_HEADER_LEN, IPV4_MIN_MTU, Icmpv4DstUnreachable, Icmpv4Repr, IpAddress, IpProtocol,
-        IpRepr, Ipv4Address, Ipv4Packet, Ipv4Repr, Ipv6Packet, Ipv6Repr, TcpControl, TcpPacket,
-        TcpRepr, UdpPacket, UdpRepr,
+        IPV4_HEADER_LEN, IPV4_MIN_MTU, Icmpv4DstUnreachable, Icmpv4Packet, Icmpv4Repr,
+        Icmpv6Packet, Icmpv6Repr, IpAddress, IpEndpoint, IpProtocol, IpRepr, Ipv4Address,
+        Ipv4Packet, Ipv4Repr, Ipv6Address, Ipv6ExtHeader, Ipv6ExtHeaderRepr, Ipv6Packet, Ipv6Repr,
+        TcpControl, TcpPacket, TcpRepr, UdpPacket, UdpRepr,
     },
 };
 
-use super::{common::IpPacket, poll_iface::PollableIfaceMut};
+use super::{
+    common::{ForwardedPacket, IpPacket},
+    filter::{self, FilterHook, PacketFilter},
+    multicast::MulticastGroups,
+    poll_iface::PollableIfaceMut,
+    stats::{IfaceCounter, IfaceCounters},
+};
 use crate::{
     ext::Ext,
     socket::{TcpConnectionBg, TcpProcessResult},
@@ -26,6 +35,10 @@ pub(super) struct PollContext<'a, E: Ext> {
     iface: PollableIfaceMut<'a, E>,
     sockets: &'a SocketTable<E>,
     actions: &'a mut Vec<SocketTableAction<E>>,
+    /// The packet filter and the index of the iface, if the packet filter is enabled.
+    filter: Option<(&'a E::PacketFilter, u32)>,
+    multicast: &'a SpinLock<MulticastGroups, BottomHalfDisabled>,
+    counters: &'a IfaceCounters,
 }
 
 /// Socket table actions such as adding or removing TCP connections.
@@ -42,11 +55,17 @@ impl<'a, E: Ext> PollContext<'a, E> {
         iface: PollableIfaceMut<'a, E>,
         sockets: &'a SocketTable<E>,
         actions: &'a mut Vec<SocketTableAction<E>>,
+        filter: Option<(&'a E::PacketFilter, u32)>,
+        multicast: &'a SpinLock<MulticastGroups, BottomHalfDisabled>,
+        counters: &'a IfaceCounters,
     ) -> Self {
         Self {
             iface,
             sockets,
             actions,
+            filter,
+            multicast,
+            counters,
         }
     }
 }
@@ -57,11 +76,12 @@ pub(super) trait FnHelper<A, B, C, O>: FnMut(A, B, C) -> O {}
 impl<A, B, C, O, F> FnHelper<A, B, C, O> for F where F: FnMut(A, B, C) -> O {}
 
 impl<E: Ext> PollContext<'_, E> {
-    pub(super) fn poll_ingress<D, P, Q>(
+    pub(super) fn poll_ingress<D, P, Q, F>(
         &mut self,
         device: &mut D,
         process_phy: &mut P,
         dispatch_phy: &mut Q,
+        forward: &mut F,
     ) where
         D: Device + ?Sized,
         P: for<'pkt, 'cx, 'tx> FnHelper<
@@ -71,6 +91,7 @@ impl<E: Ext> PollContext<'_, E> {
                 Option<(IpPacket<'pkt>, D::TxToken<'tx>)>,
             >,
         Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
+        F: FnMut(&IpRepr, &[u8]) -> bool,
     {
         while let Some((rx_token, tx_token)) = device.receive(self.iface.context().now()) {
             rx_token.consume(|data| {
@@ -79,25 +100,90 @@ impl<E: Ext> PollContext<'_, E> {
                 else {
                     return;
                 };
+                self.counters.inc(IfaceCounter::IpInReceives);
+
+                let filtered;
+                let ip_packet = if self.filter.is_some() {
+                    let mut buffer = ip_packet.to_vec();
+                    if !self.filter_ingress(&mut buffer) {
+                        return;
+                    }
+                    filtered = buffer;
+                    let Some(ip_packet) = IpPacket::new_checked(&filtered) else {
+                        return;
+                    };
+                    ip_packet
+                } else {
+                    ip_packet
+                };
 
                 let reply = match ip_packet {
-                    IpPacket::Ipv4(p) => self.parse_and_process_ipv4(p),
+                    IpPacket::Ipv4(p) => self.parse_and_process_ipv4(p, forward),
                     IpPacket::Ipv6(p) => self.parse_and_process_ipv6(p),
                 };
                 let Some(reply) = reply else { return };
-                dispatch_phy(&reply, self.iface.context_mut(), tx_token);
+                self.dispatch_filtered(&reply, None, tx_token, dispatch_phy);
             });
         }
     }
 
-    fn parse_and_process_ipv4<'pkt>(
+    /// Passes an incoming packet to the [`FilterHook::PreRouting`] hook and, if the packet is
+    /// destined for the local interface, the [`FilterHook::LocalIn`] hook.
+    ///
+    /// This method returns whether the packet is accepted.
+    fn filter_ingress(&self, buffer: &mut [u8]) -> bool {
+        let Some((filter, index)) = self.filter else {
+            return true;
+        };
+
+        if !filter.filter(FilterHook::PreRouting, Some(index), None, buffer) {
+            return false;
+        }
+
+        // The destination address may have been changed by the packet filter (e.g., by DNAT).
+        let Some((ip_repr, _)) = filter::parse_packet(buffer) else {
+            return false;
+        };
+        let dst_addr = ip_repr.dst_addr();
+        if !dst_addr.is_broadcast() && !dst_addr.is_multicast() && !self.is_unicast_local(dst_addr)
+        {
+            // The packet will go through the `FilterHook::Forward` hook if it is forwarded.
+            return true;
+        }
+
+        filter.filter(FilterHook::LocalIn, Some(index), None, buffer)
+    }
+
+    fn parse_and_process_ipv4<'pkt, F>(
         &mut self,
         pkt: Ipv4Packet<&'pkt [u8]>,
-    ) -> Option<Packet<'pkt>> {
+        forward: &mut F,
+    ) -> Option<Packet<'pkt>>
+    where
+        F: FnMut(&IpRepr, &[u8]) -> bool,
+    {
         // Parse the IP header. Ignore the packet if the header is ill-formed.
-        let repr = Ipv4Repr::parse(&pkt, &self.iface.context().checksum_caps()).ok()?;
+        let Ok(repr) = Ipv4Repr::parse(&pkt, &self.iface.context().checksum_caps()) else {
+            self.counters.inc(IfaceCounter::IpInHdrErrors);
+            return None;
+        };
 
-        if !repr.dst_addr.is_broadcast() && !self.is_unicast_local(IpAddress::Ipv4(repr.dst_addr)) {
+        if repr.dst_addr.is_multicast() {
+            // Multicast packets are never forwarded. They are dropped if the multicast group has
+            // not been joined.
+            if !self.has_joined_multicast(IpAddress::Ipv4(repr.dst_addr)) {
+                return None;
+            }
+        } else if !repr.dst_addr.is_broadcast()
+            && !self.is_unicast_local(IpAddress::Ipv4(repr.dst_addr))
+        {
+            // The packet may be destined for another iface or may need to be forwarded, which is
+            // decided by the router.
+            if forward(&IpRepr::Ipv4(repr), pkt.payload()) {
+                self.counters.inc(IfaceCounter::IpForwDatagrams);
+                return None;
+            }
+            self.counters.inc(IfaceCounter::IpInAddrErrors);
             return self.generate_icmp_unreachable(
                 &IpRepr::Ipv4(repr),
                 pkt.payload(),
@@ -106,15 +192,7 @@ impl<E: Ext> PollContext<'_, E> {
         }
 
         let checksum_caps = self.iface.context().checksum_caps();
-        match repr.next_header {
-            IpProtocol::Tcp => {
-                self.parse_and_process_tcp(&IpRepr::Ipv4(repr), pkt.payload(), &checksum_caps)
-            }
-            IpProtocol::Udp => {
-                self.parse_and_process_udp(&IpRepr::Ipv4(repr), pkt.payload(), &checksum_caps)
-            }
-            _ => None,
-        }
+        self.process_ip(&IpRepr::Ipv4(repr), pkt.payload(), &checksum_caps)
     }
 
     fn parse_and_process_ipv6<'pkt>(
@@ -122,25 +200,101 @@ impl<E: Ext> PollContext<'_, E> {
         pkt: Ipv6Packet<&'pkt [u8]>,
     ) -> Option<Packet<'pkt>> {
         // Parse the IPv6 header. Ignore the packet if the header is ill-formed.
-        let repr = Ipv6Repr::parse(&pkt).ok()?;
+        let Ok(repr) = Ipv6Repr::parse(&pkt) else {
+            self.counters.inc(IfaceCounter::IpInHdrErrors);
+            return None;
+        };
 
-        if !self.is_unicast_local(IpAddress::Ipv6(repr.dst_addr)) {
+        if repr.dst_addr.is_multicast() {
+            if !self.has_joined_multicast(IpAddress::Ipv6(repr.dst_addr)) {
+                return None;
+            }
+        } else if !self.is_unicast_local(IpAddress::Ipv6(repr.dst_addr)) {
             // TODO: Generate an IPv6 ICMP unreachable message.
+            self.counters.inc(IfaceCounter::IpInAddrErrors);
             return None;
         }
 
         let checksum_caps = self.iface.context().checksum_caps();
-        match repr.next_header {
-            IpProtocol::Tcp => {
-                self.parse_and_process_tcp(&IpRepr::Ipv6(repr), pkt.payload(), &checksum_caps)
+        self.process_ip(&IpRepr::Ipv6(repr), pkt.payload(), &checksum_caps)
+    }
+
+    /// Processes an IP packet whose destination is the local interface.
+    fn process_ip<'pkt>(
+        &mut self,
+        ip_repr: &IpRepr,
+        ip_payload: &'pkt [u8],
+        checksum_caps: &ChecksumCapabilities,
+    ) -> Option<Packet<'pkt>> {
+        self.counters.inc(IfaceCounter::IpInDelivers);
+
+        // Raw sockets receive copies of the packet, so the packet is always processed further.
+        self.process_raw(ip_repr, ip_payload);
+
+        match (ip_repr, ip_repr.next_header()) {
+            (_, IpProtocol::Tcp) => self.parse_and_process_tcp(ip_repr, ip_payload, checksum_caps),
+            (_, IpProtocol::Udp) => self.parse_and_process_udp(ip_repr, ip_payload, checksum_caps),
+            (IpRepr::Ipv4(ipv4_repr), IpProtocol::Icmp) => {
+                self.parse_and_process_icmpv4(ipv4_repr, ip_payload, checksum_caps)
+            }
+            (IpRepr::Ipv6(ipv6_repr), IpProtocol::Icmpv6) => {
+                self.parse_and_process_icmpv6(ipv6_repr, ip_payload, checksum_caps)
             }
-            IpProtocol::Udp => {
-                self.parse_and_process_udp(&IpRepr::Ipv6(repr), pkt.payload(), &checksum_caps)
+            (IpRepr::Ipv4(_), IpProtocol::Igmp) => {
+                self.multicast.lock().process_igmp(ip_payload);
+                None
+            }
+            (IpRepr::Ipv6(ipv6_repr), IpProtocol::HopByHop) => {
+                self.parse_and_process_hop_by_hop(ipv6_repr, ip_payload, checksum_caps);
+                None
             }
             _ => None,
         }
     }
 
+    /// Processes an IPv6 packet that starts with a Hop-by-Hop Options header.
+    ///
+    /// Only MLD messages, which are always sent with a Hop-by-Hop Options header that contains a
+    /// Router Alert option, are handled here.
+    fn parse_and_process_hop_by_hop(
+        &mut self,
+        ipv6_repr: &Ipv6Repr,
+        ip_payload: &[u8],
+        checksum_caps: &ChecksumCapabilities,
+    ) {
+        let Some(ext_repr) = Ipv6ExtHeader::new_checked(ip_payload)
+            .ok()
+            .and_then(|ext_header| Ipv6ExtHeaderRepr::parse(&ext_header).ok())
+        else {
+            return;
+        };
+        if ext_repr.next_header != IpProtocol::Icmpv6 {
+            return;
+        }
+
+        // The length of the header is in 8-octet units, not including the first 8 octets. See
+        // <https://datatracker.ietf.org/doc/html/rfc8200#section-4.3>.
+        let Some(icmp_payload) = ip_payload.get((ext_repr.length as usize + 1) * 8..) else {
+            return;
+        };
+        let Some(Icmpv6Repr::Mld(mld_repr)) = Icmpv6Packet::new_checked(icmp_payload)
+            .ok()
+            .and_then(|icmp_pkt| {
+                Icmpv6Repr::parse(
+                    &ipv6_repr.src_addr,
+                    &ipv6_repr.dst_addr,
+                    &icmp_pkt,
+                    checksum_caps,
+                )
+                .ok()
+            })
+        else {
+            return;
+        };
+
+        self.multicast.lock().process_mld(&mld_repr);
+    }
+
     fn parse_and_process_tcp<'pkt>(
         &mut self,
         ip_repr: &IpRepr,
@@ -155,14 +309,19 @@ impl<E: Ext> PollContext<'_, E> {
         }
 
         // Parse the TCP header. Ignore the packet if the header is ill-formed.
-        let tcp_pkt = TcpPacket::new_checked(ip_payload).ok()?;
-        let tcp_repr = TcpRepr::parse(
-            &tcp_pkt,
-            &ip_repr.src_addr(),
-            &ip_repr.dst_addr(),
-            checksum_caps,
-        )
-        .ok()?;
+        let Some(tcp_repr) = TcpPacket::new_checked(ip_payload).ok().and_then(|tcp_pkt| {
+            TcpRepr::parse(
+                &tcp_pkt,
+                &ip_repr.src_addr(),
+                &ip_repr.dst_addr(),
+                checksum_caps,
+            )
+            .ok()
+        }) else {
+            self.counters.inc(IfaceCounter::TcpInErrs);
+            return None;
+        };
+        self.counters.inc(IfaceCounter::TcpInSegs);
 
         self.process_tcp_until_outgoing(ip_repr, &tcp_repr)
             .map(|(ip_repr, tcp_repr)| Packet::new(ip_repr, IpPayload::Tcp(tcp_repr)))
@@ -247,11 +406,15 @@ impl<E: Ext> PollContext<'_, E> {
         // Process packets that request to create new connections second.
         if tcp_repr.control == TcpControl::Syn && tcp_repr.ack_number.is_none() {
             let listener_key = ListenerKey::new(ip_repr.dst_addr(), tcp_repr.dst_port);
-            if let Some(listener) = self.sockets.lookup_listener(&listener_key) {
+            if let Some(listener) =
+                self.sockets
+                    .lookup_listener(&listener_key, connection_key.hash(), tcp_repr.payload)
+            {
                 let (processed, new_tcp_conn) =
                     listener.process(&mut self.iface, ip_repr, tcp_repr);
 
                 if let Some(tcp_conn) = new_tcp_conn {
+                    self.counters.inc(IfaceCounter::TcpPassiveOpens);
                     self.actions.push(SocketTableAction::AddTcpConn(tcp_conn));
                 }
 
@@ -271,6 +434,7 @@ impl<E: Ext> PollContext<'_, E> {
             return None;
         }
 
+        self.counters.inc(IfaceCounter::TcpOutRsts);
         Some(smoltcp::socket::tcp::Socket::rst_reply(ip_repr, tcp_repr))
     }
 
@@ -281,16 +445,24 @@ impl<E: Ext> PollContext<'_, E> {
         checksum_caps: &ChecksumCapabilities,
     ) -> Option<Packet<'pkt>> {
         // Parse the UDP header. Ignore the packet if the header is ill-formed.
-        let udp_pkt = UdpPacket::new_checked(ip_payload).ok()?;
-        let udp_repr = UdpRepr::parse(
+        let Ok(udp_pkt) = UdpPacket::new_checked(ip_payload) else {
+            self.counters.inc(IfaceCounter::UdpInErrors);
+            return None;
+        };
+        let Ok(udp_repr) = UdpRepr::parse(
             &udp_pkt,
             &ip_repr.src_addr(),
             &ip_repr.dst_addr(),
             checksum_caps,
-        )
-        .ok()?;
+        ) else {
+            self.counters.inc(IfaceCounter::UdpInErrors);
+            return None;
+        };
 
-        if !self.process_udp(ip_repr, &udp_repr, udp_pkt.payload()) {
+        if self.process_udp(ip_repr, &udp_repr, udp_pkt.payload()) {
+            self.counters.inc(IfaceCounter::UdpInDatagrams);
+        } else {
+            self.counters.inc(IfaceCounter::UdpNoPorts);
             return self.generate_icmp_unreachable(
                 ip_repr,
                 ip_payload,
@@ -302,6 +474,25 @@ impl<E: Ext> PollContext<'_, E> {
     }
 
     fn process_udp(&mut self, ip_repr: &IpRepr, udp_repr: &UdpRepr, udp_payload: &[u8]) -> bool {
+        // Deliver unicast packets to the reuseport group first, if there is one.
+        if ip_repr.dst_addr().is_unicast() {
+            let dst_endpoint = IpEndpoint::new(ip_repr.dst_addr(), udp_repr.dst_port);
+            let hash = ConnectionKey::new(
+                ip_repr.dst_addr(),
+                udp_repr.dst_port,
+                ip_repr.src_addr(),
+                udp_repr.src_port,
+            )
+            .hash();
+            if let Some(socket) =
+                self.sockets
+                    .select_reuseport_udp_socket(&dst_endpoint, hash, udp_payload)
+                && socket.process(self.iface.context_mut(), ip_repr, udp_repr, udp_payload)
+            {
+                return true;
+            }
+        }
+
         let mut processed = false;
 
         for socket in self.sockets.udp_socket_iter() {
@@ -318,6 +509,112 @@ impl<E: Ext> PollContext<'_, E> {
         processed
     }
 
+    fn process_raw(&mut self, ip_repr: &IpRepr, ip_payload: &[u8]) {
+        for socket in self.sockets.raw_socket_iter() {
+            socket.process(ip_repr, ip_payload);
+        }
+    }
+
+    fn parse_and_process_icmpv4<'pkt>(
+        &mut self,
+        ipv4_repr: &Ipv4Repr,
+        ip_payload: &'pkt [u8],
+        checksum_caps: &ChecksumCapabilities,
+    ) -> Option<Packet<'pkt>> {
+        // Parse the ICMP header. Ignore the packet if the header is ill-formed.
+        let Some(icmp_repr) = Icmpv4Packet::new_checked(ip_payload)
+            .ok()
+            .and_then(|icmp_pkt| Icmpv4Repr::parse(&icmp_pkt, checksum_caps).ok())
+        else {
+            self.counters.inc(IfaceCounter::IcmpInErrors);
+            return None;
+        };
+        self.counters.inc(IfaceCounter::IcmpInMsgs);
+
+        // Only echo requests are handled here. Other messages (e.g., echo replies) are delivered
+        // to raw sockets and ping sockets in `process_raw`.
+        let Icmpv4Repr::EchoRequest {
+            ident,
+            seq_no,
+            data,
+        } = icmp_repr
+        else {
+            return None;
+        };
+
+        // Like Linux (i.e., `net.ipv4.icmp_echo_ignore_broadcasts` is 1), echo requests sent to
+        // broadcast addresses are ignored.
+        if !self.is_unicast_local(IpAddress::Ipv4(ipv4_repr.dst_addr)) {
+            return None;
+        }
+
+        let reply_repr = Icmpv4Repr::EchoReply {
+            ident,
+            seq_no,
+            data,
+        };
+        Some(Packet::new_ipv4(
+            Ipv4Repr {
+                src_addr: ipv4_repr.dst_addr,
+                dst_addr: ipv4_repr.src_addr,
+                next_header: IpProtocol::Icmp,
+                payload_len: reply_repr.buffer_len(),
+                hop_limit: 64,
+            },
+            IpPayload::Icmpv4(reply_repr),
+        ))
+    }
+
+    fn parse_and_process_icmpv6<'pkt>(
+        &mut self,
+        ipv6_repr: &Ipv6Repr,
+        ip_payload: &'pkt [u8],
+        checksum_caps: &ChecksumCapabilities,
+    ) -> Option<Packet<'pkt>> {
+        // Parse the ICMPv6 header. Ignore the packet if the header is ill-formed.
+        let icmp_pkt = Icmpv6Packet::new_checked(ip_payload).ok()?;
+        let icmp_repr = Icmpv6Repr::parse(
+            &ipv6_repr.src_addr,
+            &ipv6_repr.dst_addr,
+            &icmp_pkt,
+            checksum_caps,
+        )
+        .ok()?;
+
+        // Only echo requests are handled here. Neighbor discovery messages are handled by the
+        // Ethernet layer, and other messages are delivered to raw sockets and ping sockets in
+        // `process_raw`.
+        let Icmpv6Repr::EchoRequest {
+            ident,
+            seq_no,
+            data,
+        } = icmp_repr
+        else {
+            return None;
+        };
+
+        // Like ICMPv4, echo requests sent to multicast addresses are ignored.
+        if !self.is_unicast_local(IpAddress::Ipv6(ipv6_repr.dst_addr)) {
+            return None;
+        }
+
+        let reply_repr = Icmpv6Repr::EchoReply {
+            ident,
+            seq_no,
+            data,
+        };
+        Some(Packet::new_ipv6(
+            Ipv6Repr {
+                src_addr: ipv6_repr.dst_addr,
+                dst_addr: ipv6_repr.src_addr,
+                next_header: IpProtocol::Icmpv6,
+                payload_len: reply_repr.buffer_len(),
+                hop_limit: 64,
+            },
+            IpPayload::Icmpv6(reply_repr),
+        ))
+    }
+
     fn generate_icmp_unreachable<'pkt>(
         &self,
         ip_repr: &IpRepr,
@@ -365,6 +662,11 @@ impl<E: Ext> PollContext<'_, E> {
         ))
     }
 
+    /// Returns whether the multicast group has been joined by the local interface.
+    fn has_joined_multicast(&self, group: IpAddress) -> bool {
+        self.multicast.lock().has_joined(&group)
+    }
+
     /// Returns whether the destination address is the unicast address of a local interface.
     ///
     /// Note: "local" means that the IP address belongs to the local interface, not to be confused
@@ -376,11 +678,9 @@ impl<E: Ext> PollContext<'_, E> {
                 .context()
                 .ipv4_addr()
                 .is_some_and(|addr| addr == dst_addr),
-            IpAddress::Ipv6(dst_addr) => self
-                .iface
-                .context()
-                .ipv6_addr()
-                .is_some_and(|addr| addr == dst_addr),
+            // An interface may have multiple IPv6 addresses (e.g., a link-local address and
+            // addresses configured by SLAAC), so all of them must be checked.
+            IpAddress::Ipv6(dst_addr) => self.iface.context().has_ip_addr(dst_addr),
         }
     }
 }
@@ -409,9 +709,21 @@ impl<E: Ext> PollContext<'_, E> {
             return did_something_tcp;
         };
 
-        let (did_something_udp, _tx_token) = self.dispatch_udp(tx_token, dispatch_phy);
+        let (did_something_udp, tx_token) = self.dispatch_udp(tx_token, dispatch_phy);
+
+        let Some(tx_token) = tx_token else {
+            return did_something_tcp || did_something_udp;
+        };
+
+        let (did_something_raw, tx_token) = self.dispatch_raw(tx_token, dispatch_phy);
+
+        let Some(tx_token) = tx_token else {
+            return did_something_tcp || did_something_udp || did_something_raw;
+        };
+
+        let did_something_packet = self.dispatch_packet(tx_token);
 
-        did_something_tcp || did_something_udp
+        did_something_tcp || did_something_udp || did_something_raw || did_something_packet
     }
 
     fn dispatch_tcp<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> (bool, Option<T>)
@@ -435,13 +747,21 @@ impl<E: Ext> PollContext<'_, E> {
 
             let (reply, became_dead) =
                 TcpConnectionBg::dispatch(&socket, &mut self.iface, |iface, ip_repr, tcp_repr| {
-                    let mut this = PollContext::new(iface, self.sockets, self.actions);
+                    let mut this = PollContext::new(
+                        iface,
+                        self.sockets,
+                        self.actions,
+                        self.filter,
+                        self.multicast,
+                        self.counters,
+                    );
 
                     if !this.is_unicast_local(ip_repr.dst_addr()) {
-                        dispatch_phy(
+                        this.dispatch_filtered(
                             &Packet::new(ip_repr.clone(), IpPayload::Tcp(*tcp_repr)),
-                            this.iface.context_mut(),
+                            None,
                             tx_token.take().unwrap(),
+                            dispatch_phy,
                         );
                         return None;
                     }
@@ -479,24 +799,31 @@ impl<E: Ext> PollContext<'_, E> {
                         &ip_payload,
                         &ChecksumCapabilities::ignored(),
                     ) {
-                        dispatch_phy(&reply, self.iface.context_mut(), tx_token.take().unwrap());
+                        self.dispatch_filtered(
+                            &reply,
+                            None,
+                            tx_token.take().unwrap(),
+                            dispatch_phy,
+                        );
                     }
                 }
                 (None, Some((ip_repr, tcp_repr))) if !self.is_unicast_local(ip_repr.dst_addr()) => {
-                    dispatch_phy(
+                    self.dispatch_filtered(
                         &Packet::new(ip_repr, IpPayload::Tcp(tcp_repr)),
-                        self.iface.context_mut(),
+                        None,
                         tx_token.take().unwrap(),
+                        dispatch_phy,
                     );
                 }
                 (None, Some((ip_repr, tcp_repr))) => {
                     if let Some((new_ip_repr, new_tcp_repr)) =
                         self.process_tcp_until_outgoing(&ip_repr, &tcp_repr)
                     {
-                        dispatch_phy(
+                        self.dispatch_filtered(
                             &Packet::new(new_ip_repr, IpPayload::Tcp(new_tcp_repr)),
-                            self.iface.context_mut(),
+                            None,
                             tx_token.take().unwrap(),
+                            dispatch_phy,
                         );
                     }
                 }
@@ -535,15 +862,32 @@ impl<E: Ext> PollContext<'_, E> {
             let (cx, pending) = self.iface.inner_mut();
             socket.dispatch(cx, |cx, ip_repr, udp_repr, udp_payload| {
                 let iface = PollableIfaceMut::new(cx, pending);
-                let mut this = PollContext::new(iface, self.sockets, &mut actions);
-
-                if ip_repr.dst_addr().is_broadcast() || !this.is_unicast_local(ip_repr.dst_addr()) {
-                    dispatch_phy(
+                let mut this = PollContext::new(
+                    iface,
+                    self.sockets,
+                    &mut actions,
+                    self.filter,
+                    self.multicast,
+                    self.counters,
+                );
+
+                let dst_addr = ip_repr.dst_addr();
+                if dst_addr.is_broadcast() || !this.is_unicast_local(dst_addr) {
+                    this.dispatch_filtered(
                         &Packet::new(ip_repr.clone(), IpPayload::Udp(*udp_repr, udp_payload)),
-                        this.iface.context_mut(),
+                        None,
                         tx_token.take().unwrap(),
+                        dispatch_phy,
                     );
-                    if !ip_repr.dst_addr().is_broadcast() {
+
+                    // Broadcast packets are also delivered locally. So are multicast packets, if
+                    // the multicast group has been joined and the socket allows looping them back.
+                    let should_loop_back = if dst_addr.is_multicast() {
+                        socket.multicast_loop() && this.has_joined_multicast(dst_addr)
+                    } else {
+                        dst_addr.is_broadcast()
+                    };
+                    if !should_loop_back {
                         return;
                     }
                 }
@@ -578,7 +922,7 @@ impl<E: Ext> PollContext<'_, E> {
                     &ChecksumCapabilities::ignored(),
                 )
             {
-                dispatch_phy(&reply, self.iface.context_mut(), tx_token.take().unwrap());
+                self.dispatch_filtered(&reply, None, tx_token.take().unwrap(), dispatch_phy);
             }
 
             if tx_token.is_none() {
@@ -593,4 +937,277 @@ impl<E: Ext> PollContext<'_, E> {
 
         (did_something, tx_token)
     }
+
+    fn dispatch_raw<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> (bool, Option<T>)
+    where
+        T: TxToken,
+        Q: FnMut(&Packet, &mut Context, T),
+    {
+        let mut tx_token = Some(tx_token);
+        let mut did_something = false;
+
+        for socket in self.sockets.raw_socket_iter() {
+            if !socket.need_dispatch() {
+                continue;
+            }
+
+            let Some((ip_repr, ip_payload)) = socket.dequeue_send() else {
+                continue;
+            };
+            did_something = true;
+
+            let dst_addr = ip_repr.dst_addr();
+            if dst_addr.is_broadcast() || !self.is_unicast_local(dst_addr) {
+                self.dispatch_filtered(
+                    &Packet::new(ip_repr.clone(), IpPayload::Raw(&ip_payload)),
+                    None,
+                    tx_token.take().unwrap(),
+                    dispatch_phy,
+                );
+            }
+            if dst_addr.is_broadcast() || self.is_unicast_local(dst_addr) {
+                // The socket lock has been released in `dequeue_send`, so we can safely process
+                // the packet now, even if it is sent back to the same socket.
+                self.process_ip_until_outgoing(ip_repr, ip_payload, &mut tx_token, dispatch_phy);
+            }
+
+            if tx_token.is_none() {
+                break;
+            }
+        }
+
+        (did_something, tx_token)
+    }
+
+    /// Dispatches a frame queued by a packet socket, if any.
+    ///
+    /// The frames already contain link-layer headers, so they are transmitted as is.
+    fn dispatch_packet<T: TxToken>(&mut self, tx_token: T) -> bool {
+        for socket in self.sockets.packet_socket_iter() {
+            if !socket.need_dispatch() {
+                continue;
+            }
+
+            let Some(frame) = socket.dequeue_send() else {
+                continue;
+            };
+
+            socket.set_sending(true);
+            tx_token.consume(frame.len(), |buffer| buffer.copy_from_slice(&frame));
+            socket.set_sending(false);
+
+            return true;
+        }
+
+        false
+    }
+
+    /// Sends the packets forwarded from other ifaces.
+    pub(super) fn poll_forwarded<D, Q>(
+        &mut self,
+        device: &mut D,
+        forwarded: &SpinLock<VecDeque<ForwardedPacket>, BottomHalfDisabled>,
+        dispatch_phy: &mut Q,
+    ) where
+        D: Device + ?Sized,
+        Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
+    {
+        loop {
+            let Some(packet) = forwarded.lock().pop_front() else {
+                break;
+            };
+            let Some(tx_token) = device.transmit(self.iface.context().now()) else {
+                forwarded.lock().push_front(packet);
+                break;
+            };
+
+            let ForwardedPacket {
+                ip_repr,
+                ip_payload,
+                in_iface,
+            } = packet;
+
+            if self.is_unicast_local(ip_repr.dst_addr()) {
+                // This happens if the packet is received by another iface, but it is destined for
+                // this iface.
+                let Some((ip_repr, ip_payload)) =
+                    self.filter_local_in(ip_repr, ip_payload, in_iface)
+                else {
+                    continue;
+                };
+                self.process_ip_until_outgoing(
+                    ip_repr,
+                    ip_payload,
+                    &mut Some(tx_token),
+                    dispatch_phy,
+                );
+            } else {
+                self.dispatch_filtered(
+                    &Packet::new(ip_repr, IpPayload::Raw(&ip_payload)),
+                    Some(in_iface),
+                    tx_token,
+                    dispatch_phy,
+                );
+            }
+        }
+    }
+
+    /// Sends the pending multicast membership reports.
+    ///
+    /// `src_addrs` contains the IPv4 address and the link-local IPv6 address of the iface, which
+    /// are used as the source addresses of the reports.
+    pub(super) fn poll_multicast_reports<D, Q>(
+        &mut self,
+        device: &mut D,
+        src_addrs: (Option<Ipv4Address>, Option<Ipv6Address>),
+        dispatch_phy: &mut Q,
+    ) where
+        D: Device + ?Sized,
+        Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
+    {
+        while self.multicast.lock().has_pending_reports() {
+            let Some(tx_token) = device.transmit(self.iface.context().now()) else {
+                break;
+            };
+            let Some((ip_repr, ip_payload)) =
+                self.multicast.lock().pop_report(src_addrs.0, src_addrs.1)
+            else {
+                break;
+            };
+
+            self.dispatch_filtered(
+                &Packet::new(ip_repr, IpPayload::Raw(&ip_payload)),
+                None,
+                tx_token,
+                dispatch_phy,
+            );
+        }
+    }
+
+    /// Passes a packet received by another iface, but destined for this iface, to the
+    /// [`FilterHook::LocalIn`] hook.
+    fn filter_local_in(
+        &self,
+        ip_repr: IpRepr,
+        ip_payload: Vec<u8>,
+        in_iface: u32,
+    ) -> Option<(IpRepr, Vec<u8>)> {
+        let Some((filter, _)) = self.filter else {
+            return Some((ip_repr, ip_payload));
+        };
+
+        let mut buffer = filter::emit_packet(&Packet::new(ip_repr, IpPayload::Raw(&ip_payload)));
+        if !filter.filter(FilterHook::LocalIn, Some(in_iface), None, &mut buffer) {
+            return None;
+        }
+
+        let (ip_repr, ip_payload) = filter::parse_packet(&buffer)?;
+        Some((ip_repr, ip_payload.to_vec()))
+    }
+
+    /// Dispatches an outgoing packet if it is accepted by the packet filter.
+    ///
+    /// Locally generated packets go through the [`FilterHook::LocalOut`] hook, while packets
+    /// received by the iface whose index is `in_iface` go through the [`FilterHook::Forward`]
+    /// hook. Then both go through the [`FilterHook::PostRouting`] hook.
+    //
+    // TODO: Packets that are sent to the local interface and processed directly without going
+    // through the device are not passed to the packet filter.
+    fn dispatch_filtered<T, Q>(
+        &mut self,
+        pkt: &Packet,
+        in_iface: Option<u32>,
+        tx_token: T,
+        dispatch_phy: &mut Q,
+    ) where
+        T: TxToken,
+        Q: FnMut(&Packet, &mut Context, T),
+    {
+        // Forwarded packets have been counted when they are received.
+        if in_iface.is_none() {
+            self.count_outgoing(&pkt.ip_repr());
+        }
+
+        let Some((filter, index)) = self.filter else {
+            dispatch_phy(pkt, self.iface.context_mut(), tx_token);
+            return;
+        };
+
+        let mut buffer = filter::emit_packet(pkt);
+        let first_hook = if in_iface.is_some() {
+            FilterHook::Forward
+        } else {
+            FilterHook::LocalOut
+        };
+        for hook in [first_hook, FilterHook::PostRouting] {
+            if !filter.filter(hook, in_iface, Some(index), &mut buffer) {
+                return;
+            }
+        }
+
+        let Some((ip_repr, ip_payload)) = filter::parse_packet(&buffer) else {
+            return;
+        };
+        dispatch_phy(
+            &Packet::new(ip_repr, IpPayload::Raw(ip_payload)),
+            self.iface.context_mut(),
+            tx_token,
+        );
+    }
+
+    /// Updates the counters for a locally generated packet that is sent out.
+    fn count_outgoing(&self, ip_repr: &IpRepr) {
+        self.counters.inc(IfaceCounter::IpOutRequests);
+
+        let counter = match ip_repr.next_header() {
+            IpProtocol::Tcp => IfaceCounter::TcpOutSegs,
+            IpProtocol::Udp => IfaceCounter::UdpOutDatagrams,
+            IpProtocol::Icmp => IfaceCounter::IcmpOutMsgs,
+            _ => return,
+        };
+        self.counters.inc(counter);
+    }
+
+    /// Processes a packet sent to the local interface until an outgoing packet is generated.
+    ///
+    /// Unlike TCP and UDP packets, a raw packet can be of any IP protocol, so the packets
+    /// generated in reply have to be serialized and processed again if they are also sent to the
+    /// local interface.
+    fn process_ip_until_outgoing<T, Q>(
+        &mut self,
+        mut ip_repr: IpRepr,
+        mut ip_payload: Vec<u8>,
+        tx_token: &mut Option<T>,
+        dispatch_phy: &mut Q,
+    ) where
+        T: TxToken,
+        Q: FnMut(&Packet, &mut Context, T),
+    {
+        loop {
+            let Some(reply) =
+                self.process_ip(&ip_repr, &ip_payload, &ChecksumCapabilities::ignored())
+            else {
+                return;
+            };
+
+            let reply_ip_repr = reply.ip_repr();
+            if !self.is_unicast_local(reply_ip_repr.dst_addr()) {
+                if let Some(tx_token) = tx_token.take() {
+                    self.dispatch_filtered(&reply, None, tx_token, dispatch_phy);
+                }
+                return;
+            }
+
+            // Compute the checksums, since raw sockets and ping sockets may verify them.
+            let mut reply_payload = vec![0; reply_ip_repr.payload_len()];
+            reply.emit_payload(
+                &reply_ip_repr,
+                &mut reply_payload,
+                &DeviceCapabilities::default(),
+            );
+
+            ip_repr = reply_ip_repr;
+            ip_payload = reply_payload;
+        }
+    }
 }
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{collections::btree_set::BTreeSet, sync::Arc};
+use alloc::{collections::btree_set::BTreeSet, sync::Arc, vec::Vec};
 use core::{
     borrow::Borrow,
     sync::atomic::{AtomicU64, Ordering},
@@ -49,11 +49,61 @@ impl<E: Ext> PollableIface<E> {
         })
     }
 
-    pub(super) fn prefix_len(&self) -> Option<u8> {
+    pub(super) fn ipv6_addrs(&self) -> Vec<smoltcp::wire::Ipv6Cidr> {
         self.interface
             .ip_addrs()
-            .first()
-            .map(|ip_addr| ip_addr.prefix_len())
+            .iter()
+            .filter_map(|cidr| {
+                if let smoltcp::wire::IpCidr::Ipv6(ipv6_cidr) = cidr {
+                    Some(*ipv6_cidr)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the link-local IPv6 address of the interface, if any.
+    pub(super) fn ipv6_link_local_addr(&self) -> Option<smoltcp::wire::Ipv6Address> {
+        self.interface
+            .ip_addrs()
+            .iter()
+            .find_map(|cidr| match cidr {
+                smoltcp::wire::IpCidr::Ipv6(ipv6_cidr)
+                    if ipv6_cidr.address().is_unicast_link_local() =>
+                {
+                    Some(ipv6_cidr.address())
+                }
+                _ => None,
+            })
+    }
+
+    /// Adds an IPv6 address to the interface.
+    ///
+    /// The address will be ignored if it already exists or if there is no room for more
+    /// addresses.
+    pub(super) fn add_ipv6_addr(&mut self, cidr: smoltcp::wire::Ipv6Cidr) {
+        self.interface.update_ip_addrs(|ip_addrs| {
+            let ip_cidr = smoltcp::wire::IpCidr::Ipv6(cidr);
+            if !ip_addrs.contains(&ip_cidr) {
+                let _ = ip_addrs.push(ip_cidr);
+            }
+        });
+    }
+
+    /// Sets the default IPv6 router of the interface.
+    pub(super) fn set_ipv6_default_router(&mut self, router: smoltcp::wire::Ipv6Address) {
+        let _ = self.interface.routes_mut().add_default_ipv6_route(router);
+    }
+
+    pub(super) fn prefix_len(&self) -> Option<u8> {
+        self.interface.ip_addrs().iter().find_map(|cidr| {
+            if let smoltcp::wire::IpCidr::Ipv4(ipv4_cidr) = cidr {
+                Some(ipv4_cidr.prefix_len())
+            } else {
+                None
+            }
+        })
     }
 
     /// Returns the next poll time.
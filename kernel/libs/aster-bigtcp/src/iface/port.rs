@@ -6,6 +6,8 @@ use smoltcp::wire::{IpAddress, IpEndpoint};
 pub struct BindPortConfig {
     addr: IpAddress,
     kind: PortKind,
+    /// The user ID of the socket owner if port reuse (i.e., `SO_REUSEPORT`) is enabled.
+    reuse_port: Option<u32>,
 }
 
 enum PortKind {
@@ -31,6 +33,7 @@ impl BindPortConfig {
         Self {
             addr: endpoint.addr,
             kind,
+            reuse_port: None,
         }
     }
 
@@ -39,9 +42,18 @@ impl BindPortConfig {
         Self {
             addr: endpoint.addr,
             kind: PortKind::Backlog(endpoint.port),
+            reuse_port: None,
         }
     }
 
+    /// Enables port reuse (i.e., `SO_REUSEPORT`) for the socket owned by the user.
+    ///
+    /// Sockets that enable port reuse can share the same port if they are owned by the same user.
+    pub fn with_reuse_port(mut self, uid: u32) -> Self {
+        self.reuse_port = Some(uid);
+        self
+    }
+
     pub(super) fn is_backlog(&self) -> bool {
         matches!(self.kind, PortKind::Backlog(..))
     }
@@ -53,6 +65,10 @@ impl BindPortConfig {
         )
     }
 
+    pub(super) fn reuse_port(&self) -> Option<u32> {
+        self.reuse_port
+    }
+
     pub(super) fn port(&self) -> Option<u16> {
         match &self.kind {
             PortKind::CanReuse(port) | PortKind::Specified(port) | PortKind::Backlog(port) => {
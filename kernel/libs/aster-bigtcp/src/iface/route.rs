@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use smoltcp::wire::{IpAddress, IpRepr};
+
+/// A trait to make routing decisions that involve more than one iface.
+///
+/// An iface only knows about the networks that it is directly attached to. The routing table,
+/// which decides the next hops of outgoing packets and whether incoming packets should be
+/// forwarded to other ifaces, is maintained by the users of this crate.
+pub trait Router: Send + Sync {
+    /// Returns the next hop for sending a packet from `src_addr` to `dst_addr` through the iface
+    /// whose index is `iface_index`.
+    ///
+    /// If this method returns `None`, the iface will fall back to its own routes.
+    ///
+    /// This method is called with the iface locked, so it must not try to lock any iface.
+    fn next_hop(
+        &self,
+        iface_index: u32,
+        src_addr: &IpAddress,
+        dst_addr: &IpAddress,
+    ) -> Option<IpAddress>;
+
+    /// Handles an IP packet that is received by the iface whose index is `iface_index` but is
+    /// not destined for the iface.
+    ///
+    /// This method returns whether the packet has been taken over (e.g., it has been forwarded
+    /// to another iface or has been dropped on purpose). Otherwise, the iface will reply with an
+    /// ICMP destination unreachable message.
+    ///
+    /// This method is called with the iface locked, so it must not try to lock any iface.
+    /// Packets should be forwarded via [`Iface::enqueue_forwarded`].
+    ///
+    /// [`Iface::enqueue_forwarded`]: crate::iface::Iface::enqueue_forwarded
+    fn forward(&self, iface_index: u32, ip_repr: &IpRepr, ip_payload: &[u8]) -> bool;
+}
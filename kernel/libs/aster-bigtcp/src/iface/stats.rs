@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// A statistics counter of an iface.
+///
+/// The link-layer counters are shown in `/proc/net/dev`, while the others are SNMP MIB counters
+/// shown in `/proc/net/snmp` on Linux.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum IfaceCounter {
+    /// The number of frames received by the device.
+    RxPackets,
+    /// The number of bytes received by the device.
+    RxBytes,
+    /// The number of frames transmitted by the device.
+    TxPackets,
+    /// The number of bytes transmitted by the device.
+    TxBytes,
+    /// The number of packets dropped because the transmission queue is full.
+    TxDropped,
+
+    /// The number of IP datagrams received, including those with errors.
+    IpInReceives,
+    /// The number of IP datagrams discarded because their headers are ill-formed.
+    IpInHdrErrors,
+    /// The number of IP datagrams discarded because they are not destined for the host and
+    /// cannot be forwarded.
+    IpInAddrErrors,
+    /// The number of IP datagrams forwarded to other hosts.
+    IpForwDatagrams,
+    /// The number of IP datagrams delivered to the upper-layer protocols.
+    IpInDelivers,
+    /// The number of IP datagrams generated locally and sent to the device.
+    IpOutRequests,
+
+    /// The number of ICMP messages received.
+    IcmpInMsgs,
+    /// The number of ICMP messages received with errors.
+    IcmpInErrors,
+    /// The number of ICMP messages sent.
+    IcmpOutMsgs,
+
+    /// The number of connections that are actively opened (i.e., by `connect`).
+    TcpActiveOpens,
+    /// The number of connections that are passively opened (i.e., by listeners).
+    TcpPassiveOpens,
+    /// The number of TCP segments received.
+    TcpInSegs,
+    /// The number of TCP segments sent.
+    TcpOutSegs,
+    /// The number of TCP segments received with errors.
+    TcpInErrs,
+    /// The number of TCP segments sent with the RST flag.
+    TcpOutRsts,
+
+    /// The number of UDP datagrams delivered to sockets.
+    UdpInDatagrams,
+    /// The number of UDP datagrams received without any socket bound to the destination port.
+    UdpNoPorts,
+    /// The number of UDP datagrams received with errors.
+    UdpInErrors,
+    /// The number of UDP datagrams sent.
+    UdpOutDatagrams,
+}
+
+impl IfaceCounter {
+    const COUNT: usize = Self::UdpOutDatagrams as usize + 1;
+}
+
+/// The statistics counters of an iface.
+pub(crate) struct IfaceCounters([AtomicU64; IfaceCounter::COUNT]);
+
+impl IfaceCounters {
+    pub(super) const fn new() -> Self {
+        Self([const { AtomicU64::new(0) }; IfaceCounter::COUNT])
+    }
+
+    /// Increments the counter by one.
+    pub(crate) fn inc(&self, counter: IfaceCounter) {
+        self.add(counter, 1);
+    }
+
+    /// Increments the counter by the value.
+    pub(crate) fn add(&self, counter: IfaceCounter, value: u64) {
+        self.0[counter as usize].fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Returns the value of the counter.
+    pub(crate) fn get(&self, counter: IfaceCounter) -> u64 {
+        self.0[counter as usize].load(Ordering::Relaxed)
+    }
+}
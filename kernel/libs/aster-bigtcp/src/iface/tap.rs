@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use smoltcp::{
+    phy::{Device, DeviceCapabilities, RxToken, TxToken},
+    time::Instant,
+    wire::{ETHERNET_HEADER_LEN, EthernetAddress, EthernetFrame},
+};
+
+use super::{common::IfaceCommon, stats::IfaceCounter};
+use crate::{
+    ext::Ext,
+    socket::{FrameType, LinkFrame, PacketSocketBg},
+};
+
+/// The link layer of an iface, as seen by packet sockets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum LinkLayer {
+    /// Ethernet frames with the local Ethernet address.
+    Ethernet(EthernetAddress),
+    /// IP packets with a fake Ethernet header whose addresses are all zeros.
+    ///
+    /// This is how Linux presents packets on loopback devices.
+    Loopback,
+    /// Bare IP packets without link-layer headers.
+    None,
+}
+
+impl LinkLayer {
+    /// Returns the length of the link-layer header.
+    pub(crate) fn header_len(&self) -> usize {
+        match self {
+            Self::Ethernet(_) | Self::Loopback => ETHERNET_HEADER_LEN,
+            Self::None => 0,
+        }
+    }
+}
+
+/// A [`Device`] that delivers copies of the frames going through it to packet sockets.
+///
+/// The frames are also accounted in the link-layer counters of the iface.
+pub(super) struct TapDevice<'a, D: ?Sized, E: Ext> {
+    device: &'a mut D,
+    tap: Tap<'a, E>,
+}
+
+struct Tap<'a, E: Ext> {
+    common: &'a IfaceCommon<E>,
+    sockets: Vec<Arc<PacketSocketBg<E>>>,
+}
+
+impl<'a, D: ?Sized, E: Ext> TapDevice<'a, D, E> {
+    pub(super) fn new(device: &'a mut D, common: &'a IfaceCommon<E>) -> Self {
+        // Take a snapshot of the packet sockets so that the socket table is not locked while
+        // frames are being delivered.
+        let sockets = common.sockets().packet_socket_iter().cloned().collect();
+
+        Self {
+            device,
+            tap: Tap { common, sockets },
+        }
+    }
+}
+
+impl<D: ?Sized, E: Ext> Drop for TapDevice<'_, D, E> {
+    fn drop(&mut self) {
+        for socket in self.tap.sockets.iter() {
+            socket.on_batch_end();
+        }
+    }
+}
+
+impl<D: Device + ?Sized, E: Ext> Device for TapDevice<'_, D, E> {
+    type RxToken<'a>
+        = TapRxToken<'a, D::RxToken<'a>, E>
+    where
+        Self: 'a;
+    type TxToken<'a>
+        = TapTxToken<'a, D::TxToken<'a>, E>
+    where
+        Self: 'a;
+
+    fn receive(&mut self, timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let Self { device, tap } = self;
+        let tap = &*tap;
+        let (rx_token, tx_token) = device.receive(timestamp)?;
+        Some((
+            TapRxToken {
+                token: rx_token,
+                tap,
+            },
+            TapTxToken {
+                token: tx_token,
+                tap,
+            },
+        ))
+    }
+
+    fn transmit(&mut self, timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        let Self { device, tap } = self;
+        let tx_token = device.transmit(timestamp)?;
+        let tap = &*tap;
+        Some(TapTxToken {
+            token: tx_token,
+            tap,
+        })
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.device.capabilities()
+    }
+}
+
+pub(super) struct TapRxToken<'a, T, E: Ext> {
+    token: T,
+    tap: &'a Tap<'a, E>,
+}
+
+impl<T: RxToken, E: Ext> RxToken for TapRxToken<'_, T, E> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        let tap = self.tap;
+        self.token.consume(|data| {
+            tap.account(IfaceCounter::RxPackets, IfaceCounter::RxBytes, data.len());
+            tap.deliver(data, false);
+            f(data)
+        })
+    }
+}
+
+pub(super) struct TapTxToken<'a, T, E: Ext> {
+    token: T,
+    tap: &'a Tap<'a, E>,
+}
+
+impl<T: TxToken, E: Ext> TxToken for TapTxToken<'_, T, E> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let tap = self.tap;
+        self.token.consume(len, |buffer| {
+            let res = f(buffer);
+            tap.account(IfaceCounter::TxPackets, IfaceCounter::TxBytes, buffer.len());
+            tap.deliver(buffer, true);
+            res
+        })
+    }
+}
+
+impl<E: Ext> Tap<'_, E> {
+    fn account(&self, packets: IfaceCounter, bytes: IfaceCounter, len: usize) {
+        let counters = self.common.counters();
+        counters.inc(packets);
+        counters.add(bytes, len as u64);
+    }
+
+    fn deliver(&self, data: &[u8], is_outgoing: bool) {
+        if self.sockets.is_empty() {
+            return;
+        }
+
+        let common = self.common;
+        let mut frame = LinkFrame {
+            data,
+            header_len: 0,
+            protocol: 0,
+            src_addr: None,
+            frame_type: if is_outgoing {
+                FrameType::Outgoing
+            } else {
+                FrameType::Host
+            },
+            iface_index: common.index(),
+            iface_type: common.type_(),
+        };
+
+        match common.link_layer() {
+            LinkLayer::Ethernet(ether_addr) => {
+                let Ok(ether_frame) = EthernetFrame::new_checked(data) else {
+                    return;
+                };
+                let dst_addr = ether_frame.dst_addr();
+
+                frame.header_len = ETHERNET_HEADER_LEN;
+                frame.protocol = ether_frame.ethertype().into();
+                frame.src_addr = Some(ether_frame.src_addr());
+                if !is_outgoing && dst_addr != ether_addr {
+                    frame.frame_type = if dst_addr.is_broadcast() {
+                        FrameType::Broadcast
+                    } else if dst_addr.is_multicast() {
+                        FrameType::Multicast
+                    } else if common.is_promiscuous() {
+                        FrameType::OtherHost
+                    } else {
+                        return;
+                    };
+                }
+                self.deliver_frame(&frame);
+            }
+            LinkLayer::Loopback => {
+                let Some(protocol) = ip_version_to_protocol(data) else {
+                    return;
+                };
+                let mut buffer = vec![0; ETHERNET_HEADER_LEN + data.len()];
+                buffer[ETHERNET_HEADER_LEN - 2..ETHERNET_HEADER_LEN]
+                    .copy_from_slice(&protocol.to_be_bytes());
+                buffer[ETHERNET_HEADER_LEN..].copy_from_slice(data);
+
+                frame.data = &buffer;
+                frame.header_len = ETHERNET_HEADER_LEN;
+                frame.protocol = protocol;
+                frame.src_addr = Some(EthernetAddress([0; 6]));
+                self.deliver_frame(&frame);
+            }
+            LinkLayer::None => {
+                let Some(protocol) = ip_version_to_protocol(data) else {
+                    return;
+                };
+                frame.protocol = protocol;
+                self.deliver_frame(&frame);
+            }
+        }
+    }
+
+    fn deliver_frame(&self, frame: &LinkFrame) {
+        for socket in self.sockets.iter() {
+            if socket.can_process(frame.protocol, frame.frame_type) {
+                socket.process(frame);
+            }
+        }
+    }
+}
+
+/// Returns the Ethernet protocol number of an IP packet.
+fn ip_version_to_protocol(data: &[u8]) -> Option<u16> {
+    const ETH_P_IP: u16 = 0x0800;
+    const ETH_P_IPV6: u16 = 0x86DD;
+
+    match data.first()? >> 4 {
+        4 => Some(ETH_P_IP),
+        6 => Some(ETH_P_IPV6),
+        _ => None,
+    }
+}
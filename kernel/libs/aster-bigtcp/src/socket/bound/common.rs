@@ -16,11 +16,12 @@ use crate::{
 
 pub struct Socket<T: Inner<E>, E: Ext>(pub(super) Takeable<Arc<SocketBg<T, E>>>);
 
-/// [`TcpConnectionInner`], [`TcpListenerInner`], or [`UdpSocketInner`].
+/// [`TcpConnectionInner`], [`TcpListenerInner`], [`UdpSocketInner`], or [`RawSocketInner`].
 ///
 /// [`TcpConnectionInner`]: super::tcp_conn::TcpConnectionInner
 /// [`TcpListenerInner`]: super::tcp_listen::TcpListenerInner
 /// [`UdpSocketInner`]: super::udp::UdpSocketInner
+/// [`RawSocketInner`]: super::raw::RawSocketInner
 pub trait Inner<E: Ext> {
     type BoundPort: Deref<Target = BoundPort<E>>;
     type Observer: SocketEventObserver;
@@ -126,6 +127,14 @@ impl<T: Inner<E>, E: Ext> SocketBg<T, E> {
         }
     }
 
+    pub(crate) fn bound_port(&self) -> &BoundPort<E> {
+        &self.bound
+    }
+
+    pub(super) fn observer(&self) -> Option<&T::Observer> {
+        self.observer.get()
+    }
+
     pub(super) fn notify_events(&self, new_events: SocketEvents) {
         if let Some(observer) = self.observer.get() {
             observer.on_events(new_events);
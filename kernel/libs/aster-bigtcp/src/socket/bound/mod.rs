@@ -1,11 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod common;
+mod raw;
 mod tcp_conn;
+mod tcp_gso;
 mod tcp_listen;
 mod udp;
 
 pub use common::NeedIfacePoll;
+pub(crate) use common::{Inner, SocketBg};
+pub(crate) use raw::RawSocketBg;
+pub use raw::{RawSocket, RawSocketKind};
 pub use tcp_conn::{ConnectState, RawTcpSocketExt, TcpConnection};
 pub(crate) use tcp_conn::{TcpConnectionBg, TcpProcessResult};
 pub use tcp_listen::TcpListener;
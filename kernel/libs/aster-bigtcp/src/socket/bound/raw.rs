@@ -0,0 +1,365 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{collections::vec_deque::VecDeque, sync::Arc, vec, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_softirq::BottomHalfDisabled;
+use ostd::sync::SpinLock;
+use smoltcp::{
+    phy::ChecksumCapabilities,
+    wire::{
+        Icmpv4Message, Icmpv4Packet, Icmpv6Message, Icmpv6Packet, IpAddress, IpProtocol, IpRepr,
+        Ipv4Packet, Ipv4Repr,
+    },
+};
+
+use super::common::{Inner, Socket, SocketBg};
+use crate::{
+    errors::raw::{RecvError, SendError},
+    ext::Ext,
+    iface::BoundRawPort,
+    socket::event::SocketEvents,
+};
+
+pub type RawSocket<E> = Socket<RawSocketInner, E>;
+
+/// The kind of a [`RawSocket`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RawSocketKind {
+    /// A raw IP socket.
+    ///
+    /// The socket receives a copy of every packet of the bound IP protocol. For IPv4, the packets
+    /// include the IP header. For IPv6, the packets start at the IP payload.
+    Ip,
+    /// An ICMP ping socket.
+    ///
+    /// The socket sends ICMP echo requests and receives the ICMP echo replies with the bound
+    /// identifier. The packets start at the ICMP header.
+    Ping,
+}
+
+/// States needed by [`RawSocketBg`].
+pub struct RawSocketInner {
+    kind: RawSocketKind,
+    queues: SpinLock<RawQueues, BottomHalfDisabled>,
+    need_dispatch: AtomicBool,
+}
+
+struct RawQueues {
+    recv: VecDeque<(IpAddress, Vec<u8>)>,
+    recv_len: usize,
+    send: VecDeque<(IpRepr, Vec<u8>)>,
+    send_len: usize,
+}
+
+// Raw socket buffer sizes:
+const RAW_RECV_BUF_LEN: usize = 65536;
+const RAW_SEND_BUF_LEN: usize = 65536;
+
+impl<E: Ext> Inner<E> for RawSocketInner {
+    type BoundPort = BoundRawPort<E>;
+    type Observer = E::RawEventObserver;
+
+    fn on_drop(this: &Arc<SocketBg<Self, E>>) {
+        // A raw socket can be removed immediately.
+        this.bound.iface().common().remove_raw_socket(this);
+    }
+}
+
+pub(crate) type RawSocketBg<E> = SocketBg<RawSocketInner, E>;
+
+impl<E: Ext> RawSocketBg<E> {
+    /// Tries to process an incoming packet and returns whether the packet is processed.
+    ///
+    /// Unlike TCP or UDP sockets, a raw socket only receives a copy of the packet. The packet
+    /// should be further processed by the network stack as usual.
+    pub(crate) fn process(&self, ip_repr: &IpRepr, ip_payload: &[u8]) -> bool {
+        let local_addr = *self.bound.addr();
+        if local_addr.version() != ip_repr.version() {
+            return false;
+        }
+        // A socket bound to the unspecified address receives packets to any local address.
+        if !local_addr.is_unspecified()
+            && ip_repr.dst_addr().is_unicast()
+            && ip_repr.dst_addr() != local_addr
+        {
+            return false;
+        }
+
+        let data = match self.inner.kind {
+            RawSocketKind::Ip => {
+                if u16::from(u8::from(ip_repr.next_header())) != self.bound.port() {
+                    return false;
+                }
+                match ip_repr {
+                    // The IP options are not preserved, so the header is always 20 bytes long.
+                    IpRepr::Ipv4(_) => {
+                        let header_len = ip_repr.header_len();
+                        let mut data = vec![0; header_len + ip_payload.len()];
+                        ip_repr.emit(&mut data[..], &ChecksumCapabilities::default());
+                        data[header_len..].copy_from_slice(ip_payload);
+                        data
+                    }
+                    IpRepr::Ipv6(_) => ip_payload.to_vec(),
+                }
+            }
+            RawSocketKind::Ping => {
+                if !self.is_echo_reply(ip_repr, ip_payload) {
+                    return false;
+                }
+                ip_payload.to_vec()
+            }
+        };
+
+        let mut queues = self.inner.queues.lock();
+        // Drop the packet silently if the receive queue is full.
+        if queues.recv_len + data.len() <= RAW_RECV_BUF_LEN {
+            queues.recv_len += data.len();
+            queues.recv.push_back((ip_repr.src_addr(), data));
+            drop(queues);
+
+            self.notify_events(SocketEvents::CAN_RECV);
+        }
+
+        true
+    }
+
+    fn is_echo_reply(&self, ip_repr: &IpRepr, ip_payload: &[u8]) -> bool {
+        match ip_repr {
+            IpRepr::Ipv4(ipv4_repr) if ipv4_repr.next_header == IpProtocol::Icmp => {
+                let Ok(packet) = Icmpv4Packet::new_checked(ip_payload) else {
+                    return false;
+                };
+                packet.msg_type() == Icmpv4Message::EchoReply
+                    && packet.echo_ident() == self.bound.port()
+                    && packet.verify_checksum()
+            }
+            IpRepr::Ipv6(ipv6_repr) if ipv6_repr.next_header == IpProtocol::Icmpv6 => {
+                let Ok(packet) = Icmpv6Packet::new_checked(ip_payload) else {
+                    return false;
+                };
+                packet.msg_type() == Icmpv6Message::EchoReply
+                    && packet.echo_ident() == self.bound.port()
+                    && packet.verify_checksum(&ipv6_repr.src_addr, &ipv6_repr.dst_addr)
+            }
+            _ => false,
+        }
+    }
+
+    /// Dequeues an outgoing packet, if any.
+    ///
+    /// The packet is returned instead of being dispatched under the lock, so that it can be
+    /// looped back to other raw sockets (including this one) without deadlocks.
+    pub(crate) fn dequeue_send(&self) -> Option<(IpRepr, Vec<u8>)> {
+        let mut queues = self.inner.queues.lock();
+
+        let packet = queues.send.pop_front();
+        if let Some((_, data)) = packet.as_ref() {
+            queues.send_len -= data.len();
+        }
+
+        self.inner
+            .need_dispatch
+            .store(!queues.send.is_empty(), Ordering::Relaxed);
+        drop(queues);
+
+        if packet.is_some() {
+            // Dequeuing a packet means that we can queue more packets.
+            self.notify_events(SocketEvents::CAN_SEND);
+        }
+
+        packet
+    }
+
+    /// Returns whether the socket _may_ generate an outgoing packet.
+    ///
+    /// The check is intended to be lock-free and fast, but may have false positives.
+    pub(crate) fn need_dispatch(&self) -> bool {
+        self.inner.need_dispatch.load(Ordering::Relaxed)
+    }
+}
+
+impl<E: Ext> RawSocket<E> {
+    /// Creates a socket from a bound raw IP protocol or ICMP echo identifier.
+    ///
+    /// The `kind` must match the way `bound` is obtained, i.e., [`RawSocketKind::Ip`] for
+    /// [`bind_raw`] and [`RawSocketKind::Ping`] for [`bind_icmp`].
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    ///
+    /// [`bind_raw`]: crate::iface::Iface::bind_raw
+    /// [`bind_icmp`]: crate::iface::Iface::bind_icmp
+    pub fn new_bind(
+        bound: BoundRawPort<E>,
+        kind: RawSocketKind,
+        observer: E::RawEventObserver,
+    ) -> Self {
+        let inner = RawSocketInner {
+            kind,
+            queues: SpinLock::new(RawQueues {
+                recv: VecDeque::new(),
+                recv_len: 0,
+                send: VecDeque::new(),
+                send_len: 0,
+            }),
+            need_dispatch: AtomicBool::new(false),
+        };
+
+        let socket = Self::new(bound, inner);
+        socket.init_observer(observer);
+        socket
+            .iface()
+            .common()
+            .register_raw_socket(socket.inner().clone());
+
+        socket
+    }
+
+    /// Returns the kind of the socket.
+    pub fn kind(&self) -> RawSocketKind {
+        self.0.inner.kind
+    }
+
+    /// Sends a packet from the source address to the destination address.
+    ///
+    /// The source address must be the bound address, unless the socket is bound to the
+    /// unspecified address, in which case it can be any address of the iface.
+    ///
+    /// For raw IP sockets, `data` is the IP payload, unless `hdrincl` is true, in which case
+    /// `data` should start with an IPv4 header. For ping sockets, `data` should be an ICMP echo
+    /// request, whose identifier and checksum will be filled in.
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn send(
+        &self,
+        src_addr: IpAddress,
+        dst_addr: IpAddress,
+        hop_limit: u8,
+        hdrincl: bool,
+        mut data: Vec<u8>,
+    ) -> Result<(), SendError> {
+        let bound_addr = *self.0.bound.addr();
+        if !bound_addr.is_unspecified() && src_addr != bound_addr {
+            return Err(SendError::Unaddressable);
+        }
+
+        let local_addr = src_addr;
+        if local_addr.is_unspecified() || local_addr.version() != dst_addr.version() {
+            return Err(SendError::Unaddressable);
+        }
+        if data.len() > RAW_SEND_BUF_LEN {
+            return Err(SendError::TooLarge);
+        }
+
+        let ip_repr = match (self.0.inner.kind, local_addr, dst_addr) {
+            (RawSocketKind::Ip, IpAddress::Ipv4(local_addr), _) if hdrincl => {
+                let packet = Ipv4Packet::new_checked(&data).map_err(|_| SendError::Malformed)?;
+                let mut ipv4_repr = Ipv4Repr::parse(&packet, &ChecksumCapabilities::ignored())
+                    .map_err(|_| SendError::Malformed)?;
+                // Like Linux, fill in the source address if it is left unspecified.
+                if ipv4_repr.src_addr.is_unspecified() {
+                    ipv4_repr.src_addr = local_addr;
+                }
+                let header_len = packet.header_len() as usize;
+                let total_len = packet.total_len() as usize;
+                data.truncate(total_len);
+                data.drain(..header_len);
+                IpRepr::Ipv4(ipv4_repr)
+            }
+            (RawSocketKind::Ip, IpAddress::Ipv4(_), _) => {
+                let protocol = IpProtocol::from(self.0.bound.port() as u8);
+                IpRepr::new(local_addr, dst_addr, protocol, data.len(), hop_limit)
+            }
+            (RawSocketKind::Ip, IpAddress::Ipv6(local_addr), IpAddress::Ipv6(dst_addr)) => {
+                let protocol = IpProtocol::from(self.0.bound.port() as u8);
+                // Like Linux, the checksum of ICMPv6 messages is always computed by the kernel.
+                if protocol == IpProtocol::Icmpv6 {
+                    let mut packet =
+                        Icmpv6Packet::new_checked(&mut data).map_err(|_| SendError::Malformed)?;
+                    packet.fill_checksum(&local_addr, &dst_addr);
+                }
+                IpRepr::new(
+                    local_addr.into(),
+                    dst_addr.into(),
+                    protocol,
+                    data.len(),
+                    hop_limit,
+                )
+            }
+            (RawSocketKind::Ping, IpAddress::Ipv4(_), _) => {
+                let mut packet =
+                    Icmpv4Packet::new_checked(&mut data).map_err(|_| SendError::Malformed)?;
+                if packet.msg_type() != Icmpv4Message::EchoRequest || packet.msg_code() != 0 {
+                    return Err(SendError::Malformed);
+                }
+                packet.set_echo_ident(self.0.bound.port());
+                packet.fill_checksum();
+                IpRepr::new(
+                    local_addr,
+                    dst_addr,
+                    IpProtocol::Icmp,
+                    data.len(),
+                    hop_limit,
+                )
+            }
+            (RawSocketKind::Ping, IpAddress::Ipv6(local_addr), IpAddress::Ipv6(dst_addr)) => {
+                let mut packet =
+                    Icmpv6Packet::new_checked(&mut data).map_err(|_| SendError::Malformed)?;
+                if packet.msg_type() != Icmpv6Message::EchoRequest || packet.msg_code() != 0 {
+                    return Err(SendError::Malformed);
+                }
+                packet.set_echo_ident(self.0.bound.port());
+                packet.fill_checksum(&local_addr, &dst_addr);
+                IpRepr::new(
+                    local_addr.into(),
+                    dst_addr.into(),
+                    IpProtocol::Icmpv6,
+                    data.len(),
+                    hop_limit,
+                )
+            }
+            (_, IpAddress::Ipv6(_), IpAddress::Ipv4(_)) => unreachable!(),
+        };
+
+        let mut queues = self.0.inner.queues.lock();
+        if queues.send_len + data.len() > RAW_SEND_BUF_LEN {
+            return Err(SendError::BufferFull);
+        }
+        queues.send_len += data.len();
+        queues.send.push_back((ip_repr, data));
+
+        self.0.inner.need_dispatch.store(true, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Receives a packet.
+    ///
+    /// `f` will be called with the packet and the source address.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn recv<F, R>(&self, f: F) -> Result<R, RecvError>
+    where
+        F: FnOnce(&[u8], IpAddress) -> R,
+    {
+        let mut queues = self.0.inner.queues.lock();
+
+        let Some((src_addr, data)) = queues.recv.pop_front() else {
+            return Err(RecvError::Exhausted);
+        };
+        queues.recv_len -= data.len();
+        drop(queues);
+
+        Ok(f(&data, src_addr))
+    }
+
+    /// Returns whether there are packets to receive.
+    pub fn can_recv(&self) -> bool {
+        !self.0.inner.queues.lock().recv.is_empty()
+    }
+
+    /// Returns whether there is space to queue more packets to send.
+    pub fn can_send(&self) -> bool {
+        self.0.inner.queues.lock().send_len < RAW_SEND_BUF_LEN
+    }
+}
@@ -19,15 +19,17 @@ use smoltcp::{
 
 use super::{
     common::{Inner, NeedIfacePoll, Socket, SocketBg},
+    tcp_gso::TcpSuperSegment,
     tcp_listen::TcpListenerBg,
 };
 use crate::{
     define_boolean_value,
     errors::tcp::{ConnectError, IoError, RecvError, SendError},
     ext::Ext,
-    iface::{BoundTcpPort, PollKey, PollableIfaceMut},
+    iface::{BoundTcpPort, IfaceCounter, PollKey, PollableIfaceMut},
     socket::{
         event::SocketEvents,
+        info::TcpInfo,
         option::{RawTcpOption, RawTcpSetOption},
         unbound::{RawTcpSocket, new_tcp_socket},
     },
@@ -333,6 +335,8 @@ impl<E: Ext> TcpConnection<E> {
         let res = sockets.insert_connection(connection.inner().clone());
         debug_assert!(res.is_ok());
 
+        iface.common().counters().inc(IfaceCounter::TcpActiveOpens);
+
         Ok(connection)
     }
 
@@ -637,6 +641,22 @@ impl<E: Ext> RawTcpSetOption for TcpConnection<E> {
 }
 
 impl<E: Ext> TcpConnectionBg<E> {
+    /// Returns a snapshot of the state of the connection.
+    pub(crate) fn info(&self) -> TcpInfo<'_, E> {
+        let socket = self.inner.lock();
+
+        TcpInfo {
+            state: socket.state(),
+            local_endpoint: socket
+                .local_endpoint()
+                .unwrap_or_else(|| self.bound.endpoint()),
+            remote_endpoint: socket.remote_endpoint(),
+            recv_queue: socket.recv_queue(),
+            send_queue: socket.send_queue(),
+            observer: self.observer(),
+        }
+    }
+
     pub(crate) const fn poll_key(&self) -> &PollKey {
         &self.inner.poll_key
     }
@@ -745,12 +765,47 @@ impl<E: Ext> TcpConnectionBg<E> {
 
         let mut reply = None;
         let (cx, pending) = iface.inner_mut();
-        socket
-            .dispatch(cx, |cx, (ip_repr, tcp_repr)| {
+        let gso_max_size = self.bound.iface().common().gso_max_size();
+        if gso_max_size == 0 {
+            socket
+                .dispatch(cx, |cx, (ip_repr, tcp_repr)| {
+                    reply = dispatch(PollableIfaceMut::new(cx, pending), &ip_repr, &tcp_repr);
+                    Ok::<(), ()>(())
+                })
+                .unwrap();
+        } else {
+            let mut dispatch = Some(dispatch);
+            let mut super_segment = TcpSuperSegment::new(gso_max_size);
+
+            // Collect as many segments as possible into the super-segment. If a segment cannot
+            // be merged, we report an error so that the socket will not consider it sent. The
+            // segment will be generated again the next time the socket is dispatched.
+            loop {
+                let mut has_segment = false;
+                let result = socket.dispatch(cx, |cx, (ip_repr, tcp_repr)| {
+                    has_segment = true;
+                    if super_segment.try_push(&ip_repr, &tcp_repr, cx.caps.ip_mtu()) {
+                        return Ok(());
+                    }
+                    if !super_segment.is_empty() {
+                        return Err(());
+                    }
+                    // The segment cannot start a super-segment, so send it as usual.
+                    let dispatch = dispatch.take().unwrap();
+                    reply = dispatch(PollableIfaceMut::new(cx, pending), &ip_repr, &tcp_repr);
+                    Ok(())
+                });
+                if result.is_err() || !has_segment || dispatch.is_none() || super_segment.is_ended()
+                {
+                    break;
+                }
+            }
+
+            if let Some((ip_repr, tcp_repr)) = super_segment.build() {
+                let dispatch = dispatch.unwrap();
                 reply = dispatch(PollableIfaceMut::new(cx, pending), &ip_repr, &tcp_repr);
-                Ok::<(), ()>(())
-            })
-            .unwrap();
+            }
+        }
 
         // `dispatch` can return a packet in response to the generated packet. If the socket
         // accepts the packet, we can process it directly.
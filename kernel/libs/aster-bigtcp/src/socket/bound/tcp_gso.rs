@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::vec::Vec;
+
+use smoltcp::wire::{IpRepr, TcpControl, TcpRepr, TcpSeqNumber};
+
+/// A GSO super-segment built from consecutive TCP segments of a connection.
+///
+/// If a connection has enough data to send, it generates multiple full-sized segments in a row.
+/// When the device supports TCP segmentation offload (TSO), these segments are merged into a
+/// super-segment, which is sent to the device at once. The device will split the super-segment
+/// into segments that fit in the MTU again.
+///
+/// The device may split the super-segment at different positions. This is fine as long as the
+/// resulting segments are no larger than the original ones, which is guaranteed because only
+/// full-sized segments (i.e., segments that fill the MTU) can start a super-segment.
+pub(super) struct TcpSuperSegment {
+    max_size: usize,
+    header: Option<SuperSegmentHeader>,
+    payload: Vec<u8>,
+    is_ended: bool,
+}
+
+struct SuperSegmentHeader {
+    ip_repr: IpRepr,
+    /// The sequence number of the first segment.
+    seq_number: TcpSeqNumber,
+    /// The TCP header of the last segment.
+    ///
+    /// The acknowledgment number, the window, and the control flag of the last segment are
+    /// the most up-to-date, so they are used as those of the super-segment.
+    tcp_repr: TcpRepr<'static>,
+    /// The payload length of the first segment.
+    segment_len: usize,
+}
+
+impl TcpSuperSegment {
+    /// Creates an empty super-segment whose IP packet is no larger than `max_size` bytes.
+    pub(super) fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            header: None,
+            payload: Vec::new(),
+            is_ended: false,
+        }
+    }
+
+    /// Tries to append the segment to the super-segment.
+    ///
+    /// `ip_mtu` is the MTU of the IP layer, which determines whether a segment is full-sized.
+    ///
+    /// This method returns `false` if the segment cannot be appended. In this case, the
+    /// super-segment is left untouched.
+    pub(super) fn try_push(&mut self, ip_repr: &IpRepr, tcp_repr: &TcpRepr, ip_mtu: usize) -> bool {
+        let Some(header) = self.header.as_mut() else {
+            let header_len = ip_repr.header_len() + tcp_repr.header_len();
+            if tcp_repr.control != TcpControl::None
+                || header_len + tcp_repr.payload.len() < ip_mtu
+                || header_len + tcp_repr.payload.len() >= self.max_size
+            {
+                return false;
+            }
+
+            self.header = Some(SuperSegmentHeader {
+                ip_repr: ip_repr.clone(),
+                seq_number: tcp_repr.seq_number,
+                tcp_repr: Self::strip_payload(tcp_repr),
+                segment_len: tcp_repr.payload.len(),
+            });
+            self.payload.extend_from_slice(tcp_repr.payload);
+            return true;
+        };
+
+        if self.is_ended {
+            return false;
+        }
+
+        // Pure ACKs cannot be merged. Segments with the SYN or RST flag are never generated in
+        // the middle of the data.
+        let can_merge = match tcp_repr.control {
+            TcpControl::None | TcpControl::Psh => !tcp_repr.payload.is_empty(),
+            TcpControl::Fin => true,
+            TcpControl::Syn | TcpControl::Rst => false,
+        };
+        let header_len = header.ip_repr.header_len() + header.tcp_repr.header_len();
+        if !can_merge
+            || tcp_repr.seq_number != header.seq_number + self.payload.len()
+            || tcp_repr.header_len() != header.tcp_repr.header_len()
+            || tcp_repr.payload.len() > header.segment_len
+            || header_len + self.payload.len() + tcp_repr.payload.len() > self.max_size
+        {
+            return false;
+        }
+
+        header.tcp_repr = Self::strip_payload(tcp_repr);
+        self.payload.extend_from_slice(tcp_repr.payload);
+        // Only the last segment can be shorter or have the PSH or FIN flag.
+        self.is_ended =
+            tcp_repr.payload.len() < header.segment_len || tcp_repr.control != TcpControl::None;
+
+        true
+    }
+
+    fn strip_payload(tcp_repr: &TcpRepr) -> TcpRepr<'static> {
+        TcpRepr {
+            src_port: tcp_repr.src_port,
+            dst_port: tcp_repr.dst_port,
+            control: tcp_repr.control,
+            seq_number: tcp_repr.seq_number,
+            ack_number: tcp_repr.ack_number,
+            window_len: tcp_repr.window_len,
+            window_scale: tcp_repr.window_scale,
+            max_seg_size: tcp_repr.max_seg_size,
+            sack_permitted: tcp_repr.sack_permitted,
+            sack_ranges: tcp_repr.sack_ranges,
+            timestamp: tcp_repr.timestamp,
+            payload: &[],
+        }
+    }
+
+    /// Returns whether no segments have been appended.
+    pub(super) fn is_empty(&self) -> bool {
+        self.header.is_none()
+    }
+
+    /// Returns whether no more segments can be appended.
+    pub(super) fn is_ended(&self) -> bool {
+        self.is_ended
+    }
+
+    /// Returns the headers of the super-segment, or `None` if the super-segment is empty.
+    pub(super) fn build(&self) -> Option<(IpRepr, TcpRepr<'_>)> {
+        let header = self.header.as_ref()?;
+
+        let tcp_repr = TcpRepr {
+            seq_number: header.seq_number,
+            payload: &self.payload,
+            ..header.tcp_repr
+        };
+        let mut ip_repr = header.ip_repr.clone();
+        ip_repr.set_payload_len(tcp_repr.buffer_len());
+
+        Some((ip_repr, tcp_repr))
+    }
+}
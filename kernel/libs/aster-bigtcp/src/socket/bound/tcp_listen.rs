@@ -19,6 +19,7 @@ use crate::{
     ext::Ext,
     iface::{BindPortConfig, BoundTcpPort, PollableIfaceMut},
     socket::{
+        info::{TcpInfo, TcpState},
         option::{RawTcpOption, RawTcpSetOption},
         unbound::{RawTcpSocket, new_tcp_socket},
     },
@@ -81,7 +82,7 @@ impl<E: Ext> TcpListener<E> {
 
         let listener_key = ListenerKey::new(local_endpoint.addr, local_endpoint.port);
 
-        if sockets.lookup_listener(&listener_key).is_some() {
+        if !sockets.can_insert_listener(&listener_key, bound.reuse_port()) {
             return Err((bound, ListenError::AddressInUse));
         }
 
@@ -187,6 +188,20 @@ impl<E: Ext> TcpListenerBg<E> {
     pub(crate) const fn listener_key(&self) -> &ListenerKey {
         &self.inner.listener_key
     }
+
+    /// Returns a snapshot of the state of the listener.
+    pub(crate) fn info(&self) -> TcpInfo<'_, E> {
+        let backlog = self.inner.backlog.lock();
+
+        TcpInfo {
+            state: TcpState::Listen,
+            local_endpoint: self.bound.endpoint(),
+            remote_endpoint: None,
+            recv_queue: backlog.connected.len(),
+            send_queue: backlog.max_conn,
+            observer: self.observer(),
+        }
+    }
 }
 
 impl<E: Ext> TcpListenerBg<E> {
@@ -232,19 +247,22 @@ impl<E: Ext> TcpListenerBg<E> {
             socket
         };
 
-        let conn = TcpConnection::new_cyclic(
-            self.bound
-                .iface()
-                .bind_tcp(BindPortConfig::new_backlog(self.bound.endpoint()))
-                .unwrap(),
-            |weak| {
+        // Like Linux, new connections inherit `SO_REUSEPORT` from the listener so that the
+        // listener's reuseport group can still accept new members.
+        let config = BindPortConfig::new_backlog(self.bound.endpoint());
+        let config = match self.bound.reuse_port() {
+            Some(uid) => config.with_reuse_port(uid),
+            None => config,
+        };
+
+        let conn =
+            TcpConnection::new_cyclic(self.bound.iface().bind_tcp(config).unwrap(), |weak| {
                 TcpConnectionInner::new(
                     core::mem::replace(&mut backlog.socket, new_socket),
                     Some(self.clone()),
                     weak,
                 )
-            },
-        );
+            });
         let conn_bg = conn.inner().clone();
 
         let old_conn = backlog.connecting.insert(*conn_bg.connection_key(), conn);
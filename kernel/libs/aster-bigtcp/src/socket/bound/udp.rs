@@ -1,14 +1,14 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use alloc::{boxed::Box, sync::Arc};
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use aster_softirq::BottomHalfDisabled;
 use ostd::sync::SpinLock;
 use smoltcp::{
     iface::Context,
     socket::udp::UdpMetadata,
-    wire::{IpRepr, UdpRepr},
+    wire::{IpAddress, IpRepr, UdpRepr},
 };
 
 use super::common::{Inner, Socket, SocketBg};
@@ -16,7 +16,7 @@ use crate::{
     errors::udp::SendError,
     ext::Ext,
     iface::BoundUdpPort,
-    socket::{RawUdpSocket, event::SocketEvents, unbound::new_udp_socket},
+    socket::{RawUdpSocket, event::SocketEvents, info::UdpInfo, unbound::new_udp_socket},
 };
 
 pub type UdpSocket<E> = Socket<UdpSocketInner, E>;
@@ -25,6 +25,8 @@ pub type UdpSocket<E> = Socket<UdpSocketInner, E>;
 pub struct UdpSocketInner {
     socket: SpinLock<Box<RawUdpSocket>, BottomHalfDisabled>,
     need_dispatch: AtomicBool,
+    multicast_hop_limit: AtomicU8,
+    multicast_loop: AtomicBool,
 }
 
 impl<E: Ext> Inner<E> for UdpSocketInner {
@@ -78,6 +80,31 @@ impl<E: Ext> UdpSocketBg<E> {
 
         socket
             .dispatch(cx, |cx, _meta, (ip_repr, udp_repr, udp_payload)| {
+                // A socket bound to a multicast group has no unicast source address. Like Linux,
+                // the address of the iface is used instead.
+                let src_addr = match ip_repr.src_addr() {
+                    IpAddress::Ipv4(addr) if addr.is_multicast() => match cx.ipv4_addr() {
+                        Some(iface_addr) => IpAddress::Ipv4(iface_addr),
+                        None => return Ok(()),
+                    },
+                    // TODO: Select the source address for IPv6 sockets bound to multicast groups.
+                    IpAddress::Ipv6(addr) if addr.is_multicast() => return Ok(()),
+                    src_addr => src_addr,
+                };
+
+                let hop_limit = if ip_repr.dst_addr().is_multicast() {
+                    self.inner.multicast_hop_limit.load(Ordering::Relaxed)
+                } else {
+                    ip_repr.hop_limit()
+                };
+
+                let ip_repr = IpRepr::new(
+                    src_addr,
+                    ip_repr.dst_addr(),
+                    ip_repr.next_header(),
+                    ip_repr.payload_len(),
+                    hop_limit,
+                );
                 dispatch(cx, &ip_repr, &udp_repr, udp_payload);
                 Ok::<(), ()>(())
             })
@@ -97,6 +124,23 @@ impl<E: Ext> UdpSocketBg<E> {
     pub(crate) fn need_dispatch(&self) -> bool {
         self.inner.need_dispatch.load(Ordering::Relaxed)
     }
+
+    /// Returns whether outgoing multicast packets should be looped back to local sockets.
+    pub(crate) fn multicast_loop(&self) -> bool {
+        self.inner.multicast_loop.load(Ordering::Relaxed)
+    }
+
+    /// Returns a snapshot of the state of the socket.
+    pub(crate) fn info(&self) -> UdpInfo<'_, E> {
+        let socket = self.inner.socket.lock();
+
+        UdpInfo {
+            local_endpoint: self.bound.endpoint(),
+            recv_queue: socket.recv_queue(),
+            send_queue: socket.send_queue(),
+            observer: self.observer(),
+        }
+    }
 }
 
 impl<E: Ext> UdpSocket<E> {
@@ -122,6 +166,10 @@ impl<E: Ext> UdpSocket<E> {
         let inner = UdpSocketInner {
             socket: SpinLock::new(socket),
             need_dispatch: AtomicBool::new(false),
+            // The default values follow Linux's behavior. See
+            // <https://man7.org/linux/man-pages/man7/ip.7.html>.
+            multicast_hop_limit: AtomicU8::new(1),
+            multicast_loop: AtomicBool::new(true),
         };
 
         let socket = Self::new(bound, inner);
@@ -178,6 +226,26 @@ impl<E: Ext> UdpSocket<E> {
         Ok(result)
     }
 
+    /// Sets the hop limit (TTL) of outgoing multicast packets.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn set_multicast_hop_limit(&self, hop_limit: u8) {
+        self.0
+            .inner
+            .multicast_hop_limit
+            .store(hop_limit, Ordering::Relaxed);
+    }
+
+    /// Sets whether outgoing multicast packets should be looped back to local sockets.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn set_multicast_loop(&self, multicast_loop: bool) {
+        self.0
+            .inner
+            .multicast_loop
+            .store(multicast_loop, Ordering::Relaxed);
+    }
+
     /// Calls `f` with an immutable reference to the associated [`RawUdpSocket`].
     //
     // NOTE: If a mutable reference is required, add a method above that correctly updates the next
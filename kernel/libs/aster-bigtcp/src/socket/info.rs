@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MPL-2.0
+
+pub use smoltcp::socket::tcp::State as TcpState;
+use smoltcp::wire::IpEndpoint;
+
+use crate::ext::Ext;
+
+/// A snapshot of the state of a socket in the socket table.
+///
+/// This is used to monitor sockets (e.g., via `sock_diag` or `/proc/net/tcp`).
+pub enum SocketInfo<'a, E: Ext> {
+    Tcp(TcpInfo<'a, E>),
+    Udp(UdpInfo<'a, E>),
+}
+
+/// A snapshot of the state of a TCP listener or a TCP connection.
+pub struct TcpInfo<'a, E: Ext> {
+    pub state: TcpState,
+    pub local_endpoint: IpEndpoint,
+    pub remote_endpoint: Option<IpEndpoint>,
+    /// The number of bytes in the receive buffer.
+    ///
+    /// For listeners, this is the number of connections waiting to be accepted.
+    pub recv_queue: usize,
+    /// The number of bytes in the send buffer.
+    ///
+    /// For listeners, this is the maximum number of connections waiting to be accepted.
+    pub send_queue: usize,
+    /// The observer of the socket.
+    ///
+    /// This is `None` if the connection has not been accepted yet.
+    pub observer: Option<&'a E::TcpEventObserver>,
+}
+
+/// A snapshot of the state of a UDP socket.
+pub struct UdpInfo<'a, E: Ext> {
+    pub local_endpoint: IpEndpoint,
+    /// The number of bytes in the receive buffer.
+    pub recv_queue: usize,
+    /// The number of bytes in the send buffer.
+    pub send_queue: usize,
+    /// The observer of the socket.
+    pub observer: Option<&'a E::UdpEventObserver>,
+}
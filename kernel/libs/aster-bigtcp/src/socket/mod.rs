@@ -2,15 +2,26 @@
 
 mod bound;
 mod event;
+mod info;
 mod option;
+mod packet;
+mod reuseport;
 mod unbound;
 
 pub use bound::{
-    ConnectState, NeedIfacePoll, RawTcpSocketExt, TcpConnection, TcpListener, UdpSocket,
+    ConnectState, NeedIfacePoll, RawSocket, RawSocketKind, RawTcpSocketExt, TcpConnection,
+    TcpListener, UdpSocket,
+};
+pub(crate) use bound::{
+    RawSocketBg, TcpConnectionBg, TcpListenerBg, TcpProcessResult, UdpSocketBg,
 };
-pub(crate) use bound::{TcpConnectionBg, TcpListenerBg, TcpProcessResult, UdpSocketBg};
 pub use event::{SocketEventObserver, SocketEvents};
+pub use info::{SocketInfo, TcpInfo, TcpState, UdpInfo};
 pub use option::{RawTcpOption, RawTcpSetOption};
+pub(crate) use packet::PacketSocketBg;
+pub use packet::{ETH_P_ALL, FrameObserver, FrameType, LinkFrame, PacketSocket};
+pub use reuseport::ReuseportSelector;
+pub(crate) use reuseport::select_reuseport_socket;
 pub use unbound::{
     RawUdpSocket, TCP_RECV_BUF_LEN, TCP_SEND_BUF_LEN, UDP_RECV_PAYLOAD_LEN, UDP_SEND_PAYLOAD_LEN,
 };
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{collections::vec_deque::VecDeque, sync::Arc, vec, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_softirq::BottomHalfDisabled;
+use ostd::sync::SpinLock;
+use smoltcp::wire::{EthernetAddress, EthernetFrame, EthernetRepr};
+
+use crate::{
+    errors::packet::SendError,
+    ext::Ext,
+    iface::{Iface, InterfaceType, LinkLayer},
+};
+
+/// The protocol number that matches frames of all protocols.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.18/source/include/uapi/linux/if_ether.h>
+pub const ETH_P_ALL: u16 = 0x0003;
+
+/// The type of a link-layer frame, as seen by packet sockets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.18/source/include/uapi/linux/if_packet.h>
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameType {
+    /// The frame is sent to us.
+    Host = 0,
+    /// The frame is sent to the broadcast address.
+    Broadcast = 1,
+    /// The frame is sent to a multicast address.
+    Multicast = 2,
+    /// The frame is sent to some other host and is received in promiscuous mode.
+    OtherHost = 3,
+    /// The frame is sent by us.
+    Outgoing = 4,
+}
+
+/// A link-layer frame that is received or transmitted by an iface.
+pub struct LinkFrame<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) header_len: usize,
+    pub(crate) protocol: u16,
+    pub(crate) src_addr: Option<EthernetAddress>,
+    pub(crate) frame_type: FrameType,
+    pub(crate) iface_index: u32,
+    pub(crate) iface_type: InterfaceType,
+}
+
+impl LinkFrame<'_> {
+    /// Returns the whole frame, including the link-layer header.
+    pub fn data(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the frame payload, excluding the link-layer header.
+    pub fn payload(&self) -> &[u8] {
+        &self.data[self.header_len..]
+    }
+
+    /// Returns the length of the link-layer header.
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    /// Returns the protocol of the payload (e.g., `0x0800` for IPv4), in host byte order.
+    pub fn protocol(&self) -> u16 {
+        self.protocol
+    }
+
+    /// Returns the link-layer source address, if the link layer has addresses.
+    pub fn src_addr(&self) -> Option<EthernetAddress> {
+        self.src_addr
+    }
+
+    /// Returns the type of the frame.
+    pub fn frame_type(&self) -> FrameType {
+        self.frame_type
+    }
+
+    /// Returns the index of the iface that the frame goes through.
+    pub fn iface_index(&self) -> u32 {
+        self.iface_index
+    }
+
+    /// Returns the type of the iface that the frame goes through.
+    pub fn iface_type(&self) -> InterfaceType {
+        self.iface_type
+    }
+}
+
+/// An observer that will be invoked whenever a packet socket sees a link-layer frame.
+pub trait FrameObserver: Send + Sync {
+    /// Notifies that a frame matching the socket is received or transmitted.
+    ///
+    /// This method is called while the iface is being polled, so it should not block.
+    fn on_frame(&self, frame: &LinkFrame);
+
+    /// Notifies that the iface has finished polling a batch of frames.
+    fn on_batch_end(&self) {}
+}
+
+impl FrameObserver for () {
+    fn on_frame(&self, _frame: &LinkFrame) {}
+}
+
+/// A packet socket that captures and injects link-layer frames on an iface.
+pub struct PacketSocket<E: Ext>(Arc<PacketSocketBg<E>>);
+
+/// The background part of a [`PacketSocket`], which lives in the socket table of the iface.
+pub(crate) struct PacketSocketBg<E: Ext> {
+    iface: Arc<dyn Iface<E>>,
+    protocol: u16,
+    observer: E::FrameObserver,
+    send_queue: SpinLock<SendQueue, BottomHalfDisabled>,
+    need_dispatch: AtomicBool,
+    is_sending: AtomicBool,
+}
+
+struct SendQueue {
+    frames: VecDeque<Vec<u8>>,
+    len: usize,
+}
+
+// The packet socket buffer size, which is the default `wmem_default` in Linux.
+const PACKET_SEND_BUF_LEN: usize = 212992;
+
+impl<E: Ext> PacketSocketBg<E> {
+    /// Returns whether the socket wants to see the frame.
+    pub(crate) fn can_process(&self, protocol: u16, frame_type: FrameType) -> bool {
+        // Like Linux, only sockets capturing all protocols can see outgoing frames, and a socket
+        // never sees the frames sent by itself.
+        match self.protocol {
+            0 => false,
+            ETH_P_ALL => {
+                frame_type != FrameType::Outgoing || !self.is_sending.load(Ordering::Relaxed)
+            }
+            _ => frame_type != FrameType::Outgoing && self.protocol == protocol,
+        }
+    }
+
+    /// Processes a frame that is received or transmitted by the iface.
+    pub(crate) fn process(&self, frame: &LinkFrame) {
+        self.observer.on_frame(frame);
+    }
+
+    /// Notifies the observer that the iface has finished polling a batch of frames.
+    pub(crate) fn on_batch_end(&self) {
+        self.observer.on_batch_end();
+    }
+
+    /// Dequeues an outgoing frame, if any.
+    pub(crate) fn dequeue_send(&self) -> Option<Vec<u8>> {
+        let mut send_queue = self.send_queue.lock();
+
+        let frame = send_queue.frames.pop_front();
+        if let Some(frame) = frame.as_ref() {
+            send_queue.len -= frame.len();
+        }
+
+        self.need_dispatch
+            .store(!send_queue.frames.is_empty(), Ordering::Relaxed);
+
+        frame
+    }
+
+    /// Marks whether the socket is sending a frame, so that the frame is not looped back to it.
+    pub(crate) fn set_sending(&self, is_sending: bool) {
+        self.is_sending.store(is_sending, Ordering::Relaxed);
+    }
+
+    /// Returns whether the socket _may_ generate an outgoing frame.
+    ///
+    /// The check is intended to be lock-free and fast, but may have false positives.
+    pub(crate) fn need_dispatch(&self) -> bool {
+        self.need_dispatch.load(Ordering::Relaxed)
+    }
+}
+
+impl<E: Ext> PacketSocket<E> {
+    /// Creates a packet socket on the iface.
+    ///
+    /// The socket sees incoming frames whose protocol is `protocol`. If `protocol` is
+    /// [`ETH_P_ALL`], the socket sees all incoming and outgoing frames. If `protocol` is zero, the
+    /// socket sees no frames, but can still send frames.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn new(iface: Arc<dyn Iface<E>>, protocol: u16, observer: E::FrameObserver) -> Self {
+        let bg = Arc::new(PacketSocketBg {
+            iface,
+            protocol,
+            observer,
+            send_queue: SpinLock::new(SendQueue {
+                frames: VecDeque::new(),
+                len: 0,
+            }),
+            need_dispatch: AtomicBool::new(false),
+            is_sending: AtomicBool::new(false),
+        });
+        bg.iface.common().register_packet_socket(bg.clone());
+
+        Self(bg)
+    }
+
+    /// Returns the iface that the socket is on.
+    pub fn iface(&self) -> &Arc<dyn Iface<E>> {
+        &self.0.iface
+    }
+
+    /// Returns the protocol of the frames that the socket sees.
+    pub fn protocol(&self) -> u16 {
+        self.0.protocol
+    }
+
+    /// Sends a frame, including its link-layer header.
+    ///
+    /// The link-layer header should be in the same format as that of the frames seen by the
+    /// socket (see [`LinkFrame::data`]).
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn send(&self, mut frame: Vec<u8>) -> Result<(), SendError> {
+        let link_layer = self.0.iface.common().link_layer();
+        if frame.len() < link_layer.header_len() {
+            return Err(SendError::Malformed);
+        }
+
+        // The fake header of loopback devices is never transmitted.
+        if link_layer == LinkLayer::Loopback {
+            frame.drain(..link_layer.header_len());
+        }
+
+        self.enqueue(frame)
+    }
+
+    /// Sends a payload, whose link-layer header will be built with the destination address and
+    /// the protocol.
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn send_payload(
+        &self,
+        dst_addr: Option<EthernetAddress>,
+        protocol: u16,
+        payload: &[u8],
+    ) -> Result<(), SendError> {
+        let frame = match self.0.iface.common().link_layer() {
+            LinkLayer::Ethernet(src_addr) => {
+                let repr = EthernetRepr {
+                    src_addr,
+                    dst_addr: dst_addr.ok_or(SendError::Unaddressable)?,
+                    ethertype: protocol.into(),
+                };
+                let mut frame = vec![0; repr.buffer_len() + payload.len()];
+                repr.emit(&mut EthernetFrame::new_unchecked(&mut frame));
+                frame[repr.buffer_len()..].copy_from_slice(payload);
+                frame
+            }
+            LinkLayer::Loopback | LinkLayer::None => payload.to_vec(),
+        };
+
+        self.enqueue(frame)
+    }
+
+    fn enqueue(&self, frame: Vec<u8>) -> Result<(), SendError> {
+        // Note that the MTU of Ethernet devices includes the Ethernet header.
+        if frame.len() > self.0.iface.mtu() {
+            return Err(SendError::TooLarge);
+        }
+
+        let mut send_queue = self.0.send_queue.lock();
+        if send_queue.len + frame.len() > PACKET_SEND_BUF_LEN {
+            return Err(SendError::BufferFull);
+        }
+        send_queue.len += frame.len();
+        send_queue.frames.push_back(frame);
+
+        self.0.need_dispatch.store(true, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Returns whether there is space to queue more frames to send.
+    pub fn can_send(&self) -> bool {
+        self.0.send_queue.lock().len < PACKET_SEND_BUF_LEN
+    }
+}
+
+impl<E: Ext> Drop for PacketSocket<E> {
+    fn drop(&mut self) {
+        self.0.iface.common().remove_packet_socket(&self.0);
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::sync::Arc;
+
+use super::bound::{Inner, SocketBg};
+use crate::{ext::Ext, socket_table::SocketHash};
+
+/// A program that selects a socket in a reuseport group to receive an incoming packet.
+///
+/// A reuseport group consists of the sockets that enable port reuse (i.e., `SO_REUSEPORT`) and
+/// share the same port.
+pub trait ReuseportSelector: Send + Sync {
+    /// Selects the index of the socket that should receive the packet.
+    ///
+    /// The `payload` is the transport-layer payload of the packet and `num_socks` is the number
+    /// of sockets in the reuseport group. If this method returns `None` or an index that is out
+    /// of range, a socket is selected according to the hash of the packet.
+    fn select(&self, payload: &[u8], num_socks: usize) -> Option<usize>;
+}
+
+impl ReuseportSelector for () {
+    fn select(&self, _payload: &[u8], _num_socks: usize) -> Option<usize> {
+        None
+    }
+}
+
+/// Selects a socket in the reuseport group to receive an incoming packet.
+///
+/// This is similar to `reuseport_select_sock` in Linux.
+pub(crate) fn select_reuseport_socket<'a, T, E>(
+    mut group: impl Iterator<Item = &'a Arc<SocketBg<T, E>>> + Clone,
+    hash: SocketHash,
+    payload: &[u8],
+) -> Option<&'a Arc<SocketBg<T, E>>>
+where
+    T: Inner<E> + 'a,
+    E: Ext + 'a,
+{
+    let num_socks = group.clone().count();
+    if num_socks <= 1 {
+        return group.next();
+    }
+
+    // The program is shared by all sockets in the group, so it can be found via any of them.
+    let index = group
+        .clone()
+        .next()?
+        .bound_port()
+        .reuseport_selector()
+        .and_then(|selector| selector.select(payload, num_socks))
+        .filter(|index| *index < num_socks)
+        .unwrap_or_else(|| reciprocal_scale(hash, num_socks));
+
+    group.nth(index)
+}
+
+/// Scales the hash value to the range `0..num`.
+///
+/// This is the same as `reciprocal_scale` in Linux.
+fn reciprocal_scale(hash: SocketHash, num: usize) -> usize {
+    ((hash as u64 * num as u64) >> 32) as usize
+}
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-//! This module defines the socket table, which manages all TCP and UDP sockets,
+//! This module defines the socket table, which manages all TCP, UDP, raw, and packet sockets,
 //! for efficiently inserting, looking up, and removing sockets.
 
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
@@ -12,17 +12,21 @@ use smoltcp::wire::{IpAddress, IpEndpoint, IpListenEndpoint};
 
 use crate::{
     ext::Ext,
-    socket::{TcpConnectionBg, TcpListenerBg, UdpSocketBg},
+    socket::{
+        PacketSocketBg, RawSocketBg, TcpConnectionBg, TcpListenerBg, UdpSocketBg,
+        select_reuseport_socket,
+    },
     wire::PortNum,
 };
 
 pub type SocketHash = u32;
 
-/// A unique key for identifying a `TcpListener`.
+/// A key for identifying a `TcpListener`.
 ///
 /// Note that two `TcpListener`s cannot listen on the same address
-/// even if both sockets set SO_REUSEADDR to true,
-/// so there cannot be multiple listeners with the same `ListenerKey`.
+/// even if both sockets set SO_REUSEADDR to true.
+/// Multiple listeners can have the same `ListenerKey`
+/// only if they belong to the same reuseport group (i.e., they set SO_REUSEPORT to true).
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) struct ListenerKey {
     addr: IpAddress,
@@ -147,7 +151,7 @@ const fn hash_addr_port(addr: IpAddress, port: PortNum) -> SocketHash {
     }
 }
 
-/// The socket table manages TCP and UDP sockets.
+/// The socket table manages TCP, UDP, raw, and packet sockets.
 ///
 /// Unlike the Linux inet hashtable, which is shared across a single network namespace,
 /// this table is currently limited to a single interface.
@@ -167,6 +171,10 @@ pub(crate) struct SocketTable<E: Ext> {
     // Note that multiple UDP sockets can be bound to the same address,
     // so we cannot use (addr, port) as a _unique_ key for UDP sockets.
     udp_sockets: Vec<Arc<UdpSocketBg<E>>>,
+    // Raw sockets and ICMP ping sockets. Each of them may receive a copy of any packet.
+    raw_sockets: Vec<Arc<RawSocketBg<E>>>,
+    // Packet sockets. Each of them may see a copy of any link-layer frame.
+    packet_sockets: Vec<Arc<PacketSocketBg<E>>>,
 }
 
 // On Linux, the number of buckets is determined at runtime based on the available memory.
@@ -191,17 +199,42 @@ impl<E: Ext> SocketTable<E> {
             .collect();
 
         let udp_sockets = Vec::new();
+        let raw_sockets = Vec::new();
+        let packet_sockets = Vec::new();
 
         Self {
             listener_buckets,
             connection_buckets,
             udp_sockets,
+            raw_sockets,
+            packet_sockets,
         }
     }
 
+    /// Checks whether a TCP listener can be inserted into the table.
+    ///
+    /// A listener cannot be inserted if a listener with the same [`ListenerKey`] has already been
+    /// inserted, unless both listeners have enabled port reuse and are owned by the same user
+    /// (i.e., `reuse_port` is the same user ID).
+    pub(crate) fn can_insert_listener(&self, key: &ListenerKey, reuse_port: Option<u32>) -> bool {
+        let bucket = {
+            let hash = key.hash();
+            let bucket_index = hash & LISTENER_BUCKET_MASK;
+            &self.listener_buckets[bucket_index as usize]
+        };
+
+        bucket
+            .listeners
+            .iter()
+            .filter(|tcp_listener| tcp_listener.listener_key() == key)
+            .all(|tcp_listener| {
+                reuse_port.is_some() && tcp_listener.bound_port().reuse_port() == reuse_port
+            })
+    }
+
     /// Inserts a TCP listener into the table.
     ///
-    /// If a socket with the same [`ListenerKey`] has already been inserted,
+    /// If the listener cannot be inserted (see [`Self::can_insert_listener`]),
     /// this method will return an error and the listener will not be inserted.
     pub(crate) fn insert_listener(
         &mut self,
@@ -209,20 +242,16 @@ impl<E: Ext> SocketTable<E> {
     ) -> Result<(), Arc<TcpListenerBg<E>>> {
         let key = listener.listener_key();
 
+        if !self.can_insert_listener(key, listener.bound_port().reuse_port()) {
+            return Err(listener);
+        }
+
         let bucket = {
             let hash = key.hash();
             let bucket_index = hash & LISTENER_BUCKET_MASK;
             &mut self.listener_buckets[bucket_index as usize]
         };
 
-        if bucket
-            .listeners
-            .iter()
-            .any(|tcp_listener| tcp_listener.listener_key() == listener.listener_key())
-        {
-            return Err(listener);
-        }
-
         bucket.listeners.push(listener);
         Ok(())
     }
@@ -261,17 +290,47 @@ impl<E: Ext> SocketTable<E> {
         self.udp_sockets.push(udp_socket);
     }
 
-    pub(crate) fn lookup_listener(&self, key: &ListenerKey) -> Option<&Arc<TcpListenerBg<E>>> {
+    pub(crate) fn insert_raw_socket(&mut self, raw_socket: Arc<RawSocketBg<E>>) {
+        debug_assert!(
+            !self
+                .raw_sockets
+                .iter()
+                .any(|socket| Arc::ptr_eq(socket, &raw_socket))
+        );
+        self.raw_sockets.push(raw_socket);
+    }
+
+    pub(crate) fn insert_packet_socket(&mut self, packet_socket: Arc<PacketSocketBg<E>>) {
+        debug_assert!(
+            !self
+                .packet_sockets
+                .iter()
+                .any(|socket| Arc::ptr_eq(socket, &packet_socket))
+        );
+        self.packet_sockets.push(packet_socket);
+    }
+
+    /// Looks up a TCP listener to receive an incoming connection.
+    ///
+    /// If multiple listeners in a reuseport group match the key, one of them is selected
+    /// according to the `hash` of the connection and the `payload` of the packet.
+    pub(crate) fn lookup_listener(
+        &self,
+        key: &ListenerKey,
+        hash: SocketHash,
+        payload: &[u8],
+    ) -> Option<&Arc<TcpListenerBg<E>>> {
         let bucket = {
             let hash = key.hash();
             let bucket_index = hash & LISTENER_BUCKET_MASK;
             &self.listener_buckets[bucket_index as usize]
         };
 
-        bucket
+        let group = bucket
             .listeners
             .iter()
-            .find(|listener| listener.listener_key() == key)
+            .filter(|listener| listener.listener_key() == key);
+        select_reuseport_socket(group, hash, payload)
     }
 
     pub(crate) fn lookup_connection(
@@ -290,9 +349,12 @@ impl<E: Ext> SocketTable<E> {
             .find(|connection| connection.connection_key() == key)
     }
 
-    pub(crate) fn remove_listener(&mut self, key: &ListenerKey) -> Option<Arc<TcpListenerBg<E>>> {
+    pub(crate) fn remove_listener(
+        &mut self,
+        listener: &Arc<TcpListenerBg<E>>,
+    ) -> Option<Arc<TcpListenerBg<E>>> {
         let bucket = {
-            let hash = key.hash();
+            let hash = listener.listener_key().hash();
             let bucket_index = hash & LISTENER_BUCKET_MASK;
             &mut self.listener_buckets[bucket_index as usize]
         };
@@ -300,7 +362,7 @@ impl<E: Ext> SocketTable<E> {
         let index = bucket
             .listeners
             .iter()
-            .position(|tcp_listener| tcp_listener.listener_key() == key)?;
+            .position(|tcp_listener| Arc::ptr_eq(tcp_listener, listener))?;
         Some(bucket.listeners.swap_remove(index))
     }
 
@@ -325,6 +387,18 @@ impl<E: Ext> SocketTable<E> {
         connection.notify_dead_events();
     }
 
+    pub(crate) fn listener_iter(&self) -> impl Iterator<Item = &Arc<TcpListenerBg<E>>> {
+        self.listener_buckets
+            .iter()
+            .flat_map(|bucket| bucket.listeners.iter())
+    }
+
+    pub(crate) fn connection_iter(&self) -> impl Iterator<Item = &Arc<TcpConnectionBg<E>>> {
+        self.connection_buckets
+            .iter()
+            .flat_map(|bucket| bucket.connections.iter())
+    }
+
     pub(crate) fn remove_udp_socket(
         &mut self,
         socket: &Arc<UdpSocketBg<E>>,
@@ -339,6 +413,76 @@ impl<E: Ext> SocketTable<E> {
     pub(crate) fn udp_socket_iter(&self) -> impl Iterator<Item = &Arc<UdpSocketBg<E>>> {
         self.udp_sockets.iter()
     }
+
+    /// Selects a UDP socket in the reuseport group bound to the destination endpoint.
+    ///
+    /// Sockets bound to the destination address take precedence over sockets bound to the
+    /// unspecified address. Like Linux, a reuseport group only contains sockets owned by the same
+    /// user. One of the sockets in the group is selected according to the `hash` of the packet
+    /// and its `payload`.
+    pub(crate) fn select_reuseport_udp_socket(
+        &self,
+        dst_endpoint: &IpEndpoint,
+        hash: SocketHash,
+        payload: &[u8],
+    ) -> Option<&Arc<UdpSocketBg<E>>> {
+        let group_of = |is_member: fn(&IpAddress, &IpAddress) -> bool| {
+            let candidates = self.udp_sockets.iter().filter(move |socket| {
+                let bound_port = socket.bound_port();
+                bound_port.reuse_port().is_some()
+                    && bound_port.port() == dst_endpoint.port
+                    && is_member(bound_port.addr(), &dst_endpoint.addr)
+            });
+
+            let uid = candidates
+                .clone()
+                .next()
+                .and_then(|socket| socket.bound_port().reuse_port());
+            candidates.filter(move |socket| socket.bound_port().reuse_port() == uid)
+        };
+
+        select_reuseport_socket(group_of(|addr, dst_addr| addr == dst_addr), hash, payload).or_else(
+            || {
+                select_reuseport_socket(
+                    group_of(|addr, dst_addr| {
+                        addr.is_unspecified() && addr.version() == dst_addr.version()
+                    }),
+                    hash,
+                    payload,
+                )
+            },
+        )
+    }
+
+    pub(crate) fn remove_raw_socket(
+        &mut self,
+        socket: &Arc<RawSocketBg<E>>,
+    ) -> Option<Arc<RawSocketBg<E>>> {
+        let index = self
+            .raw_sockets
+            .iter()
+            .position(|raw_socket| Arc::ptr_eq(raw_socket, socket))?;
+        Some(self.raw_sockets.swap_remove(index))
+    }
+
+    pub(crate) fn raw_socket_iter(&self) -> impl Iterator<Item = &Arc<RawSocketBg<E>>> {
+        self.raw_sockets.iter()
+    }
+
+    pub(crate) fn remove_packet_socket(
+        &mut self,
+        socket: &Arc<PacketSocketBg<E>>,
+    ) -> Option<Arc<PacketSocketBg<E>>> {
+        let index = self
+            .packet_sockets
+            .iter()
+            .position(|packet_socket| Arc::ptr_eq(packet_socket, socket))?;
+        Some(self.packet_sockets.swap_remove(index))
+    }
+
+    pub(crate) fn packet_socket_iter(&self) -> impl Iterator<Item = &Arc<PacketSocketBg<E>>> {
+        self.packet_sockets.iter()
+    }
 }
 
 impl<E: Ext> Default for SocketTable<E> {
@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub use smoltcp::wire::{
-    EthernetAddress, IpAddress, IpCidr, IpEndpoint, Ipv4Address, Ipv4Cidr, Ipv6Address, Ipv6Cidr,
+    EthernetAddress, IPV4_HEADER_LEN, Icmpv4Message, Icmpv4Packet, IpAddress, IpCidr, IpEndpoint,
+    IpProtocol, IpRepr, Ipv4Address, Ipv4Cidr, Ipv4Packet, Ipv6Address, Ipv6Cidr, Ipv6Packet,
+    TcpPacket, UdpPacket,
 };
 
 pub type PortNum = u16;
@@ -6,12 +6,12 @@ use core::ops::Range;
 
 use ostd::{
     arch::{
-        cpu::context::{FsBase, GeneralRegs, GsBase},
+        cpu::context::{DebugRegs, DebugStatus, FpuContext, FsBase, GeneralRegs, GsBase},
         trap::{USER_CS_VALUE, USER_SS_VALUE},
     },
     mm::MAX_USERSPACE_VADDR,
 };
-use x86_64::registers::rflags::RFlags;
+use x86_64::registers::{rflags::RFlags, xcontrol::XCr0};
 
 use crate::prelude::*;
 
@@ -125,6 +125,7 @@ pub fn read_user_word(
     general_regs: &GeneralRegs,
     fs_base: FsBase,
     gs_base: GsBase,
+    debug_regs: &DebugRegs,
     orig_rax: usize,
     offset: usize,
 ) -> Result<usize> {
@@ -139,15 +140,12 @@ pub fn read_user_word(
         return Ok(orig_rax);
     }
 
-    // FIXME: This emulates the default state of the x86 debug registers,
-    // so it can correctly respond when a tracer reads the tracee’s debug
-    // registers via `PTRACE_PEEKUSER`.
-    // Currently, the tracee’s x86 debug registers are never actually modified,
-    // so they always remain at their default values.
     if let Some(index) = debug_register_index(offset) {
         let value = match index {
-            6 => DEBUG_STATUS_DEFAULT_VALUE,
-            0..=5 | 7 => 0,
+            0..=3 => debug_regs.addr(index),
+            4 | 5 => 0,
+            6 => DebugStatus::FIXED_ONES | debug_regs.status().bits(),
+            7 => debug_regs.control(),
             _ => unreachable!(),
         };
         return Ok(value);
@@ -166,6 +164,7 @@ pub fn write_user_word(
     general_regs: &mut GeneralRegs,
     fs_base: &mut FsBase,
     gs_base: &mut GsBase,
+    debug_regs: &mut DebugRegs,
     orig_rax: &mut usize,
     offset: usize,
     value: usize,
@@ -189,11 +188,8 @@ pub fn write_user_word(
         *orig_rax = value;
         return Ok(());
     }
-    if debug_register_index(offset).is_some() {
-        return_errno_with_message!(
-            Errno::EOPNOTSUPP,
-            "writing x86 debug registers is not supported currently"
-        );
+    if let Some(index) = debug_register_index(offset) {
+        return write_debug_register(debug_regs, index, value);
     }
 
     let rule =
@@ -201,6 +197,80 @@ pub fn write_user_word(
     rule.apply(general_regs, value)
 }
 
+/// Writes the debug register at `index`.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/kernel/ptrace.c#L618-L649>
+fn write_debug_register(debug_regs: &mut DebugRegs, index: usize, value: usize) -> Result<()> {
+    match index {
+        0..=3 => {
+            if !is_user_addr(value) {
+                return_errno_with_message!(
+                    Errno::EIO,
+                    "the breakpoint address is not in user space"
+                );
+            }
+            let mut new_regs = *debug_regs;
+            new_regs.set_addr(index, value);
+            check_breakpoints(&new_regs)?;
+            *debug_regs = new_regs;
+        }
+        4 | 5 => {
+            return_errno_with_message!(Errno::EIO, "DR4 and DR5 are not accessible");
+        }
+        6 => debug_regs.set_status(DebugStatus::from_bits_truncate(value)),
+        7 => {
+            let mut new_regs = *debug_regs;
+            new_regs
+                .set_control(value)
+                .map_err(|_| Error::with_message(Errno::EINVAL, "invalid debug control value"))?;
+            check_breakpoints(&new_regs)?;
+            *debug_regs = new_regs;
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+/// Checks that all enabled breakpoints in `debug_regs` are well-formed.
+///
+/// An execution breakpoint must have a length of one byte, and the address of a data breakpoint
+/// must be aligned to its length.
+fn check_breakpoints(debug_regs: &DebugRegs) -> Result<()> {
+    const RW_EXEC: usize = 0b00;
+
+    let control = debug_regs.control();
+    for index in 0..DebugRegs::NR_ADDRS {
+        // Check the local and global enable bits.
+        if (control >> (index * 2)) & 0b11 == 0 {
+            continue;
+        }
+
+        let rw = (control >> (16 + index * 4)) & 0b11;
+        let len = match (control >> (18 + index * 4)) & 0b11 {
+            0b00 => 1,
+            0b01 => 2,
+            0b10 => 8,
+            0b11 => 4,
+            _ => unreachable!(),
+        };
+        if rw == RW_EXEC && len != 1 {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the length of an execution breakpoint must be one byte"
+            );
+        }
+        if !debug_regs.addr(index).is_multiple_of(len) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the breakpoint address is not aligned to its length"
+            );
+        }
+    }
+
+    Ok(())
+}
+
 /// Enables x86-64 single-step execution by setting the trap flag.
 pub fn enable_single_step(regs: &mut GeneralRegs) {
     regs.rflags |= RFlags::TRAP_FLAG.bits() as usize;
@@ -211,6 +281,161 @@ pub fn disable_single_step(regs: &mut GeneralRegs) {
     regs.rflags &= !(RFlags::TRAP_FLAG.bits() as usize);
 }
 
+// =====================================================================
+// Register sets.
+// =====================================================================
+
+/// The types of register sets for `PTRACE_GETREGSET` and `PTRACE_SETREGSET`.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/elf.h#L378-L413>
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, TryFromInt)]
+#[expect(non_camel_case_types)]
+pub enum RegSetType {
+    /// The general-purpose registers in `struct user_regs_struct`.
+    NT_PRSTATUS = 1,
+    /// The floating-point registers in `struct user_i387_struct`.
+    NT_PRFPREG = 2,
+    /// The extended processor states in the XSAVE format.
+    NT_X86_XSTATE = 0x202,
+}
+
+/// The size of Linux's `struct user_i387_struct`, which is in the FXSAVE format.
+pub const USER_FPREGS_SIZE: usize = 512;
+
+/// Reads the floating-point registers in the FXSAVE format.
+pub fn read_fpregs(fpu_context: &FpuContext) -> [u8; USER_FPREGS_SIZE] {
+    fpu_context.as_bytes()[..USER_FPREGS_SIZE]
+        .try_into()
+        .unwrap()
+}
+
+/// Validates the floating-point registers in the FXSAVE format
+/// and then writes them into the FPU context.
+///
+/// # Errors
+///
+/// Returns `EINVAL` if reserved MXCSR bits are set.
+pub fn write_fpregs(fpu_context: &mut FpuContext, fpregs: &[u8; USER_FPREGS_SIZE]) -> Result<()> {
+    check_mxcsr(fpu_context.as_bytes(), fpregs)?;
+
+    let bytes = fpu_context.as_bytes_mut();
+    bytes[..USER_FPREGS_SIZE].copy_from_slice(fpregs);
+
+    // Make sure that `XRSTOR` loads the new x87 and SSE states
+    // instead of resetting them to the initial states.
+    if bytes.len() > USER_FPREGS_SIZE {
+        let xstate_bv = read_u64(bytes, XSTATE_BV_OFFSET) | XFEATURE_MASK_FP_SSE;
+        write_u64(bytes, XSTATE_BV_OFFSET, xstate_bv);
+    }
+
+    Ok(())
+}
+
+/// Reads at most `max_len` bytes of the extended processor states in the standard XSAVE format.
+///
+/// # Errors
+///
+/// Returns `ENODEV` if the CPU does not support XSAVE.
+pub fn read_xstate(fpu_context: &FpuContext, max_len: usize) -> Result<Vec<u8>> {
+    let bytes = fpu_context.as_bytes();
+    if bytes.len() <= USER_FPREGS_SIZE {
+        return_errno_with_message!(Errno::ENODEV, "XSAVE is not supported");
+    }
+
+    let len = max_len.min(bytes.len());
+    let mut buf = bytes[..len].to_vec();
+
+    // Debuggers (e.g., GDB) read the enabled XSAVE features from the software-reserved bytes.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/include/asm/user.h#L19-L49>
+    if len >= XSTATE_XCR0_OFFSET + size_of::<u64>() {
+        write_u64(&mut buf, XSTATE_XCR0_OFFSET, XCr0::read().bits());
+    }
+
+    Ok(buf)
+}
+
+/// Validates the extended processor states in the standard XSAVE format
+/// and then writes them into the FPU context.
+///
+/// # Errors
+///
+/// Returns `ENODEV` if the CPU does not support XSAVE,
+/// `EFAULT` if `buf` does not cover the whole XSAVE area,
+/// and `EINVAL` if the XSAVE header or the MXCSR contains invalid bits.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/kernel/fpu/regset.c#L143-L175>
+pub fn write_xstate(fpu_context: &mut FpuContext, buf: &[u8]) -> Result<()> {
+    let bytes = fpu_context.as_bytes();
+    if bytes.len() <= USER_FPREGS_SIZE {
+        return_errno_with_message!(Errno::ENODEV, "XSAVE is not supported");
+    }
+    if buf.len() != bytes.len() {
+        return_errno_with_message!(Errno::EFAULT, "the XSAVE buffer size does not match");
+    }
+
+    let xstate_bv = read_u64(buf, XSTATE_BV_OFFSET);
+    if xstate_bv & !XCr0::read().bits() != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the XSAVE features are not enabled");
+    }
+    let xcomp_bv = read_u64(buf, XCOMP_BV_OFFSET);
+    if xcomp_bv != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the compacted XSAVE format is not supported");
+    }
+    if buf[XSTATE_HEADER_RESERVED].iter().any(|byte| *byte != 0) {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the reserved XSAVE header bytes are not zero"
+        );
+    }
+    check_mxcsr(bytes, buf[..USER_FPREGS_SIZE].try_into().unwrap())?;
+
+    fpu_context.as_bytes_mut().copy_from_slice(buf);
+    Ok(())
+}
+
+/// Checks that no reserved MXCSR bits are set in `fpregs`.
+fn check_mxcsr(current: &[u8], fpregs: &[u8; USER_FPREGS_SIZE]) -> Result<()> {
+    // The MXCSR mask is recorded by `FXSAVE`/`XSAVE`. A zero mask means that
+    // the default mask should be used.
+    //
+    // Reference: Intel SDM, Volume 1, Section 11.6.6 "Guidelines for Writing to the MXCSR Register".
+    const DEFAULT_MXCSR_MASK: u32 = 0xffbf;
+
+    let mut mxcsr_mask = read_u32(current, MXCSR_MASK_OFFSET);
+    if mxcsr_mask == 0 {
+        mxcsr_mask = DEFAULT_MXCSR_MASK;
+    }
+
+    if read_u32(fpregs, MXCSR_OFFSET) & !mxcsr_mask != 0 {
+        return_errno_with_message!(Errno::EINVAL, "reserved MXCSR bits are set");
+    }
+
+    Ok(())
+}
+
+// Reference: Intel SDM, Volume 1, Section 13.4 "XSAVE Area".
+const MXCSR_OFFSET: usize = 24;
+const MXCSR_MASK_OFFSET: usize = 28;
+const XSTATE_XCR0_OFFSET: usize = 464;
+const XSTATE_BV_OFFSET: usize = 512;
+const XCOMP_BV_OFFSET: usize = 520;
+const XSTATE_HEADER_RESERVED: Range<usize> = 528..576;
+const XFEATURE_MASK_FP_SSE: u64 = 0b11;
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + size_of::<u32>()].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_ne_bytes(bytes[offset..offset + size_of::<u64>()].try_into().unwrap())
+}
+
+fn write_u64(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..offset + size_of::<u64>()].copy_from_slice(&value.to_ne_bytes());
+}
+
 // =====================================================================
 // Per-register policy table.
 // =====================================================================
@@ -406,7 +631,6 @@ fn check_user_offset(offset: usize) -> Result<()> {
 // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/arch/x86/include/asm/user_64.h#L103-L132>.
 const DEBUG_REGS_OFFSET: usize = 848;
 const DEBUG_REGS_COUNT: usize = 8;
-const DEBUG_STATUS_DEFAULT_VALUE: usize = 0xffff0ff0;
 
 const fn debug_register_index(offset: usize) -> Option<usize> {
     if !offset.is_multiple_of(size_of::<usize>()) {
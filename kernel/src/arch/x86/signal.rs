@@ -26,9 +26,13 @@ impl ToFaultSignal for CpuException {
 
         let (num, code, addr) = match self {
             CpuException::DivisionError => (SIGFPE, FPE_INTDIV, Some(rip)),
-            CpuException::Debug => {
-                // TODO: Derive the code from the debug status.
-                (SIGTRAP, TRAP_TRACE, Some(rip))
+            CpuException::Debug(status) => {
+                let code = if status.breakpoints().is_empty() {
+                    TRAP_TRACE
+                } else {
+                    TRAP_HWBKPT
+                };
+                (SIGTRAP, code, Some(rip))
             }
             CpuException::BreakPoint => {
                 // Linux uses `SI_KERNEL` without an address.
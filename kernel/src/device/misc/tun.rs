@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! TUN/TAP misc-device support.
+//!
+//! This module registers the `/dev/net/tun` character device. Each opened file can be attached
+//! to a TUN/TAP device with the `TUNSETIFF` ioctl, after which it reads the packets sent by the
+//! device and writes the packets received by the device.
+
+use device_id::{DeviceId, MinorId};
+use ostd::{mm::VmIo, task::Task};
+
+use crate::{
+    context::current_userspace,
+    device::{Device, DeviceType, DevtmpfsInodeMeta, registry::char},
+    events::IoEvents,
+    fs::{
+        file::{PerOpenFileOps, StatusFlags, mkmod},
+        vfs::inode::FileOps,
+    },
+    net::{
+        iface::{TunFlags, TunQueue},
+        net_ns::NetNamespace,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+    util::ioctl::{RawIoctl, dispatch_ioctl},
+};
+
+const TUN_MINOR: u32 = 200;
+
+/// The maximum length of interface names, including the trailing null byte.
+const IFNAMSIZ: usize = 16;
+
+/// The `/dev/net/tun` device.
+#[derive(Debug)]
+struct TunDevice {
+    id: DeviceId,
+}
+
+impl TunDevice {
+    fn new() -> Arc<Self> {
+        let major = super::MISC_MAJOR.get().unwrap().get();
+        let minor = MinorId::new(TUN_MINOR);
+
+        let id = DeviceId::new(major, minor);
+        Arc::new(Self { id })
+    }
+}
+
+impl Device for TunDevice {
+    fn type_(&self) -> DeviceType {
+        DeviceType::Char
+    }
+
+    fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    fn devtmpfs_meta(&self) -> Option<DevtmpfsInodeMeta<'_>> {
+        Some(DevtmpfsInodeMeta::with_mode("net/tun", mkmod!(a+rw)))
+    }
+
+    fn open(&self) -> Result<Box<dyn PerOpenFileOps>> {
+        // The devices are created in the network namespace of the opener, as in Linux.
+        let net_ns = {
+            let current_task = Task::current().unwrap();
+            let thread_local = current_task.as_thread_local().unwrap();
+            thread_local.borrow_ns_proxy().unwrap().net_ns().clone()
+        };
+
+        Ok(Box::new(TunFile {
+            queue: TunQueue::new(),
+            net_ns,
+        }))
+    }
+}
+
+/// A file handle opened from `/dev/net/tun`.
+struct TunFile {
+    queue: Arc<TunQueue>,
+    net_ns: Arc<NetNamespace>,
+}
+
+impl TunFile {
+    fn set_iff(&self, req: &mut CIfreq) -> Result<()> {
+        if self.queue.device_info().is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the file is already attached");
+        }
+
+        let Some(flags) = TunFlags::from_bits(req.flags) else {
+            return_errno_with_message!(Errno::EINVAL, "the TUN/TAP flags are invalid");
+        };
+        if flags.contains(TunFlags::TUN) == flags.contains(TunFlags::TAP) {
+            return_errno_with_message!(Errno::EINVAL, "either IFF_TUN or IFF_TAP must be set");
+        }
+        if flags.contains(TunFlags::VNET_HDR) {
+            return_errno_with_message!(Errno::EINVAL, "IFF_VNET_HDR is not supported");
+        }
+
+        let name = req.name()?;
+        let name = self.net_ns.attach_tun(&self.queue, name, flags)?;
+        req.set_name(&name);
+
+        Ok(())
+    }
+}
+
+impl Pollable for TunFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.queue.poll(mask, poller)
+    }
+}
+
+impl FileOps for TunFile {
+    fn read_at(
+        &self,
+        _offset: usize,
+        writer: &mut VmWriter,
+        status_flags: StatusFlags,
+    ) -> Result<usize> {
+        if status_flags.contains(StatusFlags::O_NONBLOCK) {
+            self.queue.try_recv(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.queue.try_recv(writer))
+        }
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        // Writing never blocks. Packets will be dropped if the receive queue is full.
+        self.queue.send(reader)
+    }
+}
+
+impl PerOpenFileOps for TunFile {
+    fn check_seekable(&self) -> Result<()> {
+        return_errno_with_message!(Errno::ESPIPE, "the inode is a TUN/TAP device");
+    }
+
+    fn is_offset_aware(&self) -> bool {
+        false
+    }
+
+    fn ioctl(&self, raw_ioctl: RawIoctl) -> Result<i32> {
+        use ioctl_defs::*;
+
+        // The argument of these ioctls is a `struct ifreq`, although its size encoded in the
+        // ioctl command is the size of an `int`.
+        let ifreq_addr = raw_ioctl.arg();
+
+        dispatch_ioctl!(match raw_ioctl {
+            SetIff => {
+                let mut req: CIfreq = current_userspace!().read_val(ifreq_addr)?;
+                self.set_iff(&mut req)?;
+                current_userspace!().write_val(ifreq_addr, &req)?;
+            }
+            GetIff => {
+                let Some((name, flags)) = self.queue.device_info() else {
+                    return_errno_with_message!(Errno::EBADFD, "the file is not attached");
+                };
+                let mut req = CIfreq::new_zeroed();
+                req.set_name(&name);
+                req.flags = flags.bits();
+                current_userspace!().write_val(ifreq_addr, &req)?;
+            }
+            _ => return_errno_with_message!(Errno::EINVAL, "the ioctl command is unknown"),
+        });
+
+        Ok(0)
+    }
+}
+
+impl Drop for TunFile {
+    fn drop(&mut self) {
+        self.net_ns.detach_tun(&self.queue);
+    }
+}
+
+/// The `struct ifreq` with the `ifr_flags` member.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/if.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIfreq {
+    name: [u8; IFNAMSIZ],
+    flags: u16,
+    _pad: [u8; 22],
+}
+
+impl CIfreq {
+    /// Returns the interface name, or `None` if the name is empty.
+    fn name(&self) -> Result<Option<CString>> {
+        let Some(len) = self.name.iter().position(|byte| *byte == 0) else {
+            return_errno_with_message!(Errno::EINVAL, "the interface name is too long");
+        };
+        if len == 0 {
+            return Ok(None);
+        }
+        Ok(Some(CString::new(&self.name[..len]).unwrap()))
+    }
+
+    fn set_name(&mut self, name: &CStr) {
+        let bytes = name.to_bytes();
+        let len = bytes.len().min(IFNAMSIZ - 1);
+        self.name = [0; IFNAMSIZ];
+        self.name[..len].copy_from_slice(&bytes[..len]);
+    }
+}
+
+mod ioctl_defs {
+    use crate::util::ioctl::{InData, OutData, ioc};
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/if_tun.h>
+
+    pub(super) type SetIff = ioc!(TUNSETIFF, b'T', 202, InData<i32>);
+    pub(super) type GetIff = ioc!(TUNGETIFF, b'T', 210, OutData<u32>);
+}
+
+pub(super) fn init_in_first_kthread() {
+    char::register(TunDevice::new()).unwrap();
+}
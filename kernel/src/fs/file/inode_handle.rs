@@ -23,7 +23,10 @@ use crate::{
         },
     },
     prelude::*,
-    process::signal::{PollHandle, Pollable},
+    process::{
+        credentials::file_capabilities::FileCapabilities,
+        signal::{PollHandle, Pollable},
+    },
     util::ioctl::RawIoctl,
 };
 
@@ -40,12 +43,11 @@ pub struct InodeHandle {
 
 impl InodeHandle {
     pub fn new(path: Path, access_mode: AccessMode, status_flags: StatusFlags) -> Result<Self> {
-        let inode = path.inode();
         if !status_flags.contains(StatusFlags::O_PATH) {
             // "Opening a file or directory with the O_PATH flag requires no permissions on the
             // object itself".
             // Reference: <https://man7.org/linux/man-pages/man2/openat.2.html>
-            inode.check_permission(access_mode.into())?;
+            path.check_permission(access_mode.into())?;
         }
 
         Self::new_unchecked_access(path, access_mode, status_flags)
@@ -280,6 +282,8 @@ impl FileLike for InodeHandle {
             return_errno_with_message!(Errno::EBADF, "the file is not opened writable");
         }
 
+        FileCapabilities::remove_from(&self.path)?;
+
         let (file_ops, is_offset_aware) = self.file_ops_and_is_offset_aware();
         let status_flags = self.status_flags();
 
@@ -319,6 +323,8 @@ impl FileLike for InodeHandle {
             return_errno_with_message!(Errno::EBADF, "the file is not opened writable");
         }
 
+        FileCapabilities::remove_from(&self.path)?;
+
         let status_flags = self.status_flags();
 
         // FIXME: How can we deal with the `O_APPEND` flag if `open_file` is set?
@@ -375,6 +381,7 @@ impl FileLike for InodeHandle {
             // FIXME: It's allowed to `ftruncate` an append-only file on Linux.
             return_errno_with_message!(Errno::EPERM, "can not resize append-only file");
         }
+        FileCapabilities::remove_from(&self.path)?;
         self.path.inode().resize(new_size)
     }
 
@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    fmt::Write as _,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use ostd::task::Task;
+
+use crate::{
+    fs::vfs::path::{FsPath, Path},
+    prelude::*,
+};
+
+/// The maximum length of a registration string.
+pub(super) const MAX_REGISTER_LENGTH: usize = 1920;
+/// The minimum length of a registration string.
+const MIN_REGISTER_LENGTH: usize = 11;
+
+/// The number of bytes at the beginning of a binary that can be matched against magic.
+const BINPRM_BUF_SIZE: usize = 256;
+
+bitflags! {
+    /// The flags of a `binfmt_misc` entry.
+    pub struct BinfmtFlags: u8 {
+        /// Keeps the original `argv[0]` instead of replacing it with the file name (`P`).
+        const PRESERVE_ARGV0 = 1 << 0;
+        /// Passes the binary to the interpreter as an open file descriptor (`O`).
+        const OPEN_BINARY    = 1 << 1;
+        /// Computes the credentials from the binary instead of the interpreter (`C`).
+        ///
+        /// This flag implies [`Self::OPEN_BINARY`].
+        const CREDENTIALS    = 1 << 2;
+        /// Opens the interpreter when the entry is registered (`F`).
+        const FIX_BINARY     = 1 << 3;
+    }
+}
+
+/// An entry that registers an interpreter for a format of binaries.
+///
+/// Reference: <https://docs.kernel.org/admin-guide/binfmt-misc.html>
+pub struct BinfmtEntry {
+    name: String,
+    matcher: Matcher,
+    interpreter: String,
+    flags: BinfmtFlags,
+    /// The interpreter that is opened at registration time if the `F` flag is set.
+    interpreter_file: Option<Path>,
+    is_enabled: AtomicBool,
+}
+
+/// How a `binfmt_misc` entry recognizes binaries.
+#[derive(Debug, PartialEq, Eq)]
+enum Matcher {
+    /// Matches the bytes at `offset` against `magic`, ignoring the bits cleared in `mask`.
+    ///
+    /// The magic has already been masked.
+    Magic {
+        offset: usize,
+        magic: Vec<u8>,
+        mask: Option<Vec<u8>>,
+    },
+    /// Matches the extension of the file name.
+    Extension(Vec<u8>),
+}
+
+impl BinfmtEntry {
+    /// Parses a registration string of the form `:name:type:offset:magic:mask:interpreter:flags`.
+    ///
+    /// The first byte of the string is used as the delimiter. If the `F` flag is present, the
+    /// interpreter is looked up in the file system of the current thread.
+    pub(super) fn new_from_registration(bytes: &[u8]) -> Result<Self> {
+        let mut entry = Self::parse(bytes)?;
+
+        if entry.flags.contains(BinfmtFlags::FIX_BINARY) {
+            let current = Task::current().unwrap();
+            let fs_ref = current.as_thread_local().unwrap().borrow_fs();
+            let path_resolver = fs_ref.resolver().read();
+            let fs_path = FsPath::try_from(entry.interpreter.as_str())?;
+            entry.interpreter_file = Some(path_resolver.lookup(&fs_path)?);
+        }
+
+        Ok(entry)
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c#L363-L538>
+
+        if !(MIN_REGISTER_LENGTH..=MAX_REGISTER_LENGTH).contains(&bytes.len()) {
+            return_errno_with_message!(Errno::EINVAL, "the registration string has a bad length");
+        }
+
+        let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+        let delimiter = bytes[0];
+        let mut fields = bytes[1..].split(|&byte| byte == delimiter);
+        let mut next_field = || {
+            fields.next().ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "the registration string has too few fields")
+            })
+        };
+
+        let name = next_field()?;
+        if name.is_empty() || name == b"." || name == b".." || name.contains(&b'/') {
+            return_errno_with_message!(Errno::EINVAL, "the entry name is invalid");
+        }
+        let name = String::from_utf8(name.to_vec())
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the entry name is not UTF-8"))?;
+
+        let type_ = next_field()?;
+        let offset = next_field()?;
+        let magic = next_field()?;
+        let mask = next_field()?;
+        let matcher = match type_ {
+            b"M" => Self::parse_magic_matcher(offset, magic, mask)?,
+            b"E" => Self::parse_extension_matcher(offset, magic, mask)?,
+            _ => return_errno_with_message!(Errno::EINVAL, "the entry type is invalid"),
+        };
+
+        let interpreter = next_field()?;
+        if interpreter.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "the interpreter is empty");
+        }
+        let interpreter = String::from_utf8(interpreter.to_vec())
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the interpreter is not UTF-8"))?;
+
+        // Following Linux, the flags field can be omitted.
+        let flags = Self::parse_flags(next_field().unwrap_or_default())?;
+
+        if fields.next().is_some() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the registration string has too many fields"
+            );
+        }
+
+        Ok(Self {
+            name,
+            matcher,
+            interpreter,
+            flags,
+            interpreter_file: None,
+            is_enabled: AtomicBool::new(true),
+        })
+    }
+
+    fn parse_magic_matcher(offset: &[u8], magic: &[u8], mask: &[u8]) -> Result<Matcher> {
+        let offset = if offset.is_empty() {
+            0
+        } else {
+            core::str::from_utf8(offset)
+                .ok()
+                .and_then(|offset| offset.parse::<usize>().ok())
+                .ok_or_else(|| Error::with_message(Errno::EINVAL, "the offset is invalid"))?
+        };
+
+        let mut magic = unescape_hex(magic);
+        if magic.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "the magic is empty");
+        }
+        if magic.len() > BINPRM_BUF_SIZE || BINPRM_BUF_SIZE - magic.len() < offset {
+            return_errno_with_message!(Errno::EINVAL, "the magic is out of the matched range");
+        }
+
+        let mask = if mask.is_empty() {
+            None
+        } else {
+            let mask = unescape_hex(mask);
+            if mask.len() != magic.len() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the mask and the magic have different lengths"
+                );
+            }
+            for (magic_byte, mask_byte) in magic.iter_mut().zip(mask.iter()) {
+                *magic_byte &= mask_byte;
+            }
+            Some(mask)
+        };
+
+        Ok(Matcher::Magic {
+            offset,
+            magic,
+            mask,
+        })
+    }
+
+    fn parse_extension_matcher(offset: &[u8], extension: &[u8], mask: &[u8]) -> Result<Matcher> {
+        if !offset.is_empty() || !mask.is_empty() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the offset and the mask must be empty for extension matching"
+            );
+        }
+        if extension.is_empty() || extension.contains(&b'/') {
+            return_errno_with_message!(Errno::EINVAL, "the extension is invalid");
+        }
+
+        Ok(Matcher::Extension(extension.to_vec()))
+    }
+
+    fn parse_flags(flags: &[u8]) -> Result<BinfmtFlags> {
+        let mut res = BinfmtFlags::empty();
+        for flag in flags {
+            res |= match flag {
+                b'P' => BinfmtFlags::PRESERVE_ARGV0,
+                b'O' => BinfmtFlags::OPEN_BINARY,
+                b'C' => BinfmtFlags::CREDENTIALS | BinfmtFlags::OPEN_BINARY,
+                b'F' => BinfmtFlags::FIX_BINARY,
+                _ => return_errno_with_message!(Errno::EINVAL, "the flag is unknown"),
+            };
+        }
+        Ok(res)
+    }
+
+    /// Returns the name of the entry.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path of the interpreter.
+    pub fn interpreter(&self) -> &str {
+        &self.interpreter
+    }
+
+    /// Returns the interpreter that was opened at registration time, if any.
+    pub fn interpreter_file(&self) -> Option<&Path> {
+        self.interpreter_file.as_ref()
+    }
+
+    /// Returns the flags of the entry.
+    pub fn flags(&self) -> BinfmtFlags {
+        self.flags
+    }
+
+    pub(super) fn is_enabled(&self) -> bool {
+        self.is_enabled.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_enabled(&self, is_enabled: bool) {
+        self.is_enabled.store(is_enabled, Ordering::Relaxed);
+    }
+
+    /// Returns whether the entry recognizes the binary.
+    ///
+    /// `header` is the beginning of the binary and `file_name` is the last component of its path.
+    pub(super) fn matches(&self, header: &[u8], file_name: &str) -> bool {
+        match &self.matcher {
+            Matcher::Magic {
+                offset,
+                magic,
+                mask,
+            } => magic.iter().enumerate().all(|(i, magic_byte)| {
+                // Following Linux, the bytes beyond the end of the file are zeros.
+                let byte = header.get(offset + i).copied().unwrap_or(0);
+                let mask_byte = mask.as_ref().map_or(0xff, |mask| mask[i]);
+                (byte ^ magic_byte) & mask_byte == 0
+            }),
+            Matcher::Extension(extension) => file_name
+                .rsplit_once('.')
+                .is_some_and(|(_, file_extension)| file_extension.as_bytes() == extension),
+        }
+    }
+
+    /// Returns the status of the entry as shown in its file.
+    pub(super) fn status(&self) -> String {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c#L567-L612>
+
+        let mut status = String::new();
+
+        let state = if self.is_enabled() {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        writeln!(status, "{}", state).unwrap();
+        writeln!(status, "interpreter {}", self.interpreter).unwrap();
+
+        status.push_str("flags: ");
+        for (flag, letter) in [
+            (BinfmtFlags::PRESERVE_ARGV0, 'P'),
+            (BinfmtFlags::OPEN_BINARY, 'O'),
+            (BinfmtFlags::CREDENTIALS, 'C'),
+            (BinfmtFlags::FIX_BINARY, 'F'),
+        ] {
+            if self.flags.contains(flag) {
+                status.push(letter);
+            }
+        }
+        status.push('\n');
+
+        match &self.matcher {
+            Matcher::Magic {
+                offset,
+                magic,
+                mask,
+            } => {
+                writeln!(status, "offset {}", offset).unwrap();
+                status.push_str("magic ");
+                magic
+                    .iter()
+                    .for_each(|byte| write!(status, "{:02x}", byte).unwrap());
+                status.push('\n');
+                if let Some(mask) = mask {
+                    status.push_str("mask ");
+                    mask.iter()
+                        .for_each(|byte| write!(status, "{:02x}", byte).unwrap());
+                    status.push('\n');
+                }
+            }
+            Matcher::Extension(extension) => {
+                writeln!(status, "extension .{}", String::from_utf8_lossy(extension)).unwrap();
+            }
+        }
+
+        status
+    }
+}
+
+/// Replaces the `\xHH` escape sequences with the bytes they represent.
+///
+/// A sequence may have one or two hexadecimal digits. Other backslashes are kept as is.
+fn unescape_hex(bytes: &[u8]) -> Vec<u8> {
+    fn hex_digit(byte: u8) -> Option<u8> {
+        (byte as char).to_digit(16).map(|digit| digit as u8)
+    }
+
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && bytes.get(i + 1) == Some(&b'x')
+            && let Some(high) = bytes.get(i + 2).copied().and_then(hex_digit)
+        {
+            match bytes.get(i + 3).copied().and_then(hex_digit) {
+                Some(low) => {
+                    res.push(high << 4 | low);
+                    i += 4;
+                }
+                None => {
+                    res.push(high);
+                    i += 3;
+                }
+            }
+            continue;
+        }
+
+        res.push(bytes[i]);
+        i += 1;
+    }
+
+    res
+}
+
+#[cfg(ktest)]
+mod test {
+    use alloc::vec;
+
+    use ostd::prelude::*;
+
+    use super::{BinfmtEntry, BinfmtFlags, Matcher};
+
+    #[ktest]
+    fn parse_magic_registration() {
+        const QEMU_AARCH64: &[u8] = b":qemu-aarch64:M::\\x7fELF\\x02\\x01\\x01\\x00\\x00\\x00\\x00\\x00\\x00\\x00\\x00\\x00\\x02\\x00\\xb7\\x00:\\xff\\xff\\xff\\xff\\xff\\xff\\xff\\x00\\xff\\xff\\xff\\xff\\xff\\xff\\xff\\xff\\xfe\\xff\\xff\\xff:/usr/bin/qemu-aarch64-static:FPC\n";
+        let entry = BinfmtEntry::parse(QEMU_AARCH64).unwrap();
+        assert_eq!(entry.name, "qemu-aarch64");
+        assert_eq!(entry.interpreter, "/usr/bin/qemu-aarch64-static");
+        assert_eq!(
+            entry.flags,
+            BinfmtFlags::FIX_BINARY
+                | BinfmtFlags::PRESERVE_ARGV0
+                | BinfmtFlags::CREDENTIALS
+                | BinfmtFlags::OPEN_BINARY
+        );
+
+        let mut header = vec![0u8; 64];
+        header[..4].copy_from_slice(b"\x7fELF");
+        header[4..7].copy_from_slice(&[2, 1, 1]);
+        header[7] = 3; // ELFOSABI_LINUX, masked out
+        header[16] = 3; // ET_DYN, whose lowest bit is masked out
+        header[18] = 0xb7; // EM_AARCH64
+        assert!(entry.matches(&header, "a.out"));
+
+        header[18] = 0x3e; // EM_X86_64
+        assert!(!entry.matches(&header, "a.out"));
+    }
+
+    #[ktest]
+    fn parse_extension_registration() {
+        let entry = BinfmtEntry::parse(b":jar:E::jar::/usr/bin/jexec:").unwrap();
+        assert_eq!(entry.matcher, Matcher::Extension(b"jar".to_vec()));
+        assert!(entry.flags.is_empty());
+        assert!(entry.matches(&[], "app.jar"));
+        assert!(!entry.matches(&[], "app.jar.bak"));
+        assert!(!entry.matches(&[], "jar"));
+    }
+
+    #[ktest]
+    fn parse_invalid_registration() {
+        // Unknown type
+        assert!(BinfmtEntry::parse(b":name:X::abc::/bin/interp:").is_err());
+        // Bad name
+        assert!(BinfmtEntry::parse(b":..:E::abc::/bin/interp:").is_err());
+        // Mismatched mask
+        assert!(BinfmtEntry::parse(b":name:M::abc:\\xff:/bin/interp:").is_err());
+        // Magic beyond the matched range
+        assert!(BinfmtEntry::parse(b":name:M:255:abc::/bin/interp:").is_err());
+        // Offset for extension matching
+        assert!(BinfmtEntry::parse(b":name:E:1:abc::/bin/interp:").is_err());
+        // Unknown flag
+        assert!(BinfmtEntry::parse(b":name:E::abc::/bin/interp:X").is_err());
+        // Empty interpreter
+        assert!(BinfmtEntry::parse(b":name:E::abc:::").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::Once;
+
+use super::{entry::BinfmtEntry, inode::RootInode};
+use crate::{
+    fs::{
+        pseudofs::AnonDeviceId,
+        utils::NAME_MAX,
+        vfs::{
+            file_system::{FileSystem, FsEventSubscriberStats, SuperBlock},
+            inode::Inode,
+            registry::{FsCreationCtx, FsProperties, FsType},
+        },
+    },
+    prelude::*,
+};
+
+/// A file system for registering interpreters for binary formats.
+///
+/// Like in Linux, all mounts of `binfmt_misc` share the same set of entries.
+pub(super) struct BinfmtMiscFs {
+    _anon_device_id: AnonDeviceId,
+    sb: SuperBlock,
+    root: Arc<RootInode>,
+    next_ino: AtomicU64,
+    fs_event_subscriber_stats: FsEventSubscriberStats,
+}
+
+// Magic number for `binfmt_misc` (taken from Linux).
+const BINFMTFS_MAGIC: u64 = 0x42494e4d;
+pub(super) const BLOCK_SIZE: usize = 4096;
+
+pub(super) const ROOT_INO: u64 = 1;
+pub(super) const REGISTER_INO: u64 = 2;
+pub(super) const STATUS_INO: u64 = 3;
+const FIRST_ENTRY_INO: u64 = 4;
+
+impl BinfmtMiscFs {
+    /// Returns the `BinfmtMiscFs` singleton.
+    pub(super) fn singleton() -> &'static Arc<BinfmtMiscFs> {
+        static SINGLETON: Once<Arc<BinfmtMiscFs>> = Once::new();
+
+        SINGLETON.call_once(Self::new)
+    }
+
+    fn new() -> Arc<Self> {
+        let anon_device_id =
+            AnonDeviceId::acquire().expect("no device ID is available for binfmt_misc");
+        let sb = SuperBlock::new(BINFMTFS_MAGIC, BLOCK_SIZE, NAME_MAX, anon_device_id.id());
+
+        Arc::new_cyclic(|weak_self| Self {
+            _anon_device_id: anon_device_id,
+            sb: sb.clone(),
+            root: RootInode::new(weak_self.clone(), &sb),
+            next_ino: AtomicU64::new(FIRST_ENTRY_INO),
+            fs_event_subscriber_stats: FsEventSubscriberStats::new(),
+        })
+    }
+
+    pub(super) fn root(&self) -> &Arc<RootInode> {
+        &self.root
+    }
+
+    /// Allocates an inode number for a new entry.
+    pub(super) fn alloc_ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Finds the most recently registered enabled entry that recognizes the binary.
+    pub(super) fn find_entry(&self, header: &[u8], file_name: &str) -> Option<Arc<BinfmtEntry>> {
+        self.root.find_entry(header, file_name)
+    }
+}
+
+impl FileSystem for BinfmtMiscFs {
+    fn name(&self) -> &'static str {
+        "binfmt_misc"
+    }
+
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn fs_event_subscriber_stats(&self) -> &FsEventSubscriberStats {
+        &self.fs_event_subscriber_stats
+    }
+}
+
+pub(super) struct BinfmtMiscFsType;
+
+impl FsType for BinfmtMiscFsType {
+    fn name(&self) -> &'static str {
+        "binfmt_misc"
+    }
+
+    fn properties(&self) -> FsProperties {
+        FsProperties::empty()
+    }
+
+    fn create(&self, _fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
+        Ok(BinfmtMiscFs::singleton().clone())
+    }
+
+    fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
+        None
+    }
+}
@@ -0,0 +1,579 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use aster_util::{printer::VmPrinter, slot_vec::SlotVec};
+use inherit_methods_macro::inherit_methods;
+
+use super::{
+    entry::{BinfmtEntry, MAX_REGISTER_LENGTH},
+    fs::{BLOCK_SIZE, BinfmtMiscFs, REGISTER_INO, ROOT_INO, STATUS_INO},
+};
+use crate::{
+    fs::{
+        file::{InodeMode, InodeType, StatusFlags, mkmod},
+        utils::DirentVisitor,
+        vfs::{
+            file_system::{FileSystem, SuperBlock},
+            inode::{Extension, FileOps, Inode, Metadata, MknodType, RevalidationPolicy},
+        },
+    },
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+/// Shared inode state in `binfmt_misc`.
+struct Common {
+    metadata: RwLock<Metadata>,
+    extension: Extension,
+    fs: Weak<BinfmtMiscFs>,
+}
+
+impl Common {
+    fn new(metadata: Metadata, fs: Weak<BinfmtMiscFs>) -> Self {
+        Self {
+            metadata: RwLock::new(metadata),
+            extension: Extension::new(),
+            fs,
+        }
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().last_access_at
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().last_access_at = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().last_modify_at
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().last_modify_at = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().last_meta_change_at
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().last_meta_change_at = time;
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn extension(&self) -> &Extension {
+        &self.extension
+    }
+}
+
+/// The root directory of `binfmt_misc`.
+///
+/// Besides the `register` and `status` files, the directory contains one file per entry.
+pub(super) struct RootInode {
+    register: Arc<ControlInode>,
+    status: Arc<ControlInode>,
+    entries: RwLock<SlotVec<Arc<EntryInode>>>,
+    is_enabled: AtomicBool,
+    common: Common,
+}
+
+impl RootInode {
+    pub(super) fn new(fs: Weak<BinfmtMiscFs>, sb: &SuperBlock) -> Arc<Self> {
+        let new_control = |kind, ino, mode| {
+            let metadata = Metadata::new_file(ino, mode, BLOCK_SIZE, sb.container_dev_id);
+            Arc::new(ControlInode {
+                kind,
+                common: Common::new(metadata, fs.clone()),
+            })
+        };
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c#L924-L928>
+        let register = new_control(ControlKind::Register, REGISTER_INO, mkmod!(u+w));
+        let status = new_control(ControlKind::Status, STATUS_INO, mkmod!(a+r, u+w));
+        let metadata =
+            Metadata::new_dir(ROOT_INO, mkmod!(a+rx, u+w), BLOCK_SIZE, sb.container_dev_id);
+
+        Arc::new(Self {
+            register,
+            status,
+            entries: RwLock::new(SlotVec::new()),
+            is_enabled: AtomicBool::new(true),
+            common: Common::new(metadata, fs),
+        })
+    }
+
+    pub(super) fn find_entry(&self, header: &[u8], file_name: &str) -> Option<Arc<BinfmtEntry>> {
+        if !self.is_enabled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        // Like Linux, the newest entry takes precedence. Slots are reused after removal, so the
+        // registration order is given by the inode numbers, which are allocated in ascending
+        // order.
+        self.entries
+            .read()
+            .iter()
+            .filter(|inode| inode.entry.is_enabled() && inode.entry.matches(header, file_name))
+            .max_by_key(|inode| inode.ino())
+            .map(|inode| inode.entry.clone())
+    }
+
+    fn find_entry_inode(&self, name: &str) -> Option<Arc<EntryInode>> {
+        self.entries
+            .read()
+            .iter()
+            .find(|inode| inode.entry.name() == name)
+            .cloned()
+    }
+
+    fn add_entry(&self, entry: BinfmtEntry) -> Result<()> {
+        let mut entries = self.entries.write();
+
+        let name = entry.name();
+        if name == "register"
+            || name == "status"
+            || entries.iter().any(|inode| inode.entry.name() == name)
+        {
+            return_errno_with_message!(Errno::EEXIST, "the entry already exists");
+        }
+
+        let fs = self.common.fs.upgrade().unwrap();
+        let metadata = Metadata::new_file(
+            fs.alloc_ino(),
+            mkmod!(a+r, u+w),
+            BLOCK_SIZE,
+            fs.sb().container_dev_id,
+        );
+        entries.put(Arc::new(EntryInode {
+            entry: Arc::new(entry),
+            common: Common::new(metadata, self.common.fs.clone()),
+        }));
+
+        Ok(())
+    }
+
+    fn remove_entry(&self, inode: &EntryInode) {
+        let mut entries = self.entries.write();
+
+        let idx = entries
+            .idxes_and_items()
+            .find(|(_, entry_inode)| core::ptr::eq(entry_inode.as_ref(), inode))
+            .map(|(idx, _)| idx);
+        if let Some(idx) = idx {
+            entries.remove(idx);
+        }
+    }
+
+    fn remove_all_entries(&self) {
+        *self.entries.write() = SlotVec::new();
+    }
+}
+
+impl FileOps for RootInode {
+    fn read_at(
+        &self,
+        _offset: usize,
+        _writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the 4 special entries.
+            if *offset == 0 {
+                visitor.visit(".", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+            if *offset == 1 {
+                visitor.visit("..", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+            if *offset == 2 {
+                visitor.visit("register", self.register.ino(), InodeType::File, *offset)?;
+                *offset += 1;
+            }
+            if *offset == 3 {
+                visitor.visit("status", self.status.ino(), InodeType::File, *offset)?;
+                *offset += 1;
+            }
+
+            // Read the entries.
+            let entries = self.entries.read();
+            let start_offset = *offset;
+            for (idx, inode) in entries
+                .idxes_and_items()
+                .map(|(idx, inode)| (idx + 4, inode))
+                .skip_while(|(idx, _)| idx < &start_offset)
+            {
+                visitor.visit(inode.entry.name(), inode.ino(), InodeType::File, idx)?;
+                *offset = idx + 1;
+            }
+            Ok(())
+        };
+
+        let mut iterate_offset = offset;
+        match try_readdir(&mut iterate_offset, visitor) {
+            Err(e) if offset == iterate_offset => Err(e),
+            _ => Ok(iterate_offset - offset),
+        }
+    }
+}
+
+#[inherit_methods(from = "self.common")]
+impl Inode for RootInode {
+    fn size(&self) -> usize;
+    fn metadata(&self) -> Metadata;
+    fn extension(&self) -> &Extension;
+    fn ino(&self) -> u64;
+    fn mode(&self) -> Result<InodeMode>;
+    fn set_mode(&self, mode: InodeMode) -> Result<()>;
+    fn owner(&self) -> Result<Uid>;
+    fn set_owner(&self, uid: Uid) -> Result<()>;
+    fn group(&self) -> Result<Gid>;
+    fn set_group(&self, gid: Gid) -> Result<()>;
+    fn atime(&self) -> Duration;
+    fn set_atime(&self, time: Duration);
+    fn mtime(&self) -> Duration;
+    fn set_mtime(&self, time: Duration);
+    fn ctime(&self) -> Duration;
+    fn set_ctime(&self, time: Duration);
+    fn fs(&self) -> Arc<dyn FileSystem>;
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn type_(&self) -> InodeType {
+        InodeType::Dir
+    }
+
+    fn create(&self, _name: &str, _type_: InodeType, _mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn mknod(&self, _name: &str, _mode: InodeMode, _type_: MknodType) -> Result<Arc<dyn Inode>> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn link(&self, _old: &Arc<dyn Inode>, _name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        if self.lookup(name).is_err() {
+            return_errno_with_message!(Errno::ENOENT, "the entry does not exist");
+        }
+        return_errno_with_message!(
+            Errno::EPERM,
+            "binfmt_misc files cannot be unlinked; write -1 to remove an entry"
+        );
+    }
+
+    fn rmdir(&self, _name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode: Arc<dyn Inode> = match name {
+            "." | ".." => self.fs().root_inode(),
+            "register" => self.register.clone(),
+            "status" => self.status.clone(),
+            name => self
+                .find_entry_inode(name)
+                .ok_or(Error::new(Errno::ENOENT))?,
+        };
+        Ok(inode)
+    }
+
+    fn rename(&self, _old_name: &str, _target: &Arc<dyn Inode>, _new_name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn revalidation_policy(&self) -> RevalidationPolicy {
+        // Entries are added and removed by writing to files, bypassing VFS dentry updates.
+        RevalidationPolicy::REVALIDATE_EXISTS | RevalidationPolicy::REVALIDATE_ABSENT
+    }
+
+    fn revalidate_exists(&self, name: &str, child: &dyn Inode) -> bool {
+        match name {
+            "register" | "status" => true,
+            name => self
+                .find_entry_inode(name)
+                .is_some_and(|inode| core::ptr::addr_eq(inode.as_ref(), child)),
+        }
+    }
+
+    fn revalidate_absent(&self, name: &str) -> bool {
+        self.find_entry_inode(name).is_none()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ControlKind {
+    /// The `register` file, which accepts new entries.
+    Register,
+    /// The `status` file, which enables or disables `binfmt_misc` as a whole.
+    Status,
+}
+
+/// The `register` or `status` file of `binfmt_misc`.
+struct ControlInode {
+    kind: ControlKind,
+    common: Common,
+}
+
+impl ControlInode {
+    fn root(&self) -> Arc<RootInode> {
+        self.common.fs.upgrade().unwrap().root().clone()
+    }
+}
+
+impl FileOps for ControlInode {
+    fn read_at(
+        &self,
+        offset: usize,
+        writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        if let ControlKind::Register = self.kind {
+            return_errno_with_message!(Errno::EINVAL, "the register file cannot be read");
+        }
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        if self.root().is_enabled.load(Ordering::Relaxed) {
+            writeln!(printer, "enabled")?;
+        } else {
+            writeln!(printer, "disabled")?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        let root = self.root();
+
+        match self.kind {
+            ControlKind::Register => {
+                let len = reader.remain();
+                if len > MAX_REGISTER_LENGTH {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the registration string is too long"
+                    );
+                }
+                let mut buf = vec![0u8; len];
+                reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+
+                let entry = BinfmtEntry::new_from_registration(&buf)?;
+                root.add_entry(entry)?;
+
+                Ok(len)
+            }
+            ControlKind::Status => {
+                let (command, len) = read_command(reader)?;
+                match command {
+                    Command::Disable => root.is_enabled.store(false, Ordering::Relaxed),
+                    Command::Enable => root.is_enabled.store(true, Ordering::Relaxed),
+                    Command::Remove => root.remove_all_entries(),
+                }
+                Ok(len)
+            }
+        }
+    }
+}
+
+#[inherit_methods(from = "self.common")]
+impl Inode for ControlInode {
+    fn size(&self) -> usize;
+    fn metadata(&self) -> Metadata;
+    fn extension(&self) -> &Extension;
+    fn ino(&self) -> u64;
+    fn mode(&self) -> Result<InodeMode>;
+    fn set_mode(&self, mode: InodeMode) -> Result<()>;
+    fn owner(&self) -> Result<Uid>;
+    fn set_owner(&self, uid: Uid) -> Result<()>;
+    fn group(&self) -> Result<Gid>;
+    fn set_group(&self, gid: Gid) -> Result<()>;
+    fn atime(&self) -> Duration;
+    fn set_atime(&self, time: Duration);
+    fn mtime(&self) -> Duration;
+    fn set_mtime(&self, time: Duration);
+    fn ctime(&self) -> Duration;
+    fn set_ctime(&self, time: Duration);
+    fn fs(&self) -> Arc<dyn FileSystem>;
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Ok(())
+    }
+
+    fn type_(&self) -> InodeType {
+        InodeType::File
+    }
+
+    fn seek_end(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// The file of a `binfmt_misc` entry.
+struct EntryInode {
+    entry: Arc<BinfmtEntry>,
+    common: Common,
+}
+
+impl FileOps for EntryInode {
+    fn read_at(
+        &self,
+        offset: usize,
+        writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        write!(printer, "{}", self.entry.status())?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        let (command, len) = read_command(reader)?;
+        match command {
+            Command::Disable => self.entry.set_enabled(false),
+            Command::Enable => self.entry.set_enabled(true),
+            Command::Remove => self.common.fs.upgrade().unwrap().root().remove_entry(self),
+        }
+        Ok(len)
+    }
+}
+
+#[inherit_methods(from = "self.common")]
+impl Inode for EntryInode {
+    fn size(&self) -> usize;
+    fn metadata(&self) -> Metadata;
+    fn extension(&self) -> &Extension;
+    fn ino(&self) -> u64;
+    fn mode(&self) -> Result<InodeMode>;
+    fn set_mode(&self, mode: InodeMode) -> Result<()>;
+    fn owner(&self) -> Result<Uid>;
+    fn set_owner(&self, uid: Uid) -> Result<()>;
+    fn group(&self) -> Result<Gid>;
+    fn set_group(&self, gid: Gid) -> Result<()>;
+    fn atime(&self) -> Duration;
+    fn set_atime(&self, time: Duration);
+    fn mtime(&self) -> Duration;
+    fn set_mtime(&self, time: Duration);
+    fn ctime(&self) -> Duration;
+    fn set_ctime(&self, time: Duration);
+    fn fs(&self) -> Arc<dyn FileSystem>;
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Ok(())
+    }
+
+    fn type_(&self) -> InodeType {
+        InodeType::File
+    }
+
+    fn seek_end(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// A command written to the `status` file or the file of an entry.
+enum Command {
+    /// Disables `binfmt_misc` or the entry (`0`).
+    Disable,
+    /// Enables `binfmt_misc` or the entry (`1`).
+    Enable,
+    /// Removes all entries or the entry (`-1`).
+    Remove,
+}
+
+/// Reads a command from `reader`.
+///
+/// Returns the command and the number of bytes read.
+fn read_command(reader: &mut VmReader) -> Result<(Command, usize)> {
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c#L541-L564>
+
+    let len = reader.remain();
+    if len > 3 {
+        return_errno_with_message!(Errno::EINVAL, "the command is too long");
+    }
+    let mut buf = [0u8; 3];
+    reader.read_fallible(&mut VmWriter::from(&mut buf[..len]))?;
+
+    let command = buf[..len].strip_suffix(b"\n").unwrap_or(&buf[..len]);
+    match command {
+        b"0" => Ok((Command::Disable, len)),
+        b"1" => Ok((Command::Enable, len)),
+        b"-1" => Ok((Command::Remove, len)),
+        _ => return_errno_with_message!(Errno::EINVAL, "the command is invalid"),
+    }
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The `binfmt_misc` file system.
+//!
+//! `binfmt_misc` lets user space register interpreters for binary formats that the kernel
+//! cannot execute by itself, e.g., binaries for foreign architectures or Java archives.
+//! An entry is registered by writing `:name:type:offset:magic:mask:interpreter:flags` to the
+//! `register` file. Then `execve` runs the interpreter for the binaries that the entry
+//! recognizes, either by magic bytes or by file name extension.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/binfmt-misc.html>
+
+pub use self::entry::{BinfmtEntry, BinfmtFlags};
+use self::fs::{BinfmtMiscFs, BinfmtMiscFsType};
+use crate::prelude::*;
+
+mod entry;
+mod fs;
+mod inode;
+
+pub(super) fn init() {
+    crate::fs::vfs::registry::register(&BinfmtMiscFsType).unwrap();
+}
+
+/// Finds the enabled entry that recognizes a binary.
+///
+/// If several entries recognize the binary, the most recently registered one is chosen.
+/// `header` is the beginning of the binary and `file_name` is the last component of its path.
+pub fn find_entry(header: &[u8], file_name: &str) -> Option<Arc<BinfmtEntry>> {
+    BinfmtMiscFs::singleton().find_entry(header, file_name)
+}
@@ -808,11 +808,12 @@ fn with_process_cgroup_locked<F>(pid: Pid, op: F) -> Result<()>
 where
     F: FnOnce(Arc<Process>, &mut CgroupMembership) -> Result<()>,
 {
+    let current = current!();
     let process = if pid == 0 {
-        current!()
+        current
     } else {
         pid_table::pid_table_mut()
-            .get_process(pid)
+            .get_process_in_ns(pid, current.pid_ns())
             .ok_or(Error::InvalidOperation)?
     };
 
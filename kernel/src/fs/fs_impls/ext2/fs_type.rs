@@ -22,7 +22,7 @@ impl FsType for Ext2Type {
     }
 
     fn properties(&self) -> FsProperties {
-        FsProperties::NEED_DISK
+        FsProperties::NEED_DISK | FsProperties::ALLOW_IDMAP
     }
 
     fn create(&self, fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
@@ -4,6 +4,7 @@
 //!
 //! This module contains all the specific file system implementations supported by the kernel.
 
+pub mod binfmt_misc;
 pub mod cgroupfs;
 pub mod configfs;
 pub mod devpts;
@@ -13,6 +14,7 @@ pub mod overlayfs;
 pub mod procfs;
 pub mod pseudofs;
 pub mod ramfs;
+pub mod securityfs;
 pub mod sysfs;
 pub mod tmpfs;
 pub mod virtiofs;
@@ -22,10 +24,12 @@ pub(super) fn init() {
     procfs::init();
     cgroupfs::init();
     configfs::init();
+    securityfs::init();
     ramfs::init();
     tmpfs::init();
     devpts::init();
     pseudofs::init();
+    binfmt_misc::init();
 
     ext2::init();
     exfat::init();
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/keys` and `/proc/key-users` file support, which provide
+//! information about the keys of the key retention service.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/keyrings.7.html>
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+    security::keys,
+};
+
+/// Represents the inode at `/proc/keys`.
+pub struct KeysFileOps;
+
+impl KeysFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/proc.c#L64>
+        ProcFile::new(Self, parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for KeysFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // Only the keys that can be viewed by the reading thread are listed.
+        keys::print_keys(&mut printer, current_thread!().as_posix_thread().unwrap())?;
+
+        Ok(printer.bytes_written())
+    }
+}
+
+/// Represents the inode at `/proc/key-users`.
+pub struct KeyUsersFileOps;
+
+impl KeyUsersFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/proc.c#L65>
+        ProcFile::new(Self, parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for KeyUsersFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        keys::print_key_users(&mut printer)?;
+
+        Ok(printer.bytes_written())
+    }
+}
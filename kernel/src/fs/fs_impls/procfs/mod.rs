@@ -2,6 +2,7 @@
 
 use core::sync::atomic::{AtomicU64, Ordering};
 
+use ostd::task::Task;
 use template::{
     ListedEntry, ProcDir, ProcDirOps, ReaddirEntry, StaticDirEntry, keyed_readdir_entries,
     listed_entries_from_table, lookup_child_from_table, sequential_readdir_entries,
@@ -11,9 +12,11 @@ use template::{
 use self::{
     cmdline::CmdLineFileOps,
     cpuinfo::CpuInfoFileOps,
+    keys::{KeyUsersFileOps, KeysFileOps},
     loadavg::LoadAvgFileOps,
     meminfo::MemInfoFileOps,
     mounts::MountsSymOps,
+    net::NetDirOps,
     pid::{PidDirOps, TidDirOps},
     self_::SelfSymOps,
     sys::SysDirOps,
@@ -33,9 +36,10 @@ use crate::{
             registry::{FsCreationCtx, FsProperties, FsType},
         },
     },
+    net::net_ns::NetNamespace,
     prelude::*,
     process::{
-        Pid,
+        Pid, PidNamespace,
         pid_table::{self, PidEntryType},
     },
 };
@@ -43,9 +47,11 @@ use crate::{
 mod cmdline;
 mod cpuinfo;
 mod filesystems;
+mod keys;
 mod loadavg;
 mod meminfo;
 mod mounts;
+mod net;
 mod pid;
 mod self_;
 mod stat;
@@ -76,24 +82,33 @@ struct ProcFs {
     root: Arc<dyn Inode>,
     inode_allocator: AtomicU64,
     fs_event_subscriber_stats: FsEventSubscriberStats,
+    /// The PID namespace whose processes are shown in the procfs.
+    pid_ns: Arc<PidNamespace>,
 }
 
 impl ProcFs {
-    pub(self) fn new() -> Arc<Self> {
+    pub(self) fn new(pid_ns: Arc<PidNamespace>) -> Arc<Self> {
         let anon_device_id = AnonDeviceId::acquire().expect("no device ID is available for procfs");
         let sb = SuperBlock::new(PROC_MAGIC, BLOCK_SIZE, NAME_MAX, anon_device_id.id());
         Arc::new_cyclic(|weak_fs| Self {
             _anon_device_id: anon_device_id,
             sb: sb.clone(),
-            root: RootDirOps::new_inode(weak_fs.clone(), &sb),
+            root: RootDirOps::new_inode(weak_fs.clone(), &sb, pid_ns.clone()),
             inode_allocator: AtomicU64::new(PROC_ROOT_INO + 1),
             fs_event_subscriber_stats: FsEventSubscriberStats::new(),
+            pid_ns,
         })
     }
 
     pub(self) fn alloc_id(&self) -> u64 {
         self.inode_allocator.fetch_add(1, Ordering::Relaxed)
     }
+
+    /// Returns the PID namespace of the procfs that `inode` belongs to.
+    pub(self) fn pid_ns_of(inode: &dyn Inode) -> Arc<PidNamespace> {
+        let fs = inode.fs();
+        fs.downcast_ref::<ProcFs>().unwrap().pid_ns.clone()
+    }
 }
 
 impl FileSystem for ProcFs {
@@ -129,8 +144,9 @@ impl FsType for ProcFsType {
         FsProperties::empty()
     }
 
-    fn create(&self, _fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
-        Ok(ProcFs::new())
+    fn create(&self, fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
+        // The procfs shows the processes in the PID namespace of the process that mounts it.
+        Ok(ProcFs::new(fs_creation_ctx.pid_ns().clone()))
     }
 
     fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
@@ -139,13 +155,19 @@ impl FsType for ProcFsType {
 }
 
 /// Represents the inode at `/proc`.
-struct RootDirOps;
+struct RootDirOps {
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl RootDirOps {
-    pub fn new_inode(fs: Weak<ProcFs>, sb: &SuperBlock) -> Arc<dyn Inode> {
+    pub fn new_inode(
+        fs: Weak<ProcFs>,
+        sb: &SuperBlock,
+        pid_ns: Arc<PidNamespace>,
+    ) -> Arc<dyn Inode> {
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/root.c#L368>
         let fs: Weak<dyn FileSystem> = fs;
-        ProcDir::new_root(Self, fs, PROC_ROOT_INO, sb, mkmod!(a+rx))
+        ProcDir::new_root(Self { pid_ns }, fs, PROC_ROOT_INO, sb, mkmod!(a+rx))
     }
 
     const STATIC_ENTRIES: &'static [StaticEntry] = &[
@@ -156,9 +178,12 @@ impl RootDirOps {
             InodeType::File,
             FileSystemsFileOps::new_inode,
         ),
+        ("key-users", InodeType::File, KeyUsersFileOps::new_inode),
+        ("keys", InodeType::File, KeysFileOps::new_inode),
         ("loadavg", InodeType::File, LoadAvgFileOps::new_inode),
         ("meminfo", InodeType::File, MemInfoFileOps::new_inode),
         ("mounts", InodeType::SymLink, MountsSymOps::new_inode),
+        ("net", InodeType::Dir, NetDirOps::new_inode),
         ("self", InodeType::SymLink, SelfSymOps::new_inode),
         ("stat", InodeType::File, StatFileOps::new_inode),
         ("sys", InodeType::Dir, SysDirOps::new_inode),
@@ -174,7 +199,9 @@ impl RootDirOps {
 
 impl ProcDirOps for RootDirOps {
     fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
-        if let Ok(pid) = name.parse::<Pid>() {
+        if let Ok(pid) = name.parse::<Pid>()
+            && let Some(pid) = self.pid_ns.id_of(pid)
+        {
             let pid_entry = {
                 let pid_table = pid_table::pid_table_mut();
                 pid_table.get_entry(pid)
@@ -182,12 +209,13 @@ impl ProcDirOps for RootDirOps {
             if let Some(pid_entry) = pid_entry
                 && let Some(type_) = pid_entry.type_()
             {
+                let pid_ns = self.pid_ns.clone();
                 return Ok(match type_ {
                     PidEntryType::Process => {
-                        PidDirOps::new_inode(pid_entry, this_dir.this_weak().clone())
+                        PidDirOps::new_inode(pid_entry, pid_ns, this_dir.this_weak().clone())
                     }
                     PidEntryType::Thread => {
-                        TidDirOps::new_inode(pid_entry, this_dir.this_weak().clone())
+                        TidDirOps::new_inode(pid_entry, pid_ns, this_dir.this_weak().clone())
                     }
                 });
             }
@@ -217,7 +245,8 @@ impl ProcDirOps for RootDirOps {
             let pid_table = pid_table::pid_table_mut();
             pid_table
                 .iter_processes()
-                .filter_map(|process| usize::try_from(process.pid()).ok())
+                .filter_map(|process| self.pid_ns.nr_of(process.pid()))
+                .filter_map(|pid| usize::try_from(pid).ok())
                 .collect::<Vec<_>>()
         };
 
@@ -263,6 +292,9 @@ impl ProcDirOps for RootDirOps {
         let Ok(pid) = name.parse::<Pid>() else {
             return true;
         };
+        let Some(pid) = self.pid_ns.id_of(pid) else {
+            return true;
+        };
 
         let pid_entry = {
             let pid_table = pid_table::pid_table_mut();
@@ -275,3 +307,11 @@ impl ProcDirOps for RootDirOps {
 
 type StaticEntry = StaticDirEntry<fn(Weak<dyn Inode>) -> Arc<dyn Inode>>;
 type StaticEntryWithOps<T> = StaticDirEntry<fn(&T, Weak<dyn Inode>) -> Arc<dyn Inode>>;
+
+/// Returns the network namespace of the current thread.
+fn current_net_ns() -> Arc<NetNamespace> {
+    let current_task = Task::current().unwrap();
+    let thread_local = current_task.as_thread_local().unwrap();
+    let ns_proxy = thread_local.borrow_ns_proxy();
+    ns_proxy.unwrap().net_ns().clone()
+}
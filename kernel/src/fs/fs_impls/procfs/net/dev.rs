@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::iface::IfaceCounter;
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            current_net_ns,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/net/dev`.
+///
+/// The file shows the statistics of the network interfaces.
+pub struct DevFileOps;
+
+impl DevFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/core/net-procfs.c#L261>
+        ProcFile::new(Self, parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for DevFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        writeln!(
+            printer,
+            "Inter-|   Receive                                                |  Transmit"
+        )?;
+        writeln!(
+            printer,
+            " face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed"
+        )?;
+
+        // TODO: Report the errors and the other statistics.
+        for iface in current_net_ns().ifaces() {
+            writeln!(
+                printer,
+                "{:>6}: {:7} {:7} {:4} {:4} {:4} {:5} {:10} {:9} {:8} {:7} {:4} {:4} {:4} {:5} {:7} {:10}",
+                iface.name().to_string_lossy(),
+                iface.counter(IfaceCounter::RxBytes),
+                iface.counter(IfaceCounter::RxPackets),
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                iface.counter(IfaceCounter::TxBytes),
+                iface.counter(IfaceCounter::TxPackets),
+                0,
+                iface.counter(IfaceCounter::TxDropped),
+                0,
+                0,
+                0,
+                0,
+            )?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::{IpAddress, IpEndpoint};
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            current_net_ns,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    net::socket::ip::{InetSocketProtocol, InetSocketState, inet_socket_infos},
+    prelude::*,
+};
+
+/// Represents the inodes at `/proc/net/{tcp,tcp6,udp,udp6}`.
+///
+/// The files show the TCP or UDP sockets of IPv4 or IPv6.
+pub struct InetFileOps {
+    protocol: InetSocketProtocol,
+    is_ipv6: bool,
+}
+
+impl InetFileOps {
+    pub fn new_tcp_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/tcp_ipv4.c#L2906>
+        Self::new_inode(InetSocketProtocol::Tcp, false, parent)
+    }
+
+    pub fn new_tcp6_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv6/tcp_ipv6.c#L2191>
+        Self::new_inode(InetSocketProtocol::Tcp, true, parent)
+    }
+
+    pub fn new_udp_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/udp.c#L3480>
+        Self::new_inode(InetSocketProtocol::Udp, false, parent)
+    }
+
+    pub fn new_udp6_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv6/udp.c#L1805>
+        Self::new_inode(InetSocketProtocol::Udp, true, parent)
+    }
+
+    fn new_inode(
+        protocol: InetSocketProtocol,
+        is_ipv6: bool,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        ProcFile::new(Self { protocol, is_ipv6 }, parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for InetFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let (header, index_width) = match (self.protocol, self.is_ipv6) {
+            (InetSocketProtocol::Tcp, false) => (IPV4_HEADER, 4),
+            (InetSocketProtocol::Tcp, true) => (IPV6_HEADER, 4),
+            (InetSocketProtocol::Udp, false) => (UDP_IPV4_HEADER, 5),
+            (InetSocketProtocol::Udp, true) => (IPV6_HEADER, 5),
+        };
+        writeln!(printer, "{}", header)?;
+
+        let infos = inet_socket_infos(&current_net_ns())
+            .into_iter()
+            .filter(|info| {
+                info.protocol == self.protocol
+                    && matches!(info.local_endpoint.addr, IpAddress::Ipv6(_)) == self.is_ipv6
+            });
+        for (index, info) in infos.enumerate() {
+            // For listeners, Linux reports the number of pending connections as `rx_queue`.
+            let tx_queue = if info.state == InetSocketState::Listen {
+                0
+            } else {
+                info.send_queue
+            };
+
+            // TODO: Report the timers and the retransmissions.
+            writeln!(
+                printer,
+                "{:w$}: {}:{:04X} {}:{:04X} {:02X} {:08X}:{:08X} 00:00000000 00000000 {:5} {:8} {}",
+                index,
+                HexAddr(&info.local_endpoint, self.is_ipv6),
+                info.local_endpoint.port,
+                HexAddr(&info.remote_endpoint, self.is_ipv6),
+                info.remote_endpoint.port,
+                info.state as u8,
+                tx_queue,
+                info.recv_queue,
+                info.owner.map_or(0, |owner| u32::from(owner.uid())),
+                0,
+                info.owner.map_or(0, |owner| owner.ino()),
+                w = index_width,
+            )?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
+
+const IPV4_HEADER: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode";
+const UDP_IPV4_HEADER: &str = "   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode";
+const IPV6_HEADER: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode";
+
+/// An IP address formatted as in `/proc/net/{tcp,tcp6,udp,udp6}`.
+///
+/// Linux prints each 32-bit word of the address in network byte order as a hexadecimal
+/// integer in native byte order (i.e., little endian for the supported architectures).
+struct HexAddr<'a>(&'a IpEndpoint, bool);
+
+impl core::fmt::Display for HexAddr<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let HexAddr(endpoint, is_ipv6) = *self;
+
+        let mut octets = [0u8; 16];
+        let octets = match (endpoint.addr, is_ipv6) {
+            (IpAddress::Ipv4(ipv4_addr), false) => {
+                octets[..4].copy_from_slice(&ipv4_addr.octets());
+                &octets[..4]
+            }
+            (IpAddress::Ipv6(ipv6_addr), true) => {
+                octets = ipv6_addr.octets();
+                &octets[..]
+            }
+            // The remote address is unspecified if the socket is not connected.
+            (_, false) => &octets[..4],
+            (_, true) => &octets[..],
+        };
+
+        for word in octets.chunks_exact(4) {
+            write!(f, "{:08X}", u32::from_le_bytes(word.try_into().unwrap()))?;
+        }
+
+        Ok(())
+    }
+}
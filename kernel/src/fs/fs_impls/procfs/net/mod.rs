@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        file::{InodeType, mkmod},
+        procfs::{
+            ProcDir, StaticEntry,
+            net::{dev::DevFileOps, inet::InetFileOps, snmp::SnmpFileOps, unix::UnixFileOps},
+            template::{
+                ProcDirOps, ReaddirEntry, listed_entries_from_table, lookup_child_from_table,
+                visit_listed_entries,
+            },
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+mod dev;
+mod inet;
+mod snmp;
+mod unix;
+
+/// Represents the inode at `/proc/net`.
+///
+/// The files in the directory show the network statistics of the network namespace of the
+/// current thread.
+//
+// TODO: Linux makes `/proc/net` a symlink to `/proc/self/net`, so that the network namespace
+// is determined by the process that opens the files instead of the one that reads them.
+pub struct NetDirOps;
+
+impl NetDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/proc_net.c#L389>
+        ProcDir::new(Self, parent, mkmod!(a+rx))
+    }
+
+    const STATIC_ENTRIES: &'static [StaticEntry] = &[
+        ("dev", InodeType::File, DevFileOps::new_inode),
+        ("snmp", InodeType::File, SnmpFileOps::new_inode),
+        ("tcp", InodeType::File, InetFileOps::new_tcp_inode),
+        ("tcp6", InodeType::File, InetFileOps::new_tcp6_inode),
+        ("udp", InodeType::File, InetFileOps::new_udp_inode),
+        ("udp6", InodeType::File, InetFileOps::new_udp6_inode),
+        ("unix", InodeType::File, UnixFileOps::new_inode),
+    ];
+}
+
+impl ProcDirOps for NetDirOps {
+    fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
+        if let Some(child) = lookup_child_from_table(name, Self::STATIC_ENTRIES, |f| {
+            (f)(this_dir.this_weak().clone())
+        }) {
+            return Ok(child);
+        }
+
+        return_errno_with_message!(Errno::ENOENT, "the file does not exist");
+    }
+
+    fn visit_entries_from_offset<'a, F>(&'a self, offset: usize, visit_fn: F) -> Result<()>
+    where
+        F: FnMut(ReaddirEntry<'a>) -> Result<()>,
+    {
+        visit_listed_entries(
+            offset,
+            listed_entries_from_table(Self::STATIC_ENTRIES),
+            visit_fn,
+        )
+    }
+}
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::iface::IfaceCounter;
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            current_net_ns,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    net::{
+        net_ns::NetNamespace,
+        socket::ip::{InetSocketProtocol, InetSocketState, inet_socket_infos},
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/net/snmp`.
+///
+/// The file shows the SNMP MIB counters of the network namespace, which are the sums of the
+/// counters of all the network interfaces.
+pub struct SnmpFileOps;
+
+impl SnmpFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/proc.c#L542>
+        ProcFile::new(Self, parent, mkmod!(a+r))
+    }
+}
+
+/// The default TTL of IP packets.
+const DEFAULT_TTL: u64 = 64;
+
+impl ProcFileOps for SnmpFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let net_ns = current_net_ns();
+        let counter = |counter: IfaceCounter| -> u64 {
+            net_ns
+                .ifaces()
+                .iter()
+                .map(|iface| iface.counter(counter))
+                .sum()
+        };
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // The values of `Forwarding` are 1 (forwarding) and 2 (not forwarding).
+        let forwarding = if net_ns.ip_forward() { 1 } else { 2 };
+        print_mib(
+            &mut printer,
+            "Ip",
+            &[
+                ("Forwarding", forwarding),
+                ("DefaultTTL", DEFAULT_TTL),
+                ("InReceives", counter(IfaceCounter::IpInReceives)),
+                ("InHdrErrors", counter(IfaceCounter::IpInHdrErrors)),
+                ("InAddrErrors", counter(IfaceCounter::IpInAddrErrors)),
+                ("ForwDatagrams", counter(IfaceCounter::IpForwDatagrams)),
+                ("InUnknownProtos", 0),
+                ("InDiscards", 0),
+                ("InDelivers", counter(IfaceCounter::IpInDelivers)),
+                ("OutRequests", counter(IfaceCounter::IpOutRequests)),
+                ("OutDiscards", 0),
+                ("OutNoRoutes", 0),
+                ("ReasmTimeout", 0),
+                ("ReasmReqds", 0),
+                ("ReasmOKs", 0),
+                ("ReasmFails", 0),
+                ("FragOKs", 0),
+                ("FragFails", 0),
+                ("FragCreates", 0),
+                ("OutTransmits", counter(IfaceCounter::IpOutRequests)),
+            ],
+        )?;
+
+        print_mib(
+            &mut printer,
+            "Icmp",
+            &[
+                ("InMsgs", counter(IfaceCounter::IcmpInMsgs)),
+                ("InErrors", counter(IfaceCounter::IcmpInErrors)),
+                ("InCsumErrors", 0),
+                ("OutMsgs", counter(IfaceCounter::IcmpOutMsgs)),
+                ("OutErrors", 0),
+                ("OutRateLimitGlobal", 0),
+                ("OutRateLimitHost", 0),
+            ],
+        )?;
+
+        // `RtoAlgorithm` is 1 (other), and `MaxConn` is -1 (dynamic), as in Linux.
+        writeln!(
+            printer,
+            "Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens PassiveOpens AttemptFails \
+             EstabResets CurrEstab InSegs OutSegs RetransSegs InErrs OutRsts InCsumErrors"
+        )?;
+        write!(printer, "Tcp: 1 200 120000 -1")?;
+        for value in [
+            counter(IfaceCounter::TcpActiveOpens),
+            counter(IfaceCounter::TcpPassiveOpens),
+            0,
+            0,
+            current_established(&net_ns),
+            counter(IfaceCounter::TcpInSegs),
+            counter(IfaceCounter::TcpOutSegs),
+            0,
+            counter(IfaceCounter::TcpInErrs),
+            counter(IfaceCounter::TcpOutRsts),
+            0,
+        ] {
+            write!(printer, " {}", value)?;
+        }
+        writeln!(printer)?;
+
+        print_mib(
+            &mut printer,
+            "Udp",
+            &[
+                ("InDatagrams", counter(IfaceCounter::UdpInDatagrams)),
+                ("NoPorts", counter(IfaceCounter::UdpNoPorts)),
+                ("InErrors", counter(IfaceCounter::UdpInErrors)),
+                ("OutDatagrams", counter(IfaceCounter::UdpOutDatagrams)),
+                ("RcvbufErrors", 0),
+                ("SndbufErrors", 0),
+                ("InCsumErrors", 0),
+                ("IgnoredMulti", 0),
+                ("MemErrors", 0),
+            ],
+        )?;
+
+        Ok(printer.bytes_written())
+    }
+}
+
+/// Prints a line with the names of the counters and a line with their values.
+fn print_mib(
+    printer: &mut VmPrinter<'_, '_>,
+    prefix: &str,
+    counters: &[(&str, u64)],
+) -> Result<()> {
+    write!(printer, "{}:", prefix)?;
+    for (name, _) in counters.iter() {
+        write!(printer, " {}", name)?;
+    }
+    writeln!(printer)?;
+
+    write!(printer, "{}:", prefix)?;
+    for (_, value) in counters.iter() {
+        write!(printer, " {}", value)?;
+    }
+    writeln!(printer)?;
+
+    Ok(())
+}
+
+/// Returns the number of TCP connections in the `ESTABLISHED` or `CLOSE-WAIT` state.
+fn current_established(net_ns: &NetNamespace) -> u64 {
+    inet_socket_infos(net_ns)
+        .iter()
+        .filter(|info| {
+            info.protocol == InetSocketProtocol::Tcp
+                && matches!(
+                    info.state,
+                    InetSocketState::Established | InetSocketState::CloseWait
+                )
+        })
+        .count() as u64
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            current_net_ns,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    net::socket::unix::{UnixSocketAddr, UnixSocketState, unix_socket_infos},
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/net/unix`.
+///
+/// The file shows the UNIX sockets.
+pub struct UnixFileOps;
+
+impl UnixFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/unix/af_unix.c#L3522>
+        ProcFile::new(Self, parent, mkmod!(a+r))
+    }
+}
+
+/// The flag that indicates a listening socket (`__SO_ACCEPTCON` in Linux).
+const SO_ACCEPTCON: u32 = 1 << 16;
+
+/// The socket states (`SS_*` in Linux).
+const SS_UNCONNECTED: u8 = 1;
+const SS_CONNECTED: u8 = 3;
+
+impl ProcFileOps for UnixFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        writeln!(
+            printer,
+            "Num       RefCount Protocol Flags    Type St Inode Path"
+        )?;
+
+        for info in unix_socket_infos(&current_net_ns()) {
+            let (flags, state) = match info.state {
+                UnixSocketState::Unconnected => (0, SS_UNCONNECTED),
+                UnixSocketState::Listening => (SO_ACCEPTCON, SS_UNCONNECTED),
+                UnixSocketState::Connected => (0, SS_CONNECTED),
+            };
+
+            // Like Linux with `kptr_restrict`, the kernel address is hidden.
+            write!(
+                printer,
+                "{:016X}: {:08X} {:08X} {:08X} {:04X} {:02X} {:5}",
+                0,
+                2,
+                0,
+                flags,
+                info.type_ as u32,
+                state,
+                info.owner.ino(),
+            )?;
+
+            match &info.addr {
+                UnixSocketAddr::Unnamed => (),
+                UnixSocketAddr::Path(path) => write!(printer, " {}", path)?,
+                UnixSocketAddr::Abstract(name) => {
+                    // Linux shows the null bytes in abstract names as `@`.
+                    let name: String = name
+                        .iter()
+                        .map(|byte| if *byte == 0 { '@' } else { *byte as char })
+                        .collect();
+                    write!(printer, " @{}", name)?;
+                }
+            }
+            writeln!(printer)?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use super::{PidDirOps, TidDirOps};
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::coredump::CoreDumpFilter,
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/coredump_filter`.
+pub struct CoreDumpFilterFileOps(TidDirOps);
+
+impl CoreDumpFilterFileOps {
+    pub fn new_inode(dir: &PidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3415>
+        ProcFile::new(Self(dir.tid_dir_ops().clone()), parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for CoreDumpFilterFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let Some(process) = self.0.process() else {
+            return_errno_with_message!(Errno::ESRCH, "the process does not exist");
+        };
+        let vmar_guard = process.lock_vmar();
+        let Some(vmar) = vmar_guard.as_ref() else {
+            return_errno_with_message!(Errno::ESRCH, "the process has exited");
+        };
+        let filter = vmar.process_vm().coredump_filter();
+        writeln!(printer, "{:08x}", u32::from(filter))?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        /// The longest valid input is a 32-bit octal integer, `"037777777777\n"`.
+        const MAX_INPUT_LEN: usize = 13;
+
+        let (cstr, read_bytes) = reader.read_cstring_until_end(MAX_INPUT_LEN)?;
+        let filter = cstr
+            .to_str()
+            .ok()
+            .and_then(|str| parse_u32_with_radix_prefix(str.trim()))
+            .ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "the value is not a valid integer")
+            })?;
+
+        let Some(process) = self.0.process() else {
+            return_errno_with_message!(Errno::ESRCH, "the process does not exist");
+        };
+        let vmar_guard = process.lock_vmar();
+        let Some(vmar) = vmar_guard.as_ref() else {
+            return_errno_with_message!(Errno::ESRCH, "the process has exited");
+        };
+        vmar.process_vm()
+            .set_coredump_filter(CoreDumpFilter::from(filter));
+
+        Ok(read_bytes)
+    }
+}
+
+/// Parses an integer like `kstrtouint(_, 0, _)` in Linux.
+///
+/// The radix is 16 if the string starts with `0x`, 8 if it starts with `0`,
+/// and 10 otherwise.
+fn parse_u32_with_radix_prefix(str: &str) -> Option<u32> {
+    if let Some(hex) = str.strip_prefix("0x").or_else(|| str.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if str.len() > 1
+        && let Some(oct) = str.strip_prefix('0')
+    {
+        u32::from_str_radix(oct, 8).ok()
+    } else {
+        str.parse::<u32>().ok()
+    }
+}
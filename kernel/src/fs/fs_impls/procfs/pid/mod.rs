@@ -14,10 +14,14 @@ use crate::{
         vfs::inode::{Inode, RevalidationPolicy},
     },
     prelude::*,
-    process::pid_table::{PidEntry, PidEntryType},
+    process::{
+        PidNamespace,
+        pid_table::{PidEntry, PidEntryType},
+    },
     thread::Thread,
 };
 
+mod coredump_filter;
 mod task;
 pub(super) use task::TidDirOps;
 
@@ -30,8 +34,12 @@ pub struct PidDirOps(
 );
 
 impl PidDirOps {
-    pub fn new_inode(pid_entry: Arc<PidEntry>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
-        let this = Self(TidDirOps::new(pid_entry));
+    pub fn new_inode(
+        pid_entry: Arc<PidEntry>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        let this = Self(TidDirOps::new(pid_entry, pid_ns));
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3493>
         ProcDir::new(this, parent, mkmod!(a+rx))
     }
@@ -46,6 +54,11 @@ impl PidDirOps {
 
     const STATIC_ENTRIES: &[StaticEntryWithOps<PidDirOps>] = &[
         ("task", InodeType::Dir, TaskDirOps::new_inode),
+        (
+            "coredump_filter",
+            InodeType::File,
+            coredump_filter::CoreDumpFilterFileOps::new_inode,
+        ),
         (
             "stat",
             InodeType::File,
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+use ostd::task::Task;
+
+use super::TidDirOps;
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps, read_u32_from},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::{
+        Uid,
+        posix_thread::{AsPosixThread, AsThreadLocal},
+    },
+    security::audit,
+    thread::{AsThread, Thread},
+};
+
+/// Represents the inode at `/proc/[pid]/task/[tid]/loginuid` (and also `/proc/[pid]/loginuid`).
+pub struct LoginUidFileOps(TidDirOps);
+
+impl LoginUidFileOps {
+    pub fn new_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3384>
+        ProcFile::new(Self(dir.clone()), parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for LoginUidFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let Some(thread) = self.0.thread() else {
+            return_errno_with_message!(Errno::ESRCH, "the thread does not exist");
+        };
+        let loginuid = thread.as_posix_thread().unwrap().credentials().loginuid();
+        // Linux does not print a trailing new line.
+        write!(printer, "{}", u32::from(loginuid))?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (loginuid, read_bytes) = read_u32_from(reader)?;
+
+        let current_task = Task::current().unwrap();
+        let current_thread = current_task.as_thread().unwrap();
+        if self
+            .0
+            .thread()
+            .is_none_or(|thread| !Arc::ptr_eq(&thread, current_thread))
+        {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the login UID can only be set by the thread itself"
+            );
+        }
+
+        let current_posix_thread = current_thread.as_posix_thread().unwrap();
+        let ctx = Context {
+            process: current_posix_thread.process(),
+            thread_local: current_task.as_thread_local().unwrap(),
+            posix_thread: current_posix_thread,
+            thread: current_thread.as_ref(),
+            task: &current_task,
+        };
+        audit::set_loginuid(&ctx, Uid::new(loginuid))?;
+
+        Ok(read_bytes)
+    }
+}
@@ -9,10 +9,11 @@ use crate::{
             pid::task::{
                 auxv::AuxvFileOps, cgroup::CgroupFileOps, cmdline::CmdlineFileOps,
                 comm::CommFileOps, environ::EnvironFileOps, exe::ExeSymOps, fd::FdDirOps,
-                gid_map::GidMapFileOps, maps::MapsFileOps, mem::MemFileOps,
-                mountinfo::MountInfoFileOps, mounts::MountsFileOps, mountstats::MountStatsFileOps,
-                ns::NsDirOps, oom_score_adj::OomScoreAdjFileOps, stat::StatFileOps,
-                status::StatusFileOps, uid_map::UidMapFileOps,
+                gid_map::GidMapFileOps, loginuid::LoginUidFileOps, maps::MapsFileOps,
+                mem::MemFileOps, mountinfo::MountInfoFileOps, mounts::MountsFileOps,
+                mountstats::MountStatsFileOps, ns::NsDirOps, oom_score_adj::OomScoreAdjFileOps,
+                sessionid::SessionIdFileOps, stat::StatFileOps, status::StatusFileOps,
+                uid_map::UidMapFileOps,
             },
             template::{
                 ListedEntry, ProcDir, ProcDirOps, ReaddirEntry, keyed_readdir_entries,
@@ -23,7 +24,7 @@ use crate::{
         vfs::inode::{Inode, RevalidationPolicy},
     },
     prelude::*,
-    process::{Process, pid_table, pid_table::PidEntry, posix_thread::AsPosixThread},
+    process::{PidNamespace, Process, pid_table, pid_table::PidEntry, posix_thread::AsPosixThread},
     thread::{Thread, Tid},
 };
 
@@ -35,6 +36,7 @@ mod environ;
 mod exe;
 mod fd;
 mod gid_map;
+mod loginuid;
 mod maps;
 mod mem;
 mod mountinfo;
@@ -42,17 +44,19 @@ mod mounts;
 mod mountstats;
 mod ns;
 mod oom_score_adj;
+mod sessionid;
 pub(super) mod stat;
 mod status;
 mod uid_map;
 
 /// Represents the inode at `/proc/[pid]/task`.
-pub struct TaskDirOps(Arc<PidEntry>);
+pub struct TaskDirOps(Arc<PidEntry>, Arc<PidNamespace>);
 
 impl TaskDirOps {
     pub fn new_inode(dir: &PidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let this = Self(dir.pid_entry().clone(), dir.tid_dir_ops().pid_ns().clone());
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3316>
-        ProcDir::new(Self(dir.pid_entry().clone()), parent, mkmod!(a+rx))
+        ProcDir::new(this, parent, mkmod!(a+rx))
     }
 
     fn process(&self) -> Option<Arc<Process>> {
@@ -64,16 +68,22 @@ impl TaskDirOps {
 #[derive(Clone)]
 pub struct TidDirOps {
     pid_entry: Arc<PidEntry>,
+    /// The PID namespace of the procfs.
+    pid_ns: Arc<PidNamespace>,
 }
 
 impl TidDirOps {
-    pub fn new(pid_entry: Arc<PidEntry>) -> Self {
-        Self { pid_entry }
+    pub fn new(pid_entry: Arc<PidEntry>, pid_ns: Arc<PidNamespace>) -> Self {
+        Self { pid_entry, pid_ns }
     }
 
-    pub fn new_inode(pid_entry: Arc<PidEntry>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+    pub fn new_inode(
+        pid_entry: Arc<PidEntry>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
         ProcDir::new(
-            Self { pid_entry },
+            Self { pid_entry, pid_ns },
             parent,
             // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3796>
             mkmod!(a+rx),
@@ -84,6 +94,10 @@ impl TidDirOps {
         &self.pid_entry
     }
 
+    pub(super) fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
     pub(super) fn process(&self) -> Option<Arc<Process>> {
         self.pid_entry.process_of_thread()
     }
@@ -113,6 +127,7 @@ impl TidDirOps {
             FdDirOps::<fd::FileInfoOps>::new_inode,
         ),
         ("gid_map", InodeType::File, GidMapFileOps::new_inode),
+        ("loginuid", InodeType::File, LoginUidFileOps::new_inode),
         ("mem", InodeType::File, MemFileOps::new_inode),
         ("mountinfo", InodeType::File, MountInfoFileOps::new_inode),
         ("mountstats", InodeType::File, MountStatsFileOps::new_inode),
@@ -122,6 +137,7 @@ impl TidDirOps {
             InodeType::File,
             OomScoreAdjFileOps::new_inode,
         ),
+        ("sessionid", InodeType::File, SessionIdFileOps::new_inode),
         ("stat", InodeType::File, StatFileOps::new_thread_inode),
         ("status", InodeType::File, StatusFileOps::new_inode),
         ("uid_map", InodeType::File, UidMapFileOps::new_inode),
@@ -192,6 +208,9 @@ impl ProcDirOps for TaskDirOps {
         let Ok(tid) = name.parse::<Tid>() else {
             return_errno_with_message!(Errno::ENOENT, "the name is not a valid TID");
         };
+        let Some(tid) = self.1.id_of(tid) else {
+            return_errno_with_message!(Errno::ENOENT, "the thread does not exist");
+        };
 
         // Note: After a PID-number recycling mechanism is introduced, there may be a race here:
         // - If a PID number is recycled as soon as its `PidEntry` is removed from the `PidTable`,
@@ -227,6 +246,7 @@ impl ProcDirOps for TaskDirOps {
 
         Ok(TidDirOps::new_inode(
             pid_entry,
+            self.1.clone(),
             this_dir.this_weak().clone(),
         ))
     }
@@ -245,7 +265,8 @@ impl ProcDirOps for TaskDirOps {
             .lock()
             .as_slice()
             .iter()
-            .filter_map(|task| usize::try_from(task.as_posix_thread().unwrap().tid()).ok())
+            .filter_map(|task| self.1.nr_of(task.as_posix_thread().unwrap().tid()))
+            .filter_map(|tid| usize::try_from(tid).ok())
             .collect::<Vec<_>>();
 
         visit_readdir_entries(
@@ -280,6 +301,9 @@ impl ProcDirOps for TaskDirOps {
         let Ok(tid) = name.parse::<Tid>() else {
             return true;
         };
+        let Some(tid) = self.1.id_of(tid) else {
+            return true;
+        };
 
         let Some(process) = self.process() else {
             return true;
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use super::TidDirOps;
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/task/[tid]/sessionid` (and also `/proc/[pid]/sessionid`).
+pub struct SessionIdFileOps(TidDirOps);
+
+impl SessionIdFileOps {
+    pub fn new_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3385>
+        ProcFile::new(Self(dir.clone()), parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for SessionIdFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let Some(thread) = self.0.thread() else {
+            return_errno_with_message!(Errno::ESRCH, "the thread does not exist");
+        };
+        let sessionid = thread.as_posix_thread().unwrap().credentials().sessionid();
+        // Linux does not print a trailing new line.
+        write!(printer, "{}", sessionid)?;
+
+        Ok(printer.bytes_written())
+    }
+}
@@ -131,7 +131,11 @@ impl ProcFileOps for StatFileOps {
         //
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/array.c#L467-L681>
 
-        let pid = posix_thread.tid();
+        // The IDs are shown in the PID namespace of the procfs.
+        let pid_ns = self.dir.pid_ns();
+        let nr_of = |id| pid_ns.nr_of(id).unwrap_or(0);
+
+        let pid = nr_of(posix_thread.tid());
 
         let comm = posix_thread
             .thread_name()
@@ -150,9 +154,9 @@ impl ProcFileOps for StatFileOps {
                 SleepingState::StopByPtrace => 't',
             }
         };
-        let ppid = process.parent().pid();
-        let pgrp = process.pgid();
-        let session = process.sid();
+        let ppid = nr_of(process.parent().pid());
+        let pgrp = nr_of(process.pgid());
+        let session = nr_of(process.sid());
 
         let (tty_nr, tpgid) = if let Some(terminal) = process.terminal() {
             (
@@ -160,7 +164,7 @@ impl ProcFileOps for StatFileOps {
                 terminal
                     .job_control()
                     .foreground()
-                    .map(|pgrp| pgrp.pgid() as i64)
+                    .map(|pgrp| nr_of(pgrp.pgid()) as i64)
                     .unwrap_or(-1),
             )
         } else {
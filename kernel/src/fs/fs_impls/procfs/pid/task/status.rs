@@ -11,7 +11,7 @@ use crate::{
     },
     prelude::*,
     process::{
-        credentials::AMBIENT_CAPSET,
+        Pid, PidNamespace,
         posix_thread::{AsPosixThread, SleepingState},
     },
     thread::Thread,
@@ -33,6 +33,10 @@ use crate::{
 /// - Gid:    Real, effective, saved set, and filesystem GIDs.
 /// - FDSize: The number of file descriptor slots currently allocated.
 /// - Groups: Supplementary group IDs.
+/// - NStgid: Thread group IDs in each of the PID namespaces of the thread.
+/// - NSpid:  Thread IDs in each of the PID namespaces of the thread.
+/// - NSpgid: Process group IDs in each of the PID namespaces of the thread.
+/// - NSsid:  Session IDs in each of the PID namespaces of the thread.
 /// - VmPeak: Peak virtual memory size.
 /// - VmSize: Current virtual memory size.
 /// - VmLck:  Locked memory size.
@@ -57,6 +61,7 @@ use crate::{
 /// - CapEff: Effective capabilities.
 /// - CapBnd: Bounding set.
 /// - CapAmb: Ambient capabilities.
+/// - NoNewPrivs: Whether the thread has the no_new_privs flag set.
 /// - Seccomp: Seccomp mode.
 /// - Cpus_allowed: CPUs allowed for this process.
 /// - Cpus_allowed_list: List of CPUs allowed for this process.
@@ -113,15 +118,20 @@ impl ProcFileOps for StatusFileOps {
         };
         writeln!(printer, "State:\t{}", state)?;
 
-        writeln!(printer, "Tgid:\t{}", process.pid())?;
-        writeln!(printer, "Pid:\t{}", posix_thread.tid())?;
-        writeln!(printer, "PPid:\t{}", process.parent().pid())?;
+        // The IDs are shown in the PID namespace of the procfs. An ID that is not visible in the
+        // namespace is shown as zero.
+        let proc_pid_ns = self.0.pid_ns();
+        let nr_of = |id: Pid| proc_pid_ns.nr_of(id).unwrap_or(0);
+
+        writeln!(printer, "Tgid:\t{}", nr_of(process.pid()))?;
+        writeln!(printer, "Pid:\t{}", nr_of(posix_thread.tid()))?;
+        writeln!(printer, "PPid:\t{}", nr_of(process.parent().pid()))?;
         writeln!(
             printer,
             "TracerPid:\t{}",
             posix_thread
                 .tracer()
-                .map(|tracer| tracer.as_posix_thread().unwrap().tid())
+                .map(|tracer| nr_of(tracer.as_posix_thread().unwrap().tid()))
                 .unwrap_or(0)
         )?;
 
@@ -153,6 +163,20 @@ impl ProcFileOps for StatusFileOps {
                 .unwrap_or(0)
         )?;
 
+        let pid_ns = process.pid_ns();
+        for (name, id) in [
+            ("NStgid", process.pid()),
+            ("NSpid", posix_thread.tid()),
+            ("NSpgid", process.pgid()),
+            ("NSsid", process.sid()),
+        ] {
+            write!(printer, "{}:", name)?;
+            for nr in ns_nrs(id, pid_ns, proc_pid_ns) {
+                write!(printer, "\t{}", nr)?;
+            }
+            writeln!(printer)?;
+        }
+
         if let Some(vmar_ref) = process.lock_vmar().as_ref() {
             let vsize = vmar_ref.get_mappings_total_size();
             let anon = vmar_ref.get_rss_counter(RssType::Anon) * (PAGE_SIZE / 1024);
@@ -191,8 +215,35 @@ impl ProcFileOps for StatusFileOps {
             "CapBnd:\t{:016x}",
             credentials.bounding_capset().bits()
         )?;
-        writeln!(printer, "CapAmb:\t{:016x}", AMBIENT_CAPSET.bits())?;
+        writeln!(
+            printer,
+            "CapAmb:\t{:016x}",
+            credentials.ambient_capset().bits()
+        )?;
+        writeln!(printer, "NoNewPrivs:\t{}", credentials.no_new_privs() as u8)?;
 
         Ok(printer.bytes_written())
     }
 }
+
+/// Returns the IDs of `id` in the PID namespaces from `proc_pid_ns` down to `pid_ns`.
+///
+/// An ID that is not visible in a namespace is returned as zero.
+fn ns_nrs(id: Pid, pid_ns: &PidNamespace, proc_pid_ns: &PidNamespace) -> Vec<Pid> {
+    let mut nrs = Vec::new();
+
+    let mut ns = pid_ns;
+    loop {
+        nrs.push(ns.nr_of(id).unwrap_or(0));
+        if core::ptr::eq(ns, proc_pid_ns) {
+            break;
+        }
+        let Some(parent_ns) = ns.parent_ns() else {
+            break;
+        };
+        ns = parent_ns;
+    }
+
+    nrs.reverse();
+    nrs
+}
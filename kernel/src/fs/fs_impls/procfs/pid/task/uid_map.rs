@@ -10,7 +10,7 @@ use crate::{
         vfs::inode::Inode,
     },
     prelude::*,
-    process::Uid,
+    process::{IdMap, posix_thread::AsPosixThread},
     thread::Thread,
 };
 
@@ -32,17 +32,63 @@ impl ProcFileOps for UidMapFileOps {
     fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
         let mut printer = VmPrinter::new_skip(writer, offset);
 
-        // This is the default UID map for the initial user namespace.
-        // TODO: Retrieve the UID map from the user namespace of the current process
-        // instead of returning this hard-coded value.
-        writeln!(
-            printer,
-            "{:>10} {:>10} {:>10}",
-            0,
-            0,
-            u32::from(Uid::INVALID)
-        )?;
+        let Some(thread) = self.0.thread() else {
+            return_errno_with_message!(Errno::ESRCH, "the thread does not exist");
+        };
+        let user_ns = thread
+            .as_posix_thread()
+            .unwrap()
+            .process()
+            .user_ns()
+            .lock()
+            .clone();
+
+        for extent in user_ns.uid_map().extents() {
+            // The lower IDs are shown as IDs in the parent namespace. They have been mapped down
+            // through the mapping of the parent namespace, so they can always be mapped back up.
+            let lower_first = match user_ns.parent_ns() {
+                Some(parent) => parent.uid_map().map_up(extent.lower_first).unwrap(),
+                None => extent.lower_first,
+            };
+            writeln!(
+                printer,
+                "{:>10} {:>10} {:>10}",
+                extent.first, lower_first, extent.count
+            )?;
+        }
 
         Ok(printer.bytes_written())
     }
+
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/user_namespace.c#L929>
+        if offset != 0 || reader.remain() >= PAGE_SIZE {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the UID mapping must be written at once and be shorter than a page"
+            );
+        }
+
+        let (text, read_bytes) = reader.read_cstring_until_end(PAGE_SIZE - 1)?;
+        let text = text.to_str().map_err(|_| {
+            Error::with_message(Errno::EINVAL, "the UID mapping is not valid UTF-8")
+        })?;
+        let map = IdMap::parse(text)?;
+
+        let Some(thread) = self.0.thread() else {
+            return_errno_with_message!(Errno::ESRCH, "the thread does not exist");
+        };
+        let user_ns = thread
+            .as_posix_thread()
+            .unwrap()
+            .process()
+            .user_ns()
+            .lock()
+            .clone();
+
+        let current = current_thread!();
+        user_ns.set_uid_map(map, current.as_posix_thread().unwrap())?;
+
+        Ok(read_bytes)
+    }
 }
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::coredump::{CORENAME_MAX_SIZE, core_pattern, set_core_pattern},
+};
+
+/// Represents the inode at `/proc/sys/kernel/core_pattern`.
+pub struct CorePatternFileOps;
+
+impl CorePatternFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c#L1077-L1084>
+        ProcFile::new(Self, parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for CorePatternFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let pattern = core_pattern();
+        writeln!(printer, "{}", String::from_utf8_lossy(&pattern))?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (pattern, read_bytes) = reader.read_cstring_until_end(CORENAME_MAX_SIZE - 1)?;
+        set_core_pattern(pattern.to_bytes());
+
+        // Following Linux, the bytes beyond the maximum length are silently discarded.
+        let skipped_bytes = reader.remain();
+        reader.skip(skipped_bytes);
+
+        Ok(read_bytes + skipped_bytes)
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            current_net_ns,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/sys/net/ipv4/ip_forward`.
+///
+/// The file shows whether the network namespace of the current thread forwards IPv4 packets.
+pub struct IpForwardFileOps;
+
+impl IpForwardFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference:
+        // <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/sysctl_net_ipv4.c>
+        ProcFile::new(Self, parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for IpForwardFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let ip_forward = current_net_ns().ip_forward();
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        writeln!(printer, "{}", ip_forward as u8)?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        /// Worst case buffer size needed for holding an integer.
+        const BUF_SIZE: usize = 16;
+
+        let (cstr, read_bytes) = reader.read_cstring_until_end(BUF_SIZE - 1)?;
+        let value = cstr
+            .to_str()
+            .ok()
+            .and_then(|str| str.trim().parse::<i32>().ok())
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the value is not an integer"))?;
+
+        current_net_ns().set_ip_forward(value != 0)?;
+
+        Ok(read_bytes)
+    }
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            current_net_ns,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::Gid,
+};
+
+/// Represents the inode at `/proc/sys/net/ipv4/ping_group_range`.
+///
+/// The file shows the range of the network namespace of the current thread.
+pub struct PingGroupRangeFileOps;
+
+impl PingGroupRangeFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference:
+        // <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/sysctl_net_ipv4.c>
+        ProcFile::new(Self, parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for PingGroupRangeFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let (low, high) = current_net_ns().ping_group_range();
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        writeln!(printer, "{}\t{}", u32::from(low), u32::from(high))?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        /// Worst case buffer size needed for holding two integers.
+        const BUF_SIZE: usize = 32;
+
+        let (cstr, read_bytes) = reader.read_cstring_until_end(BUF_SIZE - 1)?;
+        let invalid_range = || Error::with_message(Errno::EINVAL, "the range is invalid");
+
+        let mut gids = cstr
+            .to_str()
+            .map_err(|_| invalid_range())?
+            .split_whitespace()
+            .map(|str| {
+                // Like Linux, the group IDs must be in `0..=i32::MAX`.
+                str.parse::<i32>()
+                    .ok()
+                    .and_then(|gid| u32::try_from(gid).ok())
+                    .map(Gid::new)
+                    .ok_or_else(invalid_range)
+            });
+        let (Some(low), Some(high), None) = (gids.next(), gids.next(), gids.next()) else {
+            return Err(invalid_range());
+        };
+
+        current_net_ns().set_ping_group_range(low?, high?)?;
+
+        Ok(read_bytes)
+    }
+}
@@ -197,3 +197,22 @@ pub fn read_i32_from(reader: &mut VmReader) -> Result<(i32, usize)> {
 
     Ok((val, read_bytes))
 }
+
+/// Reads a string from `reader` and parses it as a `u32`.
+pub fn read_u32_from(reader: &mut VmReader) -> Result<(u32, usize)> {
+    /// Worst case buffer size needed for holding an integer.
+    ///
+    /// The longest possible string is `"4294967295\n\0"`,
+    /// whose length is 12 bytes.
+    const BUF_SIZE_U32: usize = 12;
+
+    let (cstr, read_bytes) = reader.read_cstring_until_end(BUF_SIZE_U32 - 1)?;
+    let val = cstr
+        .to_str()
+        .ok()
+        .map(|str| str.trim())
+        .and_then(|str| str.parse::<u32>().ok())
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the value is not a valid integer"))?;
+
+    Ok((val, read_bytes))
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The security file system.
+//!
+//! `SecurityFs` lets LSM modules expose their policy interfaces to user space. It is usually
+//! mounted at `/sys/kernel/security`, where each module owns a top-level directory (e.g.,
+//! `apparmor`).
+
+use aster_systree::{EmptyNode, SysBranchNode};
+use systree_node::SecurityRootNode;
+
+use crate::{fs::securityfs::fs::SecurityFsType, prelude::*};
+
+mod fs;
+mod inode;
+mod systree_node;
+
+// This method should be called during kernel file system initialization,
+// _after_ `aster_systree::init`.
+pub(super) fn init() {
+    let security_kernel_sysnode = EmptyNode::new("security".into());
+    super::sysfs::register_kernel_sysnode(security_kernel_sysnode).unwrap();
+
+    crate::fs::vfs::registry::register(&SecurityFsType).unwrap();
+}
+
+/// Registers the `SysTree` node of an LSM module under the root node of [`SecurityFs`].
+///
+/// If a module with the same name has already been registered,
+/// this function returns an error.
+///
+/// [`SecurityFs`]: fs::SecurityFs
+pub fn register_subsystem(subsystem: Arc<dyn SysBranchNode>) -> Result<()> {
+    SecurityRootNode::singleton().add_child(subsystem)?;
+
+    Ok(())
+}
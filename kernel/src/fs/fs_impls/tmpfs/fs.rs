@@ -37,7 +37,7 @@ impl FsType for TmpFsType {
     }
 
     fn properties(&self) -> FsProperties {
-        FsProperties::empty()
+        FsProperties::ALLOW_IDMAP
     }
 
     fn create(&self, _fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
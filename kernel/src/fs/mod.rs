@@ -9,7 +9,8 @@ pub mod utils;
 pub mod vfs;
 
 pub use fs_impls::{
-    cgroupfs, configfs, devpts, exfat, ext2, procfs, pseudofs, ramfs, sysfs, tmpfs,
+    binfmt_misc, cgroupfs, configfs, devpts, exfat, ext2, procfs, pseudofs, ramfs, securityfs,
+    sysfs, tmpfs,
 };
 
 use crate::{
@@ -547,62 +547,72 @@ pub trait Inode: Any + FileOps + Send + Sync {
     ///
     /// Similar to Linux, using "fsuid" here allows setting filesystem permissions
     /// without changing the "normal" uids for other tasks.
-    fn check_permission(&self, mut perm: Permission) -> Result<()> {
-        let Some(task) = Task::current() else {
-            return Ok(());
-        };
-        let Some(posix_thread) = task.as_posix_thread() else {
-            return Ok(());
-        };
-
-        let creds = posix_thread.credentials();
-        let metadata = self.metadata();
-        let mode = metadata.mode;
-
-        // With DAC_OVERRIDE capability, the user can bypass some permission checks.
-        if has_dac_override_capability(&task, posix_thread) {
-            // Read/write DACs are always overridable.
-            perm -= Permission::MAY_READ | Permission::MAY_WRITE;
-
-            // Executable DACs are overridable when there is at least one exec bit set.
-            if perm.may_exec() {
-                if mode.is_owner_executable()
-                    || mode.is_group_executable()
-                    || mode.is_other_executable()
-                {
-                    perm -= Permission::MAY_EXEC;
-                } else {
-                    return_errno_with_message!(
-                        Errno::EACCES,
-                        "root execute permission denied: no execute bits set"
-                    );
-                }
-            }
-        }
+    fn check_permission(&self, perm: Permission) -> Result<()> {
+        check_permission_with_metadata(&self.metadata(), perm)
+    }
+}
 
-        if metadata.uid == creds.fsuid() {
-            if (perm.may_read() && !mode.is_owner_readable())
-                || (perm.may_write() && !mode.is_owner_writable())
-                || (perm.may_exec() && !mode.is_owner_executable())
-            {
-                return_errno_with_message!(Errno::EACCES, "owner permission check failed");
-            }
-        } else if metadata.gid == creds.fsgid() {
-            if (perm.may_read() && !mode.is_group_readable())
-                || (perm.may_write() && !mode.is_group_writable())
-                || (perm.may_exec() && !mode.is_group_executable())
+/// Checks for read/write/execute permissions against the given metadata.
+///
+/// The ownership in the metadata may differ from the one stored in the inode,
+/// e.g., when the inode is accessed through an ID-mapped mount.
+pub(in crate::fs) fn check_permission_with_metadata(
+    metadata: &Metadata,
+    mut perm: Permission,
+) -> Result<()> {
+    let Some(task) = Task::current() else {
+        return Ok(());
+    };
+    let Some(posix_thread) = task.as_posix_thread() else {
+        return Ok(());
+    };
+
+    let creds = posix_thread.credentials();
+    let mode = metadata.mode;
+
+    // With DAC_OVERRIDE capability, the user can bypass some permission checks.
+    if has_dac_override_capability(&task, posix_thread) {
+        // Read/write DACs are always overridable.
+        perm -= Permission::MAY_READ | Permission::MAY_WRITE;
+
+        // Executable DACs are overridable when there is at least one exec bit set.
+        if perm.may_exec() {
+            if mode.is_owner_executable()
+                || mode.is_group_executable()
+                || mode.is_other_executable()
             {
-                return_errno_with_message!(Errno::EACCES, "group permission check failed");
+                perm -= Permission::MAY_EXEC;
+            } else {
+                return_errno_with_message!(
+                    Errno::EACCES,
+                    "root execute permission denied: no execute bits set"
+                );
             }
-        } else if (perm.may_read() && !mode.is_other_readable())
-            || (perm.may_write() && !mode.is_other_writable())
-            || (perm.may_exec() && !mode.is_other_executable())
-        {
-            return_errno_with_message!(Errno::EACCES, "other permission check failed");
         }
+    }
 
-        Ok(())
+    if metadata.uid == creds.fsuid() {
+        if (perm.may_read() && !mode.is_owner_readable())
+            || (perm.may_write() && !mode.is_owner_writable())
+            || (perm.may_exec() && !mode.is_owner_executable())
+        {
+            return_errno_with_message!(Errno::EACCES, "owner permission check failed");
+        }
+    } else if metadata.gid == creds.fsgid() {
+        if (perm.may_read() && !mode.is_group_readable())
+            || (perm.may_write() && !mode.is_group_writable())
+            || (perm.may_exec() && !mode.is_group_executable())
+        {
+            return_errno_with_message!(Errno::EACCES, "group permission check failed");
+        }
+    } else if (perm.may_read() && !mode.is_other_readable())
+        || (perm.may_write() && !mode.is_other_writable())
+        || (perm.may_exec() && !mode.is_other_executable())
+    {
+        return_errno_with_message!(Errno::EACCES, "other permission check failed");
     }
+
+    Ok(())
 }
 
 fn has_dac_override_capability(task: &CurrentTask, posix_thread: &PosixThread) -> bool {
@@ -681,6 +691,7 @@ impl Debug for dyn Inode {
 pub struct Extension {
     group1: Once<ThinBox<dyn Any + Send + Sync>>,
     group2: Once<ThinBox<dyn Any + Send + Sync>>,
+    group3: Once<ThinBox<dyn Any + Send + Sync>>,
 }
 
 impl Extension {
@@ -689,6 +700,7 @@ impl Extension {
         Self {
             group1: Once::new(),
             group2: Once::new(),
+            group3: Once::new(),
         }
     }
 
@@ -701,6 +713,11 @@ impl Extension {
     pub fn group2(&self) -> &Once<ThinBox<dyn Any + Send + Sync>> {
         &self.group2
     }
+
+    /// Gets the third extension group.
+    pub fn group3(&self) -> &Once<ThinBox<dyn Any + Send + Sync>> {
+        &self.group3
+    }
 }
 
 /// A symbolic link.
@@ -2,9 +2,12 @@
 
 use alloc::boxed::ThinBox;
 
-use crate::fs::{
-    file::flock::FlockList,
-    vfs::{inode::Inode, notify::FsEventPublisher, range_lock::RangeLockList},
+use crate::{
+    fs::{
+        file::flock::FlockList,
+        vfs::{inode::Inode, notify::FsEventPublisher, range_lock::RangeLockList},
+    },
+    security::lsm::SecurityBlob,
 };
 
 /// Context for FS locks.
@@ -55,6 +58,16 @@ pub trait InodeExt {
     ///
     /// If the context does not exist for this inode, a [`None`] will be returned.
     fn fs_lock_context(&self) -> Option<&FsLockContext>;
+
+    /// Gets or initializes the security blob.
+    ///
+    /// If the blob does not exist for this inode, it will be created.
+    fn security_blob_or_init(&self) -> &SecurityBlob;
+
+    /// Returns a reference to the security blob.
+    ///
+    /// If the blob does not exist for this inode, a [`None`] will be returned.
+    fn security_blob(&self) -> Option<&SecurityBlob>;
 }
 
 impl InodeExt for dyn Inode {
@@ -81,4 +94,16 @@ impl InodeExt for dyn Inode {
     fn fs_lock_context(&self) -> Option<&FsLockContext> {
         Some(self.extension().group2().get()?.downcast_ref().unwrap())
     }
+
+    fn security_blob_or_init(&self) -> &SecurityBlob {
+        self.extension()
+            .group3()
+            .call_once(|| ThinBox::new_unsize(SecurityBlob::new()))
+            .downcast_ref()
+            .unwrap()
+    }
+
+    fn security_blob(&self) -> Option<&SecurityBlob> {
+        Some(self.extension().group3().get()?.downcast_ref().unwrap())
+    }
 }
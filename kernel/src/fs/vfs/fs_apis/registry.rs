@@ -15,6 +15,7 @@ use crate::{
         },
     },
     prelude::*,
+    process::PidNamespace,
 };
 
 /// A type of file system.
@@ -80,6 +81,11 @@ impl<'a> FsCreationCtx<'a> {
         self.args
     }
 
+    /// Returns the PID namespace of the process that creates the filesystem.
+    pub(in crate::fs) fn pid_ns(&self) -> &Arc<PidNamespace> {
+        self.task_ctx.process.pid_ns()
+    }
+
     /// Resolves the mount source into a block device.
     pub(in crate::fs) fn resolve_block_device(&self) -> Result<Arc<dyn BlockDevice>> {
         let source = self
@@ -113,6 +119,11 @@ bitflags! {
         /// But a volatile FS such as ramfs or
         /// a pseudo FS such as sysfs does not.
         const NEED_DISK = 1 << 1;
+        /// Whether a FS can be mounted with an ID mapping.
+        ///
+        /// The FS must store the ownership of its inodes
+        /// so that the ownership can be translated by ID-mapped mounts.
+        const ALLOW_IDMAP = 1 << 2;
     }
 }
 
@@ -19,6 +19,7 @@ use crate::{
         },
     },
     prelude::*,
+    process::{Gid, Uid},
 };
 
 /// A `Dentry` represents a cached filesystem node in the VFS tree.
@@ -390,14 +391,19 @@ impl Deref for DirDentry<'_> {
 
 impl DirDentry<'_> {
     /// Creates a `Dentry` by creating a new inode of the `type_` with the `mode`.
+    ///
+    /// If `owner` is provided, the new inode is given that owner and group before it becomes
+    /// reachable by path lookup.
     pub(super) fn create(
         &self,
         name: &str,
         type_: InodeType,
         mode: InodeMode,
+        owner: Option<(Uid, Gid)>,
     ) -> Result<Arc<Dentry>> {
         let children = self.validate_child_absent(name)?;
         let new_inode = self.inode.create(name, type_, mode)?;
+        self.init_child_owner(name, &new_inode, owner)?;
         let mut children = children.upgrade();
         let new_child = Dentry::new(
             new_inode,
@@ -407,6 +413,31 @@ impl DirDentry<'_> {
         Ok(self.insert_positive_child(&mut children, name, new_child))
     }
 
+    /// Initializes the ownership of the newly created child `name`.
+    ///
+    /// If the ownership cannot be set, the child is removed so that no file is left behind
+    /// with the wrong owner.
+    fn init_child_owner(
+        &self,
+        name: &str,
+        inode: &Arc<dyn Inode>,
+        owner: Option<(Uid, Gid)>,
+    ) -> Result<()> {
+        let Some((uid, gid)) = owner else {
+            return Ok(());
+        };
+
+        let res = inode.set_owner(uid).and_then(|_| inode.set_group(gid));
+        if res.is_err() {
+            let _ = if inode.type_() == InodeType::Dir {
+                self.inode.rmdir(name)
+            } else {
+                self.inode.unlink(name)
+            };
+        }
+        res
+    }
+
     /// Validates that `name` is absent and keeps that result stable for creation.
     fn validate_child_absent<'a>(
         &'a self,
@@ -540,14 +571,19 @@ impl DirDentry<'_> {
     }
 
     /// Creates a `Dentry` by making an inode of the `type_` with the `mode`.
+    ///
+    /// If `owner` is provided, the new inode is given that owner and group before it becomes
+    /// reachable by path lookup.
     pub(super) fn mknod(
         &self,
         name: &str,
         mode: InodeMode,
         type_: MknodType,
+        owner: Option<(Uid, Gid)>,
     ) -> Result<Arc<Dentry>> {
         let children = self.validate_child_absent(name)?;
         let inode = self.inode.mknod(name, mode, type_)?;
+        self.init_child_owner(name, &inode, owner)?;
         let new_child = Dentry::new(
             inode,
             DentryOptions::Named((String::from(name), self.this())),
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! ID mappings of ID-mapped mounts.
+
+use ostd::task::Task;
+
+use crate::{
+    prelude::*,
+    process::{Gid, Uid, UserNamespace, posix_thread::AsPosixThread},
+};
+
+/// The ID mapping attached to an ID-mapped mount.
+///
+/// An ID-mapped mount translates the ownership stored in the filesystem through the ID mapping
+/// of a user namespace. The IDs stored in the filesystem are treated as IDs inside the user
+/// namespace, so reading the ownership through the mount maps them down to the parent
+/// namespace, while writing the ownership through the mount maps them back up.
+///
+/// Reference: <https://docs.kernel.org/filesystems/idmappings.html>
+#[derive(Clone)]
+pub struct MountIdmap {
+    user_ns: Arc<UserNamespace>,
+}
+
+impl MountIdmap {
+    /// Creates an ID mapping from the mappings of the given user namespace.
+    pub fn new(user_ns: Arc<UserNamespace>) -> Self {
+        Self { user_ns }
+    }
+
+    /// Maps a UID stored in the filesystem to the UID seen through the mount.
+    ///
+    /// Returns [`Uid::INVALID`] if the UID has no mapping.
+    pub(super) fn map_uid_to_mount(&self, uid: Uid) -> Uid {
+        self.user_ns
+            .uid_map()
+            .map_down(uid.into())
+            .map_or(Uid::INVALID, Uid::new)
+    }
+
+    /// Maps a GID stored in the filesystem to the GID seen through the mount.
+    ///
+    /// Returns [`Gid::INVALID`] if the GID has no mapping.
+    pub(super) fn map_gid_to_mount(&self, gid: Gid) -> Gid {
+        self.user_ns
+            .gid_map()
+            .map_down(gid.into())
+            .map_or(Gid::INVALID, Gid::new)
+    }
+
+    /// Maps a UID seen through the mount to the UID stored in the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EOVERFLOW` if the UID has no mapping.
+    pub(super) fn map_uid_to_fs(&self, uid: Uid) -> Result<Uid> {
+        self.user_ns
+            .uid_map()
+            .map_up(uid.into())
+            .map(Uid::new)
+            .ok_or_else(|| {
+                Error::with_message(Errno::EOVERFLOW, "the UID has no mapping in the mount")
+            })
+    }
+
+    /// Maps a GID seen through the mount to the GID stored in the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EOVERFLOW` if the GID has no mapping.
+    pub(super) fn map_gid_to_fs(&self, gid: Gid) -> Result<Gid> {
+        self.user_ns
+            .gid_map()
+            .map_up(gid.into())
+            .map(Gid::new)
+            .ok_or_else(|| {
+                Error::with_message(Errno::EOVERFLOW, "the GID has no mapping in the mount")
+            })
+    }
+
+    /// Returns the owner and the group stored in the filesystem
+    /// for a new inode created by the current thread through the mount.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EOVERFLOW` if the filesystem UID or GID of the current thread has no mapping.
+    pub(super) fn new_inode_owner(&self) -> Result<Option<(Uid, Gid)>> {
+        let Some(task) = Task::current() else {
+            return Ok(None);
+        };
+        let Some(posix_thread) = task.as_posix_thread() else {
+            return Ok(None);
+        };
+
+        let credentials = posix_thread.credentials();
+        let uid = self.map_uid_to_fs(credentials.fsuid())?;
+        let gid = self.map_gid_to_fs(credentials.fsgid())?;
+
+        Ok(Some((uid, gid)))
+    }
+}
+
+impl Debug for MountIdmap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MountIdmap").finish_non_exhaustive()
+    }
+}
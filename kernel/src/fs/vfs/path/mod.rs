@@ -6,6 +6,7 @@ use core::time::Duration;
 
 pub(in crate::fs) use dentry::Dentry;
 use dentry::DirDentry;
+pub use idmap::MountIdmap;
 use inherit_methods_macro::inherit_methods;
 use mount::MountNsFileCopying;
 pub use mount::{Mount, MountPropType, PerMountFlags};
@@ -22,15 +23,18 @@ use crate::{
         pseudofs::NsInode,
         vfs::{
             file_system::{FileSystem, FsFlags},
-            inode::{HardLinkability, Inode, Metadata, MknodType},
+            inode::{HardLinkability, Inode, Metadata, MknodType, check_permission_with_metadata},
+            registry::{self, FsProperties},
             xattr::{XattrName, XattrNamespace, XattrSetFlags},
         },
     },
     prelude::*,
-    process::{Gid, Uid},
+    process::{Gid, Uid, credentials::file_capabilities::FileCapabilities},
+    security::lsm::hooks as lsm_hooks,
 };
 
 mod dentry;
+mod idmap;
 mod mount;
 mod mount_namespace;
 mod resolver;
@@ -62,17 +66,22 @@ impl Path {
 
     /// Creates a new `Path` to represent the child directory of a file system.
     pub fn new_fs_child(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Self> {
-        if self
-            .inode()
-            .check_permission(Permission::MAY_WRITE)
-            .is_err()
-        {
+        if self.check_permission(Permission::MAY_WRITE).is_err() {
             return_errno!(Errno::EACCES);
         }
+        lsm_hooks::with_current_posix_thread(|posix_thread| {
+            lsm_hooks::on_path_create(lsm_hooks::PathCreateContext::new(
+                posix_thread,
+                self,
+                name,
+                type_,
+            ))
+        })?;
+        let new_owner = self.new_inode_owner()?;
         let new_child_dentry = self
             .dentry
             .as_dir_dentry_or_err()?
-            .create(name, type_, mode)?;
+            .create(name, type_, mode, new_owner)?;
         Ok(Self::new(self.mount.clone(), new_child_dentry))
     }
 
@@ -86,14 +95,16 @@ impl Path {
         mode: InodeMode,
         hard_linkability: HardLinkability,
     ) -> Result<Self> {
-        if self
-            .inode()
-            .check_permission(Permission::MAY_WRITE)
-            .is_err()
-        {
+        if self.check_permission(Permission::MAY_WRITE).is_err() {
             return_errno!(Errno::EACCES);
         }
+        let new_owner = self.new_inode_owner()?;
         let tmp_inode = self.inode().create_tmpfile(mode, hard_linkability)?;
+        // The temporary file has no name, so it is released if its ownership cannot be set.
+        if let Some((uid, gid)) = new_owner {
+            tmp_inode.set_owner(uid)?;
+            tmp_inode.set_group(gid)?;
+        }
         let tmp_dentry = Dentry::new_anonymous(tmp_inode, self.dentry.clone());
         Ok(Self::new(self.mount.clone(), tmp_dentry))
     }
@@ -161,10 +172,25 @@ impl Path {
             );
         }
 
+        if !status_flags.contains(StatusFlags::O_PATH) {
+            let permission = Permission::from(open_args.access_mode);
+            lsm_hooks::with_current_posix_thread(|posix_thread| {
+                lsm_hooks::on_file_open(lsm_hooks::FileOpenContext::new(
+                    posix_thread,
+                    self,
+                    permission,
+                ))
+            })?;
+        }
+
         if inode_type.is_regular_file()
             && creation_flags.contains(CreationFlags::O_TRUNC)
             && !status_flags.contains(StatusFlags::O_PATH)
         {
+            lsm_hooks::with_current_posix_thread(|posix_thread| {
+                lsm_hooks::on_path_truncate(lsm_hooks::PathTruncateContext::new(posix_thread, self))
+            })?;
+            FileCapabilities::remove_from(self)?;
             self.resize(0)?;
         }
 
@@ -182,12 +208,27 @@ impl Path {
     /// It does NOT cross mount boundaries. If the current path is the root of a mount,
     /// it will return `None`.
     ///
-    /// For cross-filesystem parent lookup, use `effective_parent()` instead.
+    /// For cross-filesystem parent lookup, use [`Self::parent_across_mounts`] instead.
     pub(super) fn parent_within_mount(&self) -> Option<Self> {
         let parent = self.dentry.parent()?;
         Some(Self::new(self.mount.clone(), parent))
     }
 
+    /// Gets the parent `Path`, crossing mount boundaries.
+    ///
+    /// If the current path is the root of a mount, this method returns its mount point, which
+    /// refers to the same location in the parent mount. Returns `None` at the root of the
+    /// mount tree.
+    pub fn parent_across_mounts(&self) -> Option<Self> {
+        if !self.is_mount_root() {
+            return self.parent_within_mount();
+        }
+
+        let parent_mount = self.mount.parent()?.upgrade()?;
+        let mountpoint = self.mount.mountpoint()?;
+        Some(Self::new(parent_mount, mountpoint))
+    }
+
     /// Gets the top `Path` of the current.
     ///
     /// Used when different file systems are mounted on the same mount point.
@@ -259,13 +300,60 @@ impl Path {
     }
 
     /// Returns true if the `Path` represents a pseudo file.
-    fn is_pseudo(&self) -> bool {
+    pub fn is_pseudo(&self) -> bool {
         self.dentry.is_pseudo()
     }
 
+    /// Checks for read/write/execute permissions on the `Path`.
+    ///
+    /// Unlike [`Inode::check_permission`], this method honors the ID mapping of the mount.
+    pub fn check_permission(&self, perm: Permission) -> Result<()> {
+        check_permission_with_metadata(&self.idmapped_metadata(), perm)
+    }
+
+    /// Gets the metadata with the ownership translated through the ID mapping of the mount.
+    ///
+    /// IDs without a mapping are represented as [`Uid::INVALID`] and [`Gid::INVALID`].
+    fn idmapped_metadata(&self) -> Metadata {
+        let mut metadata = self.inode().metadata();
+        if let Some(idmap) = self.mount.idmap() {
+            metadata.uid = idmap.map_uid_to_mount(metadata.uid);
+            metadata.gid = idmap.map_gid_to_mount(metadata.gid);
+        }
+        metadata
+    }
+
+    /// Returns the owner and the group that a new inode created under this `Path` should have.
+    ///
+    /// Returns `None` if the mount is not ID-mapped, in which case the filesystem decides
+    /// the ownership by itself.
+    fn new_inode_owner(&self) -> Result<Option<(Uid, Gid)>> {
+        match self.mount.idmap() {
+            Some(idmap) => idmap.new_inode_owner(),
+            None => Ok(None),
+        }
+    }
+
     fn this(&self) -> Self {
         self.clone()
     }
+
+    /// Runs the LSM hooks for removing the child `name` from this directory.
+    fn check_lsm_remove(&self, dir_dentry: &DirDentry<'_>, name: &str) -> Result<()> {
+        // The removal of `.` and `..` is rejected by the dentry.
+        if is_dot_or_dotdot(name) {
+            return Ok(());
+        }
+
+        let victim = Self::new(self.mount.clone(), dir_dentry.lookup_child(name)?);
+        lsm_hooks::with_current_posix_thread(|posix_thread| {
+            lsm_hooks::on_path_remove(lsm_hooks::PathRemoveContext::new(
+                posix_thread,
+                self,
+                &victim,
+            ))
+        })
+    }
 }
 
 fn try_get_mnt_ns_inode(dentry: &Dentry) -> Option<&NsInode<MountNamespace>> {
@@ -514,6 +602,84 @@ impl Path {
     }
 }
 
+impl Path {
+    /// Changes the attributes of the mount of this `Path`.
+    ///
+    /// The flags in `set_flags` are set and the flags in `clear_flags` are cleared.
+    /// If `idmap` is provided, the mount becomes ID-mapped.
+    /// If `recursive` is true, the whole mount subtree is changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EINVAL` in the following cases:
+    /// - The current path is not a mount root.
+    /// - The current path is not in the current mount namespace.
+    /// - An ID mapping is requested but the filesystem does not support ID-mapped mounts.
+    ///
+    /// Returns `EPERM` if an ID mapping is requested but the mount is already ID-mapped.
+    pub fn set_mount_attr(
+        &self,
+        set_flags: PerMountFlags,
+        clear_flags: PerMountFlags,
+        idmap: Option<MountIdmap>,
+        recursive: bool,
+        ctx: &Context,
+    ) -> Result<()> {
+        if !self.is_mount_root() {
+            return_errno_with_message!(Errno::EINVAL, "the path is not a mount root");
+        };
+
+        let current_ns_proxy = ctx.thread_local.borrow_ns_proxy();
+        let current_mnt_ns = current_ns_proxy.unwrap().mnt_ns();
+        if !current_mnt_ns.owns(&self.mount) {
+            return_errno_with_message!(Errno::EINVAL, "the path is not in this mount namespace");
+        }
+
+        let mut mounts = vec![self.mount.clone()];
+        if recursive {
+            let mut worklist: VecDeque<Arc<Mount>> =
+                self.mount.children.read().values().cloned().collect();
+            while let Some(mount) = worklist.pop_front() {
+                worklist.extend(mount.children.read().values().cloned());
+                mounts.push(mount);
+            }
+        }
+
+        // Check all the mounts before changing any of them,
+        // so that a failed request leaves the mounts untouched.
+        //
+        // Note that Linux only allows ID-mapping mounts that are not yet attached to the mount
+        // tree (i.e., those created by `open_tree(OPEN_TREE_CLONE)`). Since `open_tree` is not
+        // supported yet, we allow ID-mapping attached mounts instead.
+        if idmap.is_some() {
+            for mount in mounts.iter() {
+                if mount.idmap().is_some() {
+                    return_errno_with_message!(Errno::EPERM, "the mount is already ID-mapped");
+                }
+
+                let allows_idmap = registry::look_up(mount.fs().name()).is_some_and(|fs_type| {
+                    fs_type.properties().contains(FsProperties::ALLOW_IDMAP)
+                });
+                if !allows_idmap {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the filesystem does not support ID-mapped mounts"
+                    );
+                }
+            }
+        }
+
+        for mount in mounts.iter() {
+            mount.change_flags(set_flags, clear_flags);
+            if let Some(idmap) = idmap.as_ref() {
+                mount.set_idmap(idmap.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 // Methods inherited from `Dentry`.
 #[inherit_methods(from = "self.dentry")]
 impl Path {
@@ -522,10 +688,25 @@ impl Path {
 
     /// Creates a `Path` by making an inode of the `type_` with the `mode`.
     pub fn mknod(&self, name: &str, mode: InodeMode, type_: MknodType) -> Result<Self> {
+        let inode_type = match type_ {
+            MknodType::NamedPipe => InodeType::NamedPipe,
+            MknodType::CharDevice(_) => InodeType::CharDevice,
+            MknodType::BlockDevice(_) => InodeType::BlockDevice,
+        };
+        lsm_hooks::with_current_posix_thread(|posix_thread| {
+            lsm_hooks::on_path_create(lsm_hooks::PathCreateContext::new(
+                posix_thread,
+                self,
+                name,
+                inode_type,
+            ))
+        })?;
+
+        let new_owner = self.new_inode_owner()?;
         let inner = self
             .dentry
             .as_dir_dentry_or_err()?
-            .mknod(name, mode, type_)?;
+            .mknod(name, mode, type_, new_owner)?;
         Ok(Self::new(self.mount.clone(), inner))
     }
 
@@ -535,17 +716,33 @@ impl Path {
             return_errno_with_message!(Errno::EXDEV, "the operation cannot cross mounts");
         }
 
-        self.dentry.as_dir_dentry_or_err()?.link(old.inode(), name)
+        let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
+        lsm_hooks::with_current_posix_thread(|posix_thread| {
+            lsm_hooks::on_path_link(lsm_hooks::PathLinkContext::new(
+                posix_thread,
+                old,
+                self,
+                name,
+            ))
+        })?;
+
+        dir_dentry.link(old.inode(), name)
     }
 
     /// Unlinks a name from the `Path`.
     pub fn unlink(&self, name: &str) -> Result<()> {
-        self.dentry.as_dir_dentry_or_err()?.unlink(name)
+        let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
+        self.check_lsm_remove(&dir_dentry, name)?;
+
+        dir_dentry.unlink(name)
     }
 
     /// Removes a directory by `rmdir()` the inner inode.
     pub fn rmdir(&self, name: &str) -> Result<()> {
-        self.dentry.as_dir_dentry_or_err()?.rmdir(name)
+        let dir_dentry = self.dentry.as_dir_dentry_or_err()?;
+        self.check_lsm_remove(&dir_dentry, name)?;
+
+        dir_dentry.rmdir(name)
     }
 
     /// Renames a `Path` to the new `Path` by `rename()` the inner inode.
@@ -554,6 +751,27 @@ impl Path {
             return_errno_with_message!(Errno::EXDEV, "the operation cannot cross mounts");
         }
 
+        if !is_dot_or_dotdot(old_name) && !is_dot_or_dotdot(new_name) {
+            let old_dentry = self.dentry.as_dir_dentry_or_err()?.lookup_child(old_name)?;
+            let old = Self::new(self.mount.clone(), old_dentry);
+            let replaced = new_dir
+                .dentry
+                .as_dir_dentry_or_err()?
+                .lookup_child(new_name)
+                .ok()
+                .map(|dentry| Self::new(self.mount.clone(), dentry));
+            lsm_hooks::with_current_posix_thread(|posix_thread| {
+                lsm_hooks::on_path_rename(lsm_hooks::PathRenameContext::new(
+                    posix_thread,
+                    self,
+                    &old,
+                    new_dir,
+                    new_name,
+                    replaced.as_ref(),
+                ))
+            })?;
+        }
+
         DirDentry::rename(&self.dentry, old_name, &new_dir.dentry, new_name)
     }
 }
@@ -564,15 +782,10 @@ impl Path {
     pub fn fs(&self) -> Arc<dyn FileSystem>;
     pub fn sync_all(&self) -> Result<()>;
     pub fn sync_data(&self) -> Result<()>;
-    pub fn metadata(&self) -> Metadata;
     pub fn mode(&self) -> Result<InodeMode>;
     pub fn set_mode(&self, mode: InodeMode) -> Result<()>;
     pub fn size(&self) -> usize;
     pub fn resize(&self, size: usize) -> Result<()>;
-    pub fn owner(&self) -> Result<Uid>;
-    pub fn set_owner(&self, uid: Uid) -> Result<()>;
-    pub fn group(&self) -> Result<Gid>;
-    pub fn set_group(&self, gid: Gid) -> Result<()>;
     pub fn atime(&self) -> Duration;
     pub fn set_atime(&self, time: Duration);
     pub fn mtime(&self) -> Duration;
@@ -594,6 +807,85 @@ impl Path {
     pub fn remove_xattr(&self, name: XattrName) -> Result<()>;
 }
 
+// Ownership methods that honor the ID mapping of the mount.
+impl Path {
+    /// Gets the metadata of the `Path`.
+    ///
+    /// If the mount is ID-mapped, the owner and the group are translated through the ID
+    /// mapping. Similar to Linux, IDs without a mapping are reported as the overflow IDs.
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = self.idmapped_metadata();
+        if metadata.uid == Uid::INVALID {
+            metadata.uid = Uid::OVERFLOW;
+        }
+        if metadata.gid == Gid::INVALID {
+            metadata.gid = Gid::OVERFLOW;
+        }
+        metadata
+    }
+
+    /// Gets the owner of the `Path`.
+    pub fn owner(&self) -> Result<Uid> {
+        let uid = self.inode().owner()?;
+        let Some(idmap) = self.mount.idmap() else {
+            return Ok(uid);
+        };
+
+        match idmap.map_uid_to_mount(uid) {
+            Uid::INVALID => Ok(Uid::OVERFLOW),
+            uid => Ok(uid),
+        }
+    }
+
+    /// Translates a UID stored in the filesystem to the UID seen through the mount.
+    ///
+    /// This is for UIDs stored in places other than the owner, e.g., the root UID of the file
+    /// capabilities. Returns [`Uid::INVALID`] if the UID has no mapping.
+    pub fn map_uid_to_mount(&self, uid: Uid) -> Uid {
+        match self.mount.idmap() {
+            Some(idmap) => idmap.map_uid_to_mount(uid),
+            None => uid,
+        }
+    }
+
+    /// Sets the owner of the `Path`.
+    ///
+    /// If the mount is ID-mapped, the owner is translated back through the ID mapping
+    /// before being stored in the filesystem.
+    pub fn set_owner(&self, uid: Uid) -> Result<()> {
+        let uid = match self.mount.idmap() {
+            Some(idmap) => idmap.map_uid_to_fs(uid)?,
+            None => uid,
+        };
+        self.inode().set_owner(uid)
+    }
+
+    /// Gets the group of the `Path`.
+    pub fn group(&self) -> Result<Gid> {
+        let gid = self.inode().group()?;
+        let Some(idmap) = self.mount.idmap() else {
+            return Ok(gid);
+        };
+
+        match idmap.map_gid_to_mount(gid) {
+            Gid::INVALID => Ok(Gid::OVERFLOW),
+            gid => Ok(gid),
+        }
+    }
+
+    /// Sets the group of the `Path`.
+    ///
+    /// If the mount is ID-mapped, the group is translated back through the ID mapping
+    /// before being stored in the filesystem.
+    pub fn set_group(&self, gid: Gid) -> Result<()> {
+        let gid = match self.mount.idmap() {
+            Some(idmap) => idmap.map_gid_to_fs(gid)?,
+            None => gid,
+        };
+        self.inode().set_group(gid)
+    }
+}
+
 /// Checks if the file name is ".", indicating it's the current directory.
 pub const fn is_dot(filename: &str) -> bool {
     let name_bytes = filename.as_bytes();
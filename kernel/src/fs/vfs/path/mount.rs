@@ -16,6 +16,7 @@ use crate::{
             path::{
                 Path,
                 dentry::{Dentry, DentryKey},
+                idmap::MountIdmap,
                 mount_namespace::MountNamespace,
             },
         },
@@ -202,6 +203,8 @@ pub struct Mount {
     propagation: RwLock<MountPropType>,
     /// The flags of this mount.
     flags: AtomicPerMountFlags,
+    /// The ID mapping of this mount if the mount is ID-mapped.
+    idmap: RwLock<Option<MountIdmap>>,
     /// Reference to self.
     this: Weak<Self>,
 }
@@ -258,6 +261,7 @@ impl Mount {
             mnt_ns,
             propagation: RwLock::new(MountPropType::default()),
             flags: AtomicPerMountFlags::new(flags),
+            idmap: RwLock::new(None),
             this: weak_self.clone(),
         }))
     }
@@ -349,6 +353,7 @@ impl Mount {
             mnt_ns: new_ns.clone(),
             propagation: RwLock::new(MountPropType::default()),
             flags: AtomicPerMountFlags::new(self.flags.load(Ordering::Relaxed)),
+            idmap: RwLock::new(self.idmap()),
             this: weak_self.clone(),
         }))
     }
@@ -573,6 +578,43 @@ impl Mount {
         self.flags.load(Ordering::Relaxed)
     }
 
+    /// Sets and clears the given flags of this mount.
+    ///
+    /// If `set_flags` contains an atime policy, the old atime policy is replaced.
+    pub(super) fn change_flags(&self, set_flags: PerMountFlags, clear_flags: PerMountFlags) {
+        const ATIME_MASK: PerMountFlags = PerMountFlags::NOATIME
+            .union(PerMountFlags::RELATIME)
+            .union(PerMountFlags::STRICTATIME);
+
+        let mut new_flags = self.flags.load(Ordering::Relaxed) - clear_flags;
+        if set_flags.intersects(ATIME_MASK) {
+            new_flags -= ATIME_MASK;
+        }
+        new_flags |= set_flags;
+
+        self.flags.store(new_flags, Ordering::Relaxed);
+    }
+
+    /// Gets the ID mapping of this mount if the mount is ID-mapped.
+    pub(super) fn idmap(&self) -> Option<MountIdmap> {
+        self.idmap.read().clone()
+    }
+
+    /// Sets the ID mapping of this mount.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EPERM` if the mount is already ID-mapped.
+    pub(super) fn set_idmap(&self, idmap: MountIdmap) -> Result<()> {
+        let mut old_idmap = self.idmap.write();
+        if old_idmap.is_some() {
+            return_errno_with_message!(Errno::EPERM, "the mount is already ID-mapped");
+        }
+
+        *old_idmap = Some(idmap);
+        Ok(())
+    }
+
     /// Sets the parent mount node.
     ///
     /// In some cases we may need to reset the parent of
@@ -16,6 +16,7 @@ use crate::{
     },
     prelude::*,
     process::{pid_table::PidTable, posix_thread::AsPosixThread},
+    security::audit,
 };
 
 /// The file descriptor of the current working directory.
@@ -537,7 +538,7 @@ impl PathResolver {
     pub fn lookup_at_path(&self, path: &Path, name: &str) -> Result<Path> {
         let dir_dentry = path.dentry.as_dir_dentry_or_err()?;
 
-        if path.inode().check_permission(Permission::MAY_EXEC).is_err() {
+        if path.check_permission(Permission::MAY_EXEC).is_err() {
             return_errno_with_message!(Errno::EACCES, "the path cannot be looked up");
         }
         if name.len() > NAME_MAX {
@@ -595,6 +596,14 @@ impl PathResolver {
     }
 
     fn lookup_inner(&self, fs_path: &FsPath, follow_tail_link: bool) -> Result<LookupResult> {
+        let lookup_res = self.lookup_fs_path(fs_path, follow_tail_link);
+        if audit::is_auditing_syscall() {
+            audit::log_path(self, fs_path.path_str(), &lookup_res);
+        }
+        lookup_res
+    }
+
+    fn lookup_fs_path(&self, fs_path: &FsPath, follow_tail_link: bool) -> Result<LookupResult> {
         let lookup_res = match fs_path.inner {
             FsPathInner::Absolute(path) => {
                 self.lookup_from_parent(&self.root, path.trim_start_matches('/'), follow_tail_link)?
@@ -801,6 +810,16 @@ impl LookupParentResult {
         }
     }
 
+    /// Returns the path of the parent directory where resolution stopped.
+    pub fn parent(&self) -> &Path {
+        &self.parent
+    }
+
+    /// Returns the remaining unresolved component name.
+    pub fn unresolved_name(&self) -> &str {
+        &self.unresolved_name
+    }
+
     /// Returns true if the target was expected to be a directory.
     pub fn target_is_dir(&self) -> bool {
         self.target_is_dir
@@ -901,6 +920,16 @@ impl<'a> FsPath<'a> {
             inner: fs_path_inner,
         })
     }
+
+    /// Returns the path string, if the `FsPath` is constructed from a non-empty path string.
+    fn path_str(&self) -> Option<&'a str> {
+        match self.inner {
+            FsPathInner::Absolute(path)
+            | FsPathInner::CwdRelative(path)
+            | FsPathInner::FdRelative(_, path) => Some(path),
+            FsPathInner::Cwd | FsPathInner::Fd(_) => None,
+        }
+    }
 }
 
 impl<'a> TryFrom<&'a str> for FsPath<'a> {
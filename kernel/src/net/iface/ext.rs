@@ -1,7 +1,15 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::sched::PollScheduler;
-use crate::net::socket::ip::{DatagramObserver, StreamObserver};
+use crate::net::{
+    netfilter::Netfilter,
+    route::Router,
+    socket::{
+        ip::{DatagramObserver, StreamObserver},
+        packet::PacketObserver,
+        util::SocketFilter,
+    },
+};
 
 pub struct BigtcpExt;
 
@@ -10,4 +18,13 @@ impl aster_bigtcp::ext::Ext for BigtcpExt {
 
     type TcpEventObserver = StreamObserver;
     type UdpEventObserver = DatagramObserver;
+    type RawEventObserver = DatagramObserver;
+
+    type FrameObserver = PacketObserver;
+
+    type ReuseportSelector = SocketFilter;
+
+    type Router = Router;
+
+    type PacketFilter = Netfilter;
 }
@@ -1,21 +1,27 @@
 // SPDX-License-Identifier: MPL-2.0
 
-mod broadcast;
 mod ext;
 mod init;
 mod poll;
 mod sched;
+mod virt;
 
-pub use broadcast::is_broadcast_endpoint;
-pub use init::{init, iter_all_ifaces, loopback_iface, virtio_iface};
-pub(super) use poll::init_in_first_kthread;
+pub use init::init;
+pub(super) use init::{VIRTIO_GATEWAY, new_loopback, new_virtio};
+pub(super) use poll::{init_in_first_kthread, spawn_background_poll_thread};
+pub(super) use virt::{Bridge, TunDevice, VethEnd, VirtLink, virt_flags};
+pub use virt::{TunFlags, TunQueue};
 
 pub type Iface = dyn aster_bigtcp::iface::Iface<ext::BigtcpExt>;
+pub type BoundPort = aster_bigtcp::iface::BoundPort<ext::BigtcpExt>;
 pub type BoundTcpPort = aster_bigtcp::iface::BoundTcpPort<ext::BigtcpExt>;
 pub type BoundUdpPort = aster_bigtcp::iface::BoundUdpPort<ext::BigtcpExt>;
+pub type BoundRawPort = aster_bigtcp::iface::BoundRawPort<ext::BigtcpExt>;
 
 pub type RawTcpSocketExt = aster_bigtcp::socket::RawTcpSocketExt<ext::BigtcpExt>;
 
 pub type TcpConnection = aster_bigtcp::socket::TcpConnection<ext::BigtcpExt>;
 pub type TcpListener = aster_bigtcp::socket::TcpListener<ext::BigtcpExt>;
 pub type UdpSocket = aster_bigtcp::socket::UdpSocket<ext::BigtcpExt>;
+pub type RawSocket = aster_bigtcp::socket::RawSocket<ext::BigtcpExt>;
+pub type PacketSocket = aster_bigtcp::socket::PacketSocket<ext::BigtcpExt>;
@@ -5,37 +5,48 @@ use core::time::Duration;
 
 use ostd::{debug, timer::Jiffies};
 
-use super::{Iface, iter_all_ifaces};
+use super::Iface;
 use crate::{
+    net::net_ns::NetNamespace,
     sched::{Nice, SchedPolicy},
     thread::kernel_thread::ThreadOptions,
     time::wait::WaitTimeout,
 };
 
 pub fn init_in_first_kthread() {
-    for iface in iter_all_ifaces() {
-        spawn_background_poll_thread(iface.clone());
+    for iface in NetNamespace::get_init_singleton().ifaces() {
+        spawn_background_poll_thread(iface);
     }
 }
 
-pub(super) fn poll_ifaces() {
-    for iface in iter_all_ifaces() {
+pub(super) fn poll_ifaces(ifaces: &[Arc<Iface>]) {
+    for iface in ifaces {
         iface.poll();
     }
 }
 
-fn spawn_background_poll_thread(iface: Arc<Iface>) {
+/// Spawns a background thread that polls `iface`.
+///
+/// The thread exits once the poll scheduler of `iface` is stopped.
+pub(in crate::net) fn spawn_background_poll_thread(iface: Arc<Iface>) {
     let task_fn = move || {
         debug!("spawn background poll thread for {:?}", iface.name());
 
         let sched_poll = iface.sched_poll();
         let wait_queue = sched_poll.polling_wait_queue();
 
-        loop {
-            let next_poll_at_ms = if let Some(next_poll_at_ms) = sched_poll.next_poll_at_ms() {
-                next_poll_at_ms
-            } else {
-                wait_queue.wait_until(|| sched_poll.next_poll_at_ms())
+        while !sched_poll.is_stopped() {
+            if sched_poll.take_poll_request() {
+                iface.poll();
+                continue;
+            }
+
+            let Some(next_poll_at_ms) = sched_poll.next_poll_at_ms() else {
+                wait_queue.wait_until(|| {
+                    (sched_poll.should_wake_up() || sched_poll.next_poll_at_ms().is_some())
+                        .then_some(())
+                });
+                continue;
             };
 
             let now_as_ms = Jiffies::elapsed().as_duration().as_millis() as u64;
@@ -55,9 +66,14 @@ fn spawn_background_poll_thread(iface: Arc<Iface>) {
 
             let duration = Duration::from_millis(next_poll_at_ms - now_as_ms);
             let _ = wait_queue.wait_until_or_timeout(
-                // If `sched_poll.next_poll_at_ms()` changes to an earlier time, we will end the
-                // waiting.
-                || (sched_poll.next_poll_at_ms()? < next_poll_at_ms).then_some(()),
+                // If `sched_poll.next_poll_at_ms()` changes to an earlier time, a poll is
+                // requested, or the poll scheduler is stopped, we will end the waiting.
+                || {
+                    if sched_poll.should_wake_up() {
+                        return Some(());
+                    }
+                    (sched_poll.next_poll_at_ms()? < next_poll_at_ms).then_some(())
+                },
                 &duration,
             );
         }
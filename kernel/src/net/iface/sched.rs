@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU64, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use aster_bigtcp::iface::ScheduleNextPoll;
 use ostd::sync::WaitQueue;
@@ -11,6 +11,10 @@ pub struct PollScheduler {
     next_poll_at_ms: AtomicU64,
     /// The wait queue that the background polling thread will sleep on.
     polling_wait_queue: WaitQueue,
+    /// Whether the background polling thread should exit.
+    is_stopped: AtomicBool,
+    /// Whether a poll is requested regardless of `next_poll_at_ms`.
+    is_poll_requested: AtomicBool,
 }
 
 impl PollScheduler {
@@ -18,6 +22,8 @@ impl PollScheduler {
         Self {
             next_poll_at_ms: AtomicU64::new(0),
             polling_wait_queue: WaitQueue::new(),
+            is_stopped: AtomicBool::new(false),
+            is_poll_requested: AtomicBool::new(false),
         }
     }
 
@@ -29,6 +35,35 @@ impl PollScheduler {
     pub(super) fn polling_wait_queue(&self) -> &WaitQueue {
         &self.polling_wait_queue
     }
+
+    /// Stops the background polling thread.
+    pub(in crate::net) fn stop(&self) {
+        self.is_stopped.store(true, Ordering::Relaxed);
+        self.polling_wait_queue.wake_all();
+    }
+
+    pub(super) fn is_stopped(&self) -> bool {
+        self.is_stopped.load(Ordering::Relaxed)
+    }
+
+    /// Requests the background polling thread to poll the iface as soon as possible.
+    ///
+    /// This is used by virtual devices, which have no interrupts to notify the arrival of
+    /// new packets.
+    pub(in crate::net) fn request_poll(&self) {
+        self.is_poll_requested.store(true, Ordering::Release);
+        self.polling_wait_queue.wake_all();
+    }
+
+    pub(super) fn take_poll_request(&self) -> bool {
+        self.is_poll_requested.swap(false, Ordering::AcqRel)
+    }
+
+    /// Returns whether the background polling thread should stop waiting for
+    /// `next_poll_at_ms`.
+    pub(super) fn should_wake_up(&self) -> bool {
+        self.is_stopped() || self.is_poll_requested.load(Ordering::Acquire)
+    }
 }
 
 impl ScheduleNextPoll for PollScheduler {
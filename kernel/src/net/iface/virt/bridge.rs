@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::EthernetAddress;
+
+use super::{
+    ETHERNET_HEADER_LEN, VethEnd,
+    device::{RxQueue, VirtEndpoint},
+    new_virt_iface, random_ether_addr,
+};
+use crate::{net::iface::Iface, prelude::*};
+
+/// A learning Ethernet bridge.
+///
+/// The bridge learns the source addresses of the frames that arrive at its ports, and forwards
+/// frames to the port that the destination address is learned from. Frames with unknown
+/// destinations, broadcast frames, and multicast frames are flooded to all other ports.
+///
+/// Only veth ends can be enslaved to a bridge. The bridge itself is also an iface, which receives
+/// the frames that are destined to its own address and the flooded broadcast or multicast frames.
+//
+// TODO: The Spanning Tree Protocol (STP) is not supported, so bridging loops will cause broadcast
+// storms. The learned addresses never expire.
+pub(in crate::net) struct Bridge {
+    ether_addr: EthernetAddress,
+    rx_queue: RxQueue,
+    ports: SpinLock<Vec<Arc<VethEnd>>>,
+    fdb: SpinLock<BTreeMap<EthernetAddress, Weak<VethEnd>>>,
+}
+
+impl Bridge {
+    /// Creates a bridge and returns it with its iface.
+    pub(in crate::net) fn new(name: CString) -> (Arc<Self>, Arc<Iface>) {
+        let ether_addr = random_ether_addr();
+        let bridge = Arc::new(Self {
+            ether_addr,
+            rx_queue: RxQueue::new(),
+            ports: SpinLock::new(Vec::new()),
+            fdb: SpinLock::new(BTreeMap::new()),
+        });
+
+        let iface = new_virt_iface(bridge.clone(), ether_addr, name);
+        bridge.rx_queue.set_iface(&iface);
+
+        (bridge, iface)
+    }
+
+    /// Returns the index of the iface.
+    pub(in crate::net) fn index(&self) -> Option<u32> {
+        self.rx_queue.iface().map(|iface| iface.index())
+    }
+
+    /// Requests the iface to poll so that the frames queued in the ports will be forwarded.
+    pub(super) fn request_poll(&self) {
+        self.rx_queue.request_poll();
+    }
+
+    pub(super) fn add_port(&self, port: Arc<VethEnd>) {
+        let mut ports = self.ports.lock();
+        if !ports.iter().any(|existing| Arc::ptr_eq(existing, &port)) {
+            ports.push(port);
+        }
+    }
+
+    pub(super) fn remove_port(&self, port: &Arc<VethEnd>) {
+        self.ports
+            .lock()
+            .retain(|existing| !Arc::ptr_eq(existing, port));
+        self.fdb
+            .lock()
+            .retain(|_, learned| !core::ptr::eq(learned.as_ptr(), Arc::as_ptr(port)));
+    }
+
+    /// Releases all the ports.
+    pub(super) fn release_all_ports(&self) {
+        let ports = core::mem::take(&mut *self.ports.lock());
+        for port in ports {
+            port.clear_master();
+        }
+        self.fdb.lock().clear();
+    }
+
+    /// Forwards a frame that comes from `src_port`, or from the bridge iface if it is `None`.
+    fn forward(&self, src_port: Option<&Arc<VethEnd>>, frame: Vec<u8>) {
+        if frame.len() < ETHERNET_HEADER_LEN {
+            return;
+        }
+        let dst_addr = EthernetAddress::from_bytes(&frame[0..6]);
+        let src_addr = EthernetAddress::from_bytes(&frame[6..12]);
+
+        if let Some(src_port) = src_port
+            && src_addr.is_unicast()
+        {
+            self.fdb.lock().insert(src_addr, Arc::downgrade(src_port));
+        }
+
+        if dst_addr == self.ether_addr {
+            if src_port.is_some() {
+                self.rx_queue.push(frame);
+            }
+            return;
+        }
+
+        if dst_addr.is_unicast() {
+            let dst_port = self.fdb.lock().get(&dst_addr).and_then(Weak::upgrade);
+            if let Some(dst_port) = dst_port {
+                if !src_port.is_some_and(|src_port| Arc::ptr_eq(src_port, &dst_port)) {
+                    dst_port.transmit(frame);
+                }
+                return;
+            }
+        }
+
+        // Flood the frame.
+        if src_port.is_some() && !dst_addr.is_unicast() {
+            self.rx_queue.push(frame.clone());
+        }
+        let ports = self.ports.lock().clone();
+        for port in ports.iter() {
+            if src_port.is_some_and(|src_port| Arc::ptr_eq(src_port, port)) {
+                continue;
+            }
+            port.transmit(frame.clone());
+        }
+    }
+}
+
+impl VirtEndpoint for Bridge {
+    fn receive(&self) -> Option<Vec<u8>> {
+        loop {
+            if let Some(frame) = self.rx_queue.pop() {
+                return Some(frame);
+            }
+
+            // Forward the frames queued in the ports. Some of them may be delivered to the
+            // bridge iface.
+            let ports = self.ports.lock().clone();
+            let mut has_forwarded = false;
+            for port in ports.iter() {
+                while let Some(frame) = port.pop_frame() {
+                    self.forward(Some(port), frame);
+                    has_forwarded = true;
+                }
+            }
+
+            if !has_forwarded {
+                return None;
+            }
+        }
+    }
+
+    fn transmit(&self, frame: Vec<u8>) {
+        self.forward(None, frame);
+    }
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    device::{self, DeviceCapabilities, Medium, NotifyDevice, WithDevice},
+    time::Instant,
+};
+use spin::Once;
+
+use super::{ETHERNET_HEADER_LEN, VIRT_MTU};
+use crate::{net::iface::Iface, prelude::*};
+
+/// An endpoint that moves Ethernet frames (or IP packets) in and out of a virtual device.
+pub(super) trait VirtEndpoint: Send + Sync {
+    /// Returns the medium of the device.
+    fn medium(&self) -> Medium {
+        Medium::Ethernet
+    }
+
+    /// Takes the next frame that should be received by the iface.
+    fn receive(&self) -> Option<Vec<u8>>;
+
+    /// Transmits a frame that is sent by the iface.
+    fn transmit(&self, frame: Vec<u8>);
+}
+
+/// A queue of incoming Ethernet frames.
+pub(super) struct RxQueue {
+    frames: SpinLock<VecDeque<Vec<u8>>>,
+    iface: Once<Weak<Iface>>,
+}
+
+impl RxQueue {
+    /// The maximum number of queued frames.
+    ///
+    /// This is the default `txqueuelen` of veth devices in Linux.
+    const MAX_FRAMES: usize = 1000;
+
+    pub(super) fn new() -> Self {
+        Self {
+            frames: SpinLock::new(VecDeque::new()),
+            iface: Once::new(),
+        }
+    }
+
+    /// Sets the iface that receives the frames.
+    pub(super) fn set_iface(&self, iface: &Arc<Iface>) {
+        self.iface.call_once(|| Arc::downgrade(iface));
+    }
+
+    /// Returns the iface that receives the frames.
+    pub(super) fn iface(&self) -> Option<Arc<Iface>> {
+        self.iface.get().and_then(Weak::upgrade)
+    }
+
+    /// Pushes a frame to the queue.
+    ///
+    /// The frame will be dropped if the queue is full.
+    pub(super) fn push(&self, frame: Vec<u8>) {
+        let mut frames = self.frames.lock();
+        if frames.len() >= Self::MAX_FRAMES {
+            return;
+        }
+        frames.push_back(frame);
+    }
+
+    pub(super) fn pop(&self) -> Option<Vec<u8>> {
+        self.frames.lock().pop_front()
+    }
+
+    /// Requests the iface to poll so that the queued frames will be processed.
+    pub(super) fn request_poll(&self) {
+        if let Some(iface) = self.iface() {
+            iface.sched_poll().request_poll();
+        }
+    }
+}
+
+/// A [`device::Device`] that is backed by a [`VirtEndpoint`].
+pub(super) struct VirtDevice<L>(Arc<L>);
+
+impl<L: VirtEndpoint> device::Device for VirtDevice<L> {
+    type RxToken<'a> = RxToken;
+    type TxToken<'a> = TxToken<'a, L>;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let frame = self.0.receive()?;
+        Some((RxToken(frame), TxToken(&self.0)))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        Some(TxToken(&self.0))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.medium = self.0.medium();
+        caps.max_transmission_unit = match caps.medium {
+            Medium::Ethernet => VIRT_MTU + ETHERNET_HEADER_LEN,
+            _ => VIRT_MTU,
+        };
+        caps
+    }
+}
+
+impl<L> NotifyDevice for VirtDevice<L> {
+    fn notify_poll_end(&mut self) {}
+}
+
+pub(super) struct RxToken(Vec<u8>);
+
+impl device::RxToken for RxToken {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        f(&self.0)
+    }
+}
+
+pub(super) struct TxToken<'a, L>(&'a Arc<L>);
+
+impl<L: VirtEndpoint> device::TxToken for TxToken<'_, L> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mut buffer = vec![0u8; len];
+        let res = f(&mut buffer);
+        self.0.transmit(buffer);
+        res
+    }
+}
+
+/// A [`WithDevice`] implementation for [`VirtDevice`]s.
+pub(super) struct VirtDriver<L>(Mutex<VirtDevice<L>>);
+
+impl<L> VirtDriver<L> {
+    pub(super) fn new(endpoint: Arc<L>) -> Self {
+        Self(Mutex::new(VirtDevice(endpoint)))
+    }
+}
+
+impl<L: VirtEndpoint> WithDevice for VirtDriver<L> {
+    type Device = VirtDevice<L>;
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Device) -> R,
+    {
+        let mut device = self.0.lock();
+        f(&mut device)
+    }
+}
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Virtual network devices.
+//!
+//! Virtual devices are not backed by any hardware. Instead, Ethernet frames (or IP packets) are
+//! moved between devices, or between devices and user space, through in-memory queues. Since there are no interrupts, a device requests its iface
+//! to be polled (see [`PollScheduler::request_poll`]) whenever new frames arrive.
+//!
+//! [`PollScheduler::request_poll`]: super::sched::PollScheduler::request_poll
+
+mod bridge;
+mod device;
+mod tun;
+mod veth;
+
+use aster_bigtcp::{
+    iface::{EtherIface, InterfaceFlags},
+    wire::EthernetAddress,
+};
+pub(in crate::net) use bridge::Bridge;
+use device::{VirtDriver, VirtEndpoint};
+pub(in crate::net) use tun::TunDevice;
+pub use tun::{TunFlags, TunQueue};
+pub(in crate::net) use veth::VethEnd;
+
+use super::{Iface, sched::PollScheduler};
+use crate::{prelude::*, util::random::getrandom};
+
+/// A virtual link.
+#[derive(Clone)]
+pub(in crate::net) enum VirtLink {
+    Veth(Arc<VethEnd>),
+    Bridge(Arc<Bridge>),
+    Tun(Arc<TunDevice>),
+}
+
+impl VirtLink {
+    /// Returns the index of the bridge that the link is enslaved to, if any.
+    pub(in crate::net) fn master_index(&self) -> Option<u32> {
+        match self {
+            VirtLink::Veth(veth) => veth.master().and_then(|bridge| bridge.index()),
+            VirtLink::Bridge(_) | VirtLink::Tun(_) => None,
+        }
+    }
+
+    /// Detaches the link from other links before it is removed.
+    ///
+    /// A veth end leaves its bridge, a bridge releases all of its ports, and a TUN/TAP device
+    /// detaches all of its files.
+    pub(in crate::net) fn detach(&self) {
+        match self {
+            VirtLink::Veth(veth) => veth.set_master(None),
+            VirtLink::Bridge(bridge) => bridge.release_all_ports(),
+            VirtLink::Tun(tun) => tun.detach_all(),
+        }
+    }
+}
+
+/// The maximum transmission unit of virtual devices, excluding the Ethernet header.
+const VIRT_MTU: usize = 1500;
+
+/// The length of the Ethernet header.
+const ETHERNET_HEADER_LEN: usize = 14;
+
+/// The flags of newly created virtual devices.
+//
+// FIXME: Setting the link up or down is not supported yet. Virtual devices are always up, and
+// netlink requests that change the flags fail with `EOPNOTSUPP`.
+pub(in crate::net) fn virt_flags() -> InterfaceFlags {
+    InterfaceFlags::UP
+        | InterfaceFlags::BROADCAST
+        | InterfaceFlags::RUNNING
+        | InterfaceFlags::MULTICAST
+        | InterfaceFlags::LOWER_UP
+}
+
+/// Generates a random, locally administered, unicast Ethernet address.
+fn random_ether_addr() -> EthernetAddress {
+    let mut bytes = [0u8; 6];
+    getrandom(&mut bytes);
+    // Clear the multicast bit and set the locally administered bit.
+    bytes[0] = (bytes[0] & 0xfe) | 0x02;
+    EthernetAddress(bytes)
+}
+
+/// Creates an Ethernet iface on top of a virtual endpoint.
+fn new_virt_iface<L: VirtEndpoint + 'static>(
+    endpoint: Arc<L>,
+    ether_addr: EthernetAddress,
+    name: CString,
+) -> Arc<Iface> {
+    EtherIface::new(
+        VirtDriver::new(endpoint),
+        ether_addr,
+        None,
+        name,
+        PollScheduler::new(),
+        virt_flags(),
+    ) as Arc<Iface>
+}
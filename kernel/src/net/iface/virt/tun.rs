@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicU16, Ordering};
+
+use aster_bigtcp::{
+    device::Medium,
+    iface::{InterfaceFlags, InterfaceType, IpIface},
+};
+
+use super::{
+    ETHERNET_HEADER_LEN, VIRT_MTU,
+    device::{RxQueue, VirtDriver, VirtEndpoint},
+    new_virt_iface, random_ether_addr,
+};
+use crate::{
+    events::IoEvents,
+    net::iface::{Iface, sched::PollScheduler},
+    prelude::*,
+    process::signal::{PollHandle, Pollee},
+};
+
+bitflags! {
+    /// The flags of TUN/TAP devices.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/if_tun.h>.
+    pub struct TunFlags: u16 {
+        /// TUN device (no Ethernet headers)
+        const TUN = 0x0001;
+        /// TAP device
+        const TAP = 0x0002;
+        /// Multi-queue device
+        const MULTI_QUEUE = 0x0100;
+        /// Do not provide packet information
+        const NO_PI = 0x1000;
+        /// This flag has no real effect
+        const ONE_QUEUE = 0x2000;
+        /// Prepend `virtio_net_hdr` to the packets
+        const VNET_HDR = 0x4000;
+    }
+}
+
+impl TunFlags {
+    /// The flags that describe the features of a device, rather than its type.
+    const FEATURES: Self =
+        Self::from_bits_truncate(Self::MULTI_QUEUE.bits | Self::NO_PI.bits | Self::ONE_QUEUE.bits);
+}
+
+/// The packet information that is prepended to packets, unless [`TunFlags::NO_PI`] is set.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/if_tun.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CTunPi {
+    flags: u16,
+    /// The protocol in network byte order
+    proto: u16,
+}
+
+/// A TUN or TAP device.
+///
+/// The IP packets (for TUN) or Ethernet frames (for TAP) that the iface sends are queued to the
+/// attached files, while the ones written to the files are received by the iface.
+pub(in crate::net) struct TunDevice {
+    is_tap: bool,
+    features: AtomicU16,
+    rx_queue: RxQueue,
+    queues: SpinLock<Vec<Arc<TunQueue>>>,
+}
+
+impl TunDevice {
+    /// Creates a TUN/TAP device and returns it with its iface.
+    ///
+    /// `flags` must contain either [`TunFlags::TUN`] or [`TunFlags::TAP`].
+    pub(in crate::net) fn new(name: CString, flags: TunFlags) -> (Arc<Self>, Arc<Iface>) {
+        let is_tap = flags.contains(TunFlags::TAP);
+        let device = Arc::new(Self {
+            is_tap,
+            features: AtomicU16::new((flags & TunFlags::FEATURES).bits()),
+            rx_queue: RxQueue::new(),
+            queues: SpinLock::new(Vec::new()),
+        });
+
+        let iface = if is_tap {
+            new_virt_iface(device.clone(), random_ether_addr(), name)
+        } else {
+            // FIXME: These flags are currently hardcoded. Setting the link up or down is not
+            // supported yet.
+            let iface_flags = InterfaceFlags::UP
+                | InterfaceFlags::POINTOPOINT
+                | InterfaceFlags::RUNNING
+                | InterfaceFlags::NOARP
+                | InterfaceFlags::MULTICAST
+                | InterfaceFlags::LOWER_UP;
+
+            IpIface::new(
+                VirtDriver::new(device.clone()),
+                None,
+                None,
+                name,
+                PollScheduler::new(),
+                InterfaceType::NONE,
+                iface_flags,
+            ) as Arc<Iface>
+        };
+        device.rx_queue.set_iface(&iface);
+
+        (device, iface)
+    }
+
+    /// Returns the index of the iface.
+    pub(in crate::net) fn index(&self) -> Option<u32> {
+        self.rx_queue.iface().map(|iface| iface.index())
+    }
+
+    /// Returns the flags of the device, including the type and the features.
+    fn flags(&self) -> TunFlags {
+        let type_ = if self.is_tap {
+            TunFlags::TAP
+        } else {
+            TunFlags::TUN
+        };
+        type_ | TunFlags::from_bits_truncate(self.features.load(Ordering::Relaxed))
+    }
+
+    /// Attaches `queue` to the device.
+    pub(in crate::net) fn attach(
+        self: &Arc<Self>,
+        queue: &Arc<TunQueue>,
+        flags: TunFlags,
+    ) -> Result<()> {
+        if flags.contains(TunFlags::TAP) != self.is_tap {
+            return_errno_with_message!(Errno::EINVAL, "the TUN/TAP device type does not match");
+        }
+
+        let mut queues = self.queues.lock();
+
+        let is_multi_queue = self.flags().contains(TunFlags::MULTI_QUEUE);
+        if flags.contains(TunFlags::MULTI_QUEUE) != is_multi_queue {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the multi-queue flag does not match the device"
+            );
+        }
+        if !is_multi_queue && !queues.is_empty() {
+            return_errno_with_message!(Errno::EBUSY, "the TUN/TAP device is already attached");
+        }
+        // The attached queues rely on the current features to frame their packets, so a new
+        // queue cannot change them.
+        if !queues.is_empty()
+            && flags.contains(TunFlags::NO_PI) != self.flags().contains(TunFlags::NO_PI)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the packet information flag does not match the device"
+            );
+        }
+
+        let mut device = queue.device.lock();
+        if device.is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the file is already attached");
+        }
+        *device = Some(self.clone());
+        drop(device);
+
+        self.features
+            .store((flags & TunFlags::FEATURES).bits(), Ordering::Relaxed);
+        queues.push(queue.clone());
+        queue.pollee.notify(IoEvents::OUT);
+
+        Ok(())
+    }
+
+    /// Detaches `queue` from the device.
+    ///
+    /// This method returns whether the device should be removed, i.e., whether the last queue is
+    /// detached.
+    //
+    // TODO: Support persistent devices (`TUNSETPERSIST`), which are not removed in this case.
+    pub(in crate::net) fn detach(&self, queue: &Arc<TunQueue>) -> bool {
+        let mut queues = self.queues.lock();
+        queues.retain(|attached| !Arc::ptr_eq(attached, queue));
+        queue.detach();
+
+        queues.is_empty()
+    }
+
+    /// Detaches all the queues from the device.
+    pub(in crate::net) fn detach_all(&self) {
+        let queues = core::mem::take(&mut *self.queues.lock());
+        for queue in queues {
+            queue.detach();
+        }
+    }
+}
+
+impl VirtEndpoint for TunDevice {
+    fn medium(&self) -> Medium {
+        if self.is_tap {
+            Medium::Ethernet
+        } else {
+            Medium::Ip
+        }
+    }
+
+    fn receive(&self) -> Option<Vec<u8>> {
+        self.rx_queue.pop()
+    }
+
+    fn transmit(&self, packet: Vec<u8>) {
+        let queue = {
+            let queues = self.queues.lock();
+            if queues.is_empty() {
+                return;
+            }
+            let index = flow_hash(&packet, self.is_tap) as usize % queues.len();
+            queues[index].clone()
+        };
+
+        queue.push(packet);
+    }
+}
+
+/// Returns the protocol (i.e., the EtherType) of the packet.
+fn packet_proto(packet: &[u8], is_tap: bool) -> u16 {
+    if is_tap {
+        return packet
+            .get(12..14)
+            .map_or(0, |bytes| u16::from_be_bytes([bytes[0], bytes[1]]));
+    }
+
+    match packet.first().map(|byte| byte >> 4) {
+        Some(4) => ETH_P_IP,
+        Some(6) => ETH_P_IPV6,
+        _ => 0,
+    }
+}
+
+/// Computes a hash of the addresses in the packet.
+///
+/// Packets of the same flow will be queued to the same queue of a multi-queue device, so that
+/// they will not be reordered.
+fn flow_hash(packet: &[u8], is_tap: bool) -> u32 {
+    let offset = if is_tap { ETHERNET_HEADER_LEN } else { 0 };
+    let addrs = match packet_proto(packet, is_tap) {
+        ETH_P_IP => packet.get(offset + 12..offset + 20),
+        ETH_P_IPV6 => packet.get(offset + 8..offset + 40),
+        _ => packet.get(0..12),
+    };
+
+    // FNV-1a
+    addrs
+        .unwrap_or(&[])
+        .iter()
+        .fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ *byte as u32).wrapping_mul(0x01000193)
+        })
+}
+
+/// A queue of a TUN/TAP device, which belongs to an opened `/dev/net/tun` file.
+pub struct TunQueue {
+    device: SpinLock<Option<Arc<TunDevice>>>,
+    packets: SpinLock<VecDeque<Vec<u8>>>,
+    pollee: Pollee,
+}
+
+impl TunQueue {
+    /// The maximum number of queued packets.
+    ///
+    /// This is the default `txqueuelen` of TUN/TAP devices in Linux.
+    const MAX_PACKETS: usize = 500;
+
+    /// Creates a queue that is not attached to any device.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            device: SpinLock::new(None),
+            packets: SpinLock::new(VecDeque::new()),
+            pollee: Pollee::new(),
+        })
+    }
+
+    pub(in crate::net) fn device(&self) -> Option<Arc<TunDevice>> {
+        self.device.lock().clone()
+    }
+
+    /// Returns the name and the flags of the attached device.
+    pub fn device_info(&self) -> Option<(CString, TunFlags)> {
+        let device = self.device()?;
+        let iface = device.rx_queue.iface()?;
+        Some((iface.name().into(), device.flags()))
+    }
+
+    /// Receives a packet that is sent by the iface.
+    pub fn try_recv(&self, writer: &mut VmWriter) -> Result<usize> {
+        let Some(device) = self.device() else {
+            return_errno_with_message!(Errno::EBADFD, "the file is not attached to a device");
+        };
+
+        let has_pi = !device.flags().contains(TunFlags::NO_PI);
+        if has_pi && writer.avail() < size_of::<CTunPi>() {
+            return_errno_with_message!(Errno::EINVAL, "the buffer is too small");
+        }
+
+        let Some(packet) = self.packets.lock().pop_front() else {
+            return_errno_with_message!(Errno::EAGAIN, "no packets are available");
+        };
+        self.pollee.invalidate();
+
+        let write_packet = |writer: &mut VmWriter| -> Result<usize> {
+            let mut copied = 0;
+            if has_pi {
+                let pi = CTunPi {
+                    flags: 0,
+                    proto: packet_proto(&packet, device.is_tap).to_be(),
+                };
+                writer.write_val(&pi)?;
+                copied += size_of::<CTunPi>();
+            }
+
+            // TODO: Set `TUN_PKT_STRIP` in the packet information if the packet is truncated.
+            copied += writer.write_fallible(&mut packet.as_slice().into())?;
+
+            Ok(copied)
+        };
+
+        match write_packet(writer) {
+            Ok(copied) => Ok(copied),
+            Err(err) => {
+                // The packet cannot be copied while holding the lock, since copying to user space
+                // may fail or sleep. Put it back so that it is not lost if copying fails.
+                self.packets.lock().push_front(packet);
+                self.pollee.notify(IoEvents::IN);
+                Err(err)
+            }
+        }
+    }
+
+    /// Sends a packet to the iface.
+    pub fn send(&self, reader: &mut VmReader) -> Result<usize> {
+        let Some(device) = self.device() else {
+            return_errno_with_message!(Errno::EBADFD, "the file is not attached to a device");
+        };
+
+        let len = reader.remain();
+        if !device.flags().contains(TunFlags::NO_PI) {
+            if len < size_of::<CTunPi>() {
+                return_errno_with_message!(Errno::EINVAL, "the packet information is missing");
+            }
+            reader.read_val::<CTunPi>()?;
+        }
+
+        let max_len = if device.is_tap {
+            VIRT_MTU + ETHERNET_HEADER_LEN
+        } else {
+            VIRT_MTU
+        };
+        if reader.remain() > max_len {
+            return_errno_with_message!(Errno::EINVAL, "the packet is too large");
+        }
+        if device.is_tap && reader.remain() < ETHERNET_HEADER_LEN {
+            return_errno_with_message!(Errno::EINVAL, "the frame is too small");
+        }
+
+        let mut packet = vec![0u8; reader.remain()];
+        reader.read_fallible(&mut VmWriter::from(packet.as_mut_slice()))?;
+
+        device.rx_queue.push(packet);
+        device.rx_queue.request_poll();
+
+        Ok(len)
+    }
+
+    pub fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.device.lock().is_none() {
+            return IoEvents::ERR;
+        }
+
+        let mut events = IoEvents::OUT;
+        if !self.packets.lock().is_empty() {
+            events |= IoEvents::IN;
+        }
+        events
+    }
+
+    fn push(&self, packet: Vec<u8>) {
+        let mut packets = self.packets.lock();
+        if packets.len() >= Self::MAX_PACKETS {
+            return;
+        }
+        packets.push_back(packet);
+        drop(packets);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn detach(&self) {
+        *self.device.lock() = None;
+        self.packets.lock().clear();
+        self.pollee.notify(IoEvents::ERR);
+    }
+}
+
+const ETH_P_IP: u16 = 0x0800;
+const ETH_P_IPV6: u16 = 0x86DD;
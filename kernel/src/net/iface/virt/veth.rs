@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::{
+    Bridge,
+    device::{RxQueue, VirtEndpoint},
+    new_virt_iface, random_ether_addr,
+};
+use crate::{
+    net::{iface::Iface, net_ns::NetNamespace},
+    prelude::*,
+};
+
+/// One end of a virtual Ethernet (veth) pair.
+///
+/// Frames transmitted by one end are received by the other end. If an end is enslaved to a
+/// [`Bridge`], the frames that it receives are handed over to the bridge instead of its own
+/// iface.
+///
+/// The two ends of a pair may be in different network namespaces.
+pub(in crate::net) struct VethEnd {
+    rx_queue: RxQueue,
+    peer: Once<Weak<VethEnd>>,
+    master: SpinLock<Option<Weak<Bridge>>>,
+    net_ns: Weak<NetNamespace>,
+}
+
+impl VethEnd {
+    fn new(net_ns: Weak<NetNamespace>) -> Arc<Self> {
+        Arc::new(Self {
+            rx_queue: RxQueue::new(),
+            peer: Once::new(),
+            master: SpinLock::new(None),
+            net_ns,
+        })
+    }
+
+    /// Creates a veth pair and returns the ends with their ifaces.
+    ///
+    /// The ends are in `net_ns` and `peer_net_ns`, respectively.
+    pub(in crate::net) fn new_pair(
+        name: CString,
+        net_ns: Weak<NetNamespace>,
+        peer_name: CString,
+        peer_net_ns: Weak<NetNamespace>,
+    ) -> ((Arc<Self>, Arc<Iface>), (Arc<Self>, Arc<Iface>)) {
+        let end = Self::new(net_ns);
+        let peer = Self::new(peer_net_ns);
+        end.peer.call_once(|| Arc::downgrade(&peer));
+        peer.peer.call_once(|| Arc::downgrade(&end));
+
+        let iface = new_virt_iface(end.clone(), random_ether_addr(), name);
+        end.rx_queue.set_iface(&iface);
+        let peer_iface = new_virt_iface(peer.clone(), random_ether_addr(), peer_name);
+        peer.rx_queue.set_iface(&peer_iface);
+
+        ((end, iface), (peer, peer_iface))
+    }
+
+    /// Returns the index of the iface.
+    pub(in crate::net) fn index(&self) -> Option<u32> {
+        self.rx_queue.iface().map(|iface| iface.index())
+    }
+
+    /// Returns the network namespace that this end is in, if it is still alive.
+    pub(in crate::net) fn net_ns(&self) -> Option<Arc<NetNamespace>> {
+        self.net_ns.upgrade()
+    }
+
+    /// Returns the other end of the pair.
+    pub(in crate::net) fn peer(&self) -> Option<Arc<VethEnd>> {
+        self.peer.get().and_then(Weak::upgrade)
+    }
+
+    /// Returns the bridge that this end is enslaved to, if any.
+    pub(in crate::net) fn master(&self) -> Option<Arc<Bridge>> {
+        self.master.lock().as_ref().and_then(Weak::upgrade)
+    }
+
+    /// Enslaves this end to `master`, or releases it if `master` is `None`.
+    pub(in crate::net) fn set_master(self: &Arc<Self>, master: Option<&Arc<Bridge>>) {
+        let old_master = {
+            let mut master_guard = self.master.lock();
+            core::mem::replace(&mut *master_guard, master.map(Arc::downgrade))
+        };
+
+        if let Some(old_master) = old_master.as_ref().and_then(Weak::upgrade) {
+            old_master.remove_port(self);
+        }
+        if let Some(master) = master {
+            master.add_port(self.clone());
+        }
+    }
+
+    /// Releases this end from its bridge without notifying the bridge.
+    pub(super) fn clear_master(&self) {
+        *self.master.lock() = None;
+    }
+
+    /// Takes the next frame that is received from the peer.
+    pub(super) fn pop_frame(&self) -> Option<Vec<u8>> {
+        self.rx_queue.pop()
+    }
+
+    /// Delivers a frame that comes from the peer.
+    fn deliver(&self, frame: Vec<u8>) {
+        self.rx_queue.push(frame);
+
+        if let Some(master) = self.master() {
+            master.request_poll();
+        } else {
+            self.rx_queue.request_poll();
+        }
+    }
+}
+
+impl VirtEndpoint for VethEnd {
+    fn receive(&self) -> Option<Vec<u8>> {
+        // The frames of an enslaved end are consumed by the bridge.
+        if self.master().is_some() {
+            return None;
+        }
+
+        self.rx_queue.pop()
+    }
+
+    fn transmit(&self, frame: Vec<u8>) {
+        if let Some(peer) = self.peer() {
+            peer.deliver(frame);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub mod iface;
+pub mod net_ns;
+pub mod netfilter;
+pub mod route;
 pub mod socket;
 pub mod uts_ns;
 
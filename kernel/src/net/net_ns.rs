@@ -0,0 +1,604 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::{net::Ipv4Addr, ptr};
+
+use aster_bigtcp::{
+    iface::InterfaceType,
+    wire::{IpAddress, IpCidr, IpEndpoint},
+};
+use spin::Once;
+
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    net::{
+        iface::{self, Bridge, Iface, TunDevice, TunFlags, TunQueue, VethEnd, VirtLink},
+        netfilter::Netfilter,
+        route::{InsertPolicy, RT_TABLE_MAIN, RTPROT_BOOT, Route, RouteType, Router, RtScope},
+    },
+    prelude::*,
+    process::{
+        Gid, UserNamespace,
+        credentials::capabilities::CapSet,
+        posix_thread::{AsPosixThread, PosixThread},
+    },
+    security::lsm::hooks as lsm_hooks,
+};
+
+/// The default value of `net.ipv4.ping_group_range`, which allows no groups to create ICMP ping
+/// sockets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/af_inet.c>
+const DEFAULT_PING_GROUP_RANGE: (Gid, Gid) = (Gid::new(1), Gid::new(0));
+
+/// The network namespace.
+///
+/// Each network namespace owns a private set of network interfaces. Since every interface
+/// maintains its own port table and socket table, sockets created in different network
+/// namespaces can never see each other, even if they bind to the same address and port.
+pub struct NetNamespace {
+    /// The interfaces in this namespace.
+    ///
+    /// The first interface is always the loopback interface.
+    ifaces: RwLock<Vec<Arc<Iface>>>,
+    /// The virtual links in this namespace, keyed by the interface indexes.
+    virt_links: Mutex<BTreeMap<u32, VirtLink>>,
+    /// The router, which owns the routing tables and the routing rules of this namespace.
+    router: Arc<Router>,
+    /// The packet filter, which filters the packets that go through the interfaces.
+    netfilter: Arc<Netfilter>,
+    /// The range of groups that are allowed to create ICMP ping sockets.
+    ///
+    /// This is the `net.ipv4.ping_group_range` sysctl. The range is inclusive and empty if the
+    /// lower bound is greater than the upper bound.
+    ping_group_range: RwLock<(Gid, Gid)>,
+    owner: Arc<UserNamespace>,
+    stashed_dentry: StashedDentry,
+    this: Weak<NetNamespace>,
+}
+
+impl NetNamespace {
+    /// Returns a reference to the singleton initial network namespace.
+    ///
+    /// The initial network namespace contains the loopback interface and
+    /// all the physical network interfaces.
+    pub fn get_init_singleton() -> &'static Arc<NetNamespace> {
+        static INIT: Once<Arc<NetNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            let mut ifaces = Vec::with_capacity(2);
+
+            // Initialize loopback before virtio
+            // to ensure the loopback interface index is ahead of virtio.
+            ifaces.push(iface::new_loopback());
+
+            let iface_virtio = iface::new_virtio();
+            let virtio_index = iface_virtio.as_ref().map(|iface| iface.index());
+            ifaces.extend(iface_virtio);
+
+            let owner = UserNamespace::get_init_singleton().clone();
+            let net_ns = Self::new(ifaces, owner);
+
+            if let Some(virtio_index) = virtio_index {
+                let default_route = Route {
+                    dst: IpCidr::new(IpAddress::v4(0, 0, 0, 0), 0),
+                    tos: 0,
+                    type_: RouteType::Unicast,
+                    protocol: RTPROT_BOOT,
+                    scope: RtScope::UNIVERSE,
+                    gateway: Some(IpAddress::Ipv4(iface::VIRTIO_GATEWAY)),
+                    oif: Some(virtio_index),
+                    prefsrc: None,
+                    priority: 0,
+                };
+                net_ns
+                    .router
+                    .fib_mut()
+                    .table_mut(RT_TABLE_MAIN)
+                    .insert(default_route, InsertPolicy::Exclusive)
+                    .unwrap();
+            }
+
+            net_ns
+        })
+    }
+
+    fn new(ifaces: Vec<Arc<Iface>>, owner: Arc<UserNamespace>) -> Arc<Self> {
+        let router = Router::new();
+        let netfilter = Netfilter::new(router.clone());
+        for iface in ifaces.iter() {
+            router.add_iface(iface);
+            iface.set_packet_filter(netfilter.clone());
+        }
+
+        let stashed_dentry = StashedDentry::new();
+        Arc::new_cyclic(|this| Self {
+            ifaces: RwLock::new(ifaces),
+            virt_links: Mutex::new(BTreeMap::new()),
+            router,
+            netfilter,
+            ping_group_range: RwLock::new(DEFAULT_PING_GROUP_RANGE),
+            owner,
+            stashed_dentry,
+            this: this.clone(),
+        })
+    }
+
+    /// Creates a new network namespace.
+    ///
+    /// Unlike other namespaces, nothing is copied from `self`.
+    /// The new network namespace only contains a fresh loopback interface.
+    pub fn new_clone(
+        &self,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            owner.as_ref(),
+            posix_thread,
+            CapSet::SYS_ADMIN,
+        ))?;
+
+        let loopback = iface::new_loopback();
+        iface::spawn_background_poll_thread(loopback.clone());
+
+        Ok(Self::new(vec![loopback], owner))
+    }
+
+    /// Returns a strong reference to this namespace.
+    pub(in crate::net) fn this(&self) -> Arc<NetNamespace> {
+        self.this.upgrade().unwrap()
+    }
+
+    /// Checks whether the current thread has `CAP_NET_ADMIN` over this namespace.
+    pub(in crate::net) fn check_net_admin(&self) -> Result<()> {
+        let current = current_thread!();
+        let posix_thread = current.as_posix_thread().unwrap();
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            self.owner.as_ref(),
+            posix_thread,
+            CapSet::NET_ADMIN,
+        ))
+    }
+
+    /// Checks whether the current thread has `CAP_NET_RAW` over this namespace.
+    pub fn check_net_raw(&self) -> Result<()> {
+        let current = current_thread!();
+        let posix_thread = current.as_posix_thread().unwrap();
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            self.owner.as_ref(),
+            posix_thread,
+            CapSet::NET_RAW,
+        ))
+    }
+
+    /// Checks whether the current thread is allowed to create ICMP ping sockets.
+    ///
+    /// This is allowed if the effective group or any supplementary group of the thread is in
+    /// [`Self::ping_group_range`].
+    pub fn check_ping_permission(&self) -> Result<()> {
+        let (low, high) = self.ping_group_range();
+        let is_in_range = |gid: &Gid| low <= *gid && *gid <= high;
+
+        let current = current_thread!();
+        let credentials = current.as_posix_thread().unwrap().credentials();
+        if is_in_range(&credentials.egid()) || credentials.groups().iter().any(is_in_range) {
+            return Ok(());
+        }
+
+        return_errno_with_message!(
+            Errno::EACCES,
+            "the group is not allowed to create ping sockets"
+        );
+    }
+
+    /// Returns the range of groups that are allowed to create ICMP ping sockets.
+    pub fn ping_group_range(&self) -> (Gid, Gid) {
+        *self.ping_group_range.read()
+    }
+
+    /// Sets the range of groups that are allowed to create ICMP ping sockets.
+    ///
+    /// The current thread must have `CAP_NET_ADMIN` over this namespace. Like Linux, an inverted
+    /// range disables ping sockets for all groups.
+    pub fn set_ping_group_range(&self, low: Gid, high: Gid) -> Result<()> {
+        self.check_net_admin()?;
+
+        let range = if low <= high {
+            (low, high)
+        } else {
+            DEFAULT_PING_GROUP_RANGE
+        };
+        *self.ping_group_range.write() = range;
+
+        Ok(())
+    }
+
+    /// Returns the router of this namespace.
+    pub(in crate::net) fn router(&self) -> &Arc<Router> {
+        &self.router
+    }
+
+    /// Returns the packet filter of this namespace.
+    pub(in crate::net) fn netfilter(&self) -> &Arc<Netfilter> {
+        &self.netfilter
+    }
+
+    /// Returns whether IPv4 packets can be forwarded between interfaces.
+    ///
+    /// This is the `net.ipv4.ip_forward` sysctl.
+    pub fn ip_forward(&self) -> bool {
+        self.router.ip_forward()
+    }
+
+    /// Sets whether IPv4 packets can be forwarded between interfaces.
+    ///
+    /// The current thread must have `CAP_NET_ADMIN` over this namespace.
+    pub fn set_ip_forward(&self, ip_forward: bool) -> Result<()> {
+        self.check_net_admin()?;
+        self.router.set_ip_forward(ip_forward);
+        Ok(())
+    }
+
+    /// Returns all the interfaces in this namespace.
+    pub fn ifaces(&self) -> Vec<Arc<Iface>> {
+        self.ifaces.read().clone()
+    }
+
+    /// Returns the interface whose index is `index`, if any.
+    pub fn iface_by_index(&self, index: u32) -> Option<Arc<Iface>> {
+        self.ifaces
+            .read()
+            .iter()
+            .find(|iface| iface.index() == index)
+            .cloned()
+    }
+
+    /// Returns the default interface of this namespace.
+    ///
+    /// The default interface is the first non-loopback interface that has an address of the
+    /// same family as `ip_addr`. If there are no such interfaces, the loopback interface will be
+    /// returned.
+    ///
+    /// This is only a fallback used when the routing table does not have a route to `ip_addr`.
+    pub fn default_iface(&self, ip_addr: &IpAddress) -> Arc<Iface> {
+        let ifaces = self.ifaces.read();
+        ifaces
+            .iter()
+            .filter(|iface| iface.type_() != InterfaceType::LOOPBACK)
+            .find(|iface| match ip_addr {
+                IpAddress::Ipv4(_) => iface.ipv4_addr().is_some(),
+                IpAddress::Ipv6(_) => iface.ipv6_addr().is_some(),
+            })
+            .unwrap_or(&ifaces[0])
+            .clone()
+    }
+
+    /// Determines if a given IP endpoint's address is a known broadcast address.
+    ///
+    /// IPv6 has no broadcast; multicast (`ff00::/8`) handles fan-out instead and
+    /// is intentionally not covered by this method.
+    //
+    // FIXME: This information should be maintained in the routing table.
+    pub fn is_broadcast_endpoint(&self, endpoint: &IpEndpoint) -> bool {
+        let IpAddress::Ipv4(ipv4_addr) = &endpoint.addr else {
+            return false;
+        };
+
+        // 255.255.255.255 is always a broadcast address.
+        *ipv4_addr == Ipv4Addr::BROADCAST
+            || self
+                .ifaces
+                .read()
+                .iter()
+                .any(|iface| iface.broadcast_addr() == Some(*ipv4_addr))
+    }
+
+    /// Returns the virtual link whose interface index is `index`, if any.
+    pub(in crate::net) fn virt_link(&self, index: u32) -> Option<VirtLink> {
+        self.virt_links.lock().get(&index).cloned()
+    }
+
+    /// Creates a veth pair and returns the interface index of the first end.
+    ///
+    /// The first end is created in this namespace, and the peer is created in `peer_net_ns`. If
+    /// a name is not given, a name like `veth0` will be generated.
+    pub(in crate::net) fn add_veth_pair(
+        &self,
+        name: Option<CString>,
+        peer_name: Option<CString>,
+        peer_net_ns: &Arc<NetNamespace>,
+    ) -> Result<u32> {
+        if ptr::eq(self, peer_net_ns.as_ref()) {
+            let mut virt_links = self.virt_links.lock();
+
+            let name = self.check_or_generate_name(name, "veth", &[])?;
+            let peer_name = self.check_or_generate_name(peer_name, "veth", &[&name])?;
+
+            let ((end, iface), (peer, peer_iface)) =
+                VethEnd::new_pair(name, self.this.clone(), peer_name, self.this.clone());
+            let index = iface.index();
+            virt_links.insert(index, VirtLink::Veth(end));
+            virt_links.insert(peer_iface.index(), VirtLink::Veth(peer));
+            self.add_iface(iface);
+            self.add_iface(peer_iface);
+
+            return Ok(index);
+        }
+
+        // Lock the two namespaces in the order of their addresses to avoid deadlocks.
+        let (mut virt_links, mut peer_virt_links) =
+            if ptr::from_ref(self) < Arc::as_ptr(peer_net_ns) {
+                let virt_links = self.virt_links.lock();
+                (virt_links, peer_net_ns.virt_links.lock())
+            } else {
+                let peer_virt_links = peer_net_ns.virt_links.lock();
+                (self.virt_links.lock(), peer_virt_links)
+            };
+
+        let name = self.check_or_generate_name(name, "veth", &[])?;
+        let peer_name = peer_net_ns.check_or_generate_name(peer_name, "veth", &[])?;
+
+        let ((end, iface), (peer, peer_iface)) =
+            VethEnd::new_pair(name, self.this.clone(), peer_name, peer_net_ns.this.clone());
+        let index = iface.index();
+        virt_links.insert(index, VirtLink::Veth(end));
+        peer_virt_links.insert(peer_iface.index(), VirtLink::Veth(peer));
+        self.add_iface(iface);
+        peer_net_ns.add_iface(peer_iface);
+
+        Ok(index)
+    }
+
+    /// Creates a bridge and returns its interface index.
+    ///
+    /// If a name is not given, a name like `bridge0` will be generated.
+    pub(in crate::net) fn add_bridge(&self, name: Option<CString>) -> Result<u32> {
+        let mut virt_links = self.virt_links.lock();
+
+        let name = self.check_or_generate_name(name, "bridge", &[])?;
+
+        let (bridge, iface) = Bridge::new(name);
+        let index = iface.index();
+        virt_links.insert(index, VirtLink::Bridge(bridge));
+        self.add_iface(iface);
+
+        Ok(index)
+    }
+
+    /// Attaches `queue` to the TUN/TAP device named `name`, and returns the name of the device.
+    ///
+    /// If the device does not exist, it will be created. If a name is not given, a name like
+    /// `tun0` or `tap0` will be generated.
+    pub fn attach_tun(
+        &self,
+        queue: &Arc<TunQueue>,
+        name: Option<CString>,
+        flags: TunFlags,
+    ) -> Result<CString> {
+        let mut virt_links = self.virt_links.lock();
+
+        let existing_index = name.as_ref().and_then(|name| {
+            self.ifaces
+                .read()
+                .iter()
+                .find(|iface| iface.name() == name.as_c_str())
+                .map(|iface| iface.index())
+        });
+        if let Some(index) = existing_index {
+            let Some(VirtLink::Tun(tun)) = virt_links.get(&index) else {
+                return_errno_with_message!(Errno::EINVAL, "the link is not a TUN/TAP device");
+            };
+            // TODO: Allow the owner of a persistent device to attach without `CAP_NET_ADMIN`.
+            self.check_net_admin()?;
+            tun.attach(queue, flags)?;
+            return Ok(name.unwrap());
+        }
+
+        self.check_net_admin()?;
+
+        let prefix = if flags.contains(TunFlags::TAP) {
+            "tap"
+        } else {
+            "tun"
+        };
+        let name = self.check_or_generate_name(name, prefix, &[])?;
+
+        let (tun, iface) = TunDevice::new(name.clone(), flags);
+        tun.attach(queue, flags)?;
+        virt_links.insert(iface.index(), VirtLink::Tun(tun));
+        self.add_iface(iface);
+
+        Ok(name)
+    }
+
+    /// Detaches `queue` from its TUN/TAP device.
+    ///
+    /// The device will be removed if no more queues are attached to it.
+    pub fn detach_tun(&self, queue: &Arc<TunQueue>) {
+        let mut virt_links = self.virt_links.lock();
+
+        let Some(tun) = queue.device() else {
+            return;
+        };
+        if !tun.detach(queue) {
+            return;
+        }
+
+        if let Some(index) = tun.index()
+            && virt_links.contains_key(&index)
+        {
+            self.remove_link(&mut virt_links, index).unwrap();
+        }
+    }
+
+    /// Deletes a virtual link.
+    ///
+    /// Deleting one end of a veth pair also deletes the other end, even if the other end is in
+    /// another namespace.
+    pub(in crate::net) fn del_link(&self, index: u32) -> Result<()> {
+        let remote_peer = {
+            let mut virt_links = self.virt_links.lock();
+            self.remove_link(&mut virt_links, index)?
+        };
+
+        // The other namespace is locked only after releasing the lock of this namespace to avoid
+        // deadlocks. The peer may have been deleted in the meantime, so errors are ignored.
+        if let Some(peer) = remote_peer
+            && let Some(peer_net_ns) = peer.net_ns()
+            && let Some(peer_index) = peer.index()
+        {
+            let _ = peer_net_ns.del_link(peer_index);
+        }
+
+        Ok(())
+    }
+
+    /// Removes a virtual link.
+    ///
+    /// If the link is a veth end whose peer is in another namespace, the peer is returned so
+    /// that it can be removed from that namespace.
+    fn remove_link(
+        &self,
+        virt_links: &mut BTreeMap<u32, VirtLink>,
+        index: u32,
+    ) -> Result<Option<Arc<VethEnd>>> {
+        let Some(link) = virt_links.remove(&index) else {
+            if self
+                .ifaces
+                .read()
+                .iter()
+                .any(|iface| iface.index() == index)
+            {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "the link cannot be deleted");
+            }
+            return_errno_with_message!(Errno::ENODEV, "the link does not exist");
+        };
+
+        let mut removed_indexes = vec![index];
+        let mut remote_peer = None;
+        link.detach();
+        if let VirtLink::Veth(veth) = &link
+            && let Some(peer) = veth.peer()
+            && let Some(peer_index) = peer.index()
+        {
+            if let Some(peer_link) = virt_links.remove(&peer_index) {
+                peer_link.detach();
+                removed_indexes.push(peer_index);
+            } else {
+                remote_peer = Some(peer);
+            }
+        }
+
+        for removed_index in removed_indexes.iter() {
+            self.router.remove_iface(*removed_index);
+        }
+        self.ifaces.write().retain(|iface| {
+            if !removed_indexes.contains(&iface.index()) {
+                return true;
+            }
+            iface.sched_poll().stop();
+            false
+        });
+
+        Ok(remote_peer)
+    }
+
+    /// Enslaves the link to the bridge at `master_index`, or releases it from its bridge if
+    /// `master_index` is zero.
+    pub(in crate::net) fn set_link_master(&self, index: u32, master_index: u32) -> Result<()> {
+        let virt_links = self.virt_links.lock();
+
+        let Some(VirtLink::Veth(veth)) = virt_links.get(&index) else {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the link cannot be enslaved");
+        };
+
+        if master_index == 0 {
+            veth.set_master(None);
+            return Ok(());
+        }
+
+        let Some(VirtLink::Bridge(bridge)) = virt_links.get(&master_index) else {
+            return_errno_with_message!(Errno::EINVAL, "the master link is not a bridge");
+        };
+        veth.set_master(Some(bridge));
+
+        Ok(())
+    }
+
+    fn add_iface(&self, iface: Arc<Iface>) {
+        self.router.add_iface(&iface);
+        iface.set_packet_filter(self.netfilter.clone());
+        iface::spawn_background_poll_thread(iface.clone());
+        self.ifaces.write().push(iface);
+    }
+
+    /// Checks that `name` is not used, or generates an unused name from `prefix` if `name` is
+    /// `None`.
+    ///
+    /// Names in `reserved` are also treated as used.
+    fn check_or_generate_name(
+        &self,
+        name: Option<CString>,
+        prefix: &str,
+        reserved: &[&CString],
+    ) -> Result<CString> {
+        let ifaces = self.ifaces.read();
+        let is_used = |name: &CStr| {
+            ifaces.iter().any(|iface| iface.name() == name)
+                || reserved.iter().any(|reserved| reserved.as_c_str() == name)
+        };
+
+        if let Some(name) = name {
+            if is_used(name.as_c_str()) {
+                return_errno_with_message!(Errno::EEXIST, "the link name is already used");
+            }
+            return Ok(name);
+        }
+
+        (0..)
+            .map(|id| CString::new(format!("{}{}", prefix, id)).unwrap())
+            .find(|name| !is_used(name.as_c_str()))
+            .ok_or_else(|| Error::with_message(Errno::ENFILE, "no link names are available"))
+    }
+}
+
+impl Drop for NetNamespace {
+    fn drop(&mut self) {
+        // Stop the background polling threads so that they release the interfaces.
+        for iface in self.ifaces.get_mut().iter() {
+            iface.sched_poll().stop();
+        }
+        for link in self.virt_links.get_mut().values() {
+            link.detach();
+
+            // A veth end cannot outlive its peer, even if the peer is in another namespace.
+            if let VirtLink::Veth(veth) = link
+                && let Some(peer) = veth.peer()
+                && let Some(peer_net_ns) = peer.net_ns()
+                && let Some(peer_index) = peer.index()
+            {
+                let _ = peer_net_ns.del_link(peer_index);
+            }
+        }
+    }
+}
+
+impl NsCommonOps for NetNamespace {
+    const TYPE: NsType = NsType::Net;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "a network namespace does not have a parent namespace"
+        );
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
@@ -0,0 +1,398 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use aster_bigtcp::wire::{IpAddress, IpProtocol, TcpPacket};
+
+use super::packet::PacketInfo;
+use crate::prelude::*;
+
+/// The tuple that identifies the packets of a connection in one direction.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(super) struct Tuple {
+    pub(super) proto: u8,
+    pub(super) src: (IpAddress, u16),
+    pub(super) dst: (IpAddress, u16),
+}
+
+impl Tuple {
+    pub(super) fn of_packet(info: &PacketInfo, packet: &[u8]) -> Option<Self> {
+        let (src_port, dst_port) = info.ports(packet)?;
+        Some(Self {
+            proto: info.l4proto,
+            src: (info.src_addr, src_port),
+            dst: (info.dst_addr, dst_port),
+        })
+    }
+
+    /// Returns the tuple of the packets in the opposite direction.
+    pub(super) fn invert(&self) -> Self {
+        Self {
+            proto: self.proto,
+            src: self.dst,
+            dst: self.src,
+        }
+    }
+}
+
+/// The direction of a packet in its connection.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Direction {
+    /// The packet is sent by the endpoint that initiates the connection.
+    Original,
+    /// The packet is sent in reply.
+    Reply,
+}
+
+/// The kind of the address translation performed at a hook.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum ManipType {
+    /// The source is translated at the `PostRouting` and `LocalIn` hooks.
+    Src,
+    /// The destination is translated at the `PreRouting` and `LocalOut` hooks.
+    Dst,
+}
+
+bitflags! {
+    /// The connection tracking states that can be matched by rules.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_conntrack_common.h>.
+    pub struct CtState: u32 {
+        const INVALID     = 1 << 0;
+        const ESTABLISHED = 1 << 1;
+        const RELATED     = 1 << 2;
+        const NEW         = 1 << 3;
+        const UNTRACKED   = 1 << 6;
+    }
+}
+
+/// The result of tracking a packet.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct CtInfo {
+    pub(super) id: u64,
+    pub(super) direction: Direction,
+    pub(super) state: CtState,
+}
+
+/// A tracked connection.
+#[derive(Debug)]
+struct Conn {
+    /// The tuple of the first packet, before any translations.
+    original: Tuple,
+    /// The tuple of the packets in the original direction after the destination translation.
+    dnat: Tuple,
+    /// The tuple of the packets in the original direction after both translations.
+    snat: Tuple,
+    /// The translations that have been decided.
+    decided: [bool; 2],
+    seen_reply: bool,
+    is_closing: bool,
+    expires: Duration,
+}
+
+impl Conn {
+    /// Returns the tuples that the packets of the connection may have at the hooks.
+    fn tuples(&self) -> [(Tuple, Direction); 6] {
+        [
+            (self.original, Direction::Original),
+            (self.dnat, Direction::Original),
+            (self.snat, Direction::Original),
+            (self.snat.invert(), Direction::Reply),
+            (self.dnat.invert(), Direction::Reply),
+            (self.original.invert(), Direction::Reply),
+        ]
+    }
+
+    fn timeout(&self) -> Duration {
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nf_conntrack_proto_tcp.c>.
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nf_conntrack_proto_udp.c>.
+        let secs = match (IpProtocol::from(self.original.proto), self.seen_reply) {
+            (IpProtocol::Tcp, _) if self.is_closing => 10,
+            (IpProtocol::Tcp, false) => 120,
+            (IpProtocol::Tcp, true) => 5 * 24 * 60 * 60,
+            (IpProtocol::Udp, false) => 30,
+            (IpProtocol::Udp, true) => 120,
+            (IpProtocol::Icmp | IpProtocol::Icmpv6, _) => 30,
+            (_, _) => 600,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+/// The connection tracking table.
+pub(super) struct ConnTrack {
+    conns: BTreeMap<u64, Conn>,
+    /// The connections keyed by all the tuples that their packets may have.
+    tuples: BTreeMap<Tuple, (u64, Direction)>,
+    next_id: u64,
+}
+
+/// The maximum number of tracked connections.
+///
+/// This is similar to `net.netfilter.nf_conntrack_max` in Linux.
+const MAX_CONNS: usize = 65536;
+
+impl ConnTrack {
+    pub(super) const fn new() -> Self {
+        Self {
+            conns: BTreeMap::new(),
+            tuples: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Tracks a packet, creating a new connection if the packet does not belong to any.
+    ///
+    /// This method returns `None` if the packet cannot be tracked (e.g., because it is
+    /// malformed or the table is full).
+    pub(super) fn track(
+        &mut self,
+        info: &PacketInfo,
+        packet: &[u8],
+        now: Duration,
+    ) -> Option<CtInfo> {
+        if info.is_icmp_error(packet) {
+            return self.track_icmp_error(info, packet, now);
+        }
+
+        let tuple = Tuple::of_packet(info, packet)?;
+        let tcp_flags = (IpProtocol::from(info.l4proto) == IpProtocol::Tcp)
+            .then(|| TcpPacket::new_checked(info.transport(packet)).ok())
+            .flatten()
+            .map(|tcp_packet| (tcp_packet.fin(), tcp_packet.rst()));
+
+        let (id, direction) = match self.lookup(&tuple, now) {
+            Some(found) => found,
+            None => (self.insert(tuple, now)?, Direction::Original),
+        };
+
+        let conn = self.conns.get_mut(&id).unwrap();
+        if direction == Direction::Reply {
+            conn.seen_reply = true;
+        }
+        if tcp_flags.is_some_and(|(fin, rst)| fin || rst) {
+            conn.is_closing = true;
+        }
+        conn.expires = now + conn.timeout();
+
+        let state = if conn.seen_reply {
+            CtState::ESTABLISHED
+        } else {
+            CtState::NEW
+        };
+        Some(CtInfo {
+            id,
+            direction,
+            state,
+        })
+    }
+
+    /// Tracks an ICMP error message, which is related to the connection of the packet embedded
+    /// in it.
+    fn track_icmp_error(
+        &mut self,
+        info: &PacketInfo,
+        packet: &[u8],
+        now: Duration,
+    ) -> Option<CtInfo> {
+        // The embedded packet may be truncated, so only the headers are parsed.
+        let embedded = info.icmp_error_payload(packet)?;
+        let embedded_info = PacketInfo::parse_truncated(embedded)?;
+        let tuple = Tuple::of_packet(&embedded_info, embedded)?;
+
+        // The embedded packet is sent in the opposite direction of the ICMP error message.
+        let (id, direction) = self.lookup(&tuple, now)?;
+        let direction = match direction {
+            Direction::Original => Direction::Reply,
+            Direction::Reply => Direction::Original,
+        };
+
+        // TODO: Translate the addresses of the embedded packet if the connection is translated.
+        Some(CtInfo {
+            id,
+            direction,
+            state: CtState::RELATED,
+        })
+    }
+
+    fn lookup(&mut self, tuple: &Tuple, now: Duration) -> Option<(u64, Direction)> {
+        let (id, direction) = *self.tuples.get(tuple)?;
+        if self.conns[&id].expires > now {
+            return Some((id, direction));
+        }
+
+        self.remove(id);
+        None
+    }
+
+    fn insert(&mut self, tuple: Tuple, now: Duration) -> Option<u64> {
+        if self.conns.len() >= MAX_CONNS {
+            self.remove_expired(now);
+            if self.conns.len() >= MAX_CONNS {
+                return None;
+            }
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let conn = Conn {
+            original: tuple,
+            dnat: tuple,
+            snat: tuple,
+            decided: [false; 2],
+            seen_reply: false,
+            is_closing: false,
+            expires: now,
+        };
+        self.add_tuples(id, &conn);
+        self.conns.insert(id, conn);
+
+        Some(id)
+    }
+
+    fn remove(&mut self, id: u64) {
+        let Some(conn) = self.conns.remove(&id) else {
+            return;
+        };
+        self.remove_tuples(id, &conn);
+    }
+
+    fn remove_expired(&mut self, now: Duration) {
+        let expired: Vec<u64> = self
+            .conns
+            .iter()
+            .filter(|(_, conn)| conn.expires <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            self.remove(id);
+        }
+    }
+
+    fn add_tuples(&mut self, id: u64, conn: &Conn) {
+        for (tuple, direction) in conn.tuples() {
+            self.tuples.entry(tuple).or_insert((id, direction));
+        }
+    }
+
+    fn remove_tuples(&mut self, id: u64, conn: &Conn) {
+        for (tuple, _) in conn.tuples() {
+            if self
+                .tuples
+                .get(&tuple)
+                .is_some_and(|(owner, _)| *owner == id)
+            {
+                self.tuples.remove(&tuple);
+            }
+        }
+    }
+
+    /// Returns whether the translation of the kind has yet to be decided for the connection.
+    pub(super) fn is_undecided(&self, ct: &CtInfo, manip: ManipType) -> bool {
+        ct.state == CtState::NEW
+            && ct.direction == Direction::Original
+            && !self.conns[&ct.id].decided[manip as usize]
+    }
+
+    /// Decides the translation of the kind for the connection.
+    ///
+    /// If `target` is `None`, no translation will be performed. Otherwise, the address and the
+    /// port (if any) will be translated to the target. If the port is not specified and the
+    /// translated tuple clashes with another connection, another port will be selected.
+    ///
+    /// This method fails if no tuples are available.
+    pub(super) fn decide(
+        &mut self,
+        ct: &CtInfo,
+        manip: ManipType,
+        target: Option<(IpAddress, Option<u16>)>,
+    ) -> bool {
+        let conn = self.conns.get(&ct.id).unwrap();
+        let mut dnat = conn.dnat;
+        let mut snat = conn.snat;
+
+        if let Some((addr, port)) = target {
+            let endpoint: fn(&mut Tuple) -> &mut (IpAddress, u16) = match manip {
+                ManipType::Dst => |tuple| &mut tuple.dst,
+                ManipType::Src => |tuple| &mut tuple.src,
+            };
+            let tuple = match manip {
+                ManipType::Dst => &mut dnat,
+                ManipType::Src => &mut snat,
+            };
+
+            endpoint(tuple).0 = addr;
+            if let Some(port) = port {
+                endpoint(tuple).1 = port;
+            } else if manip == ManipType::Src && !self.select_port(ct.id, tuple, endpoint) {
+                // Like Linux, the destination port is never changed unless it is specified.
+                return false;
+            }
+            if manip == ManipType::Dst {
+                snat.dst = dnat.dst;
+            }
+        }
+
+        let mut conn = self.conns.remove(&ct.id).unwrap();
+        self.remove_tuples(ct.id, &conn);
+        conn.dnat = dnat;
+        conn.snat = snat;
+        conn.decided[manip as usize] = true;
+        self.add_tuples(ct.id, &conn);
+        self.conns.insert(ct.id, conn);
+
+        true
+    }
+
+    /// Selects a port for the translated tuple so that its reply tuple does not clash with other
+    /// connections.
+    fn select_port(
+        &self,
+        id: u64,
+        tuple: &mut Tuple,
+        endpoint: fn(&mut Tuple) -> &mut (IpAddress, u16),
+    ) -> bool {
+        let is_free = |tuple: &Tuple| {
+            self.tuples
+                .get(&tuple.invert())
+                .is_none_or(|(owner, _)| *owner == id)
+        };
+        if is_free(tuple) {
+            return true;
+        }
+
+        // Protocols without ports cannot be distinguished by selecting another port.
+        if !matches!(
+            IpProtocol::from(tuple.proto),
+            IpProtocol::Tcp | IpProtocol::Udp | IpProtocol::Icmp
+        ) {
+            return false;
+        }
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nf_nat_proto.c>.
+        const MIN_PORT: u16 = 1024;
+        let orig_port = endpoint(tuple).1;
+        for offset in 0..=(u16::MAX - MIN_PORT) {
+            let port = MIN_PORT + (orig_port.wrapping_add(offset) % (u16::MAX - MIN_PORT + 1));
+            endpoint(tuple).1 = port;
+            if is_free(tuple) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Returns the address and the port that the packet should be translated to at a hook of
+    /// the kind.
+    pub(super) fn manip_target(&self, ct: &CtInfo, manip: ManipType) -> (IpAddress, u16) {
+        let conn = &self.conns[&ct.id];
+        match (ct.direction, manip) {
+            (Direction::Original, ManipType::Dst) => conn.dnat.dst,
+            (Direction::Original, ManipType::Src) => conn.snat.src,
+            (Direction::Reply, ManipType::Dst) => conn.original.src,
+            (Direction::Reply, ManipType::Src) => conn.original.dst,
+        }
+    }
+}
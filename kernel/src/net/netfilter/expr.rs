@@ -0,0 +1,463 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    ops::ControlFlow,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use aster_bigtcp::wire::IpAddress;
+
+use super::{
+    conntrack::{CtInfo, CtState, Direction},
+    packet::PacketInfo,
+    ruleset::NfProto,
+};
+use crate::prelude::*;
+
+/// The length of interface names, including the terminating null byte.
+pub const IFNAMSIZ: usize = 16;
+
+/// A data register, which stores the data loaded from packets or immediate values.
+///
+/// Registers 1 to 4 are 16 bytes long, while registers 8 to 23 are 4 bytes long and alias the
+/// former ones. Data that is longer than a register spills over into the following registers.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L19>.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Register {
+    index: u32,
+    len: usize,
+}
+
+/// The total length of the data registers in bytes.
+const REGISTERS_LEN: usize = 64;
+
+impl Register {
+    /// Creates a register that holds `len` bytes of data.
+    pub fn new(index: u32, len: usize) -> Result<Self> {
+        let offset = match index {
+            1..=4 => (index as usize - 1) * 16,
+            8..=23 => (index as usize - 8) * 4,
+            _ => return_errno_with_message!(Errno::EINVAL, "the register is invalid"),
+        };
+        if len == 0 || offset + len > REGISTERS_LEN {
+            return_errno_with_message!(Errno::ERANGE, "the data does not fit in the registers");
+        }
+
+        Ok(Self { index, len })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn data_len(&self) -> usize {
+        self.len
+    }
+
+    fn range(&self) -> core::ops::Range<usize> {
+        let offset = match self.index {
+            1..=4 => (self.index as usize - 1) * 16,
+            _ => (self.index as usize - 8) * 4,
+        };
+        offset..offset + self.len
+    }
+}
+
+/// A verdict, which decides what happens to a packet.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L50>.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    /// Drops the packet.
+    Drop,
+    /// Accepts the packet, which stops the evaluation of the base chain.
+    Accept,
+    /// Continues with the next expression.
+    Continue,
+    /// Stops the evaluation of the rule, and continues with the next rule.
+    Break,
+    /// Continues with the named chain, and returns to the next rule afterwards.
+    Jump(String),
+    /// Continues with the named chain without returning.
+    Goto(String),
+    /// Returns to the calling chain.
+    Return,
+}
+
+impl Verdict {
+    pub const NF_DROP: i32 = 0;
+    pub const NF_ACCEPT: i32 = 1;
+    pub const NFT_CONTINUE: i32 = -1;
+    pub const NFT_BREAK: i32 = -2;
+    pub const NFT_JUMP: i32 = -3;
+    pub const NFT_GOTO: i32 = -4;
+    pub const NFT_RETURN: i32 = -5;
+
+    /// Creates a verdict from its code and, for jumps and gotos, the name of the target chain.
+    pub fn from_code(code: i32, chain: Option<String>) -> Result<Self> {
+        let verdict = match (code, chain) {
+            (Self::NF_DROP, _) => Self::Drop,
+            (Self::NF_ACCEPT, _) => Self::Accept,
+            (Self::NFT_CONTINUE, _) => Self::Continue,
+            (Self::NFT_BREAK, _) => Self::Break,
+            (Self::NFT_RETURN, _) => Self::Return,
+            (Self::NFT_JUMP, Some(chain)) => Self::Jump(chain),
+            (Self::NFT_GOTO, Some(chain)) => Self::Goto(chain),
+            (Self::NFT_JUMP | Self::NFT_GOTO, None) => {
+                return_errno_with_message!(Errno::EINVAL, "the target chain is not specified")
+            }
+            _ => return_errno_with_message!(Errno::EINVAL, "the verdict is invalid"),
+        };
+        Ok(verdict)
+    }
+
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Drop => Self::NF_DROP,
+            Self::Accept => Self::NF_ACCEPT,
+            Self::Continue => Self::NFT_CONTINUE,
+            Self::Break => Self::NFT_BREAK,
+            Self::Jump(_) => Self::NFT_JUMP,
+            Self::Goto(_) => Self::NFT_GOTO,
+            Self::Return => Self::NFT_RETURN,
+        }
+    }
+
+    pub fn chain(&self) -> Option<&str> {
+        match self {
+            Self::Jump(chain) | Self::Goto(chain) => Some(chain),
+            _ => None,
+        }
+    }
+}
+
+/// The header that the offset of a payload expression is relative to.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum PayloadBase {
+    LinkLayer = 0,
+    Network = 1,
+    Transport = 2,
+}
+
+/// The packet metadata that can be loaded by a meta expression.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L906>.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum MetaKey {
+    Len = 0,
+    Protocol = 1,
+    Iif = 4,
+    Oif = 5,
+    IifName = 6,
+    OifName = 7,
+    NfProto = 15,
+    L4Proto = 16,
+}
+
+impl MetaKey {
+    /// Returns the length of the loaded data.
+    pub fn data_len(&self) -> usize {
+        match self {
+            Self::Len | Self::Iif | Self::Oif => 4,
+            Self::Protocol => 2,
+            Self::IifName | Self::OifName => IFNAMSIZ,
+            Self::NfProto | Self::L4Proto => 1,
+        }
+    }
+}
+
+/// The comparison operator of a cmp expression.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum CmpOp {
+    Eq = 0,
+    Neq = 1,
+    Lt = 2,
+    Lte = 3,
+    Gt = 4,
+    Gte = 5,
+}
+
+/// The connection tracking data that can be loaded by a ct expression.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum CtKey {
+    State = 0,
+    Direction = 1,
+}
+
+impl CtKey {
+    /// Returns the length of the loaded data.
+    pub fn data_len(&self) -> usize {
+        match self {
+            Self::State => 4,
+            Self::Direction => 1,
+        }
+    }
+}
+
+/// The kind of a nat expression.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum NatType {
+    Snat = 0,
+    Dnat = 1,
+}
+
+/// A counter of packets and bytes.
+#[derive(Debug, Default)]
+pub struct Counter {
+    packets: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Counter {
+    pub fn new(packets: u64, bytes: u64) -> Self {
+        Self {
+            packets: AtomicU64::new(packets),
+            bytes: AtomicU64::new(bytes),
+        }
+    }
+
+    pub fn packets(&self) -> u64 {
+        self.packets.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+}
+
+impl Clone for Counter {
+    fn clone(&self) -> Self {
+        Self::new(self.packets(), self.bytes())
+    }
+}
+
+/// An expression, which is the building block of rules.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nf_tables_core.c>.
+#[derive(Clone, Debug)]
+pub enum Expr {
+    /// Loads data from the packet into a register.
+    Payload {
+        dreg: Register,
+        base: PayloadBase,
+        offset: u32,
+    },
+    /// Loads the packet metadata into a register.
+    Meta { dreg: Register, key: MetaKey },
+    /// Compares the data in a register, and breaks if the comparison fails.
+    Cmp {
+        sreg: Register,
+        op: CmpOp,
+        data: Vec<u8>,
+    },
+    /// Computes `(sreg & mask) ^ xor` and stores the result in a register.
+    Bitwise {
+        sreg: Register,
+        dreg: Register,
+        mask: Vec<u8>,
+        xor: Vec<u8>,
+    },
+    /// Loads an immediate value into a register.
+    Immediate { dreg: Register, data: Vec<u8> },
+    /// Issues a verdict.
+    Verdict(Verdict),
+    /// Loads the connection tracking data into a register.
+    Ct { dreg: Register, key: CtKey },
+    /// Translates the source or destination address, and accepts the packet.
+    Nat {
+        type_: NatType,
+        family: NfProto,
+        addr: Option<Register>,
+        port: Option<Register>,
+        flags: u32,
+    },
+    /// Translates the source address to the address of the output interface, and accepts the
+    /// packet.
+    Masq { port: Option<Register>, flags: u32 },
+    /// Counts the packets and the bytes.
+    Counter(Counter),
+}
+
+/// The translation requested by a nat expression or a masq expression.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum NatRequest {
+    /// Translates the source address and, if specified, the source port.
+    Snat(IpAddress, Option<u16>),
+    /// Translates the destination address and, if specified, the destination port.
+    Dnat(IpAddress, Option<u16>),
+    /// Translates the source address to the address of the output interface.
+    Masquerade(Option<u16>),
+}
+
+/// An interface that a packet goes through.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct IfaceInfo {
+    pub(super) index: u32,
+    pub(super) name: [u8; IFNAMSIZ],
+}
+
+/// The context in which expressions are evaluated.
+pub(super) struct EvalContext<'a> {
+    pub(super) packet: &'a [u8],
+    pub(super) info: &'a PacketInfo,
+    pub(super) in_iface: Option<IfaceInfo>,
+    pub(super) out_iface: Option<IfaceInfo>,
+    pub(super) ct: Option<CtInfo>,
+    /// The translation requested by the rules, if any.
+    pub(super) nat: Option<NatRequest>,
+}
+
+/// The data registers.
+pub(super) struct Registers([u8; REGISTERS_LEN]);
+
+impl Registers {
+    pub(super) fn new() -> Self {
+        Self([0; REGISTERS_LEN])
+    }
+
+    fn get(&self, reg: &Register) -> &[u8] {
+        &self.0[reg.range()]
+    }
+
+    fn get_mut(&mut self, reg: &Register) -> &mut [u8] {
+        &mut self.0[reg.range()]
+    }
+}
+
+impl Expr {
+    /// Evaluates the expression.
+    ///
+    /// This method breaks with the verdict if the evaluation of the rule should stop.
+    pub(super) fn eval(&self, regs: &mut Registers, cx: &mut EvalContext) -> ControlFlow<Verdict> {
+        match self {
+            Expr::Payload { dreg, base, offset } => {
+                let header = match base {
+                    PayloadBase::Network => &cx.packet[..cx.info.len],
+                    PayloadBase::Transport => cx.info.transport(cx.packet),
+                    PayloadBase::LinkLayer => return ControlFlow::Break(Verdict::Break),
+                };
+                let start = *offset as usize;
+                let Some(data) = header.get(start..start + dreg.data_len()) else {
+                    return ControlFlow::Break(Verdict::Break);
+                };
+                regs.get_mut(dreg).copy_from_slice(data);
+            }
+            Expr::Meta { dreg, key } => {
+                let dst = regs.get_mut(dreg);
+                match key {
+                    MetaKey::Len => dst.copy_from_slice(&(cx.info.len as u32).to_ne_bytes()),
+                    MetaKey::Protocol => {
+                        let ethertype: u16 = match cx.info.family {
+                            NfProto::Ipv6 => 0x86dd,
+                            _ => 0x0800,
+                        };
+                        dst.copy_from_slice(&ethertype.to_be_bytes());
+                    }
+                    MetaKey::Iif | MetaKey::IifName | MetaKey::Oif | MetaKey::OifName => {
+                        let iface = match key {
+                            MetaKey::Iif | MetaKey::IifName => cx.in_iface,
+                            _ => cx.out_iface,
+                        };
+                        let Some(iface) = iface else {
+                            return ControlFlow::Break(Verdict::Break);
+                        };
+                        match key {
+                            MetaKey::Iif | MetaKey::Oif => {
+                                dst.copy_from_slice(&iface.index.to_ne_bytes())
+                            }
+                            _ => dst.copy_from_slice(&iface.name),
+                        }
+                    }
+                    MetaKey::NfProto => dst[0] = cx.info.family as u8,
+                    MetaKey::L4Proto => dst[0] = cx.info.l4proto,
+                }
+            }
+            Expr::Cmp { sreg, op, data } => {
+                let ordering = regs.get(sreg).cmp(data.as_slice());
+                let matches = match op {
+                    CmpOp::Eq => ordering.is_eq(),
+                    CmpOp::Neq => ordering.is_ne(),
+                    CmpOp::Lt => ordering.is_lt(),
+                    CmpOp::Lte => ordering.is_le(),
+                    CmpOp::Gt => ordering.is_gt(),
+                    CmpOp::Gte => ordering.is_ge(),
+                };
+                if !matches {
+                    return ControlFlow::Break(Verdict::Break);
+                }
+            }
+            Expr::Bitwise {
+                sreg,
+                dreg,
+                mask,
+                xor,
+            } => {
+                let mut result = regs.get(sreg).to_vec();
+                for ((byte, mask), xor) in result.iter_mut().zip(mask).zip(xor) {
+                    *byte = (*byte & mask) ^ xor;
+                }
+                regs.get_mut(dreg).copy_from_slice(&result);
+            }
+            Expr::Immediate { dreg, data } => regs.get_mut(dreg).copy_from_slice(data),
+            Expr::Verdict(verdict) => return ControlFlow::Break(verdict.clone()),
+            Expr::Ct { dreg, key } => {
+                let dst = regs.get_mut(dreg);
+                match (key, cx.ct.as_ref()) {
+                    (CtKey::State, Some(ct)) => dst.copy_from_slice(&ct.state.bits().to_ne_bytes()),
+                    (CtKey::State, None) => {
+                        dst.copy_from_slice(&CtState::INVALID.bits().to_ne_bytes())
+                    }
+                    (CtKey::Direction, Some(ct)) => {
+                        dst[0] = (ct.direction == Direction::Reply) as u8
+                    }
+                    (CtKey::Direction, None) => return ControlFlow::Break(Verdict::Break),
+                }
+            }
+            Expr::Nat {
+                type_,
+                family,
+                addr,
+                port,
+                flags: _,
+            } => {
+                let addr = match (family, addr) {
+                    (NfProto::Ipv4, Some(addr)) => {
+                        let bytes: [u8; 4] = regs.get(addr)[..4].try_into().unwrap();
+                        IpAddress::from(core::net::Ipv4Addr::from(bytes))
+                    }
+                    _ => return ControlFlow::Break(Verdict::Drop),
+                };
+                let port = port.map(|port| load_port(regs, &port));
+                cx.nat = Some(match type_ {
+                    NatType::Snat => NatRequest::Snat(addr, port),
+                    NatType::Dnat => NatRequest::Dnat(addr, port),
+                });
+                return ControlFlow::Break(Verdict::Accept);
+            }
+            Expr::Masq { port, flags: _ } => {
+                let port = port.map(|port| load_port(regs, &port));
+                cx.nat = Some(NatRequest::Masquerade(port));
+                return ControlFlow::Break(Verdict::Accept);
+            }
+            Expr::Counter(counter) => {
+                counter.packets.fetch_add(1, Ordering::Relaxed);
+                counter
+                    .bytes
+                    .fetch_add(cx.info.len as u64, Ordering::Relaxed);
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+fn load_port(regs: &Registers, reg: &Register) -> u16 {
+    let bytes = regs.get(reg);
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Packet filtering and address translation.
+//!
+//! This module implements a subset of nf_tables, which filters the packets that go through the
+//! interfaces of network namespaces according to the rules configured via `NETLINK_NETFILTER`
+//! sockets. The connections are tracked so that rules can match connection states and
+//! addresses can be translated (NAT) for all packets of a connection.
+
+mod conntrack;
+mod expr;
+mod packet;
+mod ruleset;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_bigtcp::{
+    iface::{FilterHook, PacketFilter},
+    wire::{IpAddress, Ipv4Address},
+};
+use aster_softirq::BottomHalfDisabled;
+use ostd::{
+    sync::{RwLockReadGuard, RwLockUpgradeableGuard},
+    timer::Jiffies,
+};
+
+use self::{
+    conntrack::{ConnTrack, CtInfo, ManipType},
+    expr::{EvalContext, IFNAMSIZ, IfaceInfo, NatRequest},
+    packet::PacketInfo,
+};
+pub use self::{
+    expr::{CmpOp, Counter, CtKey, Expr, MetaKey, NatType, PayloadBase, Register, Verdict},
+    ruleset::{BaseChain, Chain, ChainType, NfProto, Rule, Ruleset, Table},
+};
+use crate::{net::route::Router, prelude::*};
+
+/// The packet filter of a network namespace.
+pub struct Netfilter {
+    router: Arc<Router>,
+    /// The active ruleset.
+    ///
+    /// The packets are filtered when the interfaces are polled. Therefore, the lock must disable
+    /// bottom halves.
+    ruleset: RwLock<Ruleset, BottomHalfDisabled>,
+    conntrack: SpinLock<ConnTrack, BottomHalfDisabled>,
+    /// Whether the active ruleset has any base chains.
+    is_enabled: AtomicBool,
+}
+
+impl Netfilter {
+    pub(in crate::net) fn new(router: Arc<Router>) -> Arc<Self> {
+        Arc::new(Self {
+            router,
+            ruleset: RwLock::new(Ruleset::new()),
+            conntrack: SpinLock::new(ConnTrack::new()),
+            is_enabled: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the active ruleset.
+    pub(in crate::net) fn ruleset(&self) -> RwLockReadGuard<'_, Ruleset, BottomHalfDisabled> {
+        self.ruleset.read()
+    }
+
+    /// Locks the active ruleset so that changes can be committed later.
+    ///
+    /// Only one batch of changes can be prepared at a time.
+    pub(in crate::net) fn lock_ruleset(
+        &self,
+    ) -> RwLockUpgradeableGuard<'_, Ruleset, BottomHalfDisabled> {
+        self.ruleset.upread()
+    }
+
+    /// Commits the changes by replacing the active ruleset.
+    pub(in crate::net) fn commit(
+        &self,
+        guard: RwLockUpgradeableGuard<'_, Ruleset, BottomHalfDisabled>,
+        mut ruleset: Ruleset,
+    ) {
+        ruleset.bump_generation();
+        let is_enabled = ruleset.has_base_chains();
+
+        *guard.upgrade() = ruleset;
+        self.is_enabled.store(is_enabled, Ordering::Relaxed);
+    }
+
+    /// Evaluates the NAT chains at the hook, and translates the packet according to the
+    /// translation of its connection.
+    ///
+    /// This method returns whether the packet is accepted.
+    fn translate(
+        &self,
+        nat_chains: &[(&Table, &Chain)],
+        manip: ManipType,
+        state: &HookState,
+        info: &PacketInfo,
+        packet: &mut [u8],
+    ) -> bool {
+        let Some(ct) = state.ct else {
+            return true;
+        };
+
+        let mut conntrack = self.conntrack.lock();
+
+        // Only the first packet of a connection traverses the NAT chains. The translation is
+        // then applied to all packets of the connection.
+        if conntrack.is_undecided(&ct, manip) {
+            let mut cx = state.context(packet, info);
+            for (table, chain) in nat_chains.iter() {
+                if !table.eval(chain, &mut cx) {
+                    return false;
+                }
+                if cx.nat.is_some() {
+                    break;
+                }
+            }
+
+            let target = match (manip, cx.nat) {
+                (ManipType::Dst, Some(NatRequest::Dnat(addr, port)))
+                | (ManipType::Src, Some(NatRequest::Snat(addr, port))) => Some((addr, port)),
+                (ManipType::Src, Some(NatRequest::Masquerade(port))) => {
+                    // Like Linux, the packet is dropped if the output interface has no address.
+                    let Some(addr) = state.masq_addr else {
+                        return false;
+                    };
+                    Some((IpAddress::Ipv4(addr), port))
+                }
+                _ => None,
+            };
+            if !conntrack.decide(&ct, manip, target) {
+                return false;
+            }
+        }
+
+        let Some((src_port, dst_port)) = info.ports(packet) else {
+            return true;
+        };
+        let (addr, port) = conntrack.manip_target(&ct, manip);
+        drop(conntrack);
+
+        match manip {
+            ManipType::Src if (info.src_addr, src_port) != (addr, port) => {
+                packet::set_source(packet, info, addr, port)
+            }
+            ManipType::Dst if (info.dst_addr, dst_port) != (addr, port) => {
+                packet::set_destination(packet, info, addr, port)
+            }
+            _ => (),
+        }
+
+        true
+    }
+}
+
+/// The state of a packet at a hook, which does not change while the chains are evaluated.
+struct HookState {
+    in_iface: Option<IfaceInfo>,
+    out_iface: Option<IfaceInfo>,
+    /// The address of the output interface, which is used by masquerading.
+    masq_addr: Option<Ipv4Address>,
+    ct: Option<CtInfo>,
+}
+
+impl HookState {
+    fn context<'a>(&self, packet: &'a [u8], info: &'a PacketInfo) -> EvalContext<'a> {
+        EvalContext {
+            packet,
+            info,
+            in_iface: self.in_iface,
+            out_iface: self.out_iface,
+            ct: self.ct,
+            nat: None,
+        }
+    }
+}
+
+impl PacketFilter for Netfilter {
+    fn is_enabled(&self) -> bool {
+        self.is_enabled.load(Ordering::Relaxed)
+    }
+
+    fn filter(
+        &self,
+        hook: FilterHook,
+        in_iface: Option<u32>,
+        out_iface: Option<u32>,
+        packet: &mut [u8],
+    ) -> bool {
+        let Some(mut info) = PacketInfo::parse(packet) else {
+            return true;
+        };
+
+        let ruleset = self.ruleset.read();
+        let base_chains = ruleset.base_chains(info.family, hook);
+
+        let state = {
+            let fib = self.router.fib();
+            let iface_info = |index: Option<u32>| {
+                let index = index?;
+                let mut name = [0; IFNAMSIZ];
+                if let Some(iface_name) = fib.iface_name(index) {
+                    let bytes = iface_name.to_bytes();
+                    let len = bytes.len().min(IFNAMSIZ - 1);
+                    name[..len].copy_from_slice(&bytes[..len]);
+                }
+                Some(IfaceInfo { index, name })
+            };
+
+            let now = Jiffies::elapsed().as_duration();
+            HookState {
+                in_iface: iface_info(in_iface),
+                out_iface: iface_info(out_iface),
+                masq_addr: out_iface.and_then(|index| fib.iface_ipv4_addr(index)),
+                ct: self.conntrack.lock().track(&info, packet, now),
+            }
+        };
+
+        let nat_chains: Vec<_> = base_chains
+            .iter()
+            .filter(|(_, _, base)| base.type_ == ChainType::Nat)
+            .map(|(table, chain, _)| (*table, *chain))
+            .collect();
+        let mut pending_manip = match hook {
+            FilterHook::PreRouting | FilterHook::LocalOut => Some(ManipType::Dst),
+            FilterHook::PostRouting | FilterHook::LocalIn => Some(ManipType::Src),
+            FilterHook::Forward => None,
+        };
+
+        // The NAT chains are evaluated together when the first of them is reached, so that the
+        // chains after them see the translated packet.
+        for (table, chain, base) in base_chains.iter() {
+            if base.type_ != ChainType::Nat {
+                let mut cx = state.context(packet, &info);
+                if !table.eval(chain, &mut cx) {
+                    return false;
+                }
+                continue;
+            }
+
+            let Some(manip) = pending_manip.take() else {
+                continue;
+            };
+            if !self.translate(&nat_chains, manip, &state, &info, packet) {
+                return false;
+            }
+            let Some(new_info) = PacketInfo::parse(packet) else {
+                return false;
+            };
+            info = new_info;
+        }
+
+        // Even if there are no NAT chains, the packets of translated connections must be
+        // translated.
+        if let Some(manip) = pending_manip {
+            return self.translate(&[], manip, &state, &info, packet);
+        }
+
+        true
+    }
+}
@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::{
+    IPV4_HEADER_LEN, Icmpv4Message, Icmpv4Packet, IpAddress, IpProtocol, Ipv4Packet, Ipv6Packet,
+    TcpPacket, UdpPacket,
+};
+
+use super::ruleset::NfProto;
+use crate::prelude::*;
+
+/// The information about an IP packet that is needed to filter and mangle it.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PacketInfo {
+    pub(super) family: NfProto,
+    pub(super) l4proto: u8,
+    /// The offset of the transport header, which is the packet length if there is no transport
+    /// header (e.g., in non-first fragments).
+    pub(super) thoff: usize,
+    /// The length of the packet, excluding any trailing bytes after the IP payload.
+    pub(super) len: usize,
+    pub(super) src_addr: IpAddress,
+    pub(super) dst_addr: IpAddress,
+}
+
+impl PacketInfo {
+    pub(super) fn parse(packet: &[u8]) -> Option<Self> {
+        match packet.first()? >> 4 {
+            4 => {
+                let ipv4_packet = Ipv4Packet::new_checked(packet).ok()?;
+                let len = ipv4_packet.total_len() as usize;
+                let thoff = if ipv4_packet.frag_offset() == 0 {
+                    ipv4_packet.header_len() as usize
+                } else {
+                    len
+                };
+                Some(Self {
+                    family: NfProto::Ipv4,
+                    l4proto: ipv4_packet.next_header().into(),
+                    thoff,
+                    len,
+                    src_addr: IpAddress::Ipv4(ipv4_packet.src_addr()),
+                    dst_addr: IpAddress::Ipv4(ipv4_packet.dst_addr()),
+                })
+            }
+            // TODO: Skip the IPv6 extension headers to find the transport header.
+            6 => {
+                let ipv6_packet = Ipv6Packet::new_checked(packet).ok()?;
+                Some(Self {
+                    family: NfProto::Ipv6,
+                    l4proto: ipv6_packet.next_header().into(),
+                    thoff: ipv6_packet.header_len(),
+                    len: ipv6_packet.total_len(),
+                    src_addr: IpAddress::Ipv6(ipv6_packet.src_addr()),
+                    dst_addr: IpAddress::Ipv6(ipv6_packet.dst_addr()),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses an IPv4 packet that may be truncated, such as the one embedded in an ICMP error
+    /// message.
+    pub(super) fn parse_truncated(packet: &[u8]) -> Option<Self> {
+        if packet.first()? >> 4 != 4 || packet.len() < IPV4_HEADER_LEN {
+            return None;
+        }
+
+        let ipv4_packet = Ipv4Packet::new_unchecked(packet);
+        let header_len = ipv4_packet.header_len() as usize;
+        if header_len < IPV4_HEADER_LEN || header_len > packet.len() {
+            return None;
+        }
+
+        Some(Self {
+            family: NfProto::Ipv4,
+            l4proto: ipv4_packet.next_header().into(),
+            thoff: header_len,
+            len: packet.len(),
+            src_addr: IpAddress::Ipv4(ipv4_packet.src_addr()),
+            dst_addr: IpAddress::Ipv4(ipv4_packet.dst_addr()),
+        })
+    }
+
+    /// Returns the transport header and the transport payload.
+    pub(super) fn transport<'a>(&self, packet: &'a [u8]) -> &'a [u8] {
+        &packet[self.thoff..self.len]
+    }
+
+    /// Returns the ports (or the ICMP echo identifiers) of the packet.
+    ///
+    /// For ICMP echo requests, the identifier is regarded as the source port. For ICMP echo
+    /// replies, the identifier is regarded as the destination port. This way, the two directions
+    /// of an ICMP echo exchange have inverted tuples, like those of TCP and UDP.
+    pub(super) fn ports(&self, packet: &[u8]) -> Option<(u16, u16)> {
+        let transport = self.transport(packet);
+
+        match IpProtocol::from(self.l4proto) {
+            // Only the first 4 bytes are parsed, since the packet may be truncated.
+            IpProtocol::Tcp | IpProtocol::Udp => {
+                let ports = transport.get(..4)?;
+                Some((
+                    u16::from_be_bytes([ports[0], ports[1]]),
+                    u16::from_be_bytes([ports[2], ports[3]]),
+                ))
+            }
+            IpProtocol::Icmp if self.family == NfProto::Ipv4 => {
+                let icmp_packet = Icmpv4Packet::new_checked(transport).ok()?;
+                match icmp_packet.msg_type() {
+                    Icmpv4Message::EchoRequest => Some((icmp_packet.echo_ident(), 0)),
+                    Icmpv4Message::EchoReply => Some((0, icmp_packet.echo_ident())),
+                    _ => Some((0, 0)),
+                }
+            }
+            _ => Some((0, 0)),
+        }
+    }
+
+    /// Returns whether the packet is an ICMP error message (e.g., destination unreachable).
+    pub(super) fn is_icmp_error(&self, packet: &[u8]) -> bool {
+        if self.family != NfProto::Ipv4 || IpProtocol::from(self.l4proto) != IpProtocol::Icmp {
+            return false;
+        }
+        let Ok(icmp_packet) = Icmpv4Packet::new_checked(self.transport(packet)) else {
+            return false;
+        };
+        matches!(
+            icmp_packet.msg_type(),
+            Icmpv4Message::DstUnreachable
+                | Icmpv4Message::TimeExceeded
+                | Icmpv4Message::ParamProblem
+                | Icmpv4Message::Redirect
+        )
+    }
+
+    /// Returns the IP packet embedded in an ICMP error message.
+    pub(super) fn icmp_error_payload<'a>(&self, packet: &'a [u8]) -> Option<&'a [u8]> {
+        let icmp_packet = Icmpv4Packet::new_checked(self.transport(packet)).ok()?;
+        let header_len = icmp_packet.header_len();
+        self.transport(packet).get(header_len..)
+    }
+}
+
+/// Rewrites the source address and port of an IPv4 packet, and updates the checksums.
+pub(super) fn set_source(packet: &mut [u8], info: &PacketInfo, addr: IpAddress, port: u16) {
+    rewrite(packet, info, Some((addr, port)), None)
+}
+
+/// Rewrites the destination address and port of an IPv4 packet, and updates the checksums.
+pub(super) fn set_destination(packet: &mut [u8], info: &PacketInfo, addr: IpAddress, port: u16) {
+    rewrite(packet, info, None, Some((addr, port)))
+}
+
+fn rewrite(
+    packet: &mut [u8],
+    info: &PacketInfo,
+    src: Option<(IpAddress, u16)>,
+    dst: Option<(IpAddress, u16)>,
+) {
+    // TODO: Support NAT for IPv6 packets.
+    let (IpAddress::Ipv4(src_addr), IpAddress::Ipv4(dst_addr)) = (
+        src.map_or(info.src_addr, |(addr, _)| addr),
+        dst.map_or(info.dst_addr, |(addr, _)| addr),
+    ) else {
+        return;
+    };
+
+    let mut ipv4_packet = Ipv4Packet::new_unchecked(&mut packet[..info.len]);
+    ipv4_packet.set_src_addr(src_addr);
+    ipv4_packet.set_dst_addr(dst_addr);
+    ipv4_packet.fill_checksum();
+
+    let (src_addr, dst_addr) = (IpAddress::Ipv4(src_addr), IpAddress::Ipv4(dst_addr));
+    let transport = &mut packet[info.thoff..info.len];
+
+    match IpProtocol::from(info.l4proto) {
+        IpProtocol::Tcp => {
+            let Ok(mut tcp_packet) = TcpPacket::new_checked(transport) else {
+                return;
+            };
+            if let Some((_, port)) = src {
+                tcp_packet.set_src_port(port);
+            }
+            if let Some((_, port)) = dst {
+                tcp_packet.set_dst_port(port);
+            }
+            tcp_packet.fill_checksum(&src_addr, &dst_addr);
+        }
+        IpProtocol::Udp => {
+            let Ok(mut udp_packet) = UdpPacket::new_checked(transport) else {
+                return;
+            };
+            if let Some((_, port)) = src {
+                udp_packet.set_src_port(port);
+            }
+            if let Some((_, port)) = dst {
+                udp_packet.set_dst_port(port);
+            }
+            // A zero checksum means that the checksum is not used.
+            if udp_packet.checksum() != 0 {
+                udp_packet.fill_checksum(&src_addr, &dst_addr);
+            }
+        }
+        IpProtocol::Icmp => {
+            let Ok(mut icmp_packet) = Icmpv4Packet::new_checked(transport) else {
+                return;
+            };
+            match (icmp_packet.msg_type(), src, dst) {
+                (Icmpv4Message::EchoRequest, Some((_, ident)), _)
+                | (Icmpv4Message::EchoReply, _, Some((_, ident))) => {
+                    icmp_packet.set_echo_ident(ident);
+                    icmp_packet.fill_checksum();
+                }
+                _ => (),
+            }
+        }
+        _ => (),
+    }
+}
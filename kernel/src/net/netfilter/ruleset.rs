@@ -0,0 +1,303 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::iface::FilterHook;
+
+use super::expr::{EvalContext, Expr, Registers, Verdict};
+use crate::prelude::*;
+
+/// The address family of a table.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter.h#L61>.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum NfProto {
+    /// Both IPv4 and IPv6.
+    Inet = 1,
+    Ipv4 = 2,
+    Ipv6 = 10,
+}
+
+impl NfProto {
+    /// Returns whether the tables of this family see the packets of `family`.
+    pub fn contains(&self, family: NfProto) -> bool {
+        *self == family || *self == NfProto::Inet
+    }
+}
+
+/// The type of a base chain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChainType {
+    /// Filters packets.
+    Filter,
+    /// Translates addresses. Only the first packet of each connection traverses this type of
+    /// chains.
+    Nat,
+    /// Reroutes packets if they are modified.
+    //
+    // TODO: Reroute the modified packets. Currently, route chains behave like filter chains.
+    Route,
+}
+
+impl ChainType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "filter" => Some(Self::Filter),
+            "nat" => Some(Self::Nat),
+            "route" => Some(Self::Route),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Filter => "filter",
+            Self::Nat => "nat",
+            Self::Route => "route",
+        }
+    }
+}
+
+/// The properties of a base chain, which is attached to a hook.
+#[derive(Clone, Copy, Debug)]
+pub struct BaseChain {
+    pub hook: FilterHook,
+    /// The priority, where chains with lower values are evaluated first.
+    pub priority: i32,
+    pub type_: ChainType,
+    /// Whether the packets that reach the end of the chain are accepted.
+    pub policy_accept: bool,
+}
+
+/// A rule, which consists of expressions that are evaluated in order.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub handle: u64,
+    pub exprs: Vec<Expr>,
+    /// The opaque data attached by the user space (e.g., comments).
+    pub userdata: Option<Vec<u8>>,
+}
+
+impl Rule {
+    fn eval(&self, cx: &mut EvalContext) -> Verdict {
+        let mut regs = Registers::new();
+        for expr in self.exprs.iter() {
+            if let core::ops::ControlFlow::Break(verdict) = expr.eval(&mut regs, cx) {
+                return verdict;
+            }
+        }
+        Verdict::Continue
+    }
+
+    /// Returns the names of the chains that the rule jumps to or goes to.
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.exprs.iter().filter_map(|expr| match expr {
+            Expr::Verdict(verdict) => verdict.chain(),
+            _ => None,
+        })
+    }
+}
+
+/// A chain, which is a list of rules.
+///
+/// Base chains are attached to hooks, while regular chains can only be reached by jumping from
+/// other chains.
+#[derive(Clone, Debug)]
+pub struct Chain {
+    pub name: String,
+    pub handle: u64,
+    pub base: Option<BaseChain>,
+    pub rules: Vec<Rule>,
+}
+
+/// A table, which is a container of chains.
+#[derive(Clone, Debug)]
+pub struct Table {
+    pub name: String,
+    pub family: NfProto,
+    pub handle: u64,
+    pub flags: u32,
+    pub chains: Vec<Chain>,
+    next_handle: u64,
+}
+
+impl Table {
+    /// The flag that makes the chains of a table inactive.
+    pub const NFT_TABLE_F_DORMANT: u32 = 0x1;
+
+    pub fn new(name: String, family: NfProto, handle: u64, flags: u32) -> Self {
+        Self {
+            name,
+            family,
+            handle,
+            flags,
+            chains: Vec::new(),
+            next_handle: 1,
+        }
+    }
+
+    /// Allocates a handle for a chain or a rule in this table.
+    pub fn alloc_handle(&mut self) -> u64 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        handle
+    }
+
+    pub fn chain(&self, name: &str) -> Option<&Chain> {
+        self.chains.iter().find(|chain| chain.name == name)
+    }
+
+    pub fn chain_mut(&mut self, name: &str) -> Option<&mut Chain> {
+        self.chains.iter_mut().find(|chain| chain.name == name)
+    }
+
+    /// Returns whether the chain named `to` can be reached from the chain named `from` by
+    /// jumping or going to other chains.
+    pub fn reaches(&self, from: &str, to: &str) -> bool {
+        let mut pending = vec![from];
+        let mut visited = Vec::new();
+
+        while let Some(name) = pending.pop() {
+            if name == to {
+                return true;
+            }
+            if visited.contains(&name) {
+                continue;
+            }
+            visited.push(name);
+
+            if let Some(chain) = self.chain(name) {
+                pending.extend(chain.rules.iter().flat_map(Rule::targets));
+            }
+        }
+
+        false
+    }
+
+    fn is_dormant(&self) -> bool {
+        self.flags & Self::NFT_TABLE_F_DORMANT != 0
+    }
+
+    /// Evaluates a base chain, and returns whether the packet is accepted.
+    pub(super) fn eval(&self, chain: &Chain, cx: &mut EvalContext) -> bool {
+        /// The maximum depth of jumps.
+        ///
+        /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/netfilter/nf_tables.h#L1217>.
+        const MAX_JUMP_STACK_SIZE: usize = 16;
+
+        let policy_accept = chain.base.is_none_or(|base| base.policy_accept);
+
+        let mut stack: Vec<(&Chain, usize)> = Vec::new();
+        let (mut chain, mut index) = (chain, 0);
+
+        loop {
+            let verdict = match chain.rules.get(index) {
+                Some(rule) => rule.eval(cx),
+                None => Verdict::Return,
+            };
+
+            match verdict {
+                Verdict::Accept => return true,
+                Verdict::Drop => return false,
+                Verdict::Continue | Verdict::Break => index += 1,
+                Verdict::Return => {
+                    let Some(caller) = stack.pop() else {
+                        return policy_accept;
+                    };
+                    (chain, index) = caller;
+                }
+                Verdict::Jump(ref target) | Verdict::Goto(ref target) => {
+                    // The targets are checked when the rules are added, so this should never fail.
+                    let Some(target_chain) = self.chain(target) else {
+                        return false;
+                    };
+                    if matches!(verdict, Verdict::Jump(_)) {
+                        if stack.len() >= MAX_JUMP_STACK_SIZE {
+                            return false;
+                        }
+                        stack.push((chain, index + 1));
+                    }
+                    (chain, index) = (target_chain, 0);
+                }
+            }
+        }
+    }
+}
+
+/// A set of tables.
+///
+/// Changes are made to a copy of the ruleset, which replaces the active ruleset atomically when
+/// the changes are committed.
+#[derive(Clone, Debug)]
+pub struct Ruleset {
+    pub tables: Vec<Table>,
+    next_table_handle: u64,
+    /// The generation, which increases every time the changes are committed.
+    generation: u32,
+}
+
+impl Ruleset {
+    pub(super) const fn new() -> Self {
+        Self {
+            tables: Vec::new(),
+            next_table_handle: 1,
+            generation: 0,
+        }
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub(super) fn bump_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Allocates a handle for a table.
+    pub fn alloc_table_handle(&mut self) -> u64 {
+        let handle = self.next_table_handle;
+        self.next_table_handle += 1;
+        handle
+    }
+
+    pub fn table(&self, family: NfProto, name: &str) -> Option<&Table> {
+        self.tables
+            .iter()
+            .find(|table| table.family == family && table.name == name)
+    }
+
+    pub fn table_mut(&mut self, family: NfProto, name: &str) -> Option<&mut Table> {
+        self.tables
+            .iter_mut()
+            .find(|table| table.family == family && table.name == name)
+    }
+
+    /// Returns whether any base chains are active.
+    pub fn has_base_chains(&self) -> bool {
+        self.tables
+            .iter()
+            .filter(|table| !table.is_dormant())
+            .any(|table| table.chains.iter().any(|chain| chain.base.is_some()))
+    }
+
+    /// Returns the active base chains that see the packets of `family` at the hook, sorted by
+    /// their priorities.
+    pub(super) fn base_chains(
+        &self,
+        family: NfProto,
+        hook: FilterHook,
+    ) -> Vec<(&Table, &Chain, BaseChain)> {
+        let mut base_chains: Vec<_> = self
+            .tables
+            .iter()
+            .filter(|table| table.family.contains(family) && !table.is_dormant())
+            .flat_map(|table| {
+                table.chains.iter().filter_map(move |chain| {
+                    let base = chain.base.filter(|base| base.hook == hook)?;
+                    Some((table, chain, base))
+                })
+            })
+            .collect();
+        base_chains.sort_by_key(|(_, _, base)| base.priority);
+        base_chains
+    }
+}
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::{IpAddress, IpCidr, Ipv4Address, Ipv4Cidr, Ipv6Cidr};
+
+use super::{
+    rule::{Rule, RuleAction},
+    table::{
+        RT_TABLE_DEFAULT, RT_TABLE_LOCAL, RT_TABLE_MAIN, RTPROT_KERNEL, Route, RouteTable,
+        RouteType, RtScope, network_of,
+    },
+};
+use crate::{prelude::*, util::net::CSocketAddrFamily};
+
+/// The flow of a packet, which is the input of route lookups.
+#[derive(Clone, Copy, Debug)]
+pub struct Flow {
+    pub dst: IpAddress,
+    pub src: Option<IpAddress>,
+    pub tos: u8,
+    /// The index of the input interface, which is `None` for locally generated packets.
+    pub iif: Option<u32>,
+    /// The index of the output interface, if the packet must be sent through it.
+    pub oif: Option<u32>,
+}
+
+impl Flow {
+    /// Creates a flow for a locally generated packet to `dst`.
+    pub fn new(dst: IpAddress) -> Self {
+        Self {
+            dst,
+            src: None,
+            tos: 0,
+            iif: None,
+            oif: None,
+        }
+    }
+}
+
+/// The forwarding information base (FIB), which consists of routing tables and routing rules.
+///
+/// Like Linux, the routes to the local addresses and to the directly attached networks are
+/// installed automatically when interfaces are added.
+pub struct Fib {
+    tables: BTreeMap<u32, RouteTable>,
+    /// The routing rules, which are sorted by their priorities.
+    rules: Vec<Rule>,
+    /// The names of the interfaces, which are used to match routing rules.
+    iface_names: BTreeMap<u32, CString>,
+}
+
+/// The priority of the default rule that looks up the local table.
+const LOCAL_RULE_PRIORITY: u32 = 0;
+/// The priority of the default rule that looks up the main table.
+const MAIN_RULE_PRIORITY: u32 = 32766;
+/// The priority of the default rule that looks up the default table.
+const DEFAULT_RULE_PRIORITY: u32 = 32767;
+
+/// The priority of the routes to the directly attached IPv6 networks.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/ip6_route.h>.
+const IPV6_CONNECTED_ROUTE_PRIORITY: u32 = 256;
+
+impl Fib {
+    pub(super) fn new() -> Self {
+        let mut tables = BTreeMap::new();
+        for id in [RT_TABLE_DEFAULT, RT_TABLE_MAIN, RT_TABLE_LOCAL] {
+            tables.insert(id, RouteTable::default());
+        }
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/fib_rules.c>.
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv6/fib6_rules.c>.
+        let rules = vec![
+            Rule::new_default(
+                CSocketAddrFamily::AF_INET,
+                LOCAL_RULE_PRIORITY,
+                RT_TABLE_LOCAL,
+            ),
+            Rule::new_default(
+                CSocketAddrFamily::AF_INET6,
+                LOCAL_RULE_PRIORITY,
+                RT_TABLE_LOCAL,
+            ),
+            Rule::new_default(
+                CSocketAddrFamily::AF_INET,
+                MAIN_RULE_PRIORITY,
+                RT_TABLE_MAIN,
+            ),
+            Rule::new_default(
+                CSocketAddrFamily::AF_INET6,
+                MAIN_RULE_PRIORITY,
+                RT_TABLE_MAIN,
+            ),
+            Rule::new_default(
+                CSocketAddrFamily::AF_INET,
+                DEFAULT_RULE_PRIORITY,
+                RT_TABLE_DEFAULT,
+            ),
+        ];
+
+        Self {
+            tables,
+            rules,
+            iface_names: BTreeMap::new(),
+        }
+    }
+
+    /// Looks up the route for the flow.
+    ///
+    /// The routing rules are evaluated in order, and the first route found in the selected
+    /// tables is returned. Routes and rules that reject packets are reported as errors.
+    pub fn lookup(&self, flow: &Flow) -> Result<Route> {
+        self.lookup_with_table(flow).map(|(_, route)| route)
+    }
+
+    /// Looks up the route for the flow, and returns it together with the ID of the routing table
+    /// that it is found in.
+    pub fn lookup_with_table(&self, flow: &Flow) -> Result<(u32, Route)> {
+        let name_of = |index| self.iface_names.get(&index).map(CString::as_c_str);
+
+        for rule in self.rules.iter() {
+            if !rule.matches(flow, name_of) {
+                continue;
+            }
+
+            let rejected_type = match rule.action {
+                RuleAction::ToTable => {
+                    let Some(route) = self
+                        .tables
+                        .get(&rule.table)
+                        .and_then(|table| table.lookup(&flow.dst, flow.tos, flow.oif))
+                    else {
+                        continue;
+                    };
+                    match route.type_ {
+                        RouteType::Throw => continue,
+                        RouteType::Blackhole | RouteType::Unreachable | RouteType::Prohibit => {
+                            route.type_
+                        }
+                        _ => return Ok((rule.table, *route)),
+                    }
+                }
+                RuleAction::Blackhole => RouteType::Blackhole,
+                RuleAction::Unreachable => RouteType::Unreachable,
+                RuleAction::Prohibit => RouteType::Prohibit,
+                // TODO: Support `goto` rules.
+                RuleAction::Goto | RuleAction::Nop | RuleAction::Unspec => continue,
+            };
+
+            return Err(rejection_error(rejected_type));
+        }
+
+        return_errno_with_message!(Errno::ENETUNREACH, "the network is unreachable");
+    }
+
+    /// Returns all the routing tables together with their IDs.
+    pub fn tables(&self) -> impl Iterator<Item = (u32, &RouteTable)> {
+        self.tables.iter().map(|(id, table)| (*id, table))
+    }
+
+    /// Returns a mutable reference to the routing table, which is created if it does not exist.
+    pub fn table_mut(&mut self, id: u32) -> &mut RouteTable {
+        self.tables.entry(id).or_default()
+    }
+
+    /// Returns all the routing rules in order.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Inserts a routing rule after the rules whose priorities are not greater than its
+    /// priority.
+    pub fn insert_rule(&mut self, rule: Rule) {
+        let index = self
+            .rules
+            .partition_point(|existing| existing.priority <= rule.priority);
+        self.rules.insert(index, rule);
+    }
+
+    /// Removes the first routing rule that satisfies `pred` and returns it.
+    pub fn remove_rule_if(&mut self, pred: impl Fn(&Rule) -> bool) -> Option<Rule> {
+        let index = self.rules.iter().position(pred)?;
+        Some(self.rules.remove(index))
+    }
+
+    /// Returns the index of the interface named `name`, if any.
+    pub fn iface_index(&self, name: &CStr) -> Option<u32> {
+        self.iface_names
+            .iter()
+            .find(|(_, iface_name)| iface_name.as_c_str() == name)
+            .map(|(index, _)| *index)
+    }
+
+    /// Returns whether the interface exists.
+    pub fn has_iface(&self, index: u32) -> bool {
+        self.iface_names.contains_key(&index)
+    }
+
+    /// Returns the name of the interface, if it exists.
+    pub fn iface_name(&self, index: u32) -> Option<&CStr> {
+        self.iface_names.get(&index).map(CString::as_c_str)
+    }
+
+    /// Returns the primary IPv4 address of the interface, if any.
+    ///
+    /// This is the preferred source address of the local route that is installed for the
+    /// interface.
+    pub fn iface_ipv4_addr(&self, index: u32) -> Option<Ipv4Address> {
+        self.tables[&RT_TABLE_LOCAL]
+            .routes()
+            .iter()
+            .filter(|route| route.type_ == RouteType::Local && route.oif == Some(index))
+            .find_map(|route| match route.prefsrc {
+                Some(IpAddress::Ipv4(addr)) => Some(addr),
+                _ => None,
+            })
+    }
+
+    /// Adds an interface and installs the routes to its addresses.
+    pub(super) fn add_iface(
+        &mut self,
+        index: u32,
+        name: CString,
+        ipv4_cidr: Option<Ipv4Cidr>,
+        ipv6_cidrs: &[Ipv6Cidr],
+        is_loopback: bool,
+    ) {
+        self.iface_names.insert(index, name);
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/fib_frontend.c>.
+        if let Some(ipv4_cidr) = ipv4_cidr {
+            let addr = IpAddress::Ipv4(ipv4_cidr.address());
+            let kernel_route = |dst, type_, scope| Route {
+                dst,
+                tos: 0,
+                type_,
+                protocol: RTPROT_KERNEL,
+                scope,
+                gateway: None,
+                oif: Some(index),
+                prefsrc: Some(addr),
+                priority: 0,
+            };
+
+            let local_table = self.table_mut(RT_TABLE_LOCAL);
+            local_table.push(kernel_route(
+                IpCidr::new(addr, 32),
+                RouteType::Local,
+                RtScope::HOST,
+            ));
+            if is_loopback {
+                // Like Linux, the whole loopback network is local.
+                local_table.push(kernel_route(
+                    network_of(&IpCidr::Ipv4(ipv4_cidr)),
+                    RouteType::Local,
+                    RtScope::HOST,
+                ));
+            } else if let Some(broadcast) = ipv4_cidr.broadcast() {
+                local_table.push(kernel_route(
+                    IpCidr::new(IpAddress::Ipv4(broadcast), 32),
+                    RouteType::Broadcast,
+                    RtScope::LINK,
+                ));
+            }
+
+            if !is_loopback {
+                self.table_mut(RT_TABLE_MAIN).push(kernel_route(
+                    network_of(&IpCidr::Ipv4(ipv4_cidr)),
+                    RouteType::Unicast,
+                    RtScope::LINK,
+                ));
+            }
+        }
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv6/addrconf.c>.
+        for ipv6_cidr in ipv6_cidrs.iter() {
+            let addr = IpAddress::Ipv6(ipv6_cidr.address());
+            let kernel_route = |dst, type_, priority| Route {
+                dst,
+                tos: 0,
+                type_,
+                protocol: RTPROT_KERNEL,
+                scope: RtScope::UNIVERSE,
+                gateway: None,
+                oif: Some(index),
+                prefsrc: None,
+                priority,
+            };
+
+            self.table_mut(RT_TABLE_LOCAL).push(kernel_route(
+                IpCidr::new(addr, 128),
+                RouteType::Local,
+                0,
+            ));
+            self.table_mut(RT_TABLE_MAIN).push(kernel_route(
+                network_of(&IpCidr::Ipv6(*ipv6_cidr)),
+                RouteType::Unicast,
+                IPV6_CONNECTED_ROUTE_PRIORITY,
+            ));
+        }
+    }
+
+    /// Removes an interface and the routes through it.
+    pub(super) fn remove_iface(&mut self, index: u32) {
+        self.iface_names.remove(&index);
+        for table in self.tables.values_mut() {
+            table.remove_by_oif(index);
+        }
+    }
+}
+
+/// Returns the error for packets that are rejected by a route or a rule of the type.
+fn rejection_error(type_: RouteType) -> Error {
+    match type_ {
+        RouteType::Unreachable => {
+            Error::with_message(Errno::EHOSTUNREACH, "the host is unreachable")
+        }
+        RouteType::Prohibit => Error::with_message(Errno::EACCES, "the route is prohibited"),
+        _ => Error::with_message(Errno::EINVAL, "the route is a blackhole"),
+    }
+}
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! IP routing.
+//!
+//! This module maintains the routing tables and the routing rules of network namespaces, which
+//! decide the output interfaces and the next hops of outgoing packets, and whether incoming
+//! packets are forwarded between interfaces.
+
+mod fib;
+mod router;
+mod rule;
+mod table;
+
+pub use fib::{Fib, Flow};
+pub use router::Router;
+pub use rule::{Rule, RuleAction, RuleFlags};
+pub use table::{
+    InsertPolicy, RT_TABLE_COMPAT, RT_TABLE_MAIN, RT_TABLE_UNSPEC, RTPROT_BOOT, Route, RouteType,
+    RtScope, network_of,
+};
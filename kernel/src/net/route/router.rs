@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_bigtcp::{
+    iface::InterfaceType,
+    wire::{IpAddress, IpRepr, Ipv4Cidr},
+};
+use aster_softirq::BottomHalfDisabled;
+use ostd::sync::{RwLockReadGuard, RwLockWriteGuard};
+
+use super::{
+    fib::{Fib, Flow},
+    table::RouteType,
+};
+use crate::{net::iface::Iface, prelude::*};
+
+/// The router of a network namespace.
+///
+/// The router owns the FIB of the network namespace. It is shared by all the interfaces in the
+/// namespace, so that outgoing packets can be routed according to the FIB and incoming packets
+/// can be forwarded between the interfaces.
+pub struct Router {
+    fib: RwLock<Fib, BottomHalfDisabled>,
+    /// The interfaces, keyed by their indexes.
+    ///
+    /// The interfaces are looked up when packets are forwarded, which may happen when other
+    /// interfaces are being polled. Therefore, the lock must disable bottom halves.
+    ifaces: RwLock<BTreeMap<u32, Weak<Iface>>, BottomHalfDisabled>,
+    /// Whether IPv4 packets can be forwarded between interfaces.
+    ///
+    /// This is the `net.ipv4.ip_forward` sysctl.
+    ip_forward: AtomicBool,
+}
+
+impl Router {
+    pub(in crate::net) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            fib: RwLock::new(Fib::new()),
+            ifaces: RwLock::new(BTreeMap::new()),
+            ip_forward: AtomicBool::new(false),
+        })
+    }
+
+    /// Adds an interface to be routed by this router.
+    ///
+    /// The routes to the addresses of the interface are installed in the FIB.
+    pub(in crate::net) fn add_iface(self: &Arc<Self>, iface: &Arc<Iface>) {
+        let index = iface.index();
+        let ipv4_cidr = iface
+            .ipv4_addr()
+            .map(|addr| Ipv4Cidr::new(addr, iface.prefix_len().unwrap()));
+        let ipv6_cidrs = iface.ipv6_addrs();
+        let is_loopback = iface.type_() == InterfaceType::LOOPBACK;
+
+        iface.set_router(self.clone());
+        self.ifaces.write().insert(index, Arc::downgrade(iface));
+        self.fib.write().add_iface(
+            index,
+            iface.name().to_owned(),
+            ipv4_cidr,
+            &ipv6_cidrs,
+            is_loopback,
+        );
+    }
+
+    /// Removes an interface and the routes through it.
+    pub(in crate::net) fn remove_iface(&self, index: u32) {
+        self.fib.write().remove_iface(index);
+        self.ifaces.write().remove(&index);
+    }
+
+    /// Acquires the read lock to the FIB.
+    pub(in crate::net) fn fib(&self) -> RwLockReadGuard<'_, Fib, BottomHalfDisabled> {
+        self.fib.read()
+    }
+
+    /// Acquires the write lock to the FIB.
+    pub(in crate::net) fn fib_mut(&self) -> RwLockWriteGuard<'_, Fib, BottomHalfDisabled> {
+        self.fib.write()
+    }
+
+    /// Returns whether IPv4 packets can be forwarded between interfaces.
+    pub(in crate::net) fn ip_forward(&self) -> bool {
+        self.ip_forward.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether IPv4 packets can be forwarded between interfaces.
+    pub(in crate::net) fn set_ip_forward(&self, ip_forward: bool) {
+        self.ip_forward.store(ip_forward, Ordering::Relaxed);
+    }
+
+    /// Looks up the output interface for locally generated packets to `dst_addr`.
+    pub(in crate::net) fn route_output(&self, dst_addr: &IpAddress) -> Result<Arc<Iface>> {
+        let route = self.fib.read().lookup(&Flow::new(*dst_addr))?;
+        route
+            .oif
+            .and_then(|oif| self.iface(oif))
+            .ok_or_else(|| Error::with_message(Errno::ENETUNREACH, "the network is unreachable"))
+    }
+
+    fn iface(&self, index: u32) -> Option<Arc<Iface>> {
+        self.ifaces.read().get(&index).and_then(Weak::upgrade)
+    }
+}
+
+impl aster_bigtcp::iface::Router for Router {
+    fn next_hop(
+        &self,
+        iface_index: u32,
+        src_addr: &IpAddress,
+        dst_addr: &IpAddress,
+    ) -> Option<IpAddress> {
+        // TODO: Forwarded packets are routed again without the input interface, which may select
+        // a different route if there are routing rules that match the input interface.
+        let flow = Flow {
+            src: Some(*src_addr),
+            oif: Some(iface_index),
+            ..Flow::new(*dst_addr)
+        };
+        let route = self.fib.read().lookup(&flow).ok()?;
+
+        if route.type_ != RouteType::Unicast || route.oif != Some(iface_index) {
+            return None;
+        }
+        Some(route.gateway.unwrap_or(*dst_addr))
+    }
+
+    fn forward(&self, iface_index: u32, ip_repr: &IpRepr, ip_payload: &[u8]) -> bool {
+        // TODO: Support forwarding IPv6 packets.
+        let IpRepr::Ipv4(ipv4_repr) = ip_repr else {
+            return false;
+        };
+        if !ipv4_repr.src_addr.is_unicast()
+            || !ipv4_repr.dst_addr.is_unicast()
+            || ipv4_repr.dst_addr.is_loopback()
+        {
+            return false;
+        }
+
+        let flow = Flow {
+            src: Some(IpAddress::Ipv4(ipv4_repr.src_addr)),
+            iif: Some(iface_index),
+            ..Flow::new(IpAddress::Ipv4(ipv4_repr.dst_addr))
+        };
+        let Ok(route) = self.fib.read().lookup(&flow) else {
+            return false;
+        };
+
+        let mut ipv4_repr = *ipv4_repr;
+        match route.type_ {
+            // Like Linux, packets destined for the addresses of other local interfaces are
+            // accepted, regardless of which interfaces they arrive on.
+            RouteType::Local => (),
+            // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/ip_forward.c>.
+            RouteType::Unicast if self.ip_forward() => {
+                if ipv4_repr.hop_limit <= 1 {
+                    // TODO: Send an ICMP time exceeded message.
+                    return true;
+                }
+                ipv4_repr.hop_limit -= 1;
+            }
+            // Directed broadcasts are never forwarded.
+            RouteType::Broadcast => return true,
+            _ => return false,
+        }
+
+        let Some(iface) = route.oif.and_then(|oif| self.iface(oif)) else {
+            return false;
+        };
+        // TODO: Fragment the packet, or send an ICMP fragmentation needed message, if the packet
+        // exceeds the MTU of the output interface.
+        if iface.enqueue_forwarded(IpRepr::Ipv4(ipv4_repr), ip_payload.to_vec(), iface_index) {
+            iface.sched_poll().request_poll();
+        }
+
+        true
+    }
+}
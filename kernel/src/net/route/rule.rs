@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::{IpAddress, IpCidr};
+
+use super::{
+    fib::Flow,
+    table::{RT_TABLE_UNSPEC, RTPROT_KERNEL},
+};
+use crate::{prelude::*, util::net::CSocketAddrFamily};
+
+/// The action of a routing rule.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/fib_rules.h>.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum RuleAction {
+    /// An unspecified action, which can only appear in requests.
+    Unspec = 0,
+    /// Looks up the route in a routing table.
+    ToTable = 1,
+    /// Jumps to another rule.
+    Goto = 2,
+    /// Does nothing.
+    Nop = 3,
+    /// Drops packets silently.
+    Blackhole = 6,
+    /// Rejects packets because the destination is unreachable.
+    Unreachable = 7,
+    /// Rejects packets because they are administratively prohibited.
+    Prohibit = 8,
+}
+
+bitflags! {
+    /// Flags of a routing rule.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/fib_rules.h>.
+    pub struct RuleFlags: u32 {
+        const PERMANENT    = 0x00000001;
+        const INVERT       = 0x00000002;
+        const UNRESOLVED   = 0x00000004;
+        const IIF_DETACHED = 0x00000008;
+        const OIF_DETACHED = 0x00000010;
+        const FIND_SADDR   = 0x00010000;
+    }
+}
+
+/// A routing rule, which selects the routing table for packets.
+///
+/// Rules are evaluated in ascending order of their priorities. A rule is applied to a packet if
+/// all of its selectors match the packet (or if any of them does not match, when the rule is
+/// inverted).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rule {
+    pub family: CSocketAddrFamily,
+    pub priority: u32,
+    pub action: RuleAction,
+    /// The ID of the routing table, which is used when the action is [`RuleAction::ToTable`].
+    pub table: u32,
+    /// The source network selector.
+    pub src: Option<IpCidr>,
+    /// The destination network selector.
+    pub dst: Option<IpCidr>,
+    /// The TOS selector, where zero matches all values.
+    pub tos: u8,
+    /// The input interface selector.
+    pub iif_name: Option<CString>,
+    /// The output interface selector.
+    pub oif_name: Option<CString>,
+    /// The firewall mark selector.
+    pub fwmark: u32,
+    pub fwmask: u32,
+    /// The origin of the rule.
+    pub protocol: u8,
+    pub flags: RuleFlags,
+}
+
+impl Rule {
+    /// Creates a rule that looks up `table` for all packets of the family.
+    pub(super) fn new_default(family: CSocketAddrFamily, priority: u32, table: u32) -> Self {
+        Self {
+            family,
+            priority,
+            action: RuleAction::ToTable,
+            table,
+            src: None,
+            dst: None,
+            tos: 0,
+            iif_name: None,
+            oif_name: None,
+            fwmark: 0,
+            fwmask: 0,
+            protocol: RTPROT_KERNEL,
+            flags: RuleFlags::empty(),
+        }
+    }
+
+    /// Returns whether the rule selects the flow.
+    ///
+    /// `name_of` maps interface indexes to interface names.
+    pub(super) fn matches<'a>(
+        &self,
+        flow: &Flow,
+        name_of: impl Fn(u32) -> Option<&'a CStr>,
+    ) -> bool {
+        let family_matches = match flow.dst {
+            IpAddress::Ipv4(_) => self.family == CSocketAddrFamily::AF_INET,
+            IpAddress::Ipv6(_) => self.family == CSocketAddrFamily::AF_INET6,
+        };
+        if !family_matches {
+            return false;
+        }
+
+        // Like Linux, locally generated packets are treated as if they come from the loopback
+        // interface.
+        let iif_name = match flow.iif {
+            Some(iif) => name_of(iif),
+            None => Some(c"lo"),
+        };
+        let oif_name = flow.oif.and_then(name_of);
+
+        // TODO: Support firewall marks. For now, all packets are treated as unmarked.
+        let mark = 0;
+
+        let src_matches = self
+            .src
+            .is_none_or(|src| flow.src.is_some_and(|addr| src.contains_addr(&addr)));
+        let dst_matches = self.dst.is_none_or(|dst| dst.contains_addr(&flow.dst));
+        let tos_matches = self.tos == 0 || self.tos == flow.tos;
+        let iif_matches = self
+            .iif_name
+            .as_deref()
+            .is_none_or(|name| iif_name == Some(name));
+        let oif_matches = self
+            .oif_name
+            .as_deref()
+            .is_none_or(|name| oif_name == Some(name));
+        let mark_matches = (mark ^ self.fwmark) & self.fwmask == 0;
+
+        let is_selected =
+            src_matches && dst_matches && tos_matches && iif_matches && oif_matches && mark_matches;
+        is_selected != self.flags.contains(RuleFlags::INVERT)
+    }
+
+    /// Returns whether the rule is a candidate of a deletion request described by `filter`.
+    ///
+    /// Like Linux, unspecified fields in `filter` match all values. Since zero is a valid
+    /// priority, whether the priority is specified is indicated by `priority` instead.
+    pub fn matches_filter(&self, filter: &Rule, priority: Option<u32>) -> bool {
+        self.family == filter.family
+            && (filter.action == RuleAction::Unspec || self.action == filter.action)
+            && priority.is_none_or(|priority| self.priority == priority)
+            && (filter.table == RT_TABLE_UNSPEC || self.table == filter.table)
+            && (filter.src.is_none() || self.src == filter.src)
+            && (filter.dst.is_none() || self.dst == filter.dst)
+            && (filter.tos == 0 || self.tos == filter.tos)
+            && (filter.iif_name.is_none() || self.iif_name == filter.iif_name)
+            && (filter.oif_name.is_none() || self.oif_name == filter.oif_name)
+            && (filter.fwmark == 0 || self.fwmark == filter.fwmark)
+            && (filter.fwmask == 0 || self.fwmask == filter.fwmask)
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::cmp::Reverse;
+
+use aster_bigtcp::wire::{IpAddress, IpCidr, Ipv6Address, Ipv6Cidr};
+
+use crate::prelude::*;
+
+/// The ID of an unspecified routing table.
+pub const RT_TABLE_UNSPEC: u32 = 0;
+/// The ID reported for routing tables whose IDs do not fit in the `rtm_table` field.
+pub const RT_TABLE_COMPAT: u32 = 252;
+/// The ID of the default routing table.
+pub const RT_TABLE_DEFAULT: u32 = 253;
+/// The ID of the main routing table, which contains the routes added without specifying a table.
+pub const RT_TABLE_MAIN: u32 = 254;
+/// The ID of the local routing table, which contains the routes to local and broadcast addresses.
+pub const RT_TABLE_LOCAL: u32 = 255;
+
+/// The protocol of the routes installed by the kernel.
+pub const RTPROT_KERNEL: u8 = 2;
+/// The protocol of the routes installed during boot.
+pub const RTPROT_BOOT: u8 = 3;
+
+/// The type of a route.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h>.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum RouteType {
+    /// An unspecified type, which can only appear in requests.
+    Unspec = 0,
+    /// A route to a gateway or a directly attached network.
+    Unicast = 1,
+    /// A route to a local address, which is accepted locally.
+    Local = 2,
+    /// A route to a broadcast address, which is sent as a broadcast.
+    Broadcast = 3,
+    /// A route that drops packets silently.
+    Blackhole = 6,
+    /// A route that rejects packets because the destination is unreachable.
+    Unreachable = 7,
+    /// A route that rejects packets because they are administratively prohibited.
+    Prohibit = 8,
+    /// A route that makes the lookup continue with the next routing rule.
+    Throw = 9,
+}
+
+/// `rt_scope_t` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h#L320>.
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum RtScope {
+    UNIVERSE = 0,
+    // User defined values
+    SITE = 200,
+    LINK = 253,
+    HOST = 254,
+    NOWHERE = 255,
+}
+
+/// A route in a routing table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Route {
+    /// The destination network.
+    pub dst: IpCidr,
+    /// The type of service (TOS), where zero matches all values.
+    pub tos: u8,
+    pub type_: RouteType,
+    /// The origin of the route (e.g., [`RTPROT_KERNEL`]).
+    pub protocol: u8,
+    pub scope: RtScope,
+    /// The gateway, which is absent if the destination network is directly attached.
+    pub gateway: Option<IpAddress>,
+    /// The index of the output interface.
+    pub oif: Option<u32>,
+    /// The preferred source address.
+    pub prefsrc: Option<IpAddress>,
+    /// The priority (also known as the metric), where lower values are preferred.
+    pub priority: u32,
+}
+
+impl Route {
+    /// Returns whether the two routes have the same key.
+    ///
+    /// Routes with the same key cannot coexist in the same table unless they are explicitly
+    /// appended.
+    fn has_same_key(&self, other: &Route) -> bool {
+        self.dst == other.dst && self.tos == other.tos && self.priority == other.priority
+    }
+}
+
+/// The policy for inserting a route whose key is already in use.
+#[derive(Clone, Copy, Debug)]
+pub enum InsertPolicy {
+    /// Fails with `EEXIST`.
+    Exclusive,
+    /// Replaces the existing route. If there are no such routes, the route is inserted if
+    /// `create` is true, or `ENOENT` is reported otherwise.
+    Replace { create: bool },
+    /// Appends the route after the existing routes.
+    Append,
+}
+
+/// A routing table.
+#[derive(Debug, Default)]
+pub struct RouteTable {
+    routes: Vec<Route>,
+}
+
+impl RouteTable {
+    /// Returns all the routes in the table.
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// Looks up the route with the longest prefix that contains `dst_addr`.
+    ///
+    /// If `oif` is specified, routes through other interfaces are ignored. Among the routes with
+    /// the same prefix length, the one with the lowest priority is preferred.
+    pub(super) fn lookup(&self, dst_addr: &IpAddress, tos: u8, oif: Option<u32>) -> Option<&Route> {
+        self.routes
+            .iter()
+            .filter(|route| route.dst.contains_addr(dst_addr))
+            .filter(|route| route.tos == 0 || route.tos == tos)
+            .filter(|route| oif.is_none() || route.oif.is_none() || route.oif == oif)
+            .min_by_key(|route| (Reverse(route.dst.prefix_len()), route.priority))
+    }
+
+    /// Inserts a route according to the policy.
+    pub fn insert(&mut self, route: Route, policy: InsertPolicy) -> Result<()> {
+        let existing = self
+            .routes
+            .iter()
+            .position(|existing| existing.has_same_key(&route));
+
+        match (existing, policy) {
+            (Some(_), InsertPolicy::Exclusive) => {
+                return_errno_with_message!(Errno::EEXIST, "the route already exists");
+            }
+            (Some(index), InsertPolicy::Replace { .. }) => self.routes[index] = route,
+            (None, InsertPolicy::Replace { create: false }) => {
+                return_errno_with_message!(Errno::ENOENT, "the route does not exist");
+            }
+            (_, _) => self.routes.push(route),
+        }
+
+        Ok(())
+    }
+
+    /// Appends a route without checking whether its key is in use.
+    pub(super) fn push(&mut self, route: Route) {
+        self.routes.push(route);
+    }
+
+    /// Removes the first route that satisfies `pred` and returns it.
+    pub fn remove_if(&mut self, pred: impl Fn(&Route) -> bool) -> Option<Route> {
+        let index = self.routes.iter().position(pred)?;
+        Some(self.routes.remove(index))
+    }
+
+    /// Removes all the routes through the interface.
+    pub(super) fn remove_by_oif(&mut self, oif: u32) {
+        self.routes.retain(|route| route.oif != Some(oif));
+    }
+}
+
+/// Returns the network of the CIDR block, i.e., the CIDR block whose host bits are cleared.
+pub fn network_of(cidr: &IpCidr) -> IpCidr {
+    match cidr {
+        IpCidr::Ipv4(ipv4_cidr) => IpCidr::Ipv4(ipv4_cidr.network()),
+        IpCidr::Ipv6(ipv6_cidr) => {
+            let prefix_len = ipv6_cidr.prefix_len() as u32;
+            let mut octets = ipv6_cidr.address().octets();
+            for (i, octet) in octets.iter_mut().enumerate() {
+                let kept_bits = prefix_len.saturating_sub(i as u32 * 8).min(8);
+                *octet &= !0xffu8.checked_shr(kept_bits).unwrap_or(0);
+            }
+            IpCidr::Ipv6(Ipv6Cidr::new(
+                Ipv6Address::from(octets),
+                ipv6_cidr.prefix_len(),
+            ))
+        }
+    }
+}
@@ -56,6 +56,86 @@ impl IpAddressFamily {
             IpAddressFamily::IPv6 => UNSPECIFIED_LOCAL_ENDPOINT_V6,
         }
     }
+
+    /// Converts a socket address to a local endpoint that a socket of this family can bind to.
+    ///
+    /// Unless `v6only` is true, IPv6 sockets can bind to IPv4-mapped IPv6 addresses, in which case
+    /// the IPv4 endpoint will be returned.
+    pub(super) fn local_endpoint(
+        &self,
+        socket_addr: SocketAddr,
+        v6only: bool,
+    ) -> Result<IpEndpoint> {
+        match (self, socket_addr) {
+            (IpAddressFamily::IPv4, SocketAddr::IPv4(addr, port)) => {
+                Ok(IpEndpoint::new(addr.into(), port))
+            }
+            (IpAddressFamily::IPv6, SocketAddr::IPv6(addr, port)) => match addr.to_ipv4_mapped() {
+                Some(_) if v6only => return_errno_with_message!(
+                    Errno::EINVAL,
+                    "IPv4-mapped addresses are not allowed with IPV6_V6ONLY"
+                ),
+                Some(ipv4_addr) => Ok(IpEndpoint::new(ipv4_addr.into(), port)),
+                None => Ok(IpEndpoint::new(addr.into(), port)),
+            },
+            (IpAddressFamily::IPv6, SocketAddr::IPv4(..)) => {
+                return_errno_with_message!(Errno::EINVAL, "the socket address is too short")
+            }
+            _ => return_errno_with_message!(
+                Errno::EAFNOSUPPORT,
+                "the address is in an unsupported address family"
+            ),
+        }
+    }
+
+    /// Converts a socket address to a remote endpoint that a socket of this family can send to.
+    ///
+    /// Unless `v6only` is true, IPv6 sockets can send to IPv4 addresses or IPv4-mapped IPv6
+    /// addresses, in which case the IPv4 endpoint will be returned.
+    pub(super) fn remote_endpoint(
+        &self,
+        socket_addr: SocketAddr,
+        v6only: bool,
+    ) -> Result<IpEndpoint> {
+        match (self, socket_addr) {
+            (IpAddressFamily::IPv4, SocketAddr::IPv4(addr, port)) => {
+                Ok(IpEndpoint::new(addr.into(), port))
+            }
+            (IpAddressFamily::IPv6, SocketAddr::IPv6(addr, port)) => match addr.to_ipv4_mapped() {
+                Some(_) if v6only => return_errno_with_message!(
+                    Errno::ENETUNREACH,
+                    "IPv4-mapped addresses are not reachable with IPV6_V6ONLY"
+                ),
+                Some(ipv4_addr) => Ok(IpEndpoint::new(ipv4_addr.into(), port)),
+                None => Ok(IpEndpoint::new(addr.into(), port)),
+            },
+            (IpAddressFamily::IPv6, SocketAddr::IPv4(..)) if v6only => {
+                return_errno_with_message!(
+                    Errno::ENETUNREACH,
+                    "IPv4 addresses are not reachable with IPV6_V6ONLY"
+                )
+            }
+            (IpAddressFamily::IPv6, SocketAddr::IPv4(addr, port)) => {
+                Ok(IpEndpoint::new(addr.into(), port))
+            }
+            _ => return_errno_with_message!(
+                Errno::EAFNOSUPPORT,
+                "the address is in an unsupported address family"
+            ),
+        }
+    }
+
+    /// Converts an endpoint to the socket address reported by a socket of this family.
+    ///
+    /// IPv6 sockets report IPv4 endpoints as IPv4-mapped IPv6 addresses.
+    pub(super) fn socket_addr(&self, endpoint: IpEndpoint) -> SocketAddr {
+        match (self, endpoint.addr) {
+            (IpAddressFamily::IPv6, IpAddress::Ipv4(addr)) => {
+                SocketAddr::IPv6(addr.to_ipv6_mapped(), endpoint.port)
+            }
+            _ => endpoint.into(),
+        }
+    }
 }
 
 // Note: This does not handle IPv4-mapped IPv6 addresses. When `IPV6_V6ONLY` is set,
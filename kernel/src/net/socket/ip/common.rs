@@ -8,84 +8,148 @@ use aster_bigtcp::{
 
 use crate::{
     net::{
-        iface::{Iface, iter_all_ifaces, loopback_iface, virtio_iface},
-        socket::util::check_port_privilege,
+        iface::{BoundPort, Iface},
+        net_ns::NetNamespace,
+        socket::util::{SocketFilter, SocketOwner, check_port_privilege, options::SocketOptionSet},
     },
     prelude::*,
+    process::Uid,
 };
 
-fn get_iface_to_bind(ip_addr: &IpAddress) -> Option<Arc<Iface>> {
+fn get_iface_to_bind(net_ns: &NetNamespace, ip_addr: &IpAddress) -> Option<Arc<Iface>> {
+    let mut ifaces = net_ns.ifaces().into_iter();
     match *ip_addr {
-        IpAddress::Ipv4(ipv4_addr) => iter_all_ifaces()
-            .find(|iface| iface.ipv4_addr().is_some_and(|addr| addr == ipv4_addr))
-            .map(Clone::clone),
-        IpAddress::Ipv6(ipv6_addr) => iter_all_ifaces()
-            .find(|iface| iface.ipv6_addr().is_some_and(|addr| addr == ipv6_addr))
-            .map(Clone::clone),
+        IpAddress::Ipv4(ipv4_addr) => {
+            ifaces.find(|iface| iface.ipv4_addr().is_some_and(|addr| addr == ipv4_addr))
+        }
+        IpAddress::Ipv6(ipv6_addr) => ifaces.find(|iface| {
+            iface
+                .ipv6_addrs()
+                .iter()
+                .any(|cidr| cidr.address() == ipv6_addr)
+        }),
     }
 }
 
 /// Get a suitable iface to deal with sendto/connect request if the socket is not bound to an iface.
 /// If the remote address is the same as that of some iface, we will use the iface.
-/// Otherwise, we will use a default interface.
-fn get_ephemeral_iface(remote_ip_addr: &IpAddress) -> Arc<Iface> {
-    match remote_ip_addr {
-        IpAddress::Ipv4(remote_ipv4_addr) => {
-            if let Some(iface) = iter_all_ifaces().find(|iface| {
-                iface
-                    .ipv4_addr()
-                    .is_some_and(|addr| addr == *remote_ipv4_addr)
-            }) {
-                return iface.clone();
-            }
+/// Otherwise, we will use the output iface selected by the routing table, or the default
+/// interface of the network namespace if there are no routes to the remote address.
+pub(super) fn get_ephemeral_iface(net_ns: &NetNamespace, remote_ip_addr: &IpAddress) -> Arc<Iface> {
+    if let Some(iface) = get_iface_to_bind(net_ns, remote_ip_addr) {
+        return iface;
+    }
 
-            // FIXME: Instead of hardcoding the rules here, we should choose the
-            // default interface according to the routing table.
-            if let Some(virtio_iface) = virtio_iface() {
-                virtio_iface.clone()
-            } else {
-                loopback_iface().clone()
-            }
+    net_ns
+        .router()
+        .route_output(remote_ip_addr)
+        .unwrap_or_else(|_| net_ns.default_iface(remote_ip_addr))
+}
+
+/// Gets the iface that sends packets to the multicast group.
+///
+/// This is also the iface to join the multicast group on if the socket does not specify one.
+pub(super) fn get_multicast_iface(net_ns: &NetNamespace, group: &IpAddress) -> Arc<Iface> {
+    net_ns
+        .router()
+        .route_output(group)
+        .unwrap_or_else(|_| net_ns.default_iface(group))
+}
+
+/// Resolves the iface to join a multicast group on.
+///
+/// The iface is specified either by its index or by its address. If neither is specified, the
+/// iface is selected by the routing table.
+pub(super) fn resolve_multicast_iface(
+    net_ns: &NetNamespace,
+    group: &IpAddress,
+    ifindex: u32,
+    iface_addr: Option<IpAddress>,
+) -> Result<Arc<Iface>> {
+    if ifindex != 0 {
+        return net_ns
+            .iface_by_index(ifindex)
+            .ok_or_else(|| Error::with_message(Errno::ENODEV, "the interface does not exist"));
+    }
+
+    if let Some(iface_addr) = iface_addr {
+        return get_iface_to_bind(net_ns, &iface_addr).ok_or_else(|| {
+            Error::with_message(Errno::ENODEV, "no interface has the specified address")
+        });
+    }
+
+    Ok(get_multicast_iface(net_ns, group))
+}
+
+pub(super) fn resolve_bind_iface(net_ns: &NetNamespace, ip_addr: &IpAddress) -> Result<Arc<Iface>> {
+    match get_iface_to_bind(net_ns, ip_addr) {
+        Some(iface) => Ok(iface),
+        None => {
+            return_errno_with_message!(
+                Errno::EADDRNOTAVAIL,
+                "the address is not available from the local machine"
+            );
         }
-        IpAddress::Ipv6(remote_ipv6_addr) => {
-            if let Some(iface) = iter_all_ifaces().find(|iface| {
-                iface
-                    .ipv6_addr()
-                    .is_some_and(|addr| addr == *remote_ipv6_addr)
-            }) {
-                return iface.clone();
-            }
+    }
+}
 
-            // Fall back to an interface with an IPv6 address.
-            // Prefer virtio over loopback for external traffic.
-            if let Some(virtio_iface) = virtio_iface()
-                && virtio_iface.ipv6_addr().is_some()
-            {
-                return virtio_iface.clone();
-            }
+/// Options that determine whether a port can be shared with other sockets.
+#[derive(Default)]
+pub(super) struct BindOptions {
+    /// Whether the address can be reused (i.e., `SO_REUSEADDR`).
+    can_reuse: bool,
+    /// The owner of the socket if the port can be reused (i.e., `SO_REUSEPORT`).
+    reuse_port: Option<Uid>,
+    /// The program that selects a socket in the reuseport group.
+    reuseport_filter: Option<Arc<SocketFilter>>,
+    /// Whether the socket can bind to a multicast address (e.g., it is a datagram socket).
+    can_bind_multicast: bool,
+}
+
+impl BindOptions {
+    pub(super) fn new(options: &SocketOptionSet, owner: &SocketOwner) -> Self {
+        Self {
+            can_reuse: options.reuse_addr(),
+            reuse_port: options.reuse_port().then(|| owner.uid()),
+            reuseport_filter: options.reuseport_filter().cloned(),
+            can_bind_multicast: false,
+        }
+    }
+
+    /// Allows binding to a multicast address.
+    ///
+    /// A socket bound to a multicast address only receives the packets sent to the multicast
+    /// group. The port is bound on the iface that sends packets to the multicast group.
+    pub(super) fn with_multicast(mut self) -> Self {
+        self.can_bind_multicast = true;
+        self
+    }
 
-            loopback_iface().clone()
+    /// Applies the options that take effect after the port is bound.
+    pub(super) fn apply(&self, bound_port: &BoundPort) {
+        if let Some(filter) = self.reuseport_filter.as_ref() {
+            bound_port.set_reuseport_selector(Some(filter.clone()));
         }
     }
 }
 
 pub(super) fn resolve_bind_iface_and_config(
+    net_ns: &NetNamespace,
     endpoint: &IpEndpoint,
-    can_reuse: bool,
+    options: &BindOptions,
 ) -> Result<(Arc<Iface>, BindPortConfig)> {
     check_port_privilege(endpoint.port)?;
 
-    let iface = match get_iface_to_bind(&endpoint.addr) {
-        Some(iface) => iface,
-        None => {
-            return_errno_with_message!(
-                Errno::EADDRNOTAVAIL,
-                "the address is not available from the local machine"
-            );
-        }
+    let iface = if options.can_bind_multicast && endpoint.addr.is_multicast() {
+        get_multicast_iface(net_ns, &endpoint.addr)
+    } else {
+        resolve_bind_iface(net_ns, &endpoint.addr)?
     };
 
-    let bind_port_config = BindPortConfig::new(*endpoint, can_reuse);
+    let mut bind_port_config = BindPortConfig::new(*endpoint, options.can_reuse);
+    if let Some(uid) = options.reuse_port {
+        bind_port_config = bind_port_config.with_reuse_port(uid.into());
+    }
 
     Ok((iface, bind_port_config))
 }
@@ -103,15 +167,27 @@ impl From<BindError> for Error {
     }
 }
 
-pub(super) fn get_ephemeral_endpoint(remote_endpoint: &IpEndpoint) -> Option<IpEndpoint> {
-    let iface = get_ephemeral_iface(&remote_endpoint.addr);
+pub(super) fn get_ephemeral_endpoint(
+    net_ns: &NetNamespace,
+    remote_endpoint: &IpEndpoint,
+) -> Option<IpEndpoint> {
+    let iface = get_ephemeral_iface(net_ns, &remote_endpoint.addr);
     match remote_endpoint.addr {
         IpAddress::Ipv4(_) => {
             let ip_addr = iface.ipv4_addr()?;
             Some(IpEndpoint::new(IpAddress::Ipv4(ip_addr), 0))
         }
-        IpAddress::Ipv6(_) => {
-            let ipv6_addr = iface.ipv6_addr()?;
+        IpAddress::Ipv6(remote_ipv6_addr) => {
+            // Prefer a local address with the same scope as the remote address, so that
+            // link-local addresses are used to talk to link-local peers only.
+            let ipv6_addrs = iface.ipv6_addrs();
+            let ipv6_addr = ipv6_addrs
+                .iter()
+                .map(|cidr| cidr.address())
+                .find(|addr| {
+                    addr.is_unicast_link_local() == remote_ipv6_addr.is_unicast_link_local()
+                })
+                .or_else(|| ipv6_addrs.first().map(|cidr| cidr.address()))?;
             Some(IpEndpoint::new(IpAddress::Ipv6(ipv6_addr), 0))
         }
     }
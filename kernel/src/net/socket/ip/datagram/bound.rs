@@ -9,7 +9,10 @@ use crate::{
     events::IoEvents,
     net::{
         iface::{BoundUdpPort, Iface, UdpSocket},
-        socket::util::{SendRecvFlags, datagram_common},
+        socket::{
+            ip::IpAddressFamily,
+            util::{SendRecvFlags, datagram_common},
+        },
     },
     prelude::*,
     util::{MultiRead, MultiWrite},
@@ -35,6 +38,20 @@ impl BoundDatagram {
     pub(super) fn bound_port(&self) -> &BoundUdpPort {
         self.bound_socket.bound_port()
     }
+
+    /// Sets the hop limit and the loopback behavior of outgoing multicast packets.
+    pub(super) fn set_multicast_options(&self, hop_limit: u8, multicast_loop: bool) {
+        self.bound_socket.set_multicast_hop_limit(hop_limit);
+        self.bound_socket.set_multicast_loop(multicast_loop);
+    }
+
+    /// Returns whether the remote endpoint is in the same family as the local endpoint.
+    ///
+    /// A dual-stack IPv6 socket is bound to either an IPv4 address or an IPv6 address, after
+    /// which it can only talk to peers in the same family.
+    pub(super) fn can_reach(&self, remote: &IpEndpoint) -> bool {
+        IpAddressFamily::from(*self.bound_port().addr()) == IpAddressFamily::from(remote.addr)
+    }
 }
 
 impl datagram_common::Bound for BoundDatagram {
@@ -83,6 +100,13 @@ impl datagram_common::Bound for BoundDatagram {
         remote: &Self::Endpoint,
         _flags: SendRecvFlags,
     ) -> Result<usize> {
+        if !self.can_reach(remote) {
+            return_errno_with_message!(
+                Errno::ENETUNREACH,
+                "the remote address is in a different family from the local address"
+            );
+        }
+
         let result = self
             .bound_socket
             .send(reader.sum_lens(), *remote, |socket_buffer| {
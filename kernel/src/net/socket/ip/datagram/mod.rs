@@ -2,23 +2,32 @@
 
 use core::sync::atomic::{AtomicBool, Ordering};
 
-use aster_bigtcp::wire::IpEndpoint;
+use aster_bigtcp::wire::{IpAddress, IpEndpoint};
 use bound::BoundDatagram;
-use unbound::{BindOptions, UnboundDatagram};
+use unbound::UnboundDatagram;
 
-use super::addr::UNSPECIFIED_LOCAL_ENDPOINT;
+use super::{
+    IpAddressFamily,
+    common::resolve_multicast_iface,
+    multicast::MulticastMemberships,
+    options::{AddMembership, DropMembership, Ipv6AddMembership, Ipv6DropMembership},
+};
 use crate::{
     events::IoEvents,
     fs::{pseudofs::SockFs, vfs::path::Path},
     net::{
-        iface::is_broadcast_endpoint,
+        iface::Iface,
+        net_ns::NetNamespace,
         socket::{
             Socket,
-            ip::options::{IpOptionSet, SetIpLevelOption},
-            options::{Error as SocketError, SocketOption, macros::sock_option_mut},
+            ip::options::{IpOptionSet, Ipv6OptionSet, SetIpLevelOption, SetIpv6LevelOption},
+            options::{
+                Error as SocketError, SocketOption,
+                macros::{sock_option_mut, sock_option_ref},
+            },
             private::SocketPrivate,
             util::{
-                MessageHeader, SendRecvFlags, SocketAddr,
+                MessageHeader, SendRecvFlags, SocketAddr, SocketFilter, SocketOwner,
                 datagram_common::{Bound, Inner, select_remote_and_bind},
                 options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
             },
@@ -26,7 +35,7 @@ use crate::{
     },
     prelude::*,
     process::signal::{PollHandle, Pollable, Pollee},
-    util::{MultiRead, MultiWrite},
+    util::{MultiRead, MultiWrite, net::Protocol},
 };
 
 mod bound;
@@ -37,7 +46,10 @@ pub struct DatagramSocket {
     // Lock order: `inner` first, `options` second
     inner: RwMutex<Inner<UnboundDatagram, BoundDatagram>>,
     options: RwLock<OptionSet>,
+    memberships: Mutex<MulticastMemberships>,
 
+    family: IpAddressFamily,
+    net_ns: Arc<NetNamespace>,
     is_nonblocking: AtomicBool,
     pollee: Pollee,
     pseudo_path: Path,
@@ -47,6 +59,7 @@ pub struct DatagramSocket {
 struct OptionSet {
     socket: SocketOptionSet,
     ip: IpOptionSet,
+    ipv6: Ipv6OptionSet,
     // TODO: UDP option set
 }
 
@@ -54,19 +67,29 @@ impl OptionSet {
     fn new() -> Self {
         let socket = SocketOptionSet::new_udp();
         let ip = IpOptionSet::new_udp();
-        OptionSet { socket, ip }
+        let ipv6 = Ipv6OptionSet::new_udp();
+        OptionSet { socket, ip, ipv6 }
     }
 }
 
 impl DatagramSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        let unbound_datagram = UnboundDatagram::new();
+    pub fn new(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        let pseudo_path = SockFs::new_path();
+        let owner = SocketOwner::new_current(&pseudo_path);
+        let unbound_datagram = UnboundDatagram::new(net_ns.clone(), owner);
         Arc::new(Self {
             inner: RwMutex::new(Inner::Unbound(unbound_datagram)),
             options: RwLock::new(OptionSet::new()),
+            memberships: Mutex::new(MulticastMemberships::new()),
+            family,
+            net_ns,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
         })
     }
 
@@ -75,14 +98,10 @@ impl DatagramSocket {
         writer: &mut dyn MultiWrite,
         flags: SendRecvFlags,
     ) -> Result<(usize, SocketAddr)> {
-        let recv_bytes = self
-            .inner
-            .read()
-            .try_recv(writer, flags)
-            .map(|(recv_bytes, remote_endpoint)| (recv_bytes, remote_endpoint.into()))?;
+        let (recv_bytes, remote_endpoint) = self.inner.read().try_recv(writer, flags)?;
         self.pollee.invalidate();
 
-        Ok(recv_bytes)
+        Ok((recv_bytes, self.family.socket_addr(remote_endpoint)))
     }
 
     fn try_send(
@@ -101,11 +120,29 @@ impl DatagramSocket {
                         "the destination address is not specified",
                     )
                 })?;
-                self.inner
-                    .write()
-                    .bind_ephemeral(remote_endpoint, &self.pollee)
+                let mut inner = self.inner.write();
+                if let Inner::Unbound(_) = &*inner
+                    && let Some(local_endpoint) = self.multicast_local_endpoint(remote_endpoint)
+                {
+                    let socket_options = self.options.read().socket.clone();
+                    return inner.bind(&local_endpoint, &self.pollee, socket_options);
+                }
+                inner.bind_ephemeral(remote_endpoint, &self.pollee)
             },
             |bound_datagram, remote_endpoint| {
+                if remote_endpoint.addr.is_multicast() {
+                    let options = self.options.read();
+                    let (hop_limit, multicast_loop) = match remote_endpoint.addr {
+                        IpAddress::Ipv4(_) => {
+                            (options.ip.multicast_ttl(), options.ip.multicast_loop())
+                        }
+                        IpAddress::Ipv6(_) => {
+                            (options.ipv6.multicast_hops(), options.ipv6.multicast_loop())
+                        }
+                    };
+                    bound_datagram.set_multicast_options(hop_limit, multicast_loop);
+                }
+
                 let sent_bytes = bound_datagram.try_send(reader, remote_endpoint, flags)?;
                 let iface_to_poll = bound_datagram.iface().clone();
                 Ok((sent_bytes, iface_to_poll))
@@ -119,6 +156,116 @@ impl DatagramSocket {
     }
 }
 
+impl DatagramSocket {
+    /// Returns the local endpoint that the socket should bind to before sending multicast packets
+    /// to `remote`.
+    ///
+    /// The local endpoint is determined by `IP_MULTICAST_IF` or `IPV6_MULTICAST_IF`. If these
+    /// options are not set, `None` will be returned and the socket will be bound as usual.
+    fn multicast_local_endpoint(&self, remote: &IpEndpoint) -> Option<IpEndpoint> {
+        if !remote.addr.is_multicast() {
+            return None;
+        }
+
+        let options = self.options.read();
+        let local_addr = match remote.addr {
+            IpAddress::Ipv4(_) => {
+                let multicast_if = options.ip.multicast_if();
+                let ipv4_addr = if !multicast_if.addr.is_unspecified() {
+                    multicast_if.addr
+                } else if multicast_if.ifindex != 0 {
+                    self.net_ns
+                        .iface_by_index(multicast_if.ifindex)?
+                        .ipv4_addr()?
+                } else {
+                    return None;
+                };
+                IpAddress::Ipv4(ipv4_addr)
+            }
+            IpAddress::Ipv6(_) => {
+                let multicast_if = options.ipv6.multicast_if();
+                if multicast_if == 0 {
+                    return None;
+                }
+                // Multicast packets are sent from the link-local address, if there is one.
+                let ipv6_addrs = self.net_ns.iface_by_index(multicast_if)?.ipv6_addrs();
+                let ipv6_addr = ipv6_addrs
+                    .iter()
+                    .map(|cidr| cidr.address())
+                    .find(|addr| addr.is_unicast_link_local())
+                    .or_else(|| ipv6_addrs.first().map(|cidr| cidr.address()))?;
+                IpAddress::Ipv6(ipv6_addr)
+            }
+        };
+
+        Some(IpEndpoint::new(local_addr, 0))
+    }
+
+    /// Joins or leaves a multicast group if the option is a membership option.
+    ///
+    /// This method returns `None` if the option is not a membership option. Otherwise, it
+    /// returns the iface that needs to be polled.
+    fn set_membership_option(&self, option: &dyn SocketOption) -> Option<Result<Arc<Iface>>> {
+        let mut memberships = self.memberships.lock();
+
+        sock_option_ref!(match option {
+            add_membership @ AddMembership => {
+                let membership = add_membership.get().unwrap();
+                let group = IpAddress::Ipv4(membership.group);
+                let iface_addr = (!membership.interface.addr.is_unspecified())
+                    .then_some(IpAddress::Ipv4(membership.interface.addr));
+                let res = resolve_multicast_iface(
+                    &self.net_ns,
+                    &group,
+                    membership.interface.ifindex,
+                    iface_addr,
+                )
+                .and_then(|iface| memberships.join(iface.clone(), group).map(|_| iface));
+                Some(res)
+            }
+            drop_membership @ DropMembership => {
+                let membership = drop_membership.get().unwrap();
+                let group = IpAddress::Ipv4(membership.group);
+                let ifindex = if membership.interface.ifindex != 0 {
+                    membership.interface.ifindex
+                } else if !membership.interface.addr.is_unspecified() {
+                    match resolve_multicast_iface(
+                        &self.net_ns,
+                        &group,
+                        0,
+                        Some(IpAddress::Ipv4(membership.interface.addr)),
+                    ) {
+                        Ok(iface) => iface.index(),
+                        Err(err) => return Some(Err(err)),
+                    }
+                } else {
+                    0
+                };
+                Some(memberships.leave(ifindex, group))
+            }
+            add_membership @ Ipv6AddMembership => {
+                if self.family != IpAddressFamily::IPv6 {
+                    return None;
+                }
+                let membership = add_membership.get().unwrap();
+                let group = IpAddress::Ipv6(membership.group);
+                let res = resolve_multicast_iface(&self.net_ns, &group, membership.ifindex, None)
+                    .and_then(|iface| memberships.join(iface.clone(), group).map(|_| iface));
+                Some(res)
+            }
+            drop_membership @ Ipv6DropMembership => {
+                if self.family != IpAddressFamily::IPv6 {
+                    return None;
+                }
+                let membership = drop_membership.get().unwrap();
+                let group = IpAddress::Ipv6(membership.group);
+                Some(memberships.leave(membership.ifindex, group))
+            }
+            _ => None,
+        })
+    }
+}
+
 impl Pollable for DatagramSocket {
     fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
         self.pollee
@@ -137,26 +284,49 @@ impl SocketPrivate for DatagramSocket {
 }
 
 impl Socket for DatagramSocket {
+    fn protocol(&self) -> Protocol {
+        Protocol::IPPROTO_UDP
+    }
+
     fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
-        let can_reuse = self.options.read().socket.reuse_addr();
+        let (endpoint, socket_options) = {
+            let options = self.options.read();
+            let endpoint = self
+                .family
+                .local_endpoint(socket_addr, options.ipv6.v6only())?;
+            (endpoint, options.socket.clone())
+        };
 
         self.inner
             .write()
-            .bind(&endpoint, &self.pollee, BindOptions { can_reuse })
+            .bind(&endpoint, &self.pollee, socket_options)
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
-        let can_broadcast = self.options.read().socket.broadcast();
-        if !can_broadcast && is_broadcast_endpoint(&endpoint) {
+        let (endpoint, can_broadcast) = {
+            let options = self.options.read();
+            let endpoint = self
+                .family
+                .remote_endpoint(socket_addr, options.ipv6.v6only())?;
+            (endpoint, options.socket.broadcast())
+        };
+        if !can_broadcast && self.net_ns.is_broadcast_endpoint(&endpoint) {
             return_errno_with_message!(
                 Errno::EACCES,
                 "connecting to a broadcast address without SO_BROADCAST is not allowed"
             );
         }
 
-        self.inner.write().connect(&endpoint, &self.pollee)
+        let mut inner = self.inner.write();
+        if let Inner::Bound(bound_datagram) = &*inner
+            && !bound_datagram.can_reach(&endpoint)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the remote address is in a different family from the local address"
+            );
+        }
+        inner.connect(&endpoint, &self.pollee)
     }
 
     fn addr(&self) -> Result<SocketAddr> {
@@ -164,9 +334,9 @@ impl Socket for DatagramSocket {
             .inner
             .read()
             .addr()
-            .unwrap_or(UNSPECIFIED_LOCAL_ENDPOINT);
+            .unwrap_or_else(|| self.family.unspecified_endpoint());
 
-        Ok(endpoint.into())
+        Ok(self.family.socket_addr(endpoint))
     }
 
     fn peer_addr(&self) -> Result<SocketAddr> {
@@ -175,7 +345,7 @@ impl Socket for DatagramSocket {
                 Error::with_message(Errno::ENOTCONN, "the socket is not connected")
             })?;
 
-        Ok(endpoint.into())
+        Ok(self.family.socket_addr(endpoint))
     }
 
     fn sendmsg(
@@ -195,13 +365,16 @@ impl Socket for DatagramSocket {
         } = message_header;
 
         let endpoint = match addr {
-            Some(addr) => Some(addr.try_into()?),
+            Some(addr) => {
+                let v6only = self.options.read().ipv6.v6only();
+                Some(self.family.remote_endpoint(addr, v6only)?)
+            }
             None => None,
         };
 
         if let Some(endpoint) = endpoint.as_ref() {
             let can_broadcast = self.options.read().socket.broadcast();
-            if !can_broadcast && is_broadcast_endpoint(endpoint) {
+            if !can_broadcast && self.net_ns.is_broadcast_endpoint(endpoint) {
                 return_errno_with_message!(
                     Errno::EACCES,
                     "sending to a broadcast address without SO_BROADCAST is not allowed"
@@ -258,10 +431,25 @@ impl Socket for DatagramSocket {
         }
 
         // Deal with IP-level options
-        options.ip.get_option(option)
+        match options.ip.get_option(option) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res,
+        }
+
+        // Deal with IPv6-level options
+        if self.family != IpAddressFamily::IPv6 {
+            return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown");
+        }
+        options.ipv6.get_option(option)
     }
 
     fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        // Deal with multicast group memberships
+        if let Some(res) = self.set_membership_option(option) {
+            res?.poll();
+            return Ok(());
+        }
+
         let inner = self.inner.read();
         let mut options = self.options.write();
 
@@ -269,7 +457,17 @@ impl Socket for DatagramSocket {
         let need_iface_poll = match options.socket.set_option(option, &*inner) {
             Err(err) if err.error() == Errno::ENOPROTOOPT => {
                 // Deal with IP-level options
-                options.ip.set_option(option, &*inner)?
+                match options.ip.set_option(option, &*inner) {
+                    Err(err)
+                        if err.error() == Errno::ENOPROTOOPT
+                            && self.family == IpAddressFamily::IPv6 =>
+                    {
+                        // Deal with IPv6-level options
+                        options.ipv6.set_option(option, &*inner)?
+                    }
+                    Err(err) => return Err(err),
+                    Ok(need_iface_poll) => need_iface_poll,
+                }
             }
             Err(err) => return Err(err),
             Ok(need_iface_poll) => need_iface_poll,
@@ -311,6 +509,14 @@ impl SetSocketLevelOption for Inner<UnboundDatagram, BoundDatagram> {
 
         bound.bound_port().set_can_reuse(reuse_addr);
     }
+
+    fn set_reuseport_filter(&self, filter: Option<Arc<SocketFilter>>) -> bool {
+        let Inner::Bound(bound) = self else {
+            return false;
+        };
+
+        bound.bound_port().set_reuseport_selector(filter).is_some()
+    }
 }
 
 impl SetIpLevelOption for Inner<UnboundDatagram, BoundDatagram> {
@@ -321,3 +527,16 @@ impl SetIpLevelOption for Inner<UnboundDatagram, BoundDatagram> {
         );
     }
 }
+
+impl SetIpv6LevelOption for Inner<UnboundDatagram, BoundDatagram> {
+    fn set_v6only(&self, _v6only: bool) -> Result<()> {
+        if matches!(self, Inner::Bound(_)) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IPV6_V6ONLY cannot be changed after the socket is bound"
+            );
+        }
+
+        Ok(())
+    }
+}
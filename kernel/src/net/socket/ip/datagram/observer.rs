@@ -2,13 +2,21 @@
 
 use aster_bigtcp::socket::{SocketEventObserver, SocketEvents};
 
-use crate::{events::IoEvents, process::signal::Pollee};
+use crate::{events::IoEvents, net::socket::util::SocketOwner, process::signal::Pollee};
 
-pub struct DatagramObserver(Pollee);
+pub struct DatagramObserver {
+    pollee: Pollee,
+    owner: SocketOwner,
+}
 
 impl DatagramObserver {
-    pub(super) fn new(pollee: Pollee) -> Self {
-        Self(pollee)
+    pub(in crate::net::socket::ip) fn new(pollee: Pollee, owner: SocketOwner) -> Self {
+        Self { pollee, owner }
+    }
+
+    /// Returns the owner of the socket.
+    pub(in crate::net) fn owner(&self) -> &SocketOwner {
+        &self.owner
     }
 }
 
@@ -24,6 +32,6 @@ impl SocketEventObserver for DatagramObserver {
             io_events |= IoEvents::OUT;
         }
 
-        self.0.notify(io_events);
+        self.pollee.notify(io_events);
     }
 }
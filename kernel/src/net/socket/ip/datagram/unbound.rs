@@ -7,9 +7,10 @@ use crate::{
     events::IoEvents,
     net::{
         iface::BoundUdpPort,
+        net_ns::NetNamespace,
         socket::{
-            ip::common::{get_ephemeral_endpoint, resolve_bind_iface_and_config},
-            util::datagram_common,
+            ip::common::{BindOptions, get_ephemeral_endpoint, resolve_bind_iface_and_config},
+            util::{SocketOwner, datagram_common, options::SocketOptionSet},
         },
     },
     prelude::*,
@@ -17,22 +18,40 @@ use crate::{
 };
 
 pub(super) struct UnboundDatagram {
-    _private: (),
+    net_ns: Arc<NetNamespace>,
+    owner: SocketOwner,
 }
 
 impl UnboundDatagram {
-    pub(super) fn new() -> Self {
-        Self { _private: () }
+    pub(super) fn new(net_ns: Arc<NetNamespace>, owner: SocketOwner) -> Self {
+        Self { net_ns, owner }
     }
-}
 
-pub(super) struct BindOptions {
-    pub(super) can_reuse: bool,
+    fn bind_with_options(
+        &mut self,
+        endpoint: &IpEndpoint,
+        pollee: &Pollee,
+        options: &BindOptions,
+    ) -> Result<BoundDatagram> {
+        let bound_port = bind_port(&self.net_ns, endpoint, options)?;
+
+        let bound_socket = match UdpSocket::new_bind(
+            bound_port,
+            DatagramObserver::new(pollee.clone(), self.owner),
+        ) {
+            Ok(bound_socket) => bound_socket,
+            Err((_, err)) => {
+                unreachable!("`new_bind` fails with {:?}, which should not happen", err)
+            }
+        };
+
+        Ok(BoundDatagram::new(bound_socket))
+    }
 }
 
 impl datagram_common::Unbound for UnboundDatagram {
     type Endpoint = IpEndpoint;
-    type BindOptions = BindOptions;
+    type BindOptions = SocketOptionSet;
 
     type Bound = BoundDatagram;
 
@@ -40,19 +59,10 @@ impl datagram_common::Unbound for UnboundDatagram {
         &mut self,
         endpoint: &Self::Endpoint,
         pollee: &Pollee,
-        options: BindOptions,
+        options: SocketOptionSet,
     ) -> Result<Self::Bound> {
-        let bound_port = bind_port(endpoint, options.can_reuse)?;
-
-        let bound_socket =
-            match UdpSocket::new_bind(bound_port, DatagramObserver::new(pollee.clone())) {
-                Ok(bound_socket) => bound_socket,
-                Err((_, err)) => {
-                    unreachable!("`new_bind` fails with {:?}, which should not happen", err)
-                }
-            };
-
-        Ok(BoundDatagram::new(bound_socket))
+        let bind_options = BindOptions::new(&options, &self.owner).with_multicast();
+        self.bind_with_options(endpoint, pollee, &bind_options)
     }
 
     fn bind_ephemeral(
@@ -60,13 +70,13 @@ impl datagram_common::Unbound for UnboundDatagram {
         remote_endpoint: &Self::Endpoint,
         pollee: &Pollee,
     ) -> Result<Self::Bound> {
-        let endpoint = get_ephemeral_endpoint(remote_endpoint).ok_or_else(|| {
+        let endpoint = get_ephemeral_endpoint(&self.net_ns, remote_endpoint).ok_or_else(|| {
             Error::with_message(
                 Errno::EADDRNOTAVAIL,
                 "no interface has an address for the specified family",
             )
         })?;
-        self.bind(&endpoint, pollee, BindOptions { can_reuse: false })
+        self.bind_with_options(&endpoint, pollee, &BindOptions::default())
     }
 
     fn check_io_events(&self) -> IoEvents {
@@ -74,7 +84,13 @@ impl datagram_common::Unbound for UnboundDatagram {
     }
 }
 
-fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundUdpPort> {
-    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse)?;
-    Ok(iface.bind_udp(config)?)
+fn bind_port(
+    net_ns: &NetNamespace,
+    endpoint: &IpEndpoint,
+    options: &BindOptions,
+) -> Result<BoundUdpPort> {
+    let (iface, config) = resolve_bind_iface_and_config(net_ns, endpoint, options)?;
+    let bound_port = iface.bind_udp(config)?;
+    options.apply(&bound_port);
+    Ok(bound_port)
 }
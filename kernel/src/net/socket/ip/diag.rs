@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    socket::{SocketInfo, TcpState},
+    wire::{IpAddress, IpEndpoint, Ipv4Address},
+};
+
+use crate::{
+    net::{net_ns::NetNamespace, socket::util::SocketOwner},
+    prelude::*,
+};
+
+/// A snapshot of the state of a TCP or UDP socket.
+///
+/// This is used to monitor sockets (e.g., via `sock_diag` or `/proc/net/tcp`).
+pub struct InetSocketInfo {
+    pub protocol: InetSocketProtocol,
+    pub state: InetSocketState,
+    pub local_endpoint: IpEndpoint,
+    /// The remote endpoint.
+    ///
+    /// This is the unspecified endpoint if the socket is not connected.
+    pub remote_endpoint: IpEndpoint,
+    pub recv_queue: usize,
+    pub send_queue: usize,
+    /// The owner of the socket.
+    ///
+    /// This is `None` if the socket does not have a socket file (e.g., a TCP connection that
+    /// has not been accepted yet).
+    pub owner: Option<SocketOwner>,
+}
+
+/// The transport protocol of an inet socket.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InetSocketProtocol {
+    Tcp,
+    Udp,
+}
+
+/// The state of an inet socket.
+///
+/// The values are the same as the TCP states in Linux, which are also used for UDP sockets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/tcp_states.h#L12>.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InetSocketState {
+    Established = 1,
+    SynSent = 2,
+    SynRecv = 3,
+    FinWait1 = 4,
+    FinWait2 = 5,
+    TimeWait = 6,
+    Close = 7,
+    CloseWait = 8,
+    LastAck = 9,
+    Listen = 10,
+    Closing = 11,
+}
+
+impl From<TcpState> for InetSocketState {
+    fn from(value: TcpState) -> Self {
+        match value {
+            TcpState::Closed => Self::Close,
+            TcpState::Listen => Self::Listen,
+            TcpState::SynSent => Self::SynSent,
+            TcpState::SynReceived => Self::SynRecv,
+            TcpState::Established => Self::Established,
+            TcpState::FinWait1 => Self::FinWait1,
+            TcpState::FinWait2 => Self::FinWait2,
+            TcpState::CloseWait => Self::CloseWait,
+            TcpState::Closing => Self::Closing,
+            TcpState::LastAck => Self::LastAck,
+            TcpState::TimeWait => Self::TimeWait,
+        }
+    }
+}
+
+/// Returns the snapshots of all the TCP and UDP sockets in the network namespace.
+pub fn inet_socket_infos(net_ns: &NetNamespace) -> Vec<InetSocketInfo> {
+    let unspecified_endpoint = IpEndpoint::new(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED), 0);
+
+    let mut infos = Vec::new();
+    for iface in net_ns.ifaces() {
+        iface.visit_sockets(|socket| {
+            let info = match socket {
+                SocketInfo::Tcp(tcp) => InetSocketInfo {
+                    protocol: InetSocketProtocol::Tcp,
+                    state: InetSocketState::from(tcp.state),
+                    local_endpoint: tcp.local_endpoint,
+                    remote_endpoint: tcp.remote_endpoint.unwrap_or(unspecified_endpoint),
+                    recv_queue: tcp.recv_queue,
+                    send_queue: tcp.send_queue,
+                    owner: tcp.observer.map(|observer| *observer.owner()),
+                },
+                // TODO: Report the remote endpoints of connected UDP sockets.
+                SocketInfo::Udp(udp) => InetSocketInfo {
+                    protocol: InetSocketProtocol::Udp,
+                    state: InetSocketState::Close,
+                    local_endpoint: udp.local_endpoint,
+                    remote_endpoint: unspecified_endpoint,
+                    recv_queue: udp.recv_queue,
+                    send_queue: udp.send_queue,
+                    owner: udp.observer.map(|observer| *observer.owner()),
+                },
+            };
+            infos.push(info);
+        });
+    }
+
+    infos
+}
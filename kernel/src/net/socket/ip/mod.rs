@@ -3,11 +3,16 @@
 mod addr;
 mod common;
 mod datagram;
+mod diag;
+mod multicast;
 pub mod options;
+mod raw;
 mod stream;
 
 pub use addr::IpAddressFamily;
 pub use datagram::DatagramSocket;
 pub(in crate::net) use datagram::observer::DatagramObserver;
+pub use diag::{InetSocketInfo, InetSocketProtocol, InetSocketState, inet_socket_infos};
+pub use raw::RawSocket;
 pub(in crate::net) use stream::observer::StreamObserver;
 pub use stream::{StreamSocket, options as stream_options};
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::IpAddress;
+
+use crate::{net::iface::Iface, prelude::*};
+
+/// The multicast groups joined by a socket.
+///
+/// All the groups are left when the memberships are dropped (e.g., when the socket is closed).
+pub(super) struct MulticastMemberships {
+    groups: Vec<(Arc<Iface>, IpAddress)>,
+}
+
+/// The maximum number of multicast groups that a socket can join.
+///
+/// This is the default value of `net.ipv4.igmp_max_memberships` in Linux.
+const MAX_MEMBERSHIPS: usize = 20;
+
+impl MulticastMemberships {
+    pub(super) const fn new() -> Self {
+        Self { groups: Vec::new() }
+    }
+
+    /// Joins the multicast group on the iface.
+    ///
+    /// Polling the iface is required after this method succeeds, since a membership report may
+    /// need to be sent.
+    pub(super) fn join(&mut self, iface: Arc<Iface>, group: IpAddress) -> Result<()> {
+        if !group.is_multicast() {
+            return_errno_with_message!(Errno::EINVAL, "the address is not a multicast address");
+        }
+        if self.groups.iter().any(|(joined_iface, joined_group)| {
+            joined_iface.index() == iface.index() && *joined_group == group
+        }) {
+            return_errno_with_message!(Errno::EADDRINUSE, "the multicast group is already joined");
+        }
+        if self.groups.len() >= MAX_MEMBERSHIPS {
+            return_errno_with_message!(Errno::ENOBUFS, "too many multicast groups are joined");
+        }
+
+        iface.join_multicast_group(group);
+        self.groups.push((iface, group));
+
+        Ok(())
+    }
+
+    /// Leaves the multicast group and returns the iface that the group is joined on.
+    ///
+    /// If `ifindex` is zero, the group joined on any iface will be left.
+    ///
+    /// Polling the iface is required after this method succeeds, since a leave report may need to
+    /// be sent.
+    pub(super) fn leave(&mut self, ifindex: u32, group: IpAddress) -> Result<Arc<Iface>> {
+        let Some(pos) = self.groups.iter().position(|(joined_iface, joined_group)| {
+            (ifindex == 0 || joined_iface.index() == ifindex) && *joined_group == group
+        }) else {
+            return_errno_with_message!(Errno::EADDRNOTAVAIL, "the multicast group is not joined");
+        };
+
+        let (iface, group) = self.groups.swap_remove(pos);
+        iface.leave_multicast_group(group);
+
+        Ok(iface)
+    }
+}
+
+impl Drop for MulticastMemberships {
+    fn drop(&mut self) {
+        // The leave reports will be sent the next time the ifaces are polled.
+        for (iface, group) in self.groups.drain(..) {
+            iface.leave_multicast_group(group);
+        }
+    }
+}
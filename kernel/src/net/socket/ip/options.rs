@@ -2,7 +2,10 @@
 
 use core::num::NonZeroU8;
 
-use aster_bigtcp::socket::NeedIfacePoll;
+use aster_bigtcp::{
+    socket::NeedIfacePoll,
+    wire::{IpProtocol, Ipv4Address, Ipv6Address},
+};
 
 use crate::{
     net::socket::options::{
@@ -10,6 +13,7 @@ use crate::{
         macros::{impl_socket_options, sock_option_mut, sock_option_ref},
     },
     prelude::*,
+    util::net::Protocol,
 };
 
 /// IP-level socket options.
@@ -21,9 +25,17 @@ pub(super) struct IpOptionSet {
     ttl: IpTtl,
     hdrincl: bool,
     recverr: bool,
+    multicast_if: IpMulticastIf,
+    multicast_ttl: u8,
+    multicast_loop: bool,
 }
 
 const DEFAULT_TTL: u8 = 64;
+/// The default TTL (or hop limit) of multicast packets.
+///
+/// Multicast packets are restricted to the same subnet by default. See
+/// <https://man7.org/linux/man-pages/man7/ip.7.html>.
+const DEFAULT_MULTICAST_TTL: u8 = 1;
 pub(super) const INET_ECN_MASK: u8 = 3;
 
 impl IpOptionSet {
@@ -33,6 +45,9 @@ impl IpOptionSet {
             ttl: IpTtl(None),
             hdrincl: false,
             recverr: false,
+            multicast_if: IpMulticastIf::UNSPECIFIED,
+            multicast_ttl: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
         }
     }
 
@@ -42,6 +57,22 @@ impl IpOptionSet {
             ttl: IpTtl(None),
             hdrincl: false,
             recverr: false,
+            multicast_if: IpMulticastIf::UNSPECIFIED,
+            multicast_ttl: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
+        }
+    }
+
+    pub(super) fn new_raw(protocol: IpProtocol) -> Self {
+        Self {
+            tos: 0,
+            ttl: IpTtl(None),
+            // Like Linux, `IP_HDRINCL` is implied for `IPPROTO_RAW` sockets.
+            hdrincl: u8::from(protocol) == Protocol::IPPROTO_RAW as u8,
+            recverr: false,
+            multicast_if: IpMulticastIf::UNSPECIFIED,
+            multicast_ttl: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
         }
     }
 
@@ -63,6 +94,18 @@ impl IpOptionSet {
                 let recverr = self.recverr();
                 ip_recverr.set(recverr);
             }
+            ip_multicast_if @ MulticastIf => {
+                let multicast_if = self.multicast_if();
+                ip_multicast_if.set(multicast_if);
+            }
+            ip_multicast_ttl @ MulticastTtl => {
+                let multicast_ttl = self.multicast_ttl();
+                ip_multicast_ttl.set(IntOrByte(multicast_ttl as _));
+            }
+            ip_multicast_loop @ MulticastLoop => {
+                let multicast_loop = self.multicast_loop();
+                ip_multicast_loop.set(IntOrByte(multicast_loop as _));
+            }
             _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown"),
         });
 
@@ -95,6 +138,22 @@ impl IpOptionSet {
                 let recverr = ip_recverr.get().unwrap();
                 self.set_recverr(*recverr);
             }
+            ip_multicast_if @ MulticastIf => {
+                let multicast_if = ip_multicast_if.get().unwrap();
+                self.set_multicast_if(*multicast_if);
+            }
+            ip_multicast_ttl @ MulticastTtl => {
+                let multicast_ttl = match ip_multicast_ttl.get().unwrap().0 {
+                    -1 => DEFAULT_MULTICAST_TTL,
+                    ttl @ 0..=255 => ttl as u8,
+                    _ => return_errno_with_message!(Errno::EINVAL, "the multicast TTL is invalid"),
+                };
+                self.set_multicast_ttl(multicast_ttl);
+            }
+            ip_multicast_loop @ MulticastLoop => {
+                let multicast_loop = ip_multicast_loop.get().unwrap().0 != 0;
+                self.set_multicast_loop(multicast_loop);
+            }
             _ => return_errno_with_message!(
                 Errno::ENOPROTOOPT,
                 "the socket option to be set is unknown"
@@ -110,8 +169,44 @@ impl_socket_options!(
     pub struct Ttl(IpTtl);
     pub struct Hdrincl(bool);
     pub struct Recverr(bool);
+    pub struct MulticastIf(IpMulticastIf);
+    pub struct MulticastTtl(IntOrByte);
+    pub struct MulticastLoop(IntOrByte);
+    pub struct AddMembership(IpMembership);
+    pub struct DropMembership(IpMembership);
 );
 
+/// An integer option value that can also be given as a single byte.
+///
+/// Like Linux, `IP_MULTICAST_TTL` and `IP_MULTICAST_LOOP` accept both an `int` and an `unsigned
+/// char` for historical reasons.
+#[derive(Clone, Copy, Debug)]
+pub struct IntOrByte(pub i32);
+
+/// The interface used to send IPv4 multicast packets.
+///
+/// The interface can be specified either by its address or by its index. If neither is
+/// specified, the interface is selected by the routing table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpMulticastIf {
+    pub addr: Ipv4Address,
+    pub ifindex: u32,
+}
+
+impl IpMulticastIf {
+    pub const UNSPECIFIED: Self = Self {
+        addr: Ipv4Address::UNSPECIFIED,
+        ifindex: 0,
+    };
+}
+
+/// An IPv4 multicast group membership (`struct ip_mreq` or `struct ip_mreqn`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpMembership {
+    pub group: Ipv4Address,
+    pub interface: IpMulticastIf,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct IpTtl(Option<NonZeroU8>);
 
@@ -132,3 +227,122 @@ impl IpTtl {
 pub(super) trait SetIpLevelOption {
     fn set_hdrincl(&self, _hdrincl: bool) -> Result<()>;
 }
+
+/// IPv6-level socket options.
+#[derive(Clone, Copy, CopyGetters, Debug, Setters)]
+#[get_copy = "pub"]
+#[set = "pub"]
+pub(super) struct Ipv6OptionSet {
+    v6only: bool,
+    multicast_if: u32,
+    multicast_hops: u8,
+    multicast_loop: bool,
+}
+
+impl Ipv6OptionSet {
+    pub(super) const fn new_udp() -> Self {
+        Self {
+            // Dual-stack sockets are the default in Linux (i.e., `net.ipv6.bindv6only` is 0).
+            v6only: false,
+            multicast_if: 0,
+            multicast_hops: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
+        }
+    }
+
+    pub(super) const fn new_raw() -> Self {
+        Self {
+            v6only: false,
+            multicast_if: 0,
+            multicast_hops: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
+        }
+    }
+
+    pub(super) fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        sock_option_mut!(match option {
+            ipv6_v6only @ V6Only => {
+                let v6only = self.v6only();
+                ipv6_v6only.set(v6only);
+            }
+            ipv6_multicast_if @ Ipv6MulticastIf => {
+                let multicast_if = self.multicast_if();
+                ipv6_multicast_if.set(multicast_if as _);
+            }
+            ipv6_multicast_hops @ Ipv6MulticastHops => {
+                let multicast_hops = self.multicast_hops();
+                ipv6_multicast_hops.set(multicast_hops as _);
+            }
+            ipv6_multicast_loop @ Ipv6MulticastLoop => {
+                let multicast_loop = self.multicast_loop();
+                ipv6_multicast_loop.set(multicast_loop);
+            }
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown"),
+        });
+
+        Ok(())
+    }
+
+    pub(super) fn set_option(
+        &mut self,
+        option: &dyn SocketOption,
+        socket: &dyn SetIpv6LevelOption,
+    ) -> Result<NeedIfacePoll> {
+        sock_option_ref!(match option {
+            ipv6_v6only @ V6Only => {
+                let v6only = ipv6_v6only.get().unwrap();
+                socket.set_v6only(*v6only)?;
+                self.set_v6only(*v6only);
+            }
+            ipv6_multicast_if @ Ipv6MulticastIf => {
+                let Ok(multicast_if) = u32::try_from(*ipv6_multicast_if.get().unwrap()) else {
+                    return_errno_with_message!(Errno::EINVAL, "the interface index is invalid");
+                };
+                self.set_multicast_if(multicast_if);
+            }
+            ipv6_multicast_hops @ Ipv6MulticastHops => {
+                let multicast_hops = match *ipv6_multicast_hops.get().unwrap() {
+                    -1 => DEFAULT_MULTICAST_TTL,
+                    hops @ 0..=255 => hops as u8,
+                    _ => return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the multicast hop limit is invalid"
+                    ),
+                };
+                self.set_multicast_hops(multicast_hops);
+            }
+            ipv6_multicast_loop @ Ipv6MulticastLoop => {
+                let multicast_loop = ipv6_multicast_loop.get().unwrap();
+                self.set_multicast_loop(*multicast_loop);
+            }
+            _ => return_errno_with_message!(
+                Errno::ENOPROTOOPT,
+                "the socket option to be set is unknown"
+            ),
+        });
+
+        Ok(NeedIfacePoll::FALSE)
+    }
+}
+
+impl_socket_options!(
+    pub struct V6Only(bool);
+    pub struct Ipv6MulticastIf(i32);
+    pub struct Ipv6MulticastHops(i32);
+    pub struct Ipv6MulticastLoop(bool);
+    pub struct Ipv6AddMembership(Ipv6Membership);
+    pub struct Ipv6DropMembership(Ipv6Membership);
+);
+
+/// An IPv6 multicast group membership (`struct ipv6_mreq`).
+///
+/// If the interface index is zero, the interface is selected by the routing table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ipv6Membership {
+    pub group: Ipv6Address,
+    pub ifindex: u32,
+}
+
+pub(super) trait SetIpv6LevelOption {
+    fn set_v6only(&self, _v6only: bool) -> Result<()>;
+}
@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::Ordering;
+
+use aster_bigtcp::{
+    errors::raw::{RecvError, SendError},
+    wire::{IpAddress, IpEndpoint},
+};
+
+use super::SendOptions;
+use crate::{
+    events::IoEvents,
+    net::{
+        iface::{Iface, RawSocket},
+        net_ns::NetNamespace,
+        socket::{
+            ip::{
+                IpAddressFamily,
+                common::{get_ephemeral_endpoint, get_ephemeral_iface},
+            },
+            util::{SendRecvFlags, datagram_common},
+        },
+    },
+    prelude::*,
+    util::{MultiRead, MultiWrite},
+};
+
+pub(super) struct BoundRaw {
+    /// The sockets on the ifaces.
+    ///
+    /// A socket bound to a specific address has a single socket on the iface with the address.
+    /// A raw IP socket bound to the unspecified address has one socket on each iface, so that it
+    /// receives packets to any local address on all ifaces.
+    bound_sockets: Vec<RawSocket>,
+    net_ns: Arc<NetNamespace>,
+    remote_endpoint: Option<IpEndpoint>,
+    send_options: Arc<SendOptions>,
+    is_ephemeral: bool,
+}
+
+impl BoundRaw {
+    pub(super) fn new(
+        bound_sockets: Vec<RawSocket>,
+        net_ns: Arc<NetNamespace>,
+        send_options: Arc<SendOptions>,
+        is_ephemeral: bool,
+    ) -> Self {
+        debug_assert!(!bound_sockets.is_empty());
+
+        Self {
+            bound_sockets,
+            net_ns,
+            remote_endpoint: None,
+            send_options,
+            is_ephemeral,
+        }
+    }
+
+    /// Returns whether the socket is bound implicitly rather than by `bind`.
+    ///
+    /// Implicitly bound sockets can be rebound to another iface if the destination of a later
+    /// packet is reached via that iface.
+    pub(super) fn is_ephemeral(&self) -> bool {
+        self.is_ephemeral
+    }
+
+    /// Returns whether the socket is bound to the unspecified address.
+    ///
+    /// Such sockets are bound to all ifaces and can be rebound to a specific address later.
+    pub(super) fn is_wildcard(&self) -> bool {
+        self.local_addr().is_unspecified()
+    }
+
+    /// Returns whether the remote endpoint is in the same family as the local endpoint.
+    pub(super) fn can_reach(&self, remote: &IpEndpoint) -> bool {
+        IpAddressFamily::from(self.local_addr()) == IpAddressFamily::from(remote.addr)
+    }
+
+    fn local_addr(&self) -> IpAddress {
+        *self.bound_sockets[0].bound_port().addr()
+    }
+
+    /// Sends a packet to the remote endpoint and returns the iface that needs to be polled.
+    pub(super) fn try_send_to(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: &IpEndpoint,
+        _flags: SendRecvFlags,
+    ) -> Result<(usize, &Arc<Iface>)> {
+        if !self.can_reach(remote) {
+            return_errno_with_message!(
+                Errno::ENETUNREACH,
+                "the remote address is in a different family from the local address"
+            );
+        }
+
+        let (bound_socket, src_addr) = self.select_socket(remote)?;
+
+        let mut data = vec![0; reader.sum_lens()];
+        let len = reader.read(&mut VmWriter::from(data.as_mut_slice()))?;
+        data.truncate(len);
+
+        let hop_limit = self.send_options.ttl.load(Ordering::Relaxed);
+        let hdrincl = self.send_options.hdrincl.load(Ordering::Relaxed);
+
+        match bound_socket.send(src_addr, remote.addr, hop_limit, hdrincl, data) {
+            Ok(()) => Ok((len, bound_socket.iface())),
+            Err(SendError::Unaddressable) => {
+                return_errno_with_message!(Errno::EINVAL, "the destination address is invalid");
+            }
+            Err(SendError::Malformed) => {
+                return_errno_with_message!(Errno::EINVAL, "the packet header is invalid");
+            }
+            Err(SendError::TooLarge) => {
+                return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+            }
+            Err(SendError::BufferFull) => {
+                return_errno_with_message!(Errno::EAGAIN, "the send buffer is full");
+            }
+        }
+    }
+
+    /// Selects the socket to send packets to the remote endpoint and the source address.
+    fn select_socket(&self, remote: &IpEndpoint) -> Result<(&RawSocket, IpAddress)> {
+        if !self.is_wildcard() {
+            return Ok((&self.bound_sockets[0], self.local_addr()));
+        }
+
+        let unreachable = || {
+            Error::with_message(
+                Errno::ENETUNREACH,
+                "no interface has an address to reach the remote address",
+            )
+        };
+
+        let src_addr = get_ephemeral_endpoint(&self.net_ns, remote)
+            .ok_or_else(unreachable)?
+            .addr;
+        let iface = get_ephemeral_iface(&self.net_ns, &remote.addr);
+        // FIXME: Sockets bound to all ifaces should also be bound to ifaces that are added later.
+        let bound_socket = self
+            .bound_sockets
+            .iter()
+            .find(|socket| socket.iface().index() == iface.index())
+            .ok_or_else(unreachable)?;
+
+        Ok((bound_socket, src_addr))
+    }
+}
+
+impl datagram_common::Bound for BoundRaw {
+    type Endpoint = IpEndpoint;
+
+    fn local_endpoint(&self) -> Self::Endpoint {
+        self.bound_sockets[0].local_endpoint().unwrap()
+    }
+
+    fn remote_endpoint(&self) -> Option<&Self::Endpoint> {
+        self.remote_endpoint.as_ref()
+    }
+
+    fn set_remote_endpoint(&mut self, endpoint: &Self::Endpoint) {
+        self.remote_endpoint = Some(*endpoint)
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        _flags: SendRecvFlags,
+    ) -> Result<(usize, Self::Endpoint)> {
+        for bound_socket in self.bound_sockets.iter() {
+            let result = bound_socket.recv(|packet, src_addr| {
+                let copied_res = writer
+                    .write(&mut VmReader::from(packet))
+                    .map_err(Into::into);
+                // Like Linux, the port of the source address is always zero.
+                (copied_res, IpEndpoint::new(src_addr, 0))
+            });
+
+            match result {
+                Ok((Ok(res), endpoint)) => return Ok((res, endpoint)),
+                Ok((Err(e), _)) => return Err(e),
+                Err(RecvError::Exhausted) => (),
+            }
+        }
+
+        return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty")
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: &Self::Endpoint,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        self.try_send_to(reader, remote, flags).map(|(len, _)| len)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+
+        if self
+            .bound_sockets
+            .iter()
+            .any(|bound_socket| bound_socket.can_recv())
+        {
+            events |= IoEvents::IN;
+        }
+
+        if self
+            .bound_sockets
+            .iter()
+            .any(|bound_socket| bound_socket.can_send())
+        {
+            events |= IoEvents::OUT;
+        }
+
+        events
+    }
+}
@@ -0,0 +1,492 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use aster_bigtcp::{
+    socket::RawSocketKind,
+    wire::{IpEndpoint, IpProtocol},
+};
+use bound::BoundRaw;
+use unbound::UnboundRaw;
+
+use super::{
+    IpAddressFamily,
+    common::get_ephemeral_endpoint,
+    options::{IpOptionSet, Ipv6OptionSet, SetIpLevelOption, SetIpv6LevelOption},
+};
+use crate::{
+    events::IoEvents,
+    fs::{pseudofs::SockFs, vfs::path::Path},
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            options::{Error as SocketError, SocketOption, macros::sock_option_mut},
+            private::SocketPrivate,
+            util::{
+                MessageHeader, SendRecvFlags, SocketAddr, SocketOwner,
+                datagram_common::{Bound, Inner, select_remote_and_bind},
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
+        },
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    util::{MultiRead, MultiWrite},
+};
+
+mod bound;
+mod unbound;
+
+/// A raw IP socket (`SOCK_RAW`) or an ICMP ping socket (`SOCK_DGRAM` with `IPPROTO_ICMP` or
+/// `IPPROTO_ICMPV6`).
+pub struct RawSocket {
+    // Lock order: `inner` first, `options` second
+    inner: RwMutex<Inner<UnboundRaw, BoundRaw>>,
+    options: RwLock<OptionSet>,
+    send_options: Arc<SendOptions>,
+
+    kind: RawSocketKind,
+    protocol: IpProtocol,
+    family: IpAddressFamily,
+    net_ns: Arc<NetNamespace>,
+    is_nonblocking: AtomicBool,
+    pollee: Pollee,
+    pseudo_path: Path,
+    owner: SocketOwner,
+}
+
+#[derive(Clone, Debug)]
+struct OptionSet {
+    socket: SocketOptionSet,
+    ip: IpOptionSet,
+    ipv6: Ipv6OptionSet,
+}
+
+impl OptionSet {
+    fn new(protocol: IpProtocol) -> Self {
+        let socket = SocketOptionSet::new_udp();
+        let ip = IpOptionSet::new_raw(protocol);
+        let ipv6 = Ipv6OptionSet::new_raw();
+        OptionSet { socket, ip, ipv6 }
+    }
+}
+
+/// The IP-level options that are needed to build outgoing packets.
+///
+/// They are duplicated from [`IpOptionSet`] so that they can be read without locking the option
+/// set while the socket is being used to send packets.
+struct SendOptions {
+    ttl: AtomicU8,
+    hdrincl: AtomicBool,
+}
+
+impl SendOptions {
+    fn new(ip_options: &IpOptionSet) -> Self {
+        Self {
+            ttl: AtomicU8::new(ip_options.ttl().get()),
+            hdrincl: AtomicBool::new(ip_options.hdrincl()),
+        }
+    }
+
+    fn update(&self, ip_options: &IpOptionSet) {
+        self.ttl.store(ip_options.ttl().get(), Ordering::Relaxed);
+        self.hdrincl.store(ip_options.hdrincl(), Ordering::Relaxed);
+    }
+}
+
+impl RawSocket {
+    /// Creates a raw IP socket.
+    ///
+    /// The caller must have checked that the current thread has `CAP_NET_RAW`.
+    pub fn new_raw(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        protocol: IpProtocol,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        let socket = Self::new(is_nonblocking, RawSocketKind::Ip, family, protocol, net_ns);
+
+        // Raw IP sockets receive packets even if they are not bound, so bind them to all ifaces
+        // now.
+        socket
+            .inner
+            .write()
+            .bind_ephemeral(&family.unspecified_endpoint(), &socket.pollee)
+            .expect("binding raw IP sockets to all ifaces should never fail");
+
+        socket
+    }
+
+    /// Creates an ICMP ping socket.
+    ///
+    /// The caller must have checked that the current thread is allowed to create ping sockets.
+    pub fn new_ping(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        let protocol = match family {
+            IpAddressFamily::IPv4 => IpProtocol::Icmp,
+            IpAddressFamily::IPv6 => IpProtocol::Icmpv6,
+        };
+        Self::new(
+            is_nonblocking,
+            RawSocketKind::Ping,
+            family,
+            protocol,
+            net_ns,
+        )
+    }
+
+    fn new(
+        is_nonblocking: bool,
+        kind: RawSocketKind,
+        family: IpAddressFamily,
+        protocol: IpProtocol,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        let options = OptionSet::new(protocol);
+        let send_options = Arc::new(SendOptions::new(&options.ip));
+        let pseudo_path = SockFs::new_path();
+        let owner = SocketOwner::new_current(&pseudo_path);
+        let unbound_raw =
+            UnboundRaw::new(net_ns.clone(), kind, protocol, send_options.clone(), owner);
+
+        Arc::new(Self {
+            inner: RwMutex::new(Inner::Unbound(unbound_raw)),
+            options: RwLock::new(options),
+            send_options,
+            kind,
+            protocol,
+            family,
+            net_ns,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            pollee: Pollee::new(),
+            pseudo_path,
+            owner,
+        })
+    }
+
+    fn new_unbound(&self) -> UnboundRaw {
+        UnboundRaw::new(
+            self.net_ns.clone(),
+            self.kind,
+            self.protocol,
+            self.send_options.clone(),
+            self.owner,
+        )
+    }
+
+    /// Unbinds the socket if it is bound implicitly to an iface other than the one that
+    /// `remote_endpoint` should be reached via.
+    ///
+    /// Since each ping socket is bound to a single iface, this allows an unbound socket to send
+    /// packets to different ifaces (e.g., to ping both the loopback address and a remote host).
+    /// Raw IP sockets are bound to all ifaces, so they are never unbound.
+    fn unbind_ephemeral_if_unreachable(&self, remote_endpoint: &IpEndpoint) {
+        let is_unreachable = |inner: &Inner<UnboundRaw, BoundRaw>| {
+            let Inner::Bound(bound_raw) = inner else {
+                return false;
+            };
+            bound_raw.is_ephemeral()
+                && !bound_raw.is_wildcard()
+                && get_ephemeral_endpoint(&self.net_ns, remote_endpoint)
+                    .is_some_and(|endpoint| endpoint.addr != bound_raw.local_endpoint().addr)
+        };
+
+        if !is_unreachable(&self.inner.read()) {
+            return;
+        }
+
+        let mut inner = self.inner.write();
+        if is_unreachable(&inner) {
+            *inner = Inner::Unbound(self.new_unbound());
+        }
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, SocketAddr)> {
+        let (recv_bytes, remote_endpoint) = self.inner.read().try_recv(writer, flags)?;
+        self.pollee.invalidate();
+
+        Ok((recv_bytes, self.family.socket_addr(remote_endpoint)))
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: Option<&IpEndpoint>,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        if let Some(remote_endpoint) = remote {
+            self.unbind_ephemeral_if_unreachable(remote_endpoint);
+        }
+
+        let (sent_bytes, iface_to_poll) = select_remote_and_bind(
+            &self.inner,
+            remote,
+            || {
+                let remote_endpoint = remote.ok_or_else(|| {
+                    Error::with_message(
+                        Errno::EDESTADDRREQ,
+                        "the destination address is not specified",
+                    )
+                })?;
+                self.inner
+                    .write()
+                    .bind_ephemeral(remote_endpoint, &self.pollee)
+            },
+            |bound_raw, remote_endpoint| {
+                let (sent_bytes, iface_to_poll) =
+                    bound_raw.try_send_to(reader, remote_endpoint, flags)?;
+                Ok((sent_bytes, iface_to_poll.clone()))
+            },
+        )?;
+
+        self.pollee.invalidate();
+        iface_to_poll.poll();
+
+        Ok(sent_bytes)
+    }
+}
+
+impl Pollable for RawSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.inner.read().check_io_events())
+    }
+}
+
+impl SocketPrivate for RawSocket {
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, is_nonblocking: bool) {
+        self.is_nonblocking.store(is_nonblocking, Ordering::Relaxed);
+    }
+}
+
+impl Socket for RawSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let endpoint = {
+            let options = self.options.read();
+            self.family
+                .local_endpoint(socket_addr, options.ipv6.v6only())?
+        };
+
+        let mut inner = self.inner.write();
+        // Raw IP sockets are bound to the unspecified address when they are created, so binding
+        // them again only changes the local address.
+        if self.kind == RawSocketKind::Ip
+            && let Inner::Bound(bound_raw) = &*inner
+            && bound_raw.is_wildcard()
+        {
+            *inner = Inner::Unbound(self.new_unbound());
+        }
+        inner.bind(&endpoint, &self.pollee, ())
+    }
+
+    fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        let endpoint = {
+            let options = self.options.read();
+            self.family
+                .remote_endpoint(socket_addr, options.ipv6.v6only())?
+        };
+
+        self.unbind_ephemeral_if_unreachable(&endpoint);
+
+        let mut inner = self.inner.write();
+        if let Inner::Bound(bound_raw) = &*inner
+            && !bound_raw.can_reach(&endpoint)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the remote address is in a different family from the local address"
+            );
+        }
+        inner.connect(&endpoint, &self.pollee)
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let endpoint = self
+            .inner
+            .read()
+            .addr()
+            .unwrap_or_else(|| self.family.unspecified_endpoint());
+
+        Ok(self.family.socket_addr(endpoint))
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        let endpoint =
+            *self.inner.read().peer_addr().ok_or_else(|| {
+                Error::with_message(Errno::ENOTCONN, "the socket is not connected")
+            })?;
+
+        Ok(self.family.socket_addr(endpoint))
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        let endpoint = match addr {
+            Some(addr) => {
+                let v6only = self.options.read().ipv6.v6only();
+                Some(self.family.remote_endpoint(addr, v6only)?)
+            }
+            None => None,
+        };
+
+        if let Some(endpoint) = endpoint.as_ref() {
+            let can_broadcast = self.options.read().socket.broadcast();
+            if !can_broadcast && self.net_ns.is_broadcast_endpoint(endpoint) {
+                return_errno_with_message!(
+                    Errno::EACCES,
+                    "sending to a broadcast address without SO_BROADCAST is not allowed"
+                );
+            }
+        }
+
+        if !control_messages.is_empty() {
+            // TODO: Support sending control message
+            warn!("sending control message is not supported");
+        }
+
+        // TODO: Block if the send buffer is full
+        self.try_send(reader, endpoint.as_ref(), flags)
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let (received_bytes, peer_addr) =
+            self.block_on(IoEvents::IN, || self.try_recv(writer, flags))?;
+
+        // TODO: Receive control message
+
+        let message_header = MessageHeader::new(Some(peer_addr), Vec::new());
+
+        Ok((received_bytes, message_header))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        sock_option_mut!(match option {
+            socket_errors @ SocketError => {
+                // TODO: Support socket errors for raw sockets
+                socket_errors.set(None);
+                return Ok(());
+            }
+            _ => (),
+        });
+
+        let inner = self.inner.read();
+        let options = self.options.read();
+
+        // Deal with socket-level options
+        match options.socket.get_option(option, &*inner) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res,
+        }
+
+        // Deal with IP-level options
+        match options.ip.get_option(option) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res,
+        }
+
+        // Deal with IPv6-level options
+        if self.family != IpAddressFamily::IPv6 {
+            return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown");
+        }
+        options.ipv6.get_option(option)
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        let inner = self.inner.read();
+        let mut options = self.options.write();
+
+        // Deal with socket-level options
+        match options.socket.set_option(option, &*inner) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res.map(|_| ()),
+        }
+
+        // Deal with IP-level options
+        match options.ip.set_option(option, self) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => {
+                self.send_options.update(&options.ip);
+                return res.map(|_| ());
+            }
+        }
+
+        // Deal with IPv6-level options
+        if self.family != IpAddressFamily::IPv6 {
+            return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown");
+        }
+        options.ipv6.set_option(option, &*inner).map(|_| ())
+    }
+
+    fn pseudo_path(&self) -> &Path {
+        &self.pseudo_path
+    }
+}
+
+impl GetSocketLevelOption for Inner<UnboundRaw, BoundRaw> {
+    fn is_listening(&self) -> bool {
+        false
+    }
+}
+
+impl SetSocketLevelOption for Inner<UnboundRaw, BoundRaw> {}
+
+impl SetIpLevelOption for RawSocket {
+    fn set_hdrincl(&self, _hdrincl: bool) -> Result<()> {
+        if self.kind != RawSocketKind::Ip {
+            return_errno_with_message!(
+                Errno::ENOPROTOOPT,
+                "IP_HDRINCL cannot be set on ping sockets"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl SetIpv6LevelOption for Inner<UnboundRaw, BoundRaw> {
+    fn set_v6only(&self, _v6only: bool) -> Result<()> {
+        if matches!(self, Inner::Bound(_)) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IPV6_V6ONLY cannot be changed after the socket is bound"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    iface::BindPortConfig,
+    socket::RawSocketKind,
+    wire::{IpEndpoint, IpProtocol},
+};
+
+use super::{SendOptions, bound::BoundRaw};
+use crate::{
+    events::IoEvents,
+    net::{
+        iface::{BoundRawPort, RawSocket},
+        net_ns::NetNamespace,
+        socket::{
+            ip::{
+                DatagramObserver, IpAddressFamily,
+                common::{get_ephemeral_endpoint, resolve_bind_iface},
+            },
+            util::{SocketOwner, datagram_common},
+        },
+    },
+    prelude::*,
+    process::signal::Pollee,
+};
+
+pub(super) struct UnboundRaw {
+    net_ns: Arc<NetNamespace>,
+    kind: RawSocketKind,
+    protocol: IpProtocol,
+    send_options: Arc<SendOptions>,
+    owner: SocketOwner,
+}
+
+impl UnboundRaw {
+    pub(super) fn new(
+        net_ns: Arc<NetNamespace>,
+        kind: RawSocketKind,
+        protocol: IpProtocol,
+        send_options: Arc<SendOptions>,
+        owner: SocketOwner,
+    ) -> Self {
+        Self {
+            net_ns,
+            kind,
+            protocol,
+            send_options,
+            owner,
+        }
+    }
+
+    fn bind_sockets(
+        &self,
+        endpoint: &IpEndpoint,
+        pollee: &Pollee,
+        is_ephemeral: bool,
+    ) -> Result<BoundRaw> {
+        let new_socket = |bound_port: BoundRawPort| {
+            RawSocket::new_bind(
+                bound_port,
+                self.kind,
+                DatagramObserver::new(pollee.clone(), self.owner),
+            )
+        };
+
+        let bound_sockets = if self.kind == RawSocketKind::Ip && endpoint.addr.is_unspecified() {
+            // Like Linux, raw IP sockets bound to the unspecified address receive packets to any
+            // local address on all ifaces.
+            //
+            // FIXME: Sockets bound to all ifaces should also see packets on ifaces that are
+            // added later.
+            let mut bound_sockets = Vec::new();
+            for iface in self.net_ns.ifaces() {
+                let bound_port = iface.bind_raw(endpoint.addr, self.protocol)?;
+                bound_sockets.push(new_socket(bound_port));
+            }
+            bound_sockets
+        } else {
+            let bound_port = bind_port(&self.net_ns, endpoint, self.kind, self.protocol)?;
+            vec![new_socket(bound_port)]
+        };
+
+        Ok(BoundRaw::new(
+            bound_sockets,
+            self.net_ns.clone(),
+            self.send_options.clone(),
+            is_ephemeral,
+        ))
+    }
+}
+
+impl datagram_common::Unbound for UnboundRaw {
+    type Endpoint = IpEndpoint;
+    type BindOptions = ();
+
+    type Bound = BoundRaw;
+
+    fn bind(
+        &mut self,
+        endpoint: &Self::Endpoint,
+        pollee: &Pollee,
+        _options: Self::BindOptions,
+    ) -> Result<Self::Bound> {
+        self.bind_sockets(endpoint, pollee, false)
+    }
+
+    fn bind_ephemeral(
+        &mut self,
+        remote_endpoint: &Self::Endpoint,
+        pollee: &Pollee,
+    ) -> Result<Self::Bound> {
+        // Raw IP sockets are bound to all ifaces, so that they can reach any remote address.
+        if self.kind == RawSocketKind::Ip {
+            let endpoint = IpAddressFamily::from(remote_endpoint.addr).unspecified_endpoint();
+            return self.bind_sockets(&endpoint, pollee, true);
+        }
+
+        let endpoint = get_ephemeral_endpoint(&self.net_ns, remote_endpoint).ok_or_else(|| {
+            Error::with_message(
+                Errno::EADDRNOTAVAIL,
+                "no interface has an address for the specified family",
+            )
+        })?;
+        self.bind_sockets(&endpoint, pollee, true)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        IoEvents::OUT
+    }
+}
+
+fn bind_port(
+    net_ns: &NetNamespace,
+    endpoint: &IpEndpoint,
+    kind: RawSocketKind,
+    protocol: IpProtocol,
+) -> Result<BoundRawPort> {
+    let iface = resolve_bind_iface(net_ns, &endpoint.addr)?;
+    let bound_port = match kind {
+        // Like Linux, the port specified in the socket address is ignored by raw IP sockets.
+        RawSocketKind::Ip => iface.bind_raw(endpoint.addr, protocol)?,
+        // Like Linux, the port specified in the socket address is used as the ICMP echo
+        // identifier by ping sockets.
+        RawSocketKind::Ping => iface.bind_icmp(BindPortConfig::new(*endpoint, false))?,
+    };
+    Ok(bound_port)
+}
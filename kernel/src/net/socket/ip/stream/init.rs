@@ -12,10 +12,11 @@ use crate::{
     events::IoEvents,
     net::{
         iface::BoundTcpPort,
+        net_ns::NetNamespace,
         socket::{
             ip::{
                 addr::IpAddressFamily,
-                common::{get_ephemeral_endpoint, resolve_bind_iface_and_config},
+                common::{BindOptions, get_ephemeral_endpoint, resolve_bind_iface_and_config},
             },
             util::SocketAddr,
         },
@@ -85,7 +86,12 @@ impl InitStream {
         self.family
     }
 
-    pub(super) fn bind(&mut self, endpoint: &IpEndpoint, can_reuse: bool) -> Result<()> {
+    pub(super) fn bind(
+        &mut self,
+        net_ns: &NetNamespace,
+        endpoint: &IpEndpoint,
+        options: &BindOptions,
+    ) -> Result<()> {
         if self.bound_port.is_some() {
             return_errno_with_message!(Errno::EINVAL, "the socket is already bound to an address");
         }
@@ -99,7 +105,7 @@ impl InitStream {
             );
         }
 
-        self.bound_port = Some(bind_port(endpoint, can_reuse)?);
+        self.bound_port = Some(bind_port(net_ns, endpoint, options)?);
 
         Ok(())
     }
@@ -110,9 +116,10 @@ impl InitStream {
 
     pub(super) fn connect(
         self,
+        net_ns: &NetNamespace,
         remote_endpoint: &IpEndpoint,
         option: &RawTcpOption,
-        can_reuse: bool,
+        bind_options: &BindOptions,
         observer: StreamObserver,
     ) -> Result<ConnectingStream, (Error, Self)> {
         debug_assert!(
@@ -135,7 +142,7 @@ impl InitStream {
         let bound_port = if let Some(bound_port) = self.bound_port {
             bound_port
         } else {
-            let endpoint = match get_ephemeral_endpoint(remote_endpoint) {
+            let endpoint = match get_ephemeral_endpoint(net_ns, remote_endpoint) {
                 Some(ep) => ep,
                 None => {
                     return Err((
@@ -147,7 +154,7 @@ impl InitStream {
                     ));
                 }
             };
-            match bind_port(&endpoint, can_reuse) {
+            match bind_port(net_ns, &endpoint, bind_options) {
                 Ok(bound_port) => bound_port,
                 Err(err) => return Err((err, self)),
             }
@@ -273,7 +280,13 @@ impl InitStream {
     }
 }
 
-fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundTcpPort> {
-    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse)?;
-    Ok(iface.bind_tcp(config)?)
+fn bind_port(
+    net_ns: &NetNamespace,
+    endpoint: &IpEndpoint,
+    options: &BindOptions,
+) -> Result<BoundTcpPort> {
+    let (iface, config) = resolve_bind_iface_and_config(net_ns, endpoint, options)?;
+    let bound_port = iface.bind_tcp(config)?;
+    options.apply(&bound_port);
+    Ok(bound_port)
 }
@@ -55,6 +55,10 @@ impl ListenStream {
         self.tcp_listener.iface()
     }
 
+    pub(super) fn bound_port(&self) -> &BoundTcpPort {
+        self.tcp_listener.bound_port()
+    }
+
     pub(super) fn check_io_events(&self) -> IoEvents {
         let can_accept = self.tcp_listener.can_accept();
 
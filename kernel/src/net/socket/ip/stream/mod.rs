@@ -22,6 +22,7 @@ use util::{Retrans, TcpOptionSet};
 
 use super::{
     addr::IpAddressFamily,
+    common::BindOptions,
     options::{IpOptionSet, SetIpLevelOption},
 };
 use crate::{
@@ -29,6 +30,7 @@ use crate::{
     fs::{file::FileLike, pseudofs::SockFs, vfs::path::Path},
     net::{
         iface::Iface,
+        net_ns::NetNamespace,
         socket::{
             Socket,
             options::{
@@ -37,14 +39,15 @@ use crate::{
             },
             private::SocketPrivate,
             util::{
-                MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
+                MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr, SocketFilter,
+                SocketOwner,
                 options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
             },
         },
     },
     prelude::*,
     process::signal::{PollHandle, Pollable, Pollee},
-    util::{MultiRead, MultiWrite},
+    util::{MultiRead, MultiWrite, net::Protocol},
 };
 
 mod connected;
@@ -62,9 +65,11 @@ pub struct StreamSocket {
     state: RwLock<Takeable<State>>,
     options: RwLock<OptionSet>,
 
+    net_ns: Arc<NetNamespace>,
     is_nonblocking: AtomicBool,
     pollee: Pollee,
     pseudo_path: Path,
+    owner: SocketOwner,
 }
 
 enum State {
@@ -104,18 +109,31 @@ impl OptionSet {
 }
 
 impl StreamSocket {
-    pub fn new(is_nonblocking: bool, family: IpAddressFamily) -> Arc<Self> {
+    pub fn new(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
         let init_stream = InitStream::new(family);
+        let pseudo_path = SockFs::new_path();
+        let owner = SocketOwner::new_current(&pseudo_path);
         Arc::new(Self {
             state: RwLock::new(Takeable::new(State::Init(init_stream))),
             options: RwLock::new(OptionSet::new()),
+            net_ns,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
+            owner,
         })
     }
 
-    fn new_accepted(connected_stream: ConnectedStream, listener_options: &OptionSet) -> Arc<Self> {
+    fn new_accepted(
+        connected_stream: ConnectedStream,
+        listener_options: &OptionSet,
+        listener_owner: &SocketOwner,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
         let options = connected_stream.raw_with(|raw_tcp_socket| {
             let mut options = OptionSet::new();
 
@@ -148,14 +166,19 @@ impl StreamSocket {
         });
 
         let pollee = Pollee::new();
-        connected_stream.init_observer(StreamObserver::new(pollee.clone()));
+        let pseudo_path = SockFs::new_path();
+        // Like Linux, an accepted socket belongs to the owner of the listener.
+        let owner = SocketOwner::new(&pseudo_path, listener_owner.uid());
+        connected_stream.init_observer(StreamObserver::new(pollee.clone(), owner));
 
         Arc::new(Self {
             state: RwLock::new(Takeable::new(State::Connected(connected_stream))),
             options: RwLock::new(options),
+            net_ns,
             is_nonblocking: AtomicBool::new(false),
             pollee,
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
+            owner,
         })
     }
 
@@ -256,10 +279,11 @@ impl StreamSocket {
             }
 
             let (target_state, iface_to_poll) = match init_stream.connect(
+                &self.net_ns,
                 remote_endpoint,
                 &raw_option,
-                options.socket.reuse_addr(),
-                StreamObserver::new(self.pollee.clone()),
+                &BindOptions::new(&options.socket, &self.owner),
+                StreamObserver::new(self.pollee.clone(), self.owner),
             ) {
                 Ok(connecting_stream) => {
                     let iface_to_poll = connecting_stream.iface().clone();
@@ -322,7 +346,12 @@ impl StreamSocket {
         let accepted = listen_stream.try_accept().map(|connected_stream| {
             let remote_endpoint = connected_stream.remote_endpoint();
             let listener_options = self.options.read();
-            let accepted_socket = Self::new_accepted(connected_stream, &listener_options);
+            let accepted_socket = Self::new_accepted(
+                connected_stream,
+                &listener_options,
+                &self.owner,
+                self.net_ns.clone(),
+            );
             (accepted_socket as _, remote_endpoint.into())
         });
         let iface_to_poll = listen_stream.iface().clone();
@@ -447,6 +476,10 @@ impl SocketPrivate for StreamSocket {
 }
 
 impl Socket for StreamSocket {
+    fn protocol(&self) -> Protocol {
+        Protocol::IPPROTO_TCP
+    }
+
     fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
         let endpoint = socket_addr.try_into()?;
 
@@ -455,8 +488,8 @@ impl Socket for StreamSocket {
             return_errno_with_message!(Errno::EINVAL, "the socket is already bound to an address");
         };
 
-        let can_reuse = self.options.read().socket.reuse_addr();
-        init_stream.bind(&endpoint, can_reuse)
+        let bind_options = BindOptions::new(&self.options.read().socket, &self.owner);
+        init_stream.bind(&self.net_ns, &endpoint, &bind_options)
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
@@ -495,7 +528,7 @@ impl Socket for StreamSocket {
             let listen_stream = match init_stream.listen(
                 backlog,
                 &raw_option,
-                StreamObserver::new(self.pollee.clone()),
+                StreamObserver::new(self.pollee.clone(), self.owner),
             ) {
                 Ok(listen_stream) => listen_stream,
                 Err((err, init_stream)) => {
@@ -902,6 +935,23 @@ impl State {
         bound_port.set_can_reuse(reuse_addr);
     }
 
+    fn set_reuseport_filter(&self, filter: Option<Arc<SocketFilter>>) -> bool {
+        let bound_port = match self {
+            State::Init(init_stream) => {
+                if let Some(bound_port) = init_stream.bound_port() {
+                    bound_port
+                } else {
+                    return false;
+                }
+            }
+            State::Connecting(connecting_stream) => connecting_stream.bound_port(),
+            State::Connected(connected_stream) => connected_stream.bound_port(),
+            State::Listen(listen_stream) => listen_stream.bound_port(),
+        };
+
+        bound_port.set_reuseport_selector(filter).is_some()
+    }
+
     fn set_keep_alive(&self, is_keep_alive_enabled: bool, keep_intvl: u32) -> NeedIfacePoll {
         let interval = is_keep_alive_enabled.then(|| Duration::from_secs(keep_intvl as u64));
 
@@ -926,6 +976,10 @@ impl SetSocketLevelOption for (&State, &TcpOptionSet) {
     fn set_keep_alive(&self, keep_alive: bool) -> NeedIfacePoll {
         self.0.set_keep_alive(keep_alive, self.1.keep_intvl())
     }
+
+    fn set_reuseport_filter(&self, filter: Option<Arc<SocketFilter>>) -> bool {
+        self.0.set_reuseport_filter(filter)
+    }
 }
 
 impl SetIpLevelOption for State {
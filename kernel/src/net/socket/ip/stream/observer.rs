@@ -2,14 +2,22 @@
 
 use aster_bigtcp::socket::{SocketEventObserver, SocketEvents};
 
-use crate::{events::IoEvents, process::signal::Pollee};
+use crate::{events::IoEvents, net::socket::util::SocketOwner, process::signal::Pollee};
 
 #[derive(Clone)]
-pub struct StreamObserver(Pollee);
+pub struct StreamObserver {
+    pollee: Pollee,
+    owner: SocketOwner,
+}
 
 impl StreamObserver {
-    pub(super) fn new(pollee: Pollee) -> Self {
-        Self(pollee)
+    pub(super) fn new(pollee: Pollee, owner: SocketOwner) -> Self {
+        Self { pollee, owner }
+    }
+
+    /// Returns the owner of the socket.
+    pub(in crate::net) fn owner(&self) -> &SocketOwner {
+        &self.owner
     }
 }
 
@@ -39,6 +47,6 @@ impl SocketEventObserver for StreamObserver {
             io_events |= IoEvents::HUP | IoEvents::ERR;
         }
 
-        self.0.notify(io_events);
+        self.pollee.notify(io_events);
     }
 }
@@ -7,17 +7,18 @@ use util::{MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr};
 
 use crate::{
     fs::{
-        file::{AccessMode, CreationFlags, FileLike, StatusFlags, file_table::FdFlags},
+        file::{AccessMode, CreationFlags, FileLike, Mappable, StatusFlags, file_table::FdFlags},
         pseudofs::SockFs,
         vfs::path::Path,
     },
     prelude::*,
-    util::{MultiRead, MultiWrite},
+    util::{MultiRead, MultiWrite, net::Protocol},
 };
 
 pub mod ip;
 pub mod netlink;
 pub mod options;
+pub mod packet;
 pub mod unix;
 pub mod util;
 pub mod vsock;
@@ -94,6 +95,14 @@ pub trait Socket: private::SocketPrivate + Send + Sync {
         return_errno_with_message!(Errno::EOPNOTSUPP, "getpeername() is not supported");
     }
 
+    /// Returns the protocol of the socket.
+    ///
+    /// Sockets whose address family has no protocols, e.g., UNIX sockets, report zero, which is
+    /// [`Protocol::IPPROTO_IP`].
+    fn protocol(&self) -> Protocol {
+        Protocol::IPPROTO_IP
+    }
+
     /// Gets options on the socket.
     ///
     /// If the method succeeds, the result will be stored in the `option` parameter.
@@ -125,6 +134,11 @@ pub trait Socket: private::SocketPrivate + Send + Sync {
         flags: SendRecvFlags,
     ) -> Result<(usize, MessageHeader)>;
 
+    /// Returns the object that can be memory mapped for the socket.
+    fn mappable(&self) -> Result<Mappable> {
+        return_errno_with_message!(Errno::ENODEV, "the socket cannot be memory mapped");
+    }
+
     /// Returns a reference to the pseudo path associated with this socket.
     fn pseudo_path(&self) -> &Path;
 }
@@ -178,6 +192,10 @@ impl<T: Socket + 'static> FileLike for T {
         Some(self)
     }
 
+    fn mappable(&self) -> Result<Mappable> {
+        Socket::mappable(self)
+    }
+
     fn path(&self) -> &Path {
         self.pseudo_path()
     }
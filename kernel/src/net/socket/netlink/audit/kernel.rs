@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines the kernel socket,
+//! which is responsible for handling requests from user space.
+
+use super::message::{AuditDataSegment, AuditMessage, AuditRecordSegment, AuditSegment};
+use crate::{
+    net::socket::netlink::{
+        addr::PortNum,
+        message::{CMsgSegHdr, DoneSegment, ErrorSegment, SegHdrCommonFlags},
+        table::{NetlinkAuditProtocol, SupportedNetlinkProtocol},
+    },
+    prelude::*,
+    process::{
+        UserNamespace,
+        credentials::capabilities::CapSet,
+        posix_thread::{AsPosixThread, PosixThread},
+    },
+    security::{
+        audit::{self, AuditRecord, AuditRule, CAuditStatus},
+        lsm::hooks as lsm_hooks,
+    },
+};
+
+/// The types of the control requests.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/audit.h#L53>.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+enum AuditRequestType {
+    /// Gets the status
+    Get = 1000,
+    /// Sets the status (enable/disable/auditd)
+    Set = 1001,
+    /// Lists the syscall rules (deprecated)
+    List = 1002,
+    /// Adds a syscall rule (deprecated)
+    Add = 1003,
+    /// Deletes a syscall rule (deprecated)
+    Del = 1004,
+    /// Gets the information about the sender of a signal to auditd
+    SignalInfo = 1010,
+    /// Adds a syscall rule
+    AddRule = 1011,
+    /// Deletes a syscall rule
+    DelRule = 1012,
+    /// Lists the syscall rules
+    ListRules = 1013,
+    /// Trims the junk from the watched tree
+    Trim = 1014,
+    /// Appends to the watched tree
+    MakeEquiv = 1015,
+    /// Gets the TTY auditing status
+    TtyGet = 1016,
+    /// Sets the TTY auditing status
+    TtySet = 1017,
+    /// Turns an audit feature on or off
+    SetFeature = 1018,
+    /// Gets the audit features
+    GetFeature = 1019,
+}
+
+pub(super) fn handle_request(request: &AuditDataSegment, dst_port: PortNum) {
+    debug!("netlink audit request: {:?}", request);
+
+    let request_header = request.header();
+
+    let current = current_thread!();
+    let posix_thread = current.as_posix_thread().unwrap();
+
+    let response_segments = match do_request(request, posix_thread, dst_port) {
+        Ok(segments) => segments,
+        Err(error) => {
+            let err_segment = ErrorSegment::new_from_request(request_header, Some(error));
+            report_error(err_segment, dst_port);
+            return;
+        }
+    };
+
+    let flags = SegHdrCommonFlags::from_bits_truncate(request_header.flags);
+    if flags.contains(SegHdrCommonFlags::ACK) {
+        let ack_segment = ErrorSegment::new_from_request(request_header, None);
+        report_error(ack_segment, dst_port);
+    }
+
+    // Linux sends each reply segment in a separate message. User-space programs (e.g.,
+    // `auditctl`) expect to receive one segment at a time.
+    for segment in response_segments {
+        let response = AuditMessage::new(vec![segment]);
+        debug!("netlink audit response: {:?}", response);
+        NetlinkAuditProtocol::unicast(dst_port, response).unwrap();
+    }
+}
+
+pub(super) fn report_error(err_segment: ErrorSegment, dst_port: PortNum) {
+    let response = AuditMessage::new(vec![AuditSegment::Error(err_segment)]);
+
+    debug!("netlink audit error: {:?}", response);
+
+    NetlinkAuditProtocol::unicast(dst_port, response).unwrap();
+}
+
+/// Sends an audit record to the socket bound to `port`.
+///
+/// This method fails with [`Errno::ECONNREFUSED`] if no socket is bound to the port, and with
+/// [`Errno::EAGAIN`] if the receive buffer of the socket is full.
+pub fn send_audit_record(port: PortNum, record: &AuditRecord) -> Result<()> {
+    let segment = AuditRecordSegment::new(record.clone());
+    let message = AuditMessage::new(vec![AuditSegment::Record(segment)]);
+    NetlinkAuditProtocol::try_unicast(port, message)
+}
+
+fn do_request(
+    request: &AuditDataSegment,
+    posix_thread: &PosixThread,
+    port: PortNum,
+) -> Result<Vec<AuditSegment>> {
+    let request_header = request.header();
+    let payload = request.payload();
+
+    if audit::is_user_message_type(request_header.type_) {
+        check_capability(posix_thread, CapSet::AUDIT_WRITE)?;
+        audit::log_user_message(posix_thread, request_header.type_, payload);
+        return Ok(Vec::new());
+    }
+
+    let Ok(request_type) = AuditRequestType::try_from(request_header.type_) else {
+        return_errno_with_message!(Errno::EINVAL, "the audit request type is invalid");
+    };
+    if matches!(
+        request_type,
+        AuditRequestType::List | AuditRequestType::Add | AuditRequestType::Del
+    ) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the audit request type is deprecated");
+    }
+    check_capability(posix_thread, CapSet::AUDIT_CONTROL)?;
+
+    match request_type {
+        AuditRequestType::Get => {
+            let status = audit::get_status();
+            let segment = new_reply_segment(
+                request_header,
+                AuditRequestType::Get,
+                false,
+                status.as_bytes().to_vec(),
+            );
+            Ok(vec![segment])
+        }
+        AuditRequestType::Set => {
+            // Older versions of user-space programs may send a shorter structure.
+            let mut status = CAuditStatus::new_zeroed();
+            let len = payload.len().min(size_of::<CAuditStatus>());
+            status.as_mut_bytes()[..len].copy_from_slice(&payload[..len]);
+            audit::set_status(posix_thread, port, &status)?;
+            Ok(Vec::new())
+        }
+        AuditRequestType::AddRule => {
+            let rule = AuditRule::parse(payload)?;
+            audit::add_rule(posix_thread, rule)?;
+            Ok(Vec::new())
+        }
+        AuditRequestType::DelRule => {
+            let rule = AuditRule::parse(payload)?;
+            audit::del_rule(posix_thread, &rule)?;
+            Ok(Vec::new())
+        }
+        AuditRequestType::ListRules => {
+            let mut segments: Vec<_> = audit::list_rules()
+                .iter()
+                .map(|rule| {
+                    new_reply_segment(
+                        request_header,
+                        AuditRequestType::ListRules,
+                        true,
+                        rule.to_bytes(),
+                    )
+                })
+                .collect();
+
+            let mut done_segment = DoneSegment::new_from_request(request_header, None);
+            done_segment.header_mut().flags |= SegHdrCommonFlags::MULTI.bits();
+            segments.push(AuditSegment::Done(done_segment));
+
+            Ok(segments)
+        }
+        _ => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the audit request type is not supported")
+        }
+    }
+}
+
+fn check_capability(posix_thread: &PosixThread, cap: CapSet) -> Result<()> {
+    lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+        UserNamespace::get_init_singleton().as_ref(),
+        posix_thread,
+        cap,
+    ))
+    .map_err(|_| {
+        Error::with_message(
+            Errno::EPERM,
+            "the capability required by the audit request is missing",
+        )
+    })
+}
+
+fn new_reply_segment(
+    request_header: &CMsgSegHdr,
+    type_: AuditRequestType,
+    is_multi: bool,
+    payload: Vec<u8>,
+) -> AuditSegment {
+    let flags = if is_multi {
+        SegHdrCommonFlags::MULTI
+    } else {
+        SegHdrCommonFlags::empty()
+    };
+
+    let header = CMsgSegHdr {
+        len: 0,
+        type_: type_ as u16,
+        flags: flags.bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    };
+
+    AuditSegment::Data(AuditDataSegment::new(header, payload))
+}
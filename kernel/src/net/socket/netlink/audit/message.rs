@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink message types for the audit protocol.
+//!
+//! Unlike other netlink protocols, the audit protocol does not use attributes. The payload of a
+//! segment is either a C structure (e.g., `struct audit_status`) or a text record, so it is kept
+//! as raw bytes and interpreted by the kernel socket.
+
+use align_ext::AlignExt;
+
+use crate::{
+    net::socket::netlink::message::{
+        CMsgSegHdr, ContinueRead, DoneSegment, ErrorSegment, Message, NLMSG_ALIGN, ProtocolSegment,
+    },
+    prelude::*,
+    security::audit::AuditRecord,
+    util::{MultiRead, MultiWrite},
+};
+
+/// A netlink audit message.
+pub(in crate::net::socket::netlink) type AuditMessage = Message<AuditSegment>;
+
+/// The netlink audit segment, which is the basic unit of a netlink audit message.
+#[derive(Debug)]
+pub enum AuditSegment {
+    /// A request from user space or a reply to the request.
+    Data(AuditDataSegment),
+    /// An audit record sent to the audit daemon.
+    Record(AuditRecordSegment),
+    Done(DoneSegment),
+    Error(ErrorSegment),
+}
+
+impl ProtocolSegment for AuditSegment {
+    fn header(&self) -> &CMsgSegHdr {
+        match self {
+            AuditSegment::Data(segment) => &segment.header,
+            AuditSegment::Record(segment) => &segment.header,
+            AuditSegment::Done(done_segment) => done_segment.header(),
+            AuditSegment::Error(error_segment) => error_segment.header(),
+        }
+    }
+
+    fn header_mut(&mut self) -> &mut CMsgSegHdr {
+        match self {
+            AuditSegment::Data(segment) => &mut segment.header,
+            AuditSegment::Record(segment) => &mut segment.header,
+            AuditSegment::Done(done_segment) => done_segment.header_mut(),
+            AuditSegment::Error(error_segment) => error_segment.header_mut(),
+        }
+    }
+
+    fn read_from(reader: &mut dyn MultiRead) -> Result<ContinueRead<Self, ErrorSegment>> {
+        let header = reader
+            .read_val_opt::<CMsgSegHdr>()?
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the reader length is too small"))?;
+
+        let payload_len = header.calc_payload_len_with_padding(reader)?;
+        let mut payload = vec![0; payload_len];
+        reader.read(&mut VmWriter::from(payload.as_mut_slice()))?;
+        // Remove the padding bytes.
+        payload.truncate(header.len as usize - size_of::<CMsgSegHdr>());
+
+        Ok(ContinueRead::Parsed(AuditSegment::Data(AuditDataSegment {
+            header,
+            payload,
+        })))
+    }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        match self {
+            AuditSegment::Data(segment) => segment.write_to(writer)?,
+            AuditSegment::Record(segment) => segment.write_to(writer)?,
+            AuditSegment::Done(done_segment) => done_segment.write_to(writer)?,
+            AuditSegment::Error(error_segment) => error_segment.write_to(writer)?,
+        }
+        Ok(())
+    }
+}
+
+/// A segment whose payload is a C structure or a list of C structures.
+#[derive(Debug)]
+pub struct AuditDataSegment {
+    header: CMsgSegHdr,
+    payload: Vec<u8>,
+}
+
+impl AuditDataSegment {
+    /// Creates a reply segment.
+    ///
+    /// The length in `header` will be calculated from the length of `payload`.
+    pub(super) fn new(mut header: CMsgSegHdr, payload: Vec<u8>) -> Self {
+        header.len = (size_of::<CMsgSegHdr>() + payload.len()) as u32;
+        Self { header, payload }
+    }
+
+    pub(super) fn header(&self) -> &CMsgSegHdr {
+        &self.header
+    }
+
+    pub(super) fn header_mut(&mut self) -> &mut CMsgSegHdr {
+        &mut self.header
+    }
+
+    pub(super) fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        writer.write_val_trunc(&self.header)?;
+        write_payload(writer, &self.payload)
+    }
+}
+
+/// A segment that carries an audit record.
+#[derive(Debug)]
+pub struct AuditRecordSegment {
+    header: CMsgSegHdr,
+    record: AuditRecord,
+}
+
+impl AuditRecordSegment {
+    pub(super) fn new(record: AuditRecord) -> Self {
+        let header = CMsgSegHdr {
+            len: (size_of::<CMsgSegHdr>() + record.data().len()) as u32,
+            type_: record.type_(),
+            flags: 0,
+            seq: 0,
+            pid: 0,
+        };
+        Self { header, record }
+    }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        // For historical reasons, Linux sets the length in the header of audit records to the
+        // length of the payload, excluding the header. User-space programs (e.g., `auditd`) rely
+        // on this behavior.
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/audit.c#L2463>.
+        let mut header = self.header;
+        header.len = self.record.data().len() as u32;
+        writer.write_val_trunc(&header)?;
+        write_payload(writer, self.record.data())
+    }
+}
+
+fn write_payload(writer: &mut dyn MultiWrite, payload: &[u8]) -> Result<()> {
+    let _nbytes = writer.write(&mut VmReader::from(payload))?;
+    // `_nbytes` may be smaller than the payload size. We ignore it to truncate the payload.
+
+    // Skip the padding bytes.
+    writer.skip_some(payload.len().align_up(NLMSG_ALIGN) - payload.len());
+
+    Ok(())
+}
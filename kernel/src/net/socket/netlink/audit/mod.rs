@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink Audit (`NETLINK_AUDIT`) Socket.
+
+pub use kernel::send_audit_record;
+pub(super) use message::AuditMessage;
+
+use crate::net::socket::netlink::{common::NetlinkSocket, table::NetlinkAuditProtocol};
+
+mod bound;
+mod kernel;
+mod message;
+
+pub type NetlinkAuditSocket = NetlinkSocket<NetlinkAuditProtocol>;
@@ -4,12 +4,15 @@ use core::marker::PhantomData;
 
 use crate::{
     events::IoEvents,
-    net::socket::{
-        netlink::{
-            GroupIdSet, NetlinkSocketAddr, common::bound::BoundNetlink, receiver::MessageQueue,
-            table::SupportedNetlinkProtocol,
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            netlink::{
+                GroupIdSet, NetlinkSocketAddr, common::bound::BoundNetlink, receiver::MessageQueue,
+                table::SupportedNetlinkProtocol,
+            },
+            util::datagram_common,
         },
-        util::datagram_common,
     },
     prelude::*,
     process::signal::Pollee,
@@ -17,13 +20,15 @@ use crate::{
 
 pub(super) struct UnboundNetlink<P: SupportedNetlinkProtocol> {
     groups: GroupIdSet,
+    net_ns: Arc<NetNamespace>,
     phantom: PhantomData<BoundNetlink<P::Message>>,
 }
 
 impl<P: SupportedNetlinkProtocol> UnboundNetlink<P> {
-    pub(super) const fn new() -> Self {
+    pub(super) fn new(net_ns: Arc<NetNamespace>) -> Self {
         Self {
             groups: GroupIdSet::new_empty(),
+            net_ns,
             phantom: PhantomData,
         }
     }
@@ -65,7 +70,11 @@ impl<P: SupportedNetlinkProtocol> datagram_common::Unbound for UnboundNetlink<P>
             <P as SupportedNetlinkProtocol>::bind(&endpoint, message_receiver)?
         };
 
-        Ok(BoundNetlink::new(bound_handle, message_queue))
+        Ok(BoundNetlink::new(
+            bound_handle,
+            message_queue,
+            self.net_ns.clone(),
+        ))
     }
 
     fn bind_ephemeral(
@@ -85,7 +94,11 @@ impl<P: SupportedNetlinkProtocol> datagram_common::Unbound for UnboundNetlink<P>
             <P as SupportedNetlinkProtocol>::bind(&endpoint, message_receiver)?
         };
 
-        Ok(BoundNetlink::new(bound_handle, message_queue))
+        Ok(BoundNetlink::new(
+            bound_handle,
+            message_queue,
+            self.net_ns.clone(),
+        ))
     }
 
     fn check_io_events(&self) -> IoEvents {
@@ -88,7 +88,8 @@ impl CAttrHeader {
     }
 }
 
-const IS_NESTED_MASK: u16 = 1u16 << 15;
+/// The flag in the attribute type that indicates a nested attribute (`NLA_F_NESTED` in Linux).
+pub const IS_NESTED_MASK: u16 = 1u16 << 15;
 const IS_NET_BYTEORDER_MASK: u16 = 1u16 << 14;
 const ATTRIBUTE_TYPE_MASK: u16 = !(IS_NESTED_MASK | IS_NET_BYTEORDER_MASK);
 
@@ -186,6 +187,19 @@ pub trait Attribute: Debug + Send + Sync {
         Ok(ContinueRead::Parsed(res))
     }
 
+    /// Reads all attributes from `bytes`, which is usually the payload of a nested attribute.
+    fn read_all_from_bytes(bytes: &[u8]) -> Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        let mut reader = VmReader::from(bytes).to_fallible();
+        match Self::read_all_from(&mut reader, bytes.len())? {
+            ContinueRead::Parsed(attrs) => Ok(attrs),
+            ContinueRead::Skipped => Ok(Vec::new()),
+            ContinueRead::SkippedErr(err) => Err(err),
+        }
+    }
+
     /// Writes the attribute to the `writer`.
     fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
         let type_ = self.type_();
@@ -9,13 +9,13 @@ mod attr;
 mod result;
 mod segment;
 
-pub(super) use attr::{Attribute, CAttrHeader, noattr::NoAttr};
+pub(super) use attr::{Attribute, CAttrHeader, IS_NESTED_MASK, noattr::NoAttr};
 pub(super) use result::ContinueRead;
 pub(super) use segment::{
     CSegmentType, SegmentBody,
     ack::{DoneSegment, ErrorSegment},
     common::SegmentCommon,
-    header::{CMsgSegHdr, GetRequestFlags, SegHdrCommonFlags},
+    header::{CMsgSegHdr, DeleteRequestFlags, GetRequestFlags, NewRequestFlags, SegHdrCommonFlags},
 };
 
 use super::receiver::QueueableMessage;
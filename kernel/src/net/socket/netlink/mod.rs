@@ -38,19 +38,25 @@
 //!
 
 mod addr;
+mod audit;
 mod common;
 mod kobject_uevent;
 mod message;
+mod netfilter;
 mod options;
 mod receiver;
 mod route;
+mod sock_diag;
 mod table;
 
 pub use addr::{GroupIdSet, NetlinkSocketAddr};
+pub use audit::{NetlinkAuditSocket, send_audit_record};
 pub use kobject_uevent::NetlinkUeventSocket;
+pub use netfilter::NetlinkNetfilterSocket;
 pub use options::{AddMembership, DropMembership};
 pub(super) use receiver::NETLINK_DEFAULT_BUF_SIZE;
 pub use route::NetlinkRouteSocket;
+pub use sock_diag::NetlinkSockDiagSocket;
 pub use table::{StandardNetlinkProtocol, is_valid_protocol};
 
 pub(in crate::net) fn init() {
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle chain-related requests.
+
+use aster_bigtcp::iface::FilterHook;
+
+use super::util::{
+    NFT_NAME_MAXLEN, dump_response, is_dump, new_response_segment, parse_family,
+    parse_family_filter, parse_name, parse_name_opt, parse_u32_opt, parse_u64_opt,
+};
+use crate::{
+    net::{
+        netfilter::{BaseChain, Chain, ChainType, Rule, Ruleset, Table, Verdict},
+        socket::netlink::{
+            message::{CMsgSegHdr, DeleteRequestFlags, NewRequestFlags},
+            netfilter::message::{NfAttr, NfSegment, NfnlSegment, NftMsgType, find_attr},
+        },
+    },
+    prelude::*,
+};
+
+/// Chain attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L209>.
+const NFTA_CHAIN_TABLE: u16 = 1;
+const NFTA_CHAIN_HANDLE: u16 = 2;
+const NFTA_CHAIN_NAME: u16 = 3;
+const NFTA_CHAIN_HOOK: u16 = 4;
+const NFTA_CHAIN_POLICY: u16 = 5;
+const NFTA_CHAIN_USE: u16 = 6;
+const NFTA_CHAIN_TYPE: u16 = 7;
+const NFTA_CHAIN_FLAGS: u16 = 10;
+
+/// Hook attributes, which are nested in `NFTA_CHAIN_HOOK`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L145>.
+const NFTA_HOOK_HOOKNUM: u16 = 1;
+const NFTA_HOOK_PRIORITY: u16 = 2;
+const NFTA_HOOK_DEV: u16 = 3;
+
+/// The chain flag that indicates a base chain.
+const NFT_CHAIN_BASE: u32 = 0x1;
+
+pub(super) fn do_new_chain(ruleset: &mut Ruleset, request_segment: &NfSegment) -> Result<()> {
+    let family = parse_family(request_segment.body().family)?;
+    let attrs = request_segment.attrs();
+
+    let table_name = parse_name(attrs, NFTA_CHAIN_TABLE)?;
+    let Some(table) = ruleset.table_mut(family, &table_name) else {
+        return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+    };
+
+    let name = parse_name(attrs, NFTA_CHAIN_NAME)?;
+    let base = find_attr(attrs, NFTA_CHAIN_HOOK)
+        .map(|hook_attr| parse_base_chain(hook_attr, attrs))
+        .transpose()?;
+    let policy = parse_u32_opt(attrs, NFTA_CHAIN_POLICY)?
+        .map(|policy| match policy as i32 {
+            Verdict::NF_ACCEPT => Ok(true),
+            Verdict::NF_DROP => Ok(false),
+            _ => Err(Error::with_message(Errno::EINVAL, "the policy is invalid")),
+        })
+        .transpose()?;
+    if parse_u32_opt(attrs, NFTA_CHAIN_FLAGS)?.is_some_and(|flags| flags & !NFT_CHAIN_BASE != 0) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the chain flags are not supported");
+    }
+
+    let request_flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    if let Some(chain) = table.chain_mut(&name) {
+        if request_flags.contains(NewRequestFlags::EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the chain already exists");
+        }
+        if request_flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "chains cannot be replaced");
+        }
+        if let Some(base) = base
+            && chain.base.is_none_or(|existing| {
+                (existing.hook, existing.priority, existing.type_)
+                    != (base.hook, base.priority, base.type_)
+            })
+        {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the hook of a chain cannot be changed");
+        }
+        if let Some(policy) = policy {
+            let Some(existing) = chain.base.as_mut() else {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "regular chains have no policies");
+            };
+            existing.policy_accept = policy;
+        }
+        return Ok(());
+    }
+
+    let base = match (base, policy) {
+        (Some(base), Some(policy_accept)) => Some(BaseChain {
+            policy_accept,
+            ..base
+        }),
+        (base, None) => base,
+        (None, Some(_)) => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "regular chains have no policies")
+        }
+    };
+
+    let handle = table.alloc_handle();
+    table.chains.push(Chain {
+        name,
+        handle,
+        base,
+        rules: Vec::new(),
+    });
+
+    Ok(())
+}
+
+pub(super) fn do_del_chain(ruleset: &mut Ruleset, request_segment: &NfSegment) -> Result<()> {
+    let family = parse_family(request_segment.body().family)?;
+    let attrs = request_segment.attrs();
+
+    let table_name = parse_name(attrs, NFTA_CHAIN_TABLE)?;
+    let Some(table) = ruleset.table_mut(family, &table_name) else {
+        return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+    };
+
+    let name = parse_name_opt(attrs, NFTA_CHAIN_NAME)?;
+    let handle = parse_u64_opt(attrs, NFTA_CHAIN_HANDLE)?;
+    if name.is_none() && handle.is_none() {
+        return_errno_with_message!(Errno::EINVAL, "the chain is not specified");
+    }
+    let Some(index) = table.chains.iter().position(|chain| match handle {
+        Some(handle) => chain.handle == handle,
+        None => name.as_ref() == Some(&chain.name),
+    }) else {
+        return_errno_with_message!(Errno::ENOENT, "the chain does not exist");
+    };
+
+    let chain = &table.chains[index];
+    if count_references(table, &chain.name) > 0 {
+        return_errno_with_message!(Errno::EBUSY, "the chain is referenced by other rules");
+    }
+    let request_flags = DeleteRequestFlags::from_bits_truncate(request_segment.header().flags);
+    if request_flags.contains(DeleteRequestFlags::NONREC) && !chain.rules.is_empty() {
+        return_errno_with_message!(Errno::EBUSY, "the chain is not empty");
+    }
+
+    table.chains.remove(index);
+
+    Ok(())
+}
+
+pub(super) fn do_get_chain(
+    ruleset: &Ruleset,
+    request_segment: &NfSegment,
+) -> Result<Vec<NfnlSegment>> {
+    let request_header = request_segment.header();
+    let attrs = request_segment.attrs();
+
+    if is_dump(request_header) {
+        let family = parse_family_filter(request_segment.body().family)?;
+        let table_name = parse_name_opt(attrs, NFTA_CHAIN_TABLE)?;
+        let response_segments = ruleset
+            .tables
+            .iter()
+            .filter(|table| family.is_none_or(|family| table.family == family))
+            .filter(|table| table_name.as_ref().is_none_or(|name| *name == table.name))
+            .flat_map(|table| {
+                table
+                    .chains
+                    .iter()
+                    .map(move |chain| chain_to_new_chain(request_header, ruleset, table, chain))
+            })
+            .collect();
+        return Ok(dump_response(request_header, response_segments));
+    }
+
+    let family = parse_family(request_segment.body().family)?;
+    let table_name = parse_name(attrs, NFTA_CHAIN_TABLE)?;
+    let Some(table) = ruleset.table(family, &table_name) else {
+        return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+    };
+    let name = parse_name(attrs, NFTA_CHAIN_NAME)?;
+    let Some(chain) = table.chain(&name) else {
+        return_errno_with_message!(Errno::ENOENT, "the chain does not exist");
+    };
+
+    Ok(vec![chain_to_new_chain(
+        request_header,
+        ruleset,
+        table,
+        chain,
+    )])
+}
+
+/// Parses the hook of a base chain.
+fn parse_base_chain(hook_attr: &NfAttr, attrs: &[NfAttr]) -> Result<BaseChain> {
+    let hook_attrs = hook_attr.as_nested()?;
+
+    let Some(hooknum) = parse_u32_opt(&hook_attrs, NFTA_HOOK_HOOKNUM)? else {
+        return_errno_with_message!(Errno::EINVAL, "the hook is not specified");
+    };
+    let Some(priority) = parse_u32_opt(&hook_attrs, NFTA_HOOK_PRIORITY)? else {
+        return_errno_with_message!(Errno::EINVAL, "the priority is not specified");
+    };
+    if find_attr(&hook_attrs, NFTA_HOOK_DEV).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "device hooks are not supported");
+    }
+
+    let hook = match hooknum {
+        0 => FilterHook::PreRouting,
+        1 => FilterHook::LocalIn,
+        2 => FilterHook::Forward,
+        3 => FilterHook::LocalOut,
+        4 => FilterHook::PostRouting,
+        _ => return_errno_with_message!(Errno::EOPNOTSUPP, "the hook is not supported"),
+    };
+
+    let type_ = match find_attr(attrs, NFTA_CHAIN_TYPE) {
+        Some(type_attr) => ChainType::from_name(&type_attr.as_str(NFT_NAME_MAXLEN)?)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain type does not exist"))?,
+        None => ChainType::Filter,
+    };
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nft_chain_nat.c>.
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nft_chain_route.c>.
+    match (type_, hook) {
+        (ChainType::Nat, FilterHook::Forward) => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "NAT chains cannot be attached here")
+        }
+        (ChainType::Route, hook) if hook != FilterHook::LocalOut => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "route chains cannot be attached here")
+        }
+        _ => (),
+    }
+
+    Ok(BaseChain {
+        hook,
+        priority: priority as i32,
+        type_,
+        policy_accept: true,
+    })
+}
+
+/// Counts the rules that jump to or go to the chain.
+fn count_references(table: &Table, name: &str) -> usize {
+    table
+        .chains
+        .iter()
+        .flat_map(|chain| chain.rules.iter())
+        .flat_map(Rule::targets)
+        .filter(|target| *target == name)
+        .count()
+}
+
+fn chain_to_new_chain(
+    request_header: &CMsgSegHdr,
+    ruleset: &Ruleset,
+    table: &Table,
+    chain: &Chain,
+) -> NfnlSegment {
+    let mut attrs = vec![
+        NfAttr::new_str(NFTA_CHAIN_TABLE, &table.name),
+        NfAttr::new_u64(NFTA_CHAIN_HANDLE, chain.handle),
+        NfAttr::new_str(NFTA_CHAIN_NAME, &chain.name),
+    ];
+
+    if let Some(base) = chain.base.as_ref() {
+        let hook_attrs = [
+            NfAttr::new_u32(NFTA_HOOK_HOOKNUM, base.hook as u32),
+            NfAttr::new_u32(NFTA_HOOK_PRIORITY, base.priority as u32),
+        ];
+        let policy = if base.policy_accept {
+            Verdict::NF_ACCEPT
+        } else {
+            Verdict::NF_DROP
+        };
+        attrs.push(NfAttr::new_nested(NFTA_CHAIN_HOOK, &hook_attrs));
+        attrs.push(NfAttr::new_u32(NFTA_CHAIN_POLICY, policy as u32));
+        attrs.push(NfAttr::new_str(NFTA_CHAIN_TYPE, base.type_.name()));
+        attrs.push(NfAttr::new_u32(NFTA_CHAIN_FLAGS, NFT_CHAIN_BASE));
+    }
+
+    let references = count_references(table, &chain.name);
+    attrs.push(NfAttr::new_u32(NFTA_CHAIN_USE, references as u32));
+
+    new_response_segment(
+        request_header,
+        NftMsgType::NEWCHAIN,
+        table.family as u8,
+        ruleset,
+        attrs,
+    )
+}
@@ -0,0 +1,492 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Parse and dump the expressions of rules.
+
+use super::util::{NFT_NAME_MAXLEN, parse_u32_opt, parse_u64_opt};
+use crate::{
+    net::{
+        netfilter::{
+            CmpOp, Counter, CtKey, Expr, MetaKey, NatType, NfProto, PayloadBase, Register, Verdict,
+        },
+        socket::netlink::netfilter::message::{NfAttr, find_attr},
+    },
+    prelude::*,
+};
+
+/// The attribute of the elements in lists.
+const NFTA_LIST_ELEM: u16 = 1;
+
+/// Expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L460>.
+const NFTA_EXPR_NAME: u16 = 1;
+const NFTA_EXPR_DATA: u16 = 2;
+
+/// Data attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L518>.
+const NFTA_DATA_VALUE: u16 = 1;
+const NFTA_DATA_VERDICT: u16 = 2;
+
+/// Verdict attributes, which are nested in `NFTA_DATA_VERDICT`.
+const NFTA_VERDICT_CODE: u16 = 1;
+const NFTA_VERDICT_CHAIN: u16 = 2;
+const NFTA_VERDICT_CHAIN_ID: u16 = 3;
+
+/// The register that holds the verdict.
+const NFT_REG_VERDICT: u32 = 0;
+
+/// The maximum number of expressions in a rule.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nf_tables_api.c>.
+const NFT_RULE_MAXEXPRS: usize = 128;
+
+/// The maximum length of data values.
+const NFT_DATA_VALUE_MAXLEN: usize = 64;
+
+/// Parses the expressions in a list attribute.
+pub(super) fn parse_exprs(list_attr: &NfAttr) -> Result<Vec<Expr>> {
+    let elems = list_attr.as_nested()?;
+    if elems.len() > NFT_RULE_MAXEXPRS {
+        return_errno_with_message!(Errno::EINVAL, "there are too many expressions");
+    }
+
+    elems
+        .iter()
+        .map(|elem| {
+            if elem.class() != NFTA_LIST_ELEM {
+                return_errno_with_message!(Errno::EINVAL, "the list element is invalid");
+            }
+            parse_expr(&elem.as_nested()?)
+        })
+        .collect()
+}
+
+/// Dumps expressions as a list attribute.
+pub(super) fn exprs_to_attr(type_: u16, exprs: &[Expr]) -> NfAttr {
+    let elems: Vec<NfAttr> = exprs
+        .iter()
+        .map(|expr| NfAttr::new_nested(NFTA_LIST_ELEM, &expr_to_attrs(expr)))
+        .collect();
+    NfAttr::new_nested(type_, &elems)
+}
+
+fn parse_expr(attrs: &[NfAttr]) -> Result<Expr> {
+    let Some(name_attr) = find_attr(attrs, NFTA_EXPR_NAME) else {
+        return_errno_with_message!(Errno::EINVAL, "the expression name is not specified");
+    };
+    let name = name_attr.as_str(NFT_NAME_MAXLEN)?;
+    let data = match find_attr(attrs, NFTA_EXPR_DATA) {
+        Some(data_attr) => data_attr.as_nested()?,
+        None => Vec::new(),
+    };
+
+    match name.as_str() {
+        "payload" => parse_payload(&data),
+        "meta" => parse_meta(&data),
+        "cmp" => parse_cmp(&data),
+        "bitwise" => parse_bitwise(&data),
+        "immediate" => parse_immediate(&data),
+        "ct" => parse_ct(&data),
+        "nat" => parse_nat(&data),
+        "masq" => parse_masq(&data),
+        "counter" => parse_counter(&data),
+        _ => return_errno_with_message!(Errno::EOPNOTSUPP, "the expression is not supported"),
+    }
+}
+
+fn expr_to_attrs(expr: &Expr) -> Vec<NfAttr> {
+    let (name, data) = match expr {
+        Expr::Payload { dreg, base, offset } => (
+            "payload",
+            vec![
+                NfAttr::new_u32(NFTA_PAYLOAD_DREG, dreg.index()),
+                NfAttr::new_u32(NFTA_PAYLOAD_BASE, *base as u32),
+                NfAttr::new_u32(NFTA_PAYLOAD_OFFSET, *offset),
+                NfAttr::new_u32(NFTA_PAYLOAD_LEN, dreg.data_len() as u32),
+            ],
+        ),
+        Expr::Meta { dreg, key } => (
+            "meta",
+            vec![
+                NfAttr::new_u32(NFTA_META_DREG, dreg.index()),
+                NfAttr::new_u32(NFTA_META_KEY, *key as u32),
+            ],
+        ),
+        Expr::Cmp { sreg, op, data } => (
+            "cmp",
+            vec![
+                NfAttr::new_u32(NFTA_CMP_SREG, sreg.index()),
+                NfAttr::new_u32(NFTA_CMP_OP, *op as u32),
+                value_to_attr(NFTA_CMP_DATA, data),
+            ],
+        ),
+        Expr::Bitwise {
+            sreg,
+            dreg,
+            mask,
+            xor,
+        } => (
+            "bitwise",
+            vec![
+                NfAttr::new_u32(NFTA_BITWISE_SREG, sreg.index()),
+                NfAttr::new_u32(NFTA_BITWISE_DREG, dreg.index()),
+                NfAttr::new_u32(NFTA_BITWISE_LEN, sreg.data_len() as u32),
+                value_to_attr(NFTA_BITWISE_MASK, mask),
+                value_to_attr(NFTA_BITWISE_XOR, xor),
+            ],
+        ),
+        Expr::Immediate { dreg, data } => (
+            "immediate",
+            vec![
+                NfAttr::new_u32(NFTA_IMMEDIATE_DREG, dreg.index()),
+                value_to_attr(NFTA_IMMEDIATE_DATA, data),
+            ],
+        ),
+        Expr::Verdict(verdict) => {
+            let mut verdict_attrs = vec![NfAttr::new_u32(NFTA_VERDICT_CODE, verdict.code() as u32)];
+            if let Some(chain) = verdict.chain() {
+                verdict_attrs.push(NfAttr::new_str(NFTA_VERDICT_CHAIN, chain));
+            }
+            let data_attrs = [NfAttr::new_nested(NFTA_DATA_VERDICT, &verdict_attrs)];
+            (
+                "immediate",
+                vec![
+                    NfAttr::new_u32(NFTA_IMMEDIATE_DREG, NFT_REG_VERDICT),
+                    NfAttr::new_nested(NFTA_IMMEDIATE_DATA, &data_attrs),
+                ],
+            )
+        }
+        Expr::Ct { dreg, key } => (
+            "ct",
+            vec![
+                NfAttr::new_u32(NFTA_CT_DREG, dreg.index()),
+                NfAttr::new_u32(NFTA_CT_KEY, *key as u32),
+            ],
+        ),
+        Expr::Nat {
+            type_,
+            family,
+            addr,
+            port,
+            flags,
+        } => {
+            let mut data = vec![
+                NfAttr::new_u32(NFTA_NAT_TYPE, *type_ as u32),
+                NfAttr::new_u32(NFTA_NAT_FAMILY, *family as u32),
+            ];
+            if let Some(addr) = addr {
+                data.push(NfAttr::new_u32(NFTA_NAT_REG_ADDR_MIN, addr.index()));
+            }
+            if let Some(port) = port {
+                data.push(NfAttr::new_u32(NFTA_NAT_REG_PROTO_MIN, port.index()));
+            }
+            if *flags != 0 {
+                data.push(NfAttr::new_u32(NFTA_NAT_FLAGS, *flags));
+            }
+            ("nat", data)
+        }
+        Expr::Masq { port, flags } => {
+            let mut data = Vec::new();
+            if *flags != 0 {
+                data.push(NfAttr::new_u32(NFTA_MASQ_FLAGS, *flags));
+            }
+            if let Some(port) = port {
+                data.push(NfAttr::new_u32(NFTA_MASQ_REG_PROTO_MIN, port.index()));
+            }
+            ("masq", data)
+        }
+        Expr::Counter(counter) => (
+            "counter",
+            vec![
+                NfAttr::new_u64(NFTA_COUNTER_BYTES, counter.bytes()),
+                NfAttr::new_u64(NFTA_COUNTER_PACKETS, counter.packets()),
+            ],
+        ),
+    };
+
+    vec![
+        NfAttr::new_str(NFTA_EXPR_NAME, name),
+        NfAttr::new_nested(NFTA_EXPR_DATA, &data),
+    ]
+}
+
+/// Parses a data attribute that contains a value.
+fn parse_value(attrs: &[NfAttr], type_: u16) -> Result<Vec<u8>> {
+    let Some(data_attr) = find_attr(attrs, type_) else {
+        return_errno_with_message!(Errno::EINVAL, "the data is not specified");
+    };
+    let data_attrs = data_attr.as_nested()?;
+    let Some(value_attr) = find_attr(&data_attrs, NFTA_DATA_VALUE) else {
+        return_errno_with_message!(Errno::EINVAL, "the data is not a value");
+    };
+
+    let value = value_attr.as_bytes();
+    if value.is_empty() || value.len() > NFT_DATA_VALUE_MAXLEN {
+        return_errno_with_message!(Errno::EINVAL, "the data length is invalid");
+    }
+    Ok(value.to_vec())
+}
+
+fn value_to_attr(type_: u16, value: &[u8]) -> NfAttr {
+    let data_attrs = [NfAttr::new_bytes(NFTA_DATA_VALUE, value.to_vec())];
+    NfAttr::new_nested(type_, &data_attrs)
+}
+
+/// Parses the register in the attribute of the type, which must exist.
+fn parse_reg(attrs: &[NfAttr], type_: u16, len: usize) -> Result<Register> {
+    let Some(index) = parse_u32_opt(attrs, type_)? else {
+        return_errno_with_message!(Errno::EINVAL, "the register is not specified");
+    };
+    Register::new(index, len)
+}
+
+/// Parses an enumeration in the attribute of the type, which must exist.
+fn parse_enum<T: TryFrom<u32>>(attrs: &[NfAttr], type_: u16) -> Result<T> {
+    let Some(value) = parse_u32_opt(attrs, type_)? else {
+        return_errno_with_message!(Errno::EINVAL, "a required attribute is not specified");
+    };
+    T::try_from(value)
+        .map_err(|_| Error::with_message(Errno::EOPNOTSUPP, "the attribute value is not supported"))
+}
+
+/// Payload expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L808>.
+const NFTA_PAYLOAD_DREG: u16 = 1;
+const NFTA_PAYLOAD_BASE: u16 = 2;
+const NFTA_PAYLOAD_OFFSET: u16 = 3;
+const NFTA_PAYLOAD_LEN: u16 = 4;
+const NFTA_PAYLOAD_SREG: u16 = 5;
+
+fn parse_payload(attrs: &[NfAttr]) -> Result<Expr> {
+    if find_attr(attrs, NFTA_PAYLOAD_SREG).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "payload statements are not supported");
+    }
+
+    let base: PayloadBase = parse_enum(attrs, NFTA_PAYLOAD_BASE)?;
+    if base == PayloadBase::LinkLayer {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "link layer payloads are not supported");
+    }
+    let Some(offset) = parse_u32_opt(attrs, NFTA_PAYLOAD_OFFSET)? else {
+        return_errno_with_message!(Errno::EINVAL, "the payload offset is not specified");
+    };
+    let Some(len) = parse_u32_opt(attrs, NFTA_PAYLOAD_LEN)? else {
+        return_errno_with_message!(Errno::EINVAL, "the payload length is not specified");
+    };
+    let dreg = parse_reg(attrs, NFTA_PAYLOAD_DREG, len as usize)?;
+
+    Ok(Expr::Payload { dreg, base, offset })
+}
+
+/// Meta expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L980>.
+const NFTA_META_DREG: u16 = 1;
+const NFTA_META_KEY: u16 = 2;
+const NFTA_META_SREG: u16 = 3;
+
+fn parse_meta(attrs: &[NfAttr]) -> Result<Expr> {
+    if find_attr(attrs, NFTA_META_SREG).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "meta statements are not supported");
+    }
+
+    let key: MetaKey = parse_enum(attrs, NFTA_META_KEY)?;
+    let dreg = parse_reg(attrs, NFTA_META_DREG, key.data_len())?;
+
+    Ok(Expr::Meta { dreg, key })
+}
+
+/// Cmp expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L679>.
+const NFTA_CMP_SREG: u16 = 1;
+const NFTA_CMP_OP: u16 = 2;
+const NFTA_CMP_DATA: u16 = 3;
+
+fn parse_cmp(attrs: &[NfAttr]) -> Result<Expr> {
+    let op: CmpOp = parse_enum(attrs, NFTA_CMP_OP)?;
+    let data = parse_value(attrs, NFTA_CMP_DATA)?;
+    let sreg = parse_reg(attrs, NFTA_CMP_SREG, data.len())?;
+
+    Ok(Expr::Cmp { sreg, op, data })
+}
+
+/// Bitwise expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L583>.
+const NFTA_BITWISE_SREG: u16 = 1;
+const NFTA_BITWISE_DREG: u16 = 2;
+const NFTA_BITWISE_LEN: u16 = 3;
+const NFTA_BITWISE_MASK: u16 = 4;
+const NFTA_BITWISE_XOR: u16 = 5;
+const NFTA_BITWISE_OP: u16 = 6;
+
+/// The bitwise operation that computes `(sreg & mask) ^ xor`.
+const NFT_BITWISE_BOOL: u32 = 0;
+
+fn parse_bitwise(attrs: &[NfAttr]) -> Result<Expr> {
+    if parse_u32_opt(attrs, NFTA_BITWISE_OP)?.is_some_and(|op| op != NFT_BITWISE_BOOL) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the bitwise operation is not supported");
+    }
+
+    let Some(len) = parse_u32_opt(attrs, NFTA_BITWISE_LEN)? else {
+        return_errno_with_message!(Errno::EINVAL, "the bitwise length is not specified");
+    };
+    let len = len as usize;
+    let sreg = parse_reg(attrs, NFTA_BITWISE_SREG, len)?;
+    let dreg = parse_reg(attrs, NFTA_BITWISE_DREG, len)?;
+    let mask = parse_value(attrs, NFTA_BITWISE_MASK)?;
+    let xor = parse_value(attrs, NFTA_BITWISE_XOR)?;
+    if mask.len() != len || xor.len() != len {
+        return_errno_with_message!(Errno::EINVAL, "the bitwise data length is invalid");
+    }
+
+    Ok(Expr::Bitwise {
+        sreg,
+        dreg,
+        mask,
+        xor,
+    })
+}
+
+/// Immediate expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L556>.
+const NFTA_IMMEDIATE_DREG: u16 = 1;
+const NFTA_IMMEDIATE_DATA: u16 = 2;
+
+fn parse_immediate(attrs: &[NfAttr]) -> Result<Expr> {
+    let Some(dreg) = parse_u32_opt(attrs, NFTA_IMMEDIATE_DREG)? else {
+        return_errno_with_message!(Errno::EINVAL, "the register is not specified");
+    };
+    if dreg != NFT_REG_VERDICT {
+        let data = parse_value(attrs, NFTA_IMMEDIATE_DATA)?;
+        let dreg = Register::new(dreg, data.len())?;
+        return Ok(Expr::Immediate { dreg, data });
+    }
+
+    let Some(data_attr) = find_attr(attrs, NFTA_IMMEDIATE_DATA) else {
+        return_errno_with_message!(Errno::EINVAL, "the data is not specified");
+    };
+    let data_attrs = data_attr.as_nested()?;
+    let Some(verdict_attr) = find_attr(&data_attrs, NFTA_DATA_VERDICT) else {
+        return_errno_with_message!(Errno::EINVAL, "the data is not a verdict");
+    };
+    let verdict_attrs = verdict_attr.as_nested()?;
+
+    let Some(code) = parse_u32_opt(&verdict_attrs, NFTA_VERDICT_CODE)? else {
+        return_errno_with_message!(Errno::EINVAL, "the verdict code is not specified");
+    };
+    if find_attr(&verdict_attrs, NFTA_VERDICT_CHAIN_ID).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "chain IDs are not supported");
+    }
+    let chain = find_attr(&verdict_attrs, NFTA_VERDICT_CHAIN)
+        .map(|chain_attr| chain_attr.as_str(NFT_NAME_MAXLEN))
+        .transpose()?;
+
+    Ok(Expr::Verdict(Verdict::from_code(code as i32, chain)?))
+}
+
+/// Ct expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L1131>.
+const NFTA_CT_DREG: u16 = 1;
+const NFTA_CT_KEY: u16 = 2;
+const NFTA_CT_DIRECTION: u16 = 3;
+const NFTA_CT_SREG: u16 = 4;
+
+fn parse_ct(attrs: &[NfAttr]) -> Result<Expr> {
+    if find_attr(attrs, NFTA_CT_SREG).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "ct statements are not supported");
+    }
+    if find_attr(attrs, NFTA_CT_DIRECTION).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "ct directions are not supported");
+    }
+
+    let key: CtKey = parse_enum(attrs, NFTA_CT_KEY)?;
+    let dreg = parse_reg(attrs, NFTA_CT_DREG, key.data_len())?;
+
+    Ok(Expr::Ct { dreg, key })
+}
+
+/// Nat expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L1477>.
+const NFTA_NAT_TYPE: u16 = 1;
+const NFTA_NAT_FAMILY: u16 = 2;
+const NFTA_NAT_REG_ADDR_MIN: u16 = 3;
+const NFTA_NAT_REG_ADDR_MAX: u16 = 4;
+const NFTA_NAT_REG_PROTO_MIN: u16 = 5;
+const NFTA_NAT_REG_PROTO_MAX: u16 = 6;
+const NFTA_NAT_FLAGS: u16 = 7;
+
+fn parse_nat(attrs: &[NfAttr]) -> Result<Expr> {
+    let type_: NatType = parse_enum(attrs, NFTA_NAT_TYPE)?;
+    let family = match parse_u32_opt(attrs, NFTA_NAT_FAMILY)?.map(u8::try_from) {
+        Some(Ok(family)) => NfProto::try_from(family).ok(),
+        _ => None,
+    };
+    // TODO: Support NAT for IPv6 packets.
+    if family != Some(NfProto::Ipv4) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the NAT family is not supported");
+    }
+
+    let addr = parse_range(attrs, NFTA_NAT_REG_ADDR_MIN, NFTA_NAT_REG_ADDR_MAX, 4)?;
+    let port = parse_range(attrs, NFTA_NAT_REG_PROTO_MIN, NFTA_NAT_REG_PROTO_MAX, 2)?;
+    let flags = parse_u32_opt(attrs, NFTA_NAT_FLAGS)?.unwrap_or(0);
+
+    Ok(Expr::Nat {
+        type_,
+        family: NfProto::Ipv4,
+        addr,
+        port,
+        flags,
+    })
+}
+
+/// Masq expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L1405>.
+const NFTA_MASQ_FLAGS: u16 = 1;
+const NFTA_MASQ_REG_PROTO_MIN: u16 = 2;
+const NFTA_MASQ_REG_PROTO_MAX: u16 = 3;
+
+fn parse_masq(attrs: &[NfAttr]) -> Result<Expr> {
+    let port = parse_range(attrs, NFTA_MASQ_REG_PROTO_MIN, NFTA_MASQ_REG_PROTO_MAX, 2)?;
+    let flags = parse_u32_opt(attrs, NFTA_MASQ_FLAGS)?.unwrap_or(0);
+
+    Ok(Expr::Masq { port, flags })
+}
+
+/// Parses the registers that hold the minimum and the maximum of a range.
+///
+/// Only ranges that contain a single value are supported.
+fn parse_range(
+    attrs: &[NfAttr],
+    min_type: u16,
+    max_type: u16,
+    len: usize,
+) -> Result<Option<Register>> {
+    let Some(min) = parse_u32_opt(attrs, min_type)? else {
+        return Ok(None);
+    };
+    if parse_u32_opt(attrs, max_type)?.is_some_and(|max| max != min) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "NAT ranges are not supported");
+    }
+
+    Register::new(min, len).map(Some)
+}
+
+/// Counter expression attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L1199>.
+const NFTA_COUNTER_BYTES: u16 = 1;
+const NFTA_COUNTER_PACKETS: u16 = 2;
+
+fn parse_counter(attrs: &[NfAttr]) -> Result<Expr> {
+    let bytes = parse_u64_opt(attrs, NFTA_COUNTER_BYTES)?.unwrap_or(0);
+    let packets = parse_u64_opt(attrs, NFTA_COUNTER_PACKETS)?.unwrap_or(0);
+
+    Ok(Expr::Counter(Counter::new(packets, bytes)))
+}
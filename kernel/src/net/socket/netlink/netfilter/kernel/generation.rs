@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle generation-related requests.
+
+use super::util::new_response_segment;
+use crate::{
+    net::{
+        netfilter::Ruleset,
+        socket::netlink::netfilter::message::{NfAttr, NfSegment, NfnlSegment, NftMsgType},
+    },
+    prelude::*,
+};
+
+/// Generation attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L1634>.
+const NFTA_GEN_ID: u16 = 1;
+const NFTA_GEN_PROC_PID: u16 = 2;
+
+pub(super) fn do_get_gen(
+    ruleset: &Ruleset,
+    request_segment: &NfSegment,
+) -> Result<Vec<NfnlSegment>> {
+    const NFPROTO_UNSPEC: u8 = 0;
+
+    let attrs = vec![
+        NfAttr::new_u32(NFTA_GEN_ID, ruleset.generation()),
+        NfAttr::new_u32(NFTA_GEN_PROC_PID, current!().pid()),
+    ];
+
+    Ok(vec![new_response_segment(
+        request_segment.header(),
+        NftMsgType::NEWGEN,
+        NFPROTO_UNSPEC,
+        ruleset,
+        attrs,
+    )])
+}
@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines the kernel socket,
+//! which is responsible for handling requests from user space.
+
+use super::message::{NFNL_SUBSYS_NFTABLES, NfSegment, NfnlMessage, NfnlSegment, NftMsgType};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        netfilter::Ruleset,
+        socket::netlink::{
+            addr::PortNum,
+            message::{ErrorSegment, ProtocolSegment, SegHdrCommonFlags},
+            table::{NetlinkNetfilterProtocol, SupportedNetlinkProtocol},
+        },
+    },
+    prelude::*,
+};
+
+mod chain;
+mod expr;
+mod generation;
+mod rule;
+mod table;
+mod util;
+
+/// The kernel socket of a network namespace.
+pub(super) struct NetlinkNetfilterKernelSocket<'a> {
+    net_ns: &'a NetNamespace,
+}
+
+impl<'a> NetlinkNetfilterKernelSocket<'a> {
+    fn new(net_ns: &'a NetNamespace) -> Self {
+        Self { net_ns }
+    }
+
+    pub(super) fn handle_requests(
+        &self,
+        requests: Vec<core::result::Result<NfnlSegment, ErrorSegment>>,
+        dst_port: PortNum,
+    ) {
+        let mut requests = requests.into_iter();
+
+        while let Some(request) = requests.next() {
+            match request {
+                Ok(NfnlSegment::BatchBegin(begin)) => {
+                    self.handle_batch(&begin, &mut requests, dst_port)
+                }
+                Ok(request) => self.handle_request(&request, dst_port),
+                Err(err_segment) => self.report_error(err_segment, dst_port),
+            }
+        }
+    }
+
+    /// Handles a request that is not in a batch.
+    fn handle_request(&self, request: &NfnlSegment, dst_port: PortNum) {
+        debug!("netlink netfilter request: {:?}", request);
+
+        let request_header = request.header();
+
+        let response_segments = match request {
+            NfnlSegment::NfTables(msg_type, request_segment) if msg_type.is_get() => {
+                self.do_get(*msg_type, request_segment)
+            }
+            NfnlSegment::NfTables(..) => Err(Error::with_message(
+                Errno::EINVAL,
+                "the request must be sent in a batch",
+            )),
+            _ => Err(Error::with_message(
+                Errno::EINVAL,
+                "the batch is not started",
+            )),
+        };
+
+        let response = match response_segments {
+            Ok(segments) => NfnlMessage::new(segments),
+            Err(error) => {
+                let err_segment = ErrorSegment::new_from_request(request_header, Some(error));
+                self.report_error(err_segment, dst_port);
+                return;
+            }
+        };
+
+        debug!("netlink netfilter response: {:?}", response);
+
+        NetlinkNetfilterProtocol::unicast(dst_port, response).unwrap();
+    }
+
+    fn do_get(
+        &self,
+        msg_type: NftMsgType,
+        request_segment: &NfSegment,
+    ) -> Result<Vec<NfnlSegment>> {
+        self.net_ns.check_net_admin()?;
+
+        let ruleset = self.net_ns.netfilter().ruleset();
+        match msg_type {
+            NftMsgType::GETTABLE => table::do_get_table(&ruleset, request_segment),
+            NftMsgType::GETCHAIN => chain::do_get_chain(&ruleset, request_segment),
+            NftMsgType::GETRULE => rule::do_get_rule(&ruleset, request_segment),
+            NftMsgType::GETGEN => generation::do_get_gen(&ruleset, request_segment),
+            // Sets, objects, and flowtables are not supported, so there are none of them.
+            NftMsgType::GETSET
+            | NftMsgType::GETSETELEM
+            | NftMsgType::GETOBJ
+            | NftMsgType::GETOBJ_RESET
+            | NftMsgType::GETFLOWTABLE
+                if util::is_dump(request_segment.header()) =>
+            {
+                Ok(util::dump_response(request_segment.header(), Vec::new()))
+            }
+            _ => Err(Error::with_message(
+                Errno::EOPNOTSUPP,
+                "the netlink netfilter request is not supported",
+            )),
+        }
+    }
+
+    /// Handles a batch of requests, which are committed or aborted as a whole.
+    ///
+    /// The requests are consumed until the end of the batch.
+    fn handle_batch(
+        &self,
+        begin: &NfSegment,
+        requests: &mut impl Iterator<Item = core::result::Result<NfnlSegment, ErrorSegment>>,
+        dst_port: PortNum,
+    ) {
+        debug!("netlink netfilter batch: {:?}", begin);
+
+        let check_batch = || {
+            if begin.body().res_id != NFNL_SUBSYS_NFTABLES {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "the subsystem is not supported");
+            }
+            self.net_ns.check_net_admin()
+        };
+        if let Err(error) = check_batch() {
+            let err_segment = ErrorSegment::new_from_request(begin.header(), Some(error));
+            self.report_error(err_segment, dst_port);
+            // Like Linux, the rest of the batch is ignored.
+            requests.for_each(drop);
+            return;
+        }
+
+        let netfilter = self.net_ns.netfilter();
+        let guard = netfilter.lock_ruleset();
+        let mut ruleset = (*guard).clone();
+
+        let mut response_segments = Vec::new();
+        let mut is_failed = false;
+        let mut is_ended = false;
+
+        for request in requests.by_ref() {
+            let request = match request {
+                Ok(NfnlSegment::BatchEnd(_)) => {
+                    is_ended = true;
+                    break;
+                }
+                Ok(request) => request,
+                Err(err_segment) => {
+                    response_segments.push(NfnlSegment::Error(err_segment));
+                    is_failed = true;
+                    continue;
+                }
+            };
+
+            let request_header = request.header();
+            let result = match &request {
+                NfnlSegment::NfTables(msg_type, request_segment) => {
+                    Self::do_modify(&mut ruleset, *msg_type, request_segment)
+                }
+                _ => Err(Error::with_message(Errno::EINVAL, "the batch is not ended")),
+            };
+
+            match result {
+                Ok(()) => {
+                    let flags = SegHdrCommonFlags::from_bits_truncate(request_header.flags);
+                    if flags.contains(SegHdrCommonFlags::ACK) {
+                        let ack_segment = ErrorSegment::new_from_request(request_header, None);
+                        response_segments.push(NfnlSegment::Error(ack_segment));
+                    }
+                }
+                Err(error) => {
+                    let err_segment = ErrorSegment::new_from_request(request_header, Some(error));
+                    response_segments.push(NfnlSegment::Error(err_segment));
+                    is_failed = true;
+                }
+            }
+        }
+
+        // Like Linux, an incomplete batch is aborted.
+        if is_ended && !is_failed {
+            netfilter.commit(guard, ruleset);
+        } else {
+            drop(guard);
+        }
+
+        // Each response is a separate message.
+        for segment in response_segments {
+            let response = NfnlMessage::new(vec![segment]);
+
+            debug!("netlink netfilter response: {:?}", response);
+
+            NetlinkNetfilterProtocol::unicast(dst_port, response).unwrap();
+        }
+    }
+
+    fn do_modify(
+        ruleset: &mut Ruleset,
+        msg_type: NftMsgType,
+        request_segment: &NfSegment,
+    ) -> Result<()> {
+        match msg_type {
+            NftMsgType::NEWTABLE => table::do_new_table(ruleset, request_segment),
+            NftMsgType::DELTABLE => table::do_del_table(ruleset, request_segment),
+            NftMsgType::NEWCHAIN => chain::do_new_chain(ruleset, request_segment),
+            NftMsgType::DELCHAIN => chain::do_del_chain(ruleset, request_segment),
+            NftMsgType::NEWRULE => rule::do_new_rule(ruleset, request_segment),
+            NftMsgType::DELRULE => rule::do_del_rule(ruleset, request_segment),
+            msg_type if msg_type.is_get() => Err(Error::with_message(
+                Errno::EINVAL,
+                "the request cannot be sent in a batch",
+            )),
+            _ => Err(Error::with_message(
+                Errno::EOPNOTSUPP,
+                "the netlink netfilter request is not supported",
+            )),
+        }
+    }
+
+    fn report_error(&self, err_segment: ErrorSegment, dst_port: PortNum) {
+        let response = NfnlMessage::new(vec![NfnlSegment::Error(err_segment)]);
+
+        debug!("netlink netfilter error: {:?}", response);
+
+        NetlinkNetfilterProtocol::unicast(dst_port, response).unwrap();
+    }
+}
+
+/// Returns the kernel socket of the given network namespace.
+pub(super) fn get_netlink_netfilter_kernel(
+    net_ns: &NetNamespace,
+) -> NetlinkNetfilterKernelSocket<'_> {
+    NetlinkNetfilterKernelSocket::new(net_ns)
+}
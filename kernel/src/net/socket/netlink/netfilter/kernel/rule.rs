@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle rule-related requests.
+
+use aster_bigtcp::iface::FilterHook;
+
+use super::{
+    expr::{exprs_to_attr, parse_exprs},
+    util::{
+        dump_response, is_dump, new_response_segment, parse_family, parse_family_filter,
+        parse_name, parse_name_opt, parse_u64_opt,
+    },
+};
+use crate::{
+    net::{
+        netfilter::{Chain, ChainType, Expr, NatType, Rule, Ruleset, Table},
+        socket::netlink::{
+            message::{CMsgSegHdr, NewRequestFlags},
+            netfilter::message::{NfAttr, NfSegment, NfnlSegment, NftMsgType, find_attr},
+        },
+    },
+    prelude::*,
+};
+
+/// Rule attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L249>.
+const NFTA_RULE_TABLE: u16 = 1;
+const NFTA_RULE_CHAIN: u16 = 2;
+const NFTA_RULE_HANDLE: u16 = 3;
+const NFTA_RULE_EXPRESSIONS: u16 = 4;
+const NFTA_RULE_COMPAT: u16 = 5;
+const NFTA_RULE_POSITION: u16 = 6;
+const NFTA_RULE_USERDATA: u16 = 7;
+
+/// The maximum length of the user data of a rule.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L5>.
+const NFT_USERDATA_MAXLEN: usize = 256;
+
+pub(super) fn do_new_rule(ruleset: &mut Ruleset, request_segment: &NfSegment) -> Result<()> {
+    let family = parse_family(request_segment.body().family)?;
+    let attrs = request_segment.attrs();
+
+    let table_name = parse_name(attrs, NFTA_RULE_TABLE)?;
+    let Some(table) = ruleset.table_mut(family, &table_name) else {
+        return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+    };
+    let chain_name = parse_name(attrs, NFTA_RULE_CHAIN)?;
+    let Some(chain) = table.chain(&chain_name) else {
+        return_errno_with_message!(Errno::ENOENT, "the chain does not exist");
+    };
+
+    if find_attr(attrs, NFTA_RULE_COMPAT).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "xtables extensions are not supported");
+    }
+    let exprs = match find_attr(attrs, NFTA_RULE_EXPRESSIONS) {
+        Some(exprs_attr) => parse_exprs(exprs_attr)?,
+        None => Vec::new(),
+    };
+    let userdata = find_attr(attrs, NFTA_RULE_USERDATA)
+        .map(|userdata_attr| {
+            let bytes = userdata_attr.as_bytes();
+            if bytes.len() > NFT_USERDATA_MAXLEN {
+                return_errno_with_message!(Errno::EINVAL, "the user data is too long");
+            }
+            Ok(bytes.to_vec())
+        })
+        .transpose()?;
+    check_exprs(table, chain, &exprs)?;
+
+    let handle = parse_u64_opt(attrs, NFTA_RULE_HANDLE)?;
+    let position = parse_u64_opt(attrs, NFTA_RULE_POSITION)?;
+    let request_flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+
+    // Find where to insert the rule and which rule to replace.
+    let (index, replaced) = if let Some(handle) = handle {
+        if !request_flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "rules can only be replaced");
+        }
+        if request_flags.contains(NewRequestFlags::EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the rule already exists");
+        }
+        (rule_index(chain, handle)?, true)
+    } else if let Some(position) = position {
+        let index = rule_index(chain, position)?;
+        if request_flags.contains(NewRequestFlags::APPEND) {
+            (index + 1, false)
+        } else {
+            (index, false)
+        }
+    } else if request_flags.contains(NewRequestFlags::APPEND) {
+        (chain.rules.len(), false)
+    } else {
+        (0, false)
+    };
+
+    // The handle is allocated only after the request is validated, so the handles are not
+    // wasted by invalid requests.
+    let rule = Rule {
+        handle: table.alloc_handle(),
+        exprs,
+        userdata,
+    };
+    let chain = table.chain_mut(&chain_name).unwrap();
+    if replaced {
+        chain.rules[index] = rule;
+    } else {
+        chain.rules.insert(index, rule);
+    }
+
+    Ok(())
+}
+
+pub(super) fn do_del_rule(ruleset: &mut Ruleset, request_segment: &NfSegment) -> Result<()> {
+    let family = parse_family(request_segment.body().family)?;
+    let attrs = request_segment.attrs();
+
+    let table_name = parse_name(attrs, NFTA_RULE_TABLE)?;
+    let Some(table) = ruleset.table_mut(family, &table_name) else {
+        return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+    };
+
+    let chain_name = parse_name_opt(attrs, NFTA_RULE_CHAIN)?;
+    let handle = parse_u64_opt(attrs, NFTA_RULE_HANDLE)?;
+
+    match (chain_name, handle) {
+        (Some(chain_name), Some(handle)) => {
+            let Some(chain) = table.chain_mut(&chain_name) else {
+                return_errno_with_message!(Errno::ENOENT, "the chain does not exist");
+            };
+            let index = rule_index(chain, handle)?;
+            chain.rules.remove(index);
+        }
+        // Without a handle, all the rules of the chain are deleted (e.g., by `nft flush chain`).
+        (Some(chain_name), None) => {
+            let Some(chain) = table.chain_mut(&chain_name) else {
+                return_errno_with_message!(Errno::ENOENT, "the chain does not exist");
+            };
+            chain.rules.clear();
+        }
+        // Without a chain, all the rules of the table are deleted (e.g., by `nft flush table`).
+        (None, None) => table
+            .chains
+            .iter_mut()
+            .for_each(|chain| chain.rules.clear()),
+        (None, Some(_)) => {
+            return_errno_with_message!(Errno::EINVAL, "the chain is not specified")
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) fn do_get_rule(
+    ruleset: &Ruleset,
+    request_segment: &NfSegment,
+) -> Result<Vec<NfnlSegment>> {
+    let request_header = request_segment.header();
+    if !is_dump(request_header) {
+        // TODO: Support getting a single rule.
+        return_errno_with_message!(Errno::EOPNOTSUPP, "only dumping rules is supported");
+    }
+
+    let attrs = request_segment.attrs();
+    let family = parse_family_filter(request_segment.body().family)?;
+    let table_name = parse_name_opt(attrs, NFTA_RULE_TABLE)?;
+    let chain_name = parse_name_opt(attrs, NFTA_RULE_CHAIN)?;
+
+    let response_segments = ruleset
+        .tables
+        .iter()
+        .filter(|table| family.is_none_or(|family| table.family == family))
+        .filter(|table| table_name.as_ref().is_none_or(|name| *name == table.name))
+        .flat_map(|table| {
+            table
+                .chains
+                .iter()
+                .filter(|chain| chain_name.as_ref().is_none_or(|name| *name == chain.name))
+                .map(move |chain| (table, chain))
+        })
+        .flat_map(|(table, chain)| {
+            chain.rules.iter().enumerate().map(move |(index, rule)| {
+                let previous = index.checked_sub(1).map(|index| &chain.rules[index]);
+                rule_to_new_rule(request_header, ruleset, table, chain, rule, previous)
+            })
+        })
+        .collect();
+
+    Ok(dump_response(request_header, response_segments))
+}
+
+/// Checks whether the expressions can be used in the chain.
+fn check_exprs(table: &Table, chain: &Chain, exprs: &[Expr]) -> Result<()> {
+    for expr in exprs.iter() {
+        match expr {
+            Expr::Verdict(verdict) => {
+                let Some(target) = verdict.chain() else {
+                    continue;
+                };
+                let Some(target_chain) = table.chain(target) else {
+                    return_errno_with_message!(Errno::ENOENT, "the target chain does not exist");
+                };
+                if target_chain.base.is_some() {
+                    return_errno_with_message!(
+                        Errno::EOPNOTSUPP,
+                        "jumping to base chains is not supported"
+                    );
+                }
+                if table.reaches(target, &chain.name) {
+                    return_errno_with_message!(Errno::ELOOP, "the jump creates a loop");
+                }
+            }
+            // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netfilter/nft_nat.c>.
+            Expr::Nat { type_, .. } => {
+                let hooks: &[FilterHook] = match type_ {
+                    NatType::Snat => &[FilterHook::PostRouting, FilterHook::LocalIn],
+                    NatType::Dnat => &[FilterHook::PreRouting, FilterHook::LocalOut],
+                };
+                check_nat_chain(chain, hooks)?;
+            }
+            Expr::Masq { .. } => check_nat_chain(chain, &[FilterHook::PostRouting])?,
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether NAT expressions can be used in the chain, which must be a NAT base chain
+/// attached to one of the hooks.
+///
+/// NAT expressions in regular chains are not supported, since the chains may be reached from
+/// any hooks.
+fn check_nat_chain(chain: &Chain, hooks: &[FilterHook]) -> Result<()> {
+    let Some(base) = chain.base else {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "NAT expressions in regular chains are not supported"
+        );
+    };
+    if base.type_ != ChainType::Nat || !hooks.contains(&base.hook) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "NAT expressions cannot be used here");
+    }
+
+    Ok(())
+}
+
+/// Returns the index of the rule with the handle.
+fn rule_index(chain: &Chain, handle: u64) -> Result<usize> {
+    chain
+        .rules
+        .iter()
+        .position(|rule| rule.handle == handle)
+        .ok_or_else(|| Error::with_message(Errno::ENOENT, "the rule does not exist"))
+}
+
+fn rule_to_new_rule(
+    request_header: &CMsgSegHdr,
+    ruleset: &Ruleset,
+    table: &Table,
+    chain: &Chain,
+    rule: &Rule,
+    previous: Option<&Rule>,
+) -> NfnlSegment {
+    let mut attrs = vec![
+        NfAttr::new_str(NFTA_RULE_TABLE, &table.name),
+        NfAttr::new_str(NFTA_RULE_CHAIN, &chain.name),
+        NfAttr::new_u64(NFTA_RULE_HANDLE, rule.handle),
+        exprs_to_attr(NFTA_RULE_EXPRESSIONS, &rule.exprs),
+    ];
+    if let Some(previous) = previous {
+        attrs.push(NfAttr::new_u64(NFTA_RULE_POSITION, previous.handle));
+    }
+    if let Some(userdata) = rule.userdata.as_ref() {
+        attrs.push(NfAttr::new_bytes(NFTA_RULE_USERDATA, userdata.clone()));
+    }
+
+    new_response_segment(
+        request_header,
+        NftMsgType::NEWRULE,
+        table.family as u8,
+        ruleset,
+        attrs,
+    )
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle table-related requests.
+
+use super::util::{
+    dump_response, is_dump, new_response_segment, parse_family, parse_family_filter, parse_name,
+    parse_name_opt, parse_u32_opt, parse_u64_opt,
+};
+use crate::{
+    net::{
+        netfilter::{Ruleset, Table},
+        socket::netlink::{
+            message::{CMsgSegHdr, NewRequestFlags},
+            netfilter::message::{NfAttr, NfSegment, NfnlSegment, NftMsgType},
+        },
+    },
+    prelude::*,
+};
+
+/// Table attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L169>.
+const NFTA_TABLE_NAME: u16 = 1;
+const NFTA_TABLE_FLAGS: u16 = 2;
+const NFTA_TABLE_USE: u16 = 3;
+const NFTA_TABLE_HANDLE: u16 = 4;
+
+pub(super) fn do_new_table(ruleset: &mut Ruleset, request_segment: &NfSegment) -> Result<()> {
+    let family = parse_family(request_segment.body().family)?;
+    let attrs = request_segment.attrs();
+    let name = parse_name(attrs, NFTA_TABLE_NAME)?;
+
+    let flags = parse_u32_opt(attrs, NFTA_TABLE_FLAGS)?;
+    if flags.is_some_and(|flags| flags & !Table::NFT_TABLE_F_DORMANT != 0) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the table flags are not supported");
+    }
+
+    let request_flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    if let Some(table) = ruleset.table_mut(family, &name) {
+        if request_flags.contains(NewRequestFlags::EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the table already exists");
+        }
+        if request_flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "tables cannot be replaced");
+        }
+        if let Some(flags) = flags {
+            table.flags = flags;
+        }
+        return Ok(());
+    }
+
+    let handle = ruleset.alloc_table_handle();
+    ruleset
+        .tables
+        .push(Table::new(name, family, handle, flags.unwrap_or(0)));
+
+    Ok(())
+}
+
+pub(super) fn do_del_table(ruleset: &mut Ruleset, request_segment: &NfSegment) -> Result<()> {
+    let attrs = request_segment.attrs();
+    let name = parse_name_opt(attrs, NFTA_TABLE_NAME)?;
+    let handle = parse_u64_opt(attrs, NFTA_TABLE_HANDLE)?;
+
+    // Without a name or a handle, all the tables of the family are deleted (e.g., by `nft
+    // flush ruleset`).
+    if name.is_none() && handle.is_none() {
+        let family = parse_family_filter(request_segment.body().family)?;
+        ruleset
+            .tables
+            .retain(|table| family.is_some_and(|family| table.family != family));
+        return Ok(());
+    }
+
+    let family = parse_family(request_segment.body().family)?;
+    let Some(index) = ruleset.tables.iter().position(|table| {
+        table.family == family
+            && match handle {
+                Some(handle) => table.handle == handle,
+                None => name.as_ref() == Some(&table.name),
+            }
+    }) else {
+        return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+    };
+    ruleset.tables.remove(index);
+
+    Ok(())
+}
+
+pub(super) fn do_get_table(
+    ruleset: &Ruleset,
+    request_segment: &NfSegment,
+) -> Result<Vec<NfnlSegment>> {
+    let request_header = request_segment.header();
+
+    if is_dump(request_header) {
+        let family = parse_family_filter(request_segment.body().family)?;
+        let response_segments = ruleset
+            .tables
+            .iter()
+            .filter(|table| family.is_none_or(|family| table.family == family))
+            .map(|table| table_to_new_table(request_header, ruleset, table))
+            .collect();
+        return Ok(dump_response(request_header, response_segments));
+    }
+
+    let family = parse_family(request_segment.body().family)?;
+    let name = parse_name(request_segment.attrs(), NFTA_TABLE_NAME)?;
+    let Some(table) = ruleset.table(family, &name) else {
+        return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+    };
+
+    Ok(vec![table_to_new_table(request_header, ruleset, table)])
+}
+
+fn table_to_new_table(
+    request_header: &CMsgSegHdr,
+    ruleset: &Ruleset,
+    table: &Table,
+) -> NfnlSegment {
+    let attrs = vec![
+        NfAttr::new_str(NFTA_TABLE_NAME, &table.name),
+        NfAttr::new_u32(NFTA_TABLE_FLAGS, table.flags),
+        NfAttr::new_u32(NFTA_TABLE_USE, table.chains.len() as u32),
+        NfAttr::new_u64(NFTA_TABLE_HANDLE, table.handle),
+    ];
+
+    new_response_segment(
+        request_header,
+        NftMsgType::NEWTABLE,
+        table.family as u8,
+        ruleset,
+        attrs,
+    )
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    net::{
+        netfilter::{NfProto, Ruleset},
+        socket::netlink::{
+            message::{
+                CMsgSegHdr, DoneSegment, GetRequestFlags, ProtocolSegment, SegHdrCommonFlags,
+            },
+            netfilter::message::{
+                NfAttr, NfGenMsgBody, NfSegment, NfnlSegment, NftMsgType, find_attr,
+            },
+        },
+    },
+    prelude::*,
+};
+
+/// The maximum length of the names of tables, chains, and other objects, including the
+/// terminating null byte.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L5>.
+pub const NFT_NAME_MAXLEN: usize = 256;
+
+/// Parses the address family of an object.
+pub fn parse_family(family: u8) -> Result<NfProto> {
+    NfProto::try_from(family).map_err(|_| {
+        Error::with_message(Errno::EAFNOSUPPORT, "the address family is not supported")
+    })
+}
+
+/// Parses the address family that filters the objects, where `NFPROTO_UNSPEC` matches all
+/// families.
+pub fn parse_family_filter(family: u8) -> Result<Option<NfProto>> {
+    const NFPROTO_UNSPEC: u8 = 0;
+
+    if family == NFPROTO_UNSPEC {
+        return Ok(None);
+    }
+    parse_family(family).map(Some)
+}
+
+/// Parses the name in the attribute of the type, which must exist.
+pub fn parse_name(attrs: &[NfAttr], type_: u16) -> Result<String> {
+    find_attr(attrs, type_)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the name is not specified"))?
+        .as_str(NFT_NAME_MAXLEN)
+}
+
+/// Parses the name in the attribute of the type, if it exists.
+pub fn parse_name_opt(attrs: &[NfAttr], type_: u16) -> Result<Option<String>> {
+    find_attr(attrs, type_)
+        .map(|attr| attr.as_str(NFT_NAME_MAXLEN))
+        .transpose()
+}
+
+/// Parses the integer in the attribute of the type, if it exists.
+pub fn parse_u32_opt(attrs: &[NfAttr], type_: u16) -> Result<Option<u32>> {
+    find_attr(attrs, type_).map(NfAttr::as_u32).transpose()
+}
+
+/// Parses the integer in the attribute of the type, if it exists.
+pub fn parse_u64_opt(attrs: &[NfAttr], type_: u16) -> Result<Option<u64>> {
+    find_attr(attrs, type_).map(NfAttr::as_u64).transpose()
+}
+
+/// Returns whether the request asks for all objects.
+pub fn is_dump(request_header: &CMsgSegHdr) -> bool {
+    let flags = GetRequestFlags::from_bits_truncate(request_header.flags);
+    flags.contains(GetRequestFlags::DUMP)
+}
+
+/// Creates a response segment that describes an object.
+pub fn new_response_segment(
+    request_header: &CMsgSegHdr,
+    msg_type: NftMsgType,
+    family: u8,
+    ruleset: &Ruleset,
+    attrs: Vec<NfAttr>,
+) -> NfnlSegment {
+    let header = CMsgSegHdr {
+        len: 0,
+        type_: msg_type.segment_type(),
+        flags: SegHdrCommonFlags::empty().bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    };
+
+    let body = NfGenMsgBody {
+        family,
+        res_id: ruleset.generation() as u16,
+    };
+
+    NfnlSegment::NfTables(msg_type, NfSegment::new(header, body, attrs))
+}
+
+/// Finishes the response to a dump request.
+pub fn dump_response(
+    request_header: &CMsgSegHdr,
+    mut response_segments: Vec<NfnlSegment>,
+) -> Vec<NfnlSegment> {
+    let done_segment = DoneSegment::new_from_request(request_header, None);
+    response_segments.push(NfnlSegment::Done(done_segment));
+
+    for segment in response_segments.iter_mut() {
+        let header = segment.header_mut();
+        let mut flags = SegHdrCommonFlags::from_bits_truncate(header.flags);
+        flags |= SegHdrCommonFlags::MULTI;
+        header.flags = flags.bits();
+    }
+
+    response_segments
+}
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    net::socket::netlink::message::{Attribute, CAttrHeader, ContinueRead, IS_NESTED_MASK},
+    prelude::*,
+    util::MultiRead,
+};
+
+/// A netfilter attribute.
+///
+/// Unlike netlink route attributes, the meanings of nf_tables attributes depend on the
+/// enclosing objects and expressions, and many of them are deeply nested. Therefore, the
+/// attributes are kept as raw bytes and interpreted by the handlers. Integers are in network
+/// byte order.
+#[derive(Debug)]
+pub struct NfAttr {
+    /// The attribute type, which includes [`IS_NESTED_MASK`] for nested attributes.
+    type_: u16,
+    payload: Vec<u8>,
+}
+
+impl NfAttr {
+    pub fn new_bytes(type_: u16, payload: Vec<u8>) -> Self {
+        Self { type_, payload }
+    }
+
+    pub fn new_u32(type_: u16, value: u32) -> Self {
+        Self::new_bytes(type_, value.to_be_bytes().to_vec())
+    }
+
+    pub fn new_u64(type_: u16, value: u64) -> Self {
+        Self::new_bytes(type_, value.to_be_bytes().to_vec())
+    }
+
+    pub fn new_str(type_: u16, value: &str) -> Self {
+        let mut payload = Vec::with_capacity(value.len() + 1);
+        payload.extend_from_slice(value.as_bytes());
+        payload.push(0);
+        Self::new_bytes(type_, payload)
+    }
+
+    pub fn new_nested(type_: u16, attrs: &[NfAttr]) -> Self {
+        let len = attrs.iter().map(|attr| attr.total_len_with_padding()).sum();
+        let mut payload = vec![0; len];
+
+        let mut writer = VmWriter::from(payload.as_mut_slice()).to_fallible();
+        for attr in attrs.iter() {
+            attr.write_to(&mut writer).unwrap();
+        }
+
+        Self::new_bytes(type_ | IS_NESTED_MASK, payload)
+    }
+
+    /// Returns the attribute type, excluding the flags.
+    pub fn class(&self) -> u16 {
+        self.type_ & !IS_NESTED_MASK
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn as_u32(&self) -> Result<u32> {
+        let bytes = <[u8; 4]>::try_from(self.payload.as_slice())
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the attribute is not a u32"))?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    pub fn as_u64(&self) -> Result<u64> {
+        let bytes = <[u8; 8]>::try_from(self.payload.as_slice())
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the attribute is not a u64"))?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Interprets the attribute as a null-terminated string of at most `max_len` bytes.
+    pub fn as_str(&self, max_len: usize) -> Result<String> {
+        let bytes = match self.payload.iter().position(|byte| *byte == 0) {
+            Some(nul_pos) => &self.payload[..nul_pos],
+            None => &self.payload,
+        };
+        if bytes.len() >= max_len {
+            return_errno_with_message!(Errno::ENAMETOOLONG, "the name is too long");
+        }
+
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the string is not valid UTF-8"))
+    }
+
+    pub fn as_nested(&self) -> Result<Vec<NfAttr>> {
+        Self::read_all_from_bytes(&self.payload)
+    }
+}
+
+impl Attribute for NfAttr {
+    fn type_(&self) -> u16 {
+        self.type_
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let mut payload = vec![0; header.payload_len()];
+        reader.read(&mut VmWriter::from(payload.as_mut_slice()))?;
+
+        Ok(ContinueRead::Parsed(Self {
+            type_: header.type_(),
+            payload,
+        }))
+    }
+}
+
+/// Finds the attribute of the type.
+///
+/// If there are multiple attributes of the type, the last one is returned, which matches the
+/// behavior of `nla_parse` in Linux.
+pub fn find_attr(attrs: &[NfAttr], type_: u16) -> Option<&NfAttr> {
+    attrs.iter().rev().find(|attr| attr.class() == type_)
+}
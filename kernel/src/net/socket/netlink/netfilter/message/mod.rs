@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink message types for the netlink netfilter protocol.
+//!
+//! This module defines how to interpret messages sent from user space and how to write
+//! kernel messages back to user space.
+
+mod attr;
+mod segment;
+
+pub(super) use attr::{NfAttr, find_attr};
+pub(super) use segment::{NFNL_SUBSYS_NFTABLES, NfGenMsgBody, NfSegment, NfnlSegment, NftMsgType};
+
+use crate::net::socket::netlink::message::Message;
+
+/// A netlink netfilter message.
+pub(in crate::net::socket::netlink) type NfnlMessage = Message<NfnlSegment>;
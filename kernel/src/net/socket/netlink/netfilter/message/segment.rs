@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::attr::NfAttr;
+use crate::{
+    net::socket::netlink::message::{
+        CMsgSegHdr, ContinueRead, DoneSegment, ErrorSegment, ProtocolSegment, SegmentBody,
+        SegmentCommon,
+    },
+    prelude::*,
+    util::{MultiRead, MultiWrite},
+};
+
+pub type NfSegment = SegmentCommon<NfGenMsgBody, NfAttr>;
+
+impl SegmentBody for NfGenMsgBody {
+    type CType = CNfGenMsg;
+}
+
+/// `nfgenmsg` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nfnetlink.h#L33>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CNfGenMsg {
+    /// The address family (e.g., `NFPROTO_INET`)
+    pub family: u8,
+    /// The version of the netlink protocol
+    pub version: u8,
+    /// The resource ID in network byte order
+    pub res_id: u16,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NfGenMsgBody {
+    pub family: u8,
+    /// The resource ID.
+    ///
+    /// In requests, this is the subsystem ID for batch messages. In responses, this is the low
+    /// 16 bits of the generation of the ruleset.
+    pub res_id: u16,
+}
+
+/// The version of the netlink protocol.
+const NFNETLINK_V0: u8 = 0;
+
+impl TryFrom<CNfGenMsg> for NfGenMsgBody {
+    type Error = Error;
+
+    fn try_from(value: CNfGenMsg) -> Result<Self> {
+        Ok(Self {
+            family: value.family,
+            res_id: u16::from_be(value.res_id),
+        })
+    }
+}
+
+impl From<NfGenMsgBody> for CNfGenMsg {
+    fn from(value: NfGenMsgBody) -> Self {
+        Self {
+            family: value.family,
+            version: NFNETLINK_V0,
+            res_id: value.res_id.to_be(),
+        }
+    }
+}
+
+/// The subsystem ID of nf_tables.
+pub const NFNL_SUBSYS_NFTABLES: u16 = 10;
+
+/// The message type that begins a batch.
+const NFNL_MSG_BATCH_BEGIN: u16 = 0x10;
+/// The message type that ends a batch.
+const NFNL_MSG_BATCH_END: u16 = 0x11;
+
+/// The types of nf_tables messages.
+///
+/// The type of the netlink segment is `(NFNL_SUBSYS_NFTABLES << 8) | msg_type`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/netfilter/nf_tables.h#L105>.
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum NftMsgType {
+    NEWTABLE = 0,
+    GETTABLE = 1,
+    DELTABLE = 2,
+    NEWCHAIN = 3,
+    GETCHAIN = 4,
+    DELCHAIN = 5,
+    NEWRULE = 6,
+    GETRULE = 7,
+    DELRULE = 8,
+    NEWSET = 9,
+    GETSET = 10,
+    DELSET = 11,
+    NEWSETELEM = 12,
+    GETSETELEM = 13,
+    DELSETELEM = 14,
+    NEWGEN = 15,
+    GETGEN = 16,
+    TRACE = 17,
+    NEWOBJ = 18,
+    GETOBJ = 19,
+    DELOBJ = 20,
+    GETOBJ_RESET = 21,
+    NEWFLOWTABLE = 22,
+    GETFLOWTABLE = 23,
+    DELFLOWTABLE = 24,
+    // TODO: The list is not exhaustive.
+}
+
+impl NftMsgType {
+    /// Returns the type of the netlink segment.
+    pub fn segment_type(&self) -> u16 {
+        (NFNL_SUBSYS_NFTABLES << 8) | (*self as u16)
+    }
+
+    /// Returns whether the message is a read-only request, which is not sent in batches.
+    pub fn is_get(&self) -> bool {
+        matches!(
+            self,
+            Self::GETTABLE
+                | Self::GETCHAIN
+                | Self::GETRULE
+                | Self::GETSET
+                | Self::GETSETELEM
+                | Self::GETGEN
+                | Self::GETOBJ
+                | Self::GETOBJ_RESET
+                | Self::GETFLOWTABLE
+        )
+    }
+}
+
+/// The netlink netfilter segment, which is the basic unit of a netlink netfilter message.
+#[derive(Debug)]
+pub enum NfnlSegment {
+    BatchBegin(NfSegment),
+    BatchEnd(NfSegment),
+    NfTables(NftMsgType, NfSegment),
+    Done(DoneSegment),
+    Error(ErrorSegment),
+}
+
+impl ProtocolSegment for NfnlSegment {
+    fn header(&self) -> &CMsgSegHdr {
+        match self {
+            NfnlSegment::BatchBegin(segment)
+            | NfnlSegment::BatchEnd(segment)
+            | NfnlSegment::NfTables(_, segment) => segment.header(),
+            NfnlSegment::Done(done_segment) => done_segment.header(),
+            NfnlSegment::Error(error_segment) => error_segment.header(),
+        }
+    }
+
+    fn header_mut(&mut self) -> &mut CMsgSegHdr {
+        match self {
+            NfnlSegment::BatchBegin(segment)
+            | NfnlSegment::BatchEnd(segment)
+            | NfnlSegment::NfTables(_, segment) => segment.header_mut(),
+            NfnlSegment::Done(done_segment) => done_segment.header_mut(),
+            NfnlSegment::Error(error_segment) => error_segment.header_mut(),
+        }
+    }
+
+    fn read_from(reader: &mut dyn MultiRead) -> Result<ContinueRead<Self, ErrorSegment>> {
+        let header = reader
+            .read_val_opt::<CMsgSegHdr>()?
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the reader length is too small"))?;
+
+        let msg_type = if header.type_ >> 8 == NFNL_SUBSYS_NFTABLES {
+            NftMsgType::try_from((header.type_ & 0xff) as u8).ok()
+        } else {
+            None
+        };
+
+        let segment = match (header.type_, msg_type) {
+            (NFNL_MSG_BATCH_BEGIN, _) => {
+                NfSegment::read_from(&header, reader)?.map(NfnlSegment::BatchBegin)
+            }
+            (NFNL_MSG_BATCH_END, _) => {
+                NfSegment::read_from(&header, reader)?.map(NfnlSegment::BatchEnd)
+            }
+            (_, Some(msg_type)) => NfSegment::read_from(&header, reader)?
+                .map(|segment| NfnlSegment::NfTables(msg_type, segment)),
+            (_, None) => {
+                let payload_len = header.calc_payload_len_with_padding(reader)?;
+                reader.skip_some(payload_len);
+                ContinueRead::skipped_with_error(
+                    Errno::EOPNOTSUPP,
+                    "the segment type is not supported",
+                )
+            }
+        };
+
+        Ok(segment.map_err(|error| ErrorSegment::new_from_request(&header, Some(error))))
+    }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        match self {
+            NfnlSegment::NfTables(_, segment) => segment.write_to(writer)?,
+            NfnlSegment::Done(done_segment) => done_segment.write_to(writer)?,
+            NfnlSegment::Error(error_segment) => error_segment.write_to(writer)?,
+            NfnlSegment::BatchBegin(_) | NfnlSegment::BatchEnd(_) => {
+                unreachable!("kernel should not write batch messages to user space");
+            }
+        }
+        Ok(())
+    }
+}
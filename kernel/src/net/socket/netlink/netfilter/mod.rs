@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink Netfilter Socket.
+
+pub(super) use message::NfnlMessage;
+
+use crate::net::socket::netlink::{common::NetlinkSocket, table::NetlinkNetfilterProtocol};
+
+mod bound;
+mod kernel;
+mod message;
+
+pub type NetlinkNetfilterSocket = NetlinkSocket<NetlinkNetfilterProtocol>;
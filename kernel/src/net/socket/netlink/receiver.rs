@@ -81,12 +81,10 @@ impl<Message: QueueableMessage> MessageQueue<Message> {
     /// Tries to enqueue a new message. Returns `false` if the buffer is full.
     #[must_use]
     pub(self) fn enqueue(&mut self, message: Message) -> bool {
-        let length = message.total_len();
-
         // Currently, we don't support sending netlink messages between user spaces, so only the
         // kernel can enqueue new messages. If the kernel fails to enqueue a new message, `ENOBUFS`
         // will be returned when userspace calls `recv`.
-        if NETLINK_DEFAULT_BUF_SIZE - self.total_length < length {
+        if !self.push(message) {
             self.error = Some(Error::with_message(
                 Errno::ENOBUFS,
                 "the receive buffer is full",
@@ -94,6 +92,17 @@ impl<Message: QueueableMessage> MessageQueue<Message> {
             return false;
         }
 
+        true
+    }
+
+    /// Pushes a new message to the queue. Returns `false` if the buffer is full.
+    #[must_use]
+    fn push(&mut self, message: Message) -> bool {
+        let length = message.total_len();
+        if NETLINK_DEFAULT_BUF_SIZE - self.total_length < length {
+            return false;
+        }
+
         self.messages.push_back(message);
         self.total_length += length;
 
@@ -110,6 +119,20 @@ impl<Message: QueueableMessage> MessageReceiver<Message> {
             self.pollee.notify(IoEvents::ERR);
         }
     }
+
+    /// Tries to enqueue a message without reporting errors to user space.
+    ///
+    /// If the buffer is full, this method will fail with [`Errno::EAGAIN`] so that the caller can
+    /// retry later.
+    pub(super) fn try_enqueue_message(&self, message: Message) -> Result<()> {
+        let is_ok = self.message_queue.lock().push(message);
+        if !is_ok {
+            return_errno_with_message!(Errno::EAGAIN, "the receive buffer is full");
+        }
+
+        self.pollee.notify(IoEvents::IN);
+        Ok(())
+    }
 }
 
 pub(in crate::net) const NETLINK_DEFAULT_BUF_SIZE: usize = 65536;
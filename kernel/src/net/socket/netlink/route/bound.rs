@@ -61,7 +61,7 @@ impl datagram_common::Bound for BoundNetlinkRoute {
         let sum_lens = reader.sum_lens();
 
         let local_port = self.handle.port();
-        let rtnl_kernel = get_netlink_route_kernel();
+        let rtnl_kernel = get_netlink_route_kernel(&self.net_ns);
 
         loop {
             let mut segment = match RtnlSegment::read_from(reader) {
@@ -8,11 +8,13 @@ use core::num::NonZeroU32;
 use super::util::finish_response;
 use crate::{
     net::{
-        iface::{Iface, iter_all_ifaces},
+        iface::Iface,
+        net_ns::NetNamespace,
+        route::RtScope,
         socket::netlink::{
             message::{CMsgSegHdr, CSegmentType, GetRequestFlags, SegHdrCommonFlags},
             route::message::{
-                AddrAttr, AddrMessageFlags, AddrSegment, AddrSegmentBody, RtScope, RtnlSegment,
+                AddrAttr, AddrMessageFlags, AddrSegment, AddrSegmentBody, RtnlSegment,
             },
         },
     },
@@ -20,7 +22,10 @@ use crate::{
     util::net::CSocketAddrFamily,
 };
 
-pub(super) fn do_get_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegment>> {
+pub(super) fn do_get_addr(
+    net_ns: &NetNamespace,
+    request_segment: &AddrSegment,
+) -> Result<Vec<RtnlSegment>> {
     let dump_all = {
         let flags = GetRequestFlags::from_bits_truncate(request_segment.header().flags);
         flags.contains(GetRequestFlags::DUMP)
@@ -29,28 +34,34 @@ pub(super) fn do_get_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegme
         return_errno_with_message!(Errno::EOPNOTSUPP, "GETADDR only supports dump requests");
     }
 
-    let mut response_segments: Vec<RtnlSegment> = iter_all_ifaces()
+    let family = request_segment.body().family;
+    let dump_ipv4 = family == CSocketAddrFamily::AF_UNSPEC as i32
+        || family == CSocketAddrFamily::AF_INET as i32;
+    let dump_ipv6 = family == CSocketAddrFamily::AF_UNSPEC as i32
+        || family == CSocketAddrFamily::AF_INET6 as i32;
+
+    let mut response_segments: Vec<RtnlSegment> = Vec::new();
+    for iface in net_ns.ifaces().iter() {
         // GETADDR only supports dump mode, so we're going to report all addresses.
-        .filter_map(|iface| iface_to_new_addr(request_segment.header(), iface))
-        .map(RtnlSegment::NewAddr)
-        .collect();
+        if dump_ipv4 && let Some(segment) = iface_to_new_ipv4_addr(request_segment.header(), iface)
+        {
+            response_segments.push(RtnlSegment::NewAddr(segment));
+        }
+        if dump_ipv6 {
+            response_segments.extend(
+                iface_to_new_ipv6_addrs(request_segment.header(), iface).map(RtnlSegment::NewAddr),
+            );
+        }
+    }
 
     finish_response(request_segment.header(), dump_all, &mut response_segments);
 
     Ok(response_segments)
 }
 
-fn iface_to_new_addr(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> Option<AddrSegment> {
+fn iface_to_new_ipv4_addr(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> Option<AddrSegment> {
     let ipv4_addr = iface.ipv4_addr()?;
 
-    let header = CMsgSegHdr {
-        len: 0,
-        type_: CSegmentType::NEWADDR as _,
-        flags: SegHdrCommonFlags::empty().bits(),
-        seq: request_header.seq,
-        pid: request_header.pid,
-    };
-
     let addr_message = AddrSegmentBody {
         family: CSocketAddrFamily::AF_INET as _,
         prefix_len: iface.prefix_len().unwrap(),
@@ -60,10 +71,57 @@ fn iface_to_new_addr(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> Option<
     };
 
     let attrs = vec![
-        AddrAttr::Address(ipv4_addr.octets()),
+        AddrAttr::Address(ipv4_addr.octets().to_vec()),
         AddrAttr::Label(iface.name().to_owned()),
-        AddrAttr::Local(ipv4_addr.octets()),
+        AddrAttr::Local(ipv4_addr.octets().to_vec()),
     ];
 
-    Some(AddrSegment::new(header, addr_message, attrs))
+    Some(AddrSegment::new(
+        new_addr_header(request_header),
+        addr_message,
+        attrs,
+    ))
+}
+
+fn iface_to_new_ipv6_addrs(
+    request_header: &CMsgSegHdr,
+    iface: &Arc<Iface>,
+) -> impl Iterator<Item = AddrSegment> {
+    let header = new_addr_header(request_header);
+    let index = NonZeroU32::new(iface.index());
+
+    iface.ipv6_addrs().into_iter().map(move |ipv6_cidr| {
+        let ipv6_addr = ipv6_cidr.address();
+        let scope = if ipv6_addr.is_loopback() {
+            RtScope::HOST
+        } else if ipv6_addr.is_unicast_link_local() {
+            RtScope::LINK
+        } else {
+            RtScope::UNIVERSE
+        };
+
+        let addr_message = AddrSegmentBody {
+            family: CSocketAddrFamily::AF_INET6 as _,
+            prefix_len: ipv6_cidr.prefix_len(),
+            flags: AddrMessageFlags::PERMANENT,
+            scope,
+            index,
+        };
+
+        // Unlike IPv4 addresses, IPv6 addresses do not have labels, and `IFA_LOCAL` is omitted
+        // unless the interface is point-to-point.
+        let attrs = vec![AddrAttr::Address(ipv6_addr.octets().to_vec())];
+
+        AddrSegment::new(header, addr_message, attrs)
+    })
+}
+
+fn new_addr_header(request_header: &CMsgSegHdr) -> CMsgSegHdr {
+    CMsgSegHdr {
+        len: 0,
+        type_: CSegmentType::NEWADDR as _,
+        flags: SegHdrCommonFlags::empty().bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    }
 }
@@ -5,32 +5,49 @@
 use alloc::borrow::ToOwned;
 use core::num::NonZero;
 
-use aster_bigtcp::iface::InterfaceType;
+use aster_bigtcp::iface::{InterfaceFlags, InterfaceType};
 
-use super::util::finish_response;
+use super::util::{ack_response, finish_response};
 use crate::{
+    fs::{
+        file::{InodeHandle, file_table::FileDesc},
+        pseudofs::NsFile,
+    },
     net::{
-        iface::{Iface, iter_all_ifaces},
+        iface::{Iface, virt_flags},
+        net_ns::NetNamespace,
         socket::netlink::{
-            message::{CMsgSegHdr, CSegmentType, GetRequestFlags, SegHdrCommonFlags},
-            route::message::{LinkAttr, LinkSegment, LinkSegmentBody, RtnlSegment},
+            message::{
+                Attribute, CMsgSegHdr, CSegmentType, GetRequestFlags, NewRequestFlags,
+                SegHdrCommonFlags,
+            },
+            route::message::{
+                CIfinfoMsg, LinkAttr, LinkInfoAttr, LinkSegment, LinkSegmentBody, RtnlSegment,
+                VethInfoAttr,
+            },
         },
     },
     prelude::*,
+    process::{Pid, pid_table, posix_thread::AsPosixThread},
     util::net::CSocketAddrFamily,
 };
 
-pub(super) fn do_get_link(request_segment: &LinkSegment) -> Result<Vec<RtnlSegment>> {
+pub(super) fn do_get_link(
+    net_ns: &NetNamespace,
+    request_segment: &LinkSegment,
+) -> Result<Vec<RtnlSegment>> {
     let filter_by = FilterBy::from_request(request_segment)?;
 
-    let mut response_segments: Vec<RtnlSegment> = iter_all_ifaces()
+    let mut response_segments: Vec<RtnlSegment> = net_ns
+        .ifaces()
+        .into_iter()
         // Filter to include only requested links.
         .filter(|iface| match &filter_by {
             FilterBy::Index(index) => *index == iface.index(),
             FilterBy::Name(name) => *name == iface.name(),
             FilterBy::Dump => true,
         })
-        .map(|iface| iface_to_new_link(request_segment.header(), iface))
+        .map(|iface| iface_to_new_link(net_ns, request_segment.header(), &iface))
         .map(RtnlSegment::NewLink)
         .collect();
 
@@ -45,6 +62,310 @@ pub(super) fn do_get_link(request_segment: &LinkSegment) -> Result<Vec<RtnlSegme
     Ok(response_segments)
 }
 
+pub(super) fn do_new_link(
+    net_ns: &NetNamespace,
+    request_segment: &LinkSegment,
+) -> Result<Vec<RtnlSegment>> {
+    net_ns.check_net_admin()?;
+
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    let name = find_name(request_segment.attrs());
+
+    let existing_iface = if let Some(index) = request_segment.body().index {
+        let Some(iface) = find_iface(net_ns, |iface| iface.index() == index.get()) else {
+            return_errno_with_message!(Errno::ENODEV, "the link does not exist");
+        };
+        Some(iface)
+    } else if let Some(name) = name.as_ref() {
+        find_iface(net_ns, |iface| iface.name() == name.as_c_str())
+    } else {
+        None
+    };
+
+    if let Some(iface) = existing_iface {
+        if flags.contains(NewRequestFlags::EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the link already exists");
+        }
+        if flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "replacing a link is not supported");
+        }
+
+        set_link(net_ns, &iface, request_segment)?;
+
+        return Ok(ack_response(request_segment.header()));
+    }
+
+    if !flags.contains(NewRequestFlags::CREATE) {
+        return_errno_with_message!(Errno::ENODEV, "the link does not exist");
+    }
+
+    let link_info_attrs = match request_segment.attrs().iter().find_map(|attr| {
+        if let LinkAttr::LinkInfo(link_info) = attr {
+            Some(link_info)
+        } else {
+            None
+        }
+    }) {
+        Some(link_info) => LinkInfoAttr::read_all_from_bytes(link_info)?,
+        None => Vec::new(),
+    };
+    let mut kind = None;
+    let mut data = None;
+    for attr in link_info_attrs {
+        match attr {
+            LinkInfoAttr::Kind(attr_kind) => kind = Some(attr_kind),
+            LinkInfoAttr::Data(attr_data) => data = Some(attr_data),
+        }
+    }
+
+    // The link is created in the namespace specified by `IFLA_NET_NS_PID` or `IFLA_NET_NS_FD`,
+    // while the veth peer is created in the current namespace unless specified otherwise.
+    // Reference: `veth_newlink` in <https://elixir.bootlin.com/linux/v6.13/source/drivers/net/veth.c>.
+    let current_net_ns = net_ns.this();
+    let link_net_ns =
+        find_net_ns(request_segment.attrs())?.unwrap_or_else(|| current_net_ns.clone());
+
+    // Newly created virtual links are always up, so they cannot be created with other flags.
+    check_flags_unchanged(virt_flags(), request_segment.body())?;
+
+    let index = match kind.as_ref().map(|kind| kind.to_bytes()) {
+        Some(b"veth") => {
+            let peer_attrs = match data {
+                Some(data) => parse_veth_peer_attrs(&data)?,
+                None => Vec::new(),
+            };
+            let peer_name = find_name(&peer_attrs);
+            let peer_net_ns = find_net_ns(&peer_attrs)?.unwrap_or(current_net_ns);
+            link_net_ns.add_veth_pair(name, peer_name, &peer_net_ns)?
+        }
+        Some(b"bridge") => link_net_ns.add_bridge(name)?,
+        Some(_) => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the link kind is not supported")
+        }
+        None => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the link kind is not specified")
+        }
+    };
+
+    if let Some(master_index) = find_master_index(request_segment.attrs()) {
+        link_net_ns.set_link_master(index, master_index)?;
+    }
+
+    Ok(ack_response(request_segment.header()))
+}
+
+pub(super) fn do_set_link(
+    net_ns: &NetNamespace,
+    request_segment: &LinkSegment,
+) -> Result<Vec<RtnlSegment>> {
+    net_ns.check_net_admin()?;
+
+    let iface = if let Some(index) = request_segment.body().index {
+        find_iface(net_ns, |iface| iface.index() == index.get())
+    } else if let Some(name) = find_name(request_segment.attrs()) {
+        find_iface(net_ns, |iface| iface.name() == name.as_c_str())
+    } else {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "either interface name or index should be specified"
+        );
+    };
+    let Some(iface) = iface else {
+        return_errno_with_message!(Errno::ENODEV, "the link does not exist");
+    };
+
+    set_link(net_ns, &iface, request_segment)?;
+
+    Ok(ack_response(request_segment.header()))
+}
+
+/// Changes the properties of an existing link.
+fn set_link(net_ns: &NetNamespace, iface: &Iface, request_segment: &LinkSegment) -> Result<()> {
+    if request_segment
+        .attrs()
+        .iter()
+        .any(|attr| matches!(attr, LinkAttr::NetNsPid(_) | LinkAttr::NetNsFd(_)))
+    {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "moving a link to another namespace is not supported"
+        );
+    }
+
+    // TODO: Support changing other link properties, such as the MTU.
+    check_flags_unchanged(iface.flags(), request_segment.body())?;
+    if let Some(master_index) = find_master_index(request_segment.attrs()) {
+        net_ns.set_link_master(iface.index(), master_index)?;
+    }
+
+    Ok(())
+}
+
+/// The flags that can be changed by user space.
+///
+/// Reference: `__dev_change_flags` in <https://elixir.bootlin.com/linux/v6.13/source/net/core/dev.c>.
+const CHANGEABLE_FLAGS: InterfaceFlags = InterfaceFlags::UP
+    .union(InterfaceFlags::DEBUG)
+    .union(InterfaceFlags::NOTRAILERS)
+    .union(InterfaceFlags::NOARP)
+    .union(InterfaceFlags::PROMISC)
+    .union(InterfaceFlags::ALLMULTI)
+    .union(InterfaceFlags::MULTICAST)
+    .union(InterfaceFlags::PORTSEL)
+    .union(InterfaceFlags::AUTOMEDIA)
+    .union(InterfaceFlags::DYNAMIC);
+
+/// Checks that the request does not change `flags`.
+///
+/// Changing the flags (e.g., setting the link up or down) is not supported yet, so a request
+/// that changes the flags fails with `EOPNOTSUPP`.
+fn check_flags_unchanged(flags: InterfaceFlags, body: &LinkSegmentBody) -> Result<()> {
+    if body.flags.is_empty() && body.change.is_empty() {
+        return Ok(());
+    }
+
+    // For backward compatibility, an empty change mask means that all flags are specified.
+    // Reference: `rtnl_dev_combine_flags` in <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c>.
+    let change = if body.change.is_empty() {
+        InterfaceFlags::all()
+    } else {
+        body.change
+    };
+    let new_flags = (body.flags & change) | (flags - change);
+
+    if !((new_flags ^ flags) & CHANGEABLE_FLAGS).is_empty() {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "changing the link flags is not supported"
+        );
+    }
+
+    Ok(())
+}
+
+pub(super) fn do_del_link(
+    net_ns: &NetNamespace,
+    request_segment: &LinkSegment,
+) -> Result<Vec<RtnlSegment>> {
+    net_ns.check_net_admin()?;
+
+    let index = if let Some(index) = request_segment.body().index {
+        index.get()
+    } else if let Some(name) = find_name(request_segment.attrs()) {
+        let Some(iface) = find_iface(net_ns, |iface| iface.name() == name.as_c_str()) else {
+            return_errno_with_message!(Errno::ENODEV, "the link does not exist");
+        };
+        iface.index()
+    } else {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "either interface name or index should be specified"
+        );
+    };
+
+    net_ns.del_link(index)?;
+
+    Ok(ack_response(request_segment.header()))
+}
+
+fn find_name(attrs: &[LinkAttr]) -> Option<CString> {
+    attrs.iter().find_map(|attr| {
+        if let LinkAttr::Name(name) = attr {
+            Some(name.clone())
+        } else {
+            None
+        }
+    })
+}
+
+fn find_master_index(attrs: &[LinkAttr]) -> Option<u32> {
+    attrs.iter().find_map(|attr| {
+        if let LinkAttr::Master(master_index) = attr {
+            Some(*master_index)
+        } else {
+            None
+        }
+    })
+}
+
+/// Finds the network namespace specified by `IFLA_NET_NS_PID` or `IFLA_NET_NS_FD`, if any.
+///
+/// The current thread must have `CAP_NET_ADMIN` over the namespace.
+fn find_net_ns(attrs: &[LinkAttr]) -> Result<Option<Arc<NetNamespace>>> {
+    let mut ns_attrs = attrs
+        .iter()
+        .filter(|attr| matches!(attr, LinkAttr::NetNsPid(_) | LinkAttr::NetNsFd(_)));
+    let Some(ns_attr) = ns_attrs.next() else {
+        return Ok(None);
+    };
+    if ns_attrs.next().is_some() {
+        return_errno_with_message!(Errno::EINVAL, "multiple network namespaces are specified");
+    }
+
+    let net_ns = match ns_attr {
+        LinkAttr::NetNsPid(pid) => net_ns_of_pid(*pid)?,
+        LinkAttr::NetNsFd(fd) => net_ns_of_fd(*fd)?,
+        _ => unreachable!(),
+    };
+    net_ns.check_net_admin()?;
+
+    Ok(Some(net_ns))
+}
+
+fn net_ns_of_pid(pid: Pid) -> Result<Arc<NetNamespace>> {
+    let current = current!();
+    let process = pid_table::pid_table_mut()
+        .get_process_in_ns(pid, current.pid_ns())
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?;
+
+    let main_thread = process.main_thread();
+    let ns_proxy = main_thread.as_posix_thread().unwrap().ns_proxy().lock();
+    ns_proxy
+        .as_ref()
+        .map(|ns_proxy| ns_proxy.net_ns().clone())
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process has exited"))
+}
+
+fn net_ns_of_fd(fd: u32) -> Result<Arc<NetNamespace>> {
+    let fd = FileDesc::try_from(fd.cast_signed())?;
+
+    let file = {
+        let current = current_thread!();
+        let file_table = current.as_posix_thread().unwrap().file_table().lock();
+        let Some(file_table) = file_table.as_ref() else {
+            return_errno_with_message!(Errno::EBADF, "the file table does not exist");
+        };
+        file_table.read().get_file(fd)?.clone()
+    };
+
+    let ns_file = file
+        .downcast_ref::<InodeHandle>()
+        .map(|inode_handle| inode_handle.downcast_open_file::<NsFile<NetNamespace>>())
+        .transpose()?
+        .flatten();
+    ns_file
+        .map(|ns_file| ns_file.ns().clone())
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the file is not a network namespace"))
+}
+
+fn find_iface(net_ns: &NetNamespace, pred: impl Fn(&Arc<Iface>) -> bool) -> Option<Arc<Iface>> {
+    net_ns.ifaces().into_iter().find(pred)
+}
+
+/// Parses the peer attributes from the `IFLA_INFO_DATA` attribute of a veth link.
+fn parse_veth_peer_attrs(data: &[u8]) -> Result<Vec<LinkAttr>> {
+    let Some(VethInfoAttr::Peer(peer)) =
+        VethInfoAttr::read_all_from_bytes(data)?.into_iter().next()
+    else {
+        return Ok(Vec::new());
+    };
+
+    let Some(peer_attrs) = peer.get(size_of::<CIfinfoMsg>()..) else {
+        return_errno_with_message!(Errno::EINVAL, "the veth peer information is too short");
+    };
+    LinkAttr::read_all_from_bytes(peer_attrs)
+}
+
 enum FilterBy<'a> {
     Index(u32),
     Name(&'a CStr),
@@ -92,11 +413,10 @@ impl<'a> FilterBy<'a> {
 // Reference: <https://docs.kernel.org/userspace-api/netlink/intro.html#strict-checking>.
 
 fn validate_getlink_request(body: &LinkSegmentBody) -> Result<()> {
-    // FIXME: The Linux implementation also checks the `padding` and `change` fields,
-    // but these fields are lost during the conversion of a `CIfInfoMsg` to `LinkSegmentBody`.
-    // We should consider including the `change` field in `LinkSegmentBody`.
+    // FIXME: The Linux implementation also checks the `padding` field,
+    // but this field is lost during the conversion of a `CIfInfoMsg` to `LinkSegmentBody`.
     // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c#L4043>.
-    if !body.flags.is_empty() || body.type_ != InterfaceType::NETROM {
+    if !body.flags.is_empty() || !body.change.is_empty() || body.type_ != InterfaceType::NETROM {
         return_errno_with_message!(Errno::EINVAL, "the flags or the type is not valid");
     }
 
@@ -104,9 +424,9 @@ fn validate_getlink_request(body: &LinkSegmentBody) -> Result<()> {
 }
 
 fn validate_dumplink_request(body: &LinkSegmentBody) -> Result<()> {
-    // FIXME: The Linux implementation also checks the `padding` and `change` fields.
+    // FIXME: The Linux implementation also checks the `padding` field.
     // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c#L2378>.
-    if !body.flags.is_empty() || body.type_ != InterfaceType::NETROM {
+    if !body.flags.is_empty() || !body.change.is_empty() || body.type_ != InterfaceType::NETROM {
         return_errno_with_message!(Errno::EINVAL, "the flags or the type is not valid");
     }
 
@@ -121,7 +441,11 @@ fn validate_dumplink_request(body: &LinkSegmentBody) -> Result<()> {
     Ok(())
 }
 
-fn iface_to_new_link(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> LinkSegment {
+fn iface_to_new_link(
+    net_ns: &NetNamespace,
+    request_header: &CMsgSegHdr,
+    iface: &Arc<Iface>,
+) -> LinkSegment {
     let header = CMsgSegHdr {
         len: 0,
         type_: CSegmentType::NEWLINK as _,
@@ -135,12 +459,19 @@ fn iface_to_new_link(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> LinkSeg
         type_: iface.type_(),
         index: NonZero::new(iface.index()),
         flags: iface.flags(),
+        change: InterfaceFlags::empty(),
     };
 
-    let attrs = vec![
+    let mut attrs = vec![
         LinkAttr::Name(iface.name().to_owned()),
         LinkAttr::Mtu(iface.mtu() as u32),
     ];
+    if let Some(master_index) = net_ns
+        .virt_link(iface.index())
+        .and_then(|link| link.master_index())
+    {
+        attrs.push(LinkAttr::Master(master_index));
+    }
 
     LinkSegment::new(header, link_message, attrs)
 }
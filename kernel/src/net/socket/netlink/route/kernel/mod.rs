@@ -3,31 +3,33 @@
 //! This module defines the kernel socket,
 //! which is responsible for handling requests from user space.
 
-use core::marker::PhantomData;
-
 use super::message::{RtnlMessage, RtnlSegment};
 use crate::{
-    net::socket::netlink::{
-        addr::PortNum,
-        message::{ErrorSegment, ProtocolSegment},
-        table::{NetlinkRouteProtocol, SupportedNetlinkProtocol},
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            addr::PortNum,
+            message::{ErrorSegment, ProtocolSegment},
+            table::{NetlinkRouteProtocol, SupportedNetlinkProtocol},
+        },
     },
     prelude::*,
 };
 
 mod addr;
 mod link;
+mod route;
+mod rule;
 mod util;
 
-pub(super) struct NetlinkRouteKernelSocket {
-    _private: PhantomData<()>,
+/// The kernel socket of a network namespace.
+pub(super) struct NetlinkRouteKernelSocket<'a> {
+    net_ns: &'a NetNamespace,
 }
 
-impl NetlinkRouteKernelSocket {
-    const fn new() -> Self {
-        Self {
-            _private: PhantomData,
-        }
+impl<'a> NetlinkRouteKernelSocket<'a> {
+    fn new(net_ns: &'a NetNamespace) -> Self {
+        Self { net_ns }
     }
 
     pub(super) fn handle_request(&self, request: &RtnlSegment, dst_port: PortNum) {
@@ -36,8 +38,39 @@ impl NetlinkRouteKernelSocket {
         let request_header = request.header();
 
         let response_segments = match request {
-            RtnlSegment::GetLink(request_segment) => link::do_get_link(request_segment),
-            RtnlSegment::GetAddr(request_segment) => addr::do_get_addr(request_segment),
+            RtnlSegment::NewLink(request_segment) => {
+                link::do_new_link(self.net_ns, request_segment)
+            }
+            RtnlSegment::DelLink(request_segment) => {
+                link::do_del_link(self.net_ns, request_segment)
+            }
+            RtnlSegment::GetLink(request_segment) => {
+                link::do_get_link(self.net_ns, request_segment)
+            }
+            RtnlSegment::SetLink(request_segment) => {
+                link::do_set_link(self.net_ns, request_segment)
+            }
+            RtnlSegment::GetAddr(request_segment) => {
+                addr::do_get_addr(self.net_ns, request_segment)
+            }
+            RtnlSegment::NewRoute(request_segment) => {
+                route::do_new_route(self.net_ns, request_segment)
+            }
+            RtnlSegment::DelRoute(request_segment) => {
+                route::do_del_route(self.net_ns, request_segment)
+            }
+            RtnlSegment::GetRoute(request_segment) => {
+                route::do_get_route(self.net_ns, request_segment)
+            }
+            RtnlSegment::NewRule(request_segment) => {
+                rule::do_new_rule(self.net_ns, request_segment)
+            }
+            RtnlSegment::DelRule(request_segment) => {
+                rule::do_del_rule(self.net_ns, request_segment)
+            }
+            RtnlSegment::GetRule(request_segment) => {
+                rule::do_get_rule(self.net_ns, request_segment)
+            }
             _ => Err(Error::with_message(
                 Errno::EOPNOTSUPP,
                 "the netlink route request is not supported",
@@ -45,6 +78,8 @@ impl NetlinkRouteKernelSocket {
         };
 
         let response = match response_segments {
+            // No acknowledgment is requested.
+            Ok(segments) if segments.is_empty() => return,
             Ok(segments) => RtnlMessage::new(segments),
             Err(error) => {
                 // TODO: Deal with the `NetlinkMessageCommonFlags::ACK` flag.
@@ -70,9 +105,7 @@ impl NetlinkRouteKernelSocket {
     }
 }
 
-/// FIXME: NETLINK_ROUTE_KERNEL should be a per-network namespace socket
-static NETLINK_ROUTE_KERNEL: NetlinkRouteKernelSocket = NetlinkRouteKernelSocket::new();
-
-pub(super) fn get_netlink_route_kernel() -> &'static NetlinkRouteKernelSocket {
-    &NETLINK_ROUTE_KERNEL
+/// Returns the kernel socket of the given network namespace.
+pub(super) fn get_netlink_route_kernel(net_ns: &NetNamespace) -> NetlinkRouteKernelSocket<'_> {
+    NetlinkRouteKernelSocket::new(net_ns)
 }
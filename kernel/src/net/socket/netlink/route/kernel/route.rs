@@ -0,0 +1,359 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle route-related requests.
+
+use aster_bigtcp::wire::{IpAddress, IpCidr};
+
+use super::util::{
+    ack_response, finish_response, ip_addr_to_bytes, ip_family_of, parse_ip_addr, parse_ip_cidr,
+    parse_ip_family,
+};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        route::{
+            Fib, Flow, InsertPolicy, RT_TABLE_COMPAT, RT_TABLE_MAIN, RT_TABLE_UNSPEC, Route,
+            RouteType, RtScope, network_of,
+        },
+        socket::netlink::{
+            message::{
+                CMsgSegHdr, CSegmentType, GetRequestFlags, NewRequestFlags, SegHdrCommonFlags,
+            },
+            route::message::{
+                RouteAttr, RouteMessageFlags, RouteSegment, RouteSegmentBody, RtnlSegment,
+            },
+        },
+    },
+    prelude::*,
+    util::net::CSocketAddrFamily,
+};
+
+/// The default priority of the IPv6 routes added by users.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/ip6_route.h>.
+const IP6_RT_PRIO_USER: u32 = 1024;
+
+pub(super) fn do_new_route(
+    net_ns: &NetNamespace,
+    request_segment: &RouteSegment,
+) -> Result<Vec<RtnlSegment>> {
+    net_ns.check_net_admin()?;
+
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    let policy = if flags.contains(NewRequestFlags::REPLACE) {
+        InsertPolicy::Replace {
+            create: flags.contains(NewRequestFlags::CREATE),
+        }
+    } else if !flags.contains(NewRequestFlags::CREATE) {
+        return_errno_with_message!(Errno::ENOENT, "the route does not exist");
+    } else if flags.contains(NewRequestFlags::APPEND) && !flags.contains(NewRequestFlags::EXCL) {
+        InsertPolicy::Append
+    } else {
+        InsertPolicy::Exclusive
+    };
+
+    let request = RouteRequest::parse(request_segment)?;
+
+    let mut fib = net_ns.router().fib_mut();
+
+    let type_ = match request.body.type_ {
+        RouteType::Unspec => RouteType::Unicast,
+        type_ => type_,
+    };
+    let oif = match (request.oif, request.gateway) {
+        (Some(oif), _) if !fib.has_iface(oif) => {
+            return_errno_with_message!(Errno::ENODEV, "the output interface does not exist");
+        }
+        (Some(oif), _) => Some(oif),
+        // Like Linux, the output interface of a gateway route is the interface through which the
+        // gateway is directly reachable.
+        (None, Some(gateway)) => Some(find_gateway_oif(&fib, &gateway)?),
+        (None, None)
+            if matches!(
+                type_,
+                RouteType::Unicast | RouteType::Local | RouteType::Broadcast
+            ) =>
+        {
+            return_errno_with_message!(Errno::EINVAL, "the output interface is not specified");
+        }
+        (None, None) => None,
+    };
+
+    let dst = match request.dst {
+        IpCidr::Ipv4(_) if network_of(&request.dst) != request.dst => {
+            return_errno_with_message!(Errno::EINVAL, "the prefix has host bits set");
+        }
+        IpCidr::Ipv4(_) => request.dst,
+        // Like Linux, the host bits of IPv6 prefixes are ignored.
+        IpCidr::Ipv6(_) => network_of(&request.dst),
+    };
+    let priority = match (request.priority, dst) {
+        (Some(priority), _) => priority,
+        (None, IpCidr::Ipv4(_)) => 0,
+        (None, IpCidr::Ipv6(_)) => IP6_RT_PRIO_USER,
+    };
+
+    let route = Route {
+        dst,
+        tos: request.body.tos,
+        type_,
+        protocol: request.body.protocol,
+        scope: request.body.scope,
+        gateway: request.gateway,
+        oif,
+        prefsrc: request.prefsrc,
+        priority,
+    };
+    fib.table_mut(request.table).insert(route, policy)?;
+
+    Ok(ack_response(request_segment.header()))
+}
+
+pub(super) fn do_del_route(
+    net_ns: &NetNamespace,
+    request_segment: &RouteSegment,
+) -> Result<Vec<RtnlSegment>> {
+    net_ns.check_net_admin()?;
+
+    let request = RouteRequest::parse(request_segment)?;
+    let body = &request.body;
+
+    let dst = match request.dst {
+        IpCidr::Ipv4(_) => request.dst,
+        IpCidr::Ipv6(_) => network_of(&request.dst),
+    };
+
+    // Like Linux, unspecified fields match all routes.
+    let is_target = |route: &Route| {
+        route.dst == dst
+            && (body.tos == 0 || route.tos == body.tos)
+            && (body.type_ == RouteType::Unspec || route.type_ == body.type_)
+            && (body.protocol == 0 || route.protocol == body.protocol)
+            && (body.scope == RtScope::NOWHERE || route.scope == body.scope)
+            && request
+                .gateway
+                .is_none_or(|gateway| route.gateway == Some(gateway))
+            && request.oif.is_none_or(|oif| route.oif == Some(oif))
+            && request
+                .priority
+                .is_none_or(|priority| route.priority == priority)
+    };
+
+    let mut fib = net_ns.router().fib_mut();
+    if fib.table_mut(request.table).remove_if(is_target).is_none() {
+        return_errno_with_message!(Errno::ESRCH, "the route does not exist");
+    }
+
+    Ok(ack_response(request_segment.header()))
+}
+
+pub(super) fn do_get_route(
+    net_ns: &NetNamespace,
+    request_segment: &RouteSegment,
+) -> Result<Vec<RtnlSegment>> {
+    let dump_all = {
+        let flags = GetRequestFlags::from_bits_truncate(request_segment.header().flags);
+        flags.contains(GetRequestFlags::DUMP)
+    };
+    if dump_all {
+        return dump_routes(net_ns, request_segment);
+    }
+
+    let request = RouteRequest::parse(request_segment)?;
+    let flow = Flow {
+        src: request.src,
+        tos: request.body.tos,
+        iif: request.iif,
+        oif: request.oif,
+        ..Flow::new(request.dst.address())
+    };
+    let (table, route) = net_ns.router().fib().lookup_with_table(&flow)?;
+    // Like Linux, the table of IPv4 routes is reported as the main table unless the user asks
+    // for the table that contains the route.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/route.c>.
+    let table = match flow.dst {
+        IpAddress::Ipv4(_) if !request.body.flags.contains(RouteMessageFlags::LOOKUP_TABLE) => {
+            RT_TABLE_MAIN
+        }
+        _ => table,
+    };
+
+    // Report the route for the exact destination, like Linux does for the cloned routes.
+    let full_prefix_len = match flow.dst {
+        IpAddress::Ipv4(_) => 32,
+        IpAddress::Ipv6(_) => 128,
+    };
+    let prefsrc = route.prefsrc.or_else(|| {
+        let iface = net_ns.iface_by_index(route.oif?)?;
+        match flow.dst {
+            IpAddress::Ipv4(_) => iface.ipv4_addr().map(IpAddress::Ipv4),
+            IpAddress::Ipv6(_) => iface.ipv6_addr().map(IpAddress::Ipv6),
+        }
+    });
+    let route = Route {
+        dst: IpCidr::new(flow.dst, full_prefix_len),
+        prefsrc,
+        ..route
+    };
+
+    let mut response_segments = vec![RtnlSegment::NewRoute(route_to_new_route(
+        request_segment.header(),
+        table,
+        &route,
+        RouteMessageFlags::CLONED,
+    ))];
+
+    finish_response(request_segment.header(), dump_all, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+fn dump_routes(net_ns: &NetNamespace, request_segment: &RouteSegment) -> Result<Vec<RtnlSegment>> {
+    let family = request_segment.body().family;
+    let family_matches = |route: &Route| {
+        family == CSocketAddrFamily::AF_UNSPEC as i32
+            || family == ip_family_of(&route.dst.address()) as i32
+    };
+
+    let mut response_segments: Vec<RtnlSegment> = Vec::new();
+    for (table, route_table) in net_ns.router().fib().tables() {
+        response_segments.extend(
+            route_table
+                .routes()
+                .iter()
+                .filter(|route| family_matches(route))
+                .map(|route| {
+                    route_to_new_route(
+                        request_segment.header(),
+                        table,
+                        route,
+                        RouteMessageFlags::empty(),
+                    )
+                })
+                .map(RtnlSegment::NewRoute),
+        );
+    }
+
+    finish_response(request_segment.header(), true, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+/// The parsed fields of a route request.
+struct RouteRequest {
+    body: RouteSegmentBody,
+    table: u32,
+    dst: IpCidr,
+    src: Option<IpAddress>,
+    iif: Option<u32>,
+    oif: Option<u32>,
+    gateway: Option<IpAddress>,
+    priority: Option<u32>,
+    prefsrc: Option<IpAddress>,
+}
+
+impl RouteRequest {
+    fn parse(request_segment: &RouteSegment) -> Result<Self> {
+        let body = *request_segment.body();
+        let family = parse_ip_family(body.family)?;
+
+        if body.src_len != 0 {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "source-specific routes are not supported"
+            );
+        }
+
+        let mut request = Self {
+            body,
+            table: body.table as u32,
+            dst: parse_ip_cidr(family, None, 0)?,
+            src: None,
+            iif: None,
+            oif: None,
+            gateway: None,
+            priority: None,
+            prefsrc: None,
+        };
+        let mut dst_bytes = None;
+
+        for attr in request_segment.attrs().iter() {
+            match attr {
+                RouteAttr::Dst(bytes) => dst_bytes = Some(bytes.as_slice()),
+                RouteAttr::Src(bytes) => request.src = Some(parse_ip_addr(family, bytes)?),
+                RouteAttr::Iif(index) => request.iif = Some(*index),
+                RouteAttr::Oif(index) => request.oif = Some(*index),
+                RouteAttr::Gateway(bytes) => request.gateway = Some(parse_ip_addr(family, bytes)?),
+                RouteAttr::Priority(priority) => request.priority = Some(*priority),
+                RouteAttr::PrefSrc(bytes) => request.prefsrc = Some(parse_ip_addr(family, bytes)?),
+                RouteAttr::Table(table) => request.table = *table,
+            }
+        }
+
+        request.dst = parse_ip_cidr(family, dst_bytes, body.dst_len)?;
+        if request.table == RT_TABLE_UNSPEC {
+            request.table = RT_TABLE_MAIN;
+        }
+
+        Ok(request)
+    }
+}
+
+/// Finds the interface through which the gateway is directly reachable.
+fn find_gateway_oif(fib: &Fib, gateway: &IpAddress) -> Result<u32> {
+    let route = fib.lookup(&Flow::new(*gateway))?;
+    if route.type_ != RouteType::Unicast || route.gateway.is_some() {
+        return_errno_with_message!(Errno::ENETUNREACH, "the gateway is not directly reachable");
+    }
+
+    route.oif.ok_or_else(|| {
+        Error::with_message(Errno::ENETUNREACH, "the gateway is not directly reachable")
+    })
+}
+
+fn route_to_new_route(
+    request_header: &CMsgSegHdr,
+    table: u32,
+    route: &Route,
+    flags: RouteMessageFlags,
+) -> RouteSegment {
+    let header = CMsgSegHdr {
+        len: 0,
+        type_: CSegmentType::NEWROUTE as _,
+        flags: SegHdrCommonFlags::empty().bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    };
+
+    let route_message = RouteSegmentBody {
+        family: ip_family_of(&route.dst.address()) as _,
+        dst_len: route.dst.prefix_len(),
+        src_len: 0,
+        tos: route.tos,
+        table: u8::try_from(table).unwrap_or(RT_TABLE_COMPAT as u8),
+        protocol: route.protocol,
+        scope: route.scope,
+        type_: route.type_,
+        flags,
+    };
+
+    let mut attrs = vec![RouteAttr::Table(table)];
+    if route.dst.prefix_len() != 0 {
+        attrs.push(RouteAttr::Dst(ip_addr_to_bytes(&route.dst.address())));
+    }
+    if route.priority != 0 {
+        attrs.push(RouteAttr::Priority(route.priority));
+    }
+    if let Some(prefsrc) = route.prefsrc.as_ref() {
+        attrs.push(RouteAttr::PrefSrc(ip_addr_to_bytes(prefsrc)));
+    }
+    if let Some(gateway) = route.gateway.as_ref() {
+        attrs.push(RouteAttr::Gateway(ip_addr_to_bytes(gateway)));
+    }
+    if let Some(oif) = route.oif {
+        attrs.push(RouteAttr::Oif(oif));
+    }
+
+    RouteSegment::new(header, route_message, attrs)
+}
@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle routing-rule-related requests.
+
+use super::util::{
+    ack_response, finish_response, ip_addr_to_bytes, parse_ip_cidr, parse_ip_family,
+};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        route::{RT_TABLE_COMPAT, RT_TABLE_UNSPEC, Rule, RuleAction},
+        socket::netlink::{
+            message::{
+                CMsgSegHdr, CSegmentType, GetRequestFlags, NewRequestFlags, SegHdrCommonFlags,
+            },
+            route::message::{RtnlSegment, RuleAttr, RuleSegment, RuleSegmentBody},
+        },
+    },
+    prelude::*,
+    util::net::CSocketAddrFamily,
+};
+
+pub(super) fn do_new_rule(
+    net_ns: &NetNamespace,
+    request_segment: &RuleSegment,
+) -> Result<Vec<RtnlSegment>> {
+    net_ns.check_net_admin()?;
+
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    let (mut rule, priority) = parse_rule(request_segment)?;
+
+    match rule.action {
+        RuleAction::ToTable if rule.table == RT_TABLE_UNSPEC => {
+            return_errno_with_message!(Errno::EINVAL, "the routing table is not specified");
+        }
+        RuleAction::Goto => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "goto rules are not supported");
+        }
+        RuleAction::Unspec => {
+            return_errno_with_message!(Errno::EINVAL, "the rule action is not specified");
+        }
+        _ => (),
+    }
+
+    let mut fib = net_ns.router().fib_mut();
+
+    // Like Linux, a rule without a priority is placed right before the first rule that has a
+    // nonzero priority.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/fib_rules.c>.
+    rule.priority = priority.unwrap_or_else(|| {
+        fib.rules()
+            .iter()
+            .find(|existing| existing.family == rule.family && existing.priority != 0)
+            .map_or(0, |existing| existing.priority - 1)
+    });
+
+    if flags.contains(NewRequestFlags::EXCL) && fib.rules().contains(&rule) {
+        return_errno_with_message!(Errno::EEXIST, "the rule already exists");
+    }
+    fib.insert_rule(rule);
+
+    Ok(ack_response(request_segment.header()))
+}
+
+pub(super) fn do_del_rule(
+    net_ns: &NetNamespace,
+    request_segment: &RuleSegment,
+) -> Result<Vec<RtnlSegment>> {
+    net_ns.check_net_admin()?;
+
+    let (filter, priority) = parse_rule(request_segment)?;
+
+    let mut fib = net_ns.router().fib_mut();
+    if fib
+        .remove_rule_if(|rule| rule.matches_filter(&filter, priority))
+        .is_none()
+    {
+        return_errno_with_message!(Errno::ENOENT, "the rule does not exist");
+    }
+
+    Ok(ack_response(request_segment.header()))
+}
+
+pub(super) fn do_get_rule(
+    net_ns: &NetNamespace,
+    request_segment: &RuleSegment,
+) -> Result<Vec<RtnlSegment>> {
+    let dump_all = {
+        let flags = GetRequestFlags::from_bits_truncate(request_segment.header().flags);
+        flags.contains(GetRequestFlags::DUMP)
+    };
+    if !dump_all {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "GETRULE only supports dump requests");
+    }
+
+    let family = request_segment.body().family;
+    let mut response_segments: Vec<RtnlSegment> = net_ns
+        .router()
+        .fib()
+        .rules()
+        .iter()
+        .filter(|rule| {
+            family == CSocketAddrFamily::AF_UNSPEC as i32 || family == rule.family as i32
+        })
+        .map(|rule| rule_to_new_rule(request_segment.header(), rule))
+        .map(RtnlSegment::NewRule)
+        .collect();
+
+    finish_response(request_segment.header(), dump_all, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+/// Parses the rule in a request, and returns the rule together with its priority, if any.
+fn parse_rule(request_segment: &RuleSegment) -> Result<(Rule, Option<u32>)> {
+    let body = request_segment.body();
+    let family = parse_ip_family(body.family)?;
+
+    let mut rule = Rule {
+        family,
+        priority: 0,
+        action: body.action,
+        table: body.table as u32,
+        src: None,
+        dst: None,
+        tos: body.tos,
+        iif_name: None,
+        oif_name: None,
+        fwmark: 0,
+        fwmask: 0,
+        protocol: 0,
+        flags: body.flags,
+    };
+
+    let mut priority = None;
+    let mut fwmask = None;
+    for attr in request_segment.attrs().iter() {
+        match attr {
+            RuleAttr::Dst(bytes) => {
+                rule.dst = Some(parse_ip_cidr(family, Some(bytes), body.dst_len)?)
+            }
+            RuleAttr::Src(bytes) => {
+                rule.src = Some(parse_ip_cidr(family, Some(bytes), body.src_len)?)
+            }
+            RuleAttr::IifName(name) => rule.iif_name = Some(name.clone()),
+            RuleAttr::OifName(name) => rule.oif_name = Some(name.clone()),
+            RuleAttr::Priority(value) => priority = Some(*value),
+            RuleAttr::FwMark(value) => rule.fwmark = *value,
+            RuleAttr::FwMask(value) => fwmask = Some(*value),
+            RuleAttr::Table(value) => rule.table = *value,
+            RuleAttr::Protocol(value) => rule.protocol = *value,
+        }
+    }
+
+    // Like Linux, a firewall mark without a mask must match exactly.
+    rule.fwmask = match fwmask {
+        Some(fwmask) => fwmask,
+        None if rule.fwmark != 0 => u32::MAX,
+        None => 0,
+    };
+
+    Ok((rule, priority))
+}
+
+fn rule_to_new_rule(request_header: &CMsgSegHdr, rule: &Rule) -> RuleSegment {
+    let header = CMsgSegHdr {
+        len: 0,
+        type_: CSegmentType::NEWRULE as _,
+        flags: SegHdrCommonFlags::empty().bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    };
+
+    let rule_message = RuleSegmentBody {
+        family: rule.family as _,
+        dst_len: rule.dst.map_or(0, |dst| dst.prefix_len()),
+        src_len: rule.src.map_or(0, |src| src.prefix_len()),
+        tos: rule.tos,
+        table: u8::try_from(rule.table).unwrap_or(RT_TABLE_COMPAT as u8),
+        action: rule.action,
+        flags: rule.flags,
+    };
+
+    let mut attrs = vec![RuleAttr::Table(rule.table)];
+    if rule.priority != 0 {
+        attrs.push(RuleAttr::Priority(rule.priority));
+    }
+    if let Some(dst) = rule.dst.as_ref() {
+        attrs.push(RuleAttr::Dst(ip_addr_to_bytes(&dst.address())));
+    }
+    if let Some(src) = rule.src.as_ref() {
+        attrs.push(RuleAttr::Src(ip_addr_to_bytes(&src.address())));
+    }
+    if let Some(iif_name) = rule.iif_name.as_ref() {
+        attrs.push(RuleAttr::IifName(iif_name.clone()));
+    }
+    if let Some(oif_name) = rule.oif_name.as_ref() {
+        attrs.push(RuleAttr::OifName(oif_name.clone()));
+    }
+    if rule.fwmark != 0 || rule.fwmask != 0 {
+        attrs.push(RuleAttr::FwMark(rule.fwmark));
+        attrs.push(RuleAttr::FwMask(rule.fwmask));
+    }
+    attrs.push(RuleAttr::Protocol(rule.protocol));
+
+    RuleSegment::new(header, rule_message, attrs)
+}
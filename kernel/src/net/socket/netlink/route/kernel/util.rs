@@ -1,11 +1,14 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_bigtcp::wire::{IpAddress, IpCidr, Ipv4Address, Ipv6Address};
+
 use crate::{
     net::socket::netlink::{
-        message::{CMsgSegHdr, DoneSegment, ProtocolSegment, SegHdrCommonFlags},
+        message::{CMsgSegHdr, DoneSegment, ErrorSegment, ProtocolSegment, SegHdrCommonFlags},
         route::message::RtnlSegment,
     },
     prelude::*,
+    util::net::CSocketAddrFamily,
 };
 
 /// Finishes a response message.
@@ -22,6 +25,19 @@ pub fn finish_response(
     add_multi_flag(response_segments);
 }
 
+/// Creates the response to a request that does not return any data.
+///
+/// The response is an acknowledgment if the request asks for it, or is empty otherwise.
+pub fn ack_response(request_header: &CMsgSegHdr) -> Vec<RtnlSegment> {
+    let flags = SegHdrCommonFlags::from_bits_truncate(request_header.flags);
+    if !flags.contains(SegHdrCommonFlags::ACK) {
+        return Vec::new();
+    }
+
+    let ack_segment = ErrorSegment::new_from_request(request_header, None);
+    vec![RtnlSegment::Error(ack_segment)]
+}
+
 /// Appends a done segment as the last segment of the provided segments.
 fn append_done_segment(request_header: &CMsgSegHdr, response_segments: &mut Vec<RtnlSegment>) {
     let done_segment = DoneSegment::new_from_request(request_header, None);
@@ -37,3 +53,71 @@ fn add_multi_flag(response_segments: &mut [RtnlSegment]) {
         header.flags = flags.bits();
     }
 }
+
+/// Parses the address family of a route or a routing rule.
+pub fn parse_ip_family(family: i32) -> Result<CSocketAddrFamily> {
+    match CSocketAddrFamily::try_from(family) {
+        Ok(family @ (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6)) => Ok(family),
+        _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "the address family is not supported"),
+    }
+}
+
+/// Parses an IP address of the family from its bytes in network byte order.
+pub fn parse_ip_addr(family: CSocketAddrFamily, bytes: &[u8]) -> Result<IpAddress> {
+    if let Ok(octets) = <[u8; 4]>::try_from(bytes)
+        && family == CSocketAddrFamily::AF_INET
+    {
+        return Ok(IpAddress::Ipv4(Ipv4Address::from(octets)));
+    }
+    if let Ok(octets) = <[u8; 16]>::try_from(bytes)
+        && family == CSocketAddrFamily::AF_INET6
+    {
+        return Ok(IpAddress::Ipv6(Ipv6Address::from(octets)));
+    }
+
+    return_errno_with_message!(
+        Errno::EINVAL,
+        "the address does not match the address family"
+    );
+}
+
+/// Parses a CIDR block of the family.
+///
+/// If `addr_bytes` is `None`, the address will be the unspecified address.
+pub fn parse_ip_cidr(
+    family: CSocketAddrFamily,
+    addr_bytes: Option<&[u8]>,
+    prefix_len: u8,
+) -> Result<IpCidr> {
+    let addr = match (family, addr_bytes) {
+        (_, Some(bytes)) => parse_ip_addr(family, bytes)?,
+        (CSocketAddrFamily::AF_INET6, None) => IpAddress::Ipv6(Ipv6Address::UNSPECIFIED),
+        (_, None) => IpAddress::Ipv4(Ipv4Address::UNSPECIFIED),
+    };
+    let max_prefix_len = match addr {
+        IpAddress::Ipv4(_) => 32,
+        IpAddress::Ipv6(_) => 128,
+    };
+
+    if prefix_len > max_prefix_len {
+        return_errno_with_message!(Errno::EINVAL, "the prefix length is invalid");
+    }
+
+    Ok(IpCidr::new(addr, prefix_len))
+}
+
+/// Converts an IP address to its bytes in network byte order.
+pub fn ip_addr_to_bytes(addr: &IpAddress) -> Vec<u8> {
+    match addr {
+        IpAddress::Ipv4(ipv4_addr) => ipv4_addr.octets().to_vec(),
+        IpAddress::Ipv6(ipv6_addr) => ipv6_addr.octets().to_vec(),
+    }
+}
+
+/// Returns the address family of an IP address.
+pub fn ip_family_of(addr: &IpAddress) -> CSocketAddrFamily {
+    match addr {
+        IpAddress::Ipv4(_) => CSocketAddrFamily::AF_INET,
+        IpAddress::Ipv6(_) => CSocketAddrFamily::AF_INET6,
+    }
+}
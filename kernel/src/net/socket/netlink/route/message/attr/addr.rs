@@ -27,10 +27,14 @@ enum AddrAttrClass {
     TARGET_NETNSID = 10,
 }
 
+/// An address attribute.
+///
+/// The addresses are in network byte order. Their lengths are 4 bytes for IPv4 addresses and 16
+/// bytes for IPv6 addresses.
 #[derive(Debug)]
 pub enum AddrAttr {
-    Address([u8; 4]),
-    Local([u8; 4]),
+    Address(Vec<u8>),
+    Local(Vec<u8>),
     Label(CString),
 }
 
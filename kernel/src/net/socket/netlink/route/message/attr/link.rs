@@ -87,6 +87,15 @@ pub enum LinkAttr {
     Name(CString),
     Mtu(u32),
     TxqLen(u32),
+    Master(u32),
+    /// The nested attributes, which can be parsed as [`LinkInfoAttr`]s.
+    ///
+    /// [`LinkInfoAttr`]: super::link_info::LinkInfoAttr
+    LinkInfo(Vec<u8>),
+    /// The PID of a process whose network namespace the link should be in.
+    NetNsPid(u32),
+    /// A file descriptor that refers to the network namespace the link should be in.
+    NetNsFd(u32),
     LinkMode(u8),
     ExtMask(RtExtFilter),
 }
@@ -97,6 +106,10 @@ impl LinkAttr {
             LinkAttr::Name(_) => LinkAttrClass::IFNAME,
             LinkAttr::Mtu(_) => LinkAttrClass::MTU,
             LinkAttr::TxqLen(_) => LinkAttrClass::TXQLEN,
+            LinkAttr::Master(_) => LinkAttrClass::MASTER,
+            LinkAttr::LinkInfo(_) => LinkAttrClass::LINKINFO,
+            LinkAttr::NetNsPid(_) => LinkAttrClass::NET_NS_PID,
+            LinkAttr::NetNsFd(_) => LinkAttrClass::NET_NS_FD,
             LinkAttr::LinkMode(_) => LinkAttrClass::LINKMODE,
             LinkAttr::ExtMask(_) => LinkAttrClass::EXT_MASK,
         }
@@ -113,6 +126,10 @@ impl Attribute for LinkAttr {
             LinkAttr::Name(name) => name.as_bytes_with_nul(),
             LinkAttr::Mtu(mtu) => mtu.as_bytes(),
             LinkAttr::TxqLen(txq_len) => txq_len.as_bytes(),
+            LinkAttr::Master(master) => master.as_bytes(),
+            LinkAttr::LinkInfo(link_info) => link_info.as_slice(),
+            LinkAttr::NetNsPid(pid) => pid.as_bytes(),
+            LinkAttr::NetNsFd(fd) => fd.as_bytes(),
             LinkAttr::LinkMode(link_mode) => link_mode.as_bytes(),
             LinkAttr::ExtMask(ext_filter) => ext_filter.as_bytes(),
         }
@@ -149,6 +166,12 @@ impl Attribute for LinkAttr {
             }
             (LinkAttrClass::MTU, 4) => Self::Mtu(reader.read_val_opt::<u32>()?.unwrap()),
             (LinkAttrClass::TXQLEN, 4) => Self::TxqLen(reader.read_val_opt::<u32>()?.unwrap()),
+            (LinkAttrClass::MASTER, 4) => Self::Master(reader.read_val_opt::<u32>()?.unwrap()),
+            (LinkAttrClass::LINKINFO, _) => Self::LinkInfo(read_bytes(reader, payload_len)?),
+            (LinkAttrClass::NET_NS_PID, 4) => {
+                Self::NetNsPid(reader.read_val_opt::<u32>()?.unwrap())
+            }
+            (LinkAttrClass::NET_NS_FD, 4) => Self::NetNsFd(reader.read_val_opt::<u32>()?.unwrap()),
             (LinkAttrClass::LINKMODE, 1) => Self::LinkMode(reader.read_val_opt::<u8>()?.unwrap()),
             (LinkAttrClass::EXT_MASK, 4) => {
                 const { assert!(size_of::<RtExtFilter>() == 4) };
@@ -159,6 +182,9 @@ impl Attribute for LinkAttr {
                 LinkAttrClass::IFNAME
                 | LinkAttrClass::MTU
                 | LinkAttrClass::TXQLEN
+                | LinkAttrClass::MASTER
+                | LinkAttrClass::NET_NS_PID
+                | LinkAttrClass::NET_NS_FD
                 | LinkAttrClass::LINKMODE
                 | LinkAttrClass::EXT_MASK,
                 _,
@@ -186,6 +212,13 @@ impl Attribute for LinkAttr {
     }
 }
 
+/// Reads `len` bytes from the `reader`.
+pub(super) fn read_bytes(reader: &mut dyn MultiRead, len: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    reader.read(&mut VmWriter::from(bytes.as_mut_slice()))?;
+    Ok(bytes)
+}
+
 bitflags! {
     /// New extended info filters for [`NlLinkAttr::ExtMask`].
     ///
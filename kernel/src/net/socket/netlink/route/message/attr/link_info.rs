@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::link::read_bytes;
+use crate::{
+    net::socket::netlink::message::{Attribute, CAttrHeader, ContinueRead},
+    prelude::*,
+    util::MultiRead,
+};
+
+/// Link information attributes, which are nested in `IFLA_LINKINFO`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/if_link.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum LinkInfoAttrClass {
+    UNSPEC = 0,
+    KIND = 1,
+    DATA = 2,
+    XSTATS = 3,
+    SLAVE_KIND = 4,
+    SLAVE_DATA = 5,
+}
+
+#[derive(Debug)]
+pub enum LinkInfoAttr {
+    Kind(CString),
+    /// The kind-specific nested attributes (e.g., [`VethInfoAttr`]s).
+    Data(Vec<u8>),
+}
+
+impl LinkInfoAttr {
+    fn class(&self) -> LinkInfoAttrClass {
+        match self {
+            LinkInfoAttr::Kind(_) => LinkInfoAttrClass::KIND,
+            LinkInfoAttr::Data(_) => LinkInfoAttrClass::DATA,
+        }
+    }
+}
+
+impl Attribute for LinkInfoAttr {
+    fn type_(&self) -> u16 {
+        self.class() as u16
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            LinkInfoAttr::Kind(kind) => kind.as_bytes_with_nul(),
+            LinkInfoAttr::Data(data) => data.as_slice(),
+        }
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let payload_len = header.payload_len();
+
+        let Ok(class) = LinkInfoAttrClass::try_from(header.type_()) else {
+            reader.skip_some(payload_len);
+            return Ok(ContinueRead::Skipped);
+        };
+
+        let res = match (class, payload_len) {
+            (LinkInfoAttrClass::KIND, 1..) => {
+                let (kind, kind_len) = reader.read_cstring_until_end(payload_len)?;
+                if kind_len != payload_len {
+                    reader.skip_some(payload_len - kind_len);
+                }
+                Self::Kind(kind)
+            }
+            (LinkInfoAttrClass::DATA, _) => Self::Data(read_bytes(reader, payload_len)?),
+
+            (LinkInfoAttrClass::KIND, _) => {
+                warn!("link info attribute `{:?}` contains invalid payload", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::skipped_with_error(
+                    Errno::EINVAL,
+                    "the link info attribute is invalid",
+                ));
+            }
+
+            (_, _) => {
+                warn!("link info attribute `{:?}` is not supported", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::Skipped);
+            }
+        };
+
+        Ok(ContinueRead::Parsed(res))
+    }
+}
+
+/// Veth-specific attributes, which are nested in `IFLA_INFO_DATA`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/veth.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum VethInfoAttrClass {
+    UNSPEC = 0,
+    PEER = 1,
+}
+
+#[derive(Debug)]
+pub enum VethInfoAttr {
+    /// The peer's `ifinfomsg`, followed by the peer's link attributes.
+    Peer(Vec<u8>),
+}
+
+impl Attribute for VethInfoAttr {
+    fn type_(&self) -> u16 {
+        match self {
+            VethInfoAttr::Peer(_) => VethInfoAttrClass::PEER as u16,
+        }
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            VethInfoAttr::Peer(peer) => peer.as_slice(),
+        }
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let payload_len = header.payload_len();
+
+        match VethInfoAttrClass::try_from(header.type_()) {
+            Ok(VethInfoAttrClass::PEER) => Ok(ContinueRead::Parsed(Self::Peer(read_bytes(
+                reader,
+                payload_len,
+            )?))),
+            _ => {
+                reader.skip_some(payload_len);
+                Ok(ContinueRead::Skipped)
+            }
+        }
+    }
+}
@@ -2,6 +2,9 @@
 
 pub mod addr;
 pub mod link;
+pub mod link_info;
+pub mod route;
+pub mod rule;
 
 /// The size limit for interface names.
 const IFNAME_SIZE: usize = 16;
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::link::read_bytes;
+use crate::{
+    net::socket::netlink::message::{Attribute, CAttrHeader, ContinueRead},
+    prelude::*,
+    util::MultiRead,
+};
+
+/// Route-related attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum RouteAttrClass {
+    UNSPEC = 0,
+    DST = 1,
+    SRC = 2,
+    IIF = 3,
+    OIF = 4,
+    GATEWAY = 5,
+    PRIORITY = 6,
+    PREFSRC = 7,
+    METRICS = 8,
+    MULTIPATH = 9,
+    PROTOINFO = 10,
+    FLOW = 11,
+    CACHEINFO = 12,
+    SESSION = 13,
+    MP_ALGO = 14,
+    TABLE = 15,
+    MARK = 16,
+    MFC_STATS = 17,
+    VIA = 18,
+    NEWDST = 19,
+    PREF = 20,
+    ENCAP_TYPE = 21,
+    ENCAP = 22,
+    EXPIRES = 23,
+    PAD = 24,
+    UID = 25,
+    TTL_PROPAGATE = 26,
+    IP_PROTO = 27,
+    SPORT = 28,
+    DPORT = 29,
+    NH_ID = 30,
+}
+
+/// A route attribute.
+///
+/// The addresses are in network byte order. Their lengths are 4 bytes for IPv4 addresses and 16
+/// bytes for IPv6 addresses.
+#[derive(Debug)]
+pub enum RouteAttr {
+    Dst(Vec<u8>),
+    Src(Vec<u8>),
+    Iif(u32),
+    Oif(u32),
+    Gateway(Vec<u8>),
+    Priority(u32),
+    PrefSrc(Vec<u8>),
+    Table(u32),
+}
+
+impl RouteAttr {
+    fn class(&self) -> RouteAttrClass {
+        match self {
+            RouteAttr::Dst(_) => RouteAttrClass::DST,
+            RouteAttr::Src(_) => RouteAttrClass::SRC,
+            RouteAttr::Iif(_) => RouteAttrClass::IIF,
+            RouteAttr::Oif(_) => RouteAttrClass::OIF,
+            RouteAttr::Gateway(_) => RouteAttrClass::GATEWAY,
+            RouteAttr::Priority(_) => RouteAttrClass::PRIORITY,
+            RouteAttr::PrefSrc(_) => RouteAttrClass::PREFSRC,
+            RouteAttr::Table(_) => RouteAttrClass::TABLE,
+        }
+    }
+}
+
+impl Attribute for RouteAttr {
+    fn type_(&self) -> u16 {
+        self.class() as u16
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            RouteAttr::Dst(addr)
+            | RouteAttr::Src(addr)
+            | RouteAttr::Gateway(addr)
+            | RouteAttr::PrefSrc(addr) => addr,
+            RouteAttr::Iif(index) | RouteAttr::Oif(index) => index.as_bytes(),
+            RouteAttr::Priority(priority) => priority.as_bytes(),
+            RouteAttr::Table(table) => table.as_bytes(),
+        }
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let payload_len = header.payload_len();
+
+        let Ok(class) = RouteAttrClass::try_from(header.type_()) else {
+            // Unknown attributes should be ignored.
+            // Reference: <https://docs.kernel.org/userspace-api/netlink/intro.html#unknown-attributes>.
+            reader.skip_some(payload_len);
+            return Ok(ContinueRead::Skipped);
+        };
+
+        let res = match (class, payload_len) {
+            (RouteAttrClass::DST, 4 | 16) => Self::Dst(read_bytes(reader, payload_len)?),
+            (RouteAttrClass::SRC, 4 | 16) => Self::Src(read_bytes(reader, payload_len)?),
+            (RouteAttrClass::IIF, 4) => Self::Iif(reader.read_val_opt::<u32>()?.unwrap()),
+            (RouteAttrClass::OIF, 4) => Self::Oif(reader.read_val_opt::<u32>()?.unwrap()),
+            (RouteAttrClass::GATEWAY, 4 | 16) => Self::Gateway(read_bytes(reader, payload_len)?),
+            (RouteAttrClass::PRIORITY, 4) => Self::Priority(reader.read_val_opt::<u32>()?.unwrap()),
+            (RouteAttrClass::PREFSRC, 4 | 16) => Self::PrefSrc(read_bytes(reader, payload_len)?),
+            (RouteAttrClass::TABLE, 4) => Self::Table(reader.read_val_opt::<u32>()?.unwrap()),
+
+            (
+                RouteAttrClass::DST
+                | RouteAttrClass::SRC
+                | RouteAttrClass::IIF
+                | RouteAttrClass::OIF
+                | RouteAttrClass::GATEWAY
+                | RouteAttrClass::PRIORITY
+                | RouteAttrClass::PREFSRC
+                | RouteAttrClass::TABLE,
+                _,
+            ) => {
+                warn!("route attribute `{:?}` contains invalid payload", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::skipped_with_error(
+                    Errno::EINVAL,
+                    "the route attribute is invalid",
+                ));
+            }
+
+            (_, _) => {
+                warn!("route attribute `{:?}` is not supported", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::Skipped);
+            }
+        };
+
+        Ok(ContinueRead::Parsed(res))
+    }
+}
@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{IFNAME_SIZE, link::read_bytes};
+use crate::{
+    net::socket::netlink::message::{Attribute, CAttrHeader, ContinueRead},
+    prelude::*,
+    util::MultiRead,
+};
+
+/// Routing rule attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/fib_rules.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum RuleAttrClass {
+    UNSPEC = 0,
+    DST = 1,
+    SRC = 2,
+    IIFNAME = 3,
+    GOTO = 4,
+    UNUSED2 = 5,
+    PRIORITY = 6,
+    UNUSED3 = 7,
+    UNUSED4 = 8,
+    UNUSED5 = 9,
+    FWMARK = 10,
+    FLOW = 11,
+    TUN_ID = 12,
+    SUPPRESS_IFGROUP = 13,
+    SUPPRESS_PREFIXLEN = 14,
+    TABLE = 15,
+    FWMASK = 16,
+    OIFNAME = 17,
+    PAD = 18,
+    L3MDEV = 19,
+    UID_RANGE = 20,
+    PROTOCOL = 21,
+    IP_PROTO = 22,
+    SPORT_RANGE = 23,
+    DPORT_RANGE = 24,
+    DSCP = 25,
+}
+
+/// A routing rule attribute.
+///
+/// The addresses are in network byte order. Their lengths are 4 bytes for IPv4 addresses and 16
+/// bytes for IPv6 addresses.
+#[derive(Debug)]
+pub enum RuleAttr {
+    Dst(Vec<u8>),
+    Src(Vec<u8>),
+    IifName(CString),
+    Priority(u32),
+    FwMark(u32),
+    Table(u32),
+    FwMask(u32),
+    OifName(CString),
+    Protocol(u8),
+}
+
+impl RuleAttr {
+    fn class(&self) -> RuleAttrClass {
+        match self {
+            RuleAttr::Dst(_) => RuleAttrClass::DST,
+            RuleAttr::Src(_) => RuleAttrClass::SRC,
+            RuleAttr::IifName(_) => RuleAttrClass::IIFNAME,
+            RuleAttr::Priority(_) => RuleAttrClass::PRIORITY,
+            RuleAttr::FwMark(_) => RuleAttrClass::FWMARK,
+            RuleAttr::Table(_) => RuleAttrClass::TABLE,
+            RuleAttr::FwMask(_) => RuleAttrClass::FWMASK,
+            RuleAttr::OifName(_) => RuleAttrClass::OIFNAME,
+            RuleAttr::Protocol(_) => RuleAttrClass::PROTOCOL,
+        }
+    }
+}
+
+impl Attribute for RuleAttr {
+    fn type_(&self) -> u16 {
+        self.class() as u16
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            RuleAttr::Dst(addr) | RuleAttr::Src(addr) => addr,
+            RuleAttr::IifName(name) | RuleAttr::OifName(name) => name.as_bytes_with_nul(),
+            RuleAttr::Priority(value)
+            | RuleAttr::FwMark(value)
+            | RuleAttr::Table(value)
+            | RuleAttr::FwMask(value) => value.as_bytes(),
+            RuleAttr::Protocol(protocol) => protocol.as_bytes(),
+        }
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let payload_len = header.payload_len();
+
+        let Ok(class) = RuleAttrClass::try_from(header.type_()) else {
+            // Unknown attributes should be ignored.
+            // Reference: <https://docs.kernel.org/userspace-api/netlink/intro.html#unknown-attributes>.
+            reader.skip_some(payload_len);
+            return Ok(ContinueRead::Skipped);
+        };
+
+        let res = match (class, payload_len) {
+            (RuleAttrClass::DST, 4 | 16) => Self::Dst(read_bytes(reader, payload_len)?),
+            (RuleAttrClass::SRC, 4 | 16) => Self::Src(read_bytes(reader, payload_len)?),
+            (RuleAttrClass::IIFNAME | RuleAttrClass::OIFNAME, 1..=IFNAME_SIZE) => {
+                let (name, namelen) =
+                    reader.read_cstring_until_end(IFNAME_SIZE.min(payload_len))?;
+                if namelen != payload_len {
+                    reader.skip_some(payload_len - namelen);
+                }
+                if name.as_bytes().len() == IFNAME_SIZE {
+                    return Ok(ContinueRead::skipped_with_error(
+                        Errno::EINVAL,
+                        "the rule attribute is invalid",
+                    ));
+                }
+                if class == RuleAttrClass::IIFNAME {
+                    Self::IifName(name)
+                } else {
+                    Self::OifName(name)
+                }
+            }
+            (RuleAttrClass::PRIORITY, 4) => Self::Priority(reader.read_val_opt::<u32>()?.unwrap()),
+            (RuleAttrClass::FWMARK, 4) => Self::FwMark(reader.read_val_opt::<u32>()?.unwrap()),
+            (RuleAttrClass::TABLE, 4) => Self::Table(reader.read_val_opt::<u32>()?.unwrap()),
+            (RuleAttrClass::FWMASK, 4) => Self::FwMask(reader.read_val_opt::<u32>()?.unwrap()),
+            (RuleAttrClass::PROTOCOL, 1) => Self::Protocol(reader.read_val_opt::<u8>()?.unwrap()),
+
+            (
+                RuleAttrClass::DST
+                | RuleAttrClass::SRC
+                | RuleAttrClass::IIFNAME
+                | RuleAttrClass::OIFNAME
+                | RuleAttrClass::PRIORITY
+                | RuleAttrClass::FWMARK
+                | RuleAttrClass::TABLE
+                | RuleAttrClass::FWMASK
+                | RuleAttrClass::PROTOCOL,
+                _,
+            ) => {
+                warn!("rule attribute `{:?}` contains invalid payload", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::skipped_with_error(
+                    Errno::EINVAL,
+                    "the rule attribute is invalid",
+                ));
+            }
+
+            (_, _) => {
+                warn!("rule attribute `{:?}` is not supported", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::Skipped);
+            }
+        };
+
+        Ok(ContinueRead::Parsed(res))
+    }
+}
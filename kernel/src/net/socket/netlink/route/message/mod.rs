@@ -8,11 +8,19 @@
 mod attr;
 mod segment;
 
-pub(super) use attr::{addr::AddrAttr, link::LinkAttr};
+pub(super) use attr::{
+    addr::AddrAttr,
+    link::LinkAttr,
+    link_info::{LinkInfoAttr, VethInfoAttr},
+    route::RouteAttr,
+    rule::RuleAttr,
+};
 pub(super) use segment::{
     RtnlSegment,
-    addr::{AddrMessageFlags, AddrSegment, AddrSegmentBody, RtScope},
-    link::{LinkSegment, LinkSegmentBody},
+    addr::{AddrMessageFlags, AddrSegment, AddrSegmentBody},
+    link::{CIfinfoMsg, LinkSegment, LinkSegmentBody},
+    route::{RouteMessageFlags, RouteSegment, RouteSegmentBody},
+    rule::{RuleSegment, RuleSegmentBody},
 };
 
 use crate::net::socket::netlink::message::Message;
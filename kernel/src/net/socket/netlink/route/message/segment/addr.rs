@@ -4,9 +4,12 @@ use core::num::NonZeroU32;
 
 use super::legacy::CRtGenMsg;
 use crate::{
-    net::socket::netlink::{
-        message::{SegmentBody, SegmentCommon},
-        route::message::attr::addr::AddrAttr,
+    net::{
+        route::RtScope,
+        socket::netlink::{
+            message::{SegmentBody, SegmentCommon},
+            route::message::attr::addr::AddrAttr,
+        },
     },
     prelude::*,
 };
@@ -99,18 +102,3 @@ bitflags! {
         const STABLE_PRIVACY = 0x800;
     }
 }
-
-/// `rt_scope_t` in Linux.
-///
-/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h#L320>.
-#[expect(clippy::upper_case_acronyms)]
-#[repr(u8)]
-#[derive(Clone, Copy, Debug, TryFromInt)]
-pub enum RtScope {
-    UNIVERSE = 0,
-    // User defined values
-    SITE = 200,
-    LINK = 253,
-    HOST = 254,
-    NOWHERE = 255,
-}
@@ -35,9 +35,12 @@ pub mod addr;
 mod legacy;
 pub mod link;
 pub mod route;
+pub mod rule;
 
 use addr::AddrSegment;
 use link::LinkSegment;
+use route::RouteSegment;
+use rule::RuleSegment;
 
 use crate::{
     net::socket::netlink::message::{
@@ -51,9 +54,17 @@ use crate::{
 #[derive(Debug)]
 pub enum RtnlSegment {
     NewLink(LinkSegment),
+    DelLink(LinkSegment),
     GetLink(LinkSegment),
+    SetLink(LinkSegment),
     NewAddr(AddrSegment),
     GetAddr(AddrSegment),
+    NewRoute(RouteSegment),
+    DelRoute(RouteSegment),
+    GetRoute(RouteSegment),
+    NewRule(RuleSegment),
+    DelRule(RuleSegment),
+    GetRule(RuleSegment),
     Done(DoneSegment),
     Error(ErrorSegment),
 }
@@ -61,12 +72,19 @@ pub enum RtnlSegment {
 impl ProtocolSegment for RtnlSegment {
     fn header(&self) -> &CMsgSegHdr {
         match self {
-            RtnlSegment::NewLink(link_segment) | RtnlSegment::GetLink(link_segment) => {
-                link_segment.header()
-            }
+            RtnlSegment::NewLink(link_segment)
+            | RtnlSegment::DelLink(link_segment)
+            | RtnlSegment::GetLink(link_segment)
+            | RtnlSegment::SetLink(link_segment) => link_segment.header(),
             RtnlSegment::NewAddr(addr_segment) | RtnlSegment::GetAddr(addr_segment) => {
                 addr_segment.header()
             }
+            RtnlSegment::NewRoute(route_segment)
+            | RtnlSegment::DelRoute(route_segment)
+            | RtnlSegment::GetRoute(route_segment) => route_segment.header(),
+            RtnlSegment::NewRule(rule_segment)
+            | RtnlSegment::DelRule(rule_segment)
+            | RtnlSegment::GetRule(rule_segment) => rule_segment.header(),
             RtnlSegment::Done(done_segment) => done_segment.header(),
             RtnlSegment::Error(error_segment) => error_segment.header(),
         }
@@ -74,12 +92,19 @@ impl ProtocolSegment for RtnlSegment {
 
     fn header_mut(&mut self) -> &mut CMsgSegHdr {
         match self {
-            RtnlSegment::NewLink(link_segment) | RtnlSegment::GetLink(link_segment) => {
-                link_segment.header_mut()
-            }
+            RtnlSegment::NewLink(link_segment)
+            | RtnlSegment::DelLink(link_segment)
+            | RtnlSegment::GetLink(link_segment)
+            | RtnlSegment::SetLink(link_segment) => link_segment.header_mut(),
             RtnlSegment::NewAddr(addr_segment) | RtnlSegment::GetAddr(addr_segment) => {
                 addr_segment.header_mut()
             }
+            RtnlSegment::NewRoute(route_segment)
+            | RtnlSegment::DelRoute(route_segment)
+            | RtnlSegment::GetRoute(route_segment) => route_segment.header_mut(),
+            RtnlSegment::NewRule(rule_segment)
+            | RtnlSegment::DelRule(rule_segment)
+            | RtnlSegment::GetRule(rule_segment) => rule_segment.header_mut(),
             RtnlSegment::Done(done_segment) => done_segment.header_mut(),
             RtnlSegment::Error(error_segment) => error_segment.header_mut(),
         }
@@ -91,12 +116,39 @@ impl ProtocolSegment for RtnlSegment {
             .ok_or_else(|| Error::with_message(Errno::EINVAL, "the reader length is too small"))?;
 
         let segment = match CSegmentType::try_from(header.type_) {
+            Ok(CSegmentType::NEWLINK) => {
+                LinkSegment::read_from(&header, reader)?.map(RtnlSegment::NewLink)
+            }
+            Ok(CSegmentType::DELLINK) => {
+                LinkSegment::read_from(&header, reader)?.map(RtnlSegment::DelLink)
+            }
             Ok(CSegmentType::GETLINK) => {
                 LinkSegment::read_from(&header, reader)?.map(RtnlSegment::GetLink)
             }
+            Ok(CSegmentType::SETLINK) => {
+                LinkSegment::read_from(&header, reader)?.map(RtnlSegment::SetLink)
+            }
             Ok(CSegmentType::GETADDR) => {
                 AddrSegment::read_from(&header, reader)?.map(RtnlSegment::GetAddr)
             }
+            Ok(CSegmentType::NEWROUTE) => {
+                RouteSegment::read_from(&header, reader)?.map(RtnlSegment::NewRoute)
+            }
+            Ok(CSegmentType::DELROUTE) => {
+                RouteSegment::read_from(&header, reader)?.map(RtnlSegment::DelRoute)
+            }
+            Ok(CSegmentType::GETROUTE) => {
+                RouteSegment::read_from(&header, reader)?.map(RtnlSegment::GetRoute)
+            }
+            Ok(CSegmentType::NEWRULE) => {
+                RuleSegment::read_from(&header, reader)?.map(RtnlSegment::NewRule)
+            }
+            Ok(CSegmentType::DELRULE) => {
+                RuleSegment::read_from(&header, reader)?.map(RtnlSegment::DelRule)
+            }
+            Ok(CSegmentType::GETRULE) => {
+                RuleSegment::read_from(&header, reader)?.map(RtnlSegment::GetRule)
+            }
             _ => {
                 let payload_len = header.calc_payload_len_with_padding(reader)?;
                 reader.skip_some(payload_len);
@@ -114,10 +166,19 @@ impl ProtocolSegment for RtnlSegment {
         match self {
             RtnlSegment::NewLink(link_segment) => link_segment.write_to(writer)?,
             RtnlSegment::NewAddr(addr_segment) => addr_segment.write_to(writer)?,
+            RtnlSegment::NewRoute(route_segment) => route_segment.write_to(writer)?,
+            RtnlSegment::NewRule(rule_segment) => rule_segment.write_to(writer)?,
             RtnlSegment::Done(done_segment) => done_segment.write_to(writer)?,
             RtnlSegment::Error(error_segment) => error_segment.write_to(writer)?,
-            RtnlSegment::GetAddr(_) | RtnlSegment::GetLink(_) => {
-                unreachable!("kernel should not write get requests to user space");
+            RtnlSegment::GetAddr(_)
+            | RtnlSegment::GetLink(_)
+            | RtnlSegment::DelLink(_)
+            | RtnlSegment::SetLink(_)
+            | RtnlSegment::GetRoute(_)
+            | RtnlSegment::DelRoute(_)
+            | RtnlSegment::GetRule(_)
+            | RtnlSegment::DelRule(_) => {
+                unreachable!("kernel should not write get, set or delete requests to user space");
             }
         }
         Ok(())
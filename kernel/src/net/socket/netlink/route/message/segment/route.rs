@@ -1,21 +1,114 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::prelude::*;
+use super::legacy::CRtGenMsg;
+use crate::{
+    net::{
+        route::{RouteType, RtScope},
+        socket::netlink::{
+            message::{SegmentBody, SegmentCommon},
+            route::message::attr::route::RouteAttr,
+        },
+    },
+    prelude::*,
+};
+
+pub type RouteSegment = SegmentCommon<RouteSegmentBody, RouteAttr>;
+
+impl SegmentBody for RouteSegmentBody {
+    type CLegacyType = CRtGenMsg;
+    type CType = CRtMsg;
+}
 
 /// `rtmsg` in Linux.
 ///
 /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h#L237>.
-#[expect(unused)]
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod)]
-pub(super) struct CRtMsg {
-    family: u8,
-    dst_len: u8,
-    src_len: u8,
-    tos: u8,
-    table: u8,
-    protocol: u8,
-    scope: u8,
-    type_: u8,
-    flags: u32,
+pub struct CRtMsg {
+    pub family: u8,
+    /// The prefix length of the destination
+    pub dst_len: u8,
+    /// The prefix length of the source
+    pub src_len: u8,
+    /// The TOS filter
+    pub tos: u8,
+    /// The routing table ID
+    pub table: u8,
+    /// The routing protocol
+    pub protocol: u8,
+    pub scope: u8,
+    pub type_: u8,
+    pub flags: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RouteSegmentBody {
+    pub family: i32,
+    pub dst_len: u8,
+    pub src_len: u8,
+    pub tos: u8,
+    pub table: u8,
+    pub protocol: u8,
+    pub scope: RtScope,
+    pub type_: RouteType,
+    pub flags: RouteMessageFlags,
+}
+
+impl TryFrom<CRtMsg> for RouteSegmentBody {
+    type Error = Error;
+
+    fn try_from(value: CRtMsg) -> Result<Self> {
+        let scope = RtScope::try_from(value.scope)?;
+        let type_ = RouteType::try_from(value.type_)?;
+        let flags = RouteMessageFlags::from_bits_truncate(value.flags);
+
+        Ok(Self {
+            family: value.family as i32,
+            dst_len: value.dst_len,
+            src_len: value.src_len,
+            tos: value.tos,
+            table: value.table,
+            protocol: value.protocol,
+            scope,
+            type_,
+            flags,
+        })
+    }
+}
+
+impl From<RouteSegmentBody> for CRtMsg {
+    fn from(value: RouteSegmentBody) -> Self {
+        CRtMsg {
+            family: value.family as u8,
+            dst_len: value.dst_len,
+            src_len: value.src_len,
+            tos: value.tos,
+            table: value.table,
+            protocol: value.protocol,
+            scope: value.scope as _,
+            type_: value.type_ as _,
+            flags: value.flags.bits(),
+        }
+    }
+}
+
+bitflags! {
+    /// Flags in [`CRtMsg`].
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h#L330>.
+    pub struct RouteMessageFlags: u32 {
+        /// Notify users of route changes
+        const NOTIFY         = 0x100;
+        /// The route is cloned (i.e., it is the result of a lookup)
+        const CLONED         = 0x200;
+        const EQUALIZE       = 0x400;
+        const PREFIX         = 0x800;
+        /// Set the table ID to the table that the route is found in
+        const LOOKUP_TABLE   = 0x1000;
+        /// Return the matching FIB entry instead of the lookup result
+        const FIB_MATCH      = 0x2000;
+        const OFFLOAD        = 0x4000;
+        const TRAP           = 0x8000;
+        const OFFLOAD_FAILED = 0x20000000;
+    }
 }
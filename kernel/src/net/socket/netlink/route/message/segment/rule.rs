@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::legacy::CRtGenMsg;
+use crate::{
+    net::{
+        route::{RuleAction, RuleFlags},
+        socket::netlink::{
+            message::{SegmentBody, SegmentCommon},
+            route::message::attr::rule::RuleAttr,
+        },
+    },
+    prelude::*,
+};
+
+pub type RuleSegment = SegmentCommon<RuleSegmentBody, RuleAttr>;
+
+impl SegmentBody for RuleSegmentBody {
+    type CLegacyType = CRtGenMsg;
+    type CType = CFibRuleHdr;
+}
+
+/// `fib_rule_hdr` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/fib_rules.h#L21>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CFibRuleHdr {
+    pub family: u8,
+    /// The prefix length of the destination
+    pub dst_len: u8,
+    /// The prefix length of the source
+    pub src_len: u8,
+    /// The TOS selector
+    pub tos: u8,
+    /// The routing table ID
+    pub table: u8,
+    pub _res1: u8,
+    pub _res2: u8,
+    pub action: u8,
+    pub flags: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RuleSegmentBody {
+    pub family: i32,
+    pub dst_len: u8,
+    pub src_len: u8,
+    pub tos: u8,
+    pub table: u8,
+    pub action: RuleAction,
+    pub flags: RuleFlags,
+}
+
+impl TryFrom<CFibRuleHdr> for RuleSegmentBody {
+    type Error = Error;
+
+    fn try_from(value: CFibRuleHdr) -> Result<Self> {
+        let action = RuleAction::try_from(value.action)?;
+        let flags = RuleFlags::from_bits_truncate(value.flags);
+
+        Ok(Self {
+            family: value.family as i32,
+            dst_len: value.dst_len,
+            src_len: value.src_len,
+            tos: value.tos,
+            table: value.table,
+            action,
+            flags,
+        })
+    }
+}
+
+impl From<RuleSegmentBody> for CFibRuleHdr {
+    fn from(value: RuleSegmentBody) -> Self {
+        CFibRuleHdr {
+            family: value.family as u8,
+            dst_len: value.dst_len,
+            src_len: value.src_len,
+            tos: value.tos,
+            table: value.table,
+            _res1: 0,
+            _res2: 0,
+            action: value.action as _,
+            flags: value.flags.bits(),
+        }
+    }
+}
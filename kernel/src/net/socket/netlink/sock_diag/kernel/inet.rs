@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle requests for TCP and UDP sockets.
+
+use aster_bigtcp::wire::{IpAddress, IpEndpoint};
+
+use super::{finish_dump_response, is_dump_request, new_response_header};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            ip::{InetSocketInfo, InetSocketProtocol, inet_socket_infos},
+            netlink::{
+                message::CMsgSegHdr,
+                sock_diag::message::{
+                    CInetDiagSockId, DiagSegment, InetDiagMsgBody, InetDiagMsgSegment,
+                    InetDiagReqSegment,
+                },
+            },
+        },
+    },
+    prelude::*,
+    util::net::CSocketAddrFamily,
+};
+
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+pub(super) fn do_get_inet_sockets(
+    net_ns: &NetNamespace,
+    request_segment: &InetDiagReqSegment,
+) -> Result<Vec<DiagSegment>> {
+    let request = request_segment.body();
+    let protocol = match request.protocol {
+        IPPROTO_TCP => InetSocketProtocol::Tcp,
+        IPPROTO_UDP => InetSocketProtocol::Udp,
+        _ => return_errno_with_message!(Errno::ENOENT, "the protocol is not supported"),
+    };
+
+    let dump_all = is_dump_request(request_segment.header());
+    let header = new_response_header(request_segment.header(), dump_all);
+
+    let mut matches = inet_socket_infos(net_ns).into_iter().filter(|info| {
+        info.protocol == protocol && family_of(&info.local_endpoint.addr) == request.family
+    });
+
+    if !dump_all {
+        // Find the socket with exactly the same addresses and ports.
+        let Some(info) = matches.find(|info| {
+            let id = sock_id_of(info);
+            (id.sport, id.dport, id.src, id.dst)
+                == (
+                    request.id.sport,
+                    request.id.dport,
+                    request.id.src,
+                    request.id.dst,
+                )
+        }) else {
+            return_errno_with_message!(Errno::ENOENT, "the socket does not exist");
+        };
+        return Ok(vec![DiagSegment::InetMsg(new_inet_diag_msg(header, &info))]);
+    }
+
+    let mut response_segments: Vec<DiagSegment> = matches
+        .filter(|info| request.states & (1 << info.state as u8) != 0)
+        .map(|info| DiagSegment::InetMsg(new_inet_diag_msg(header, &info)))
+        .collect();
+    finish_dump_response(request_segment.header(), &mut response_segments);
+
+    Ok(response_segments)
+}
+
+fn new_inet_diag_msg(header: CMsgSegHdr, info: &InetSocketInfo) -> InetDiagMsgSegment {
+    let body = InetDiagMsgBody {
+        family: family_of(&info.local_endpoint.addr),
+        state: info.state as u8,
+        id: sock_id_of(info),
+        rqueue: info.recv_queue as u32,
+        wqueue: info.send_queue as u32,
+        uid: info.owner.map_or(0, |owner| owner.uid().into()),
+        inode: info.owner.map_or(0, |owner| owner.ino() as u32),
+    };
+
+    // TODO: Report the extensions requested in `inet_diag_req_v2::idiag_ext`.
+    InetDiagMsgSegment::new(header, body, Vec::new())
+}
+
+fn sock_id_of(info: &InetSocketInfo) -> CInetDiagSockId {
+    CInetDiagSockId {
+        sport: info.local_endpoint.port.to_be(),
+        dport: info.remote_endpoint.port.to_be(),
+        src: addr_to_bytes(&info.local_endpoint),
+        dst: addr_to_bytes(&info.remote_endpoint),
+        if_: 0,
+        cookie: [0; 2],
+    }
+}
+
+/// Converts the address of an endpoint to the bytes in `inet_diag_sockid`.
+///
+/// IPv4 addresses occupy the first four bytes.
+fn addr_to_bytes(endpoint: &IpEndpoint) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    match endpoint.addr {
+        IpAddress::Ipv4(ipv4_addr) => bytes[..4].copy_from_slice(&ipv4_addr.octets()),
+        IpAddress::Ipv6(ipv6_addr) => bytes = ipv6_addr.octets(),
+    }
+    bytes
+}
+
+fn family_of(addr: &IpAddress) -> CSocketAddrFamily {
+    match addr {
+        IpAddress::Ipv4(_) => CSocketAddrFamily::AF_INET,
+        IpAddress::Ipv6(_) => CSocketAddrFamily::AF_INET6,
+    }
+}
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines the kernel socket,
+//! which is responsible for handling requests from user space.
+
+use super::message::{DiagMessage, DiagSegment, SOCK_DIAG_BY_FAMILY};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            addr::PortNum,
+            message::{
+                CMsgSegHdr, DoneSegment, ErrorSegment, GetRequestFlags, ProtocolSegment,
+                SegHdrCommonFlags,
+            },
+            table::{NetlinkSockDiagProtocol, SupportedNetlinkProtocol},
+        },
+    },
+    prelude::*,
+};
+
+mod inet;
+mod unix;
+
+/// The kernel socket of a network namespace.
+pub(super) struct NetlinkSockDiagKernelSocket<'a> {
+    net_ns: &'a NetNamespace,
+}
+
+impl<'a> NetlinkSockDiagKernelSocket<'a> {
+    fn new(net_ns: &'a NetNamespace) -> Self {
+        Self { net_ns }
+    }
+
+    pub(super) fn handle_request(&self, request: &DiagSegment, dst_port: PortNum) {
+        debug!("netlink socket monitoring request: {:?}", request);
+
+        let request_header = request.header();
+
+        let response_segments = match request {
+            DiagSegment::InetReq(request_segment) => {
+                inet::do_get_inet_sockets(self.net_ns, request_segment)
+            }
+            DiagSegment::UnixReq(request_segment) => {
+                unix::do_get_unix_sockets(self.net_ns, request_segment)
+            }
+            _ => Err(Error::with_message(
+                Errno::EOPNOTSUPP,
+                "the netlink socket monitoring request is not supported",
+            )),
+        };
+
+        let response = match response_segments {
+            Ok(segments) => DiagMessage::new(segments),
+            Err(error) => {
+                let err_segment = ErrorSegment::new_from_request(request_header, Some(error));
+                self.report_error(err_segment, dst_port);
+                return;
+            }
+        };
+
+        debug!("netlink socket monitoring response: {:?}", response);
+
+        NetlinkSockDiagProtocol::unicast(dst_port, response).unwrap();
+    }
+
+    pub(super) fn report_error(&self, err_segment: ErrorSegment, dst_port: PortNum) {
+        let response = DiagMessage::new(vec![DiagSegment::Error(err_segment)]);
+
+        debug!("netlink socket monitoring error: {:?}", response);
+
+        NetlinkSockDiagProtocol::unicast(dst_port, response).unwrap();
+    }
+}
+
+/// Returns the kernel socket of the given network namespace.
+pub(super) fn get_netlink_sock_diag_kernel(
+    net_ns: &NetNamespace,
+) -> NetlinkSockDiagKernelSocket<'_> {
+    NetlinkSockDiagKernelSocket::new(net_ns)
+}
+
+/// Returns whether the request asks for all the matching sockets.
+fn is_dump_request(request_header: &CMsgSegHdr) -> bool {
+    let flags = GetRequestFlags::from_bits_truncate(request_header.flags);
+    flags.contains(GetRequestFlags::DUMP)
+}
+
+/// Creates the header of a response segment.
+fn new_response_header(request_header: &CMsgSegHdr, dump_all: bool) -> CMsgSegHdr {
+    let flags = if dump_all {
+        SegHdrCommonFlags::MULTI
+    } else {
+        SegHdrCommonFlags::empty()
+    };
+
+    CMsgSegHdr {
+        len: 0,
+        type_: SOCK_DIAG_BY_FAMILY,
+        flags: flags.bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    }
+}
+
+/// Finishes the response to a dump request by appending a done segment.
+fn finish_dump_response(request_header: &CMsgSegHdr, response_segments: &mut Vec<DiagSegment>) {
+    let mut done_segment = DoneSegment::new_from_request(request_header, None);
+    done_segment.header_mut().flags |= SegHdrCommonFlags::MULTI.bits();
+    response_segments.push(DiagSegment::Done(done_segment));
+}
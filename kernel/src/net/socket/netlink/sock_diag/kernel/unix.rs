@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle requests for UNIX sockets.
+
+use super::{finish_dump_response, is_dump_request, new_response_header};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            ip::InetSocketState,
+            netlink::{
+                message::CMsgSegHdr,
+                sock_diag::message::{
+                    DiagAttr, DiagSegment, UnixDiagAttrType, UnixDiagMsgBody, UnixDiagMsgSegment,
+                    UnixDiagReqSegment, UnixDiagShow,
+                },
+            },
+            unix::{UnixSocketAddr, UnixSocketInfo, UnixSocketState, unix_socket_infos},
+        },
+    },
+    prelude::*,
+};
+
+pub(super) fn do_get_unix_sockets(
+    net_ns: &NetNamespace,
+    request_segment: &UnixDiagReqSegment,
+) -> Result<Vec<DiagSegment>> {
+    let request = request_segment.body();
+
+    let dump_all = is_dump_request(request_segment.header());
+    let header = new_response_header(request_segment.header(), dump_all);
+
+    let mut infos = unix_socket_infos(net_ns).into_iter();
+
+    if !dump_all {
+        let Some(info) = infos.find(|info| info.owner.ino() as u32 == request.ino) else {
+            return_errno_with_message!(Errno::ENOENT, "the socket does not exist");
+        };
+        let segment = new_unix_diag_msg(header, &info, request.show);
+        return Ok(vec![DiagSegment::UnixMsg(segment)]);
+    }
+
+    let mut response_segments: Vec<DiagSegment> = infos
+        .filter(|info| request.states & (1 << state_of(info.state)) != 0)
+        .map(|info| DiagSegment::UnixMsg(new_unix_diag_msg(header, &info, request.show)))
+        .collect();
+    finish_dump_response(request_segment.header(), &mut response_segments);
+
+    Ok(response_segments)
+}
+
+fn new_unix_diag_msg(
+    header: CMsgSegHdr,
+    info: &UnixSocketInfo,
+    show: UnixDiagShow,
+) -> UnixDiagMsgSegment {
+    let body = UnixDiagMsgBody {
+        type_: info.type_ as u8,
+        state: state_of(info.state),
+        ino: info.owner.ino() as u32,
+    };
+
+    // TODO: Report the other attributes (e.g., `UNIX_DIAG_PEER` and `UNIX_DIAG_RQLEN`).
+    let mut attrs = Vec::new();
+    if show.contains(UnixDiagShow::NAME)
+        && let Some(name) = name_of(&info.addr)
+    {
+        attrs.push(DiagAttr::new_bytes(UnixDiagAttrType::Name as u16, name));
+    }
+    if show.contains(UnixDiagShow::UID) {
+        let uid = info.owner.uid().into();
+        attrs.push(DiagAttr::new_u32(UnixDiagAttrType::Uid as u16, uid));
+    }
+
+    UnixDiagMsgSegment::new(header, body, attrs)
+}
+
+/// Returns the state of a UNIX socket, which is represented by TCP states in Linux.
+fn state_of(state: UnixSocketState) -> u8 {
+    let state = match state {
+        UnixSocketState::Unconnected => InetSocketState::Close,
+        UnixSocketState::Listening => InetSocketState::Listen,
+        UnixSocketState::Connected => InetSocketState::Established,
+    };
+    state as u8
+}
+
+/// Returns the name of a UNIX socket, which is `sun_path` with the same length as the one in
+/// `sockaddr_un` returned by `getsockname`.
+fn name_of(addr: &UnixSocketAddr) -> Option<Vec<u8>> {
+    match addr {
+        UnixSocketAddr::Unnamed => None,
+        UnixSocketAddr::Path(path) => {
+            let mut name = path.as_bytes().to_vec();
+            name.push(0);
+            Some(name)
+        }
+        UnixSocketAddr::Abstract(abstract_name) => {
+            let mut name = vec![0];
+            name.extend_from_slice(abstract_name);
+            Some(name)
+        }
+    }
+}
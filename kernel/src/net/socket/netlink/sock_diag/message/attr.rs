@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    net::socket::netlink::message::{Attribute, CAttrHeader, ContinueRead},
+    prelude::*,
+    util::MultiRead,
+};
+
+/// A socket monitoring attribute.
+///
+/// The attributes are only used in responses, so they are kept as raw bytes. Integers are in
+/// native byte order.
+#[derive(Debug)]
+pub struct DiagAttr {
+    type_: u16,
+    payload: Vec<u8>,
+}
+
+impl DiagAttr {
+    pub fn new_bytes(type_: u16, payload: Vec<u8>) -> Self {
+        Self { type_, payload }
+    }
+
+    pub fn new_u32(type_: u16, value: u32) -> Self {
+        Self::new_bytes(type_, value.to_ne_bytes().to_vec())
+    }
+}
+
+impl Attribute for DiagAttr {
+    fn type_(&self) -> u16 {
+        self.type_
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        // TODO: Support the attributes in requests (e.g., `INET_DIAG_REQ_BYTECODE`).
+        let payload_len = header.payload_len();
+        reader.skip_some(payload_len);
+
+        Ok(ContinueRead::Skipped)
+    }
+}
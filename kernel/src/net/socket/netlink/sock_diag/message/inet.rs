@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::attr::DiagAttr;
+use crate::{
+    net::socket::netlink::message::{SegmentBody, SegmentCommon},
+    prelude::*,
+    util::net::CSocketAddrFamily,
+};
+
+pub type InetDiagReqSegment = SegmentCommon<InetDiagReqBody, DiagAttr>;
+
+impl SegmentBody for InetDiagReqBody {
+    type CType = CInetDiagReqV2;
+}
+
+pub type InetDiagMsgSegment = SegmentCommon<InetDiagMsgBody, DiagAttr>;
+
+impl SegmentBody for InetDiagMsgBody {
+    type CType = CInetDiagMsg;
+}
+
+/// `inet_diag_sockid` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/inet_diag.h#L14>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CInetDiagSockId {
+    /// The source port in network byte order
+    pub sport: u16,
+    /// The destination port in network byte order
+    pub dport: u16,
+    /// The source address in network byte order
+    pub src: [u8; 16],
+    /// The destination address in network byte order
+    pub dst: [u8; 16],
+    /// The interface index
+    pub if_: u32,
+    /// The socket cookie
+    pub cookie: [u32; 2],
+}
+
+/// `inet_diag_req_v2` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/inet_diag.h#L38>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CInetDiagReqV2 {
+    pub family: u8,
+    pub protocol: u8,
+    /// The extensions to report (`INET_DIAG_*`)
+    pub ext: u8,
+    pub pad: u8,
+    /// The bitmask of the socket states to report
+    pub states: u32,
+    pub id: CInetDiagSockId,
+}
+
+/// `inet_diag_msg` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/inet_diag.h#L117>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CInetDiagMsg {
+    pub family: u8,
+    pub state: u8,
+    pub timer: u8,
+    pub retrans: u8,
+    pub id: CInetDiagSockId,
+    pub expires: u32,
+    pub rqueue: u32,
+    pub wqueue: u32,
+    pub uid: u32,
+    pub inode: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct InetDiagReqBody {
+    pub family: CSocketAddrFamily,
+    /// The IP protocol number (`IPPROTO_TCP` or `IPPROTO_UDP`).
+    pub protocol: u8,
+    /// The bitmask of the socket states to report.
+    pub states: u32,
+    pub id: CInetDiagSockId,
+}
+
+impl TryFrom<CInetDiagReqV2> for InetDiagReqBody {
+    type Error = Error;
+
+    fn try_from(value: CInetDiagReqV2) -> Result<Self> {
+        let family = match CSocketAddrFamily::try_from(value.family as i32) {
+            Ok(family @ (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6)) => family,
+            _ => return_errno_with_message!(Errno::EINVAL, "the address family is not valid"),
+        };
+
+        Ok(Self {
+            family,
+            protocol: value.protocol,
+            states: value.states,
+            id: value.id,
+        })
+    }
+}
+
+impl From<InetDiagReqBody> for CInetDiagReqV2 {
+    fn from(value: InetDiagReqBody) -> Self {
+        Self {
+            family: value.family as u8,
+            protocol: value.protocol,
+            ext: 0,
+            pad: 0,
+            states: value.states,
+            id: value.id,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct InetDiagMsgBody {
+    pub family: CSocketAddrFamily,
+    pub state: u8,
+    pub id: CInetDiagSockId,
+    pub rqueue: u32,
+    pub wqueue: u32,
+    pub uid: u32,
+    pub inode: u32,
+}
+
+impl TryFrom<CInetDiagMsg> for InetDiagMsgBody {
+    type Error = Error;
+
+    fn try_from(value: CInetDiagMsg) -> Result<Self> {
+        let family = CSocketAddrFamily::try_from(value.family as i32)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the address family is not valid"))?;
+
+        Ok(Self {
+            family,
+            state: value.state,
+            id: value.id,
+            rqueue: value.rqueue,
+            wqueue: value.wqueue,
+            uid: value.uid,
+            inode: value.inode,
+        })
+    }
+}
+
+impl From<InetDiagMsgBody> for CInetDiagMsg {
+    fn from(value: InetDiagMsgBody) -> Self {
+        Self {
+            family: value.family as u8,
+            state: value.state,
+            // TODO: Report the timers and the retransmissions.
+            timer: 0,
+            retrans: 0,
+            id: value.id,
+            expires: 0,
+            rqueue: value.rqueue,
+            wqueue: value.wqueue,
+            uid: value.uid,
+            inode: value.inode,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink message types for the netlink socket monitoring protocol.
+//!
+//! This module defines how to interpret messages sent from user space and how to write
+//! kernel messages back to user space.
+
+mod attr;
+mod inet;
+mod segment;
+mod unix;
+
+pub(super) use attr::DiagAttr;
+pub(super) use inet::{CInetDiagSockId, InetDiagMsgBody, InetDiagMsgSegment, InetDiagReqSegment};
+pub(super) use segment::{DiagSegment, SOCK_DIAG_BY_FAMILY};
+pub(super) use unix::{
+    UnixDiagAttrType, UnixDiagMsgBody, UnixDiagMsgSegment, UnixDiagReqSegment, UnixDiagShow,
+};
+
+use crate::net::socket::netlink::message::Message;
+
+/// A netlink socket monitoring message.
+pub(in crate::net::socket::netlink) type DiagMessage = Message<DiagSegment>;
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    inet::{InetDiagMsgSegment, InetDiagReqSegment},
+    unix::{UnixDiagMsgSegment, UnixDiagReqSegment},
+};
+use crate::{
+    net::socket::netlink::message::{
+        CMsgSegHdr, ContinueRead, DoneSegment, ErrorSegment, ProtocolSegment,
+    },
+    prelude::*,
+    util::{MultiRead, MultiWrite, net::CSocketAddrFamily},
+};
+
+/// The type of the requests and the responses, whose bodies depend on the address family.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/sock_diag.h#L7>.
+pub const SOCK_DIAG_BY_FAMILY: u16 = 20;
+
+/// The netlink socket monitoring segment, which is the basic unit of a netlink socket
+/// monitoring message.
+#[derive(Debug)]
+pub enum DiagSegment {
+    InetReq(InetDiagReqSegment),
+    UnixReq(UnixDiagReqSegment),
+    InetMsg(InetDiagMsgSegment),
+    UnixMsg(UnixDiagMsgSegment),
+    Done(DoneSegment),
+    Error(ErrorSegment),
+}
+
+impl ProtocolSegment for DiagSegment {
+    fn header(&self) -> &CMsgSegHdr {
+        match self {
+            DiagSegment::InetReq(segment) => segment.header(),
+            DiagSegment::UnixReq(segment) => segment.header(),
+            DiagSegment::InetMsg(segment) => segment.header(),
+            DiagSegment::UnixMsg(segment) => segment.header(),
+            DiagSegment::Done(done_segment) => done_segment.header(),
+            DiagSegment::Error(error_segment) => error_segment.header(),
+        }
+    }
+
+    fn header_mut(&mut self) -> &mut CMsgSegHdr {
+        match self {
+            DiagSegment::InetReq(segment) => segment.header_mut(),
+            DiagSegment::UnixReq(segment) => segment.header_mut(),
+            DiagSegment::InetMsg(segment) => segment.header_mut(),
+            DiagSegment::UnixMsg(segment) => segment.header_mut(),
+            DiagSegment::Done(done_segment) => done_segment.header_mut(),
+            DiagSegment::Error(error_segment) => error_segment.header_mut(),
+        }
+    }
+
+    fn read_from(reader: &mut dyn MultiRead) -> Result<ContinueRead<Self, ErrorSegment>> {
+        let header = reader
+            .read_val_opt::<CMsgSegHdr>()?
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the reader length is too small"))?;
+
+        let payload_len = header.calc_payload_len_with_padding(reader)?;
+        let segment = if header.type_ == SOCK_DIAG_BY_FAMILY {
+            read_by_family(&header, payload_len, reader)?
+        } else {
+            reader.skip_some(payload_len);
+            ContinueRead::skipped_with_error(Errno::EOPNOTSUPP, "the segment type is not supported")
+        };
+
+        Ok(segment.map_err(|error| ErrorSegment::new_from_request(&header, Some(error))))
+    }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        match self {
+            DiagSegment::InetMsg(segment) => segment.write_to(writer)?,
+            DiagSegment::UnixMsg(segment) => segment.write_to(writer)?,
+            DiagSegment::Done(done_segment) => done_segment.write_to(writer)?,
+            DiagSegment::Error(error_segment) => error_segment.write_to(writer)?,
+            DiagSegment::InetReq(_) | DiagSegment::UnixReq(_) => {
+                unreachable!("kernel should not write requests to user space");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a request whose body depends on the address family.
+///
+/// The address family is in the first byte of the body, so the payload is read before it is
+/// parsed.
+fn read_by_family(
+    header: &CMsgSegHdr,
+    payload_len: usize,
+    reader: &mut dyn MultiRead,
+) -> Result<ContinueRead<DiagSegment>> {
+    let mut payload = vec![0; payload_len];
+    reader.read(&mut VmWriter::from(payload.as_mut_slice()))?;
+    let mut payload_reader = VmReader::from(payload.as_slice()).to_fallible();
+
+    let Some(family) = payload.first() else {
+        return Ok(ContinueRead::skipped_with_error(
+            Errno::EINVAL,
+            "the message length is too small",
+        ));
+    };
+
+    let segment = match CSocketAddrFamily::try_from(*family as i32) {
+        Ok(CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6) => {
+            InetDiagReqSegment::read_from(header, &mut payload_reader)?.map(DiagSegment::InetReq)
+        }
+        Ok(CSocketAddrFamily::AF_UNIX) => {
+            UnixDiagReqSegment::read_from(header, &mut payload_reader)?.map(DiagSegment::UnixReq)
+        }
+        _ => ContinueRead::skipped_with_error(Errno::ENOENT, "the address family is not supported"),
+    };
+
+    Ok(segment)
+}
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::attr::DiagAttr;
+use crate::{
+    net::socket::netlink::message::{SegmentBody, SegmentCommon},
+    prelude::*,
+    util::net::CSocketAddrFamily,
+};
+
+pub type UnixDiagReqSegment = SegmentCommon<UnixDiagReqBody, DiagAttr>;
+
+impl SegmentBody for UnixDiagReqBody {
+    type CType = CUnixDiagReq;
+}
+
+pub type UnixDiagMsgSegment = SegmentCommon<UnixDiagMsgBody, DiagAttr>;
+
+impl SegmentBody for UnixDiagMsgBody {
+    type CType = CUnixDiagMsg;
+}
+
+/// `unix_diag_req` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/unix_diag.h#L7>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CUnixDiagReq {
+    pub family: u8,
+    pub protocol: u8,
+    pub pad: u16,
+    /// The bitmask of the socket states to report
+    pub states: u32,
+    /// The inode number of the socket to report
+    pub ino: u32,
+    /// The attributes to report (`UDIAG_SHOW_*`)
+    pub show: u32,
+    /// The socket cookie
+    pub cookie: [u32; 2],
+}
+
+/// `unix_diag_msg` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/unix_diag.h#L28>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CUnixDiagMsg {
+    pub family: u8,
+    pub type_: u8,
+    pub state: u8,
+    pub pad: u8,
+    pub ino: u32,
+    pub cookie: [u32; 2],
+}
+
+bitflags! {
+    /// The attributes to report in the responses.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/unix_diag.h#L18>.
+    pub struct UnixDiagShow: u32 {
+        const NAME = 0x00000001;
+        const VFS = 0x00000002;
+        const PEER = 0x00000004;
+        const ICONS = 0x00000008;
+        const RQLEN = 0x00000010;
+        const MEMINFO = 0x00000020;
+        const UID = 0x00000040;
+    }
+}
+
+/// The types of attributes in the responses.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/unix_diag.h#L37>.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug)]
+pub enum UnixDiagAttrType {
+    Name = 0,
+    Uid = 7,
+    // TODO: The list is not exhaustive.
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct UnixDiagReqBody {
+    /// The bitmask of the socket states to report.
+    pub states: u32,
+    /// The inode number of the socket to report, if it is not a dump request.
+    pub ino: u32,
+    pub show: UnixDiagShow,
+}
+
+impl TryFrom<CUnixDiagReq> for UnixDiagReqBody {
+    type Error = Error;
+
+    fn try_from(value: CUnixDiagReq) -> Result<Self> {
+        if value.family != CSocketAddrFamily::AF_UNIX as u8 {
+            return_errno_with_message!(Errno::EINVAL, "the address family is not valid");
+        }
+
+        Ok(Self {
+            states: value.states,
+            ino: value.ino,
+            show: UnixDiagShow::from_bits_truncate(value.show),
+        })
+    }
+}
+
+impl From<UnixDiagReqBody> for CUnixDiagReq {
+    fn from(value: UnixDiagReqBody) -> Self {
+        Self {
+            family: CSocketAddrFamily::AF_UNIX as u8,
+            protocol: 0,
+            pad: 0,
+            states: value.states,
+            ino: value.ino,
+            show: value.show.bits(),
+            cookie: [0; 2],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct UnixDiagMsgBody {
+    /// The socket type (e.g., `SOCK_STREAM`).
+    pub type_: u8,
+    pub state: u8,
+    pub ino: u32,
+}
+
+impl TryFrom<CUnixDiagMsg> for UnixDiagMsgBody {
+    type Error = Error;
+
+    fn try_from(value: CUnixDiagMsg) -> Result<Self> {
+        Ok(Self {
+            type_: value.type_,
+            state: value.state,
+            ino: value.ino,
+        })
+    }
+}
+
+impl From<UnixDiagMsgBody> for CUnixDiagMsg {
+    fn from(value: UnixDiagMsgBody) -> Self {
+        Self {
+            family: CSocketAddrFamily::AF_UNIX as u8,
+            type_: value.type_,
+            state: value.state,
+            pad: 0,
+            ino: value.ino,
+            // TODO: Support socket cookies.
+            cookie: [0; 2],
+        }
+    }
+}
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink Socket Monitoring (`NETLINK_SOCK_DIAG`) Socket.
+
+pub(super) use message::DiagMessage;
+
+use crate::net::socket::netlink::{common::NetlinkSocket, table::NetlinkSockDiagProtocol};
+
+mod bound;
+mod kernel;
+mod message;
+
+pub type NetlinkSockDiagSocket = NetlinkSocket<NetlinkSockDiagProtocol>;
@@ -10,8 +10,9 @@ use super::{
 };
 use crate::{
     net::socket::netlink::{
-        addr::UNSPECIFIED_PORT, kobject_uevent::UeventMessage, receiver::MessageReceiver,
-        route::RtnlMessage,
+        addr::UNSPECIFIED_PORT, audit::AuditMessage, kobject_uevent::UeventMessage,
+        netfilter::NfnlMessage, receiver::MessageReceiver, route::RtnlMessage,
+        sock_diag::DiagMessage,
     },
     prelude::*,
     util::random::getrandom,
@@ -25,6 +26,9 @@ static NETLINK_SOCKET_TABLE: Once<NetlinkSocketTable> = Once::new();
 struct NetlinkSocketTable {
     route: RwMutex<ProtocolSocketTable<RtnlMessage>>,
     uevent: RwMutex<ProtocolSocketTable<UeventMessage>>,
+    netfilter: RwMutex<ProtocolSocketTable<NfnlMessage>>,
+    sock_diag: RwMutex<ProtocolSocketTable<DiagMessage>>,
+    audit: RwMutex<ProtocolSocketTable<AuditMessage>>,
 }
 
 impl NetlinkSocketTable {
@@ -32,6 +36,9 @@ impl NetlinkSocketTable {
         Self {
             route: RwMutex::new(ProtocolSocketTable::new()),
             uevent: RwMutex::new(ProtocolSocketTable::new()),
+            netfilter: RwMutex::new(ProtocolSocketTable::new()),
+            sock_diag: RwMutex::new(ProtocolSocketTable::new()),
+            audit: RwMutex::new(ProtocolSocketTable::new()),
         }
     }
 }
@@ -57,6 +64,18 @@ pub trait SupportedNetlinkProtocol {
         socket_table.unicast(dst_port, message)
     }
 
+    /// Sends a message to the socket bound to `dst_port`, failing if it cannot be delivered.
+    ///
+    /// Unlike [`Self::unicast`], this method fails with [`Errno::ECONNREFUSED`] if no socket is
+    /// bound to the port, and with [`Errno::EAGAIN`] if the receive buffer of the socket is full.
+    fn try_unicast(dst_port: PortNum, message: Self::Message) -> Result<()>
+    where
+        Self::Message: QueueableMessage,
+    {
+        let socket_table = Self::socket_table().read();
+        socket_table.try_unicast(dst_port, message)
+    }
+
     #[cfg_attr(not(ktest), expect(dead_code))]
     fn multicast(dst_groups: GroupIdSet, message: Self::Message) -> Result<()>
     where
@@ -87,6 +106,36 @@ impl SupportedNetlinkProtocol for NetlinkUeventProtocol {
     }
 }
 
+pub enum NetlinkNetfilterProtocol {}
+
+impl SupportedNetlinkProtocol for NetlinkNetfilterProtocol {
+    type Message = NfnlMessage;
+
+    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>> {
+        &NETLINK_SOCKET_TABLE.get().unwrap().netfilter
+    }
+}
+
+pub enum NetlinkSockDiagProtocol {}
+
+impl SupportedNetlinkProtocol for NetlinkSockDiagProtocol {
+    type Message = DiagMessage;
+
+    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>> {
+        &NETLINK_SOCKET_TABLE.get().unwrap().sock_diag
+    }
+}
+
+pub enum NetlinkAuditProtocol {}
+
+impl SupportedNetlinkProtocol for NetlinkAuditProtocol {
+    type Message = AuditMessage;
+
+    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>> {
+        &NETLINK_SOCKET_TABLE.get().unwrap().audit
+    }
+}
+
 /// Bound socket table of a single netlink protocol.
 ///
 /// Each table can have bound sockets for unicast
@@ -161,6 +210,16 @@ impl<Message: 'static> ProtocolSocketTable<Message> {
         Ok(())
     }
 
+    fn try_unicast(&self, dst_port: PortNum, message: Message) -> Result<()>
+    where
+        Message: QueueableMessage,
+    {
+        let Some(receiver) = self.unicast_sockets.get(&dst_port) else {
+            return_errno_with_message!(Errno::ECONNREFUSED, "the netlink port is not bound");
+        };
+        receiver.try_enqueue_message(message)
+    }
+
     fn multicast(&self, dst_groups: GroupIdSet, message: Message) -> Result<()>
     where
         Message: MulticastMessage,
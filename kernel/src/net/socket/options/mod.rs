@@ -2,7 +2,7 @@
 
 use macros::impl_socket_options;
 
-use super::util::LingerOption;
+use super::util::{LingerOption, SocketFilter};
 use crate::{net::socket::unix::CUserCred, prelude::*, process::Gid};
 
 pub(in crate::net) mod macros;
@@ -30,4 +30,8 @@ impl_socket_options!(
     pub struct SendBufForce(u32);
     pub struct RecvBufForce(u32);
     pub struct PeerGroups(Arc<[Gid]>);
+    pub struct AttachFilter(Arc<SocketFilter>);
+    pub struct DetachFilter(());
+    pub struct AttachReuseportCbpf(Arc<SocketFilter>);
+    pub struct DetachReuseportBpf(());
 );
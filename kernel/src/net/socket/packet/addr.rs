@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::EthernetAddress;
+
+use crate::{net::socket::util::SocketAddr, prelude::*};
+
+/// A link-layer socket address.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PacketSocketAddr {
+    /// The physical-layer protocol, in host byte order.
+    pub protocol: u16,
+    /// The interface index, where zero means any interface.
+    pub ifindex: u32,
+    /// The ARP hardware type of the interface.
+    pub hatype: u16,
+    /// The packet type (e.g., [`PACKET_HOST`]).
+    pub pkttype: u8,
+    /// The length of the physical-layer address.
+    pub halen: u8,
+    /// The physical-layer address.
+    pub addr: [u8; 8],
+}
+
+impl PacketSocketAddr {
+    /// Creates a socket address with the Ethernet address, if any.
+    pub(super) fn new(
+        protocol: u16,
+        ifindex: u32,
+        hatype: u16,
+        pkttype: u8,
+        ether_addr: Option<EthernetAddress>,
+    ) -> Self {
+        let mut addr = [0; 8];
+        let halen = if let Some(ether_addr) = ether_addr {
+            addr[..6].copy_from_slice(ether_addr.as_bytes());
+            6
+        } else {
+            0
+        };
+
+        Self {
+            protocol,
+            ifindex,
+            hatype,
+            pkttype,
+            halen,
+            addr,
+        }
+    }
+
+    /// Returns the Ethernet address, if the physical-layer address is one.
+    pub(super) fn ether_addr(&self) -> Option<EthernetAddress> {
+        (self.halen >= 6).then(|| EthernetAddress::from_bytes(&self.addr[..6]))
+    }
+}
+
+impl TryFrom<SocketAddr> for PacketSocketAddr {
+    type Error = Error;
+
+    fn try_from(value: SocketAddr) -> Result<Self> {
+        let SocketAddr::Packet(addr) = value else {
+            return_errno_with_message!(Errno::EINVAL, "the socket address is not a packet address");
+        };
+
+        Ok(addr)
+    }
+}
+
+impl From<PacketSocketAddr> for SocketAddr {
+    fn from(value: PacketSocketAddr) -> Self {
+        SocketAddr::Packet(value)
+    }
+}
+
+/// The packet is sent to us.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_packet.h>.
+pub(super) const PACKET_HOST: u8 = 0;
@@ -0,0 +1,521 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Packet sockets (`AF_PACKET`).
+//!
+//! Packet sockets capture and inject link-layer frames on network interfaces. `SOCK_RAW` sockets
+//! see frames including their link-layer headers, while `SOCK_DGRAM` sockets see only the
+//! payloads.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use addr::PACKET_HOST;
+pub use addr::PacketSocketAddr;
+use aster_bigtcp::errors::packet::SendError;
+pub use options::{
+    AddMembership, DropMembership, IgnoreOutgoing, MembershipType, PacketMembership,
+    PacketStatistics, RxRing, Statistics, Version,
+};
+pub use receiver::PacketObserver;
+use receiver::PacketReceiver;
+pub use ring::{TpacketReq3, TpacketVersion};
+
+use crate::{
+    events::IoEvents,
+    fs::{file::Mappable, pseudofs::SockFs, vfs::path::Path},
+    net::{
+        iface::{Iface, PacketSocket as IfacePacketSocket},
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            options::{
+                AttachFilter, DetachFilter, Error as SocketError, SocketOption,
+                macros::{sock_option_mut, sock_option_ref},
+            },
+            private::SocketPrivate,
+            util::{
+                MessageHeader, SendRecvFlags, SocketAddr,
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
+        },
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    util::{MultiRead, MultiWrite},
+};
+
+mod addr;
+mod options;
+mod receiver;
+mod ring;
+
+/// A packet socket.
+pub struct PacketSocket {
+    // Lock order: `inner` first, `options` second
+    inner: RwMutex<Inner>,
+    options: RwLock<OptionSet>,
+    receiver: Arc<PacketReceiver>,
+
+    kind: PacketSocketKind,
+    net_ns: Arc<NetNamespace>,
+    is_nonblocking: AtomicBool,
+    pollee: Pollee,
+    pseudo_path: Path,
+}
+
+/// The kind of a packet socket.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PacketSocketKind {
+    /// `SOCK_RAW` sockets, which see frames including their link-layer headers.
+    Raw,
+    /// `SOCK_DGRAM` sockets, which see frames without their link-layer headers.
+    Dgram,
+}
+
+struct Inner {
+    /// The protocol of the frames that the socket sees, in host byte order.
+    protocol: u16,
+    /// The index of the iface that the socket is bound to, where zero means all ifaces.
+    ifindex: u32,
+    /// The sockets on the ifaces that capture frames.
+    capture_sockets: Vec<IfacePacketSocket>,
+    /// The sockets on the ifaces that only send frames.
+    send_sockets: Vec<IfacePacketSocket>,
+    memberships: Vec<Membership>,
+}
+
+#[derive(Clone, Debug)]
+struct OptionSet {
+    socket: SocketOptionSet,
+}
+
+impl OptionSet {
+    fn new() -> Self {
+        Self {
+            socket: SocketOptionSet::new_packet(),
+        }
+    }
+}
+
+/// A membership, which is undone when it is dropped.
+struct Membership {
+    iface: Arc<Iface>,
+    membership: PacketMembership,
+}
+
+impl Membership {
+    fn new(iface: Arc<Iface>, membership: PacketMembership) -> Self {
+        match membership.type_ {
+            MembershipType::Promisc => iface.set_promiscuity(1),
+            MembershipType::AllMulti => iface.set_allmulti(1),
+            // Ifaces do not filter frames by their destination addresses, so there is nothing
+            // to do for multicast and unicast addresses.
+            MembershipType::Multicast | MembershipType::Unicast => (),
+        }
+
+        Self { iface, membership }
+    }
+}
+
+impl Drop for Membership {
+    fn drop(&mut self) {
+        match self.membership.type_ {
+            MembershipType::Promisc => self.iface.set_promiscuity(-1),
+            MembershipType::AllMulti => self.iface.set_allmulti(-1),
+            MembershipType::Multicast | MembershipType::Unicast => (),
+        }
+    }
+}
+
+/// The default buffer size of packet sockets, which is the default `rmem_default` and
+/// `wmem_default` in Linux.
+pub(in crate::net) const PACKET_DEFAULT_BUF_SIZE: usize = 212992;
+
+impl PacketSocket {
+    /// Creates a packet socket.
+    ///
+    /// If `protocol` is not zero, the socket sees frames on all ifaces immediately.
+    ///
+    /// The caller must have checked that the current thread has `CAP_NET_RAW`.
+    pub fn new(
+        is_nonblocking: bool,
+        kind: PacketSocketKind,
+        protocol: u16,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        let options = OptionSet::new();
+        let pollee = Pollee::new();
+        let receiver = Arc::new(PacketReceiver::new(
+            kind == PacketSocketKind::Raw,
+            options.socket.recv_buf() as usize,
+            pollee.clone(),
+        ));
+
+        let mut inner = Inner {
+            protocol,
+            ifindex: 0,
+            capture_sockets: Vec::new(),
+            send_sockets: Vec::new(),
+            memberships: Vec::new(),
+        };
+        inner.capture_sockets = Self::new_capture_sockets(&net_ns, &receiver, protocol, 0)
+            .expect("capturing frames on all ifaces should never fail");
+
+        Arc::new(Self {
+            inner: RwMutex::new(inner),
+            options: RwLock::new(options),
+            receiver,
+            kind,
+            net_ns,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            pollee,
+            pseudo_path: SockFs::new_path(),
+        })
+    }
+
+    fn new_capture_sockets(
+        net_ns: &NetNamespace,
+        receiver: &Arc<PacketReceiver>,
+        protocol: u16,
+        ifindex: u32,
+    ) -> Result<Vec<IfacePacketSocket>> {
+        let ifaces = if ifindex == 0 {
+            net_ns.ifaces()
+        } else {
+            let iface = net_ns
+                .iface_by_index(ifindex)
+                .ok_or_else(|| Error::with_message(Errno::ENODEV, "the iface does not exist"))?;
+            vec![iface]
+        };
+
+        // Sockets with a zero protocol see no frames.
+        //
+        // FIXME: Sockets bound to all ifaces should also see frames on ifaces that are added
+        // later.
+        if protocol == 0 {
+            return Ok(Vec::new());
+        }
+
+        let sockets = ifaces
+            .into_iter()
+            .map(|iface| {
+                IfacePacketSocket::new(iface, protocol, PacketObserver::new(receiver.clone()))
+            })
+            .collect();
+        Ok(sockets)
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, PacketSocketAddr)> {
+        let res = self.receiver.try_recv(writer, flags);
+        self.pollee.invalidate();
+        res
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        addr: Option<&PacketSocketAddr>,
+    ) -> Result<usize> {
+        let mut inner = self.inner.write();
+
+        let (ifindex, protocol) = match addr {
+            Some(addr) => (addr.ifindex, addr.protocol),
+            None => (inner.ifindex, inner.protocol),
+        };
+        if ifindex == 0 {
+            return_errno_with_message!(Errno::ENXIO, "the iface is not specified");
+        }
+
+        let socket = inner.socket_on(&self.net_ns, &self.receiver, ifindex)?;
+
+        let mut data = vec![0; reader.sum_lens()];
+        let len = reader.read(&mut VmWriter::from(data.as_mut_slice()))?;
+        data.truncate(len);
+
+        let res = match self.kind {
+            PacketSocketKind::Raw => socket.send(data),
+            PacketSocketKind::Dgram => {
+                let dst_addr = addr.and_then(PacketSocketAddr::ether_addr);
+                socket.send_payload(dst_addr, protocol, &data)
+            }
+        };
+        match res {
+            Ok(()) => (),
+            Err(SendError::Unaddressable) => {
+                return_errno_with_message!(Errno::EINVAL, "the destination address is invalid");
+            }
+            Err(SendError::Malformed) => {
+                return_errno_with_message!(Errno::EINVAL, "the link-layer header is invalid");
+            }
+            Err(SendError::TooLarge) => {
+                return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+            }
+            Err(SendError::BufferFull) => {
+                return_errno_with_message!(Errno::EAGAIN, "the send buffer is full");
+            }
+        }
+
+        let iface_to_poll = socket.iface().clone();
+        drop(inner);
+        iface_to_poll.poll();
+
+        Ok(len)
+    }
+
+    fn add_membership(&self, membership: &PacketMembership) -> Result<()> {
+        let iface = self
+            .net_ns
+            .iface_by_index(membership.ifindex)
+            .ok_or_else(|| Error::with_message(Errno::ENODEV, "the iface does not exist"))?;
+
+        let mut inner = self.inner.write();
+        inner.memberships.push(Membership::new(iface, *membership));
+
+        Ok(())
+    }
+
+    fn drop_membership(&self, membership: &PacketMembership) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        let Some(pos) = inner
+            .memberships
+            .iter()
+            .position(|added| added.membership == *membership)
+        else {
+            return_errno_with_message!(Errno::EADDRNOTAVAIL, "the membership does not exist");
+        };
+        inner.memberships.remove(pos);
+
+        Ok(())
+    }
+}
+
+impl Inner {
+    /// Returns a socket that can send frames on the iface.
+    fn socket_on(
+        &mut self,
+        net_ns: &NetNamespace,
+        receiver: &Arc<PacketReceiver>,
+        ifindex: u32,
+    ) -> Result<&IfacePacketSocket> {
+        let pos = self
+            .capture_sockets
+            .iter()
+            .position(|socket| socket.iface().index() == ifindex);
+        if let Some(pos) = pos {
+            return Ok(&self.capture_sockets[pos]);
+        }
+
+        let pos = self
+            .send_sockets
+            .iter()
+            .position(|socket| socket.iface().index() == ifindex);
+        if let Some(pos) = pos {
+            return Ok(&self.send_sockets[pos]);
+        }
+
+        let iface = net_ns
+            .iface_by_index(ifindex)
+            .ok_or_else(|| Error::with_message(Errno::ENXIO, "the iface does not exist"))?;
+        let socket = IfacePacketSocket::new(iface, 0, PacketObserver::new(receiver.clone()));
+        self.send_sockets.push(socket);
+
+        Ok(self.send_sockets.last().unwrap())
+    }
+}
+
+impl Pollable for PacketSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.receiver.check_io_events())
+    }
+}
+
+impl SocketPrivate for PacketSocket {
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, is_nonblocking: bool) {
+        self.is_nonblocking.store(is_nonblocking, Ordering::Relaxed);
+    }
+}
+
+impl Socket for PacketSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr = PacketSocketAddr::try_from(socket_addr)?;
+
+        let mut inner = self.inner.write();
+
+        // Like Linux, a zero protocol means that the protocol is unchanged.
+        let protocol = if addr.protocol != 0 {
+            addr.protocol
+        } else {
+            inner.protocol
+        };
+        let capture_sockets =
+            Self::new_capture_sockets(&self.net_ns, &self.receiver, protocol, addr.ifindex)?;
+
+        inner.protocol = protocol;
+        inner.ifindex = addr.ifindex;
+        inner.capture_sockets = capture_sockets;
+
+        Ok(())
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let inner = self.inner.read();
+
+        let iface = self.net_ns.iface_by_index(inner.ifindex);
+        let addr = PacketSocketAddr::new(
+            inner.protocol,
+            inner.ifindex,
+            iface.as_ref().map_or(0, |iface| iface.type_() as u16),
+            PACKET_HOST,
+            iface.and_then(|iface| iface.hardware_addr()),
+        );
+
+        Ok(addr.into())
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        let addr = addr.map(PacketSocketAddr::try_from).transpose()?;
+
+        if !control_messages.is_empty() {
+            // TODO: Support sending control message
+            warn!("sending control message is not supported");
+        }
+
+        // TODO: Block if the send buffer is full
+        self.try_send(reader, addr.as_ref())
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with other flags. Only MSG_PEEK and MSG_TRUNC are handled here.
+        if !flags
+            .difference(SendRecvFlags::MSG_PEEK | SendRecvFlags::MSG_TRUNC)
+            .is_all_supported()
+        {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let (received_bytes, peer_addr) =
+            self.block_on(IoEvents::IN, || self.try_recv(writer, flags))?;
+
+        // TODO: Receive control message (e.g., `PACKET_AUXDATA`)
+
+        let message_header = MessageHeader::new(Some(peer_addr.into()), Vec::new());
+
+        Ok((received_bytes, message_header))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        sock_option_mut!(match option {
+            socket_errors @ SocketError => {
+                // TODO: Support socket errors for packet sockets
+                socket_errors.set(None);
+                return Ok(());
+            }
+            version @ Version => {
+                version.set(self.receiver.version());
+                return Ok(());
+            }
+            statistics @ Statistics => {
+                statistics.set(self.receiver.take_statistics());
+                return Ok(());
+            }
+            ignore_outgoing @ IgnoreOutgoing => {
+                ignore_outgoing.set(self.receiver.ignore_outgoing());
+                return Ok(());
+            }
+            _ => (),
+        });
+
+        let inner = self.inner.read();
+        let options = self.options.read();
+
+        options.socket.get_option(option, &*inner)
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        sock_option_ref!(match option {
+            attach_filter @ AttachFilter => {
+                self.receiver
+                    .set_filter(attach_filter.get().unwrap().clone());
+                return Ok(());
+            }
+            _detach_filter @ DetachFilter => {
+                return self.receiver.detach_filter();
+            }
+            add_membership @ AddMembership => {
+                return self.add_membership(add_membership.get().unwrap());
+            }
+            drop_membership @ DropMembership => {
+                return self.drop_membership(drop_membership.get().unwrap());
+            }
+            version @ Version => {
+                return self.receiver.set_version(*version.get().unwrap());
+            }
+            rx_ring @ RxRing => {
+                return self.receiver.set_ring(rx_ring.get().unwrap());
+            }
+            ignore_outgoing @ IgnoreOutgoing => {
+                self.receiver
+                    .set_ignore_outgoing(*ignore_outgoing.get().unwrap());
+                return Ok(());
+            }
+            _ => (),
+        });
+
+        let inner = self.inner.read();
+        let mut options = self.options.write();
+
+        options.socket.set_option(option, &*inner)?;
+        self.receiver
+            .set_recv_buf_len(options.socket.recv_buf() as usize);
+
+        Ok(())
+    }
+
+    fn mappable(&self) -> Result<Mappable> {
+        let Some(vmo) = self.receiver.ring_vmo() else {
+            return_errno_with_message!(Errno::EINVAL, "the receive ring is not set up");
+        };
+
+        Ok(Mappable::Vmo(vmo))
+    }
+
+    fn pseudo_path(&self) -> &Path {
+        &self.pseudo_path
+    }
+}
+
+impl GetSocketLevelOption for Inner {
+    fn is_listening(&self) -> bool {
+        false
+    }
+}
+
+impl SetSocketLevelOption for Inner {}
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::ring::{TpacketReq3, TpacketVersion};
+use crate::{net::socket::options::macros::impl_socket_options, prelude::*};
+
+impl_socket_options!(
+    pub struct AddMembership(PacketMembership);
+    pub struct DropMembership(PacketMembership);
+    pub struct Version(TpacketVersion);
+    pub struct RxRing(TpacketReq3);
+    pub struct Statistics(PacketStatistics);
+    pub struct IgnoreOutgoing(bool);
+);
+
+/// A membership of a packet socket (`struct packet_mreq`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PacketMembership {
+    pub ifindex: u32,
+    pub type_: MembershipType,
+    pub halen: u8,
+    pub addr: [u8; 8],
+}
+
+/// The type of a membership.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_packet.h>.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum MembershipType {
+    /// Receives the frames sent to a multicast address.
+    Multicast = 0,
+    /// Receives all frames (i.e., promiscuous mode).
+    Promisc = 1,
+    /// Receives all multicast frames.
+    AllMulti = 2,
+    /// Receives the frames sent to a secondary unicast address.
+    Unicast = 3,
+}
+
+/// The statistics of a packet socket (`struct tpacket_stats` or `struct tpacket_stats_v3`).
+#[derive(Clone, Copy, Debug)]
+pub struct PacketStatistics {
+    pub packets: u32,
+    pub drops: u32,
+    /// The number of times that the ring becomes full, which is only reported for
+    /// `TPACKET_V3` sockets.
+    pub freeze_q_cnt: Option<u32>,
+}
@@ -0,0 +1,343 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_bigtcp::socket::{FrameObserver, FrameType, LinkFrame};
+use aster_softirq::BottomHalfDisabled;
+
+use super::{
+    addr::PacketSocketAddr,
+    options::PacketStatistics,
+    ring::{MmapRing, PushResult, RingPacket, TpacketReq3, TpacketVersion},
+};
+use crate::{
+    events::IoEvents,
+    net::socket::util::{FilterInput, SendRecvFlags, SocketFilter},
+    prelude::*,
+    process::signal::Pollee,
+    time::clocks::RealTimeClock,
+    util::MultiWrite,
+    vm::page_cache::Vmo,
+};
+
+/// An observer that delivers link-layer frames to a packet socket.
+pub struct PacketObserver(Arc<PacketReceiver>);
+
+impl PacketObserver {
+    pub(super) fn new(receiver: Arc<PacketReceiver>) -> Self {
+        Self(receiver)
+    }
+}
+
+impl FrameObserver for PacketObserver {
+    fn on_frame(&self, frame: &LinkFrame) {
+        self.0.on_frame(frame);
+    }
+
+    fn on_batch_end(&self) {
+        self.0.on_batch_end();
+    }
+}
+
+/// The receiving side of a packet socket.
+///
+/// Received frames are either queued or, if a ring is set up, written into the ring.
+pub(super) struct PacketReceiver {
+    has_link_header: bool,
+    ignore_outgoing: AtomicBool,
+    state: SpinLock<ReceiverState, BottomHalfDisabled>,
+    pollee: Pollee,
+}
+
+struct ReceiverState {
+    frames: VecDeque<ReceivedFrame>,
+    frames_len: usize,
+    recv_buf_len: usize,
+    filter: Option<Arc<SocketFilter>>,
+    version: TpacketVersion,
+    ring: Option<MmapRing>,
+    packets: u32,
+    drops: u32,
+    freeze_q_cnt: u32,
+}
+
+#[derive(Clone)]
+struct ReceivedFrame {
+    data: Vec<u8>,
+    len: usize,
+    addr: PacketSocketAddr,
+}
+
+impl PacketReceiver {
+    /// Creates a receiver.
+    ///
+    /// If `has_link_header` is true (i.e., for `SOCK_RAW` sockets), received frames include
+    /// their link-layer headers.
+    pub(super) fn new(has_link_header: bool, recv_buf_len: usize, pollee: Pollee) -> Self {
+        Self {
+            has_link_header,
+            ignore_outgoing: AtomicBool::new(false),
+            state: SpinLock::new(ReceiverState {
+                frames: VecDeque::new(),
+                frames_len: 0,
+                recv_buf_len,
+                filter: None,
+                version: TpacketVersion::V1,
+                ring: None,
+                packets: 0,
+                drops: 0,
+                freeze_q_cnt: 0,
+            }),
+            pollee,
+        }
+    }
+
+    fn on_frame(&self, frame: &LinkFrame) {
+        if frame.frame_type() == FrameType::Outgoing && self.ignore_outgoing.load(Ordering::Relaxed)
+        {
+            return;
+        }
+
+        let data_offset = if self.has_link_header {
+            0
+        } else {
+            frame.header_len()
+        };
+        let data = &frame.data()[data_offset..];
+
+        let mut state = self.state.lock();
+
+        let snap_len = if let Some(filter) = state.filter.as_ref() {
+            let input = FilterInput {
+                packet: frame.data(),
+                data_offset,
+                net_offset: frame.header_len(),
+                protocol: frame.protocol(),
+                pkt_type: frame.frame_type() as u8,
+                ifindex: frame.iface_index(),
+                hatype: frame.iface_type() as u16,
+            };
+            (filter.run(&input) as usize).min(data.len())
+        } else {
+            data.len()
+        };
+        if snap_len == 0 {
+            return;
+        }
+
+        let addr = PacketSocketAddr::new(
+            frame.protocol(),
+            frame.iface_index(),
+            frame.iface_type() as u16,
+            frame.frame_type() as u8,
+            frame.src_addr(),
+        );
+        state.packets = state.packets.wrapping_add(1);
+
+        let has_drops = state.drops > 0;
+        if let Some(ring) = state.ring.as_mut() {
+            let header_len = (frame.header_len() - data_offset).min(snap_len);
+            let (header, payload) = data[..snap_len].split_at(header_len);
+            let packet = RingPacket {
+                header,
+                payload,
+                len: data.len(),
+                addr,
+                timestamp: RealTimeClock::get().read_time(),
+                has_drops,
+            };
+            match ring.push(&packet) {
+                PushResult::Written => (),
+                PushResult::Dropped => state.drops = state.drops.wrapping_add(1),
+                PushResult::Frozen => {
+                    state.drops = state.drops.wrapping_add(1);
+                    state.freeze_q_cnt = state.freeze_q_cnt.wrapping_add(1);
+                }
+            }
+            // The user space will be notified once the block is passed to it.
+            return;
+        }
+
+        if state.frames_len + snap_len > state.recv_buf_len {
+            state.drops = state.drops.wrapping_add(1);
+            return;
+        }
+        state.frames_len += snap_len;
+        state.frames.push_back(ReceivedFrame {
+            data: data[..snap_len].to_vec(),
+            len: data.len(),
+            addr,
+        });
+        drop(state);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn on_batch_end(&self) {
+        let mut state = self.state.lock();
+        let Some(ring) = state.ring.as_mut() else {
+            return;
+        };
+        let has_new_block = ring.flush();
+        drop(state);
+
+        if has_new_block {
+            self.pollee.notify(IoEvents::IN);
+        }
+    }
+
+    /// Receives a frame.
+    ///
+    /// If `MSG_TRUNC` is specified, this method returns the original length of the frame even if
+    /// it is truncated.
+    pub(super) fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, PacketSocketAddr)> {
+        // Dequeue the frame before copying it, since copying it to the user space may sleep.
+        let frame = {
+            let mut state = self.state.lock();
+
+            let Some(frame) = state.frames.front() else {
+                return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty");
+            };
+
+            if flags.contains(SendRecvFlags::MSG_PEEK) {
+                frame.clone()
+            } else {
+                let frame = state.frames.pop_front().unwrap();
+                state.frames_len -= frame.data.len();
+                frame
+            }
+        };
+
+        let copied_len = writer.write(&mut VmReader::from(frame.data.as_slice()))?;
+        let len = if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            frame.len
+        } else {
+            copied_len
+        };
+
+        Ok((len, frame.addr))
+    }
+
+    pub(super) fn check_io_events(&self) -> IoEvents {
+        let state = self.state.lock();
+
+        let can_recv = match state.ring.as_ref() {
+            Some(ring) => ring.has_user_blocks(),
+            None => !state.frames.is_empty(),
+        };
+
+        if can_recv {
+            IoEvents::IN | IoEvents::OUT
+        } else {
+            IoEvents::OUT
+        }
+    }
+
+    pub(super) fn set_recv_buf_len(&self, recv_buf_len: usize) {
+        self.state.lock().recv_buf_len = recv_buf_len;
+    }
+
+    pub(super) fn ignore_outgoing(&self) -> bool {
+        self.ignore_outgoing.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_ignore_outgoing(&self, ignore_outgoing: bool) {
+        self.ignore_outgoing
+            .store(ignore_outgoing, Ordering::Relaxed);
+    }
+
+    pub(super) fn set_filter(&self, filter: Arc<SocketFilter>) {
+        self.state.lock().filter = Some(filter);
+    }
+
+    pub(super) fn detach_filter(&self) -> Result<()> {
+        if self.state.lock().filter.take().is_none() {
+            return_errno_with_message!(Errno::ENOENT, "no filter is attached");
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn version(&self) -> TpacketVersion {
+        self.state.lock().version
+    }
+
+    pub(super) fn set_version(&self, version: TpacketVersion) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.ring.is_some() {
+            return_errno_with_message!(
+                Errno::EBUSY,
+                "the version cannot be changed after the ring is set up"
+            );
+        }
+
+        state.version = version;
+        Ok(())
+    }
+
+    /// Sets up or tears down (if the number of blocks is zero) the receive ring.
+    pub(super) fn set_ring(&self, req: &TpacketReq3) -> Result<()> {
+        if req.block_nr == 0 {
+            if req.frame_nr != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the number of frames is invalid");
+            }
+
+            let mut state = self.state.lock();
+            if state.ring.as_ref().is_some_and(MmapRing::is_mapped) {
+                return_errno_with_message!(Errno::EBUSY, "the ring is mapped");
+            }
+            state.ring = None;
+            return Ok(());
+        }
+
+        if self.version() != TpacketVersion::V3 {
+            // TODO: Support the `TPACKET_V1` and `TPACKET_V2` formats.
+            return_errno_with_message!(Errno::EINVAL, "only TPACKET_V3 rings are supported");
+        }
+
+        // Allocate the ring before locking the state, since allocation may sleep.
+        let ring = MmapRing::new(req)?;
+
+        let mut state = self.state.lock();
+        if state.ring.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "the ring is already set up");
+        }
+        if state.version != TpacketVersion::V3 {
+            return_errno_with_message!(Errno::EINVAL, "only TPACKET_V3 rings are supported");
+        }
+        state.ring = Some(ring);
+
+        Ok(())
+    }
+
+    /// Returns the VMO of the receive ring, if any.
+    pub(super) fn ring_vmo(&self) -> Option<Arc<Vmo>> {
+        self.state
+            .lock()
+            .ring
+            .as_ref()
+            .map(|ring| ring.vmo().clone())
+    }
+
+    /// Returns and resets the statistics.
+    pub(super) fn take_statistics(&self) -> PacketStatistics {
+        let mut state = self.state.lock();
+
+        let freeze_q_cnt = (state.version == TpacketVersion::V3).then_some(state.freeze_q_cnt);
+        let statistics = PacketStatistics {
+            packets: state.packets,
+            drops: state.drops,
+            freeze_q_cnt,
+        };
+
+        state.packets = 0;
+        state.drops = 0;
+        state.freeze_q_cnt = 0;
+
+        statistics
+    }
+}
@@ -0,0 +1,398 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The memory-mapped receive ring of packet sockets (`PACKET_RX_RING`).
+//!
+//! Only the `TPACKET_V3` format is supported. The ring consists of blocks, each of which contains
+//! a block descriptor followed by a variable number of packets. The kernel fills a block with
+//! packets and then passes the whole block to the user space by setting its status to
+//! `TP_STATUS_USER`. The user space passes the block back by setting its status to
+//! `TP_STATUS_KERNEL`.
+//!
+//! Reference: <https://www.kernel.org/doc/html/v6.0/networking/packet_mmap.html>.
+
+use core::{
+    sync::atomic::{Ordering, fence},
+    time::Duration,
+};
+
+use align_ext::AlignExt;
+use ostd::mm::io::util::HasVmReaderWriter;
+
+use super::addr::PacketSocketAddr;
+use crate::{
+    prelude::*,
+    util::net::CSocketAddrPacket,
+    vm::page_cache::{CachePage, Vmo, VmoOptions},
+};
+
+/// The version of the ring format (`PACKET_VERSION`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_packet.h>.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum TpacketVersion {
+    V1 = 0,
+    V2 = 1,
+    V3 = 2,
+}
+
+/// A request to set up a ring (`struct tpacket_req3`).
+///
+/// The older `struct tpacket_req` is a prefix of this structure.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct TpacketReq3 {
+    pub block_size: u32,
+    pub block_nr: u32,
+    pub frame_size: u32,
+    pub frame_nr: u32,
+    pub retire_blk_tov: u32,
+    pub sizeof_priv: u32,
+    pub feature_req_word: u32,
+}
+
+/// A block descriptor (`struct tpacket_block_desc` with `struct tpacket_hdr_v1`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CTpacketBlockDesc {
+    version: u32,
+    offset_to_priv: u32,
+    block_status: u32,
+    num_pkts: u32,
+    offset_to_first_pkt: u32,
+    blk_len: u32,
+    seq_num: u64,
+    ts_first_sec: u32,
+    ts_first_nsec: u32,
+    ts_last_sec: u32,
+    ts_last_nsec: u32,
+}
+
+/// A packet header (`struct tpacket3_hdr`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CTpacket3Hdr {
+    tp_next_offset: u32,
+    tp_sec: u32,
+    tp_nsec: u32,
+    tp_snaplen: u32,
+    tp_len: u32,
+    tp_status: u32,
+    tp_mac: u16,
+    tp_net: u16,
+    hv1_rxhash: u32,
+    hv1_vlan_tci: u32,
+    hv1_vlan_tpid: u16,
+    hv1_padding: u16,
+    tp_padding: [u8; 8],
+}
+
+const TPACKET_ALIGNMENT: usize = 16;
+
+/// The length of the packet header, followed by the link-layer socket address.
+const TPACKET3_HDRLEN: usize =
+    size_of::<CTpacket3Hdr>().next_multiple_of(TPACKET_ALIGNMENT) + size_of::<CSocketAddrPacket>();
+
+const BLK_HDR_LEN: usize = size_of::<CTpacketBlockDesc>().next_multiple_of(8);
+const BLOCK_STATUS_OFFSET: usize = 8;
+
+// Block and packet statuses.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_packet.h>.
+const TP_STATUS_KERNEL: u32 = 0;
+const TP_STATUS_USER: u32 = 1 << 0;
+const TP_STATUS_LOSING: u32 = 1 << 2;
+const TP_STATUS_BLK_TMO: u32 = 1 << 5;
+const TP_STATUS_TS_SOFTWARE: u32 = 1 << 29;
+
+/// A packet to be written into the ring.
+pub(super) struct RingPacket<'a> {
+    /// The link-layer header, which is empty for `SOCK_DGRAM` sockets.
+    pub(super) header: &'a [u8],
+    /// The data after the link-layer header.
+    pub(super) payload: &'a [u8],
+    /// The original length of the packet, before it is truncated.
+    pub(super) len: usize,
+    pub(super) addr: PacketSocketAddr,
+    pub(super) timestamp: Duration,
+    pub(super) has_drops: bool,
+}
+
+/// The result of writing a packet into the ring.
+pub(super) enum PushResult {
+    /// The packet is written.
+    Written,
+    /// The packet is dropped because the ring is full.
+    Dropped,
+    /// The packet is dropped because the ring is full, and the ring has just become full.
+    Frozen,
+}
+
+pub(super) struct MmapRing {
+    vmo: Arc<Vmo>,
+    // The pages are committed in advance so that they can be accessed while the iface is being
+    // polled, without locking the VMO.
+    pages: Vec<CachePage>,
+    block_size: usize,
+    block_nr: usize,
+    first_pkt_offset: usize,
+    current: Option<OpenBlock>,
+    current_idx: usize,
+    seq_num: u64,
+    is_frozen: bool,
+}
+
+struct OpenBlock {
+    num_pkts: u32,
+    next_offset: usize,
+    last_pkt_offset: usize,
+    ts_first: Duration,
+    ts_last: Duration,
+}
+
+impl MmapRing {
+    /// Sets up a ring according to the request.
+    pub(super) fn new(req: &TpacketReq3) -> Result<Self> {
+        let block_size = req.block_size as usize;
+        let block_nr = req.block_nr as usize;
+        let frame_size = req.frame_size as usize;
+
+        // Like Linux, the block size must be a positive `int`.
+        if block_size == 0 || block_size > i32::MAX as usize || block_size % PAGE_SIZE != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the block size is invalid");
+        }
+        let first_pkt_offset = BLK_HDR_LEN + (req.sizeof_priv as usize).align_up(8);
+        if block_size < first_pkt_offset + TPACKET3_HDRLEN {
+            return_errno_with_message!(Errno::EINVAL, "the block size is too small");
+        }
+        if frame_size < TPACKET3_HDRLEN || frame_size % TPACKET_ALIGNMENT != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the frame size is invalid");
+        }
+        let frames_per_block = block_size / frame_size;
+        if frames_per_block == 0
+            || frames_per_block.checked_mul(block_nr) != Some(req.frame_nr as usize)
+        {
+            return_errno_with_message!(Errno::EINVAL, "the number of frames is invalid");
+        }
+        let Some(total_size) = block_size.checked_mul(block_nr) else {
+            return_errno_with_message!(Errno::EINVAL, "the ring is too large");
+        };
+        // The ring is allocated eagerly, so it cannot be larger than the total memory.
+        if total_size > crate::vm::mem_total() {
+            return_errno_with_message!(Errno::ENOMEM, "the ring is larger than the total memory");
+        }
+
+        let vmo = VmoOptions::new(total_size).alloc()?;
+        let pages = (0..total_size / PAGE_SIZE)
+            .map(|idx| vmo.commit_on(idx))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            vmo,
+            pages,
+            block_size,
+            block_nr,
+            first_pkt_offset,
+            current: None,
+            current_idx: 0,
+            seq_num: 0,
+            is_frozen: false,
+        })
+    }
+
+    /// Returns the VMO that backs the ring.
+    pub(super) fn vmo(&self) -> &Arc<Vmo> {
+        &self.vmo
+    }
+
+    /// Returns whether the ring is mapped into some address spaces.
+    pub(super) fn is_mapped(&self) -> bool {
+        Arc::strong_count(&self.vmo) > 1
+    }
+
+    /// Returns the maximum number of bytes that a packet can occupy in a block.
+    fn max_frame_len(&self) -> usize {
+        self.block_size - self.first_pkt_offset
+    }
+
+    /// Writes a packet into the ring.
+    pub(super) fn push(&mut self, packet: &RingPacket) -> PushResult {
+        let mac_len = packet.header.len();
+        let (mac_offset, net_offset) = if mac_len == 0 {
+            let net_offset = TPACKET3_HDRLEN.align_up(TPACKET_ALIGNMENT) + 16;
+            (net_offset, net_offset)
+        } else {
+            let net_offset = (TPACKET3_HDRLEN + mac_len.max(16)).align_up(TPACKET_ALIGNMENT);
+            (net_offset - mac_len, net_offset)
+        };
+
+        let snap_len =
+            (mac_len + packet.payload.len()).min(self.max_frame_len().saturating_sub(mac_offset));
+        let total_len = (mac_offset + snap_len).align_up(8);
+        if total_len > self.max_frame_len() {
+            return PushResult::Dropped;
+        }
+
+        if let Some(block) = self.current.as_ref()
+            && block.next_offset + total_len > self.block_size
+        {
+            self.close_block(false);
+        }
+        if self.current.is_none() && !self.open_block(packet.timestamp) {
+            if self.is_frozen {
+                return PushResult::Dropped;
+            }
+            self.is_frozen = true;
+            return PushResult::Frozen;
+        }
+        self.is_frozen = false;
+
+        let block = self.current.as_mut().unwrap();
+        let block_offset = self.current_idx * self.block_size;
+        let pkt_offset = block_offset + block.next_offset;
+
+        let mut status = TP_STATUS_USER | TP_STATUS_TS_SOFTWARE;
+        if packet.has_drops {
+            status |= TP_STATUS_LOSING;
+        }
+        let hdr = CTpacket3Hdr {
+            tp_next_offset: total_len as u32,
+            tp_sec: packet.timestamp.as_secs() as u32,
+            tp_nsec: packet.timestamp.subsec_nanos(),
+            tp_snaplen: snap_len as u32,
+            tp_len: packet.len as u32,
+            tp_status: status,
+            tp_mac: mac_offset as u16,
+            tp_net: net_offset as u16,
+            ..CTpacket3Hdr::new_zeroed()
+        };
+        let addr = CSocketAddrPacket::from(packet.addr);
+
+        let memory = RingMemory(&self.pages);
+        memory.write_bytes(pkt_offset, hdr.as_bytes());
+        memory.write_bytes(
+            pkt_offset + size_of::<CTpacket3Hdr>().align_up(TPACKET_ALIGNMENT),
+            addr.as_bytes(),
+        );
+        let header_len = mac_len.min(snap_len);
+        memory.write_bytes(pkt_offset + mac_offset, &packet.header[..header_len]);
+        memory.write_bytes(
+            pkt_offset + mac_offset + header_len,
+            &packet.payload[..snap_len - header_len],
+        );
+
+        block.num_pkts += 1;
+        block.last_pkt_offset = block.next_offset;
+        block.next_offset += total_len;
+        block.ts_last = packet.timestamp;
+
+        PushResult::Written
+    }
+
+    /// Passes the current block to the user space if it contains any packets.
+    ///
+    /// Linux does this when the block retire timer expires. We do this once the iface finishes
+    /// polling a batch of packets so that no timers are needed, which may make the blocks less
+    /// full than they are in Linux.
+    ///
+    /// This method returns whether a block is passed to the user space.
+    pub(super) fn flush(&mut self) -> bool {
+        if self
+            .current
+            .as_ref()
+            .is_none_or(|block| block.num_pkts == 0)
+        {
+            return false;
+        }
+
+        self.close_block(true);
+        true
+    }
+
+    /// Returns whether some blocks are owned by the user space.
+    pub(super) fn has_user_blocks(&self) -> bool {
+        let prev_idx = (self.current_idx + self.block_nr - 1) % self.block_nr;
+        self.block_status(prev_idx) != TP_STATUS_KERNEL
+    }
+
+    fn block_status(&self, idx: usize) -> u32 {
+        let status = RingMemory(&self.pages).read_u32(idx * self.block_size + BLOCK_STATUS_OFFSET);
+        fence(Ordering::Acquire);
+        status
+    }
+
+    fn open_block(&mut self, timestamp: Duration) -> bool {
+        if self.block_status(self.current_idx) != TP_STATUS_KERNEL {
+            return false;
+        }
+
+        self.seq_num += 1;
+        self.current = Some(OpenBlock {
+            num_pkts: 0,
+            next_offset: self.first_pkt_offset,
+            last_pkt_offset: self.first_pkt_offset,
+            ts_first: timestamp,
+            ts_last: timestamp,
+        });
+
+        true
+    }
+
+    fn close_block(&mut self, is_timeout: bool) {
+        let block = self.current.take().unwrap();
+        let block_offset = self.current_idx * self.block_size;
+        let memory = RingMemory(&self.pages);
+
+        // The last packet has no next packet.
+        if block.num_pkts > 0 {
+            memory.write_bytes(block_offset + block.last_pkt_offset, &0u32.to_ne_bytes());
+        }
+
+        let desc = CTpacketBlockDesc {
+            version: 0,
+            offset_to_priv: BLK_HDR_LEN as u32,
+            block_status: TP_STATUS_KERNEL,
+            num_pkts: block.num_pkts,
+            offset_to_first_pkt: self.first_pkt_offset as u32,
+            blk_len: block.next_offset as u32,
+            seq_num: self.seq_num,
+            ts_first_sec: block.ts_first.as_secs() as u32,
+            ts_first_nsec: block.ts_first.subsec_nanos(),
+            ts_last_sec: block.ts_last.as_secs() as u32,
+            ts_last_nsec: block.ts_last.subsec_nanos(),
+        };
+        memory.write_bytes(block_offset, desc.as_bytes());
+
+        // Publish the block only after its content is written.
+        fence(Ordering::Release);
+        let mut status = TP_STATUS_USER;
+        if is_timeout {
+            status |= TP_STATUS_BLK_TMO;
+        }
+        memory.write_bytes(block_offset + BLOCK_STATUS_OFFSET, &status.to_ne_bytes());
+
+        self.current_idx = (self.current_idx + 1) % self.block_nr;
+    }
+}
+
+/// The memory of a ring, which is accessed via the committed pages.
+struct RingMemory<'a>(&'a [CachePage]);
+
+impl RingMemory<'_> {
+    fn write_bytes(&self, mut offset: usize, bytes: &[u8]) {
+        let mut reader = VmReader::from(bytes);
+        while reader.has_remain() {
+            let mut writer = self.0[offset / PAGE_SIZE].writer();
+            writer.skip(offset % PAGE_SIZE);
+            offset += writer.write(&mut reader);
+        }
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        debug_assert!(offset % size_of::<u32>() == 0);
+
+        let mut reader = self.0[offset / PAGE_SIZE].reader();
+        reader.skip(offset % PAGE_SIZE);
+        reader.read_val::<u32>().unwrap()
+    }
+}
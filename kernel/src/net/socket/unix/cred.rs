@@ -9,6 +9,7 @@ use crate::{
 };
 
 pub(super) struct SocketCred<R = ReadOp> {
+    /// The global PID of the process that owns the credentials.
     pid: Pid,
     cred: Credentials<R>,
 }
@@ -36,20 +37,25 @@ impl SocketCred<ReadDupOp> {
 
 impl<R: TRights> SocketCred<R> {
     /// Converts to a [`CUserCred`] with the PID and the _effective_ UID/GID.
+    ///
+    /// The PID is translated into the PID namespace of the current process, which is the one
+    /// that reads the credentials. It is zero if the owner is not visible in that namespace.
     #[require(R > Read)]
     pub(super) fn to_effective_c_cred(&self) -> CUserCred {
         CUserCred {
-            pid: self.pid,
+            pid: current!().nr_in_ns(self.pid),
             uid: self.cred.euid(),
             gid: self.cred.egid(),
         }
     }
 
     /// Converts to a [`CUserCred`] with the PID and the _real_ UID/GID.
+    ///
+    /// The PID is translated in the same way as [`Self::to_effective_c_cred`].
     #[require(R > Read)]
     pub(super) fn to_real_c_cred(&self) -> CUserCred {
         CUserCred {
-            pid: self.pid,
+            pid: current!().nr_in_ns(self.pid),
             uid: self.cred.ruid(),
             gid: self.cred.rgid(),
         }
@@ -12,15 +12,23 @@ use crate::{
         Socket,
         options::{Error as SocketError, PeerCred, SocketOption, macros::sock_option_mut},
         private::SocketPrivate,
-        unix::{CUserCred, UnixSocketAddr, cred::SocketCred, ctrl_msg::AuxiliaryData},
+        unix::{
+            CUserCred, UnixSocketAddr,
+            cred::SocketCred,
+            ctrl_msg::AuxiliaryData,
+            diag::{
+                InspectUnixSocket, UnixSocketInfo, UnixSocketState, register_socket,
+                unregister_socket,
+            },
+        },
         util::{
-            MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
+            MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr, SocketOwner,
             options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
         },
     },
     prelude::*,
     process::signal::{PollHandle, Pollable},
-    util::{MultiRead, MultiWrite},
+    util::{MultiRead, MultiWrite, net::SockType},
 };
 
 pub struct UnixDatagramSocket {
@@ -35,6 +43,7 @@ pub struct UnixDatagramSocket {
     is_nonblocking: AtomicBool,
     is_write_shutdown: AtomicBool,
     pseudo_path: Path,
+    owner: SocketOwner,
 }
 
 #[derive(Clone, Debug)]
@@ -52,7 +61,7 @@ impl OptionSet {
 
 impl UnixDatagramSocket {
     pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        Arc::new(Self::new_raw(is_nonblocking))
+        Self::new_registered(Self::new_raw(is_nonblocking))
     }
 
     pub fn new_pair(is_nonblocking: bool) -> (Arc<Self>, Arc<Self>) {
@@ -69,10 +78,15 @@ impl UnixDatagramSocket {
         *remote_queue_a = Some(socket_b.local_receiver.queue().clone());
         *remote_queue_b = Some(socket_a.local_receiver.queue().clone());
 
-        (Arc::new(socket_a), Arc::new(socket_b))
+        (
+            Self::new_registered(socket_a),
+            Self::new_registered(socket_b),
+        )
     }
 
     fn new_raw(is_nonblocking: bool) -> Self {
+        let pseudo_path = SockFs::new_path();
+        let owner = SocketOwner::new_current(&pseudo_path);
         Self {
             local_receiver: MessageReceiver::new(),
             remote_queue: RwLock::new(None),
@@ -80,10 +94,18 @@ impl UnixDatagramSocket {
             peer_cred: None,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             is_write_shutdown: AtomicBool::new(false),
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
+            owner,
         }
     }
 
+    fn new_registered(socket: Self) -> Arc<Self> {
+        let owner = socket.owner;
+        let socket = Arc::new(socket);
+        register_socket(&socket, &owner);
+        socket
+    }
+
     fn do_send(
         &self,
         reader: &mut dyn MultiRead,
@@ -142,6 +164,29 @@ impl UnixDatagramSocket {
     }
 }
 
+impl InspectUnixSocket for UnixDatagramSocket {
+    fn info(&self) -> UnixSocketInfo {
+        let state = if self.remote_queue.read().is_some() {
+            UnixSocketState::Connected
+        } else {
+            UnixSocketState::Unconnected
+        };
+
+        UnixSocketInfo {
+            type_: SockType::SOCK_DGRAM,
+            state,
+            owner: self.owner,
+            addr: self.local_receiver.addr(),
+        }
+    }
+}
+
+impl Drop for UnixDatagramSocket {
+    fn drop(&mut self) {
+        unregister_socket(&self.owner);
+    }
+}
+
 impl Pollable for UnixDatagramSocket {
     fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
         self.local_receiver
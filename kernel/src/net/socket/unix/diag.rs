@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ptr;
+
+use ostd::task::Task;
+
+use super::UnixSocketAddr;
+use crate::{
+    net::{net_ns::NetNamespace, socket::util::SocketOwner},
+    prelude::*,
+    util::net::SockType,
+};
+
+/// A snapshot of the state of a UNIX socket.
+///
+/// This is used to monitor sockets (e.g., via `sock_diag` or `/proc/net/unix`).
+pub struct UnixSocketInfo {
+    pub type_: SockType,
+    pub state: UnixSocketState,
+    pub owner: SocketOwner,
+    pub addr: UnixSocketAddr,
+}
+
+/// The state of a UNIX socket.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnixSocketState {
+    Unconnected,
+    Listening,
+    Connected,
+}
+
+/// A UNIX socket whose state can be inspected by socket monitoring interfaces.
+pub(super) trait InspectUnixSocket: Send + Sync {
+    fn info(&self) -> UnixSocketInfo;
+}
+
+/// A registered UNIX socket.
+struct RegisteredSocket {
+    socket: Weak<dyn InspectUnixSocket>,
+    /// The network namespace in which the socket is created.
+    net_ns: Weak<NetNamespace>,
+}
+
+/// All the live UNIX sockets, indexed by the inode numbers of their socket files.
+static UNIX_SOCKETS: Mutex<BTreeMap<u64, RegisteredSocket>> = Mutex::new(BTreeMap::new());
+
+/// Registers a new UNIX socket so that it can be found by [`unix_socket_infos`].
+///
+/// Like Linux, the socket belongs to the network namespace of the current thread.
+///
+/// The socket should be unregistered via [`unregister_socket`] when it is dropped.
+pub(super) fn register_socket<T: InspectUnixSocket + 'static>(
+    socket: &Arc<T>,
+    owner: &SocketOwner,
+) {
+    let net_ns = {
+        let current_task = Task::current().unwrap();
+        let thread_local = current_task.as_thread_local().unwrap();
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        Arc::downgrade(ns_proxy.unwrap().net_ns())
+    };
+
+    let registered = RegisteredSocket {
+        socket: Arc::downgrade(socket) as Weak<dyn InspectUnixSocket>,
+        net_ns,
+    };
+    UNIX_SOCKETS.lock().insert(owner.ino(), registered);
+}
+
+/// Unregisters a UNIX socket that is being dropped.
+pub(super) fn unregister_socket(owner: &SocketOwner) {
+    UNIX_SOCKETS.lock().remove(&owner.ino());
+}
+
+/// Returns the snapshots of all the live UNIX sockets in the network namespace.
+pub fn unix_socket_infos(net_ns: &NetNamespace) -> Vec<UnixSocketInfo> {
+    // Collect the sockets first because querying their states may require sleeping locks.
+    let sockets: Vec<_> = UNIX_SOCKETS
+        .lock()
+        .values()
+        .filter(|registered| ptr::eq(registered.net_ns.as_ptr(), net_ns))
+        .filter_map(|registered| registered.socket.upgrade())
+        .collect();
+
+    sockets.iter().map(|socket| socket.info()).collect()
+}
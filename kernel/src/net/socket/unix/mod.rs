@@ -4,6 +4,7 @@ mod addr;
 mod cred;
 mod ctrl_msg;
 mod datagram;
+mod diag;
 mod ns;
 mod stream;
 
@@ -12,5 +13,6 @@ pub use cred::CUserCred;
 pub(super) use ctrl_msg::UnixControlMessage;
 pub(super) use datagram::UNIX_DATAGRAM_DEFAULT_BUF_SIZE;
 pub use datagram::UnixDatagramSocket;
+pub use diag::{UnixSocketInfo, UnixSocketState, unix_socket_infos};
 pub(super) use stream::UNIX_STREAM_DEFAULT_BUF_SIZE;
 pub use stream::UnixStreamSocket;
@@ -20,7 +20,6 @@ pub fn lookup_socket_file(path: &str) -> Result<Path> {
     };
 
     if path
-        .inode()
         .check_permission(Permission::MAY_READ | Permission::MAY_WRITE)
         .is_err()
     {
@@ -19,9 +19,17 @@ use crate::{
             Error as SocketError, PeerCred, PeerGroups, SocketOption, macros::sock_option_mut,
         },
         private::SocketPrivate,
-        unix::{CUserCred, UnixSocketAddr, cred::SocketCred, ctrl_msg::AuxiliaryData},
+        unix::{
+            CUserCred, UnixSocketAddr,
+            cred::SocketCred,
+            ctrl_msg::AuxiliaryData,
+            diag::{
+                InspectUnixSocket, UnixSocketInfo, UnixSocketState, register_socket,
+                unregister_socket,
+            },
+        },
         util::{
-            ControlMessage, MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
+            ControlMessage, MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr, SocketOwner,
             options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
         },
     },
@@ -30,7 +38,7 @@ use crate::{
         Gid,
         signal::{PollHandle, Pollable, Pollee},
     },
-    util::{MultiRead, MultiWrite},
+    util::{MultiRead, MultiWrite, net::SockType},
 };
 
 pub struct UnixStreamSocket {
@@ -43,6 +51,7 @@ pub struct UnixStreamSocket {
 
     is_seqpacket: bool,
     pseudo_path: Path,
+    owner: SocketOwner,
 }
 
 enum State {
@@ -168,14 +177,19 @@ impl UnixStreamSocket {
     }
 
     fn new_init(init: Init, is_nonblocking: bool, is_seqpacket: bool) -> Arc<Self> {
-        Arc::new(Self {
+        let pseudo_path = SockFs::new_path();
+        let owner = SocketOwner::new_current(&pseudo_path);
+        let socket = Arc::new(Self {
             state: RwMutex::new(Takeable::new(State::Init(init))),
             options: RwLock::new(OptionSet::new()),
             pollee: Pollee::new(),
             is_nonblocking: AtomicBool::new(is_nonblocking),
             is_seqpacket,
-            pseudo_path: SockFs::new_path(),
-        })
+            pseudo_path,
+            owner,
+        });
+        register_socket(&socket, &owner);
+        socket
     }
 
     pub fn new_pair(is_nonblocking: bool, is_seqpacket: bool) -> (Arc<Self>, Arc<Self>) {
@@ -202,14 +216,19 @@ impl UnixStreamSocket {
         is_seqpacket: bool,
     ) -> Arc<Self> {
         let cloned_pollee = connected.cloned_pollee();
-        Arc::new(Self {
+        let pseudo_path = SockFs::new_path();
+        let owner = SocketOwner::new_current(&pseudo_path);
+        let socket = Arc::new(Self {
             state: RwMutex::new(Takeable::new(State::Connected(connected))),
             options: RwLock::new(options),
             pollee: cloned_pollee,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             is_seqpacket,
-            pseudo_path: SockFs::new_path(),
-        })
+            pseudo_path,
+            owner,
+        });
+        register_socket(&socket, &owner);
+        socket
     }
 
     fn try_send(
@@ -289,6 +308,33 @@ impl UnixStreamSocket {
     }
 }
 
+impl InspectUnixSocket for UnixStreamSocket {
+    fn info(&self) -> UnixSocketInfo {
+        let (state, addr) = match self.state.read().as_ref() {
+            State::Init(init) => (UnixSocketState::Unconnected, init.addr().cloned()),
+            State::Listen(listener) => (UnixSocketState::Listening, Some(listener.addr().clone())),
+            State::Connected(connected) => (UnixSocketState::Connected, connected.addr().cloned()),
+        };
+
+        UnixSocketInfo {
+            type_: if self.is_seqpacket {
+                SockType::SOCK_SEQPACKET
+            } else {
+                SockType::SOCK_STREAM
+            },
+            state,
+            owner: self.owner,
+            addr: addr.into(),
+        }
+    }
+}
+
+impl Drop for UnixStreamSocket {
+    fn drop(&mut self) {
+        unregister_socket(&self.owner);
+    }
+}
+
 pub(super) const SHUT_READ_EVENTS: IoEvents =
     IoEvents::RDHUP.union(IoEvents::IN).union(IoEvents::HUP);
 pub(super) const SHUT_WRITE_EVENTS: IoEvents = IoEvents::OUT.union(IoEvents::HUP);
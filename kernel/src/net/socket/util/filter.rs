@@ -0,0 +1,514 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Classic BPF socket filters.
+//!
+//! A socket filter is a classic BPF (cBPF) program that is attached to a socket with
+//! `SO_ATTACH_FILTER`. The program runs on each packet that the socket is about to receive and
+//! returns the number of bytes of the packet to keep, where zero means that the packet should be
+//! dropped.
+//!
+//! Reference: <https://www.kernel.org/doc/html/v6.0/networking/filter.html>.
+
+use aster_bigtcp::socket::ReuseportSelector;
+use ostd::cpu::CpuId;
+
+use crate::prelude::*;
+
+/// A classic BPF instruction (`struct sock_filter`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CSockFilter {
+    code: u16,
+    jt: u8,
+    jf: u8,
+    k: u32,
+}
+
+/// The maximum number of instructions in a program.
+pub const BPF_MAXINSNS: usize = 4096;
+
+/// The number of words in the scratch memory.
+const BPF_MEMWORDS: usize = 16;
+
+// Offsets of the ancillary data and of the data relative to the network or link-layer header.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/filter.h>.
+const SKF_AD_OFF: i32 = -0x1000;
+const SKF_NET_OFF: i32 = -0x100000;
+const SKF_LL_OFF: i32 = -0x200000;
+
+/// A packet that a socket filter runs on.
+pub struct FilterInput<'a> {
+    /// The whole packet, including the link-layer header.
+    pub packet: &'a [u8],
+    /// The offset of the data that the socket sees.
+    ///
+    /// Absolute and indirect loads are relative to this offset.
+    pub data_offset: usize,
+    /// The offset of the network header.
+    pub net_offset: usize,
+    /// The protocol of the network-layer packet, in host byte order.
+    pub protocol: u16,
+    /// The packet type (e.g., `PACKET_HOST`).
+    pub pkt_type: u8,
+    /// The index of the interface that the packet goes through.
+    pub ifindex: u32,
+    /// The hardware type of the interface that the packet goes through.
+    pub hatype: u16,
+}
+
+/// A validated classic BPF program.
+#[derive(Debug)]
+pub struct SocketFilter {
+    insns: Box<[Insn]>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Insn {
+    /// `A <- P[k:size]`
+    LoadAbs(Size, u32),
+    /// `A <- P[X+k:size]`
+    LoadInd(Size, u32),
+    /// `A <- len`
+    LoadLen,
+    /// `A <- k`
+    LoadImm(u32),
+    /// `A <- M[k]`
+    LoadMem(usize),
+    /// `A <- ancillary data`
+    LoadAnc(Ancillary),
+    /// `X <- len`
+    LoadXLen,
+    /// `X <- k`
+    LoadXImm(u32),
+    /// `X <- M[k]`
+    LoadXMem(usize),
+    /// `X <- 4*(P[k:1]&0xf)`
+    LoadXMsh(u32),
+    /// `M[k] <- A`
+    Store(usize),
+    /// `M[k] <- X`
+    StoreX(usize),
+    /// `A <- A op src`
+    Alu(AluOp, Src),
+    /// `A <- -A`
+    Neg,
+    /// `pc += k`
+    Jump(u32),
+    /// `pc += (A op src) ? jt : jf`
+    JumpIf(JumpOp, Src, u8, u8),
+    /// Returns `k`.
+    RetK(u32),
+    /// Returns `A`.
+    RetA,
+    /// `X <- A`
+    Tax,
+    /// `A <- X`
+    Txa,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Size {
+    Word,
+    Half,
+    Byte,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Src {
+    K(u32),
+    X,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AluOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    And,
+    Or,
+    Xor,
+    Lsh,
+    Rsh,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum JumpOp {
+    Eq,
+    Gt,
+    Ge,
+    Set,
+}
+
+/// Ancillary data that can be loaded with `SKF_AD_OFF + offset`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/filter.h>.
+#[derive(Clone, Copy, Debug)]
+enum Ancillary {
+    Protocol,
+    PktType,
+    IfIndex,
+    Mark,
+    Queue,
+    HaType,
+    RxHash,
+    Cpu,
+    AluXorX,
+    VlanTag,
+    VlanTagPresent,
+    VlanTpid,
+}
+
+impl Ancillary {
+    fn from_offset(offset: u32) -> Option<Self> {
+        let ancillary = match offset {
+            0 => Self::Protocol,
+            4 => Self::PktType,
+            8 => Self::IfIndex,
+            20 => Self::Mark,
+            24 => Self::Queue,
+            28 => Self::HaType,
+            32 => Self::RxHash,
+            36 => Self::Cpu,
+            40 => Self::AluXorX,
+            44 => Self::VlanTag,
+            48 => Self::VlanTagPresent,
+            60 => Self::VlanTpid,
+            _ => return None,
+        };
+        Some(ancillary)
+    }
+}
+
+// Instruction classes, sizes, modes, operations, and sources.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/bpf_common.h>.
+const BPF_LD: u16 = 0x00;
+const BPF_LDX: u16 = 0x01;
+const BPF_ST: u16 = 0x02;
+const BPF_STX: u16 = 0x03;
+const BPF_ALU: u16 = 0x04;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+const BPF_MISC: u16 = 0x07;
+
+const BPF_W: u16 = 0x00;
+const BPF_H: u16 = 0x08;
+const BPF_B: u16 = 0x10;
+
+const BPF_IMM: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_IND: u16 = 0x40;
+const BPF_MEM: u16 = 0x60;
+const BPF_LEN: u16 = 0x80;
+const BPF_MSH: u16 = 0xa0;
+
+const BPF_K: u16 = 0x00;
+const BPF_X: u16 = 0x08;
+const BPF_A: u16 = 0x10;
+
+const BPF_NEG: u16 = 0x80;
+
+const BPF_TAX: u16 = 0x00;
+const BPF_TXA: u16 = 0x80;
+
+impl SocketFilter {
+    /// Validates and creates a socket filter from classic BPF instructions.
+    ///
+    /// Like Linux, this method rejects programs with invalid opcodes, backward jumps, jumps out of
+    /// the program, out-of-range memory slots, division by constant zero, and programs that do
+    /// not end with a return instruction.
+    pub fn new(insns: &[CSockFilter]) -> Result<Self> {
+        if insns.is_empty() || insns.len() > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the filter length is invalid");
+        }
+
+        let insns = insns
+            .iter()
+            .enumerate()
+            .map(|(pc, insn)| Self::decode(insn, insns.len() - pc - 1))
+            .collect::<Result<Box<[_]>>>()?;
+
+        if !matches!(insns.last(), Some(Insn::RetK(_) | Insn::RetA)) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the filter does not end with a return instruction"
+            );
+        }
+
+        Ok(Self { insns })
+    }
+
+    /// Decodes an instruction, given the number of instructions that follow it.
+    fn decode(insn: &CSockFilter, remaining: usize) -> Result<Insn> {
+        let CSockFilter { code, jt, jf, k } = *insn;
+        if code > 0xff {
+            return_errno_with_message!(Errno::EINVAL, "the opcode is invalid");
+        }
+
+        let mem_slot = || {
+            if (k as usize) < BPF_MEMWORDS {
+                Ok(k as usize)
+            } else {
+                Err(Error::with_message(
+                    Errno::EINVAL,
+                    "the memory slot is out of range",
+                ))
+            }
+        };
+        let size = match code & 0x18 {
+            BPF_W => Some(Size::Word),
+            BPF_H => Some(Size::Half),
+            BPF_B => Some(Size::Byte),
+            _ => None,
+        };
+        let src = if code & BPF_X != 0 { Src::X } else { Src::K(k) };
+
+        let decoded = match code & 0x07 {
+            BPF_LD => match (code & 0xe0, size) {
+                (BPF_ABS, Some(_)) if (SKF_AD_OFF..0).contains(&(k as i32)) => {
+                    let offset = k.wrapping_sub(SKF_AD_OFF as u32);
+                    let Some(ancillary) = Ancillary::from_offset(offset) else {
+                        return_errno_with_message!(Errno::EINVAL, "the ancillary data is unknown");
+                    };
+                    Some(Insn::LoadAnc(ancillary))
+                }
+                (BPF_ABS, Some(size)) => Some(Insn::LoadAbs(size, k)),
+                (BPF_IND, Some(size)) => Some(Insn::LoadInd(size, k)),
+                (BPF_LEN, Some(Size::Word)) => Some(Insn::LoadLen),
+                (BPF_IMM, Some(Size::Word)) => Some(Insn::LoadImm(k)),
+                (BPF_MEM, Some(Size::Word)) => Some(Insn::LoadMem(mem_slot()?)),
+                _ => None,
+            },
+            BPF_LDX => match (code & 0xe0, size) {
+                (BPF_LEN, Some(Size::Word)) => Some(Insn::LoadXLen),
+                (BPF_IMM, Some(Size::Word)) => Some(Insn::LoadXImm(k)),
+                (BPF_MEM, Some(Size::Word)) => Some(Insn::LoadXMem(mem_slot()?)),
+                (BPF_MSH, Some(Size::Byte)) => Some(Insn::LoadXMsh(k)),
+                _ => None,
+            },
+            BPF_ST if code == BPF_ST => Some(Insn::Store(mem_slot()?)),
+            BPF_STX if code == BPF_STX => Some(Insn::StoreX(mem_slot()?)),
+            BPF_ALU if code == BPF_ALU | BPF_NEG => Some(Insn::Neg),
+            BPF_ALU => {
+                let op = match code & 0xf0 {
+                    0x00 => Some(AluOp::Add),
+                    0x10 => Some(AluOp::Sub),
+                    0x20 => Some(AluOp::Mul),
+                    0x30 => Some(AluOp::Div),
+                    0x40 => Some(AluOp::Or),
+                    0x50 => Some(AluOp::And),
+                    0x60 => Some(AluOp::Lsh),
+                    0x70 => Some(AluOp::Rsh),
+                    0x90 => Some(AluOp::Mod),
+                    0xa0 => Some(AluOp::Xor),
+                    _ => None,
+                };
+                match (op, src) {
+                    (Some(AluOp::Div | AluOp::Mod), Src::K(0)) => {
+                        return_errno_with_message!(Errno::EINVAL, "the filter divides by zero");
+                    }
+                    (Some(AluOp::Lsh | AluOp::Rsh), Src::K(k)) if k >= 32 => {
+                        return_errno_with_message!(Errno::EINVAL, "the shift is out of range");
+                    }
+                    (Some(op), src) => Some(Insn::Alu(op, src)),
+                    (None, _) => None,
+                }
+            }
+            BPF_JMP => {
+                let check_offset = |offset: usize| {
+                    if offset < remaining {
+                        Ok(())
+                    } else {
+                        Err(Error::with_message(
+                            Errno::EINVAL,
+                            "the jump goes out of the filter",
+                        ))
+                    }
+                };
+                let op = match code & 0xf0 {
+                    0x00 if code == BPF_JMP => {
+                        check_offset(k as usize)?;
+                        return Ok(Insn::Jump(k));
+                    }
+                    0x10 => Some(JumpOp::Eq),
+                    0x20 => Some(JumpOp::Gt),
+                    0x30 => Some(JumpOp::Ge),
+                    0x40 => Some(JumpOp::Set),
+                    _ => None,
+                };
+                if let Some(op) = op {
+                    check_offset(jt as usize)?;
+                    check_offset(jf as usize)?;
+                    Some(Insn::JumpIf(op, src, jt, jf))
+                } else {
+                    None
+                }
+            }
+            BPF_RET => match code & 0x18 {
+                BPF_K if code & !0x18 == BPF_RET => Some(Insn::RetK(k)),
+                BPF_A if code & !0x18 == BPF_RET => Some(Insn::RetA),
+                _ => None,
+            },
+            BPF_MISC => match code & 0xf8 {
+                BPF_TAX => Some(Insn::Tax),
+                BPF_TXA => Some(Insn::Txa),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        decoded.ok_or_else(|| Error::with_message(Errno::EINVAL, "the opcode is invalid"))
+    }
+
+    /// Runs the filter on a packet and returns the number of bytes to keep.
+    ///
+    /// A return value of zero means that the packet should be dropped.
+    pub fn run(&self, input: &FilterInput) -> u32 {
+        let mut a: u32 = 0;
+        let mut x: u32 = 0;
+        // Linux rejects programs that read the memory before writing to it. We zero the memory
+        // instead, which gives these programs well-defined (but meaningless) results.
+        let mut mem = [0u32; BPF_MEMWORDS];
+        let mut pc = 0;
+
+        loop {
+            let insn = self.insns[pc];
+            pc += 1;
+
+            match insn {
+                Insn::LoadAbs(size, k) => match input.load(size, k) {
+                    Some(val) => a = val,
+                    None => return 0,
+                },
+                Insn::LoadInd(size, k) => match input.load(size, x.wrapping_add(k)) {
+                    Some(val) => a = val,
+                    None => return 0,
+                },
+                Insn::LoadLen => a = input.data_len(),
+                Insn::LoadImm(k) => a = k,
+                Insn::LoadMem(slot) => a = mem[slot],
+                Insn::LoadAnc(ancillary) => {
+                    a = match ancillary {
+                        Ancillary::Protocol => input.protocol as u32,
+                        Ancillary::PktType => input.pkt_type as u32,
+                        Ancillary::IfIndex => input.ifindex,
+                        Ancillary::HaType => input.hatype as u32,
+                        Ancillary::Cpu => CpuId::current_racy().into(),
+                        Ancillary::AluXorX => a ^ x,
+                        // TODO: Support packet marks, multiqueue devices, receive hashes, and
+                        // VLANs.
+                        Ancillary::Mark
+                        | Ancillary::Queue
+                        | Ancillary::RxHash
+                        | Ancillary::VlanTag
+                        | Ancillary::VlanTagPresent
+                        | Ancillary::VlanTpid => 0,
+                    }
+                }
+                Insn::LoadXLen => x = input.data_len(),
+                Insn::LoadXImm(k) => x = k,
+                Insn::LoadXMem(slot) => x = mem[slot],
+                Insn::LoadXMsh(k) => match input.load(Size::Byte, k) {
+                    Some(val) => x = 4 * (val & 0xf),
+                    None => return 0,
+                },
+                Insn::Store(slot) => mem[slot] = a,
+                Insn::StoreX(slot) => mem[slot] = x,
+                Insn::Alu(op, src) => {
+                    let val = match src {
+                        Src::K(k) => k,
+                        Src::X => x,
+                    };
+                    a = match op {
+                        AluOp::Add => a.wrapping_add(val),
+                        AluOp::Sub => a.wrapping_sub(val),
+                        AluOp::Mul => a.wrapping_mul(val),
+                        AluOp::Div | AluOp::Mod if val == 0 => return 0,
+                        AluOp::Div => a / val,
+                        AluOp::Mod => a % val,
+                        AluOp::And => a & val,
+                        AluOp::Or => a | val,
+                        AluOp::Xor => a ^ val,
+                        AluOp::Lsh => a.checked_shl(val).unwrap_or(0),
+                        AluOp::Rsh => a.checked_shr(val).unwrap_or(0),
+                    };
+                }
+                Insn::Neg => a = a.wrapping_neg(),
+                Insn::Jump(k) => pc += k as usize,
+                Insn::JumpIf(op, src, jt, jf) => {
+                    let val = match src {
+                        Src::K(k) => k,
+                        Src::X => x,
+                    };
+                    let cond = match op {
+                        JumpOp::Eq => a == val,
+                        JumpOp::Gt => a > val,
+                        JumpOp::Ge => a >= val,
+                        JumpOp::Set => a & val != 0,
+                    };
+                    pc += if cond { jt as usize } else { jf as usize };
+                }
+                Insn::RetK(k) => return k,
+                Insn::RetA => return a,
+                Insn::Tax => x = a,
+                Insn::Txa => a = x,
+            }
+        }
+    }
+}
+
+impl FilterInput<'_> {
+    fn data_len(&self) -> u32 {
+        (self.packet.len() - self.data_offset) as u32
+    }
+
+    /// Loads a big-endian value at the offset, which may be relative to the network or the
+    /// link-layer header if it is negative.
+    fn load(&self, size: Size, offset: u32) -> Option<u32> {
+        let offset = offset as i32;
+        let start = if offset >= 0 {
+            self.data_offset.checked_add(offset as usize)?
+        } else if offset >= SKF_AD_OFF {
+            return None;
+        } else if offset >= SKF_NET_OFF {
+            self.net_offset
+                .checked_add((offset - SKF_NET_OFF) as usize)?
+        } else if offset >= SKF_LL_OFF {
+            (offset - SKF_LL_OFF) as usize
+        } else {
+            return None;
+        };
+
+        let len = match size {
+            Size::Word => 4,
+            Size::Half => 2,
+            Size::Byte => 1,
+        };
+        let bytes = self.packet.get(start..start.checked_add(len)?)?;
+
+        Some(bytes.iter().fold(0, |val, byte| (val << 8) | *byte as u32))
+    }
+}
+
+/// A socket filter that is attached to a reuseport group with `SO_ATTACH_REUSEPORT_CBPF`.
+///
+/// Like Linux, the program runs on the transport-layer payload and returns the index of the
+/// socket that should receive the packet.
+impl ReuseportSelector for SocketFilter {
+    fn select(&self, payload: &[u8], _num_socks: usize) -> Option<usize> {
+        let input = FilterInput {
+            packet: payload,
+            data_offset: 0,
+            // The headers are not available, so loads relative to them see the payload.
+            net_offset: 0,
+            protocol: 0,
+            pkt_type: 0,
+            ifindex: 0,
+            hatype: 0,
+        };
+        Some(self.run(&input) as usize)
+    }
+}
@@ -1,17 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub(super) mod datagram_common;
+mod filter;
 mod linger_option;
 mod message_header;
 pub(super) mod options;
+mod owner;
 mod port_privilege;
 mod send_recv_flags;
 mod shutdown_cmd;
 mod socket_addr;
 
+pub use filter::{BPF_MAXINSNS, CSockFilter, FilterInput, SocketFilter};
 pub use linger_option::LingerOption;
 pub(super) use message_header::CControlHeader;
 pub use message_header::{ControlMessage, MessageHeader};
+pub use owner::SocketOwner;
 pub(super) use port_privilege::check_port_privilege;
 pub use send_recv_flags::SendRecvFlags;
 pub use shutdown_cmd::SockShutdownCmd;
@@ -6,15 +6,17 @@ use aster_bigtcp::socket::{
     NeedIfacePoll, TCP_RECV_BUF_LEN, TCP_SEND_BUF_LEN, UDP_RECV_PAYLOAD_LEN, UDP_SEND_PAYLOAD_LEN,
 };
 
-use super::LingerOption;
+use super::{LingerOption, SocketFilter};
 use crate::{
     net::socket::{
         netlink::NETLINK_DEFAULT_BUF_SIZE,
         options::{
-            AcceptConn, Broadcast, KeepAlive, Linger, PassCred, PeerCred, PeerGroups, Priority,
-            RecvBuf, RecvBufForce, ReuseAddr, ReusePort, SendBuf, SendBufForce, SocketOption,
+            AcceptConn, AttachReuseportCbpf, Broadcast, DetachReuseportBpf, KeepAlive, Linger,
+            PassCred, PeerCred, PeerGroups, Priority, RecvBuf, RecvBufForce, ReuseAddr, ReusePort,
+            SendBuf, SendBufForce, SocketOption,
             macros::{sock_option_mut, sock_option_ref},
         },
+        packet::PACKET_DEFAULT_BUF_SIZE,
         unix::{CUserCred, UNIX_DATAGRAM_DEFAULT_BUF_SIZE, UNIX_STREAM_DEFAULT_BUF_SIZE},
     },
     prelude::*,
@@ -35,6 +37,8 @@ pub struct SocketOptionSet {
     linger: LingerOption,
     reuse_port: bool,
     pass_cred: bool,
+    #[getset(skip)]
+    reuseport_filter: Option<Arc<SocketFilter>>,
 }
 
 impl Default for SocketOptionSet {
@@ -49,6 +53,7 @@ impl Default for SocketOptionSet {
             linger: LingerOption::default(),
             reuse_port: false,
             pass_cred: false,
+            reuseport_filter: None,
         }
     }
 }
@@ -99,6 +104,20 @@ impl SocketOptionSet {
         }
     }
 
+    /// Returns the default socket level options for packet socket.
+    pub(in crate::net) fn new_packet() -> Self {
+        Self {
+            send_buf: PACKET_DEFAULT_BUF_SIZE as u32,
+            recv_buf: PACKET_DEFAULT_BUF_SIZE as u32,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the program that selects a socket in the reuseport group.
+    pub fn reuseport_filter(&self) -> Option<&Arc<SocketFilter>> {
+        self.reuseport_filter.as_ref()
+    }
+
     /// Gets socket-level options.
     ///
     /// Note that the socket error has to be handled separately. This method does not handle it
@@ -233,6 +252,23 @@ impl SocketOptionSet {
                 self.set_pass_cred(*pass_cred);
                 socket.set_pass_cred(*pass_cred);
             }
+            socket_attach_reuseport_cbpf @ AttachReuseportCbpf => {
+                if !self.reuse_port() {
+                    return_errno_with_message!(Errno::EINVAL, "port reuse is not enabled");
+                }
+                let filter = socket_attach_reuseport_cbpf.get().unwrap();
+                self.reuseport_filter = Some(filter.clone());
+                socket.set_reuseport_filter(Some(filter.clone()));
+            }
+            _socket_detach_reuseport_bpf @ DetachReuseportBpf => {
+                if !self.reuse_port() {
+                    return_errno_with_message!(Errno::EINVAL, "port reuse is not enabled");
+                }
+                let old_filter = self.reuseport_filter.take();
+                if !socket.set_reuseport_filter(None) && old_filter.is_none() {
+                    return_errno_with_message!(Errno::ENOENT, "no program is attached");
+                }
+            }
             socket_sendbuf_force @ SendBufForce => {
                 check_current_privileged()?;
                 let send_buf = socket_sendbuf_force.get().unwrap();
@@ -301,4 +337,11 @@ pub(in crate::net) trait SetSocketLevelOption {
     }
     /// Sets whether receipt of the credentials of the sending process is enabled.
     fn set_pass_cred(&self, _pass_cred: bool) {}
+
+    /// Sets the program that selects a socket in the reuseport group.
+    ///
+    /// This method returns whether a program was attached to the reuseport group before.
+    fn set_reuseport_filter(&self, _filter: Option<Arc<SocketFilter>>) -> bool {
+        false
+    }
 }
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::vfs::path::Path,
+    prelude::*,
+    process::{Uid, posix_thread::AsPosixThread},
+};
+
+/// The owner of a socket, as reported by socket monitoring interfaces.
+///
+/// Socket monitoring interfaces (e.g., `sock_diag` and `/proc/net/tcp`) identify a socket by the
+/// inode number of its socket file and report the user who created it.
+#[derive(Clone, Copy, Debug)]
+pub struct SocketOwner {
+    ino: u64,
+    uid: Uid,
+}
+
+impl SocketOwner {
+    /// Creates the owner of a socket that is created by the current thread.
+    pub fn new_current(pseudo_path: &Path) -> Self {
+        // Like Linux, the owner is the filesystem UID of the creator.
+        let uid = current_thread!()
+            .as_posix_thread()
+            .unwrap()
+            .credentials()
+            .fsuid();
+
+        Self::new(pseudo_path, uid)
+    }
+
+    /// Creates the owner of a socket that is created on behalf of the user.
+    pub fn new(pseudo_path: &Path, uid: Uid) -> Self {
+        Self {
+            ino: pseudo_path.inode().ino(),
+            uid,
+        }
+    }
+
+    /// Returns the inode number of the socket file.
+    pub fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    /// Returns the UID of the user who owns the socket.
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+}
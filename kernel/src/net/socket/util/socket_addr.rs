@@ -3,7 +3,10 @@
 use aster_bigtcp::wire::{Ipv4Address, Ipv6Address, PortNum};
 
 use crate::{
-    net::socket::{netlink::NetlinkSocketAddr, unix::UnixSocketAddr, vsock::VsockSocketAddr},
+    net::socket::{
+        netlink::NetlinkSocketAddr, packet::PacketSocketAddr, unix::UnixSocketAddr,
+        vsock::VsockSocketAddr,
+    },
     prelude::*,
 };
 
@@ -14,4 +17,5 @@ pub enum SocketAddr {
     IPv6(Ipv6Address, PortNum),
     Netlink(NetlinkSocketAddr),
     Vsock(VsockSocketAddr),
+    Packet(PacketSocketAddr),
 }
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    events::IoEvents,
+    net::socket::{
+        util::{SendRecvFlags, datagram_common},
+        vsock::{VsockSocketAddr, transport::Datagram},
+    },
+    prelude::*,
+    util::{MultiRead, MultiWrite},
+};
+
+pub(super) struct BoundDatagram {
+    datagram: Datagram,
+    remote_addr: Option<VsockSocketAddr>,
+}
+
+impl BoundDatagram {
+    pub(super) fn new(datagram: Datagram) -> Self {
+        Self {
+            datagram,
+            remote_addr: None,
+        }
+    }
+}
+
+impl datagram_common::Bound for BoundDatagram {
+    type Endpoint = VsockSocketAddr;
+
+    fn local_endpoint(&self) -> Self::Endpoint {
+        self.datagram.local_addr()
+    }
+
+    fn remote_endpoint(&self) -> Option<&Self::Endpoint> {
+        self.remote_addr.as_ref()
+    }
+
+    fn set_remote_endpoint(&mut self, endpoint: &Self::Endpoint) {
+        self.remote_addr = Some(*endpoint);
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, Self::Endpoint)> {
+        self.datagram.try_recv(writer, flags)
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: &Self::Endpoint,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        self.datagram.try_send(reader, remote, flags)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        self.datagram.check_io_events()
+    }
+}
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use bound::BoundDatagram;
+use unbound::UnboundDatagram;
+
+use crate::{
+    events::IoEvents,
+    fs::{pseudofs::SockFs, vfs::path::Path},
+    net::socket::{
+        Socket,
+        options::{Error as SocketError, SocketOption, macros::sock_option_mut},
+        private::SocketPrivate,
+        util::{
+            MessageHeader, SendRecvFlags, SocketAddr,
+            datagram_common::{Bound, Inner, select_remote_and_bind},
+        },
+        vsock::addr::{UNSPECIFIED_VSOCK_ADDR, VsockSocketAddr},
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    util::{MultiRead, MultiWrite},
+};
+
+mod bound;
+mod unbound;
+
+/// A vsock datagram socket.
+///
+/// Virtio-vsock devices do not support datagrams, so datagrams can only be exchanged with other
+/// sockets in the same guest (i.e., via the local CID or the guest CID).
+pub struct VsockDatagramSocket {
+    inner: RwMutex<Inner<UnboundDatagram, BoundDatagram>>,
+    is_nonblocking: AtomicBool,
+    // Note that for vsock, all pollee notifications and invalidations live in the transport module
+    // (e.g., `super::transport`) rather than in this module.
+    pollee: Pollee,
+    pseudo_path: Path,
+}
+
+impl VsockDatagramSocket {
+    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+        Arc::new(Self {
+            inner: RwMutex::new(Inner::Unbound(UnboundDatagram)),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            pollee: Pollee::new(),
+            pseudo_path: SockFs::new_path(),
+        })
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, VsockSocketAddr)> {
+        self.inner.read().try_recv(writer, flags)
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: Option<&VsockSocketAddr>,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        select_remote_and_bind(
+            &self.inner,
+            remote,
+            || {
+                let remote_addr = remote.ok_or_else(|| {
+                    Error::with_message(
+                        Errno::EDESTADDRREQ,
+                        "the destination address is not specified",
+                    )
+                })?;
+                self.inner.write().bind_ephemeral(remote_addr, &self.pollee)
+            },
+            |bound_datagram, remote_addr| bound_datagram.try_send(reader, remote_addr, flags),
+        )
+    }
+}
+
+impl Pollable for VsockDatagramSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.inner.read().check_io_events())
+    }
+}
+
+impl SocketPrivate for VsockDatagramSocket {
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) {
+        self.is_nonblocking.store(nonblocking, Ordering::Relaxed);
+    }
+}
+
+impl Socket for VsockDatagramSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr = VsockSocketAddr::try_from(socket_addr)?;
+
+        // Linux does not support `SO_REUSEADDR`/`SO_REUSEPORT` for `AF_VSOCK`. Therefore, port
+        // binding is always exclusive.
+        self.inner.write().bind(&addr, &self.pollee, ())
+    }
+
+    fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        let remote_addr = VsockSocketAddr::try_from(socket_addr)?;
+
+        self.inner.write().connect(&remote_addr, &self.pollee)
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let local_addr = self.inner.read().addr();
+
+        Ok(local_addr.unwrap_or(UNSPECIFIED_VSOCK_ADDR).into())
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        let remote_addr =
+            *self.inner.read().peer_addr().ok_or_else(|| {
+                Error::with_message(Errno::ENOTCONN, "the socket is not connected")
+            })?;
+
+        Ok(remote_addr.into())
+    }
+
+    // TODO: Support setting socket options
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        sock_option_mut!(match option {
+            socket_errors @ SocketError => {
+                // Vsock datagram sockets never have pending errors.
+                socket_errors.set(None);
+                return Ok(());
+            }
+            _ => {}
+        });
+
+        // TODO: Support getting other socket options
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the socket option to be get is unknown");
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        let remote_addr = addr.map(VsockSocketAddr::try_from).transpose()?;
+
+        if !control_messages.is_empty() {
+            // TODO: Support sending control message
+            warn!("sending control message is not supported");
+        }
+
+        // Datagrams are delivered to the receive queue of the peer directly. So sending never
+        // blocks, and datagrams are dropped if the peer's receive queue is full.
+        self.try_send(reader, remote_addr.as_ref(), flags)
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let (received_bytes, peer_addr) =
+            self.block_on(IoEvents::IN, || self.try_recv(writer, flags))?;
+
+        // TODO: Receive control message
+        let message_header = MessageHeader::new(Some(peer_addr.into()), Vec::new());
+
+        Ok((received_bytes, message_header))
+    }
+
+    fn pseudo_path(&self) -> &Path {
+        &self.pseudo_path
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::bound::BoundDatagram;
+use crate::{
+    events::IoEvents,
+    net::socket::{
+        util::{check_port_privilege, datagram_common},
+        vsock::{VsockSocketAddr, transport::BoundPort},
+    },
+    prelude::*,
+    process::signal::Pollee,
+};
+
+pub(super) struct UnboundDatagram;
+
+impl datagram_common::Unbound for UnboundDatagram {
+    type Endpoint = VsockSocketAddr;
+    type BindOptions = ();
+
+    type Bound = BoundDatagram;
+
+    fn bind(
+        &mut self,
+        endpoint: &Self::Endpoint,
+        pollee: &Pollee,
+        _options: Self::BindOptions,
+    ) -> Result<Self::Bound> {
+        if let Ok(port) = u16::try_from(endpoint.port) {
+            check_port_privilege(port)?;
+        }
+
+        let bound_port = BoundPort::new_exclusive(*endpoint, true)?;
+        bind_datagram(bound_port, pollee)
+    }
+
+    fn bind_ephemeral(
+        &mut self,
+        _remote_endpoint: &Self::Endpoint,
+        pollee: &Pollee,
+    ) -> Result<Self::Bound> {
+        let bound_port = BoundPort::new_ephemeral(true)?;
+        bind_datagram(bound_port, pollee)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        IoEvents::OUT
+    }
+}
+
+fn bind_datagram(bound_port: BoundPort, pollee: &Pollee) -> Result<BoundDatagram> {
+    let datagram = bound_port
+        .bind_datagram(pollee)
+        .map_err(|(error, _)| error)?;
+    Ok(BoundDatagram::new(datagram))
+}
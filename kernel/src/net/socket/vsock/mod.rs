@@ -11,15 +11,17 @@
 //!   receive primitives.
 //! - The [_transport layer_](`self::transport`) implements protocol logic such as connection and
 //!   listener management.
-//! - The [_socket layer_](`self::stream`) builds the Linux-compatible socket interface used by
-//!   userspace-facing system calls.
+//! - The _socket layer_ (see [`self::stream`] and [`self::datagram`]) builds the Linux-compatible
+//!   socket interface used by userspace-facing system calls.
 //!
 
 mod addr;
+mod datagram;
 mod stream;
 mod transport;
 
 pub use addr::VsockSocketAddr;
+pub use datagram::VsockDatagramSocket;
 pub use stream::VsockStreamSocket;
 
 pub(in crate::net) fn init() {
@@ -26,9 +26,10 @@ impl ConnectingStream {
         bound_port: BoundPort,
         remote_addr: VsockSocketAddr,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<Self, (Error, BoundPort)> {
         bound_port
-            .connect(remote_addr, pollee)
+            .connect(remote_addr, pollee, is_seqpacket)
             .map(|connection| Self { connection })
     }
 
@@ -5,9 +5,9 @@ use crate::{
     net::socket::{
         util::{SockShutdownCmd, check_port_privilege},
         vsock::{
-            addr::{VMADDR_CID_HOST, VMADDR_PORT_ANY, VsockSocketAddr},
+            addr::{VMADDR_PORT_ANY, VsockSocketAddr},
             stream::{ConnectingStream, ListenStream},
-            transport::BoundPort,
+            transport::{BoundPort, check_connectible},
         },
     },
     prelude::*,
@@ -64,7 +64,7 @@ impl InitStream {
 
         // Linux does not support `SO_REUSEADDR`/`SO_REUSEPORT` for `AF_VSOCK`. Therefore, port
         // binding is always exclusive.
-        self.bound_port = Some(BoundPort::new_exclusive(addr)?);
+        self.bound_port = Some(BoundPort::new_exclusive(addr, false)?);
         Ok(())
     }
 
@@ -72,12 +72,10 @@ impl InitStream {
         self,
         remote_addr: VsockSocketAddr,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<ConnectingStream, (Error, Self)> {
-        if remote_addr.cid != VMADDR_CID_HOST {
-            return Err((
-                Error::with_message(Errno::ENETUNREACH, "only the host vsock CID is supported"),
-                self,
-            ));
+        if let Err(error) = check_connectible(remote_addr.cid, is_seqpacket) {
+            return Err((error, self));
         }
         if remote_addr.port == VMADDR_PORT_ANY {
             return Err((
@@ -89,13 +87,13 @@ impl InitStream {
         let bound_port = if let Some(bound_port) = self.bound_port {
             bound_port
         } else {
-            match BoundPort::new_ephemeral() {
+            match BoundPort::new_ephemeral(false) {
                 Ok(bound_port) => bound_port,
                 Err(error) => return Err((error, self)),
             }
         };
 
-        ConnectingStream::new(bound_port, remote_addr, pollee)
+        ConnectingStream::new(bound_port, remote_addr, pollee, is_seqpacket)
             .map_err(|(error, bound_port)| (error, Self::new_bound(bound_port)))
     }
 
@@ -107,6 +105,7 @@ impl InitStream {
         self,
         backlog: usize,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<ListenStream, (Error, Self)> {
         if !self.is_connect_done {
             return Err((
@@ -122,7 +121,7 @@ impl InitStream {
             ));
         };
 
-        ListenStream::new(bound_port, backlog, pollee)
+        ListenStream::new(bound_port, backlog, pollee, is_seqpacket)
             .map_err(|(error, bound_port)| (error, Self::new_bound(bound_port)))
     }
 
@@ -20,9 +20,10 @@ impl ListenStream {
         bound_port: BoundPort,
         backlog: usize,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<Self, (Error, BoundPort)> {
         bound_port
-            .listen(backlog, pollee)
+            .listen(backlog, pollee, is_seqpacket)
             .map(|listener| Self { listener })
     }
 
@@ -31,6 +31,7 @@ use crate::{
 pub struct VsockStreamSocket {
     state: Mutex<Takeable<State>>,
     is_nonblocking: AtomicBool,
+    is_seqpacket: bool,
     // Note that for vsock, all pollee notifications and invalidations live in the transport module
     // (e.g., `super::transport`) rather than in this module.
     pollee: Pollee,
@@ -45,10 +46,11 @@ enum State {
 }
 
 impl VsockStreamSocket {
-    pub fn new(is_nonblocking: bool) -> Result<Arc<Self>> {
+    pub fn new(is_nonblocking: bool, is_seqpacket: bool) -> Result<Arc<Self>> {
         Ok(Arc::new(Self {
             state: Mutex::new(Takeable::new(State::Init(InitStream::new()))),
             is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_seqpacket,
             pollee: Pollee::new(),
             pseudo_path: SockFs::new_path(),
         }))
@@ -97,7 +99,7 @@ impl VsockStreamSocket {
                 );
             }
 
-            match init_stream.connect(remote_addr, &self.pollee) {
+            match init_stream.connect(remote_addr, &self.pollee, self.is_seqpacket) {
                 Ok(connecting_stream) if self.is_nonblocking() => (
                     State::Connecting(connecting_stream),
                     Some(Err(Error::with_message(
@@ -150,6 +152,7 @@ impl VsockStreamSocket {
         let accepted = Arc::new(Self {
             state: Mutex::new(Takeable::new(State::Connected(connected))),
             is_nonblocking: AtomicBool::new(false),
+            is_seqpacket: self.is_seqpacket,
             pollee,
             pseudo_path: SockFs::new_path(),
         });
@@ -291,7 +294,7 @@ impl Socket for VsockStreamSocket {
                 }
             };
 
-            match init_stream.listen(backlog, &self.pollee) {
+            match init_stream.listen(backlog, &self.pollee, self.is_seqpacket) {
                 Ok(listen_stream) => (State::Listen(listen_stream), Ok(())),
                 Err((error, init_stream)) => (State::Init(init_stream), Err(error)),
             }
@@ -382,13 +385,13 @@ impl Socket for VsockStreamSocket {
                 State::Init(_) | State::Listen(_) | State::Connecting(_) => {
                     return_errno_with_message!(
                         Errno::EOPNOTSUPP,
-                        "sending to a specific address is not allowed on vsock stream sockets"
+                        "sending to a specific address is not allowed on connection-mode vsock sockets"
                     );
                 }
                 State::Connected(_) => {
                     return_errno_with_message!(
                         Errno::EISCONN,
-                        "sending to a specific address is not allowed on vsock stream sockets"
+                        "sending to a specific address is not allowed on connection-mode vsock sockets"
                     );
                 }
             }
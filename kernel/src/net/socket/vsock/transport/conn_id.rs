@@ -15,7 +15,7 @@ pub(super) struct ConnId {
 impl ConnId {
     pub(super) fn from_port_and_remote(port: &BoundPort, remote: VsockSocketAddr) -> Self {
         Self {
-            local_cid: port.vsock_space().guest_cid(),
+            local_cid: port.vsock_space().local_cid_for(remote.cid as u64),
             peer_cid: remote.cid as u64,
             local_port: port.port(),
             peer_port: remote.port,
@@ -52,7 +52,12 @@ impl Connection {
             state.shutdown.local_read_closed && state.shutdown.local_write_closed;
         let peer_fully_closed = state.shutdown.peer_read_closed && state.shutdown.peer_write_closed;
 
-        if !state.rx_queue.packets.is_empty() {
+        let can_recv = if self.inner.is_seqpacket {
+            state.rx_queue.num_messages != 0
+        } else {
+            !state.rx_queue.packets.is_empty()
+        };
+        if can_recv {
             events |= IoEvents::IN;
         }
 
@@ -63,8 +68,10 @@ impl Connection {
         // Most sockets tend to report EPOLLOUT once the write side has been shut down. However,
         // the logic for vsock appears to be different.
         if !state.shutdown.local_write_closed {
-            if state.peer_credit() != 0
-                && self.inner.pending_tx_bytes.load(Ordering::Relaxed) < DEFAULT_TX_BUF_SIZE
+            let min_tx_room = state.credit.min_tx_room;
+            if state.peer_credit() >= min_tx_room
+                && self.inner.pending_tx_bytes.load(Ordering::Relaxed) + min_tx_room
+                    <= DEFAULT_TX_BUF_SIZE
             {
                 events |= IoEvents::OUT;
             }
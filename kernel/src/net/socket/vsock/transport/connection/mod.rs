@@ -23,16 +23,15 @@ mod utils;
 use core::sync::atomic::AtomicUsize;
 
 use aster_softirq::BottomHalfDisabled;
-use aster_virtio::device::socket::{
-    header::{VirtioVsockHdr, VirtioVsockOp, VirtioVsockShutdownFlags},
-    packet::RxPacket,
+use aster_virtio::device::socket::header::{
+    VirtioVsockHdr, VirtioVsockOp, VirtioVsockShutdownFlags, VirtioVsockType,
 };
 use takeable::Takeable;
 
 use crate::{
     events::IoEvents,
     net::socket::vsock::transport::{
-        BoundPort, DEFAULT_CONNECT_TIMEOUT, DEFAULT_RX_BUF_SIZE, conn_id::ConnId,
+        BoundPort, DEFAULT_CONNECT_TIMEOUT, DEFAULT_RX_BUF_SIZE, conn_id::ConnId, packet::RxPacket,
     },
     prelude::*,
     process::signal::Pollee,
@@ -66,6 +65,13 @@ pub(super) struct ConnectionInner {
     /// [`TxQueue`]: aster_virtio::device::socket::queue::TxQueue
     /// [`DEFAULT_TX_BUF_SIZE`]: super::DEFAULT_TX_BUF_SIZE
     pending_tx_bytes: AtomicUsize,
+    /// Whether the connection is a `SOCK_SEQPACKET` connection.
+    ///
+    /// A `SOCK_SEQPACKET` connection preserves message boundaries. The last packet of each
+    /// message carries the [`VirtioVsockRwFlags::SEQ_EOM`] flag.
+    ///
+    /// [`VirtioVsockRwFlags::SEQ_EOM`]: aster_virtio::device::socket::header::VirtioVsockRwFlags::SEQ_EOM
+    is_seqpacket: bool,
 }
 
 struct ConnectionState {
@@ -121,6 +127,10 @@ struct RxQueue {
     packets: VecDeque<RxPacket>,
     used_bytes: usize,
     read_offset: usize,
+    /// The number of complete messages in the queue.
+    ///
+    /// This is only used by `SOCK_SEQPACKET` connections.
+    num_messages: usize,
 }
 
 struct CreditState {
@@ -130,6 +140,12 @@ struct CreditState {
     last_reported_fwd_cnt: u32,
     credit_request_pending: bool,
     tx_cnt: u32,
+    /// The number of bytes that must be sendable before the connection is reported as writable.
+    ///
+    /// This is always one for `SOCK_STREAM` connections. For `SOCK_SEQPACKET` connections, a
+    /// message can only be sent as a whole, so this is the length of the last message that could
+    /// not be sent due to the lack of space.
+    min_tx_room: usize,
 }
 
 struct ShutdownState {
@@ -150,10 +166,11 @@ impl ConnectionInner {
         bound_port: BoundPort,
         conn_id: &ConnId,
         pollee: Pollee,
+        is_seqpacket: bool,
     ) -> Arc<Self> {
         pollee.invalidate();
 
-        let this = Self::new(bound_port, conn_id, pollee, Phase::Connecting);
+        let this = Self::new(bound_port, conn_id, pollee, Phase::Connecting, is_seqpacket);
 
         let mut state = this.state.lock();
         let _ = state.send_packet(&this, VirtioVsockOp::Request, 0);
@@ -167,8 +184,15 @@ impl ConnectionInner {
         bound_port: BoundPort,
         conn_id: &ConnId,
         header: &VirtioVsockHdr,
+        is_seqpacket: bool,
     ) -> Arc<Self> {
-        let this = Self::new(bound_port, conn_id, Pollee::new(), Phase::Connected);
+        let this = Self::new(
+            bound_port,
+            conn_id,
+            Pollee::new(),
+            Phase::Connected,
+            is_seqpacket,
+        );
 
         let mut state = this.state.lock();
         state.update_peer_credit(&this, header);
@@ -178,7 +202,13 @@ impl ConnectionInner {
         this
     }
 
-    fn new(bound_port: BoundPort, conn_id: &ConnId, pollee: Pollee, phase: Phase) -> Arc<Self> {
+    fn new(
+        bound_port: BoundPort,
+        conn_id: &ConnId,
+        pollee: Pollee,
+        phase: Phase,
+        is_seqpacket: bool,
+    ) -> Arc<Self> {
         debug_assert_eq!(bound_port.port(), conn_id.local_port);
 
         let peer_fully_closed = phase != Phase::Connected;
@@ -190,6 +220,7 @@ impl ConnectionInner {
                 packets: VecDeque::new(),
                 used_bytes: 0,
                 read_offset: 0,
+                num_messages: 0,
             },
             credit: CreditState {
                 peer_buf_alloc: 0,
@@ -198,6 +229,7 @@ impl ConnectionInner {
                 last_reported_fwd_cnt: 0,
                 credit_request_pending: false,
                 tx_cnt: 0,
+                min_tx_room: 1,
             },
             shutdown: ShutdownState {
                 local_read_closed: false,
@@ -214,6 +246,7 @@ impl ConnectionInner {
             pollee,
             state: SpinLock::new(state),
             pending_tx_bytes: AtomicUsize::new(0),
+            is_seqpacket,
         })
     }
 
@@ -221,6 +254,15 @@ impl ConnectionInner {
         self.conn_id
     }
 
+    /// Returns the socket type of the connection.
+    pub(super) fn vsock_type(&self) -> VirtioVsockType {
+        if self.is_seqpacket {
+            VirtioVsockType::SeqPacket
+        } else {
+            VirtioVsockType::Stream
+        }
+    }
+
     pub(super) fn pollee(&self) -> &Pollee {
         &self.pollee
     }
@@ -313,10 +355,15 @@ impl ConnectionInner {
 
         state.update_peer_credit(self, header);
 
-        if len != 0 {
+        // An empty packet may still end a (zero-length) message.
+        let is_end_of_message = self.is_seqpacket && packet.is_end_of_message();
+        if len != 0 || is_end_of_message {
             state.rx_queue.used_bytes += len;
             state.rx_queue.packets.push_back(packet);
         }
+        if is_end_of_message {
+            state.rx_queue.num_messages += 1;
+        }
 
         // TODO: If the peer sends too many small packets, we'll exhaust a large amount of kernel
         // memory. We need to support merging small packets to avoid this.
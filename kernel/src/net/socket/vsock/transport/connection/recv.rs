@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use aster_virtio::device::socket::{header::VirtioVsockOp, packet::RxPacket};
+use aster_virtio::device::socket::header::VirtioVsockOp;
 
 use crate::{
     net::socket::{
@@ -8,6 +8,7 @@ use crate::{
         vsock::transport::{
             CREDIT_UPDATE_THRESHOLD, Connection,
             connection::{ConnectionInner, ConnectionState},
+            packet::RxPacket,
         },
     },
     prelude::*,
@@ -16,11 +17,19 @@ use crate::{
 
 impl Connection {
     /// Copies queued payload bytes into `writer` and updates receive credit accounting.
+    ///
+    /// For `SOCK_SEQPACKET` connections, exactly one message is received. If `writer` is too
+    /// small, the rest of the message is discarded. In this case, the original length of the
+    /// message is returned if `MSG_TRUNC` is specified.
     pub(in crate::net::socket::vsock) fn try_recv(
         &mut self,
         writer: &mut dyn MultiWrite,
-        _flags: SendRecvFlags,
+        flags: SendRecvFlags,
     ) -> Result<usize> {
+        if self.inner.is_seqpacket {
+            return self.try_recv_message(writer, flags);
+        }
+
         // We use a packet-pool approach here so a receive attempt either completes for the chosen
         // packets or leaves the receive queue unchanged.
         //
@@ -62,6 +71,50 @@ impl Connection {
 
         result
     }
+
+    fn try_recv_message(
+        &mut self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        let Some(packets) = self.inner.state.lock().grab_message_to_recv(&self.inner)? else {
+            return Ok(0);
+        };
+
+        // Messages can only be received from a `&mut connection`. Therefore, releasing the state
+        // lock does not cause race conditions. See the comments in `try_recv` for details.
+        let result = copy_message_to_userspace(&packets, writer);
+
+        let mut state = self.inner.state.lock();
+        let Ok(recv_len) = result else {
+            state.ungrab_message(packets);
+            return result;
+        };
+
+        let msg_len = packets.iter().map(RxPacket::payload_len).sum::<usize>();
+        drop(packets);
+        state.finish_recv_message(&self.inner, msg_len);
+        drop(state);
+
+        self.inner.pollee.invalidate();
+
+        if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            Ok(msg_len)
+        } else {
+            Ok(recv_len)
+        }
+    }
+}
+
+fn copy_message_to_userspace(packets: &[RxPacket], writer: &mut dyn MultiWrite) -> Result<usize> {
+    let mut total_write_len = 0;
+
+    for packet in packets.iter() {
+        // The bytes that do not fit in `writer` are discarded.
+        total_write_len += writer.write(&mut packet.payload())?;
+    }
+
+    Ok(total_write_len)
 }
 
 struct PoppedRxPackets<'a> {
@@ -195,6 +248,47 @@ impl ConnectionState {
         self.rx_queue.read_offset = packets.read_offset;
     }
 
+    fn grab_message_to_recv(&mut self, conn: &ConnectionInner) -> Result<Option<Vec<RxPacket>>> {
+        if self.rx_queue.num_messages != 0 {
+            debug_assert_eq!(self.rx_queue.read_offset, 0);
+
+            let mut packets = Vec::new();
+            loop {
+                let packet = self.rx_queue.packets.pop_front().unwrap();
+                let is_end_of_message = packet.is_end_of_message();
+                packets.push(packet);
+                if is_end_of_message {
+                    break;
+                }
+            }
+            self.rx_queue.num_messages -= 1;
+
+            return Ok(Some(packets));
+        }
+
+        self.test_and_clear_error(conn)?;
+
+        if self.shutdown.local_read_closed || self.shutdown.peer_write_closed {
+            return Ok(None);
+        }
+
+        return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty");
+    }
+
+    fn ungrab_message(&mut self, packets: Vec<RxPacket>) {
+        for packet in packets.into_iter().rev() {
+            self.rx_queue.packets.push_front(packet);
+        }
+        self.rx_queue.num_messages += 1;
+    }
+
+    fn finish_recv_message(&mut self, conn: &ConnectionInner, msg_len: usize) {
+        self.rx_queue.used_bytes -= msg_len;
+        self.credit.local_fwd_cnt = self.credit.local_fwd_cnt.wrapping_add(msg_len as u32);
+
+        self.send_credit_update_header_if_needed(conn);
+    }
+
     fn send_credit_update_header_if_needed(&mut self, conn: &ConnectionInner) {
         let new_credit = self
             .credit
@@ -3,7 +3,7 @@
 use core::sync::atomic::Ordering;
 
 use aster_virtio::device::socket::{
-    header::VirtioVsockOp,
+    header::{VirtioVsockOp, VirtioVsockRwFlags},
     packet::{TxPacket, TxPacketBuilder},
     queue::TxCompletion,
 };
@@ -13,8 +13,9 @@ use crate::{
     net::socket::{
         util::SendRecvFlags,
         vsock::transport::{
-            Connection, DEFAULT_TX_BUF_SIZE,
+            Connection, DEFAULT_TX_BUF_SIZE, MAX_SEQPACKET_MSG_LEN,
             connection::{ConnectionInner, ConnectionState},
+            loopback,
         },
     },
     prelude::*,
@@ -26,11 +27,18 @@ impl Connection {
     ///
     /// The method respects both peer receive credit and the connection's pending-byte budget. It
     /// may return `EAGAIN` when either resource is exhausted.
+    ///
+    /// For `SOCK_SEQPACKET` connections, all the data in `reader` is sent as one message. The
+    /// message is either sent as a whole or not sent at all.
     pub(in crate::net::socket::vsock) fn try_send(
         &mut self,
         reader: &mut dyn MultiRead,
         _flags: SendRecvFlags,
     ) -> Result<usize> {
+        if self.inner.is_seqpacket {
+            return self.try_send_message(reader);
+        }
+
         // See the comments in `try_recv` to know why we use a packet-pool approach here.
         let mut packet_pool = [const { None }; 8];
 
@@ -54,6 +62,63 @@ impl Connection {
         Ok(num_bytes)
     }
 
+    fn try_send_message(&mut self, reader: &mut dyn MultiRead) -> Result<usize> {
+        let len = reader.sum_lens();
+        if len > MAX_SEQPACKET_MSG_LEN {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+        }
+
+        // A message may not fit in the fixed-size packet pool, so we allocate one. Note that even
+        // an empty message takes one packet.
+        let num_packets = len.div_ceil(TxPacketBuilder::MAX_NBYTES).max(1);
+        let mut packet_pool = (0..num_packets).map(|_| None).collect::<Vec<_>>();
+
+        self.alloc_message_buffers(&mut packet_pool[..], len)?;
+
+        Self::copy_to_send_buffers(&mut packet_pool[..], reader, len)?;
+
+        self.build_and_send_tx_packets(&mut packet_pool[..])?;
+
+        self.inner.pollee.invalidate();
+
+        Ok(len)
+    }
+
+    fn alloc_message_buffers(
+        &mut self,
+        packet_pool: &mut [Option<TxPacketBuilder>],
+        len: usize,
+    ) -> Result<()> {
+        let mut state = self.inner.state.lock();
+
+        state.test_and_clear_error(&self.inner)?;
+
+        if state.shutdown.local_write_closed || state.shutdown.peer_read_closed {
+            return_errno_with_message!(Errno::EPIPE, "the connection is closed for writing");
+        }
+
+        let pending_queue_room =
+            DEFAULT_TX_BUF_SIZE - self.inner.pending_tx_bytes.load(Ordering::Relaxed);
+        if pending_queue_room < len {
+            state.credit.min_tx_room = len;
+            return_errno_with_message!(Errno::EAGAIN, "the pending queue is full");
+        }
+
+        if state.peer_credit() < len {
+            state.credit.min_tx_room = len;
+            state.request_peer_credit(&self.inner);
+            return_errno_with_message!(Errno::EAGAIN, "the peer has no enough receive credit");
+        }
+
+        state.credit.min_tx_room = 1;
+
+        for packet_opt in packet_pool.iter_mut() {
+            *packet_opt = Some(TxPacket::new_builder()?);
+        }
+
+        Ok(())
+    }
+
     fn alloc_send_buffers(
         &mut self,
         packet_pool: &mut [Option<TxPacketBuilder>],
@@ -129,18 +194,39 @@ impl Connection {
         }
 
         let vsock_space = self.inner.bound_port.vsock_space();
-        let mut tx = vsock_space.device().lock_tx();
+        // Connections to non-local CIDs can only be established if the device is present.
+        let mut tx = if vsock_space.is_local_cid(self.inner.conn_id.peer_cid) {
+            None
+        } else {
+            Some(vsock_space.device().unwrap().lock_tx())
+        };
+
+        let num_packets = packet_pool
+            .iter()
+            .take_while(|packet_opt| packet_opt.is_some())
+            .count();
 
         let mut num_bytes = 0;
         let mut num_bytes_in_pending = 0;
 
-        for packet_opt in packet_pool.iter_mut() {
-            let Some(packet_builder) = packet_opt.take() else {
-                break;
+        for (i, packet_opt) in packet_pool.iter_mut().take(num_packets).enumerate() {
+            let packet_builder = packet_opt.take().unwrap();
+
+            // The last packet of a message carries the `SEQ_EOM` flag.
+            let flags = if self.inner.is_seqpacket && i + 1 == num_packets {
+                VirtioVsockRwFlags::SEQ_EOM
+            } else {
+                VirtioVsockRwFlags::empty()
             };
 
             let nbytes = packet_builder.payload_len();
-            let packet = state.make_tx_packet(&self.inner, packet_builder);
+            let packet = state.make_tx_packet(&self.inner, packet_builder, flags);
+            num_bytes += nbytes;
+
+            let Some(tx) = tx.as_mut() else {
+                loopback::send_packet(packet);
+                continue;
+            };
 
             match tx.try_send(packet) {
                 Ok(()) => (),
@@ -154,8 +240,6 @@ impl Connection {
                     num_bytes_in_pending += nbytes;
                 }
             }
-
-            num_bytes += nbytes;
         }
 
         let old_pending_bytes = self
@@ -178,13 +262,17 @@ impl ConnectionState {
             return Ok(peer_free);
         }
 
+        self.request_peer_credit(conn);
+
+        return_errno_with_message!(Errno::EAGAIN, "the peer has no receive credit");
+    }
+
+    fn request_peer_credit(&mut self, conn: &ConnectionInner) {
         if !self.credit.credit_request_pending
             && self.send_packet(conn, VirtioVsockOp::CreditRequest, 0)
         {
             self.credit.credit_request_pending = true;
         }
-
-        return_errno_with_message!(Errno::EAGAIN, "the peer has no receive credit");
     }
 
     pub(super) fn peer_credit(&self) -> usize {
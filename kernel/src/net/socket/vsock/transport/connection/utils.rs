@@ -3,7 +3,7 @@
 use core::time::Duration;
 
 use aster_virtio::device::socket::{
-    header::{VirtioVsockHdr, VirtioVsockOp},
+    header::{VirtioVsockHdr, VirtioVsockOp, VirtioVsockRwFlags},
     packet::{TxPacket, TxPacketBuilder},
 };
 
@@ -38,6 +38,7 @@ impl ConnectionState {
             conn.conn_id.local_port,
             conn.conn_id.peer_port,
             0,
+            conn.vsock_type(),
             op,
             flags,
             DEFAULT_RX_BUF_SIZE as u32,
@@ -58,6 +59,7 @@ impl ConnectionState {
         &mut self,
         conn: &ConnectionInner,
         packet_builder: TxPacketBuilder,
+        flags: VirtioVsockRwFlags,
     ) -> TxPacket {
         let header = VirtioVsockHdr::new(
             conn.conn_id.local_cid,
@@ -65,8 +67,9 @@ impl ConnectionState {
             conn.conn_id.local_port,
             conn.conn_id.peer_port,
             packet_builder.payload_len() as u32,
+            conn.vsock_type(),
             VirtioVsockOp::Rw,
-            0,
+            flags.bits(),
             DEFAULT_RX_BUF_SIZE as u32,
             self.credit.local_fwd_cnt,
         );
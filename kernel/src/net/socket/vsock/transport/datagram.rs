@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_softirq::BottomHalfDisabled;
+use aster_virtio::device::socket::{
+    header::{VirtioVsockHdr, VirtioVsockOp, VirtioVsockType},
+    packet::{TxPacket, TxPacketBuilder},
+};
+
+use crate::{
+    events::IoEvents,
+    net::socket::{
+        util::SendRecvFlags,
+        vsock::{
+            VsockSocketAddr,
+            addr::VMADDR_PORT_ANY,
+            transport::{BoundPort, DEFAULT_RX_BUF_SIZE, loopback, packet::RxPacket},
+        },
+    },
+    prelude::*,
+    process::signal::Pollee,
+    util::{MultiRead, MultiWrite},
+};
+
+/// A uniquely owned vsock datagram handle; dropping it will release the port.
+///
+/// Virtio-vsock devices do not support datagrams. Therefore, datagrams can only be sent to the
+/// guest itself via the loopback transport.
+pub(in crate::net::socket::vsock) struct Datagram {
+    inner: Arc<DatagramInner>,
+}
+
+impl Datagram {
+    pub(super) fn new(inner: Arc<DatagramInner>) -> Self {
+        Self { inner }
+    }
+
+    /// Sends the data in `reader` as one datagram to `remote_addr`.
+    pub(in crate::net::socket::vsock) fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote_addr: &VsockSocketAddr,
+        _flags: SendRecvFlags,
+    ) -> Result<usize> {
+        let len = reader.sum_lens();
+        if len > TxPacketBuilder::MAX_NBYTES {
+            return_errno_with_message!(Errno::EMSGSIZE, "the datagram is too large");
+        }
+
+        let vsock_space = self.inner.bound_port.vsock_space();
+        if !vsock_space.is_local_cid(remote_addr.cid as u64) {
+            return_errno_with_message!(
+                Errno::ENETUNREACH,
+                "vsock datagrams can only be sent to local CIDs"
+            );
+        }
+        if remote_addr.port == VMADDR_PORT_ANY {
+            return_errno_with_message!(Errno::EINVAL, "the vsock port is invalid to send to");
+        }
+
+        let mut packet_builder = TxPacket::new_builder()?;
+        packet_builder.copy_payload(|mut writer| {
+            writer.limit(len);
+            reader.read(&mut writer).map_err(|(err, _)| err)
+        })?;
+        debug_assert_eq!(packet_builder.payload_len(), len);
+
+        let header = VirtioVsockHdr::new(
+            vsock_space.local_cid_for(remote_addr.cid as u64),
+            remote_addr.cid as u64,
+            self.inner.bound_port.port(),
+            remote_addr.port,
+            len as u32,
+            VirtioVsockType::Dgram,
+            VirtioVsockOp::Rw,
+            0,
+            0,
+            0,
+        );
+        loopback::send_packet(packet_builder.build(&header));
+
+        Ok(len)
+    }
+
+    /// Receives one datagram and returns its length and its source address.
+    ///
+    /// If `writer` is too small, the rest of the datagram is discarded. In this case, the original
+    /// length of the datagram is returned if `MSG_TRUNC` is specified.
+    pub(in crate::net::socket::vsock) fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, VsockSocketAddr)> {
+        // Dequeue the datagram before copying it, since copying it to the user space may sleep.
+        let packet = {
+            let mut rx_queue = self.inner.rx_queue.lock();
+
+            let Some(packet) = rx_queue.packets.pop_front() else {
+                return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty");
+            };
+            rx_queue.used_bytes -= packet.payload_len();
+
+            packet
+        };
+        self.inner.pollee.invalidate();
+
+        let header = packet.header();
+        let remote_addr = VsockSocketAddr {
+            cid: header.src_cid as u32,
+            port: header.src_port,
+        };
+
+        let copied_len = writer.write(&mut packet.payload())?;
+        let len = if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            packet.payload_len()
+        } else {
+            copied_len
+        };
+
+        Ok((len, remote_addr))
+    }
+
+    /// Returns the local address bound to this datagram endpoint.
+    pub(in crate::net::socket::vsock) fn local_addr(&self) -> VsockSocketAddr {
+        self.inner.bound_port.local_addr()
+    }
+
+    /// Returns the currently observable I/O readiness for the datagram endpoint.
+    pub(in crate::net::socket::vsock) fn check_io_events(&self) -> IoEvents {
+        let rx_queue = self.inner.rx_queue.lock();
+
+        if rx_queue.packets.is_empty() {
+            IoEvents::OUT
+        } else {
+            IoEvents::IN | IoEvents::OUT
+        }
+    }
+}
+
+impl Drop for Datagram {
+    fn drop(&mut self) {
+        let vsock_space = self.inner.bound_port.vsock_space();
+        vsock_space.remove_datagram(&self.inner);
+    }
+}
+
+pub(super) struct DatagramInner {
+    bound_port: BoundPort,
+    pollee: Pollee,
+    rx_queue: SpinLock<DatagramRxQueue, BottomHalfDisabled>,
+}
+
+struct DatagramRxQueue {
+    packets: VecDeque<RxPacket>,
+    used_bytes: usize,
+}
+
+impl DatagramInner {
+    pub(super) fn new(bound_port: BoundPort, pollee: Pollee) -> Arc<Self> {
+        pollee.invalidate();
+
+        Arc::new(Self {
+            bound_port,
+            pollee,
+            rx_queue: SpinLock::new(DatagramRxQueue {
+                packets: VecDeque::new(),
+                used_bytes: 0,
+            }),
+        })
+    }
+
+    pub(super) fn on_rw(&self, packet: RxPacket) {
+        let mut rx_queue = self.rx_queue.lock();
+
+        // Datagrams are dropped if the receive queue is full.
+        let len = packet.payload_len();
+        if rx_queue.used_bytes + len > DEFAULT_RX_BUF_SIZE {
+            return;
+        }
+
+        rx_queue.used_bytes += len;
+        rx_queue.packets.push_back(packet);
+
+        drop(rx_queue);
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    pub(super) fn bound_port(&self) -> &BoundPort {
+        &self.bound_port
+    }
+}
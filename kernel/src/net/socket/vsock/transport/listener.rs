@@ -3,6 +3,7 @@
 use core::sync::atomic::{AtomicUsize, Ordering};
 
 use aster_softirq::BottomHalfDisabled;
+use aster_virtio::device::socket::header::VirtioVsockType;
 
 use crate::{
     events::IoEvents,
@@ -58,10 +59,16 @@ pub(super) struct ListenerInner {
     backlog: AtomicUsize,
     num_conns: AtomicUsize,
     incoming_conns: SpinLock<VecDeque<Arc<ConnectionInner>>, BottomHalfDisabled>,
+    is_seqpacket: bool,
 }
 
 impl ListenerInner {
-    pub(super) fn new(bound_port: BoundPort, backlog: usize, pollee: Pollee) -> Arc<Self> {
+    pub(super) fn new(
+        bound_port: BoundPort,
+        backlog: usize,
+        pollee: Pollee,
+        is_seqpacket: bool,
+    ) -> Arc<Self> {
         pollee.invalidate();
 
         Arc::new(Self {
@@ -70,9 +77,23 @@ impl ListenerInner {
             backlog: AtomicUsize::new(backlog.min(MAX_BACKLOG)),
             num_conns: AtomicUsize::new(0),
             incoming_conns: SpinLock::new(VecDeque::new()),
+            is_seqpacket,
         })
     }
 
+    pub(super) fn is_seqpacket(&self) -> bool {
+        self.is_seqpacket
+    }
+
+    /// Returns the socket type of the connections accepted by the listener.
+    pub(super) fn vsock_type(&self) -> VirtioVsockType {
+        if self.is_seqpacket {
+            VirtioVsockType::SeqPacket
+        } else {
+            VirtioVsockType::Stream
+        }
+    }
+
     pub(super) fn is_full(&self) -> bool {
         // Race conditions don't matter here. We use `>` instead of `>=` because Linux allows to
         // have `backlog + 1` connections in the backlog queue.
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The loopback transport.
+//!
+//! Packets sent to `VMADDR_CID_LOCAL`, or to the guest CID itself, never reach the virtio-vsock
+//! device. Instead, they are queued here and processed in the bottom half as if they were
+//! received from the device. This allows vsock applications to communicate within the guest
+//! without a host peer.
+
+use alloc::{collections::vec_deque::VecDeque, sync::Arc};
+
+use aster_softirq::{BottomHalfDisabled, Taskless};
+use aster_virtio::device::socket::packet::TxPacket;
+use ostd::sync::SpinLock;
+use spin::Once;
+
+use crate::net::socket::vsock::transport::space::vsock_space;
+
+static PENDING_PACKETS: SpinLock<VecDeque<TxPacket>, BottomHalfDisabled> =
+    SpinLock::new(VecDeque::new());
+
+static TASKLESS: Once<Arc<Taskless>> = Once::new();
+
+/// Sends a packet via the loopback transport.
+///
+/// This method may be called while holding the socket state lock, since the packet is processed
+/// later in the bottom half.
+pub(super) fn send_packet(packet: TxPacket) {
+    PENDING_PACKETS.lock().push_back(packet);
+
+    TASKLESS.get().unwrap().schedule();
+}
+
+fn process_pending_packets() {
+    let packets = {
+        let mut pending = PENDING_PACKETS.lock();
+        core::mem::take(&mut *pending)
+    };
+
+    let vsock_space = vsock_space().unwrap();
+    vsock_space.process_loopback(packets);
+}
+
+pub(super) fn init() {
+    TASKLESS.call_once(|| Taskless::new(process_pending_packets));
+}
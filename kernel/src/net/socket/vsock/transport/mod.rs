@@ -8,19 +8,28 @@
 //! The socket layer is expected to build on these APIs to provide the user-visible socket
 //! interface.
 //!
+//! Packets sent to `VMADDR_CID_LOCAL` or to the guest CID itself are delivered by the
+//! [_loopback transport_](`self::loopback`) instead of the device, so the transport is usable
+//! even if no virtio-vsock device is present.
+//!
 //! For a quick start, bind to a port by creating a [`BoundPort`] instance.
 //!  - To connect to a remote address, use the [`BoundPort::connect`] method and get a
 //!    [`Connection`] instance. Data can be transmitted or received via the [`Connection::try_send`]
 //!    and [`Connection::try_recv`] methods.
 //!  - To listen to the local address, use the [`BoundPort::listen`] method and get a [`Listener`]
 //!    instance. Incoming connections can be accepted via the [`Listener::try_accept`] method.
+//!  - To send or receive datagrams, bind a datagram port and use the [`BoundPort::bind_datagram`]
+//!    method to get a [`Datagram`] instance.
 //!
-//! Drop the [`BoundPort`], [`Connection`], or [`Listener`] instance will shut down the underlying
-//! connection (if any) and release the resources.
+//! Drop the [`BoundPort`], [`Connection`], [`Listener`], or [`Datagram`] instance will shut down
+//! the underlying connection (if any) and release the resources.
 
 mod conn_id;
 mod connection;
+mod datagram;
 mod listener;
+mod loopback;
+mod packet;
 mod port;
 mod space;
 mod timer;
@@ -28,9 +37,12 @@ mod timer;
 use core::time::Duration;
 
 pub(super) use connection::{Connection, connect::ConnectResult};
+pub(super) use datagram::Datagram;
 pub(super) use listener::Listener;
 pub(super) use port::BoundPort;
 
+use crate::prelude::*;
+
 // Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/af_vsock.c#L136>
 const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
 // Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/virtio_transport_common.c#L24>
@@ -44,6 +56,13 @@ const MAX_BACKLOG: usize = 4096;
 
 const CREDIT_UPDATE_THRESHOLD: u32 = (DEFAULT_RX_BUF_SIZE / 4) as u32;
 
+/// The maximum length of a message sent via a `SOCK_SEQPACKET` connection.
+///
+/// A message is sent at once only if the peer has enough receive credit. The peer may hold back
+/// up to `CREDIT_UPDATE_THRESHOLD` bytes of credit before reporting it, so larger messages may
+/// never be sent.
+const MAX_SEQPACKET_MSG_LEN: usize = DEFAULT_TX_BUF_SIZE - CREDIT_UPDATE_THRESHOLD as usize;
+
 fn process_rx_callback() {
     if let Ok(vsock_space) = space::vsock_space() {
         vsock_space.process_rx();
@@ -56,17 +75,28 @@ fn process_event_callback() {
     }
 }
 
-/// Initializes the virtio-vsock transport when the default device is present.
+/// Checks whether a connection can be made to the remote CID.
+///
+/// If `is_seqpacket` is true, the connection is a `SOCK_SEQPACKET` connection. Otherwise, it is a
+/// `SOCK_STREAM` connection.
+pub(super) fn check_connectible(remote_cid: u32, is_seqpacket: bool) -> Result<()> {
+    space::vsock_space()?.check_connectible(remote_cid, is_seqpacket)
+}
+
+/// Initializes the vsock transport.
+///
+/// The virtio-vsock transport is enabled when the default device is present. The loopback
+/// transport is always enabled.
 pub(super) fn init() {
     use aster_virtio::device::socket::DEVICE_NAME;
 
-    let Some(device) = aster_virtio::device::socket::get_device(DEVICE_NAME) else {
-        return;
-    };
-
-    device.init_rx_callback(process_rx_callback);
-    device.init_event_callback(process_event_callback);
+    let device = aster_virtio::device::socket::get_device(DEVICE_NAME);
+    if let Some(device) = device.as_ref() {
+        device.init_rx_callback(process_rx_callback);
+        device.init_event_callback(process_event_callback);
+    }
     space::init(device);
 
+    loopback::init();
     timer::init();
 }
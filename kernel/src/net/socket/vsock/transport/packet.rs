@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_virtio::device::socket::{
+    header::{VirtioVsockHdr, VirtioVsockRwFlags},
+    packet::{RxPacket as DeviceRxPacket, TxPacket},
+};
+use ostd::mm::{Infallible, VmReader};
+
+/// A packet received by the transport.
+pub(super) enum RxPacket {
+    /// A packet received from the virtio-vsock device.
+    Device(DeviceRxPacket),
+    /// A packet sent by a local socket via the loopback transport.
+    Loopback(TxPacket),
+}
+
+impl RxPacket {
+    pub(super) fn header(&self) -> VirtioVsockHdr {
+        match self {
+            Self::Device(packet) => packet.header(),
+            Self::Loopback(packet) => packet.header(),
+        }
+    }
+
+    pub(super) fn payload_len(&self) -> usize {
+        match self {
+            Self::Device(packet) => packet.payload_len(),
+            Self::Loopback(packet) => packet.payload_len(),
+        }
+    }
+
+    pub(super) fn payload(&self) -> VmReader<'_, Infallible> {
+        match self {
+            Self::Device(packet) => packet.payload(),
+            Self::Loopback(packet) => packet.payload(),
+        }
+    }
+
+    /// Returns whether the packet ends a message of a `SOCK_SEQPACKET` connection.
+    pub(super) fn is_end_of_message(&self) -> bool {
+        VirtioVsockRwFlags::from_bits_truncate(self.header().flags)
+            .contains(VirtioVsockRwFlags::SEQ_EOM)
+    }
+}
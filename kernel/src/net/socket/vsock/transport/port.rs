@@ -7,7 +7,7 @@ use crate::{
     net::socket::vsock::{
         addr::{VMADDR_CID_ANY, VMADDR_PORT_ANY, VsockSocketAddr},
         transport::{
-            Connection, Listener,
+            Connection, Datagram, Listener,
             space::{VsockSpace, vsock_space},
         },
     },
@@ -22,6 +22,8 @@ use crate::{
 #[derive(Debug)]
 pub(in crate::net::socket::vsock) struct BoundPort {
     port: u32,
+    /// Whether the port is in the port namespace of datagram sockets.
+    is_dgram: bool,
 }
 
 pub(super) struct PortTable {
@@ -54,31 +56,40 @@ impl PortTable {
 
 impl BoundPort {
     /// Binds exclusively to `addr` and returns the resulting port lease.
-    pub(in crate::net::socket::vsock) fn new_exclusive(addr: VsockSocketAddr) -> Result<Self> {
+    ///
+    /// If `is_dgram` is true, the port is bound in the port namespace of datagram sockets.
+    pub(in crate::net::socket::vsock) fn new_exclusive(
+        addr: VsockSocketAddr,
+        is_dgram: bool,
+    ) -> Result<Self> {
         let vsock_space = vsock_space()?;
 
-        let guest_cid = vsock_space.guest_cid();
-        if addr.cid != VMADDR_CID_ANY && addr.cid as u64 != guest_cid {
+        if addr.cid != VMADDR_CID_ANY && !vsock_space.is_local_cid(addr.cid as u64) {
             return_errno_with_message!(Errno::EADDRNOTAVAIL, "the vsock CID is not local");
         }
 
         if addr.port == VMADDR_PORT_ANY {
-            return Self::new_ephemeral();
+            return Self::new_ephemeral(is_dgram);
         }
 
-        let mut ports = vsock_space.lock_ports();
+        let mut ports = vsock_space.lock_ports(is_dgram);
         let usage = ports.usage.entry(addr.port).or_insert(0);
         if *usage != 0 {
             return_errno_with_message!(Errno::EADDRINUSE, "the vsock port is already in use");
         }
         *usage += 1;
-        Ok(Self { port: addr.port })
+        Ok(Self {
+            port: addr.port,
+            is_dgram,
+        })
     }
 
     /// Allocates and returns a fresh ephemeral port lease.
-    pub(in crate::net::socket::vsock) fn new_ephemeral() -> Result<Self> {
+    ///
+    /// If `is_dgram` is true, the port is allocated in the port namespace of datagram sockets.
+    pub(in crate::net::socket::vsock) fn new_ephemeral(is_dgram: bool) -> Result<Self> {
         let vsock_space = vsock_space()?;
-        let mut ports = vsock_space.lock_ports();
+        let mut ports = vsock_space.lock_ports(is_dgram);
 
         let start_port = ports.next_ephemeral_port;
         let mut current_port = start_port;
@@ -88,7 +99,10 @@ impl BoundPort {
             if *usage == 0 {
                 *usage += 1;
                 ports.next_ephemeral_port = PortTable::next_ephemeral_port_after(current_port);
-                return Ok(Self { port: current_port });
+                return Ok(Self {
+                    port: current_port,
+                    is_dgram,
+                });
             }
 
             current_port = PortTable::next_ephemeral_port_after(current_port);
@@ -104,11 +118,12 @@ impl BoundPort {
     pub(super) fn new_shared(bound_port: &BoundPort) -> BoundPort {
         let vsock_space = bound_port.vsock_space();
 
-        let mut ports = vsock_space.lock_ports();
+        let mut ports = vsock_space.lock_ports(bound_port.is_dgram);
         let usage = ports.usage.entry(bound_port.port).or_insert(0);
         *usage += 1;
         BoundPort {
             port: bound_port.port,
+            is_dgram: bound_port.is_dgram,
         }
     }
 
@@ -116,26 +131,52 @@ impl BoundPort {
     ///
     /// On success, ownership of the lease moves into the returned `Connection`. On failure, the
     /// error is returned together with the original lease.
+    ///
+    /// If `is_seqpacket` is true, the connection is a `SOCK_SEQPACKET` connection. Otherwise, it
+    /// is a `SOCK_STREAM` connection.
     pub(in crate::net::socket::vsock) fn connect(
         self,
         remote_addr: VsockSocketAddr,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<Connection, (Error, BoundPort)> {
+        debug_assert!(!self.is_dgram);
+
         let vsock_space = self.vsock_space();
-        vsock_space.new_connection(self, remote_addr, pollee)
+        vsock_space.new_connection(self, remote_addr, pollee, is_seqpacket)
     }
 
     /// Starts listening on the leased port.
     ///
     /// On success, ownership of the lease moves into the returned `Listener`. On failure, the
     /// error is returned together with the original lease.
+    ///
+    /// If `is_seqpacket` is true, the listener accepts `SOCK_SEQPACKET` connections. Otherwise,
+    /// it accepts `SOCK_STREAM` connections.
     pub(in crate::net::socket::vsock) fn listen(
         self,
         backlog: usize,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<Listener, (Error, BoundPort)> {
+        debug_assert!(!self.is_dgram);
+
+        let vsock_space = self.vsock_space();
+        vsock_space.new_listener(self, backlog, pollee, is_seqpacket)
+    }
+
+    /// Starts sending and receiving datagrams on the leased port.
+    ///
+    /// On success, ownership of the lease moves into the returned `Datagram`. On failure, the
+    /// error is returned together with the original lease.
+    pub(in crate::net::socket::vsock) fn bind_datagram(
+        self,
+        pollee: &Pollee,
+    ) -> Result<Datagram, (Error, BoundPort)> {
+        debug_assert!(self.is_dgram);
+
         let vsock_space = self.vsock_space();
-        vsock_space.new_listener(self, backlog, pollee)
+        vsock_space.new_datagram(self, pollee)
     }
 
     /// Returns the local address described by this lease.
@@ -160,7 +201,7 @@ impl Drop for BoundPort {
     fn drop(&mut self) {
         use alloc::collections::btree_map::Entry;
 
-        let mut ports = self.vsock_space().lock_ports();
+        let mut ports = self.vsock_space().lock_ports(self.is_dgram);
         let Entry::Occupied(mut usage) = ports.usage.entry(self.port) else {
             return;
         };
@@ -3,8 +3,11 @@
 use aster_softirq::BottomHalfDisabled;
 use aster_virtio::device::socket::{
     device::SocketDevice,
-    header::{VirtioVsockHdr, VirtioVsockOp, VirtioVsockShutdownFlags, VirtioVsockType},
-    packet::{RxPacket, TxPacket},
+    header::{
+        VirtioVsockHdr, VirtioVsockOp, VirtioVsockRwFlags, VirtioVsockShutdownFlags,
+        VirtioVsockType,
+    },
+    packet::TxPacket,
 };
 use ostd::sync::PreemptDisabled;
 use spin::Once;
@@ -12,51 +15,122 @@ use spin::Once;
 use crate::{
     events::IoEvents,
     net::socket::vsock::{
-        addr::VsockSocketAddr,
+        addr::{VMADDR_CID_HOST, VMADDR_CID_LOCAL, VsockSocketAddr},
         transport::{
-            BoundPort, Connection, Listener, conn_id::ConnId, connection::ConnectionInner,
-            listener::ListenerInner, port::PortTable, timer::TimerEvent,
+            BoundPort, Connection, Datagram, Listener, conn_id::ConnId,
+            connection::ConnectionInner, datagram::DatagramInner, listener::ListenerInner,
+            loopback, packet::RxPacket, port::PortTable, timer::TimerEvent,
         },
     },
     prelude::*,
     process::signal::Pollee,
 };
 
-// We currently support only one vsock device.
-// TODO: Add support for multiple vsock devices and the loopback vsock device.
+// We currently support only one vsock device, plus the loopback transport.
+// TODO: Add support for multiple vsock devices.
 pub(super) struct VsockSpace {
-    device: Arc<SocketDevice>,
+    device: Option<Arc<SocketDevice>>,
     ports: SpinLock<PortTable>,
+    /// The ports used by datagram sockets.
+    ///
+    /// Datagram sockets and connection-oriented sockets have separate port namespaces, as in
+    /// Linux.
+    dgram_ports: SpinLock<PortTable>,
     sockets: SpinLock<SocketTable, BottomHalfDisabled>,
 }
 
 struct SocketTable {
     connections: BTreeMap<ConnId, Arc<ConnectionInner>>,
     listeners: BTreeMap<u32, Arc<ListenerInner>>,
+    datagrams: BTreeMap<u32, Arc<DatagramInner>>,
 }
 
 impl VsockSpace {
-    fn new(device: Arc<SocketDevice>) -> Self {
+    fn new(device: Option<Arc<SocketDevice>>) -> Self {
         Self {
             device,
             ports: SpinLock::new(PortTable::new()),
+            dgram_ports: SpinLock::new(PortTable::new()),
             sockets: SpinLock::new(SocketTable {
                 connections: BTreeMap::new(),
                 listeners: BTreeMap::new(),
+                datagrams: BTreeMap::new(),
             }),
         }
     }
 
-    pub(super) fn device(&self) -> &SocketDevice {
-        &self.device
+    /// Returns the virtio-vsock device, or `None` if the device is not present.
+    pub(super) fn device(&self) -> Option<&SocketDevice> {
+        self.device.as_deref()
     }
 
+    /// Returns the guest CID.
+    ///
+    /// If the virtio-vsock device is not present, the guest can only be reached via the loopback
+    /// transport, so `VMADDR_CID_LOCAL` is returned.
     pub(super) fn guest_cid(&self) -> u64 {
-        self.device.guest_cid()
+        match self.device.as_ref() {
+            Some(device) => device.guest_cid(),
+            None => VMADDR_CID_LOCAL as u64,
+        }
+    }
+
+    /// Returns whether the CID refers to the guest itself.
+    ///
+    /// Packets sent to such CIDs are delivered via the loopback transport.
+    pub(super) fn is_local_cid(&self, cid: u64) -> bool {
+        cid == VMADDR_CID_LOCAL as u64
+            || self
+                .device
+                .as_ref()
+                .is_some_and(|device| device.guest_cid() == cid)
+    }
+
+    /// Returns the local CID used to communicate with the remote CID.
+    pub(super) fn local_cid_for(&self, remote_cid: u64) -> u64 {
+        if remote_cid == VMADDR_CID_LOCAL as u64 {
+            remote_cid
+        } else {
+            self.guest_cid()
+        }
     }
 
-    pub(super) fn lock_ports(&self) -> SpinLockGuard<'_, PortTable, PreemptDisabled> {
-        self.ports.lock()
+    pub(super) fn check_connectible(&self, remote_cid: u32, is_seqpacket: bool) -> Result<()> {
+        if self.is_local_cid(remote_cid as u64) {
+            return Ok(());
+        }
+
+        let Some(device) = self.device.as_ref() else {
+            return_errno_with_message!(Errno::ENETUNREACH, "the vsock CID is not reachable");
+        };
+        if remote_cid != VMADDR_CID_HOST {
+            return_errno_with_message!(
+                Errno::ENETUNREACH,
+                "only the host and local vsock CIDs are supported"
+            );
+        }
+        if is_seqpacket && !device.supports_seqpacket() {
+            return_errno_with_message!(
+                Errno::ESOCKTNOSUPPORT,
+                "the vsock device does not support SOCK_SEQPACKET"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Locks the port table.
+    ///
+    /// If `is_dgram` is true, the port table of datagram sockets is locked.
+    pub(super) fn lock_ports(
+        &self,
+        is_dgram: bool,
+    ) -> SpinLockGuard<'_, PortTable, PreemptDisabled> {
+        if is_dgram {
+            self.dgram_ports.lock()
+        } else {
+            self.ports.lock()
+        }
     }
 }
 
@@ -67,9 +141,14 @@ impl VsockSpace {
         bound_port: BoundPort,
         remote_addr: VsockSocketAddr,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<Connection, (Error, BoundPort)> {
         use alloc::collections::btree_map::Entry;
 
+        if let Err(error) = self.check_connectible(remote_addr.cid, is_seqpacket) {
+            return Err((error, bound_port));
+        }
+
         let mut sockets = self.sockets.lock();
 
         // Note that we should query the guest CID (part of `from_port_and_remote`) after locking
@@ -82,7 +161,8 @@ impl VsockSpace {
             ));
         };
 
-        let inner = ConnectionInner::new_connecting(bound_port, &conn_id, pollee.clone());
+        let inner =
+            ConnectionInner::new_connecting(bound_port, &conn_id, pollee.clone(), is_seqpacket);
         entry.insert(inner.clone());
 
         Ok(Connection::new(inner))
@@ -116,6 +196,7 @@ impl VsockSpace {
         bound_port: BoundPort,
         backlog: usize,
         pollee: &Pollee,
+        is_seqpacket: bool,
     ) -> Result<Listener, (Error, BoundPort)> {
         use alloc::collections::btree_map::Entry;
 
@@ -129,7 +210,7 @@ impl VsockSpace {
             ));
         };
 
-        let inner = ListenerInner::new(bound_port, backlog, pollee.clone());
+        let inner = ListenerInner::new(bound_port, backlog, pollee.clone(), is_seqpacket);
         entry.insert(inner.clone());
 
         Ok(Listener::new(inner))
@@ -171,6 +252,41 @@ impl VsockSpace {
             // No need to notify the pollee since the connection isn't even accepted.
         }
     }
+
+    pub(super) fn new_datagram(
+        &self,
+        bound_port: BoundPort,
+        pollee: &Pollee,
+    ) -> Result<Datagram, (Error, BoundPort)> {
+        use alloc::collections::btree_map::Entry;
+
+        let mut sockets = self.sockets.lock();
+
+        let port = bound_port.port();
+        let Entry::Vacant(entry) = sockets.datagrams.entry(port) else {
+            return Err((
+                Error::with_message(Errno::EADDRINUSE, "the vsock datagram port is in use"),
+                bound_port,
+            ));
+        };
+
+        let inner = DatagramInner::new(bound_port, pollee.clone());
+        entry.insert(inner.clone());
+
+        Ok(Datagram::new(inner))
+    }
+
+    pub(super) fn remove_datagram(&self, datagram: &Arc<DatagramInner>) {
+        let mut sockets = self.sockets.lock();
+
+        let port = datagram.bound_port().port();
+        let removed = sockets.datagrams.remove(&port);
+        debug_assert!(
+            removed
+                .as_ref()
+                .is_some_and(|removed| Arc::ptr_eq(removed, datagram))
+        );
+    }
 }
 
 // RX packet and transport event processing.
@@ -178,11 +294,25 @@ impl VsockSpace {
     pub(super) fn process_rx(&self) {
         // Lock order: device RX -> sockets -> socket state -> device TX
 
-        let mut rx = self.device.lock_rx();
+        let Some(device) = self.device.as_ref() else {
+            return;
+        };
+
+        let mut rx = device.lock_rx();
         let mut sockets = self.sockets.lock();
 
         while let Some(packet) = rx.recv() {
-            self.process_rx_packet(&mut sockets, packet);
+            self.process_rx_packet(&mut sockets, RxPacket::Device(packet));
+        }
+    }
+
+    pub(super) fn process_loopback(&self, packets: VecDeque<TxPacket>) {
+        // Lock order: sockets -> socket state -> loopback
+
+        let mut sockets = self.sockets.lock();
+
+        for packet in packets {
+            self.process_rx_packet(&mut sockets, RxPacket::Loopback(packet));
         }
     }
 
@@ -191,6 +321,11 @@ impl VsockSpace {
 
         let header = packet.header();
 
+        if header.type_ == VirtioVsockType::Dgram as u16 {
+            self.process_rx_datagram(&sockets.datagrams, &header, packet);
+            return;
+        }
+
         let conn_id = ConnId::from_incoming_header(&header);
         let entry = sockets.connections.entry(conn_id);
 
@@ -218,6 +353,7 @@ impl VsockSpace {
 
         let dst_port = header.dst_port;
         let listener = if let Some(listener) = listeners.get(&dst_port)
+            && listener.vsock_type() as u16 == header.type_
             && !listener.is_full()
         {
             listener
@@ -229,7 +365,8 @@ impl VsockSpace {
         let bound_port = BoundPort::new_shared(listener.bound_port());
         let conn_id = vacant_conn.key();
 
-        let inner = ConnectionInner::new_connected(bound_port, conn_id, header);
+        let inner =
+            ConnectionInner::new_connected(bound_port, conn_id, header, listener.is_seqpacket());
         vacant_conn.insert(inner.clone());
 
         listener.push_incoming(inner.clone());
@@ -245,7 +382,10 @@ impl VsockSpace {
         header: &VirtioVsockHdr,
         packet: RxPacket,
     ) {
+        let connection = occupied_conn.get();
+
         let op = if let Some(op) = header.op()
+            && connection.vsock_type() as u16 == header.type_
             && self.validate_rx_header(op, header, &packet)
         {
             op
@@ -254,8 +394,6 @@ impl VsockSpace {
             return;
         };
 
-        let connection = occupied_conn.get();
-
         let should_remove = match op {
             VirtioVsockOp::Request => {
                 connection.active_rst();
@@ -283,8 +421,8 @@ impl VsockSpace {
             return;
         }
 
-        // We do not use `VirtioVsockHdr::new` here because we want to specify the `type_` field. It
-        // may not be `VirtioVsockType::Stream`.
+        // We do not use `VirtioVsockHdr::new` here because we want to copy the `type_` field. It
+        // may not be a valid `VirtioVsockType`.
         let rst_header = VirtioVsockHdr {
             src_cid: header.dst_cid,
             dst_cid: header.src_cid,
@@ -300,24 +438,56 @@ impl VsockSpace {
         let _ = self.send_packet(&rst_header);
     }
 
+    fn process_rx_datagram(
+        &self,
+        datagrams: &BTreeMap<u32, Arc<DatagramInner>>,
+        header: &VirtioVsockHdr,
+        packet: RxPacket,
+    ) {
+        // Datagrams are unreliable, so invalid datagrams are silently dropped.
+        if header.op != VirtioVsockOp::Rw as u16
+            || header.flags != 0
+            || !self.is_local_cid(header.dst_cid)
+            || packet.payload_len() != header.len as usize
+        {
+            return;
+        }
+
+        let dst_port = header.dst_port;
+        if let Some(datagram) = datagrams.get(&dst_port) {
+            datagram.on_rw(packet);
+        }
+    }
+
     pub(super) fn process_transport_event(&self) {
         // Lock order: sockets -> socket state
 
+        let Some(device) = self.device.as_ref() else {
+            return;
+        };
+
         let mut sockets = self.sockets.lock();
 
         // As stated in the specification, we only need to deal with the connections:
         // "The driver shuts down established connections and the guest_cid configuration field is
         // fetched again. Existing listen sockets remain but their CID is updated to reflect the
         // current guest_cid."
+        //
+        // Connections to `VMADDR_CID_LOCAL` do not depend on the guest CID, so they remain.
 
         let connections = core::mem::take(&mut sockets.connections);
-        for connection in connections.into_values() {
+        for (conn_id, connection) in connections.into_iter() {
+            if conn_id.local_cid == VMADDR_CID_LOCAL as u64 {
+                sockets.connections.insert(conn_id, connection);
+                continue;
+            }
+
             connection.on_rst();
             Self::notify_removed_connection(connection);
         }
 
         // The reload of the guest CID is protectd by the `sockets` lock.
-        self.device.reload_guest_id();
+        device.reload_guest_id();
     }
 
     pub(super) fn process_timer_events(&self, events: Vec<TimerEvent>) {
@@ -376,9 +546,17 @@ impl VsockSpace {
         };
         let packet = builder.build(header);
 
+        if self.is_local_cid(header.dst_cid) {
+            loopback::send_packet(packet);
+            return true;
+        }
+        let Some(device) = self.device.as_ref() else {
+            return false;
+        };
+
         // Lock order: socket state -> device TX
 
-        let mut tx = self.device.lock_tx();
+        let mut tx = device.lock_tx();
         match tx.try_send(packet) {
             Ok(()) => (),
             Err(pending) => {
@@ -398,11 +576,13 @@ impl VsockSpace {
         header: &VirtioVsockHdr,
         packet: &RxPacket,
     ) -> bool {
-        if header.type_ != VirtioVsockType::Stream as u16 {
+        if header.type_ != VirtioVsockType::Stream as u16
+            && header.type_ != VirtioVsockType::SeqPacket as u16
+        {
             return false;
         }
 
-        if header.dst_cid != self.guest_cid() {
+        if !self.is_local_cid(header.dst_cid) {
             return false;
         }
 
@@ -420,6 +600,9 @@ impl VsockSpace {
             VirtioVsockOp::Shutdown => {
                 payload_len == 0 && VirtioVsockShutdownFlags::from_bits(header.flags).is_some()
             }
+            VirtioVsockOp::Rw if header.type_ == VirtioVsockType::SeqPacket as u16 => {
+                VirtioVsockRwFlags::from_bits(header.flags).is_some()
+            }
             VirtioVsockOp::Rw => header.flags == 0,
         }
     }
@@ -430,9 +613,9 @@ static VSOCK_SPACE: Once<VsockSpace> = Once::new();
 pub(super) fn vsock_space() -> Result<&'static VsockSpace> {
     VSOCK_SPACE
         .get()
-        .ok_or_else(|| Error::with_message(Errno::ENODEV, "no virtio-vsock device is available"))
+        .ok_or_else(|| Error::with_message(Errno::ENODEV, "the vsock transport is not available"))
 }
 
-pub(super) fn init(device: Arc<SocketDevice>) {
+pub(super) fn init(device: Option<Arc<SocketDevice>>) {
     VSOCK_SPACE.call_once(move || VsockSpace::new(device));
 }
@@ -208,6 +208,15 @@ impl UtsName {
     };
 }
 
+impl UtsName {
+    /// Returns the node name (i.e., the hostname) without the trailing nul bytes.
+    pub fn nodename(&self) -> &[u8] {
+        CStr::from_bytes_until_nul(&self.nodename)
+            .unwrap()
+            .to_bytes()
+    }
+}
+
 impl NsCommonOps for UtsNamespace {
     const TYPE: NsType = NsType::Uts;
 
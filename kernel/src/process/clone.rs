@@ -26,9 +26,9 @@ use crate::{
     },
     prelude::*,
     process::{
-        NsProxy, UserNamespace,
+        NsProxy, PidNamespace, UserNamespace,
         pid_file::PidFile,
-        posix_thread::{PosixThread, ThreadLocal, allocate_posix_tid},
+        posix_thread::{PosixThread, ThreadLocal, ptrace::PtraceEvent},
         stats::PROCESS_CREATION_COUNTER,
     },
     sched::Nice,
@@ -203,7 +203,7 @@ impl CloneArgs {
                 );
             }
 
-            if ctx.process.is_init_process() {
+            if ctx.process.is_init_process() || ctx.process.is_pid_ns_init() {
                 return_errno_with_message!(
                     Errno::EINVAL,
                     "`CLONE_PARENT` cannot be used if the process is the init process"
@@ -226,6 +226,22 @@ impl CloneArgs {
                     "`CLONE_THREAD` cannot be used together with `CLONE_PIDFD` or `CLONE_NEWUSER`"
                 );
             }
+
+            // A thread must live in the same PID namespace as the other threads in the process.
+            if clone_flags.contains(CloneFlags::CLONE_NEWPID)
+                || !Arc::ptr_eq(
+                    ctx.thread_local
+                        .borrow_ns_proxy()
+                        .unwrap()
+                        .pid_ns_for_children(),
+                    ctx.process.pid_ns(),
+                )
+            {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "`CLONE_THREAD` cannot be used to create a thread in another PID namespace"
+                );
+            }
         }
 
         // Reject invalid argument combinations related to the CLONE_SIGHAND flag.
@@ -238,16 +254,6 @@ impl CloneArgs {
             );
         }
 
-        // TODO: Support clone-family ptrace events and remove this check.
-        if !clone_flags.contains(CloneFlags::CLONE_UNTRACED)
-            && ctx.posix_thread.needs_ptrace_clone_stop(self)
-        {
-            return_errno_with_message!(
-                Errno::EOPNOTSUPP,
-                "ptrace clone events are not supported currently"
-            );
-        }
-
         Ok(())
     }
 }
@@ -276,7 +282,10 @@ impl CloneFlags {
             | CloneFlags::CLONE_VFORK
             | CloneFlags::CLONE_NEWCGROUP
             | CloneFlags::CLONE_NEWNS
+            | CloneFlags::CLONE_NEWNET
+            | CloneFlags::CLONE_NEWPID
             | CloneFlags::CLONE_NEWUTS
+            | CloneFlags::CLONE_NEWUSER
             | CloneFlags::CLONE_PARENT;
         let unsupported_flags = *self - supported_flags;
         if !unsupported_flags.is_empty() {
@@ -292,7 +301,7 @@ impl CloneFlags {
 /// but this may not be the expected behavior.
 pub fn clone_child(
     ctx: &Context,
-    parent_context: &UserContext,
+    parent_context: &mut UserContext,
     clone_args: CloneArgs,
 ) -> Result<Tid> {
     clone_args.check(ctx)?;
@@ -300,10 +309,18 @@ pub fn clone_child(
     if clone_args.flags.contains(CloneFlags::CLONE_THREAD) {
         let child_task = clone_child_task(ctx, parent_context, clone_args)?;
         let child_thread = child_task.as_thread().unwrap();
+        let ptrace_event = ctx
+            .posix_thread
+            .ptrace_attach_child(&clone_args, child_thread);
         child_thread.run();
 
+        if let Some(event) = ptrace_event {
+            ctx.posix_thread
+                .ptrace_may_stop_on(event, ctx, parent_context);
+        }
+
         let child_tid = child_thread.as_posix_thread().unwrap().tid();
-        Ok(child_tid)
+        Ok(ctx.process.nr_in_ns(child_tid))
     } else {
         // Hold the read lock before charge to ensure the cgroup of current process
         // won't change during the charge and the subsequent move operation.
@@ -345,8 +362,16 @@ pub fn clone_child(
             child_process.status().set_vfork_child(true);
         }
 
+        let ptrace_event = ctx
+            .posix_thread
+            .ptrace_attach_child(&clone_args, &child_process.main_thread());
         child_process.run();
 
+        if let Some(event) = ptrace_event {
+            ctx.posix_thread
+                .ptrace_may_stop_on(event, ctx, parent_context);
+        }
+
         PROCESS_CREATION_COUNTER
             .get()
             .unwrap()
@@ -357,10 +382,16 @@ pub fn clone_child(
             let cond = || (!child_process.status().is_vfork_child()).then_some(());
             let current = ctx.process.as_ref();
             current.children_wait_queue().wait_until(cond);
+
+            ctx.posix_thread.ptrace_may_stop_on(
+                PtraceEvent::VforkDone(child_process.pid()),
+                ctx,
+                parent_context,
+            );
         }
 
         let child_pid = child_process.pid();
-        Ok(child_pid)
+        Ok(ctx.process.nr_in_ns(child_pid))
     }
 }
 
@@ -436,7 +467,8 @@ fn clone_child_task(
     // Inherit the thread name.
     let thread_name = posix_thread.thread_name().lock().clone();
 
-    let child_tid = allocate_posix_tid();
+    let allocated_tid = pid_table::alloc_id(process.pid_ns())?;
+    let child_tid = allocated_tid.id();
     let child_task = {
         let credentials = {
             let credentials = ctx.posix_thread.credentials();
@@ -464,7 +496,11 @@ fn clone_child_task(
         }
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        clone_parent_settid(
+            process.nr_in_ns(child_tid),
+            clone_args.parent_tid,
+            clone_flags,
+        )?;
         thread_builder = clone_child_cleartid(thread_builder, clone_args.child_tid, clone_flags);
         thread_builder = clone_child_settid(thread_builder, clone_args.child_tid, clone_flags);
 
@@ -484,6 +520,7 @@ fn clone_child_task(
 
     let child_thread = child_task.as_thread().unwrap();
     pid_table::pid_table_mut().insert_thread(child_tid, child_thread);
+    allocated_tid.commit();
 
     Ok(child_task)
 }
@@ -534,7 +571,7 @@ fn clone_child_process(
     let child_fpu_context = thread_local.supp_user_context().fpu().get();
 
     // Clone the namespaces
-    let child_user_ns = clone_user_ns(clone_flags, thread_local)?;
+    let child_user_ns = clone_user_ns(clone_flags, thread_local, posix_thread)?;
     let child_ns_proxy = clone_ns_proxy(
         thread_local.borrow_ns_proxy().unwrap(),
         &child_user_ns,
@@ -565,7 +602,10 @@ fn clone_child_process(
     // Inherit the parent's OOM score adjustment
     let child_oom_score_adj = process.oom_score_adj().load(Ordering::Relaxed);
 
-    let child_tid = allocate_posix_tid();
+    // Put the child process into the PID namespace for children
+    let child_pid_ns = child_ns_proxy.pid_ns_for_children().clone();
+    let allocated_tid = pid_table::alloc_id(&child_pid_ns)?;
+    let child_tid = allocated_tid.id();
 
     let child = {
         let child_vmar_arc = child_vmar.clone_arc();
@@ -576,7 +616,10 @@ fn clone_child_process(
 
             let credentials = {
                 let credentials = ctx.posix_thread.credentials();
-                Credentials::new_from(&credentials)
+                let credentials = Credentials::new_from(&credentials);
+                // The process keyring is only shared by the threads in the same process.
+                credentials.keyrings().unshare_process_keyring();
+                credentials
             };
 
             PosixThreadBuilder::new(
@@ -602,7 +645,11 @@ fn clone_child_process(
         }
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        clone_parent_settid(
+            process.nr_in_ns(child_tid),
+            clone_args.parent_tid,
+            clone_flags,
+        )?;
         child_thread_builder =
             clone_child_cleartid(child_thread_builder, clone_args.child_tid, clone_flags);
         child_thread_builder =
@@ -616,6 +663,7 @@ fn clone_child_process(
             child_oom_score_adj,
             child_sig_dispositions,
             child_user_ns,
+            child_pid_ns,
             child_thread_builder,
         )
     };
@@ -628,6 +676,7 @@ fn clone_child_process(
 
     // Sets parent process and group for child process.
     set_parent_and_group(clone_flags, process, &child);
+    allocated_tid.commit();
 
     Ok(child)
 }
@@ -805,14 +854,13 @@ fn clone_pidfd(
 fn clone_user_ns(
     clone_flags: CloneFlags,
     thread_local: &ThreadLocal,
+    posix_thread: &PosixThread,
 ) -> Result<Arc<UserNamespace>> {
+    let user_ns = thread_local.borrow_user_ns();
     if clone_flags.contains(CloneFlags::CLONE_NEWUSER) {
-        return_errno_with_message!(
-            Errno::EINVAL,
-            "cloning a new user namespace is not supported"
-        );
+        user_ns.new_child(posix_thread)
     } else {
-        Ok(thread_local.borrow_user_ns().clone())
+        Ok(user_ns.clone())
     }
 }
 
@@ -835,6 +883,7 @@ fn create_child_process(
     oom_score_adj: i16,
     sig_dispositions: Arc<Mutex<SigDispositions>>,
     user_ns: Arc<UserNamespace>,
+    pid_ns: Arc<PidNamespace>,
     thread_builder: PosixThreadBuilder,
 ) -> Arc<Process> {
     let child_proc = Process::new(
@@ -845,6 +894,7 @@ fn create_child_process(
         oom_score_adj,
         sig_dispositions,
         user_ns,
+        pid_ns,
     );
 
     let child_task = thread_builder.process(Arc::downgrade(&child_proc)).build();
@@ -853,7 +903,11 @@ fn create_child_process(
     child_proc
 }
 
-fn set_parent_and_group(clone_flags: CloneFlags, parent: &Arc<Process>, child: &Arc<Process>) {
+pub(super) fn set_parent_and_group(
+    clone_flags: CloneFlags,
+    parent: &Arc<Process>,
+    child: &Arc<Process>,
+) {
     loop {
         let real_parent = clone_parent(clone_flags, parent);
 
@@ -0,0 +1,666 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Producing core dumps.
+
+use core::{ops::Range, time::Duration};
+
+use ostd::{arch::cpu::context::UserContext, sync::RwArc, task::Task};
+
+use super::{
+    CoreDumpFilter, Dumpable,
+    elf::{
+        ELF_PRARGSZ, ElfHeader, ElfPrPsInfo, ElfPrStatus, ElfProgramHeader, ElfSigInfo,
+        NOTE_NAME_CORE, NT_AUXV, NT_FILE, NT_PRPSINFO, NT_PRSTATUS, NT_SIGINFO, NoteBuf, PF_R,
+        PF_W, PF_X, PT_LOAD, PT_NOTE, ThreadRegs,
+    },
+    name::CoreName,
+};
+use crate::{
+    fs::{
+        self,
+        file::{
+            AccessMode, CreationFlags, FileLike, InodeMode, InodeType, OpenArgs, StatusFlags,
+            file_table::{FdFlags, FileTable},
+        },
+        pipe,
+        vfs::path::{FsPath, LookupResult},
+    },
+    prelude::*,
+    process::{
+        ResourceType, TermStatus,
+        execve::wait_other_threads_exit,
+        posix_thread::{ContextPthreadAdminApi, sigkill_other_threads},
+        process::spawn_user_mode_helper,
+        signal::{
+            HandlePendingSignal,
+            c_types::siginfo_t,
+            sig_mask::{SigMask, SigSet},
+            sig_num::SigNum,
+        },
+    },
+    thread::Tid,
+    time::timeval_t,
+    vm::{
+        perms::VmPerms,
+        vmar::{VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, VmMapping, Vmar},
+    },
+};
+
+/// The state of an ongoing core dump.
+///
+/// The state is stored in the task set of the dumping process, so that each
+/// exiting thread can record its own registers before it goes away.
+pub(in crate::process) struct CoreDumpState {
+    threads: Vec<ThreadCore>,
+}
+
+impl CoreDumpState {
+    fn new() -> Self {
+        Self {
+            threads: Vec::new(),
+        }
+    }
+
+    /// Records the state of the current thread, which is exiting.
+    pub(in crate::process) fn record_exiting_thread(
+        &mut self,
+        ctx: &Context,
+        user_ctx: &UserContext,
+    ) {
+        self.threads.push(ThreadCore::capture(ctx, user_ctx));
+    }
+}
+
+/// The state of a thread that is recorded in a core dump.
+struct ThreadCore {
+    tid: Tid,
+    sig_pending: SigSet,
+    sig_mask: SigMask,
+    regs: ThreadRegs,
+    user_time: Duration,
+    kernel_time: Duration,
+}
+
+impl ThreadCore {
+    fn capture(ctx: &Context, user_ctx: &UserContext) -> Self {
+        let prof_clock = ctx.posix_thread.prof_clock();
+
+        Self {
+            tid: ctx.posix_thread.tid(),
+            sig_pending: ctx.pending_signals(),
+            sig_mask: ctx.posix_thread.sig_mask(),
+            regs: ThreadRegs::capture(ctx, user_ctx),
+            user_time: prof_clock.user_clock().read_time(),
+            kernel_time: prof_clock.kernel_clock().read_time(),
+        }
+    }
+}
+
+/// Dumps the core of the current process, which is being killed by a signal.
+///
+/// All other threads in the process are killed. If a core file is written
+/// successfully, [`TermStatus::Dumped`] is returned; otherwise,
+/// [`TermStatus::Killed`] is returned.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c#L521-L734>
+pub(in crate::process) fn do_coredump(
+    sig_info: &siginfo_t,
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> TermStatus {
+    let sig_num = SigNum::try_from(sig_info.si_signo as u8).unwrap();
+    let killed = TermStatus::Killed(sig_num);
+    let dumped = TermStatus::Dumped(sig_num);
+
+    let user_space = ctx.user_space();
+    let process_vm = user_space.vmar().process_vm();
+    let dumpable = process_vm.dumpable();
+    if dumpable == Dumpable::Disable {
+        return killed;
+    }
+    let coredump_filter = process_vm.coredump_filter();
+
+    let Some(threads) = kill_other_threads(ctx, user_ctx, killed) else {
+        return killed;
+    };
+
+    let Some(core_name) = CoreName::format(ctx, sig_num, dumpable) else {
+        return killed;
+    };
+
+    let result = open_core_target(core_name, ctx).and_then(|target| {
+        let Some(mut writer) = target else {
+            return Ok(false);
+        };
+        let vmar = user_space.vmar();
+        write_core(&mut writer, sig_info, &threads, coredump_filter, vmar, ctx)?;
+        Ok(true)
+    });
+    match result {
+        Ok(true) => {}
+        Ok(false) => return killed,
+        Err(err) => {
+            warn!("PID {}: failed to dump core: {:?}", ctx.process.pid(), err);
+            return killed;
+        }
+    }
+
+    ctx.process.status().set_exit_code(dumped.as_u32());
+    dumped
+}
+
+/// Kills all other threads in the current process and collects their states.
+///
+/// Returns `None` if the process is already exiting or executing a new program.
+fn kill_other_threads(
+    ctx: &Context,
+    user_ctx: &UserContext,
+    killed: TermStatus,
+) -> Option<Vec<ThreadCore>> {
+    let current_task = Task::current().unwrap();
+
+    {
+        let mut tasks = ctx.process.tasks().lock();
+        if tasks.has_exited_group() || tasks.in_execve() {
+            return None;
+        }
+
+        let mut state = CoreDumpState::new();
+        // The dumping thread comes first in the core file.
+        state.record_exiting_thread(ctx, user_ctx);
+        tasks.start_core_dump(state);
+
+        sigkill_other_threads(&current_task, &tasks);
+        tasks.set_exited_group();
+        ctx.process.status().set_exit_code(killed.as_u32());
+    }
+
+    // Signals other than `SIGKILL` should not interrupt writing the core file.
+    ctx.set_sig_mask(SigMask::new_full());
+
+    let wait_result = wait_other_threads_exit(ctx);
+    let state = ctx.process.tasks().lock().take_core_dump().unwrap();
+    wait_result.ok()?;
+
+    Some(state.threads)
+}
+
+/// The destination that a core file is written to.
+struct CoreWriter {
+    file: Arc<dyn FileLike>,
+    written: usize,
+    limit: usize,
+}
+
+impl CoreWriter {
+    /// Writes all the bytes, failing if the size limit is exceeded.
+    fn write(&mut self, mut buf: &[u8], ctx: &Context) -> Result<()> {
+        if self.written + buf.len() > self.limit {
+            return_errno_with_message!(Errno::EFBIG, "the core file size limit is exceeded");
+        }
+
+        while !buf.is_empty() {
+            match self.file.write_bytes(buf) {
+                Ok(0) => return_errno_with_message!(Errno::EIO, "the core file accepts no data"),
+                Ok(len) => {
+                    buf = &buf[len..];
+                    self.written += len;
+                }
+                Err(err) if err.error() == Errno::EINTR && !ctx.has_pending_sigkill() => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes zeros until the core file reaches `offset`.
+    fn pad_to(&mut self, offset: usize, ctx: &Context) -> Result<()> {
+        const ZEROS: [u8; 256] = [0; 256];
+
+        while self.written < offset {
+            let len = (offset - self.written).min(ZEROS.len());
+            self.write(&ZEROS[..len], ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Opens the destination of the core file.
+///
+/// Returns `None` if the core file should not be written due to `RLIMIT_CORE`.
+fn open_core_target(core_name: CoreName, ctx: &Context) -> Result<Option<CoreWriter>> {
+    let core_limit = ctx
+        .process
+        .resource_limits()
+        .get_rlimit(ResourceType::RLIMIT_CORE)
+        .get_cur();
+
+    let writer = match core_name {
+        CoreName::File(name) => {
+            if core_limit < PAGE_SIZE as u64 {
+                return Ok(None);
+            }
+            CoreWriter {
+                file: open_core_file(&name, ctx)?,
+                written: 0,
+                limit: usize::try_from(core_limit).unwrap_or(usize::MAX),
+            }
+        }
+        CoreName::Pipe(argv) => {
+            // Following Linux, a limit of one disables piping core files, so
+            // that a helper that crashes does not recursively dump itself.
+            if core_limit == 1 {
+                return Ok(None);
+            }
+            CoreWriter {
+                file: open_core_pipe(argv)?,
+                written: 0,
+                limit: usize::MAX,
+            }
+        }
+    };
+
+    Ok(Some(writer))
+}
+
+fn open_core_file(name: &str, ctx: &Context) -> Result<Arc<dyn FileLike>> {
+    let flags = AccessMode::O_WRONLY as u32
+        | (CreationFlags::O_CREAT | CreationFlags::O_NOFOLLOW | CreationFlags::O_TRUNC).bits();
+    let open_args = OpenArgs::from_flags_and_mode(flags, InodeMode::from_bits_truncate(0o600))?;
+
+    let fs_path = FsPath::try_from(name)?;
+    let fs_ref = ctx.thread_local.borrow_fs();
+    let path_resolver = fs_ref.resolver().read();
+
+    let path = match path_resolver.lookup_unresolved_no_follow(&fs_path)? {
+        LookupResult::Resolved(path) => path,
+        LookupResult::AtParent(result) => {
+            if result.target_is_dir() {
+                return_errno_with_message!(Errno::EISDIR, "the core file is a directory");
+            }
+            let (parent, tail_name) = result.into_parent_and_basename();
+            let new_path =
+                parent.new_fs_child(&tail_name, InodeType::File, open_args.inode_mode)?;
+            fs::vfs::notify::on_create(&parent, || tail_name.clone());
+            new_path
+        }
+    };
+
+    // Refuse to write to an existing file that the user does not own, or that
+    // is not a regular file, to avoid being tricked into overwriting it.
+    if path.type_() != InodeType::File {
+        return_errno_with_message!(Errno::EACCES, "the core file is not a regular file");
+    }
+    if path.owner()? != ctx.posix_thread.credentials().fsuid() {
+        return_errno_with_message!(Errno::EACCES, "the core file is owned by another user");
+    }
+
+    Ok(Arc::new(path.open(open_args)?))
+}
+
+fn open_core_pipe(argv: Vec<CString>) -> Result<Arc<dyn FileLike>> {
+    let Some(executable_path) = argv.first() else {
+        return_errno_with_message!(Errno::EINVAL, "the core pattern has no helper");
+    };
+    let executable_path = executable_path.to_string_lossy().into_owned();
+
+    let (reader, writer) = pipe::new_file_pair(StatusFlags::empty())?;
+
+    // The helper reads the core file from its standard input.
+    let mut file_table = FileTable::new();
+    file_table.insert(reader, FdFlags::empty());
+
+    spawn_user_mode_helper(&executable_path, argv, Vec::new(), RwArc::new(file_table))?;
+
+    Ok(writer)
+}
+
+/// A memory mapping to be written to a core file.
+struct DumpedMapping {
+    range: Range<Vaddr>,
+    perms: VmPerms,
+    dump_size: DumpSize,
+    /// The page offset and the absolute path of the backing file, if any.
+    file: Option<(usize, String)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DumpSize {
+    /// Nothing is dumped.
+    None,
+    /// The first page is dumped if it contains an ELF header.
+    ElfHeader,
+    /// The whole mapping is dumped.
+    Whole,
+}
+
+impl DumpedMapping {
+    fn dump_len(&self) -> usize {
+        match self.dump_size {
+            DumpSize::None => 0,
+            DumpSize::ElfHeader => PAGE_SIZE,
+            DumpSize::Whole => self.range.len(),
+        }
+    }
+}
+
+/// Decides how much of a mapping to dump according to the core dump filter.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c#L1212-L1293>
+fn dump_size_of(vm_mapping: &VmMapping, filter: CoreDumpFilter) -> DumpSize {
+    let whole_if = |is_dumped: bool| {
+        if is_dumped {
+            DumpSize::Whole
+        } else {
+            DumpSize::None
+        }
+    };
+
+    if vm_mapping.is_dontdump() || vm_mapping.is_device() {
+        return DumpSize::None;
+    }
+
+    if vm_mapping.is_shared() {
+        return if vm_mapping.path().is_none() {
+            whole_if(filter.contains(CoreDumpFilter::ANON_SHARED))
+        } else {
+            whole_if(filter.contains(CoreDumpFilter::MAPPED_SHARED))
+        };
+    }
+
+    // Anonymous private mappings include the stack and the heap.
+    if vm_mapping.path().is_none() {
+        return whole_if(filter.contains(CoreDumpFilter::ANON_PRIVATE));
+    }
+
+    // Writable private file mappings may contain anonymous copies of the file pages.
+    let perms = vm_mapping.perms();
+    if perms.contains(VmPerms::WRITE) && filter.contains(CoreDumpFilter::ANON_PRIVATE) {
+        return DumpSize::Whole;
+    }
+    if filter.contains(CoreDumpFilter::MAPPED_PRIVATE) {
+        return DumpSize::Whole;
+    }
+
+    if filter.contains(CoreDumpFilter::ELF_HEADERS)
+        && vm_mapping.vmo_offset() == Some(0)
+        && perms.contains(VmPerms::READ)
+    {
+        return DumpSize::ElfHeader;
+    }
+
+    DumpSize::None
+}
+
+fn collect_mappings(vmar: &Vmar, filter: CoreDumpFilter, ctx: &Context) -> Vec<DumpedMapping> {
+    let mut mappings = {
+        let fs_ref = ctx.thread_local.borrow_fs();
+        let path_resolver = fs_ref.resolver().read();
+
+        let guard = vmar.query(VMAR_LOWEST_ADDR..VMAR_CAP_ADDR);
+        guard
+            .iter()
+            .map(|vm_mapping| DumpedMapping {
+                range: vm_mapping.map_to_addr()..vm_mapping.map_end(),
+                perms: vm_mapping.perms(),
+                dump_size: dump_size_of(vm_mapping, filter),
+                file: vm_mapping.path().map(|path| {
+                    let offset = vm_mapping.vmo_offset().unwrap_or(0);
+                    let name = path_resolver.make_abs_path(path).into_string();
+                    (offset / PAGE_SIZE, name)
+                }),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The pages are read after releasing the lock of the VMAR,
+    // since reading them may need to fault them in.
+    let mut page = vec![0u8; PAGE_SIZE];
+    for mapping in mappings.iter_mut() {
+        if mapping.dump_size != DumpSize::ElfHeader {
+            continue;
+        }
+
+        let mut page_writer = VmWriter::from(page.as_mut_slice()).to_fallible();
+        let has_elf_header = vmar
+            .read_page_for_dump(mapping.range.start, &mut page_writer)
+            .is_ok_and(|_| ElfHeader::has_magic(&page));
+        if !has_elf_header {
+            mapping.dump_size = DumpSize::None;
+        }
+    }
+
+    mappings
+}
+
+fn write_core(
+    writer: &mut CoreWriter,
+    sig_info: &siginfo_t,
+    threads: &[ThreadCore],
+    filter: CoreDumpFilter,
+    vmar: &Vmar,
+    ctx: &Context,
+) -> Result<()> {
+    let mappings = collect_mappings(vmar, filter, ctx);
+    let notes = build_notes(sig_info, threads, &mappings, ctx)?;
+
+    let nr_phdrs = mappings.len() + 1;
+    // Core files with too many program headers need an extra section header
+    // to store the number, which is not supported yet.
+    let phnum = u16::try_from(nr_phdrs)
+        .ok()
+        .filter(|phnum| *phnum < u16::MAX)
+        .ok_or_else(|| Error::with_message(Errno::EFBIG, "too many mappings to dump"))?;
+
+    let notes_offset = size_of::<ElfHeader>() + size_of::<ElfProgramHeader>() * nr_phdrs;
+    let notes_len = notes.as_bytes().len();
+    let data_offset = (notes_offset + notes_len).next_multiple_of(PAGE_SIZE);
+
+    writer.write(ElfHeader::new_core(phnum).as_bytes(), ctx)?;
+
+    let note_phdr = ElfProgramHeader {
+        type_: PT_NOTE,
+        offset: notes_offset as u64,
+        filesz: notes_len as u64,
+        ..Default::default()
+    };
+    writer.write(note_phdr.as_bytes(), ctx)?;
+
+    let mut offset = data_offset;
+    for mapping in mappings.iter() {
+        let mut flags = 0;
+        if mapping.perms.contains(VmPerms::READ) {
+            flags |= PF_R;
+        }
+        if mapping.perms.contains(VmPerms::WRITE) {
+            flags |= PF_W;
+        }
+        if mapping.perms.contains(VmPerms::EXEC) {
+            flags |= PF_X;
+        }
+
+        let dump_len = mapping.dump_len();
+        let load_phdr = ElfProgramHeader {
+            type_: PT_LOAD,
+            flags,
+            offset: offset as u64,
+            vaddr: mapping.range.start as u64,
+            paddr: 0,
+            filesz: dump_len as u64,
+            memsz: mapping.range.len() as u64,
+            align: PAGE_SIZE as u64,
+        };
+        writer.write(load_phdr.as_bytes(), ctx)?;
+
+        offset += dump_len;
+    }
+
+    writer.write(notes.as_bytes(), ctx)?;
+    writer.pad_to(data_offset, ctx)?;
+
+    let mut page = vec![0u8; PAGE_SIZE];
+    for mapping in mappings.iter() {
+        let dump_range = mapping.range.start..mapping.range.start + mapping.dump_len();
+        for page_addr in dump_range.step_by(PAGE_SIZE) {
+            let mut page_writer = VmWriter::from(page.as_mut_slice()).to_fallible();
+            if vmar
+                .read_page_for_dump(page_addr, &mut page_writer)
+                .is_err()
+            {
+                // The page cannot be read, e.g., because it is beyond the end
+                // of the backing file. Zeros are written to keep the layout.
+                page.fill(0);
+            }
+            writer.write(&page, ctx)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the notes for all threads and the process.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_elf.c#L1804-L1884>
+fn build_notes(
+    sig_info: &siginfo_t,
+    threads: &[ThreadCore],
+    mappings: &[DumpedMapping],
+    ctx: &Context,
+) -> Result<NoteBuf> {
+    let mut notes = NoteBuf::new();
+
+    for (index, thread) in threads.iter().enumerate() {
+        let prstatus = build_prstatus(sig_info, thread, ctx);
+        notes.push(NOTE_NAME_CORE, NT_PRSTATUS, prstatus.as_bytes());
+
+        // The process-wide notes follow the status of the dumping thread.
+        if index == 0 {
+            let prpsinfo = build_prpsinfo(ctx)?;
+            notes.push(NOTE_NAME_CORE, NT_PRPSINFO, prpsinfo.as_bytes());
+            notes.push(NOTE_NAME_CORE, NT_SIGINFO, sig_info.as_bytes());
+            notes.push(NOTE_NAME_CORE, NT_AUXV, &read_auxv(ctx)?);
+            notes.push(NOTE_NAME_CORE, NT_FILE, &build_file_note(mappings));
+        }
+
+        thread.regs.push_notes(&mut notes);
+    }
+
+    Ok(notes)
+}
+
+fn build_prstatus(sig_info: &siginfo_t, thread: &ThreadCore, ctx: &Context) -> ElfPrStatus {
+    let process = ctx.process;
+    let (children_user_time, children_kernel_time) = process.reaped_children_stats().lock().get();
+
+    ElfPrStatus {
+        info: ElfSigInfo {
+            signo: sig_info.si_signo,
+            code: sig_info.si_code,
+            errno: sig_info.si_errno,
+        },
+        cursig: sig_info.si_signo as u16,
+        sigpend: u64::from(thread.sig_pending),
+        sighold: u64::from(thread.sig_mask),
+        pid: process.nr_in_ns(thread.tid) as i32,
+        ppid: process.nr_in_ns(process.parent().pid()) as i32,
+        pgrp: process.nr_in_ns(process.pgid()) as i32,
+        sid: process.nr_in_ns(process.sid()) as i32,
+        utime: timeval_t::from(thread.user_time),
+        stime: timeval_t::from(thread.kernel_time),
+        cutime: timeval_t::from(children_user_time),
+        cstime: timeval_t::from(children_kernel_time),
+        reg: thread.regs.general(),
+        fpvalid: 1,
+        ..Default::default()
+    }
+}
+
+fn build_prpsinfo(ctx: &Context) -> Result<ElfPrPsInfo> {
+    use core::sync::atomic::Ordering;
+
+    let process = ctx.process;
+    let credentials = ctx.posix_thread.credentials();
+
+    let mut prpsinfo = ElfPrPsInfo {
+        // The dumping process is running.
+        state: 0,
+        sname: b'R',
+        zomb: 0,
+        nice: process.nice().load(Ordering::Relaxed).value().get(),
+        uid: u32::from(credentials.ruid()),
+        gid: u32::from(credentials.rgid()),
+        pid: process.nr_in_ns(process.pid()) as i32,
+        ppid: process.nr_in_ns(process.parent().pid()) as i32,
+        pgrp: process.nr_in_ns(process.pgid()) as i32,
+        sid: process.nr_in_ns(process.sid()) as i32,
+        ..Default::default()
+    };
+
+    {
+        let thread_name = ctx.posix_thread.thread_name().lock();
+        let name = thread_name.name().to_bytes();
+        let len = name.len().min(prpsinfo.fname.len() - 1);
+        prpsinfo.fname[..len].copy_from_slice(&name[..len]);
+    }
+
+    // The arguments are separated by spaces and always nul-terminated.
+    let vmar_guard = process.lock_vmar();
+    if let Some(init_stack_reader) = vmar_guard.init_stack_reader() {
+        let mut psargs_writer =
+            VmWriter::from(&mut prpsinfo.psargs[..ELF_PRARGSZ - 1]).to_fallible();
+        let len = init_stack_reader.argv(0, &mut psargs_writer)?;
+        for byte in prpsinfo.psargs[..len].iter_mut() {
+            if *byte == 0 {
+                *byte = b' ';
+            }
+        }
+    }
+
+    Ok(prpsinfo)
+}
+
+fn read_auxv(ctx: &Context) -> Result<Vec<u8>> {
+    let mut auxv = vec![0u8; PAGE_SIZE];
+
+    let vmar_guard = ctx.process.lock_vmar();
+    let Some(init_stack_reader) = vmar_guard.init_stack_reader() else {
+        return Ok(Vec::new());
+    };
+    let mut auxv_writer = VmWriter::from(auxv.as_mut_slice()).to_fallible();
+    let len = init_stack_reader.auxv(0, &mut auxv_writer)?;
+
+    auxv.truncate(len);
+    Ok(auxv)
+}
+
+/// Builds the note that describes the file-backed mappings.
+///
+/// The note starts with the number of mappings and the page size, followed by
+/// the start address, the end address, and the page offset of each mapping,
+/// and ends with the nul-terminated file names.
+fn build_file_note(mappings: &[DumpedMapping]) -> Vec<u8> {
+    let file_mappings = mappings
+        .iter()
+        .filter_map(|mapping| {
+            let (page_offset, name) = mapping.file.as_ref()?;
+            Some((&mapping.range, *page_offset, name))
+        })
+        .collect::<Vec<_>>();
+
+    let mut note = Vec::new();
+    note.extend_from_slice(&(file_mappings.len() as u64).to_ne_bytes());
+    note.extend_from_slice(&(PAGE_SIZE as u64).to_ne_bytes());
+    for (range, page_offset, _) in file_mappings.iter() {
+        note.extend_from_slice(&(range.start as u64).to_ne_bytes());
+        note.extend_from_slice(&(range.end as u64).to_ne_bytes());
+        note.extend_from_slice(&(*page_offset as u64).to_ne_bytes());
+    }
+    for (_, _, name) in file_mappings.iter() {
+        note.extend_from_slice(name.as_bytes());
+        note.push(0);
+    }
+
+    note
+}
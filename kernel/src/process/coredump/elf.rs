@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The layout of ELF core files on x86-64.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_elf.c#L1447-L2102>
+
+use ostd::{arch::cpu::context::UserContext, const_assert};
+
+use crate::{
+    arch::ptrace::{CUserRegsStruct, RegSetType, USER_FPREGS_SIZE, read_fpregs, read_xstate},
+    prelude::*,
+    time::timeval_t,
+};
+
+const ELF_MAGIC: [u8; 4] = *b"\x7fELF";
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EV_CURRENT: u8 = 1;
+const ET_CORE: u16 = 4;
+const EM_X86_64: u16 = 62;
+
+pub(super) const PT_LOAD: u32 = 1;
+pub(super) const PT_NOTE: u32 = 4;
+
+pub(super) const PF_X: u32 = 1;
+pub(super) const PF_W: u32 = 2;
+pub(super) const PF_R: u32 = 4;
+
+pub(super) const NT_PRSTATUS: u32 = RegSetType::NT_PRSTATUS as u32;
+pub(super) const NT_PRFPREG: u32 = RegSetType::NT_PRFPREG as u32;
+pub(super) const NT_PRPSINFO: u32 = 3;
+pub(super) const NT_AUXV: u32 = 6;
+pub(super) const NT_X86_XSTATE: u32 = RegSetType::NT_X86_XSTATE as u32;
+pub(super) const NT_SIGINFO: u32 = 0x53494749;
+pub(super) const NT_FILE: u32 = 0x46494c45;
+
+/// The note name for the notes defined by the System V ABI and the core notes.
+pub(super) const NOTE_NAME_CORE: &str = "CORE";
+/// The note name for the Linux-specific notes.
+pub(super) const NOTE_NAME_LINUX: &str = "LINUX";
+
+/// The maximum length of the arguments in [`ElfPrPsInfo`].
+pub(super) const ELF_PRARGSZ: usize = 80;
+
+/// The ELF file header (`Elf64_Ehdr`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub(super) struct ElfHeader {
+    ident: [u8; 16],
+    type_: u16,
+    machine: u16,
+    version: u32,
+    entry: u64,
+    phoff: u64,
+    shoff: u64,
+    flags: u32,
+    ehsize: u16,
+    phentsize: u16,
+    phnum: u16,
+    shentsize: u16,
+    shnum: u16,
+    shstrndx: u16,
+}
+
+impl ElfHeader {
+    /// Creates the header of a core file with `phnum` program headers
+    /// following the header immediately.
+    pub(super) fn new_core(phnum: u16) -> Self {
+        let mut ident = [0; 16];
+        ident[..4].copy_from_slice(&ELF_MAGIC);
+        ident[4] = ELFCLASS64;
+        ident[5] = ELFDATA2LSB;
+        ident[6] = EV_CURRENT;
+
+        Self {
+            ident,
+            type_: ET_CORE,
+            machine: EM_X86_64,
+            version: EV_CURRENT as u32,
+            phoff: size_of::<Self>() as u64,
+            ehsize: size_of::<Self>() as u16,
+            phentsize: size_of::<ElfProgramHeader>() as u16,
+            phnum,
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether `bytes` starts with the ELF magic number.
+    pub(super) fn has_magic(bytes: &[u8]) -> bool {
+        bytes.starts_with(&ELF_MAGIC)
+    }
+}
+
+/// The ELF program header (`Elf64_Phdr`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub(super) struct ElfProgramHeader {
+    pub(super) type_: u32,
+    pub(super) flags: u32,
+    pub(super) offset: u64,
+    pub(super) vaddr: u64,
+    pub(super) paddr: u64,
+    pub(super) filesz: u64,
+    pub(super) memsz: u64,
+    pub(super) align: u64,
+}
+
+/// The ELF note header (`Elf64_Nhdr`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct ElfNoteHeader {
+    namesz: u32,
+    descsz: u32,
+    type_: u32,
+}
+
+/// The signal information in [`ElfPrStatus`] (`struct elf_siginfo`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub(super) struct ElfSigInfo {
+    pub(super) signo: i32,
+    pub(super) code: i32,
+    pub(super) errno: i32,
+}
+
+/// The status of a thread (`struct elf_prstatus`).
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub(super) struct ElfPrStatus {
+    pub(super) info: ElfSigInfo,
+    pub(super) cursig: u16,
+    pub(super) sigpend: u64,
+    pub(super) sighold: u64,
+    pub(super) pid: i32,
+    pub(super) ppid: i32,
+    pub(super) pgrp: i32,
+    pub(super) sid: i32,
+    pub(super) utime: timeval_t,
+    pub(super) stime: timeval_t,
+    pub(super) cutime: timeval_t,
+    pub(super) cstime: timeval_t,
+    pub(super) reg: CUserRegsStruct,
+    pub(super) fpvalid: i32,
+}
+
+/// The information of a process (`struct elf_prpsinfo`).
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub(super) struct ElfPrPsInfo {
+    pub(super) state: u8,
+    pub(super) sname: u8,
+    pub(super) zomb: u8,
+    pub(super) nice: i8,
+    pub(super) flag: u64,
+    pub(super) uid: u32,
+    pub(super) gid: u32,
+    pub(super) pid: i32,
+    pub(super) ppid: i32,
+    pub(super) pgrp: i32,
+    pub(super) sid: i32,
+    pub(super) fname: [u8; 16],
+    pub(super) psargs: [u8; ELF_PRARGSZ],
+}
+
+impl Default for ElfPrPsInfo {
+    fn default() -> Self {
+        Self::new_zeroed()
+    }
+}
+
+const_assert!(size_of::<ElfPrStatus>() == 336);
+const_assert!(size_of::<ElfPrPsInfo>() == 136);
+
+/// The registers of a thread that are recorded in a core file.
+pub(super) struct ThreadRegs {
+    general: CUserRegsStruct,
+    fpregs: [u8; USER_FPREGS_SIZE],
+    xstate: Option<Vec<u8>>,
+}
+
+impl ThreadRegs {
+    /// Captures the registers of the current thread.
+    pub(super) fn capture(ctx: &Context, user_ctx: &UserContext) -> Self {
+        let supp = ctx.thread_local.supp_user_context();
+
+        let mut general = CUserRegsStruct::from_regs(
+            user_ctx.general_regs(),
+            supp.fs_base().get(),
+            supp.gs_base().get(),
+        );
+        // Outside a system call, `orig_rax` is -1.
+        general.orig_rax = ctx.thread_local.orig_syscall_ret().unwrap_or(usize::MAX);
+
+        let fpu_context = supp.fpu().get();
+        let fpregs = read_fpregs(&fpu_context);
+        let xstate = read_xstate(&fpu_context, usize::MAX).ok();
+
+        Self {
+            general,
+            fpregs,
+            xstate,
+        }
+    }
+
+    /// Returns the general-purpose registers.
+    pub(super) fn general(&self) -> CUserRegsStruct {
+        self.general
+    }
+
+    /// Appends the notes of the registers other than the general-purpose ones.
+    pub(super) fn push_notes(&self, notes: &mut NoteBuf) {
+        notes.push(NOTE_NAME_CORE, NT_PRFPREG, &self.fpregs);
+        if let Some(xstate) = self.xstate.as_ref() {
+            notes.push(NOTE_NAME_LINUX, NT_X86_XSTATE, xstate);
+        }
+    }
+}
+
+/// A buffer of ELF notes.
+pub(super) struct NoteBuf(Vec<u8>);
+
+impl NoteBuf {
+    pub(super) fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Appends a note.
+    ///
+    /// The name is nul-terminated. Both the name and the descriptor are padded
+    /// to 4-byte boundaries.
+    pub(super) fn push(&mut self, name: &str, type_: u32, desc: &[u8]) {
+        let header = ElfNoteHeader {
+            namesz: (name.len() + 1) as u32,
+            descsz: desc.len() as u32,
+            type_,
+        };
+        self.0.extend_from_slice(header.as_bytes());
+
+        self.0.extend_from_slice(name.as_bytes());
+        self.0.push(0);
+        self.pad();
+
+        self.0.extend_from_slice(desc);
+        self.pad();
+    }
+
+    fn pad(&mut self) {
+        let padded_len = self.0.len().next_multiple_of(4);
+        self.0.resize(padded_len, 0);
+    }
+
+    pub(super) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
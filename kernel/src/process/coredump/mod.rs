@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Core dumps.
+//!
+//! When a process is killed by a signal whose default action is to dump core,
+//! an ELF core file describing the threads and the memory of the process is
+//! written to the destination specified by `/proc/sys/kernel/core_pattern`.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/core.5.html>
+
+use core::sync::atomic::{AtomicU8, AtomicU32};
+
+use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
+
+#[cfg(target_arch = "x86_64")]
+mod dump;
+#[cfg(target_arch = "x86_64")]
+mod elf;
+#[cfg(target_arch = "x86_64")]
+mod name;
+mod pattern;
+
+#[cfg(target_arch = "x86_64")]
+pub(super) use dump::{CoreDumpState, do_coredump};
+pub use pattern::{CORENAME_MAX_SIZE, core_pattern, set_core_pattern};
+
+use crate::prelude::*;
+#[cfg(not(target_arch = "x86_64"))]
+use crate::process::{TermStatus, signal::c_types::siginfo_t};
+
+/// Whether a process is allowed to produce core dumps.
+///
+/// This attribute is managed by `PR_GET_DUMPABLE` and `PR_SET_DUMPABLE`.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum Dumpable {
+    Disable = 0, /* No setuid dumping */
+    User = 1,    /* Dump as user of process */
+    Root = 2,    /* Dump as root */
+}
+
+impl From<Dumpable> for u8 {
+    fn from(value: Dumpable) -> Self {
+        value as _
+    }
+}
+
+define_atomic_version_of_integer_like_type!(Dumpable, try_from = true, {
+    /// An atomic version of `Dumpable`.
+    #[derive(Debug)]
+    pub struct AtomicDumpable(AtomicU8);
+});
+
+bitflags! {
+    /// The types of memory mappings that are written to core dumps.
+    ///
+    /// This attribute is managed by `/proc/[pid]/coredump_filter`.
+    pub struct CoreDumpFilter: u32 {
+        /// Anonymous private mappings.
+        const ANON_PRIVATE     = 1 << 0;
+        /// Anonymous shared mappings.
+        const ANON_SHARED      = 1 << 1;
+        /// File-backed private mappings.
+        const MAPPED_PRIVATE   = 1 << 2;
+        /// File-backed shared mappings.
+        const MAPPED_SHARED    = 1 << 3;
+        /// The first page of file-backed mappings of ELF files.
+        const ELF_HEADERS      = 1 << 4;
+        /// Private huge pages.
+        const HUGETLB_PRIVATE  = 1 << 5;
+        /// Shared huge pages.
+        const HUGETLB_SHARED   = 1 << 6;
+        /// Private DAX pages.
+        const DAX_PRIVATE      = 1 << 7;
+        /// Shared DAX pages.
+        const DAX_SHARED       = 1 << 8;
+    }
+}
+
+impl Default for CoreDumpFilter {
+    fn default() -> Self {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/sched/coredump.h#L73-L79>
+        Self::ANON_PRIVATE | Self::ANON_SHARED | Self::ELF_HEADERS | Self::HUGETLB_PRIVATE
+    }
+}
+
+impl From<u32> for CoreDumpFilter {
+    fn from(value: u32) -> Self {
+        Self::from_bits_truncate(value)
+    }
+}
+
+impl From<CoreDumpFilter> for u32 {
+    fn from(value: CoreDumpFilter) -> Self {
+        value.bits()
+    }
+}
+
+define_atomic_version_of_integer_like_type!(CoreDumpFilter, {
+    /// An atomic version of `CoreDumpFilter`.
+    #[derive(Debug)]
+    pub struct AtomicCoreDumpFilter(AtomicU32);
+});
+
+/// Dumps the core of the current process, which is being killed by a signal.
+///
+/// Core dumps are only supported on x86-64 for now. On other architectures,
+/// this function does nothing but returns the status for being killed.
+#[cfg(not(target_arch = "x86_64"))]
+pub(super) fn do_coredump(
+    sig_info: &siginfo_t,
+    _ctx: &Context,
+    _user_ctx: &ostd::arch::cpu::context::UserContext,
+) -> TermStatus {
+    use crate::process::signal::sig_num::SigNum;
+
+    TermStatus::Killed(SigNum::try_from(sig_info.si_signo as u8).unwrap())
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The names of core files, which are expanded from the core pattern.
+
+use core::fmt::Write;
+
+use super::{Dumpable, pattern::core_pattern};
+use crate::{
+    prelude::*,
+    process::{ResourceType, signal::sig_num::SigNum},
+    time::SystemTime,
+};
+
+/// The destination of a core dump.
+pub(super) enum CoreName {
+    /// A file at the path.
+    File(String),
+    /// A pipe to a user-mode helper with the arguments.
+    Pipe(Vec<CString>),
+}
+
+impl CoreName {
+    /// Expands the core pattern for the current process killed by `sig_num`.
+    ///
+    /// Returns `None` if the core pattern is empty, which disables core dumps.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/coredump.c#L216-L393>
+    pub(super) fn format(ctx: &Context, sig_num: SigNum, dumpable: Dumpable) -> Option<Self> {
+        let pattern = core_pattern();
+        let pattern = String::from_utf8_lossy(&pattern);
+
+        let (is_pipe, pattern) = match pattern.strip_prefix('|') {
+            Some(helper) => (true, helper.trim_start()),
+            None => (false, pattern.as_ref()),
+        };
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let mut args = Vec::new();
+        let mut name = String::new();
+        let mut chars = pattern.chars();
+
+        while let Some(ch) = chars.next() {
+            // In the pipe form, the arguments are split at spaces before
+            // expanding the specifiers, so the expanded values never split.
+            if is_pipe && ch == ' ' {
+                if !name.is_empty() {
+                    args.push(core::mem::take(&mut name));
+                }
+                continue;
+            }
+
+            if ch != '%' {
+                name.push(ch);
+                continue;
+            }
+
+            // Unknown specifiers and a trailing `%` are dropped.
+            let Some(specifier) = chars.next() else {
+                break;
+            };
+            expand_specifier(&mut name, specifier, ctx, sig_num, dumpable);
+        }
+
+        if !is_pipe {
+            return Some(Self::File(name));
+        }
+
+        if !name.is_empty() {
+            args.push(name);
+        }
+        let args = args
+            .into_iter()
+            .map(|arg| CString::new(arg.replace('\0', "")).unwrap())
+            .collect();
+        Some(Self::Pipe(args))
+    }
+}
+
+fn expand_specifier(
+    name: &mut String,
+    specifier: char,
+    ctx: &Context,
+    sig_num: SigNum,
+    dumpable: Dumpable,
+) {
+    let process = ctx.process;
+    let credentials = ctx.posix_thread.credentials();
+
+    // The results of writing to a `String` are always `Ok`.
+    let _ = match specifier {
+        '%' => write!(name, "%"),
+        // The PID in the PID namespace of the process.
+        'p' => write!(name, "{}", process.nr_in_ns(process.pid())),
+        // The PID in the initial PID namespace.
+        'P' => write!(name, "{}", process.pid()),
+        // The TID in the PID namespace of the process.
+        'i' => write!(name, "{}", process.nr_in_ns(ctx.posix_thread.tid())),
+        // The TID in the initial PID namespace.
+        'I' => write!(name, "{}", ctx.posix_thread.tid()),
+        'u' => write!(name, "{}", u32::from(credentials.ruid())),
+        'g' => write!(name, "{}", u32::from(credentials.rgid())),
+        'd' => write!(name, "{}", dumpable as u8),
+        's' => write!(name, "{}", sig_num.as_u8()),
+        't' => {
+            let now = SystemTime::now()
+                .duration_since(&SystemTime::UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+            write!(name, "{}", now)
+        }
+        'h' => {
+            let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+            let uts_name = ns_proxy.unwrap().uts_ns().uts_name();
+            write_escaped(name, &String::from_utf8_lossy(uts_name.nodename()))
+        }
+        'e' => {
+            let thread_name = ctx.posix_thread.thread_name().lock();
+            write_escaped(name, &thread_name.name().to_string_lossy())
+        }
+        'E' => {
+            let fs_ref = ctx.thread_local.borrow_fs();
+            let path_resolver = fs_ref.resolver().read();
+            let user_space = ctx.user_space();
+            let executable_file = user_space.vmar().process_vm().executable_file();
+            let executable_path = path_resolver.make_abs_path(executable_file).into_string();
+            write_escaped(name, &executable_path)
+        }
+        'c' => {
+            let core_limit = process
+                .resource_limits()
+                .get_rlimit(ResourceType::RLIMIT_CORE)
+                .get_cur();
+            write!(name, "{}", core_limit)
+        }
+        _ => Ok(()),
+    };
+}
+
+/// Writes `value` with slashes replaced by exclamation marks,
+/// so that the value never introduces new path components.
+fn write_escaped(name: &mut String, value: &str) -> core::fmt::Result {
+    name.extend(value.chars().map(|ch| if ch == '/' { '!' } else { ch }));
+    Ok(())
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The core pattern, which specifies where core dumps are written.
+
+use crate::prelude::*;
+
+/// The maximum length of the core pattern, including the trailing nul byte.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/binfmts.h#L105>
+pub const CORENAME_MAX_SIZE: usize = 128;
+
+static CORE_PATTERN: SpinLock<CorePattern> = SpinLock::new(CorePattern::new_default());
+
+/// The core pattern stored in a fixed-size buffer.
+struct CorePattern {
+    buf: [u8; CORENAME_MAX_SIZE - 1],
+    len: usize,
+}
+
+impl CorePattern {
+    const fn new_default() -> Self {
+        const DEFAULT_PATTERN: &[u8] = b"core";
+
+        let mut buf = [0; CORENAME_MAX_SIZE - 1];
+        let mut i = 0;
+        while i < DEFAULT_PATTERN.len() {
+            buf[i] = DEFAULT_PATTERN[i];
+            i += 1;
+        }
+
+        Self {
+            buf,
+            len: DEFAULT_PATTERN.len(),
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// Returns the core pattern.
+pub fn core_pattern() -> Vec<u8> {
+    CORE_PATTERN.lock().as_bytes().to_vec()
+}
+
+/// Sets the core pattern.
+///
+/// Following Linux, the pattern ends at the first newline character, and a
+/// pattern that is too long is silently truncated.
+pub fn set_core_pattern(pattern: &[u8]) {
+    let pattern = pattern
+        .split(|byte| *byte == b'\n')
+        .next()
+        .unwrap_or_default();
+    let len = pattern.len().min(CORENAME_MAX_SIZE - 1);
+
+    let mut core_pattern = CORE_PATTERN.lock();
+    core_pattern.buf[..len].copy_from_slice(&pattern[..len]);
+    core_pattern.len = len;
+}
@@ -1,18 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::Ordering;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
 
 use super::{
-    Gid, SecureBits, Uid, group::AtomicGid, secure_bits::AtomicSecureBits, user::AtomicUid,
+    Gid, SecureBits, Uid,
+    capabilities::{AtomicCapSet, CapSet},
+    exec_credentials::ExecCredentials,
+    file_capabilities::FileCapabilities,
+    group::AtomicGid,
+    secure_bits::AtomicSecureBits,
+    user::AtomicUid,
 };
 use crate::{
     prelude::*,
-    process::credentials::{
-        AMBIENT_CAPSET,
-        capabilities::{AtomicCapSet, CapSet},
-    },
+    security::{keys::ProcessKeyrings, lsm::SecurityBlob},
 };
 
 #[derive(Debug)]
@@ -72,9 +75,38 @@ pub(super) struct Credentials_ {
     /// Capabilities that limit privileges granted during `execve()` and may be added to the
     /// inheritable set.
     bounding_capset: AtomicCapSet,
+    /// Capabilities that are preserved across `execve()` of a program without file capabilities.
+    ///
+    /// It is always a subset of both the permitted set and the inheritable set.
+    ambient_capset: AtomicCapSet,
 
     /// Secure bits.
     securebits: AtomicSecureBits,
+
+    /// Whether `execve()` is prevented from granting privileges.
+    ///
+    /// Once set, this flag cannot be unset. It is inherited by child threads and preserved
+    /// across `execve()`.
+    no_new_privs: AtomicBool,
+
+    /// The keyrings of the key retention service.
+    ///
+    /// The session keyring is inherited by child threads and preserved across `execve()`. The
+    /// thread keyring and the process keyring are not.
+    keyrings: ProcessKeyrings,
+
+    /// The login user ID of the audit subsystem.
+    ///
+    /// It identifies the user who logged in, which is kept unchanged when the process switches to
+    /// other users. It is inherited by child threads and preserved across `execve()`.
+    loginuid: AtomicUid,
+    /// The session ID of the audit subsystem, which is assigned when the login user ID is set.
+    sessionid: AtomicU32,
+
+    /// The security data of the LSM modules (e.g., the Landlock domain).
+    ///
+    /// It is inherited by child threads and preserved across `execve()`.
+    security: SecurityBlob,
 }
 
 impl Credentials_ {
@@ -99,7 +131,13 @@ impl Credentials_ {
             permitted_capset: AtomicCapSet::new(capset),
             effective_capset: AtomicCapSet::new(capset),
             bounding_capset: AtomicCapSet::new(CapSet::all()),
+            ambient_capset: AtomicCapSet::new(CapSet::empty()),
             securebits: AtomicSecureBits::new(SecureBits::new_empty()),
+            no_new_privs: AtomicBool::new(false),
+            keyrings: ProcessKeyrings::new(),
+            loginuid: AtomicUid::new(Uid::INVALID),
+            sessionid: AtomicU32::new(u32::MAX),
+            security: SecurityBlob::new(),
         }
     }
 
@@ -179,42 +217,6 @@ impl Credentials_ {
         Ok(old_fsuid)
     }
 
-    pub(super) fn set_euid(&self, euid: Uid) {
-        self.set_resuid_unchecked(None, Some(euid), None);
-    }
-
-    pub(super) fn set_suid(&self, suid: Uid) {
-        self.set_resuid_unchecked(None, None, Some(suid));
-
-        // Begin to adjust capabilities.
-        // Reference: The "Transformation of capabilities during execve()" section and
-        // the "Capabilities and execution of programs by root" section in
-        // <https://man7.org/linux/man-pages/man7/capabilities.7.html>.
-
-        let (file_permitted, file_inheritable) =
-            if (self.euid().is_root() || self.ruid().is_root()) && !self.securebits().no_root() {
-                (CapSet::all(), CapSet::all())
-            } else {
-                // TODO: Get the file capabilities from the file system.
-                (CapSet::empty(), CapSet::empty())
-            };
-
-        let file_effective = if self.euid().is_root() && !self.securebits().no_root() {
-            CapSet::all()
-        } else {
-            // TODO: Get the file capabilities from the file system.
-            CapSet::empty()
-        };
-
-        let new_permitted = (self.inheritable_capset() & file_inheritable)
-            | (file_permitted & self.bounding_capset())
-            | AMBIENT_CAPSET;
-        let new_effective = (file_effective & new_permitted) | (!file_effective & AMBIENT_CAPSET);
-
-        self.set_permitted_capset(new_permitted);
-        self.set_effective_capset(new_effective);
-    }
-
     // For `setreuid`, the real UID can *NOT* be set to the old saved-set user ID,
     // For `setresuid`, the real UID can be set to the old saved-set user ID.
     fn check_uid_perm(
@@ -308,8 +310,11 @@ impl Credentials_ {
         if had_root && all_nonroot && !self.keep_capabilities() {
             self.set_permitted_capset(CapSet::empty());
             self.set_inheritable_capset(CapSet::empty());
-            // TODO: Clear ambient capabilities when we support it. Note that ambient capabilities
-            // should be cleared even if `keep_capabilities` is true.
+        }
+        // Unlike the permitted set, the ambient set is cleared even if `keep_capabilities` is
+        // true.
+        if had_root && all_nonroot {
+            self.clear_ambient_capset();
         }
 
         if old_euid.is_root() && !new_euid.is_root() {
@@ -420,14 +425,6 @@ impl Credentials_ {
         Ok(old_fsgid)
     }
 
-    pub(super) fn set_egid(&self, egid: Gid) {
-        self.set_resgid_unchecked(None, Some(egid), None);
-    }
-
-    pub(super) fn set_sgid(&self, sgid: Gid) {
-        self.set_resgid_unchecked(None, None, Some(sgid));
-    }
-
     // For `setregid`, the real GID can *NOT* be set to the old saved-set GID,
     // For `setresgid`, the real GID can be set to the old saved-set GID.
     fn check_gid_perm(
@@ -525,14 +522,26 @@ impl Credentials_ {
         self.bounding_capset.load(Ordering::Relaxed)
     }
 
+    pub(super) fn ambient_capset(&self) -> CapSet {
+        self.ambient_capset.load(Ordering::Relaxed)
+    }
+
     pub(super) fn set_inheritable_capset(&self, inheritable_capset: CapSet) {
         self.inheritable_capset
             .store(inheritable_capset, Ordering::Relaxed);
+
+        // A capability that is no longer inheritable cannot be ambient.
+        let ambient_capset = self.ambient_capset() & inheritable_capset;
+        self.set_ambient_capset(ambient_capset);
     }
 
     pub(super) fn set_permitted_capset(&self, permitted_capset: CapSet) {
         self.permitted_capset
             .store(permitted_capset, Ordering::Relaxed);
+
+        // A capability that is no longer permitted cannot be ambient.
+        let ambient_capset = self.ambient_capset() & permitted_capset;
+        self.set_ambient_capset(ambient_capset);
     }
 
     pub(super) fn set_effective_capset(&self, effective_capset: CapSet) {
@@ -545,6 +554,38 @@ impl Credentials_ {
             .store(bounding_capset, Ordering::Relaxed);
     }
 
+    fn set_ambient_capset(&self, ambient_capset: CapSet) {
+        self.ambient_capset.store(ambient_capset, Ordering::Relaxed);
+    }
+
+    pub(super) fn raise_ambient_capability(&self, capability: CapSet) -> Result<()> {
+        if !(self.permitted_capset() & self.inheritable_capset()).contains(capability) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "only capabilities that are both permitted and inheritable can be ambient"
+            );
+        }
+        if self.securebits().no_cap_ambient_raise() {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "raising ambient capabilities is disabled by the secure bits"
+            );
+        }
+
+        let ambient_capset = self.ambient_capset() | capability;
+        self.set_ambient_capset(ambient_capset);
+        Ok(())
+    }
+
+    pub(super) fn lower_ambient_capability(&self, capability: CapSet) {
+        let ambient_capset = self.ambient_capset() - capability;
+        self.set_ambient_capset(ambient_capset);
+    }
+
+    pub(super) fn clear_ambient_capset(&self) {
+        self.set_ambient_capset(CapSet::empty());
+    }
+
     pub(super) fn drop_bounding_capability(&self, capability: CapSet) -> Result<()> {
         if !self.effective_capset().contains(CapSet::SETPCAP) {
             return_errno_with_message!(
@@ -589,6 +630,164 @@ impl Credentials_ {
 
         self.securebits.try_store(securebits, Ordering::Relaxed)
     }
+
+    //  ******* Execution methods *******
+
+    /// Computes the credentials for executing a program.
+    ///
+    /// `file_uid` and `file_gid` are the owner and the group of the program if it has the
+    /// set-user-ID and set-group-ID bits, respectively. If `is_ptraced_unsafely` is true, the
+    /// thread is traced by a tracer that does not have `CAP_SYS_PTRACE`.
+    ///
+    /// Reference: The "Transformation of capabilities during execve()" section and the
+    /// "Capabilities and execution of programs by root" section in
+    /// <https://man7.org/linux/man-pages/man7/capabilities.7.html>, and the
+    /// `cap_bprm_creds_from_file` function in
+    /// <https://elixir.bootlin.com/linux/v6.16/source/security/commoncap.c>.
+    pub(super) fn prepare_exec(
+        &self,
+        file_uid: Option<Uid>,
+        file_gid: Option<Gid>,
+        file_caps: Option<&FileCapabilities>,
+        is_ptraced_unsafely: bool,
+    ) -> ExecCredentials {
+        let ruid = self.ruid();
+        let rgid = self.rgid();
+        let no_new_privs = self.no_new_privs();
+
+        // The set-user-ID and set-group-ID bits are ignored if `no_new_privs` is set.
+        let (euid, egid) = if no_new_privs {
+            (self.euid(), self.egid())
+        } else {
+            (
+                file_uid.unwrap_or_else(|| self.euid()),
+                file_gid.unwrap_or_else(|| self.egid()),
+            )
+        };
+        let is_setid = euid != ruid || egid != rgid;
+
+        let bounding_capset = self.bounding_capset();
+        let inheritable_capset = self.inheritable_capset();
+
+        let (mut new_permitted, mut is_effective) = match file_caps {
+            Some(file_caps) => (
+                (file_caps.permitted() & bounding_capset)
+                    | (file_caps.inheritable() & inheritable_capset),
+                file_caps.is_effective(),
+            ),
+            None => (CapSet::empty(), false),
+        };
+
+        // The file capabilities of a set-user-ID-root program take precedence over the
+        // capabilities granted to root.
+        let is_setuid_root = euid.is_root() && !ruid.is_root();
+        if !self.securebits().no_root() && !(file_caps.is_some() && is_setuid_root) {
+            if euid.is_root() || ruid.is_root() {
+                new_permitted = bounding_capset | inheritable_capset;
+            }
+            if euid.is_root() {
+                is_effective = true;
+            }
+        }
+
+        // With `no_new_privs`, or if traced by a tracer without `CAP_SYS_PTRACE` that could take
+        // control of it, the program cannot gain any privileges. The effective IDs that differ
+        // from the real IDs are dropped, unless the program is traced and the thread has
+        // `CAP_SETUID`.
+        let is_gaining_privs = is_setid || !self.permitted_capset().contains(new_permitted);
+        let (euid, egid) = if is_gaining_privs && (no_new_privs || is_ptraced_unsafely) {
+            new_permitted &= self.permitted_capset();
+            if no_new_privs || !self.effective_capset().contains(CapSet::SETUID) {
+                (ruid, rgid)
+            } else {
+                (euid, egid)
+            }
+        } else {
+            (euid, egid)
+        };
+
+        // Executing a privileged program clears the ambient set.
+        let new_ambient = if file_caps.is_some() || is_setid {
+            CapSet::empty()
+        } else {
+            self.ambient_capset()
+        };
+
+        let new_permitted = new_permitted | new_ambient;
+        let new_effective = if is_effective {
+            new_permitted
+        } else {
+            new_ambient
+        };
+
+        let is_secure =
+            is_setid || (!ruid.is_root() && (is_effective || !new_ambient.contains(new_permitted)));
+
+        ExecCredentials {
+            euid,
+            egid,
+            permitted_capset: new_permitted,
+            effective_capset: new_effective,
+            ambient_capset: new_ambient,
+            is_secure,
+        }
+    }
+
+    /// Installs the credentials for executing a program.
+    ///
+    /// The saved-set and filesystem IDs are set to the effective IDs. Unlike the `set*id`
+    /// system calls, the capabilities are not adjusted for the UID changes, because they have
+    /// been computed by [`Self::prepare_exec`].
+    pub(super) fn commit_exec(&self, exec_credentials: &ExecCredentials) {
+        let euid = exec_credentials.euid;
+        self.euid.store(euid, Ordering::Relaxed);
+        self.suid.store(euid, Ordering::Relaxed);
+        self.fsuid.store(euid, Ordering::Relaxed);
+
+        let egid = exec_credentials.egid;
+        self.egid.store(egid, Ordering::Relaxed);
+        self.sgid.store(egid, Ordering::Relaxed);
+        self.fsgid.store(egid, Ordering::Relaxed);
+
+        self.set_permitted_capset(exec_credentials.permitted_capset);
+        self.set_effective_capset(exec_credentials.effective_capset);
+        self.set_ambient_capset(exec_credentials.ambient_capset);
+
+        self.keyrings.reset_for_exec();
+    }
+
+    pub(super) fn no_new_privs(&self) -> bool {
+        self.no_new_privs.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_no_new_privs(&self) {
+        self.no_new_privs.store(true, Ordering::Relaxed);
+    }
+
+    pub(super) fn keyrings(&self) -> &ProcessKeyrings {
+        &self.keyrings
+    }
+
+    //  ******* Audit methods *******
+
+    pub(super) fn loginuid(&self) -> Uid {
+        self.loginuid.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn sessionid(&self) -> u32 {
+        self.sessionid.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_loginuid(&self, loginuid: Uid, sessionid: u32) {
+        self.loginuid.store(loginuid, Ordering::Relaxed);
+        self.sessionid.store(sessionid, Ordering::Relaxed);
+    }
+
+    //  ******* LSM methods *******
+
+    pub(super) fn security(&self) -> &SecurityBlob {
+        &self.security
+    }
 }
 
 impl Clone for Credentials_ {
@@ -607,7 +806,13 @@ impl Clone for Credentials_ {
             permitted_capset: self.permitted_capset.clone(),
             effective_capset: self.effective_capset.clone(),
             bounding_capset: self.bounding_capset.clone(),
+            ambient_capset: self.ambient_capset.clone(),
             securebits: self.securebits.clone(),
+            no_new_privs: AtomicBool::new(self.no_new_privs()),
+            keyrings: self.keyrings.clone(),
+            loginuid: self.loginuid.clone(),
+            sessionid: AtomicU32::new(self.sessionid()),
+            security: self.security.clone(),
         }
     }
 }
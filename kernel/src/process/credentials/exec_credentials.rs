@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{Gid, Uid, capabilities::CapSet};
+
+/// The credentials that a thread will have after executing a new program.
+///
+/// `execve` computes the credentials with [`Credentials::prepare_exec`] before the point of no
+/// return and installs them with [`Credentials::commit_exec`] after that point.
+///
+/// [`Credentials::prepare_exec`]: super::Credentials::prepare_exec
+/// [`Credentials::commit_exec`]: super::Credentials::commit_exec
+#[derive(Clone, Copy, Debug)]
+pub struct ExecCredentials {
+    pub(super) euid: Uid,
+    pub(super) egid: Gid,
+    pub(super) permitted_capset: CapSet,
+    pub(super) effective_capset: CapSet,
+    pub(super) ambient_capset: CapSet,
+    pub(super) is_secure: bool,
+}
+
+impl ExecCredentials {
+    /// Returns the effective user ID of the new program.
+    pub fn euid(&self) -> Uid {
+        self.euid
+    }
+
+    /// Returns the effective group ID of the new program.
+    pub fn egid(&self) -> Gid {
+        self.egid
+    }
+
+    /// Returns the permitted capabilities of the new program.
+    pub fn permitted_capset(&self) -> CapSet {
+        self.permitted_capset
+    }
+
+    /// Returns whether the new program must run in the secure-execution mode.
+    ///
+    /// This is the case if the program is executed with elevated privileges, e.g., through the
+    /// set-user-ID bit or file capabilities. The mode is passed to the program as `AT_SECURE`
+    /// in the auxiliary vector, so that the dynamic linker and the C library can disregard
+    /// untrusted environment variables like `LD_PRELOAD`.
+    pub fn is_secure(&self) -> bool {
+        self.is_secure
+    }
+}
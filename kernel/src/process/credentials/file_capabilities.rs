@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! File capabilities stored in the `security.capability` xattr.
+//!
+//! Reference: The "File capabilities" section in
+//! <https://man7.org/linux/man-pages/man7/capabilities.7.html>.
+
+use super::{Uid, capabilities::CapSet};
+use crate::{
+    fs::vfs::{path::Path, xattr::XattrName},
+    prelude::*,
+    process::UserNamespace,
+};
+
+/// The name of the xattr that stores the file capabilities.
+pub const XATTR_NAME_CAPS: &str = "security.capability";
+
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x0000_0001;
+
+/// The revision that only supports the low 32 capabilities.
+const VFS_CAP_REVISION_1: u32 = 0x0100_0000;
+/// The revision that supports 64 capabilities.
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+/// The revision that supports 64 capabilities and a root UID for user namespaces.
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+
+const XATTR_CAPS_SZ_1: usize = 4 + 2 * 4;
+const XATTR_CAPS_SZ_2: usize = 4 + 2 * 2 * 4;
+const XATTR_CAPS_SZ_3: usize = XATTR_CAPS_SZ_2 + 4;
+
+/// The capabilities attached to an executable file.
+#[derive(Clone, Copy, Debug)]
+pub struct FileCapabilities {
+    permitted: CapSet,
+    inheritable: CapSet,
+    is_effective: bool,
+}
+
+impl FileCapabilities {
+    /// Reads the file capabilities of an executable file.
+    ///
+    /// Returns `None` if the file has no capabilities, or if the capabilities belong to a user
+    /// namespace whose root is not the root of `user_ns` or its ancestors.
+    pub fn read_from(path: &Path, user_ns: &UserNamespace) -> Result<Option<Self>> {
+        let name = XattrName::try_from_full_name(XATTR_NAME_CAPS).unwrap();
+
+        let mut buf = [0u8; XATTR_CAPS_SZ_3];
+        let mut writer = VmWriter::from(&mut buf[..]).to_fallible();
+        let len = match path.get_xattr(name, &mut writer) {
+            Ok(len) => len,
+            Err(err) if matches!(err.error(), Errno::ENODATA | Errno::EOPNOTSUPP) => {
+                return Ok(None);
+            }
+            Err(err) if err.error() == Errno::ERANGE => {
+                return_errno_with_message!(Errno::EINVAL, "the file capabilities are too long")
+            }
+            Err(err) => return Err(err),
+        };
+
+        let (caps, root_uid) = Self::parse(&buf[..len])?;
+
+        // Like the file owner, the root UID is stored in the filesystem and must be translated
+        // through the ID mapping of the mount.
+        let root_uid = path.map_uid_to_mount(root_uid);
+
+        // The capabilities only take effect if the root UID is the root of `user_ns` or one of
+        // its ancestors.
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/commoncap.c#L669>
+        let is_root_of_ns = |ns: &UserNamespace| {
+            ns.uid_map().map_down(Uid::new_root().into()) == Some(root_uid.into())
+        };
+        if !core::iter::successors(Some(user_ns), |ns| ns.parent_ns().map(Arc::as_ref))
+            .any(is_root_of_ns)
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(caps))
+    }
+
+    /// Removes the file capabilities of a regular file, if any.
+    ///
+    /// This should be done when the file is written, truncated, or has its owner or group
+    /// changed, so that the modified file cannot keep the privileges granted to the original.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/commoncap.c#L325-L345>
+    pub fn remove_from(path: &Path) -> Result<()> {
+        if !path.type_().is_regular_file() {
+            return Ok(());
+        }
+
+        let name = XattrName::try_from_full_name(XATTR_NAME_CAPS).unwrap();
+        match path.remove_xattr(name) {
+            Err(err) if matches!(err.error(), Errno::ENODATA | Errno::EOPNOTSUPP) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Parses the value of the `security.capability` xattr.
+    ///
+    /// Returns the file capabilities and the UID of the root user that owns them. The root UID
+    /// is always zero except for `VFS_CAP_REVISION_3`.
+    fn parse(value: &[u8]) -> Result<(Self, Uid)> {
+        let Some(magic_etc) = read_u32(value, 0) else {
+            return_errno_with_message!(Errno::EINVAL, "the file capabilities are too short");
+        };
+
+        let (expected_len, has_high_bits) = match magic_etc & VFS_CAP_REVISION_MASK {
+            VFS_CAP_REVISION_1 => (XATTR_CAPS_SZ_1, false),
+            VFS_CAP_REVISION_2 => (XATTR_CAPS_SZ_2, true),
+            VFS_CAP_REVISION_3 => (XATTR_CAPS_SZ_3, true),
+            _ => return_errno_with_message!(
+                Errno::EINVAL,
+                "the file capabilities have an unknown revision"
+            ),
+        };
+        if value.len() != expected_len {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the file capabilities have an invalid length"
+            );
+        }
+
+        // The layout is the magic number followed by `{ permitted, inheritable }` pairs for
+        // each 32-bit word, and for `VFS_CAP_REVISION_3`, the root UID.
+        let permitted_lo = read_u32(value, 4).unwrap();
+        let inheritable_lo = read_u32(value, 8).unwrap();
+        let (permitted_hi, inheritable_hi) = if has_high_bits {
+            (read_u32(value, 12).unwrap(), read_u32(value, 16).unwrap())
+        } else {
+            (0, 0)
+        };
+        let root_uid = read_u32(value, XATTR_CAPS_SZ_2).unwrap_or(0);
+
+        let caps = Self {
+            permitted: CapSet::from_lo_hi(permitted_lo, permitted_hi),
+            inheritable: CapSet::from_lo_hi(inheritable_lo, inheritable_hi),
+            is_effective: magic_etc & VFS_CAP_FLAGS_EFFECTIVE != 0,
+        };
+        Ok((caps, Uid::new(root_uid)))
+    }
+
+    /// Returns the capabilities that are permitted regardless of the thread's inheritable set.
+    pub fn permitted(&self) -> CapSet {
+        self.permitted
+    }
+
+    /// Returns the capabilities that are permitted if they are in the thread's inheritable set.
+    pub fn inheritable(&self) -> CapSet {
+        self.inheritable
+    }
+
+    /// Returns whether the new permitted capabilities are also raised in the effective set.
+    pub fn is_effective(&self) -> bool {
+        self.is_effective
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    fn encode(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    #[ktest]
+    fn parse_revisions() {
+        let net_bind = CapSet::NET_BIND_SERVICE.bits() as u32;
+
+        let value = encode(&[
+            VFS_CAP_REVISION_2 | VFS_CAP_FLAGS_EFFECTIVE,
+            net_bind,
+            0,
+            0,
+            0,
+        ]);
+        let (caps, root_uid) = FileCapabilities::parse(&value).unwrap();
+        assert_eq!(caps.permitted(), CapSet::NET_BIND_SERVICE);
+        assert!(caps.inheritable().is_empty());
+        assert!(caps.is_effective());
+        assert!(root_uid.is_root());
+
+        let value = encode(&[VFS_CAP_REVISION_1, 0, net_bind]);
+        let (caps, _) = FileCapabilities::parse(&value).unwrap();
+        assert!(caps.permitted().is_empty());
+        assert_eq!(caps.inheritable(), CapSet::NET_BIND_SERVICE);
+        assert!(!caps.is_effective());
+
+        let value = encode(&[VFS_CAP_REVISION_3, 0, 0, 1 << 8, 0, 1000]);
+        let (caps, root_uid) = FileCapabilities::parse(&value).unwrap();
+        assert_eq!(caps.permitted(), CapSet::CHECKPOINT_RESTORE);
+        assert_eq!(root_uid, Uid::new(1000));
+    }
+
+    #[ktest]
+    fn parse_invalid() {
+        assert!(FileCapabilities::parse(&[]).is_err());
+        // The length does not match the revision.
+        let value = encode(&[VFS_CAP_REVISION_2, 0, 0]);
+        assert!(FileCapabilities::parse(&value).is_err());
+        // The revision is unknown.
+        let value = encode(&[0x0400_0000, 0, 0, 0, 0]);
+        assert!(FileCapabilities::parse(&value).is_err());
+    }
+}
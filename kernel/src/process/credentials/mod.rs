@@ -3,14 +3,16 @@
 pub mod c_types;
 pub mod capabilities;
 mod credentials_;
+mod exec_credentials;
+pub mod file_capabilities;
 mod group;
 mod secure_bits;
 mod static_cap;
 mod user;
 
 use aster_rights::FullOp;
-use capabilities::CapSet;
 use credentials_::Credentials_;
+pub use exec_credentials::ExecCredentials;
 pub use group::Gid;
 pub use secure_bits::SecureBits;
 pub use user::Uid;
@@ -26,8 +28,9 @@ use crate::prelude::*;
 /// - filesystem user ID and group ID (Linux-specific);
 /// - supplementary group IDs;
 /// - Linux capabilities;
-/// - secure bits.
+/// - secure bits;
+/// - the no-new-privileges flag;
+/// - the keyrings of the key retention service;
+/// - the login user ID and the session ID of the audit subsystem;
+/// - the security data of the LSM modules.
 pub struct Credentials<R = FullOp>(Arc<Credentials_>, R);
-
-// TODO: Support the ambient capability set.
-pub const AMBIENT_CAPSET: CapSet = CapSet::empty();
@@ -4,8 +4,14 @@ use aster_rights::{Dup, Read, TRights, Write};
 use aster_rights_proc::require;
 use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
 
-use super::{Credentials, Gid, SecureBits, Uid, capabilities::CapSet, credentials_::Credentials_};
-use crate::prelude::*;
+use super::{
+    Credentials, Gid, SecureBits, Uid, capabilities::CapSet, credentials_::Credentials_,
+    exec_credentials::ExecCredentials, file_capabilities::FileCapabilities,
+};
+use crate::{
+    prelude::*,
+    security::{keys::ProcessKeyrings, lsm::SecurityBlob},
+};
 
 impl<R: TRights> Credentials<R> {
     /// Creates a root `Credentials`.
@@ -125,27 +131,6 @@ impl<R: TRights> Credentials<R> {
         self.0.set_fsuid(fsuid)
     }
 
-    /// Sets the effective user ID as `euid`.
-    ///
-    /// This method should only be used when executing a file whose `setuid` bit is set.
-    ///
-    /// This method requires the `Write` right.
-    #[require(R > Write)]
-    pub fn set_euid(&self, euid: Uid) {
-        self.0.set_euid(euid);
-    }
-
-    /// Sets the saved-set user ID as the same of the effective user ID.
-    ///
-    /// This method should only be used when executing a new executable file.
-    ///
-    /// This method requires the `Write` right.
-    #[require(R > Write)]
-    pub fn reset_suid(&self) {
-        let euid = self.0.euid();
-        self.0.set_suid(euid);
-    }
-
     // *********** GID methods **********
 
     /// Gets the real group ID.
@@ -226,27 +211,6 @@ impl<R: TRights> Credentials<R> {
         self.0.set_fsgid(fsgid)
     }
 
-    /// Sets the effective group ID as `egid`.
-    ///
-    /// This method should only be used when executing a file whose `setgid` bit is set.
-    ///
-    /// This method requires the `Write` right.
-    #[require(R > Write)]
-    pub fn set_egid(&self, egid: Gid) {
-        self.0.set_egid(egid);
-    }
-
-    /// Sets the saved-set group ID as the same of the effective group ID.
-    ///
-    /// This method should only be used when executing a new executable file.
-    ///
-    /// This method requires the `Write` right.
-    #[require(R > Write)]
-    pub fn reset_sgid(&self) {
-        let egid = self.0.egid();
-        self.0.set_sgid(egid);
-    }
-
     // *********** Supplementary Groups methods **********
 
     /// Acquires the read lock of supplementary group IDs.
@@ -299,8 +263,18 @@ impl<R: TRights> Credentials<R> {
         self.0.bounding_capset()
     }
 
+    /// Gets the capabilities that are preserved across `execve()` of unprivileged programs.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn ambient_capset(&self) -> CapSet {
+        self.0.ambient_capset()
+    }
+
     /// Sets the capabilities that child processes can inherit.
     ///
+    /// Capabilities that are no longer inheritable are also removed from the ambient set.
+    ///
     /// This method requires the `Write` right.
     #[require(R > Write)]
     pub fn set_inheritable_capset(&self, inheritable_capset: CapSet) {
@@ -309,6 +283,8 @@ impl<R: TRights> Credentials<R> {
 
     /// Sets the capabilities that a process can potentially be granted.
     ///
+    /// Capabilities that are no longer permitted are also removed from the ambient set.
+    ///
     /// This method requires the `Write` right.
     #[require(R > Write)]
     pub fn set_permitted_capset(&self, permitted_capset: CapSet) {
@@ -323,6 +299,33 @@ impl<R: TRights> Credentials<R> {
         self.0.set_effective_capset(effective_capset);
     }
 
+    /// Raises one capability in the ambient set.
+    ///
+    /// If the capability is not both permitted and inheritable, or if
+    /// [`SecureBits::NO_CAP_AMBIENT_RAISE`] is set, this method returns an error.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn raise_ambient_capability(&self, capability: CapSet) -> Result<()> {
+        self.0.raise_ambient_capability(capability)
+    }
+
+    /// Lowers one capability in the ambient set.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn lower_ambient_capability(&self, capability: CapSet) {
+        self.0.lower_ambient_capability(capability);
+    }
+
+    /// Clears the ambient set.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn clear_ambient_capset(&self) {
+        self.0.clear_ambient_capset();
+    }
+
     /// Drops one capability from the capability bounding set.
     ///
     /// If the caller does not have the `CAP_SETPCAP` capability, this method returns an error.
@@ -371,4 +374,101 @@ impl<R: TRights> Credentials<R> {
     pub fn set_securebits(&self, securebits: SecureBits) -> Result<()> {
         self.0.set_securebits(securebits)
     }
+
+    // *********** Execution methods **********
+
+    /// Computes the credentials for executing a program.
+    ///
+    /// `file_uid` and `file_gid` are the owner and the group of the program if it has the
+    /// set-user-ID and set-group-ID bits, respectively. `file_caps` are the file capabilities of
+    /// the program. `is_ptraced_unsafely` indicates whether the thread is traced by a tracer that
+    /// does not have `CAP_SYS_PTRACE`.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn prepare_exec(
+        &self,
+        file_uid: Option<Uid>,
+        file_gid: Option<Gid>,
+        file_caps: Option<&FileCapabilities>,
+        is_ptraced_unsafely: bool,
+    ) -> ExecCredentials {
+        self.0
+            .prepare_exec(file_uid, file_gid, file_caps, is_ptraced_unsafely)
+    }
+
+    /// Installs the credentials computed by [`Self::prepare_exec`].
+    ///
+    /// This method should only be used when executing a new executable file.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn commit_exec(&self, exec_credentials: &ExecCredentials) {
+        self.0.commit_exec(exec_credentials);
+    }
+
+    /// Gets the no-new-privileges flag.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn no_new_privs(&self) -> bool {
+        self.0.no_new_privs()
+    }
+
+    /// Sets the no-new-privileges flag.
+    ///
+    /// The flag cannot be unset once it is set.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn set_no_new_privs(&self) {
+        self.0.set_no_new_privs();
+    }
+
+    /// Gets the keyrings of the key retention service.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn keyrings(&self) -> &ProcessKeyrings {
+        self.0.keyrings()
+    }
+
+    // *********** Audit methods **********
+
+    /// Gets the login user ID of the audit subsystem.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn loginuid(&self) -> Uid {
+        self.0.loginuid()
+    }
+
+    /// Gets the session ID of the audit subsystem.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn sessionid(&self) -> u32 {
+        self.0.sessionid()
+    }
+
+    /// Sets the login user ID and the session ID of the audit subsystem.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn set_loginuid(&self, loginuid: Uid, sessionid: u32) {
+        self.0.set_loginuid(loginuid, sessionid);
+    }
+
+    // *********** LSM methods **********
+
+    /// Gets the security blob, which holds the security data of the LSM modules.
+    ///
+    /// The LSM modules are responsible for keeping their own data consistent, e.g., the
+    /// Landlock domain can only be replaced by a nested domain.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn security(&self) -> &SecurityBlob {
+        self.0.security()
+    }
 }
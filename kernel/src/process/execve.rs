@@ -2,7 +2,7 @@
 
 use aster_rights::ReadWriteOp;
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GsBase};
+use ostd::arch::cpu::context::{DebugRegs, FsBase, GsBase};
 use ostd::{
     arch::cpu::context::{FpuContext, GeneralRegs, UserContext},
     mm::VmIo,
@@ -12,10 +12,13 @@ use ostd::{
 
 use super::process_vm::activate_vmar;
 use crate::{
-    fs::vfs::{inode::Inode, path::Path},
+    fs::{file::file_table::FileDesc, vfs::path::Path},
     prelude::*,
     process::{
-        ContextUnshareAdminApi, Credentials, Process, pid_table,
+        ContextUnshareAdminApi, Credentials, Process,
+        coredump::Dumpable,
+        credentials::{ExecCredentials, file_capabilities::FileCapabilities},
+        pid_table,
         posix_thread::{
             AsPosixThread, ContextPthreadAdminApi, ThreadLocal, ThreadName, ptrace::PtraceEvent,
             sigkill_other_threads,
@@ -28,6 +31,7 @@ use crate::{
             signals::kernel::KernelSignal,
         },
     },
+    security::{audit, lsm::hooks as lsm_hooks},
     vm::vmar::VmarHandle,
 };
 
@@ -46,6 +50,7 @@ pub fn do_execve(
     // of all strings to enforce a sensible overall limit.
     let argv = read_cstring_vec(argv_ptr_ptr, MAX_NR_STRING_ARGS, MAX_LEN_STRING_ARG, ctx)?;
     let envp = read_cstring_vec(envp_ptr_ptr, MAX_NR_STRING_ARGS, MAX_LEN_STRING_ARG, ctx)?;
+    audit::log_execve_args(ctx, &argv);
 
     let fs_ref = ctx.thread_local.borrow_fs();
     let path_resolver = fs_ref.resolver().read();
@@ -57,16 +62,26 @@ pub fn do_execve(
         envp
     );
 
-    let program_to_load =
+    let mut program_to_load =
         ProgramToLoad::build_from_file(elf_file.clone(), &path_resolver, argv, envp)?;
+    let exec_credentials = prepare_exec_credentials(program_to_load.creds_file(), ctx)?;
+
+    // The binary is installed into the file table before the new program is loaded, because
+    // its file descriptor is passed in the auxiliary vector.
+    let exec_fd = program_to_load
+        .install_exec_fd(&mut ctx.thread_local.borrow_file_table().unwrap().write())?;
 
     let new_vmar = VmarHandle::new(ProcessVm::new(elf_file.clone()));
-    let elf_load_info = program_to_load.load_to_vmar(&new_vmar, &path_resolver)?;
+    let elf_load_info = program_to_load
+        .load_to_vmar(&new_vmar, &path_resolver, exec_credentials.is_secure())
+        .inspect_err(|_| close_exec_fd(ctx, exec_fd))?;
 
     // Ensure no other thread is concurrently performing exit_group or execve.
     // If such an operation is in progress, return EAGAIN.
     let mut task_set = ctx.process.tasks().lock();
     if task_set.has_exited_group() || task_set.in_execve() {
+        drop(task_set);
+        close_exec_fd(ctx, exec_fd);
         return_errno_with_message!(
             Errno::EAGAIN,
             "the process has exited or has already executed a new program"
@@ -86,13 +101,18 @@ pub fn do_execve(
     let res = do_execve_no_return(
         ctx,
         user_context,
-        elf_file,
+        &exec_credentials,
         thread_name,
         new_vmar,
         &elf_load_info,
+        exec_fd,
     );
 
     if res.is_ok() {
+        lsm_hooks::on_bprm_committed_creds(lsm_hooks::BprmContext::new(
+            ctx.posix_thread,
+            &elf_file,
+        ));
         ctx.posix_thread
             .ptrace_may_stop_on(PtraceEvent::Exec(former_tid), ctx, user_context);
     } else {
@@ -105,6 +125,64 @@ pub fn do_execve(
     res
 }
 
+/// Computes the credentials of the program to execute.
+///
+/// If the file has the `set_uid` or `set_gid` bit, its UID or GID is taken as seen through the
+/// (possibly ID-mapped) mount.
+fn prepare_exec_credentials(creds_file: &Path, ctx: &Context) -> Result<ExecCredentials> {
+    let file_caps = read_file_caps(creds_file, ctx)?;
+
+    let mode = creds_file.mode()?;
+    let owner = creds_file.owner()?;
+    let group = creds_file.group()?;
+
+    // The set-user-ID and set-group-ID bits are ignored if the owner or the group of the file
+    // has no mapping in the user namespace of the thread.
+    // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/exec.c#L1606>
+    let is_owner_mapped = {
+        let user_ns = ctx.thread_local.borrow_user_ns();
+        user_ns.uid_map().map_up(owner.into()).is_some()
+            && user_ns.gid_map().map_up(group.into()).is_some()
+    };
+
+    let file_uid = (is_owner_mapped && mode.has_set_uid()).then_some(owner);
+    let file_gid = (is_owner_mapped && mode.has_set_gid()).then_some(group);
+
+    let credentials = ctx.posix_thread.credentials();
+    Ok(credentials.prepare_exec(
+        file_uid,
+        file_gid,
+        file_caps.as_ref(),
+        ctx.posix_thread.is_ptraced_unsafely(),
+    ))
+}
+
+/// Reads the file capabilities of the program to execute.
+///
+/// This fails with `EPERM` if the file capabilities have the effective bit set but not all of
+/// their permitted capabilities can be granted. Such a program is unaware of capabilities and
+/// cannot run correctly with only some of them.
+fn read_file_caps(creds_file: &Path, ctx: &Context) -> Result<Option<FileCapabilities>> {
+    let user_ns = ctx.thread_local.borrow_user_ns();
+    let Some(file_caps) = FileCapabilities::read_from(creds_file, &user_ns)? else {
+        return Ok(None);
+    };
+
+    if file_caps.is_effective() {
+        let credentials = ctx.posix_thread.credentials();
+        let granted_capset = (file_caps.permitted() & credentials.bounding_capset())
+            | (file_caps.inheritable() & credentials.inheritable_capset());
+        if !granted_capset.contains(file_caps.permitted()) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "not all permitted file capabilities can be granted"
+            );
+        }
+    }
+
+    Ok(Some(file_caps))
+}
+
 fn read_cstring_vec(
     array_ptr: Vaddr,
     max_string_number: usize,
@@ -146,10 +224,11 @@ fn read_cstring_vec(
 fn do_execve_no_return(
     ctx: &Context,
     user_context: &mut UserContext,
-    elf_file: Path,
+    exec_credentials: &ExecCredentials,
     thread_name: ThreadName,
     new_vmar: VmarHandle,
     elf_load_info: &ElfLoadInfo,
+    exec_fd: Option<FileDesc>,
 ) -> Result<()> {
     let Context {
         process,
@@ -168,7 +247,8 @@ fn do_execve_no_return(
     // This prevents race conditions when checking access permissions while opening
     // `/proc/[pid]/mem` or `/proc/[pid]/maps`.
     let (vmar_guard, old_vmar) = activate_vmar(ctx, new_vmar);
-    apply_caps_from_exec(process, ctx.credentials_mut(), elf_file.inode())?;
+    commit_exec_credentials(process, ctx.credentials_mut(), exec_credentials)?;
+    inherit_coredump_attrs(vmar_guard.unwrap().process_vm(), old_vmar.process_vm(), ctx);
     drop(vmar_guard);
     drop(old_vmar);
 
@@ -184,7 +264,7 @@ fn do_execve_no_return(
     reset_vfork_child(process);
 
     // Unshare file descriptor table and close files with O_CLOEXEC flag.
-    unshare_and_close_files(ctx);
+    unshare_and_close_files(ctx, exec_fd);
 
     // Set the thread name.
     *posix_thread.thread_name().lock() = thread_name;
@@ -199,7 +279,12 @@ fn do_execve_no_return(
     Ok(())
 }
 
-fn wait_other_threads_exit(ctx: &Context) -> Result<()> {
+/// Waits for all other threads in the current process to exit.
+///
+/// This is used by `execve` and core dumps, both of which have killed the
+/// other threads. If the current thread receives `SIGKILL`, this function
+/// fails with `EAGAIN`.
+pub(super) fn wait_other_threads_exit(ctx: &Context) -> Result<()> {
     let is_main_thread = ctx.posix_thread.tid() == ctx.process.pid();
 
     let mut tasks = ctx.process.tasks().lock();
@@ -291,6 +376,8 @@ fn set_cpu_context(
     {
         supp.fs_base().set(FsBase::default());
         supp.gs_base().set(GsBase::default());
+        // Clear the hardware breakpoints.
+        supp.debug_regs().set(DebugRegs::default());
     }
     #[cfg(not(target_arch = "x86_64"))]
     user_context.set_tls_pointer(0);
@@ -303,60 +390,48 @@ fn set_cpu_context(
     debug!("user stack top: 0x{:x}", elf_load_info.user_stack_top);
 }
 
-/// Sets the UID and GID in the credentials according to the ELF inode.
+/// Sets the core dump attributes of the new program.
 ///
-/// The capabilities will be updated accordingly.
-fn apply_caps_from_exec(
-    process: &Process,
-    credentials: Credentials<ReadWriteOp>,
-    elf_inode: &Arc<dyn Inode>,
-) -> Result<()> {
-    set_uid_from_elf(process, &credentials, elf_inode)?;
-    set_gid_from_elf(process, &credentials, elf_inode)?;
-    credentials.set_keep_capabilities(false)?;
-
-    Ok(())
+/// The core dump filter is preserved across `execve`. Core dumps are disabled
+/// if the effective IDs differ from the real IDs, so that the memory of
+/// privileged programs does not leak to unprivileged users.
+fn inherit_coredump_attrs(new_vm: &ProcessVm, old_vm: &ProcessVm, ctx: &Context) {
+    new_vm.set_coredump_filter(old_vm.coredump_filter());
+
+    let credentials = ctx.posix_thread.credentials();
+    let dumpable =
+        if credentials.euid() != credentials.ruid() || credentials.egid() != credentials.rgid() {
+            Dumpable::Disable
+        } else {
+            Dumpable::User
+        };
+    new_vm.set_dumpable(dumpable);
 }
 
-/// Sets the UID in the credentials according to the ELF inode.
-///
-/// If the ELF inode has the `set_uid` bit, the effective UID is set to the same value as the ELF
-/// inode's UID.
-fn set_uid_from_elf(
-    current: &Process,
-    credentials: &Credentials<ReadWriteOp>,
-    elf_inode: &Arc<dyn Inode>,
+/// Installs the credentials of the new program.
+fn commit_exec_credentials(
+    process: &Process,
+    credentials: Credentials<ReadWriteOp>,
+    exec_credentials: &ExecCredentials,
 ) -> Result<()> {
-    if elf_inode.mode()?.has_set_uid() {
-        let uid = elf_inode.owner()?;
-        credentials.set_euid(uid);
-
-        current.clear_parent_death_signal();
+    // Like Linux, the parent death signal is cleared if the new program runs with different IDs
+    // or more capabilities.
+    let new_euid = exec_credentials.euid();
+    let new_egid = exec_credentials.egid();
+    if new_euid != credentials.euid()
+        || new_euid != credentials.fsuid()
+        || new_egid != credentials.egid()
+        || new_egid != credentials.fsgid()
+        || !credentials
+            .permitted_capset()
+            .contains(exec_credentials.permitted_capset())
+    {
+        process.clear_parent_death_signal();
     }
 
-    // No matter whether the ELF inode has `set_uid` bit, SUID should be reset.
-    credentials.reset_suid();
-    Ok(())
-}
-
-/// Sets the GID in the credentials according to the ELF inode.
-///
-/// If the ELF inode has the `set_gid` bit, the effective GID is set to the same value as the ELF
-/// inode's GID.
-fn set_gid_from_elf(
-    current: &Process,
-    credentials: &Credentials<ReadWriteOp>,
-    elf_inode: &Arc<dyn Inode>,
-) -> Result<()> {
-    if elf_inode.mode()?.has_set_gid() {
-        let gid = elf_inode.group()?;
-        credentials.set_egid(gid);
-
-        current.clear_parent_death_signal();
-    }
+    credentials.commit_exec(exec_credentials);
+    credentials.set_keep_capabilities(false)?;
 
-    // No matter whether the ELF inode has `set_gid` bit, SGID should be reset.
-    credentials.reset_sgid();
     Ok(())
 }
 
@@ -369,9 +444,16 @@ fn reset_vfork_child(process: &Process) {
     }
 }
 
-fn unshare_and_close_files(ctx: &Context) {
+fn unshare_and_close_files(ctx: &Context, exec_fd: Option<FileDesc>) {
+    let old_file_table = ctx.thread_local.borrow_file_table().unwrap().clone();
     ctx.unshare_files();
 
+    // The binary was installed before the file table was unshared. It belongs to the new
+    // program only, so remove it from the old file table, which may still be shared.
+    if let Some(exec_fd) = exec_fd {
+        old_file_table.write().close_file(exec_fd);
+    }
+
     ctx.thread_local
         .borrow_file_table()
         .unwrap()
@@ -379,6 +461,17 @@ fn unshare_and_close_files(ctx: &Context) {
         .close_files_on_exec();
 }
 
+/// Closes the file descriptor of the binary if the new program fails to load.
+fn close_exec_fd(ctx: &Context, exec_fd: Option<FileDesc>) {
+    if let Some(exec_fd) = exec_fd {
+        ctx.thread_local
+            .borrow_file_table()
+            .unwrap()
+            .write()
+            .close_file(exec_fd);
+    }
+}
+
 fn unshare_and_reset_sigdispositions(process: &Process) {
     let mut sig_dispositions = process.sig_dispositions().lock();
 
@@ -2,10 +2,19 @@
 
 use core::sync::atomic::Ordering;
 
-use super::{INIT_PROCESS_PID, Pid, Process, pid_table};
+use super::{INIT_PROCESS_PID, Pid, Process, TermStatus, pid_table};
 use crate::{
-    events::IoEvents, fs::cgroupfs::CgroupMembership, prelude::*,
-    process::signal::signals::kernel::KernelSignal,
+    events::IoEvents,
+    fs::cgroupfs::CgroupMembership,
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{
+            c_types::siginfo_t,
+            constants::SIGKILL,
+            signals::{kernel::KernelSignal, raw::RawSignal},
+        },
+    },
 };
 
 /// Exits the current POSIX process.
@@ -21,6 +30,10 @@ pub(super) fn exit_process(current_process: &Process) {
     // Drop fields in `Process`.
     drop_after!(current_process.lock_vmar().set_vmar(None));
 
+    if current_process.is_pid_ns_init() {
+        kill_pid_ns_processes(current_process);
+    }
+
     // Move the children to the reaper process and send them signals. The children should see a new
     // parent when they receive the signal.
     let children = move_children_to_reaper_process(current_process);
@@ -80,19 +93,25 @@ fn move_children_to_reaper_process(current_process: &Process) -> BTreeMap<Pid, A
         }
     }
 
-    let init_process = pid_table::pid_table_mut()
-        .get_process(INIT_PROCESS_PID)
-        .unwrap();
-    let children = move_process_children(current_process, &init_process).unwrap();
-    init_process.children_wait_queue().wake_all();
-
-    children
+    loop {
+        let reaper_process = find_pid_ns_reaper_process(current_process);
+        if let Ok(children) = move_process_children(current_process, &reaper_process) {
+            reaper_process.children_wait_queue().wake_all();
+            return children;
+        }
+    }
 }
 
 /// Finds a reaper process for `current_process`.
 ///
 /// If there is no reaper process for `current_process`, returns `None`.
 fn find_reaper_process(current_process: &Process) -> Option<Arc<Process>> {
+    // The children of the init process of a PID namespace are never reaped by the processes in
+    // the parent namespace, except for the init process of the parent namespace.
+    if current_process.is_pid_ns_init() {
+        return None;
+    }
+
     // The current process is not yet zombie (see `exit_process`), so it cannot have been reaped,
     // and it is still present in its parent's children map. An exiting parent will move the
     // current process to a new reaper (updating this `Weak` reference) before becoming zombie
@@ -104,6 +123,11 @@ fn find_reaper_process(current_process: &Process) -> Option<Arc<Process>> {
             return Some(parent);
         }
 
+        // Subreapers are never searched beyond the init process of a PID namespace.
+        if parent.is_pid_ns_init() {
+            return (!parent.status().is_zombie()).then_some(parent);
+        }
+
         if !parent.has_child_subreaper.load(Ordering::Acquire) {
             return None;
         }
@@ -126,6 +150,50 @@ fn find_reaper_process(current_process: &Process) -> Option<Arc<Process>> {
     }
 }
 
+/// Finds the init process of the innermost PID namespace that is still alive to be the reaper
+/// process for `current_process`.
+///
+/// If the init process of a PID namespace exits, its children are reaped by the init process of
+/// the parent namespace. The init process of the initial PID namespace never exits.
+fn find_pid_ns_reaper_process(current_process: &Process) -> Arc<Process> {
+    let mut pid_ns = current_process.pid_ns();
+
+    while let Some(parent_ns) = pid_ns.parent_ns() {
+        if let Some(reaper_process) = pid_ns.child_reaper()
+            && !core::ptr::eq(reaper_process.as_ref(), current_process)
+            && !reaper_process.status().is_zombie()
+        {
+            return reaper_process;
+        }
+        pid_ns = parent_ns;
+    }
+
+    pid_table::pid_table_mut()
+        .get_process(INIT_PROCESS_PID)
+        .unwrap()
+}
+
+/// Kills all other processes in the PID namespace whose init process is `current_process`.
+///
+/// After the init process of a PID namespace exits, no new processes can be created in the
+/// namespace.
+//
+// FIXME: Linux makes the init process wait until all other processes in the namespace have been
+// reaped before it becomes a zombie. We do not wait here, so the parent may see the init process
+// exit before the other processes in the namespace.
+fn kill_pid_ns_processes(current_process: &Process) {
+    let pid_ns = current_process.pid_ns();
+    pid_ns.disable_adding();
+
+    for process in pid_table::pid_table_mut().iter_processes() {
+        if core::ptr::eq(process.as_ref(), current_process) || pid_ns.nr_of(process.pid()).is_none()
+        {
+            continue;
+        }
+        process.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+    }
+}
+
 /// Moves the children of `current_process` to be the children of `reaper_process`.
 ///
 /// Returns the moved children on success. Otherwise, if the `reaper_process` is zombie, returns
@@ -180,8 +248,22 @@ fn send_child_death_signal(current_process: &Process) {
         return;
     };
 
-    if let Some(signal) = current_process.exit_signal().map(KernelSignal::new) {
-        parent.enqueue_signal(Box::new(signal));
+    if let Some(signum) = current_process.exit_signal() {
+        let (si_code, si_status) =
+            TermStatus::parse_si_code_and_status(current_process.status().exit_code());
+        let uid = current_process
+            .main_thread()
+            .as_posix_thread()
+            .unwrap()
+            .credentials()
+            .ruid();
+
+        let mut siginfo = siginfo_t::new(signum, si_code);
+        // The PID is seen in the PID namespace of the parent.
+        siginfo.set_pid_uid(parent.nr_in_ns(current_process.pid()), uid);
+        siginfo.set_status(si_status);
+
+        parent.enqueue_signal(Box::new(RawSignal::new(siginfo)));
     };
     parent.children_wait_queue().wake_all();
 }
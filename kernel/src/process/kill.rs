@@ -3,7 +3,11 @@
 use super::{
     Pgid, Pid, Process, pid_table,
     posix_thread::AsPosixThread,
-    signal::{constants::SIGCONT, sig_num::SigNum, signals::Signal},
+    signal::{
+        constants::{SIGCONT, SIGKILL, SIGSTOP},
+        sig_num::SigNum,
+        signals::Signal,
+    },
 };
 use crate::{
     prelude::*,
@@ -26,6 +30,10 @@ pub fn kill(pid: Pid, signal: Option<Box<dyn Signal>>, ctx: &Context) -> Result<
             return Ok(());
         };
 
+        if is_ignored_by_pid_ns_init(&ctx.process, signal.num(), ctx) {
+            return Ok(());
+        }
+
         if !ctx.posix_thread.has_signal_blocked(signal.num()) {
             // Killing the current thread does not raise any permission issues.
             ctx.posix_thread.enqueue_signal(signal);
@@ -110,7 +118,9 @@ pub fn tgkill(
         return Ok(());
     }
 
-    if let Some(signal) = signal {
+    if let Some(signal) = signal
+        && !is_ignored_by_pid_ns_init(&target_posix_thread.process(), signal.num(), ctx)
+    {
         // We've checked the permission issues above.
         // FIXME: We should take some lock while checking the permission to avoid race conditions.
         target_posix_thread.enqueue_signal(signal);
@@ -119,8 +129,8 @@ pub fn tgkill(
     Ok(())
 }
 
-/// Sends a signal to all processes except current process and init process, using
-/// the current process as the sender.
+/// Sends a signal to all processes in the PID namespace of the current process except current
+/// process and init process, using the current process as the sender.
 ///
 /// The credentials of the current process will be checked to determine
 /// if it is authorized to send the signal to the target group.
@@ -128,7 +138,9 @@ pub fn kill_all<S: Signal + Clone>(signal: Option<S>, ctx: &Context) -> Result<(
     let mut result = Ok(());
 
     for process in pid_table::pid_table_mut().iter_processes() {
-        if Arc::ptr_eq(&ctx.process, &process) || process.is_init_process() {
+        // Skip the init process of the PID namespace (whose PID is one) and the processes that
+        // are not visible in the PID namespace (whose PIDs are zero).
+        if Arc::ptr_eq(&ctx.process, &process) || ctx.process.nr_in_ns(process.pid()) <= 1 {
             continue;
         }
 
@@ -150,15 +162,41 @@ fn kill_process(process: &Process, signal: Option<Box<dyn Signal>>, ctx: &Contex
     let target_main_thread = process.main_thread();
     check_signal_perm(target_main_thread.as_posix_thread().unwrap(), ctx, signum)?;
 
-    if let Some(signal) = signal {
+    if let Some(signal) = signal
+        && !is_ignored_by_pid_ns_init(process, signal.num(), ctx)
+    {
         process.enqueue_signal(signal);
     }
 
     Ok(())
 }
 
+/// Returns whether the signal sent by the current process should be dropped because the target
+/// process is the init process of a PID namespace.
+///
+/// `SIGKILL` and `SIGSTOP` cannot be sent to the init process of a PID namespace from inside the
+/// namespace, but they can be sent from an ancestor namespace. Other signals with the default
+/// action are ignored when they are handled (see `handle_pending_signal`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16/source/kernel/signal.c>
+fn is_ignored_by_pid_ns_init(target: &Process, signum: SigNum, ctx: &Context) -> bool {
+    (signum == SIGKILL || signum == SIGSTOP)
+        && target.is_pid_ns_init()
+        && target.pid_ns().nr_of(ctx.process.pid()).is_some()
+}
+
 // Reference: <https://elixir.bootlin.com/linux/v6.17/source/kernel/signal.c#L799>.
 fn check_signal_perm(target: &PosixThread, ctx: &Context, signum: Option<SigNum>) -> Result<()> {
+    check_signal_cred(target, ctx, signum)?;
+
+    lsm_hooks::on_task_kill(lsm_hooks::TaskKillContext::new(
+        ctx.posix_thread,
+        target,
+        signum,
+    ))
+}
+
+fn check_signal_cred(target: &PosixThread, ctx: &Context, signum: Option<SigNum>) -> Result<()> {
     let target_process = target.process();
 
     if Arc::ptr_eq(&target_process, &ctx.process) {
@@ -5,9 +5,9 @@ use spin::Once;
 use crate::{
     fs::{cgroupfs::CgroupNamespace, vfs::path::MountNamespace},
     ipc::IpcNamespace,
-    net::uts_ns::UtsNamespace,
+    net::{net_ns::NetNamespace, uts_ns::UtsNamespace},
     prelude::*,
-    process::{CloneFlags, Process, UserNamespace, posix_thread::PosixThread},
+    process::{CloneFlags, PidNamespace, Process, UserNamespace, posix_thread::PosixThread},
 };
 
 /// A struct that acts as a per-thread proxy to give access to most namespaces.
@@ -16,11 +16,14 @@ use crate::{
 /// and keeps a local copy in `ThreadLocal` for fast access.
 /// `NsProxy` contains all types of namespaces except
 /// 1. The user namespace, which is included in the `Process` struct.
-/// 2. The PID namespace, which is included in the `Process` struct (TODO).
+/// 2. The PID namespace in which the process lives, which is included in the `Process` struct.
+///    `NsProxy` only contains the PID namespace for the children of the process.
 pub struct NsProxy {
     cgroup_ns: Arc<CgroupNamespace>,
     ipc_ns: Arc<IpcNamespace>,
     mnt_ns: Arc<MountNamespace>,
+    net_ns: Arc<NetNamespace>,
+    pid_ns_for_children: Arc<PidNamespace>,
     uts_ns: Arc<UtsNamespace>,
 }
 
@@ -33,6 +36,8 @@ impl NsProxy {
                 cgroup_ns: CgroupNamespace::get_init_singleton().clone(),
                 ipc_ns: IpcNamespace::get_init_singleton().clone(),
                 mnt_ns: MountNamespace::get_init_singleton().clone(),
+                net_ns: NetNamespace::get_init_singleton().clone(),
+                pid_ns_for_children: PidNamespace::get_init_singleton().clone(),
                 uts_ns: UtsNamespace::get_init_singleton().clone(),
             })
         })
@@ -43,9 +48,12 @@ impl NsProxy {
     /// If no namespaces need to be cloned, this method simply clones `self` and returns.
     /// Otherwise, a new `NsProxy` will be created
     /// by selectively cloning fields from the proxy and newly created namespaces.
+    ///
+    /// Note that a new PID namespace only affects the children. The caller
+    /// of `clone()` should put the child process into the new PID namespace.
     //
     // FIXME: This method is currently used by both `unshare()` and `clone()`.
-    // Once we support PID and time namespaces, their semantics diverge.
+    // Once we support time namespaces, their semantics diverge.
     // We will need to refactor (or split) this method accordingly.
     pub(in crate::process) fn new_clone(
         self: &Arc<Self>,
@@ -86,6 +94,25 @@ impl NsProxy {
             builder.mnt_ns(new_mnt_ns);
         }
 
+        if clone_ns_flags.contains(CloneFlags::CLONE_NEWNET) {
+            let new_net_ns = self.net_ns.new_clone(user_ns.clone(), posix_thread)?;
+            builder.net_ns(new_net_ns);
+        }
+
+        if clone_ns_flags.contains(CloneFlags::CLONE_NEWPID) {
+            // Reference: <https://elixir.bootlin.com/linux/v6.16/source/kernel/pid_namespace.c>
+            if !Arc::ptr_eq(&self.pid_ns_for_children, process.pid_ns()) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the PID namespace for children has already been changed"
+                );
+            }
+            let new_pid_ns = self
+                .pid_ns_for_children
+                .new_child(user_ns.clone(), posix_thread)?;
+            builder.pid_ns_for_children(new_pid_ns);
+        }
+
         if clone_ns_flags.contains(CloneFlags::CLONE_NEWUTS) {
             let new_uts_ns = self.uts_ns.new_clone(user_ns.clone(), posix_thread)?;
             builder.uts_ns(new_uts_ns);
@@ -111,6 +138,19 @@ impl NsProxy {
         &self.mnt_ns
     }
 
+    /// Returns the associated network namespace.
+    pub fn net_ns(&self) -> &Arc<NetNamespace> {
+        &self.net_ns
+    }
+
+    /// Returns the PID namespace for the children.
+    ///
+    /// This namespace may differ from the PID namespace of the process
+    /// after calling `unshare()` or `setns()` with `CLONE_NEWPID`.
+    pub fn pid_ns_for_children(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns_for_children
+    }
+
     /// Returns the associated UTS namespace.
     pub fn uts_ns(&self) -> &Arc<UtsNamespace> {
         &self.uts_ns
@@ -126,6 +166,8 @@ pub struct NsProxyBuilder<'a> {
     cgroup_ns: Option<Arc<CgroupNamespace>>,
     ipc_ns: Option<Arc<IpcNamespace>>,
     mnt_ns: Option<Arc<MountNamespace>>,
+    net_ns: Option<Arc<NetNamespace>>,
+    pid_ns_for_children: Option<Arc<PidNamespace>>,
     uts_ns: Option<Arc<UtsNamespace>>,
 }
 
@@ -137,6 +179,8 @@ impl<'a> NsProxyBuilder<'a> {
             cgroup_ns: None,
             ipc_ns: None,
             mnt_ns: None,
+            net_ns: None,
+            pid_ns_for_children: None,
             uts_ns: None,
         }
     }
@@ -159,6 +203,18 @@ impl<'a> NsProxyBuilder<'a> {
         self
     }
 
+    /// Sets the new network namespace for the context being built.
+    pub fn net_ns(&mut self, net_ns: Arc<NetNamespace>) -> &mut Self {
+        self.net_ns = Some(net_ns);
+        self
+    }
+
+    /// Sets the new PID namespace for children for the context being built.
+    pub fn pid_ns_for_children(&mut self, pid_ns: Arc<PidNamespace>) -> &mut Self {
+        self.pid_ns_for_children = Some(pid_ns);
+        self
+    }
+
     /// Sets the new UTS namespace for the context being built.
     pub fn uts_ns(&mut self, uts_ns: Arc<UtsNamespace>) -> &mut Self {
         self.uts_ns = Some(uts_ns);
@@ -172,18 +228,24 @@ impl<'a> NsProxyBuilder<'a> {
             cgroup_ns: new_cgroup,
             ipc_ns: new_ipc,
             mnt_ns: new_mnt,
+            net_ns: new_net,
+            pid_ns_for_children: new_pid,
             uts_ns: new_uts,
         } = self;
 
         let new_cgroup = new_cgroup.unwrap_or_else(|| old_proxy.cgroup_ns.clone());
         let new_ipc = new_ipc.unwrap_or_else(|| old_proxy.ipc_ns.clone());
         let new_mnt = new_mnt.unwrap_or_else(|| old_proxy.mnt_ns.clone());
+        let new_net = new_net.unwrap_or_else(|| old_proxy.net_ns.clone());
+        let new_pid = new_pid.unwrap_or_else(|| old_proxy.pid_ns_for_children.clone());
         let new_uts = new_uts.unwrap_or_else(|| old_proxy.uts_ns.clone());
 
         NsProxy {
             cgroup_ns: new_cgroup,
             ipc_ns: new_ipc,
             mnt_ns: new_mnt,
+            net_ns: new_net,
+            pid_ns_for_children: new_pid,
             uts_ns: new_uts,
         }
     }
@@ -196,6 +258,8 @@ pub fn check_unsupported_ns_flags(flags: CloneFlags) -> Result<()> {
     const SUPPORTED_FLAGS: CloneFlags = CloneFlags::CLONE_NEWCGROUP
         .union(CloneFlags::CLONE_NEWIPC)
         .union(CloneFlags::CLONE_NEWNS)
+        .union(CloneFlags::CLONE_NEWNET)
+        .union(CloneFlags::CLONE_NEWPID)
         .union(CloneFlags::CLONE_NEWUTS);
 
     let unsupported_flags =
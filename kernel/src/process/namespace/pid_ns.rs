@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    prelude::*,
+    process::{
+        Pid, Process, UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread,
+    },
+    security::lsm::hooks as lsm_hooks,
+};
+
+/// The PID namespace.
+///
+/// PID namespaces form a tree. A thread living in a PID namespace also has an ID in each of the
+/// ancestor namespaces. The IDs used inside the kernel are the IDs in the initial PID namespace
+/// (i.e., the global IDs), while each non-initial namespace maps its own IDs to the global IDs.
+pub struct PidNamespace {
+    level: usize,
+    parent: Option<Arc<PidNamespace>>,
+    owner: Arc<UserNamespace>,
+    ids: Mutex<PidNsIds>,
+    /// The init process of the namespace, which reaps orphaned processes in the namespace.
+    child_reaper: Mutex<Weak<Process>>,
+    stashed_dentry: StashedDentry,
+}
+
+/// The ID mappings of a non-initial PID namespace.
+struct PidNsIds {
+    /// The next ID to allocate.
+    next_nr: Pid,
+    /// Whether new IDs can still be allocated.
+    ///
+    /// This becomes false once the init process of the namespace exits.
+    is_adding: bool,
+    /// The mapping from the IDs in the namespace to the global IDs.
+    nr_to_id: BTreeMap<Pid, Pid>,
+    /// The mapping from the global IDs to the IDs in the namespace.
+    id_to_nr: BTreeMap<Pid, Pid>,
+}
+
+/// The maximum nesting level of PID namespaces.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16/source/include/linux/pid_namespace.h>
+const MAX_PID_NS_LEVEL: usize = 32;
+
+impl PidNamespace {
+    /// Returns a reference to the singleton initial PID namespace.
+    pub fn get_init_singleton() -> &'static Arc<PidNamespace> {
+        static INIT: Once<Arc<PidNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            let owner = UserNamespace::get_init_singleton().clone();
+            Self::new(0, None, owner)
+        })
+    }
+
+    fn new(
+        level: usize,
+        parent: Option<Arc<PidNamespace>>,
+        owner: Arc<UserNamespace>,
+    ) -> Arc<Self> {
+        let ids = PidNsIds {
+            next_nr: 1,
+            is_adding: true,
+            nr_to_id: BTreeMap::new(),
+            id_to_nr: BTreeMap::new(),
+        };
+
+        Arc::new(Self {
+            level,
+            parent,
+            owner,
+            ids: Mutex::new(ids),
+            child_reaper: Mutex::new(Weak::new()),
+            stashed_dentry: StashedDentry::new(),
+        })
+    }
+
+    /// Creates a new child PID namespace of `self`.
+    pub fn new_child(
+        self: &Arc<Self>,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            owner.as_ref(),
+            posix_thread,
+            CapSet::SYS_ADMIN,
+        ))?;
+
+        if self.level >= MAX_PID_NS_LEVEL {
+            return_errno_with_message!(Errno::ENOSPC, "too many nested PID namespaces");
+        }
+
+        Ok(Self::new(self.level + 1, Some(self.clone()), owner))
+    }
+
+    /// Returns the nesting level of the namespace.
+    ///
+    /// The initial PID namespace is at level zero.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the parent namespace, or `None` for the initial PID namespace.
+    pub fn parent_ns(&self) -> Option<&Arc<PidNamespace>> {
+        self.parent.as_ref()
+    }
+
+    /// Returns whether this namespace is the same as, or an ancestor of, the other namespace.
+    pub fn is_same_or_ancestor_of(&self, other: &PidNamespace) -> bool {
+        let mut ns = other;
+        while ns.level > self.level {
+            ns = ns.parent.as_ref().unwrap();
+        }
+        core::ptr::eq(ns, self)
+    }
+
+    /// Translates a global ID to the ID in this namespace.
+    ///
+    /// Returns `None` if the thread (or the process group or the session) with the global ID is
+    /// not visible in this namespace.
+    pub fn nr_of(&self, id: Pid) -> Option<Pid> {
+        if self.level == 0 {
+            return Some(id);
+        }
+        self.ids.lock().id_to_nr.get(&id).copied()
+    }
+
+    /// Translates an ID in this namespace to the global ID.
+    ///
+    /// Returns `None` if no thread uses the ID in this namespace.
+    pub fn id_of(&self, nr: Pid) -> Option<Pid> {
+        if self.level == 0 {
+            return Some(nr);
+        }
+        self.ids.lock().nr_to_id.get(&nr).copied()
+    }
+
+    /// Returns whether new IDs can still be allocated in the namespace.
+    pub(in crate::process) fn is_adding(&self) -> bool {
+        self.ids.lock().is_adding
+    }
+
+    /// Allocates an ID in this namespace for the thread with the global ID.
+    ///
+    /// This method should only be called on non-initial namespaces.
+    pub(in crate::process) fn alloc_nr(&self, id: Pid) -> Pid {
+        debug_assert!(self.level > 0);
+
+        let mut ids = self.ids.lock();
+        let nr = ids.next_nr;
+        ids.next_nr += 1;
+        ids.nr_to_id.insert(nr, id);
+        ids.id_to_nr.insert(id, nr);
+
+        nr
+    }
+
+    /// Releases the ID in this namespace of the thread with the global ID.
+    pub(in crate::process) fn free_nr(&self, id: Pid) {
+        debug_assert!(self.level > 0);
+
+        let mut ids = self.ids.lock();
+        if let Some(nr) = ids.id_to_nr.remove(&id) {
+            ids.nr_to_id.remove(&nr);
+        }
+    }
+
+    /// Stops allocating new IDs in the namespace.
+    ///
+    /// This is called when the init process of the namespace exits.
+    pub(in crate::process) fn disable_adding(&self) {
+        self.ids.lock().is_adding = false;
+    }
+
+    /// Returns the init process of the namespace, if it is alive.
+    pub fn child_reaper(&self) -> Option<Arc<Process>> {
+        self.child_reaper.lock().upgrade()
+    }
+
+    /// Sets the init process of the namespace.
+    pub(in crate::process) fn set_child_reaper(&self, process: &Arc<Process>) {
+        *self.child_reaper.lock() = Arc::downgrade(process);
+    }
+}
+
+impl NsCommonOps for PidNamespace {
+    const TYPE: NsType = NsType::Pid;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        // FIXME: Linux also fails with `EPERM` if the parent namespace is not the same as, or a
+        // descendant of, the PID namespace of the current process.
+        // Reference: <https://elixir.bootlin.com/linux/v6.16/source/kernel/pid_namespace.c>
+        self.parent.as_ref().ok_or_else(|| {
+            Error::with_message(Errno::EPERM, "the initial PID namespace has no parent")
+        })
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
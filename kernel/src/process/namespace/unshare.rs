@@ -42,14 +42,14 @@ impl ContextUnshareAdminApi for Context<'_> {
     }
 
     fn unshare_namespaces(&self, flags: CloneFlags) -> Result<()> {
-        if flags.contains(CloneFlags::CLONE_NEWUSER) {
-            return_errno_with_message!(
-                Errno::EINVAL,
-                "cloning a new user namespace is not supported"
-            );
-        }
-
-        let user_ns_ref = self.thread_local.borrow_user_ns();
+        let new_user_ns = {
+            let user_ns_ref = self.thread_local.borrow_user_ns();
+            if flags.contains(CloneFlags::CLONE_NEWUSER) {
+                user_ns_ref.new_child(self.posix_thread)?
+            } else {
+                user_ns_ref.clone()
+            }
+        };
 
         let mut pthread_ns_proxy = self.posix_thread.ns_proxy().lock();
 
@@ -57,7 +57,7 @@ impl ContextUnshareAdminApi for Context<'_> {
         let thread_local_ns_proxy = thread_local_ns_proxy_ref.unwrap();
 
         let new_ns_proxy = thread_local_ns_proxy.new_clone(
-            &user_ns_ref,
+            &new_user_ns,
             self.process.as_ref(),
             self.posix_thread,
             flags,
@@ -74,6 +74,12 @@ impl ContextUnshareAdminApi for Context<'_> {
         *pthread_ns_proxy = Some(new_ns_proxy.clone());
         *thread_local_ns_proxy = new_ns_proxy;
 
+        if flags.contains(CloneFlags::CLONE_NEWUSER) {
+            // The process is single-threaded, as checked by the caller.
+            *self.process.user_ns().lock() = new_user_ns.clone();
+            *self.thread_local.borrow_user_ns_mut() = new_user_ns;
+        }
+
         Ok(())
     }
 }
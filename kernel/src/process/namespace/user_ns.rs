@@ -5,14 +5,29 @@ use spin::Once;
 use crate::{
     fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
     prelude::*,
-    process::Uid,
+    process::{Uid, credentials::capabilities::CapSet, posix_thread::PosixThread},
+    security::lsm::hooks as lsm_hooks,
 };
 
 /// The user namespace.
+///
+/// User namespaces form a tree. The IDs used inside the kernel are the IDs in the initial user
+/// namespace (i.e., the global IDs), while each non-initial namespace maps its own IDs to the
+/// global IDs via its UID and GID mappings.
 pub struct UserNamespace {
+    level: usize,
+    parent: Option<Arc<UserNamespace>>,
+    owner: Uid,
+    uid_map: Once<IdMap>,
+    gid_map: Once<IdMap>,
     stashed_dentry: StashedDentry,
 }
 
+/// The maximum nesting level of user namespaces.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/user_namespace.c#L92>
+const MAX_USER_NS_LEVEL: usize = 32;
+
 impl UserNamespace {
     /// Returns a reference to the singleton initial user namespace.
     pub fn get_init_singleton() -> &'static Arc<UserNamespace> {
@@ -20,26 +35,150 @@ impl UserNamespace {
 
         INIT.call_once(|| {
             Arc::new(Self {
+                level: 0,
+                parent: None,
+                owner: Uid::new_root(),
+                uid_map: Once::initialized(IdMap::new_identity()),
+                gid_map: Once::initialized(IdMap::new_identity()),
                 stashed_dentry: StashedDentry::new(),
             })
         })
     }
 
+    /// Creates a new child user namespace of `self`.
+    ///
+    /// The new namespace is owned by the effective UID of `posix_thread`. Its UID and GID
+    /// mappings are empty until they are written via `/proc/[pid]/uid_map` and
+    /// `/proc/[pid]/gid_map`.
+    pub fn new_child(self: &Arc<Self>, posix_thread: &PosixThread) -> Result<Arc<Self>> {
+        // FIXME: Linux allows unprivileged threads to create user namespaces, in which they gain
+        // the full set of capabilities. This is safe only if the capabilities and the IDs are
+        // interpreted relative to the user namespaces throughout the kernel, which is not yet the
+        // case. Until then, creating a user namespace requires `CAP_SYS_ADMIN`, and the
+        // credentials of the creator are left unchanged.
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            self.as_ref(),
+            posix_thread,
+            CapSet::SYS_ADMIN,
+        ))?;
+
+        if self.level >= MAX_USER_NS_LEVEL {
+            return_errno_with_message!(Errno::ENOSPC, "too many nested user namespaces");
+        }
+
+        let credentials = posix_thread.credentials();
+        let owner = credentials.euid();
+        if self.uid_map().map_up(owner.into()).is_none()
+            || self.gid_map().map_up(credentials.egid().into()).is_none()
+        {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the owner of the new user namespace has no mapping in the parent namespace"
+            );
+        }
+
+        Ok(Arc::new(Self {
+            level: self.level + 1,
+            parent: Some(self.clone()),
+            owner,
+            uid_map: Once::new(),
+            gid_map: Once::new(),
+            stashed_dentry: StashedDentry::new(),
+        }))
+    }
+
+    /// Returns the parent namespace, or `None` for the initial user namespace.
+    pub fn parent_ns(&self) -> Option<&Arc<UserNamespace>> {
+        self.parent.as_ref()
+    }
+
+    /// Returns the UID mapping of the user namespace.
+    ///
+    /// The mapping translates UIDs inside this namespace (the upper IDs)
+    /// to UIDs in the initial user namespace (the lower IDs).
+    /// It is empty if it has not been written yet.
+    pub fn uid_map(&self) -> &IdMap {
+        self.uid_map.get().unwrap_or(&EMPTY_ID_MAP)
+    }
+
+    /// Returns the GID mapping of the user namespace.
+    ///
+    /// The mapping translates GIDs inside this namespace (the upper IDs)
+    /// to GIDs in the initial user namespace (the lower IDs).
+    /// It is empty if it has not been written yet.
+    pub fn gid_map(&self) -> &IdMap {
+        self.gid_map.get().unwrap_or(&EMPTY_ID_MAP)
+    }
+
+    /// Sets the UID mapping of the user namespace on behalf of `writer`.
+    ///
+    /// The lower IDs in `map` are IDs in the parent namespace. The mapping can be set only once.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/user_namespace.c#L893>
+    pub fn set_uid_map(&self, map: IdMap, writer: &PosixThread) -> Result<()> {
+        let parent = self.check_map_writer(writer, CapSet::SETUID)?;
+        let map = map.map_lower_down(parent.uid_map())?;
+        Self::set_map_once(&self.uid_map, map)
+    }
+
+    /// Sets the GID mapping of the user namespace on behalf of `writer`.
+    ///
+    /// The lower IDs in `map` are IDs in the parent namespace. The mapping can be set only once.
+    pub fn set_gid_map(&self, map: IdMap, writer: &PosixThread) -> Result<()> {
+        let parent = self.check_map_writer(writer, CapSet::SETGID)?;
+        let map = map.map_lower_down(parent.gid_map())?;
+        Self::set_map_once(&self.gid_map, map)
+    }
+
+    fn check_map_writer(&self, writer: &PosixThread, cap: CapSet) -> Result<&Arc<UserNamespace>> {
+        let Some(parent) = self.parent.as_ref() else {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the mappings of the initial user namespace cannot be changed"
+            );
+        };
+
+        // The writer must live in this namespace or its parent.
+        let writer_ns = writer.process().user_ns().lock().clone();
+        if !core::ptr::eq(writer_ns.as_ref(), self) && !Arc::ptr_eq(&writer_ns, parent) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the writer is not in the user namespace or its parent"
+            );
+        }
+
+        // Linux also allows the owner to map its own ID without privileges. This is not supported
+        // because creating the namespace already requires privileges.
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(parent.as_ref(), writer, cap))?;
+
+        Ok(parent)
+    }
+
+    fn set_map_once(slot: &Once<IdMap>, map: IdMap) -> Result<()> {
+        let mut is_set = false;
+        slot.call_once(|| {
+            is_set = true;
+            map
+        });
+        if !is_set {
+            return_errno_with_message!(Errno::EPERM, "the mapping has already been set");
+        }
+
+        Ok(())
+    }
+
     /// Returns the owner UID of the user namespace.
     pub fn owner_uid(&self) -> Result<Uid> {
-        // FIXME: The owner of the user namespace is not yet tracked.
-        // Return the correct user ID once ownership tracking is implemented.
-        Ok(Uid::new_root())
+        Ok(self.owner)
     }
 
     /// Returns whether this namespace is the same as, or an ancestor of, the other namespace.
     pub fn is_same_or_ancestor_of(self: &Arc<Self>, other: &Arc<Self>) -> bool {
-        // FIXME: Creating new user namespaces is not yet supported,
-        // so we simply check pointer equality.
-        // Once user namespace creation is implemented,
-        // this should walk up the ancestor chain to verify
-        // whether `self` is an ancestor of `other`.
-        Arc::ptr_eq(self, other)
+        let mut ns = other;
+        while ns.level > self.level {
+            ns = ns.parent.as_ref().unwrap();
+        }
+        Arc::ptr_eq(ns, self)
     }
 }
 
@@ -50,16 +189,221 @@ impl NsCommonOps for UserNamespace {
         // For user namespaces, `NS_GET_USERNS` returns the parent user namespace
         // rather than an "owner". The initial user namespace has no parent.
         // Reference: <https://elixir.bootlin.com/linux/v6.19/source/kernel/user_namespace.c#L1406>
-        None
+        self.parent.as_ref()
     }
 
     fn parent(&self) -> Result<&Arc<Self>> {
-        // User namespaces do not support `NS_GET_PARENT`.
+        // For user namespaces, `NS_GET_PARENT` is the same as `NS_GET_USERNS`.
         // Reference: <https://elixir.bootlin.com/linux/v6.19/source/kernel/user_namespace.c#L1407>
-        return_errno_with_message!(Errno::EPERM, "user namespaces do not support NS_GET_PARENT");
+        let parent = self.parent.as_ref().ok_or_else(|| {
+            Error::with_message(Errno::EPERM, "the initial user namespace has no parent")
+        })?;
+
+        let current_user_ns = current!().user_ns().lock().clone();
+        if !current_user_ns.is_same_or_ancestor_of(parent) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the current namespace is not an ancestor of the parent user namespace"
+            );
+        }
+
+        Ok(parent)
     }
 
     fn stashed_dentry(&self) -> &StashedDentry {
         &self.stashed_dentry
     }
 }
+
+/// An ID mapping between a user namespace and its parent.
+///
+/// This is the in-kernel representation of `/proc/[pid]/uid_map` and `/proc/[pid]/gid_map`.
+/// Each line of these files describes an [`IdMapExtent`].
+///
+/// Reference: <https://man7.org/linux/man-pages/man7/user_namespaces.7.html>
+#[derive(Clone, Debug)]
+pub struct IdMap {
+    extents: Vec<IdMapExtent>,
+}
+
+/// A contiguous range of IDs in an [`IdMap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdMapExtent {
+    /// The start of the range in the user namespace.
+    pub first: u32,
+    /// The start of the range in the lower user namespace.
+    pub lower_first: u32,
+    /// The length of the range.
+    pub count: u32,
+}
+
+/// The mapping of a user namespace whose mapping has not been written yet.
+static EMPTY_ID_MAP: IdMap = IdMap {
+    extents: Vec::new(),
+};
+
+/// The maximum number of extents in an [`IdMap`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/user_namespace.h#L16>
+const UID_GID_MAP_MAX_EXTENTS: usize = 340;
+
+impl IdMap {
+    /// Creates the identity mapping, which covers all valid IDs.
+    ///
+    /// This is the mapping of the initial user namespace. Note that `u32::MAX` is not covered
+    /// because it represents an invalid ID.
+    pub fn new_identity() -> Self {
+        Self {
+            extents: vec![IdMapExtent {
+                first: 0,
+                lower_first: 0,
+                count: u32::MAX,
+            }],
+        }
+    }
+
+    /// Parses a mapping in the format of `/proc/[pid]/uid_map` and `/proc/[pid]/gid_map`.
+    ///
+    /// Each line contains three numbers separated by white spaces, which are the fields of an
+    /// [`IdMapExtent`]. The extents must not be empty or overlap with each other.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut extents = Vec::new();
+
+        for line in text.lines() {
+            let mut fields = line.split_ascii_whitespace().map(|field| {
+                field
+                    .parse::<u32>()
+                    .map_err(|_| Error::with_message(Errno::EINVAL, "the ID is not a number"))
+            });
+            let (Some(first), Some(lower_first), Some(count), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return_errno_with_message!(Errno::EINVAL, "the line must contain three numbers");
+            };
+            let extent = IdMapExtent {
+                first: first?,
+                lower_first: lower_first?,
+                count: count?,
+            };
+
+            if extent.count == 0
+                || extent.first.checked_add(extent.count).is_none()
+                || extent.lower_first.checked_add(extent.count).is_none()
+            {
+                return_errno_with_message!(Errno::EINVAL, "the ID range is invalid");
+            }
+            if extents.iter().any(|other| extent.overlaps(other)) {
+                return_errno_with_message!(Errno::EINVAL, "the ID ranges overlap");
+            }
+            if extents.len() == UID_GID_MAP_MAX_EXTENTS {
+                return_errno_with_message!(Errno::EINVAL, "there are too many ID ranges");
+            }
+
+            extents.push(extent);
+        }
+
+        if extents.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "the mapping is empty");
+        }
+
+        Ok(Self { extents })
+    }
+
+    /// Returns the extents of the mapping.
+    pub fn extents(&self) -> &[IdMapExtent] {
+        &self.extents
+    }
+
+    /// Maps an ID in the user namespace to the corresponding ID in the lower namespace.
+    ///
+    /// Returns `None` if the ID is not covered by the mapping.
+    pub fn map_down(&self, id: u32) -> Option<u32> {
+        self.extents.iter().find_map(|extent| {
+            let offset = id.checked_sub(extent.first)?;
+            (offset < extent.count).then(|| extent.lower_first + offset)
+        })
+    }
+
+    /// Maps an ID in the lower namespace to the corresponding ID in the user namespace.
+    ///
+    /// Returns `None` if the ID is not covered by the mapping.
+    pub fn map_up(&self, id: u32) -> Option<u32> {
+        self.extents.iter().find_map(|extent| {
+            let offset = id.checked_sub(extent.lower_first)?;
+            (offset < extent.count).then(|| extent.first + offset)
+        })
+    }
+
+    /// Translates the lower IDs of the mapping through `lower_map`.
+    ///
+    /// Each range of lower IDs must be covered by a single extent of `lower_map`.
+    fn map_lower_down(mut self, lower_map: &IdMap) -> Result<Self> {
+        for extent in self.extents.iter_mut() {
+            let last = extent.lower_first + (extent.count - 1);
+            let lower_extent = lower_map.extents.iter().find(|lower_extent| {
+                lower_extent.first <= extent.lower_first
+                    && last - lower_extent.first < lower_extent.count
+            });
+            let Some(lower_extent) = lower_extent else {
+                return_errno_with_message!(
+                    Errno::EPERM,
+                    "the ID range has no mapping in the parent namespace"
+                );
+            };
+            extent.lower_first =
+                lower_extent.lower_first + (extent.lower_first - lower_extent.first);
+        }
+
+        Ok(self)
+    }
+}
+
+impl IdMapExtent {
+    fn overlaps(&self, other: &IdMapExtent) -> bool {
+        let ranges_overlap =
+            |a: u32, b: u32| a < b.saturating_add(other.count) && b < a.saturating_add(self.count);
+        ranges_overlap(self.first, other.first)
+            || ranges_overlap(self.lower_first, other.lower_first)
+    }
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn parse_id_map() {
+        let map = IdMap::parse("0 1000 1\n1 100000 65536\n").unwrap();
+        assert_eq!(map.map_down(0), Some(1000));
+        assert_eq!(map.map_down(2), Some(100001));
+        assert_eq!(map.map_down(65537), None);
+        assert_eq!(map.map_up(1000), Some(0));
+        assert_eq!(map.map_up(999), None);
+    }
+
+    #[ktest]
+    fn parse_invalid_id_map() {
+        assert!(IdMap::parse("").is_err());
+        assert!(IdMap::parse("0 1000").is_err());
+        assert!(IdMap::parse("0 1000 1 2").is_err());
+        assert!(IdMap::parse("0 1000 0").is_err());
+        assert!(IdMap::parse("0 4294967295 1").is_err());
+        assert!(IdMap::parse("0 1000 10\n5 2000 10").is_err());
+        assert!(IdMap::parse("0 1000 10\n100 1005 10").is_err());
+    }
+
+    #[ktest]
+    fn map_lower_ids_down() {
+        let parent_map = IdMap::parse("0 100000 1000\n").unwrap();
+
+        let map = IdMap::parse("0 10 5\n").unwrap();
+        let map = map.map_lower_down(&parent_map).unwrap();
+        assert_eq!(map.map_down(0), Some(100010));
+        assert_eq!(map.map_down(4), Some(100014));
+
+        let map = IdMap::parse("0 995 10\n").unwrap();
+        assert!(map.map_lower_down(&parent_map).is_err());
+    }
+}
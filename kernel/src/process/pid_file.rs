@@ -14,7 +14,7 @@ use crate::{
     },
     prelude::*,
     process::{
-        Process,
+        Pid, PidNamespace, Process,
         signal::{PollHandle, Pollable},
     },
 };
@@ -120,7 +120,11 @@ impl FileLike for PidFile {
     fn dump_proc_fdinfo(self: Arc<Self>, fd_flags: FdFlags) -> Box<dyn Display> {
         struct FdInfo {
             flags: u32,
-            pid: u32,
+            /// The PIDs of the process in the PID namespace of the reader and in each of the
+            /// descendant namespaces that the process lives in.
+            ///
+            /// This is `None` if the process has been reaped.
+            ns_pids: Option<Vec<Pid>>,
         }
 
         impl Display for FdInfo {
@@ -129,9 +133,17 @@ impl FileLike for PidFile {
                 writeln!(f, "flags:\t0{:o}", self.flags)?;
                 writeln!(f, "mnt_id:\t{}", PidfdFs::mount_node().id())?;
                 writeln!(f, "ino:\t{}", PidfdFs::shared_inode().ino())?;
-                writeln!(f, "Pid:\t{}", self.pid)?;
-                // TODO: Currently we do not support PID namespaces. Just print the PID once.
-                writeln!(f, "NSpid:\t{}", self.pid)
+
+                let Some(ns_pids) = self.ns_pids.as_ref() else {
+                    writeln!(f, "Pid:\t-1")?;
+                    return writeln!(f, "NSpid:\t-1");
+                };
+                writeln!(f, "Pid:\t{}", ns_pids[0])?;
+                write!(f, "NSpid:")?;
+                for pid in ns_pids.iter() {
+                    write!(f, "\t{}", pid)?;
+                }
+                writeln!(f)
             }
         }
 
@@ -139,10 +151,39 @@ impl FileLike for PidFile {
         if fd_flags.contains(FdFlags::CLOEXEC) {
             flags |= CreationFlags::O_CLOEXEC.bits();
         }
-        let pid = self.process.upgrade().map_or(u32::MAX, |p| p.pid());
+        let ns_pids = self
+            .process
+            .upgrade()
+            .map(|process| ns_pids_seen_from(&process, current!().pid_ns()));
+
+        Box::new(FdInfo { flags, ns_pids })
+    }
+}
 
-        Box::new(FdInfo { flags, pid })
+/// Returns the PIDs of the process in `reader_ns` and in each of the descendant namespaces
+/// that the process lives in, from the outermost to the innermost.
+///
+/// If the process is not visible in `reader_ns`, the only PID is zero.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/pid.c#L658-L676>
+fn ns_pids_seen_from(process: &Process, reader_ns: &Arc<PidNamespace>) -> Vec<Pid> {
+    let pid = process.pid();
+    if !reader_ns.is_same_or_ancestor_of(process.pid_ns()) {
+        return vec![0];
     }
+
+    let mut ns_pids = Vec::with_capacity(process.pid_ns().level() - reader_ns.level() + 1);
+    let mut ns = process.pid_ns();
+    loop {
+        ns_pids.push(ns.nr_of(pid).unwrap_or(0));
+        if Arc::ptr_eq(ns, reader_ns) {
+            break;
+        }
+        ns = ns.parent_ns().unwrap();
+    }
+    ns_pids.reverse();
+
+    ns_pids
 }
 
 impl Pollable for PidFile {
@@ -9,10 +9,10 @@
 
 use alloc::collections::btree_map::Entry;
 
-use super::{Pgid, Pid, Process, ProcessGroup, Session, Sid};
+use super::{Pgid, Pid, PidNamespace, Process, ProcessGroup, Session, Sid};
 use crate::{
     prelude::*,
-    process::posix_thread::AsPosixThread,
+    process::posix_thread::{AsPosixThread, allocate_posix_tid},
     thread::{Thread, Tid},
 };
 
@@ -22,9 +22,17 @@ static PID_TABLE: Mutex<PidTable> = Mutex::new(PidTable::new());
 ///
 /// Combines the process, process-group, session, and thread tables into a
 /// single structure.
+///
+/// The table is indexed by global IDs (i.e., the IDs in the initial PID namespace). The IDs in
+/// non-initial PID namespaces are translated by [`PidNamespace::nr_of`] and
+/// [`PidNamespace::id_of`].
 pub struct PidTable {
     entries: BTreeMap<u32, Arc<PidEntry>>,
     process_count: usize,
+    /// The PID namespaces in which the IDs are allocated.
+    ///
+    /// IDs that are allocated only in the initial PID namespace are not tracked.
+    id_namespaces: BTreeMap<u32, Arc<PidNamespace>>,
 }
 
 impl PidTable {
@@ -32,6 +40,21 @@ impl PidTable {
         Self {
             entries: BTreeMap::new(),
             process_count: 0,
+            id_namespaces: BTreeMap::new(),
+        }
+    }
+
+    /// Releases the IDs in non-initial PID namespaces once the entry with the global ID is
+    /// removed.
+    fn free_ns_ids(&mut self, id: u32) {
+        let Some(pid_ns) = self.id_namespaces.remove(&id) else {
+            return;
+        };
+
+        let mut pid_ns = pid_ns.as_ref();
+        while let Some(parent_ns) = pid_ns.parent_ns() {
+            pid_ns.free_nr(id);
+            pid_ns = parent_ns;
         }
     }
 
@@ -77,6 +100,7 @@ impl PidTable {
 
         if should_remove {
             map_entry.remove();
+            self.free_ns_ids(tid);
         }
     }
 
@@ -100,6 +124,7 @@ impl PidTable {
 
         if should_remove {
             map_entry.remove();
+            self.free_ns_ids(tid);
         }
 
         Some(thread)
@@ -120,6 +145,11 @@ impl PidTable {
             .and_then(|entry| entry.lock().thread())
     }
 
+    /// Gets a thread by a TID in the PID namespace.
+    pub fn get_thread_in_ns(&self, tid: Tid, pid_ns: &PidNamespace) -> Option<Arc<Thread>> {
+        self.get_thread(pid_ns.id_of(tid)?)
+    }
+
     /// Returns an iterator over threads that have a live thread reference.
     pub fn iter_threads(&self) -> impl Iterator<Item = Arc<Thread>> + '_ {
         self.entries
@@ -138,6 +168,12 @@ impl PidTable {
         let mut entry = entry.lock();
         entry.set_process(process);
         entry.set_thread(&process.main_thread());
+
+        // The first process in a non-initial PID namespace is the init process of the namespace.
+        let pid_ns = process.pid_ns();
+        if pid_ns.level() > 0 && pid_ns.nr_of(pid) == Some(1) {
+            pid_ns.set_child_reaper(process);
+        }
     }
 
     /// Removes a process and its main thread from the table.
@@ -164,6 +200,7 @@ impl PidTable {
 
         if should_remove {
             map_entry.remove();
+            self.free_ns_ids(pid);
         }
     }
 
@@ -174,6 +211,11 @@ impl PidTable {
             .and_then(|entry| entry.lock().process())
     }
 
+    /// Gets a process by a PID in the PID namespace.
+    pub fn get_process_in_ns(&self, pid: Pid, pid_ns: &PidNamespace) -> Option<Arc<Process>> {
+        self.get_process(pid_ns.id_of(pid)?)
+    }
+
     /// Returns an iterator over processes that have a live process reference.
     pub fn iter_processes(&self) -> impl Iterator<Item = Arc<Process>> + '_ {
         self.entries
@@ -209,6 +251,7 @@ impl PidTable {
 
         if should_remove {
             map_entry.remove();
+            self.free_ns_ids(pgid);
         }
     }
 
@@ -249,6 +292,7 @@ impl PidTable {
 
         if should_remove {
             map_entry.remove();
+            self.free_ns_ids(sid);
         }
     }
 
@@ -469,6 +513,67 @@ impl PidEntryInner {
     }
 }
 
+/// Allocates a new ID for a thread in the PID namespace.
+///
+/// The returned ID is the global ID, which is used to index the [`PidTable`]. If `pid_ns` is not
+/// the initial PID namespace, an ID is also allocated in `pid_ns` and each of its ancestors.
+///
+/// # Errors
+///
+/// This method will return `ENOMEM` if the init process of `pid_ns` has exited.
+pub fn alloc_id(pid_ns: &Arc<PidNamespace>) -> Result<AllocatedId> {
+    if pid_ns.level() == 0 {
+        return Ok(AllocatedId(allocate_posix_tid()));
+    }
+
+    let mut pid_table = pid_table_mut();
+
+    if !pid_ns.is_adding() {
+        return_errno_with_message!(
+            Errno::ENOMEM,
+            "the init process of the PID namespace has exited"
+        );
+    }
+
+    let id = allocate_posix_tid();
+
+    let mut ns = pid_ns.as_ref();
+    while let Some(parent_ns) = ns.parent_ns() {
+        ns.alloc_nr(id);
+        ns = parent_ns;
+    }
+    pid_table.id_namespaces.insert(id, pid_ns.clone());
+
+    Ok(AllocatedId(id))
+}
+
+/// An ID allocated by [`alloc_id`].
+///
+/// If the guard is dropped before the ID is inserted into the [`PidTable`] (e.g., because the
+/// `clone` system call fails), the IDs in non-initial PID namespaces are released. Otherwise,
+/// [`Self::commit`] should be called after the insertion, and the IDs will be released once the
+/// entry is removed from the [`PidTable`].
+#[must_use]
+pub struct AllocatedId(Tid);
+
+impl AllocatedId {
+    /// Returns the global ID.
+    pub fn id(&self) -> Tid {
+        self.0
+    }
+
+    /// Commits the ID after it has been inserted into the [`PidTable`].
+    pub fn commit(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl Drop for AllocatedId {
+    fn drop(&mut self) {
+        pid_table_mut().free_ns_ids(self.0);
+    }
+}
+
 /// Acquires a mutable reference to the global PID table.
 pub fn pid_table_mut() -> MutexGuard<'static, PidTable> {
     PID_TABLE.lock()
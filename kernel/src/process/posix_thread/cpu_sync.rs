@@ -278,3 +278,15 @@ impl UserReg for ostd::arch::cpu::context::GsBase {
         self.load(guard)
     }
 }
+
+#[cfg(target_arch = "x86_64")]
+impl UserReg for ostd::arch::cpu::context::DebugRegs {
+    fn save_from_cpu(&mut self) {
+        // The debug registers can only be modified by the kernel, so the in-memory copy is
+        // always up to date.
+    }
+
+    fn restore_to_cpu(&self) {
+        self.load();
+    }
+}
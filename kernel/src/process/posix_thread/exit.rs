@@ -18,6 +18,7 @@ use crate::{
         signal::{constants::SIGKILL, signals::kernel::KernelSignal},
         task_set::TaskSet,
     },
+    security::audit,
     thread::{AsThread, Tid},
 };
 
@@ -78,6 +79,12 @@ fn exit_internal(
 
         posix_thread.set_exit_code(exit_code);
 
+        // Record the state of the current thread if a core dump is in progress.
+        #[cfg(target_arch = "x86_64")]
+        if let Some(core_dump) = tasks.core_dump_mut() {
+            core_dump.record_exiting_thread(ctx, user_ctx);
+        }
+
         // We should only change the thread status when running as the thread, so no race
         // conditions can occur in between.
         if current_thread.is_exited() {
@@ -88,6 +95,9 @@ fn exit_internal(
         tasks.remove_exited(&current_task)
     };
 
+    // Log the system call that terminates the thread, since it never returns.
+    audit::thread_exit(ctx);
+
     // This is put after `current_thread.exit()`,
     // so `attach_tracee` will observe that the tracer has exited while
     // holding the `tracees` lock, and can not race with `clear_tracees`.
@@ -99,7 +99,7 @@ pub struct PosixThread {
     // Ptrace
     /// Status of being traced.
     tracee_status: Once<TraceeStatus>,
-    /// Threads traced by this thread.
+    /// Threads traced by this thread, keyed by their global TIDs.
     tracees: Once<Mutex<BTreeMap<Tid, Arc<Thread>>>>,
 
     /// Exit code of this thread.
@@ -259,7 +259,11 @@ impl PosixThread {
     /// This method does not perform permission checks on user signals.
     /// Therefore, unless the caller can ensure that there are no permission issues,
     /// this method should be used to enqueue kernel signals or fault signals.
-    pub fn enqueue_signal(&self, signal: Box<dyn Signal>) {
+    pub fn enqueue_signal(&self, mut signal: Box<dyn Signal>) {
+        if let Some(process) = self.process.upgrade() {
+            signal.translate_ids(process.pid_ns());
+        }
+
         self.sig_queues.enqueue(signal);
         self.wake_signalled_waker();
     }
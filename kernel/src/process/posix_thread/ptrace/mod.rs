@@ -5,7 +5,7 @@
 use core::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GeneralRegs, GsBase};
+use ostd::arch::cpu::context::{DebugRegs, FpuContext, FsBase, GeneralRegs, GsBase};
 use ostd::{arch::cpu::context::UserContext, sync::Waiter};
 
 use super::{AsPosixThread, PosixThread};
@@ -18,7 +18,8 @@ use crate::{
         signal::{
             DequeuedSignal, PauseReason,
             c_types::siginfo_t,
-            constants::{CLD_TRAPPED, SIGCHLD, SIGKILL, SIGTRAP},
+            constants::{CLD_TRAPPED, SIGCHLD, SIGKILL, SIGSTOP, SIGTRAP},
+            sig_num::SigNum,
             signals::{kernel::KernelSignal, raw::RawSignal, user::UserSignal},
         },
     },
@@ -42,14 +43,31 @@ impl PosixThread {
         self.tracee_status.get().and_then(|status| status.tracer())
     }
 
+    /// Returns whether this thread is traced, but the tracer was not attached with
+    /// `CAP_SYS_PTRACE`.
+    ///
+    /// Such a thread cannot gain privileges when executing programs, since the tracer could
+    /// take control of the privileged program.
+    pub(in crate::process) fn is_ptraced_unsafely(&self) -> bool {
+        self.tracee_status
+            .get()
+            .is_some_and(|status| status.is_ptraced_unsafely())
+    }
+
     /// Sets the tracer of this thread.
     ///
     /// # Errors
     ///
     /// Returns `EPERM` if this thread is already being traced.
-    fn set_tracer(&self, tracer: Weak<Thread>) -> Result<()> {
+    fn set_tracer(
+        &self,
+        tracer: Weak<Thread>,
+        options: PtraceOptions,
+        is_seized: bool,
+        is_capable: bool,
+    ) -> Result<()> {
         let status = self.tracee_status.call_once(TraceeStatus::new);
-        status.set_tracer(tracer)
+        status.set_tracer(tracer, options, is_seized, is_capable)
     }
 
     /// Detaches the tracer of this thread.
@@ -115,11 +133,81 @@ impl PosixThread {
         }
     }
 
-    /// Returns whether a clone-family ptrace event would be required for `clone_args`.
-    pub(in crate::process) fn needs_ptrace_clone_stop(&self, clone_args: &CloneArgs) -> bool {
+    /// Stops this thread at a `PTRACE_EVENT_STOP` if `PTRACE_INTERRUPT` is pending.
+    ///
+    /// May block in the event-stop until the tracer continues the stop,
+    /// or until a `SIGKILL` interrupts it.
+    pub(in crate::process) fn ptrace_may_stop_on_interrupt(
+        &self,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+    ) {
+        if let Some(status) = self.tracee_status.get() {
+            status.ptrace_may_stop_on_interrupt(ctx, user_ctx)
+        }
+    }
+
+    /// Returns whether a `PTRACE_INTERRUPT` is pending on this thread.
+    pub(in crate::process) fn has_pending_ptrace_interrupt(&self) -> bool {
         self.tracee_status
             .get()
-            .is_some_and(|status| status.needs_clone_stop(clone_args))
+            .is_some_and(|status| status.is_interrupt_pending.load(Ordering::Relaxed))
+    }
+
+    /// Returns whether the next syscall of this thread should be skipped
+    /// after its syscall-enter-stop, as requested by `PTRACE_SYSEMU`.
+    pub fn is_ptrace_emulating_syscall(&self) -> bool {
+        self.tracee_status
+            .get()
+            .is_some_and(|status| status.is_emulating_syscall())
+    }
+
+    /// Makes the tracer of this thread trace the new child, if the clone-family ptrace event
+    /// for `clone_args` is enabled.
+    ///
+    /// Returns the event that should be reported after the child starts running.
+    pub(in crate::process) fn ptrace_attach_child(
+        &self,
+        clone_args: &CloneArgs,
+        child_thread: &Arc<Thread>,
+    ) -> Option<PtraceEvent> {
+        if clone_args.flags.contains(CloneFlags::CLONE_UNTRACED) {
+            return None;
+        }
+
+        let child = child_thread.as_posix_thread().unwrap();
+        let (tracer_thread, options, is_seized, is_capable, event) = self
+            .tracee_status
+            .get()?
+            .clone_event(clone_args, child.tid())?;
+
+        // The child inherits whether the tracer was attached with `CAP_SYS_PTRACE`.
+        let tracer = tracer_thread.as_posix_thread().unwrap();
+        tracer
+            .do_attach(
+                &tracer_thread,
+                child_thread.clone(),
+                options,
+                is_seized,
+                is_capable,
+            )
+            .ok()?;
+        if !child.is_traced() {
+            // The tracer has exited in the meantime.
+            return None;
+        }
+
+        // The new child starts with a `SIGSTOP`, or with a `PTRACE_EVENT_STOP` if the
+        // tracer uses `PTRACE_SEIZE`.
+        //
+        // Reference: <https://man7.org/linux/man-pages/man2/ptrace.2.html>
+        if is_seized {
+            let _ = child.ptrace_interrupt();
+        } else {
+            child.enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+        }
+
+        Some(event)
     }
 
     /// Returns the ptrace-stop status changes for the `wait` syscall.
@@ -146,6 +234,36 @@ impl PosixThread {
         Ok(())
     }
 
+    /// Interrupts this thread into a `PTRACE_EVENT_STOP`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EIO` if this thread is not attached by `PTRACE_SEIZE`.
+    pub fn ptrace_interrupt(&self) -> Result<()> {
+        let status = self.get_tracee_status()?;
+
+        status.interrupt()?;
+        self.wake_signalled_waker();
+
+        Ok(())
+    }
+
+    /// Restarts this thread from a `PTRACE_EVENT_STOP` without resuming its execution
+    /// if it is also group-stopped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if this thread is not ptrace-stopped,
+    /// or `EIO` if this thread is not stopped at a `PTRACE_EVENT_STOP` after `PTRACE_SEIZE`.
+    pub fn ptrace_listen(&self) -> Result<()> {
+        let status = self.get_tracee_status()?;
+
+        status.listen()?;
+        self.wake_signalled_waker();
+
+        Ok(())
+    }
+
     /// Gets the general-purpose registers of this thread for ptrace.
     ///
     /// # Errors
@@ -168,6 +286,50 @@ impl PosixThread {
         status.set_regs(regs)
     }
 
+    /// Gets the floating-point registers of this thread for ptrace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if this thread is not ptrace-stopped.
+    #[cfg(target_arch = "x86_64")]
+    pub fn ptrace_get_fpregs(&self) -> Result<[u8; arch_ptrace::USER_FPREGS_SIZE]> {
+        let status = self.get_tracee_status()?;
+        status.get_fpregs()
+    }
+
+    /// Sets the floating-point registers of this thread for ptrace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if this thread is not ptrace-stopped.
+    #[cfg(target_arch = "x86_64")]
+    pub fn ptrace_set_fpregs(&self, fpregs: &[u8; arch_ptrace::USER_FPREGS_SIZE]) -> Result<()> {
+        let status = self.get_tracee_status()?;
+        status.set_fpregs(fpregs)
+    }
+
+    /// Gets at most `max_len` bytes of the extended processor states of this thread for ptrace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if this thread is not ptrace-stopped.
+    #[cfg(target_arch = "x86_64")]
+    pub fn ptrace_get_xstate(&self, max_len: usize) -> Result<Vec<u8>> {
+        let status = self.get_tracee_status()?;
+        status.get_xstate(max_len)
+    }
+
+    /// Sets the extended processor states of this thread for ptrace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if this thread is not ptrace-stopped.
+    #[cfg(target_arch = "x86_64")]
+    pub fn ptrace_set_xstate(&self, xstate: &[u8]) -> Result<()> {
+        let status = self.get_tracee_status()?;
+        status.set_xstate(xstate)
+    }
+
     /// Reads one word in the tracee's USER area.
     ///
     /// # Errors
@@ -256,6 +418,38 @@ impl PosixThread {
 impl PosixThread {
     /// Attaches this tracer to the given tracee.
     ///
+    /// `is_capable` indicates whether the thread that requests the attachment has
+    /// `CAP_SYS_PTRACE` in the user namespace of the tracee. Note that it is the tracee itself
+    /// for `PTRACE_TRACEME`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EPERM` if the tracee is already being traced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tracer_thread` and `self` do not point to the same thread,
+    /// or if `tracee_thread` is not a POSIX thread.
+    pub fn attach_to(
+        &self,
+        tracer_thread: &Arc<Thread>,
+        tracee_thread: Arc<Thread>,
+        is_capable: bool,
+    ) -> Result<()> {
+        self.do_attach(
+            tracer_thread,
+            tracee_thread,
+            PtraceOptions::empty(),
+            false,
+            is_capable,
+        )
+    }
+
+    /// Attaches this tracer to the given tracee by `PTRACE_SEIZE` with the given options.
+    ///
+    /// Unlike [`Self::attach_to`], the tracee is not stopped, and can be later stopped
+    /// by `PTRACE_INTERRUPT`.
+    ///
     /// # Errors
     ///
     /// Returns `EPERM` if the tracee is already being traced.
@@ -264,7 +458,24 @@ impl PosixThread {
     ///
     /// Panics if `tracer_thread` and `self` do not point to the same thread,
     /// or if `tracee_thread` is not a POSIX thread.
-    pub fn attach_to(&self, tracer_thread: &Arc<Thread>, tracee_thread: Arc<Thread>) -> Result<()> {
+    pub fn seize(
+        &self,
+        tracer_thread: &Arc<Thread>,
+        tracee_thread: Arc<Thread>,
+        options: PtraceOptions,
+        is_capable: bool,
+    ) -> Result<()> {
+        self.do_attach(tracer_thread, tracee_thread, options, true, is_capable)
+    }
+
+    fn do_attach(
+        &self,
+        tracer_thread: &Arc<Thread>,
+        tracee_thread: Arc<Thread>,
+        options: PtraceOptions,
+        is_seized: bool,
+        is_capable: bool,
+    ) -> Result<()> {
         debug_assert!(core::ptr::eq(
             tracer_thread.as_posix_thread().unwrap(),
             self
@@ -282,18 +493,46 @@ impl PosixThread {
         }
 
         let tracee = tracee_thread.as_posix_thread().unwrap();
-        tracee.set_tracer(Arc::downgrade(tracer_thread))?;
+        tracee.set_tracer(
+            Arc::downgrade(tracer_thread),
+            options,
+            is_seized,
+            is_capable,
+        )?;
         tracees.insert(tracee.tid(), tracee_thread);
 
         Ok(())
     }
 
+    /// Detaches the tracee with the given global tid from this tracer,
+    /// and delivers the signal `sig_num` to the tracee if it is not `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ESRCH` if there is no tracee with the given tid,
+    /// or if the tracee is not ptrace-stopped.
+    pub fn detach_tracee(&self, tid: Tid, sig_num: Option<SigNum>, ctx: &Context) -> Result<()> {
+        let no_such_tracee = || Error::with_message(Errno::ESRCH, "no such tracee");
+
+        // Lock order: tracer.tracees -> tracee.tracee_status
+        let mut tracees = self.tracees().ok_or_else(no_such_tracee)?.lock();
+        let tracee_thread = tracees.get(&tid).ok_or_else(no_such_tracee)?;
+
+        let tracee = tracee_thread.as_posix_thread().unwrap();
+        tracee.get_tracee_status()?.detach(sig_num, ctx)?;
+        tracee.wake_signalled_waker();
+
+        tracees.remove(&tid);
+
+        Ok(())
+    }
+
     /// Returns the tracee map of this thread if it is a tracer.
     pub(in crate::process) fn tracees(&self) -> Option<&Mutex<BTreeMap<Tid, Arc<Thread>>>> {
         self.tracees.get()
     }
 
-    /// Returns the tracee with the given tid, if it is being traced by this thread.
+    /// Returns the tracee with the given global tid, if it is being traced by this thread.
     ///
     /// # Errors
     ///
@@ -338,6 +577,8 @@ impl PosixThread {
 
 pub(super) struct TraceeStatus {
     is_stopped: AtomicBool,
+    /// Whether a `PTRACE_INTERRUPT` is pending.
+    is_interrupt_pending: AtomicBool,
     state: Mutex<TraceeState>,
 }
 
@@ -345,6 +586,7 @@ impl TraceeStatus {
     pub(super) fn new() -> Self {
         Self {
             is_stopped: AtomicBool::new(false),
+            is_interrupt_pending: AtomicBool::new(false),
             state: Mutex::new(TraceeState::new()),
         }
     }
@@ -353,12 +595,26 @@ impl TraceeStatus {
         self.state.lock().tracer()
     }
 
-    fn set_tracer(&self, tracer: Weak<Thread>) -> Result<()> {
+    fn is_ptraced_unsafely(&self) -> bool {
+        let state = self.state.lock();
+        state.tracer().is_some() && !state.is_tracer_capable
+    }
+
+    fn set_tracer(
+        &self,
+        tracer: Weak<Thread>,
+        options: PtraceOptions,
+        is_seized: bool,
+        is_capable: bool,
+    ) -> Result<()> {
         let mut state = self.state.lock();
         if state.tracer().is_some() {
             return_errno_with_message!(Errno::EPERM, "the thread is already being traced");
         }
         state.tracer = tracer;
+        state.options = options;
+        state.is_seized = is_seized;
+        state.is_tracer_capable = is_capable;
 
         Ok(())
     }
@@ -370,15 +626,39 @@ impl TraceeStatus {
         // Hold the lock first to avoid race conditions.
         let mut state = self.state.lock();
 
+        self.do_detach(&mut state);
+        detach_callback(&state);
+    }
+
+    fn detach(&self, sig_num: Option<SigNum>, ctx: &Context) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        self.do_resume(&mut state, PtraceContRequest::Continue(sig_num), ctx);
+        self.do_detach(&mut state);
+
+        Ok(())
+    }
+
+    fn do_detach(&self, state: &mut TraceeState) {
         state.tracer = Weak::new();
         #[cfg(target_arch = "x86_64")]
         {
             if let Some(regs) = state.general_regs.as_mut() {
                 arch_ptrace::disable_single_step(regs);
             }
+            // FIXME: If the tracee is running when the tracer detaches it (e.g., because the
+            // tracer exits), the hardware breakpoints set by the tracer will remain active.
+            if let Some(debug_regs) = state.debug_regs.as_mut() {
+                *debug_regs = DebugRegs::default();
+            }
         }
         state.is_tracing_syscall = false;
-        detach_callback(&state);
+        state.is_emulating_syscall = false;
+        state.is_seized = false;
+        state.is_tracer_capable = false;
+        self.is_interrupt_pending.store(false, Ordering::Relaxed);
         self.is_stopped.store(false, Ordering::Relaxed);
     }
 
@@ -415,10 +695,11 @@ impl TraceeStatus {
             // If the PTRACE_O_TRACEEXEC option is not in effect, all successful
             // calls to execve(2) by the traced process will cause it to be sent
             // a SIGTRAP signal, giving the parent a chance to gain control
-            // before the new program begins execution.
+            // before the new program begins execution. This does not apply to
+            // tracees attached by `PTRACE_SEIZE`.
             //
             // Reference: <https://man7.org/linux/man-pages/man2/ptrace.2.html>
-            if matches!(&event, PtraceEvent::Exec(_)) {
+            if matches!(&event, PtraceEvent::Exec(_)) && !state.is_seized {
                 ctx.posix_thread
                     .enqueue_signal(Box::new(UserSignal::new_kill(SIGTRAP, ctx)));
             }
@@ -463,6 +744,39 @@ impl TraceeStatus {
         self.do_ptrace_stop(state, tracer, signal, wait_status, None, ctx, user_ctx)
     }
 
+    fn ptrace_may_stop_on_interrupt(&self, ctx: &Context, user_ctx: &mut UserContext) {
+        // Fast path: No interrupts are pending.
+        if !self.is_interrupt_pending.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // Hold the lock first to avoid race conditions.
+        let state = self.state.lock();
+
+        if !self.is_interrupt_pending.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let Some(tracer) = state.tracer() else {
+            return;
+        };
+
+        let event = PtraceEvent::Stop;
+        let siginfo = event.siginfo(ctx);
+        let signal = Box::new(RawSignal::new(siginfo));
+        let signal = DequeuedSignal::FromThread(signal);
+        let wait_status = PtraceWaitStatus::from_event(&event);
+
+        self.do_ptrace_stop(
+            state,
+            tracer,
+            signal,
+            wait_status,
+            Some(event),
+            ctx,
+            user_ctx,
+        );
+    }
+
     #[expect(clippy::too_many_arguments)]
     fn do_ptrace_stop(
         &self,
@@ -486,6 +800,8 @@ impl TraceeStatus {
             let supp = ctx.thread_local.supp_user_context();
             state.fs_base = Some(supp.fs_base().get());
             state.gs_base = Some(supp.gs_base().get());
+            state.debug_regs = Some(supp.debug_regs().get());
+            state.fpu_context = Some(supp.fpu().get());
             state.general_regs = Some(*user_ctx.general_regs());
             state.set_orig_syscall_ret(ctx.thread_local.orig_syscall_ret());
         }
@@ -495,7 +811,11 @@ impl TraceeStatus {
         let tracer = tracer.as_posix_thread().unwrap();
         tracer.enqueue_signal(Box::new(RawSignal::new({
             let mut siginfo = siginfo_t::new(SIGCHLD, CLD_TRAPPED);
-            siginfo.set_pid_uid_by(ctx);
+            // The PID is seen in the PID namespace of the tracer.
+            siginfo.set_pid_uid(
+                tracer.process().nr_in_ns(ctx.process.pid()),
+                ctx.posix_thread.credentials().ruid(),
+            );
             siginfo
         })));
         tracer.process().children_wait_queue().wake_all();
@@ -517,9 +837,12 @@ impl TraceeStatus {
                 state.general_regs = None;
                 state.fs_base = None;
                 state.gs_base = None;
+                state.debug_regs = None;
+                state.fpu_context = None;
                 state.clear_orig_syscall_ret();
             }
             state.is_tracing_syscall = false;
+            state.is_emulating_syscall = false;
             self.is_stopped.store(false, Ordering::Relaxed);
             return PtraceStopResult::Interrupted;
         };
@@ -535,6 +858,8 @@ impl TraceeStatus {
             let supp = ctx.thread_local.supp_user_context();
             supp.fs_base().set(state.fs_base.take().unwrap());
             supp.gs_base().set(state.gs_base.take().unwrap());
+            supp.debug_regs().set(state.debug_regs.take().unwrap());
+            supp.fpu().set(state.fpu_context.take().unwrap());
             ctx.thread_local
                 .set_orig_syscall_ret(state.take_orig_syscall_ret());
         }
@@ -542,23 +867,75 @@ impl TraceeStatus {
         PtraceStopResult::Continued(signal)
     }
 
-    fn needs_clone_stop(&self, clone_args: &CloneArgs) -> bool {
+    /// Returns the tracer, the options, whether the tracee is seized, and the event
+    /// if the clone-family ptrace event for `clone_args` is enabled.
+    fn clone_event(
+        &self,
+        clone_args: &CloneArgs,
+        child_tid: Tid,
+    ) -> Option<(Arc<Thread>, PtraceOptions, bool, bool, PtraceEvent)> {
+        let state = self.state.lock();
+        let tracer = state.tracer()?;
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/fork.c#L2675-L2684>
+        let event = if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
+            PtraceEvent::Vfork(child_tid)
+        } else if clone_args.exit_signal == Some(SIGCHLD) {
+            PtraceEvent::Fork(child_tid)
+        } else {
+            PtraceEvent::Clone(child_tid)
+        };
+        if !state.options.contains(event.option()) {
+            return None;
+        }
+
+        Some((
+            tracer,
+            state.options,
+            state.is_seized,
+            state.is_tracer_capable,
+            event,
+        ))
+    }
+
+    fn is_emulating_syscall(&self) -> bool {
+        self.state.lock().is_emulating_syscall
+    }
+
+    fn interrupt(&self) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
         let state = self.state.lock();
-        if state.tracer().is_none() {
-            return false;
+        if !state.is_seized {
+            return_errno_with_message!(Errno::EIO, "the thread is not attached by `PTRACE_SEIZE`");
         }
-        let options = state.options;
 
-        if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
-            return options.contains(PtraceOptions::PTRACE_O_TRACEVFORK)
-                || options.contains(PtraceOptions::PTRACE_O_TRACEVFORKDONE);
+        // An interrupt has no effect if the tracee is already ptrace-stopped.
+        if !self.is_ptrace_stopped() {
+            self.is_interrupt_pending.store(true, Ordering::Relaxed);
         }
 
-        if clone_args.exit_signal == Some(SIGCHLD) {
-            return options.contains(PtraceOptions::PTRACE_O_TRACEFORK);
+        Ok(())
+    }
+
+    fn listen(&self) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+        if !state.is_seized || !matches!(state.event, Some(PtraceEvent::Stop)) {
+            return_errno_with_message!(
+                Errno::EIO,
+                "the thread is not stopped at `PTRACE_EVENT_STOP` after `PTRACE_SEIZE`"
+            );
         }
 
-        options.contains(PtraceOptions::PTRACE_O_TRACECLONE)
+        // The tracee leaves the ptrace-stop, but remains stopped if its process is
+        // group-stopped, until the process is continued.
+        //
+        // TODO: Report the continuation of the group-stop as another `PTRACE_EVENT_STOP`.
+        state.signal.clear();
+        self.is_stopped.store(false, Ordering::Relaxed);
+
+        Ok(())
     }
 
     fn is_ptrace_stopped(&self) -> bool {
@@ -590,6 +967,12 @@ impl TraceeStatus {
         let mut state = self.state.lock();
         self.check_ptrace_stopped(&state)?;
 
+        self.do_resume(&mut state, request, ctx);
+
+        Ok(())
+    }
+
+    fn do_resume(&self, state: &mut TraceeState, request: PtraceContRequest, ctx: &Context) {
         if let Some(sig_num) = request.sig_num() {
             let signal = Box::new(UserSignal::new_kill(sig_num, ctx));
             state.signal.inject(signal);
@@ -600,18 +983,17 @@ impl TraceeStatus {
         #[cfg(target_arch = "x86_64")]
         {
             let regs = state.general_regs.as_mut().unwrap();
-            if matches!(request, PtraceContRequest::SingleStep(_)) {
+            if request.is_single_step() {
                 arch_ptrace::enable_single_step(regs);
             } else {
                 arch_ptrace::disable_single_step(regs);
             }
         }
 
-        state.is_tracing_syscall = matches!(request, PtraceContRequest::Syscall(_));
+        state.is_tracing_syscall = request.is_tracing_syscall();
+        state.is_emulating_syscall = request.is_emulating_syscall();
 
         self.is_stopped.store(false, Ordering::Relaxed);
-
-        Ok(())
     }
 
     #[cfg(target_arch = "x86_64")]
@@ -658,10 +1040,12 @@ impl TraceeStatus {
         let general_regs = state.general_regs.as_ref().unwrap();
         let fs_base = state.fs_base.unwrap();
         let gs_base = state.gs_base.unwrap();
+        let debug_regs = state.debug_regs.as_ref().unwrap();
         arch_ptrace::read_user_word(
             general_regs,
             fs_base,
             gs_base,
+            debug_regs,
             state.orig_syscall_ret,
             offset,
         )
@@ -677,12 +1061,14 @@ impl TraceeStatus {
             general_regs,
             fs_base,
             gs_base,
+            debug_regs,
             ..
         } = &mut *state;
         arch_ptrace::write_user_word(
             general_regs.as_mut().unwrap(),
             fs_base.as_mut().unwrap(),
             gs_base.as_mut().unwrap(),
+            debug_regs.as_mut().unwrap(),
             &mut orig_syscall_ret,
             offset,
             value,
@@ -691,6 +1077,44 @@ impl TraceeStatus {
         Ok(())
     }
 
+    #[cfg(target_arch = "x86_64")]
+    fn get_fpregs(&self) -> Result<[u8; arch_ptrace::USER_FPREGS_SIZE]> {
+        // Hold the lock first to avoid race conditions.
+        let state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        Ok(arch_ptrace::read_fpregs(
+            state.fpu_context.as_ref().unwrap(),
+        ))
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn set_fpregs(&self, fpregs: &[u8; arch_ptrace::USER_FPREGS_SIZE]) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        arch_ptrace::write_fpregs(state.fpu_context.as_mut().unwrap(), fpregs)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn get_xstate(&self, max_len: usize) -> Result<Vec<u8>> {
+        // Hold the lock first to avoid race conditions.
+        let state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        arch_ptrace::read_xstate(state.fpu_context.as_ref().unwrap(), max_len)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn set_xstate(&self, xstate: &[u8]) -> Result<()> {
+        // Hold the lock first to avoid race conditions.
+        let mut state = self.state.lock();
+        self.check_ptrace_stopped(&state)?;
+
+        arch_ptrace::write_xstate(state.fpu_context.as_mut().unwrap(), xstate)
+    }
+
     fn peek_data(&self, process: &Weak<Process>, addr: usize) -> Result<usize> {
         // Hold the lock first to avoid race conditions.
         let state = self.state.lock();
@@ -763,8 +1187,15 @@ struct TraceeState {
     event: Option<PtraceEvent>,
     /// The configured ptrace options.
     options: PtraceOptions,
+    /// Whether the tracee is attached by `PTRACE_SEIZE`.
+    is_seized: bool,
+    /// Whether the tracer is attached with `CAP_SYS_PTRACE` in the user namespace of the
+    /// tracee.
+    is_tracer_capable: bool,
     /// Whether the tracee should stop at the next syscall enter or exit.
     is_tracing_syscall: bool,
+    /// Whether the tracee should skip the next syscall after its syscall-enter-stop.
+    is_emulating_syscall: bool,
     /// The general-purpose registers of the tracee at the time of ptrace-stop.
     #[cfg(target_arch = "x86_64")]
     general_regs: Option<GeneralRegs>,
@@ -774,6 +1205,12 @@ struct TraceeState {
     /// The GS base of the tracee at the time of ptrace-stop.
     #[cfg(target_arch = "x86_64")]
     gs_base: Option<GsBase>,
+    /// The debug registers of the tracee at the time of ptrace-stop.
+    #[cfg(target_arch = "x86_64")]
+    debug_regs: Option<DebugRegs>,
+    /// The FPU context of the tracee at the time of ptrace-stop.
+    #[cfg(target_arch = "x86_64")]
+    fpu_context: Option<FpuContext>,
     /// The value of `ThreadLocal::orig_syscall_ret` at the time of ptrace-stop,
     /// or [`Self::NOT_A_SYSCALL`] for non-syscall stops.
     #[cfg(target_arch = "x86_64")]
@@ -787,7 +1224,10 @@ impl TraceeState {
             signal: StopDeliverySignal::default(),
             event: None,
             options: PtraceOptions::empty(),
+            is_seized: false,
+            is_tracer_capable: false,
             is_tracing_syscall: false,
+            is_emulating_syscall: false,
             #[cfg(target_arch = "x86_64")]
             general_regs: None,
             #[cfg(target_arch = "x86_64")]
@@ -795,6 +1235,10 @@ impl TraceeState {
             #[cfg(target_arch = "x86_64")]
             gs_base: None,
             #[cfg(target_arch = "x86_64")]
+            debug_regs: None,
+            #[cfg(target_arch = "x86_64")]
+            fpu_context: None,
+            #[cfg(target_arch = "x86_64")]
             orig_syscall_ret: Self::NOT_A_SYSCALL,
         }
     }
@@ -5,7 +5,7 @@
 use crate::{
     prelude::*,
     process::{
-        ExitCode, WaitOptions,
+        ExitCode, PidNamespace, WaitOptions,
         signal::{
             DequeuedSignal, c_types::siginfo_t, constants::SIGTRAP, sig_num::SigNum,
             signals::Signal,
@@ -20,6 +20,10 @@ pub enum PtraceContRequest {
     #[cfg_attr(not(target_arch = "x86_64"), expect(dead_code))]
     SingleStep(Option<SigNum>),
     Syscall(Option<SigNum>),
+    #[cfg_attr(not(target_arch = "x86_64"), expect(dead_code))]
+    Sysemu(Option<SigNum>),
+    #[cfg_attr(not(target_arch = "x86_64"), expect(dead_code))]
+    SysemuSingleStep(Option<SigNum>),
 }
 
 impl PtraceContRequest {
@@ -27,10 +31,31 @@ impl PtraceContRequest {
         match self {
             Self::Continue(Some(sig_num))
             | Self::SingleStep(Some(sig_num))
-            | Self::Syscall(Some(sig_num)) => Some(*sig_num),
+            | Self::Syscall(Some(sig_num))
+            | Self::Sysemu(Some(sig_num))
+            | Self::SysemuSingleStep(Some(sig_num)) => Some(*sig_num),
             _ => None,
         }
     }
+
+    /// Returns whether the tracee should execute a single instruction and then stop.
+    #[cfg_attr(not(target_arch = "x86_64"), expect(dead_code))]
+    pub(super) fn is_single_step(&self) -> bool {
+        matches!(self, Self::SingleStep(_) | Self::SysemuSingleStep(_))
+    }
+
+    /// Returns whether the tracee should stop at the next syscall.
+    pub(super) fn is_tracing_syscall(&self) -> bool {
+        matches!(
+            self,
+            Self::Syscall(_) | Self::Sysemu(_) | Self::SysemuSingleStep(_)
+        )
+    }
+
+    /// Returns whether the next syscall should be skipped after its syscall-enter-stop.
+    pub(super) fn is_emulating_syscall(&self) -> bool {
+        matches!(self, Self::Sysemu(_) | Self::SysemuSingleStep(_))
+    }
 }
 
 /// The result of a ptrace-stop.
@@ -148,6 +173,8 @@ bitflags! {
 }
 
 /// The events of ptrace-event-stops.
+///
+/// The thread IDs in the events are global IDs.
 #[derive(Debug, Clone)]
 pub enum PtraceEvent {
     /// A `fork` event with the new child thread ID.
@@ -162,6 +189,8 @@ pub enum PtraceEvent {
     VforkDone(Tid),
     /// An `exit` event with the tracee's exit code.
     Exit(ExitCode),
+    /// A stop of a seized tracee, e.g., requested by `PTRACE_INTERRUPT`.
+    Stop,
 }
 
 impl PtraceEvent {
@@ -174,23 +203,33 @@ impl PtraceEvent {
             Self::Exec(_) => 4,
             Self::VforkDone(_) => 5,
             Self::Exit(_) => 6,
+            Self::Stop => 128,
         }
     }
 
     /// Returns the `PtraceOptions` corresponding to this event.
+    ///
+    /// [`Self::Stop`] cannot be disabled, so it corresponds to no options.
     pub(super) const fn option(&self) -> PtraceOptions {
-        PtraceOptions::from_bits(1 << self.code()).unwrap()
+        match self {
+            Self::Stop => PtraceOptions::empty(),
+            _ => PtraceOptions::from_bits(1 << self.code()).unwrap(),
+        }
     }
 
     /// Returns the message of this event.
-    pub const fn message(&self) -> usize {
+    ///
+    /// Thread IDs are translated into `pid_ns`, which should be the PID namespace of the tracer.
+    /// They are zero if the threads are not visible in that namespace.
+    pub fn message(&self, pid_ns: &PidNamespace) -> usize {
         match self {
             Self::Fork(tid)
             | Self::Vfork(tid)
             | Self::Clone(tid)
             | Self::Exec(tid)
-            | Self::VforkDone(tid) => *tid as usize,
+            | Self::VforkDone(tid) => pid_ns.nr_of(*tid).unwrap_or(0) as usize,
             Self::Exit(exit_code) => *exit_code as usize,
+            Self::Stop => 0,
         }
     }
 
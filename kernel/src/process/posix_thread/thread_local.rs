@@ -3,7 +3,7 @@
 use core::cell::{Cell, Ref, RefCell, RefMut};
 
 #[cfg(target_arch = "x86_64")]
-use ostd::arch::cpu::context::{FsBase, GsBase};
+use ostd::arch::cpu::context::{DebugRegs, FsBase, GsBase};
 use ostd::{
     arch::cpu::context::FpuContext, irq::DisabledLocalIrqGuard, sync::RwArc, task::CurrentTask,
 };
@@ -16,6 +16,7 @@ use crate::{
         NsProxy, UserNamespace,
         signal::{SigStack, sig_mask::SigMask},
     },
+    security::audit::AuditContext,
     vm::vmar::VmarHandle,
 };
 
@@ -56,6 +57,10 @@ pub struct ThreadLocal {
     // Namespaces.
     user_ns: RefCell<Arc<UserNamespace>>,
     ns_proxy: RefCell<Option<Arc<NsProxy>>>,
+
+    // Audit.
+    /// The information about the system call being audited.
+    audit_context: RefCell<AuditContext>,
 }
 
 impl ThreadLocal {
@@ -84,6 +89,7 @@ impl ThreadLocal {
             orig_syscall_ret: Cell::new(None),
             user_ns: RefCell::new(user_ns),
             ns_proxy: RefCell::new(Some(ns_proxy)),
+            audit_context: RefCell::new(AuditContext::default()),
         }
     }
 
@@ -192,6 +198,10 @@ impl ThreadLocal {
         self.user_ns.borrow()
     }
 
+    pub(in crate::process) fn borrow_user_ns_mut(&self) -> RefMut<'_, Arc<UserNamespace>> {
+        self.user_ns.borrow_mut()
+    }
+
     pub fn borrow_ns_proxy(&self) -> NsProxyRef<'_> {
         ThreadLocalOptionRef(self.ns_proxy.borrow())
     }
@@ -199,6 +209,10 @@ impl ThreadLocal {
     pub(in crate::process) fn borrow_ns_proxy_mut(&self) -> NsProxyRefMut<'_> {
         ThreadLocalOptionRefMut(self.ns_proxy.borrow_mut())
     }
+
+    pub fn audit_context(&self) -> &RefCell<AuditContext> {
+        &self.audit_context
+    }
 }
 
 /// Supplementary userspace CPU context.
@@ -234,6 +248,8 @@ pub struct SuppUserContext {
     fs_base: CpuSync<FsBase>,
     #[cfg(target_arch = "x86_64")]
     gs_base: CpuSync<GsBase>,
+    #[cfg(target_arch = "x86_64")]
+    debug_regs: CpuSync<DebugRegs>,
 }
 
 impl SuppUserContext {
@@ -244,6 +260,8 @@ impl SuppUserContext {
             fs_base: CpuSync::new(FsBase::default()),
             #[cfg(target_arch = "x86_64")]
             gs_base: CpuSync::new(GsBase::default()),
+            #[cfg(target_arch = "x86_64")]
+            debug_regs: CpuSync::new(DebugRegs::default()),
         }
     }
 
@@ -278,12 +296,18 @@ impl SuppUserContext {
         &self.gs_base
     }
 
+    #[cfg(target_arch = "x86_64")]
+    pub fn debug_regs(&self) -> &CpuSync<DebugRegs> {
+        &self.debug_regs
+    }
+
     pub fn before_schedule(&self, guard: &DisabledLocalIrqGuard) {
         self.fpu.before_schedule(guard);
         #[cfg(target_arch = "x86_64")]
         {
             self.fs_base.before_schedule(guard);
             self.gs_base.before_schedule(guard);
+            self.debug_regs.before_schedule(guard);
         }
     }
 
@@ -293,6 +317,7 @@ impl SuppUserContext {
         {
             self.fs_base.before_user_exec(guard);
             self.gs_base.before_user_exec(guard);
+            self.debug_regs.before_user_exec(guard);
         }
     }
 }
@@ -2,17 +2,18 @@
 
 //! This module defines functions related to spawning the init process.
 
-use ostd::{arch::cpu::context::UserContext, task::Task, user::UserContextApi};
+use ostd::{arch::cpu::context::UserContext, sync::RwArc, task::Task, user::UserContextApi};
 
 use super::{Process, Session};
 use crate::{
     fs::{
+        file::file_table::FileTable,
         thread_info::ThreadFsInfo,
         vfs::path::{FsPath, MountNamespace, Path},
     },
     prelude::*,
     process::{
-        Credentials, ProcessVm, UserNamespace, pid_table,
+        Credentials, PidNamespace, ProcessVm, UserNamespace, pid_table,
         posix_thread::{PosixThreadBuilder, ThreadName, allocate_posix_tid},
         program_loader::ProgramToLoad,
         rlimit::new_resource_limits_for_init,
@@ -34,6 +35,7 @@ pub fn spawn_init_process(
             executable_path,
             with_init_argv0(executable_path, argv),
             envp,
+            None,
         )?
     } else {
         create_default_init_process(argv, envp)?
@@ -66,6 +68,7 @@ fn create_default_init_process(argv: Vec<CString>, envp: Vec<CString>) -> Result
             default_init_exec_path,
             with_init_argv0(default_init_exec_path, argv.clone()),
             envp.clone(),
+            None,
         ) {
             Ok(process) => return Ok(process),
             Err(error) => last_error = Some(error),
@@ -82,10 +85,15 @@ fn with_init_argv0(executable_path: &str, mut argv: Vec<CString>) -> Vec<CString
     argv
 }
 
-fn create_init_process(
+/// Creates a process that runs the executable file in the initial namespaces.
+///
+/// The process is neither scheduled nor inserted to the PID table.
+/// If `file_table` is `None`, the process starts with an empty file table.
+pub(super) fn create_init_process(
     executable_path: &str,
     argv: Vec<CString>,
     envp: Vec<CString>,
+    file_table: Option<RwArc<FileTable>>,
 ) -> Result<Arc<Process>> {
     let fs = {
         let fs_resolver = MountNamespace::get_init_singleton().new_path_resolver();
@@ -101,6 +109,7 @@ fn create_init_process(
     let oom_score_adj = 0;
     let sig_dispositions = Arc::new(Mutex::new(SigDispositions::default()));
     let user_ns = UserNamespace::get_init_singleton().clone();
+    let pid_ns = PidNamespace::get_init_singleton().clone();
 
     let init_proc = Process::new(
         pid,
@@ -110,9 +119,10 @@ fn create_init_process(
         oom_score_adj,
         sig_dispositions,
         user_ns,
+        pid_ns,
     );
 
-    let init_task = create_init_task(pid, &init_proc, fs, vmar, elf_path, argv, envp)?;
+    let init_task = create_init_task(pid, &init_proc, fs, vmar, elf_path, argv, envp, file_table)?;
     init_proc.tasks().lock().insert(init_task).unwrap();
 
     Ok(init_proc)
@@ -133,6 +143,7 @@ fn set_bootstrap_session_and_group(process: &Arc<Process>) {
 }
 
 /// Creates the init task from the given executable file.
+#[expect(clippy::too_many_arguments)]
 fn create_init_task(
     tid: Tid,
     process: &Arc<Process>,
@@ -141,16 +152,19 @@ fn create_init_task(
     elf_path: Path,
     argv: Vec<CString>,
     envp: Vec<CString>,
+    mut file_table: Option<RwArc<FileTable>>,
 ) -> Result<Arc<Task>> {
     let credentials = Credentials::new_root();
 
     let (elf_load_info, elf_abs_path) = {
         let path_resolver = fs.resolver().read();
 
-        let program_to_load =
+        let mut program_to_load =
             ProgramToLoad::build_from_file(elf_path.clone(), &path_resolver, argv, envp)?;
+        let file_table = file_table.get_or_insert_with(|| RwArc::new(FileTable::new()));
+        program_to_load.install_exec_fd(&mut file_table.write())?;
         let vmar = process.lock_vmar();
-        let elf_load_info = program_to_load.load_to_vmar(vmar.unwrap(), &path_resolver)?;
+        let elf_load_info = program_to_load.load_to_vmar(vmar.unwrap(), &path_resolver, false)?;
         let elf_abs_path = path_resolver.make_abs_path(&elf_path).into_string();
 
         (elf_load_info, elf_abs_path)
@@ -162,9 +176,12 @@ fn create_init_task(
 
     let thread_name = ThreadName::new_from_executable_path(&elf_abs_path);
 
-    let thread_builder =
+    let mut thread_builder =
         PosixThreadBuilder::new(tid, thread_name, Box::new(user_ctx), credentials, vmar)
             .process(Arc::downgrade(process))
             .fs(Arc::new(fs));
+    if let Some(file_table) = file_table {
+        thread_builder = thread_builder.file_table(file_table);
+    }
     Ok(thread_builder.build())
 }
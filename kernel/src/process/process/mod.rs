@@ -26,7 +26,7 @@ use crate::{
     fs::cgroupfs::CgroupNode,
     prelude::*,
     process::{
-        UserNamespace, WaitOptions,
+        PidNamespace, UserNamespace, WaitOptions,
         signal::{Pollee, sig_queues::SigQueues},
         status::StopWaitStatus,
     },
@@ -42,6 +42,8 @@ mod process_group;
 mod session;
 mod terminal;
 mod timer_manager;
+#[cfg(target_arch = "x86_64")]
+mod umh;
 
 use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 pub use init_proc::spawn_init_process;
@@ -53,6 +55,8 @@ use ostd::{
 pub use process_group::ProcessGroup;
 pub use session::Session;
 pub use terminal::Terminal;
+#[cfg(target_arch = "x86_64")]
+pub(super) use umh::spawn_user_mode_helper;
 
 /// Process ID.
 pub type Pid = u32;
@@ -154,6 +158,8 @@ pub struct Process {
     // Namespaces
     /// The user namespace
     user_ns: Mutex<Arc<UserNamespace>>,
+    /// The PID namespace in which the process lives.
+    pid_ns: Arc<PidNamespace>,
 }
 
 impl Drop for Process {
@@ -225,6 +231,7 @@ impl Process {
         Some(Task::current()?.as_posix_thread()?.process())
     }
 
+    #[expect(clippy::too_many_arguments)]
     pub(super) fn new(
         pid: Pid,
         vmar: Arc<Vmar>,
@@ -234,6 +241,7 @@ impl Process {
         oom_score_adj: i16,
         sig_dispositions: Arc<Mutex<SigDispositions>>,
         user_ns: Arc<UserNamespace>,
+        pid_ns: Arc<PidNamespace>,
     ) -> Arc<Self> {
         Arc::new_cyclic(|process_ref: &Weak<Process>| {
             // SIGCHID does not interrupt pauser. Child process will
@@ -268,6 +276,7 @@ impl Process {
                 timer_manager,
                 start_time: Jiffies::elapsed(),
                 user_ns: Mutex::new(user_ns),
+                pid_ns,
             }
         })
     }
@@ -336,6 +345,13 @@ impl Process {
         self.parent.pid() == 0
     }
 
+    /// Returns whether the process is the init process of a non-initial PID namespace.
+    pub fn is_pid_ns_init(&self) -> bool {
+        self.pid_ns
+            .child_reaper()
+            .is_some_and(|reaper| core::ptr::eq(reaper.as_ref(), self))
+    }
+
     pub(super) fn children(&self) -> &Mutex<Option<BTreeMap<Pid, Arc<Process>>>> {
         &self.children
     }
@@ -639,11 +655,12 @@ impl Process {
     /// This method does not perform permission checks on user signals.
     /// Therefore, unless the caller can ensure that there are no permission issues,
     /// this method should be used to enqueue kernel signals or fault signals.
-    pub fn enqueue_signal(&self, signal: Box<dyn Signal>) {
+    pub fn enqueue_signal(&self, mut signal: Box<dyn Signal>) {
         if self.status.is_zombie() {
             return;
         }
 
+        signal.translate_ids(&self.pid_ns);
         self.sig_queues.enqueue(signal);
 
         for task in self.tasks.lock().as_slice() {
@@ -787,6 +804,20 @@ impl Process {
         &self.user_ns
     }
 
+    /// Returns the PID namespace in which the process lives.
+    ///
+    /// Unlike other namespaces, the PID namespace of a process never changes.
+    pub fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
+    /// Translates a global ID to the ID seen by the process.
+    ///
+    /// Returns zero if the ID is not visible in the PID namespace of the process.
+    pub fn nr_in_ns(&self, id: u32) -> u32 {
+        self.pid_ns.nr_of(id).unwrap_or(0)
+    }
+
     // ******************* cgroup ********************
 
     /// Returns a RCU read guard to the cgroup of the process.
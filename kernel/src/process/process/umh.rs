@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines functions related to spawning user-mode helpers.
+//!
+//! A user-mode helper is a user program that the kernel starts on its own,
+//! e.g., to receive a core dump via a pipe.
+
+use ostd::sync::RwArc;
+
+use super::{INIT_PROCESS_PID, Process, init_proc::create_init_process};
+use crate::{
+    fs::file::file_table::FileTable,
+    prelude::*,
+    process::{CloneFlags, clone::set_parent_and_group, pid_table, signal::constants::SIGCHLD},
+};
+
+/// Creates and schedules a user-mode helper to run.
+///
+/// Like the init process, the helper runs with root credentials in the initial
+/// namespaces. It is a child of the init process, which will reap it.
+/// The helper uses `file_table` as its file table.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/umh.c#L64-L117>
+pub(in crate::process) fn spawn_user_mode_helper(
+    executable_path: &str,
+    argv: Vec<CString>,
+    envp: Vec<CString>,
+    file_table: RwArc<FileTable>,
+) -> Result<Arc<Process>> {
+    let Some(init_process) = pid_table::pid_table_mut().get_process(INIT_PROCESS_PID) else {
+        return_errno_with_message!(Errno::ESRCH, "the init process does not exist");
+    };
+
+    let process = create_init_process(executable_path, argv, envp, Some(file_table))?;
+    set_parent_and_group(CloneFlags::empty(), &init_process, &process);
+    process.set_exit_signal(SIGCHLD);
+
+    process.run();
+
+    Ok(process)
+}
@@ -4,7 +4,7 @@ use super::{Pgid, Pid};
 use crate::{
     fs::file::file_table::{FileDesc, get_file_fast},
     prelude::*,
-    process::PidFile,
+    process::{PidFile, Process},
 };
 
 #[derive(Clone, Debug)]
@@ -27,8 +27,8 @@ impl ProcessFilter {
 
         match which {
             P_ALL => Ok(ProcessFilter::Any),
-            P_PID => Ok(ProcessFilter::WithPid(id)),
-            P_PGID => Ok(ProcessFilter::WithPgid(id)),
+            P_PID => Ok(ProcessFilter::WithPid(id_from_ns(id, &ctx.process))),
+            P_PGID => Ok(ProcessFilter::WithPgid(id_from_ns(id, &ctx.process))),
             P_PIDFD => {
                 let fd = FileDesc::try_from(id.cast_signed())
                     .map_err(|_| Error::with_message(Errno::EINVAL, "the pidfd is invalid"))?;
@@ -56,7 +56,8 @@ impl ProcessFilter {
         } else if wait_pid < -1 {
             // "wait for any child process whose process group ID is equal to the absolute value of
             // `pid`"
-            Ok(ProcessFilter::WithPgid((-wait_pid).cast_unsigned()))
+            let pgid = id_from_ns((-wait_pid).cast_unsigned(), &current!());
+            Ok(ProcessFilter::WithPgid(pgid))
         } else if wait_pid == -1 {
             // "wait for any child process"
             Ok(ProcessFilter::Any)
@@ -67,7 +68,18 @@ impl ProcessFilter {
             Ok(ProcessFilter::WithPgid(pgid))
         } else {
             // "wait for the child whose process ID is equal to the value of `pid`"
-            Ok(ProcessFilter::WithPid(wait_pid.cast_unsigned()))
+            let pid = id_from_ns(wait_pid.cast_unsigned(), &current!());
+            Ok(ProcessFilter::WithPid(pid))
         }
     }
 }
+
+/// Translates an ID in the PID namespace of `process` to the global ID.
+///
+/// If the ID is not visible in the PID namespace, an invalid ID that matches no process and no
+/// process group is returned.
+fn id_from_ns(nr: u32, process: &Process) -> u32 {
+    const INVALID_ID: u32 = u32::MAX;
+
+    process.pid_ns().id_of(nr).unwrap_or(INVALID_ID)
+}
@@ -12,9 +12,9 @@
 mod heap;
 mod init_stack;
 
-use core::ops::Range;
 #[cfg(target_arch = "riscv64")]
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::AtomicUsize;
+use core::{ops::Range, sync::atomic::Ordering};
 
 use ostd::task::disable_preempt;
 
@@ -28,6 +28,7 @@ pub use self::{
 use crate::{
     fs::vfs::path::Path,
     prelude::*,
+    process::coredump::{AtomicCoreDumpFilter, AtomicDumpable, CoreDumpFilter, Dumpable},
     vm::vmar::{Vmar, VmarHandle},
 };
 
@@ -77,6 +78,10 @@ pub struct ProcessVm {
     data_range: SpinLock<Range<Vaddr>>,
     /// The executable file.
     executable_file: Path,
+    /// Whether core dumps can be produced.
+    dumpable: AtomicDumpable,
+    /// The types of mappings written to core dumps.
+    coredump_filter: AtomicCoreDumpFilter,
     /// The base address for vDSO segment
     #[cfg(target_arch = "riscv64")]
     vdso_base: AtomicUsize,
@@ -91,6 +96,8 @@ impl ProcessVm {
             code_range: SpinLock::new(0..0),
             data_range: SpinLock::new(0..0),
             executable_file,
+            dumpable: AtomicDumpable::new(Dumpable::User),
+            coredump_filter: AtomicCoreDumpFilter::new(CoreDumpFilter::default()),
             #[cfg(target_arch = "riscv64")]
             vdso_base: AtomicUsize::new(0),
         }
@@ -104,6 +111,8 @@ impl ProcessVm {
             code_range: SpinLock::new(process_vm.code_range.lock().clone()),
             data_range: SpinLock::new(process_vm.data_range.lock().clone()),
             executable_file: process_vm.executable_file.clone(),
+            dumpable: AtomicDumpable::new(process_vm.dumpable()),
+            coredump_filter: AtomicCoreDumpFilter::new(process_vm.coredump_filter()),
             #[cfg(target_arch = "riscv64")]
             vdso_base: AtomicUsize::new(process_vm.vdso_base.load(Ordering::Relaxed)),
         }
@@ -134,6 +143,26 @@ impl ProcessVm {
         &self.executable_file
     }
 
+    /// Returns whether core dumps can be produced.
+    pub fn dumpable(&self) -> Dumpable {
+        self.dumpable.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether core dumps can be produced.
+    pub fn set_dumpable(&self, dumpable: Dumpable) {
+        self.dumpable.store(dumpable, Ordering::Relaxed);
+    }
+
+    /// Returns the types of mappings written to core dumps.
+    pub fn coredump_filter(&self) -> CoreDumpFilter {
+        self.coredump_filter.load(Ordering::Relaxed)
+    }
+
+    /// Sets the types of mappings written to core dumps.
+    pub fn set_coredump_filter(&self, filter: CoreDumpFilter) {
+        self.coredump_filter.store(filter, Ordering::Relaxed);
+    }
+
     /// Maps and writes the initial portion of the main stack of a process.
     pub(super) fn map_and_write_init_stack(
         &self,
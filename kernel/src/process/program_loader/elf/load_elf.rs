@@ -11,11 +11,14 @@ use super::{
     relocate::RelocatedRange,
 };
 use crate::{
-    fs::vfs::path::{FsPath, Path, PathResolver},
+    fs::{
+        file::file_table::FileDesc,
+        vfs::path::{FsPath, Path, PathResolver},
+    },
     prelude::*,
     process::{
         process_vm::{AuxKey, AuxVec},
-        program_loader::check_executable_inode,
+        program_loader::check_executable_file,
     },
     util::random::getrandom,
     vm::{
@@ -47,6 +50,10 @@ pub struct ElfLoadInfo {
 ///
 /// This function will map ELF segments and
 /// initialize the init stack and heap.
+///
+/// If `exec_fd` is `Some(_)`, it is the file descriptor of the binary that the ELF file
+/// interprets, and it is passed in the auxiliary vector. `is_secure` is passed in the auxiliary
+/// vector as `AT_SECURE`.
 pub fn load_elf_to_vmar(
     vmar: &Vmar,
     elf_file: Path,
@@ -54,15 +61,17 @@ pub fn load_elf_to_vmar(
     elf_headers: ElfHeaders,
     argv: Vec<CString>,
     envp: Vec<CString>,
+    exec_fd: Option<FileDesc>,
+    is_secure: bool,
 ) -> Result<ElfLoadInfo> {
     let ldso = lookup_and_parse_ldso(&elf_headers, &elf_file, path_resolver)?;
 
-    #[cfg_attr(
-        not(any(target_arch = "x86_64", target_arch = "riscv64")),
-        expect(unused_mut)
-    )]
     let (elf_mapped_info, entry_point, mut aux_vec) =
         map_vmos_and_build_aux_vec(vmar, ldso, &elf_headers, &elf_file)?;
+    if let Some(exec_fd) = exec_fd {
+        aux_vec.set(AuxKey::AT_EXECFD, exec_fd.into());
+    }
+    aux_vec.set(AuxKey::AT_SECURE, is_secure as u64);
     vmar.process_vm()
         .set_code_range(elf_mapped_info.code_range.clone());
     vmar.process_vm()
@@ -118,8 +127,8 @@ fn lookup_and_parse_ldso(
     };
 
     let ldso_elf = {
+        check_executable_file(&ldso_file)?;
         let inode = ldso_file.inode();
-        check_executable_inode(inode.as_ref())?;
 
         let mut buf = Box::new([0u8; PAGE_SIZE]);
         let len = inode.read_bytes_at(0, &mut *buf)?;
@@ -158,15 +167,6 @@ fn map_vmos_and_build_aux_vec(
         init_aux_vec(parsed_elf, &elf_mapped_info.full_range, ldso_base)?
     };
 
-    // Set AT_SECURE based on setuid/setgid bits of the executable file.
-    let mode = elf_file.inode().mode()?;
-    let secure = if mode.has_set_uid() || mode.has_set_gid() {
-        1
-    } else {
-        0
-    };
-    aux_vec.set(AuxKey::AT_SECURE, secure);
-
     let entry_point = if let Some(ldso_load_info) = ldso_load_info {
         ldso_load_info.entry_point
     } else {
@@ -9,13 +9,15 @@ use self::{
 };
 use crate::{
     fs::{
-        file::{InodeType, Permission},
-        vfs::{
-            inode::Inode,
-            path::{FsPath, Path, PathResolver},
+        binfmt_misc::{self, BinfmtFlags},
+        file::{
+            AccessMode, InodeHandle, InodeType, Permission, StatusFlags,
+            file_table::{FdFlags, FileDesc, FileTable},
         },
+        vfs::path::{FsPath, Path, PathResolver},
     },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     vm::vmar::Vmar,
 };
 
@@ -28,33 +30,87 @@ pub(super) struct ProgramToLoad {
     elf_headers: ElfHeaders,
     argv: Vec<CString>,
     envp: Vec<CString>,
+    /// The file whose set-user-ID and set-group-ID bits apply to the new program.
+    creds_file: Path,
+    /// The binary that should be passed to its interpreter as an open file descriptor.
+    exec_file: Option<Path>,
+    /// The file descriptor of `exec_file` in the file table.
+    exec_fd: Option<FileDesc>,
 }
 
 impl ProgramToLoad {
-    /// Constructs a new `ProgramToLoad` from a file and handles shebang interpretation if
-    /// necessary.
+    /// Constructs a new `ProgramToLoad` from a file and handles shebang and `binfmt_misc`
+    /// interpretation if necessary.
     pub(super) fn build_from_file(
         mut elf_file: Path,
         path_resolver: &PathResolver,
         mut argv: Vec<CString>,
         envp: Vec<CString>,
     ) -> Result<Self> {
-        check_executable_inode(elf_file.inode().as_ref())?;
+        check_executable_file(&elf_file)?;
 
-        // A limit to the recursion depth of shebang executables.
+        let mut creds_file = elf_file.clone();
+        let mut exec_file = None;
+
+        // A limit to the recursion depth of interpreted executables.
         //
-        // If the interpreter is a shebang, then recursion will be triggered. If it loops, we
-        // should fail. We follow the same limit as Linux.
+        // If the interpreter needs an interpreter as well, then recursion will be triggered. If
+        // it loops, we should fail. We follow the same limit as Linux.
         let mut recursive_limit = 5;
 
         let (file_first_page, len) = loop {
-            // Read the first page of the file, which should contain a shebang or an ELF header.
+            // Like Linux, the check runs on each pass, so the interpreters of scripts and
+            // `binfmt_misc` entries are checked as well.
+            lsm_hooks::with_current_posix_thread(|posix_thread| {
+                lsm_hooks::on_bprm_check(lsm_hooks::BprmContext::new(posix_thread, &elf_file))
+            })?;
+
+            // Read the first page of the file, which should contain a shebang or an ELF header,
+            // unless the file is recognized by `binfmt_misc`.
             let (file_first_page, len) = {
                 let mut buffer = Box::new([0u8; PAGE_SIZE]);
                 let len = elf_file.inode().read_bytes_at(0, &mut *buffer)?;
                 (buffer, len)
             };
 
+            if let Some(entry) = binfmt_misc::find_entry(&file_first_page[..len], &elf_file.name())
+            {
+                if recursive_limit == 0 {
+                    return_errno_with_message!(Errno::ELOOP, "the recursive limit is reached");
+                }
+                recursive_limit -= 1;
+
+                let interpreter = if let Some(interpreter) = entry.interpreter_file() {
+                    interpreter.clone()
+                } else {
+                    let fs_path = FsPath::try_from(entry.interpreter())?;
+                    path_resolver.lookup(&fs_path)?
+                };
+                check_executable_file(&interpreter)?;
+
+                // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/binfmt_misc.c#L197-L243>
+                let flags = entry.flags();
+                let filename = path_resolver.make_abs_path(&elf_file).into_string();
+                let mut new_argv =
+                    vec![CString::new(entry.interpreter())?, CString::new(filename)?];
+                if flags.contains(BinfmtFlags::PRESERVE_ARGV0) {
+                    new_argv.extend(argv);
+                } else {
+                    new_argv.extend(argv.into_iter().skip(1));
+                }
+
+                // Update the argument list and the executable inode. Then, try again.
+                argv = new_argv;
+                if flags.contains(BinfmtFlags::OPEN_BINARY) {
+                    exec_file = Some(elf_file.clone());
+                }
+                if !flags.contains(BinfmtFlags::CREDENTIALS) {
+                    creds_file = interpreter.clone();
+                }
+                elf_file = interpreter;
+                continue;
+            }
+
             let Some(mut new_argv) = parse_shebang_line(&file_first_page[..len])? else {
                 break (file_first_page, len);
             };
@@ -69,7 +125,7 @@ impl ProgramToLoad {
                 let fs_path = FsPath::try_from(filename.as_str())?;
                 path_resolver.lookup(&fs_path)?
             };
-            check_executable_inode(interpreter.inode().as_ref())?;
+            check_executable_file(&interpreter)?;
 
             // Update the argument list and the executable inode. Then, try again.
             new_argv.extend(argv);
@@ -84,16 +140,56 @@ impl ProgramToLoad {
             elf_headers,
             argv,
             envp,
+            creds_file,
+            exec_file,
+            exec_fd: None,
         })
     }
 
+    /// Returns the file whose set-user-ID and set-group-ID bits apply to the new program.
+    ///
+    /// This is the executable file itself unless a `binfmt_misc` interpreter without the `C`
+    /// flag is involved, in which case it is the interpreter.
+    pub(super) fn creds_file(&self) -> &Path {
+        &self.creds_file
+    }
+
+    /// Installs the binary into `file_table` if it should be passed to its interpreter as an
+    /// open file descriptor.
+    ///
+    /// The file descriptor is recorded in the auxiliary vector as `AT_EXECFD` and is returned.
+    pub(super) fn install_exec_fd(
+        &mut self,
+        file_table: &mut FileTable,
+    ) -> Result<Option<FileDesc>> {
+        let Some(exec_file) = self.exec_file.as_ref() else {
+            return Ok(None);
+        };
+
+        // Like Linux, the binary is passed as it is opened for execution, so read permission is
+        // not required.
+        let file = InodeHandle::new_unchecked_access(
+            exec_file.clone(),
+            AccessMode::O_RDONLY,
+            StatusFlags::empty(),
+        )?;
+        let fd = file_table.insert(Arc::new(file), FdFlags::empty());
+        self.exec_fd = Some(fd);
+
+        Ok(Some(fd))
+    }
+
     /// Loads the executable into the specified virtual memory space.
     ///
+    /// If `is_secure` is true, the program is executed in the secure-execution mode, which is
+    /// indicated by `AT_SECURE` in the auxiliary vector.
+    ///
     /// Returns the information about the ELF loading process.
     pub(super) fn load_to_vmar(
         self,
         vmar: &Vmar,
         path_resolver: &PathResolver,
+        is_secure: bool,
     ) -> Result<ElfLoadInfo> {
         let elf_load_info = load_elf_to_vmar(
             vmar,
@@ -102,13 +198,16 @@ impl ProgramToLoad {
             self.elf_headers,
             self.argv,
             self.envp,
+            self.exec_fd,
+            is_secure,
         )?;
 
         Ok(elf_load_info)
     }
 }
 
-fn check_executable_inode(inode: &dyn Inode) -> Result<()> {
+fn check_executable_file(file: &Path) -> Result<()> {
+    let inode = file.inode();
     if inode.type_().is_directory() {
         return_errno_with_message!(Errno::EISDIR, "the inode is a directory");
     }
@@ -121,9 +220,16 @@ fn check_executable_inode(inode: &dyn Inode) -> Result<()> {
         return_errno_with_message!(Errno::EACCES, "the inode is not a regular file");
     }
 
-    if inode.check_permission(Permission::MAY_EXEC).is_err() {
+    if file.check_permission(Permission::MAY_EXEC).is_err() {
         return_errno_with_message!(Errno::EACCES, "the inode is not executable");
     }
 
-    Ok(())
+    // Like Linux, the file is opened for reading in order to be executed.
+    lsm_hooks::with_current_posix_thread(|posix_thread| {
+        lsm_hooks::on_file_open(lsm_hooks::FileOpenContext::new(
+            posix_thread,
+            file,
+            Permission::MAY_READ | Permission::MAY_EXEC,
+        ))
+    })
 }
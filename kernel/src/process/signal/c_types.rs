@@ -64,8 +64,12 @@ impl siginfo_t {
         self.siginfo_fields.common_mut().first = pid_uid;
     }
 
+    /// Sets the PID and UID to those of the current process, as seen by the current process.
     pub fn set_pid_uid_by(&mut self, ctx: &Context) {
-        self.set_pid_uid(ctx.process.pid(), ctx.posix_thread.credentials().ruid());
+        self.set_pid_uid(
+            ctx.process.nr_in_ns(ctx.process.pid()),
+            ctx.posix_thread.credentials().ruid(),
+        );
     }
 
     pub fn set_status(&mut self, status: i32) {
@@ -35,8 +35,12 @@ use crate::{
     prelude::*,
     process::{
         TermStatus,
+        coredump::do_coredump,
         posix_thread::{ContextPthreadAdminApi, do_exit_group, ptrace::PtraceStopResult},
-        signal::{c_types::stack_t, constants::SIGKILL},
+        signal::{
+            c_types::stack_t,
+            constants::{SIGKILL, SIGSTOP},
+        },
     },
 };
 
@@ -61,6 +65,9 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
         None
     };
 
+    // Stop at the `PTRACE_EVENT_STOP` requested by `PTRACE_INTERRUPT` before handling signals.
+    ctx.posix_thread.ptrace_may_stop_on_interrupt(ctx, user_ctx);
+
     let mut restore_sig_mask = ctx
         .thread_local
         .sig_mask_saved()
@@ -183,12 +190,30 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
             // "The only signals that can be sent to process ID 1, the init process, are those for
             // which init has explicitly installed signal handlers."
         }
+        SigAction::Dfl
+            if ctx.process.is_pid_ns_init() && sig_num != SIGKILL && sig_num != SIGSTOP =>
+        {
+            // The same applies to the init process of a PID namespace, except that `SIGKILL` and
+            // `SIGSTOP` sent from an ancestor namespace are delivered. Those sent from inside the
+            // namespace have already been dropped when they are sent (see `kill_process`).
+            //
+            // Reference: <https://elixir.bootlin.com/linux/v6.16/source/kernel/signal.c>
+        }
         SigAction::Dfl => {
             let sig_default_action = SigDefaultAction::from_signum(sig_num);
             debug!("sig_default_action = {:?}", sig_default_action);
 
             match sig_default_action {
-                SigDefaultAction::Core | SigDefaultAction::Term => {
+                SigDefaultAction::Core => {
+                    warn!(
+                        "PID {}: dumping core on signal {}",
+                        ctx.process.pid(),
+                        sig_num.sig_name()
+                    );
+                    let term_status = do_coredump(&signal.to_info(), ctx, user_ctx);
+                    do_exit_group(term_status, ctx, user_ctx);
+                }
+                SigDefaultAction::Term => {
                     warn!(
                         "PID {}: terminating on signal {}",
                         ctx.process.pid(),
@@ -129,6 +129,11 @@ impl HandlePendingSignal for PosixThread {
 }
 
 fn has_pending_signal(posix_thread: &PosixThread, process: &Process) -> bool {
+    // A pending `PTRACE_INTERRUPT` also needs to be handled before returning to the user space.
+    if posix_thread.has_pending_ptrace_interrupt() {
+        return true;
+    }
+
     // Fast path: No signals are pending.
     if posix_thread.sig_queues().is_empty() && process.sig_queues().is_empty() {
         return false;
@@ -8,10 +8,16 @@ pub mod user;
 use core::{any::Any, fmt::Debug};
 
 use super::{c_types::siginfo_t, sig_num::SigNum};
+use crate::process::PidNamespace;
 
 pub trait Signal: Send + Sync + Debug + Any {
     /// Returns the number of the signal.
     fn num(&self) -> SigNum;
+    /// Translates the process IDs carried by the signal to the IDs in the PID namespace of the
+    /// receiver.
+    ///
+    /// This is called whenever the signal is enqueued, so it must be idempotent.
+    fn translate_ids(&mut self, _pid_ns: &PidNamespace) {}
     /// Returns the siginfo_t that gives more details about a signal.
     fn to_info(&self) -> siginfo_t;
 }
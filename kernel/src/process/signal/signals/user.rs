@@ -6,7 +6,7 @@ use super::Signal;
 use crate::{
     context::Context,
     process::{
-        Pid, Uid,
+        Pid, PidNamespace, Uid,
         signal::{
             c_types::siginfo_t,
             constants::{SI_QUEUE, SI_TKILL, SI_USER},
@@ -18,7 +18,12 @@ use crate::{
 #[derive(Clone, Copy, Debug)]
 pub struct UserSignal {
     num: SigNum,
+    /// The global PID of the sender.
     pid: Pid,
+    /// The PID of the sender in the PID namespace of the receiver.
+    ///
+    /// This is zero if the sender is not visible in that namespace.
+    nr: Pid,
     uid: Uid,
     kind: UserSignalKind,
 }
@@ -35,18 +40,19 @@ impl UserSignal {
         Self {
             num,
             pid,
+            nr: pid,
             uid,
             kind,
         }
     }
 
     pub fn new_kill(num: SigNum, ctx: &Context) -> Self {
-        Self {
+        Self::new(
             num,
-            pid: ctx.process.pid(),
-            uid: ctx.posix_thread.credentials().ruid(),
-            kind: UserSignalKind::Kill,
-        }
+            UserSignalKind::Kill,
+            ctx.process.pid(),
+            ctx.posix_thread.credentials().ruid(),
+        )
     }
 
     pub fn pid(&self) -> Pid {
@@ -63,6 +69,10 @@ impl Signal for UserSignal {
         self.num
     }
 
+    fn translate_ids(&mut self, pid_ns: &PidNamespace) {
+        self.nr = pid_ns.nr_of(self.pid).unwrap_or(0);
+    }
+
     fn to_info(&self) -> siginfo_t {
         let code = match self.kind {
             UserSignalKind::Kill => SI_USER,
@@ -71,7 +81,7 @@ impl Signal for UserSignal {
         };
 
         let mut info = siginfo_t::new(self.num, code);
-        info.set_pid_uid(self.pid, self.uid);
+        info.set_pid_uid(self.nr, self.uid);
 
         info
     }
@@ -7,6 +7,8 @@ use ostd::{
     task::{CurrentTask, Task},
 };
 
+#[cfg(target_arch = "x86_64")]
+use super::coredump::CoreDumpState;
 use crate::prelude::*;
 
 /// A task set that maintains all tasks in a POSIX process.
@@ -16,6 +18,8 @@ pub struct TaskSet {
     has_exited_group: bool,
     in_execve: bool,
     execve_waker: Option<Arc<Waker>>,
+    #[cfg(target_arch = "x86_64")]
+    core_dump: Option<CoreDumpState>,
 }
 
 impl TaskSet {
@@ -27,6 +31,8 @@ impl TaskSet {
             has_exited_group: false,
             in_execve: false,
             execve_waker: None,
+            #[cfg(target_arch = "x86_64")]
+            core_dump: None,
         }
     }
 
@@ -119,8 +125,8 @@ impl TaskSet {
 
     /// Registers a waker to be notified when any thread exits.
     ///
-    /// Only a thread performing execve should set this waker; it is used to
-    /// wake the execve-ing thread while it waits for other threads to exit.
+    /// Only a thread performing execve or a core dump should set this waker;
+    /// it is used to wake that thread while it waits for other threads to exit.
     pub(super) fn set_execve_waker(&mut self, waker: Arc<Waker>) {
         debug_assert!(self.execve_waker.is_none());
         self.execve_waker = Some(waker);
@@ -130,6 +136,27 @@ impl TaskSet {
     pub(super) fn clear_execve_waker(&mut self) {
         self.execve_waker = None;
     }
+
+    /// Starts a core dump, which collects the states of the exiting threads.
+    ///
+    /// This method should be called together with [`Self::set_exited_group`].
+    #[cfg(target_arch = "x86_64")]
+    pub(super) fn start_core_dump(&mut self, state: CoreDumpState) {
+        debug_assert!(self.core_dump.is_none());
+        self.core_dump = Some(state);
+    }
+
+    /// Returns the state of the ongoing core dump, if any.
+    #[cfg(target_arch = "x86_64")]
+    pub(super) fn core_dump_mut(&mut self) -> Option<&mut CoreDumpState> {
+        self.core_dump.as_mut()
+    }
+
+    /// Finishes the ongoing core dump and returns its state.
+    #[cfg(target_arch = "x86_64")]
+    pub(super) fn take_core_dump(&mut self) -> Option<CoreDumpState> {
+        self.core_dump.take()
+    }
 }
 
 impl TaskSet {
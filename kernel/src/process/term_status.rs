@@ -1,19 +1,45 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::signal::sig_num::SigNum;
+use super::signal::{
+    constants::{CLD_DUMPED, CLD_EXITED, CLD_KILLED},
+    sig_num::SigNum,
+};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TermStatus {
     Exited(u8),
     Killed(SigNum),
+    /// Killed by a signal after a core dump was written.
+    Dumped(SigNum),
 }
 
 impl TermStatus {
+    /// The bit that is set in the wait status if a core dump was produced.
+    pub const CORE_DUMP_FLAG: u32 = 0x80;
+
     /// Return as a 32-bit integer encoded as specified in wait(2) man page.
     pub fn as_u32(&self) -> u32 {
         match self {
             TermStatus::Exited(status) => (*status as u32) << 8,
             TermStatus::Killed(signum) => signum.as_u8() as u32,
+            TermStatus::Dumped(signum) => signum.as_u8() as u32 | Self::CORE_DUMP_FLAG,
+        }
+    }
+
+    /// Parses a 32-bit integer encoded as specified in wait(2) man page, and returns the
+    /// `si_code` and `si_status` fields of the corresponding `SIGCHLD` signal.
+    pub fn parse_si_code_and_status(exit_code: u32) -> (i32, i32) {
+        const NORMAL_EXIT_MASK: u32 = 0xff;
+
+        // If the process exits normally, the lowest 8 bits of `status_code`
+        // will be zero. In this case, we return the actual exit code by
+        // shifting the `status_code` right by 8 bits.
+        if (exit_code & NORMAL_EXIT_MASK) == 0 {
+            (CLD_EXITED, (exit_code >> 8) as i32)
+        } else if (exit_code & Self::CORE_DUMP_FLAG) != 0 {
+            (CLD_DUMPED, (exit_code & !Self::CORE_DUMP_FLAG) as i32)
+        } else {
+            (CLD_KILLED, exit_code as i32)
         }
     }
 }
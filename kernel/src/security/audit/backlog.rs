@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use ostd::timer::{Jiffies, TIMER_FREQ};
+
+use super::{
+    record::{AuditRecord, AuditStamp},
+    rule,
+    status::{self, FailureMode},
+};
+use crate::{net::socket::netlink::send_audit_record, prelude::*};
+
+/// The records waiting to be sent to the audit daemon.
+static BACKLOG: SpinLock<Backlog> = SpinLock::new(Backlog::new());
+
+/// Serializes the senders so that the records are received in order.
+static SEND_LOCK: Mutex<()> = Mutex::new(());
+
+/// The number of lost records.
+static LOST: AtomicU32 = AtomicU32::new(0);
+
+struct Backlog {
+    records: VecDeque<AuditRecord>,
+    /// The start of the current one-second window for rate limiting.
+    rate_window: u64,
+    /// The number of records accepted in the current window.
+    rate_count: u32,
+}
+
+impl Backlog {
+    const fn new() -> Self {
+        Self {
+            records: VecDeque::new(),
+            rate_window: 0,
+            rate_count: 0,
+        }
+    }
+
+    /// Returns whether one more record can be accepted without exceeding the rate limit.
+    fn check_rate(&mut self, rate_limit: u32) -> bool {
+        if rate_limit == 0 {
+            return true;
+        }
+
+        let now = Jiffies::elapsed().as_u64();
+        if now - self.rate_window >= TIMER_FREQ {
+            self.rate_window = now;
+            self.rate_count = 0;
+        }
+
+        if self.rate_count >= rate_limit {
+            return false;
+        }
+        self.rate_count += 1;
+        true
+    }
+}
+
+/// Logs a record of a new event and sends it to the audit daemon.
+pub(super) fn log_record(type_: u16, body: &str) {
+    submit(AuditRecord::new(type_, &AuditStamp::new(), body));
+    flush_backlog();
+}
+
+/// Appends a record to the backlog.
+///
+/// The record will be sent when [`flush_backlog`] is called. The record is dropped if it is
+/// excluded by the audit rules, or lost if the backlog is full or the rate limit is exceeded.
+pub(super) fn submit(record: AuditRecord) {
+    if rule::is_excluded(record.type_()) {
+        return;
+    }
+
+    let limits = status::backlog_limits();
+
+    let mut backlog = BACKLOG.lock();
+    let reason =
+        if limits.backlog_limit != 0 && backlog.records.len() >= limits.backlog_limit as usize {
+            "backlog limit exceeded"
+        } else if !backlog.check_rate(limits.rate_limit) {
+            "rate limit exceeded"
+        } else {
+            backlog.records.push_back(record);
+            return;
+        };
+    drop(backlog);
+
+    let lost = LOST.fetch_add(1, Ordering::Relaxed) + 1;
+    match limits.failure {
+        FailureMode::Silent => (),
+        FailureMode::Printk => warn!(
+            "audit: {}: audit_lost={} audit_rate_limit={} audit_backlog_limit={}",
+            reason, lost, limits.rate_limit, limits.backlog_limit
+        ),
+        FailureMode::Panic => panic!("audit: {}", reason),
+    }
+}
+
+/// Sends the records in the backlog to the audit daemon.
+///
+/// If there is no audit daemon, the records are printed to the kernel log and then dropped. If
+/// the receive buffer of the audit daemon is full, the remaining records are kept and will be
+/// sent when this function is called again after the audit daemon receives some records.
+pub fn flush_backlog() {
+    let _guard = SEND_LOCK.lock();
+
+    loop {
+        let Some(record) = BACKLOG.lock().records.pop_front() else {
+            return;
+        };
+
+        let Some(port) = status::daemon_port() else {
+            info!(
+                "audit: type={} {}",
+                record.type_(),
+                String::from_utf8_lossy(record.data())
+            );
+            continue;
+        };
+
+        match send_audit_record(port, &record) {
+            Ok(()) => (),
+            Err(err) if err.error() == Errno::EAGAIN => {
+                BACKLOG.lock().records.push_front(record);
+                return;
+            }
+            Err(_) => {
+                // The audit daemon has gone. The record will be printed in the next iteration.
+                status::reset_daemon(port);
+                BACKLOG.lock().records.push_front(record);
+            }
+        }
+    }
+}
+
+/// Returns the number of records in the backlog.
+pub(super) fn len() -> usize {
+    BACKLOG.lock().records.len()
+}
+
+/// Returns the number of lost records.
+pub(super) fn lost() -> u32 {
+    LOST.load(Ordering::Relaxed)
+}
+
+/// Resets the number of lost records to zero and returns the old number.
+pub(super) fn reset_lost() -> u32 {
+    LOST.swap(0, Ordering::Relaxed)
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+/// The per-thread context for auditing system calls.
+///
+/// The context collects the information about the system call being executed (e.g., the path
+/// names that it resolves). The information is logged when the system call exits if the system
+/// call matches the audit rules.
+#[derive(Default)]
+pub struct AuditContext {
+    syscall: Option<AuditSyscall>,
+}
+
+impl AuditContext {
+    /// Starts collecting the information about a system call.
+    pub(super) fn enter(&mut self, syscall: AuditSyscall) {
+        self.syscall = Some(syscall);
+    }
+
+    /// Stops collecting the information and returns the collected information.
+    pub(super) fn exit(&mut self) -> Option<AuditSyscall> {
+        self.syscall.take()
+    }
+
+    /// Returns whether the information about a system call is being collected.
+    pub(super) fn is_active(&self) -> bool {
+        self.syscall.is_some()
+    }
+
+    /// Returns the system call whose information is being collected.
+    pub(super) fn syscall_mut(&mut self) -> Option<&mut AuditSyscall> {
+        self.syscall.as_mut()
+    }
+}
+
+/// The information about a system call.
+pub(super) struct AuditSyscall {
+    /// The system call number.
+    pub(super) number: u64,
+    /// The arguments of the system call.
+    pub(super) args: [u64; 6],
+    /// The return value of the system call, which is available after the system call exits.
+    ///
+    /// The return value is never available if the system call does not return a value (e.g.,
+    /// `rt_sigreturn`) or does not return at all (e.g., `exit`).
+    pub(super) exit: Option<isize>,
+    /// The key of the task rule that forces the system call to be logged, if there is one.
+    ///
+    /// The outer `Option` is `None` if no task rule forces the system call to be logged.
+    pub(super) task_key: Option<Option<String>>,
+    /// The path names resolved by the system call.
+    pub(super) names: Vec<AuditName>,
+    /// The arguments of the new program, if the system call executes a program.
+    pub(super) execve_args: Option<Vec<CString>>,
+}
+
+impl AuditSyscall {
+    pub(super) fn new(number: u64, args: [u64; 6]) -> Self {
+        Self {
+            number,
+            args,
+            exit: None,
+            task_key: None,
+            names: Vec::new(),
+            execve_args: None,
+        }
+    }
+}
+
+/// A path name resolved by a system call.
+pub(super) struct AuditName {
+    /// The path name supplied by user space.
+    pub(super) name: Option<String>,
+    /// The absolute path of the file that the path name refers to.
+    pub(super) abs_path: Option<String>,
+    pub(super) kind: NameKind,
+    /// The attributes of the file, if it exists.
+    pub(super) inode: Option<InodeInfo>,
+}
+
+/// The kind of a path name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum NameKind {
+    /// The path name is resolved to a file.
+    Normal,
+    /// The path name is resolved to the parent directory, since the file does not exist.
+    Parent,
+    /// The path name cannot be resolved.
+    Unknown,
+}
+
+impl NameKind {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Parent => "PARENT",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// The attributes of a file that is referred to by a path name.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct InodeInfo {
+    pub(super) ino: u64,
+    /// The major and minor numbers of the device that contains the file.
+    pub(super) dev: (u32, u32),
+    /// The type and the permissions of the file, in the format of `st_mode`.
+    pub(super) mode: u32,
+    pub(super) uid: Uid,
+    pub(super) gid: Gid,
+    /// The major and minor numbers of the device that the file represents.
+    pub(super) rdev: (u32, u32),
+}
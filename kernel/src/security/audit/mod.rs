@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The audit subsystem.
+//!
+//! The audit subsystem logs security-relevant events, such as system calls that match the audit
+//! rules, changes of the audit configuration, logins, and messages from trusted user-space
+//! programs. Each event is logged as one or more records that share the same stamp. The records
+//! are sent to the audit daemon via its `NETLINK_AUDIT` socket, or printed to the kernel log if
+//! there is no audit daemon.
+//!
+//! The audit daemon and other control programs (e.g., `auditctl`) configure the audit subsystem
+//! and manage the audit rules via `NETLINK_AUDIT` sockets as well.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/audit.c>.
+
+mod backlog;
+mod context;
+mod record;
+mod rule;
+mod status;
+mod syscall;
+mod user;
+
+pub use backlog::flush_backlog;
+pub use context::AuditContext;
+pub use record::{AuditRecord, is_user_message_type};
+pub use rule::{AuditPerm, AuditRule, SyscallClass, add_rule, del_rule, list_rules};
+pub use status::{CAuditStatus, get_status, set_status};
+pub use syscall::{
+    is_auditing_syscall, log_execve_args, log_path, syscall_entry, syscall_exit, thread_exit,
+};
+pub use user::{log_user_message, set_loginuid};
+
+/// The architecture reported in the records and matched by the audit rules.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/audit.h#L404>.
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "riscv64")]
+const AUDIT_ARCH: u32 = 0xc000_00f3;
+#[cfg(target_arch = "loongarch64")]
+const AUDIT_ARCH: u32 = 0xc000_0102;
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::{
+    fmt::{self, Display, Write},
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use crate::{prelude::*, time::clocks::RealTimeClock};
+
+// Types of audit records.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/audit.h>.
+
+/// A message from user space.
+pub const AUDIT_USER: u16 = 1005;
+/// A change of the login user ID.
+pub(super) const AUDIT_LOGIN: u16 = 1006;
+/// The first type of the messages from user-space programs.
+pub const AUDIT_FIRST_USER_MSG: u16 = 1100;
+/// The last type of the messages from user-space programs.
+pub const AUDIT_LAST_USER_MSG: u16 = 1199;
+/// The first type of the second range of the messages from user-space programs.
+pub const AUDIT_FIRST_USER_MSG2: u16 = 2100;
+/// The last type of the second range of the messages from user-space programs.
+pub const AUDIT_LAST_USER_MSG2: u16 = 2999;
+/// The information about a system call.
+pub(super) const AUDIT_SYSCALL: u16 = 1300;
+/// A path name used by a system call.
+pub(super) const AUDIT_PATH: u16 = 1302;
+/// A change of the audit configuration.
+pub(super) const AUDIT_CONFIG_CHANGE: u16 = 1305;
+/// The current working directory of a system call.
+pub(super) const AUDIT_CWD: u16 = 1307;
+/// The arguments of `execve()`.
+pub(super) const AUDIT_EXECVE: u16 = 1309;
+/// The end of a multi-record event.
+pub(super) const AUDIT_EOE: u16 = 1320;
+/// A probe sent to the audit daemon when another process wants to replace it.
+pub(super) const AUDIT_REPLACE: u16 = 1329;
+
+/// Returns whether the record type is reserved for messages from user space.
+pub fn is_user_message_type(type_: u16) -> bool {
+    type_ == AUDIT_USER
+        || (AUDIT_FIRST_USER_MSG..=AUDIT_LAST_USER_MSG).contains(&type_)
+        || (AUDIT_FIRST_USER_MSG2..=AUDIT_LAST_USER_MSG2).contains(&type_)
+}
+
+/// An audit record.
+///
+/// The text of a record starts with the stamp of the event that the record belongs to, e.g.,
+/// `audit(1700000000.123:42): `, followed by `key=value` pairs.
+#[derive(Clone, Debug)]
+pub struct AuditRecord {
+    type_: u16,
+    data: Vec<u8>,
+}
+
+impl AuditRecord {
+    /// Creates a record of an event.
+    pub(super) fn new(type_: u16, stamp: &AuditStamp, body: &str) -> Self {
+        Self {
+            type_,
+            data: format!("{}: {}", stamp, body).into_bytes(),
+        }
+    }
+
+    /// Creates a record that is not part of any event.
+    ///
+    /// Such records are only used to communicate with the audit daemon. They contain binary
+    /// data instead of text.
+    pub(super) fn new_raw(type_: u16, data: &[u8]) -> Self {
+        Self {
+            type_,
+            data: data.to_vec(),
+        }
+    }
+
+    /// Returns the type of the record.
+    pub fn type_(&self) -> u16 {
+        self.type_
+    }
+
+    /// Returns the data of the record.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// The stamp that identifies an audit event.
+///
+/// All records of an event share the same stamp, which consists of the time when the event
+/// happens and a serial number.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct AuditStamp {
+    time: Duration,
+    serial: u32,
+}
+
+impl AuditStamp {
+    /// Creates a stamp for a new event.
+    pub(super) fn new() -> Self {
+        static SERIAL: AtomicU32 = AtomicU32::new(0);
+
+        // The serial number zero is reserved, so it is skipped when the counter wraps around.
+        let serial = loop {
+            let serial = SERIAL.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+            if serial != 0 {
+                break serial;
+            }
+        };
+
+        Self {
+            time: RealTimeClock::get().read_time(),
+            serial,
+        }
+    }
+}
+
+impl Display for AuditStamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "audit({}.{:03}:{})",
+            self.time.as_secs(),
+            self.time.subsec_millis(),
+            self.serial
+        )
+    }
+}
+
+/// Appends a string that may be controlled by user space to the record body.
+///
+/// The string is quoted if it contains only printable characters other than spaces and double
+/// quotes. Otherwise, it is encoded in hexadecimal so that it cannot forge other fields.
+pub(super) fn push_untrusted(body: &mut String, bytes: &[u8]) {
+    let needs_encoding = bytes
+        .iter()
+        .any(|&byte| byte == b'"' || !(0x21..=0x7e).contains(&byte));
+
+    if needs_encoding {
+        for byte in bytes {
+            let _ = write!(body, "{:02X}", byte);
+        }
+    } else {
+        body.push('"');
+        // The bytes are all printable ASCII characters.
+        body.push_str(core::str::from_utf8(bytes).unwrap());
+        body.push('"');
+    }
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn untrusted_strings() {
+        let mut body = String::new();
+        push_untrusted(&mut body, b"/bin/ls");
+        assert_eq!(body, "\"/bin/ls\"");
+
+        let mut body = String::new();
+        push_untrusted(&mut body, b"a b");
+        assert_eq!(body, "612062");
+
+        let mut body = String::new();
+        push_untrusted(&mut body, b"x\"=");
+        assert_eq!(body, "78223D");
+    }
+
+    #[ktest]
+    fn user_message_types() {
+        assert!(is_user_message_type(AUDIT_USER));
+        assert!(is_user_message_type(1112));
+        assert!(is_user_message_type(2500));
+        assert!(!is_user_message_type(AUDIT_SYSCALL));
+        assert!(!is_user_message_type(3000));
+    }
+}
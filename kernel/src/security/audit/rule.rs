@@ -0,0 +1,741 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use ostd::task::Task;
+
+use super::{
+    AUDIT_ARCH, backlog,
+    context::AuditSyscall,
+    record::{AUDIT_CONFIG_CHANGE, push_untrusted},
+    status,
+};
+use crate::{
+    fs::{file::AccessMode, utils::PATH_MAX},
+    prelude::*,
+    process::{
+        Uid,
+        posix_thread::{AsPosixThread, PosixThread},
+    },
+    syscall::classify_syscall,
+};
+
+/// The audit rules.
+static RULES: RwMutex<RuleLists> = RwMutex::new(RuleLists::new());
+
+/// Whether there are rules that may cause system calls to be logged.
+static HAS_SYSCALL_RULES: AtomicBool = AtomicBool::new(false);
+
+/// The maximum number of fields in a rule.
+const AUDIT_MAX_FIELDS: usize = 64;
+/// The number of words in the bitmap of system call numbers.
+const AUDIT_BITMASK_SIZE: usize = 64;
+/// The maximum length of a filter key.
+const AUDIT_MAX_KEY_LEN: usize = 256;
+/// The flag that adds a rule to the head of the list instead of the tail.
+const AUDIT_FILTER_PREPEND: u32 = 0x10;
+/// The bits of the field flags that specify the comparison operator.
+const AUDIT_OPERATORS: u32 = 0x7800_0000;
+
+/// `struct audit_rule_data` in Linux, excluding the trailing string buffer.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/audit.h#L508>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CAuditRuleData {
+    /// The filter list and `AUDIT_FILTER_PREPEND`
+    flags: u32,
+    /// `AUDIT_NEVER` or `AUDIT_ALWAYS`
+    action: u32,
+    field_count: u32,
+    /// Syscall(s) to match
+    mask: [u32; AUDIT_BITMASK_SIZE],
+    fields: [u32; AUDIT_MAX_FIELDS],
+    values: [u32; AUDIT_MAX_FIELDS],
+    fieldflags: [u32; AUDIT_MAX_FIELDS],
+    /// Total length of string fields
+    buflen: u32,
+}
+
+/// The filter lists, which decide when the rules are applied.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+enum FilterList {
+    /// Filters the messages from user space.
+    User = 0,
+    /// Filters the system calls when they enter the kernel.
+    Task = 1,
+    /// Filters the system calls when they exit.
+    Exit = 4,
+    /// Filters all records by their types.
+    Exclude = 5,
+}
+
+/// The actions of the rules.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub(super) enum RuleAction {
+    /// Does not generate records.
+    Never = 0,
+    /// Generates records.
+    Always = 2,
+}
+
+/// The types of the fields of the rules.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+enum FieldType {
+    Pid = 0,
+    Uid = 1,
+    Euid = 2,
+    Suid = 3,
+    Fsuid = 4,
+    Gid = 5,
+    Egid = 6,
+    Sgid = 7,
+    Fsgid = 8,
+    Loginuid = 9,
+    Arch = 11,
+    Msgtype = 12,
+    Ppid = 18,
+    LoginuidSet = 24,
+    Sessionid = 25,
+    Exit = 103,
+    Success = 104,
+    Watch = 105,
+    Perm = 106,
+    Dir = 107,
+    Arg0 = 200,
+    Arg1 = 201,
+    Arg2 = 202,
+    Arg3 = 203,
+    Filterkey = 210,
+}
+
+impl FieldType {
+    /// Returns whether the value of the field is a string.
+    fn has_string_value(self) -> bool {
+        matches!(self, Self::Watch | Self::Dir | Self::Filterkey)
+    }
+}
+
+/// The comparison operators of the fields.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+enum Operator {
+    BitMask = 0x0800_0000,
+    LessThan = 0x1000_0000,
+    GreaterThan = 0x2000_0000,
+    NotEqual = 0x3000_0000,
+    Equal = 0x4000_0000,
+    BitTest = 0x4800_0000,
+    LessThanOrEqual = 0x5000_0000,
+    GreaterThanOrEqual = 0x6000_0000,
+}
+
+impl Operator {
+    fn compare(self, left: u32, right: u32) -> bool {
+        match self {
+            Self::BitMask => left & right != 0,
+            Self::LessThan => left < right,
+            Self::GreaterThan => left > right,
+            Self::NotEqual => left != right,
+            Self::Equal => left == right,
+            Self::BitTest => left & right == right,
+            Self::LessThanOrEqual => left <= right,
+            Self::GreaterThanOrEqual => left >= right,
+        }
+    }
+
+    fn is_equality(self) -> bool {
+        matches!(self, Self::Equal | Self::NotEqual)
+    }
+}
+
+bitflags! {
+    /// The permissions that are matched by the `AUDIT_PERM` field.
+    pub struct AuditPerm: u32 {
+        const EXEC = 1;
+        const WRITE = 2;
+        const READ = 4;
+        const ATTR = 8;
+    }
+}
+
+/// The class of a system call, which determines how the system call accesses files.
+#[derive(Clone, Copy, Debug)]
+pub enum SyscallClass {
+    /// A system call that accesses files with the permissions.
+    Native(AuditPerm),
+    /// A system call that opens a file with the flags in the argument at the index.
+    Open { flags_arg: usize },
+    /// A system call that executes a program.
+    Execve,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FieldValue {
+    Int(u32),
+    Str(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RuleField {
+    type_: FieldType,
+    op: Operator,
+    value: FieldValue,
+}
+
+/// An audit rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditRule {
+    list: FilterList,
+    is_prepended: bool,
+    action: RuleAction,
+    syscalls: [u32; AUDIT_BITMASK_SIZE],
+    fields: Vec<RuleField>,
+}
+
+impl AuditRule {
+    /// Parses a rule from the bytes of `struct audit_rule_data`.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let Some((header_bytes, buf)) = bytes.split_at_checked(size_of::<CAuditRuleData>()) else {
+            return_errno_with_message!(Errno::EINVAL, "the rule is too short");
+        };
+        let data = CAuditRuleData::from_bytes(header_bytes);
+        if data.buflen as usize > buf.len() {
+            return_errno_with_message!(Errno::EINVAL, "the string buffer of the rule is too short");
+        }
+        let mut buf = &buf[..data.buflen as usize];
+
+        let list = FilterList::try_from(data.flags & !AUDIT_FILTER_PREPEND)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the filter list is not supported"))?;
+        let action = RuleAction::try_from(data.action)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the rule action is invalid"))?;
+        if data.field_count as usize > AUDIT_MAX_FIELDS {
+            return_errno_with_message!(Errno::EINVAL, "the rule has too many fields");
+        }
+
+        let mut fields = Vec::with_capacity(data.field_count as usize);
+        for i in 0..data.field_count as usize {
+            let type_ = FieldType::try_from(data.fields[i])
+                .map_err(|_| Error::with_message(Errno::EINVAL, "the field is not supported"))?;
+            let op = Operator::try_from(data.fieldflags[i] & AUDIT_OPERATORS)
+                .map_err(|_| Error::with_message(Errno::EINVAL, "the operator is invalid"))?;
+
+            let value = if type_.has_string_value() {
+                let len = data.values[i] as usize;
+                if len > PATH_MAX {
+                    return_errno_with_message!(Errno::ENAMETOOLONG, "the string is too long");
+                }
+                let Some((string, rest)) = buf.split_at_checked(len) else {
+                    return_errno_with_message!(Errno::EINVAL, "the string exceeds the buffer");
+                };
+                buf = rest;
+                let string = String::from_utf8(string.to_vec()).map_err(|_| {
+                    Error::with_message(Errno::EINVAL, "the string is not valid UTF-8")
+                })?;
+                FieldValue::Str(string)
+            } else {
+                FieldValue::Int(data.values[i])
+            };
+
+            let field = RuleField { type_, op, value };
+            field.validate(list, &fields)?;
+            fields.push(field);
+        }
+
+        Ok(Self {
+            list,
+            is_prepended: data.flags & AUDIT_FILTER_PREPEND != 0,
+            action,
+            syscalls: data.mask,
+            fields,
+        })
+    }
+
+    /// Converts the rule to the bytes of `struct audit_rule_data`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = CAuditRuleData::new_zeroed();
+        let mut buf = Vec::new();
+
+        data.flags = self.list as u32;
+        if self.is_prepended {
+            data.flags |= AUDIT_FILTER_PREPEND;
+        }
+        data.action = self.action as u32;
+        data.field_count = self.fields.len() as u32;
+        data.mask = self.syscalls;
+        for (i, field) in self.fields.iter().enumerate() {
+            data.fields[i] = field.type_ as u32;
+            data.fieldflags[i] = field.op as u32;
+            data.values[i] = match &field.value {
+                FieldValue::Int(value) => *value,
+                FieldValue::Str(string) => {
+                    buf.extend_from_slice(string.as_bytes());
+                    string.len() as u32
+                }
+            };
+        }
+        data.buflen = buf.len() as u32;
+
+        let mut bytes = data.as_bytes().to_vec();
+        bytes.extend_from_slice(&buf);
+        bytes
+    }
+
+    /// Returns the filter key of the rule.
+    fn key(&self) -> Option<&str> {
+        self.fields.iter().find_map(|field| match &field.value {
+            FieldValue::Str(key) if field.type_ == FieldType::Filterkey => Some(key.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns whether the rule applies to the system call.
+    fn contains_syscall(&self, number: u64) -> bool {
+        let (word, bit) = (number as usize / 32, number % 32);
+        self.syscalls
+            .get(word)
+            .is_some_and(|word| word & (1 << bit) != 0)
+    }
+
+    /// Returns whether all fields of the rule match.
+    fn matches(&self, subject: Option<&PosixThread>, target: &MatchTarget) -> bool {
+        self.fields
+            .iter()
+            .all(|field| field.matches(subject, target))
+    }
+}
+
+impl RuleField {
+    /// Validates the field of a rule in the list, given the preceding fields of the rule.
+    fn validate(&self, list: FilterList, preceding_fields: &[RuleField]) -> Result<()> {
+        let has_same_type = |types: &[FieldType]| {
+            preceding_fields
+                .iter()
+                .any(|field| types.contains(&field.type_))
+        };
+
+        let is_valid = match (self.type_, &self.value) {
+            (
+                FieldType::Uid
+                | FieldType::Euid
+                | FieldType::Suid
+                | FieldType::Fsuid
+                | FieldType::Gid
+                | FieldType::Egid
+                | FieldType::Sgid
+                | FieldType::Fsgid
+                | FieldType::Loginuid,
+                FieldValue::Int(id),
+            ) => *id != u32::MAX,
+            (FieldType::Msgtype, _) => matches!(list, FilterList::User | FilterList::Exclude),
+            (FieldType::Arch, _) => self.op.is_equality(),
+            (FieldType::LoginuidSet, FieldValue::Int(value)) => {
+                self.op.is_equality() && *value <= 1
+            }
+            (FieldType::Exit | FieldType::Success | FieldType::Sessionid, _) => {
+                !matches!(self.op, Operator::BitMask | Operator::BitTest)
+            }
+            (FieldType::Perm, FieldValue::Int(perm)) => {
+                self.op.is_equality() && AuditPerm::from_bits(*perm).is_some()
+            }
+            (FieldType::Watch | FieldType::Dir, FieldValue::Str(path)) => {
+                list == FilterList::Exit
+                    && self.op == Operator::Equal
+                    && path.starts_with('/')
+                    && (self.type_ == FieldType::Dir || !path.ends_with('/'))
+                    && !has_same_type(&[FieldType::Watch, FieldType::Dir])
+            }
+            (FieldType::Filterkey, FieldValue::Str(key)) => {
+                key.len() <= AUDIT_MAX_KEY_LEN
+                    && self.op == Operator::Equal
+                    && !has_same_type(&[FieldType::Filterkey])
+            }
+            _ => true,
+        };
+
+        if !is_valid {
+            return_errno_with_message!(Errno::EINVAL, "the field is invalid");
+        }
+        Ok(())
+    }
+
+    fn matches(&self, subject: Option<&PosixThread>, target: &MatchTarget) -> bool {
+        let value = match &self.value {
+            FieldValue::Int(value) => *value,
+            FieldValue::Str(path) => {
+                let names = target.syscall.map_or(&[][..], |syscall| &syscall.names[..]);
+                let mut abs_paths = names.iter().filter_map(|name| name.abs_path.as_deref());
+                return match self.type_ {
+                    FieldType::Watch => abs_paths.any(|abs_path| abs_path == path),
+                    FieldType::Dir => abs_paths.any(|abs_path| is_in_dir(abs_path, path)),
+                    // The filter key is not a condition.
+                    _ => true,
+                };
+            }
+        };
+
+        let left = match self.type_ {
+            FieldType::Arch => AUDIT_ARCH,
+            FieldType::Msgtype => {
+                let Some(msg_type) = target.msg_type else {
+                    return false;
+                };
+                msg_type as u32
+            }
+            FieldType::Exit => {
+                let Some(exit) = target.syscall.and_then(|syscall| syscall.exit) else {
+                    return false;
+                };
+                exit as u32
+            }
+            FieldType::Success => {
+                let Some(exit) = target.syscall.and_then(|syscall| syscall.exit) else {
+                    return false;
+                };
+                // Success and failure are represented by 1 and 2, respectively.
+                let result = if is_success(exit) { 1 } else { 2 };
+                let expected = if value != 0 { 1 } else { 2 };
+                return self.op.compare(result, expected);
+            }
+            FieldType::Arg0 | FieldType::Arg1 | FieldType::Arg2 | FieldType::Arg3 => {
+                let Some(syscall) = target.syscall else {
+                    return false;
+                };
+                let index = self.type_ as usize - FieldType::Arg0 as usize;
+                syscall.args[index] as u32
+            }
+            FieldType::Perm => {
+                let Some(syscall) = target.syscall else {
+                    return false;
+                };
+                let is_matched = perm_matches(AuditPerm::from_bits_truncate(value), syscall);
+                return is_matched == (self.op == Operator::Equal);
+            }
+            _ => {
+                let Some(posix_thread) = subject else {
+                    return false;
+                };
+                subject_field(posix_thread, self.type_)
+            }
+        };
+
+        self.op.compare(left, value)
+    }
+}
+
+/// Returns the value of a field that describes the subject thread.
+fn subject_field(posix_thread: &PosixThread, type_: FieldType) -> u32 {
+    let credentials = posix_thread.credentials();
+    match type_ {
+        FieldType::Pid => posix_thread.process().pid(),
+        FieldType::Ppid => posix_thread.process().parent().pid(),
+        FieldType::Uid => credentials.ruid().into(),
+        FieldType::Euid => credentials.euid().into(),
+        FieldType::Suid => credentials.suid().into(),
+        FieldType::Fsuid => credentials.fsuid().into(),
+        FieldType::Gid => credentials.rgid().into(),
+        FieldType::Egid => credentials.egid().into(),
+        FieldType::Sgid => credentials.sgid().into(),
+        FieldType::Fsgid => credentials.fsgid().into(),
+        FieldType::Loginuid => credentials.loginuid().into(),
+        FieldType::LoginuidSet => (credentials.loginuid() != Uid::INVALID) as u32,
+        FieldType::Sessionid => credentials.sessionid(),
+        _ => unreachable!("the field does not describe the subject"),
+    }
+}
+
+/// Returns whether the system call accesses files with any of the permissions.
+fn perm_matches(perm: AuditPerm, syscall: &AuditSyscall) -> bool {
+    match classify_syscall(syscall.number) {
+        SyscallClass::Native(class) => perm.intersects(class),
+        SyscallClass::Open { flags_arg } => {
+            let access_perm = match AccessMode::from_u32(syscall.args[flags_arg] as u32) {
+                Ok(AccessMode::O_RDONLY) => AuditPerm::READ,
+                Ok(AccessMode::O_WRONLY) => AuditPerm::WRITE,
+                _ => AuditPerm::READ | AuditPerm::WRITE,
+            };
+            perm.intersects(access_perm)
+        }
+        SyscallClass::Execve => perm.contains(AuditPerm::EXEC),
+    }
+}
+
+/// Returns whether the path is the directory or is in the directory.
+fn is_in_dir(path: &str, dir: &str) -> bool {
+    let dir = dir.trim_end_matches('/');
+    path.strip_prefix(dir)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Returns whether the return value of a system call indicates success.
+pub(super) fn is_success(exit: isize) -> bool {
+    // Error codes are in the range of `-4095..=-1`.
+    !(-4095..0).contains(&exit)
+}
+
+/// The event that the rules are matched against.
+struct MatchTarget<'a> {
+    syscall: Option<&'a AuditSyscall>,
+    msg_type: Option<u16>,
+}
+
+struct RuleLists {
+    user: Vec<AuditRule>,
+    task: Vec<AuditRule>,
+    exit: Vec<AuditRule>,
+    exclude: Vec<AuditRule>,
+}
+
+impl RuleLists {
+    const fn new() -> Self {
+        Self {
+            user: Vec::new(),
+            task: Vec::new(),
+            exit: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    fn get_mut(&mut self, list: FilterList) -> &mut Vec<AuditRule> {
+        match list {
+            FilterList::User => &mut self.user,
+            FilterList::Task => &mut self.task,
+            FilterList::Exit => &mut self.exit,
+            FilterList::Exclude => &mut self.exclude,
+        }
+    }
+
+    fn has_syscall_rules(&self) -> bool {
+        !self.task.is_empty() || !self.exit.is_empty()
+    }
+}
+
+/// Returns the first rule in the list that matches the target.
+fn filter<'a>(
+    rules: &'a [AuditRule],
+    subject: Option<&PosixThread>,
+    target: &MatchTarget,
+) -> Option<&'a AuditRule> {
+    rules.iter().find(|rule| {
+        target.syscall.is_none_or(|syscall| {
+            rule.list != FilterList::Exit || rule.contains_syscall(syscall.number)
+        }) && rule.matches(subject, target)
+    })
+}
+
+/// Returns whether there are rules that may cause system calls to be logged.
+pub(super) fn has_syscall_rules() -> bool {
+    HAS_SYSCALL_RULES.load(Ordering::Relaxed)
+}
+
+/// Filters a system call with the task rules when the system call enters the kernel.
+///
+/// Returns the action and the filter key of the matching rule, if there is one.
+pub(super) fn filter_task(
+    posix_thread: &PosixThread,
+    syscall: &AuditSyscall,
+) -> Option<(RuleAction, Option<String>)> {
+    let rules = RULES.read();
+    let target = MatchTarget {
+        syscall: Some(syscall),
+        msg_type: None,
+    };
+    filter(&rules.task, Some(posix_thread), &target)
+        .map(|rule| (rule.action, rule.key().map(String::from)))
+}
+
+/// Filters a system call with the exit rules when the system call exits.
+///
+/// Returns the action and the filter key of the matching rule, if there is one.
+pub(super) fn filter_exit(
+    posix_thread: &PosixThread,
+    syscall: &AuditSyscall,
+) -> Option<(RuleAction, Option<String>)> {
+    let rules = RULES.read();
+    let target = MatchTarget {
+        syscall: Some(syscall),
+        msg_type: None,
+    };
+    filter(&rules.exit, Some(posix_thread), &target)
+        .map(|rule| (rule.action, rule.key().map(String::from)))
+}
+
+/// Returns whether a message of the type from user space should be logged.
+pub(super) fn filter_user(posix_thread: &PosixThread, msg_type: u16) -> bool {
+    let rules = RULES.read();
+    let target = MatchTarget {
+        syscall: None,
+        msg_type: Some(msg_type),
+    };
+    filter(&rules.user, Some(posix_thread), &target)
+        .is_none_or(|rule| rule.action != RuleAction::Never)
+}
+
+/// Returns whether records of the type are excluded from being logged.
+pub(super) fn is_excluded(msg_type: u16) -> bool {
+    let rules = RULES.read();
+    if rules.exclude.is_empty() {
+        return false;
+    }
+
+    let target = MatchTarget {
+        syscall: None,
+        msg_type: Some(msg_type),
+    };
+    let current = Task::current();
+    let subject = current.as_ref().and_then(|task| task.as_posix_thread());
+    filter(&rules.exclude, subject, &target).is_some_and(|rule| rule.action == RuleAction::Never)
+}
+
+/// Adds a rule.
+pub fn add_rule(posix_thread: &PosixThread, rule: AuditRule) -> Result<()> {
+    if status::is_locked() {
+        log_rule_change(posix_thread, "add_rule", &rule, false);
+        return_errno_with_message!(Errno::EPERM, "the audit configuration is locked");
+    }
+
+    let mut rules = RULES.write();
+    let list = rules.get_mut(rule.list);
+    if list.contains(&rule) {
+        return_errno_with_message!(Errno::EEXIST, "the rule already exists");
+    }
+    if rule.is_prepended {
+        list.insert(0, rule.clone());
+    } else {
+        list.push(rule.clone());
+    }
+    HAS_SYSCALL_RULES.store(rules.has_syscall_rules(), Ordering::Relaxed);
+    drop(rules);
+
+    log_rule_change(posix_thread, "add_rule", &rule, true);
+    Ok(())
+}
+
+/// Deletes a rule.
+pub fn del_rule(posix_thread: &PosixThread, rule: &AuditRule) -> Result<()> {
+    if status::is_locked() {
+        log_rule_change(posix_thread, "remove_rule", rule, false);
+        return_errno_with_message!(Errno::EPERM, "the audit configuration is locked");
+    }
+
+    let mut rules = RULES.write();
+    let list = rules.get_mut(rule.list);
+    let Some(index) = list.iter().position(|existing| existing == rule) else {
+        return_errno_with_message!(Errno::ENOENT, "the rule does not exist");
+    };
+    list.remove(index);
+    HAS_SYSCALL_RULES.store(rules.has_syscall_rules(), Ordering::Relaxed);
+    drop(rules);
+
+    log_rule_change(posix_thread, "remove_rule", rule, true);
+    Ok(())
+}
+
+/// Returns all rules.
+pub fn list_rules() -> Vec<AuditRule> {
+    let rules = RULES.read();
+    [&rules.user, &rules.task, &rules.exit, &rules.exclude]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// Logs a change of the rules.
+fn log_rule_change(posix_thread: &PosixThread, op: &str, rule: &AuditRule, res: bool) {
+    let credentials = posix_thread.credentials();
+    let mut body = format!(
+        "auid={} ses={} op={} key=",
+        u32::from(credentials.loginuid()),
+        credentials.sessionid(),
+        op
+    );
+    match rule.key() {
+        Some(key) => push_untrusted(&mut body, key.as_bytes()),
+        None => body.push_str("(null)"),
+    }
+    body.push_str(&format!(" list={} res={}", rule.list as u32, res as u8));
+    backlog::log_record(AUDIT_CONFIG_CHANGE, &body);
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    fn rule_data(fields: &[(u32, u32, u32)], buf: &[u8]) -> Vec<u8> {
+        let mut data = CAuditRuleData::new_zeroed();
+        data.flags = FilterList::Exit as u32;
+        data.action = RuleAction::Always as u32;
+        data.field_count = fields.len() as u32;
+        data.mask = [u32::MAX; AUDIT_BITMASK_SIZE];
+        for (i, (type_, op, value)) in fields.iter().enumerate() {
+            data.fields[i] = *type_;
+            data.fieldflags[i] = *op;
+            data.values[i] = *value;
+        }
+        data.buflen = buf.len() as u32;
+
+        let mut bytes = data.as_bytes().to_vec();
+        bytes.extend_from_slice(buf);
+        bytes
+    }
+
+    #[ktest]
+    fn parse_and_serialize() {
+        let bytes = rule_data(
+            &[
+                (FieldType::Watch as u32, Operator::Equal as u32, 11),
+                (FieldType::Perm as u32, Operator::Equal as u32, 0b1010),
+                (FieldType::Filterkey as u32, Operator::Equal as u32, 6),
+            ],
+            b"/etc/passwdpasswd",
+        );
+
+        let rule = AuditRule::parse(&bytes).unwrap();
+        assert_eq!(rule.list, FilterList::Exit);
+        assert_eq!(rule.key(), Some("passwd"));
+        assert!(rule.contains_syscall(59));
+        assert_eq!(rule.to_bytes(), bytes);
+    }
+
+    #[ktest]
+    fn parse_invalid() {
+        // The watched path is not absolute.
+        let bytes = rule_data(
+            &[(FieldType::Watch as u32, Operator::Equal as u32, 3)],
+            b"etc",
+        );
+        assert_eq!(AuditRule::parse(&bytes).unwrap_err().error(), Errno::EINVAL);
+
+        // The string exceeds the buffer.
+        let bytes = rule_data(
+            &[(FieldType::Dir as u32, Operator::Equal as u32, 5)],
+            b"/etc",
+        );
+        assert_eq!(AuditRule::parse(&bytes).unwrap_err().error(), Errno::EINVAL);
+
+        // The operator is missing.
+        let bytes = rule_data(&[(FieldType::Uid as u32, 0, 0)], b"");
+        assert_eq!(AuditRule::parse(&bytes).unwrap_err().error(), Errno::EINVAL);
+
+        // The message type is only valid in the user and exclude lists.
+        let bytes = rule_data(
+            &[(FieldType::Msgtype as u32, Operator::Equal as u32, 1300)],
+            b"",
+        );
+        assert_eq!(AuditRule::parse(&bytes).unwrap_err().error(), Errno::EINVAL);
+    }
+
+    #[ktest]
+    fn paths_in_dir() {
+        assert!(is_in_dir("/etc", "/etc"));
+        assert!(is_in_dir("/etc/passwd", "/etc/"));
+        assert!(!is_in_dir("/etcd/passwd", "/etc"));
+    }
+}
@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use ostd::timer::TIMER_FREQ;
+
+use super::{
+    backlog,
+    record::{AUDIT_CONFIG_CHANGE, AUDIT_REPLACE, AuditRecord},
+};
+use crate::{
+    net::socket::netlink::send_audit_record,
+    prelude::*,
+    process::{Pid, posix_thread::PosixThread},
+};
+
+/// Whether the audit subsystem is enabled.
+///
+/// The value is [`AUDIT_OFF`] if the audit subsystem is disabled, [`AUDIT_LOCKED`] if it is
+/// enabled and locked, and one if it is enabled but not locked.
+static ENABLED: AtomicU32 = AtomicU32::new(AUDIT_OFF);
+
+static CONFIG: Mutex<AuditConfig> = Mutex::new(AuditConfig::new());
+
+const AUDIT_OFF: u32 = 0;
+/// The audit subsystem is enabled and its configuration cannot be changed until reboot.
+const AUDIT_LOCKED: u32 = 2;
+
+/// The default value of the backlog limit.
+const DEFAULT_BACKLOG_LIMIT: u32 = 64;
+/// The default time to wait when the backlog limit is reached, in jiffies.
+const DEFAULT_BACKLOG_WAIT_TIME: u32 = 60 * TIMER_FREQ as u32;
+
+/// `struct audit_status` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/audit.h#L465>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CAuditStatus {
+    /// Bit mask for valid entries
+    pub mask: u32,
+    /// 1 = enabled, 0 = disabled, 2 = locked
+    pub enabled: u32,
+    /// Failure-to-log action
+    pub failure: u32,
+    /// PID of auditd process
+    pub pid: u32,
+    /// Message rate limit (per second)
+    pub rate_limit: u32,
+    /// Waiting messages limit
+    pub backlog_limit: u32,
+    /// Messages lost
+    pub lost: u32,
+    /// Messages waiting in queue
+    pub backlog: u32,
+    /// Bitmap of kernel audit features
+    pub feature_bitmap: u32,
+    /// Message queue wait timeout
+    pub backlog_wait_time: u32,
+    /// Time spent waiting while message limit exceeded
+    pub backlog_wait_time_actual: u32,
+}
+
+bitflags! {
+    /// The valid fields in [`CAuditStatus`].
+    struct AuditStatusMask: u32 {
+        const ENABLED = 0x0001;
+        const FAILURE = 0x0002;
+        const PID = 0x0004;
+        const RATE_LIMIT = 0x0008;
+        const BACKLOG_LIMIT = 0x0010;
+        const BACKLOG_WAIT_TIME = 0x0020;
+        const LOST = 0x0040;
+        const BACKLOG_WAIT_TIME_ACTUAL = 0x0080;
+    }
+}
+
+bitflags! {
+    /// The features of the audit subsystem.
+    struct AuditFeatures: u32 {
+        const BACKLOG_LIMIT = 0x0001;
+        const BACKLOG_WAIT_TIME = 0x0002;
+        const EXECUTABLE_PATH = 0x0004;
+        const EXCLUDE_EXTEND = 0x0008;
+        const SESSIONID_FILTER = 0x0010;
+        const LOST_RESET = 0x0020;
+        const FILTER_FS = 0x0040;
+    }
+}
+
+/// The action to take when audit records are lost.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub(super) enum FailureMode {
+    /// Does nothing.
+    Silent = 0,
+    /// Prints a message to the kernel log.
+    Printk = 1,
+    /// Panics the kernel.
+    Panic = 2,
+}
+
+struct AuditConfig {
+    /// The action to take when records are lost, which is one of [`FailureMode`].
+    failure: u32,
+    rate_limit: u32,
+    backlog_limit: u32,
+    backlog_wait_time: u32,
+    daemon: Option<AuditDaemon>,
+}
+
+impl AuditConfig {
+    const fn new() -> Self {
+        Self {
+            failure: FailureMode::Printk as u32,
+            rate_limit: 0,
+            backlog_limit: DEFAULT_BACKLOG_LIMIT,
+            backlog_wait_time: DEFAULT_BACKLOG_WAIT_TIME,
+            daemon: None,
+        }
+    }
+}
+
+/// The audit daemon, which receives audit records via its `NETLINK_AUDIT` socket.
+#[derive(Clone, Copy, Debug)]
+struct AuditDaemon {
+    pid: Pid,
+    port: u32,
+}
+
+/// The limits of the backlog of audit records.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct BacklogLimits {
+    pub(super) failure: FailureMode,
+    pub(super) rate_limit: u32,
+    pub(super) backlog_limit: u32,
+}
+
+/// Returns whether the audit subsystem is enabled.
+pub(super) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) != AUDIT_OFF
+}
+
+/// Returns whether the configuration of the audit subsystem is locked.
+pub(super) fn is_locked() -> bool {
+    ENABLED.load(Ordering::Relaxed) == AUDIT_LOCKED
+}
+
+pub(super) fn backlog_limits() -> BacklogLimits {
+    let config = CONFIG.lock();
+    BacklogLimits {
+        failure: FailureMode::try_from(config.failure).unwrap(),
+        rate_limit: config.rate_limit,
+        backlog_limit: config.backlog_limit,
+    }
+}
+
+/// Returns the netlink port of the audit daemon, if there is one.
+pub(super) fn daemon_port() -> Option<u32> {
+    CONFIG.lock().daemon.map(|daemon| daemon.port)
+}
+
+/// Forgets the audit daemon because its netlink port is no longer bound.
+pub(super) fn reset_daemon(port: u32) {
+    let mut config = CONFIG.lock();
+    if config.daemon.is_some_and(|daemon| daemon.port == port) {
+        config.daemon = None;
+        info!("audit: the audit daemon disappeared");
+    }
+}
+
+/// Returns the status of the audit subsystem.
+pub fn get_status() -> CAuditStatus {
+    let config = CONFIG.lock();
+
+    let mut status = CAuditStatus::new_zeroed();
+    status.enabled = ENABLED.load(Ordering::Relaxed);
+    status.failure = config.failure;
+    status.pid = config.daemon.map_or(0, |daemon| daemon.pid);
+    status.rate_limit = config.rate_limit;
+    status.backlog_limit = config.backlog_limit;
+    status.lost = backlog::lost();
+    status.backlog = backlog::len() as u32;
+    status.feature_bitmap = (AuditFeatures::BACKLOG_LIMIT
+        | AuditFeatures::BACKLOG_WAIT_TIME
+        | AuditFeatures::EXCLUDE_EXTEND
+        | AuditFeatures::SESSIONID_FILTER
+        | AuditFeatures::LOST_RESET)
+        .bits();
+    status.backlog_wait_time = config.backlog_wait_time;
+    // Records are never waited for, so no time is spent waiting.
+    status.backlog_wait_time_actual = 0;
+
+    status
+}
+
+/// Changes the status of the audit subsystem.
+///
+/// The fields to change are specified by `status.mask`. The thread that requests the change is
+/// `posix_thread`, which sends the request from the netlink port `port`.
+pub fn set_status(posix_thread: &PosixThread, port: u32, status: &CAuditStatus) -> Result<()> {
+    let mask = AuditStatusMask::from_bits_truncate(status.mask);
+
+    if mask.contains(AuditStatusMask::ENABLED) {
+        if status.enabled > AUDIT_LOCKED {
+            return_errno_with_message!(Errno::EINVAL, "the audit state is invalid");
+        }
+        let old = ENABLED.load(Ordering::Relaxed);
+        let is_allowed = old != AUDIT_LOCKED;
+        if is_allowed {
+            ENABLED.store(status.enabled, Ordering::Relaxed);
+        }
+        log_config_change(
+            posix_thread,
+            "audit_enabled",
+            status.enabled,
+            old,
+            is_allowed,
+        );
+        if !is_allowed {
+            return_errno_with_message!(Errno::EPERM, "the audit configuration is locked");
+        }
+    }
+
+    if mask.contains(AuditStatusMask::FAILURE) {
+        if FailureMode::try_from(status.failure).is_err() {
+            return_errno_with_message!(Errno::EINVAL, "the failure mode is invalid");
+        }
+        change_config(posix_thread, "audit_failure", status.failure, |config| {
+            &mut config.failure
+        })?;
+    }
+
+    if mask.contains(AuditStatusMask::PID) {
+        set_daemon(posix_thread, port, status.pid)?;
+    }
+
+    if mask.contains(AuditStatusMask::RATE_LIMIT) {
+        change_config(
+            posix_thread,
+            "audit_rate_limit",
+            status.rate_limit,
+            |config| &mut config.rate_limit,
+        )?;
+    }
+
+    if mask.contains(AuditStatusMask::BACKLOG_LIMIT) {
+        change_config(
+            posix_thread,
+            "audit_backlog_limit",
+            status.backlog_limit,
+            |config| &mut config.backlog_limit,
+        )?;
+    }
+
+    if mask.contains(AuditStatusMask::BACKLOG_WAIT_TIME) {
+        if status.backlog_wait_time > 10 * DEFAULT_BACKLOG_WAIT_TIME {
+            return_errno_with_message!(Errno::EINVAL, "the backlog wait time is too long");
+        }
+        change_config(
+            posix_thread,
+            "audit_backlog_wait_time",
+            status.backlog_wait_time,
+            |config| &mut config.backlog_wait_time,
+        )?;
+    }
+
+    if mask == AuditStatusMask::LOST {
+        let lost = backlog::reset_lost();
+        log_config_change(posix_thread, "lost", 0, lost, true);
+    }
+
+    Ok(())
+}
+
+/// Changes a configuration value unless the configuration is locked.
+fn change_config<F>(posix_thread: &PosixThread, name: &str, new: u32, field: F) -> Result<()>
+where
+    F: FnOnce(&mut AuditConfig) -> &mut u32,
+{
+    let is_allowed = !is_locked();
+
+    let mut config = CONFIG.lock();
+    let value = field(&mut config);
+    let old = *value;
+    if is_allowed {
+        *value = new;
+    }
+    drop(config);
+
+    log_config_change(posix_thread, name, new, old, is_allowed);
+    if !is_allowed {
+        return_errno_with_message!(Errno::EPERM, "the audit configuration is locked");
+    }
+
+    Ok(())
+}
+
+/// Registers or unregisters the audit daemon.
+///
+/// A process registers itself as the audit daemon by sending its PID. The records will then be
+/// sent to the netlink port from which the request is sent. The audit daemon unregisters itself
+/// by sending zero.
+fn set_daemon(posix_thread: &PosixThread, port: u32, new_pid: Pid) -> Result<()> {
+    let sender_pid = posix_thread.process().pid();
+    if new_pid != 0 && new_pid != sender_pid {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the audit daemon can only register its own PID"
+        );
+    }
+
+    // Test whether the current audit daemon is still alive. If its netlink port is no longer
+    // bound, it will be forgotten.
+    if let Some(daemon_port) = daemon_port() {
+        let probe = AuditRecord::new_raw(AUDIT_REPLACE, sender_pid.as_bytes());
+        if send_audit_record(daemon_port, &probe)
+            .is_err_and(|err| err.error() == Errno::ECONNREFUSED)
+        {
+            reset_daemon(daemon_port);
+        }
+    }
+
+    let mut config = CONFIG.lock();
+    let old_pid = config.daemon.map_or(0, |daemon| daemon.pid);
+    let result = match config.daemon {
+        Some(_) if new_pid != 0 => Err(Error::with_message(
+            Errno::EEXIST,
+            "replacing a healthy audit daemon is not allowed",
+        )),
+        Some(daemon) if daemon.pid != sender_pid => Err(Error::with_message(
+            Errno::EACCES,
+            "only the audit daemon can unregister itself",
+        )),
+        _ => {
+            config.daemon = (new_pid != 0).then_some(AuditDaemon { pid: new_pid, port });
+            Ok(())
+        }
+    };
+    drop(config);
+
+    log_config_change(posix_thread, "audit_pid", new_pid, old_pid, result.is_ok());
+    result
+}
+
+/// Logs a change of the audit configuration.
+fn log_config_change(posix_thread: &PosixThread, name: &str, new: u32, old: u32, res: bool) {
+    let credentials = posix_thread.credentials();
+    let body = format!(
+        "op=set {}={} old={} auid={} ses={} res={}",
+        name,
+        new,
+        old,
+        u32::from(credentials.loginuid()),
+        credentials.sessionid(),
+        res as u8
+    );
+    backlog::log_record(AUDIT_CONFIG_CHANGE, &body);
+}
@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::fmt::Write;
+
+use ostd::task::Task;
+
+use super::{
+    AUDIT_ARCH, backlog,
+    context::{AuditName, AuditSyscall, InodeInfo, NameKind},
+    record::{
+        AUDIT_CWD, AUDIT_EOE, AUDIT_EXECVE, AUDIT_PATH, AUDIT_SYSCALL, AuditRecord, AuditStamp,
+        push_untrusted,
+    },
+    rule::{self, RuleAction},
+    status,
+};
+use crate::{
+    fs::vfs::{
+        inode::Metadata,
+        path::{LookupResult, PathResolver},
+    },
+    prelude::*,
+    process::posix_thread::AsThreadLocal,
+};
+
+/// Starts auditing a system call when it enters the kernel.
+pub fn syscall_entry(ctx: &Context, syscall_number: u64, args: [u64; 6]) {
+    if !status::is_enabled() || !rule::has_syscall_rules() {
+        return;
+    }
+
+    let mut syscall = AuditSyscall::new(syscall_number, args);
+    match rule::filter_task(ctx.posix_thread, &syscall) {
+        Some((RuleAction::Never, _)) => return,
+        Some((RuleAction::Always, key)) => syscall.task_key = Some(key),
+        None => (),
+    }
+
+    ctx.thread_local.audit_context().borrow_mut().enter(syscall);
+}
+
+/// Finishes auditing a system call when it exits.
+///
+/// The system call is logged if it matches the audit rules. `return_value` is `None` if the
+/// system call does not return a value (e.g., `rt_sigreturn`), in which case the return value is
+/// neither matched by the audit rules nor logged.
+pub fn syscall_exit(ctx: &Context, return_value: Option<isize>) {
+    let Some(mut syscall) = ctx.thread_local.audit_context().borrow_mut().exit() else {
+        return;
+    };
+    if !status::is_enabled() {
+        return;
+    }
+    syscall.exit = return_value;
+
+    // The exit rules take precedence over the task rules.
+    let key = match rule::filter_exit(ctx.posix_thread, &syscall) {
+        Some((RuleAction::Always, key)) => key,
+        Some((RuleAction::Never, _)) => return,
+        None => match syscall.task_key.take() {
+            Some(key) => key,
+            None => return,
+        },
+    };
+
+    log_syscall(ctx, &syscall, key.as_deref());
+}
+
+/// Finishes auditing the current system call when the current thread exits.
+///
+/// The system call (e.g., `exit` or `exit_group`) never returns, so it is logged without a return
+/// value. This must be called before the virtual memory of the thread is dropped.
+pub fn thread_exit(ctx: &Context) {
+    syscall_exit(ctx, None);
+}
+
+/// Returns whether the current thread is collecting the information about a system call.
+pub fn is_auditing_syscall() -> bool {
+    let Some(task) = Task::current() else {
+        return false;
+    };
+    let Some(thread_local) = task.as_thread_local() else {
+        return false;
+    };
+    thread_local
+        .audit_context()
+        .try_borrow()
+        .is_ok_and(|audit_context| audit_context.is_active())
+}
+
+/// Records a path name resolved by the current system call.
+///
+/// `name` is the path name supplied by user space, and `result` is the result of resolving it.
+pub fn log_path(resolver: &PathResolver, name: Option<&str>, result: &Result<LookupResult>) {
+    let Some(task) = Task::current() else {
+        return;
+    };
+    let Some(thread_local) = task.as_thread_local() else {
+        return;
+    };
+    let Ok(mut audit_context) = thread_local.audit_context().try_borrow_mut() else {
+        return;
+    };
+    let Some(syscall) = audit_context.syscall_mut() else {
+        return;
+    };
+
+    let audit_name = match result {
+        Ok(LookupResult::Resolved(path)) => AuditName {
+            name: name.map(String::from),
+            abs_path: Some(resolver.make_abs_path(path).into_string()),
+            kind: NameKind::Normal,
+            inode: Some(InodeInfo::from(&path.metadata())),
+        },
+        Ok(LookupResult::AtParent(result)) => {
+            let mut abs_path = resolver.make_abs_path(result.parent()).into_string();
+            if !abs_path.ends_with('/') {
+                abs_path.push('/');
+            }
+            abs_path.push_str(result.unresolved_name());
+            AuditName {
+                name: name.map(String::from),
+                abs_path: Some(abs_path),
+                kind: NameKind::Parent,
+                inode: Some(InodeInfo::from(&result.parent().metadata())),
+            }
+        }
+        Err(_) => {
+            let Some(name) = name else {
+                return;
+            };
+            AuditName {
+                name: Some(String::from(name)),
+                abs_path: None,
+                kind: NameKind::Unknown,
+                inode: None,
+            }
+        }
+    };
+
+    syscall.names.push(audit_name);
+}
+
+/// Records the arguments of the program executed by the current system call.
+pub fn log_execve_args(ctx: &Context, argv: &[CString]) {
+    let mut audit_context = ctx.thread_local.audit_context().borrow_mut();
+    if let Some(syscall) = audit_context.syscall_mut() {
+        syscall.execve_args = Some(argv.to_vec());
+    }
+}
+
+impl From<&Metadata> for InodeInfo {
+    fn from(metadata: &Metadata) -> Self {
+        let dev = metadata.container_dev_id;
+        let rdev = metadata.self_dev_id;
+        Self {
+            ino: metadata.ino,
+            dev: (dev.major().get() as u32, dev.minor().get()),
+            mode: metadata.type_ as u32 | metadata.mode.bits() as u32,
+            uid: metadata.uid,
+            gid: metadata.gid,
+            rdev: rdev.map_or((0, 0), |rdev| {
+                (rdev.major().get() as u32, rdev.minor().get())
+            }),
+        }
+    }
+}
+
+/// Logs the records of a system call event.
+fn log_syscall(ctx: &Context, syscall: &AuditSyscall, key: Option<&str>) {
+    let stamp = AuditStamp::new();
+    let credentials = ctx.posix_thread.credentials();
+    let fs_ref = ctx.thread_local.borrow_fs();
+    let resolver = fs_ref.resolver().read();
+
+    let mut body = format!("arch={:x} syscall={}", AUDIT_ARCH, syscall.number);
+    if let Some(exit) = syscall.exit {
+        let _ = write!(
+            body,
+            " success={} exit={}",
+            if rule::is_success(exit) { "yes" } else { "no" },
+            exit
+        );
+    }
+    let _ = write!(
+        body,
+        " a0={:x} a1={:x} a2={:x} a3={:x} items={} ppid={} pid={} auid={} uid={} gid={} euid={} \
+         suid={} fsuid={} egid={} sgid={} fsgid={} tty=(none) ses={} comm=",
+        syscall.args[0],
+        syscall.args[1],
+        syscall.args[2],
+        syscall.args[3],
+        syscall.names.len(),
+        ctx.process.parent().pid(),
+        ctx.process.pid(),
+        u32::from(credentials.loginuid()),
+        u32::from(credentials.ruid()),
+        u32::from(credentials.rgid()),
+        u32::from(credentials.euid()),
+        u32::from(credentials.suid()),
+        u32::from(credentials.fsuid()),
+        u32::from(credentials.egid()),
+        u32::from(credentials.sgid()),
+        u32::from(credentials.fsgid()),
+        credentials.sessionid(),
+    );
+    push_untrusted(
+        &mut body,
+        ctx.posix_thread.thread_name().lock().name().to_bytes(),
+    );
+    body.push_str(" exe=");
+    // The virtual memory may have been dropped.
+    match ctx.thread_local.vmar().borrow().as_ref() {
+        Some(vmar) => {
+            let exe_path = resolver.make_abs_path(vmar.process_vm().executable_file());
+            push_untrusted(&mut body, exe_path.into_string().as_bytes());
+        }
+        None => body.push_str("(null)"),
+    }
+    body.push_str(" key=");
+    match key {
+        Some(key) => push_untrusted(&mut body, key.as_bytes()),
+        None => body.push_str("(null)"),
+    }
+    backlog::submit(AuditRecord::new(AUDIT_SYSCALL, &stamp, &body));
+
+    if let Some(argv) = syscall.execve_args.as_ref() {
+        let mut body = format!("argc={}", argv.len());
+        for (i, arg) in argv.iter().enumerate() {
+            let _ = write!(body, " a{}=", i);
+            push_untrusted(&mut body, arg.to_bytes());
+        }
+        backlog::submit(AuditRecord::new(AUDIT_EXECVE, &stamp, &body));
+    }
+
+    if !syscall.names.is_empty() {
+        let mut body = String::from("cwd=");
+        let cwd = resolver.make_abs_path(resolver.cwd()).into_string();
+        push_untrusted(&mut body, cwd.as_bytes());
+        backlog::submit(AuditRecord::new(AUDIT_CWD, &stamp, &body));
+    }
+
+    for (i, name) in syscall.names.iter().enumerate() {
+        let mut body = format!("item={} name=", i);
+        match name.name.as_ref() {
+            Some(name) => push_untrusted(&mut body, name.as_bytes()),
+            None => body.push_str("(null)"),
+        }
+        if let Some(inode) = name.inode.as_ref() {
+            let _ = write!(
+                body,
+                " inode={} dev={:02x}:{:02x} mode=0{:o} ouid={} ogid={} rdev={:02x}:{:02x}",
+                inode.ino,
+                inode.dev.0,
+                inode.dev.1,
+                inode.mode,
+                u32::from(inode.uid),
+                u32::from(inode.gid),
+                inode.rdev.0,
+                inode.rdev.1,
+            );
+        }
+        let _ = write!(body, " nametype={}", name.kind.as_str());
+        backlog::submit(AuditRecord::new(AUDIT_PATH, &stamp, &body));
+    }
+
+    backlog::submit(AuditRecord::new(AUDIT_EOE, &stamp, ""));
+
+    drop(resolver);
+    drop(fs_ref);
+    backlog::flush_backlog();
+}
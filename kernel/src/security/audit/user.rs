@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::{backlog, record::AUDIT_LOGIN, rule, status};
+use crate::{
+    prelude::*,
+    process::{
+        Uid, UserNamespace,
+        credentials::capabilities::CapSet,
+        posix_thread::{ContextPthreadAdminApi, PosixThread},
+    },
+    security::lsm::hooks as lsm_hooks,
+};
+
+/// A message from an access vector cache in user space, which is logged even if the audit
+/// subsystem is disabled.
+const AUDIT_USER_AVC: u16 = 1107;
+
+/// The session ID of threads whose login UID is not set.
+const INVALID_SESSION_ID: u32 = u32::MAX;
+
+/// Logs a message sent from user space.
+///
+/// The message is logged as a record of type `msg_type`, which should be one of the types
+/// reserved for user space (see [`super::is_user_message_type`]).
+pub fn log_user_message(posix_thread: &PosixThread, msg_type: u16, msg: &[u8]) {
+    if !status::is_enabled() && msg_type != AUDIT_USER_AVC {
+        return;
+    }
+    if !rule::filter_user(posix_thread, msg_type) {
+        return;
+    }
+
+    // User space tools usually terminate the message with a null byte or a new line.
+    let msg = msg.strip_suffix(b"\0").unwrap_or(msg);
+    let msg = msg.strip_suffix(b"\n").unwrap_or(msg);
+
+    let credentials = posix_thread.credentials();
+    let body = format!(
+        "pid={} uid={} auid={} ses={} msg='{}'",
+        posix_thread.process().pid(),
+        u32::from(credentials.ruid()),
+        u32::from(credentials.loginuid()),
+        credentials.sessionid(),
+        String::from_utf8_lossy(msg)
+    );
+    backlog::log_record(msg_type, &body);
+}
+
+/// Sets the login UID of the current thread and starts a new session.
+///
+/// Once the login UID is set, changing it again requires `CAP_AUDIT_CONTROL`. Setting it to
+/// [`Uid::INVALID`] unsets the login UID and ends the session.
+pub fn set_loginuid(ctx: &Context, loginuid: Uid) -> Result<()> {
+    static NEXT_SESSION_ID: AtomicU32 = AtomicU32::new(1);
+
+    let credentials = ctx.credentials_mut();
+    let old_loginuid = credentials.loginuid();
+    let old_sessionid = credentials.sessionid();
+
+    if old_loginuid != Uid::INVALID
+        && lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            UserNamespace::get_init_singleton().as_ref(),
+            ctx.posix_thread,
+            CapSet::AUDIT_CONTROL,
+        ))
+        .is_err()
+    {
+        return_errno_with_message!(Errno::EPERM, "the login UID has already been set");
+    }
+
+    let sessionid = if loginuid == Uid::INVALID {
+        INVALID_SESSION_ID
+    } else {
+        // The invalid session ID is skipped when the counter wraps around.
+        loop {
+            let sessionid = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+            if sessionid != INVALID_SESSION_ID {
+                break sessionid;
+            }
+        }
+    };
+    credentials.set_loginuid(loginuid, sessionid);
+
+    if status::is_enabled() {
+        let body = format!(
+            "pid={} uid={} old-auid={} auid={} tty=(none) old-ses={} ses={} res=1",
+            ctx.process.pid(),
+            u32::from(credentials.ruid()),
+            u32::from(old_loginuid),
+            u32::from(loginuid),
+            old_sessionid,
+            sessionid
+        );
+        backlog::log_record(AUDIT_LOGIN, &body);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,522 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt;
+
+use super::{permission::KeyPerm, user::KeyUser};
+use crate::{
+    prelude::*,
+    process::{Gid, Uid},
+    time::clocks::RealTimeCoarseClock,
+    util::random::getrandom,
+};
+
+/// The serial number of a key.
+///
+/// Serial numbers are positive. Negative numbers refer to the special keyrings of the calling
+/// thread in the system calls.
+pub type KeySerial = i32;
+
+/// The maximum length of a key description, excluding the trailing nul byte.
+pub const MAX_DESCRIPTION_LEN: usize = 4095;
+
+/// The maximum length of the payload of a `user` or a `logon` key.
+const MAX_USER_DATA_LEN: usize = 32767;
+
+/// The number of bytes charged to the owner of a keyring for each link.
+pub(super) const LINK_QUOTA_BYTES: usize = 4;
+
+/// The alive keys, indexed by their serial numbers.
+static KEYS: SpinLock<BTreeMap<KeySerial, Weak<Key>>> = SpinLock::new(BTreeMap::new());
+
+/// A key.
+///
+/// A key has an immutable type and description, which identify the key in a keyring.
+pub struct Key {
+    serial: KeySerial,
+    type_: KeyType,
+    description: String,
+    state: SpinLock<KeyState>,
+    payload: Mutex<KeyPayload>,
+}
+
+#[derive(Debug)]
+struct KeyState {
+    /// The owner, to whose quota the key is charged.
+    user: Arc<KeyUser>,
+    gid: Gid,
+    perm: KeyPerm,
+    /// The real time in seconds at which the key expires.
+    expiry: Option<u64>,
+    flags: KeyFlags,
+    /// The number of bytes charged to the owner.
+    quota_len: usize,
+}
+
+bitflags! {
+    struct KeyFlags: u8 {
+        /// The key has been revoked.
+        const REVOKED     = 1 << 0;
+        /// The key has been invalidated.
+        const INVALIDATED = 1 << 1;
+        /// The key is a user keyring or a user-session keyring.
+        const UID_KEYRING = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// The flags that control the allocation of a key.
+    pub(super) struct KeyAllocFlags: u8 {
+        /// The key can be allocated even if the quota of its owner is exceeded.
+        const QUOTA_OVERRUN = 1 << 0;
+        /// The key is a user keyring or a user-session keyring.
+        const UID_KEYRING   = 1 << 1;
+    }
+}
+
+/// The payload of a key.
+#[derive(Debug)]
+pub(super) enum KeyPayload {
+    /// The data of a `user` or a `logon` key.
+    Data(Vec<u8>),
+    /// The links of a keyring.
+    Keyring(Vec<Arc<Key>>),
+}
+
+impl KeyPayload {
+    pub(super) fn new_keyring() -> Self {
+        Self::Keyring(Vec::new())
+    }
+
+    /// Returns the length of the payload that is charged to the owner of the key.
+    fn quota_len(&self) -> usize {
+        match self {
+            Self::Data(data) => data.len(),
+            Self::Keyring(links) => links.len() * LINK_QUOTA_BYTES,
+        }
+    }
+}
+
+/// The type of a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    /// A key whose payload is a blob of data that can be read by user space.
+    User,
+    /// A key whose payload is a blob of data that cannot be read by user space.
+    ///
+    /// Such keys hold secrets (e.g., the credentials of network filesystems) that are only
+    /// consumed by the kernel.
+    Logon,
+    /// A key that contains links to other keys.
+    Keyring,
+}
+
+impl KeyType {
+    /// Looks up the key type by its name.
+    pub fn lookup(name: &str) -> Option<Self> {
+        match name {
+            "user" => Some(Self::User),
+            "logon" => Some(Self::Logon),
+            "keyring" => Some(Self::Keyring),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Logon => "logon",
+            Self::Keyring => "keyring",
+        }
+    }
+
+    /// Returns whether the payload of the key can be read by user space.
+    pub(super) fn is_readable(&self) -> bool {
+        matches!(self, Self::User | Self::Keyring)
+    }
+
+    /// Returns whether the payload of the key can be replaced.
+    pub(super) fn is_updatable(&self) -> bool {
+        matches!(self, Self::User | Self::Logon)
+    }
+
+    /// Returns the permissions of a key that is created by user space.
+    pub(super) fn default_perm(&self) -> KeyPerm {
+        let mut perm = KeyPerm::POS_VIEW
+            | KeyPerm::POS_SEARCH
+            | KeyPerm::POS_LINK
+            | KeyPerm::POS_SETATTR
+            | KeyPerm::USR_VIEW;
+        if self.is_readable() {
+            perm |= KeyPerm::POS_READ;
+        }
+        if *self == Self::Keyring || self.is_updatable() {
+            perm |= KeyPerm::POS_WRITE;
+        }
+        perm
+    }
+
+    /// Checks the description and the payload of a key that is created or updated by user
+    /// space, and returns the payload.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/user_defined.c#L59>.
+    pub(super) fn prepare_payload(&self, description: &str, data: Vec<u8>) -> Result<KeyPayload> {
+        match self {
+            Self::User | Self::Logon => {
+                if data.is_empty() || data.len() > MAX_USER_DATA_LEN {
+                    return_errno_with_message!(Errno::EINVAL, "the key payload length is invalid");
+                }
+                // A `logon` key must be qualified with a service prefix, e.g., `cifs:foo`.
+                if *self == Self::Logon && description.find(':').is_none_or(|pos| pos == 0) {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the logon key description has no service prefix"
+                    );
+                }
+                Ok(KeyPayload::Data(data))
+            }
+            Self::Keyring => {
+                if !data.is_empty() {
+                    return_errno_with_message!(Errno::EINVAL, "a keyring cannot have a payload");
+                }
+                Ok(KeyPayload::new_keyring())
+            }
+        }
+    }
+}
+
+impl Key {
+    /// Creates a new key owned by `uid` and `gid`.
+    ///
+    /// The key is charged to the quota of its owner.
+    pub(super) fn new(
+        type_: KeyType,
+        description: String,
+        uid: Uid,
+        gid: Gid,
+        perm: KeyPerm,
+        payload: KeyPayload,
+        alloc_flags: KeyAllocFlags,
+    ) -> Result<Arc<Self>> {
+        let user = KeyUser::get(uid);
+        let quota_len = description.len() + 1 + payload.quota_len();
+        user.charge_key(
+            quota_len,
+            alloc_flags.contains(KeyAllocFlags::QUOTA_OVERRUN),
+        )?;
+
+        let mut flags = KeyFlags::empty();
+        if alloc_flags.contains(KeyAllocFlags::UID_KEYRING) {
+            flags |= KeyFlags::UID_KEYRING;
+        }
+        let state = KeyState {
+            user,
+            gid,
+            perm,
+            expiry: None,
+            flags,
+            quota_len,
+        };
+
+        Ok(Arc::new_cyclic(|weak_self| {
+            let serial = {
+                let mut keys = KEYS.lock();
+                let serial = alloc_serial(&keys);
+                keys.insert(serial, weak_self.clone());
+                serial
+            };
+
+            Self {
+                serial,
+                type_,
+                description,
+                state: SpinLock::new(state),
+                payload: Mutex::new(payload),
+            }
+        }))
+    }
+
+    /// Looks up the key by its serial number.
+    pub(super) fn lookup(serial: KeySerial) -> Option<Arc<Self>> {
+        KEYS.lock().get(&serial).and_then(Weak::upgrade)
+    }
+
+    /// Returns all alive keys, ordered by their serial numbers.
+    pub(super) fn all() -> Vec<Arc<Self>> {
+        KEYS.lock().values().filter_map(Weak::upgrade).collect()
+    }
+
+    pub fn serial(&self) -> KeySerial {
+        self.serial
+    }
+
+    pub fn type_(&self) -> KeyType {
+        self.type_
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the owner user, the owner group, and the permissions.
+    pub(super) fn owner_and_perm(&self) -> (Uid, Gid, KeyPerm) {
+        let state = self.state.lock();
+        (state.user.uid(), state.gid, state.perm)
+    }
+
+    /// Returns the owner user ID and the owner group ID that are shown to user space.
+    ///
+    /// An invalid group ID is shown as the overflow group ID.
+    pub(super) fn owner_ids(&self) -> (u32, u32) {
+        let (uid, gid, _) = self.owner_and_perm();
+        let gid = if gid == Gid::INVALID {
+            Gid::OVERFLOW
+        } else {
+            gid
+        };
+        (uid.into(), gid.into())
+    }
+
+    /// Changes the owner user and the owner group.
+    ///
+    /// The key is charged to the quota of the new owner user.
+    pub(super) fn set_owner(&self, uid: Option<Uid>, gid: Option<Gid>) -> Result<()> {
+        let mut state = self.state.lock();
+
+        if let Some(uid) = uid
+            && uid != state.user.uid()
+        {
+            let new_user = KeyUser::get(uid);
+            new_user.charge_key(state.quota_len, false)?;
+            state.user.uncharge_key(state.quota_len);
+            state.user = new_user;
+        }
+
+        if let Some(gid) = gid {
+            state.gid = gid;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn set_perm(&self, perm: KeyPerm) {
+        self.state.lock().perm = perm;
+    }
+
+    /// Returns the expiry time in seconds of real time, or `None` if the key never expires.
+    pub(super) fn expiry(&self) -> Option<u64> {
+        self.state.lock().expiry
+    }
+
+    /// Sets the key to expire after `timeout` seconds.
+    ///
+    /// If `timeout` is zero, the key never expires.
+    pub(super) fn set_timeout(&self, timeout: u32) {
+        self.state.lock().expiry = (timeout > 0).then(|| now() + timeout as u64);
+    }
+
+    pub(super) fn is_revoked(&self) -> bool {
+        self.state.lock().flags.contains(KeyFlags::REVOKED)
+    }
+
+    pub(super) fn is_invalidated(&self) -> bool {
+        self.state.lock().flags.contains(KeyFlags::INVALIDATED)
+    }
+
+    pub(super) fn is_expired(&self) -> bool {
+        self.expiry().is_some_and(|expiry| now() >= expiry)
+    }
+
+    pub(super) fn is_uid_keyring(&self) -> bool {
+        self.state.lock().flags.contains(KeyFlags::UID_KEYRING)
+    }
+
+    /// Checks whether the key can be used.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/permission.c#L100>.
+    pub(super) fn validate(&self) -> Result<()> {
+        let (flags, expiry) = {
+            let state = self.state.lock();
+            (state.flags, state.expiry)
+        };
+
+        if flags.contains(KeyFlags::INVALIDATED) {
+            return_errno_with_message!(Errno::ENOKEY, "the key has been invalidated");
+        }
+        if flags.contains(KeyFlags::REVOKED) {
+            return_errno_with_message!(Errno::EKEYREVOKED, "the key has been revoked");
+        }
+        if expiry.is_some_and(|expiry| now() >= expiry) {
+            return_errno_with_message!(Errno::EKEYEXPIRED, "the key has expired");
+        }
+
+        Ok(())
+    }
+
+    /// Revokes the key.
+    ///
+    /// The payload of a revoked key is discarded and the key can no longer be used.
+    pub(super) fn revoke(&self) {
+        self.state.lock().flags |= KeyFlags::REVOKED;
+        self.discard_payload();
+    }
+
+    /// Invalidates the key.
+    ///
+    /// An invalidated key is treated as if it does not exist, and it is removed from the
+    /// keyrings that link to it.
+    pub(super) fn invalidate(&self) {
+        self.state.lock().flags |= KeyFlags::INVALIDATED;
+        self.discard_payload();
+    }
+
+    fn discard_payload(&self) {
+        let old_payload = {
+            let mut payload = self.payload.lock();
+            let empty_payload = match &*payload {
+                KeyPayload::Data(_) => KeyPayload::Data(Vec::new()),
+                KeyPayload::Keyring(_) => KeyPayload::new_keyring(),
+            };
+            let old_payload = core::mem::replace(&mut *payload, empty_payload);
+            // Releasing the charged bytes never fails.
+            self.reserve(old_payload.quota_len(), 0).unwrap();
+            old_payload
+        };
+
+        // The links of a keyring are dropped after the lock is released, since dropping a key
+        // may acquire the locks of other keys.
+        drop(old_payload);
+    }
+
+    /// Replaces the payload of the key.
+    ///
+    /// The key no longer expires after it is updated.
+    pub(super) fn update(&self, new_payload: KeyPayload) -> Result<()> {
+        let old_payload = {
+            let mut payload = self.payload.lock();
+            self.reserve(payload.quota_len(), new_payload.quota_len())?;
+            core::mem::replace(&mut *payload, new_payload)
+        };
+        drop(old_payload);
+
+        self.state.lock().expiry = None;
+        Ok(())
+    }
+
+    /// Reads the payload of the key.
+    ///
+    /// The payload of a keyring is the serial numbers of the linked keys.
+    pub(super) fn read(&self) -> Result<Vec<u8>> {
+        if !self.type_.is_readable() {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the key type cannot be read");
+        }
+
+        self.validate()?;
+
+        let data = match &*self.payload.lock() {
+            KeyPayload::Data(data) => data.clone(),
+            KeyPayload::Keyring(links) => links
+                .iter()
+                .filter(|key| !key.is_invalidated())
+                .flat_map(|key| key.serial.to_ne_bytes())
+                .collect(),
+        };
+        Ok(data)
+    }
+
+    /// Returns the length of the payload data, or the number of links of a keyring.
+    pub(super) fn payload_len(&self) -> usize {
+        match &*self.payload.lock() {
+            KeyPayload::Data(data) => data.len(),
+            KeyPayload::Keyring(links) => links.iter().filter(|key| !key.is_invalidated()).count(),
+        }
+    }
+
+    pub(super) fn payload(&self) -> MutexGuard<'_, KeyPayload> {
+        self.payload.lock()
+    }
+
+    /// Changes the length of the payload that is charged to the owner.
+    pub(super) fn reserve(&self, old_len: usize, new_len: usize) -> Result<()> {
+        let mut state = self.state.lock();
+        let new_quota_len = state.quota_len - old_len + new_len;
+        state.user.recharge_bytes(state.quota_len, new_quota_len)?;
+        state.quota_len = new_quota_len;
+        Ok(())
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        KEYS.lock().remove(&self.serial);
+
+        let state = self.state.get_mut();
+        state.user.uncharge_key(state.quota_len);
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("serial", &self.serial)
+            .field("type_", &self.type_)
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Allocates a random serial number that is not in use.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/key.c#L133>.
+fn alloc_serial(keys: &BTreeMap<KeySerial, Weak<Key>>) -> KeySerial {
+    let mut bytes = [0u8; size_of::<u32>()];
+    getrandom(&mut bytes);
+
+    // Serial numbers 0, 1, and 2 are reserved.
+    let mut serial = ((u32::from_ne_bytes(bytes) >> 1) as KeySerial).max(3);
+    while keys.contains_key(&serial) {
+        serial = serial.checked_add(1).unwrap_or(3);
+    }
+    serial
+}
+
+/// Returns the current real time in seconds.
+pub(super) fn now() -> u64 {
+    RealTimeCoarseClock::get().read_time().as_secs()
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn prepare_payloads() {
+        assert!(KeyType::User.prepare_payload("foo", vec![0; 1]).is_ok());
+        assert!(KeyType::User.prepare_payload("foo", Vec::new()).is_err());
+        assert!(
+            KeyType::User
+                .prepare_payload("foo", vec![0; MAX_USER_DATA_LEN + 1])
+                .is_err()
+        );
+
+        assert!(
+            KeyType::Logon
+                .prepare_payload("cifs:foo", vec![0; 1])
+                .is_ok()
+        );
+        assert!(KeyType::Logon.prepare_payload("foo", vec![0; 1]).is_err());
+        assert!(KeyType::Logon.prepare_payload(":foo", vec![0; 1]).is_err());
+
+        assert!(KeyType::Keyring.prepare_payload("foo", Vec::new()).is_ok());
+        assert!(KeyType::Keyring.prepare_payload("foo", vec![0; 1]).is_err());
+    }
+
+    #[ktest]
+    fn lookup_types() {
+        assert_eq!(KeyType::lookup("user"), Some(KeyType::User));
+        assert_eq!(KeyType::lookup("logon"), Some(KeyType::Logon));
+        assert_eq!(KeyType::lookup("keyring"), Some(KeyType::Keyring));
+        assert_eq!(KeyType::lookup("big_key"), None);
+    }
+}
@@ -0,0 +1,474 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The operations of the key management system calls.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/keyctl.c>.
+
+use alloc::format;
+
+use super::{
+    key::{Key, KeyAllocFlags, KeySerial, KeyType},
+    keyring::{KeyRef, KeySearch},
+    permission::{KeyAccess, KeyPerm},
+    process_keys::{self, KEY_SPEC_SESSION_KEYRING, lookup_key, search_process_keyrings},
+};
+use crate::{
+    prelude::*,
+    process::{
+        Gid, Uid, UserNamespace,
+        credentials::capabilities::CapSet,
+        posix_thread::{AsPosixThread, PosixThread},
+    },
+    security::lsm::hooks as lsm_hooks,
+};
+
+/// Moves the key only if no key of the same type and description is in the destination.
+const KEYCTL_MOVE_EXCL: u32 = 0x01;
+
+/// Adds a key to the keyring, or updates the key of the same type and description in the
+/// keyring.
+///
+/// Returns the serial number of the key.
+pub fn add_key(
+    posix_thread: &PosixThread,
+    type_name: &str,
+    description: Option<String>,
+    data: Vec<u8>,
+    keyring: KeySerial,
+) -> Result<KeySerial> {
+    let credentials = posix_thread.credentials();
+    let keyring = lookup_key(&credentials, keyring, true, Some(KeyAccess::WRITE))?;
+
+    let Some(type_) = KeyType::lookup(type_name) else {
+        return_errno_with_message!(Errno::ENODEV, "the key type does not exist");
+    };
+    let Some(description) = description else {
+        return_errno_with_message!(Errno::EINVAL, "the key description is empty");
+    };
+    if keyring.key.type_() != KeyType::Keyring {
+        return_errno_with_message!(Errno::ENOTDIR, "the key is not a keyring");
+    }
+    let payload = type_.prepare_payload(&description, data)?;
+
+    if type_.is_updatable()
+        && let Some(key) = keyring.key.find_link(type_, &description)?
+        && !key.is_revoked()
+    {
+        KeyRef::new(key.clone(), keyring.possessed)
+            .check_permission(&credentials, KeyAccess::WRITE)?;
+        key.update(payload)?;
+        return Ok(key.serial());
+    }
+
+    let key = Key::new(
+        type_,
+        description,
+        credentials.fsuid(),
+        credentials.fsgid(),
+        type_.default_perm(),
+        payload,
+        KeyAllocFlags::empty(),
+    )?;
+    keyring.key.link(&key)?;
+
+    Ok(key.serial())
+}
+
+/// Requests a key of the type and description from the keyrings of the thread.
+///
+/// If `dest` is not zero, the found key is linked to the destination keyring.
+///
+/// Returns the serial number of the key.
+pub fn request_key(
+    posix_thread: &PosixThread,
+    type_name: &str,
+    description: &str,
+    dest: KeySerial,
+) -> Result<KeySerial> {
+    let credentials = posix_thread.credentials();
+    let dest = if dest != 0 {
+        Some(lookup_key(
+            &credentials,
+            dest,
+            true,
+            Some(KeyAccess::WRITE),
+        )?)
+    } else {
+        None
+    };
+
+    let Some(type_) = KeyType::lookup(type_name) else {
+        return_errno_with_message!(Errno::ENOKEY, "the key type does not exist");
+    };
+
+    let key_ref = match search_process_keyrings(
+        &credentials,
+        &mut KeySearch::new(&credentials, type_, description),
+    ) {
+        Ok(key_ref) => key_ref,
+        // Linux constructs the key by calling `/sbin/request-key` if the callout information
+        // is supplied. There is no such upcall, so the key cannot be constructed.
+        Err(err) if err.error() == Errno::EAGAIN => {
+            return_errno_with_message!(Errno::ENOKEY, "the key is not found")
+        }
+        Err(err) => return Err(err),
+    };
+
+    if let Some(dest) = dest {
+        key_ref.check_permission(&credentials, KeyAccess::LINK)?;
+        dest.key.link(&key_ref.key)?;
+    }
+
+    Ok(key_ref.key.serial())
+}
+
+/// Returns the serial number of the key, which may refer to a special keyring.
+pub fn get_keyring_id(
+    posix_thread: &PosixThread,
+    id: KeySerial,
+    create: bool,
+) -> Result<KeySerial> {
+    let credentials = posix_thread.credentials();
+    Ok(lookup_key(&credentials, id, create, None)?.key.serial())
+}
+
+/// Joins the session keyring of the name, or a new anonymous session keyring.
+pub fn join_session_keyring(posix_thread: &PosixThread, name: Option<String>) -> Result<KeySerial> {
+    process_keys::join_session_keyring(&posix_thread.credentials(), name)
+}
+
+/// Replaces the session keyring of the parent process with the session keyring of the thread.
+///
+/// The parent process must be single-threaded, and it must have the same effective ownership
+/// as the thread without being set-user-ID or set-group-ID.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/keyctl.c#L1620>.
+pub fn session_keyring_to_parent(posix_thread: &PosixThread) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let keyring = lookup_key(
+        &credentials,
+        KEY_SPEC_SESSION_KEYRING,
+        false,
+        Some(KeyAccess::LINK),
+    )?
+    .key;
+
+    let Some(parent) = posix_thread.process().parent().lock().process().upgrade() else {
+        return_errno_with_message!(Errno::EPERM, "the parent process has exited");
+    };
+    if parent.is_init_process() {
+        return_errno_with_message!(Errno::EPERM, "the parent process is the init process");
+    }
+    let parent_task = {
+        let tasks = parent.tasks().lock();
+        if tasks.as_slice().len() != 1 {
+            return_errno_with_message!(Errno::EPERM, "the parent process is multi-threaded");
+        }
+        tasks.main().clone()
+    };
+    let parent_credentials = parent_task.as_posix_thread().unwrap().credentials();
+
+    let parent_keyring = parent_credentials.keyrings().session_keyring();
+    if parent_keyring
+        .as_ref()
+        .is_some_and(|parent_keyring| Arc::ptr_eq(parent_keyring, &keyring))
+    {
+        return Ok(());
+    }
+
+    let euid = credentials.euid();
+    let egid = credentials.egid();
+    if parent_credentials.ruid() != euid
+        || parent_credentials.euid() != euid
+        || parent_credentials.suid() != euid
+        || parent_credentials.rgid() != egid
+        || parent_credentials.egid() != egid
+        || parent_credentials.sgid() != egid
+    {
+        return_errno_with_message!(Errno::EPERM, "the parent process has a different ownership");
+    }
+
+    let owned_by_euid = |keyring: &Key| keyring.owner_and_perm().0 == euid;
+    if parent_keyring.is_some_and(|parent_keyring| !owned_by_euid(&parent_keyring))
+        || !owned_by_euid(&keyring)
+    {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "the session keyrings are not owned by the effective user"
+        );
+    }
+
+    parent_credentials.keyrings().set_session_keyring(keyring);
+    Ok(())
+}
+
+/// Updates the payload of the key.
+pub fn update_key(posix_thread: &PosixThread, id: KeySerial, data: Vec<u8>) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, false, Some(KeyAccess::WRITE))?;
+    let key = &key_ref.key;
+
+    if !key.type_().is_updatable() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the key type cannot be updated");
+    }
+    let payload = key.type_().prepare_payload(key.description(), data)?;
+    key.update(payload)
+}
+
+/// Revokes the key.
+///
+/// Revoking a key requires either the write permission or the setattr permission.
+pub fn revoke_key(posix_thread: &PosixThread, id: KeySerial) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let key_ref = match lookup_key(&credentials, id, false, Some(KeyAccess::WRITE)) {
+        Err(err) if err.error() == Errno::EACCES => {
+            lookup_key(&credentials, id, false, Some(KeyAccess::SETATTR))?
+        }
+        result => result?,
+    };
+
+    key_ref.key.revoke();
+    Ok(())
+}
+
+/// Invalidates the key.
+pub fn invalidate_key(posix_thread: &PosixThread, id: KeySerial) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, false, Some(KeyAccess::SEARCH))?;
+
+    key_ref.key.invalidate();
+    Ok(())
+}
+
+/// Changes the owner user and the owner group of the key.
+///
+/// Changing the owner user, or changing the owner group to a group that the thread is not in,
+/// requires `CAP_SYS_ADMIN`.
+pub fn chown_key(
+    posix_thread: &PosixThread,
+    id: KeySerial,
+    uid: Option<Uid>,
+    gid: Option<Gid>,
+) -> Result<()> {
+    if uid.is_none() && gid.is_none() {
+        return Ok(());
+    }
+
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, true, Some(KeyAccess::SETATTR))?;
+    let (key_uid, key_gid, _) = key_ref.key.owner_and_perm();
+
+    let is_privileged_op = uid.is_some_and(|uid| uid != key_uid)
+        || gid.is_some_and(|gid| {
+            gid != key_gid && gid != credentials.fsgid() && !credentials.groups().contains(&gid)
+        });
+    if is_privileged_op && !is_sys_admin(posix_thread) {
+        return_errno_with_message!(
+            Errno::EACCES,
+            "changing the key owner requires `CAP_SYS_ADMIN`"
+        );
+    }
+
+    key_ref.key.set_owner(uid, gid)
+}
+
+/// Changes the permissions of the key.
+///
+/// Only the owner and the threads with `CAP_SYS_ADMIN` can change the permissions.
+pub fn set_key_perm(posix_thread: &PosixThread, id: KeySerial, perm: u32) -> Result<()> {
+    let Some(perm) = KeyPerm::from_bits(perm) else {
+        return_errno_with_message!(Errno::EINVAL, "the key permissions are invalid");
+    };
+
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, true, Some(KeyAccess::SETATTR))?;
+
+    let (key_uid, _, _) = key_ref.key.owner_and_perm();
+    if key_uid != credentials.fsuid() && !is_sys_admin(posix_thread) {
+        return_errno_with_message!(
+            Errno::EACCES,
+            "only the key owner can change the key permissions"
+        );
+    }
+
+    key_ref.key.set_perm(perm);
+    Ok(())
+}
+
+/// Describes the key in the form of `type;uid;gid;perm;description`.
+pub fn describe_key(posix_thread: &PosixThread, id: KeySerial) -> Result<String> {
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, false, Some(KeyAccess::VIEW))?;
+    let key = &key_ref.key;
+
+    let (uid, gid) = key.owner_ids();
+    let (_, _, perm) = key.owner_and_perm();
+    Ok(format!(
+        "{};{};{};{:08x};{}",
+        key.type_().name(),
+        uid as i32,
+        gid as i32,
+        perm.bits(),
+        key.description()
+    ))
+}
+
+/// Removes all links from the keyring.
+pub fn clear_keyring(posix_thread: &PosixThread, id: KeySerial) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let keyring = lookup_key(&credentials, id, true, Some(KeyAccess::WRITE))?;
+    keyring.key.clear()
+}
+
+/// Links the key to the keyring.
+pub fn link_key(posix_thread: &PosixThread, id: KeySerial, keyring: KeySerial) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let keyring = lookup_key(&credentials, keyring, true, Some(KeyAccess::WRITE))?;
+    let key_ref = lookup_key(&credentials, id, true, Some(KeyAccess::LINK))?;
+    keyring.key.link(&key_ref.key)
+}
+
+/// Unlinks the key from the keyring.
+pub fn unlink_key(posix_thread: &PosixThread, id: KeySerial, keyring: KeySerial) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let keyring = lookup_key(&credentials, keyring, false, Some(KeyAccess::WRITE))?;
+    // Unlinking does not use the key, so the key needs no permission.
+    let key_ref = lookup_key(&credentials, id, false, None)?;
+    keyring.key.unlink(&key_ref.key)
+}
+
+/// Moves the key from one keyring to another.
+pub fn move_key(
+    posix_thread: &PosixThread,
+    id: KeySerial,
+    from: KeySerial,
+    to: KeySerial,
+    flags: u32,
+) -> Result<()> {
+    if flags & !KEYCTL_MOVE_EXCL != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the move flags are invalid");
+    }
+
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, false, Some(KeyAccess::LINK))?;
+    let from = lookup_key(&credentials, from, false, Some(KeyAccess::WRITE))?;
+    let to = lookup_key(&credentials, to, true, Some(KeyAccess::WRITE))?;
+    let key = &key_ref.key;
+
+    if Arc::ptr_eq(&from.key, &to.key) {
+        return Ok(());
+    }
+
+    if !from.key.links()?.iter().any(|link| Arc::ptr_eq(link, key)) {
+        return_errno_with_message!(Errno::ENOENT, "the key is not linked to the keyring");
+    }
+    if flags & KEYCTL_MOVE_EXCL != 0 && to.key.find_link(key.type_(), key.description())?.is_some()
+    {
+        return_errno_with_message!(
+            Errno::EEXIST,
+            "a key of the same type and description is in the keyring"
+        );
+    }
+
+    to.key.link(key)?;
+    from.key.unlink(key)
+}
+
+/// Searches the keyring for a key of the type and description.
+///
+/// If `dest` is not zero, the found key is linked to the destination keyring.
+///
+/// Returns the serial number of the key.
+pub fn search_keyring(
+    posix_thread: &PosixThread,
+    keyring: KeySerial,
+    type_name: &str,
+    description: &str,
+    dest: KeySerial,
+) -> Result<KeySerial> {
+    let credentials = posix_thread.credentials();
+    let keyring = lookup_key(&credentials, keyring, false, Some(KeyAccess::SEARCH))?;
+    let dest = if dest != 0 {
+        Some(lookup_key(
+            &credentials,
+            dest,
+            true,
+            Some(KeyAccess::WRITE),
+        )?)
+    } else {
+        None
+    };
+
+    let Some(type_) = KeyType::lookup(type_name) else {
+        return_errno_with_message!(Errno::ENOKEY, "the key type does not exist");
+    };
+
+    let key_ref = KeySearch::new(&credentials, type_, description)
+        .search(&keyring)
+        .map_err(|err| {
+            if err.error() == Errno::EAGAIN {
+                Error::with_message(Errno::ENOKEY, "the key is not found")
+            } else {
+                err
+            }
+        })?;
+
+    if let Some(dest) = dest {
+        key_ref.check_permission(&credentials, KeyAccess::LINK)?;
+        dest.key.link(&key_ref.key)?;
+    }
+
+    Ok(key_ref.key.serial())
+}
+
+/// Reads the payload of the key.
+///
+/// Reading a key requires the read permission, unless the key is possessed and can be
+/// searched.
+pub fn read_key(posix_thread: &PosixThread, id: KeySerial) -> Result<Vec<u8>> {
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, false, None)
+        .map_err(|_| Error::with_message(Errno::ENOKEY, "the key does not exist"))?;
+
+    match key_ref.check_permission(&credentials, KeyAccess::READ) {
+        Ok(()) => (),
+        Err(_) if key_ref.possessed => (),
+        Err(err) => return Err(err),
+    }
+
+    key_ref.key.read()
+}
+
+/// Sets the key to expire after `timeout` seconds.
+///
+/// If `timeout` is zero, the key never expires.
+pub fn set_key_timeout(posix_thread: &PosixThread, id: KeySerial, timeout: u32) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let key_ref = lookup_key(&credentials, id, true, Some(KeyAccess::SETATTR))?;
+
+    key_ref.key.set_timeout(timeout);
+    Ok(())
+}
+
+/// Sets the default destination keyring of `request_key()`, and returns the old one.
+pub fn set_request_key_keyring(posix_thread: &PosixThread, dest: i32) -> Result<i32> {
+    process_keys::set_request_key_dest(&posix_thread.credentials(), dest)
+}
+
+/// Returns the security label of the key.
+///
+/// No LSM module labels keys, so the label is always empty.
+pub fn get_key_security(posix_thread: &PosixThread, id: KeySerial) -> Result<String> {
+    let credentials = posix_thread.credentials();
+    lookup_key(&credentials, id, false, Some(KeyAccess::VIEW))?;
+    Ok(String::new())
+}
+
+fn is_sys_admin(posix_thread: &PosixThread) -> bool {
+    lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+        UserNamespace::get_init_singleton().as_ref(),
+        posix_thread,
+        CapSet::SYS_ADMIN,
+    ))
+    .is_ok()
+}
@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Keyrings and the key search.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/keyring.c>.
+
+use aster_rights::ReadOp;
+
+use super::{
+    key::{Key, KeyPayload, KeyType, LINK_QUOTA_BYTES},
+    permission::KeyAccess,
+};
+use crate::{prelude::*, process::credentials::Credentials};
+
+/// The maximum depth of nested keyrings that are searched.
+const MAX_SEARCH_DEPTH: usize = 6;
+
+/// A lock that serializes linking keyrings to keyrings.
+///
+/// Without the lock, two keyrings can be linked to each other concurrently, both passing the
+/// cycle check.
+static KEYRING_LINK_LOCK: Mutex<()> = Mutex::new(());
+
+impl Key {
+    /// Returns the links of the keyring.
+    ///
+    /// The links to invalidated keys are removed.
+    pub(super) fn links(&self) -> Result<Vec<Arc<Key>>> {
+        let mut payload = self.payload();
+        let KeyPayload::Keyring(links) = &mut *payload else {
+            return_errno_with_message!(Errno::ENOTDIR, "the key is not a keyring");
+        };
+
+        let old_len = links.len();
+        links.retain(|key| !key.is_invalidated());
+        if links.len() != old_len {
+            self.reserve(old_len * LINK_QUOTA_BYTES, links.len() * LINK_QUOTA_BYTES)
+                .unwrap();
+        }
+
+        Ok(links.clone())
+    }
+
+    /// Links the key to the keyring.
+    ///
+    /// A link to a key of the same type and description is replaced.
+    pub(super) fn link(&self, key: &Arc<Key>) -> Result<()> {
+        if self.type_() != KeyType::Keyring {
+            return_errno_with_message!(Errno::ENOTDIR, "the key is not a keyring");
+        }
+
+        let _guard = if key.type_() == KeyType::Keyring {
+            let guard = KEYRING_LINK_LOCK.lock();
+            self.check_cycle(key)?;
+            Some(guard)
+        } else {
+            None
+        };
+
+        let old_key = {
+            let mut payload = self.payload();
+            let KeyPayload::Keyring(links) = &mut *payload else {
+                unreachable!("the payload of a keyring must be links");
+            };
+
+            if let Some(link) = links
+                .iter_mut()
+                .find(|link| link.type_() == key.type_() && link.description() == key.description())
+            {
+                Some(core::mem::replace(link, key.clone()))
+            } else {
+                self.reserve(
+                    links.len() * LINK_QUOTA_BYTES,
+                    (links.len() + 1) * LINK_QUOTA_BYTES,
+                )?;
+                links.push(key.clone());
+                None
+            }
+        };
+        drop(old_key);
+
+        Ok(())
+    }
+
+    /// Unlinks the key from the keyring.
+    pub(super) fn unlink(&self, key: &Arc<Key>) -> Result<()> {
+        let old_key = {
+            let mut payload = self.payload();
+            let KeyPayload::Keyring(links) = &mut *payload else {
+                return_errno_with_message!(Errno::ENOTDIR, "the key is not a keyring");
+            };
+
+            let Some(pos) = links.iter().position(|link| Arc::ptr_eq(link, key)) else {
+                return_errno_with_message!(Errno::ENOENT, "the key is not linked to the keyring");
+            };
+            self.reserve(
+                links.len() * LINK_QUOTA_BYTES,
+                (links.len() - 1) * LINK_QUOTA_BYTES,
+            )
+            .unwrap();
+            links.remove(pos)
+        };
+        drop(old_key);
+
+        Ok(())
+    }
+
+    /// Removes all links from the keyring.
+    pub(super) fn clear(&self) -> Result<()> {
+        let old_links = {
+            let mut payload = self.payload();
+            let KeyPayload::Keyring(links) = &mut *payload else {
+                return_errno_with_message!(Errno::ENOTDIR, "the key is not a keyring");
+            };
+
+            self.reserve(links.len() * LINK_QUOTA_BYTES, 0).unwrap();
+            core::mem::take(links)
+        };
+        drop(old_links);
+
+        Ok(())
+    }
+
+    /// Finds the key of the type and description that is directly linked to the keyring.
+    pub(super) fn find_link(&self, type_: KeyType, description: &str) -> Result<Option<Arc<Key>>> {
+        Ok(self
+            .links()?
+            .into_iter()
+            .find(|key| key.type_() == type_ && key.description() == description))
+    }
+
+    /// Checks that linking the keyring `key` to `self` does not create a cycle.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/keyring.c#L1238>.
+    fn check_cycle(&self, key: &Arc<Key>) -> Result<()> {
+        fn reaches(from: &Key, target: &Key, depth: usize) -> Result<bool> {
+            if core::ptr::eq(from, target) {
+                return Ok(true);
+            }
+            if depth >= MAX_SEARCH_DEPTH {
+                return_errno_with_message!(Errno::ELOOP, "the keyrings are nested too deeply");
+            }
+
+            for link in from.links()? {
+                if link.type_() == KeyType::Keyring && reaches(&link, target, depth + 1)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+
+        if reaches(key, self, 0)? {
+            return_errno_with_message!(Errno::EDEADLK, "linking the keyring creates a cycle");
+        }
+        Ok(())
+    }
+}
+
+/// A reference to a key, along with whether the key is possessed by the referrer.
+#[derive(Clone, Debug)]
+pub(super) struct KeyRef {
+    pub(super) key: Arc<Key>,
+    pub(super) possessed: bool,
+}
+
+impl KeyRef {
+    pub(super) fn new(key: Arc<Key>, possessed: bool) -> Self {
+        Self { key, possessed }
+    }
+
+    /// Checks whether the credentials have the access rights to the referred key.
+    pub(super) fn check_permission(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        access: KeyAccess,
+    ) -> Result<()> {
+        self.key
+            .check_permission(credentials, self.possessed, access)
+    }
+}
+
+/// A search for a key in nested keyrings.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/keyring.c#L583>.
+pub(super) struct KeySearch<'a> {
+    credentials: &'a Credentials<ReadOp>,
+    type_: KeyType,
+    description: &'a str,
+    /// The exact key to find, if the search is only to determine the possession of the key.
+    target: Option<&'a Arc<Key>>,
+    /// Whether to skip the revoked, invalidated, and expired keys.
+    check_state: bool,
+    /// Whether the searched keyring is possessed.
+    possessed: bool,
+    /// The error to return if no key is found.
+    result: Error,
+}
+
+impl<'a> KeySearch<'a> {
+    /// Creates a search for a key of the type and description.
+    pub(super) fn new(
+        credentials: &'a Credentials<ReadOp>,
+        type_: KeyType,
+        description: &'a str,
+    ) -> Self {
+        Self {
+            credentials,
+            type_,
+            description,
+            target: None,
+            check_state: true,
+            possessed: false,
+            result: Self::not_found(),
+        }
+    }
+
+    /// Creates a search for the exact key.
+    pub(super) fn new_exact(credentials: &'a Credentials<ReadOp>, key: &'a Arc<Key>) -> Self {
+        Self {
+            credentials,
+            type_: key.type_(),
+            description: key.description(),
+            target: Some(key),
+            check_state: false,
+            possessed: false,
+            result: Self::not_found(),
+        }
+    }
+
+    /// Returns the error that indicates no matching key is found.
+    ///
+    /// The error is distinguished from [`Errno::ENOKEY`], which indicates that a matching key is
+    /// found but cannot be used, because the callers report them differently.
+    pub(super) fn not_found() -> Error {
+        Error::with_message(Errno::EAGAIN, "no matching key is found")
+    }
+
+    /// Searches the keyring and the keyrings nested in it.
+    ///
+    /// If no key is found, the error is [`Self::not_found`] unless a matching key is skipped,
+    /// in which case the error explains why the key is skipped.
+    pub(super) fn search(&mut self, keyring: &KeyRef) -> Result<KeyRef> {
+        if keyring.key.type_() != KeyType::Keyring {
+            return_errno_with_message!(Errno::ENOTDIR, "the key is not a keyring");
+        }
+        keyring.check_permission(self.credentials, KeyAccess::SEARCH)?;
+
+        self.possessed = keyring.possessed;
+        self.result = Self::not_found();
+
+        // The keyring itself may be the key that is looked for.
+        if self.matches_index(&keyring.key) {
+            return match self.check_candidate(&keyring.key) {
+                Candidate::Found => Ok(KeyRef::new(keyring.key.clone(), self.possessed)),
+                Candidate::Skipped => Err(self.result),
+                Candidate::Mismatched => self.search_nested(&keyring.key, 0),
+            };
+        }
+
+        self.search_nested(&keyring.key, 0)
+    }
+
+    fn search_nested(&mut self, keyring: &Arc<Key>, depth: usize) -> Result<KeyRef> {
+        let links = keyring.links()?;
+
+        for key in links.iter() {
+            if self.matches_index(key) && self.check_candidate(key) == Candidate::Found {
+                return Ok(KeyRef::new(key.clone(), self.possessed));
+            }
+        }
+
+        if depth >= MAX_SEARCH_DEPTH {
+            return Err(self.result);
+        }
+
+        for key in links.iter() {
+            if key.type_() != KeyType::Keyring
+                || key
+                    .check_permission(self.credentials, self.possessed, KeyAccess::SEARCH)
+                    .is_err()
+            {
+                continue;
+            }
+
+            if let Ok(found) = self.search_nested(key, depth + 1) {
+                return Ok(found);
+            }
+        }
+
+        Err(self.result)
+    }
+
+    fn matches_index(&self, key: &Key) -> bool {
+        key.type_() == self.type_ && key.description() == self.description
+    }
+
+    /// Checks a key that matches the type and description.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/keyring.c#L553>.
+    fn check_candidate(&mut self, key: &Arc<Key>) -> Candidate {
+        if self.check_state {
+            if key.is_revoked() || key.is_invalidated() {
+                self.result = Error::with_message(Errno::EKEYREVOKED, "the key has been revoked");
+                return Candidate::Skipped;
+            }
+            if key.is_expired() {
+                self.result = Error::with_message(Errno::EKEYEXPIRED, "the key has expired");
+                return Candidate::Skipped;
+            }
+        }
+
+        if self.target.is_some_and(|target| !Arc::ptr_eq(target, key)) {
+            return Candidate::Mismatched;
+        }
+
+        if key
+            .check_permission(self.credentials, self.possessed, KeyAccess::SEARCH)
+            .is_err()
+        {
+            self.result = Error::with_message(Errno::EACCES, "the key cannot be searched");
+            return Candidate::Skipped;
+        }
+
+        Candidate::Found
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Candidate {
+    Found,
+    Skipped,
+    Mismatched,
+}
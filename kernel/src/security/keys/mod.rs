@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The kernel key retention service.
+//!
+//! Keys hold security data (e.g., authentication tokens and encryption keys) on behalf of user
+//! space. A key has a type, a description, an owner, permissions, and an optional expiry time.
+//! Keyrings are keys that link to other keys.
+//!
+//! A thread reaches keys through its thread, process, and session keyrings, as well as the user
+//! and user-session keyrings of its real user. The keys that can be found by searching the
+//! thread, process, and session keyrings are _possessed_ by the thread, which grants the
+//! possessor permissions of the keys.
+//!
+//! Reference: <https://docs.kernel.org/security/keys/core.html>.
+
+mod key;
+mod keyctl;
+mod keyring;
+mod permission;
+mod proc;
+mod process_keys;
+mod user;
+
+pub use key::{KeySerial, MAX_DESCRIPTION_LEN};
+pub use keyctl::{
+    add_key, chown_key, clear_keyring, describe_key, get_key_security, get_keyring_id,
+    invalidate_key, join_session_keyring, link_key, move_key, read_key, request_key, revoke_key,
+    search_keyring, session_keyring_to_parent, set_key_perm, set_key_timeout,
+    set_request_key_keyring, unlink_key, update_key,
+};
+pub use proc::{print_key_users, print_keys};
+pub use process_keys::ProcessKeyrings;
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_rights::ReadOp;
+
+use super::key::Key;
+use crate::{
+    prelude::*,
+    process::{Gid, credentials::Credentials},
+};
+
+bitflags! {
+    /// The permission mask of a key.
+    ///
+    /// The mask consists of four bytes, which grant [`KeyAccess`] to the possessor, the owner
+    /// user, the group, and other users, respectively.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/linux/key.h#L47>.
+    pub struct KeyPerm: u32 {
+        const POS_VIEW    = 0x0100_0000;
+        const POS_READ    = 0x0200_0000;
+        const POS_WRITE   = 0x0400_0000;
+        const POS_SEARCH  = 0x0800_0000;
+        const POS_LINK    = 0x1000_0000;
+        const POS_SETATTR = 0x2000_0000;
+        const POS_ALL     = 0x3f00_0000;
+
+        const USR_VIEW    = 0x0001_0000;
+        const USR_READ    = 0x0002_0000;
+        const USR_WRITE   = 0x0004_0000;
+        const USR_SEARCH  = 0x0008_0000;
+        const USR_LINK    = 0x0010_0000;
+        const USR_SETATTR = 0x0020_0000;
+        const USR_ALL     = 0x003f_0000;
+
+        const GRP_VIEW    = 0x0000_0100;
+        const GRP_READ    = 0x0000_0200;
+        const GRP_WRITE   = 0x0000_0400;
+        const GRP_SEARCH  = 0x0000_0800;
+        const GRP_LINK    = 0x0000_1000;
+        const GRP_SETATTR = 0x0000_2000;
+        const GRP_ALL     = 0x0000_3f00;
+
+        const OTH_VIEW    = 0x0000_0001;
+        const OTH_READ    = 0x0000_0002;
+        const OTH_WRITE   = 0x0000_0004;
+        const OTH_SEARCH  = 0x0000_0008;
+        const OTH_LINK    = 0x0000_0010;
+        const OTH_SETATTR = 0x0000_0020;
+        const OTH_ALL     = 0x0000_003f;
+    }
+}
+
+bitflags! {
+    /// The access rights to a key.
+    pub struct KeyAccess: u8 {
+        /// Viewing the attributes of the key.
+        const VIEW    = 0x01;
+        /// Reading the payload of the key, or the links of the keyring.
+        const READ    = 0x02;
+        /// Updating the payload of the key, or adding and removing links to the keyring.
+        const WRITE   = 0x04;
+        /// Finding the key in a search, or searching the keyring.
+        const SEARCH  = 0x08;
+        /// Linking the key to a keyring.
+        const LINK    = 0x10;
+        /// Changing the attributes of the key.
+        const SETATTR = 0x20;
+    }
+}
+
+impl KeyPerm {
+    /// Returns the access rights granted by the byte at `shift`.
+    fn access_at(self, shift: u32) -> KeyAccess {
+        KeyAccess::from_bits_truncate((self.bits() >> shift) as u8)
+    }
+}
+
+impl Key {
+    /// Checks whether the credentials have the access rights to the key.
+    ///
+    /// Unlike file permissions, key permissions cannot be overridden by capabilities.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/permission.c#L23>.
+    pub(super) fn check_permission(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        possessed: bool,
+        access: KeyAccess,
+    ) -> Result<()> {
+        let (uid, gid, perm) = self.owner_and_perm();
+
+        let mut granted = if uid == credentials.fsuid() {
+            perm.access_at(16)
+        } else if gid != Gid::INVALID
+            && perm.intersects(KeyPerm::GRP_ALL)
+            && (gid == credentials.fsgid() || credentials.groups().contains(&gid))
+        {
+            perm.access_at(8)
+        } else {
+            perm.access_at(0)
+        };
+
+        // The possessor permissions are additive with the other permissions.
+        if possessed {
+            granted |= perm.access_at(24);
+        }
+
+        if !granted.contains(access) {
+            return_errno_with_message!(Errno::EACCES, "the key access is not permitted");
+        }
+
+        Ok(())
+    }
+}
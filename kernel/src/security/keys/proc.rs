@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The contents of `/proc/keys` and `/proc/key-users`.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/proc.c>.
+
+use alloc::format;
+
+use aster_util::printer::VmPrinter;
+
+use super::{
+    key::{Key, KeyType, now},
+    keyring::{KeyRef, KeySearch},
+    permission::{KeyAccess, KeyPerm},
+    process_keys::search_process_keyrings,
+    user::KeyUser,
+};
+use crate::{prelude::*, process::posix_thread::PosixThread};
+
+/// Prints the keys that can be viewed by the thread.
+pub fn print_keys(printer: &mut VmPrinter, posix_thread: &PosixThread) -> Result<()> {
+    let credentials = posix_thread.credentials();
+
+    for key in Key::all() {
+        if key.is_invalidated() {
+            continue;
+        }
+
+        let (_, _, perm) = key.owner_and_perm();
+        // The possession only matters if the possessor can view the key.
+        let possessed = perm.contains(KeyPerm::POS_VIEW)
+            && search_process_keyrings(&credentials, &mut KeySearch::new_exact(&credentials, &key))
+                .is_ok();
+        let key_ref = KeyRef::new(key, possessed);
+        if key_ref
+            .check_permission(&credentials, KeyAccess::VIEW)
+            .is_err()
+        {
+            continue;
+        }
+        let key = key_ref.key;
+
+        let timeout = match key.expiry() {
+            None => String::from("perm"),
+            Some(expiry) => match expiry.checked_sub(now()).filter(|timeout| *timeout > 0) {
+                None => String::from("expd"),
+                Some(timeout) if timeout < 60 => format!("{}s", timeout),
+                Some(timeout) if timeout < 60 * 60 => format!("{}m", timeout / 60),
+                Some(timeout) if timeout < 60 * 60 * 24 => format!("{}h", timeout / (60 * 60)),
+                Some(timeout) if timeout < 60 * 60 * 24 * 7 => {
+                    format!("{}d", timeout / (60 * 60 * 24))
+                }
+                Some(timeout) => format!("{}w", timeout / (60 * 60 * 24 * 7)),
+            },
+        };
+
+        let (uid, gid) = key.owner_ids();
+        write!(
+            printer,
+            "{:08x} I{}-Q--- {:5} {:>4} {:08x} {:5} {:5} {:<9.9} {}",
+            key.serial(),
+            if key.is_revoked() { 'R' } else { '-' },
+            // Exclude the reference held by this function.
+            Arc::strong_count(&key) - 1,
+            timeout,
+            perm.bits(),
+            uid as i32,
+            gid as i32,
+            key.type_().name(),
+            key.description()
+        )?;
+
+        let payload_len = key.payload_len();
+        match key.type_() {
+            KeyType::User | KeyType::Logon => writeln!(printer, ": {}", payload_len)?,
+            KeyType::Keyring if payload_len == 0 => writeln!(printer, ": empty")?,
+            KeyType::Keyring => writeln!(printer, ": {}", payload_len)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the key usage and the quotas of the users that own keys.
+pub fn print_key_users(printer: &mut VmPrinter) -> Result<()> {
+    for user in KeyUser::all() {
+        let (nkeys, nbytes) = user.usage();
+        if nkeys == 0 {
+            continue;
+        }
+        let (max_keys, max_bytes) = user.limits();
+        writeln!(
+            printer,
+            "{:5}: {:5} {}/{} {}/{} {}/{}",
+            u32::from(user.uid()),
+            // Exclude the references held by the user table and this function.
+            Arc::strong_count(&user) - 2,
+            nkeys,
+            nkeys,
+            nkeys,
+            max_keys,
+            nbytes,
+            max_bytes
+        )?;
+    }
+
+    Ok(())
+}
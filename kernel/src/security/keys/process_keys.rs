@@ -0,0 +1,420 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The keyrings of threads and the lookup of keys by serial numbers.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/process_keys.c>.
+
+use core::sync::atomic::{AtomicI32, Ordering};
+
+use aster_rights::ReadOp;
+
+use super::{
+    key::{Key, KeyAllocFlags, KeyPayload, KeySerial, KeyType},
+    keyring::{KeyRef, KeySearch},
+    permission::{KeyAccess, KeyPerm},
+    user::KeyUser,
+};
+use crate::{prelude::*, process::Credentials};
+
+// Special serial numbers that refer to the keyrings of the calling thread.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/keyctl.h#L18>.
+const KEY_SPEC_THREAD_KEYRING: KeySerial = -1;
+const KEY_SPEC_PROCESS_KEYRING: KeySerial = -2;
+pub(super) const KEY_SPEC_SESSION_KEYRING: KeySerial = -3;
+const KEY_SPEC_USER_KEYRING: KeySerial = -4;
+const KEY_SPEC_USER_SESSION_KEYRING: KeySerial = -5;
+const KEY_SPEC_GROUP_KEYRING: KeySerial = -6;
+const KEY_SPEC_REQKEY_AUTH_KEY: KeySerial = -7;
+const KEY_SPEC_REQUESTOR_KEYRING: KeySerial = -8;
+
+// The default destination keyrings of `request_key()`.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/keyctl.h#L30>.
+const KEY_REQKEY_DEFL_NO_CHANGE: i32 = -1;
+const KEY_REQKEY_DEFL_DEFAULT: i32 = 0;
+const KEY_REQKEY_DEFL_THREAD_KEYRING: i32 = 1;
+const KEY_REQKEY_DEFL_PROCESS_KEYRING: i32 = 2;
+const KEY_REQKEY_DEFL_SESSION_KEYRING: i32 = 3;
+const KEY_REQKEY_DEFL_USER_KEYRING: i32 = 4;
+const KEY_REQKEY_DEFL_USER_SESSION_KEYRING: i32 = 5;
+const KEY_REQKEY_DEFL_REQUESTOR_KEYRING: i32 = 7;
+
+/// A lock that serializes the creation of named session keyrings.
+static SESSION_KEYRING_LOCK: Mutex<()> = Mutex::new(());
+
+type SharedKeyring = Arc<SpinLock<Option<Arc<Key>>>>;
+
+/// The keyrings in the credentials of a thread.
+#[derive(Debug)]
+pub struct ProcessKeyrings {
+    /// The thread keyring, which is private to the thread.
+    thread: SpinLock<Option<Arc<Key>>>,
+    /// The process keyring, which is shared by the threads in the process.
+    process: SpinLock<SharedKeyring>,
+    /// The session keyring, which is inherited by child threads and processes.
+    session: SpinLock<Option<Arc<Key>>>,
+    /// The default destination keyring of the keys that are constructed by `request_key()`.
+    request_key_dest: AtomicI32,
+}
+
+impl ProcessKeyrings {
+    pub fn new() -> Self {
+        Self {
+            thread: SpinLock::new(None),
+            process: SpinLock::new(Arc::new(SpinLock::new(None))),
+            session: SpinLock::new(None),
+            request_key_dest: AtomicI32::new(KEY_REQKEY_DEFL_DEFAULT),
+        }
+    }
+
+    /// Stops sharing the process keyring with the parent process.
+    ///
+    /// This method should be called when the credentials are copied for a new process.
+    pub fn unshare_process_keyring(&self) {
+        let old_keyring =
+            core::mem::replace(&mut *self.process.lock(), Arc::new(SpinLock::new(None)));
+        drop(old_keyring);
+    }
+
+    /// Discards the thread keyring and the process keyring for `execve()`.
+    ///
+    /// The session keyring is preserved.
+    pub fn reset_for_exec(&self) {
+        let old_keyring = self.thread.lock().take();
+        drop(old_keyring);
+        self.unshare_process_keyring();
+    }
+
+    fn thread_keyring(&self) -> Option<Arc<Key>> {
+        self.thread.lock().clone()
+    }
+
+    fn process_keyring(&self) -> Option<Arc<Key>> {
+        let process = self.process.lock().clone();
+        process.lock().clone()
+    }
+
+    pub(super) fn session_keyring(&self) -> Option<Arc<Key>> {
+        self.session.lock().clone()
+    }
+
+    pub(super) fn set_session_keyring(&self, keyring: Arc<Key>) {
+        let old_keyring = self.session.lock().replace(keyring);
+        drop(old_keyring);
+    }
+}
+
+impl Clone for ProcessKeyrings {
+    fn clone(&self) -> Self {
+        Self {
+            // The thread keyring is not inherited.
+            thread: SpinLock::new(None),
+            process: SpinLock::new(self.process.lock().clone()),
+            session: SpinLock::new(self.session_keyring()),
+            request_key_dest: AtomicI32::new(self.request_key_dest.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Default for ProcessKeyrings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Looks up the key by its serial number, which may refer to a special keyring.
+///
+/// If `create` is true, the thread, process, and session keyrings are created if they do not
+/// exist. If `access` is `None`, the caller is responsible for checking the state and the
+/// permissions of the key.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/process_keys.c#L610>.
+pub(super) fn lookup_key(
+    credentials: &Credentials<ReadOp>,
+    serial: KeySerial,
+    create: bool,
+    access: Option<KeyAccess>,
+) -> Result<KeyRef> {
+    let keyrings = credentials.keyrings();
+
+    let key_ref = match serial {
+        KEY_SPEC_THREAD_KEYRING => {
+            let keyring = match keyrings.thread_keyring() {
+                Some(keyring) => keyring,
+                None if create => install_thread_keyring(credentials)?,
+                None => {
+                    return_errno_with_message!(Errno::ENOKEY, "the thread keyring does not exist")
+                }
+            };
+            KeyRef::new(keyring, true)
+        }
+
+        KEY_SPEC_PROCESS_KEYRING => {
+            let keyring = match keyrings.process_keyring() {
+                Some(keyring) => keyring,
+                None if create => install_process_keyring(credentials)?,
+                None => {
+                    return_errno_with_message!(Errno::ENOKEY, "the process keyring does not exist")
+                }
+            };
+            KeyRef::new(keyring, true)
+        }
+
+        KEY_SPEC_SESSION_KEYRING => {
+            let keyring = match keyrings.session_keyring() {
+                // A session keyring is always installed upon access if one does not exist.
+                None if create => install_new_session_keyring(credentials)?,
+                None => {
+                    let (_, user_session_keyring) = KeyUser::get(credentials.ruid()).keyrings()?;
+                    keyrings.set_session_keyring(user_session_keyring.clone());
+                    user_session_keyring
+                }
+                Some(keyring) if create && keyring.is_uid_keyring() => {
+                    install_new_session_keyring(credentials)?
+                }
+                Some(keyring) => keyring,
+            };
+            KeyRef::new(keyring, true)
+        }
+
+        KEY_SPEC_USER_KEYRING => {
+            let (user_keyring, _) = KeyUser::get(credentials.ruid()).keyrings()?;
+            KeyRef::new(user_keyring, true)
+        }
+
+        KEY_SPEC_USER_SESSION_KEYRING => {
+            let (_, user_session_keyring) = KeyUser::get(credentials.ruid()).keyrings()?;
+            KeyRef::new(user_session_keyring, true)
+        }
+
+        KEY_SPEC_GROUP_KEYRING => {
+            return_errno_with_message!(Errno::EINVAL, "group keyrings are not supported")
+        }
+
+        // There are no authorization keys, since keys are never constructed by user space.
+        KEY_SPEC_REQKEY_AUTH_KEY | KEY_SPEC_REQUESTOR_KEYRING => {
+            return_errno_with_message!(Errno::ENOKEY, "there is no authorization key")
+        }
+
+        serial if serial < 1 => {
+            return_errno_with_message!(Errno::EINVAL, "the key serial number is invalid")
+        }
+
+        serial => {
+            let Some(key) = Key::lookup(serial) else {
+                return_errno_with_message!(Errno::ENOKEY, "the key does not exist");
+            };
+
+            // The key is possessed if it can be found in the keyrings of the thread.
+            let possessed =
+                search_process_keyrings(credentials, &mut KeySearch::new_exact(credentials, &key))
+                    .is_ok();
+            KeyRef::new(key, possessed)
+        }
+    };
+
+    if let Some(access) = access {
+        key_ref.key.validate()?;
+        key_ref.check_permission(credentials, access)?;
+    }
+
+    Ok(key_ref)
+}
+
+/// Searches the keyrings of the thread for a key.
+///
+/// The thread keyring, the process keyring, and the session keyring are searched in order. If
+/// there is no session keyring, the user-session keyring is searched instead.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/process_keys.c#L425>.
+pub(super) fn search_process_keyrings(
+    credentials: &Credentials<ReadOp>,
+    search: &mut KeySearch,
+) -> Result<KeyRef> {
+    let keyrings = credentials.keyrings();
+    let session_keyring = keyrings
+        .session_keyring()
+        .or_else(|| KeyUser::get(credentials.ruid()).user_session_keyring());
+
+    // If a keyring yields no key, the error takes precedence over the errors from the other
+    // keyrings (e.g., a permission error).
+    let mut not_found_error = None;
+    let mut other_error = KeySearch::not_found();
+
+    for keyring in [
+        keyrings.thread_keyring(),
+        keyrings.process_keyring(),
+        session_keyring,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        match search.search(&KeyRef::new(keyring, true)) {
+            Ok(key_ref) => return Ok(key_ref),
+            Err(err) if matches!(err.error(), Errno::EAGAIN | Errno::ENOKEY) => {
+                not_found_error = Some(err)
+            }
+            Err(err) => other_error = err,
+        }
+    }
+
+    Err(not_found_error.unwrap_or(other_error))
+}
+
+/// Installs a thread keyring if the thread has no thread keyring.
+fn install_thread_keyring(credentials: &Credentials<ReadOp>) -> Result<Arc<Key>> {
+    let keyring = new_keyring(
+        credentials,
+        "_tid",
+        KeyPerm::POS_ALL | KeyPerm::USR_VIEW,
+        KeyAllocFlags::QUOTA_OVERRUN,
+    )?;
+
+    let mut thread = credentials.keyrings().thread.lock();
+    Ok(thread.get_or_insert(keyring).clone())
+}
+
+/// Installs a process keyring if the process has no process keyring.
+fn install_process_keyring(credentials: &Credentials<ReadOp>) -> Result<Arc<Key>> {
+    let keyring = new_keyring(
+        credentials,
+        "_pid",
+        KeyPerm::POS_ALL | KeyPerm::USR_VIEW,
+        KeyAllocFlags::QUOTA_OVERRUN,
+    )?;
+
+    let process = credentials.keyrings().process.lock().clone();
+    let mut process = process.lock();
+    Ok(process.get_or_insert(keyring).clone())
+}
+
+/// Installs a new anonymous session keyring, replacing the existing one.
+fn install_new_session_keyring(credentials: &Credentials<ReadOp>) -> Result<Arc<Key>> {
+    let keyrings = credentials.keyrings();
+
+    // The first session keyring can always be created.
+    let alloc_flags = if keyrings.session_keyring().is_some() {
+        KeyAllocFlags::empty()
+    } else {
+        KeyAllocFlags::QUOTA_OVERRUN
+    };
+    let keyring = new_keyring(
+        credentials,
+        "_ses",
+        KeyPerm::POS_ALL | KeyPerm::USR_VIEW | KeyPerm::USR_READ,
+        alloc_flags,
+    )?;
+
+    keyrings.set_session_keyring(keyring.clone());
+    Ok(keyring)
+}
+
+/// Joins the session keyring of the name, or a new anonymous session keyring if the name is
+/// `None`.
+///
+/// If the named keyring does not exist, it is created. Otherwise, the existing keyring can be
+/// joined only if it can be searched.
+///
+/// Returns the serial number of the joined keyring, or zero if the keyring is already the
+/// session keyring.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/process_keys.c#L818>.
+pub(super) fn join_session_keyring(
+    credentials: &Credentials<ReadOp>,
+    name: Option<String>,
+) -> Result<KeySerial> {
+    let Some(name) = name else {
+        return Ok(install_new_session_keyring(credentials)?.serial());
+    };
+
+    let _guard = SESSION_KEYRING_LOCK.lock();
+
+    let keyring = match find_keyring_by_name(credentials, &name) {
+        Some(keyring) => keyring,
+        None => new_keyring(
+            credentials,
+            &name,
+            KeyPerm::POS_ALL | KeyPerm::USR_VIEW | KeyPerm::USR_READ | KeyPerm::USR_LINK,
+            KeyAllocFlags::empty(),
+        )?,
+    };
+
+    let keyrings = credentials.keyrings();
+    if keyrings
+        .session_keyring()
+        .is_some_and(|session_keyring| Arc::ptr_eq(&session_keyring, &keyring))
+    {
+        return Ok(0);
+    }
+
+    keyrings.set_session_keyring(keyring.clone());
+    Ok(keyring.serial())
+}
+
+/// Finds the keyring of the name that can be searched by the credentials.
+///
+/// The user keyrings and the user-session keyrings are not found by their names.
+fn find_keyring_by_name(credentials: &Credentials<ReadOp>, name: &str) -> Option<Arc<Key>> {
+    Key::all().into_iter().find(|key| {
+        key.type_() == KeyType::Keyring
+            && key.description() == name
+            && !key.is_uid_keyring()
+            && !key.is_revoked()
+            && !key.is_invalidated()
+            && key
+                .check_permission(credentials, false, KeyAccess::SEARCH)
+                .is_ok()
+    })
+}
+
+/// Sets the default destination keyring of `request_key()`, and returns the old one.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/keyctl.c#L1435>.
+pub(super) fn set_request_key_dest(credentials: &Credentials<ReadOp>, dest: i32) -> Result<i32> {
+    let keyrings = credentials.keyrings();
+
+    match dest {
+        KEY_REQKEY_DEFL_NO_CHANGE => {
+            return Ok(keyrings.request_key_dest.load(Ordering::Relaxed));
+        }
+        KEY_REQKEY_DEFL_THREAD_KEYRING => {
+            if keyrings.thread_keyring().is_none() {
+                install_thread_keyring(credentials)?;
+            }
+        }
+        KEY_REQKEY_DEFL_PROCESS_KEYRING => {
+            if keyrings.process_keyring().is_none() {
+                install_process_keyring(credentials)?;
+            }
+        }
+        KEY_REQKEY_DEFL_DEFAULT
+        | KEY_REQKEY_DEFL_SESSION_KEYRING
+        | KEY_REQKEY_DEFL_USER_KEYRING
+        | KEY_REQKEY_DEFL_USER_SESSION_KEYRING
+        | KEY_REQKEY_DEFL_REQUESTOR_KEYRING => {}
+        _ => {
+            return_errno_with_message!(Errno::EINVAL, "the default destination keyring is invalid")
+        }
+    }
+
+    Ok(keyrings.request_key_dest.swap(dest, Ordering::Relaxed))
+}
+
+/// Creates a new keyring owned by the real user and group of the credentials.
+fn new_keyring(
+    credentials: &Credentials<ReadOp>,
+    name: &str,
+    perm: KeyPerm,
+    alloc_flags: KeyAllocFlags,
+) -> Result<Arc<Key>> {
+    Key::new(
+        KeyType::Keyring,
+        name.to_string(),
+        credentials.ruid(),
+        credentials.rgid(),
+        perm,
+        KeyPayload::new_keyring(),
+        alloc_flags,
+    )
+}
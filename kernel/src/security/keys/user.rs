@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Per-user key accounting.
+
+use alloc::format;
+
+use super::{
+    key::{Key, KeyAllocFlags, KeyPayload, KeyType},
+    permission::KeyPerm,
+};
+use crate::{
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+/// The maximum number of keys that a non-root user can own.
+const MAX_KEYS: usize = 200;
+/// The maximum number of bytes that the keys of a non-root user can occupy.
+const MAX_BYTES: usize = 20000;
+/// The maximum number of keys that the root user can own.
+const ROOT_MAX_KEYS: usize = 1000000;
+/// The maximum number of bytes that the keys of the root user can occupy.
+const ROOT_MAX_BYTES: usize = 25000000;
+
+/// The users that own keys, indexed by their UIDs.
+static KEY_USERS: SpinLock<BTreeMap<u32, Arc<KeyUser>>> = SpinLock::new(BTreeMap::new());
+
+/// A user that owns keys.
+///
+/// The keys that are owned by a user are charged to the user's quota.
+#[derive(Debug)]
+pub(super) struct KeyUser {
+    uid: Uid,
+    usage: SpinLock<KeyUsage>,
+    /// The user keyring and the user-session keyring.
+    keyrings: Mutex<Option<(Arc<Key>, Arc<Key>)>>,
+}
+
+#[derive(Debug, Default)]
+struct KeyUsage {
+    nkeys: usize,
+    nbytes: usize,
+}
+
+impl KeyUser {
+    /// Gets the key user of the UID, creating it if it does not exist.
+    pub(super) fn get(uid: Uid) -> Arc<Self> {
+        KEY_USERS
+            .lock()
+            .entry(uid.into())
+            .or_insert_with(|| {
+                Arc::new(Self {
+                    uid,
+                    usage: SpinLock::new(KeyUsage::default()),
+                    keyrings: Mutex::new(None),
+                })
+            })
+            .clone()
+    }
+
+    /// Returns the users that own keys.
+    pub(super) fn all() -> Vec<Arc<Self>> {
+        KEY_USERS.lock().values().cloned().collect()
+    }
+
+    pub(super) fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Returns the quota limits as `(max_keys, max_bytes)`.
+    pub(super) fn limits(&self) -> (usize, usize) {
+        if self.uid.is_root() {
+            (ROOT_MAX_KEYS, ROOT_MAX_BYTES)
+        } else {
+            (MAX_KEYS, MAX_BYTES)
+        }
+    }
+
+    /// Returns the quota usage as `(nkeys, nbytes)`.
+    pub(super) fn usage(&self) -> (usize, usize) {
+        let usage = self.usage.lock();
+        (usage.nkeys, usage.nbytes)
+    }
+
+    /// Charges a new key of `nbytes` bytes to the user.
+    ///
+    /// If `overrun` is true, the key is charged even if the quota is exceeded.
+    pub(super) fn charge_key(&self, nbytes: usize, overrun: bool) -> Result<()> {
+        let (max_keys, max_bytes) = self.limits();
+
+        let mut usage = self.usage.lock();
+        if !overrun && (usage.nkeys + 1 > max_keys || usage.nbytes + nbytes > max_bytes) {
+            return_errno_with_message!(Errno::EDQUOT, "the key quota is exceeded");
+        }
+        usage.nkeys += 1;
+        usage.nbytes += nbytes;
+
+        Ok(())
+    }
+
+    /// Releases a key of `nbytes` bytes from the user.
+    pub(super) fn uncharge_key(&self, nbytes: usize) {
+        let mut usage = self.usage.lock();
+        usage.nkeys -= 1;
+        usage.nbytes -= nbytes;
+    }
+
+    /// Changes the number of bytes charged to the user for a key.
+    pub(super) fn recharge_bytes(&self, old_nbytes: usize, new_nbytes: usize) -> Result<()> {
+        let (_, max_bytes) = self.limits();
+
+        let mut usage = self.usage.lock();
+        if new_nbytes > old_nbytes && usage.nbytes - old_nbytes + new_nbytes > max_bytes {
+            return_errno_with_message!(Errno::EDQUOT, "the key quota is exceeded");
+        }
+        usage.nbytes = usage.nbytes - old_nbytes + new_nbytes;
+
+        Ok(())
+    }
+
+    /// Returns the user-session keyring if it exists.
+    pub(super) fn user_session_keyring(&self) -> Option<Arc<Key>> {
+        self.keyrings
+            .lock()
+            .as_ref()
+            .map(|(_, user_session_keyring)| user_session_keyring.clone())
+    }
+
+    /// Returns the user keyring and the user-session keyring, creating them if they do not
+    /// exist.
+    ///
+    /// The user-session keyring links to the user keyring. Both keyrings persist as long as the
+    /// user exists.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/security/keys/process_keys.c#L68>.
+    pub(super) fn keyrings(&self) -> Result<(Arc<Key>, Arc<Key>)> {
+        let mut keyrings = self.keyrings.lock();
+        if let Some(keyrings) = keyrings.as_ref() {
+            return Ok(keyrings.clone());
+        }
+
+        let perm = (KeyPerm::POS_ALL - KeyPerm::POS_SETATTR) | KeyPerm::USR_ALL;
+        let uid_nr = u32::from(self.uid);
+        let new_uid_keyring = |description| {
+            Key::new(
+                KeyType::Keyring,
+                description,
+                self.uid,
+                Gid::INVALID,
+                perm,
+                KeyPayload::new_keyring(),
+                KeyAllocFlags::UID_KEYRING,
+            )
+        };
+
+        let user_keyring = new_uid_keyring(format!("_uid.{}", uid_nr))?;
+        let user_session_keyring = new_uid_keyring(format!("_uid_ses.{}", uid_nr))?;
+        user_session_keyring.link(&user_keyring)?;
+
+        *keyrings = Some((user_keyring.clone(), user_session_keyring.clone()));
+        Ok((user_keyring, user_session_keyring))
+    }
+}
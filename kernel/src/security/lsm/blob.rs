@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Per-object security blobs.
+//!
+//! Linux reserves space in credentials, inodes, files and other kernel objects for each LSM
+//! module to store its security data (e.g., a label or a profile). The equivalent here is a
+//! [`SecurityBlob`], which maps each module to a value of a type chosen by the module.
+
+use super::LsmModule;
+use crate::prelude::*;
+
+/// The security data that LSM modules attach to a kernel object.
+///
+/// Each module can store at most one value in a blob. The value is private to the module: only
+/// the LSM framework can access a blob, and a module always accesses its own value.
+pub struct SecurityBlob {
+    slots: RwLock<BTreeMap<&'static str, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl SecurityBlob {
+    /// Creates an empty blob.
+    pub fn new() -> Self {
+        Self {
+            slots: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the value that `module` has stored in the blob, if any.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the stored value is not of type `T`.
+    pub(super) fn get<T: Any + Send + Sync>(&self, module: &dyn LsmModule) -> Option<Arc<T>> {
+        let value = self.slots.read().get(module.name())?.clone();
+        Some(value.downcast::<T>().unwrap())
+    }
+
+    /// Returns the value that `module` has stored in the blob, storing the value returned by
+    /// `init` if there is none.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the stored value is not of type `T`.
+    pub(super) fn get_or_init<T: Any + Send + Sync>(
+        &self,
+        module: &dyn LsmModule,
+        init: impl FnOnce() -> T,
+    ) -> Arc<T> {
+        if let Some(value) = self.get(module) {
+            return value;
+        }
+
+        let value = self
+            .slots
+            .write()
+            .entry(module.name())
+            .or_insert_with(|| Arc::new(init()))
+            .clone();
+        value.downcast::<T>().unwrap()
+    }
+
+    /// Stores the value of `module` in the blob, replacing the previous one.
+    pub(super) fn set<T: Any + Send + Sync>(&self, module: &dyn LsmModule, value: Arc<T>) {
+        self.slots.write().insert(module.name(), value);
+    }
+
+    /// Removes the value of `module` from the blob, if any.
+    pub(super) fn remove(&self, module: &dyn LsmModule) {
+        self.slots.write().remove(module.name());
+    }
+}
+
+impl Default for SecurityBlob {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for SecurityBlob {
+    /// Copies the values of all modules.
+    ///
+    /// This is used when an object is derived from another one, e.g., when the credentials are
+    /// copied for a new thread. The values themselves are shared, so a module that needs a
+    /// separate value for the new object must replace it.
+    fn clone(&self) -> Self {
+        Self {
+            slots: RwLock::new(self.slots.read().clone()),
+        }
+    }
+}
+
+impl Debug for SecurityBlob {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.slots.read().keys()).finish()
+    }
+}
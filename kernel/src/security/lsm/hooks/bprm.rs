@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Hooks for executing programs.
+//!
+//! Linux names these hooks after `struct linux_binprm`, which describes a program to execute.
+
+use super::super::modules;
+use crate::{fs::vfs::path::Path, prelude::*, process::posix_thread::PosixThread};
+
+/// Runs program execution check hooks in module order.
+pub fn on_bprm_check(context: BprmContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_bprm_check(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs hooks in module order after the credentials of the new program are installed.
+///
+/// The program can no longer fail to execute at this point, so the hooks cannot fail.
+pub fn on_bprm_committed_creds(context: BprmContext) {
+    for module in modules::active_modules() {
+        module.on_bprm_committed_creds(&context);
+    }
+}
+
+/// The inputs for executing a program.
+pub struct BprmContext<'a> {
+    posix_thread: &'a PosixThread,
+    path: &'a Path,
+}
+
+impl<'a> BprmContext<'a> {
+    /// Creates a program execution context.
+    pub const fn new(posix_thread: &'a PosixThread, path: &'a Path) -> Self {
+        Self { posix_thread, path }
+    }
+
+    /// Returns the thread that executes the program.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the program file.
+    ///
+    /// For a script, [`on_bprm_check`] runs for the script and then for each interpreter, while
+    /// the other hooks see only the script.
+    pub const fn path(&self) -> &Path {
+        self.path
+    }
+}
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::super::modules;
+use crate::{
+    fs::{
+        file::{FileLike, Permission},
+        vfs::path::Path,
+    },
+    prelude::*,
+    process::posix_thread::PosixThread,
+    vm::perms::VmPerms,
+};
+
+/// Runs file open hooks in module order.
+pub fn on_file_open(context: FileOpenContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_file_open(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs file permission hooks in module order.
+pub fn on_file_permission(context: FilePermissionContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_file_permission(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs file mapping hooks in module order.
+pub fn on_mmap_file(context: MmapFileContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_mmap_file(&context)?;
+    }
+
+    Ok(())
+}
+
+/// The inputs for opening a file.
+pub struct FileOpenContext<'a> {
+    posix_thread: &'a PosixThread,
+    path: &'a Path,
+    permission: Permission,
+}
+
+impl<'a> FileOpenContext<'a> {
+    /// Creates a file open context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        path: &'a Path,
+        permission: Permission,
+    ) -> Self {
+        Self {
+            posix_thread,
+            path,
+            permission,
+        }
+    }
+
+    /// Returns the thread that opens the file.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the file to be opened.
+    pub const fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// Returns the requested permission.
+    ///
+    /// `MAY_EXEC` is requested when the file is opened to be executed.
+    pub const fn permission(&self) -> Permission {
+        self.permission
+    }
+}
+
+/// The inputs for reading from or writing to an opened file.
+#[expect(dead_code)]
+pub struct FilePermissionContext<'a> {
+    posix_thread: &'a PosixThread,
+    file: &'a Arc<dyn FileLike>,
+    permission: Permission,
+}
+
+#[expect(dead_code)]
+impl<'a> FilePermissionContext<'a> {
+    /// Creates a file permission context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        file: &'a Arc<dyn FileLike>,
+        permission: Permission,
+    ) -> Self {
+        Self {
+            posix_thread,
+            file,
+            permission,
+        }
+    }
+
+    /// Returns the thread that accesses the file.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the file to be accessed.
+    pub const fn file(&self) -> &Arc<dyn FileLike> {
+        self.file
+    }
+
+    /// Returns the requested permission, either `MAY_READ` or `MAY_WRITE`.
+    pub const fn permission(&self) -> Permission {
+        self.permission
+    }
+}
+
+/// The inputs for mapping a file into memory.
+#[expect(dead_code)]
+pub struct MmapFileContext<'a> {
+    posix_thread: &'a PosixThread,
+    file: &'a Arc<dyn FileLike>,
+    perms: VmPerms,
+    is_shared: bool,
+}
+
+impl<'a> MmapFileContext<'a> {
+    /// Creates a file mapping context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        file: &'a Arc<dyn FileLike>,
+        perms: VmPerms,
+        is_shared: bool,
+    ) -> Self {
+        Self {
+            posix_thread,
+            file,
+            perms,
+            is_shared,
+        }
+    }
+
+    /// Returns the thread that maps the file.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the file to be mapped.
+    pub const fn file(&self) -> &Arc<dyn FileLike> {
+        self.file
+    }
+
+    /// Returns the memory access permissions of the mapping.
+    pub const fn perms(&self) -> VmPerms {
+        self.perms
+    }
+
+    /// Returns whether the mapping is shared, i.e., whether writes reach the file.
+    #[expect(dead_code)]
+    pub const fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+}
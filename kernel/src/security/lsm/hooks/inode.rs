@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::super::modules;
+use crate::{fs::vfs::path::Path, prelude::*, process::posix_thread::PosixThread};
+
+/// Runs attribute change hooks in module order.
+pub fn on_inode_setattr(context: InodeSetattrContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_inode_setattr(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs extended attribute setting hooks in module order.
+pub fn on_inode_setxattr(context: InodeXattrContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_inode_setxattr(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs extended attribute removal hooks in module order.
+pub fn on_inode_removexattr(context: InodeXattrContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_inode_removexattr(&context)?;
+    }
+
+    Ok(())
+}
+
+bitflags! {
+    /// The attributes of an inode that are changed together.
+    ///
+    /// The values are the same as the `ATTR_*` flags in Linux.
+    pub struct InodeAttrs: u32 {
+        /// The file mode.
+        const MODE = 1 << 0;
+        /// The owner.
+        const UID = 1 << 1;
+        /// The group.
+        const GID = 1 << 2;
+        /// The file size.
+        const SIZE = 1 << 3;
+        /// The last access time.
+        const ATIME = 1 << 4;
+        /// The last modification time.
+        const MTIME = 1 << 5;
+    }
+}
+
+/// The inputs for changing the attributes of a file.
+#[expect(dead_code)]
+pub struct InodeSetattrContext<'a> {
+    posix_thread: &'a PosixThread,
+    path: &'a Path,
+    attrs: InodeAttrs,
+}
+
+impl<'a> InodeSetattrContext<'a> {
+    /// Creates an attribute change context.
+    pub const fn new(posix_thread: &'a PosixThread, path: &'a Path, attrs: InodeAttrs) -> Self {
+        Self {
+            posix_thread,
+            path,
+            attrs,
+        }
+    }
+
+    /// Returns the thread that changes the attributes.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the file whose attributes are changed.
+    pub const fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// Returns the attributes to be changed.
+    #[expect(dead_code)]
+    pub const fn attrs(&self) -> InodeAttrs {
+        self.attrs
+    }
+}
+
+/// The inputs for setting or removing an extended attribute of a file.
+#[expect(dead_code)]
+pub struct InodeXattrContext<'a> {
+    posix_thread: &'a PosixThread,
+    path: &'a Path,
+    name: &'a str,
+}
+
+impl<'a> InodeXattrContext<'a> {
+    /// Creates an extended attribute context.
+    pub const fn new(posix_thread: &'a PosixThread, path: &'a Path, name: &'a str) -> Self {
+        Self {
+            posix_thread,
+            path,
+            name,
+        }
+    }
+
+    /// Returns the thread that modifies the extended attribute.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the file whose extended attribute is modified.
+    #[expect(dead_code)]
+    pub const fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// Returns the full name of the extended attribute, including the namespace prefix.
+    pub const fn name(&self) -> &str {
+        self.name
+    }
+}
@@ -1,15 +1,66 @@
 // SPDX-License-Identifier: MPL-2.0
 
 //! LSM hook points.
+//!
+//! The creation, removal, and renaming of files are mediated by the path hooks rather than by
+//! inode hooks, because the paths also tell the modules through which mounts the files are
+//! reached.
 
 mod alien_access;
+mod bprm;
 mod capability;
+mod file;
+mod inode;
+mod mount;
+mod path;
+mod socket;
+mod task;
+
+use ostd::task::Task;
 
 pub use self::{
     alien_access::{AlienAccessContext, on_alien_access},
+    bprm::{BprmContext, on_bprm_check, on_bprm_committed_creds},
     capability::{CapableContext, on_capable},
+    file::{
+        FileOpenContext, FilePermissionContext, MmapFileContext, on_file_open, on_file_permission,
+        on_mmap_file,
+    },
+    inode::{
+        InodeAttrs, InodeSetattrContext, InodeXattrContext, on_inode_removexattr, on_inode_setattr,
+        on_inode_setxattr,
+    },
+    mount::{SbMountContext, SbUmountContext, on_sb_mount, on_sb_umount},
+    path::{
+        PathCreateContext, PathLinkContext, PathRemoveContext, PathRenameContext,
+        PathTruncateContext, on_path_create, on_path_link, on_path_remove, on_path_rename,
+        on_path_truncate,
+    },
+    socket::{
+        SocketAddrContext, SocketCreateContext, SocketSendmsgContext, on_socket_bind,
+        on_socket_connect, on_socket_create, on_socket_sendmsg,
+    },
+    task::{TaskKillContext, TaskSetniceContext, on_task_kill, on_task_setnice},
+};
+use crate::{
+    prelude::*,
+    process::posix_thread::{AsPosixThread, PosixThread},
 };
-use crate::prelude::*;
+
+/// Runs `hook` with the current POSIX thread.
+///
+/// Kernel threads are not subject to LSM checks, so `hook` is skipped if the current task is
+/// not a POSIX thread.
+pub fn with_current_posix_thread(hook: impl FnOnce(&PosixThread) -> Result<()>) -> Result<()> {
+    let Some(task) = Task::current() else {
+        return Ok(());
+    };
+    let Some(posix_thread) = task.as_posix_thread() else {
+        return Ok(());
+    };
+
+    hook(posix_thread)
+}
 
 pub(super) trait LsmAlienAccessHook: Sync {
     /// Handles an alien access attempt.
@@ -24,3 +75,120 @@ pub(super) trait LsmCapabilityHook: Sync {
         Ok(())
     }
 }
+
+pub(super) trait LsmPathHook: Sync {
+    /// Checks whether a thread may create a file in a directory.
+    fn on_path_create(&self, _context: &PathCreateContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may remove a file or a directory.
+    fn on_path_remove(&self, _context: &PathRemoveContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may create a hard link.
+    fn on_path_link(&self, _context: &PathLinkContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may rename a file.
+    fn on_path_rename(&self, _context: &PathRenameContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may truncate a file.
+    fn on_path_truncate(&self, _context: &PathTruncateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(super) trait LsmInodeHook: Sync {
+    /// Checks whether a thread may change the attributes of a file.
+    fn on_inode_setattr(&self, _context: &InodeSetattrContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may set an extended attribute of a file.
+    fn on_inode_setxattr(&self, _context: &InodeXattrContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may remove an extended attribute of a file.
+    fn on_inode_removexattr(&self, _context: &InodeXattrContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(super) trait LsmFileHook: Sync {
+    /// Checks whether a thread may open a file.
+    fn on_file_open(&self, _context: &FileOpenContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may read from or write to an opened file.
+    fn on_file_permission(&self, _context: &FilePermissionContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may map a file into memory.
+    fn on_mmap_file(&self, _context: &MmapFileContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(super) trait LsmBprmHook: Sync {
+    /// Checks whether a thread may execute a program.
+    fn on_bprm_check(&self, _context: &BprmContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Updates the module state after the credentials of a new program are installed.
+    fn on_bprm_committed_creds(&self, _context: &BprmContext) {}
+}
+
+pub(super) trait LsmSocketHook: Sync {
+    /// Checks whether a thread may create a socket.
+    fn on_socket_create(&self, _context: &SocketCreateContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may bind a socket to an address.
+    fn on_socket_bind(&self, _context: &SocketAddrContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may connect a socket to an address.
+    fn on_socket_connect(&self, _context: &SocketAddrContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may send a message through a socket.
+    fn on_socket_sendmsg(&self, _context: &SocketSendmsgContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(super) trait LsmTaskHook: Sync {
+    /// Checks whether a thread may send a signal to another thread.
+    fn on_task_kill(&self, _context: &TaskKillContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may change the nice value of a process.
+    fn on_task_setnice(&self, _context: &TaskSetniceContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(super) trait LsmMountHook: Sync {
+    /// Checks whether a thread may mount a file system or change a mount.
+    fn on_sb_mount(&self, _context: &SbMountContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks whether a thread may unmount a file system.
+    fn on_sb_umount(&self, _context: &SbUmountContext) -> Result<()> {
+        Ok(())
+    }
+}
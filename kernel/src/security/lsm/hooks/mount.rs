@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Hooks for mounting and unmounting file systems.
+//!
+//! Linux names these hooks after `struct super_block`.
+
+use super::super::modules;
+use crate::{fs::vfs::path::Path, prelude::*, process::posix_thread::PosixThread};
+
+/// Runs mount hooks in module order.
+pub fn on_sb_mount(context: SbMountContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_sb_mount(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs unmount hooks in module order.
+pub fn on_sb_umount(context: SbUmountContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_sb_umount(&context)?;
+    }
+
+    Ok(())
+}
+
+/// The inputs for the `mount` system call.
+///
+/// Besides mounting a new file system, the system call can also create a bind mount, move a
+/// mount, remount, or change the propagation type, as determined by the flags.
+#[expect(dead_code)]
+pub struct SbMountContext<'a> {
+    posix_thread: &'a PosixThread,
+    target: &'a Path,
+    flags: u32,
+}
+
+impl<'a> SbMountContext<'a> {
+    /// Creates a mount context.
+    pub const fn new(posix_thread: &'a PosixThread, target: &'a Path, flags: u32) -> Self {
+        Self {
+            posix_thread,
+            target,
+            flags,
+        }
+    }
+
+    /// Returns the thread that mounts the file system.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the mount point.
+    #[expect(dead_code)]
+    pub const fn target(&self) -> &Path {
+        self.target
+    }
+
+    /// Returns the `MS_*` mount flags.
+    #[expect(dead_code)]
+    pub const fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+/// The inputs for unmounting a file system.
+#[expect(dead_code)]
+pub struct SbUmountContext<'a> {
+    posix_thread: &'a PosixThread,
+    target: &'a Path,
+    flags: u32,
+}
+
+impl<'a> SbUmountContext<'a> {
+    /// Creates an unmount context.
+    pub const fn new(posix_thread: &'a PosixThread, target: &'a Path, flags: u32) -> Self {
+        Self {
+            posix_thread,
+            target,
+            flags,
+        }
+    }
+
+    /// Returns the thread that unmounts the file system.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the mount to be unmounted.
+    #[expect(dead_code)]
+    pub const fn target(&self) -> &Path {
+        self.target
+    }
+
+    /// Returns the `MNT_*` and `UMOUNT_*` unmount flags.
+    #[expect(dead_code)]
+    pub const fn flags(&self) -> u32 {
+        self.flags
+    }
+}
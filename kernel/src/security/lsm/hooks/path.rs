@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::super::modules;
+use crate::{
+    fs::{file::InodeType, vfs::path::Path},
+    prelude::*,
+    process::posix_thread::PosixThread,
+};
+
+/// Runs path creation hooks in module order.
+pub fn on_path_create(context: PathCreateContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_path_create(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs path removal hooks in module order.
+pub fn on_path_remove(context: PathRemoveContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_path_remove(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs hard link hooks in module order.
+pub fn on_path_link(context: PathLinkContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_path_link(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs rename hooks in module order.
+pub fn on_path_rename(context: PathRenameContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_path_rename(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs truncation hooks in module order.
+pub fn on_path_truncate(context: PathTruncateContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_path_truncate(&context)?;
+    }
+
+    Ok(())
+}
+
+/// The inputs for creating a new file in a directory.
+pub struct PathCreateContext<'a> {
+    posix_thread: &'a PosixThread,
+    dir: &'a Path,
+    name: &'a str,
+    type_: InodeType,
+}
+
+impl<'a> PathCreateContext<'a> {
+    /// Creates a path creation context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        dir: &'a Path,
+        name: &'a str,
+        type_: InodeType,
+    ) -> Self {
+        Self {
+            posix_thread,
+            dir,
+            name,
+            type_,
+        }
+    }
+
+    /// Returns the thread that creates the file.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the directory in which the file is created.
+    pub const fn dir(&self) -> &Path {
+        self.dir
+    }
+
+    /// Returns the name of the new file.
+    pub const fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Returns the type of the new file.
+    pub const fn type_(&self) -> InodeType {
+        self.type_
+    }
+}
+
+/// The inputs for removing a file or a directory from a directory.
+pub struct PathRemoveContext<'a> {
+    posix_thread: &'a PosixThread,
+    dir: &'a Path,
+    victim: &'a Path,
+}
+
+impl<'a> PathRemoveContext<'a> {
+    /// Creates a path removal context.
+    pub const fn new(posix_thread: &'a PosixThread, dir: &'a Path, victim: &'a Path) -> Self {
+        Self {
+            posix_thread,
+            dir,
+            victim,
+        }
+    }
+
+    /// Returns the thread that removes the file.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the directory from which the file is removed.
+    pub const fn dir(&self) -> &Path {
+        self.dir
+    }
+
+    /// Returns the file to be removed.
+    pub const fn victim(&self) -> &Path {
+        self.victim
+    }
+}
+
+/// The inputs for creating a hard link.
+pub struct PathLinkContext<'a> {
+    posix_thread: &'a PosixThread,
+    old: &'a Path,
+    new_dir: &'a Path,
+    new_name: &'a str,
+}
+
+impl<'a> PathLinkContext<'a> {
+    /// Creates a hard link context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        old: &'a Path,
+        new_dir: &'a Path,
+        new_name: &'a str,
+    ) -> Self {
+        Self {
+            posix_thread,
+            old,
+            new_dir,
+            new_name,
+        }
+    }
+
+    /// Returns the thread that creates the hard link.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the existing file.
+    pub const fn old(&self) -> &Path {
+        self.old
+    }
+
+    /// Returns the directory in which the new link is created.
+    pub const fn new_dir(&self) -> &Path {
+        self.new_dir
+    }
+
+    /// Returns the name of the new link.
+    pub const fn new_name(&self) -> &str {
+        self.new_name
+    }
+}
+
+/// The inputs for renaming a file.
+pub struct PathRenameContext<'a> {
+    posix_thread: &'a PosixThread,
+    old_dir: &'a Path,
+    old: &'a Path,
+    new_dir: &'a Path,
+    new_name: &'a str,
+    replaced: Option<&'a Path>,
+}
+
+impl<'a> PathRenameContext<'a> {
+    /// Creates a rename context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        old_dir: &'a Path,
+        old: &'a Path,
+        new_dir: &'a Path,
+        new_name: &'a str,
+        replaced: Option<&'a Path>,
+    ) -> Self {
+        Self {
+            posix_thread,
+            old_dir,
+            old,
+            new_dir,
+            new_name,
+            replaced,
+        }
+    }
+
+    /// Returns the thread that renames the file.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the directory that contains the file.
+    pub const fn old_dir(&self) -> &Path {
+        self.old_dir
+    }
+
+    /// Returns the file to be renamed.
+    pub const fn old(&self) -> &Path {
+        self.old
+    }
+
+    /// Returns the directory to which the file is moved.
+    pub const fn new_dir(&self) -> &Path {
+        self.new_dir
+    }
+
+    /// Returns the new name of the file.
+    pub const fn new_name(&self) -> &str {
+        self.new_name
+    }
+
+    /// Returns the existing file that will be replaced, if any.
+    pub const fn replaced(&self) -> Option<&Path> {
+        self.replaced
+    }
+}
+
+/// The inputs for truncating a file.
+pub struct PathTruncateContext<'a> {
+    posix_thread: &'a PosixThread,
+    path: &'a Path,
+}
+
+impl<'a> PathTruncateContext<'a> {
+    /// Creates a truncation context.
+    pub const fn new(posix_thread: &'a PosixThread, path: &'a Path) -> Self {
+        Self { posix_thread, path }
+    }
+
+    /// Returns the thread that truncates the file.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the file to be truncated.
+    pub const fn path(&self) -> &Path {
+        self.path
+    }
+}
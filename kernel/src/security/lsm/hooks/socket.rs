@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::super::modules;
+use crate::{
+    net::socket::{Socket, util::SocketAddr},
+    prelude::*,
+    process::posix_thread::PosixThread,
+    util::net::{CSocketAddrFamily, Protocol, SockType},
+};
+
+/// Runs socket creation hooks in module order.
+pub fn on_socket_create(context: SocketCreateContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_socket_create(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs socket bind hooks in module order.
+pub fn on_socket_bind(context: SocketAddrContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_socket_bind(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs socket connect hooks in module order.
+pub fn on_socket_connect(context: SocketAddrContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_socket_connect(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs socket message sending hooks in module order.
+pub fn on_socket_sendmsg(context: SocketSendmsgContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_socket_sendmsg(&context)?;
+    }
+
+    Ok(())
+}
+
+/// The inputs for creating a socket.
+#[expect(dead_code)]
+pub struct SocketCreateContext<'a> {
+    posix_thread: &'a PosixThread,
+    family: CSocketAddrFamily,
+    type_: SockType,
+    protocol: i32,
+}
+
+impl<'a> SocketCreateContext<'a> {
+    /// Creates a socket creation context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        family: CSocketAddrFamily,
+        type_: SockType,
+        protocol: i32,
+    ) -> Self {
+        Self {
+            posix_thread,
+            family,
+            type_,
+            protocol,
+        }
+    }
+
+    /// Returns the thread that creates the socket.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the address family of the socket.
+    pub const fn family(&self) -> CSocketAddrFamily {
+        self.family
+    }
+
+    /// Returns the type of the socket.
+    pub const fn type_(&self) -> SockType {
+        self.type_
+    }
+
+    /// Returns the protocol number of the socket.
+    ///
+    /// The meaning of the number depends on the address family.
+    #[expect(dead_code)]
+    pub const fn protocol(&self) -> i32 {
+        self.protocol
+    }
+}
+
+/// The inputs for binding or connecting a socket to an address.
+pub struct SocketAddrContext<'a> {
+    posix_thread: &'a PosixThread,
+    protocol: Protocol,
+    addr: &'a SocketAddr,
+}
+
+impl<'a> SocketAddrContext<'a> {
+    /// Creates a socket address context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        protocol: Protocol,
+        addr: &'a SocketAddr,
+    ) -> Self {
+        Self {
+            posix_thread,
+            protocol,
+            addr,
+        }
+    }
+
+    /// Returns the thread that operates on the socket.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the protocol of the socket.
+    pub const fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Returns the address to which the socket is bound or connected.
+    pub const fn addr(&self) -> &SocketAddr {
+        self.addr
+    }
+}
+
+/// The inputs for sending a message through a socket.
+#[expect(dead_code)]
+pub struct SocketSendmsgContext<'a> {
+    posix_thread: &'a PosixThread,
+    socket: &'a dyn Socket,
+    addr: Option<&'a SocketAddr>,
+}
+
+#[expect(dead_code)]
+impl<'a> SocketSendmsgContext<'a> {
+    /// Creates a message sending context.
+    pub const fn new(
+        posix_thread: &'a PosixThread,
+        socket: &'a dyn Socket,
+        addr: Option<&'a SocketAddr>,
+    ) -> Self {
+        Self {
+            posix_thread,
+            socket,
+            addr,
+        }
+    }
+
+    /// Returns the thread that sends the message.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the socket through which the message is sent.
+    pub const fn socket(&self) -> &dyn Socket {
+        self.socket
+    }
+
+    /// Returns the destination address, if it is specified.
+    pub const fn addr(&self) -> Option<&SocketAddr> {
+        self.addr
+    }
+}
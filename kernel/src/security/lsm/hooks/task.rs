@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::super::modules;
+use crate::{
+    prelude::*,
+    process::{Process, posix_thread::PosixThread, signal::sig_num::SigNum},
+    sched::Nice,
+};
+
+/// Runs signal sending hooks in module order.
+pub fn on_task_kill(context: TaskKillContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_task_kill(&context)?;
+    }
+
+    Ok(())
+}
+
+/// Runs nice value change hooks in module order.
+pub fn on_task_setnice(context: TaskSetniceContext) -> Result<()> {
+    for module in modules::active_modules() {
+        module.on_task_setnice(&context)?;
+    }
+
+    Ok(())
+}
+
+/// The inputs for sending a signal to a thread.
+#[expect(dead_code)]
+pub struct TaskKillContext<'a> {
+    sender: &'a PosixThread,
+    target: &'a PosixThread,
+    signum: Option<SigNum>,
+}
+
+#[expect(dead_code)]
+impl<'a> TaskKillContext<'a> {
+    /// Creates a signal sending context.
+    pub const fn new(
+        sender: &'a PosixThread,
+        target: &'a PosixThread,
+        signum: Option<SigNum>,
+    ) -> Self {
+        Self {
+            sender,
+            target,
+            signum,
+        }
+    }
+
+    /// Returns the thread that sends the signal.
+    pub const fn sender(&self) -> &PosixThread {
+        self.sender
+    }
+
+    /// Returns the thread that receives the signal.
+    ///
+    /// For a signal sent to a process, this is the main thread of the process.
+    pub const fn target(&self) -> &PosixThread {
+        self.target
+    }
+
+    /// Returns the signal number.
+    ///
+    /// It is `None` if only the permission to send signals is checked (i.e., signal 0).
+    pub const fn signum(&self) -> Option<SigNum> {
+        self.signum
+    }
+}
+
+/// The inputs for changing the nice value of a process.
+#[expect(dead_code)]
+pub struct TaskSetniceContext<'a> {
+    posix_thread: &'a PosixThread,
+    target: &'a Process,
+    nice: Nice,
+}
+
+impl<'a> TaskSetniceContext<'a> {
+    /// Creates a nice value change context.
+    pub const fn new(posix_thread: &'a PosixThread, target: &'a Process, nice: Nice) -> Self {
+        Self {
+            posix_thread,
+            target,
+            nice,
+        }
+    }
+
+    /// Returns the thread that changes the nice value.
+    pub const fn posix_thread(&self) -> &PosixThread {
+        self.posix_thread
+    }
+
+    /// Returns the process whose nice value is changed.
+    pub const fn target(&self) -> &Process {
+        self.target
+    }
+
+    /// Returns the new nice value.
+    #[expect(dead_code)]
+    pub const fn nice(&self) -> Nice {
+        self.nice
+    }
+}
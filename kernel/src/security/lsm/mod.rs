@@ -7,17 +7,30 @@
 //! inspect common hook contexts before allowing or rejecting an operation.
 //!
 //! This module defines the common LSM traits and hook contexts shared by
-//! built-in modules such as `capability` and `yama`. Module selection follows
-//! the `lsm=` and legacy `security=` kernel command-line parameters.
+//! built-in modules such as `capability`, `apparmor`, `landlock`, and `yama`.
+//! Module selection follows the `lsm=` and legacy `security=` kernel
+//! command-line parameters. Modules can attach their own data to credentials
+//! and inodes through security blobs.
 
+mod blob;
 pub mod hooks;
 mod modules;
 
+pub mod landlock {
+    pub use super::modules::landlock::{
+        AccessFs, AccessNet, LANDLOCK_ABI_VERSION, Ruleset, RulesetFile, restrict_self,
+    };
+}
+
 pub mod yama {
     pub use super::modules::yama::{YamaScope, get_scope, set_scope};
 }
 
-use self::hooks::{LsmAlienAccessHook, LsmCapabilityHook};
+pub use self::blob::SecurityBlob;
+use self::hooks::{
+    LsmAlienAccessHook, LsmBprmHook, LsmCapabilityHook, LsmFileHook, LsmInodeHook, LsmMountHook,
+    LsmPathHook, LsmSocketHook, LsmTaskHook,
+};
 use crate::prelude::*;
 
 bitflags! {
@@ -31,12 +44,26 @@ bitflags! {
 }
 
 /// The common interface for built-in LSM modules.
-trait LsmModule: LsmAlienAccessHook + LsmCapabilityHook + Sync {
+trait LsmModule:
+    LsmAlienAccessHook
+    + LsmCapabilityHook
+    + LsmPathHook
+    + LsmInodeHook
+    + LsmFileHook
+    + LsmBprmHook
+    + LsmSocketHook
+    + LsmTaskHook
+    + LsmMountHook
+    + Sync
+{
     /// Returns the module name.
     fn name(&self) -> &'static str;
 
     /// Returns the module flags.
     fn flags(&self) -> LsmFlags;
+
+    /// Initializes the module after it is enabled.
+    fn init(&self) {}
 }
 
 /// Returns whether the Yama LSM is enabled.
@@ -46,8 +73,16 @@ pub fn is_yama_enabled() -> bool {
         .any(|module| module.name() == "yama")
 }
 
+/// Returns whether the Landlock LSM is enabled.
+pub fn is_landlock_enabled() -> bool {
+    modules::active_modules()
+        .iter()
+        .any(|module| module.name() == "landlock")
+}
+
 pub(super) fn init() {
     for module in modules::active_modules() {
         info!("[kernel] LSM module enabled: {}", module.name());
+        module.init();
     }
 }
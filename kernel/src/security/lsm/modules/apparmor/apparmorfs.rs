@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The policy interface of AppArmor.
+//!
+//! The interface is the `apparmor` directory in [`SecurityFs`], which contains the following
+//! attributes:
+//! - `.load`: Loads the profiles written to it. Loaded profiles are not replaced.
+//! - `.replace`: Loads the profiles written to it, replacing loaded profiles with the same names.
+//! - `.remove`: Removes the profile whose name is written to it.
+//! - `profiles`: Lists the loaded profiles and their modes.
+//!
+//! A policy must be written with a single write. Managing the policy requires `CAP_MAC_ADMIN`.
+//!
+//! [`SecurityFs`]: crate::fs::securityfs
+
+use alloc::{string::String, sync::Arc, vec};
+
+use aster_systree::{
+    BranchNodeFields, Error, Result, SysAttrSetBuilder, SysObj, SysPerms, SysStr,
+    inherit_sys_branch_node,
+};
+use aster_util::printer::VmPrinter;
+use ostd::mm::{FallibleVmRead, VmReader, VmWriter};
+
+use super::{parser, policy};
+use crate::{
+    error::Errno,
+    fs::securityfs,
+    process::{UserNamespace, credentials::capabilities::CapSet},
+    security::lsm::hooks as lsm_hooks,
+};
+
+/// The maximum size of a policy that can be written.
+const MAX_POLICY_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+struct AppArmorNode {
+    fields: BranchNodeFields<dyn SysObj, Self>,
+}
+
+impl AppArmorNode {
+    fn new() -> Arc<Self> {
+        let name = SysStr::from("apparmor");
+        let mut builder = SysAttrSetBuilder::new();
+        builder.add(SysStr::from(".load"), SysPerms::OWNER_W);
+        builder.add(SysStr::from(".replace"), SysPerms::OWNER_W);
+        builder.add(SysStr::from(".remove"), SysPerms::OWNER_W);
+        builder.add(SysStr::from("profiles"), SysPerms::DEFAULT_RO_ATTR_PERMS);
+        let attrs = builder.build().unwrap();
+
+        Arc::new_cyclic(|weak_self| AppArmorNode {
+            fields: BranchNodeFields::new(name, attrs, weak_self.clone()),
+        })
+    }
+}
+
+inherit_sys_branch_node!(AppArmorNode, fields, {
+    fn perms(&self) -> SysPerms {
+        SysPerms::DEFAULT_RO_PERMS
+    }
+
+    fn read_attr_at(&self, name: &str, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        match name {
+            "profiles" => {
+                let mut printer = VmPrinter::new_skip(writer, offset);
+                for (name, mode) in policy::list() {
+                    writeln!(printer, "{} ({})", name, mode)?;
+                }
+                Ok(printer.bytes_written())
+            }
+
+            _ => Err(Error::AttributeError),
+        }
+    }
+
+    fn write_attr(&self, name: &str, reader: &mut VmReader) -> Result<usize> {
+        check_mac_admin()?;
+
+        let len = reader.remain();
+        let text = read_text(reader)?;
+        match name {
+            ".load" | ".replace" => {
+                let profiles = parser::parse_profiles(&text).map_err(to_systree_error)?;
+                policy::load(profiles, name == ".replace").map_err(to_systree_error)?;
+            }
+
+            ".remove" => policy::remove(text.trim()).map_err(to_systree_error)?,
+
+            _ => return Err(Error::AttributeError),
+        }
+
+        Ok(len)
+    }
+
+    fn write_attr_at(&self, _name: &str, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
+        // A policy cannot be written in pieces.
+        Err(Error::InvalidOperation)
+    }
+});
+
+fn check_mac_admin() -> Result<()> {
+    lsm_hooks::with_current_posix_thread(|posix_thread| {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            UserNamespace::get_init_singleton().as_ref(),
+            posix_thread,
+            CapSet::MAC_ADMIN,
+        ))
+    })
+    .map_err(|_| Error::PermissionDenied)
+}
+
+fn read_text(reader: &mut VmReader) -> Result<String> {
+    if reader.remain() > MAX_POLICY_SIZE {
+        return Err(Error::InvalidOperation);
+    }
+
+    let mut buf = vec![0u8; reader.remain()];
+    reader
+        .read_fallible(&mut VmWriter::from(buf.as_mut_slice()))
+        .map_err(|_| Error::PageFault)?;
+    String::from_utf8(buf).map_err(|_| Error::InvalidOperation)
+}
+
+fn to_systree_error(err: crate::error::Error) -> Error {
+    match err.error() {
+        Errno::EEXIST => Error::AlreadyExists,
+        Errno::ENOENT => Error::NotFound,
+        Errno::EPERM | Errno::EACCES => Error::PermissionDenied,
+        _ => Error::InvalidOperation,
+    }
+}
+
+pub(super) fn init() {
+    securityfs::register_subsystem(AppArmorNode::new()).unwrap();
+}
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::prelude::*;
+
+/// A pattern that matches absolute paths.
+///
+/// The syntax follows the AppArmor profile language:
+/// - `*` matches any characters except `/`;
+/// - `**` matches any characters, including `/`;
+/// - `?` matches one character except `/`;
+/// - `[abc]`, `[a-z]`, and `[^abc]` match one character in or not in the set;
+/// - `{foo,bar}` matches any of the alternatives, which can be nested.
+///
+/// Reference: <https://manpages.ubuntu.com/manpages/noble/man5/apparmor.d.5.html>.
+#[derive(Debug)]
+pub(super) struct Glob {
+    alternatives: Vec<Vec<Token>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Literal(u8),
+    /// `*`
+    Star,
+    /// `**`
+    DoubleStar,
+    /// `?`
+    Any,
+    /// `[...]`
+    Class {
+        negated: bool,
+        ranges: Vec<(u8, u8)>,
+    },
+}
+
+/// The maximum number of alternatives that a pattern can expand to.
+const MAX_ALTERNATIVES: usize = 64;
+
+impl Glob {
+    /// Compiles a pattern.
+    pub(super) fn new(pattern: &str) -> Result<Self> {
+        if !pattern.starts_with('/') {
+            return_errno_with_message!(Errno::EINVAL, "the pattern is not an absolute path");
+        }
+
+        let alternatives = expand_braces(pattern.as_bytes())?
+            .iter()
+            .map(|alternative| tokenize(alternative))
+            .collect::<Result<_>>()?;
+
+        Ok(Self { alternatives })
+    }
+
+    /// Returns whether the path matches the pattern.
+    pub(super) fn matches(&self, path: &str) -> bool {
+        self.alternatives
+            .iter()
+            .any(|tokens| match_tokens(tokens, path.as_bytes()))
+    }
+
+    /// Returns how specific the pattern is for the path.
+    ///
+    /// When multiple patterns match a path, the most specific one wins. A pattern without
+    /// wildcards is more specific than any pattern with wildcards, and otherwise a pattern with
+    /// a longer literal prefix is more specific.
+    pub(super) fn specificity(&self, path: &str) -> usize {
+        self.alternatives
+            .iter()
+            .filter(|tokens| match_tokens(tokens, path.as_bytes()))
+            .map(|tokens| {
+                let literal_len = tokens
+                    .iter()
+                    .take_while(|token| matches!(token, Token::Literal(_)))
+                    .count();
+                if literal_len == tokens.len() {
+                    usize::MAX
+                } else {
+                    literal_len
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Expands the `{...}` alternations in the pattern.
+fn expand_braces(pattern: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let Some(open) = pattern.iter().position(|&c| c == b'{') else {
+        return Ok(vec![pattern.to_vec()]);
+    };
+
+    // Find the matching closing brace and the commas at the top level of the group.
+    let mut depth = 0;
+    let mut separators = Vec::new();
+    let mut close = None;
+    for (i, &c) in pattern.iter().enumerate().skip(open) {
+        match c {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            b',' if depth == 1 => separators.push(i),
+            _ => {}
+        }
+    }
+    let Some(close) = close else {
+        return_errno_with_message!(Errno::EINVAL, "the pattern has an unclosed brace");
+    };
+
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    let mut expanded = Vec::new();
+    let mut start = open + 1;
+    for end in separators.into_iter().chain(core::iter::once(close)) {
+        let mut alternative = prefix.to_vec();
+        alternative.extend_from_slice(&pattern[start..end]);
+        alternative.extend_from_slice(suffix);
+        // The alternative and the suffix may contain more groups.
+        expanded.extend(expand_braces(&alternative)?);
+        if expanded.len() > MAX_ALTERNATIVES {
+            return_errno_with_message!(Errno::EINVAL, "the pattern has too many alternatives");
+        }
+        start = end + 1;
+    }
+
+    Ok(expanded)
+}
+
+fn tokenize(pattern: &[u8]) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < pattern.len() {
+        let token = match pattern[i] {
+            b'*' if pattern.get(i + 1) == Some(&b'*') => {
+                // Any further stars are redundant.
+                while pattern.get(i + 1) == Some(&b'*') {
+                    i += 1;
+                }
+                Token::DoubleStar
+            }
+            b'*' => Token::Star,
+            b'?' => Token::Any,
+            b'[' => {
+                let (class, len) = parse_class(&pattern[i..])?;
+                i += len - 1;
+                class
+            }
+            b'\\' if i + 1 < pattern.len() => {
+                i += 1;
+                Token::Literal(pattern[i])
+            }
+            c => Token::Literal(c),
+        };
+        tokens.push(token);
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Parses a character class at the start of `pattern`, returning the class and its length.
+fn parse_class(pattern: &[u8]) -> Result<(Token, usize)> {
+    let mut i = 1;
+    let negated = pattern.get(i) == Some(&b'^');
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = Vec::new();
+    loop {
+        let Some(&c) = pattern.get(i) else {
+            return_errno_with_message!(Errno::EINVAL, "the pattern has an unclosed bracket");
+        };
+        if c == b']' && !ranges.is_empty() {
+            return Ok((Token::Class { negated, ranges }, i + 1));
+        }
+
+        if pattern.get(i + 1) == Some(&b'-')
+            && let Some(&end) = pattern.get(i + 2)
+            && end != b']'
+        {
+            if end < c {
+                return_errno_with_message!(Errno::EINVAL, "the pattern has an invalid range");
+            }
+            ranges.push((c, end));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+}
+
+fn match_tokens(tokens: &[Token], path: &[u8]) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return path.is_empty();
+    };
+
+    match token {
+        Token::Literal(c) => path.first() == Some(c) && match_tokens(rest, &path[1..]),
+        Token::Any => path.first().is_some_and(|&c| c != b'/') && match_tokens(rest, &path[1..]),
+        Token::Class { negated, ranges } => {
+            path.first().is_some_and(|&c| {
+                c != b'/'
+                    && ranges
+                        .iter()
+                        .any(|&(start, end)| (start..=end).contains(&c))
+                        != *negated
+            }) && match_tokens(rest, &path[1..])
+        }
+        Token::Star => {
+            let max_len = path.iter().position(|&c| c == b'/').unwrap_or(path.len());
+            (0..=max_len).any(|len| match_tokens(rest, &path[len..]))
+        }
+        Token::DoubleStar => (0..=path.len()).any(|len| match_tokens(rest, &path[len..])),
+    }
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::Glob;
+
+    #[ktest]
+    fn match_wildcards() {
+        let glob = Glob::new("/etc/*.conf").unwrap();
+        assert!(glob.matches("/etc/resolv.conf"));
+        assert!(glob.matches("/etc/.conf"));
+        assert!(!glob.matches("/etc/ssl/openssl.conf"));
+
+        let glob = Glob::new("/home/**").unwrap();
+        assert!(glob.matches("/home/"));
+        assert!(glob.matches("/home/user/.profile"));
+        assert!(!glob.matches("/home"));
+
+        let glob = Glob::new("/dev/tty?").unwrap();
+        assert!(glob.matches("/dev/tty1"));
+        assert!(!glob.matches("/dev/tty12"));
+    }
+
+    #[ktest]
+    fn match_classes_and_alternatives() {
+        let glob = Glob::new("/dev/sd[a-c][^0-9]").unwrap();
+        assert!(glob.matches("/dev/sdbx"));
+        assert!(!glob.matches("/dev/sdd1"));
+        assert!(!glob.matches("/dev/sda1"));
+
+        let glob = Glob::new("/usr/{bin,lib{,64}}/**").unwrap();
+        assert!(glob.matches("/usr/bin/ls"));
+        assert!(glob.matches("/usr/lib/libc.so"));
+        assert!(glob.matches("/usr/lib64/libc.so"));
+        assert!(!glob.matches("/usr/share/doc"));
+
+        assert!(Glob::new("/usr/{bin").is_err());
+        assert!(Glob::new("usr/bin").is_err());
+    }
+
+    #[ktest]
+    fn specificity() {
+        let exact = Glob::new("/usr/bin/ls").unwrap();
+        let prefix = Glob::new("/usr/bin/*").unwrap();
+        let any = Glob::new("/**").unwrap();
+        assert!(exact.specificity("/usr/bin/ls") > prefix.specificity("/usr/bin/ls"));
+        assert!(prefix.specificity("/usr/bin/ls") > any.specificity("/usr/bin/ls"));
+    }
+}
@@ -0,0 +1,458 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The AppArmor LSM.
+//!
+//! AppArmor confines programs with profiles. A profile is attached to the programs whose paths
+//! match its attachment pattern, and it restricts the files that a confined thread can access
+//! by path patterns, as well as the capabilities that it can use and the sockets that it can
+//! create. When a confined thread executes a program, the execution mode of the matching rule
+//! decides whether the thread keeps its profile, switches to another profile, or becomes
+//! unconfined.
+//!
+//! A profile is in either the enforce mode, where disallowed operations fail, or the complain
+//! mode, where they are allowed. Both modes report disallowed operations to the kernel log in
+//! the format of the Linux audit messages.
+//!
+//! Profiles are managed through the `apparmor` directory in the security file system.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/LSM/apparmor.html>.
+
+mod apparmorfs;
+mod glob;
+mod parser;
+mod policy;
+mod profile;
+
+use self::profile::{ExecMode, FilePerms, Profile, ProfileMode};
+use super::super::{
+    LsmFlags, LsmModule,
+    hooks::{
+        BprmContext, CapableContext, FileOpenContext, InodeSetattrContext, LsmAlienAccessHook,
+        LsmBprmHook, LsmCapabilityHook, LsmFileHook, LsmInodeHook, LsmMountHook, LsmPathHook,
+        LsmSocketHook, LsmTaskHook, MmapFileContext, PathCreateContext, PathLinkContext,
+        PathRemoveContext, PathRenameContext, PathTruncateContext, SocketCreateContext,
+    },
+};
+use crate::{
+    fs::vfs::path::Path, prelude::*, process::posix_thread::PosixThread, vm::perms::VmPerms,
+};
+
+pub(super) static APPARMOR_LSM: AppArmorLsm = AppArmorLsm;
+
+/// The AppArmor LSM.
+pub(super) struct AppArmorLsm;
+
+impl LsmModule for AppArmorLsm {
+    fn name(&self) -> &'static str {
+        "apparmor"
+    }
+
+    fn flags(&self) -> LsmFlags {
+        LsmFlags::LEGACY_MAJOR | LsmFlags::EXCLUSIVE
+    }
+
+    fn init(&self) {
+        apparmorfs::init();
+    }
+}
+
+impl LsmAlienAccessHook for AppArmorLsm {}
+
+impl LsmCapabilityHook for AppArmorLsm {
+    fn on_capable(&self, context: &CapableContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+
+        let cap = context.required_cap();
+        if profile.allows_cap(cap) {
+            return Ok(());
+        }
+
+        let number = cap.bits().trailing_zeros() as usize;
+        report(
+            &profile,
+            posix_thread,
+            "capable",
+            format_args!(
+                "capability={} capname=\"{}\"",
+                number,
+                parser::CAPABILITY_NAMES.get(number).unwrap_or(&"unknown")
+            ),
+            Errno::EPERM,
+        )
+    }
+}
+
+impl LsmPathHook for AppArmorLsm {
+    fn on_path_create(&self, context: &PathCreateContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+        let Some(name) = child_name_of(
+            posix_thread,
+            context.dir(),
+            context.name(),
+            context.type_().is_directory(),
+        ) else {
+            return Ok(());
+        };
+
+        check_file(&profile, posix_thread, "mknod", &name, FilePerms::WRITE)
+    }
+
+    fn on_path_remove(&self, context: &PathRemoveContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+        let Some(name) = name_of(posix_thread, context.victim()) else {
+            return Ok(());
+        };
+
+        check_file(&profile, posix_thread, "unlink", &name, FilePerms::WRITE)
+    }
+
+    fn on_path_link(&self, context: &PathLinkContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+        let Some(name) = child_name_of(posix_thread, context.new_dir(), context.new_name(), false)
+        else {
+            return Ok(());
+        };
+
+        check_file(&profile, posix_thread, "link", &name, FilePerms::LINK)
+    }
+
+    fn on_path_rename(&self, context: &PathRenameContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+
+        let old = context.old();
+        if let Some(old_name) = name_of(posix_thread, old) {
+            check_file(
+                &profile,
+                posix_thread,
+                "rename_src",
+                &old_name,
+                FilePerms::WRITE,
+            )?;
+        }
+        if let Some(new_name) = child_name_of(
+            posix_thread,
+            context.new_dir(),
+            context.new_name(),
+            old.type_().is_directory(),
+        ) {
+            check_file(
+                &profile,
+                posix_thread,
+                "rename_dest",
+                &new_name,
+                FilePerms::WRITE,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn on_path_truncate(&self, context: &PathTruncateContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+        let Some(name) = name_of(posix_thread, context.path()) else {
+            return Ok(());
+        };
+
+        check_file(&profile, posix_thread, "truncate", &name, FilePerms::WRITE)
+    }
+}
+
+impl LsmInodeHook for AppArmorLsm {
+    fn on_inode_setattr(&self, context: &InodeSetattrContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+        let Some(name) = name_of(posix_thread, context.path()) else {
+            return Ok(());
+        };
+
+        check_file(&profile, posix_thread, "setattr", &name, FilePerms::WRITE)
+    }
+}
+
+impl LsmFileHook for AppArmorLsm {
+    fn on_file_open(&self, context: &FileOpenContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let permission = context.permission();
+        // Executing a program requires only `x`, which is checked by `on_bprm_check`.
+        if permission.may_exec() {
+            return Ok(());
+        }
+
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+
+        let mut requested = FilePerms::empty();
+        if permission.may_read() {
+            requested |= FilePerms::READ;
+        }
+        if permission.may_write() {
+            requested |= FilePerms::WRITE;
+        }
+        if requested.is_empty() {
+            return Ok(());
+        }
+
+        let Some(name) = name_of(posix_thread, context.path()) else {
+            return Ok(());
+        };
+
+        check_file(&profile, posix_thread, "open", &name, requested)
+    }
+
+    fn on_mmap_file(&self, context: &MmapFileContext) -> Result<()> {
+        if !context.perms().contains(VmPerms::EXEC) {
+            return Ok(());
+        }
+
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+        let Some(name) = name_of(posix_thread, context.file().path()) else {
+            return Ok(());
+        };
+
+        check_file(&profile, posix_thread, "file_mmap", &name, FilePerms::MMAP)
+    }
+}
+
+impl LsmBprmHook for AppArmorLsm {
+    fn on_bprm_check(&self, context: &BprmContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        // Unconfined threads can execute any program.
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+        let Some(name) = name_of(posix_thread, context.path()) else {
+            return Ok(());
+        };
+
+        let Err(info) = exec_transition(&profile, &name) else {
+            return Ok(());
+        };
+        report(
+            &profile,
+            posix_thread,
+            "exec",
+            format_args!(
+                "name=\"{}\" info=\"{}\" requested_mask=\"x\" denied_mask=\"x\"",
+                name, info
+            ),
+            Errno::EACCES,
+        )
+    }
+
+    fn on_bprm_committed_creds(&self, context: &BprmContext) {
+        let posix_thread = context.posix_thread();
+        let Some(name) = name_of(posix_thread, context.path()) else {
+            return;
+        };
+
+        let next_profile = match profile_of(posix_thread) {
+            None => policy::find_attached(&name),
+            // If the execution is allowed only because the profile is in the complain mode,
+            // the thread stays in the profile.
+            Some(profile) => exec_transition(&profile, &name).unwrap_or(Some(profile)),
+        };
+
+        let credentials = posix_thread.credentials();
+        match next_profile {
+            Some(profile) => credentials.security().set(
+                &APPARMOR_LSM,
+                Arc::new(ProfileLabel {
+                    name: profile.name.clone(),
+                }),
+            ),
+            None => credentials.security().remove(&APPARMOR_LSM),
+        }
+    }
+}
+
+impl LsmSocketHook for AppArmorLsm {
+    fn on_socket_create(&self, context: &SocketCreateContext) -> Result<()> {
+        let posix_thread = context.posix_thread();
+        let Some(profile) = profile_of(posix_thread) else {
+            return Ok(());
+        };
+
+        let (family, type_) = (context.family(), context.type_());
+        if profile.allows_socket(family, type_) {
+            return Ok(());
+        }
+
+        report(
+            &profile,
+            posix_thread,
+            "create",
+            format_args!("family={:?} sock_type={:?}", family, type_),
+            Errno::EACCES,
+        )
+    }
+}
+
+impl LsmTaskHook for AppArmorLsm {}
+
+impl LsmMountHook for AppArmorLsm {}
+
+/// The label of a confined thread, which is stored in the security blob of its credentials.
+///
+/// The label refers to the profile by name, so replacing the profile also takes effect on the
+/// thread, and removing the profile unconfines the thread.
+struct ProfileLabel {
+    name: String,
+}
+
+/// Returns the profile that confines the thread, if any.
+fn profile_of(posix_thread: &PosixThread) -> Option<Arc<Profile>> {
+    let label = posix_thread
+        .credentials()
+        .security()
+        .get::<ProfileLabel>(&APPARMOR_LSM)?;
+    policy::find(&label.name)
+}
+
+/// Returns the name of the path that the profiles match against.
+///
+/// The names of directories end with `/`. Pseudo paths (e.g., those of pipes and sockets) have
+/// no names and are not mediated.
+fn name_of(posix_thread: &PosixThread, path: &Path) -> Option<String> {
+    if path.is_pseudo() {
+        return None;
+    }
+
+    let mut name = posix_thread
+        .read_fs()
+        .resolver()
+        .read()
+        .make_abs_path(path)
+        .into_string();
+    if path.type_().is_directory() && !name.ends_with('/') {
+        name.push('/');
+    }
+    Some(name)
+}
+
+/// Returns the name of the path of a new entry in a directory.
+fn child_name_of(
+    posix_thread: &PosixThread,
+    dir: &Path,
+    child: &str,
+    is_dir: bool,
+) -> Option<String> {
+    let mut name = name_of(posix_thread, dir)?;
+    name.push_str(child);
+    if is_dir {
+        name.push('/');
+    }
+    Some(name)
+}
+
+/// Returns the profile that confines the thread after it executes the program.
+///
+/// If the profile does not allow executing the program, the reason is returned as an error.
+fn exec_transition(
+    profile: &Arc<Profile>,
+    program: &str,
+) -> core::result::Result<Option<Arc<Profile>>, &'static str> {
+    if !profile.file_perms(program).contains(FilePerms::EXEC) {
+        return Err("no exec permission");
+    }
+
+    match profile.exec_mode(program) {
+        Some(ExecMode::Inherit) => Ok(Some(profile.clone())),
+        Some(ExecMode::Profile(Some(target))) => policy::find(target)
+            .map(Some)
+            .ok_or("target profile not found"),
+        Some(ExecMode::Profile(None)) => policy::find_attached(program)
+            .map(Some)
+            .ok_or("profile not found"),
+        Some(ExecMode::Unconfined) => Ok(None),
+        None => Err("no exec mode"),
+    }
+}
+
+/// Checks whether the profile grants the permissions on the file.
+fn check_file(
+    profile: &Profile,
+    posix_thread: &PosixThread,
+    operation: &str,
+    name: &str,
+    requested: FilePerms,
+) -> Result<()> {
+    let denied = requested - profile.file_perms(name);
+    if denied.is_empty() {
+        return Ok(());
+    }
+
+    report(
+        profile,
+        posix_thread,
+        operation,
+        format_args!(
+            "name=\"{}\" requested_mask=\"{}\" denied_mask=\"{}\"",
+            name, requested, denied
+        ),
+        Errno::EACCES,
+    )
+}
+
+/// Reports an operation that the profile does not allow.
+///
+/// In the enforce mode, the operation fails with `errno`. In the complain mode, the operation
+/// is allowed.
+fn report(
+    profile: &Profile,
+    posix_thread: &PosixThread,
+    operation: &str,
+    details: core::fmt::Arguments<'_>,
+    errno: Errno,
+) -> Result<()> {
+    let thread_name = posix_thread.thread_name().lock();
+    let comm = thread_name.name().to_string_lossy();
+
+    match profile.mode {
+        ProfileMode::Enforce => {
+            warn!(
+                "apparmor=\"DENIED\" operation=\"{}\" profile=\"{}\" {} pid={} comm=\"{}\"",
+                operation,
+                profile.name,
+                details,
+                posix_thread.tid(),
+                comm
+            );
+            return_errno_with_message!(errno, "the operation is denied by the AppArmor profile");
+        }
+        ProfileMode::Complain => {
+            notice!(
+                "apparmor=\"ALLOWED\" operation=\"{}\" profile=\"{}\" {} pid={} comm=\"{}\"",
+                operation,
+                profile.name,
+                details,
+                posix_thread.tid(),
+                comm
+            );
+            Ok(())
+        }
+    }
+}
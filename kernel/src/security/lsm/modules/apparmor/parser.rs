@@ -0,0 +1,470 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The parser of the textual profile language.
+//!
+//! A policy consists of one or more profiles:
+//!
+//! ```text
+//! # Comments start with `#`.
+//! profile NAME [ATTACHMENT] [flags=(complain|enforce)] {
+//!     capability [NAME ...],
+//!     network [FAMILY] [TYPE],
+//!     [deny] PATH_GLOB PERMS [-> TARGET_PROFILE],
+//! }
+//!
+//! ATTACHMENT [flags=(complain|enforce)] {
+//!     ...
+//! }
+//! ```
+//!
+//! A profile named after an absolute path glob is attached to the programs that match it.
+//!
+//! The file permissions are `r` (read), `w` or `a` (write), `m` (executable mapping), `l`
+//! (link), and the execution modes `ix` (inherit), `px` or `Px` (switch profile), and `ux`
+//! or `Ux` (unconfined). A bare `x` is only accepted in `deny` rules.
+//!
+//! Unlike Linux, where `apparmor_parser` compiles the profiles in user space, the kernel
+//! parses the textual profiles directly.
+
+use super::{
+    glob::Glob,
+    profile::{ExecMode, FilePerms, FileRule, NetworkRule, Profile, ProfileMode},
+};
+use crate::{
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    util::net::{CSocketAddrFamily, SockType},
+};
+
+/// Parses the profiles in the policy text.
+pub(super) fn parse_profiles(text: &str) -> Result<Vec<Profile>> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+    };
+
+    let mut profiles = Vec::new();
+    while parser.peek().is_some() {
+        profiles.push(parser.parse_profile()?);
+    }
+    if profiles.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "the policy contains no profiles");
+    }
+
+    Ok(profiles)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Word(&'a str),
+    /// `,`
+    Comma,
+    /// `{`
+    Open,
+    /// `}`
+    Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token<'_>>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            c if c.is_ascii_whitespace() => i += 1,
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            b'{' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            b'}' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            _ => {
+                // Braces and parentheses inside a word (e.g., `/usr/{bin,lib}/*` and
+                // `flags=(complain)`) belong to the word.
+                let start = i;
+                let mut brace_depth = 0usize;
+                let mut paren_depth = 0usize;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'{' => brace_depth += 1,
+                        b'}' if brace_depth > 0 => brace_depth -= 1,
+                        b'(' => paren_depth += 1,
+                        b')' if paren_depth > 0 => paren_depth -= 1,
+                        b',' if brace_depth > 0 || paren_depth > 0 => {}
+                        c if c.is_ascii_whitespace() && paren_depth > 0 => {}
+                        b',' | b'}' => break,
+                        c if c.is_ascii_whitespace() => break,
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                if brace_depth > 0 || paren_depth > 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the policy has an unclosed group");
+                }
+                tokens.push(Token::Word(&text[start..i]));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a, 'b> {
+    tokens: &'b [Token<'a>],
+    pos: usize,
+}
+
+impl<'a> Parser<'a, '_> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek()?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn next_word(&mut self) -> Option<&'a str> {
+        match self.peek()? {
+            Token::Word(word) => {
+                self.pos += 1;
+                Some(word)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_profile(&mut self) -> Result<Profile> {
+        let (name, attachment) = match self.next_word() {
+            Some("profile") => {
+                let Some(name) = self.next_word() else {
+                    return_errno_with_message!(Errno::EINVAL, "the profile has no name");
+                };
+                let attachment = match self.peek() {
+                    Some(Token::Word(word)) if word.starts_with('/') => {
+                        self.pos += 1;
+                        Some(Glob::new(word)?)
+                    }
+                    _ => None,
+                };
+                (name, attachment)
+            }
+            Some(word) if word.starts_with('/') => (word, Some(Glob::new(word)?)),
+            _ => return_errno_with_message!(Errno::EINVAL, "a profile header is expected"),
+        };
+
+        let mode = match self.next_word() {
+            None => ProfileMode::Enforce,
+            Some(flags) => parse_flags(flags)?,
+        };
+
+        if self.next() != Some(Token::Open) {
+            return_errno_with_message!(Errno::EINVAL, "the profile has no body");
+        }
+
+        let mut profile = Profile::new(name.to_string(), attachment, mode);
+        loop {
+            let mut words = Vec::new();
+            loop {
+                match self.next() {
+                    Some(Token::Word(word)) => words.push(word),
+                    Some(Token::Comma) => break,
+                    Some(Token::Close) if words.is_empty() => return Ok(profile),
+                    Some(Token::Close) => {
+                        return_errno_with_message!(Errno::EINVAL, "the rule is not terminated")
+                    }
+                    Some(Token::Open) | None => {
+                        return_errno_with_message!(Errno::EINVAL, "the profile is not closed")
+                    }
+                }
+            }
+            parse_rule(&words, &mut profile)?;
+        }
+    }
+}
+
+fn parse_flags(flags: &str) -> Result<ProfileMode> {
+    let Some(flags) = flags
+        .strip_prefix("flags=(")
+        .and_then(|flags| flags.strip_suffix(')'))
+    else {
+        return_errno_with_message!(Errno::EINVAL, "the profile flags are invalid");
+    };
+
+    let mut mode = ProfileMode::Enforce;
+    for flag in flags
+        .split(|c: char| c == ',' || c.is_ascii_whitespace())
+        .filter(|flag| !flag.is_empty())
+    {
+        mode = match flag {
+            "enforce" => ProfileMode::Enforce,
+            "complain" => ProfileMode::Complain,
+            _ => return_errno_with_message!(Errno::EINVAL, "the profile flag is unknown"),
+        };
+    }
+
+    Ok(mode)
+}
+
+fn parse_rule(words: &[&str], profile: &mut Profile) -> Result<()> {
+    match words {
+        [] => return_errno_with_message!(Errno::EINVAL, "the rule is empty"),
+        ["capability"] => profile.caps = CapSet::all(),
+        ["capability", names @ ..] => {
+            for name in names {
+                profile.caps |= parse_capability(name)?;
+            }
+        }
+        ["network", args @ ..] => profile.network_rules.push(parse_network_rule(args)?),
+        ["deny", rule @ ..] => profile.file_rules.push(parse_file_rule(rule, true)?),
+        rule => profile.file_rules.push(parse_file_rule(rule, false)?),
+    }
+
+    Ok(())
+}
+
+/// The capability names, indexed by the capability numbers.
+pub(super) static CAPABILITY_NAMES: [&str; 41] = [
+    "chown",
+    "dac_override",
+    "dac_read_search",
+    "fowner",
+    "fsetid",
+    "kill",
+    "setgid",
+    "setuid",
+    "setpcap",
+    "linux_immutable",
+    "net_bind_service",
+    "net_broadcast",
+    "net_admin",
+    "net_raw",
+    "ipc_lock",
+    "ipc_owner",
+    "sys_module",
+    "sys_rawio",
+    "sys_chroot",
+    "sys_ptrace",
+    "sys_pacct",
+    "sys_admin",
+    "sys_boot",
+    "sys_nice",
+    "sys_resource",
+    "sys_time",
+    "sys_tty_config",
+    "mknod",
+    "lease",
+    "audit_write",
+    "audit_control",
+    "setfcap",
+    "mac_override",
+    "mac_admin",
+    "syslog",
+    "wake_alarm",
+    "block_suspend",
+    "audit_read",
+    "perfmon",
+    "bpf",
+    "checkpoint_restore",
+];
+
+fn parse_capability(name: &str) -> Result<CapSet> {
+    CAPABILITY_NAMES
+        .iter()
+        .position(|cap_name| *cap_name == name)
+        .and_then(|number| CapSet::from_capability_number(number as u64))
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the capability is unknown"))
+}
+
+fn parse_network_rule(args: &[&str]) -> Result<NetworkRule> {
+    let mut rule = NetworkRule {
+        family: None,
+        type_: None,
+    };
+
+    let mut args = args.iter();
+    let mut arg = args.next();
+    if let Some(family) = arg.and_then(|name| parse_family(name)) {
+        rule.family = Some(family);
+        arg = args.next();
+    }
+    if let Some(name) = arg {
+        let Some(type_) = parse_sock_type(name) else {
+            return_errno_with_message!(Errno::EINVAL, "the network rule is invalid");
+        };
+        rule.type_ = Some(type_);
+    }
+    if args.next().is_some() {
+        return_errno_with_message!(Errno::EINVAL, "the network rule has too many arguments");
+    }
+
+    Ok(rule)
+}
+
+fn parse_family(name: &str) -> Option<CSocketAddrFamily> {
+    let family = match name {
+        "unix" => CSocketAddrFamily::AF_UNIX,
+        "inet" => CSocketAddrFamily::AF_INET,
+        "inet6" => CSocketAddrFamily::AF_INET6,
+        "netlink" => CSocketAddrFamily::AF_NETLINK,
+        "packet" => CSocketAddrFamily::AF_PACKET,
+        "vsock" => CSocketAddrFamily::AF_VSOCK,
+        _ => return None,
+    };
+    Some(family)
+}
+
+fn parse_sock_type(name: &str) -> Option<SockType> {
+    let type_ = match name {
+        "stream" => SockType::SOCK_STREAM,
+        "dgram" => SockType::SOCK_DGRAM,
+        "raw" => SockType::SOCK_RAW,
+        "rdm" => SockType::SOCK_RDM,
+        "seqpacket" => SockType::SOCK_SEQPACKET,
+        _ => return None,
+    };
+    Some(type_)
+}
+
+fn parse_file_rule(words: &[&str], deny: bool) -> Result<FileRule> {
+    let (pattern, perms, target) = match words {
+        [pattern, perms] => (pattern, perms, None),
+        [pattern, perms, "->", target] => (pattern, perms, Some(*target)),
+        _ => return_errno_with_message!(Errno::EINVAL, "the rule is invalid"),
+    };
+
+    let glob = Glob::new(pattern)?;
+    let (perms, mut exec_mode) = parse_file_perms(perms, deny)?;
+    if let Some(target) = target {
+        let Some(ExecMode::Profile(name)) = exec_mode.as_mut() else {
+            return_errno_with_message!(Errno::EINVAL, "only `px` rules can name a target");
+        };
+        *name = Some(target.to_string());
+    }
+
+    Ok(FileRule {
+        glob,
+        perms,
+        exec_mode,
+        deny,
+    })
+}
+
+fn parse_file_perms(perms: &str, deny: bool) -> Result<(FilePerms, Option<ExecMode>)> {
+    let mut file_perms = FilePerms::empty();
+    let mut exec_mode = None;
+
+    let mut chars = perms.chars().peekable();
+    while let Some(c) = chars.next() {
+        let perm = match c {
+            'r' => FilePerms::READ,
+            'w' | 'a' => FilePerms::WRITE,
+            'm' => FilePerms::MMAP,
+            'l' => FilePerms::LINK,
+            'x' if deny => FilePerms::EXEC,
+            'i' | 'p' | 'P' | 'u' | 'U' if chars.next_if_eq(&'x').is_some() => {
+                if deny || exec_mode.is_some() {
+                    return_errno_with_message!(Errno::EINVAL, "the execution mode is invalid");
+                }
+                exec_mode = Some(match c {
+                    'i' => ExecMode::Inherit,
+                    'p' | 'P' => ExecMode::Profile(None),
+                    _ => ExecMode::Unconfined,
+                });
+                FilePerms::EXEC
+            }
+            _ => return_errno_with_message!(Errno::EINVAL, "the file permission is invalid"),
+        };
+        file_perms |= perm;
+    }
+
+    if file_perms.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "the file permissions are empty");
+    }
+
+    Ok((file_perms, exec_mode))
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::{super::profile::*, parse_profiles};
+    use crate::{
+        process::credentials::capabilities::CapSet,
+        util::net::{CSocketAddrFamily, SockType},
+    };
+
+    const POLICY: &str = "
+        # A confined shell.
+        /bin/sh flags=(complain) {
+            /etc/** r,
+            /tmp/{a,b}/* rw,
+            deny /etc/shadow r,
+            /usr/lib/** rm,
+            /usr/bin/* ix,
+            /usr/sbin/* px -> helper,
+            capability net_raw setuid,
+            network inet stream,
+        }
+
+        profile helper {
+            network,
+        }
+    ";
+
+    #[ktest]
+    fn parse_policy() {
+        let profiles = parse_profiles(POLICY).unwrap();
+        assert_eq!(profiles.len(), 2);
+
+        let shell = &profiles[0];
+        assert_eq!(shell.name, "/bin/sh");
+        assert!(shell.attachment.as_ref().unwrap().matches("/bin/sh"));
+        assert_eq!(shell.mode, ProfileMode::Complain);
+        assert_eq!(shell.file_perms("/etc/passwd"), FilePerms::READ);
+        assert_eq!(shell.file_perms("/etc/shadow"), FilePerms::empty());
+        assert_eq!(
+            shell.file_perms("/tmp/b/file"),
+            FilePerms::READ | FilePerms::WRITE
+        );
+        assert_eq!(shell.exec_mode("/usr/bin/ls"), Some(&ExecMode::Inherit));
+        assert_eq!(
+            shell.exec_mode("/usr/sbin/helper"),
+            Some(&ExecMode::Profile(Some("helper".to_string())))
+        );
+        assert!(shell.allows_cap(CapSet::NET_RAW | CapSet::SETUID));
+        assert!(!shell.allows_cap(CapSet::SYS_ADMIN));
+        assert!(shell.allows_socket(CSocketAddrFamily::AF_INET, SockType::SOCK_STREAM));
+        assert!(!shell.allows_socket(CSocketAddrFamily::AF_INET, SockType::SOCK_DGRAM));
+
+        let helper = &profiles[1];
+        assert_eq!(helper.name, "helper");
+        assert!(helper.attachment.is_none());
+        assert_eq!(helper.mode, ProfileMode::Enforce);
+        assert!(helper.allows_socket(CSocketAddrFamily::AF_UNIX, SockType::SOCK_DGRAM));
+    }
+
+    #[ktest]
+    fn reject_invalid_policy() {
+        assert!(parse_profiles("").is_err());
+        assert!(parse_profiles("profile p { /etc/passwd r }").is_err());
+        assert!(parse_profiles("profile p { /etc/passwd x, }").is_err());
+        assert!(parse_profiles("profile p { /etc/passwd r -> q, }").is_err());
+        assert!(parse_profiles("profile p { capability fly, }").is_err());
+        assert!(parse_profiles("profile p { /etc/passwd r,").is_err());
+    }
+}
@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::profile::{Profile, ProfileMode};
+use crate::prelude::*;
+
+/// The loaded profiles, indexed by their names.
+static PROFILES: RwLock<BTreeMap<String, Arc<Profile>>> = RwLock::new(BTreeMap::new());
+
+/// Loads the profiles.
+///
+/// If `replace` is false, the profiles must not have been loaded. Otherwise, the loaded
+/// profiles with the same names are replaced, which also takes effect on the threads that are
+/// confined by them.
+///
+/// Either all or none of the profiles are loaded.
+pub(super) fn load(profiles: Vec<Profile>, replace: bool) -> Result<()> {
+    let mut loaded = PROFILES.write();
+
+    for (i, profile) in profiles.iter().enumerate() {
+        if (!replace && loaded.contains_key(&profile.name))
+            || profiles[..i].iter().any(|other| other.name == profile.name)
+        {
+            return_errno_with_message!(Errno::EEXIST, "the profile already exists");
+        }
+    }
+
+    let operation = if replace {
+        "profile_replace"
+    } else {
+        "profile_load"
+    };
+    for profile in profiles {
+        info!(
+            "apparmor=\"STATUS\" operation=\"{}\" name=\"{}\"",
+            operation, profile.name
+        );
+        loaded.insert(profile.name.clone(), Arc::new(profile));
+    }
+
+    Ok(())
+}
+
+/// Removes the profile.
+///
+/// The threads that are confined by the profile become unconfined.
+pub(super) fn remove(name: &str) -> Result<()> {
+    if PROFILES.write().remove(name).is_none() {
+        return_errno_with_message!(Errno::ENOENT, "the profile does not exist");
+    }
+
+    info!(
+        "apparmor=\"STATUS\" operation=\"profile_remove\" name=\"{}\"",
+        name
+    );
+
+    Ok(())
+}
+
+/// Finds the profile by its name.
+pub(super) fn find(name: &str) -> Option<Arc<Profile>> {
+    PROFILES.read().get(name).cloned()
+}
+
+/// Finds the profile that is attached to the program.
+///
+/// If multiple profiles are attached to the program, the most specific one is returned.
+pub(super) fn find_attached(path: &str) -> Option<Arc<Profile>> {
+    PROFILES
+        .read()
+        .values()
+        .filter_map(|profile| {
+            let attachment = profile.attachment.as_ref()?;
+            attachment
+                .matches(path)
+                .then(|| (attachment.specificity(path), profile))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, profile)| profile.clone())
+}
+
+/// Returns the names and the modes of the loaded profiles.
+pub(super) fn list() -> Vec<(String, ProfileMode)> {
+    PROFILES
+        .read()
+        .values()
+        .map(|profile| (profile.name.clone(), profile.mode))
+        .collect()
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Display;
+
+use super::glob::Glob;
+use crate::{
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    util::net::{CSocketAddrFamily, SockType},
+};
+
+/// A profile, which confines the threads that run a program.
+#[derive(Debug)]
+pub(super) struct Profile {
+    pub(super) name: String,
+    /// The pattern of the programs that are confined by the profile when executed.
+    pub(super) attachment: Option<Glob>,
+    pub(super) mode: ProfileMode,
+    pub(super) file_rules: Vec<FileRule>,
+    pub(super) caps: CapSet,
+    pub(super) network_rules: Vec<NetworkRule>,
+}
+
+/// The enforcement mode of a profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ProfileMode {
+    /// Denies the operations that are not allowed by the profile and reports them.
+    Enforce,
+    /// Allows the operations that are not allowed by the profile but reports them.
+    Complain,
+}
+
+impl Display for ProfileMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Enforce => f.write_str("enforce"),
+            Self::Complain => f.write_str("complain"),
+        }
+    }
+}
+
+bitflags! {
+    /// The permissions that a profile grants on files.
+    pub(super) struct FilePerms: u8 {
+        /// `r`: Reads a file or lists a directory.
+        const READ = 1 << 0;
+        /// `w`: Writes to, creates, removes, renames, or truncates a file, or changes its
+        /// attributes.
+        const WRITE = 1 << 1;
+        /// `x`: Executes a file.
+        const EXEC = 1 << 2;
+        /// `m`: Maps a file into memory as executable.
+        const MMAP = 1 << 3;
+        /// `l`: Creates a hard link.
+        const LINK = 1 << 4;
+    }
+}
+
+impl Display for FilePerms {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        const LETTERS: [(FilePerms, char); 5] = [
+            (FilePerms::READ, 'r'),
+            (FilePerms::WRITE, 'w'),
+            (FilePerms::EXEC, 'x'),
+            (FilePerms::MMAP, 'm'),
+            (FilePerms::LINK, 'l'),
+        ];
+
+        for (perm, letter) in LETTERS {
+            if self.contains(perm) {
+                write!(f, "{}", letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How a profile changes when a program is executed.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum ExecMode {
+    /// `ix`: Keeps the current profile.
+    Inherit,
+    /// `px`: Switches to the named profile, or to the profile attached to the program.
+    Profile(Option<String>),
+    /// `ux`: Runs the program unconfined.
+    Unconfined,
+}
+
+/// A rule that grants or denies permissions on the files that match a pattern.
+#[derive(Debug)]
+pub(super) struct FileRule {
+    pub(super) glob: Glob,
+    pub(super) perms: FilePerms,
+    /// The execution mode, which is present only if the rule grants `x`.
+    pub(super) exec_mode: Option<ExecMode>,
+    pub(super) deny: bool,
+}
+
+/// A rule that allows creating sockets.
+///
+/// A missing family or type matches any family or type.
+#[derive(Debug)]
+pub(super) struct NetworkRule {
+    pub(super) family: Option<CSocketAddrFamily>,
+    pub(super) type_: Option<SockType>,
+}
+
+impl Profile {
+    /// Creates a profile without any rules, which denies everything.
+    pub(super) fn new(name: String, attachment: Option<Glob>, mode: ProfileMode) -> Self {
+        Self {
+            name,
+            attachment,
+            mode,
+            file_rules: Vec::new(),
+            caps: CapSet::empty(),
+            network_rules: Vec::new(),
+        }
+    }
+
+    /// Returns the permissions that the profile grants on the file.
+    ///
+    /// The permissions granted by all matching rules are combined, and then the permissions
+    /// denied by any matching `deny` rule are removed.
+    pub(super) fn file_perms(&self, path: &str) -> FilePerms {
+        let mut allowed = FilePerms::empty();
+        let mut denied = FilePerms::empty();
+        for rule in self.file_rules.iter() {
+            if !rule.glob.matches(path) {
+                continue;
+            }
+            if rule.deny {
+                denied |= rule.perms;
+            } else {
+                allowed |= rule.perms;
+            }
+        }
+
+        allowed - denied
+    }
+
+    /// Returns the execution mode of the program.
+    ///
+    /// If multiple rules grant `x` on the program, the first one is used.
+    pub(super) fn exec_mode(&self, path: &str) -> Option<&ExecMode> {
+        self.file_rules
+            .iter()
+            .filter(|rule| !rule.deny && rule.glob.matches(path))
+            .find_map(|rule| rule.exec_mode.as_ref())
+    }
+
+    /// Returns whether the profile allows using the capability.
+    pub(super) fn allows_cap(&self, cap: CapSet) -> bool {
+        self.caps.contains(cap)
+    }
+
+    /// Returns whether the profile allows creating a socket of the family and type.
+    pub(super) fn allows_socket(&self, family: CSocketAddrFamily, type_: SockType) -> bool {
+        self.network_rules.iter().any(|rule| {
+            rule.family.is_none_or(|rule_family| rule_family == family)
+                && rule
+                    .type_
+                    .is_none_or(|rule_type| rule_type as i32 == type_ as i32)
+        })
+    }
+}
@@ -2,11 +2,18 @@
 
 use super::super::{
     LsmFlags, LsmModule,
-    hooks::{AlienAccessContext, CapableContext, LsmAlienAccessHook, LsmCapabilityHook},
+    hooks::{
+        AlienAccessContext, CapableContext, InodeXattrContext, LsmAlienAccessHook, LsmBprmHook,
+        LsmCapabilityHook, LsmFileHook, LsmInodeHook, LsmMountHook, LsmPathHook, LsmSocketHook,
+        LsmTaskHook, TaskSetniceContext,
+    },
 };
 use crate::{
     prelude::*,
-    process::{credentials::capabilities::CapSet, posix_thread::alien_access::CredsSource},
+    process::{
+        credentials::{capabilities::CapSet, file_capabilities::XATTR_NAME_CAPS},
+        posix_thread::{AsPosixThread, alien_access::CredsSource},
+    },
 };
 
 pub(super) static CAPABILITY_LSM: CapabilityLsm = CapabilityLsm;
@@ -26,10 +33,10 @@ impl LsmModule for CapabilityLsm {
 
 impl LsmCapabilityHook for CapabilityLsm {
     fn on_capable(&self, context: &CapableContext) -> Result<()> {
-        // Since creating new user namespaces is not supported at the moment,
-        // there is effectively only one user namespace in the entire system.
-        // Therefore, the thread has a single set of capabilities used for permission checks.
-        // FIXME: Once support for creating new user namespaces is added,
+        // Creating a user namespace does not grant the creator any capabilities in it (see
+        // `UserNamespace::new_child`). Therefore, the thread has a single set of capabilities
+        // used for permission checks.
+        // FIXME: Once threads can gain capabilities in non-initial user namespaces,
         // we should verify the thread's capabilities within the relevant user namespace.
         if context
             .posix_thread()
@@ -81,3 +88,64 @@ impl LsmAlienAccessHook for CapabilityLsm {
         );
     }
 }
+
+impl LsmPathHook for CapabilityLsm {}
+
+impl LsmInodeHook for CapabilityLsm {
+    fn on_inode_setxattr(&self, context: &InodeXattrContext) -> Result<()> {
+        self.check_xattr_caps_modification(context)
+    }
+
+    fn on_inode_removexattr(&self, context: &InodeXattrContext) -> Result<()> {
+        self.check_xattr_caps_modification(context)
+    }
+}
+
+impl LsmFileHook for CapabilityLsm {}
+
+impl LsmBprmHook for CapabilityLsm {}
+
+impl LsmSocketHook for CapabilityLsm {}
+
+impl LsmTaskHook for CapabilityLsm {
+    fn on_task_setnice(&self, context: &TaskSetniceContext) -> Result<()> {
+        let target_main_thread = context.target().main_thread();
+        let Some(target_posix_thread) = target_main_thread.as_posix_thread() else {
+            return Ok(());
+        };
+
+        // A thread may change the nice value of a process without `CAP_SYS_NICE` only if the
+        // process does not have more privileges than the thread.
+        let target_permitted = target_posix_thread.credentials().permitted_capset();
+        let permitted = context.posix_thread().credentials().permitted_capset();
+        if permitted.contains(target_permitted) {
+            return Ok(());
+        }
+
+        let target_user_ns = context.target().user_ns().lock();
+        self.on_capable(&CapableContext::new(
+            target_user_ns.as_ref(),
+            context.posix_thread(),
+            CapSet::SYS_NICE,
+        ))
+    }
+}
+
+impl LsmMountHook for CapabilityLsm {}
+
+impl CapabilityLsm {
+    /// Checks whether the file capabilities may be modified if the xattr stores them.
+    fn check_xattr_caps_modification(&self, context: &InodeXattrContext) -> Result<()> {
+        if context.name() != XATTR_NAME_CAPS {
+            return Ok(());
+        }
+
+        let posix_thread = context.posix_thread();
+        let user_ns = posix_thread.process().user_ns().lock();
+        self.on_capable(&CapableContext::new(
+            user_ns.as_ref(),
+            posix_thread,
+            CapSet::SETFCAP,
+        ))
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{fs::file::InodeType, prelude::*};
+
+bitflags! {
+    /// Filesystem actions that a Landlock ruleset can restrict.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16/source/include/uapi/linux/landlock.h>.
+    pub struct AccessFs: u64 {
+        /// Executes a file.
+        const EXECUTE = 1 << 0;
+        /// Opens a file with write access.
+        const WRITE_FILE = 1 << 1;
+        /// Opens a file with read access.
+        const READ_FILE = 1 << 2;
+        /// Opens a directory or lists its content.
+        const READ_DIR = 1 << 3;
+        /// Removes an empty directory or renames one.
+        const REMOVE_DIR = 1 << 4;
+        /// Unlinks or renames a file.
+        const REMOVE_FILE = 1 << 5;
+        /// Creates, renames, or links a character device.
+        const MAKE_CHAR = 1 << 6;
+        /// Creates or renames a directory.
+        const MAKE_DIR = 1 << 7;
+        /// Creates, renames, or links a regular file.
+        const MAKE_REG = 1 << 8;
+        /// Creates, renames, or links a UNIX domain socket.
+        const MAKE_SOCK = 1 << 9;
+        /// Creates, renames, or links a named pipe.
+        const MAKE_FIFO = 1 << 10;
+        /// Creates, renames, or links a block device.
+        const MAKE_BLOCK = 1 << 11;
+        /// Creates, renames, or links a symbolic link.
+        const MAKE_SYM = 1 << 12;
+        /// Links or renames a file from or to a different directory.
+        const REFER = 1 << 13;
+        /// Truncates a file.
+        const TRUNCATE = 1 << 14;
+    }
+}
+
+impl AccessFs {
+    /// The rights that can be granted on a non-directory file.
+    ///
+    /// The other rights only make sense on directories, as they apply to the files beneath.
+    pub const FILE: Self = Self::EXECUTE
+        .union(Self::WRITE_FILE)
+        .union(Self::READ_FILE)
+        .union(Self::TRUNCATE);
+
+    /// Returns the right to create a file of the given type.
+    pub fn make(type_: InodeType) -> Self {
+        match type_ {
+            InodeType::Dir => Self::MAKE_DIR,
+            InodeType::SymLink => Self::MAKE_SYM,
+            InodeType::CharDevice => Self::MAKE_CHAR,
+            InodeType::BlockDevice => Self::MAKE_BLOCK,
+            InodeType::NamedPipe => Self::MAKE_FIFO,
+            InodeType::Socket => Self::MAKE_SOCK,
+            _ => Self::MAKE_REG,
+        }
+    }
+
+    /// Returns the right to remove a file of the given type.
+    pub fn remove(type_: InodeType) -> Self {
+        if type_ == InodeType::Dir {
+            Self::REMOVE_DIR
+        } else {
+            Self::REMOVE_FILE
+        }
+    }
+}
+
+bitflags! {
+    /// Network actions that a Landlock ruleset can restrict.
+    pub struct AccessNet: u64 {
+        /// Binds a TCP socket to a local port.
+        const BIND_TCP = 1 << 0;
+        /// Connects a TCP socket to a remote port.
+        const CONNECT_TCP = 1 << 1;
+    }
+}
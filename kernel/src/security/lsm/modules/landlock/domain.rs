@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    access::{AccessFs, AccessNet},
+    ruleset::{Layer, Ruleset},
+};
+use crate::{fs::vfs::path::Path, prelude::*};
+
+/// The maximum number of layers in a domain.
+const MAX_NUM_LAYERS: usize = 16;
+
+/// A stack of enforced Landlock rulesets.
+///
+/// Each call to `landlock_restrict_self` creates a new domain that nests the current one with
+/// an additional layer. An action is allowed only if every layer allows it, so a nested domain
+/// can never be less restrictive than its parent.
+pub(super) struct LandlockDomain {
+    layers: Vec<Arc<Layer>>,
+}
+
+impl Debug for LandlockDomain {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LandlockDomain")
+            .field("num_layers", &self.layers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl LandlockDomain {
+    /// Creates a domain that enforces `ruleset` on top of `parent`.
+    pub(super) fn new_nested(parent: Option<&LandlockDomain>, ruleset: &Ruleset) -> Result<Self> {
+        let mut layers = parent.map_or_else(Vec::new, |parent| parent.layers.clone());
+        if layers.len() >= MAX_NUM_LAYERS {
+            return_errno_with_message!(Errno::E2BIG, "the domain has too many layers");
+        }
+        layers.push(Arc::new(ruleset.to_layer()));
+
+        Ok(Self { layers })
+    }
+
+    /// Returns whether this domain is the same as or an ancestor of `other`.
+    ///
+    /// A thread in this domain is then at most as restricted as a thread in `other`.
+    pub(super) fn is_equal_or_ancestor_of(&self, other: &LandlockDomain) -> bool {
+        self.layers.len() <= other.layers.len()
+            && self
+                .layers
+                .iter()
+                .zip(other.layers.iter())
+                .all(|(layer, other_layer)| Arc::ptr_eq(layer, other_layer))
+    }
+
+    /// Checks whether every layer allows the filesystem actions on `path`.
+    pub(super) fn check_fs_access(&self, path: &Path, access: AccessFs) -> Result<()> {
+        if path.is_pseudo() {
+            return Ok(());
+        }
+
+        let granted_access = self.collect_fs_access(path, access);
+        if granted_access
+            .iter()
+            .all(|granted| granted.contains(access))
+        {
+            return Ok(());
+        }
+
+        return_errno_with_message!(Errno::EACCES, "the file access is denied by Landlock");
+    }
+
+    /// Checks whether every layer allows moving `old` from `old_dir` to `new_dir`.
+    ///
+    /// Moving a file to another directory requires the `REFER` right on both directories. The
+    /// file must also not gain any rights by the move, i.e., for each layer, the rights granted
+    /// beneath `new_dir` must be a subset of those granted beneath `old_dir`.
+    pub(super) fn check_refer(&self, old: &Path, old_dir: &Path, new_dir: &Path) -> Result<()> {
+        if old_dir == new_dir || old.is_pseudo() {
+            return Ok(());
+        }
+
+        let relevant_access = if old.type_().is_directory() {
+            AccessFs::all()
+        } else {
+            AccessFs::FILE
+        };
+
+        let old_access = self.collect_fs_access(old_dir, AccessFs::all());
+        let new_access = self.collect_fs_access(new_dir, AccessFs::all());
+        let is_allowed = old_access.iter().zip(new_access.iter()).all(|(old, new)| {
+            old.contains(AccessFs::REFER)
+                && new.contains(AccessFs::REFER)
+                && old.contains(*new & relevant_access)
+        });
+        if is_allowed {
+            return Ok(());
+        }
+
+        return_errno_with_message!(
+            Errno::EXDEV,
+            "moving the file to another directory is denied by Landlock"
+        );
+    }
+
+    /// Checks whether every layer allows the network actions on the TCP port.
+    pub(super) fn check_net_access(&self, port: u16, access: AccessNet) -> Result<()> {
+        let is_allowed = self.layers.iter().all(|layer| {
+            !layer.handled_access_net().intersects(access)
+                || layer
+                    .net_rule(port)
+                    .is_some_and(|allowed| allowed.contains(access))
+        });
+        if is_allowed {
+            return Ok(());
+        }
+
+        return_errno_with_message!(Errno::EACCES, "the network access is denied by Landlock");
+    }
+
+    /// Collects the rights that each layer grants on `path`.
+    ///
+    /// A layer grants the rights that it does not handle, except for `REFER`, and the rights
+    /// allowed by its rules on `path` and all the ancestors of `path`. The walk stops early once
+    /// every layer grants all the rights in `wanted`.
+    fn collect_fs_access(&self, path: &Path, wanted: AccessFs) -> Vec<AccessFs> {
+        let mut granted_access: Vec<AccessFs> = self
+            .layers
+            .iter()
+            .map(|layer| !layer.handled_access_fs() - AccessFs::REFER)
+            .collect();
+
+        let mut current = Some(path.clone());
+        while let Some(path) = current {
+            if granted_access
+                .iter()
+                .all(|granted| granted.contains(wanted))
+            {
+                break;
+            }
+
+            for (layer, granted) in self.layers.iter().zip(granted_access.iter_mut()) {
+                if let Some(allowed) = layer.fs_rule(path.inode()) {
+                    *granted |= allowed;
+                }
+            }
+
+            current = path.parent_across_mounts();
+        }
+
+        granted_access
+    }
+}
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The Landlock LSM.
+//!
+//! Landlock lets unprivileged threads sandbox themselves. A thread creates a ruleset that
+//! declares the actions to restrict, adds rules that allow some of those actions on file
+//! hierarchies or TCP ports, and then enforces the ruleset on itself. Enforced rulesets are
+//! stacked into a domain, which is inherited by child threads and preserved across `execve()`.
+//!
+//! Reference: <https://docs.kernel.org/userspace-api/landlock.html>.
+
+mod access;
+mod domain;
+mod ruleset;
+
+use self::domain::LandlockDomain;
+pub use self::{
+    access::{AccessFs, AccessNet},
+    ruleset::{Ruleset, RulesetFile},
+};
+use super::super::{
+    LsmFlags, LsmModule,
+    hooks::{
+        AlienAccessContext, FileOpenContext, LsmAlienAccessHook, LsmBprmHook, LsmCapabilityHook,
+        LsmFileHook, LsmInodeHook, LsmMountHook, LsmPathHook, LsmSocketHook, LsmTaskHook,
+        PathCreateContext, PathLinkContext, PathRemoveContext, PathRenameContext,
+        PathTruncateContext, SbMountContext, SbUmountContext, SocketAddrContext,
+    },
+};
+use crate::{
+    net::socket::util::SocketAddr, prelude::*, process::posix_thread::PosixThread,
+    util::net::Protocol,
+};
+
+/// The version of the Landlock ABI.
+///
+/// Version 4 adds the TCP port rules on top of the `REFER` right (version 2) and the
+/// `TRUNCATE` right (version 3).
+pub const LANDLOCK_ABI_VERSION: u32 = 4;
+
+pub(super) static LANDLOCK_LSM: LandlockLsm = LandlockLsm;
+
+/// The Landlock LSM.
+pub(super) struct LandlockLsm;
+
+impl LsmModule for LandlockLsm {
+    fn name(&self) -> &'static str {
+        "landlock"
+    }
+
+    fn flags(&self) -> LsmFlags {
+        LsmFlags::empty()
+    }
+}
+
+impl LsmCapabilityHook for LandlockLsm {}
+
+impl LsmAlienAccessHook for LandlockLsm {
+    fn on_alien_access(&self, context: &AlienAccessContext) -> Result<()> {
+        let Some(accessor_domain) = domain_of(context.accessor()) else {
+            return Ok(());
+        };
+
+        // A sandboxed thread may only access threads that are at least as restricted as itself.
+        if domain_of(context.target())
+            .is_some_and(|target_domain| accessor_domain.is_equal_or_ancestor_of(&target_domain))
+        {
+            return Ok(());
+        }
+
+        return_errno_with_message!(
+            Errno::EPERM,
+            "the target thread is less restricted by Landlock"
+        );
+    }
+}
+
+impl LsmPathHook for LandlockLsm {
+    fn on_path_create(&self, context: &PathCreateContext) -> Result<()> {
+        let Some(domain) = domain_of(context.posix_thread()) else {
+            return Ok(());
+        };
+
+        domain.check_fs_access(context.dir(), AccessFs::make(context.type_()))
+    }
+
+    fn on_path_remove(&self, context: &PathRemoveContext) -> Result<()> {
+        let Some(domain) = domain_of(context.posix_thread()) else {
+            return Ok(());
+        };
+
+        domain.check_fs_access(context.dir(), AccessFs::remove(context.victim().type_()))
+    }
+
+    fn on_path_link(&self, context: &PathLinkContext) -> Result<()> {
+        let Some(domain) = domain_of(context.posix_thread()) else {
+            return Ok(());
+        };
+
+        let old = context.old();
+        let new_dir = context.new_dir();
+        domain.check_fs_access(new_dir, AccessFs::make(old.type_()))?;
+
+        let Some(old_dir) = old.parent_across_mounts() else {
+            return Ok(());
+        };
+        domain.check_refer(old, &old_dir, new_dir)
+    }
+
+    fn on_path_rename(&self, context: &PathRenameContext) -> Result<()> {
+        let Some(domain) = domain_of(context.posix_thread()) else {
+            return Ok(());
+        };
+
+        let old = context.old();
+        let old_dir = context.old_dir();
+        let new_dir = context.new_dir();
+        domain.check_fs_access(old_dir, AccessFs::remove(old.type_()))?;
+        domain.check_fs_access(new_dir, AccessFs::make(old.type_()))?;
+        if let Some(replaced) = context.replaced() {
+            domain.check_fs_access(new_dir, AccessFs::remove(replaced.type_()))?;
+        }
+
+        domain.check_refer(old, old_dir, new_dir)
+    }
+
+    fn on_path_truncate(&self, context: &PathTruncateContext) -> Result<()> {
+        let Some(domain) = domain_of(context.posix_thread()) else {
+            return Ok(());
+        };
+
+        domain.check_fs_access(context.path(), AccessFs::TRUNCATE)
+    }
+}
+
+impl LsmInodeHook for LandlockLsm {}
+
+impl LsmFileHook for LandlockLsm {
+    fn on_file_open(&self, context: &FileOpenContext) -> Result<()> {
+        let Some(domain) = domain_of(context.posix_thread()) else {
+            return Ok(());
+        };
+
+        let path = context.path();
+        let permission = context.permission();
+
+        let mut access = AccessFs::empty();
+        if path.type_().is_directory() {
+            if permission.may_read() {
+                access |= AccessFs::READ_DIR;
+            }
+        } else {
+            if permission.may_read() {
+                access |= AccessFs::READ_FILE;
+            }
+            if permission.may_write() {
+                access |= AccessFs::WRITE_FILE;
+            }
+            if permission.may_exec() {
+                access |= AccessFs::EXECUTE;
+            }
+        }
+
+        domain.check_fs_access(path, access)
+    }
+}
+
+impl LsmBprmHook for LandlockLsm {}
+
+impl LsmSocketHook for LandlockLsm {
+    fn on_socket_bind(&self, context: &SocketAddrContext) -> Result<()> {
+        check_tcp_access(context, AccessNet::BIND_TCP)
+    }
+
+    fn on_socket_connect(&self, context: &SocketAddrContext) -> Result<()> {
+        check_tcp_access(context, AccessNet::CONNECT_TCP)
+    }
+}
+
+impl LsmTaskHook for LandlockLsm {}
+
+impl LsmMountHook for LandlockLsm {
+    // A sandboxed thread could otherwise bypass the file system rules by changing the mount
+    // topology, so all mount operations are denied.
+
+    fn on_sb_mount(&self, context: &SbMountContext) -> Result<()> {
+        if domain_of(context.posix_thread()).is_some() {
+            return_errno_with_message!(Errno::EPERM, "sandboxed threads cannot mount");
+        }
+
+        Ok(())
+    }
+
+    fn on_sb_umount(&self, context: &SbUmountContext) -> Result<()> {
+        if domain_of(context.posix_thread()).is_some() {
+            return_errno_with_message!(Errno::EPERM, "sandboxed threads cannot unmount");
+        }
+
+        Ok(())
+    }
+}
+
+fn check_tcp_access(context: &SocketAddrContext, access: AccessNet) -> Result<()> {
+    if !matches!(context.protocol(), Protocol::IPPROTO_TCP) {
+        return Ok(());
+    }
+
+    let Some(domain) = domain_of(context.posix_thread()) else {
+        return Ok(());
+    };
+
+    let port = match context.addr() {
+        SocketAddr::IPv4(_, port) | SocketAddr::IPv6(_, port) => *port,
+        // Other addresses are rejected by the socket itself.
+        _ => return Ok(()),
+    };
+
+    domain.check_net_access(port, access)
+}
+
+/// Enforces the ruleset on the thread.
+///
+/// The ruleset is added as a new layer on top of the current domain of the thread. The new
+/// domain is inherited by child threads and preserved across `execve()`.
+pub fn restrict_self(posix_thread: &PosixThread, ruleset: &Ruleset) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    let parent = credentials.security().get::<LandlockDomain>(&LANDLOCK_LSM);
+    let domain = LandlockDomain::new_nested(parent.as_deref(), ruleset)?;
+    credentials.security().set(&LANDLOCK_LSM, Arc::new(domain));
+
+    Ok(())
+}
+
+fn domain_of(posix_thread: &PosixThread) -> Option<Arc<LandlockDomain>> {
+    posix_thread
+        .credentials()
+        .security()
+        .get::<LandlockDomain>(&LANDLOCK_LSM)
+}
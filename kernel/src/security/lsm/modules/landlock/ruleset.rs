@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Display;
+
+use super::{
+    LANDLOCK_LSM,
+    access::{AccessFs, AccessNet},
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file::{AccessMode, CreationFlags, FileLike, file_table::FdFlags},
+        pseudofs::AnonInodeFs,
+        vfs::{inode::Inode, inode_ext::InodeExt, path::Path},
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+};
+
+/// A set of Landlock rules that has not been enforced yet.
+///
+/// A ruleset is created by `landlock_create_ruleset`, populated by `landlock_add_rule`, and
+/// enforced on the calling thread by `landlock_restrict_self`. Later modifications to the
+/// ruleset do not affect the threads that have already enforced it.
+pub struct Ruleset {
+    handled_access_fs: AccessFs,
+    handled_access_net: AccessNet,
+    rules: Mutex<Rules>,
+}
+
+impl Ruleset {
+    /// Creates an empty ruleset that restricts the given actions.
+    pub fn new(handled_access_fs: AccessFs, handled_access_net: AccessNet) -> Result<Self> {
+        if handled_access_fs.is_empty() && handled_access_net.is_empty() {
+            return_errno_with_message!(Errno::ENOMSG, "the ruleset does not restrict any action");
+        }
+
+        Ok(Self {
+            handled_access_fs,
+            handled_access_net,
+            rules: Mutex::new(Rules::default()),
+        })
+    }
+
+    /// Allows the actions in `allowed_access` on the file hierarchy beneath `path`.
+    pub fn add_path_beneath(&self, path: &Path, allowed_access: AccessFs) -> Result<()> {
+        if allowed_access.is_empty() {
+            return_errno_with_message!(Errno::ENOMSG, "the rule does not allow any action");
+        }
+        if !self.handled_access_fs.contains(allowed_access) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the rule allows actions that are not handled by the ruleset"
+            );
+        }
+        if path.is_pseudo() {
+            return_errno_with_message!(Errno::EBADFD, "the file is not in a mountable file system");
+        }
+        if !path.type_().is_directory() && !AccessFs::FILE.contains(allowed_access) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the rule allows directory actions on a non-directory file"
+            );
+        }
+
+        let inode = path.inode();
+        let object = inode
+            .security_blob_or_init()
+            .get_or_init(&LANDLOCK_LSM, || LandlockObject);
+        let mut rules = self.rules.lock();
+        let (_, rule_access) = rules
+            .fs
+            .entry(object_key(&object))
+            .or_insert_with(|| (inode.clone(), AccessFs::empty()));
+        *rule_access |= allowed_access;
+
+        Ok(())
+    }
+
+    /// Allows the actions in `allowed_access` on the TCP port.
+    pub fn add_net_port(&self, port: u64, allowed_access: AccessNet) -> Result<()> {
+        if allowed_access.is_empty() {
+            return_errno_with_message!(Errno::ENOMSG, "the rule does not allow any action");
+        }
+        if !self.handled_access_net.contains(allowed_access) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the rule allows actions that are not handled by the ruleset"
+            );
+        }
+        let Ok(port) = u16::try_from(port) else {
+            return_errno_with_message!(Errno::EINVAL, "the port number is out of range");
+        };
+
+        let mut rules = self.rules.lock();
+        *rules.net.entry(port).or_insert(AccessNet::empty()) |= allowed_access;
+
+        Ok(())
+    }
+
+    /// Takes a snapshot of the ruleset to be enforced as a layer of a domain.
+    pub(super) fn to_layer(&self) -> Layer {
+        Layer {
+            handled_access_fs: self.handled_access_fs,
+            handled_access_net: self.handled_access_net,
+            rules: self.rules.lock().clone(),
+        }
+    }
+}
+
+/// An enforced ruleset in a domain.
+pub(super) struct Layer {
+    handled_access_fs: AccessFs,
+    handled_access_net: AccessNet,
+    rules: Rules,
+}
+
+impl Layer {
+    /// Returns the filesystem actions restricted by this layer.
+    pub(super) fn handled_access_fs(&self) -> AccessFs {
+        self.handled_access_fs
+    }
+
+    /// Returns the network actions restricted by this layer.
+    pub(super) fn handled_access_net(&self) -> AccessNet {
+        self.handled_access_net
+    }
+
+    /// Returns the filesystem actions allowed by the rule on the inode, if any.
+    pub(super) fn fs_rule(&self, inode: &Arc<dyn Inode>) -> Option<AccessFs> {
+        let object = inode
+            .security_blob()?
+            .get::<LandlockObject>(&LANDLOCK_LSM)?;
+        self.rules
+            .fs
+            .get(&object_key(&object))
+            .map(|(_, allowed_access)| *allowed_access)
+    }
+
+    /// Returns the network actions allowed by the rule on the port, if any.
+    pub(super) fn net_rule(&self, port: u16) -> Option<AccessNet> {
+        self.rules.net.get(&port).copied()
+    }
+}
+
+#[derive(Clone, Default)]
+struct Rules {
+    /// The filesystem rules, indexed by the address of the Landlock objects of their inodes.
+    ///
+    /// Each rule holds its inode, which in turn holds the object in its security blob, so that
+    /// the address cannot be reused by another object.
+    fs: BTreeMap<usize, (Arc<dyn Inode>, AccessFs)>,
+    /// The network rules, indexed by their TCP ports.
+    net: BTreeMap<u16, AccessNet>,
+}
+
+/// The identity of an inode in Landlock rules.
+///
+/// The object is created when the first rule is added for the inode and is stored in the
+/// security blob of the inode.
+struct LandlockObject;
+
+fn object_key(object: &Arc<LandlockObject>) -> usize {
+    Arc::as_ptr(object) as usize
+}
+
+/// The file that represents a ruleset.
+pub struct RulesetFile {
+    ruleset: Ruleset,
+    pseudo_path: Path,
+}
+
+impl RulesetFile {
+    /// Creates a new file for the ruleset.
+    pub fn new(ruleset: Ruleset) -> Self {
+        let pseudo_path = AnonInodeFs::new_path(|_| "anon_inode:[landlock-ruleset]".to_string());
+
+        Self {
+            ruleset,
+            pseudo_path,
+        }
+    }
+
+    /// Returns the ruleset.
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+}
+
+impl Pollable for RulesetFile {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        // Like other files without a poll method in Linux, a ruleset file is always ready.
+        mask & (IoEvents::IN | IoEvents::OUT)
+    }
+}
+
+impl FileLike for RulesetFile {
+    fn access_mode(&self) -> AccessMode {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16/source/security/landlock/syscalls.c>.
+        AccessMode::O_RDWR
+    }
+
+    fn path(&self) -> &Path {
+        &self.pseudo_path
+    }
+
+    fn dump_proc_fdinfo(self: Arc<Self>, fd_flags: FdFlags) -> Box<dyn Display> {
+        struct FdInfo {
+            flags: u32,
+        }
+
+        impl Display for FdInfo {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                writeln!(f, "pos:\t{}", 0)?;
+                writeln!(f, "flags:\t0{:o}", self.flags)?;
+                writeln!(f, "mnt_id:\t{}", AnonInodeFs::mount_node().id())?;
+                writeln!(f, "ino:\t{}", AnonInodeFs::shared_inode().ino())
+            }
+        }
+
+        let mut flags = self.status_flags().bits() | self.access_mode() as u32;
+        if fd_flags.contains(FdFlags::CLOEXEC) {
+            flags |= CreationFlags::O_CLOEXEC.bits();
+        }
+
+        Box::new(FdInfo { flags })
+    }
+}
@@ -6,7 +6,10 @@ use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 
 use super::super::{
     LsmFlags, LsmModule,
-    hooks::{AlienAccessContext, LsmAlienAccessHook, LsmCapabilityHook},
+    hooks::{
+        AlienAccessContext, LsmAlienAccessHook, LsmBprmHook, LsmCapabilityHook, LsmFileHook,
+        LsmInodeHook, LsmMountHook, LsmPathHook, LsmSocketHook, LsmTaskHook,
+    },
 };
 use crate::{
     prelude::*,
@@ -72,6 +75,20 @@ impl LsmModule for YamaLsm {
 
 impl LsmCapabilityHook for YamaLsm {}
 
+impl LsmPathHook for YamaLsm {}
+
+impl LsmInodeHook for YamaLsm {}
+
+impl LsmFileHook for YamaLsm {}
+
+impl LsmBprmHook for YamaLsm {}
+
+impl LsmSocketHook for YamaLsm {}
+
+impl LsmTaskHook for YamaLsm {}
+
+impl LsmMountHook for YamaLsm {}
+
 /// Returns the current Yama scope for alien access.
 pub fn get_scope() -> YamaScope {
     YAMA_SCOPE.load(Ordering::Relaxed)
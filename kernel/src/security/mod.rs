@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
+pub mod audit;
+pub mod keys;
 pub mod lsm;
 
 use cfg_if::cfg_if;
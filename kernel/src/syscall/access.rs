@@ -95,17 +95,15 @@ fn do_faccessat(
         }
     };
 
-    let inode = path.inode();
-
     // F_OK is represented by `AccessMode::empty()`, which does not perform permission checks.
     if mode.contains(AccessMode::R_OK) {
-        inode.check_permission(Permission::MAY_READ)?;
+        path.check_permission(Permission::MAY_READ)?;
     }
     if mode.contains(AccessMode::W_OK) {
-        inode.check_permission(Permission::MAY_WRITE)?;
+        path.check_permission(Permission::MAY_WRITE)?;
     }
     if mode.contains(AccessMode::X_OK) {
-        inode.check_permission(Permission::MAY_EXEC)?;
+        path.check_permission(Permission::MAY_EXEC)?;
     }
 
     Ok(SyscallReturn::Return(0))
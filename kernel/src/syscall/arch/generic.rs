@@ -63,7 +63,11 @@ macro_rules! import_generic_syscall_entries {
             getxattr::{sys_fgetxattr, sys_getxattr, sys_lgetxattr},
             inotify::{sys_inotify_add_watch, sys_inotify_init1, sys_inotify_rm_watch},
             ioctl::sys_ioctl,
+            keyctl::{sys_add_key, sys_keyctl, sys_request_key},
             kill::sys_kill,
+            landlock::{
+                sys_landlock_add_rule, sys_landlock_create_ruleset, sys_landlock_restrict_self,
+            },
             link::sys_linkat,
             listen::sys_listen,
             listxattr::{sys_flistxattr, sys_listxattr, sys_llistxattr},
@@ -74,6 +78,7 @@ macro_rules! import_generic_syscall_entries {
             mknod::sys_mknodat,
             mmap::sys_mmap,
             mount::sys_mount,
+            mount_setattr::sys_mount_setattr,
             mprotect::sys_mprotect,
             mremap::sys_mremap,
             msync::sys_msync,
@@ -376,7 +381,10 @@ macro_rules! define_syscalls_with_generic_syscall_table {
             SYS_BRK = 214                    => sys_brk(args[..1]);
             SYS_MUNMAP = 215                 => sys_munmap(args[..2]);
             SYS_MREMAP = 216                 => sys_mremap(args[..5]);
-            SYS_CLONE = 220                  => sys_clone(args[..5], &user_ctx);
+            SYS_ADD_KEY = 217                => sys_add_key(args[..5]);
+            SYS_REQUEST_KEY = 218            => sys_request_key(args[..4]);
+            SYS_KEYCTL = 219                 => sys_keyctl(args[..5]);
+            SYS_CLONE = 220                  => sys_clone(args[..5], &mut user_ctx);
             SYS_EXECVE = 221                 => sys_execve(args[..3], &mut user_ctx);
             SYS_MMAP = 222                   => sys_mmap(args[..6]);
             SYS_FADVISE64 = 223              => sys_fadvise64(args[..4]);
@@ -400,11 +408,15 @@ macro_rules! define_syscalls_with_generic_syscall_table {
             SYS_STATX = 291                  => sys_statx(args[..5]);
             SYS_PIDFD_SEND_SIGNAL = 424      => sys_pidfd_send_signal(args[..4]);
             SYS_PIDFD_OPEN = 434             => sys_pidfd_open(args[..2]);
-            SYS_CLONE3 = 435                 => sys_clone3(args[..2], &user_ctx);
+            SYS_CLONE3 = 435                 => sys_clone3(args[..2], &mut user_ctx);
             SYS_CLOSE_RANGE = 436            => sys_close_range(args[..3]);
             SYS_PIDFD_GETFD = 438            => sys_pidfd_getfd(args[..3]);
             SYS_FACCESSAT2 = 439             => sys_faccessat2(args[..4]);
             SYS_EPOLL_PWAIT2 = 441           => sys_epoll_pwait2(args[..6]);
+            SYS_MOUNT_SETATTR = 442          => sys_mount_setattr(args[..5]);
+            SYS_LANDLOCK_CREATE_RULESET = 444 => sys_landlock_create_ruleset(args[..3]);
+            SYS_LANDLOCK_ADD_RULE = 445      => sys_landlock_add_rule(args[..4]);
+            SYS_LANDLOCK_RESTRICT_SELF = 446 => sys_landlock_restrict_self(args[..2]);
             SYS_FCHMODAT2 = 452              => sys_fchmodat2(args[..4]);
             // Architecture-specific syscalls
             $( $name = $num => $handler $args );*
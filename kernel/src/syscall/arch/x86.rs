@@ -63,7 +63,9 @@ use super::{
     impl_syscall_nums_and_dispatch_fn,
     inotify::{sys_inotify_add_watch, sys_inotify_init, sys_inotify_init1, sys_inotify_rm_watch},
     ioctl::sys_ioctl,
+    keyctl::{sys_add_key, sys_keyctl, sys_request_key},
     kill::sys_kill,
+    landlock::{sys_landlock_add_rule, sys_landlock_create_ruleset, sys_landlock_restrict_self},
     link::{sys_link, sys_linkat},
     listen::sys_listen,
     listxattr::{sys_flistxattr, sys_listxattr, sys_llistxattr},
@@ -74,6 +76,7 @@ use super::{
     mknod::{sys_mknod, sys_mknodat},
     mmap::sys_mmap,
     mount::sys_mount,
+    mount_setattr::sys_mount_setattr,
     mprotect::sys_mprotect,
     mremap::sys_mremap,
     msync::sys_msync,
@@ -233,9 +236,9 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_SOCKETPAIR = 53        => sys_socketpair(args[..4]);
     SYS_SETSOCKOPT = 54        => sys_setsockopt(args[..5]);
     SYS_GETSOCKOPT = 55        => sys_getsockopt(args[..5]);
-    SYS_CLONE = 56             => sys_clone(args[..5], &user_ctx);
-    SYS_FORK = 57              => sys_fork(args[..0], &user_ctx);
-    SYS_VFORK = 58             => sys_vfork(args[..0], &user_ctx);
+    SYS_CLONE = 56             => sys_clone(args[..5], &mut user_ctx);
+    SYS_FORK = 57              => sys_fork(args[..0], &mut user_ctx);
+    SYS_VFORK = 58             => sys_vfork(args[..0], &mut user_ctx);
     SYS_EXECVE = 59            => sys_execve(args[..3], &mut user_ctx);
     SYS_EXIT = 60              => sys_exit(args[..1], &mut user_ctx);
     SYS_WAIT4 = 61             => sys_wait4(args[..4]);
@@ -360,6 +363,9 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_TGKILL = 234           => sys_tgkill(args[..3]);
     SYS_UTIMES = 235           => sys_utimes(args[..2]);
     SYS_WAITID = 247           => sys_waitid(args[..5]);
+    SYS_ADD_KEY = 248          => sys_add_key(args[..5]);
+    SYS_REQUEST_KEY = 249      => sys_request_key(args[..4]);
+    SYS_KEYCTL = 250           => sys_keyctl(args[..5]);
     SYS_IOPRIO_SET = 251       => sys_ioprio_set(args[..3]);
     SYS_IOPRIO_GET = 252       => sys_ioprio_get(args[..2]);
     SYS_INOTIFY_INIT = 253     => sys_inotify_init(args[..0]);
@@ -415,10 +421,14 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_STATX = 332            => sys_statx(args[..5]);
     SYS_PIDFD_SEND_SIGNAL = 424 => sys_pidfd_send_signal(args[..4]);
     SYS_PIDFD_OPEN = 434       => sys_pidfd_open(args[..2]);
-    SYS_CLONE3 = 435           => sys_clone3(args[..2], &user_ctx);
+    SYS_CLONE3 = 435           => sys_clone3(args[..2], &mut user_ctx);
     SYS_CLOSE_RANGE = 436      => sys_close_range(args[..3]);
     SYS_PIDFD_GETFD = 438      => sys_pidfd_getfd(args[..3]);
     SYS_FACCESSAT2 = 439       => sys_faccessat2(args[..4]);
     SYS_EPOLL_PWAIT2 = 441     => sys_epoll_pwait2(args[..6]);
+    SYS_MOUNT_SETATTR = 442    => sys_mount_setattr(args[..5]);
+    SYS_LANDLOCK_CREATE_RULESET = 444 => sys_landlock_create_ruleset(args[..3]);
+    SYS_LANDLOCK_ADD_RULE = 445 => sys_landlock_add_rule(args[..4]);
+    SYS_LANDLOCK_RESTRICT_SELF = 446 => sys_landlock_restrict_self(args[..2]);
     SYS_FCHMODAT2 = 452        => sys_fchmodat2(args[..4]);
 }
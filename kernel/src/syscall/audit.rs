@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::arch::*;
+use crate::security::audit::{AuditPerm, SyscallClass};
+
+/// Classifies a system call by how it accesses files.
+///
+/// The class is used to match the `AUDIT_PERM` field of the audit rules.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/asm-generic/audit_write.h>
+/// and its siblings.
+pub fn classify_syscall(syscall_number: u64) -> SyscallClass {
+    let perm = match syscall_number {
+        #[cfg(target_arch = "x86_64")]
+        SYS_OPEN => return SyscallClass::Open { flags_arg: 1 },
+        SYS_OPENAT => return SyscallClass::Open { flags_arg: 2 },
+        SYS_EXECVE | SYS_EXECVEAT => return SyscallClass::Execve,
+
+        #[cfg(target_arch = "x86_64")]
+        SYS_RENAME | SYS_MKDIR | SYS_RMDIR | SYS_CREAT | SYS_LINK | SYS_UNLINK | SYS_SYMLINK
+        | SYS_MKNOD | SYS_RENAMEAT => AuditPerm::WRITE,
+        SYS_MKDIRAT | SYS_MKNODAT | SYS_UNLINKAT | SYS_SYMLINKAT | SYS_LINKAT | SYS_RENAMEAT2
+        | SYS_TRUNCATE | SYS_FTRUNCATE | SYS_FALLOCATE | SYS_BIND => AuditPerm::WRITE,
+
+        #[cfg(target_arch = "x86_64")]
+        SYS_READLINK => AuditPerm::READ,
+        SYS_READLINKAT | SYS_GETXATTR | SYS_LGETXATTR | SYS_FGETXATTR | SYS_LISTXATTR
+        | SYS_LLISTXATTR | SYS_FLISTXATTR => AuditPerm::READ,
+
+        #[cfg(target_arch = "x86_64")]
+        SYS_CHMOD | SYS_CHOWN | SYS_LCHOWN | SYS_UTIME | SYS_UTIMES | SYS_FUTIMESAT => {
+            AuditPerm::ATTR
+        }
+        SYS_SETXATTR | SYS_LSETXATTR | SYS_FSETXATTR | SYS_REMOVEXATTR | SYS_LREMOVEXATTR
+        | SYS_FREMOVEXATTR | SYS_FCHMOD | SYS_FCHMODAT | SYS_FCHMODAT2 | SYS_FCHOWN
+        | SYS_FCHOWNAT | SYS_UTIMENSAT => AuditPerm::ATTR,
+
+        _ => AuditPerm::empty(),
+    };
+
+    SyscallClass::Native(perm)
+}
@@ -4,6 +4,7 @@ use super::SyscallReturn;
 use crate::{
     fs::file::file_table::{RawFileDesc, get_file_fast},
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     util::net::read_socket_addr_from_user,
 };
 
@@ -19,6 +20,11 @@ pub fn sys_bind(
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, sockfd.try_into()?);
     let socket = file.as_socket_or_err()?;
+    lsm_hooks::on_socket_bind(lsm_hooks::SocketAddrContext::new(
+        ctx.posix_thread,
+        socket.protocol(),
+        &socket_addr,
+    ))?;
 
     socket.bind(socket_addr)?;
 
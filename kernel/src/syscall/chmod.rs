@@ -9,9 +9,10 @@ use crate::{
             file_table::{RawFileDesc, get_file_fast},
         },
         utils::PATH_MAX,
-        vfs::path::{AT_FDCWD, EmptyPathStr, FsPath},
+        vfs::path::{AT_FDCWD, EmptyPathStr, FsPath, Path},
     },
     prelude::*,
+    security::lsm::hooks::{self as lsm_hooks, InodeAttrs},
 };
 
 pub fn sys_fchmod(raw_fd: RawFileDesc, mode: u16, ctx: &Context) -> Result<SyscallReturn> {
@@ -19,8 +20,7 @@ pub fn sys_fchmod(raw_fd: RawFileDesc, mode: u16, ctx: &Context) -> Result<Sysca
 
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
-    file.path().set_mode(InodeMode::from_bits_truncate(mode))?;
-    fs::vfs::notify::on_attr_change(file.path());
+    set_mode(file.path(), mode, ctx)?;
     Ok(SyscallReturn::Return(0))
 }
 
@@ -78,11 +78,22 @@ fn do_fchmodat(
         }
     };
 
-    path.set_mode(InodeMode::from_bits_truncate(mode))?;
-    fs::vfs::notify::on_attr_change(&path);
+    set_mode(&path, mode, ctx)?;
     Ok(SyscallReturn::Return(0))
 }
 
+fn set_mode(path: &Path, mode: u16, ctx: &Context) -> Result<()> {
+    lsm_hooks::on_inode_setattr(lsm_hooks::InodeSetattrContext::new(
+        ctx.posix_thread,
+        path,
+        InodeAttrs::MODE,
+    ))?;
+
+    path.set_mode(InodeMode::from_bits_truncate(mode))?;
+    fs::vfs::notify::on_attr_change(path);
+    Ok(())
+}
+
 bitflags::bitflags! {
     struct ChmodFlags: u32 {
         const AT_EMPTY_PATH = 1 << 12;
@@ -5,10 +5,11 @@ use crate::{
     fs::{
         file::file_table::{RawFileDesc, get_file_fast},
         utils::PATH_MAX,
-        vfs::path::{AT_FDCWD, EmptyPathStr, FsPath},
+        vfs::path::{AT_FDCWD, EmptyPathStr, FsPath, Path},
     },
     prelude::*,
-    process::{Gid, Uid},
+    process::{Gid, Uid, credentials::file_capabilities::FileCapabilities},
+    security::lsm::hooks::{self as lsm_hooks, InodeAttrs},
 };
 
 pub fn sys_fchown(raw_fd: RawFileDesc, uid: i32, gid: i32, ctx: &Context) -> Result<SyscallReturn> {
@@ -22,13 +23,7 @@ pub fn sys_fchown(raw_fd: RawFileDesc, uid: i32, gid: i32, ctx: &Context) -> Res
 
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
-    let path = file.path();
-    if let Some(uid) = uid {
-        path.set_owner(uid)?;
-    }
-    if let Some(gid) = gid {
-        path.set_group(gid)?;
-    }
+    set_owner_and_group(file.path(), uid, gid, ctx)?;
     Ok(SyscallReturn::Return(0))
 }
 
@@ -87,13 +82,33 @@ pub fn sys_fchownat(
         }
     };
 
+    set_owner_and_group(&path, uid, gid, ctx)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+fn set_owner_and_group(
+    path: &Path,
+    uid: Option<Uid>,
+    gid: Option<Gid>,
+    ctx: &Context,
+) -> Result<()> {
+    let mut attrs = InodeAttrs::empty();
+    attrs.set(InodeAttrs::UID, uid.is_some());
+    attrs.set(InodeAttrs::GID, gid.is_some());
+    lsm_hooks::on_inode_setattr(lsm_hooks::InodeSetattrContext::new(
+        ctx.posix_thread,
+        path,
+        attrs,
+    ))?;
+
+    FileCapabilities::remove_from(path)?;
     if let Some(uid) = uid {
         path.set_owner(uid)?;
     }
     if let Some(gid) = gid {
         path.set_group(gid)?;
     }
-    Ok(SyscallReturn::Return(0))
+    Ok(())
 }
 
 fn to_optional_id<T>(id: i32, f: impl Fn(u32) -> T) -> Result<Option<T>> {
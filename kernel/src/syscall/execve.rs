@@ -32,7 +32,8 @@ pub fn sys_execve(
         ctx,
         user_context,
     )?;
-    Ok(SyscallReturn::NoReturn)
+    // The new program starts with the return value of zero, like all other registers.
+    Ok(SyscallReturn::Return(0))
 }
 
 pub fn sys_execveat(
@@ -58,7 +59,8 @@ pub fn sys_execveat(
         ctx,
         user_context,
     )?;
-    Ok(SyscallReturn::NoReturn)
+    // The new program starts with the return value of zero, like all other registers.
+    Ok(SyscallReturn::Return(0))
 }
 
 fn lookup_executable_file(
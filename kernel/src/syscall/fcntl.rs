@@ -107,7 +107,7 @@ fn handle_getlk(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
     let mut lock = RangeLockItem::new(lock_type, from_c_flock_and_file(&lock_mut_c, &**file)?);
     let inode_file = file.as_inode_handle_or_err()?;
     lock = inode_file.test_range_lock(lock)?;
-    lock_mut_c.copy_from_range_lock(&lock);
+    lock_mut_c.copy_from_range_lock(&lock, ctx);
     ctx.user_space().write_val(lock_mut_ptr, &lock_mut_c)?;
     Ok(SyscallReturn::Return(0))
 }
@@ -132,7 +132,10 @@ fn handle_setlk(
 fn handle_getown(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     file_table.read_with(|inner| {
-        let pid = inner.get_entry(fd)?.owner().unwrap_or(0);
+        let pid = inner
+            .get_entry(fd)?
+            .owner()
+            .map_or(0, |pid| ctx.process.nr_in_ns(pid));
         Ok(SyscallReturn::Return(pid as _))
     })
 }
@@ -151,7 +154,7 @@ fn handle_setown(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
     } else {
         Some(
             pid_table::pid_table_mut()
-                .get_process(pid)
+                .get_process_in_ns(pid, ctx.process.pid_ns())
                 .ok_or(Error::with_message(
                     Errno::ESRCH,
                     "cannot set_owner with an invalid pid",
@@ -236,7 +239,9 @@ pub struct c_flock {
 }
 
 impl c_flock {
-    pub fn copy_from_range_lock(&mut self, lock: &RangeLockItem) {
+    /// Copies the lock to `self`, with the owner seen in the PID namespace of the current
+    /// process.
+    pub fn copy_from_range_lock(&mut self, lock: &RangeLockItem, ctx: &Context) {
         self.l_type = lock.type_() as u16;
         if RangeLockType::Unlock != lock.type_() {
             self.l_whence = RangeLockWhence::SEEK_SET as u16;
@@ -246,7 +251,7 @@ impl c_flock {
             } else {
                 lock.range().len() as off_t
             };
-            self.l_pid = lock.owner();
+            self.l_pid = ctx.process.nr_in_ns(lock.owner());
         }
     }
 }
@@ -15,11 +15,12 @@ pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // "If `pid` is equal to 0, getpgid() shall return the process group ID of the calling
     // process."
     if pid == 0 {
-        return Ok(SyscallReturn::Return(ctx.process.pgid() as _));
+        let pgid = ctx.process.nr_in_ns(ctx.process.pgid());
+        return Ok(SyscallReturn::Return(pgid as _));
     }
 
     let process = pid_table::pid_table_mut()
-        .get_process(pid)
+        .get_process_in_ns(pid, ctx.process.pid_ns())
         .ok_or(Error::with_message(
             Errno::ESRCH,
             "the process to get the PGID does not exist",
@@ -29,5 +30,6 @@ pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // session than the current process. Linux does not perform this check by default, but some
     // strict security policies (e.g. SELinux) may do so.
 
-    Ok(SyscallReturn::Return(process.pgid() as _))
+    let pgid = ctx.process.nr_in_ns(process.pgid());
+    Ok(SyscallReturn::Return(pgid as _))
 }
@@ -4,5 +4,6 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_getppid(ctx: &Context) -> Result<SyscallReturn> {
-    Ok(SyscallReturn::Return(ctx.process.parent().pid() as _))
+    let ppid = ctx.process.nr_in_ns(ctx.process.parent().pid());
+    Ok(SyscallReturn::Return(ppid as _))
 }
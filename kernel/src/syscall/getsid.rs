@@ -14,11 +14,12 @@ pub fn sys_getsid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
 
     // "If `pid` is 0, getsid() returns the session ID of the calling process."
     if pid == 0 {
-        return Ok(SyscallReturn::Return(ctx.process.sid() as _));
+        let sid = ctx.process.nr_in_ns(ctx.process.sid());
+        return Ok(SyscallReturn::Return(sid as _));
     }
 
     let process = pid_table::pid_table_mut()
-        .get_process(pid)
+        .get_process_in_ns(pid, ctx.process.pid_ns())
         .ok_or(Error::with_message(
             Errno::ESRCH,
             "the process to get the SID does not exist",
@@ -28,5 +29,6 @@ pub fn sys_getsid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // session than the current process. Linux does not perform this check by default, but some
     // strict security policies (e.g. SELinux) may do so.
 
-    Ok(SyscallReturn::Return(process.sid() as _))
+    let sid = ctx.process.nr_in_ns(process.sid());
+    Ok(SyscallReturn::Return(sid as _))
 }
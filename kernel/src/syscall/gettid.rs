@@ -4,6 +4,6 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_gettid(ctx: &Context) -> Result<SyscallReturn> {
-    let tid = ctx.posix_thread.tid();
+    let tid = ctx.process.nr_in_ns(ctx.posix_thread.tid());
     Ok(SyscallReturn::Return(tid as _))
 }
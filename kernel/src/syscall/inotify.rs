@@ -91,8 +91,8 @@ pub fn sys_inotify_add_watch(
     };
 
     // Verify caller has read permissions on the inode.
+    dentry.check_permission(Permission::MAY_READ)?;
     let inode = dentry.inode();
-    inode.check_permission(Permission::MAY_READ)?;
 
     if options.contains(InotifyControls::ONLYDIR) && inode.type_() != InodeType::Dir {
         return_errno_with_message!(Errno::ENOTDIR, "path is not a directory");
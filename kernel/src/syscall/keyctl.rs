@@ -0,0 +1,325 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The key management system calls.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/keyrings.7.html>.
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{Gid, Uid},
+    security::keys::{self, KeySerial, MAX_DESCRIPTION_LEN},
+};
+
+/// The maximum length of a key type name, including the final nul byte.
+const KEY_TYPE_MAX_LEN: usize = 32;
+/// The maximum length of the payload supplied to `add_key()`.
+const MAX_ADD_KEY_PAYLOAD_LEN: usize = 1024 * 1024 - 1;
+
+pub fn sys_add_key(
+    type_addr: Vaddr,
+    description_addr: Vaddr,
+    payload_addr: Vaddr,
+    payload_len: usize,
+    keyring: KeySerial,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "type_addr = 0x{:x}, description_addr = 0x{:x}, payload_addr = 0x{:x}, payload_len = {}, keyring = {}",
+        type_addr, description_addr, payload_addr, payload_len, keyring
+    );
+
+    if payload_len > MAX_ADD_KEY_PAYLOAD_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the payload is too long");
+    }
+
+    let type_name = read_key_type(type_addr, ctx)?;
+
+    let description = if description_addr != 0 {
+        let description = read_description(description_addr, ctx)?;
+        if description.is_empty() {
+            None
+        } else if description.starts_with('.') && type_name.starts_with("keyring") {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the keyring description must not start with a dot"
+            );
+        } else {
+            Some(description)
+        }
+    } else {
+        None
+    };
+
+    let payload = read_payload(payload_addr, payload_len, ctx)?;
+
+    let serial = keys::add_key(ctx.posix_thread, &type_name, description, payload, keyring)?;
+    Ok(SyscallReturn::Return(serial as _))
+}
+
+pub fn sys_request_key(
+    type_addr: Vaddr,
+    description_addr: Vaddr,
+    callout_addr: Vaddr,
+    dest: KeySerial,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "type_addr = 0x{:x}, description_addr = 0x{:x}, callout_addr = 0x{:x}, dest = {}",
+        type_addr, description_addr, callout_addr, dest
+    );
+
+    let type_name = read_key_type(type_addr, ctx)?;
+    let description = read_description(description_addr, ctx)?;
+    // The callout information is only used to construct the key, which is not supported.
+    if callout_addr != 0 {
+        read_string(callout_addr, PAGE_SIZE, ctx)?;
+    }
+
+    let serial = keys::request_key(ctx.posix_thread, &type_name, &description, dest)?;
+    Ok(SyscallReturn::Return(serial as _))
+}
+
+pub fn sys_keyctl(
+    option: i32,
+    arg2: usize,
+    arg3: usize,
+    arg4: usize,
+    arg5: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let option = KeyctlOption::try_from(option)
+        .map_err(|_| Error::with_message(Errno::EOPNOTSUPP, "the keyctl option is invalid"))?;
+    debug!(
+        "option = {:?}, arg2 = 0x{:x}, arg3 = 0x{:x}, arg4 = 0x{:x}, arg5 = 0x{:x}",
+        option, arg2, arg3, arg4, arg5
+    );
+
+    let posix_thread = ctx.posix_thread;
+    let ret = match option {
+        KeyctlOption::GetKeyringId => {
+            keys::get_keyring_id(posix_thread, arg2 as KeySerial, arg3 as i32 != 0)? as isize
+        }
+        KeyctlOption::JoinSessionKeyring => {
+            let name = if arg2 != 0 {
+                Some(read_description(arg2, ctx)?)
+            } else {
+                None
+            };
+            keys::join_session_keyring(posix_thread, name)? as isize
+        }
+        KeyctlOption::Update => {
+            if arg4 > PAGE_SIZE {
+                return_errno_with_message!(Errno::EINVAL, "the payload is too long");
+            }
+            let payload = read_payload(arg3, arg4, ctx)?;
+            keys::update_key(posix_thread, arg2 as KeySerial, payload)?;
+            0
+        }
+        KeyctlOption::Revoke => {
+            keys::revoke_key(posix_thread, arg2 as KeySerial)?;
+            0
+        }
+        KeyctlOption::Chown => {
+            let uid = (arg3 as u32 != u32::MAX).then(|| Uid::new(arg3 as u32));
+            let gid = (arg4 as u32 != u32::MAX).then(|| Gid::new(arg4 as u32));
+            keys::chown_key(posix_thread, arg2 as KeySerial, uid, gid)?;
+            0
+        }
+        KeyctlOption::SetPerm => {
+            keys::set_key_perm(posix_thread, arg2 as KeySerial, arg3 as u32)?;
+            0
+        }
+        KeyctlOption::Describe => {
+            let mut description = keys::describe_key(posix_thread, arg2 as KeySerial)?;
+            description.push('\0');
+            write_buffer(description.as_bytes(), arg3, arg4, ctx)?
+        }
+        KeyctlOption::Clear => {
+            keys::clear_keyring(posix_thread, arg2 as KeySerial)?;
+            0
+        }
+        KeyctlOption::Link => {
+            keys::link_key(posix_thread, arg2 as KeySerial, arg3 as KeySerial)?;
+            0
+        }
+        KeyctlOption::Unlink => {
+            keys::unlink_key(posix_thread, arg2 as KeySerial, arg3 as KeySerial)?;
+            0
+        }
+        KeyctlOption::Search => {
+            let type_name = read_key_type(arg3, ctx)?;
+            let description = read_description(arg4, ctx)?;
+            keys::search_keyring(
+                posix_thread,
+                arg2 as KeySerial,
+                &type_name,
+                &description,
+                arg5 as KeySerial,
+            )? as isize
+        }
+        KeyctlOption::Read => {
+            let payload = keys::read_key(posix_thread, arg2 as KeySerial)?;
+            write_buffer(&payload, arg3, arg4, ctx)?
+        }
+        KeyctlOption::SetReqkeyKeyring => {
+            keys::set_request_key_keyring(posix_thread, arg2 as i32)? as isize
+        }
+        KeyctlOption::SetTimeout => {
+            keys::set_key_timeout(posix_thread, arg2 as KeySerial, arg3 as u32)?;
+            0
+        }
+        KeyctlOption::AssumeAuthority => {
+            let id = arg2 as KeySerial;
+            if id < 0 {
+                return_errno_with_message!(Errno::EINVAL, "special keys cannot be authorities");
+            }
+            if id != 0 {
+                // Authorization keys are only created when constructing keys, which is not
+                // supported.
+                return_errno_with_message!(Errno::ENOKEY, "the authorization key is not found");
+            }
+            0
+        }
+        KeyctlOption::GetSecurity => {
+            let mut context = keys::get_key_security(posix_thread, arg2 as KeySerial)?;
+            context.push('\0');
+            write_buffer(context.as_bytes(), arg3, arg4, ctx)?
+        }
+        KeyctlOption::SessionToParent => {
+            keys::session_keyring_to_parent(posix_thread)?;
+            0
+        }
+        KeyctlOption::Invalidate => {
+            keys::invalidate_key(posix_thread, arg2 as KeySerial)?;
+            0
+        }
+        KeyctlOption::Move => {
+            keys::move_key(
+                posix_thread,
+                arg2 as KeySerial,
+                arg3 as KeySerial,
+                arg4 as KeySerial,
+                arg5 as u32,
+            )?;
+            0
+        }
+        KeyctlOption::Capabilities => {
+            let caps = [
+                KEYCTL_CAPS0_CAPABILITIES | KEYCTL_CAPS0_INVALIDATE | KEYCTL_CAPS0_MOVE,
+                0,
+            ];
+            if arg2 != 0 && arg3 != 0 {
+                ctx.user_space()
+                    .write_bytes(arg2, &caps[..arg3.min(caps.len())])?;
+            }
+            caps.len() as isize
+        }
+        KeyctlOption::Instantiate
+        | KeyctlOption::Negate
+        | KeyctlOption::Reject
+        | KeyctlOption::InstantiateIov => {
+            // These operations require the authority to construct a key, which can never be
+            // assumed.
+            return_errno_with_message!(Errno::EPERM, "the thread has no authority");
+        }
+        _ => {
+            warn!("unsupported keyctl option: {:?}", option);
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the keyctl option is not supported");
+        }
+    };
+
+    Ok(SyscallReturn::Return(ret))
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, TryFromInt)]
+enum KeyctlOption {
+    GetKeyringId = 0,
+    JoinSessionKeyring = 1,
+    Update = 2,
+    Revoke = 3,
+    Chown = 4,
+    SetPerm = 5,
+    Describe = 6,
+    Clear = 7,
+    Link = 8,
+    Unlink = 9,
+    Search = 10,
+    Read = 11,
+    Instantiate = 12,
+    Negate = 13,
+    SetReqkeyKeyring = 14,
+    SetTimeout = 15,
+    AssumeAuthority = 16,
+    GetSecurity = 17,
+    SessionToParent = 18,
+    Reject = 19,
+    InstantiateIov = 20,
+    Invalidate = 21,
+    GetPersistent = 22,
+    DhCompute = 23,
+    PkeyQuery = 24,
+    PkeyEncrypt = 25,
+    PkeyDecrypt = 26,
+    PkeySign = 27,
+    PkeyVerify = 28,
+    RestrictKeyring = 29,
+    Move = 30,
+    Capabilities = 31,
+    WatchKey = 32,
+}
+
+const KEYCTL_CAPS0_CAPABILITIES: u8 = 0x01;
+const KEYCTL_CAPS0_INVALIDATE: u8 = 0x20;
+const KEYCTL_CAPS0_MOVE: u8 = 0x80;
+
+/// Reads the name of a key type from the user space.
+fn read_key_type(addr: Vaddr, ctx: &Context) -> Result<String> {
+    let type_name = read_string(addr, KEY_TYPE_MAX_LEN, ctx)?;
+    if type_name.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "the key type is empty");
+    }
+    if type_name.starts_with('.') {
+        return_errno_with_message!(Errno::EPERM, "the key type is internal");
+    }
+    Ok(type_name)
+}
+
+/// Reads the description of a key from the user space.
+fn read_description(addr: Vaddr, ctx: &Context) -> Result<String> {
+    read_string(addr, MAX_DESCRIPTION_LEN + 1, ctx)
+}
+
+/// Reads a string of at most `max_len` bytes, including the final nul byte, from the user space.
+fn read_string(addr: Vaddr, max_len: usize, ctx: &Context) -> Result<String> {
+    let cstring = ctx
+        .user_space()
+        .read_cstring(addr, max_len)
+        .map_err(|err| match err.error() {
+            Errno::ENAMETOOLONG => Error::with_message(Errno::EINVAL, "the string is too long"),
+            _ => err,
+        })?;
+    Ok(cstring.to_string_lossy().into_owned())
+}
+
+/// Reads the payload of a key from the user space.
+fn read_payload(addr: Vaddr, len: usize, ctx: &Context) -> Result<Vec<u8>> {
+    let mut payload = vec![0u8; len];
+    if len != 0 {
+        ctx.user_space().read_bytes(addr, &mut payload)?;
+    }
+    Ok(payload)
+}
+
+/// Writes the data to the user buffer if the buffer is large enough to hold all of it.
+///
+/// Returns the length of the data regardless of whether it is written.
+fn write_buffer(data: &[u8], addr: Vaddr, len: usize, ctx: &Context) -> Result<isize> {
+    if addr != 0 && len >= data.len() {
+        ctx.user_space().write_bytes(addr, data)?;
+    }
+    Ok(data.len() as isize)
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The Landlock system calls.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/landlock.7.html>.
+
+use super::SyscallReturn;
+use crate::{
+    fs::file::{
+        FileLike,
+        file_table::{FdFlags, RawFileDesc, get_file_fast},
+    },
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    security::lsm::{
+        self, hooks as lsm_hooks,
+        landlock::{self, AccessFs, AccessNet, LANDLOCK_ABI_VERSION, Ruleset, RulesetFile},
+    },
+    util::CopyCompat,
+};
+
+pub fn sys_landlock_create_ruleset(
+    attr_addr: Vaddr,
+    size: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "attr_addr = 0x{:x}, size = {}, flags = {:#x}",
+        attr_addr, size, flags
+    );
+
+    check_landlock_enabled()?;
+
+    if flags == LANDLOCK_CREATE_RULESET_VERSION {
+        if attr_addr != 0 || size != 0 {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the ruleset attribute must be empty when querying the ABI version"
+            );
+        }
+        return Ok(SyscallReturn::Return(LANDLOCK_ABI_VERSION as _));
+    }
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+
+    if attr_addr == 0 {
+        return_errno_with_message!(Errno::EFAULT, "the ruleset attribute is null");
+    }
+    // The first version of the structure only contains `handled_access_fs`.
+    if size < size_of::<u64>() {
+        return_errno_with_message!(Errno::EINVAL, "the ruleset attribute size is too small");
+    }
+    if size > PAGE_SIZE {
+        return_errno_with_message!(Errno::E2BIG, "the ruleset attribute size is too large");
+    }
+    let attr: LandlockRulesetAttr = ctx.user_space().read_val_compat(attr_addr, size)?;
+    debug!("ruleset attr = {:?}", attr);
+
+    let handled_access_fs = AccessFs::from_bits(attr.handled_access_fs)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid filesystem access rights"))?;
+    let handled_access_net = AccessNet::from_bits(attr.handled_access_net)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid network access rights"))?;
+    let ruleset = Ruleset::new(handled_access_fs, handled_access_net)?;
+
+    let ruleset_fd = {
+        let ruleset_file = Arc::new(RulesetFile::new(ruleset));
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        file_table_locked.insert(ruleset_file, FdFlags::CLOEXEC)
+    };
+
+    Ok(SyscallReturn::Return(ruleset_fd.into()))
+}
+
+pub fn sys_landlock_add_rule(
+    ruleset_fd: RawFileDesc,
+    rule_type: u32,
+    attr_addr: Vaddr,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "ruleset_fd = {}, rule_type = {}, attr_addr = 0x{:x}, flags = {:#x}",
+        ruleset_fd, rule_type, attr_addr, flags
+    );
+
+    check_landlock_enabled()?;
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, ruleset_fd.try_into()?).into_owned();
+    let ruleset = as_ruleset(file.as_ref())?;
+
+    match rule_type {
+        LANDLOCK_RULE_PATH_BENEATH => {
+            let attr: LandlockPathBeneathAttr = ctx.user_space().read_val(attr_addr)?;
+            let parent_fd = attr.parent_fd;
+            let allowed_access = AccessFs::from_bits(attr.allowed_access).ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "invalid filesystem access rights")
+            })?;
+
+            let parent_file = get_file_fast!(&mut file_table, parent_fd.try_into()?);
+            ruleset.add_path_beneath(parent_file.path(), allowed_access)?;
+        }
+        LANDLOCK_RULE_NET_PORT => {
+            let attr: LandlockNetPortAttr = ctx.user_space().read_val(attr_addr)?;
+            let allowed_access = AccessNet::from_bits(attr.allowed_access).ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "invalid network access rights")
+            })?;
+
+            ruleset.add_net_port(attr.port, allowed_access)?;
+        }
+        _ => return_errno_with_message!(Errno::EINVAL, "invalid rule type"),
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_landlock_restrict_self(
+    ruleset_fd: RawFileDesc,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("ruleset_fd = {}, flags = {:#x}", ruleset_fd, flags);
+
+    check_landlock_enabled()?;
+
+    // An unprivileged thread must not be able to confuse a set-user-ID program with a
+    // restricted environment, so the new restrictions must not survive a privilege gain.
+    if !ctx.posix_thread.credentials().no_new_privs() {
+        lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+            ctx.thread_local.borrow_user_ns().as_ref(),
+            ctx.posix_thread,
+            CapSet::SYS_ADMIN,
+        ))
+        .map_err(|_| {
+            Error::with_message(
+                Errno::EPERM,
+                "`no_new_privs` or `CAP_SYS_ADMIN` is required to enforce a ruleset",
+            )
+        })?;
+    }
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, ruleset_fd.try_into()?);
+    let ruleset = as_ruleset(file.as_ref().as_ref())?;
+
+    landlock::restrict_self(ctx.posix_thread, ruleset)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn check_landlock_enabled() -> Result<()> {
+    if !lsm::is_landlock_enabled() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "Landlock is not enabled");
+    }
+
+    Ok(())
+}
+
+fn as_ruleset(file: &dyn FileLike) -> Result<&Ruleset> {
+    let Some(ruleset_file) = file.downcast_ref::<RulesetFile>() else {
+        return_errno_with_message!(Errno::EBADFD, "the file is not a Landlock ruleset");
+    };
+
+    Ok(ruleset_file.ruleset())
+}
+
+/// Queries the highest supported Landlock ABI version.
+const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+
+const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+const LANDLOCK_RULE_NET_PORT: u32 = 2;
+
+/// The `landlock_ruleset_attr` structure in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16/source/include/uapi/linux/landlock.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct LandlockRulesetAttr {
+    handled_access_fs: u64,
+    handled_access_net: u64,
+}
+
+/// The `landlock_path_beneath_attr` structure in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16/source/include/uapi/linux/landlock.h>
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct LandlockPathBeneathAttr {
+    allowed_access: u64,
+    parent_fd: i32,
+}
+
+/// The `landlock_net_port_attr` structure in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16/source/include/uapi/linux/landlock.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct LandlockNetPortAttr {
+    allowed_access: u64,
+    port: u64,
+}
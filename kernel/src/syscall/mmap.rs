@@ -6,6 +6,7 @@ use super::SyscallReturn;
 use crate::{
     fs::file::file_table::{RawFileDesc, get_file_fast},
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     vm::{
         page_cache::VmoOptions,
         perms::VmPerms,
@@ -129,6 +130,13 @@ fn do_sys_mmap(
                 vm_may_perms.remove(VmPerms::MAY_WRITE);
             }
 
+            lsm_hooks::on_mmap_file(lsm_hooks::MmapFileContext::new(
+                ctx.posix_thread,
+                &file,
+                vm_perms,
+                option.typ().is_shared(),
+            ))?;
+
             options = options
                 .may_perms(vm_may_perms)
                 .mappable(file.as_ref().as_ref())?
@@ -7,6 +7,7 @@
     expect(dead_code)
 )]
 
+pub use audit::classify_syscall;
 pub use clock_gettime::ClockId;
 use ostd::arch::cpu::context::UserContext;
 pub use timer_create::create_timer;
@@ -23,6 +24,7 @@ mod access;
 mod alarm;
 #[cfg(target_arch = "x86_64")]
 mod arch_prctl;
+mod audit;
 mod bind;
 mod brk;
 mod capget;
@@ -76,7 +78,9 @@ mod getuid;
 mod getxattr;
 mod inotify;
 mod ioctl;
+mod keyctl;
 mod kill;
+mod landlock;
 mod link;
 mod listen;
 mod listxattr;
@@ -87,6 +91,7 @@ mod mkdir;
 mod mknod;
 mod mmap;
 mod mount;
+mod mount_setattr;
 mod mprotect;
 mod mremap;
 mod msync;
@@ -373,6 +378,8 @@ impl SyscallArgument {
 
 pub fn handle_syscall(ctx: &Context, user_ctx: &mut UserContext) {
     let syscall_frame = SyscallArgument::new_from_context(user_ctx);
+    crate::security::audit::syscall_entry(ctx, syscall_frame.syscall_number, syscall_frame.args);
+
     let syscall_return = arch::syscall_dispatch(
         syscall_frame.syscall_number,
         syscall_frame.args,
@@ -380,18 +387,21 @@ pub fn handle_syscall(ctx: &Context, user_ctx: &mut UserContext) {
         user_ctx,
     );
 
-    match syscall_return {
-        Ok(return_value) => {
-            if let SyscallReturn::Return(return_value) = return_value {
-                user_ctx.set_syscall_ret(return_value as usize);
-            }
+    let return_value = match syscall_return {
+        Ok(SyscallReturn::Return(return_value)) => {
+            user_ctx.set_syscall_ret(return_value as usize);
+            Some(return_value)
         }
+        Ok(SyscallReturn::NoReturn) => None,
         Err(err) => {
             debug!("syscall return error: {:?}", err);
             let errno = err.error() as i32;
-            user_ctx.set_syscall_ret((-errno) as usize)
+            user_ctx.set_syscall_ret((-errno) as usize);
+            Some(-errno as isize)
         }
-    }
+    };
+
+    crate::security::audit::syscall_exit(ctx, return_value);
 }
 
 macro_rules! log_syscall_entry {
@@ -11,6 +11,7 @@ use crate::{
         },
     },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     syscall::constants::MAX_FILENAME_LEN,
 };
 
@@ -46,6 +47,12 @@ pub fn sys_mount(
             .get_top_path()
     };
 
+    lsm_hooks::on_sb_mount(lsm_hooks::SbMountContext::new(
+        ctx.posix_thread,
+        &dst_path,
+        mount_flags.bits(),
+    ))?;
+
     if mount_flags.contains(MountFlags::MS_REMOUNT) && mount_flags.contains(MountFlags::MS_BIND) {
         // If `MS_BIND` is specified, only the mount flags are changed.
         do_remount_mnt(&dst_path, mount_flags, ctx)?;
@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file::{InodeHandle, file_table::RawFileDesc},
+        pseudofs::NsFile,
+        vfs::path::{
+            AT_FDCWD, EmptyPathStr, FsPath, MountIdmap, MountPropType, Path, PerMountFlags,
+        },
+    },
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet},
+    security::lsm::hooks as lsm_hooks,
+    syscall::constants::MAX_FILENAME_LEN,
+    util::CopyCompat,
+};
+
+pub fn sys_mount_setattr(
+    dirfd: RawFileDesc,
+    path_addr: Vaddr,
+    flags: u32,
+    attr_addr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let path_name = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    let flags = MountSetattrFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!(
+        "dirfd = {}, path = {:?}, flags = {:?}, attr_addr = 0x{:x}, size = {}",
+        dirfd, path_name, flags, attr_addr, size
+    );
+
+    if size < size_of::<MountAttr>() {
+        return_errno_with_message!(Errno::EINVAL, "the mount attribute size is too small");
+    }
+    if size > PAGE_SIZE {
+        return_errno_with_message!(Errno::E2BIG, "the mount attribute size is too large");
+    }
+
+    let mount_attr: MountAttr = ctx.user_space().read_val_compat(attr_addr, size)?;
+    debug!("mount attr = {:?}", mount_attr);
+
+    let attr_change = MountAttrChange::try_from_mount_attr(&mount_attr, ctx)?;
+    if attr_change.is_empty() {
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    let target_path = {
+        let path_name = path_name.to_string_lossy();
+        let fs_path =
+            FsPath::from_fd_at(dirfd, &path_name, EmptyPathStr::AllowIfFlag(flags.bits()))?;
+
+        let fs_ref = ctx.thread_local.borrow_fs();
+        let path_resolver = fs_ref.resolver().read();
+        if flags.contains(MountSetattrFlags::AT_SYMLINK_NOFOLLOW) {
+            path_resolver.lookup_no_follow(&fs_path)?
+        } else {
+            path_resolver.lookup(&fs_path)?
+        }
+    }
+    .get_top_path();
+
+    let recursive = flags.contains(MountSetattrFlags::AT_RECURSIVE);
+    attr_change.apply(&target_path, recursive, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+bitflags! {
+    struct MountSetattrFlags: u32 {
+        const AT_SYMLINK_NOFOLLOW = 0x100;
+        const AT_NO_AUTOMOUNT = 0x800;
+        const AT_EMPTY_PATH = 0x1000;
+        const AT_RECURSIVE = 0x8000;
+    }
+}
+
+/// The `mount_attr` structure in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16/source/include/uapi/linux/mount.h#L140>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct MountAttr {
+    attr_set: u64,
+    attr_clr: u64,
+    propagation: u64,
+    userns_fd: u64,
+}
+
+bitflags! {
+    /// The mount attributes.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16/source/include/uapi/linux/mount.h#L123>
+    struct MountAttrFlags: u64 {
+        const MOUNT_ATTR_RDONLY = 0x00000001;
+        const MOUNT_ATTR_NOSUID = 0x00000002;
+        const MOUNT_ATTR_NODEV = 0x00000004;
+        const MOUNT_ATTR_NOEXEC = 0x00000008;
+        const MOUNT_ATTR_NOATIME = 0x00000010;
+        const MOUNT_ATTR_STRICTATIME = 0x00000020;
+        /// The mask of the atime policy.
+        ///
+        /// Note that `MOUNT_ATTR_RELATIME` is zero, so it is not a flag on its own.
+        const MOUNT_ATTR__ATIME = 0x00000070;
+        const MOUNT_ATTR_NODIRATIME = 0x00000080;
+        const MOUNT_ATTR_IDMAP = 0x00100000;
+        const MOUNT_ATTR_NOSYMFOLLOW = 0x00200000;
+    }
+}
+
+// The propagation types (the same values as the corresponding `MS_*` flags).
+const MS_UNBINDABLE: u64 = 1 << 17;
+const MS_PRIVATE: u64 = 1 << 18;
+const MS_SLAVE: u64 = 1 << 19;
+const MS_SHARED: u64 = 1 << 20;
+
+/// The changes to be applied to a mount, parsed from [`MountAttr`].
+struct MountAttrChange {
+    set_flags: PerMountFlags,
+    clear_flags: PerMountFlags,
+    idmap: Option<MountIdmap>,
+    propagation: Option<MountPropType>,
+}
+
+impl MountAttrChange {
+    fn try_from_mount_attr(mount_attr: &MountAttr, ctx: &Context) -> Result<Self> {
+        // The code below is written according to the Linux implementation.
+        // Reference: <https://elixir.bootlin.com/linux/v6.16/source/fs/namespace.c#L4921>
+
+        let propagation = match mount_attr.propagation {
+            0 => None,
+            MS_PRIVATE => Some(MountPropType::Private),
+            MS_SHARED | MS_SLAVE | MS_UNBINDABLE => {
+                return_errno_with_message!(Errno::EINVAL, "the propagation type is unsupported")
+            }
+            _ => return_errno_with_message!(Errno::EINVAL, "the propagation type is invalid"),
+        };
+
+        let attr_set = MountAttrFlags::from_bits(mount_attr.attr_set)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid attributes to set"))?;
+        let attr_clr = MountAttrFlags::from_bits(mount_attr.attr_clr)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid attributes to clear"))?;
+
+        if (attr_set | attr_clr).contains(MountAttrFlags::MOUNT_ATTR_NOSYMFOLLOW) {
+            return_errno_with_message!(Errno::EINVAL, "MOUNT_ATTR_NOSYMFOLLOW is unsupported");
+        }
+        if attr_clr.contains(MountAttrFlags::MOUNT_ATTR_IDMAP) {
+            return_errno_with_message!(Errno::EINVAL, "an ID mapping cannot be cleared");
+        }
+
+        let mut set_flags = PerMountFlags::empty();
+        let mut clear_flags = PerMountFlags::empty();
+        for (attr_flag, mount_flag) in [
+            (MountAttrFlags::MOUNT_ATTR_RDONLY, PerMountFlags::RDONLY),
+            (MountAttrFlags::MOUNT_ATTR_NOSUID, PerMountFlags::NOSUID),
+            (MountAttrFlags::MOUNT_ATTR_NODEV, PerMountFlags::NODEV),
+            (MountAttrFlags::MOUNT_ATTR_NOEXEC, PerMountFlags::NOEXEC),
+            (
+                MountAttrFlags::MOUNT_ATTR_NODIRATIME,
+                PerMountFlags::NODIRATIME,
+            ),
+        ] {
+            if attr_set.contains(attr_flag) {
+                set_flags |= mount_flag;
+            }
+            if attr_clr.contains(attr_flag) {
+                clear_flags |= mount_flag;
+            }
+        }
+
+        // The atime policy can only be changed as a whole. To change it, the caller must clear
+        // the whole atime mask and then set a single atime policy.
+        let atime_set = attr_set & MountAttrFlags::MOUNT_ATTR__ATIME;
+        let atime_clr = attr_clr & MountAttrFlags::MOUNT_ATTR__ATIME;
+        if !atime_clr.is_empty() {
+            if atime_clr != MountAttrFlags::MOUNT_ATTR__ATIME {
+                return_errno_with_message!(Errno::EINVAL, "the atime mask is partially cleared");
+            }
+            set_flags |= if atime_set.is_empty() {
+                PerMountFlags::RELATIME
+            } else if atime_set == MountAttrFlags::MOUNT_ATTR_NOATIME {
+                PerMountFlags::NOATIME
+            } else if atime_set == MountAttrFlags::MOUNT_ATTR_STRICTATIME {
+                PerMountFlags::STRICTATIME
+            } else {
+                return_errno_with_message!(Errno::EINVAL, "the atime policy is invalid");
+            };
+        } else if !atime_set.is_empty() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the atime mask must be cleared to set an atime policy"
+            );
+        }
+
+        let idmap = if attr_set.contains(MountAttrFlags::MOUNT_ATTR_IDMAP) {
+            Some(get_idmap_from_userns_fd(mount_attr.userns_fd, ctx)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            set_flags,
+            clear_flags,
+            idmap,
+            propagation,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.set_flags.is_empty()
+            && self.clear_flags.is_empty()
+            && self.idmap.is_none()
+            && self.propagation.is_none()
+    }
+
+    fn apply(self, target_path: &Path, recursive: bool, ctx: &Context) -> Result<()> {
+        target_path.set_mount_attr(self.set_flags, self.clear_flags, self.idmap, recursive, ctx)?;
+
+        if let Some(propagation) = self.propagation {
+            target_path.set_mount_propagation(propagation, recursive, ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the ID mapping from the user namespace referred to by `userns_fd`.
+fn get_idmap_from_userns_fd(userns_fd: u64, ctx: &Context) -> Result<MountIdmap> {
+    let raw_fd = RawFileDesc::try_from(userns_fd)
+        .map_err(|_| Error::with_message(Errno::EBADF, "the user namespace FD is invalid"))?;
+
+    let file = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let file_table_locked = file_table.unwrap().read();
+        file_table_locked.get_file(raw_fd.try_into()?)?.clone()
+    };
+
+    let user_ns = file
+        .downcast_ref::<InodeHandle>()
+        .and_then(|inode_handle| {
+            inode_handle
+                .downcast_open_file::<NsFile<UserNamespace>>()
+                .ok()
+                .flatten()
+        })
+        .map(|ns_file| ns_file.ns().clone())
+        .ok_or_else(|| {
+            Error::with_message(Errno::EINVAL, "the FD does not refer to a user namespace")
+        })?;
+
+    // The initial ID mapping indicates that a mount is not ID-mapped,
+    // so it cannot be used to create an ID-mapped mount.
+    if Arc::ptr_eq(&user_ns, UserNamespace::get_init_singleton()) {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "the initial user namespace cannot be used to create an ID-mapped mount"
+        );
+    }
+
+    lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+        user_ns.as_ref(),
+        ctx.posix_thread,
+        CapSet::SYS_ADMIN,
+    ))?;
+
+    Ok(MountIdmap::new(user_ns))
+}
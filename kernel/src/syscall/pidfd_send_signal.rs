@@ -40,12 +40,11 @@ pub fn sys_pidfd_send_signal(
 
     let target = get_target_from_pidfd(pidfd, flags, ctx)?;
 
-    let signal = if let Some(sig_num) = sig_num {
-        let siginfo = read_siginfo_from_user(info_ptr, sig_num, ctx)?;
+    match sig_num {
+        Some(sig_num) if info_ptr != 0 => {
+            let siginfo = read_siginfo_from_user(info_ptr, sig_num, ctx)?;
 
-        // Only check `si_code` permissions when the user explicitly provided a siginfo.
-        // When `info_ptr` is `NULL`, the kernel generates the siginfo, so no restriction applies.
-        if info_ptr != 0 {
+            // Only check `si_code` permissions when the user explicitly provided a siginfo.
             let is_self = match &target {
                 SignalTarget::Thread { tid, tgid: _ } => *tid == ctx.posix_thread.tid(),
                 SignalTarget::Process { pid } => *pid == ctx.posix_thread.tid(),
@@ -58,44 +57,44 @@ pub fn sys_pidfd_send_signal(
                     "signals with custom code can only be sent to the current thread/process"
                 );
             }
+
+            send_signal(target, Some(RawSignal::new(siginfo)), ctx)?;
         }
+        // If `info_ptr` is NULL, the kernel constructs a default `siginfo_t` structure
+        // whose fields match the values that are implicitly supplied when a signal is sent using the kill(2).
+        Some(sig_num) => send_signal(target, Some(UserSignal::new_kill(sig_num, ctx)), ctx)?,
+        None => send_signal::<UserSignal>(target, None, ctx)?,
+    }
 
-        Some(RawSignal::new(siginfo))
-    } else {
-        None
-    };
+    Ok(SyscallReturn::Return(0))
+}
+
+fn read_siginfo_from_user(info_ptr: Vaddr, sig_num: SigNum, ctx: &Context) -> Result<siginfo_t> {
+    let si = ctx.user_space().read_val::<siginfo_t>(info_ptr)?;
+    if si.si_signo != sig_num.as_u8() as i32 {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "`siginfo.si_signo` does not match the specified signal number"
+        );
+    }
+    Ok(si)
+}
 
+fn send_signal<S: Signal + Clone>(
+    target: SignalTarget,
+    signal: Option<S>,
+    ctx: &Context,
+) -> Result<()> {
     match target {
         SignalTarget::Thread { tid, tgid } => {
             let signal = signal.map(|s| Box::new(s) as Box<dyn Signal>);
-            tgkill(tid, Some(tgid), signal, ctx)?;
+            tgkill(tid, Some(tgid), signal, ctx)
         }
         SignalTarget::Process { pid } => {
             let signal = signal.map(|s| Box::new(s) as Box<dyn Signal>);
-            kill(pid, signal, ctx)?;
-        }
-        SignalTarget::ProcessGroup { pgid } => {
-            kill_group(pgid, signal, ctx)?;
-        }
-    }
-
-    Ok(SyscallReturn::Return(0))
-}
-
-fn read_siginfo_from_user(info_ptr: Vaddr, sig_num: SigNum, ctx: &Context) -> Result<siginfo_t> {
-    if info_ptr != 0 {
-        let si = ctx.user_space().read_val::<siginfo_t>(info_ptr)?;
-        if si.si_signo != sig_num.as_u8() as i32 {
-            return_errno_with_message!(
-                Errno::EINVAL,
-                "`siginfo.si_signo` does not match the specified signal number"
-            );
+            kill(pid, signal, ctx)
         }
-        Ok(si)
-    } else {
-        // If `info_ptr` is NULL, the kernel constructs a default `siginfo_t` structure
-        // whose fields match the values that are implicitly supplied when a signal is sent using the kill(2).
-        Ok(UserSignal::new_kill(sig_num, ctx).to_info())
+        SignalTarget::ProcessGroup { pgid } => kill_group(pgid, signal, ctx),
     }
 }
 
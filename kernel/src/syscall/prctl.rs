@@ -6,6 +6,7 @@ use super::SyscallReturn;
 use crate::{
     prelude::*,
     process::{
+        coredump::Dumpable,
         credentials::{SecureBits, capabilities::CapSet},
         posix_thread::{ContextPthreadAdminApi, MAX_THREAD_NAME_LEN},
         signal::sig_num::SigNum,
@@ -35,14 +36,14 @@ pub fn sys_prctl(
             ctx.user_space().write_val(write_to_addr, &write_val)?;
         }
         PrctlCmd::PR_GET_DUMPABLE => {
-            // TODO: When coredump is supported, return the actual value.
-            return Ok(SyscallReturn::Return(Dumpable::Disable as _));
+            let dumpable = ctx.user_space().vmar().process_vm().dumpable();
+            return Ok(SyscallReturn::Return(dumpable as _));
         }
         PrctlCmd::PR_SET_DUMPABLE(dumpable) => {
             if dumpable != Dumpable::Disable && dumpable != Dumpable::User {
                 return_errno_with_message!(Errno::EINVAL, "invalid dumpable attribute");
             }
-            // TODO: Implement coredump.
+            ctx.user_space().vmar().process_vm().set_dumpable(dumpable);
         }
         PrctlCmd::PR_GET_KEEPCAPS => {
             let keep_cap = {
@@ -83,6 +84,30 @@ pub fn sys_prctl(
             let credentials = ctx.credentials_mut();
             credentials.drop_bounding_capability(capability)?;
         }
+        PrctlCmd::PR_SET_NO_NEW_PRIVS => {
+            let credentials = ctx.credentials_mut();
+            credentials.set_no_new_privs();
+        }
+        PrctlCmd::PR_GET_NO_NEW_PRIVS => {
+            let credentials = ctx.posix_thread.credentials();
+            return Ok(SyscallReturn::Return(credentials.no_new_privs() as _));
+        }
+        PrctlCmd::PR_CAP_AMBIENT(ambient_cmd) => {
+            let credentials = ctx.credentials_mut();
+            match ambient_cmd {
+                PrCapAmbientCmd::IsSet(capability) => {
+                    let is_set = credentials.ambient_capset().contains(capability);
+                    return Ok(SyscallReturn::Return(is_set as _));
+                }
+                PrCapAmbientCmd::Raise(capability) => {
+                    credentials.raise_ambient_capability(capability)?;
+                }
+                PrCapAmbientCmd::Lower(capability) => {
+                    credentials.lower_ambient_capability(capability);
+                }
+                PrCapAmbientCmd::ClearAll => credentials.clear_ambient_capset(),
+            }
+        }
         PrctlCmd::PR_GET_SECUREBITS => {
             let credentials = ctx.posix_thread.credentials();
             let securebits = credentials.securebits();
@@ -142,6 +167,14 @@ const PR_SET_TIMERSLACK: i32 = 29;
 const PR_GET_TIMERSLACK: i32 = 30;
 const PR_SET_CHILD_SUBREAPER: i32 = 36;
 const PR_GET_CHILD_SUBREAPER: i32 = 37;
+const PR_SET_NO_NEW_PRIVS: i32 = 38;
+const PR_GET_NO_NEW_PRIVS: i32 = 39;
+const PR_CAP_AMBIENT: i32 = 47;
+
+const PR_CAP_AMBIENT_IS_SET: u64 = 1;
+const PR_CAP_AMBIENT_RAISE: u64 = 2;
+const PR_CAP_AMBIENT_LOWER: u64 = 3;
+const PR_CAP_AMBIENT_CLEAR_ALL: u64 = 4;
 
 #[expect(non_camel_case_types)]
 #[derive(Clone, Copy, Debug)]
@@ -162,18 +195,22 @@ pub enum PrctlCmd {
     PR_GET_TIMERSLACK,
     PR_SET_CHILD_SUBREAPER(bool),
     PR_GET_CHILD_SUBREAPER(Vaddr),
+    PR_SET_NO_NEW_PRIVS,
+    PR_GET_NO_NEW_PRIVS,
+    PR_CAP_AMBIENT(PrCapAmbientCmd),
 }
 
-#[repr(u64)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
-pub enum Dumpable {
-    Disable = 0, /* No setuid dumping */
-    User = 1,    /* Dump as user of process */
-    Root = 2,    /* Dump as root */
+/// The operations on the ambient capability set.
+#[derive(Clone, Copy, Debug)]
+pub enum PrCapAmbientCmd {
+    IsSet(CapSet),
+    Raise(CapSet),
+    Lower(CapSet),
+    ClearAll,
 }
 
 impl PrctlCmd {
-    fn from_args(option: i32, arg2: u64, _arg3: u64, _arg4: u64, _arg5: u64) -> Result<PrctlCmd> {
+    fn from_args(option: i32, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> Result<PrctlCmd> {
         match option {
             PR_SET_PDEATHSIG => {
                 let signum = SigNum::try_from(arg2 as u8)?;
@@ -181,7 +218,12 @@ impl PrctlCmd {
             }
             PR_GET_PDEATHSIG => Ok(PrctlCmd::PR_GET_PDEATHSIG(arg2 as _)),
             PR_GET_DUMPABLE => Ok(PrctlCmd::PR_GET_DUMPABLE),
-            PR_SET_DUMPABLE => Ok(PrctlCmd::PR_SET_DUMPABLE(Dumpable::try_from(arg2)?)),
+            PR_SET_DUMPABLE => {
+                let dumpable = u8::try_from(arg2).map_err(|_| {
+                    Error::with_message(Errno::EINVAL, "invalid dumpable attribute")
+                })?;
+                Ok(PrctlCmd::PR_SET_DUMPABLE(Dumpable::try_from(dumpable)?))
+            }
             PR_GET_KEEPCAPS => Ok(PrctlCmd::PR_GET_KEEPCAPS),
             PR_SET_KEEPCAPS => Ok(PrctlCmd::PR_SET_KEEPCAPS(arg2 as _)),
             PR_SET_NAME => Ok(PrctlCmd::PR_SET_NAME(arg2 as _)),
@@ -196,6 +238,40 @@ impl PrctlCmd {
             PR_GET_TIMERSLACK => Ok(PrctlCmd::PR_GET_TIMERSLACK),
             PR_SET_CHILD_SUBREAPER => Ok(PrctlCmd::PR_SET_CHILD_SUBREAPER(arg2 > 0)),
             PR_GET_CHILD_SUBREAPER => Ok(PrctlCmd::PR_GET_CHILD_SUBREAPER(arg2 as _)),
+            PR_SET_NO_NEW_PRIVS => {
+                if arg2 != 1 {
+                    return_errno_with_message!(Errno::EINVAL, "no_new_privs can only be set");
+                }
+                if arg3 != 0 || arg4 != 0 || arg5 != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "unused arguments must be zero");
+                }
+                Ok(PrctlCmd::PR_SET_NO_NEW_PRIVS)
+            }
+            PR_GET_NO_NEW_PRIVS => {
+                if arg2 != 0 || arg3 != 0 || arg4 != 0 || arg5 != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "unused arguments must be zero");
+                }
+                Ok(PrctlCmd::PR_GET_NO_NEW_PRIVS)
+            }
+            PR_CAP_AMBIENT => {
+                if arg4 != 0 || arg5 != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "unused arguments must be zero");
+                }
+                let ambient_cmd = match arg2 {
+                    PR_CAP_AMBIENT_IS_SET => PrCapAmbientCmd::IsSet(parse_capability(arg3)?),
+                    PR_CAP_AMBIENT_RAISE => PrCapAmbientCmd::Raise(parse_capability(arg3)?),
+                    PR_CAP_AMBIENT_LOWER => PrCapAmbientCmd::Lower(parse_capability(arg3)?),
+                    PR_CAP_AMBIENT_CLEAR_ALL if arg3 == 0 => PrCapAmbientCmd::ClearAll,
+                    PR_CAP_AMBIENT_CLEAR_ALL => {
+                        return_errno_with_message!(Errno::EINVAL, "unused arguments must be zero")
+                    }
+                    _ => return_errno_with_message!(
+                        Errno::EINVAL,
+                        "invalid ambient capability operation"
+                    ),
+                };
+                Ok(PrctlCmd::PR_CAP_AMBIENT(ambient_cmd))
+            }
             _ => {
                 debug!("prctl cmd number: {}", option);
                 return_errno_with_message!(Errno::EINVAL, "unsupported prctl command");
@@ -3,8 +3,12 @@
 use super::SyscallReturn;
 use crate::{
     fs,
-    fs::file::file_table::{RawFileDesc, get_file_fast},
+    fs::file::{
+        Permission,
+        file_table::{RawFileDesc, get_file_fast},
+    },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
 };
 
 pub fn sys_pread64(
@@ -25,6 +29,11 @@ pub fn sys_pread64(
 
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
+    lsm_hooks::on_file_permission(lsm_hooks::FilePermissionContext::new(
+        ctx.posix_thread,
+        &file,
+        Permission::MAY_READ,
+    ))?;
 
     if offset.checked_add(user_buf_len as i64).is_none() {
         return_errno_with_message!(Errno::EINVAL, "offset + user_buf_len overflow");
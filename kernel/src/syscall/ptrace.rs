@@ -4,17 +4,24 @@ use ostd::mm::VmIo;
 
 use super::SyscallReturn;
 #[cfg(target_arch = "x86_64")]
-use crate::arch::ptrace as arch_ptrace;
+use crate::{arch::ptrace as arch_ptrace, process::posix_thread::PosixThread};
 use crate::{
     prelude::*,
     process::{
+        credentials::capabilities::CapSet,
+        pid_table,
         posix_thread::{
             AsPosixThread,
             alien_access::AlienAccessMode,
             ptrace::{PtraceContRequest, PtraceOptions},
         },
-        signal::{constants::SIGKILL, sig_num::SigNum, signals::user::UserSignal},
+        signal::{
+            constants::{SIGKILL, SIGSTOP},
+            sig_num::SigNum,
+            signals::{kernel::KernelSignal, user::UserSignal},
+        },
     },
+    security::lsm::hooks as lsm_hooks,
     thread::{Thread, Tid},
 };
 
@@ -38,10 +45,59 @@ pub fn sys_ptrace(
             let parent_guard = ctx.process.parent().lock();
             let parent_main_thread = parent_guard.process().upgrade().unwrap().main_thread();
 
-            do_ptrace_attach(&parent_main_thread, current_thread)?;
+            do_ptrace_attach(&parent_main_thread, current_thread, ctx)?;
+        }
+        PtraceRequest::PTRACE_ATTACH => {
+            let tracee_thread = get_target_thread(tid, ctx)?;
+
+            do_ptrace_attach(&current_thread!(), tracee_thread.clone(), ctx)?;
+
+            // The tracee is sent a `SIGSTOP` after being attached. Like other stop signals, it
+            // stops the whole thread group.
+            //
+            // Reference: <https://man7.org/linux/man-pages/man2/ptrace.2.html>
+            let tracee_process = tracee_thread.as_posix_thread().unwrap().process();
+            tracee_process.enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+        }
+        PtraceRequest::PTRACE_SEIZE => {
+            if addr != 0 {
+                return_errno_with_message!(
+                    Errno::EIO,
+                    "the address of `PTRACE_SEIZE` must be zero"
+                );
+            }
+            let options = PtraceOptions::from_bits(data)
+                .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid ptrace options"))?;
+
+            let tracee_thread = get_target_thread(tid, ctx)?;
+
+            let tracer_thread = current_thread!();
+            check_ptrace_attach(&tracer_thread, &tracee_thread)?;
+            let is_capable = has_ptrace_cap(&tracee_thread, ctx);
+            let tracer = tracer_thread.as_posix_thread().unwrap();
+            tracer.seize(&tracer_thread, tracee_thread, options, is_capable)?;
+        }
+        PtraceRequest::PTRACE_DETACH => {
+            let sig_num = parse_ptrace_injected_signal(data)?;
+
+            let tracee = get_target_thread(tid, ctx)?;
+            let tracee_tid = tracee.as_posix_thread().unwrap().tid();
+            ctx.posix_thread.detach_tracee(tracee_tid, sig_num, ctx)?;
+        }
+        PtraceRequest::PTRACE_INTERRUPT => {
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            tracee.ptrace_interrupt()?;
+        }
+        PtraceRequest::PTRACE_LISTEN => {
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            tracee.ptrace_listen()?;
         }
         PtraceRequest::PTRACE_PEEKTEXT | PtraceRequest::PTRACE_PEEKDATA => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             let val = tracee.ptrace_peek_data(addr)?;
@@ -49,21 +105,21 @@ pub fn sys_ptrace(
         }
         #[cfg(target_arch = "x86_64")]
         PtraceRequest::PTRACE_PEEKUSER => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             let val = tracee.ptrace_peek_user(addr)?;
             ctx.user_space().write_val(data, &val)?;
         }
         PtraceRequest::PTRACE_POKETEXT | PtraceRequest::PTRACE_POKEDATA => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             tracee.ptrace_poke_data(addr, data)?;
         }
         #[cfg(target_arch = "x86_64")]
         PtraceRequest::PTRACE_POKEUSER => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             tracee.ptrace_poke_user(addr, data)?;
@@ -71,13 +127,13 @@ pub fn sys_ptrace(
         PtraceRequest::PTRACE_CONT => {
             let sig_num = parse_ptrace_injected_signal(data)?;
 
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             tracee.ptrace_continue(PtraceContRequest::Continue(sig_num), ctx)?;
         }
         PtraceRequest::PTRACE_KILL => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             tracee.enqueue_signal(Box::new(UserSignal::new_kill(SIGKILL, ctx)));
@@ -86,14 +142,14 @@ pub fn sys_ptrace(
         PtraceRequest::PTRACE_SINGLESTEP => {
             let sig_num = parse_ptrace_injected_signal(data)?;
 
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             tracee.ptrace_continue(PtraceContRequest::SingleStep(sig_num), ctx)?;
         }
         #[cfg(target_arch = "x86_64")]
         PtraceRequest::PTRACE_GETREGS => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             let regs = tracee.ptrace_get_regs()?;
@@ -101,7 +157,7 @@ pub fn sys_ptrace(
         }
         #[cfg(target_arch = "x86_64")]
         PtraceRequest::PTRACE_SETREGS => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             let regs = ctx
@@ -109,33 +165,95 @@ pub fn sys_ptrace(
                 .read_val::<arch_ptrace::CUserRegsStruct>(data)?;
             tracee.ptrace_set_regs(regs)?;
         }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_GETFPREGS => {
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            let fpregs = tracee.ptrace_get_fpregs()?;
+            ctx.user_space().write_bytes(data, &fpregs)?;
+        }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_SETFPREGS => {
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            let mut fpregs = [0u8; arch_ptrace::USER_FPREGS_SIZE];
+            ctx.user_space().read_bytes(data, &mut fpregs)?;
+            tracee.ptrace_set_fpregs(&fpregs)?;
+        }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_GETREGSET => {
+            let regset = parse_regset_type(addr)?;
+
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            let mut iov = ctx.user_space().read_val::<CIoVec>(data)?;
+            let bytes = do_ptrace_getregset(tracee, regset, iov.len)?;
+            ctx.user_space().write_bytes(iov.base, &bytes)?;
+
+            iov.len = bytes.len();
+            ctx.user_space().write_val(data, &iov)?;
+        }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_SETREGSET => {
+            let regset = parse_regset_type(addr)?;
+
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            let mut iov = ctx.user_space().read_val::<CIoVec>(data)?;
+            iov.len = do_ptrace_setregset(tracee, regset, &iov, ctx)?;
+            ctx.user_space().write_val(data, &iov)?;
+        }
         PtraceRequest::PTRACE_SYSCALL => {
             let sig_num = parse_ptrace_injected_signal(data)?;
 
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             tracee.ptrace_continue(PtraceContRequest::Syscall(sig_num), ctx)?;
         }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_SYSEMU => {
+            let sig_num = parse_ptrace_injected_signal(data)?;
+
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            tracee.ptrace_continue(PtraceContRequest::Sysemu(sig_num), ctx)?;
+        }
+        #[cfg(target_arch = "x86_64")]
+        PtraceRequest::PTRACE_SYSEMU_SINGLESTEP => {
+            let sig_num = parse_ptrace_injected_signal(data)?;
+
+            let tracee = get_tracee(tid, ctx)?;
+            let tracee = tracee.as_posix_thread().unwrap();
+
+            tracee.ptrace_continue(PtraceContRequest::SysemuSingleStep(sig_num), ctx)?;
+        }
         PtraceRequest::PTRACE_SETOPTIONS => {
             let options = PtraceOptions::from_bits(data)
                 .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid ptrace options"))?;
 
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             tracee.ptrace_set_options(options)?;
         }
         PtraceRequest::PTRACE_GETEVENTMSG => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let tracee = tracee.as_posix_thread().unwrap();
 
             let event = tracee.ptrace_get_event()?;
-            let eventmsg = event.map(|event| event.message()).unwrap_or(0);
+            let eventmsg = event
+                .map(|event| event.message(ctx.process.pid_ns()))
+                .unwrap_or(0);
             ctx.user_space().write_val(data, &eventmsg)?;
         }
         PtraceRequest::PTRACE_GETSIGINFO => {
-            let tracee = ctx.posix_thread.get_tracee(tid)?;
+            let tracee = get_tracee(tid, ctx)?;
             let siginfo = tracee.as_posix_thread().unwrap().ptrace_get_siginfo()?;
 
             ctx.user_space().write_val(data, &siginfo)?;
@@ -145,7 +263,38 @@ pub fn sys_ptrace(
     Ok(SyscallReturn::Return(0))
 }
 
-fn do_ptrace_attach(tracer_thread: &Arc<Thread>, tracee_thread: Arc<Thread>) -> Result<()> {
+fn do_ptrace_attach(
+    tracer_thread: &Arc<Thread>,
+    tracee_thread: Arc<Thread>,
+    ctx: &Context,
+) -> Result<()> {
+    check_ptrace_attach(tracer_thread, &tracee_thread)?;
+    let is_capable = has_ptrace_cap(&tracee_thread, ctx);
+
+    let tracer = tracer_thread.as_posix_thread().unwrap();
+    tracer.attach_to(tracer_thread, tracee_thread, is_capable)
+}
+
+/// Returns whether the current thread has `CAP_SYS_PTRACE` in the user namespace of the tracee.
+///
+/// The result is recorded when attaching the tracer. If it is false, the tracee cannot gain
+/// privileges when executing programs. Note that for `PTRACE_TRACEME`, the current thread is the
+/// tracee itself.
+///
+/// Reference: The `ptrace_link` function in
+/// <https://elixir.bootlin.com/linux/v6.16.5/source/kernel/ptrace.c>.
+fn has_ptrace_cap(tracee_thread: &Thread, ctx: &Context) -> bool {
+    let tracee_process = tracee_thread.as_posix_thread().unwrap().process();
+    let tracee_user_ns = tracee_process.user_ns().lock();
+    lsm_hooks::on_capable(lsm_hooks::CapableContext::new(
+        tracee_user_ns.as_ref(),
+        ctx.posix_thread,
+        CapSet::SYS_PTRACE,
+    ))
+    .is_ok()
+}
+
+fn check_ptrace_attach(tracer_thread: &Arc<Thread>, tracee_thread: &Arc<Thread>) -> Result<()> {
     let tracer = tracer_thread.as_posix_thread().unwrap();
     let tracee = tracee_thread.as_posix_thread().unwrap();
     if !Arc::ptr_eq(&tracer.process().main_thread(), tracer_thread) {
@@ -162,9 +311,24 @@ fn do_ptrace_attach(tracer_thread: &Arc<Thread>, tracee_thread: Arc<Thread>) ->
         );
     }
 
-    tracee.check_alien_access_from(tracer, AlienAccessMode::ATTACH_WITH_REAL_CREDS)?;
+    tracee.check_alien_access_from(tracer, AlienAccessMode::ATTACH_WITH_REAL_CREDS)
+}
+
+fn get_target_thread(tid: Tid, ctx: &Context) -> Result<Arc<Thread>> {
+    let Some(thread) = pid_table::pid_table_mut().get_thread_in_ns(tid, ctx.process.pid_ns())
+    else {
+        return_errno_with_message!(Errno::ESRCH, "the target thread does not exist");
+    };
+    Ok(thread)
+}
 
-    tracer.attach_to(tracer_thread, tracee_thread)
+/// Returns the tracee of the current thread with the thread ID in the PID namespace of the
+/// current process.
+fn get_tracee(tid: Tid, ctx: &Context) -> Result<Arc<Thread>> {
+    let thread = get_target_thread(tid, ctx)
+        .map_err(|_| Error::with_message(Errno::ESRCH, "no such tracee"))?;
+    ctx.posix_thread
+        .get_tracee(thread.as_posix_thread().unwrap().tid())
 }
 
 fn parse_ptrace_injected_signal(data: usize) -> Result<Option<SigNum>> {
@@ -179,6 +343,90 @@ fn parse_ptrace_injected_signal(data: usize) -> Result<Option<SigNum>> {
     Ok(Some(sig_num))
 }
 
+#[cfg(target_arch = "x86_64")]
+fn parse_regset_type(addr: usize) -> Result<arch_ptrace::RegSetType> {
+    u32::try_from(addr)
+        .ok()
+        .and_then(|regset| arch_ptrace::RegSetType::try_from(regset).ok())
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid register set type"))
+}
+
+/// Reads at most `max_len` bytes of the register set.
+#[cfg(target_arch = "x86_64")]
+fn do_ptrace_getregset(
+    tracee: &PosixThread,
+    regset: arch_ptrace::RegSetType,
+    max_len: usize,
+) -> Result<Vec<u8>> {
+    use arch_ptrace::RegSetType;
+
+    let bytes = match regset {
+        RegSetType::NT_PRSTATUS => {
+            let regs = tracee.ptrace_get_regs()?;
+            let len = max_len.min(size_of_val(&regs));
+            regs.as_bytes()[..len].to_vec()
+        }
+        RegSetType::NT_PRFPREG => {
+            let fpregs = tracee.ptrace_get_fpregs()?;
+            let len = max_len.min(fpregs.len());
+            fpregs[..len].to_vec()
+        }
+        RegSetType::NT_X86_XSTATE => tracee.ptrace_get_xstate(max_len)?,
+    };
+
+    Ok(bytes)
+}
+
+/// Writes the register set from the user buffer described by `iov`.
+///
+/// Returns the number of bytes consumed.
+#[cfg(target_arch = "x86_64")]
+fn do_ptrace_setregset(
+    tracee: &PosixThread,
+    regset: arch_ptrace::RegSetType,
+    iov: &CIoVec,
+    ctx: &Context,
+) -> Result<usize> {
+    use arch_ptrace::RegSetType;
+
+    // Like Linux, a partial write only modifies the leading registers of the register set.
+    let len = match regset {
+        RegSetType::NT_PRSTATUS => {
+            let mut regs = tracee.ptrace_get_regs()?;
+            let len = iov.len.min(size_of_val(&regs));
+            ctx.user_space()
+                .read_bytes(iov.base, &mut regs.as_mut_bytes()[..len])?;
+            tracee.ptrace_set_regs(regs)?;
+            len
+        }
+        RegSetType::NT_PRFPREG => {
+            let mut fpregs = tracee.ptrace_get_fpregs()?;
+            let len = iov.len.min(fpregs.len());
+            ctx.user_space().read_bytes(iov.base, &mut fpregs[..len])?;
+            tracee.ptrace_set_fpregs(&fpregs)?;
+            len
+        }
+        RegSetType::NT_X86_XSTATE => {
+            let xstate_size = tracee.ptrace_get_xstate(usize::MAX)?.len();
+            let mut xstate = vec![0u8; iov.len.min(xstate_size)];
+            ctx.user_space().read_bytes(iov.base, &mut xstate)?;
+            tracee.ptrace_set_xstate(&xstate)?;
+            xstate.len()
+        }
+    };
+
+    Ok(len)
+}
+
+/// The `struct iovec` used by `PTRACE_GETREGSET` and `PTRACE_SETREGSET`.
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIoVec {
+    base: Vaddr,
+    len: usize,
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, TryFromInt)]
 #[expect(non_camel_case_types)]
@@ -212,33 +460,45 @@ enum PtraceRequest {
     /// Sets all general-purpose registers used by the thread.
     #[cfg(target_arch = "x86_64")]
     PTRACE_SETREGS = 13,
+    /// Gets all floating-point registers used by the thread.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_GETFPREGS = 14,
+    /// Sets all floating-point registers used by the thread.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_SETFPREGS = 15,
+    /// Attaches to a thread that is already running.
+    PTRACE_ATTACH = 16,
+    /// Detaches from an attached thread.
+    PTRACE_DETACH = 17,
     /// Continues and stops at the next entry to or return from syscall.
     PTRACE_SYSCALL = 24,
+    /// Continues and stops at the next syscall, which will not be executed.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_SYSEMU = 31,
+    /// Single-steps the thread, and the next syscall will not be executed.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_SYSEMU_SINGLESTEP = 32,
     /// Sets ptrace options.
     PTRACE_SETOPTIONS = 0x4200,
     /// Gets the message of the last ptrace-event-stop.
     PTRACE_GETEVENTMSG = 0x4201,
     /// Gets the `siginfo` of the last ptrace-stop.
     PTRACE_GETSIGINFO = 0x4202,
+    /// Gets register contents.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_GETREGSET = 0x4204,
+    /// Sets register contents.
+    #[cfg(target_arch = "x86_64")]
+    PTRACE_SETREGSET = 0x4205,
+    /// Attaches to a thread that is already running without stopping it.
+    PTRACE_SEIZE = 0x4206,
+    /// Stops a thread attached by `PTRACE_SEIZE`.
+    PTRACE_INTERRUPT = 0x4207,
+    /// Restarts a stopped thread attached by `PTRACE_SEIZE`, but keeps it stopped.
+    PTRACE_LISTEN = 0x4208,
     // TODO: Support other operations.
-    // /// Gets all floating-point registers used by the thread.
-    // PTRACE_GETFPREGS = 14,
-    // /// Sets all floating-point registers used by the thread.
-    // PTRACE_SETFPREGS = 15,
-    // /// Attaches to a thread that is already running.
-    // PTRACE_ATTACH = 16,
-    // /// Detaches from an attached thread.
-    // PTRACE_DETACH = 17,
     // /// Gets all extended floating-point registers used by the thread.
     // PTRACE_GETFPXREGS = 18,
     // /// Sets all extended floating-point registers used by the thread.
     // PTRACE_SETFPXREGS = 19,
-    // /// Continues and stops at the next syscall, which will not be executed.
-    // PTRACE_SYSEMU = 31,
-    // /// Single-steps the thread, and the next syscall will not be executed.
-    // PTRACE_SYSEMU_SINGLESTEP = 32,
-    // /// Gets register contents.
-    // PTRACE_GETREGSET = 0x4204,
-    // /// Sets register contents.
-    // PTRACE_SETREGSET = 0x4205,
 }
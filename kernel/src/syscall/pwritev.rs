@@ -3,8 +3,12 @@
 use super::SyscallReturn;
 use crate::{
     fs,
-    fs::file::file_table::{RawFileDesc, get_file_fast},
+    fs::file::{
+        Permission,
+        file_table::{RawFileDesc, get_file_fast},
+    },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     util::VmReaderArray,
 };
 
@@ -86,6 +90,11 @@ fn do_sys_pwritev(
 
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
+    lsm_hooks::on_file_permission(lsm_hooks::FilePermissionContext::new(
+        ctx.posix_thread,
+        &file,
+        Permission::MAY_WRITE,
+    ))?;
 
     let user_space = ctx.user_space();
     let mut reader_array = VmReaderArray::from_user_io_vecs(&user_space, io_vec_ptr, io_vec_count)?;
@@ -144,6 +153,11 @@ fn do_sys_writev(
 
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
+    lsm_hooks::on_file_permission(lsm_hooks::FilePermissionContext::new(
+        ctx.posix_thread,
+        &file,
+        Permission::MAY_WRITE,
+    ))?;
 
     let mut total_len = 0;
 
@@ -3,8 +3,12 @@
 use super::SyscallReturn;
 use crate::{
     fs,
-    fs::file::file_table::{RawFileDesc, get_file_fast},
+    fs::file::{
+        Permission,
+        file_table::{RawFileDesc, get_file_fast},
+    },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
 };
 
 pub fn sys_read(
@@ -20,6 +24,11 @@ pub fn sys_read(
 
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
+    lsm_hooks::on_file_permission(lsm_hooks::FilePermissionContext::new(
+        ctx.posix_thread,
+        &file,
+        Permission::MAY_READ,
+    ))?;
 
     // According to <https://man7.org/linux/man-pages/man2/read.2.html>, if
     // the user specified an empty buffer, we should detect errors by checking
@@ -11,6 +11,7 @@ use crate::{
     fs,
     fs::file::file_table::{RawFileDesc, get_file_fast},
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     syscall::constants::MAX_FILENAME_LEN,
 };
 
@@ -59,6 +60,11 @@ fn removexattr(
 
     match lookup_path_for_xattr(&file_ctx, ctx) {
         Ok(path) => {
+            lsm_hooks::on_inode_removexattr(lsm_hooks::InodeXattrContext::new(
+                ctx.posix_thread,
+                &path,
+                xattr_name.full_name(),
+            ))?;
             path.remove_xattr(xattr_name)?;
             fs::vfs::notify::on_attr_change(&path);
             Ok(())
@@ -207,7 +207,8 @@ pub(super) fn access_sched_attr_with<T>(
         return f(ctx.thread.sched_attr());
     }
 
-    let Some(thread) = pid_table::pid_table_mut().get_thread(tid) else {
+    let Some(thread) = pid_table::pid_table_mut().get_thread_in_ns(tid, ctx.process.pid_ns())
+    else {
         return_errno_with_message!(Errno::ESRCH, "the target thread does not exist");
     };
     f(thread.sched_attr())
@@ -7,11 +7,12 @@ use crate::{
     fs::{
         self,
         file::{
-            InodeHandle, InodeType, SeekFrom, StatusFlags,
+            InodeHandle, InodeType, Permission, SeekFrom, StatusFlags,
             file_table::{RawFileDesc, WithFileTable},
         },
     },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
 };
 
 pub fn sys_sendfile(
@@ -100,6 +101,16 @@ pub fn sys_sendfile(
     if !out_file.access_mode().is_writable() {
         return_errno_with_message!(Errno::EBADF, "out_file is not writable");
     }
+    lsm_hooks::on_file_permission(lsm_hooks::FilePermissionContext::new(
+        ctx.posix_thread,
+        &in_file,
+        Permission::MAY_READ,
+    ))?;
+    lsm_hooks::on_file_permission(lsm_hooks::FilePermissionContext::new(
+        ctx.posix_thread,
+        &out_file,
+        Permission::MAY_WRITE,
+    ))?;
 
     // Linux returns `EINVAL` when `in_file` is a directory,
     // because directories do not implement `splice_read`.
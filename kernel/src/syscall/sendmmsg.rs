@@ -68,7 +68,7 @@ fn send_mmsg_hdrs(
         let addr = mmsghdrs_addr + size_of::<CMmsgHdr>() * i;
         let mut mmsghdr = user_space.read_val::<CMmsgHdr>(addr)?;
 
-        let sent_bytes = send_one_message(socket, &mmsghdr.msg_hdr, &user_space, flags)?;
+        let sent_bytes = send_one_message(socket, &mmsghdr.msg_hdr, &user_space, flags, ctx)?;
 
         mmsghdr.msg_len = sent_bytes as u32;
         user_space.write_val(addr, &mmsghdr)?;
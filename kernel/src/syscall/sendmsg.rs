@@ -10,6 +10,7 @@ use crate::{
         util::{MessageHeader, SendRecvFlags},
     },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     util::net::CUserMsgHdr,
 };
 
@@ -37,7 +38,7 @@ pub fn sys_sendmsg(
     };
     let socket = file.as_socket_or_err()?;
 
-    let total_bytes = send_one_message(socket, &c_user_msghdr, &user_space, flags)?;
+    let total_bytes = send_one_message(socket, &c_user_msghdr, &user_space, flags, ctx)?;
 
     Ok(SyscallReturn::Return(total_bytes as _))
 }
@@ -47,9 +48,16 @@ pub(super) fn send_one_message(
     c_user_msghdr: &CUserMsgHdr,
     user_space: &CurrentUserSpace,
     flags: SendRecvFlags,
+    ctx: &Context,
 ) -> Result<usize> {
+    let addr = c_user_msghdr.read_socket_addr_from_user()?;
+    lsm_hooks::on_socket_sendmsg(lsm_hooks::SocketSendmsgContext::new(
+        ctx.posix_thread,
+        socket,
+        addr.as_ref(),
+    ))?;
+
     let message_header = {
-        let addr = c_user_msghdr.read_socket_addr_from_user()?;
         let control_messages = c_user_msghdr.read_control_messages_from_user(user_space)?;
         MessageHeader::new(addr, control_messages)
     };
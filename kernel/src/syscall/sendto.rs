@@ -5,6 +5,7 @@ use crate::{
     fs::file::file_table::{RawFileDesc, get_file_fast},
     net::socket::util::{MessageHeader, SendRecvFlags},
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     util::net::read_socket_addr_from_user,
 };
 
@@ -32,6 +33,12 @@ pub fn sys_sendto(
     let file = get_file_fast!(&mut file_table, sockfd.try_into()?);
     let socket = file.as_socket_or_err()?;
 
+    lsm_hooks::on_socket_sendmsg(lsm_hooks::SocketSendmsgContext::new(
+        ctx.posix_thread,
+        socket,
+        socket_addr.as_ref(),
+    ))?;
+
     let message_header = MessageHeader::new(socket_addr, Vec::new());
 
     let user_space = ctx.user_space();
@@ -7,6 +7,7 @@ use crate::{
     prelude::*,
     process::ResourceType::RLIMIT_NICE,
     sched::Nice,
+    security::lsm::hooks as lsm_hooks,
     syscall::get_priority::{PriorityTarget, get_processes},
 };
 
@@ -35,6 +36,11 @@ pub fn sys_set_priority(which: i32, who: u32, prio: i32, ctx: &Context) -> Resul
         if new_nice < limit {
             return_errno!(Errno::EACCES);
         }
+        lsm_hooks::on_task_setnice(lsm_hooks::TaskSetniceContext::new(
+            ctx.posix_thread,
+            process,
+            new_nice,
+        ))?;
         // FIXME: `setpriority` updates only the per-process nice value. Fair
         // scheduler state is kept in each thread's `SchedPolicy::Fair`, so it
         // should be updated from the same source of truth.
@@ -18,10 +18,10 @@ use crate::{
         vfs::path::MountNamespace,
     },
     ipc::IpcNamespace,
-    net::uts_ns::UtsNamespace,
+    net::{net_ns::NetNamespace, uts_ns::UtsNamespace},
     prelude::*,
     process::{
-        CloneFlags, ContextSetNsAdminApi, NsProxy, NsProxyBuilder, PidFile,
+        CloneFlags, ContextSetNsAdminApi, NsProxy, NsProxyBuilder, PidFile, PidNamespace,
         check_unsupported_ns_flags, credentials::capabilities::CapSet, posix_thread::AsPosixThread,
     },
     security::lsm::hooks as lsm_hooks,
@@ -71,10 +71,10 @@ fn build_proxy_from_pid_file(
 
     check_unsupported_ns_flags(flags)?;
 
-    let target_thread = pid_file
+    let target_process = pid_file
         .process_opt()
-        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process has been reaped"))?
-        .main_thread();
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process has been reaped"))?;
+    let target_thread = target_process.main_thread();
     let target_proxy = target_thread.as_posix_thread().unwrap().ns_proxy().lock();
     let Some(target_proxy) = target_proxy.as_ref() else {
         return_errno_with_message!(Errno::ESRCH, "the target process has exited");
@@ -100,11 +100,21 @@ fn build_proxy_from_pid_file(
         set_mnt_ns(&mut builder, target_ns, ctx)?;
     }
 
+    if flags.contains(CloneFlags::CLONE_NEWNET) {
+        let target_ns = target_proxy.net_ns();
+        set_net_ns(&mut builder, target_ns, ctx)?;
+    }
+
     if flags.contains(CloneFlags::CLONE_NEWUTS) {
         let target_ns = target_proxy.uts_ns();
         set_uts_ns(&mut builder, target_ns, ctx)?;
     }
 
+    if flags.contains(CloneFlags::CLONE_NEWPID) {
+        let target_ns = target_process.pid_ns();
+        set_pid_ns(&mut builder, target_ns, ctx)?;
+    }
+
     // TODO: Support setting other namespaces from the target process.
 
     Ok(builder.build())
@@ -141,8 +151,14 @@ fn build_proxy_from_ns_file(
         || try_apply_ns_from_inode::<MountNamespace>(inode_handle, flags, |ns| {
             set_mnt_ns(&mut builder, &ns, ctx)
         })?
+        || try_apply_ns_from_inode::<NetNamespace>(inode_handle, flags, |ns| {
+            set_net_ns(&mut builder, &ns, ctx)
+        })?
         || try_apply_ns_from_inode::<UtsNamespace>(inode_handle, flags, |ns| {
             set_uts_ns(&mut builder, &ns, ctx)
+        })?
+        || try_apply_ns_from_inode::<PidNamespace>(inode_handle, flags, |ns| {
+            set_pid_ns(&mut builder, &ns, ctx)
         })?;
     // TODO: Support setting other namespaces from the ns file.
 
@@ -218,6 +234,18 @@ fn set_mnt_ns(
     Ok(())
 }
 
+fn set_net_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<NetNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    check_set_ns_perms(target_ns, ctx)?;
+
+    builder.net_ns(target_ns.clone());
+
+    Ok(())
+}
+
 fn set_uts_ns(
     builder: &mut NsProxyBuilder,
     target_ns: &Arc<UtsNamespace>,
@@ -230,6 +258,27 @@ fn set_uts_ns(
     Ok(())
 }
 
+fn set_pid_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<PidNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    check_set_ns_perms(target_ns, ctx)?;
+
+    // Only the PID namespace for children changes. The PID namespace of the current process
+    // stays the same, so the target namespace must be visible from it.
+    if !ctx.process.pid_ns().is_same_or_ancestor_of(target_ns) {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the PID namespace is not a descendant of the current PID namespace"
+        );
+    }
+
+    builder.pid_ns_for_children(target_ns.clone());
+
+    Ok(())
+}
+
 fn check_set_ns_perms<T: NsCommonOps>(target_ns: &Arc<T>, ctx: &Context) -> Result<()> {
     // Verify the thread has SYS_ADMIN capability in the target namespace's owner
     // and the current user namespace.
@@ -3,8 +3,8 @@
 use super::SyscallReturn;
 use crate::prelude::*;
 
-pub fn sys_setsid(_ctx: &Context) -> Result<SyscallReturn> {
-    let sid = current!().to_new_session()?;
+pub fn sys_setsid(ctx: &Context) -> Result<SyscallReturn> {
+    let sid = ctx.process.to_new_session()?;
 
-    Ok(SyscallReturn::Return(sid as _))
+    Ok(SyscallReturn::Return(ctx.process.nr_in_ns(sid) as _))
 }
@@ -119,6 +119,11 @@ fn setxattr(
     let mut value_reader = user_space.reader(value_ptr, value_len)?;
 
     let path = lookup_path_for_xattr(&file_ctx, ctx)?;
+    lsm_hooks::on_inode_setxattr(lsm_hooks::InodeXattrContext::new(
+        ctx.posix_thread,
+        &path,
+        xattr_name.full_name(),
+    ))?;
     path.set_xattr(xattr_name, &mut value_reader, flags)?;
     fs::vfs::notify::on_attr_change(&path);
     Ok(())
@@ -1,17 +1,22 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_bigtcp::wire::IpProtocol;
+
 use super::SyscallReturn;
 use crate::{
     fs::file::{FileLike, file_table::FdFlags},
     net::socket::{
-        ip::{DatagramSocket, IpAddressFamily, StreamSocket},
+        ip::{DatagramSocket, IpAddressFamily, RawSocket, StreamSocket},
         netlink::{
-            NetlinkRouteSocket, NetlinkUeventSocket, StandardNetlinkProtocol, is_valid_protocol,
+            NetlinkAuditSocket, NetlinkNetfilterSocket, NetlinkRouteSocket, NetlinkSockDiagSocket,
+            NetlinkUeventSocket, StandardNetlinkProtocol, is_valid_protocol,
         },
+        packet::{PacketSocket, PacketSocketKind},
         unix::{UnixDatagramSocket, UnixStreamSocket},
-        vsock::VsockStreamSocket,
+        vsock::{VsockDatagramSocket, VsockStreamSocket},
     },
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     util::net::{CSocketAddrFamily, Protocol, SOCK_TYPE_MASK, SockFlags, SockType},
 };
 
@@ -24,7 +29,15 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
         domain, sock_type, sock_flags
     );
 
+    lsm_hooks::on_socket_create(lsm_hooks::SocketCreateContext::new(
+        ctx.posix_thread,
+        domain,
+        sock_type,
+        protocol,
+    ))?;
+
     let is_nonblocking = sock_flags.contains(SockFlags::SOCK_NONBLOCK);
+    let net_ns = ctx.thread_local.borrow_ns_proxy().unwrap().net_ns().clone();
     let file_like = match (domain, sock_type) {
         (CSocketAddrFamily::AF_UNIX, SockType::SOCK_STREAM) => {
             UnixStreamSocket::new(is_nonblocking, false) as Arc<dyn FileLike>
@@ -45,30 +58,65 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
                         CSocketAddrFamily::AF_INET6 => IpAddressFamily::IPv6,
                         _ => unreachable!(),
                     };
-                    StreamSocket::new(is_nonblocking, family) as Arc<dyn FileLike>
+                    StreamSocket::new(is_nonblocking, family, net_ns) as Arc<dyn FileLike>
                 }
                 _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "unsupported protocol"),
             }
         }
-        (CSocketAddrFamily::AF_INET, SockType::SOCK_DGRAM) => {
+        (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6, SockType::SOCK_DGRAM) => {
             let protocol = Protocol::try_from(protocol)?;
             debug!("protocol = {:?}", protocol);
-            match protocol {
-                Protocol::IPPROTO_IP | Protocol::IPPROTO_UDP => {
-                    DatagramSocket::new(is_nonblocking) as Arc<dyn FileLike>
+            let family = match domain {
+                CSocketAddrFamily::AF_INET => IpAddressFamily::IPv4,
+                CSocketAddrFamily::AF_INET6 => IpAddressFamily::IPv6,
+                _ => unreachable!(),
+            };
+            match (family, protocol) {
+                (_, Protocol::IPPROTO_IP | Protocol::IPPROTO_UDP) => {
+                    DatagramSocket::new(is_nonblocking, family, net_ns) as Arc<dyn FileLike>
+                }
+                (IpAddressFamily::IPv4, Protocol::IPPROTO_ICMP)
+                | (IpAddressFamily::IPv6, Protocol::IPPROTO_ICMPV6) => {
+                    net_ns.check_ping_permission()?;
+                    RawSocket::new_ping(is_nonblocking, family, net_ns) as Arc<dyn FileLike>
                 }
                 _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "unsupported protocol"),
             }
         }
+        (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6, SockType::SOCK_RAW) => {
+            debug!("protocol = {}", protocol);
+            let protocol = match u8::try_from(protocol) {
+                Ok(0) | Err(_) => {
+                    return_errno_with_message!(Errno::EPROTONOSUPPORT, "unsupported protocol")
+                }
+                Ok(protocol) => IpProtocol::from(protocol),
+            };
+            let family = match domain {
+                CSocketAddrFamily::AF_INET => IpAddressFamily::IPv4,
+                CSocketAddrFamily::AF_INET6 => IpAddressFamily::IPv6,
+                _ => unreachable!(),
+            };
+            net_ns.check_net_raw()?;
+            RawSocket::new_raw(is_nonblocking, family, protocol, net_ns) as Arc<dyn FileLike>
+        }
         (CSocketAddrFamily::AF_NETLINK, SockType::SOCK_RAW | SockType::SOCK_DGRAM) => {
             let netlink_family = StandardNetlinkProtocol::try_from(protocol as u32);
             debug!("netlink family = {:?}", netlink_family);
             match netlink_family {
                 Ok(StandardNetlinkProtocol::ROUTE) => {
-                    NetlinkRouteSocket::new(is_nonblocking) as Arc<dyn FileLike>
+                    NetlinkRouteSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
+                }
+                Ok(StandardNetlinkProtocol::AUDIT) => {
+                    NetlinkAuditSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
                 }
                 Ok(StandardNetlinkProtocol::KOBJECT_UEVENT) => {
-                    NetlinkUeventSocket::new(is_nonblocking) as Arc<dyn FileLike>
+                    NetlinkUeventSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
+                }
+                Ok(StandardNetlinkProtocol::NETFILTER) => {
+                    NetlinkNetfilterSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
+                }
+                Ok(StandardNetlinkProtocol::SOCK_DIAG) => {
+                    NetlinkSockDiagSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
                 }
                 Ok(_) => {
                     return_errno_with_message!(
@@ -87,8 +135,26 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
                 }
             }
         }
+        (CSocketAddrFamily::AF_PACKET, SockType::SOCK_RAW | SockType::SOCK_DGRAM) => {
+            // The protocol is in network byte order.
+            let protocol = u16::from_be(protocol as u16);
+            debug!("protocol = {:#x}", protocol);
+            let kind = if matches!(sock_type, SockType::SOCK_RAW) {
+                PacketSocketKind::Raw
+            } else {
+                PacketSocketKind::Dgram
+            };
+            net_ns.check_net_raw()?;
+            PacketSocket::new(is_nonblocking, kind, protocol, net_ns) as Arc<dyn FileLike>
+        }
         (CSocketAddrFamily::AF_VSOCK, SockType::SOCK_STREAM) => {
-            VsockStreamSocket::new(is_nonblocking)? as Arc<dyn FileLike>
+            VsockStreamSocket::new(is_nonblocking, false)? as Arc<dyn FileLike>
+        }
+        (CSocketAddrFamily::AF_VSOCK, SockType::SOCK_SEQPACKET) => {
+            VsockStreamSocket::new(is_nonblocking, true)? as Arc<dyn FileLike>
+        }
+        (CSocketAddrFamily::AF_VSOCK, SockType::SOCK_DGRAM) => {
+            VsockDatagramSocket::new(is_nonblocking) as Arc<dyn FileLike>
         }
         _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "unsupported domain"),
     };
@@ -9,7 +9,8 @@ use crate::{
         vfs::path::{AT_FDCWD, EmptyPathStr, FsPath},
     },
     prelude::*,
-    process::ResourceType,
+    process::{ResourceType, credentials::file_capabilities::FileCapabilities},
+    security::lsm::hooks::{self as lsm_hooks, InodeAttrs},
 };
 
 pub fn sys_ftruncate(raw_fd: RawFileDesc, len: isize, ctx: &Context) -> Result<SyscallReturn> {
@@ -19,6 +20,11 @@ pub fn sys_ftruncate(raw_fd: RawFileDesc, len: isize, ctx: &Context) -> Result<S
 
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     let file = get_file_fast!(&mut file_table, raw_fd.try_into()?);
+    lsm_hooks::on_inode_setattr(lsm_hooks::InodeSetattrContext::new(
+        ctx.posix_thread,
+        file.path(),
+        InodeAttrs::SIZE,
+    ))?;
     file.resize(len as usize)?;
     fs::vfs::notify::on_change(file.path());
     Ok(SyscallReturn::Return(0))
@@ -39,6 +45,16 @@ pub fn sys_truncate(path_ptr: Vaddr, len: isize, ctx: &Context) -> Result<Syscal
             .read()
             .lookup(&fs_path)?
     };
+    lsm_hooks::on_path_truncate(lsm_hooks::PathTruncateContext::new(
+        ctx.posix_thread,
+        &dir_path,
+    ))?;
+    lsm_hooks::on_inode_setattr(lsm_hooks::InodeSetattrContext::new(
+        ctx.posix_thread,
+        &dir_path,
+        InodeAttrs::SIZE,
+    ))?;
+    FileCapabilities::remove_from(&dir_path)?;
     dir_path.resize(len as usize)?;
     fs::vfs::notify::on_change(&dir_path);
     Ok(SyscallReturn::Return(0))
@@ -4,6 +4,7 @@ use super::SyscallReturn;
 use crate::{
     fs::vfs::path::{AT_FDCWD, EmptyPathStr, FsPath},
     prelude::*,
+    security::lsm::hooks as lsm_hooks,
     syscall::constants::MAX_FILENAME_LEN,
 };
 
@@ -35,7 +36,14 @@ pub fn sys_umount(path_addr: Vaddr, flags: u64, ctx: &Context) -> Result<Syscall
     // to the topmost mount. If there is a mount stacked above the current thread's `cwd`, normal
     // path lookup through "." cannot access the upper mount, but umount through "." can operate
     // on the upper mount.
-    target_path.get_top_path().unmount(ctx)?;
+    let target_path = target_path.get_top_path();
+
+    lsm_hooks::on_sb_umount(lsm_hooks::SbUmountContext::new(
+        ctx.posix_thread,
+        &target_path,
+        umount_flags.bits(),
+    ))?;
+    target_path.unmount(ctx)?;
 
     Ok(SyscallReturn::Return(0))
 }
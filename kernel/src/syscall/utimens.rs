@@ -15,6 +15,7 @@ use crate::{
         vfs::path::{AT_FDCWD, EmptyPathStr, FsPath, Path},
     },
     prelude::*,
+    security::lsm::hooks::{self as lsm_hooks, InodeAttrs},
     time::{clocks::RealTimeCoarseClock, timespec_t, timeval_t},
 };
 
@@ -115,12 +116,27 @@ struct Utimbuf {
     modtime: i64,
 }
 
-fn vfs_utimes(path: &Path, times: Option<TimeSpecPair>) -> Result<SyscallReturn> {
-    let (atime, mtime, ctime) = match times {
+fn vfs_utimes(path: &Path, times: Option<TimeSpecPair>, ctx: &Context) -> Result<SyscallReturn> {
+    let attrs = match &times {
         Some(times) => {
             if !times.atime.is_valid() || !times.mtime.is_valid() {
                 return_errno_with_message!(Errno::EINVAL, "invalid time")
             }
+            let mut attrs = InodeAttrs::empty();
+            attrs.set(InodeAttrs::ATIME, !times.atime.is_utime_omit());
+            attrs.set(InodeAttrs::MTIME, !times.mtime.is_utime_omit());
+            attrs
+        }
+        None => InodeAttrs::ATIME | InodeAttrs::MTIME,
+    };
+    lsm_hooks::on_inode_setattr(lsm_hooks::InodeSetattrContext::new(
+        ctx.posix_thread,
+        path,
+        attrs,
+    ))?;
+
+    let (atime, mtime, ctime) = match times {
+        Some(times) => {
             let now = RealTimeCoarseClock::get().read_time();
             let atime = if times.atime.is_utime_omit() {
                 path.atime()
@@ -200,7 +216,7 @@ fn do_utimes(
         file.path().clone()
     };
 
-    vfs_utimes(&path, times)
+    vfs_utimes(&path, times, ctx)
 }
 
 // Sets the access and modification times for a file,
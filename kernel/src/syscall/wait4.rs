@@ -42,7 +42,8 @@ pub fn sys_wait4(
         return Ok(SyscallReturn::Return(0 as _));
     };
 
-    let (return_pid, status_code) = (wait_status.pid(), calculate_status_code(&wait_status));
+    let return_pid = ctx.process.nr_in_ns(wait_status.pid());
+    let status_code = calculate_status_code(&wait_status);
     if status_ptr != 0 {
         ctx.user_space().write_val(status_ptr as _, &status_code)?;
     }
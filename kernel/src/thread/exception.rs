@@ -15,6 +15,15 @@ use crate::{
 pub(super) fn handle_exception(ctx: &Context, user_ctx: &UserContext, exception: CpuException) {
     debug!("handle exception: {:#x?}", exception);
 
+    // Record the debug status so that the tracer can read it from the virtual DR6.
+    #[cfg(target_arch = "x86_64")]
+    if let CpuException::Debug(status) = &exception {
+        let debug_regs = ctx.thread_local.supp_user_context().debug_regs();
+        let mut regs = debug_regs.get();
+        regs.set_status(*status);
+        debug_regs.set(regs);
+    }
+
     if let Ok(page_fault_info) = PageFaultInfo::try_from(&exception) {
         let user_space = ctx.user_space();
         let vmar = user_space.vmar();
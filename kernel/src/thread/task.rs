@@ -51,7 +51,8 @@ pub fn create_new_user_task(
         if is_userspace_vaddr(child_tid_ptr) {
             // At this point, we can do almost nothing if the address is not valid and the store
             // operation fails. So we ignore the error here.
-            let _ = current_userspace!().write_val(child_tid_ptr, &current_posix_thread.tid());
+            let child_tid = current_process.nr_in_ns(current_posix_thread.tid());
+            let _ = current_userspace!().write_val(child_tid_ptr, &child_tid);
         }
 
         let ctx = Context {
@@ -85,8 +86,10 @@ pub fn create_new_user_task(
                     ctx.thread_local
                         .set_orig_syscall_ret(Some(user_ctx.syscall_ret()));
 
+                    // `PTRACE_SYSEMU` skips the syscall and its syscall-exit-stop.
+                    let is_emulated = ctx.posix_thread.is_ptrace_emulating_syscall();
                     let res = ctx.posix_thread.ptrace_may_stop_on_syscall(&ctx, user_ctx);
-                    if !matches!(res, PtraceStopResult::Interrupted) {
+                    if !is_emulated && !matches!(res, PtraceStopResult::Interrupted) {
                         handle_syscall(&ctx, user_ctx);
 
                         ctx.posix_thread.ptrace_may_stop_on_syscall(&ctx, user_ctx);
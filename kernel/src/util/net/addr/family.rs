@@ -7,6 +7,7 @@ use ostd::{mm::VmIo, task::Task};
 use super::{
     ip::{CSocketAddrInet, CSocketAddrInet6},
     netlink::CSocketAddrNetlink,
+    packet::CSocketAddrPacket,
     unix,
     vsock::CSocketAddrVm,
 };
@@ -185,6 +186,16 @@ pub fn read_socket_addr_from_user(addr: Vaddr, addr_len: usize) -> Result<Socket
             let addr = CSocketAddrVm::from_first_bytes(storage.as_bytes());
             SocketAddr::Vsock(addr.into())
         }
+        Ok(CSocketAddrFamily::AF_PACKET) => {
+            if addr_len < CSocketAddrPacket::HEADER_LEN {
+                return_errno_with_message!(Errno::EINVAL, "the socket address length is too small");
+            }
+            let addr = CSocketAddrPacket::from_first_bytes(storage.as_bytes());
+            if addr.len() > addr_len {
+                return_errno_with_message!(Errno::EINVAL, "the socket address length is too small");
+            }
+            SocketAddr::Packet(addr.into())
+        }
         _ => {
             return_errno_with_message!(
                 Errno::EAFNOSUPPORT,
@@ -276,6 +287,14 @@ pub fn write_socket_addr_with_max_len(
         SocketAddr::Vsock(addr) => {
             write_c_socket_address_util::<CSocketAddrVm, _>(*addr, dest, max_len as usize)?
         }
+        SocketAddr::Packet(addr) => {
+            // Like Linux, only the valid bytes of the physical-layer address are counted.
+            let c_socket_addr = CSocketAddrPacket::from(*addr);
+            let actual_len = c_socket_addr.len();
+            let written_len = min(actual_len, max_len as usize);
+            current_userspace!().write_bytes(dest, &c_socket_addr.as_bytes()[..written_len])?;
+            actual_len
+        }
     };
 
     Ok(actual_len as i32)
@@ -5,7 +5,7 @@ mod options;
 mod socket;
 
 pub use addr::{
-    CSocketAddrFamily, read_socket_addr_from_user, write_socket_addr_to_user,
+    CSocketAddrFamily, CSocketAddrPacket, read_socket_addr_from_user, write_socket_addr_to_user,
     write_socket_addr_with_max_len,
 };
 pub use options::{CSocketOptionLevel, new_raw_socket_option};
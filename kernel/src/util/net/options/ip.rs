@@ -2,9 +2,12 @@
 
 use int_to_c_enum::TryFromInt;
 
-use super::{RawSocketOption, SocketOption, impl_raw_socket_option};
+use super::{RawSocketOption, SocketOption, impl_raw_sock_option_set_only, impl_raw_socket_option};
 use crate::{
-    net::socket::ip::options::{Hdrincl, Recverr, Tos, Ttl},
+    net::socket::ip::options::{
+        AddMembership, DropMembership, Hdrincl, MulticastIf, MulticastLoop, MulticastTtl, Recverr,
+        Tos, Ttl,
+    },
     prelude::*,
 };
 
@@ -71,6 +74,11 @@ pub fn new_ip_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
         CIpOptionName::TTL => Ok(Box::new(Ttl::new())),
         CIpOptionName::HDRINCL => Ok(Box::new(Hdrincl::new())),
         CIpOptionName::RECVERR => Ok(Box::new(Recverr::new())),
+        CIpOptionName::MULTICAST_IF => Ok(Box::new(MulticastIf::new())),
+        CIpOptionName::MULTICAST_TTL => Ok(Box::new(MulticastTtl::new())),
+        CIpOptionName::MULTICAST_LOOP => Ok(Box::new(MulticastLoop::new())),
+        CIpOptionName::ADD_MEMBERSHIP => Ok(Box::new(AddMembership::new())),
+        CIpOptionName::DROP_MEMBERSHIP => Ok(Box::new(DropMembership::new())),
         _ => return_errno_with_message!(Errno::ENOPROTOOPT, "unsupported ip level option"),
     }
 }
@@ -79,3 +87,8 @@ impl_raw_socket_option!(Ttl);
 impl_raw_socket_option!(Tos);
 impl_raw_socket_option!(Hdrincl);
 impl_raw_socket_option!(Recverr);
+impl_raw_socket_option!(MulticastIf);
+impl_raw_socket_option!(MulticastTtl);
+impl_raw_socket_option!(MulticastLoop);
+impl_raw_sock_option_set_only!(AddMembership);
+impl_raw_sock_option_set_only!(DropMembership);
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    RawSocketOption, SocketOption, impl_raw_sock_option_get_only, impl_raw_sock_option_set_only,
+    impl_raw_socket_option,
+};
+use crate::{
+    net::socket::packet::{
+        AddMembership, DropMembership, IgnoreOutgoing, RxRing, Statistics, Version,
+    },
+    prelude::*,
+};
+
+/// Socket options for packet socket.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_packet.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, TryFromInt)]
+pub enum CPacketOptionName {
+    ADD_MEMBERSHIP = 1,
+    DROP_MEMBERSHIP = 2,
+    RECV_OUTPUT = 3,
+    RX_RING = 5,
+    STATISTICS = 6,
+    COPY_THRESH = 7,
+    AUXDATA = 8,
+    ORIGDEV = 9,
+    VERSION = 10,
+    HDRLEN = 11,
+    RESERVE = 12,
+    TX_RING = 13,
+    LOSS = 14,
+    VNET_HDR = 15,
+    TX_TIMESTAMP = 16,
+    TIMESTAMP = 17,
+    FANOUT = 18,
+    TX_HAS_OFF = 19,
+    QDISC_BYPASS = 20,
+    ROLLOVER_STATS = 21,
+    FANOUT_DATA = 22,
+    IGNORE_OUTGOING = 23,
+}
+
+pub fn new_packet_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
+    let name = CPacketOptionName::try_from(name).map_err(|_| Errno::ENOPROTOOPT)?;
+    match name {
+        CPacketOptionName::ADD_MEMBERSHIP => Ok(Box::new(AddMembership::new())),
+        CPacketOptionName::DROP_MEMBERSHIP => Ok(Box::new(DropMembership::new())),
+        CPacketOptionName::RX_RING => Ok(Box::new(RxRing::new())),
+        CPacketOptionName::STATISTICS => Ok(Box::new(Statistics::new())),
+        CPacketOptionName::VERSION => Ok(Box::new(Version::new())),
+        CPacketOptionName::IGNORE_OUTGOING => Ok(Box::new(IgnoreOutgoing::new())),
+        _ => return_errno_with_message!(Errno::ENOPROTOOPT, "unsupported packet option"),
+    }
+}
+
+impl_raw_sock_option_set_only!(AddMembership);
+impl_raw_sock_option_set_only!(DropMembership);
+impl_raw_sock_option_set_only!(RxRing);
+impl_raw_sock_option_get_only!(Statistics);
+impl_raw_socket_option!(Version);
+impl_raw_socket_option!(IgnoreOutgoing);
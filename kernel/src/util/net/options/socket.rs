@@ -2,12 +2,16 @@
 
 use ostd::mm::VmIo;
 
-use super::{RawSocketOption, impl_raw_sock_option_get_only, impl_raw_socket_option};
+use super::{
+    RawSocketOption, impl_raw_sock_option_get_only, impl_raw_sock_option_set_only,
+    impl_raw_socket_option,
+};
 use crate::{
     context::current_userspace,
     net::socket::options::{
-        AcceptConn, Broadcast, Error, KeepAlive, Linger, PassCred, PeerCred, PeerGroups, Priority,
-        RecvBuf, RecvBufForce, ReuseAddr, ReusePort, SendBuf, SendBufForce, SocketOption,
+        AcceptConn, AttachFilter, AttachReuseportCbpf, Broadcast, DetachFilter, DetachReuseportBpf,
+        Error, KeepAlive, Linger, PassCred, PeerCred, PeerGroups, Priority, RecvBuf, RecvBufForce,
+        ReuseAddr, ReusePort, SendBuf, SendBufForce, SocketOption,
     },
     prelude::*,
     process::Gid,
@@ -44,9 +48,11 @@ enum CSocketOptionName {
     PEERSEC = 31,
     SNDBUFFORCE = 32,
     RCVBUFFORCE = 33,
+    ATTACH_REUSEPORT_CBPF = 51,
     PEERGROUPS = 59,
     RCVTIMEO_NEW = 66,
     SNDTIMEO_NEW = 67,
+    DETACH_REUSEPORT_BPF = 68,
 }
 
 pub fn new_socket_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
@@ -63,10 +69,14 @@ pub fn new_socket_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
         CSocketOptionName::REUSEPORT => Ok(Box::new(ReusePort::new())),
         CSocketOptionName::PASSCRED => Ok(Box::new(PassCred::new())),
         CSocketOptionName::PEERCRED => Ok(Box::new(PeerCred::new())),
+        CSocketOptionName::ATTACH_FILTER => Ok(Box::new(AttachFilter::new())),
+        CSocketOptionName::DETACH_FILTER => Ok(Box::new(DetachFilter::new())),
         CSocketOptionName::ACCPETCONN => Ok(Box::new(AcceptConn::new())),
         CSocketOptionName::SNDBUFFORCE => Ok(Box::new(SendBufForce::new())),
         CSocketOptionName::RCVBUFFORCE => Ok(Box::new(RecvBufForce::new())),
+        CSocketOptionName::ATTACH_REUSEPORT_CBPF => Ok(Box::new(AttachReuseportCbpf::new())),
         CSocketOptionName::PEERGROUPS => Ok(Box::new(PeerGroups::new())),
+        CSocketOptionName::DETACH_REUSEPORT_BPF => Ok(Box::new(DetachReuseportBpf::new())),
         _ => return_errno_with_message!(Errno::ENOPROTOOPT, "unsupported socket-level option"),
     }
 }
@@ -82,9 +92,13 @@ impl_raw_socket_option!(Linger);
 impl_raw_socket_option!(ReusePort);
 impl_raw_socket_option!(PassCred);
 impl_raw_sock_option_get_only!(PeerCred);
+impl_raw_sock_option_set_only!(AttachFilter);
+impl_raw_sock_option_set_only!(DetachFilter);
 impl_raw_sock_option_get_only!(AcceptConn);
 impl_raw_socket_option!(SendBufForce);
 impl_raw_socket_option!(RecvBufForce);
+impl_raw_sock_option_set_only!(AttachReuseportCbpf);
+impl_raw_sock_option_set_only!(DetachReuseportBpf);
 
 // SO_PEERGROUPS is a read-only option. However, calling setsockopt on SO_PEERGROUPS will return EINVAL
 // instead of ENOPROTOOPT like other options. Therefore, we manually implement `RawSocketOption` for it.
@@ -2,14 +2,19 @@
 
 use core::{num::NonZeroU8, time::Duration};
 
+use aster_bigtcp::wire::{Ipv4Address, Ipv6Address};
 use ostd::mm::VmIo;
 
 use crate::{
     context::current_userspace,
     net::socket::{
-        ip::{options::IpTtl, stream_options::CongestionControl},
+        ip::{
+            options::{IntOrByte, IpMembership, IpMulticastIf, IpTtl, Ipv6Membership},
+            stream_options::CongestionControl,
+        },
+        packet::{MembershipType, PacketMembership, PacketStatistics, TpacketReq3, TpacketVersion},
         unix::CUserCred,
-        util::LingerOption,
+        util::{BPF_MAXINSNS, CSockFilter, LingerOption, SocketFilter},
     },
     prelude::*,
 };
@@ -121,6 +126,145 @@ impl WriteToUser for IpTtl {
     }
 }
 
+impl ReadFromUser for IntOrByte {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) >= size_of::<i32>() {
+            return Ok(IntOrByte(i32::read_from_user(addr, max_len)?));
+        }
+        if max_len == 0 {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let val = current_userspace!().read_val::<u8>(addr)?;
+        Ok(IntOrByte(val as i32))
+    }
+}
+
+impl WriteToUser for IntOrByte {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        // Like Linux, write a single byte if the buffer is too short for an `int`.
+        if (max_len as usize) < size_of::<i32>()
+            && max_len > 0
+            && let Ok(val) = u8::try_from(self.0)
+        {
+            current_userspace!().write_val(addr, &val)?;
+            return Ok(size_of::<u8>());
+        }
+
+        self.0.write_to_user(addr, max_len)
+    }
+}
+
+impl ReadFromUser for IpMulticastIf {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let max_len = max_len as usize;
+
+        // The interface can be given as `struct ip_mreqn`, `struct ip_mreq`, or `struct in_addr`.
+        if max_len >= size_of::<CIpMreqn>() {
+            let c_mreqn = current_userspace!().read_val::<CIpMreqn>(addr)?;
+            Ok(IpMulticastIf {
+                addr: Ipv4Address::from(c_mreqn.imr_address),
+                ifindex: c_mreqn.imr_ifindex as u32,
+            })
+        } else if max_len >= size_of::<CIpMreq>() {
+            let c_mreq = current_userspace!().read_val::<CIpMreq>(addr)?;
+            Ok(IpMulticastIf {
+                addr: Ipv4Address::from(c_mreq.imr_interface),
+                ifindex: 0,
+            })
+        } else if max_len >= size_of::<[u8; 4]>() {
+            let c_addr = current_userspace!().read_val::<[u8; 4]>(addr)?;
+            Ok(IpMulticastIf {
+                addr: Ipv4Address::from(c_addr),
+                ifindex: 0,
+            })
+        } else {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+    }
+}
+
+impl WriteToUser for IpMulticastIf {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let write_len = size_of::<[u8; 4]>();
+
+        if (max_len as usize) < write_len {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        current_userspace!().write_val(addr, &self.addr.octets())?;
+        Ok(write_len)
+    }
+}
+
+impl ReadFromUser for IpMembership {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let max_len = max_len as usize;
+
+        if max_len >= size_of::<CIpMreqn>() {
+            let c_mreqn = current_userspace!().read_val::<CIpMreqn>(addr)?;
+            Ok(IpMembership {
+                group: Ipv4Address::from(c_mreqn.imr_multiaddr),
+                interface: IpMulticastIf {
+                    addr: Ipv4Address::from(c_mreqn.imr_address),
+                    ifindex: c_mreqn.imr_ifindex as u32,
+                },
+            })
+        } else if max_len >= size_of::<CIpMreq>() {
+            let c_mreq = current_userspace!().read_val::<CIpMreq>(addr)?;
+            Ok(IpMembership {
+                group: Ipv4Address::from(c_mreq.imr_multiaddr),
+                interface: IpMulticastIf {
+                    addr: Ipv4Address::from(c_mreq.imr_interface),
+                    ifindex: 0,
+                },
+            })
+        } else {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+    }
+}
+
+/// An IPv4 multicast group membership (`struct ip_mreq`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpMreq {
+    imr_multiaddr: [u8; 4],
+    imr_interface: [u8; 4],
+}
+
+/// An IPv4 multicast group membership with an interface index (`struct ip_mreqn`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpMreqn {
+    imr_multiaddr: [u8; 4],
+    imr_address: [u8; 4],
+    imr_ifindex: i32,
+}
+
+impl ReadFromUser for Ipv6Membership {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) < size_of::<CIpv6Mreq>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let c_mreq = current_userspace!().read_val::<CIpv6Mreq>(addr)?;
+
+        Ok(Ipv6Membership {
+            group: Ipv6Address::from(c_mreq.ipv6mr_multiaddr),
+            ifindex: c_mreq.ipv6mr_interface,
+        })
+    }
+}
+
+/// An IPv6 multicast group membership (`struct ipv6_mreq`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpv6Mreq {
+    ipv6mr_multiaddr: [u8; 16],
+    ipv6mr_interface: u32,
+}
+
 impl WriteToUser for Option<Error> {
     fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
         let write_len = size_of::<i32>();
@@ -200,6 +344,128 @@ impl WriteToUser for CongestionControl {
     }
 }
 
+impl ReadFromUser for () {
+    fn read_from_user(_addr: Vaddr, _max_len: u32) -> Result<Self> {
+        Ok(())
+    }
+}
+
+impl ReadFromUser for Arc<SocketFilter> {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) < size_of::<CSockFprog>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let c_prog = current_userspace!().read_val::<CSockFprog>(addr)?;
+        let len = c_prog.len as usize;
+        if len == 0 || len > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the filter length is invalid");
+        }
+
+        let mut insns = vec![CSockFilter::new_zeroed(); len];
+        current_userspace!().read_bytes(c_prog.filter as Vaddr, insns.as_mut_bytes())?;
+
+        Ok(Arc::new(SocketFilter::new(&insns)?))
+    }
+}
+
+impl ReadFromUser for PacketMembership {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) < size_of::<CPacketMreq>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let c_mreq = current_userspace!().read_val::<CPacketMreq>(addr)?;
+
+        let type_ = MembershipType::try_from(c_mreq.mr_type)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "invalid membership type"))?;
+        if c_mreq.mr_alen as usize > c_mreq.mr_address.len() {
+            return_errno_with_message!(Errno::EINVAL, "the address length is too long");
+        }
+
+        Ok(PacketMembership {
+            ifindex: c_mreq.mr_ifindex as u32,
+            type_,
+            halen: c_mreq.mr_alen as u8,
+            addr: c_mreq.mr_address,
+        })
+    }
+}
+
+/// A membership of a packet socket (`struct packet_mreq`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CPacketMreq {
+    mr_ifindex: i32,
+    mr_type: u16,
+    mr_alen: u16,
+    mr_address: [u8; 8],
+}
+
+impl ReadFromUser for TpacketVersion {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let val = i32::read_from_user(addr, max_len)?;
+
+        TpacketVersion::try_from(val)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "invalid TPACKET version"))
+    }
+}
+
+impl WriteToUser for TpacketVersion {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        (*self as i32).write_to_user(addr, max_len)
+    }
+}
+
+/// The minimum length of a ring request, which is the length of `struct tpacket_req`.
+const TPACKET_REQ_LEN: usize = 16;
+
+impl ReadFromUser for TpacketReq3 {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        // Like Linux, `struct tpacket_req` is accepted for all versions. The missing fields of
+        // `struct tpacket_req3` are zero.
+        if (max_len as usize) < TPACKET_REQ_LEN {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let mut req = TpacketReq3::new_zeroed();
+        let read_len = (max_len as usize).min(size_of::<TpacketReq3>());
+        current_userspace!().read_bytes(addr, &mut req.as_mut_bytes()[..read_len])?;
+
+        Ok(req)
+    }
+}
+
+impl WriteToUser for PacketStatistics {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let mut bytes = [0u8; size_of::<u32>() * 3];
+        bytes[0..4].copy_from_slice(&self.packets.to_ne_bytes());
+        bytes[4..8].copy_from_slice(&self.drops.to_ne_bytes());
+
+        // `struct tpacket_stats_v3` has an extra field compared to `struct tpacket_stats`.
+        let len = if let Some(freeze_q_cnt) = self.freeze_q_cnt {
+            bytes[8..12].copy_from_slice(&freeze_q_cnt.to_ne_bytes());
+            12
+        } else {
+            8
+        };
+
+        let write_len = len.min(max_len as usize);
+        current_userspace!().write_bytes(addr, &bytes[..write_len])?;
+
+        Ok(write_len)
+    }
+}
+
+/// A classic BPF program (`struct sock_fprog`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CSockFprog {
+    len: u16,
+    _pad: [u8; 6],
+    filter: u64,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod)]
 struct CLinger {
@@ -13,6 +13,7 @@ use ostd::mm::Vaddr;
 
 pub use self::{
     handle::VmarHandle,
+    vm_mapping::VmMapping,
     vmar_impls::{RssType, Vmar, map::VmarMapOffset, page_fault::PageFaultInfo},
 };
 
@@ -81,6 +81,11 @@ pub struct VmMapping {
     /// Whether the mapping needs to handle surrounding pages when handling
     /// page fault.
     handle_page_faults_around: bool,
+    /// Whether the mapping is excluded from core dumps.
+    ///
+    /// This is set and cleared by `madvise` with `MADV_DONTDUMP` and
+    /// `MADV_DODUMP`, respectively.
+    is_dontdump: bool,
     /// The permissions of pages in the mapping.
     ///
     /// All pages within the same `VmMapping` have the same permissions.
@@ -112,6 +117,7 @@ impl VmMapping {
             path,
             is_shared,
             handle_page_faults_around,
+            is_dontdump: false,
             perms,
         }
     }
@@ -155,6 +161,31 @@ impl VmMapping {
         self.path.as_ref().map(|path| path.inode())
     }
 
+    /// Returns the path of the file that backs the mapping.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref()
+    }
+
+    /// Returns whether the mapping is shared.
+    pub fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+
+    /// Returns whether the mapping is excluded from core dumps.
+    pub fn is_dontdump(&self) -> bool {
+        self.is_dontdump
+    }
+
+    /// Returns whether the mapping maps device memory.
+    pub fn is_device(&self) -> bool {
+        matches!(&self.mapped_mem, MappedMemory::Device)
+    }
+
+    /// Returns the offset in the backing VMO if this mapping is VMO-backed.
+    pub fn vmo_offset(&self) -> Option<usize> {
+        self.vmo().map(|vmo| vmo.offset())
+    }
+
     /// Returns a reference to the VMO if this mapping is VMO-backed.
     pub(super) fn vmo(&self) -> Option<&MappedVmo> {
         match &self.mapped_mem {
@@ -802,6 +833,14 @@ impl VmMapping {
 
         Self { perms, ..self }
     }
+
+    /// Changes whether the mapping is excluded from core dumps.
+    pub(super) fn set_dontdump(self, is_dontdump: bool) -> Self {
+        Self {
+            is_dontdump,
+            ..self
+        }
+    }
 }
 
 /// Memory mapped by a [`VmMapping`].
@@ -962,6 +1001,7 @@ fn try_merge(left: &VmMapping, right: &VmMapping) -> Option<VmMapping> {
     let is_adjacent = left.map_end() == right.map_to_addr();
     let is_type_equal = left.is_shared == right.is_shared
         && left.handle_page_faults_around == right.handle_page_faults_around
+        && left.is_dontdump == right.is_dontdump
         && left.perms == right.perms;
 
     if !is_adjacent || !is_type_equal {
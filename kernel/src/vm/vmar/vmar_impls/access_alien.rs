@@ -38,6 +38,46 @@ impl Vmar {
         self.access_alien(vaddr, len, PageFlags::R, read)
     }
 
+    /// Reads a page for a core dump in the context of an alien thread.
+    ///
+    /// Unlike [`Self::read_alien`], this method does not fault in the pages of
+    /// anonymous mappings that have never been populated. Zeros are written for
+    /// them instead, so dumping sparse memory does not allocate new frames.
+    ///
+    /// The `VmSpace` of the process is not required to be activated on the current CPU.
+    pub fn read_page_for_dump(&self, vaddr: Vaddr, writer: &mut VmWriter) -> Result<()> {
+        debug_assert!(is_userspace_vaddr(vaddr) && vaddr.is_multiple_of(PAGE_SIZE));
+
+        let is_vmo_backed = {
+            let inner = self.inner.read();
+            let Some(vm_mapping) = inner.vm_mappings.find_one(&vaddr) else {
+                return_errno_with_message!(Errno::EFAULT, "the page is not mapped");
+            };
+            vm_mapping.vmo().is_some()
+        };
+
+        let frame = if is_vmo_backed {
+            Some(self.query_page_with_required_flags(vaddr, PageFlags::R)?)
+        } else {
+            let preempt_guard = disable_preempt();
+            let mut cursor = self
+                .vm_space()
+                .cursor(&preempt_guard, &(vaddr..vaddr + PAGE_SIZE))?;
+            match cursor.query()?.1 {
+                Some(VmQueriedItem::MappedRam { frame, .. }) => Some((*frame).clone()),
+                Some(VmQueriedItem::MappedIoMem { .. }) | None => None,
+            }
+        };
+
+        if let Some(frame) = frame {
+            frame.reader().read_fallible(writer)?;
+        } else {
+            writer.fill_zeros(PAGE_SIZE)?;
+        }
+
+        Ok(())
+    }
+
     /// Writes memory in the context of an alien thread.
     ///
     /// This method writes until one of the conditions is met:
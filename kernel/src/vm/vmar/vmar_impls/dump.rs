@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use super::{Interval, Vmar, util::get_intersected_range};
+use crate::prelude::*;
+
+impl Vmar {
+    /// Marks the memory mappings in the specified range as excluded from (or
+    /// included in) core dumps.
+    ///
+    /// The range's start and end addresses must be page-aligned.
+    ///
+    /// If the range contains unmapped pages, an [`ENOMEM`] error will be returned.
+    /// Note that pages before the unmapped hole are still marked.
+    ///
+    /// [`ENOMEM`]: Errno::ENOMEM
+    pub fn set_dontdump(&self, range: Range<usize>, is_dontdump: bool) -> Result<()> {
+        debug_assert!(range.start.is_multiple_of(PAGE_SIZE));
+        debug_assert!(range.end.is_multiple_of(PAGE_SIZE));
+
+        let mut inner = self.inner.write();
+
+        let mut marked_mappings = Vec::new();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            marked_mappings.push((vm_mapping.range(), vm_mapping.is_dontdump()))
+        }
+
+        let mut last_mapping_end = range.start;
+        for (vm_mapping_range, vm_mapping_is_dontdump) in marked_mappings {
+            if last_mapping_end < vm_mapping_range.start {
+                return_errno_with_message!(
+                    Errno::ENOMEM,
+                    "the range contains pages that are not mapped"
+                );
+            }
+            last_mapping_end = vm_mapping_range.end;
+
+            if is_dontdump == vm_mapping_is_dontdump {
+                continue;
+            }
+
+            let Some(vm_mapping) = inner.remove(&vm_mapping_range.start) else {
+                // This can happen only if the mapping is merged to the previous one (just
+                // marked before). We can skip this mapping because its property is already
+                // correct.
+                continue;
+            };
+            let vm_mapping_range = vm_mapping.range();
+            let intersected_range = get_intersected_range(&range, &vm_mapping_range);
+
+            let (left, taken, right) = vm_mapping.split_range(&intersected_range);
+
+            if let Some(left) = left {
+                inner.insert_without_try_merge(left);
+            }
+            if let Some(right) = right {
+                inner.insert_without_try_merge(right);
+            }
+
+            inner.insert_try_merge(taken.set_dontdump(is_dontdump));
+        }
+
+        if last_mapping_end < range.end {
+            return_errno_with_message!(
+                Errno::ENOMEM,
+                "the range contains pages that are not mapped"
+            );
+        }
+
+        Ok(())
+    }
+}
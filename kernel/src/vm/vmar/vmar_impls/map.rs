@@ -326,11 +326,13 @@ impl<'a> VmarMapOptions<'a> {
         // Parse the `Mappable` and prepare the `MappedMemory`.
         let (mapped_mem, io_mem) = match mappable {
             Some(Mappable::Vmo(vmo)) => {
-                if let Some(ref path) = path {
-                    debug_assert!(Arc::ptr_eq(
-                        &vmo,
-                        &path.inode().page_cache().unwrap().as_vmo().clone()
-                    ));
+                // The VMO is the page cache of the file, unless the file provides its own VMO
+                // (e.g., the ring of a packet socket). In the latter case, the path is still kept
+                // so that the mapping is attributed to the file (e.g., in `/proc/[pid]/maps`).
+                if let Some(ref path) = path
+                    && let Some(page_cache) = path.inode().page_cache()
+                {
+                    debug_assert!(Arc::ptr_eq(&vmo, &page_cache.as_vmo().clone()));
                 }
 
                 let is_writable_tracked = if let Some(ref path) = path
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod access_alien;
+mod dump;
 mod fork;
 pub(super) mod map;
 pub(super) mod page_fault;
@@ -22,7 +22,7 @@ use crate::{
         trap::{RawUserContext, TrapFrame},
     },
     cpu::PrivilegeLevel,
-    debug,
+    cpu_local_cell, debug,
     irq::{DisabledLocalIrqGuard, call_irq_callback_functions},
     mm::Vaddr,
     user::{ReturnReason, UserContextApi, UserContextApiInternal},
@@ -137,6 +137,183 @@ impl GsBase {
     }
 }
 
+/// The user-mode debug registers.
+///
+/// This includes the breakpoint address registers (DR0-DR3) and the debug control register
+/// (DR7). It also keeps a virtual copy of the debug status register (DR6) that records the
+/// status of the last debug exception triggered in the user space. The virtual DR6 is never
+/// loaded onto the CPU.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DebugRegs {
+    addrs: [usize; Self::NR_ADDRS],
+    status: DebugStatus,
+    control: usize,
+}
+
+impl DebugRegs {
+    /// The number of breakpoint address registers.
+    pub const NR_ADDRS: usize = 4;
+
+    /// The DR7 bits that can be set by the user.
+    ///
+    /// These include the local and global enable bits (L0-L3 and G0-G3), the exact breakpoint
+    /// enable bits (LE and GE), and the R/W and LEN fields for each breakpoint.
+    pub const USER_CONTROL_MASK: usize = 0xffff_03ff;
+
+    /// Returns the breakpoint address at `index`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `index` is not less than [`Self::NR_ADDRS`].
+    pub fn addr(&self, index: usize) -> usize {
+        self.addrs[index]
+    }
+
+    /// Sets the breakpoint address at `index`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `index` is not less than [`Self::NR_ADDRS`].
+    pub fn set_addr(&mut self, index: usize, addr: usize) {
+        self.addrs[index] = addr;
+    }
+
+    /// Returns the virtual debug status.
+    pub fn status(&self) -> DebugStatus {
+        self.status
+    }
+
+    /// Sets the virtual debug status.
+    pub fn set_status(&mut self, status: DebugStatus) {
+        self.status = status;
+    }
+
+    /// Returns the debug control bits.
+    pub fn control(&self) -> usize {
+        self.control
+    }
+
+    /// Sets the debug control bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArgs`] if `control` contains bits outside
+    /// [`Self::USER_CONTROL_MASK`] or if any enabled breakpoint is an I/O breakpoint.
+    ///
+    /// [`Error::InvalidArgs`]: crate::Error::InvalidArgs
+    pub fn set_control(&mut self, control: usize) -> crate::Result<()> {
+        if control & !Self::USER_CONTROL_MASK != 0 {
+            return Err(crate::Error::InvalidArgs);
+        }
+
+        // I/O breakpoints are only defined when `CR4.DE` is set. They make no sense for the user
+        // space anyway.
+        const RW_IO: usize = 0b10;
+        let has_io_breakpoints =
+            (0..Self::NR_ADDRS).any(|i| (control >> (16 + i * 4)) & 0b11 == RW_IO);
+        if has_io_breakpoints {
+            return Err(crate::Error::InvalidArgs);
+        }
+
+        self.control = control;
+        Ok(())
+    }
+
+    /// Loads this struct's breakpoint addresses and control bits onto the CPU.
+    pub fn load(&self) {
+        if self.control == 0 {
+            // Fast path: No breakpoints are enabled, so we only need to make sure that the
+            // breakpoints of other tasks are disabled.
+            if DEBUG_REGS_ENABLED.load() {
+                // SAFETY: Clearing DR7 disables all breakpoints, which does not affect kernel code.
+                unsafe { core::arch::asm!("mov dr7, {}", in(reg) 0usize, options(nostack)) };
+                DEBUG_REGS_ENABLED.store(false);
+            }
+            return;
+        }
+
+        // SAFETY:
+        // 1. DR7 is cleared first, so no breakpoint can be triggered by a half-loaded state.
+        // 2. The control bits have been validated by `set_control`. Breakpoints may be triggered
+        //    when the kernel accesses the user space, but the trap handler ignores debug
+        //    exceptions triggered in the kernel mode.
+        unsafe {
+            core::arch::asm!(
+                "mov dr7, {zero}",
+                "mov dr0, {dr0}",
+                "mov dr1, {dr1}",
+                "mov dr2, {dr2}",
+                "mov dr3, {dr3}",
+                "mov dr7, {dr7}",
+                zero = in(reg) 0usize,
+                dr0 = in(reg) self.addrs[0],
+                dr1 = in(reg) self.addrs[1],
+                dr2 = in(reg) self.addrs[2],
+                dr3 = in(reg) self.addrs[3],
+                dr7 = in(reg) self.control,
+                options(nostack),
+            )
+        };
+        DEBUG_REGS_ENABLED.store(true);
+    }
+}
+
+cpu_local_cell! {
+    /// Whether any breakpoints may be enabled in DR7 on this CPU.
+    static DEBUG_REGS_ENABLED: bool = false;
+}
+
+bitflags! {
+    /// The debug status (DR6) reported by a debug exception.
+    #[derive(Default)]
+    pub struct DebugStatus: usize {
+        /// Breakpoint 0 condition detected.
+        const B0 = 1 << 0;
+        /// Breakpoint 1 condition detected.
+        const B1 = 1 << 1;
+        /// Breakpoint 2 condition detected.
+        const B2 = 1 << 2;
+        /// Breakpoint 3 condition detected.
+        const B3 = 1 << 3;
+        /// Debug register access detected.
+        const BD = 1 << 13;
+        /// Single step.
+        const BS = 1 << 14;
+        /// Task switch.
+        const BT = 1 << 15;
+    }
+}
+
+impl DebugStatus {
+    /// The bits of DR6 that are always set.
+    ///
+    /// These bits are reserved and read as ones, or are active-low bits that are set when no
+    /// corresponding condition is detected.
+    pub const FIXED_ONES: usize = 0xffff_0ff0;
+
+    /// Returns the breakpoint condition bits (B0-B3).
+    pub fn breakpoints(&self) -> Self {
+        *self & (Self::B0 | Self::B1 | Self::B2 | Self::B3)
+    }
+
+    /// Reads and resets the debug status register of the current CPU.
+    fn take_from_cpu() -> Self {
+        let dr6: usize;
+        // SAFETY: Reading and resetting DR6 does not affect kernel code. The CPU never clears
+        // DR6, so it must be reset manually to avoid confusing the next debug exception.
+        unsafe {
+            core::arch::asm!(
+                "mov {dr6}, dr6",
+                "mov dr6, {fixed}",
+                dr6 = out(reg) dr6,
+                fixed = in(reg) Self::FIXED_ONES,
+                options(nostack),
+            )
+        };
+        Self::from_bits_truncate(dr6)
+    }
+}
+
 /// Architectural CPU exceptions (x86-64 vectors 0-31).
 ///
 /// For the authoritative specification of each vector, see the
@@ -158,7 +335,7 @@ pub enum CpuException {
     ///  0 – #DE  Divide-by-zero error.
     DivisionError,
     ///  1 – #DB  Debug.
-    Debug,
+    Debug(DebugStatus),
     ///  2 – NMI  Non-maskable interrupt.
     NonMaskableInterrupt,
     ///  3 – #BP  Breakpoint (INT3).
@@ -214,7 +391,7 @@ impl CpuException {
     pub(crate) fn new(trap_num: usize, error_code: usize) -> Option<Self> {
         let exception = match trap_num {
             0 => Self::DivisionError,
-            1 => Self::Debug,
+            1 => Self::Debug(DebugStatus::take_from_cpu()),
             2 => Self::NonMaskableInterrupt,
             3 => Self::BreakPoint,
             4 => Self::Overflow,
@@ -266,7 +443,7 @@ impl CpuException {
 
     const fn type_(&self) -> CpuExceptionType {
         match self {
-            Self::Debug => CpuExceptionType::FaultOrTrap,
+            Self::Debug(_) => CpuExceptionType::FaultOrTrap,
             Self::NonMaskableInterrupt => CpuExceptionType::Interrupt,
             Self::BreakPoint | Self::Overflow => CpuExceptionType::Trap,
             Self::DoubleFault | Self::MachineCheck => CpuExceptionType::Abort,
@@ -187,6 +187,11 @@ unsafe extern "sysv64" fn trap_handler(f: &mut TrapFrame) {
             }
             disable_local_if(was_irq_enabled);
         }
+        Some(CpuException::Debug(_)) => {
+            // The user-mode breakpoints are also triggered when the kernel accesses the user
+            // space on behalf of the user. We ignore them, as Linux does for user data
+            // breakpoints hit in the kernel mode.
+        }
         Some(exception) => {
             enable_local_if(was_irq_enabled);
             panic!(
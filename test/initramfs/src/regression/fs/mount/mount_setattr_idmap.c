@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sched.h>
+#include <stdint.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#ifndef SYS_mount_setattr
+#define SYS_mount_setattr 442
+#endif
+
+#define MOUNT_ATTR_IDMAP 0x00100000
+
+struct mount_attr_ {
+	uint64_t attr_set;
+	uint64_t attr_clr;
+	uint64_t propagation;
+	uint64_t userns_fd;
+};
+
+#define IDMAP_DIR "/tmp/idmap_mount"
+#define IDMAP_FILE IDMAP_DIR "/file"
+#define OTHER_DIR "/tmp/idmap_other"
+
+static pid_t child_pid;
+static int ready_pipe[2];
+static int done_pipe[2];
+
+static int set_idmap(const char *path, int userns_fd)
+{
+	struct mount_attr_ attr = {
+		.attr_set = MOUNT_ATTR_IDMAP,
+		.userns_fd = userns_fd,
+	};
+
+	return syscall(SYS_mount_setattr, AT_FDCWD, path, 0, &attr,
+		       sizeof(attr));
+}
+
+static int write_file(const char *path, const char *content)
+{
+	int fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	int ret = write(fd, content, strlen(content));
+	close(fd);
+	return ret;
+}
+
+static int read_file(const char *path, char *buf, size_t size)
+{
+	int fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	int ret = read(fd, buf, size - 1);
+	close(fd);
+	if (ret >= 0)
+		buf[ret] = '\0';
+	return ret;
+}
+
+FN_SETUP(mount)
+{
+	CHECK(unshare(CLONE_NEWNS));
+
+	CHECK_WITH(mkdir(IDMAP_DIR, 0755), _ret >= 0 || errno == EEXIST);
+	CHECK(mount("tmpfs", IDMAP_DIR, "tmpfs", 0, NULL));
+	CHECK_WITH(mkdir(OTHER_DIR, 0755), _ret >= 0 || errno == EEXIST);
+	CHECK(mount("tmpfs", OTHER_DIR, "tmpfs", 0, NULL));
+
+	int fd = CHECK(open(IDMAP_FILE, O_CREAT | O_WRONLY, 0644));
+	CHECK(close(fd));
+	CHECK(chown(IDMAP_FILE, 5, 6));
+}
+END_SETUP()
+
+FN_SETUP(child)
+{
+	CHECK(pipe(ready_pipe));
+	CHECK(pipe(done_pipe));
+
+	child_pid = CHECK(fork());
+	if (child_pid == 0) {
+		char byte = 0;
+
+		CHECK(close(ready_pipe[0]));
+		CHECK(close(done_pipe[1]));
+
+		CHECK(unshare(CLONE_NEWUSER));
+		CHECK(write(ready_pipe[1], &byte, 1));
+
+		// Keep the user namespace alive until the parent is done.
+		CHECK(read(done_pipe[0], &byte, 1));
+		exit(EXIT_SUCCESS);
+	}
+
+	char byte;
+	CHECK(close(ready_pipe[1]));
+	CHECK(close(done_pipe[0]));
+	CHECK_WITH(read(ready_pipe[0], &byte, 1), _ret == 1);
+}
+END_SETUP()
+
+FN_TEST(init_maps)
+{
+	char buf[128];
+
+	TEST_RES(read_file("/proc/self/uid_map", buf, sizeof(buf)),
+		 strcmp(buf, "         0          0 4294967295\n") == 0);
+	TEST_RES(read_file("/proc/self/gid_map", buf, sizeof(buf)),
+		 strcmp(buf, "         0          0 4294967295\n") == 0);
+	TEST_ERRNO(write_file("/proc/self/uid_map", "0 0 1\n"), EPERM);
+}
+END_TEST()
+
+FN_TEST(write_maps)
+{
+	char path[64];
+	char buf[128];
+
+	snprintf(path, sizeof(path), "/proc/%d/uid_map", child_pid);
+	TEST_RES(read_file(path, buf, sizeof(buf)), _ret == 0);
+	TEST_ERRNO(write_file(path, "0 1000\n"), EINVAL);
+	TEST_ERRNO(write_file(path, "0 1000 0\n"), EINVAL);
+	TEST_ERRNO(write_file(path, "0 1000 10\n5 2000 10\n"), EINVAL);
+	TEST_RES(write_file(path, "0 1000 100\n"), _ret == 11);
+	TEST_RES(read_file(path, buf, sizeof(buf)),
+		 strcmp(buf, "         0       1000        100\n") == 0);
+	TEST_ERRNO(write_file(path, "0 1000 100\n"), EPERM);
+
+	snprintf(path, sizeof(path), "/proc/%d/gid_map", child_pid);
+	TEST_RES(write_file(path, "0 2000 100\n"), _ret == 11);
+	TEST_RES(read_file(path, buf, sizeof(buf)),
+		 strcmp(buf, "         0       2000        100\n") == 0);
+}
+END_TEST()
+
+FN_TEST(invalid_userns_fd)
+{
+	int fd;
+
+	fd = TEST_SUCC(open("/proc/self/ns/user", O_RDONLY));
+	TEST_ERRNO(set_idmap(OTHER_DIR, fd), EPERM);
+	TEST_SUCC(close(fd));
+
+	fd = TEST_SUCC(open("/proc/self/ns/mnt", O_RDONLY));
+	TEST_ERRNO(set_idmap(OTHER_DIR, fd), EINVAL);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(idmapped_mount)
+{
+	char path[64];
+	struct stat st;
+
+	snprintf(path, sizeof(path), "/proc/%d/ns/user", child_pid);
+	int userns_fd = TEST_SUCC(open(path, O_RDONLY));
+
+	TEST_SUCC(set_idmap(IDMAP_DIR, userns_fd));
+	TEST_ERRNO(set_idmap(IDMAP_DIR, userns_fd), EPERM);
+
+	// The ownership stored in the filesystem is mapped down through the mount.
+	TEST_RES(stat(IDMAP_FILE, &st), st.st_uid == 1005 && st.st_gid == 2006);
+
+	// The ownership written through the mount is mapped back up.
+	TEST_SUCC(chown(IDMAP_FILE, 1010, 2020));
+	TEST_RES(stat(IDMAP_FILE, &st), st.st_uid == 1010 && st.st_gid == 2020);
+
+	// The root user has no mapping in the mount, so it cannot create files.
+	TEST_ERRNO(open(IDMAP_DIR "/new", O_CREAT | O_WRONLY, 0644), EOVERFLOW);
+
+	TEST_SUCC(close(userns_fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(done_pipe[1]));
+	CHECK(close(ready_pipe[0]));
+	CHECK_WITH(waitpid(child_pid, NULL, 0), _ret == child_pid);
+
+	CHECK(umount(IDMAP_DIR));
+	CHECK(umount(OTHER_DIR));
+}
+END_SETUP()
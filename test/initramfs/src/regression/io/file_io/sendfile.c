@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <sys/sendfile.h>
+#include <unistd.h>
+
+#define IN_FILE "/tmp/sendfile_in"
+#define OUT_FILE "/tmp/sendfile_out"
+
+static int in_fd;
+static int out_fd;
+
+FN_SETUP(files)
+{
+	in_fd = CHECK(open(IN_FILE, O_RDWR | O_CREAT | O_TRUNC, 0644));
+	CHECK_WITH(write(in_fd, "hello world", 11), _ret == 11);
+	CHECK(lseek(in_fd, 0, SEEK_SET));
+
+	out_fd = CHECK(open(OUT_FILE, O_RDWR | O_CREAT | O_TRUNC, 0644));
+}
+END_SETUP()
+
+FN_TEST(sendfile)
+{
+	char buf[16];
+	off_t offset = 6;
+
+	// Without an offset, the file offset of the input file is advanced.
+	TEST_RES(sendfile(out_fd, in_fd, NULL, 5), _ret == 5);
+	TEST_RES(lseek(in_fd, 0, SEEK_CUR), _ret == 5);
+
+	// With an offset, the offset is advanced instead.
+	TEST_RES(sendfile(out_fd, in_fd, &offset, sizeof(buf)),
+		 _ret == 5 && offset == 11);
+	TEST_RES(lseek(in_fd, 0, SEEK_CUR), _ret == 5);
+
+	TEST_RES(pread(out_fd, buf, sizeof(buf), 0),
+		 _ret == 10 && memcmp(buf, "helloworld", 10) == 0);
+}
+END_TEST()
+
+FN_TEST(sendfile_bad_files)
+{
+	int rdonly_fd = TEST_SUCC(open(OUT_FILE, O_RDONLY));
+	int wronly_fd = TEST_SUCC(open(IN_FILE, O_WRONLY));
+	int dir_fd = TEST_SUCC(open("/tmp", O_RDONLY | O_DIRECTORY));
+
+	// The input file must be readable and the output file must be writable,
+	// even if nothing is transferred.
+	TEST_ERRNO(sendfile(out_fd, wronly_fd, NULL, 0), EBADF);
+	TEST_ERRNO(sendfile(rdonly_fd, in_fd, NULL, 0), EBADF);
+
+	TEST_ERRNO(sendfile(out_fd, dir_fd, NULL, 1), EINVAL);
+
+	TEST_SUCC(close(dir_fd));
+	TEST_SUCC(close(wronly_fd));
+	TEST_SUCC(close(rdonly_fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(out_fd));
+	CHECK(close(in_fd));
+	CHECK(unlink(OUT_FILE));
+	CHECK(unlink(IN_FILE));
+}
+END_SETUP()
@@ -16,3 +16,4 @@ set -e
 ./file_io/fcntl_lock
 ./file_io/file_err
 ./file_io/iovec_err
+./file_io/sendfile
@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <net/if.h>
+#include <netinet/in.h>
+#include <poll.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PORT 8771
+#define GROUP "239.1.2.3"
+#define GROUP6 "ff15::1234"
+
+static int sk_udp;
+static int sk_udp6;
+static int lo_index;
+
+static int can_recv(int fd)
+{
+	struct pollfd pfd = { .fd = fd, .events = POLLIN };
+	char buf[16];
+
+	if (poll(&pfd, 1, 100) != 1)
+		return 0;
+	return recv(fd, buf, sizeof(buf), 0) == 5;
+}
+
+static int change_membership(int optname, const char *group,
+			     in_addr_t iface_addr)
+{
+	struct ip_mreq mreq = {
+		.imr_interface = { .s_addr = iface_addr },
+	};
+
+	if (inet_pton(AF_INET, group, &mreq.imr_multiaddr) != 1)
+		return -1;
+	return setsockopt(sk_udp, IPPROTO_IP, optname, &mreq, sizeof(mreq));
+}
+
+static int change_membership6(int optname, const char *group, int ifindex)
+{
+	struct ipv6_mreq mreq = {
+		.ipv6mr_interface = ifindex,
+	};
+
+	if (inet_pton(AF_INET6, group, &mreq.ipv6mr_multiaddr) != 1)
+		return -1;
+	return setsockopt(sk_udp6, IPPROTO_IPV6, optname, &mreq, sizeof(mreq));
+}
+
+FN_SETUP(sockets)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(PORT),
+		.sin_addr = { .s_addr = htonl(INADDR_ANY) },
+	};
+	struct sockaddr_in6 addr6 = {
+		.sin6_family = AF_INET6,
+		.sin6_port = htons(PORT),
+		.sin6_addr = IN6ADDR_ANY_INIT,
+	};
+	int enable = 1;
+
+	lo_index = CHECK_WITH(if_nametoindex("lo"), _ret != 0);
+
+	sk_udp = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	CHECK(bind(sk_udp, (struct sockaddr *)&addr, sizeof(addr)));
+
+	sk_udp6 = CHECK(socket(AF_INET6, SOCK_DGRAM, 0));
+	CHECK(setsockopt(sk_udp6, IPPROTO_IPV6, IPV6_V6ONLY, &enable,
+			 sizeof(enable)));
+	CHECK(bind(sk_udp6, (struct sockaddr *)&addr6, sizeof(addr6)));
+}
+END_SETUP()
+
+FN_TEST(ip_multicast_options)
+{
+	struct in_addr iface_addr = { .s_addr = htonl(INADDR_LOOPBACK) };
+	struct in_addr got_addr;
+	int val;
+	socklen_t len;
+
+	// Check the default values.
+	len = sizeof(val);
+	TEST_RES(getsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_TTL, &val, &len),
+		 len == sizeof(val) && val == 1);
+	TEST_RES(getsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_LOOP, &val, &len),
+		 len == sizeof(val) && val == 1);
+	len = sizeof(got_addr);
+	TEST_RES(getsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_IF, &got_addr,
+			    &len),
+		 len == sizeof(got_addr) && got_addr.s_addr == INADDR_ANY);
+
+	// The TTL must be in the range of 0 to 255, or -1 for the default value.
+	val = 8;
+	TEST_SUCC(setsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_TTL, &val,
+			     sizeof(val)));
+	len = sizeof(val);
+	TEST_RES(getsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_TTL, &val, &len),
+		 val == 8);
+	val = 256;
+	TEST_ERRNO(setsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_TTL, &val,
+			      sizeof(val)),
+		   EINVAL);
+	val = -1;
+	TEST_SUCC(setsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_TTL, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_TTL, &val, &len),
+		 val == 1);
+
+	TEST_SUCC(setsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_IF, &iface_addr,
+			     sizeof(iface_addr)));
+	len = sizeof(got_addr);
+	TEST_RES(getsockopt(sk_udp, IPPROTO_IP, IP_MULTICAST_IF, &got_addr,
+			    &len),
+		 got_addr.s_addr == htonl(INADDR_LOOPBACK));
+}
+END_TEST()
+
+FN_TEST(ip_membership)
+{
+	in_addr_t lo_addr = htonl(INADDR_LOOPBACK);
+
+	TEST_ERRNO(change_membership(IP_ADD_MEMBERSHIP, "10.0.0.1", lo_addr),
+		   EINVAL);
+
+	TEST_SUCC(change_membership(IP_ADD_MEMBERSHIP, GROUP, lo_addr));
+	TEST_ERRNO(change_membership(IP_ADD_MEMBERSHIP, GROUP, lo_addr),
+		   EADDRINUSE);
+
+	TEST_SUCC(change_membership(IP_DROP_MEMBERSHIP, GROUP, lo_addr));
+	TEST_ERRNO(change_membership(IP_DROP_MEMBERSHIP, GROUP, lo_addr),
+		   EADDRNOTAVAIL);
+}
+END_TEST()
+
+FN_TEST(ip_multicast_on_lo)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(PORT),
+	};
+	in_addr_t lo_addr = htonl(INADDR_LOOPBACK);
+	struct in_addr iface_addr = { .s_addr = lo_addr };
+	int sender = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+
+	TEST_RES(inet_pton(AF_INET, GROUP, &addr.sin_addr), _ret == 1);
+	TEST_SUCC(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_IF, &iface_addr,
+			     sizeof(iface_addr)));
+
+	// Packets to groups that are not joined are not received.
+	TEST_RES(sendto(sender, "hello", 5, 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == 5);
+	TEST_RES(can_recv(sk_udp), _ret == 0);
+
+	TEST_SUCC(change_membership(IP_ADD_MEMBERSHIP, GROUP, lo_addr));
+	TEST_RES(sendto(sender, "hello", 5, 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == 5);
+	TEST_RES(can_recv(sk_udp), _ret == 1);
+
+	TEST_SUCC(change_membership(IP_DROP_MEMBERSHIP, GROUP, lo_addr));
+	TEST_SUCC(close(sender));
+}
+END_TEST()
+
+FN_TEST(ipv6_multicast_options)
+{
+	int val;
+	socklen_t len = sizeof(val);
+
+	TEST_RES(getsockopt(sk_udp6, IPPROTO_IPV6, IPV6_MULTICAST_HOPS, &val,
+			    &len),
+		 len == sizeof(val) && val == 1);
+	TEST_RES(getsockopt(sk_udp6, IPPROTO_IPV6, IPV6_MULTICAST_LOOP, &val,
+			    &len),
+		 len == sizeof(val) && val == 1);
+	TEST_RES(getsockopt(sk_udp6, IPPROTO_IPV6, IPV6_MULTICAST_IF, &val,
+			    &len),
+		 len == sizeof(val) && val == 0);
+
+	TEST_SUCC(setsockopt(sk_udp6, IPPROTO_IPV6, IPV6_MULTICAST_IF,
+			     &lo_index, sizeof(lo_index)));
+	TEST_RES(getsockopt(sk_udp6, IPPROTO_IPV6, IPV6_MULTICAST_IF, &val,
+			    &len),
+		 val == lo_index);
+
+	val = 0;
+	TEST_SUCC(setsockopt(sk_udp6, IPPROTO_IPV6, IPV6_MULTICAST_IF, &val,
+			     sizeof(val)));
+}
+END_TEST()
+
+FN_TEST(ipv6_membership)
+{
+	TEST_ERRNO(change_membership6(IPV6_ADD_MEMBERSHIP, "::1", lo_index),
+		   EINVAL);
+
+	TEST_SUCC(change_membership6(IPV6_ADD_MEMBERSHIP, GROUP6, lo_index));
+	TEST_ERRNO(change_membership6(IPV6_ADD_MEMBERSHIP, GROUP6, lo_index),
+		   EADDRINUSE);
+
+	TEST_SUCC(change_membership6(IPV6_DROP_MEMBERSHIP, GROUP6, lo_index));
+	TEST_ERRNO(change_membership6(IPV6_DROP_MEMBERSHIP, GROUP6, lo_index),
+		   EADDRNOTAVAIL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_udp6));
+	CHECK(close(sk_udp));
+}
+END_SETUP()
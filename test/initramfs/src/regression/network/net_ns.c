@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <net/if.h>
+#include <netinet/in.h>
+#include <sched.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PORT 8123
+
+static int listen_fd;
+
+static int bind_any(int port)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(port),
+		.sin_addr = { .s_addr = htonl(INADDR_ANY) },
+	};
+	int fd = socket(AF_INET, SOCK_STREAM, 0);
+	if (fd < 0)
+		return -1;
+
+	if (bind(fd, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+		int err = errno;
+		close(fd);
+		errno = err;
+		return -1;
+	}
+
+	return fd;
+}
+
+FN_SETUP(listen)
+{
+	listen_fd = CHECK(bind_any(PORT));
+	CHECK(listen(listen_fd, 1));
+}
+END_SETUP()
+
+FN_TEST(only_loopback)
+{
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWNET));
+
+		struct if_nameindex *ifs = if_nameindex();
+		CHECK_WITH(ifs, _ret != NULL);
+		CHECK_WITH(ifs[0].if_index, _ret != 0);
+		CHECK_WITH(strcmp(ifs[0].if_name, "lo"), _ret == 0);
+		CHECK_WITH(ifs[1].if_index, _ret == 0);
+		if_freenameindex(ifs);
+
+		_exit(EXIT_SUCCESS);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+}
+END_TEST()
+
+FN_TEST(isolated_ports)
+{
+	// The port is in use in the current namespace.
+	TEST_ERRNO(bind_any(PORT), EADDRINUSE);
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int old_ns = CHECK(open("/proc/self/ns/net", O_RDONLY));
+		CHECK(unshare(CLONE_NEWNET));
+
+		// The port is free in the new namespace.
+		int fd = CHECK(bind_any(PORT));
+		CHECK(listen(fd, 1));
+
+		// The port is in use again after switching back to the original namespace.
+		CHECK(setns(old_ns, CLONE_NEWNET));
+		CHECK_WITH(bind_any(PORT), _ret < 0 && errno == EADDRINUSE);
+		CHECK(close(fd));
+
+		_exit(EXIT_SUCCESS);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(listen_fd));
+}
+END_SETUP()
@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <linux/netfilter.h>
+#include <linux/netfilter/nf_tables.h>
+#include <linux/netfilter/nfnetlink.h>
+#include <linux/rtnetlink.h>
+#include <net/if.h>
+#include <poll.h>
+#include <sched.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define TABLE_NAME "test_table"
+#define CHAIN_NAME "test_chain"
+#define PORT 8769
+
+struct batch {
+	char buf[1024];
+	size_t len;
+	unsigned int seq;
+};
+
+static int sk_nf;
+static int sk_udp;
+
+static struct nlmsghdr *add_msg(struct batch *batch, int type, int flags,
+				int family)
+{
+	struct nlmsghdr *hdr = (struct nlmsghdr *)(batch->buf + batch->len);
+	struct nfgenmsg *nfg = NLMSG_DATA(hdr);
+
+	memset(hdr, 0, NLMSG_SPACE(sizeof(*nfg)));
+	hdr->nlmsg_len = NLMSG_LENGTH(sizeof(*nfg));
+	hdr->nlmsg_type = type;
+	hdr->nlmsg_flags = NLM_F_REQUEST | flags;
+	hdr->nlmsg_seq = ++batch->seq;
+	nfg->nfgen_family = family;
+	nfg->version = NFNETLINK_V0;
+
+	return hdr;
+}
+
+static struct nlattr *add_attr(struct nlmsghdr *hdr, int type, const void *data,
+			       int len)
+{
+	struct nlattr *attr =
+		(struct nlattr *)((char *)hdr + NLMSG_ALIGN(hdr->nlmsg_len));
+
+	attr->nla_type = type;
+	attr->nla_len = NLA_HDRLEN + len;
+	if (data != NULL)
+		memcpy((char *)attr + NLA_HDRLEN, data, len);
+	hdr->nlmsg_len = NLMSG_ALIGN(hdr->nlmsg_len) + NLA_ALIGN(attr->nla_len);
+
+	return attr;
+}
+
+static void add_u32_attr(struct nlmsghdr *hdr, int type, unsigned int value)
+{
+	unsigned int be_value = htonl(value);
+
+	add_attr(hdr, type, &be_value, sizeof(be_value));
+}
+
+static void end_nested_attr(struct nlmsghdr *hdr, struct nlattr *attr)
+{
+	attr->nla_type |= NLA_F_NESTED;
+	attr->nla_len = (char *)hdr + hdr->nlmsg_len - (char *)attr;
+}
+
+static void end_msg(struct batch *batch, struct nlmsghdr *hdr)
+{
+	batch->len += NLMSG_ALIGN(hdr->nlmsg_len);
+}
+
+static void begin_batch(struct batch *batch)
+{
+	struct nlmsghdr *hdr;
+
+	batch->len = 0;
+	hdr = add_msg(batch, NFNL_MSG_BATCH_BEGIN, 0, AF_UNSPEC);
+	((struct nfgenmsg *)NLMSG_DATA(hdr))->res_id =
+		htons(NFNL_SUBSYS_NFTABLES);
+	end_msg(batch, hdr);
+}
+
+static void end_batch(struct batch *batch)
+{
+	struct nlmsghdr *hdr;
+
+	hdr = add_msg(batch, NFNL_MSG_BATCH_END, 0, AF_UNSPEC);
+	((struct nfgenmsg *)NLMSG_DATA(hdr))->res_id =
+		htons(NFNL_SUBSYS_NFTABLES);
+	end_msg(batch, hdr);
+}
+
+static int nft_type(int msg_type)
+{
+	return (NFNL_SUBSYS_NFTABLES << 8) | msg_type;
+}
+
+// Sends the messages and returns the first error in the responses.
+//
+// The responses are delivered before `send` returns, so they can be received
+// without blocking.
+static int send_msgs(struct batch *batch, int *resp_type)
+{
+	char buf[4096];
+	int error = 0;
+
+	if (send(sk_nf, batch->buf, batch->len, 0) != (ssize_t)batch->len)
+		return -1;
+
+	for (;;) {
+		ssize_t len = recv(sk_nf, buf, sizeof(buf), MSG_DONTWAIT);
+		if (len < 0 && errno == EAGAIN) {
+			errno = 0;
+			break;
+		}
+		if (len < 0)
+			return -1;
+
+		for (struct nlmsghdr *hdr = (struct nlmsghdr *)buf;
+		     NLMSG_OK(hdr, len); hdr = NLMSG_NEXT(hdr, len)) {
+			if (hdr->nlmsg_type != NLMSG_ERROR) {
+				if (resp_type != NULL)
+					*resp_type = hdr->nlmsg_type;
+				continue;
+			}
+
+			struct nlmsgerr *err = NLMSG_DATA(hdr);
+			if (err->error != 0 && error == 0)
+				error = -err->error;
+		}
+	}
+
+	if (error != 0) {
+		errno = error;
+		return -1;
+	}
+	return 0;
+}
+
+static int new_table(const char *name, int flags)
+{
+	struct batch batch = {};
+	struct nlmsghdr *hdr;
+
+	begin_batch(&batch);
+	hdr = add_msg(&batch, nft_type(NFT_MSG_NEWTABLE),
+		      NLM_F_ACK | NLM_F_CREATE | flags, NFPROTO_INET);
+	add_attr(hdr, NFTA_TABLE_NAME, name, strlen(name) + 1);
+	end_msg(&batch, hdr);
+	end_batch(&batch);
+
+	return send_msgs(&batch, NULL);
+}
+
+static int del_table(const char *name)
+{
+	struct batch batch = {};
+	struct nlmsghdr *hdr;
+
+	begin_batch(&batch);
+	hdr = add_msg(&batch, nft_type(NFT_MSG_DELTABLE), NLM_F_ACK,
+		      NFPROTO_INET);
+	add_attr(hdr, NFTA_TABLE_NAME, name, strlen(name) + 1);
+	end_msg(&batch, hdr);
+	end_batch(&batch);
+
+	return send_msgs(&batch, NULL);
+}
+
+// Returns the type of the response, or -1 with `errno` set if the table does
+// not exist.
+static int get_table(const char *name)
+{
+	struct batch batch = {};
+	struct nlmsghdr *hdr;
+	int resp_type = 0;
+
+	hdr = add_msg(&batch, nft_type(NFT_MSG_GETTABLE), NLM_F_ACK,
+		      NFPROTO_INET);
+	add_attr(hdr, NFTA_TABLE_NAME, name, strlen(name) + 1);
+	end_msg(&batch, hdr);
+
+	if (send_msgs(&batch, &resp_type) < 0)
+		return -1;
+	return resp_type;
+}
+
+static void send_udp(void)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(PORT),
+		.sin_addr = { .s_addr = htonl(INADDR_LOOPBACK) },
+	};
+
+	CHECK_WITH(sendto(sk_udp, "hello", 5, 0, (struct sockaddr *)&addr,
+			  sizeof(addr)),
+		   _ret == 5);
+}
+
+static int can_recv_udp(void)
+{
+	struct pollfd pfd = { .fd = sk_udp, .events = POLLIN };
+	char buf[16];
+
+	if (poll(&pfd, 1, 100) != 1)
+		return 0;
+	return recv(sk_udp, buf, sizeof(buf), 0) == 5;
+}
+
+FN_SETUP(net_ns)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct ifinfomsg ifi;
+	} req = {
+		.hdr = {
+			.nlmsg_len = sizeof(req),
+			.nlmsg_type = RTM_NEWLINK,
+			.nlmsg_flags = NLM_F_REQUEST,
+		},
+		.ifi = {
+			.ifi_family = AF_UNSPEC,
+			.ifi_flags = IFF_UP,
+			.ifi_change = IFF_UP,
+		},
+	};
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(PORT),
+		.sin_addr = { .s_addr = htonl(INADDR_LOOPBACK) },
+	};
+
+	// Use a new network namespace so that the ruleset of the initial one is
+	// not affected.
+	CHECK(unshare(CLONE_NEWNET));
+
+	// The loopback iface must be up to send packets on Linux.
+	int fd = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+	req.ifi.ifi_index = CHECK_WITH(if_nametoindex("lo"), _ret != 0);
+	CHECK_WITH(send(fd, &req, sizeof(req), 0), _ret == sizeof(req));
+	CHECK(close(fd));
+
+	sk_nf = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_NETFILTER));
+
+	sk_udp = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	CHECK(bind(sk_udp, (struct sockaddr *)&addr, sizeof(addr)));
+}
+END_SETUP()
+
+FN_TEST(table_lifecycle)
+{
+	TEST_ERRNO(get_table(TABLE_NAME), ENOENT);
+
+	TEST_SUCC(new_table(TABLE_NAME, NLM_F_EXCL));
+	TEST_ERRNO(new_table(TABLE_NAME, NLM_F_EXCL), EEXIST);
+	TEST_RES(get_table(TABLE_NAME), _ret == nft_type(NFT_MSG_NEWTABLE));
+
+	TEST_SUCC(del_table(TABLE_NAME));
+	TEST_ERRNO(del_table(TABLE_NAME), ENOENT);
+	TEST_ERRNO(get_table(TABLE_NAME), ENOENT);
+}
+END_TEST()
+
+FN_TEST(batch_abort)
+{
+	struct batch batch = {};
+	struct nlmsghdr *hdr;
+
+	// A batch is aborted as a whole if any request fails.
+	begin_batch(&batch);
+	for (int i = 0; i < 2; i++) {
+		hdr = add_msg(&batch, nft_type(NFT_MSG_NEWTABLE),
+			      NLM_F_ACK | NLM_F_CREATE | NLM_F_EXCL,
+			      NFPROTO_INET);
+		add_attr(hdr, NFTA_TABLE_NAME, TABLE_NAME,
+			 sizeof(TABLE_NAME));
+		end_msg(&batch, hdr);
+	}
+	end_batch(&batch);
+
+	TEST_ERRNO(send_msgs(&batch, NULL), EEXIST);
+	TEST_ERRNO(get_table(TABLE_NAME), ENOENT);
+}
+END_TEST()
+
+FN_TEST(not_in_batch)
+{
+	struct batch batch = {};
+	struct nlmsghdr *hdr;
+
+	// Requests that modify the ruleset must be sent in a batch.
+	hdr = add_msg(&batch, nft_type(NFT_MSG_NEWTABLE),
+		      NLM_F_ACK | NLM_F_CREATE, NFPROTO_INET);
+	add_attr(hdr, NFTA_TABLE_NAME, TABLE_NAME, sizeof(TABLE_NAME));
+	end_msg(&batch, hdr);
+
+	TEST_ERRNO(send_msgs(&batch, NULL), EINVAL);
+	TEST_ERRNO(get_table(TABLE_NAME), ENOENT);
+}
+END_TEST()
+
+FN_TEST(input_policy_drop)
+{
+	struct batch batch = {};
+	struct nlmsghdr *hdr;
+	struct nlattr *hook;
+
+	TEST_SUCC(new_table(TABLE_NAME, NLM_F_EXCL));
+
+	// Add a base chain that drops all incoming packets.
+	begin_batch(&batch);
+	hdr = add_msg(&batch, nft_type(NFT_MSG_NEWCHAIN),
+		      NLM_F_ACK | NLM_F_CREATE | NLM_F_EXCL, NFPROTO_INET);
+	add_attr(hdr, NFTA_CHAIN_TABLE, TABLE_NAME, sizeof(TABLE_NAME));
+	add_attr(hdr, NFTA_CHAIN_NAME, CHAIN_NAME, sizeof(CHAIN_NAME));
+	hook = add_attr(hdr, NFTA_CHAIN_HOOK, NULL, 0);
+	add_u32_attr(hdr, NFTA_HOOK_HOOKNUM, NF_INET_LOCAL_IN);
+	add_u32_attr(hdr, NFTA_HOOK_PRIORITY, 0);
+	end_nested_attr(hdr, hook);
+	add_u32_attr(hdr, NFTA_CHAIN_POLICY, NF_DROP);
+	add_attr(hdr, NFTA_CHAIN_TYPE, "filter", sizeof("filter"));
+	end_msg(&batch, hdr);
+	end_batch(&batch);
+	TEST_SUCC(send_msgs(&batch, NULL));
+
+	send_udp();
+	TEST_RES(can_recv_udp(), _ret == 0);
+
+	// Packets are accepted again after the table is deleted.
+	TEST_SUCC(del_table(TABLE_NAME));
+	send_udp();
+	TEST_RES(can_recv_udp(), _ret == 1);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_udp));
+	CHECK(close(sk_nf));
+}
+END_SETUP()
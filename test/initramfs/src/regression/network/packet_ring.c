@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <linux/if_packet.h>
+#include <sys/mman.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PAGE_SIZE 4096
+
+static int sk_packet;
+
+static int set_ring(unsigned int block_size, unsigned int block_nr,
+		    unsigned int frame_size, unsigned int frame_nr)
+{
+	struct tpacket_req3 req = {
+		.tp_block_size = block_size,
+		.tp_block_nr = block_nr,
+		.tp_frame_size = frame_size,
+		.tp_frame_nr = frame_nr,
+	};
+
+	return setsockopt(sk_packet, SOL_PACKET, PACKET_RX_RING, &req,
+			  sizeof(req));
+}
+
+FN_SETUP(socket)
+{
+	int version = TPACKET_V3;
+
+	// Sockets with a zero protocol see no frames.
+	sk_packet = CHECK(socket(AF_PACKET, SOCK_RAW, 0));
+	CHECK(setsockopt(sk_packet, SOL_PACKET, PACKET_VERSION, &version,
+			 sizeof(version)));
+}
+END_SETUP()
+
+FN_TEST(invalid_ring)
+{
+	// The block size must be a multiple of the page size.
+	TEST_ERRNO(set_ring(0, 1, 2048, 0), EINVAL);
+	TEST_ERRNO(set_ring(PAGE_SIZE + 1024, 1, 1024, 5), EINVAL);
+
+	// The block size must be a positive `int`.
+	TEST_ERRNO(set_ring(0x80000000, 1, 0x80000000, 1), EINVAL);
+
+	// The frame size must be aligned.
+	TEST_ERRNO(set_ring(PAGE_SIZE, 1, 2047, 2), EINVAL);
+
+	// The number of frames must match.
+	TEST_ERRNO(set_ring(PAGE_SIZE, 4, 2048, 7), EINVAL);
+
+	// The number of frames must not overflow.
+	TEST_ERRNO(set_ring(PAGE_SIZE, 0x8000000, 128, 0), EINVAL);
+
+	// The ring must fit in the memory.
+	TEST_ERRNO(set_ring(0x40000000, 0x100000, 0x40000000, 0x100000),
+		   ENOMEM);
+}
+END_TEST()
+
+FN_TEST(mmap_ring)
+{
+	size_t size = PAGE_SIZE * 4;
+	void *ring;
+
+	// The ring cannot be mapped before it is set up.
+	TEST_ERRNO(mmap(NULL, size, PROT_READ | PROT_WRITE, MAP_SHARED,
+			sk_packet, 0),
+		   EINVAL);
+
+	TEST_SUCC(set_ring(PAGE_SIZE, 4, 2048, 8));
+	TEST_ERRNO(set_ring(PAGE_SIZE, 4, 2048, 8), EBUSY);
+
+	ring = TEST_SUCC(mmap(NULL, size, PROT_READ | PROT_WRITE, MAP_SHARED,
+			      sk_packet, 0));
+	TEST_ERRNO(set_ring(0, 0, 0, 0), EBUSY);
+	TEST_SUCC(munmap(ring, size));
+
+	// The ring can be torn down after it is unmapped.
+	TEST_SUCC(set_ring(0, 0, 0, 0));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_packet));
+}
+END_SETUP()
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <netinet/in.h>
+#include <netinet/ip.h>
+#include <netinet/udp.h>
+#include <poll.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PORT 8768
+
+static int sk_udp;
+static int sk_udp6;
+
+// Receives the next UDP packet to `PORT` and returns the length of its payload.
+//
+// For IPv4, raw sockets see packets including the IP header. For IPv6, they see only the IP
+// payload.
+static ssize_t recv_udp(int fd, int is_ipv6, char *payload, size_t len)
+{
+	char buf[256];
+
+	for (;;) {
+		struct pollfd pfd = { .fd = fd, .events = POLLIN };
+		if (poll(&pfd, 1, 1000) != 1) {
+			errno = ETIMEDOUT;
+			return -1;
+		}
+
+		ssize_t ret = recv(fd, buf, sizeof(buf), 0);
+		if (ret < 0)
+			return ret;
+
+		size_t offset = is_ipv6 ? 0 : sizeof(struct iphdr);
+		if ((size_t)ret < offset + sizeof(struct udphdr))
+			continue;
+
+		struct udphdr *udp = (struct udphdr *)(buf + offset);
+		if (udp->dest != htons(PORT))
+			continue;
+
+		size_t payload_len = ret - offset - sizeof(struct udphdr);
+		if (payload_len > len)
+			payload_len = len;
+		memcpy(payload, udp + 1, payload_len);
+		return payload_len;
+	}
+}
+
+static ssize_t send_udp(int fd, const char *addr_str)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(PORT),
+	};
+
+	if (inet_pton(AF_INET, addr_str, &addr.sin_addr) != 1)
+		return -1;
+	return sendto(fd, "hello", 5, 0, (struct sockaddr *)&addr,
+		      sizeof(addr));
+}
+
+FN_SETUP(sockets)
+{
+	sk_udp = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	sk_udp6 = CHECK(socket(AF_INET6, SOCK_DGRAM, 0));
+}
+END_SETUP()
+
+FN_TEST(unbound_addr)
+{
+	struct sockaddr_in addr;
+	socklen_t addrlen = sizeof(addr);
+	int fd = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_UDP));
+
+	// Like Linux, the port of raw sockets is the protocol number.
+	TEST_RES(getsockname(fd, (struct sockaddr *)&addr, &addrlen),
+		 addrlen == sizeof(addr) && addr.sin_family == AF_INET &&
+			 addr.sin_addr.s_addr == htonl(INADDR_ANY) &&
+			 addr.sin_port == htons(IPPROTO_UDP));
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(unbound_recv_on_lo)
+{
+	char payload[16];
+	int fd = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_UDP));
+
+	// Unbound raw sockets see packets on all ifaces, including the loopback iface.
+	TEST_RES(send_udp(sk_udp, "127.0.0.1"), _ret == 5);
+	TEST_RES(recv_udp(fd, 0, payload, sizeof(payload)),
+		 _ret == 5 && memcmp(payload, "hello", 5) == 0);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(bind_any_and_rebind)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_addr = { .s_addr = htonl(INADDR_ANY) },
+	};
+	char payload[16];
+	int fd = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_UDP));
+
+	// Binding to the unspecified address is the same as not binding.
+	TEST_SUCC(bind(fd, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_RES(send_udp(sk_udp, "127.0.0.1"), _ret == 5);
+	TEST_RES(recv_udp(fd, 0, payload, sizeof(payload)),
+		 _ret == 5 && memcmp(payload, "hello", 5) == 0);
+
+	// The socket can be bound to a specific address later.
+	addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+	TEST_SUCC(bind(fd, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_RES(send_udp(sk_udp, "127.0.0.1"), _ret == 5);
+	TEST_RES(recv_udp(fd, 0, payload, sizeof(payload)),
+		 _ret == 5 && memcmp(payload, "hello", 5) == 0);
+
+	struct sockaddr_in bound_addr;
+	socklen_t addrlen = sizeof(bound_addr);
+	TEST_RES(getsockname(fd, (struct sockaddr *)&bound_addr, &addrlen),
+		 bound_addr.sin_addr.s_addr == htonl(INADDR_LOOPBACK));
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(unbound_recv_on_lo_v6)
+{
+	struct sockaddr_in6 addr = {
+		.sin6_family = AF_INET6,
+		.sin6_port = htons(PORT),
+		.sin6_addr = IN6ADDR_LOOPBACK_INIT,
+	};
+	char payload[16];
+	int fd = TEST_SUCC(socket(AF_INET6, SOCK_RAW, IPPROTO_UDP));
+
+	TEST_RES(sendto(sk_udp6, "world", 5, 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == 5);
+	TEST_RES(recv_udp(fd, 1, payload, sizeof(payload)),
+		 _ret == 5 && memcmp(payload, "world", 5) == 0);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(send_on_lo)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_addr = { .s_addr = htonl(INADDR_LOOPBACK) },
+	};
+	struct udphdr udp = {
+		.source = htons(PORT),
+		.dest = htons(PORT),
+		.len = htons(sizeof(udp)),
+	};
+	char payload[16];
+	int sender = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_UDP));
+	int receiver = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_UDP));
+
+	// An unbound raw socket sends packets via the iface that reaches the destination.
+	TEST_RES(sendto(sender, &udp, sizeof(udp), 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == sizeof(udp));
+	TEST_RES(recv_udp(receiver, 0, payload, sizeof(payload)), _ret == 0);
+
+	TEST_SUCC(close(receiver));
+	TEST_SUCC(close(sender));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_udp6));
+	CHECK(close(sk_udp));
+}
+END_SETUP()
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <linux/filter.h>
+#include <netinet/in.h>
+#include <poll.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PORT 8770
+#define NOBODY_UID 65534
+
+static struct sockaddr_in addr;
+
+static int new_reuseport_socket(int type)
+{
+	int enable = 1;
+	int fd = socket(AF_INET, type, 0);
+
+	if (fd < 0)
+		return -1;
+	if (setsockopt(fd, SOL_SOCKET, SO_REUSEPORT, &enable, sizeof(enable)) <
+	    0) {
+		close(fd);
+		return -1;
+	}
+	return fd;
+}
+
+static int bind_reuseport_socket(int type)
+{
+	int fd = new_reuseport_socket(type);
+
+	if (fd < 0)
+		return -1;
+	if (bind(fd, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+		int saved_errno = errno;
+		close(fd);
+		errno = saved_errno;
+		return -1;
+	}
+	return fd;
+}
+
+static int can_recv(int fd)
+{
+	struct pollfd pfd = { .fd = fd, .events = POLLIN };
+	char buf[16];
+
+	if (poll(&pfd, 1, 100) != 1)
+		return 0;
+	return recv(fd, buf, sizeof(buf), 0) == 5;
+}
+
+FN_SETUP(addr)
+{
+	addr.sin_family = AF_INET;
+	addr.sin_port = htons(PORT);
+	addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+}
+END_SETUP()
+
+FN_TEST(udp_group)
+{
+	int sk1 = TEST_SUCC(bind_reuseport_socket(SOCK_DGRAM));
+	int sk2 = TEST_SUCC(bind_reuseport_socket(SOCK_DGRAM));
+	int sender = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+
+	// Sockets without `SO_REUSEPORT` cannot join the group.
+	int fd = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+	TEST_ERRNO(bind(fd, (struct sockaddr *)&addr, sizeof(addr)),
+		   EADDRINUSE);
+	TEST_SUCC(close(fd));
+
+	// Steer all packets to the second socket in the group.
+	struct sock_filter code[] = {
+		BPF_STMT(BPF_RET | BPF_K, 1),
+	};
+	struct sock_fprog prog = {
+		.len = sizeof(code) / sizeof(code[0]),
+		.filter = code,
+	};
+	TEST_SUCC(setsockopt(sk1, SOL_SOCKET, SO_ATTACH_REUSEPORT_CBPF, &prog,
+			     sizeof(prog)));
+
+	for (int i = 0; i < 4; i++) {
+		TEST_RES(sendto(sender, "hello", 5, 0, (struct sockaddr *)&addr,
+				sizeof(addr)),
+			 _ret == 5);
+		TEST_RES(can_recv(sk2), _ret == 1);
+		TEST_RES(can_recv(sk1), _ret == 0);
+	}
+
+	TEST_SUCC(close(sender));
+	TEST_SUCC(close(sk2));
+	TEST_SUCC(close(sk1));
+}
+END_TEST()
+
+FN_TEST(tcp_group)
+{
+	int sk1 = TEST_SUCC(bind_reuseport_socket(SOCK_STREAM));
+	int sk2 = TEST_SUCC(bind_reuseport_socket(SOCK_STREAM));
+
+	TEST_SUCC(listen(sk1, 1));
+	TEST_SUCC(listen(sk2, 1));
+
+	TEST_SUCC(close(sk2));
+	TEST_SUCC(close(sk1));
+}
+END_TEST()
+
+FN_TEST(different_uid)
+{
+	int status;
+	int sk = TEST_SUCC(bind_reuseport_socket(SOCK_DGRAM));
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(setresuid(NOBODY_UID, NOBODY_UID, NOBODY_UID));
+
+		// Sockets owned by other users cannot join the group.
+		CHECK_WITH(bind_reuseport_socket(SOCK_DGRAM),
+			   _ret == -1 && errno == EADDRINUSE);
+
+		_exit(0);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	// The group is not affected by the failed attempt.
+	int sk2 = TEST_SUCC(bind_reuseport_socket(SOCK_DGRAM));
+
+	TEST_SUCC(close(sk2));
+	TEST_SUCC(close(sk));
+}
+END_TEST()
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <fcntl.h>
+#include <linux/fib_rules.h>
+#include <linux/rtnetlink.h>
+#include <net/if.h>
+#include <sched.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define TEST_TABLE 100
+#define TEST_RULE_PRIORITY 1000
+
+#define IP_FORWARD_PATH "/proc/sys/net/ipv4/ip_forward"
+
+struct nl_req {
+	struct nlmsghdr hdr;
+	union {
+		struct ifinfomsg ifi;
+		struct rtmsg rtm;
+		struct fib_rule_hdr frh;
+	};
+	char attrs[256];
+};
+
+static int lo_index;
+
+static void add_attr(struct nlmsghdr *hdr, int type, const void *data, int len)
+{
+	struct rtattr *attr =
+		(struct rtattr *)((char *)hdr + NLMSG_ALIGN(hdr->nlmsg_len));
+
+	attr->rta_type = type;
+	attr->rta_len = RTA_LENGTH(len);
+	memcpy(RTA_DATA(attr), data, len);
+	hdr->nlmsg_len = NLMSG_ALIGN(hdr->nlmsg_len) + RTA_ALIGN(attr->rta_len);
+}
+
+// Sends a request and returns the error in the acknowledgment.
+static int send_req(struct nl_req *req)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct nlmsgerr err;
+	} resp;
+	int fd = socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE);
+	if (fd < 0)
+		return -1;
+
+	if (send(fd, req, req->hdr.nlmsg_len, 0) != req->hdr.nlmsg_len ||
+	    recv(fd, &resp, sizeof(resp), 0) < (ssize_t)sizeof(resp) ||
+	    resp.hdr.nlmsg_type != NLMSG_ERROR) {
+		close(fd);
+		errno = EIO;
+		return -1;
+	}
+	close(fd);
+
+	if (resp.err.error != 0) {
+		errno = -resp.err.error;
+		return -1;
+	}
+	return 0;
+}
+
+static void init_route_req(struct nl_req *req, int type, int flags,
+			   int table, const char *dst, int dst_len)
+{
+	struct in_addr addr;
+
+	memset(req, 0, sizeof(*req));
+	req->hdr.nlmsg_len = NLMSG_LENGTH(sizeof(struct rtmsg));
+	req->hdr.nlmsg_type = type;
+	req->hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK | flags;
+	req->rtm.rtm_family = AF_INET;
+	req->rtm.rtm_dst_len = dst_len;
+	req->rtm.rtm_table = table;
+	req->rtm.rtm_protocol = RTPROT_STATIC;
+	req->rtm.rtm_scope = RT_SCOPE_UNIVERSE;
+	req->rtm.rtm_type = RTN_UNICAST;
+
+	CHECK_WITH(inet_pton(AF_INET, dst, &addr), _ret == 1);
+	add_attr(&req->hdr, RTA_DST, &addr, sizeof(addr));
+}
+
+static int add_route(int table, const char *dst, int dst_len, int flags)
+{
+	struct nl_req req;
+
+	init_route_req(&req, RTM_NEWROUTE, NLM_F_CREATE | flags, table, dst,
+		       dst_len);
+	add_attr(&req.hdr, RTA_OIF, &lo_index, sizeof(lo_index));
+
+	return send_req(&req);
+}
+
+static int del_route(int table, const char *dst, int dst_len)
+{
+	struct nl_req req;
+
+	init_route_req(&req, RTM_DELROUTE, 0, table, dst, dst_len);
+	req.rtm.rtm_scope = RT_SCOPE_NOWHERE;
+
+	return send_req(&req);
+}
+
+// Looks up the route to `dst` and returns the output iface, or -1 with `errno`
+// set on failure. The table reported by the kernel is stored in `table`.
+static int get_route(const char *dst, int flags, int *table)
+{
+	struct nl_req req;
+	char buf[1024];
+	struct nlmsghdr *hdr = (struct nlmsghdr *)buf;
+	int oif = 0;
+
+	init_route_req(&req, RTM_GETROUTE, 0, 0, dst, 32);
+	req.hdr.nlmsg_flags = NLM_F_REQUEST;
+	req.rtm.rtm_flags = flags;
+
+	int fd = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+	CHECK_WITH(send(fd, &req, req.hdr.nlmsg_len, 0),
+		   _ret == req.hdr.nlmsg_len);
+	ssize_t len = CHECK(recv(fd, buf, sizeof(buf), 0));
+	CHECK(close(fd));
+
+	CHECK_WITH(len, NLMSG_OK(hdr, len));
+	if (hdr->nlmsg_type == NLMSG_ERROR) {
+		errno = -((struct nlmsgerr *)NLMSG_DATA(hdr))->error;
+		return -1;
+	}
+	CHECK_WITH(hdr->nlmsg_type, _ret == RTM_NEWROUTE);
+
+	struct rtmsg *rtm = NLMSG_DATA(hdr);
+	*table = rtm->rtm_table;
+
+	int attrs_len = RTM_PAYLOAD(hdr);
+	for (struct rtattr *attr = RTM_RTA(rtm); RTA_OK(attr, attrs_len);
+	     attr = RTA_NEXT(attr, attrs_len)) {
+		if (attr->rta_type == RTA_OIF)
+			oif = *(int *)RTA_DATA(attr);
+		else if (attr->rta_type == RTA_TABLE)
+			*table = *(int *)RTA_DATA(attr);
+	}
+
+	return oif;
+}
+
+static int change_rule(int type, const char *dst, int dst_len, int table)
+{
+	struct nl_req req;
+	struct in_addr addr;
+	int priority = TEST_RULE_PRIORITY;
+
+	memset(&req, 0, sizeof(req));
+	req.hdr.nlmsg_len = NLMSG_LENGTH(sizeof(struct fib_rule_hdr));
+	req.hdr.nlmsg_type = type;
+	req.hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK;
+	if (type == RTM_NEWRULE)
+		req.hdr.nlmsg_flags |= NLM_F_CREATE | NLM_F_EXCL;
+	req.frh.family = AF_INET;
+	req.frh.dst_len = dst_len;
+	req.frh.action = FR_ACT_TO_TBL;
+	req.frh.table = table;
+
+	CHECK_WITH(inet_pton(AF_INET, dst, &addr), _ret == 1);
+	add_attr(&req.hdr, FRA_DST, &addr, sizeof(addr));
+	add_attr(&req.hdr, FRA_PRIORITY, &priority, sizeof(priority));
+
+	return send_req(&req);
+}
+
+static int read_ip_forward(void)
+{
+	char buf[8] = {};
+	int fd = CHECK(open(IP_FORWARD_PATH, O_RDONLY));
+
+	CHECK(read(fd, buf, sizeof(buf) - 1));
+	CHECK(close(fd));
+
+	return atoi(buf);
+}
+
+static int write_ip_forward(const char *value)
+{
+	int fd = CHECK(open(IP_FORWARD_PATH, O_WRONLY));
+	int ret = write(fd, value, strlen(value));
+
+	CHECK(close(fd));
+	return ret;
+}
+
+FN_SETUP(net_ns)
+{
+	struct nl_req req;
+
+	// Use a new network namespace so that the routes of the initial one are
+	// not affected.
+	CHECK(unshare(CLONE_NEWNET));
+
+	struct if_nameindex *ifs = CHECK_WITH(if_nameindex(), _ret != NULL);
+	for (struct if_nameindex *i = ifs; i->if_index != 0; i++)
+		if (strcmp(i->if_name, "lo") == 0)
+			lo_index = i->if_index;
+	if_freenameindex(ifs);
+	CHECK_WITH(lo_index, _ret != 0);
+
+	// The loopback iface must be up before routes can be added to it on Linux.
+	memset(&req, 0, sizeof(req));
+	req.hdr.nlmsg_len = NLMSG_LENGTH(sizeof(struct ifinfomsg));
+	req.hdr.nlmsg_type = RTM_NEWLINK;
+	req.hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK;
+	req.ifi.ifi_family = AF_UNSPEC;
+	req.ifi.ifi_index = lo_index;
+	req.ifi.ifi_flags = IFF_UP;
+	req.ifi.ifi_change = IFF_UP;
+	CHECK(send_req(&req));
+}
+END_SETUP()
+
+FN_TEST(add_and_del_route)
+{
+	int table;
+
+	TEST_ERRNO(get_route("10.123.4.5", 0, &table), ENETUNREACH);
+
+	TEST_SUCC(add_route(RT_TABLE_MAIN, "10.123.0.0", 16, NLM_F_EXCL));
+	TEST_ERRNO(add_route(RT_TABLE_MAIN, "10.123.0.0", 16, NLM_F_EXCL),
+		   EEXIST);
+
+	// The longest prefix wins.
+	TEST_RES(get_route("10.123.4.5", 0, &table),
+		 _ret == lo_index && table == RT_TABLE_MAIN);
+	TEST_ERRNO(get_route("10.124.4.5", 0, &table), ENETUNREACH);
+
+	TEST_SUCC(del_route(RT_TABLE_MAIN, "10.123.0.0", 16));
+	TEST_ERRNO(del_route(RT_TABLE_MAIN, "10.123.0.0", 16), ESRCH);
+	TEST_ERRNO(get_route("10.123.4.5", 0, &table), ENETUNREACH);
+}
+END_TEST()
+
+FN_TEST(invalid_route)
+{
+	// The host bits of IPv4 prefixes must not be set.
+	TEST_ERRNO(add_route(RT_TABLE_MAIN, "10.123.0.1", 16, NLM_F_EXCL),
+		   EINVAL);
+	TEST_ERRNO(add_route(RT_TABLE_MAIN, "10.123.0.0", 33, NLM_F_EXCL),
+		   EINVAL);
+}
+END_TEST()
+
+FN_TEST(policy_routing)
+{
+	int table;
+
+	// Routes in other tables are not used without rules.
+	TEST_SUCC(add_route(TEST_TABLE, "10.125.0.0", 16, NLM_F_EXCL));
+	TEST_ERRNO(get_route("10.125.0.1", RTM_F_LOOKUP_TABLE, &table),
+		   ENETUNREACH);
+
+	TEST_SUCC(change_rule(RTM_NEWRULE, "10.125.0.0", 16, TEST_TABLE));
+	TEST_RES(get_route("10.125.0.1", RTM_F_LOOKUP_TABLE, &table),
+		 _ret == lo_index && table == TEST_TABLE);
+	// The table is reported as the main table unless it is asked for.
+	TEST_RES(get_route("10.125.0.1", 0, &table),
+		 _ret == lo_index && table == RT_TABLE_MAIN);
+
+	TEST_SUCC(change_rule(RTM_DELRULE, "10.125.0.0", 16, TEST_TABLE));
+	TEST_ERRNO(get_route("10.125.0.1", RTM_F_LOOKUP_TABLE, &table),
+		   ENETUNREACH);
+
+	TEST_SUCC(del_route(TEST_TABLE, "10.125.0.0", 16));
+}
+END_TEST()
+
+FN_TEST(ip_forward)
+{
+	// Forwarding is disabled by default in new network namespaces.
+	TEST_RES(read_ip_forward(), _ret == 0);
+
+	TEST_RES(write_ip_forward("1"), _ret == 1);
+	TEST_RES(read_ip_forward(), _ret == 1);
+
+	TEST_RES(write_ip_forward("0"), _ret == 1);
+	TEST_RES(read_ip_forward(), _ret == 0);
+}
+END_TEST()
@@ -17,7 +17,14 @@ sleep 0.2
 ./unix_client
 
 ./listen_backlog
+./multicast
+./net_ns
+./netfilter
+./packet_ring
 ./privileged_ports
+./raw
+./reuseport
+./route
 ./send_buf_full
 ./sendmmsg
 ./socketpair
@@ -27,11 +34,16 @@ sleep 0.2
 ./tcp_poll
 ./tcp_reuseaddr
 ./tcp_wrapped_buffer_io
+./tun
 ./udp_broadcast
 ./udp_err
+./udp6
 ./unix_datagram_err
+./unix_diag
 ./unix_seqpacket_err
 ./unix_stream_err
+./veth
+./vsock_loopback
 
 ./netlink_route
 ./rtnl_err
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <fcntl.h>
+#include <linux/if_packet.h>
+#include <linux/if_tun.h>
+#include <linux/rtnetlink.h>
+#include <net/ethernet.h>
+#include <net/if.h>
+#include <sys/ioctl.h>
+#include <sys/mman.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define TAP_NAME "tap_test"
+#define TUN_NAME "tun_test"
+
+#define PAGE_SIZE 4096
+
+// The local experimental EtherType, which is not used by the kernel.
+#define TEST_PROTO 0x88b5
+
+static int tap_fd;
+static int packet_fd;
+static unsigned int tap_index;
+
+static int open_tun(const char *name, short flags)
+{
+	struct ifreq ifr = { .ifr_flags = flags };
+	int fd = open("/dev/net/tun", O_RDWR);
+	if (fd < 0)
+		return -1;
+
+	strcpy(ifr.ifr_name, name);
+	if (ioctl(fd, TUNSETIFF, &ifr) < 0) {
+		int err = errno;
+		close(fd);
+		errno = err;
+		return -1;
+	}
+
+	return fd;
+}
+
+static unsigned int find_index(const char *name)
+{
+	struct if_nameindex *ifs = CHECK_WITH(if_nameindex(), _ret != NULL);
+	unsigned int index = 0;
+
+	for (struct if_nameindex *i = ifs; i->if_index != 0; i++)
+		if (strcmp(i->if_name, name) == 0)
+			index = i->if_index;
+	if_freenameindex(ifs);
+
+	return index;
+}
+
+// Sets the link up, which is required before sending packets on Linux.
+static void set_link_up(unsigned int index)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct ifinfomsg ifi;
+	} req = {
+		.hdr = {
+			.nlmsg_len = sizeof(req),
+			.nlmsg_type = RTM_NEWLINK,
+			.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK,
+		},
+		.ifi = {
+			.ifi_family = AF_UNSPEC,
+			.ifi_index = index,
+			.ifi_flags = IFF_UP,
+			.ifi_change = IFF_UP,
+		},
+	};
+	struct {
+		struct nlmsghdr hdr;
+		struct nlmsgerr err;
+	} resp;
+	int fd = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+
+	CHECK_WITH(send(fd, &req, sizeof(req), 0), _ret == sizeof(req));
+	CHECK_WITH(recv(fd, &resp, sizeof(resp), 0),
+		   _ret == sizeof(resp) && resp.hdr.nlmsg_type == NLMSG_ERROR &&
+			   resp.err.error == 0);
+	CHECK(close(fd));
+}
+
+static void fill_frame(unsigned char *frame, size_t len)
+{
+	struct ether_header *eth = (struct ether_header *)frame;
+
+	memset(eth->ether_dhost, 0xff, ETH_ALEN);
+	memcpy(eth->ether_shost, "\x02\x00\x00\x00\x00\x01", ETH_ALEN);
+	eth->ether_type = htons(TEST_PROTO);
+	for (size_t i = sizeof(*eth); i < len; i++)
+		frame[i] = i;
+}
+
+static ssize_t send_frame(const unsigned char *frame, size_t len)
+{
+	struct sockaddr_ll addr = {
+		.sll_family = AF_PACKET,
+		.sll_ifindex = tap_index,
+	};
+
+	return sendto(packet_fd, frame, len, 0, (struct sockaddr *)&addr,
+		      sizeof(addr));
+}
+
+// Receives the next frame of `TEST_PROTO`, skipping other frames that the kernel may send.
+static ssize_t recv_frame(unsigned char *frame, size_t len)
+{
+	for (;;) {
+		ssize_t ret = read(tap_fd, frame, len);
+		if (ret < 0 ||
+		    (ret >= (ssize_t)sizeof(struct ether_header) &&
+		     ((struct ether_header *)frame)->ether_type ==
+			     htons(TEST_PROTO)))
+			return ret;
+	}
+}
+
+FN_SETUP(tap)
+{
+	tap_fd = CHECK(open_tun(TAP_NAME, IFF_TAP | IFF_NO_PI));
+	tap_index = CHECK_WITH(find_index(TAP_NAME), _ret != 0);
+	set_link_up(tap_index);
+
+	packet_fd = CHECK(socket(AF_PACKET, SOCK_RAW, htons(TEST_PROTO)));
+}
+END_SETUP()
+
+FN_TEST(send_and_recv)
+{
+	unsigned char sent[64], received[128];
+
+	fill_frame(sent, sizeof(sent));
+	TEST_RES(send_frame(sent, sizeof(sent)), _ret == sizeof(sent));
+	TEST_RES(recv_frame(received, sizeof(received)),
+		 _ret == sizeof(sent) && memcmp(sent, received, _ret) == 0);
+}
+END_TEST()
+
+// FIXME: Linux ignores the error and drops the packet if it cannot be copied to user space.
+// Asterinas reports the error and keeps the packet so that it can be received later.
+#ifdef __asterinas__
+FN_TEST(recv_efault)
+{
+	unsigned char sent[64], received[128];
+	void *bad_buf = CHECK_WITH(mmap(NULL, PAGE_SIZE, PROT_NONE,
+					MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				   _ret != MAP_FAILED);
+
+	fill_frame(sent, sizeof(sent));
+	TEST_RES(send_frame(sent, sizeof(sent)), _ret == sizeof(sent));
+	TEST_ERRNO(read(tap_fd, bad_buf, sizeof(received)), EFAULT);
+	TEST_RES(recv_frame(received, sizeof(received)),
+		 _ret == sizeof(sent) && memcmp(sent, received, _ret) == 0);
+
+	TEST_SUCC(munmap(bad_buf, PAGE_SIZE));
+}
+END_TEST()
+#endif
+
+FN_TEST(attach_flags_mismatch)
+{
+	int fd1 = TEST_SUCC(
+		open_tun(TUN_NAME, IFF_TUN | IFF_NO_PI | IFF_MULTI_QUEUE));
+
+	// The multi-queue flag must match.
+	TEST_ERRNO(open_tun(TUN_NAME, IFF_TUN | IFF_NO_PI), EINVAL);
+	// The device type must match.
+	TEST_ERRNO(open_tun(TUN_NAME, IFF_TAP | IFF_NO_PI | IFF_MULTI_QUEUE),
+		   EINVAL);
+
+	// FIXME: Linux ignores the flag and keeps the one of the device. Asterinas rejects the
+	// mismatch so that the attached queues are not affected.
+#ifdef __asterinas__
+	TEST_ERRNO(open_tun(TUN_NAME, IFF_TUN | IFF_MULTI_QUEUE), EINVAL);
+#endif
+
+	int fd2 = TEST_SUCC(
+		open_tun(TUN_NAME, IFF_TUN | IFF_NO_PI | IFF_MULTI_QUEUE));
+
+	struct ifreq ifr = {};
+	TEST_RES(ioctl(fd1, TUNGETIFF, &ifr),
+		 ifr.ifr_flags & IFF_NO_PI && strcmp(ifr.ifr_name, TUN_NAME) == 0);
+
+	TEST_SUCC(close(fd2));
+	TEST_SUCC(close(fd1));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(packet_fd));
+	CHECK(close(tap_fd));
+}
+END_SETUP()
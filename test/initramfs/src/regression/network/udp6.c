@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <ifaddrs.h>
+#include <netinet/in.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PORT 8766
+#define V6ONLY_PORT 8767
+
+static int sk_v6;
+static int sk_v6_peer;
+static int sk_v4;
+
+FN_SETUP(sockets)
+{
+	struct sockaddr_in6 any6 = {
+		.sin6_family = AF_INET6,
+		.sin6_port = htons(PORT),
+		.sin6_addr = IN6ADDR_ANY_INIT,
+	};
+
+	sk_v6 = CHECK(socket(AF_INET6, SOCK_DGRAM, 0));
+	CHECK(bind(sk_v6, (struct sockaddr *)&any6, sizeof(any6)));
+
+	sk_v6_peer = CHECK(socket(AF_INET6, SOCK_DGRAM, 0));
+	sk_v4 = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+}
+END_SETUP()
+
+FN_TEST(unbound_addr)
+{
+	struct sockaddr_in6 addr;
+	socklen_t addrlen = sizeof(addr);
+	int fd = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+
+	TEST_RES(getsockname(fd, (struct sockaddr *)&addr, &addrlen),
+		 addrlen == sizeof(addr) && addr.sin6_family == AF_INET6 &&
+			 addr.sin6_port == 0 &&
+			 IN6_IS_ADDR_UNSPECIFIED(&addr.sin6_addr));
+	TEST_ERRNO(getpeername(fd, (struct sockaddr *)&addr, &addrlen),
+		   ENOTCONN);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(send_and_recv_v6)
+{
+	struct sockaddr_in6 loopback6 = {
+		.sin6_family = AF_INET6,
+		.sin6_port = htons(PORT),
+		.sin6_addr = IN6ADDR_LOOPBACK_INIT,
+	};
+	struct sockaddr_in6 addr;
+	socklen_t addrlen = sizeof(addr);
+	char buf[16];
+
+	TEST_RES(sendto(sk_v6_peer, "hello", 5, 0, (struct sockaddr *)&loopback6,
+			sizeof(loopback6)),
+		 _ret == 5);
+	TEST_RES(recvfrom(sk_v6, buf, sizeof(buf), 0, (struct sockaddr *)&addr,
+			  &addrlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 addrlen == sizeof(addr) &&
+			 addr.sin6_family == AF_INET6 &&
+			 IN6_IS_ADDR_LOOPBACK(&addr.sin6_addr));
+
+	// The peer is bound to an ephemeral port after sending.
+	struct sockaddr_in6 peer_addr;
+	socklen_t peer_addrlen = sizeof(peer_addr);
+	TEST_RES(getsockname(sk_v6_peer, (struct sockaddr *)&peer_addr,
+			     &peer_addrlen),
+		 peer_addr.sin6_port == addr.sin6_port &&
+			 peer_addr.sin6_port != 0);
+}
+END_TEST()
+
+FN_TEST(send_and_recv_v4_mapped)
+{
+	struct sockaddr_in loopback4 = {
+		.sin_family = AF_INET,
+		.sin_port = htons(PORT),
+		.sin_addr = { .s_addr = htonl(INADDR_LOOPBACK) },
+	};
+	struct sockaddr_in6 addr;
+	socklen_t addrlen = sizeof(addr);
+	char buf[16];
+
+	// A dual-stack socket receives IPv4 datagrams with IPv4-mapped addresses.
+	TEST_RES(sendto(sk_v4, "world", 5, 0, (struct sockaddr *)&loopback4,
+			sizeof(loopback4)),
+		 _ret == 5);
+	TEST_RES(recvfrom(sk_v6, buf, sizeof(buf), 0, (struct sockaddr *)&addr,
+			  &addrlen),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0 &&
+			 addr.sin6_family == AF_INET6 &&
+			 IN6_IS_ADDR_V4MAPPED(&addr.sin6_addr) &&
+			 addr.sin6_addr.s6_addr32[3] == htonl(INADDR_LOOPBACK));
+}
+END_TEST()
+
+FN_TEST(v6only)
+{
+	struct sockaddr_in6 mapped = {
+		.sin6_family = AF_INET6,
+		.sin6_port = htons(V6ONLY_PORT),
+	};
+	int fd = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	int v6only = -1;
+	socklen_t optlen = sizeof(v6only);
+
+	TEST_SUCC(inet_pton(AF_INET6, "::ffff:127.0.0.1", &mapped.sin6_addr));
+
+	TEST_RES(getsockopt(fd, IPPROTO_IPV6, IPV6_V6ONLY, &v6only, &optlen),
+		 optlen == sizeof(v6only) && v6only == 0);
+
+	v6only = 1;
+	TEST_SUCC(setsockopt(fd, IPPROTO_IPV6, IPV6_V6ONLY, &v6only,
+			     sizeof(v6only)));
+	TEST_RES(getsockopt(fd, IPPROTO_IPV6, IPV6_V6ONLY, &v6only, &optlen),
+		 v6only == 1);
+
+	// IPv4-mapped addresses cannot be used with `IPV6_V6ONLY`.
+	TEST_ERRNO(bind(fd, (struct sockaddr *)&mapped, sizeof(mapped)),
+		   EINVAL);
+
+	// `IPV6_V6ONLY` cannot be changed after the socket is bound.
+	struct sockaddr_in6 any6 = {
+		.sin6_family = AF_INET6,
+		.sin6_port = htons(V6ONLY_PORT),
+		.sin6_addr = IN6ADDR_ANY_INIT,
+	};
+	TEST_SUCC(bind(fd, (struct sockaddr *)&any6, sizeof(any6)));
+	v6only = 0;
+	TEST_ERRNO(setsockopt(fd, IPPROTO_IPV6, IPV6_V6ONLY, &v6only,
+			      sizeof(v6only)),
+		   EINVAL);
+
+	// IPv4-mapped destinations are unreachable with `IPV6_V6ONLY`.
+	TEST_ERRNO(sendto(fd, "x", 1, 0, (struct sockaddr *)&mapped,
+			  sizeof(mapped)),
+		   ENETUNREACH);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(loopback_v6_addr)
+{
+	struct ifaddrs *ifaddrs;
+	int found = 0;
+
+	TEST_SUCC(getifaddrs(&ifaddrs));
+	for (struct ifaddrs *i = ifaddrs; i != NULL; i = i->ifa_next) {
+		if (i->ifa_addr == NULL || i->ifa_addr->sa_family != AF_INET6)
+			continue;
+		struct sockaddr_in6 *addr = (struct sockaddr_in6 *)i->ifa_addr;
+		if (strcmp(i->ifa_name, "lo") == 0 &&
+		    IN6_IS_ADDR_LOOPBACK(&addr->sin6_addr))
+			found = 1;
+	}
+	freeifaddrs(ifaddrs);
+
+	TEST_RES(found, _ret == 1);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_v4));
+	CHECK(close(sk_v6_peer));
+	CHECK(close(sk_v6));
+}
+END_SETUP()
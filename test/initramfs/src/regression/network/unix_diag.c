@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <linux/netlink.h>
+#include <linux/sock_diag.h>
+#include <linux/unix_diag.h>
+#include <sched.h>
+#include <stdio.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+static int sk_unix;
+static ino_t sk_ino;
+
+// Returns whether the socket with the inode number is listed in `/proc/net/unix`.
+static int in_proc_net_unix(ino_t ino)
+{
+	char line[256];
+	int found = 0;
+	FILE *file = fopen("/proc/net/unix", "r");
+
+	if (file == NULL)
+		return -1;
+
+	// Skip the header.
+	if (fgets(line, sizeof(line), file) == NULL) {
+		fclose(file);
+		return -1;
+	}
+
+	while (fgets(line, sizeof(line), file) != NULL) {
+		unsigned long line_ino;
+		if (sscanf(line, "%*s %*s %*s %*s %*s %*s %lu", &line_ino) == 1 &&
+		    line_ino == ino)
+			found = 1;
+	}
+
+	fclose(file);
+	return found;
+}
+
+static int send_diag_req(int flags, ino_t ino)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct unix_diag_req req;
+	} msg = {
+		.hdr = {
+			.nlmsg_len = sizeof(msg),
+			.nlmsg_type = SOCK_DIAG_BY_FAMILY,
+			.nlmsg_flags = NLM_F_REQUEST | flags,
+		},
+		.req = {
+			.sdiag_family = AF_UNIX,
+			.udiag_states = -1,
+			.udiag_ino = ino,
+			.udiag_cookie = { -1, -1 },
+		},
+	};
+	int fd = socket(AF_NETLINK, SOCK_RAW, NETLINK_SOCK_DIAG);
+
+	if (fd < 0)
+		return -1;
+	if (send(fd, &msg, sizeof(msg), 0) != sizeof(msg)) {
+		close(fd);
+		return -1;
+	}
+	return fd;
+}
+
+// Returns whether the socket with the inode number is listed in a `unix_diag`
+// dump.
+static int in_diag_dump(ino_t ino)
+{
+	char buf[8192];
+	int found = 0;
+	int fd = send_diag_req(NLM_F_DUMP, 0);
+
+	if (fd < 0)
+		return -1;
+
+	for (;;) {
+		ssize_t len = recv(fd, buf, sizeof(buf), 0);
+		if (len <= 0) {
+			close(fd);
+			return -1;
+		}
+
+		for (struct nlmsghdr *hdr = (struct nlmsghdr *)buf;
+		     NLMSG_OK(hdr, len); hdr = NLMSG_NEXT(hdr, len)) {
+			if (hdr->nlmsg_type == NLMSG_DONE) {
+				close(fd);
+				return found;
+			}
+			if (hdr->nlmsg_type != SOCK_DIAG_BY_FAMILY) {
+				close(fd);
+				return -1;
+			}
+
+			struct unix_diag_msg *msg = NLMSG_DATA(hdr);
+			if (msg->udiag_ino == ino)
+				found = 1;
+		}
+	}
+}
+
+// Queries the socket with the inode number and returns the inode number in the
+// response, or -1 with `errno` set on failure.
+static long diag_get_exact(ino_t ino)
+{
+	struct {
+		struct nlmsghdr hdr;
+		union {
+			struct unix_diag_msg msg;
+			struct nlmsgerr err;
+		};
+		char attrs[256];
+	} resp;
+	int fd = send_diag_req(0, ino);
+
+	if (fd < 0)
+		return -1;
+	if (recv(fd, &resp, sizeof(resp), 0) < (ssize_t)sizeof(resp.hdr)) {
+		close(fd);
+		errno = EIO;
+		return -1;
+	}
+	close(fd);
+
+	if (resp.hdr.nlmsg_type == NLMSG_ERROR) {
+		errno = -resp.err.error;
+		return -1;
+	}
+	return resp.msg.udiag_ino;
+}
+
+FN_SETUP(socket)
+{
+	struct stat stat;
+
+	sk_unix = CHECK(socket(AF_UNIX, SOCK_STREAM, 0));
+	CHECK(fstat(sk_unix, &stat));
+	sk_ino = stat.st_ino;
+}
+END_SETUP()
+
+FN_TEST(visible_in_same_net_ns)
+{
+	TEST_RES(in_proc_net_unix(sk_ino), _ret == 1);
+	TEST_RES(in_diag_dump(sk_ino), _ret == 1);
+	TEST_RES(diag_get_exact(sk_ino), _ret == (long)sk_ino);
+}
+END_TEST()
+
+FN_TEST(invisible_in_other_net_ns)
+{
+	int status;
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		struct stat stat;
+
+		CHECK(unshare(CLONE_NEWNET));
+
+		// Sockets in the initial network namespace are invisible.
+		CHECK_WITH(in_proc_net_unix(sk_ino), _ret == 0);
+		CHECK_WITH(in_diag_dump(sk_ino), _ret == 0);
+		CHECK_WITH(diag_get_exact(sk_ino), _ret == -1 && errno == ENOENT);
+
+		// Sockets in the new network namespace are visible.
+		int fd = CHECK(socket(AF_UNIX, SOCK_DGRAM, 0));
+		CHECK(fstat(fd, &stat));
+		CHECK_WITH(in_proc_net_unix(stat.st_ino), _ret == 1);
+		CHECK_WITH(in_diag_dump(stat.st_ino), _ret == 1);
+		CHECK(close(fd));
+
+		_exit(0);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_unix));
+}
+END_SETUP()
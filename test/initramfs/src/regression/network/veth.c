@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/rtnetlink.h>
+#include <linux/veth.h>
+#include <net/if.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define LINK_NAME "veth_a"
+#define PEER_NAME "veth_b"
+
+struct link_req {
+	struct nlmsghdr hdr;
+	struct ifinfomsg ifi;
+	char attrs[256];
+};
+
+static int init_ns_fd;
+static int other_ns_fd;
+static pid_t other_ns_pid;
+
+static struct rtattr *add_attr(struct nlmsghdr *hdr, int type,
+			       const void *data, int len)
+{
+	struct rtattr *attr =
+		(struct rtattr *)((char *)hdr + NLMSG_ALIGN(hdr->nlmsg_len));
+
+	attr->rta_type = type;
+	attr->rta_len = RTA_LENGTH(len);
+	if (data != NULL)
+		memcpy(RTA_DATA(attr), data, len);
+	else
+		memset(RTA_DATA(attr), 0, len);
+	hdr->nlmsg_len = NLMSG_ALIGN(hdr->nlmsg_len) + RTA_ALIGN(attr->rta_len);
+
+	return attr;
+}
+
+static void end_nested_attr(struct nlmsghdr *hdr, struct rtattr *attr)
+{
+	attr->rta_len = (char *)hdr + hdr->nlmsg_len - (char *)attr;
+}
+
+static void init_req(struct link_req *req, int type, int flags)
+{
+	memset(req, 0, sizeof(*req));
+	req->hdr.nlmsg_len = NLMSG_LENGTH(sizeof(struct ifinfomsg));
+	req->hdr.nlmsg_type = type;
+	req->hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK | flags;
+	req->ifi.ifi_family = AF_UNSPEC;
+}
+
+// Sends a request in the current network namespace and returns the error in the acknowledgment.
+static int send_req(struct link_req *req)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct nlmsgerr err;
+	} resp;
+	int fd = socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE);
+	if (fd < 0)
+		return -1;
+
+	if (send(fd, req, req->hdr.nlmsg_len, 0) != req->hdr.nlmsg_len ||
+	    recv(fd, &resp, sizeof(resp), 0) < (ssize_t)sizeof(resp) ||
+	    resp.hdr.nlmsg_type != NLMSG_ERROR) {
+		close(fd);
+		errno = EIO;
+		return -1;
+	}
+	close(fd);
+
+	if (resp.err.error != 0) {
+		errno = -resp.err.error;
+		return -1;
+	}
+	return 0;
+}
+
+// Creates a veth pair, where the link is created in the namespace specified by `link_ns_attr`
+// and the peer is created in the namespace specified by `peer_ns_attr`.
+static int add_veth_pair(int link_ns_attr, int link_ns, int peer_ns_attr,
+			 int peer_ns)
+{
+	struct link_req req;
+	init_req(&req, RTM_NEWLINK, NLM_F_CREATE | NLM_F_EXCL);
+
+	add_attr(&req.hdr, IFLA_IFNAME, LINK_NAME, sizeof(LINK_NAME));
+	if (link_ns_attr != IFLA_UNSPEC)
+		add_attr(&req.hdr, link_ns_attr, &link_ns, sizeof(link_ns));
+
+	struct rtattr *link_info = add_attr(&req.hdr, IFLA_LINKINFO, NULL, 0);
+	add_attr(&req.hdr, IFLA_INFO_KIND, "veth", sizeof("veth"));
+	struct rtattr *info_data = add_attr(&req.hdr, IFLA_INFO_DATA, NULL, 0);
+	struct rtattr *peer_info = add_attr(&req.hdr, VETH_INFO_PEER, NULL,
+					    sizeof(struct ifinfomsg));
+	add_attr(&req.hdr, IFLA_IFNAME, PEER_NAME, sizeof(PEER_NAME));
+	if (peer_ns_attr != IFLA_UNSPEC)
+		add_attr(&req.hdr, peer_ns_attr, &peer_ns, sizeof(peer_ns));
+	end_nested_attr(&req.hdr, peer_info);
+	end_nested_attr(&req.hdr, info_data);
+	end_nested_attr(&req.hdr, link_info);
+
+	return send_req(&req);
+}
+
+static int del_link(const char *name)
+{
+	struct link_req req;
+	init_req(&req, RTM_DELLINK, 0);
+	add_attr(&req.hdr, IFLA_IFNAME, name, strlen(name) + 1);
+
+	return send_req(&req);
+}
+
+static int set_link_flags(int type, const char *name, unsigned int flags,
+			  unsigned int change)
+{
+	struct link_req req;
+	init_req(&req, type, 0);
+	req.ifi.ifi_flags = flags;
+	req.ifi.ifi_change = change;
+	add_attr(&req.hdr, IFLA_IFNAME, name, strlen(name) + 1);
+
+	return send_req(&req);
+}
+
+static unsigned int link_flags(const char *name)
+{
+	struct link_req req;
+	struct {
+		struct nlmsghdr hdr;
+		struct ifinfomsg ifi;
+		char attrs[1024];
+	} resp;
+	int fd = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+
+	init_req(&req, RTM_GETLINK, 0);
+	add_attr(&req.hdr, IFLA_IFNAME, name, strlen(name) + 1);
+	CHECK_WITH(send(fd, &req, req.hdr.nlmsg_len, 0),
+		   _ret == req.hdr.nlmsg_len);
+	CHECK_WITH(recv(fd, &resp, sizeof(resp), 0),
+		   _ret >= NLMSG_LENGTH(sizeof(struct ifinfomsg)) &&
+			   resp.hdr.nlmsg_type == RTM_NEWLINK);
+	CHECK(close(fd));
+
+	return resp.ifi.ifi_flags;
+}
+
+static int has_link(const char *name)
+{
+	struct if_nameindex *ifs = CHECK_WITH(if_nameindex(), _ret != NULL);
+	int found = 0;
+
+	for (struct if_nameindex *i = ifs; i->if_index != 0; i++)
+		found |= strcmp(i->if_name, name) == 0;
+	if_freenameindex(ifs);
+
+	return found;
+}
+
+// Returns whether the links exist in the namespace referred to by `ns_fd`.
+static int has_links(int ns_fd, int link_exists, int peer_exists)
+{
+	CHECK(setns(ns_fd, CLONE_NEWNET));
+	int res = has_link(LINK_NAME) == link_exists &&
+		  has_link(PEER_NAME) == peer_exists;
+	CHECK(setns(init_ns_fd, CLONE_NEWNET));
+
+	return res;
+}
+
+FN_SETUP(other_ns)
+{
+	int pipefd[2];
+	char path[64];
+
+	init_ns_fd = CHECK(open("/proc/self/ns/net", O_RDONLY));
+
+	CHECK(pipe(pipefd));
+	other_ns_pid = CHECK(fork());
+	if (other_ns_pid == 0) {
+		CHECK(unshare(CLONE_NEWNET));
+		CHECK(close(pipefd[1]));
+		for (;;)
+			pause();
+	}
+	CHECK(close(pipefd[1]));
+	// Wait until the child has entered the new network namespace.
+	CHECK_WITH(read(pipefd[0], path, 1), _ret == 0);
+	CHECK(close(pipefd[0]));
+
+	snprintf(path, sizeof(path), "/proc/%d/ns/net", other_ns_pid);
+	other_ns_fd = CHECK(open(path, O_RDONLY));
+}
+END_SETUP()
+
+FN_TEST(peer_in_same_ns)
+{
+	TEST_SUCC(add_veth_pair(IFLA_UNSPEC, 0, IFLA_UNSPEC, 0));
+	TEST_RES(has_links(init_ns_fd, 1, 1), _ret);
+	TEST_RES(has_links(other_ns_fd, 0, 0), _ret);
+
+	TEST_SUCC(del_link(PEER_NAME));
+	TEST_RES(has_links(init_ns_fd, 0, 0), _ret);
+}
+END_TEST()
+
+FN_TEST(peer_in_other_ns_by_fd)
+{
+	TEST_SUCC(add_veth_pair(IFLA_UNSPEC, 0, IFLA_NET_NS_FD, other_ns_fd));
+	TEST_RES(has_links(init_ns_fd, 1, 0), _ret);
+	TEST_RES(has_links(other_ns_fd, 0, 1), _ret);
+
+	// Deleting one end also deletes the other end in the other namespace.
+	TEST_SUCC(del_link(LINK_NAME));
+	TEST_RES(has_links(init_ns_fd, 0, 0), _ret);
+	TEST_RES(has_links(other_ns_fd, 0, 0), _ret);
+}
+END_TEST()
+
+FN_TEST(peer_in_other_ns_by_pid)
+{
+	TEST_SUCC(
+		add_veth_pair(IFLA_UNSPEC, 0, IFLA_NET_NS_PID, other_ns_pid));
+	TEST_RES(has_links(init_ns_fd, 1, 0), _ret);
+	TEST_RES(has_links(other_ns_fd, 0, 1), _ret);
+
+	// The end in the other namespace cannot be deleted from this namespace.
+	TEST_ERRNO(del_link(PEER_NAME), ENODEV);
+
+	TEST_SUCC(setns(other_ns_fd, CLONE_NEWNET));
+	TEST_SUCC(del_link(PEER_NAME));
+	TEST_SUCC(setns(init_ns_fd, CLONE_NEWNET));
+
+	TEST_RES(has_links(init_ns_fd, 0, 0), _ret);
+	TEST_RES(has_links(other_ns_fd, 0, 0), _ret);
+}
+END_TEST()
+
+FN_TEST(link_in_other_ns)
+{
+	TEST_SUCC(add_veth_pair(IFLA_NET_NS_FD, other_ns_fd, IFLA_UNSPEC, 0));
+	TEST_RES(has_links(init_ns_fd, 0, 1), _ret);
+	TEST_RES(has_links(other_ns_fd, 1, 0), _ret);
+
+	TEST_SUCC(del_link(PEER_NAME));
+	TEST_RES(has_links(init_ns_fd, 0, 0), _ret);
+	TEST_RES(has_links(other_ns_fd, 0, 0), _ret);
+}
+END_TEST()
+
+FN_TEST(invalid_ns)
+{
+	TEST_ERRNO(add_veth_pair(IFLA_UNSPEC, 0, IFLA_NET_NS_FD, 1000),
+		   EBADF);
+	TEST_ERRNO(add_veth_pair(IFLA_UNSPEC, 0, IFLA_NET_NS_PID, 0x7fffffff),
+		   ESRCH);
+	TEST_RES(has_links(init_ns_fd, 0, 0), _ret);
+}
+END_TEST()
+
+FN_TEST(link_flags)
+{
+	TEST_SUCC(add_veth_pair(IFLA_UNSPEC, 0, IFLA_UNSPEC, 0));
+
+	TEST_SUCC(set_link_flags(RTM_NEWLINK, LINK_NAME, IFF_UP, IFF_UP));
+	TEST_SUCC(set_link_flags(RTM_SETLINK, PEER_NAME, IFF_UP, IFF_UP));
+	TEST_RES(link_flags(LINK_NAME), _ret & IFF_UP);
+	TEST_RES(link_flags(PEER_NAME), _ret & IFF_UP);
+
+	// Non-changeable flags are ignored.
+	TEST_SUCC(set_link_flags(RTM_SETLINK, LINK_NAME, IFF_UP | IFF_LOOPBACK,
+				 IFF_UP | IFF_LOOPBACK));
+	TEST_RES(link_flags(LINK_NAME), !(_ret & IFF_LOOPBACK));
+
+	// FIXME: Asterinas does not support setting a link down.
+#ifdef __asterinas__
+	TEST_ERRNO(set_link_flags(RTM_SETLINK, LINK_NAME, 0, IFF_UP),
+		   EOPNOTSUPP);
+	TEST_ERRNO(set_link_flags(RTM_NEWLINK, LINK_NAME, 0, IFF_UP),
+		   EOPNOTSUPP);
+	TEST_RES(link_flags(LINK_NAME), _ret & IFF_UP);
+#else
+	TEST_SUCC(set_link_flags(RTM_SETLINK, LINK_NAME, 0, IFF_UP));
+	TEST_RES(link_flags(LINK_NAME), !(_ret & IFF_UP));
+#endif
+
+	TEST_ERRNO(set_link_flags(RTM_SETLINK, "veth_none", IFF_UP, IFF_UP),
+		   ENODEV);
+
+	TEST_SUCC(del_link(LINK_NAME));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(kill(other_ns_pid, SIGKILL));
+	CHECK_WITH(waitpid(other_ns_pid, NULL, 0), _ret == other_ns_pid);
+
+	CHECK(close(other_ns_fd));
+	CHECK(close(init_ns_fd));
+}
+END_SETUP()
@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <sys/socket.h>
+#include <linux/vm_sockets.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define STREAM_PORT 8772
+#define SEQPACKET_PORT 8773
+#define DGRAM_PORT 8774
+
+static struct sockaddr_vm local_addr(unsigned int port)
+{
+	struct sockaddr_vm addr = {
+		.svm_family = AF_VSOCK,
+		.svm_cid = VMADDR_CID_LOCAL,
+		.svm_port = port,
+	};
+	return addr;
+}
+
+// Creates a connected pair of sockets of the type via the loopback transport.
+static int connect_pair(int type, unsigned int port, int *client, int *server)
+{
+	struct sockaddr_vm addr = local_addr(port);
+	int listener = socket(AF_VSOCK, type, 0);
+
+	if (listener < 0)
+		return -1;
+	if (bind(listener, (struct sockaddr *)&addr, sizeof(addr)) < 0 ||
+	    listen(listener, 1) < 0)
+		goto err_listener;
+
+	*client = socket(AF_VSOCK, type, 0);
+	if (*client < 0)
+		goto err_listener;
+	if (connect(*client, (struct sockaddr *)&addr, sizeof(addr)) < 0)
+		goto err_client;
+
+	*server = accept(listener, NULL, NULL);
+	if (*server < 0)
+		goto err_client;
+
+	close(listener);
+	return 0;
+
+err_client:
+	close(*client);
+err_listener:
+	close(listener);
+	return -1;
+}
+
+FN_TEST(stream)
+{
+	struct sockaddr_vm addr;
+	socklen_t addrlen = sizeof(addr);
+	char buf[16];
+	int client, server;
+
+	TEST_SUCC(connect_pair(SOCK_STREAM, STREAM_PORT, &client, &server));
+
+	TEST_RES(getpeername(server, (struct sockaddr *)&addr, &addrlen),
+		 addrlen == sizeof(addr) && addr.svm_cid == VMADDR_CID_LOCAL);
+	TEST_RES(getpeername(client, (struct sockaddr *)&addr, &addrlen),
+		 addr.svm_cid == VMADDR_CID_LOCAL &&
+			 addr.svm_port == STREAM_PORT);
+
+	// Data can be received in parts.
+	TEST_RES(send(client, "hello", 5, 0), _ret == 5);
+	TEST_RES(recv(server, buf, 3, 0),
+		 _ret == 3 && memcmp(buf, "hel", 3) == 0);
+	TEST_RES(recv(server, buf, sizeof(buf), 0),
+		 _ret == 2 && memcmp(buf, "lo", 2) == 0);
+
+	TEST_SUCC(close(client));
+	TEST_RES(recv(server, buf, sizeof(buf), 0), _ret == 0);
+	TEST_SUCC(close(server));
+}
+END_TEST()
+
+FN_TEST(seqpacket)
+{
+	char buf[16];
+	int client, server;
+
+	TEST_SUCC(connect_pair(SOCK_SEQPACKET, SEQPACKET_PORT, &client,
+			       &server));
+
+	// Message boundaries are preserved.
+	TEST_RES(send(client, "hello", 5, 0), _ret == 5);
+	TEST_RES(send(client, "world!", 6, 0), _ret == 6);
+	TEST_RES(recv(server, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(recv(server, buf, sizeof(buf), 0),
+		 _ret == 6 && memcmp(buf, "world!", 6) == 0);
+
+	// The rest of a truncated message is discarded.
+	TEST_RES(send(client, "hello", 5, 0), _ret == 5);
+	TEST_RES(send(client, "world!", 6, 0), _ret == 6);
+	TEST_RES(recv(server, buf, 3, 0),
+		 _ret == 3 && memcmp(buf, "hel", 3) == 0);
+	TEST_RES(recv(server, buf, 3, MSG_TRUNC),
+		 _ret == 6 && memcmp(buf, "wor", 3) == 0);
+
+	TEST_SUCC(close(client));
+	TEST_RES(recv(server, buf, sizeof(buf), 0), _ret == 0);
+	TEST_SUCC(close(server));
+}
+END_TEST()
+
+// Linux does not support datagrams via the loopback transport.
+#ifdef __asterinas__
+FN_TEST(dgram)
+{
+	struct sockaddr_vm addr = local_addr(DGRAM_PORT);
+	struct sockaddr_vm sender_addr = local_addr(DGRAM_PORT + 1);
+	struct sockaddr_vm from;
+	socklen_t fromlen = sizeof(from);
+	char buf[16];
+
+	int receiver = TEST_SUCC(socket(AF_VSOCK, SOCK_DGRAM, 0));
+	int sender = TEST_SUCC(socket(AF_VSOCK, SOCK_DGRAM, 0));
+	TEST_SUCC(bind(receiver, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_SUCC(bind(sender, (struct sockaddr *)&sender_addr,
+		       sizeof(sender_addr)));
+
+	// Datagram boundaries are preserved, and the sender address is reported.
+	TEST_RES(sendto(sender, "hello", 5, 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == 5);
+	TEST_RES(sendto(sender, "world!", 6, 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == 6);
+	TEST_RES(recvfrom(receiver, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&from, &fromlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 from.svm_cid == VMADDR_CID_LOCAL &&
+			 from.svm_port == DGRAM_PORT + 1);
+	TEST_RES(recv(receiver, buf, sizeof(buf), 0),
+		 _ret == 6 && memcmp(buf, "world!", 6) == 0);
+
+	// Connected sockets can send without specifying the address.
+	TEST_SUCC(connect(sender, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_RES(send(sender, "hello", 5, 0), _ret == 5);
+	TEST_RES(recv(receiver, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+
+	TEST_SUCC(close(sender));
+	TEST_SUCC(close(receiver));
+}
+END_TEST()
+#endif /* __asterinas__ */
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <elf.h>
+#include <fcntl.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/prctl.h>
+#include <sys/resource.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define CORE_PATTERN "/proc/sys/kernel/core_pattern"
+#define COREDUMP_FILTER "/proc/self/coredump_filter"
+#define CORE_FILE_PREFIX "/tmp/coredump_test"
+
+static char saved_core_pattern[256];
+
+static int read_file(const char *path, char *buf, size_t size)
+{
+	int fd = open(path, O_RDONLY);
+	ssize_t len;
+
+	if (fd < 0)
+		return -1;
+	len = read(fd, buf, size - 1);
+	close(fd);
+	if (len < 0)
+		return -1;
+
+	buf[len] = '\0';
+	return len;
+}
+
+static int write_file(const char *path, const char *str)
+{
+	int fd = open(path, O_WRONLY);
+	ssize_t len;
+
+	if (fd < 0)
+		return -1;
+	len = write(fd, str, strlen(str));
+	close(fd);
+	return len < 0 ? -1 : 0;
+}
+
+static void core_file_path(char *buf, size_t size, pid_t pid)
+{
+	snprintf(buf, size, CORE_FILE_PREFIX ".%d", pid);
+}
+
+// Forks a child that aborts with the given core file size limit and dumpable
+// attribute. Returns the PID of the child and stores its wait status.
+static pid_t crash_child(rlim_t core_limit, int dumpable, int *status)
+{
+	pid_t pid = fork();
+
+	if (pid < 0)
+		return -1;
+
+	if (pid == 0) {
+		struct rlimit limit = { core_limit, core_limit };
+
+		CHECK(setrlimit(RLIMIT_CORE, &limit));
+		CHECK(prctl(PR_SET_DUMPABLE, dumpable));
+		abort();
+	}
+
+	if (waitpid(pid, status, 0) != pid)
+		return -1;
+	return pid;
+}
+
+// Checks that the core file is an ELF core file with a note segment.
+static int check_core_file(const char *path)
+{
+	Elf64_Ehdr ehdr;
+	Elf64_Phdr phdr;
+	int has_note = 0;
+	int fd = open(path, O_RDONLY);
+
+	if (fd < 0)
+		return -1;
+
+	if (pread(fd, &ehdr, sizeof(ehdr), 0) != sizeof(ehdr) ||
+	    memcmp(ehdr.e_ident, ELFMAG, SELFMAG) != 0 ||
+	    ehdr.e_ident[EI_CLASS] != ELFCLASS64 || ehdr.e_type != ET_CORE)
+		goto out;
+
+	for (int i = 0; i < ehdr.e_phnum; i++) {
+		if (pread(fd, &phdr, sizeof(phdr),
+			  ehdr.e_phoff + i * ehdr.e_phentsize) != sizeof(phdr))
+			goto out;
+		if (phdr.p_type == PT_NOTE)
+			has_note = 1;
+	}
+
+out:
+	close(fd);
+	return has_note ? 0 : -1;
+}
+
+FN_SETUP(core_pattern)
+{
+	CHECK(read_file(CORE_PATTERN, saved_core_pattern,
+			sizeof(saved_core_pattern)));
+	saved_core_pattern[strcspn(saved_core_pattern, "\n")] = '\0';
+	CHECK(write_file(CORE_PATTERN, CORE_FILE_PREFIX ".%p"));
+}
+END_SETUP()
+
+FN_TEST(core_pattern)
+{
+	char buf[256];
+
+	TEST_RES(read_file(CORE_PATTERN, buf, sizeof(buf)),
+		 strcmp(buf, CORE_FILE_PREFIX ".%p\n") == 0);
+}
+END_TEST()
+
+FN_TEST(coredump_filter)
+{
+	char buf[32];
+
+	TEST_RES(read_file(COREDUMP_FILTER, buf, sizeof(buf)),
+		 strcmp(buf, "00000033\n") == 0);
+
+	TEST_SUCC(write_file(COREDUMP_FILTER, "0x7"));
+	TEST_RES(read_file(COREDUMP_FILTER, buf, sizeof(buf)),
+		 strcmp(buf, "00000007\n") == 0);
+
+	TEST_SUCC(write_file(COREDUMP_FILTER, "0x33"));
+	TEST_RES(read_file(COREDUMP_FILTER, buf, sizeof(buf)),
+		 strcmp(buf, "00000033\n") == 0);
+}
+END_TEST()
+
+FN_TEST(dump_core)
+{
+	char path[64];
+	int status;
+	pid_t pid;
+
+	pid = TEST_RES(crash_child(RLIM_INFINITY, 1, &status),
+		       WIFSIGNALED(status) && WTERMSIG(status) == SIGABRT &&
+			       WCOREDUMP(status));
+	core_file_path(path, sizeof(path), pid);
+	TEST_SUCC(check_core_file(path));
+	TEST_SUCC(unlink(path));
+}
+END_TEST()
+
+FN_TEST(no_core_if_limit_is_zero)
+{
+	char path[64];
+	int status;
+	pid_t pid;
+
+	pid = TEST_RES(crash_child(0, 1, &status),
+		       WIFSIGNALED(status) && WTERMSIG(status) == SIGABRT &&
+			       !WCOREDUMP(status));
+	core_file_path(path, sizeof(path), pid);
+	TEST_ERRNO(access(path, F_OK), ENOENT);
+}
+END_TEST()
+
+FN_TEST(no_core_if_not_dumpable)
+{
+	char path[64];
+	int status;
+	pid_t pid;
+
+	pid = TEST_RES(crash_child(RLIM_INFINITY, 0, &status),
+		       WIFSIGNALED(status) && WTERMSIG(status) == SIGABRT &&
+			       !WCOREDUMP(status));
+	core_file_path(path, sizeof(path), pid);
+	TEST_ERRNO(access(path, F_OK), ENOENT);
+}
+END_TEST()
+
+FN_SETUP(restore_core_pattern)
+{
+	CHECK(write_file(CORE_PATTERN, saved_core_pattern));
+}
+END_SETUP()
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/auxv.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define MNT_DIR "/tmp/binfmt_misc"
+#define INTERP "/tmp/binfmt_misc_interp"
+#define MAGIC_PROG "/tmp/binfmt_misc_prog"
+#define EXT_PROG "/tmp/binfmt_misc_prog.bfmt"
+#define REPORT_ENV "BINFMT_MISC_REPORT"
+
+#define MAGIC "BINFMTTEST"
+#define USER_UID 1000
+
+// When executed by the tests as an interpreter, prints the arguments, the file
+// descriptor in `AT_EXECFD`, the open file descriptors, and the effective UID.
+// This runs before any test functions.
+__attribute__((constructor(101))) static void report(int argc, char **argv)
+{
+	unsigned long execfd;
+	int i;
+
+	if (getenv(REPORT_ENV) == NULL)
+		return;
+
+	for (i = 0; i < argc; i++)
+		printf("%s ", argv[i]);
+
+	execfd = getauxval(AT_EXECFD);
+	printf("execfd=%lu fds=", execfd);
+	for (i = 3; i < 32; i++)
+		if (fcntl(i, F_GETFD) >= 0)
+			printf("%d,", i);
+	printf(" euid=%u", geteuid());
+
+	if (execfd != 0) {
+		char buf[sizeof(MAGIC)] = {};
+
+		if (pread(execfd, buf, sizeof(MAGIC) - 1, 0) < 0)
+			_exit(EXIT_FAILURE);
+		printf(" data=%s", buf);
+	}
+
+	fflush(stdout);
+	_exit(EXIT_SUCCESS);
+}
+
+static int write_file(const char *path, const char *content)
+{
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	len = write(fd, content, strlen(content));
+	close(fd);
+	return len < 0 ? -1 : 0;
+}
+
+static int copy_file(const char *src_path, const char *dst_path)
+{
+	char buf[4096];
+	ssize_t len;
+	int src, dst;
+
+	src = open(src_path, O_RDONLY);
+	if (src < 0)
+		return -1;
+	dst = open(dst_path, O_WRONLY | O_CREAT | O_TRUNC, 0755);
+	if (dst < 0) {
+		close(src);
+		return -1;
+	}
+
+	while ((len = read(src, buf, sizeof(buf))) > 0)
+		if (write(dst, buf, len) != len)
+			break;
+
+	close(dst);
+	close(src);
+	return len == 0 ? 0 : -1;
+}
+
+FN_SETUP(files)
+{
+	CHECK(copy_file("/proc/self/exe", INTERP));
+
+	CHECK(close(CHECK(open(MAGIC_PROG, O_WRONLY | O_CREAT | O_TRUNC,
+			       0755))));
+	CHECK(write_file(MAGIC_PROG, MAGIC "\n"));
+
+	CHECK(close(CHECK(open(EXT_PROG, O_WRONLY | O_CREAT | O_TRUNC, 0755))));
+	CHECK(write_file(EXT_PROG, "not an executable\n"));
+
+	CHECK(mkdir(MNT_DIR, 0755));
+	CHECK(mount("binfmt_misc", MNT_DIR, "binfmt_misc", 0, NULL));
+}
+END_SETUP()
+
+// Executes `path` as `uid` and reads what the interpreter prints into `buf`.
+//
+// Returns the exit status of the program, or -1 with `errno` set if the
+// program cannot be executed.
+static int run_prog(const char *path, uid_t uid, char *buf, size_t size)
+{
+	char *argv[] = { "argv0", "argv1", NULL };
+	char *envp[] = { REPORT_ENV "=1", NULL };
+	int fds[2], status, err;
+	ssize_t len, total = 0;
+	pid_t pid;
+
+	if (pipe(fds) < 0)
+		return -1;
+
+	pid = fork();
+	if (pid < 0)
+		return -1;
+
+	if (pid == 0) {
+		CHECK(dup2(fds[1], STDOUT_FILENO));
+		CHECK(close(fds[1]));
+		CHECK(close(fds[0]));
+		CHECK(setresuid(uid, uid, uid));
+		execve(path, argv, envp);
+		// Report the error of `execve` in the exit status.
+		_exit(128 + errno);
+	}
+
+	close(fds[1]);
+	while ((len = read(fds[0], buf + total, size - 1 - total)) > 0)
+		total += len;
+	close(fds[0]);
+	buf[total] = '\0';
+
+	if (waitpid(pid, &status, 0) != pid || !WIFEXITED(status))
+		return -1;
+
+	if (WEXITSTATUS(status) > 128) {
+		err = WEXITSTATUS(status) - 128;
+		errno = err;
+		return -1;
+	}
+	return WEXITSTATUS(status);
+}
+
+static char buf[256];
+
+FN_TEST(exec_magic)
+{
+	TEST_SUCC(write_file(MNT_DIR "/register",
+			     ":binfmt_test:M::" MAGIC "::" INTERP ":"));
+	TEST_SUCC(access(MNT_DIR "/binfmt_test", F_OK));
+
+	// The interpreter replaces `argv[0]` with its own path and the path of
+	// the program.
+	TEST_RES(run_prog(MAGIC_PROG, 0, buf, sizeof(buf)),
+		 _ret == 0 &&
+			 strcmp(buf, INTERP " " MAGIC_PROG " argv1 "
+					 "execfd=0 fds= euid=0") == 0);
+
+	// An entry with the same name cannot be registered again.
+	TEST_ERRNO(write_file(MNT_DIR "/register",
+			      ":binfmt_test:M::" MAGIC "::" INTERP ":"),
+		   EEXIST);
+}
+END_TEST()
+
+FN_TEST(exec_extension)
+{
+	TEST_SUCC(write_file(MNT_DIR "/register",
+			     ":binfmt_test_ext:E::bfmt::" INTERP ":"));
+
+	TEST_RES(run_prog(EXT_PROG, 0, buf, sizeof(buf)),
+		 _ret == 0 &&
+			 strcmp(buf, INTERP " " EXT_PROG " argv1 "
+					 "execfd=0 fds= euid=0") == 0);
+
+	TEST_SUCC(write_file(MNT_DIR "/binfmt_test_ext", "-1"));
+	TEST_ERRNO(access(MNT_DIR "/binfmt_test_ext", F_OK), ENOENT);
+	TEST_ERRNO(run_prog(EXT_PROG, 0, buf, sizeof(buf)), ENOEXEC);
+}
+END_TEST()
+
+FN_TEST(preserve_argv0)
+{
+	// The newest entry that recognizes the program takes precedence.
+	TEST_SUCC(write_file(MNT_DIR "/register",
+			     ":binfmt_test_p:M::" MAGIC "::" INTERP ":P"));
+
+	TEST_RES(run_prog(MAGIC_PROG, 0, buf, sizeof(buf)),
+		 _ret == 0 &&
+			 strcmp(buf, INTERP " " MAGIC_PROG " argv0 argv1 "
+					 "execfd=0 fds= euid=0") == 0);
+
+	TEST_SUCC(write_file(MNT_DIR "/binfmt_test_p", "-1"));
+}
+END_TEST()
+
+FN_TEST(open_binary)
+{
+	TEST_SUCC(write_file(MNT_DIR "/register",
+			     ":binfmt_test_o:M::" MAGIC "::" INTERP ":O"));
+
+	// The program is passed as the only extra file descriptor. The
+	// credentials come from the interpreter, which is not set-user-ID.
+	TEST_SUCC(chown(MAGIC_PROG, 0, 0));
+	TEST_SUCC(chmod(MAGIC_PROG, S_ISUID | 0755));
+	TEST_RES(run_prog(MAGIC_PROG, USER_UID, buf, sizeof(buf)),
+		 _ret == 0 && strcmp(buf, INTERP " " MAGIC_PROG " argv1 "
+					  "execfd=3 fds=3, euid=1000 "
+					  "data=" MAGIC) == 0);
+
+	TEST_SUCC(write_file(MNT_DIR "/binfmt_test_o", "-1"));
+}
+END_TEST()
+
+FN_TEST(credentials)
+{
+	TEST_SUCC(write_file(MNT_DIR "/register",
+			     ":binfmt_test_c:M::" MAGIC "::" INTERP ":C"));
+
+	// The credentials come from the set-user-ID program.
+	TEST_RES(run_prog(MAGIC_PROG, USER_UID, buf, sizeof(buf)),
+		 _ret == 0 && strcmp(buf, INTERP " " MAGIC_PROG " argv1 "
+					  "execfd=3 fds=3, euid=0 "
+					  "data=" MAGIC) == 0);
+
+	TEST_SUCC(write_file(MNT_DIR "/binfmt_test_c", "-1"));
+	TEST_SUCC(chmod(MAGIC_PROG, 0755));
+}
+END_TEST()
+
+FN_TEST(enable_and_disable)
+{
+	int fd;
+
+	// Disable the entry.
+	TEST_SUCC(write_file(MNT_DIR "/binfmt_test", "0"));
+	fd = TEST_SUCC(open(MNT_DIR "/binfmt_test", O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret > 9 && memcmp(buf, "disabled\n", 9) == 0);
+	TEST_SUCC(close(fd));
+	TEST_ERRNO(run_prog(MAGIC_PROG, 0, buf, sizeof(buf)), ENOEXEC);
+
+	TEST_SUCC(write_file(MNT_DIR "/binfmt_test", "1"));
+	TEST_RES(run_prog(MAGIC_PROG, 0, buf, sizeof(buf)), _ret == 0);
+
+	// Disable `binfmt_misc` as a whole.
+	TEST_SUCC(write_file(MNT_DIR "/status", "0"));
+	fd = TEST_SUCC(open(MNT_DIR "/status", O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == 9 && memcmp(buf, "disabled\n", 9) == 0);
+	TEST_SUCC(close(fd));
+	TEST_ERRNO(run_prog(MAGIC_PROG, 0, buf, sizeof(buf)), ENOEXEC);
+
+	TEST_SUCC(write_file(MNT_DIR "/status", "1"));
+	TEST_RES(run_prog(MAGIC_PROG, 0, buf, sizeof(buf)), _ret == 0);
+
+	TEST_ERRNO(write_file(MNT_DIR "/status", "2"), EINVAL);
+}
+END_TEST()
+
+FN_TEST(remove_all)
+{
+	// Remove all entries.
+	TEST_SUCC(write_file(MNT_DIR "/status", "-1"));
+	TEST_ERRNO(access(MNT_DIR "/binfmt_test", F_OK), ENOENT);
+	TEST_ERRNO(run_prog(MAGIC_PROG, 0, buf, sizeof(buf)), ENOEXEC);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(umount(MNT_DIR));
+	CHECK(rmdir(MNT_DIR));
+	CHECK(unlink(EXT_PROG));
+	CHECK(unlink(MAGIC_PROG));
+	CHECK(unlink(INTERP));
+}
+END_SETUP()
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <stdlib.h>
+#include <sys/prctl.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <linux/capability.h>
+#include <unistd.h>
+
+#define USER_UID 1000
+#define SETUID_PROG "/tmp/no_new_privs_setuid"
+#define REPORT_ENV "NO_NEW_PRIVS_REPORT"
+
+// The bits in the exit status of the executed program.
+#define IS_SETUID 1
+#define HAS_CAPS 2
+#define HAS_NO_NEW_PRIVS 4
+
+// When executed by the tests, reports the credentials in the exit status. This
+// runs before any test functions.
+__attribute__((constructor(101))) static void report(void)
+{
+	struct __user_cap_header_struct hdr = {
+		.version = _LINUX_CAPABILITY_VERSION_3,
+		.pid = 0,
+	};
+	struct __user_cap_data_struct data[2];
+	int status = 0;
+
+	if (getenv(REPORT_ENV) == NULL)
+		return;
+
+	if (geteuid() != getuid())
+		status |= IS_SETUID;
+	if (syscall(SYS_capget, &hdr, data) < 0)
+		_exit(EXIT_FAILURE);
+	if (data[0].effective != 0 || data[1].effective != 0)
+		status |= HAS_CAPS;
+	if (prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0) == 1)
+		status |= HAS_NO_NEW_PRIVS;
+
+	_exit(status);
+}
+
+FN_TEST(get_and_set)
+{
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 0);
+
+		errno = 0;
+		CHECK_WITH(prctl(PR_SET_NO_NEW_PRIVS, 0, 0, 0, 0),
+			   _ret == -1 && errno == EINVAL);
+		errno = 0;
+		CHECK_WITH(prctl(PR_SET_NO_NEW_PRIVS, 1, 1, 0, 0),
+			   _ret == -1 && errno == EINVAL);
+		errno = 0;
+		CHECK_WITH(prctl(PR_GET_NO_NEW_PRIVS, 1, 0, 0, 0),
+			   _ret == -1 && errno == EINVAL);
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK_WITH(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 1);
+
+		// The attribute cannot be unset.
+		errno = 0;
+		CHECK_WITH(prctl(PR_SET_NO_NEW_PRIVS, 0, 0, 0, 0),
+			   _ret == -1 && errno == EINVAL);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+}
+END_TEST()
+
+FN_SETUP(setuid_prog)
+{
+	char buf[4096];
+	ssize_t len;
+	int src = CHECK(open("/proc/self/exe", O_RDONLY));
+	int dst = CHECK(open(SETUID_PROG, O_WRONLY | O_CREAT | O_TRUNC, 0755));
+
+	while ((len = CHECK(read(src, buf, sizeof(buf)))) > 0)
+		CHECK_WITH(write(dst, buf, len), _ret == len);
+
+	CHECK(close(dst));
+	CHECK(close(src));
+
+	CHECK(chown(SETUID_PROG, 0, 0));
+	CHECK(chmod(SETUID_PROG, S_ISUID | 0755));
+}
+END_SETUP()
+
+// Executes the set-user-ID-root program as `uid` and returns its exit status.
+static int exec_setuid_prog(uid_t uid, int no_new_privs)
+{
+	char *argv[] = { SETUID_PROG, NULL };
+	char *envp[] = { REPORT_ENV "=1", NULL };
+	int status;
+	pid_t pid;
+
+	pid = fork();
+	if (pid < 0)
+		return -1;
+
+	if (pid == 0) {
+		CHECK(setresuid(uid, uid, uid));
+		if (no_new_privs)
+			CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(execve(SETUID_PROG, argv, envp));
+	}
+
+	if (waitpid(pid, &status, 0) != pid || !WIFEXITED(status))
+		return -1;
+	return WEXITSTATUS(status);
+}
+
+FN_TEST(exec)
+{
+	// Without `no_new_privs`, the program gains privileges.
+	TEST_RES(exec_setuid_prog(USER_UID, 0), _ret == (IS_SETUID | HAS_CAPS));
+
+	// With `no_new_privs`, the program gains no privileges.
+	TEST_RES(exec_setuid_prog(USER_UID, 1), _ret == HAS_NO_NEW_PRIVS);
+
+	// With `no_new_privs`, the program keeps the existing privileges.
+	TEST_RES(exec_setuid_prog(0, 1), _ret == (HAS_CAPS | HAS_NO_NEW_PRIVS));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(SETUID_PROG));
+}
+END_SETUP()
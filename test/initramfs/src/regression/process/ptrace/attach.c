@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdlib.h>
+#include <sys/prctl.h>
+#include <sys/socket.h>
+#include <sys/ptrace.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+#include "../../common/yama_ptrace_scope.h"
+
+#define NOBODY_UID 65534
+#define USER_UID 1000
+#define SETUID_PROG "/tmp/ptrace_attach_setuid"
+#define CHECK_EUID_ENV "PTRACE_ATTACH_CHECK_EUID"
+
+// When executed as the set-user-ID program, exits with zero if and only if the
+// effective UID has not changed. This runs before any test functions.
+__attribute__((constructor(101))) static void check_euid(void)
+{
+	if (getenv(CHECK_EUID_ENV) != NULL)
+		_exit(geteuid() == getuid() ? 0 : 1);
+}
+
+static pid_t fork_sleeper(void)
+{
+	pid_t pid = fork();
+
+	if (pid == 0) {
+		for (;;)
+			pause();
+	}
+	return pid;
+}
+
+static int kill_and_reap(pid_t pid)
+{
+	int status;
+
+	if (kill(pid, SIGKILL) < 0)
+		return -1;
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	return WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL ? 0 : -1;
+}
+
+FN_TEST(attach_detach)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	int status;
+	pid_t pid = TEST_SUCC(fork_sleeper());
+
+	// The tracee is stopped by `SIGSTOP` after being attached.
+	TEST_SUCC(ptrace(PTRACE_ATTACH, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGSTOP);
+
+	// A thread cannot be attached twice.
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, pid, 0, 0), EPERM);
+	TEST_ERRNO(ptrace(PTRACE_SEIZE, pid, 0, 0), EPERM);
+
+	// `PTRACE_INTERRUPT` and `PTRACE_LISTEN` require the tracee to be seized.
+	TEST_ERRNO(ptrace(PTRACE_INTERRUPT, pid, 0, 0), EIO);
+	TEST_ERRNO(ptrace(PTRACE_LISTEN, pid, 0, 0), EIO);
+
+	// After detaching, the thread is no longer a tracee.
+	TEST_SUCC(ptrace(PTRACE_DETACH, pid, 0, 0));
+	TEST_ERRNO(ptrace(PTRACE_CONT, pid, 0, 0), ESRCH);
+
+	TEST_SUCC(kill_and_reap(pid));
+}
+END_TEST()
+
+FN_TEST(attach_self)
+{
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, getpid(), 0, 0), EPERM);
+	TEST_ERRNO(ptrace(PTRACE_SEIZE, getpid(), 0, 0), EPERM);
+}
+END_TEST()
+
+FN_TEST(seize_interrupt)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	int status;
+	pid_t pid = TEST_SUCC(fork_sleeper());
+
+	// The tracee keeps running after being seized.
+	TEST_SUCC(ptrace(PTRACE_SEIZE, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, WNOHANG), _ret == 0);
+
+	// `PTRACE_INTERRUPT` traps the tracee with `PTRACE_EVENT_STOP`.
+	TEST_SUCC(ptrace(PTRACE_INTERRUPT, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGTRAP &&
+			 status >> 16 == PTRACE_EVENT_STOP);
+
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, WNOHANG), _ret == 0);
+
+	// A running tracee cannot be detached.
+	TEST_ERRNO(ptrace(PTRACE_DETACH, pid, 0, 0), ESRCH);
+
+	TEST_SUCC(kill_and_reap(pid));
+}
+END_TEST()
+
+FN_TEST(seize_listen)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	int status;
+	pid_t pid = TEST_SUCC(fork_sleeper());
+
+	TEST_SUCC(ptrace(PTRACE_SEIZE, pid, 0, 0));
+
+	// A stopping signal is first reported as a signal-delivery-stop.
+	TEST_SUCC(kill(pid, SIGSTOP));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP && status >> 16 == 0);
+
+	// Delivering the signal puts the tracee in a group-stop, which is
+	// reported with `PTRACE_EVENT_STOP` for seized tracees.
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, SIGSTOP));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP &&
+			 status >> 16 == PTRACE_EVENT_STOP);
+
+	// The listening tracee stays stopped, and `PTRACE_LISTEN` does not work
+	// again until the next stop.
+	TEST_SUCC(ptrace(PTRACE_LISTEN, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, WNOHANG), _ret == 0);
+	TEST_ERRNO(ptrace(PTRACE_LISTEN, pid, 0, 0), ESRCH);
+
+	TEST_SUCC(kill_and_reap(pid));
+}
+END_TEST()
+
+// Runs in a new PID namespace as its init process. The tracees are identified
+// by their IDs in the namespace.
+static void trace_in_pid_ns(void)
+{
+	unsigned long msg;
+	int status;
+	pid_t tracee;
+
+	tracee = CHECK(fork());
+	if (tracee == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, 0, 0));
+		CHECK(raise(SIGSTOP));
+		if (CHECK(fork()) == 0)
+			_exit(EXIT_SUCCESS);
+		_exit(EXIT_SUCCESS);
+	}
+	CHECK_WITH(tracee, _ret == 2);
+
+	CHECK_WITH(waitpid(tracee, &status, 0),
+		   _ret == tracee && WIFSTOPPED(status) &&
+			   WSTOPSIG(status) == SIGSTOP);
+	CHECK(ptrace(PTRACE_SETOPTIONS, tracee, 0, PTRACE_O_TRACEFORK));
+	CHECK(ptrace(PTRACE_CONT, tracee, 0, 0));
+
+	// The ID of the new child is reported in the namespace of the tracer.
+	CHECK_WITH(waitpid(tracee, &status, 0),
+		   _ret == tracee && WIFSTOPPED(status) &&
+			   status >> 16 == PTRACE_EVENT_FORK);
+	CHECK(ptrace(PTRACE_GETEVENTMSG, tracee, 0, &msg));
+	CHECK_WITH(msg, _ret == 3);
+
+	// The new child is traced as well.
+	CHECK_WITH(waitpid(msg, &status, __WALL),
+		   _ret == (pid_t)msg && WIFSTOPPED(status) &&
+			   WSTOPSIG(status) == SIGSTOP);
+	CHECK(ptrace(PTRACE_DETACH, msg, 0, 0));
+
+	// The tracee may be stopped again by the `SIGCHLD` from the new child.
+	do {
+		CHECK(ptrace(PTRACE_CONT, tracee, 0, 0));
+		CHECK_WITH(waitpid(tracee, &status, 0), _ret == tracee);
+	} while (WIFSTOPPED(status));
+	CHECK_WITH(WIFEXITED(status), _ret);
+
+	// A thread can also be attached with its ID in the namespace.
+	tracee = CHECK(fork_sleeper());
+	CHECK_WITH(tracee, _ret == 4);
+	CHECK(ptrace(PTRACE_ATTACH, tracee, 0, 0));
+	CHECK_WITH(waitpid(tracee, &status, 0),
+		   _ret == tracee && WIFSTOPPED(status) &&
+			   WSTOPSIG(status) == SIGSTOP);
+	CHECK(ptrace(PTRACE_DETACH, tracee, 0, 0));
+	CHECK(kill_and_reap(tracee));
+}
+
+FN_TEST(trace_in_pid_ns)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	int status;
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			trace_in_pid_ns();
+			_exit(EXIT_SUCCESS);
+		}
+
+		CHECK_WITH(waitpid(init, &status, 0),
+			   _ret == init && WIFEXITED(status) &&
+				   WEXITSTATUS(status) == EXIT_SUCCESS);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+}
+END_TEST()
+
+FN_SETUP(setuid_prog)
+{
+	char buf[4096];
+	ssize_t len;
+	int src = CHECK(open("/proc/self/exe", O_RDONLY));
+	int dst = CHECK(open(SETUID_PROG, O_WRONLY | O_CREAT | O_TRUNC, 0755));
+
+	while ((len = CHECK(read(src, buf, sizeof(buf)))) > 0)
+		CHECK_WITH(write(dst, buf, len), _ret == len);
+
+	CHECK(close(dst));
+	CHECK(close(src));
+
+	CHECK(chown(SETUID_PROG, NOBODY_UID, NOBODY_UID));
+	CHECK(chmod(SETUID_PROG, S_ISUID | 0755));
+}
+END_SETUP()
+
+enum tracing_mode {
+	NOT_TRACED,
+	// The tracee requests to be traced by its parent.
+	TRACEME,
+	// The tracer attaches to the tracee.
+	SEIZE,
+};
+
+// Executes the set-user-ID program as `USER_UID` and returns its exit status.
+//
+// The tracer runs as `tracer_uid`, and has no capabilities unless it is root.
+static int exec_setuid_prog(enum tracing_mode mode, uid_t tracer_uid)
+{
+	char *argv[] = { SETUID_PROG, NULL };
+	char *envp[] = { CHECK_EUID_ENV "=1", NULL };
+	int status;
+	int sockfds[2];
+	char c = 0;
+	pid_t tracer, tracee;
+
+	tracer = fork();
+	if (tracer < 0)
+		return -1;
+
+	if (tracer == 0) {
+		CHECK(setresuid(tracer_uid, tracer_uid, tracer_uid));
+		CHECK(socketpair(AF_UNIX, SOCK_STREAM, 0, sockfds));
+
+		tracee = CHECK(fork());
+		if (tracee == 0) {
+			CHECK(setresuid(USER_UID, USER_UID, USER_UID));
+			// Changing the UIDs makes the process non-dumpable, which
+			// prevents unprivileged tracers from attaching to it.
+			CHECK(prctl(PR_SET_DUMPABLE, 1));
+			if (mode == TRACEME)
+				CHECK(ptrace(PTRACE_TRACEME, 0, 0, 0));
+			// Wait until the tracer is attached.
+			CHECK_WITH(write(sockfds[1], &c, 1), _ret == 1);
+			CHECK_WITH(read(sockfds[1], &c, 1), _ret == 1);
+			CHECK(execve(SETUID_PROG, argv, envp));
+		}
+
+		CHECK_WITH(read(sockfds[0], &c, 1), _ret == 1);
+		if (mode == SEIZE)
+			CHECK(ptrace(PTRACE_SEIZE, tracee, 0, 0));
+		CHECK_WITH(write(sockfds[0], &c, 1), _ret == 1);
+
+		if (mode == TRACEME) {
+			// Wait for the `SIGTRAP` after the execution.
+			CHECK_WITH(waitpid(tracee, &status, 0),
+				   _ret == tracee && WIFSTOPPED(status) &&
+					   WSTOPSIG(status) == SIGTRAP);
+			CHECK(ptrace(PTRACE_CONT, tracee, 0, 0));
+		}
+
+		CHECK_WITH(waitpid(tracee, &status, 0),
+			   _ret == tracee && WIFEXITED(status));
+		_exit(WEXITSTATUS(status));
+	}
+
+	if (waitpid(tracer, &status, 0) != tracer || !WIFEXITED(status))
+		return -1;
+	return WEXITSTATUS(status);
+}
+
+FN_TEST(traced_setuid_exec)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	// Without a tracer, the effective UID changes.
+	TEST_RES(exec_setuid_prog(NOT_TRACED, 0), _ret == 1);
+
+	// A tracer attached with `CAP_SYS_PTRACE` does not prevent the change.
+	TEST_RES(exec_setuid_prog(SEIZE, 0), _ret == 1);
+
+	// A tracer attached without `CAP_SYS_PTRACE` prevents the change.
+	TEST_RES(exec_setuid_prog(SEIZE, USER_UID), _ret == 0);
+
+	// With `PTRACE_TRACEME`, the capabilities of the tracee matter, not those
+	// of the tracer.
+	TEST_RES(exec_setuid_prog(TRACEME, 0), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(SETUID_PROG));
+}
+END_SETUP()
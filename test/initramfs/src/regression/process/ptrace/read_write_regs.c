@@ -5,9 +5,11 @@
 #include <signal.h>
 #include <stddef.h>
 #include <stdint.h>
+#include <elf.h>
 #include <asm/prctl.h>
 #include <sys/ptrace.h>
 #include <sys/syscall.h>
+#include <sys/uio.h>
 #include <sys/user.h>
 #include <sys/wait.h>
 #include <unistd.h>
@@ -173,3 +175,84 @@ FN_TEST(setregs_rejects_invalid)
 		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
 }
 END_TEST()
+
+FN_TEST(getregset)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, 0, 0));
+		CHECK(raise(SIGSTOP));
+		exit(0);
+	}
+
+	int status = 0;
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGSTOP);
+
+	// `NT_PRSTATUS` should match GETREGS.
+	struct user_regs_struct regs = { 0 };
+	struct user_regs_struct regset = { 0 };
+	struct iovec iov = { .iov_base = &regset, .iov_len = sizeof(regset) };
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, 0, &regs));
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRSTATUS, &iov),
+		 iov.iov_len == sizeof(regset) &&
+			 memcmp(&regs, &regset, sizeof(regs)) == 0);
+
+	// A short buffer receives a truncated register set.
+	memset(&regset, 0, sizeof(regset));
+	iov.iov_len = USER_REG_OFFSET(rbp);
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRSTATUS, &iov),
+		 iov.iov_len == USER_REG_OFFSET(rbp) && regset.rbp == 0 &&
+			 regset.r15 == regs.r15);
+
+	// `NT_PRFPREG` should match GETFPREGS.
+	struct user_fpregs_struct fpregs = { 0 };
+	struct user_fpregs_struct fpregset = { 0 };
+	iov.iov_base = &fpregset;
+	iov.iov_len = sizeof(fpregset);
+	TEST_SUCC(ptrace(PTRACE_GETFPREGS, pid, 0, &fpregs));
+	TEST_RES(ptrace(PTRACE_GETREGSET, pid, NT_PRFPREG, &iov),
+		 iov.iov_len == sizeof(fpregset) &&
+			 memcmp(&fpregs, &fpregset, sizeof(fpregs)) == 0);
+
+	// Unknown register sets are rejected.
+	TEST_ERRNO(ptrace(PTRACE_GETREGSET, pid, 0x233, &iov), EINVAL);
+
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(sysemu)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, 0, 0));
+		CHECK(syscall(SYS_kill, getpid(), SIGSTOP));
+		// The tracer emulates this syscall.
+		_exit(syscall(SYS_getppid) == 233 ? 0 : 1);
+	}
+
+	int status = 0;
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGSTOP);
+
+	// The tracee stops at the entry of the next syscall.
+	TEST_SUCC(ptrace(PTRACE_SYSEMU, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGTRAP);
+	TEST_RES(ptrace(PTRACE_PEEKUSER, pid, USER_REG_OFFSET(orig_rax), 0),
+		 _ret == SYS_getppid);
+
+	// The syscall is skipped, and the return value is set by the tracer.
+	TEST_SUCC(ptrace(PTRACE_POKEUSER, pid, USER_REG_OFFSET(rax), 233));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
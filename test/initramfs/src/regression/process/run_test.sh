@@ -18,6 +18,7 @@ fi
 ./cpu_affinity/cpu_affinity
 
 ./execve/execve
+./execve/execve_binfmt_misc
 ./execve/execve_comm
 ./execve/execve_err
 ./execve/execve_memfd
@@ -36,6 +37,7 @@ fi
 ./personality/personality
 
 ./prctl/capbset
+./prctl/no_new_privs
 ./prctl/secure_bits
 ./prctl/subreaper
 ./prctl/thread_name
@@ -43,6 +45,7 @@ fi
 ./pthread/pthread_signal_test
 ./pthread/pthread_test
 
+./ptrace/attach
 ./ptrace/ptrace
 ./ptrace/set_options
 
@@ -71,6 +74,7 @@ if [ "$(uname -m)" = "x86_64" ]; then
 fi
 
 ./cgroup.sh
+./coredump
 ./group_session
 ./job_control
 ./pidfd
@@ -0,0 +1,538 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <linux/audit.h>
+#include <linux/netlink.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdbool.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/socket.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define RULE_KEY "audit_test"
+#define USER_UID 1000
+#define UNSET_ID "4294967295"
+
+struct audit_request {
+	struct nlmsghdr hdr;
+	union {
+		struct audit_status status;
+		struct audit_rule_data rule;
+	};
+	char buf[sizeof(RULE_KEY)];
+};
+
+static char reply[8192];
+
+// The socket that sends the control requests.
+static int control_fd;
+// The socket of the audit daemon, which receives the audit records.
+static int daemon_fd;
+
+// The original status, which is restored after the tests.
+static struct audit_status orig_status;
+
+// Sends a request of `type` with `payload` and waits for the acknowledgement.
+//
+// Returns zero on success, or -1 with `errno` set to the error in the
+// acknowledgement.
+static int send_request(int fd, int type, const void *payload, size_t len)
+{
+	static unsigned int seq;
+	struct audit_request req = {};
+	struct nlmsgerr *err;
+	struct nlmsghdr *hdr;
+	ssize_t reply_len;
+
+	req.hdr.nlmsg_len = NLMSG_LENGTH(len);
+	req.hdr.nlmsg_type = type;
+	req.hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK;
+	req.hdr.nlmsg_seq = ++seq;
+	memcpy(NLMSG_DATA(&req.hdr), payload, len);
+
+	if (send(fd, &req, req.hdr.nlmsg_len, 0) < 0)
+		return -1;
+
+	for (;;) {
+		reply_len = recv(fd, reply, sizeof(reply), 0);
+		if (reply_len < 0)
+			return -1;
+
+		hdr = (struct nlmsghdr *)reply;
+		if (!NLMSG_OK(hdr, reply_len) || hdr->nlmsg_seq != seq ||
+		    hdr->nlmsg_type != NLMSG_ERROR)
+			continue;
+
+		err = NLMSG_DATA(hdr);
+		if (err->error == 0)
+			return 0;
+		errno = -err->error;
+		return -1;
+	}
+}
+
+static int get_status(struct audit_status *status)
+{
+	struct nlmsghdr hdr = {
+		.nlmsg_len = NLMSG_LENGTH(0),
+		.nlmsg_type = AUDIT_GET,
+		.nlmsg_flags = NLM_F_REQUEST,
+	};
+	struct nlmsghdr *reply_hdr = (struct nlmsghdr *)reply;
+	ssize_t reply_len;
+
+	if (send(control_fd, &hdr, hdr.nlmsg_len, 0) < 0)
+		return -1;
+
+	do {
+		reply_len = recv(control_fd, reply, sizeof(reply), 0);
+		if (reply_len < 0)
+			return -1;
+	} while (!NLMSG_OK(reply_hdr, reply_len) ||
+		 reply_hdr->nlmsg_type != AUDIT_GET);
+
+	memcpy(status, NLMSG_DATA(reply_hdr), sizeof(*status));
+	return 0;
+}
+
+static int set_status(int fd, const struct audit_status *status)
+{
+	return send_request(fd, AUDIT_SET, status, sizeof(*status));
+}
+
+// Builds a rule in the exit list that logs `syscall_nr` if the field `field`
+// equals `value`.
+static void init_rule(struct audit_rule_data *rule, int syscall_nr, int field,
+		      unsigned int value)
+{
+	memset(rule, 0, sizeof(*rule) + sizeof(RULE_KEY) - 1);
+
+	rule->flags = AUDIT_FILTER_EXIT;
+	rule->action = AUDIT_ALWAYS;
+	rule->mask[syscall_nr / 32] = 1U << (syscall_nr % 32);
+
+	rule->field_count = 2;
+	rule->fields[0] = field;
+	rule->fieldflags[0] = AUDIT_EQUAL;
+	rule->values[0] = value;
+	rule->fields[1] = AUDIT_FILTERKEY;
+	rule->fieldflags[1] = AUDIT_EQUAL;
+	rule->values[1] = sizeof(RULE_KEY) - 1;
+
+	rule->buflen = sizeof(RULE_KEY) - 1;
+	memcpy(rule->buf, RULE_KEY, sizeof(RULE_KEY) - 1);
+}
+
+static int add_rule(struct audit_request *req)
+{
+	return send_request(control_fd, AUDIT_ADD_RULE, &req->rule,
+			    sizeof(req->rule) + req->rule.buflen);
+}
+
+static int del_rule(struct audit_request *req)
+{
+	return send_request(control_fd, AUDIT_DEL_RULE, &req->rule,
+			    sizeof(req->rule) + req->rule.buflen);
+}
+
+// Returns whether the rule is listed by `AUDIT_LIST_RULES`.
+static bool is_rule_listed(struct audit_request *req)
+{
+	struct nlmsghdr hdr = {
+		.nlmsg_len = NLMSG_LENGTH(0),
+		.nlmsg_type = AUDIT_LIST_RULES,
+		.nlmsg_flags = NLM_F_REQUEST,
+	};
+	size_t rule_len = sizeof(req->rule) + req->rule.buflen;
+	struct nlmsghdr *reply_hdr = (struct nlmsghdr *)reply;
+	bool is_listed = false;
+	ssize_t reply_len;
+
+	CHECK(send(control_fd, &hdr, hdr.nlmsg_len, 0));
+
+	for (;;) {
+		reply_len = CHECK(recv(control_fd, reply, sizeof(reply), 0));
+		if (!NLMSG_OK(reply_hdr, reply_len))
+			continue;
+		if (reply_hdr->nlmsg_type == NLMSG_DONE)
+			return is_listed;
+		if (reply_hdr->nlmsg_type == AUDIT_LIST_RULES &&
+		    reply_hdr->nlmsg_len == NLMSG_LENGTH(rule_len) &&
+		    memcmp(NLMSG_DATA(reply_hdr), &req->rule, rule_len) == 0)
+			is_listed = true;
+	}
+}
+
+// Receives the audit records until a system call record that contains all of
+// `patterns` is found.
+//
+// Returns whether such a record is found. Other records are discarded.
+static bool find_syscall_record(const char *const *patterns)
+{
+	struct nlmsghdr *hdr = (struct nlmsghdr *)reply;
+	struct pollfd pfd = { .fd = daemon_fd, .events = POLLIN };
+	ssize_t reply_len;
+	int i;
+
+	// Linux sends the records asynchronously.
+	while (poll(&pfd, 1, 1000) > 0) {
+		reply_len = recv(daemon_fd, reply, sizeof(reply) - 1, 0);
+		if (reply_len < (ssize_t)NLMSG_HDRLEN ||
+		    hdr->nlmsg_type != AUDIT_SYSCALL)
+			continue;
+
+		reply[reply_len] = '\0';
+		for (i = 0; patterns[i] != NULL; i++)
+			if (strstr(NLMSG_DATA(hdr), patterns[i]) == NULL)
+				break;
+		if (patterns[i] == NULL)
+			return true;
+	}
+
+	return false;
+}
+
+// Discards the audit records that are ready to be received.
+//
+// Returns the number of the discarded records.
+static int drain_records(void)
+{
+	struct pollfd pfd = { .fd = daemon_fd, .events = POLLIN };
+	int count = 0;
+
+	while (poll(&pfd, 1, 0) > 0) {
+		if (recv(daemon_fd, reply, sizeof(reply), 0) < 0)
+			return -1;
+		count++;
+	}
+
+	return count;
+}
+
+FN_SETUP(sockets)
+{
+	struct audit_status status = {};
+
+	control_fd = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_AUDIT));
+	daemon_fd = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_AUDIT));
+
+	CHECK(get_status(&orig_status));
+
+	status.mask = AUDIT_STATUS_ENABLED | AUDIT_STATUS_FAILURE;
+	status.enabled = 1;
+	status.failure = AUDIT_FAIL_SILENT;
+	CHECK(set_status(control_fd, &status));
+}
+END_SETUP()
+
+FN_TEST(status)
+{
+	struct audit_status status;
+
+	TEST_RES(get_status(&status),
+		 status.enabled == 1 && status.failure == AUDIT_FAIL_SILENT &&
+			 (status.feature_bitmap &
+			  AUDIT_FEATURE_BITMAP_BACKLOG_LIMIT));
+
+	status.mask = AUDIT_STATUS_ENABLED;
+	status.enabled = 3;
+	TEST_ERRNO(set_status(control_fd, &status), EINVAL);
+
+	status.mask = AUDIT_STATUS_FAILURE;
+	status.failure = 3;
+	TEST_ERRNO(set_status(control_fd, &status), EINVAL);
+
+	// The deprecated requests are not supported.
+	TEST_ERRNO(send_request(control_fd, AUDIT_LIST, NULL, 0), EOPNOTSUPP);
+}
+END_TEST()
+
+FN_TEST(rules)
+{
+	struct audit_request req;
+
+	init_rule(&req.rule, SYS_getppid, AUDIT_PPID, getpid());
+
+	TEST_SUCC(add_rule(&req));
+	TEST_ERRNO(add_rule(&req), EEXIST);
+	TEST_RES(is_rule_listed(&req), _ret);
+
+	TEST_SUCC(del_rule(&req));
+	TEST_ERRNO(del_rule(&req), ENOENT);
+	TEST_RES(is_rule_listed(&req), !_ret);
+
+	// The architecture can only be compared for equality.
+	req.rule.fields[0] = AUDIT_ARCH;
+	req.rule.fieldflags[0] = AUDIT_LESS_THAN;
+	TEST_ERRNO(add_rule(&req), EINVAL);
+}
+END_TEST()
+
+FN_TEST(daemon)
+{
+	struct audit_status status = {};
+
+	// Only the process itself can be registered as the audit daemon.
+	status.mask = AUDIT_STATUS_PID;
+	status.pid = getppid();
+	TEST_ERRNO(set_status(daemon_fd, &status), EINVAL);
+
+	status.pid = getpid();
+	TEST_SUCC(set_status(daemon_fd, &status));
+	TEST_RES(get_status(&status), status.pid == getpid());
+
+	// Another audit daemon cannot be registered.
+	status.mask = AUDIT_STATUS_PID;
+	TEST_ERRNO(set_status(control_fd, &status), EEXIST);
+
+	TEST_RES(drain_records(), _ret >= 0);
+}
+END_TEST()
+
+// Runs `fn` in a child process and waits for the child process to exit.
+//
+// Linux does not audit the audit daemon itself, so the system calls to audit
+// are made by child processes.
+static pid_t run_child(void (*fn)(void))
+{
+	pid_t pid;
+
+	pid = fork();
+	if (pid < 0)
+		return -1;
+
+	if (pid == 0) {
+		fn();
+		_exit(EXIT_SUCCESS);
+	}
+
+	if (waitpid(pid, NULL, 0) != pid)
+		return -1;
+	return pid;
+}
+
+static void call_getppid(void)
+{
+	syscall(SYS_getppid);
+}
+
+static void call_getppid_many(void)
+{
+	int i;
+
+	for (i = 0; i < 2000; i++)
+		syscall(SYS_getppid);
+}
+
+FN_TEST(syscall_records)
+{
+	char syscall_field[64], pid_field[32];
+	const char *patterns[] = { syscall_field, pid_field,
+				   "key=\"" RULE_KEY "\"", NULL };
+	struct audit_request getppid_req, exit_req;
+	pid_t pid;
+
+	init_rule(&getppid_req.rule, SYS_getppid, AUDIT_PPID, getpid());
+	init_rule(&exit_req.rule, SYS_exit_group, AUDIT_PPID, getpid());
+	TEST_SUCC(add_rule(&getppid_req));
+	TEST_SUCC(add_rule(&exit_req));
+
+	pid = TEST_SUCC(run_child(call_getppid));
+	snprintf(syscall_field, sizeof(syscall_field),
+		 " syscall=%d success=yes exit=%d ", SYS_getppid, getpid());
+	snprintf(pid_field, sizeof(pid_field), " pid=%d ", pid);
+	TEST_RES(find_syscall_record(patterns), _ret);
+
+	// A system call that never returns is logged without a return value.
+	snprintf(syscall_field, sizeof(syscall_field), " syscall=%d a0=0 ",
+		 SYS_exit_group);
+	TEST_RES(find_syscall_record(patterns), _ret);
+
+	TEST_SUCC(del_rule(&exit_req));
+	TEST_SUCC(del_rule(&getppid_req));
+}
+END_TEST()
+
+#ifdef __asterinas__
+static void signal_handler(int signum)
+{
+}
+
+static void call_sigreturn(void)
+{
+	CHECK_WITH(signal(SIGUSR1, signal_handler), _ret != SIG_ERR);
+	CHECK(raise(SIGUSR1));
+}
+
+FN_TEST(sigreturn_record)
+{
+	char syscall_field[64], pid_field[32];
+	const char *patterns[] = { syscall_field, pid_field,
+				   "key=\"" RULE_KEY "\"", NULL };
+	struct audit_request req;
+	pid_t pid;
+
+	init_rule(&req.rule, SYS_rt_sigreturn, AUDIT_PPID, getpid());
+	TEST_SUCC(add_rule(&req));
+
+	// Unlike Linux, the restored register is not logged as the return
+	// value of `rt_sigreturn`.
+	pid = TEST_SUCC(run_child(call_sigreturn));
+	snprintf(syscall_field, sizeof(syscall_field), " syscall=%d a0=",
+		 SYS_rt_sigreturn);
+	snprintf(pid_field, sizeof(pid_field), " pid=%d ", pid);
+	TEST_RES(find_syscall_record(patterns), _ret);
+
+	TEST_SUCC(del_rule(&req));
+}
+END_TEST()
+#endif
+
+FN_TEST(backlog_limit)
+{
+	struct audit_status status = {}, new_status;
+	struct audit_request req;
+
+	status.mask = AUDIT_STATUS_BACKLOG_LIMIT |
+		      AUDIT_STATUS_BACKLOG_WAIT_TIME;
+	status.backlog_limit = 4;
+	status.backlog_wait_time = 0;
+	TEST_SUCC(set_status(control_fd, &status));
+	TEST_SUCC(get_status(&status));
+
+	// The audit daemon does not receive the records, so the records stay
+	// in the backlog and the new records are lost once the backlog is full.
+	init_rule(&req.rule, SYS_getppid, AUDIT_PPID, getpid());
+	TEST_SUCC(add_rule(&req));
+	TEST_SUCC(run_child(call_getppid_many));
+	TEST_RES(get_status(&new_status),
+		 new_status.backlog_limit == 4 && new_status.backlog <= 5 &&
+			 new_status.lost > status.lost);
+	TEST_SUCC(del_rule(&req));
+
+	TEST_RES(drain_records(), _ret > 0);
+
+	status.mask = AUDIT_STATUS_BACKLOG_LIMIT |
+		      AUDIT_STATUS_BACKLOG_WAIT_TIME;
+	status.backlog_limit = orig_status.backlog_limit;
+	status.backlog_wait_time = orig_status.backlog_wait_time;
+	TEST_SUCC(set_status(control_fd, &status));
+}
+END_TEST()
+
+FN_TEST(unregister_daemon)
+{
+	struct audit_status status = {};
+
+	status.mask = AUDIT_STATUS_PID;
+	status.pid = 0;
+	TEST_SUCC(set_status(daemon_fd, &status));
+	TEST_RES(get_status(&status), status.pid == 0);
+}
+END_TEST()
+
+// Reads the file into `buf` as a string.
+static int read_file(const char *path, char *buf, size_t size)
+{
+	ssize_t len;
+	int fd;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	len = read(fd, buf, size - 1);
+	close(fd);
+	if (len < 0)
+		return -1;
+
+	buf[len] = '\0';
+	return 0;
+}
+
+static int write_file(const char *path, const char *content)
+{
+	ssize_t len;
+	int fd;
+
+	fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	len = write(fd, content, strlen(content));
+	close(fd);
+	return len < 0 ? -1 : 0;
+}
+
+FN_TEST(loginuid_and_sessionid)
+{
+	char buf[32], path[64], sessionid[32];
+	int status, fd;
+	pid_t pid;
+
+	// The IDs are changed in a child process, since they are inherited
+	// and cannot be unset without `CAP_AUDIT_CONTROL`.
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(read_file("/proc/self/loginuid", buf, sizeof(buf)),
+			   strcmp(buf, UNSET_ID) == 0);
+		CHECK_WITH(read_file("/proc/self/sessionid", buf, sizeof(buf)),
+			   strcmp(buf, UNSET_ID) == 0);
+
+		// Setting the login UID starts a new session.
+		CHECK(write_file("/proc/self/loginuid", "1000"));
+		CHECK_WITH(read_file("/proc/self/loginuid", buf, sizeof(buf)),
+			   strcmp(buf, "1000") == 0);
+		CHECK_WITH(read_file("/proc/self/sessionid", sessionid,
+				     sizeof(sessionid)),
+			   strcmp(sessionid, UNSET_ID) != 0);
+
+		// The login UID can be changed again with `CAP_AUDIT_CONTROL`.
+		CHECK(write_file("/proc/self/loginuid", "1001"));
+		CHECK_WITH(read_file("/proc/self/sessionid", buf, sizeof(buf)),
+			   strcmp(buf, sessionid) != 0);
+
+		// Only the thread itself can set its login UID.
+		snprintf(path, sizeof(path), "/proc/%d/loginuid", getppid());
+		CHECK_WITH(write_file(path, "1000"),
+			   _ret < 0 && errno == EPERM);
+
+		// The file is opened in advance, since it is no longer owned by
+		// the process after the UIDs are changed.
+		fd = CHECK(open("/proc/self/loginuid", O_WRONLY));
+		CHECK(setresuid(USER_UID, USER_UID, USER_UID));
+		CHECK_WITH(write(fd, "1000", 4), _ret < 0 && errno == EPERM);
+		CHECK(close(fd));
+		CHECK_WITH(read_file("/proc/self/loginuid", buf, sizeof(buf)),
+			   strcmp(buf, "1001") == 0);
+
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_RES(read_file("/proc/self/loginuid", buf, sizeof(buf)),
+		 strcmp(buf, UNSET_ID) == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	struct audit_status status = {};
+
+	status.mask = AUDIT_STATUS_ENABLED | AUDIT_STATUS_FAILURE;
+	status.enabled = orig_status.enabled;
+	status.failure = orig_status.failure;
+	CHECK(set_status(control_fd, &status));
+
+	CHECK(close(daemon_fd));
+	CHECK(close(control_fd));
+}
+END_SETUP()
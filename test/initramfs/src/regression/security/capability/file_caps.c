@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/capability.h>
+#include <sys/xattr.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define FILE_PATH "/tmp/file_caps_test"
+#define XATTR_NAME_CAPS "security.capability"
+
+// Gives `CAP_NET_BIND_SERVICE` to the file.
+static int set_file_caps(void)
+{
+	struct vfs_cap_data caps = {
+		.magic_etc = VFS_CAP_REVISION_2 | VFS_CAP_FLAGS_EFFECTIVE,
+		.data = { { 1 << CAP_NET_BIND_SERVICE, 0 }, { 0, 0 } },
+	};
+
+	return setxattr(FILE_PATH, XATTR_NAME_CAPS, &caps, XATTR_CAPS_SZ_2, 0);
+}
+
+static int has_file_caps(void)
+{
+	struct vfs_cap_data caps;
+	ssize_t len = getxattr(FILE_PATH, XATTR_NAME_CAPS, &caps, sizeof(caps));
+
+	if (len >= 0)
+		return 1;
+	if (errno != ENODATA)
+		return -1;
+
+	errno = 0;
+	return 0;
+}
+
+static int fd;
+
+FN_SETUP(file)
+{
+	fd = CHECK(open(FILE_PATH, O_RDWR | O_CREAT | O_TRUNC, 0755));
+	CHECK_WITH(write(fd, "hello", 5), _ret == 5);
+}
+END_SETUP()
+
+FN_TEST(keep_on_read)
+{
+	char buf[8];
+
+	TEST_SUCC(set_file_caps());
+	TEST_RES(has_file_caps(), _ret == 1);
+
+	TEST_RES(pread(fd, buf, sizeof(buf), 0), _ret == 5);
+	TEST_SUCC(setxattr(FILE_PATH, "user.file_caps_test", "x", 1, 0));
+	TEST_RES(has_file_caps(), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(clear_on_write)
+{
+	TEST_SUCC(set_file_caps());
+	TEST_RES(write(fd, "world", 5), _ret == 5);
+	TEST_RES(has_file_caps(), _ret == 0);
+
+	TEST_SUCC(set_file_caps());
+	TEST_RES(pwrite(fd, "hello", 5, 0), _ret == 5);
+	TEST_RES(has_file_caps(), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(clear_on_truncate)
+{
+	TEST_SUCC(set_file_caps());
+	TEST_SUCC(ftruncate(fd, 5));
+	TEST_RES(has_file_caps(), _ret == 0);
+
+	TEST_SUCC(set_file_caps());
+	TEST_SUCC(truncate(FILE_PATH, 3));
+	TEST_RES(has_file_caps(), _ret == 0);
+
+	TEST_SUCC(set_file_caps());
+	TEST_SUCC(close(TEST_SUCC(open(FILE_PATH, O_WRONLY | O_TRUNC))));
+	TEST_RES(has_file_caps(), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(clear_on_chown)
+{
+	// Even if the owner does not change, the file capabilities are cleared.
+	TEST_SUCC(set_file_caps());
+	TEST_SUCC(chown(FILE_PATH, getuid(), -1));
+	TEST_RES(has_file_caps(), _ret == 0);
+
+	TEST_SUCC(set_file_caps());
+	TEST_SUCC(fchown(fd, -1, getgid()));
+	TEST_RES(has_file_caps(), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fd));
+	CHECK(unlink(FILE_PATH));
+}
+END_SETUP()
@@ -0,0 +1,441 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <linux/keyctl.h>
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+typedef int32_t key_serial_t;
+
+#define USER_UID 1000
+#define SESSION_ENV "KEYCTL_SESSION"
+
+// The permissions of a key whose possessor has all permissions except `view`.
+#define POS_ALL_BUT_VIEW 0x3e000000
+// The permissions of a keyring whose possessor has all permissions and whose
+// owner can view, read, search, and link it.
+#define USR_SEARCHABLE 0x3f1b0000
+
+static key_serial_t add_key(const char *type, const char *description,
+			    const void *payload, size_t len,
+			    key_serial_t keyring)
+{
+	return syscall(SYS_add_key, type, description, payload, len, keyring);
+}
+
+static key_serial_t request_key(const char *type, const char *description,
+				key_serial_t dest)
+{
+	return syscall(SYS_request_key, type, description, NULL, dest);
+}
+
+static long keyctl(int option, unsigned long arg2, unsigned long arg3,
+		   unsigned long arg4, unsigned long arg5)
+{
+	return syscall(SYS_keyctl, option, arg2, arg3, arg4, arg5);
+}
+
+static key_serial_t get_keyring_id(key_serial_t id, int create)
+{
+	return keyctl(KEYCTL_GET_KEYRING_ID, id, create, 0, 0);
+}
+
+// When executed by the tests, reports in the exit status whether the thread
+// and process keyrings are discarded and the session keyring is kept. This runs
+// before any test functions.
+__attribute__((constructor(101))) static void report(void)
+{
+	const char *session = getenv(SESSION_ENV);
+
+	if (session == NULL)
+		return;
+
+	if (get_keyring_id(KEY_SPEC_THREAD_KEYRING, 0) >= 0 ||
+	    errno != ENOKEY)
+		_exit(1);
+	if (get_keyring_id(KEY_SPEC_PROCESS_KEYRING, 0) >= 0 ||
+	    errno != ENOKEY)
+		_exit(2);
+	if (get_keyring_id(KEY_SPEC_SESSION_KEYRING, 0) != atoi(session))
+		_exit(3);
+
+	_exit(EXIT_SUCCESS);
+}
+
+// Waits for the child process and returns whether it has exited successfully.
+static int wait_for_child(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	return WIFEXITED(status) && WEXITSTATUS(status) == EXIT_SUCCESS;
+}
+
+FN_SETUP(session)
+{
+	// Isolate the keys of the tests in a new anonymous session keyring.
+	CHECK(keyctl(KEYCTL_JOIN_SESSION_KEYRING, 0, 0, 0, 0));
+}
+END_SETUP()
+
+FN_TEST(add_key_and_read)
+{
+	char buf[16] = {};
+	key_serial_t key;
+
+	key = TEST_SUCC(add_key("user", "keyctl:read", "hello", 5,
+				KEY_SPEC_SESSION_KEYRING));
+	TEST_RES(keyctl(KEYCTL_READ, key, (unsigned long)buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	// The length of the payload is returned even if the buffer is too
+	// small.
+	TEST_RES(keyctl(KEYCTL_READ, key, 0, 0, 0), _ret == 5);
+
+	// Adding a key of the same type and description updates the key.
+	TEST_RES(add_key("user", "keyctl:read", "world!", 6,
+			 KEY_SPEC_SESSION_KEYRING),
+		 _ret == key);
+	TEST_RES(keyctl(KEYCTL_READ, key, (unsigned long)buf, sizeof(buf), 0),
+		 _ret == 6 && memcmp(buf, "world!", 6) == 0);
+
+	TEST_ERRNO(add_key("user", "keyctl:read", "", 0,
+			   KEY_SPEC_SESSION_KEYRING),
+		   EINVAL);
+	TEST_ERRNO(add_key("user", "", "x", 1, KEY_SPEC_SESSION_KEYRING),
+		   EINVAL);
+	TEST_ERRNO(add_key("no_such_type", "keyctl:read", "x", 1,
+			   KEY_SPEC_SESSION_KEYRING),
+		   ENODEV);
+	TEST_ERRNO(add_key("keyring", ".keyctl", NULL, 0,
+			   KEY_SPEC_SESSION_KEYRING),
+		   EPERM);
+	TEST_ERRNO(add_key("user", "keyctl:read", "x", 1, key), ENOTDIR);
+
+	// A `logon` key cannot be read from the user space.
+	key = TEST_SUCC(add_key("logon", "keyctl:logon", "secret", 6,
+				KEY_SPEC_SESSION_KEYRING));
+	TEST_ERRNO(keyctl(KEYCTL_READ, key, (unsigned long)buf, sizeof(buf), 0),
+		   EOPNOTSUPP);
+}
+END_TEST()
+
+FN_TEST(describe)
+{
+	char buf[64];
+	key_serial_t key;
+
+	key = TEST_SUCC(add_key("user", "keyctl:describe", "x", 1,
+				KEY_SPEC_SESSION_KEYRING));
+	TEST_RES(keyctl(KEYCTL_DESCRIBE, key, (unsigned long)buf, sizeof(buf),
+			0),
+		 _ret == 34 &&
+			 strcmp(buf, "user;0;0;3f010000;keyctl:describe") == 0);
+	// The length of the description is returned even if the buffer is too
+	// small.
+	TEST_RES(keyctl(KEYCTL_DESCRIBE, key, 0, 0, 0), _ret == 34);
+
+	TEST_ERRNO(keyctl(KEYCTL_DESCRIBE, 0x7fffffff, (unsigned long)buf,
+			  sizeof(buf), 0),
+		   ENOKEY);
+}
+END_TEST()
+
+FN_TEST(set_perm)
+{
+	char buf[64];
+	key_serial_t key;
+
+	key = TEST_SUCC(add_key("user", "keyctl:setperm", "x", 1,
+				KEY_SPEC_SESSION_KEYRING));
+
+	TEST_SUCC(keyctl(KEYCTL_SETPERM, key, POS_ALL_BUT_VIEW, 0, 0));
+	TEST_ERRNO(keyctl(KEYCTL_DESCRIBE, key, (unsigned long)buf, sizeof(buf),
+			  0),
+		   EACCES);
+
+	// The permissions can be restored, since the key is still searchable.
+	TEST_SUCC(keyctl(KEYCTL_SETPERM, key, 0x3f010000, 0, 0));
+	TEST_RES(keyctl(KEYCTL_DESCRIBE, key, (unsigned long)buf, sizeof(buf),
+			0),
+		 _ret > 0);
+
+	TEST_ERRNO(keyctl(KEYCTL_SETPERM, key, 0x40000000, 0, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(link_and_search)
+{
+	key_serial_t key, keyring, other_keyring;
+
+	key = TEST_SUCC(add_key("user", "keyctl:link", "x", 1,
+				KEY_SPEC_SESSION_KEYRING));
+	keyring = TEST_SUCC(add_key("keyring", "keyctl:keyring", NULL, 0,
+				    KEY_SPEC_SESSION_KEYRING));
+	other_keyring = TEST_SUCC(add_key("keyring", "keyctl:other_keyring",
+					  NULL, 0, KEY_SPEC_SESSION_KEYRING));
+
+	TEST_ERRNO(keyctl(KEYCTL_SEARCH, keyring, (unsigned long)"user",
+			  (unsigned long)"keyctl:link", 0),
+		   ENOKEY);
+	TEST_SUCC(keyctl(KEYCTL_LINK, key, keyring, 0, 0));
+	TEST_RES(keyctl(KEYCTL_SEARCH, keyring, (unsigned long)"user",
+			(unsigned long)"keyctl:link", 0),
+		 _ret == key);
+
+	// The found key is linked to the destination keyring.
+	TEST_RES(keyctl(KEYCTL_SEARCH, keyring, (unsigned long)"user",
+			(unsigned long)"keyctl:link", other_keyring),
+		 _ret == key);
+	TEST_RES(keyctl(KEYCTL_SEARCH, other_keyring, (unsigned long)"user",
+			(unsigned long)"keyctl:link", 0),
+		 _ret == key);
+
+	// Keyrings are searched recursively.
+	TEST_SUCC(keyctl(KEYCTL_UNLINK, key, other_keyring, 0, 0));
+	TEST_SUCC(keyctl(KEYCTL_LINK, keyring, other_keyring, 0, 0));
+	TEST_RES(keyctl(KEYCTL_SEARCH, other_keyring, (unsigned long)"user",
+			(unsigned long)"keyctl:link", 0),
+		 _ret == key);
+
+	TEST_SUCC(keyctl(KEYCTL_UNLINK, key, keyring, 0, 0));
+	TEST_ERRNO(keyctl(KEYCTL_UNLINK, key, keyring, 0, 0), ENOENT);
+	TEST_ERRNO(keyctl(KEYCTL_SEARCH, other_keyring, (unsigned long)"user",
+			  (unsigned long)"keyctl:link", 0),
+		   ENOKEY);
+
+	// A keyring cannot be linked into itself.
+	TEST_ERRNO(keyctl(KEYCTL_LINK, keyring, keyring, 0, 0), EDEADLK);
+	TEST_ERRNO(keyctl(KEYCTL_LINK, key, key, 0, 0), ENOTDIR);
+}
+END_TEST()
+
+FN_TEST(request_key)
+{
+	key_serial_t key, keyring;
+
+	key = TEST_SUCC(add_key("user", "keyctl:request", "x", 1,
+				KEY_SPEC_SESSION_KEYRING));
+	keyring = TEST_SUCC(add_key("keyring", "keyctl:request_keyring", NULL,
+				    0, KEY_SPEC_SESSION_KEYRING));
+
+	TEST_RES(request_key("user", "keyctl:request", 0), _ret == key);
+	TEST_RES(request_key("user", "keyctl:request", keyring), _ret == key);
+	TEST_RES(keyctl(KEYCTL_SEARCH, keyring, (unsigned long)"user",
+			(unsigned long)"keyctl:request", 0),
+		 _ret == key);
+
+	// Without a callout, a missing key is not constructed.
+	TEST_ERRNO(request_key("user", "keyctl:no_such_key", 0), ENOKEY);
+}
+END_TEST()
+
+FN_TEST(proc_keys)
+{
+	char buf[4096], expected[64];
+	key_serial_t key;
+	ssize_t len;
+	char *line;
+	int fd;
+
+	key = TEST_SUCC(add_key("user", "keyctl:proc", "hello", 5,
+				KEY_SPEC_SESSION_KEYRING));
+
+	fd = TEST_SUCC(open("/proc/keys", O_RDONLY));
+	len = TEST_SUCC(read(fd, buf, sizeof(buf) - 1));
+	buf[len] = '\0';
+	TEST_SUCC(close(fd));
+
+	snprintf(expected, sizeof(expected), "%08x ", key);
+	line = strstr(buf, expected);
+	TEST_RES(line != NULL, _ret);
+	if (line == NULL)
+		return;
+	*strchrnul(line, '\n') = '\0';
+	TEST_RES(strstr(line, " perm 3f010000     0     0 user      "
+			      "keyctl:proc: 5"),
+		 _ret != NULL);
+}
+END_TEST()
+
+// Adds keys as a non-root user until the quota is exceeded.
+static void exceed_quota(void)
+{
+	static char payload[32768];
+	char description[32];
+	int i, err = 0;
+
+	// Join a new session keyring owned by the user.
+	CHECK(keyctl(KEYCTL_JOIN_SESSION_KEYRING, 0, 0, 0, 0));
+
+	// The payload of a `user` key is limited to 32767 bytes.
+	CHECK_WITH(add_key("user", "keyctl:quota", payload, 32768,
+			   KEY_SPEC_SESSION_KEYRING),
+		   _ret < 0 && errno == EINVAL);
+
+	// A non-root user can own 200 keys that occupy 20000 bytes.
+	for (i = 0; i < 200; i++) {
+		snprintf(description, sizeof(description), "keyctl:quota%d",
+			 i);
+		if (add_key("user", description, payload, 100,
+			    KEY_SPEC_SESSION_KEYRING) < 0) {
+			err = errno;
+			break;
+		}
+	}
+	CHECK_WITH(i, _ret > 100 && _ret < 200 && err == EDQUOT);
+
+	CHECK(keyctl(KEYCTL_CLEAR, KEY_SPEC_SESSION_KEYRING, 0, 0, 0));
+}
+
+FN_TEST(quota)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		char buf[1024];
+		ssize_t len;
+		int fd;
+
+		CHECK(setresuid(USER_UID, USER_UID, USER_UID));
+		exceed_quota();
+
+		fd = CHECK(open("/proc/key-users", O_RDONLY));
+		len = CHECK(read(fd, buf, sizeof(buf) - 1));
+		buf[len] = '\0';
+		CHECK_WITH(strstr(buf, " 1000: "), _ret != NULL);
+		CHECK_WITH(strstr(strstr(buf, " 1000: "), "/200 "),
+			   _ret != NULL);
+		CHECK_WITH(strstr(strstr(buf, " 1000: "), "/20000\n"),
+			   _ret != NULL);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(join_session_keyring)
+{
+	key_serial_t session, named;
+	pid_t pid;
+
+	session = TEST_SUCC(get_keyring_id(KEY_SPEC_SESSION_KEYRING, 0));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// The child process inherits the session keyring.
+		CHECK_WITH(get_keyring_id(KEY_SPEC_SESSION_KEYRING, 0),
+			   _ret == session);
+
+		named = CHECK(keyctl(KEYCTL_JOIN_SESSION_KEYRING,
+				     (unsigned long)"keyctl:session", 0, 0, 0));
+		CHECK_WITH(get_keyring_id(KEY_SPEC_SESSION_KEYRING, 0),
+			   _ret == named && named != session);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_child(pid), _ret == 1);
+
+	// The session keyring of the parent is not changed.
+	TEST_RES(get_keyring_id(KEY_SPEC_SESSION_KEYRING, 0), _ret == session);
+
+	// A keyring of the name is joined if it can be searched.
+	named = TEST_SUCC(add_key("keyring", "keyctl:session", NULL, 0,
+				  KEY_SPEC_SESSION_KEYRING));
+	TEST_SUCC(keyctl(KEYCTL_SETPERM, named, USR_SEARCHABLE, 0, 0));
+	TEST_RES(keyctl(KEYCTL_JOIN_SESSION_KEYRING,
+			(unsigned long)"keyctl:session", 0, 0, 0),
+		 _ret == named);
+	TEST_RES(get_keyring_id(KEY_SPEC_SESSION_KEYRING, 0), _ret == named);
+
+	// Joining the current session keyring again returns zero.
+	TEST_RES(keyctl(KEYCTL_JOIN_SESSION_KEYRING,
+			(unsigned long)"keyctl:session", 0, 0, 0),
+		 _ret == 0);
+
+	// Otherwise, a new keyring of the name is created.
+	named = TEST_SUCC(add_key("keyring", "keyctl:hidden", NULL, 0,
+				  KEY_SPEC_SESSION_KEYRING));
+	TEST_RES(keyctl(KEYCTL_JOIN_SESSION_KEYRING,
+			(unsigned long)"keyctl:hidden", 0, 0, 0),
+		 _ret > 0 && _ret != named);
+}
+END_TEST()
+
+static key_serial_t process_keyring;
+
+static void *check_thread_keyrings(void *arg)
+{
+	key_serial_t thread_keyring = (intptr_t)arg;
+
+	// The threads share the process keyring but not the thread keyring.
+	CHECK_WITH(get_keyring_id(KEY_SPEC_PROCESS_KEYRING, 0),
+		   _ret == process_keyring);
+	CHECK_WITH(get_keyring_id(KEY_SPEC_THREAD_KEYRING, 1),
+		   _ret > 0 && _ret != thread_keyring);
+	return NULL;
+}
+
+FN_TEST(clone)
+{
+	key_serial_t thread_keyring;
+	pthread_t thread;
+	pid_t pid;
+
+	process_keyring =
+		TEST_SUCC(get_keyring_id(KEY_SPEC_PROCESS_KEYRING, 1));
+	thread_keyring = TEST_SUCC(get_keyring_id(KEY_SPEC_THREAD_KEYRING, 1));
+
+	TEST_RES(pthread_create(&thread, NULL, check_thread_keyrings,
+				(void *)(intptr_t)thread_keyring),
+		 _ret == 0);
+	TEST_RES(pthread_join(thread, NULL), _ret == 0);
+
+	// A child process has its own process keyring.
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(get_keyring_id(KEY_SPEC_THREAD_KEYRING, 0),
+			   _ret < 0 && errno == ENOKEY);
+		CHECK_WITH(get_keyring_id(KEY_SPEC_PROCESS_KEYRING, 0),
+			   _ret < 0 && errno == ENOKEY);
+		CHECK_WITH(get_keyring_id(KEY_SPEC_PROCESS_KEYRING, 1),
+			   _ret > 0 && _ret != process_keyring);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(wait_for_child(pid), _ret == 1);
+
+	TEST_RES(get_keyring_id(KEY_SPEC_PROCESS_KEYRING, 0),
+		 _ret == process_keyring);
+}
+END_TEST()
+
+FN_TEST(exec)
+{
+	char session_env[64];
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		char *argv[] = { "keyctl", NULL };
+		char *envp[] = { session_env, NULL };
+
+		CHECK(get_keyring_id(KEY_SPEC_THREAD_KEYRING, 1));
+		CHECK(get_keyring_id(KEY_SPEC_PROCESS_KEYRING, 1));
+		snprintf(session_env, sizeof(session_env), "%s=%d", SESSION_ENV,
+			 CHECK(get_keyring_id(KEY_SPEC_SESSION_KEYRING, 0)));
+
+		CHECK(execve("/proc/self/exe", argv, envp));
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 1);
+}
+END_TEST()
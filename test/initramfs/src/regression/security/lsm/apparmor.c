@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <fcntl.h>
+#include <stdbool.h>
+#include <stdlib.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+// AppArmor is only enabled if it is selected with `lsm=` or `security=` on the
+// kernel command line. Otherwise, the tests are skipped.
+#define APPARMOR_DIR "/sys/kernel/security/apparmor"
+
+#define PROG "/tmp/apparmor_prog"
+#define CHILD_PROG "/tmp/apparmor_child"
+#define NOEXEC_PROG "/tmp/apparmor_noexec"
+#define PUBLIC_FILE "/tmp/apparmor_public"
+#define SECRET_FILE "/tmp/apparmor_secret"
+#define REPORT_ENV "APPARMOR_REPORT"
+
+// The bits in the exit status of the executed program.
+#define CAN_READ_PUBLIC 1
+#define CAN_READ_SECRET 2
+#define EXEC_FAILED 4
+
+// `PROG` may read everything except `SECRET_FILE`, and it switches to the
+// profile of `CHILD_PROG` when executing it. `CHILD_PROG` may read everything
+// except `PUBLIC_FILE`.
+#define POLICY(flags)                                   \
+	"profile apparmor_test " PROG " " flags " {\n"  \
+	"  /** rm,\n"                                   \
+	"  deny " SECRET_FILE " r,\n"                   \
+	"  " CHILD_PROG " px -> apparmor_test_child,\n" \
+	"}\n"                                           \
+	"profile apparmor_test_child {\n"               \
+	"  /** rm,\n"                                   \
+	"  deny " PUBLIC_FILE " r,\n"                   \
+	"}\n"
+
+// When executed by the tests, reports the files that can be read in the exit
+// status. If an argument is given, executes it afterwards. This runs before any
+// test functions.
+__attribute__((constructor(101))) static void report(int argc, char **argv)
+{
+	int status = 0;
+	int fd;
+
+	if (getenv(REPORT_ENV) == NULL)
+		return;
+
+	fd = open(PUBLIC_FILE, O_RDONLY);
+	if (fd >= 0) {
+		status |= CAN_READ_PUBLIC;
+		close(fd);
+	}
+	fd = open(SECRET_FILE, O_RDONLY);
+	if (fd >= 0) {
+		status |= CAN_READ_SECRET;
+		close(fd);
+	}
+
+	if (argc > 1) {
+		char *new_argv[] = { argv[1], NULL };
+
+		execve(argv[1], new_argv, environ);
+		status |= EXEC_FAILED;
+	}
+
+	_exit(status);
+}
+
+static int write_file(const char *path, const char *content)
+{
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	len = write(fd, content, strlen(content));
+	close(fd);
+	return len < 0 ? -1 : 0;
+}
+
+static int copy_file(const char *src_path, const char *dst_path)
+{
+	char buf[4096];
+	ssize_t len;
+	int src, dst;
+
+	src = open(src_path, O_RDONLY);
+	if (src < 0)
+		return -1;
+	dst = open(dst_path, O_WRONLY | O_CREAT | O_TRUNC, 0755);
+	if (dst < 0) {
+		close(src);
+		return -1;
+	}
+
+	while ((len = read(src, buf, sizeof(buf))) > 0)
+		if (write(dst, buf, len) != len)
+			break;
+
+	close(dst);
+	close(src);
+	return len == 0 ? 0 : -1;
+}
+
+// Returns whether the loaded profiles are listed exactly as `expected`.
+static bool profiles_are(const char *expected)
+{
+	char buf[256];
+	ssize_t len;
+	int fd;
+
+	fd = CHECK(open(APPARMOR_DIR "/profiles", O_RDONLY));
+	len = CHECK(read(fd, buf, sizeof(buf) - 1));
+	CHECK(close(fd));
+
+	buf[len] = '\0';
+	return strcmp(buf, expected) == 0;
+}
+
+// Executes `PROG` with an optional program to execute afterwards and returns
+// its exit status.
+static int run_prog(const char *exec_path)
+{
+	char *argv[] = { PROG, (char *)exec_path, NULL };
+	char *envp[] = { REPORT_ENV "=1", NULL };
+	int status;
+	pid_t pid;
+
+	pid = fork();
+	if (pid < 0)
+		return -1;
+
+	if (pid == 0) {
+		CHECK(execve(PROG, argv, envp));
+		_exit(EXIT_FAILURE);
+	}
+
+	if (waitpid(pid, &status, 0) != pid || !WIFEXITED(status))
+		return -1;
+	return WEXITSTATUS(status);
+}
+
+static bool is_enabled;
+
+FN_SETUP(files)
+{
+	is_enabled = access(APPARMOR_DIR, F_OK) == 0;
+
+	CHECK(copy_file("/proc/self/exe", PROG));
+	CHECK(copy_file("/proc/self/exe", CHILD_PROG));
+	CHECK(copy_file("/proc/self/exe", NOEXEC_PROG));
+	CHECK(close(CHECK(open(PUBLIC_FILE, O_WRONLY | O_CREAT, 0644))));
+	CHECK(close(CHECK(open(SECRET_FILE, O_WRONLY | O_CREAT, 0644))));
+}
+END_SETUP()
+
+FN_TEST(unconfined)
+{
+	SKIP_TEST_IF(!is_enabled);
+
+	TEST_RES(run_prog(NULL), _ret == (CAN_READ_PUBLIC | CAN_READ_SECRET));
+}
+END_TEST()
+
+FN_TEST(load)
+{
+	SKIP_TEST_IF(!is_enabled);
+
+	TEST_SUCC(write_file(APPARMOR_DIR "/.load", POLICY("")));
+	TEST_RES(profiles_are("apparmor_test (enforce)\n"
+			      "apparmor_test_child (enforce)\n"),
+		 _ret);
+
+	// Loaded profiles are not replaced by `.load`.
+	TEST_ERRNO(write_file(APPARMOR_DIR "/.load", POLICY("")), EEXIST);
+	TEST_ERRNO(write_file(APPARMOR_DIR "/.load", "profile {"), EINVAL);
+}
+END_TEST()
+
+FN_TEST(enforce)
+{
+	SKIP_TEST_IF(!is_enabled);
+
+	TEST_RES(run_prog(NULL), _ret == CAN_READ_PUBLIC);
+
+	// Executing a program without the `x` permission fails.
+	TEST_RES(run_prog(NOEXEC_PROG),
+		 _ret == (CAN_READ_PUBLIC | EXEC_FAILED));
+}
+END_TEST()
+
+FN_TEST(exec_transition)
+{
+	SKIP_TEST_IF(!is_enabled);
+
+	// The executed program is confined by the profile named in the rule.
+	TEST_RES(run_prog(CHILD_PROG), _ret == CAN_READ_SECRET);
+}
+END_TEST()
+
+FN_TEST(complain)
+{
+	SKIP_TEST_IF(!is_enabled);
+
+	TEST_SUCC(write_file(APPARMOR_DIR "/.replace",
+			     POLICY("flags=(complain)")));
+	TEST_RES(profiles_are("apparmor_test (complain)\n"
+			      "apparmor_test_child (enforce)\n"),
+		 _ret);
+
+	// The disallowed operations are allowed but reported.
+	TEST_RES(run_prog(NULL), _ret == (CAN_READ_PUBLIC | CAN_READ_SECRET));
+	TEST_RES(run_prog(NOEXEC_PROG),
+		 _ret == (CAN_READ_PUBLIC | CAN_READ_SECRET));
+
+	// The transition to an enforced profile still takes place.
+	TEST_RES(run_prog(CHILD_PROG), _ret == CAN_READ_SECRET);
+}
+END_TEST()
+
+FN_TEST(remove)
+{
+	SKIP_TEST_IF(!is_enabled);
+
+	TEST_SUCC(write_file(APPARMOR_DIR "/.remove", "apparmor_test_child"));
+	TEST_RES(profiles_are("apparmor_test (complain)\n"), _ret);
+
+	// The target profile no longer exists, so the transition fails.
+	TEST_SUCC(write_file(APPARMOR_DIR "/.replace", POLICY("")));
+	TEST_SUCC(write_file(APPARMOR_DIR "/.remove", "apparmor_test_child"));
+	TEST_RES(run_prog(CHILD_PROG), _ret == (CAN_READ_PUBLIC | EXEC_FAILED));
+
+	TEST_SUCC(write_file(APPARMOR_DIR "/.remove", "apparmor_test"));
+	TEST_ERRNO(write_file(APPARMOR_DIR "/.remove", "apparmor_test"),
+		   ENOENT);
+	TEST_RES(profiles_are(""), _ret);
+
+	TEST_RES(run_prog(NULL), _ret == (CAN_READ_PUBLIC | CAN_READ_SECRET));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(SECRET_FILE));
+	CHECK(unlink(PUBLIC_FILE));
+	CHECK(unlink(NOEXEC_PROG));
+	CHECK(unlink(CHILD_PROG));
+	CHECK(unlink(PROG));
+}
+END_SETUP()
@@ -0,0 +1,383 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../../common/test.h"
+#include <arpa/inet.h>
+#include <fcntl.h>
+#include <linux/types.h>
+#include <netinet/in.h>
+#include <stdlib.h>
+#include <sys/prctl.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/un.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define TEST_DIR "/tmp/landlock"
+#define ALLOWED_DIR TEST_DIR "/allowed"
+#define DENIED_DIR TEST_DIR "/denied"
+#define ALLOWED_FILE ALLOWED_DIR "/file"
+#define DENIED_FILE DENIED_DIR "/file"
+
+#define ALLOWED_PORT 8780
+#define DENIED_PORT 8781
+#define LISTEN_PORT 8782
+
+#define UNIX_SOCKET_PATH "/tmp/landlock_socket"
+
+// The definitions in <linux/landlock.h> may be too old to include the TCP port
+// rules, so they are defined here.
+#define LANDLOCK_CREATE_RULESET_VERSION (1U << 0)
+
+#define LANDLOCK_RULE_PATH_BENEATH 1
+#define LANDLOCK_RULE_NET_PORT 2
+
+#define LANDLOCK_ACCESS_FS_EXECUTE (1ULL << 0)
+#define LANDLOCK_ACCESS_FS_WRITE_FILE (1ULL << 1)
+#define LANDLOCK_ACCESS_FS_READ_FILE (1ULL << 2)
+#define LANDLOCK_ACCESS_FS_READ_DIR (1ULL << 3)
+#define LANDLOCK_ACCESS_FS_REMOVE_FILE (1ULL << 5)
+#define LANDLOCK_ACCESS_FS_MAKE_REG (1ULL << 8)
+#define LANDLOCK_ACCESS_FS_TRUNCATE (1ULL << 14)
+
+#define LANDLOCK_ACCESS_NET_BIND_TCP (1ULL << 0)
+#define LANDLOCK_ACCESS_NET_CONNECT_TCP (1ULL << 1)
+
+struct landlock_ruleset_attr {
+	__u64 handled_access_fs;
+	__u64 handled_access_net;
+};
+
+struct landlock_path_beneath_attr {
+	__u64 allowed_access;
+	__s32 parent_fd;
+} __attribute__((packed));
+
+struct landlock_net_port_attr {
+	__u64 allowed_access;
+	__u64 port;
+};
+
+#define ACCESS_FS                                                      \
+	(LANDLOCK_ACCESS_FS_READ_FILE | LANDLOCK_ACCESS_FS_WRITE_FILE | \
+	 LANDLOCK_ACCESS_FS_READ_DIR | LANDLOCK_ACCESS_FS_MAKE_REG |    \
+	 LANDLOCK_ACCESS_FS_REMOVE_FILE | LANDLOCK_ACCESS_FS_TRUNCATE)
+#define ACCESS_NET \
+	(LANDLOCK_ACCESS_NET_BIND_TCP | LANDLOCK_ACCESS_NET_CONNECT_TCP)
+
+static int create_ruleset(__u64 handled_access_fs, __u64 handled_access_net)
+{
+	struct landlock_ruleset_attr attr = {
+		.handled_access_fs = handled_access_fs,
+		.handled_access_net = handled_access_net,
+	};
+
+	return syscall(SYS_landlock_create_ruleset, &attr, sizeof(attr), 0);
+}
+
+static int add_path_rule(int ruleset_fd, const char *path, __u64 access)
+{
+	struct landlock_path_beneath_attr attr = {
+		.allowed_access = access,
+	};
+	int ret;
+
+	attr.parent_fd = open(path, O_PATH | O_CLOEXEC);
+	if (attr.parent_fd < 0)
+		return -1;
+
+	ret = syscall(SYS_landlock_add_rule, ruleset_fd,
+		      LANDLOCK_RULE_PATH_BENEATH, &attr, 0);
+	close(attr.parent_fd);
+	return ret;
+}
+
+static int add_port_rule(int ruleset_fd, __u64 port, __u64 access)
+{
+	struct landlock_net_port_attr attr = {
+		.allowed_access = access,
+		.port = port,
+	};
+
+	return syscall(SYS_landlock_add_rule, ruleset_fd,
+		       LANDLOCK_RULE_NET_PORT, &attr, 0);
+}
+
+static int restrict_self(int ruleset_fd)
+{
+	return syscall(SYS_landlock_restrict_self, ruleset_fd, 0);
+}
+
+static int wait_for_child(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	return WIFEXITED(status) && WEXITSTATUS(status) == EXIT_SUCCESS ? 0 :
+									  -1;
+}
+
+static int tcp_socket_at(int port, int (*op)(int, const struct sockaddr *,
+					      socklen_t))
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(port),
+		.sin_addr = { htonl(INADDR_LOOPBACK) },
+	};
+	int sockfd = socket(AF_INET, SOCK_STREAM, 0);
+	int ret;
+
+	if (sockfd < 0)
+		return -1;
+	ret = op(sockfd, (struct sockaddr *)&addr, sizeof(addr));
+	close(sockfd);
+	return ret;
+}
+
+FN_SETUP(files)
+{
+	CHECK(mkdir(TEST_DIR, 0755));
+	CHECK(mkdir(ALLOWED_DIR, 0755));
+	CHECK(mkdir(DENIED_DIR, 0755));
+	CHECK(close(CHECK(open(ALLOWED_FILE, O_CREAT | O_WRONLY, 0644))));
+	CHECK(close(CHECK(open(DENIED_FILE, O_CREAT | O_WRONLY, 0644))));
+}
+END_SETUP()
+
+FN_TEST(create_ruleset)
+{
+	struct landlock_ruleset_attr attr = {};
+	int fd;
+
+	TEST_RES(syscall(SYS_landlock_create_ruleset, NULL, 0,
+			 LANDLOCK_CREATE_RULESET_VERSION),
+		 _ret >= 4);
+
+	TEST_ERRNO(create_ruleset(0, 0), ENOMSG);
+	TEST_ERRNO(create_ruleset(1ULL << 63, 0), EINVAL);
+	TEST_ERRNO(syscall(SYS_landlock_create_ruleset, &attr, sizeof(attr),
+			   1 << 31),
+		   EINVAL);
+
+	// The ruleset is closed on `execve()`.
+	fd = TEST_RES(create_ruleset(ACCESS_FS, 0),
+		      (fcntl(_ret, F_GETFD) & FD_CLOEXEC) != 0);
+
+	// The allowed actions must be handled by the ruleset.
+	TEST_ERRNO(add_path_rule(fd, ALLOWED_DIR, 0), ENOMSG);
+	TEST_ERRNO(add_path_rule(fd, ALLOWED_DIR, LANDLOCK_ACCESS_FS_EXECUTE),
+		   EINVAL);
+	TEST_ERRNO(add_port_rule(fd, ALLOWED_PORT,
+				 LANDLOCK_ACCESS_NET_BIND_TCP),
+		   EINVAL);
+
+	TEST_SUCC(close(fd));
+
+	// Only rulesets can be enforced.
+	TEST_ERRNO(restrict_self(STDIN_FILENO), EBADFD);
+}
+END_TEST()
+
+FN_TEST(restrict_requires_no_new_privs)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int fd = CHECK(create_ruleset(ACCESS_FS, 0));
+
+		CHECK(setresuid(65534, 65534, 65534));
+		errno = 0;
+		CHECK_WITH(restrict_self(fd), _ret == -1 && errno == EPERM);
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(restrict_self(fd));
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_SUCC(wait_for_child(pid));
+}
+END_TEST()
+
+FN_TEST(restrict_fs)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int fd = CHECK(create_ruleset(ACCESS_FS, 0));
+
+		CHECK(add_path_rule(fd, ALLOWED_DIR, ACCESS_FS));
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(restrict_self(fd));
+		CHECK(close(fd));
+
+		// Everything is allowed beneath the allowed directory.
+		CHECK(close(CHECK(open(ALLOWED_FILE, O_RDWR))));
+		CHECK(close(CHECK(open(ALLOWED_DIR, O_RDONLY | O_DIRECTORY))));
+		CHECK(truncate(ALLOWED_FILE, 0));
+		CHECK(close(CHECK(open(ALLOWED_DIR "/new", O_CREAT | O_WRONLY,
+				       0644))));
+		CHECK(unlink(ALLOWED_DIR "/new"));
+
+		// Nothing is allowed elsewhere.
+		errno = 0;
+		CHECK_WITH(open(DENIED_FILE, O_RDONLY),
+			   _ret == -1 && errno == EACCES);
+		errno = 0;
+		CHECK_WITH(open(DENIED_FILE, O_WRONLY),
+			   _ret == -1 && errno == EACCES);
+		errno = 0;
+		CHECK_WITH(open(DENIED_DIR, O_RDONLY | O_DIRECTORY),
+			   _ret == -1 && errno == EACCES);
+		errno = 0;
+		CHECK_WITH(truncate(DENIED_FILE, 0),
+			   _ret == -1 && errno == EACCES);
+		errno = 0;
+		CHECK_WITH(open(DENIED_DIR "/new", O_CREAT | O_WRONLY, 0644),
+			   _ret == -1 && errno == EACCES);
+		errno = 0;
+		CHECK_WITH(unlink(DENIED_FILE), _ret == -1 && errno == EACCES);
+
+		// Actions not handled by the ruleset are still allowed.
+		CHECK(close(CHECK(open(DENIED_FILE, O_PATH))));
+		CHECK(mkdir(DENIED_DIR "/dir", 0755));
+		CHECK(rmdir(DENIED_DIR "/dir"));
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_SUCC(wait_for_child(pid));
+}
+END_TEST()
+
+FN_TEST(restrict_fs_nested)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int fd = CHECK(create_ruleset(ACCESS_FS, 0));
+
+		CHECK(add_path_rule(fd, TEST_DIR, ACCESS_FS));
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(restrict_self(fd));
+		CHECK(close(fd));
+
+		CHECK(close(CHECK(open(ALLOWED_FILE, O_RDONLY))));
+		CHECK(close(CHECK(open(DENIED_FILE, O_RDONLY))));
+
+		// A nested ruleset can only add restrictions.
+		fd = CHECK(create_ruleset(LANDLOCK_ACCESS_FS_READ_FILE, 0));
+		CHECK(add_path_rule(fd, ALLOWED_DIR,
+				    LANDLOCK_ACCESS_FS_READ_FILE));
+		CHECK(restrict_self(fd));
+		CHECK(close(fd));
+
+		CHECK(close(CHECK(open(ALLOWED_FILE, O_RDONLY))));
+		errno = 0;
+		CHECK_WITH(open(DENIED_FILE, O_RDONLY),
+			   _ret == -1 && errno == EACCES);
+
+		// The restrictions are inherited by child processes.
+		pid_t child = CHECK(fork());
+		if (child == 0) {
+			errno = 0;
+			CHECK_WITH(open(DENIED_FILE, O_RDONLY),
+				   _ret == -1 && errno == EACCES);
+			_exit(EXIT_SUCCESS);
+		}
+		CHECK(wait_for_child(child));
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_SUCC(wait_for_child(pid));
+}
+END_TEST()
+
+FN_TEST(restrict_tcp)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(LISTEN_PORT),
+		.sin_addr = { htonl(INADDR_LOOPBACK) },
+	};
+	int listener;
+	pid_t pid;
+
+	listener = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	TEST_SUCC(bind(listener, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_SUCC(listen(listener, 1));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int fd = CHECK(create_ruleset(0, ACCESS_NET));
+
+		CHECK(add_port_rule(fd, ALLOWED_PORT,
+				    LANDLOCK_ACCESS_NET_BIND_TCP));
+		CHECK(add_port_rule(fd, LISTEN_PORT,
+				    LANDLOCK_ACCESS_NET_CONNECT_TCP));
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(restrict_self(fd));
+		CHECK(close(fd));
+
+		CHECK(tcp_socket_at(ALLOWED_PORT, bind));
+		CHECK(tcp_socket_at(LISTEN_PORT, connect));
+		errno = 0;
+		CHECK_WITH(tcp_socket_at(DENIED_PORT, bind),
+			   _ret == -1 && errno == EACCES);
+		errno = 0;
+		CHECK_WITH(tcp_socket_at(ALLOWED_PORT, connect),
+			   _ret == -1 && errno == EACCES);
+
+		// IPv6 TCP sockets are restricted in the same way.
+		struct sockaddr_in6 addr6 = {
+			.sin6_family = AF_INET6,
+			.sin6_port = htons(DENIED_PORT),
+			.sin6_addr = IN6ADDR_LOOPBACK_INIT,
+		};
+		int sockfd = CHECK(socket(AF_INET6, SOCK_STREAM, 0));
+		errno = 0;
+		CHECK_WITH(bind(sockfd, (struct sockaddr *)&addr6,
+				sizeof(addr6)),
+			   _ret == -1 && errno == EACCES);
+		CHECK(close(sockfd));
+
+		// UDP sockets are not restricted.
+		addr.sin_port = htons(DENIED_PORT);
+		sockfd = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+		CHECK(bind(sockfd, (struct sockaddr *)&addr, sizeof(addr)));
+		CHECK(connect(sockfd, (struct sockaddr *)&addr, sizeof(addr)));
+		CHECK(close(sockfd));
+
+		// UNIX sockets are not restricted either.
+		struct sockaddr_un addr_un = {
+			.sun_family = AF_UNIX,
+			.sun_path = UNIX_SOCKET_PATH,
+		};
+		sockfd = CHECK(socket(AF_UNIX, SOCK_STREAM, 0));
+		CHECK(bind(sockfd, (struct sockaddr *)&addr_un,
+			   sizeof(addr_un)));
+		CHECK(close(sockfd));
+		CHECK(unlink(UNIX_SOCKET_PATH));
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_SUCC(wait_for_child(pid));
+	TEST_SUCC(close(listener));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(ALLOWED_FILE));
+	CHECK(unlink(DENIED_FILE));
+	CHECK(rmdir(ALLOWED_DIR));
+	CHECK(rmdir(DENIED_DIR));
+	CHECK(rmdir(TEST_DIR));
+}
+END_SETUP()
@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/socket.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define CHECK_EXITED(pid)                                           \
+	({                                                          \
+		int __status;                                       \
+		CHECK_WITH(waitpid(pid, &__status, 0),              \
+			   _ret == pid && WIFEXITED(__status) &&    \
+				   WEXITSTATUS(__status) ==         \
+					   EXIT_SUCCESS);           \
+	})
+
+// Forks a child that runs as the init process of a new PID namespace.
+static pid_t fork_pid_ns_init(void)
+{
+	pid_t pid = CHECK(fork());
+	if (pid != 0)
+		return pid;
+
+	CHECK(unshare(CLONE_NEWPID));
+
+	pid_t init = CHECK(fork());
+	if (init == 0)
+		return 0;
+
+	CHECK_EXITED(init);
+	_exit(EXIT_SUCCESS);
+}
+
+FN_TEST(init_ignores_signals_from_inside)
+{
+	pid_t pid = TEST_SUCC(fork_pid_ns_init());
+	if (pid == 0) {
+		CHECK_WITH(getpid(), _ret == 1);
+		CHECK_WITH(getppid(), _ret == 0);
+
+		pid_t child = CHECK(fork());
+		if (child == 0) {
+			CHECK_WITH(getpid(), _ret == 2);
+			CHECK_WITH(getppid(), _ret == 1);
+
+			CHECK(kill(1, SIGTERM));
+			CHECK(kill(1, SIGKILL));
+			CHECK(kill(1, SIGSTOP));
+			_exit(EXIT_SUCCESS);
+		}
+		CHECK_WITH(child, _ret == 2);
+		CHECK_EXITED(child);
+
+		// The init process cannot even kill itself.
+		CHECK(kill(1, SIGKILL));
+		CHECK(raise(SIGTERM));
+
+		_exit(EXIT_SUCCESS);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+}
+END_TEST()
+
+FN_TEST(init_killed_from_parent_ns)
+{
+	int pipefd[2];
+	TEST_SUCC(pipe(pipefd));
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(close(pipefd[0]));
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			for (;;)
+				pause();
+		}
+
+		CHECK_WITH(write(pipefd[1], &init, sizeof(init)),
+			   _ret == sizeof(init));
+
+		int status;
+		CHECK_WITH(waitpid(init, &status, 0),
+			   _ret == init && WIFSIGNALED(status) &&
+				   WTERMSIG(status) == SIGKILL);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_SUCC(close(pipefd[1]));
+
+	pid_t init;
+	TEST_RES(read(pipefd[0], &init, sizeof(init)), _ret == sizeof(init));
+
+	// `SIGTERM` is still ignored, but `SIGKILL` from an ancestor namespace
+	// is delivered.
+	TEST_SUCC(kill(init, SIGTERM));
+	TEST_SUCC(kill(init, SIGKILL));
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	TEST_SUCC(close(pipefd[0]));
+}
+END_TEST()
+
+FN_TEST(si_pid_in_receiver_ns)
+{
+	pid_t pid = TEST_SUCC(fork_pid_ns_init());
+	if (pid == 0) {
+		sigset_t mask;
+		sigemptyset(&mask);
+		sigaddset(&mask, SIGUSR1);
+		sigaddset(&mask, SIGCHLD);
+		CHECK(sigprocmask(SIG_BLOCK, &mask, NULL));
+
+		pid_t child = CHECK(fork());
+		if (child == 0) {
+			CHECK(kill(1, SIGUSR1));
+			_exit(3);
+		}
+
+		siginfo_t info;
+		CHECK_WITH(waitid(P_PID, child, &info, WEXITED | WNOWAIT),
+			   _ret == 0);
+
+		CHECK_WITH(sigwaitinfo(&mask, &info), _ret == SIGUSR1);
+		CHECK_WITH(info.si_pid, _ret == child);
+		CHECK_WITH(info.si_code, _ret == SI_USER);
+
+		CHECK_WITH(sigwaitinfo(&mask, &info), _ret == SIGCHLD);
+		CHECK_WITH(info.si_pid, _ret == child);
+		CHECK_WITH(info.si_code, _ret == CLD_EXITED);
+		CHECK_WITH(info.si_status, _ret == 3);
+
+		CHECK_WITH(waitpid(child, NULL, 0), _ret == child);
+		_exit(EXIT_SUCCESS);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+}
+END_TEST()
+
+FN_TEST(si_pid_in_parent_ns)
+{
+	sigset_t mask;
+	sigemptyset(&mask);
+	sigaddset(&mask, SIGCHLD);
+	TEST_SUCC(sigprocmask(SIG_BLOCK, &mask, NULL));
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0)
+			_exit(5);
+
+		siginfo_t info;
+		CHECK_WITH(sigwaitinfo(&mask, &info), _ret == SIGCHLD);
+		CHECK_WITH(info.si_pid, _ret == init);
+		CHECK_WITH(info.si_code, _ret == CLD_EXITED);
+		CHECK_WITH(info.si_status, _ret == 5);
+
+		CHECK_WITH(waitpid(init, NULL, 0), _ret == init);
+		_exit(EXIT_SUCCESS);
+	}
+
+	siginfo_t info;
+	TEST_RES(sigwaitinfo(&mask, &info),
+		 _ret == SIGCHLD && info.si_pid == pid);
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	TEST_SUCC(sigprocmask(SIG_UNBLOCK, &mask, NULL));
+}
+END_TEST()
+
+static pid_t peer_pid(int sockfd)
+{
+	struct ucred cred;
+	socklen_t len = sizeof(cred);
+
+	if (getsockopt(sockfd, SOL_SOCKET, SO_PEERCRED, &cred, &len) < 0)
+		return -1;
+	return cred.pid;
+}
+
+FN_TEST(peer_cred_in_receiver_ns)
+{
+	int sv[2];
+	int one = 1;
+	TEST_SUCC(socketpair(AF_UNIX, SOCK_STREAM, 0, sv));
+	TEST_RES(peer_pid(sv[0]), _ret == getpid());
+	TEST_SUCC(
+		setsockopt(sv[1], SOL_SOCKET, SO_PASSCRED, &one, sizeof(one)));
+	TEST_RES(write(sv[0], "x", 1), _ret == 1);
+
+	pid_t pid = TEST_SUCC(fork_pid_ns_init());
+	if (pid == 0) {
+		// The creator of the socket pair is not visible.
+		CHECK_WITH(peer_pid(sv[1]), _ret == 0);
+
+		int inner_sv[2];
+		CHECK(socketpair(AF_UNIX, SOCK_STREAM, 0, inner_sv));
+		CHECK_WITH(peer_pid(inner_sv[0]), _ret == 1);
+
+		// The credentials of the sender are not visible either.
+		char buf[1];
+		char cbuf[CMSG_SPACE(sizeof(struct ucred))];
+		struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+		struct msghdr msg = {
+			.msg_iov = &iov,
+			.msg_iovlen = 1,
+			.msg_control = cbuf,
+			.msg_controllen = sizeof(cbuf),
+		};
+		CHECK_WITH(recvmsg(sv[1], &msg, 0), _ret == 1);
+
+		struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+		CHECK_WITH(cmsg != NULL && cmsg->cmsg_type == SCM_CREDENTIALS,
+			   _ret);
+		CHECK_WITH(((struct ucred *)CMSG_DATA(cmsg))->pid, _ret == 0);
+
+		_exit(EXIT_SUCCESS);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	TEST_SUCC(close(sv[0]));
+	TEST_SUCC(close(sv[1]));
+}
+END_TEST()
+
+#define LOCK_FILE "/tmp/pid_ns_lock"
+
+static pid_t lock_owner(int fd)
+{
+	struct flock lock = {
+		.l_type = F_WRLCK,
+		.l_whence = SEEK_SET,
+	};
+
+	if (fcntl(fd, F_GETLK, &lock) < 0)
+		return -1;
+	return lock.l_pid;
+}
+
+FN_TEST(lock_owner_in_receiver_ns)
+{
+	struct flock lock = {
+		.l_type = F_WRLCK,
+		.l_whence = SEEK_SET,
+		.l_len = 1,
+	};
+	int fd = TEST_SUCC(open(LOCK_FILE, O_RDWR | O_CREAT | O_TRUNC, 0644));
+	TEST_SUCC(fcntl(fd, F_SETLK, &lock));
+
+	pid_t pid = TEST_SUCC(fork_pid_ns_init());
+	if (pid == 0) {
+		// The owner of the lock is not visible.
+		CHECK_WITH(lock_owner(fd), _ret == 0);
+
+		lock.l_start = 1;
+		CHECK(fcntl(fd, F_SETLK, &lock));
+
+		pid_t child = CHECK(fork());
+		if (child == 0) {
+			lock.l_type = F_WRLCK;
+			CHECK(fcntl(fd, F_GETLK, &lock));
+			CHECK_WITH(lock.l_pid, _ret == 1);
+			_exit(EXIT_SUCCESS);
+		}
+		CHECK_EXITED(child);
+
+		_exit(EXIT_SUCCESS);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(LOCK_FILE));
+}
+END_TEST()
+
+// Reads the `Pid` and `NSpid` fields in the fdinfo of the PID file descriptor.
+static int read_pidfd_info(int pidfd, char *pid, char *ns_pid, size_t len)
+{
+	char path[64];
+	char line[128];
+	FILE *file;
+
+	snprintf(path, sizeof(path), "/proc/self/fdinfo/%d", pidfd);
+	file = fopen(path, "r");
+	if (file == NULL)
+		return -1;
+
+	pid[0] = ns_pid[0] = '\0';
+	while (fgets(line, sizeof(line), file) != NULL) {
+		line[strcspn(line, "\n")] = '\0';
+		if (strncmp(line, "Pid:\t", 5) == 0)
+			snprintf(pid, len, "%s", line + 5);
+		else if (strncmp(line, "NSpid:\t", 7) == 0)
+			snprintf(ns_pid, len, "%s", line + 7);
+	}
+	fclose(file);
+
+	return 0;
+}
+
+FN_TEST(pidfd_fdinfo)
+{
+	char pid_buf[32], ns_pid_buf[32], expected[32];
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			for (;;)
+				pause();
+		}
+
+		// Seen from the parent namespace, the PIDs in both namespaces
+		// are shown.
+		int pidfd = CHECK(syscall(SYS_pidfd_open, init, 0));
+		CHECK(read_pidfd_info(pidfd, pid_buf, ns_pid_buf,
+				      sizeof(pid_buf)));
+		snprintf(expected, sizeof(expected), "%d", init);
+		CHECK_WITH(strcmp(pid_buf, expected), _ret == 0);
+		snprintf(expected, sizeof(expected), "%d\t1", init);
+		CHECK_WITH(strcmp(ns_pid_buf, expected), _ret == 0);
+
+		CHECK(kill(init, SIGKILL));
+		CHECK_WITH(waitpid(init, NULL, 0), _ret == init);
+		_exit(EXIT_SUCCESS);
+	}
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+}
+END_TEST()
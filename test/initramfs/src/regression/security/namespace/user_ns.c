@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/nsfs.h>
+#include <sched.h>
+#include <signal.h>
+#include <sys/ioctl.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+static ino_t ns_ino(int fd)
+{
+	struct stat st;
+
+	if (fstat(fd, &st) < 0)
+		return 0;
+	return st.st_ino;
+}
+
+FN_TEST(unshare_user_ns)
+{
+	int init_fd = TEST_SUCC(open("/proc/self/ns/user", O_RDONLY));
+	ino_t init_ino = ns_ino(init_fd);
+
+	int pipefd[2];
+	TEST_SUCC(pipe(pipefd));
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(close(pipefd[0]));
+		CHECK(unshare(CLONE_NEWUSER));
+
+		int fd = CHECK(open("/proc/self/ns/user", O_RDONLY));
+		CHECK_WITH(ns_ino(fd), _ret != init_ino);
+
+		// The parent of the new namespace is the initial namespace, which is not visible from
+		// inside the new namespace.
+		CHECK_WITH(ioctl(fd, NS_GET_PARENT), _ret < 0 && errno == EPERM);
+		CHECK_WITH(ioctl(fd, NS_GET_USERNS), _ret < 0 && errno == EPERM);
+
+		uid_t owner = -1;
+		CHECK(ioctl(fd, NS_GET_OWNER_UID, &owner));
+		CHECK_WITH(owner, _ret == getuid());
+
+		// The mappings are empty until they are written.
+		char buf[16];
+		int map_fd = CHECK(open("/proc/self/uid_map", O_RDONLY));
+		CHECK_WITH(read(map_fd, buf, sizeof(buf)), _ret == 0);
+
+		CHECK_WITH(write(pipefd[1], "", 1), _ret == 1);
+		pause();
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_SUCC(close(pipefd[1]));
+
+	char c;
+	TEST_RES(read(pipefd[0], &c, 1), _ret == 1);
+
+	// From the initial namespace, the parent of the new namespace is visible.
+	char path[64];
+	snprintf(path, sizeof(path), "/proc/%d/ns/user", pid);
+	int fd = TEST_SUCC(open(path, O_RDONLY));
+	TEST_RES(ns_ino(fd), _ret != init_ino);
+	int parent_fd = TEST_SUCC(ioctl(fd, NS_GET_PARENT));
+	TEST_RES(ns_ino(parent_fd), _ret == init_ino);
+	int userns_fd = TEST_SUCC(ioctl(fd, NS_GET_USERNS));
+	TEST_RES(ns_ino(userns_fd), _ret == init_ino);
+
+	TEST_SUCC(close(userns_fd));
+	TEST_SUCC(close(parent_fd));
+	TEST_SUCC(close(fd));
+
+	TEST_SUCC(kill(pid, SIGTERM));
+
+	int status;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGTERM);
+
+	TEST_SUCC(close(pipefd[0]));
+	TEST_SUCC(close(init_fd));
+}
+END_TEST()
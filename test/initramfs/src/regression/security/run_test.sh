@@ -4,20 +4,29 @@
 
 set -e
 
+./audit/audit
+
 ./capability/capabilities
 ./capability/capset
 ./capability/execve
+./capability/file_caps
 ./capability/kill
 ./capability/reboot
 ./capability/setgroups
 ./capability/trusted_xattr
 
+./keys/keyctl
+
+./lsm/apparmor
+./lsm/landlock
 ./lsm/module_selection
 ./lsm/yama
 
 ./namespace/cgroup_ns
 ./namespace/ipc_ns_sem
 ./namespace/mnt_ns
+./namespace/pid_ns
 ./namespace/proc_nsfs
 ./namespace/setns
 ./namespace/unshare
+./namespace/user_ns